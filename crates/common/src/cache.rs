@@ -15,18 +15,125 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! Cache utilities.
-
-use std::{fs, marker::PhantomData, path::PathBuf, time::Duration};
+//!
+//! Cache entries carry the version of the format they were written in. Entries written by
+//! an older release are migrated when loaded, or invalidated if they cannot be; entries
+//! written by a newer release are left alone. Entries also carry a checksum of their data,
+//! so that corrupted entries are reported and invalidated instead of being used. The cache
+//! directory can be trimmed to a maximum size with [`gc_cache_dir`], least recently used
+//! files first.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    marker::PhantomData,
+    path::{Component, Path, PathBuf},
+    sync::{LazyLock, Mutex},
+    time::{Duration, SystemTime},
+};
 
 use alloy_chains::Chain;
-use eyre::Result;
+use eyre::{eyre, Result};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use tracing::{trace, warn};
+use serde_json::Value;
+use tracing::{debug, trace, warn};
+
+use crate::{checksum, describe_parse_error, verify_checksum};
+
+/// Version of the format of the cache entries written by this release.
+/// Entries written before versioning was introduced are version 0.
+pub const CACHE_FORMAT_VERSION: u32 = 2;
 
 /// Default cache TTL for etherscan.
 /// Set to 1 day since the source code of a contract is unlikely to change frequently.
 pub const DEFAULT_ETHERSCAN_CACHE_TTL: u64 = 86400;
 
+/// Default cache TTL for ENS reverse records.
+/// Set to 1 day since primary names are rarely changed.
+pub const DEFAULT_ENS_CACHE_TTL: u64 = 86400;
+
+/// Hit and miss counters of the [`EdbCache`] lookups made in this process, by cache name
+static CACHE_STATS: LazyLock<Mutex<BTreeMap<String, CacheStats>>> = LazyLock::new(Default::default);
+
+/// Hit and miss counters of [`EdbCache`] lookups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that found no valid cache entry
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Get the counters of all lookups made so far by this process
+    pub fn current() -> Self {
+        Self::by_cache().values().fold(Self::default(), |total, stats| Self {
+            hits: total.hits + stats.hits,
+            misses: total.misses + stats.misses,
+        })
+    }
+
+    /// Get the counters of the lookups made so far by this process, by cache name
+    ///
+    /// Caches are named after their directory under the cache root, without block
+    /// numbers, e.g., `etherscan/mainnet/verified_sources` or `prestate/mainnet`, so that
+    /// lookups are told apart by provider and chain and by the kind of cached data.
+    pub fn by_cache() -> BTreeMap<String, Self> {
+        CACHE_STATS.lock().unwrap().clone()
+    }
+
+    /// Get the counters of the lookups made since `earlier` was taken, by cache name,
+    /// leaving out the caches without any lookup in between
+    pub fn by_cache_since(earlier: &BTreeMap<String, Self>) -> BTreeMap<String, Self> {
+        Self::by_cache()
+            .into_iter()
+            .map(|(name, stats)| {
+                let since = stats.since(&earlier.get(&name).copied().unwrap_or_default());
+                (name, since)
+            })
+            .filter(|(_, stats)| stats.hits + stats.misses > 0)
+            .collect()
+    }
+
+    /// Get the counters of the lookups made since `earlier` was taken
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            hits: self.hits.saturating_sub(earlier.hits),
+            misses: self.misses.saturating_sub(earlier.misses),
+        }
+    }
+
+    /// Get the fraction of lookups served from the cache, if any lookup was made
+    pub fn hit_rate(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+/// Name a cache directory after its path under the cache root, leaving out numeric
+/// components such as block numbers
+fn cache_name(root: Option<&Path>, cache_dir: &Path) -> String {
+    let relative = root
+        .and_then(|root| cache_dir.strip_prefix(root).ok())
+        .filter(|relative| relative.components().next().is_some());
+
+    match relative {
+        Some(relative) => relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => name.to_str(),
+                _ => None,
+            })
+            .filter(|name| !name.chars().all(|c| c.is_ascii_digit()))
+            .collect::<Vec<_>>()
+            .join("/"),
+        None => cache_dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
 /// Trait for cache paths.
 pub trait CachePath {
     /// Returns the path to edb's cache dir: `~/.edb/cache` by default.
@@ -67,6 +174,36 @@ pub trait CachePath {
     fn compiler_chain_cache_dir(&self, chain_id: impl Into<Chain>) -> Option<PathBuf> {
         Some(self.compiler_cache_dir()?.join(chain_id.into().to_string()))
     }
+
+    /// Returns the path to edb's address label cache dir: `<cache_root>/labels`.
+    fn label_cache_dir(&self) -> Option<PathBuf> {
+        Some(self.edb_cache_dir()?.join("labels"))
+    }
+
+    /// Returns the path to edb's address label cache dir for `chain_id`:
+    /// `<cache_root>/labels/<chain>`
+    fn label_chain_cache_dir(&self, chain_id: impl Into<Chain>) -> Option<PathBuf> {
+        Some(self.label_cache_dir()?.join(chain_id.into().to_string()))
+    }
+
+    /// Returns the path to edb's transaction prestate cache dir: `<cache_root>/prestate`.
+    fn prestate_cache_dir(&self) -> Option<PathBuf> {
+        Some(self.edb_cache_dir()?.join("prestate"))
+    }
+
+    /// Returns the path to edb's transaction prestate cache dir for `chain_id` and
+    /// `block_number`: `<cache_root>/prestate/<chain>/<block>`
+    fn prestate_block_cache_dir(
+        &self,
+        chain_id: impl Into<Chain>,
+        block_number: u64,
+    ) -> Option<PathBuf> {
+        Some(
+            self.prestate_cache_dir()?
+                .join(chain_id.into().to_string())
+                .join(block_number.to_string()),
+        )
+    }
 }
 
 /// Cache path for edb.
@@ -113,6 +250,10 @@ impl CachePath for Option<EdbCachePath> {
 struct CacheWrapper<T> {
     pub data: T,
     pub expires_at: u64,
+    #[serde(default)]
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
 }
 
 impl<T> CacheWrapper<T> {
@@ -122,6 +263,8 @@ impl<T> CacheWrapper<T> {
             expires_at: ttl
                 .map(|ttl| ttl.as_secs().saturating_add(chrono::Utc::now().timestamp() as u64))
                 .unwrap_or(u64::MAX),
+            version: CACHE_FORMAT_VERSION,
+            checksum: None,
         }
     }
 
@@ -151,6 +294,8 @@ pub trait Cache {
 pub struct EdbCache<T> {
     cache_dir: PathBuf,
     cache_ttl: Option<Duration>,
+    /// Name the lookups are counted under, see [`CacheStats::by_cache`]
+    name: String,
     phantom: PhantomData<T>,
 }
 
@@ -166,7 +311,10 @@ where
         if let Some(cache_dir) = cache_dir {
             let cache_dir = cache_dir.into();
             fs::create_dir_all(&cache_dir)?;
-            Ok(Some(Self { cache_dir, cache_ttl, phantom: PhantomData }))
+            let root =
+                EdbCachePath::new(std::env::var(crate::env::EDB_CACHE_DIR).ok()).edb_cache_dir();
+            let name = cache_name(root.as_deref(), &cache_dir);
+            Ok(Some(Self { cache_dir, cache_ttl, name, phantom: PhantomData }))
         } else {
             Ok(None)
         }
@@ -181,15 +329,9 @@ where
     pub fn cache_ttl(&self) -> Option<Duration> {
         self.cache_ttl
     }
-}
 
-impl<T> Cache for EdbCache<T>
-where
-    T: Serialize + DeserializeOwned,
-{
-    type Data = T;
-
-    fn load_cache(&self, label: impl Into<String>) -> Option<T> {
+    /// Load a cache entry without counting the lookup
+    fn load_cache_file(&self, label: impl Into<String>) -> Option<T> {
         let cache_file = self.cache_dir.join(format!("{}.json", label.into()));
         trace!("loading cache: {:?}", cache_file);
         if !cache_file.exists() {
@@ -197,13 +339,53 @@ where
         }
 
         let content = fs::read_to_string(&cache_file).ok()?;
-        let cache: CacheWrapper<_> = if let Ok(cache) = serde_json::from_str(&content) {
-            cache
-        } else {
-            warn!("the cache file has been corrupted: {:?}", cache_file);
+        let entry = match serde_json::from_str::<Value>(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                let reason = describe_parse_error(&e, content.len());
+                warn!("the cache file has been corrupted: {:?}: {}", cache_file, reason);
+                let _ = fs::remove_file(&cache_file); // we do not care about the result
+                return None;
+            }
+        };
+
+        let version = entry.get("version").and_then(Value::as_u64).unwrap_or(0) as u32;
+        if version > CACHE_FORMAT_VERSION {
+            // Another release may still use it, so it is not removed
+            debug!("the cache file was written by a newer release: {:?}", cache_file);
+            return None;
+        }
+        let Some(entry) = migrate_entry(entry, version) else {
+            debug!("the cache file is in an outdated format: {:?}", cache_file);
             let _ = fs::remove_file(&cache_file); // we do not care about the result
             return None;
         };
+        if let Some(expected) = entry.get("checksum").and_then(Value::as_str) {
+            let data = entry.get("data").unwrap_or(&Value::Null);
+            if let Err(e) = verify_checksum(data, expected) {
+                warn!("the cache file has been corrupted: {:?}: {}", cache_file, e);
+                let _ = fs::remove_file(&cache_file); // we do not care about the result
+                return None;
+            }
+        }
+        let cache: CacheWrapper<T> = match serde_json::from_value(entry) {
+            Ok(cache) => cache,
+            Err(e) => {
+                // The cached data type has changed since the entry was written
+                debug!("invalidating the cache file {:?}: {}", cache_file, e);
+                let _ = fs::remove_file(&cache_file); // we do not care about the result
+                return None;
+            }
+        };
+        if version < CACHE_FORMAT_VERSION {
+            trace!("migrated the cache file to version {}: {:?}", CACHE_FORMAT_VERSION, cache_file);
+            // Rewrite the entry, or it would be migrated again on every load
+            if let Ok(data) = serde_json::to_value(&cache.data) {
+                let migrated =
+                    CacheWrapper { expires_at: cache.expires_at, ..CacheWrapper::new(data, None) };
+                let _ = write_cache_file(&cache_file, migrated);
+            }
+        }
 
         if cache.is_expired() {
             trace!("the cache file has expired: {:?}", cache_file);
@@ -214,15 +396,32 @@ where
             Some(cache.data)
         }
     }
+}
+
+impl<T> Cache for EdbCache<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    type Data = T;
+
+    fn load_cache(&self, label: impl Into<String>) -> Option<T> {
+        let data = self.load_cache_file(label);
+        let mut caches = CACHE_STATS.lock().unwrap();
+        let stats = caches.entry(self.name.clone()).or_default();
+        if data.is_some() {
+            stats.hits += 1;
+        } else {
+            stats.misses += 1;
+        }
+        data
+    }
 
     fn save_cache(&self, label: impl Into<String>, data: &T) -> Result<()> {
         let cache_file = self.cache_dir.join(format!("{}.json", label.into()));
         trace!("saving cache: {:?}", cache_file);
 
-        let cache = CacheWrapper::new(data, self.cache_ttl);
-        let content = serde_json::to_string(&cache)?;
-        fs::write(&cache_file, content)?;
-        Ok(())
+        let cache = CacheWrapper::new(serde_json::to_value(data)?, self.cache_ttl);
+        write_cache_file(&cache_file, cache)
     }
 }
 
@@ -245,6 +444,117 @@ where
     }
 }
 
+/// Write a cache entry in the current format, with the checksum of its data
+fn write_cache_file(cache_file: &Path, mut cache: CacheWrapper<Value>) -> Result<()> {
+    cache.checksum = Some(checksum(&cache.data));
+    fs::write(cache_file, serde_json::to_string(&cache)?)?;
+    Ok(())
+}
+
+/// Upgrade a cache entry written in an older format, one version at a time
+///
+/// Returns `None` if the entry cannot be upgraded and has to be invalidated.
+fn migrate_entry(mut entry: Value, from: u32) -> Option<Value> {
+    for version in from..CACHE_FORMAT_VERSION {
+        entry = match version {
+            // Version 0 entries have the same layout, only without a version
+            0 => entry,
+            // Version 1 entries have the same layout, only without a checksum
+            1 => entry,
+            _ => return None,
+        };
+    }
+    entry.as_object_mut()?.insert("version".into(), CACHE_FORMAT_VERSION.into());
+    Some(entry)
+}
+
+/// Outcome of trimming a cache directory with [`gc_cache_dir`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheGcReport {
+    /// Files in the cache before trimming
+    pub files: u64,
+    /// Size of the cache before trimming, in bytes
+    pub size: u64,
+    /// Files removed
+    pub removed_files: u64,
+    /// Size of the files removed, in bytes
+    pub removed_size: u64,
+}
+
+/// Trim a cache directory to a maximum size, removing the least recently used files first.
+///
+/// Files are whole cache entries, except for the RPC cache of each chain, which is kept in
+/// a single file and is removed at once.
+pub fn gc_cache_dir(root: &Path, max_size: u64) -> Result<CacheGcReport> {
+    let mut files = Vec::new();
+    collect_cache_files(root, &mut files)?;
+
+    let mut report = CacheGcReport {
+        files: files.len() as u64,
+        size: files.iter().map(|(_, size, _)| size).sum(),
+        ..Default::default()
+    };
+
+    files.sort_by_key(|(_, _, last_used)| *last_used);
+    let mut size = report.size;
+    for (path, file_size, _) in files {
+        if size <= max_size {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                trace!("removed cache file: {:?}", path);
+                size -= file_size;
+                report.removed_files += 1;
+                report.removed_size += file_size;
+            }
+            Err(e) => warn!("failed to remove cache file {:?}: {}", path, e),
+        }
+    }
+    Ok(report)
+}
+
+/// Collect the files of a cache directory, with their size and when they were last used
+fn collect_cache_files(dir: &Path, files: &mut Vec<(PathBuf, u64, SystemTime)>) -> Result<()> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(eyre!("failed to read cache directory {}: {e}", dir.display())),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            collect_cache_files(&entry.path(), files)?;
+        } else if metadata.is_file() {
+            let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            let last_used = metadata.accessed().map_or(modified, |accessed| accessed.max(modified));
+            files.push((entry.path(), metadata.len(), last_used));
+        }
+    }
+    Ok(())
+}
+
+/// Parse a size in bytes, optionally followed by a binary unit: `K`, `M`, `G` or `T`
+/// (e.g. `500M`, `10GB`, `1.5GiB`).
+pub fn parse_size(size: &str) -> Result<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().map_err(|_| eyre!("invalid size {size:?}"))?;
+
+    let unit = unit.trim().to_ascii_uppercase();
+    let multiplier: u64 = match unit.trim_end_matches("IB").trim_end_matches('B') {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return Err(eyre!("invalid size unit {unit:?} in {size:?}")),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,6 +624,15 @@ mod tests {
         let eth_compiler_dir = cache_path.compiler_chain_cache_dir(Chain::mainnet());
         assert!(eth_compiler_dir.is_some());
         assert!(eth_compiler_dir.unwrap().ends_with("mainnet"));
+
+        // Test prestate cache directories
+        let prestate_dir = cache_path.prestate_cache_dir();
+        assert!(prestate_dir.is_some());
+        assert!(prestate_dir.unwrap().ends_with("prestate"));
+
+        let block_prestate_dir = cache_path.prestate_block_cache_dir(Chain::mainnet(), 17_000_000);
+        assert!(block_prestate_dir.is_some());
+        assert!(block_prestate_dir.unwrap().ends_with("prestate/mainnet/17000000"));
     }
 
     #[test]
@@ -393,6 +712,28 @@ mod tests {
         assert_eq!(loaded_data.unwrap(), test_data);
     }
 
+    #[test]
+    fn test_cache_stats_by_cache() {
+        let root = std::env::temp_dir().join("edb_test_cache_root");
+        let cache_path = EdbCachePath::new(Some(&root));
+        let prestate_dir = cache_path.prestate_block_cache_dir(Chain::mainnet(), 17_000_000);
+        assert_eq!(cache_name(Some(&root), &prestate_dir.unwrap()), "prestate/mainnet");
+
+        let temp_path = std::env::temp_dir().join("edb_test_cache_stats");
+        assert_eq!(cache_name(Some(&root), &temp_path), "edb_test_cache_stats");
+        let cache = EdbCache::<TestData>::new(Some(&temp_path), None).unwrap().unwrap();
+        let before = CacheStats::by_cache();
+
+        let test_data = TestData { value: "hello".to_string(), number: 123 };
+        cache.save_cache("label", &test_data).unwrap();
+        cache.load_cache("label");
+        cache.load_cache("label");
+        cache.load_cache("nonexistent");
+
+        let stats = CacheStats::by_cache_since(&before);
+        assert_eq!(stats["edb_test_cache_stats"], CacheStats { hits: 2, misses: 1 });
+    }
+
     #[test]
     fn test_edb_cache_load_nonexistent() {
         let temp_path = std::env::temp_dir().join("edb_test_cache");
@@ -532,4 +873,109 @@ mod tests {
         assert!(loaded.is_some());
         assert_eq!(loaded.unwrap(), data2);
     }
+
+    #[test]
+    fn test_migrate_unversioned_entry() {
+        let temp_path = std::env::temp_dir().join("edb_test_cache_migration");
+        let cache = EdbCache::<TestData>::new(Some(&temp_path), None).unwrap().unwrap();
+        let cache_file = temp_path.join("unversioned.json");
+
+        // Written before entries were versioned
+        let entry = serde_json::json!({
+            "data": {"value": "old", "number": 7},
+            "expires_at": u64::MAX,
+        });
+        fs::write(&cache_file, entry.to_string()).unwrap();
+        assert_eq!(cache.load_cache("unversioned").unwrap().value, "old");
+
+        // The entry is rewritten in the current format
+        let entry: Value = serde_json::from_str(&fs::read_to_string(&cache_file).unwrap()).unwrap();
+        assert_eq!(entry["version"], CACHE_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_invalidate_incompatible_entries() {
+        let temp_path = std::env::temp_dir().join("edb_test_cache_invalidation");
+        let cache = EdbCache::<TestData>::new(Some(&temp_path), None).unwrap().unwrap();
+
+        // Entries of a newer release are ignored but kept
+        let newer = temp_path.join("newer.json");
+        let entry = serde_json::json!({
+            "data": {"value": "new", "number": 1},
+            "expires_at": u64::MAX,
+            "version": CACHE_FORMAT_VERSION + 1,
+        });
+        fs::write(&newer, entry.to_string()).unwrap();
+        assert!(cache.load_cache("newer").is_none());
+        assert!(newer.exists());
+
+        // Entries whose data no longer parses are removed
+        let changed = temp_path.join("changed.json");
+        let entry = serde_json::json!({
+            "data": {"renamed": "field"},
+            "expires_at": u64::MAX,
+            "version": CACHE_FORMAT_VERSION,
+        });
+        fs::write(&changed, entry.to_string()).unwrap();
+        assert!(cache.load_cache("changed").is_none());
+        assert!(!changed.exists());
+    }
+
+    #[test]
+    fn test_gc_cache_dir() {
+        let temp_path = std::env::temp_dir().join("edb_test_cache_gc");
+        let _ = fs::remove_dir_all(&temp_path);
+        fs::create_dir_all(temp_path.join("etherscan").join("mainnet")).unwrap();
+
+        let old = temp_path.join("etherscan").join("mainnet").join("old.json");
+        fs::write(&old, vec![b'0'; 600]).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        let new = temp_path.join("new.json");
+        fs::write(&new, vec![b'0'; 600]).unwrap();
+
+        let report = gc_cache_dir(&temp_path, 1000).unwrap();
+        assert_eq!(report.files, 2);
+        assert_eq!(report.size, 1200);
+        assert_eq!(report.removed_files, 1);
+        assert!(!old.exists());
+        assert!(new.exists());
+
+        assert_eq!(gc_cache_dir(&temp_path, 1000).unwrap().removed_files, 0);
+        assert_eq!(gc_cache_dir(&temp_path.join("missing"), 0).unwrap().files, 0);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500M").unwrap(), 500 << 20);
+        assert_eq!(parse_size("10GB").unwrap(), 10 << 30);
+        assert_eq!(parse_size("1.5GiB").unwrap(), 3 << 29);
+        assert_eq!(parse_size("2 kb").unwrap(), 2048);
+        assert!(parse_size("ten").is_err());
+        assert!(parse_size("10X").is_err());
+    }
+
+    #[test]
+    fn test_detect_corrupted_entries() {
+        let temp_path = std::env::temp_dir().join("edb_test_cache_checksum");
+        let cache = EdbCache::<TestData>::new(Some(&temp_path), None).unwrap().unwrap();
+        let cache_file = temp_path.join("checked.json");
+
+        let data = TestData { value: "intact".to_string(), number: 5 };
+        cache.save_cache("checked", &data).unwrap();
+        assert_eq!(cache.load_cache("checked").unwrap(), data);
+
+        // Altered data still parses, but no longer matches its checksum
+        let content = fs::read_to_string(&cache_file).unwrap();
+        fs::write(&cache_file, content.replace("intact", "altered")).unwrap();
+        assert!(cache.load_cache("checked").is_none());
+        assert!(!cache_file.exists());
+
+        // Truncated entries are invalidated as well
+        cache.save_cache("checked", &data).unwrap();
+        let content = fs::read_to_string(&cache_file).unwrap();
+        fs::write(&cache_file, &content[..content.len() / 2]).unwrap();
+        assert!(cache.load_cache("checked").is_none());
+        assert!(!cache_file.exists());
+    }
 }
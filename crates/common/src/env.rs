@@ -26,6 +26,14 @@
 //! - [`EDB_ASSERT`] - Controls selective runtime assertion macros
 //! - [`EDB_CACHE_DIR`] - Specifies the cache directory location
 //! - [`EDB_ETHERSCAN_CACHE_TTL`] - Sets Etherscan cache time-to-live
+//! - [`EDB_SOLC_MIRROR`] - Sets the mirror solc binaries are downloaded from
+//! - [`EDB_SOLC_DIR`] - Specifies a local directory of solc binaries
+//!
+//! ## Logging Configuration
+//! - [`EDB_LOG_FORMAT`] - Selects human-readable or JSON log output
+//! - [`EDB_LOG_DIR`] - Specifies the log directory location
+//! - [`EDB_LOG_MAX_SIZE`] - Sets the size log files are rotated at
+//! - [`EDB_LOG_MAX_FILES`] - Sets the number of rotated log files kept
 //!
 //! ## Testing Configuration
 //! - [`EDB_TEST_ETHERSCAN_MODE`] - Controls Etherscan behavior in tests
@@ -118,6 +126,87 @@ pub const EDB_CACHE_DIR: &str = "EDB_CACHE_DIR";
 /// ```
 pub const EDB_ETHERSCAN_CACHE_TTL: &str = "EDB_ETHERSCAN_CACHE_TTL";
 
+/// Environment variable for setting the mirror solc binaries are downloaded from.
+///
+/// The mirror must have the layout of `https://binaries.soliditylang.org`, i.e., a
+/// `<platform>/list.json` and the binaries it lists for each platform. Downloaded binaries
+/// are verified against the keccak256 checksums of `list.json`.
+///
+/// # Default
+///
+/// When not set, solc binaries are installed from `binaries.soliditylang.org` by svm.
+///
+/// # Related
+///
+/// This is also available as a CLI argument (`--solc-mirror`).
+pub const EDB_SOLC_MIRROR: &str = "EDB_SOLC_MIRROR";
+
+/// Environment variable for specifying a local directory of solc binaries.
+///
+/// The directory has the layout of a platform directory of
+/// `https://binaries.soliditylang.org`: a `list.json` and the binaries it lists, which are
+/// verified against its keccak256 checksums. It is checked before any download, so
+/// copying the binaries of the needed versions there is enough to recompile contracts
+/// without network access.
+///
+/// # Examples
+///
+/// ```bash
+/// EDB_SOLC_DIR=/opt/solc/linux-amd64 edb --offline-solc replay 0x...
+/// ```
+///
+/// # Related
+///
+/// This is also available as a CLI argument (`--solc-dir`).
+pub const EDB_SOLC_DIR: &str = "EDB_SOLC_DIR";
+
+/// Environment variable for selecting the format of the logs.
+///
+/// # Values
+///
+/// - `"pretty"` - Human-readable output, colored on the console (default)
+/// - `"json"` - One JSON object per event and line, for log aggregators
+///
+/// # Examples
+///
+/// ```bash
+/// EDB_LOG_FORMAT=json edb-rpc-proxy
+/// ```
+pub const EDB_LOG_FORMAT: &str = "EDB_LOG_FORMAT";
+
+/// Environment variable for specifying the log directory.
+///
+/// Each component writes to `<EDB_LOG_DIR>/<component>/<component>.log`.
+///
+/// # Default
+///
+/// When not set, `edb-logs` in the system temporary directory is used.
+///
+/// # Examples
+///
+/// ```bash
+/// EDB_LOG_DIR=/var/log/edb edb-rpc-proxy
+/// ```
+pub const EDB_LOG_DIR: &str = "EDB_LOG_DIR";
+
+/// Environment variable for setting the size log files are rotated at.
+///
+/// # Value Format
+///
+/// A size in bytes, optionally followed by a binary unit (`K`, `M`, `G`), e.g. `100M`.
+///
+/// # Default
+///
+/// When not set, log files are rotated at 50 MiB.
+pub const EDB_LOG_MAX_SIZE: &str = "EDB_LOG_MAX_SIZE";
+
+/// Environment variable for setting the number of rotated log files kept per component.
+///
+/// # Default
+///
+/// When not set, 5 rotated files are kept.
+pub const EDB_LOG_MAX_FILES: &str = "EDB_LOG_MAX_FILES";
+
 /// Environment variable for controlling Etherscan behavior in tests.
 ///
 /// This **test-only** variable allows tests to run without making real Etherscan API calls
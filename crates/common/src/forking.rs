@@ -19,7 +19,8 @@
 //! This module provides ACTUAL REVM TRANSACTION EXECUTION with transact_commit()
 
 use crate::{
-    get_blob_base_fee_update_fraction_by_spec_id, get_mainnet_spec_id, provider_db::ProviderDb,
+    apply_prestate, capture_prestate, get_blob_base_fee_update_fraction_by_spec_id,
+    get_mainnet_spec_id, prestate_cache, prestate_cache_label, provider_db::ProviderDb, Cache,
     EdbContext, EdbDB,
 };
 use alloy_primitives::{address, Address, TxHash, TxKind, B256, U256};
@@ -35,7 +36,10 @@ use revm::{
     MainContext,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tracing::{debug, error, info, warn};
 
 use revm::{
@@ -85,6 +89,8 @@ where
     pub target_tx_env: TxEnv,
     /// Target transaction hash
     pub target_tx_hash: TxHash,
+    /// Time spent forking the chain and replaying the preceding transactions
+    pub fork_duration: Duration,
 }
 
 /// Get chain id by querying RPC
@@ -107,8 +113,43 @@ pub async fn fork_and_prepare(
     quick: bool,
 ) -> Result<
     ForkResult<EdbDB<impl Clone + Database + DatabaseCommit + DatabaseRef + Send + Sync + 'static>>,
+> {
+    fork(rpc_url, target_tx_hash, quick, None).await
+}
+
+/// Fork the chain like [`fork_and_prepare`], reusing the cached prestate of the target
+/// transaction if it was prepared before.
+///
+/// On a cache hit, the preceding transactions are not replayed: the state the target
+/// transaction touched is written into the fork instead, and any other state is fetched
+/// lazily from the parent block. On a miss, the preceding transactions are replayed as
+/// usual and the prestate of the target transaction is cached afterwards. With `refetch`,
+/// the cached prestate is ignored and overwritten.
+///
+/// See [`crate::prestate`] for the cache layout.
+pub async fn fork_and_prepare_cached(
+    rpc_url: &str,
+    target_tx_hash: TxHash,
+    quick: bool,
+    refetch: bool,
+) -> Result<
+    ForkResult<EdbDB<impl Clone + Database + DatabaseCommit + DatabaseRef + Send + Sync + 'static>>,
+> {
+    fork(rpc_url, target_tx_hash, quick, Some(refetch)).await
+}
+
+/// Fork the chain for the target transaction, using the prestate cache unless
+/// `prestate_refetch` is `None`.
+async fn fork(
+    rpc_url: &str,
+    target_tx_hash: TxHash,
+    quick: bool,
+    prestate_refetch: Option<bool>,
+) -> Result<
+    ForkResult<EdbDB<impl Clone + Database + DatabaseCommit + DatabaseRef + Send + Sync + 'static>>,
 > {
     info!("forking chain and executing transactions with revm for {:?}", target_tx_hash);
+    let started = Instant::now();
 
     let provider = ProviderBuilder::new().connect(rpc_url).await?;
 
@@ -216,8 +257,29 @@ pub async fn fork_and_prepare(
     let mut evm = ctx.build_mainnet();
     info!("The evm verision is {}", evm.cfg().spec);
 
-    // Skip replaying preceding transactions if quick mode is enabled
-    if quick {
+    // Get the target transaction environment
+    let target_tx_env = get_tx_env_from_tx(&target_tx, chain_id)?;
+
+    let prestate_cache =
+        prestate_refetch.and_then(|_| prestate_cache(chain_id, target_block_number));
+    let prestate_label = prestate_cache_label(target_tx_hash, quick);
+    let cached_prestate = match prestate_refetch {
+        Some(false) => prestate_cache.load_cache(&prestate_label),
+        Some(true) => {
+            info!("Refetching the prestate of {:?}", target_tx_hash);
+            None
+        }
+        None => None,
+    };
+
+    if let Some(prestate) = &cached_prestate {
+        info!(
+            "Using the cached prestate of {} accounts - skipping replay of {} preceding transactions",
+            prestate.len(),
+            preceding_txs.len()
+        );
+        apply_prestate(evm.ctx.db_mut(), prestate)?;
+    } else if quick {
         info!(
             "Quick mode enabled - skipping replay of {} preceding transactions",
             preceding_txs.len()
@@ -309,14 +371,29 @@ pub async fn fork_and_prepare(
         ));
     }
 
-    // Get the target transaction environment
-    let target_tx_env = get_tx_env_from_tx(&target_tx, chain_id)?;
+    // Execute the target transaction without committing it, to cache the state it touches
+    if prestate_cache.is_some() && cached_prestate.is_none() {
+        let prestate = match evm.transact(target_tx_env.clone()) {
+            Ok(result) => capture_prestate(evm.ctx.db(), &result.state),
+            Err(e) => Err(eyre::eyre!("{:?}", e)),
+        };
+        match prestate.and_then(|prestate| prestate_cache.save_cache(&prestate_label, &prestate)) {
+            Ok(()) => debug!("Cached the prestate of {:?}", target_tx_hash),
+            Err(e) => warn!("Failed to cache the prestate of {:?}: {}", target_tx_hash, e),
+        }
+    }
 
     // Extract the context from the EVM
     evm.finalize();
     let context = evm.ctx;
 
-    Ok(ForkResult { fork_info, context, target_tx_env, target_tx_hash })
+    Ok(ForkResult {
+        fork_info,
+        context,
+        target_tx_env,
+        target_tx_hash,
+        fork_duration: started.elapsed(),
+    })
 }
 
 /// Get the transaction environment from the transaction.
@@ -0,0 +1,75 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Content checksums of persisted files.
+//!
+//! Files that EDB persists, and that may be copied between machines, store a checksum of
+//! their content: cache entries (compiled artifacts, verified sources, ...) and exported
+//! cache bundles. The checksum is verified on load, so that a truncated or altered file is
+//! reported precisely instead of being partially read.
+
+use alloy_primitives::keccak256;
+use eyre::{bail, Result};
+use serde_json::Value;
+
+/// Checksum of a JSON value: the hex-encoded keccak256 of its compact serialization.
+pub fn checksum(value: &Value) -> String {
+    keccak256(value.to_string()).to_string()
+}
+
+/// Verify that a JSON value matches the checksum it was stored with.
+pub fn verify_checksum(value: &Value, expected: &str) -> Result<()> {
+    let actual = checksum(value);
+    if !actual.eq_ignore_ascii_case(expected) {
+        bail!("checksum mismatch: stored {expected}, computed {actual}");
+    }
+    Ok(())
+}
+
+/// Describe why a persisted JSON file of `len` bytes could not be parsed.
+pub fn describe_parse_error(error: &serde_json::Error, len: usize) -> String {
+    if error.is_eof() {
+        format!("truncated: the file ends after {len} bytes ({error})")
+    } else {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_checksum() {
+        let value = serde_json::json!({"address": "0x01", "code": "0x6080"});
+        let stored = checksum(&value);
+        assert!(verify_checksum(&value, &stored).is_ok());
+
+        let altered = serde_json::json!({"address": "0x01", "code": "0x6081"});
+        let error = verify_checksum(&altered, &stored).unwrap_err();
+        assert!(error.to_string().contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn test_describe_parse_error() {
+        let content = r#"{"data": {"value": "trunc"#;
+        let error = serde_json::from_str::<Value>(content).unwrap_err();
+        assert!(describe_parse_error(&error, content.len()).starts_with("truncated"));
+
+        let error = serde_json::from_str::<Value>("{]").unwrap_err();
+        assert!(!describe_parse_error(&error, 2).starts_with("truncated"));
+    }
+}
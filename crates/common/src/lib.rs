@@ -34,25 +34,36 @@ pub mod env;
 pub mod expression;
 /// Chain forking utilities for creating and managing forked blockchain states
 pub mod forking;
+/// Content checksums verifying persisted files on load
+pub mod integrity;
 /// Logging setup and utilities for consistent logging across EDB components
 pub mod logging;
 /// Conditional assertion macros for strict testing mode
 pub mod macros;
 /// Extended opcode analysis utilities for EVM state modification detection and debugging
 pub mod opcode;
+/// Prestate cache of replayed transactions, keyed by chain and block
+pub mod prestate;
 /// Progress message types for tracking operation progress
 pub mod progress;
 mod provider_db;
+/// HTTP client of the engine RPC servers, with bearer tokens and custom CA certificates
+pub mod rpc_client;
 /// Specification ID utilities for handling different Ethereum hardforks and protocol versions
 pub mod spec_id;
 /// Testing utilities and helpers for integration and unit tests
 pub mod test_utils;
+/// WebSocket protocol between the EDB server and its clients
+pub mod ws_protocol;
 
 pub use cache::*;
 pub use context::*;
 pub use expression::*;
 pub use forking::*;
+pub use integrity::*;
 pub use logging::*;
 pub use opcode::*;
+pub use prestate::*;
 pub use progress::*;
+pub use rpc_client::*;
 pub use spec_id::*;
@@ -18,30 +18,148 @@
 //!
 //! Provides centralized logging setup with:
 //! - Colorful console output with structured formatting
-//! - File logging to temporary directory
+//! - File logging to a log file of each component, rotated by size
 //! - Environment variable support (RUST_LOG)
 //! - Default INFO level with beautiful styling
-
-use eyre::Result;
-use std::{env, fs, path::PathBuf, sync::Once};
-use tracing::Level;
-use tracing_appender::{non_blocking, rolling};
+//! - Optional JSON output, one object per line, for log aggregators
+//!
+//! The output is configured through environment variables, see [`LogConfig::from_env`]:
+//!
+//! ```bash
+//! EDB_LOG_FORMAT=json EDB_LOG_DIR=/var/log/edb EDB_LOG_MAX_SIZE=100M edb-rpc-proxy
+//! ```
+
+use eyre::{bail, eyre, Result};
+use serde_json::{Map, Value};
+use std::{
+    env,
+    fmt::Debug,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Once,
+    },
+};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_appender::{non_blocking, non_blocking::NonBlocking};
 use tracing_subscriber::{
-    fmt::{self, format::FmtSpan, time::LocalTime},
+    filter::filter_fn,
+    fmt::{self, format::FmtSpan, time::LocalTime, FmtContext, FormatEvent, FormatFields},
     layer::SubscriberExt,
+    registry::LookupSpan,
     util::SubscriberInitExt,
-    EnvFilter,
+    EnvFilter, Layer,
 };
 
+use crate::{env as edb_env, parse_size};
+
+/// Default size of a log file before it is rotated
+const DEFAULT_LOG_MAX_SIZE: u64 = 50 << 20;
+
+/// Default number of rotated log files kept for each component
+const DEFAULT_LOG_MAX_FILES: usize = 5;
+
+/// Whether the console layer writes events, see [`set_console_logging`]
+static CONSOLE_LOGGING: AtomicBool = AtomicBool::new(true);
+
+/// Enable or disable console logging, e.g., while a terminal UI draws on the terminal
+///
+/// File logging is not affected, so events keep being recorded in the component's log file.
+pub fn set_console_logging(enabled: bool) {
+    CONSOLE_LOGGING.store(enabled, Ordering::Relaxed);
+}
+
+/// Format of the log output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable output, colored on the console
+    #[default]
+    Pretty,
+    /// One JSON object per event and line
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            _ => bail!("Invalid log format {s:?}, expected `pretty` or `json`"),
+        }
+    }
+}
+
+/// Configuration of the log output of a component
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogConfig {
+    /// Format of the console and file output
+    pub format: LogFormat,
+    /// Directory holding the log directory of each component
+    pub dir: PathBuf,
+    /// Size a log file is rotated at, in bytes
+    pub max_file_size: u64,
+    /// Rotated log files kept for each component
+    pub max_files: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::default(),
+            dir: env::temp_dir().join("edb-logs"),
+            max_file_size: DEFAULT_LOG_MAX_SIZE,
+            max_files: DEFAULT_LOG_MAX_FILES,
+        }
+    }
+}
+
+impl LogConfig {
+    /// Read the configuration from the environment, using defaults for unset variables
+    ///
+    /// - [`EDB_LOG_FORMAT`](edb_env::EDB_LOG_FORMAT) - `pretty` (default) or `json`
+    /// - [`EDB_LOG_DIR`](edb_env::EDB_LOG_DIR) - log directory, `<temp>/edb-logs` by default
+    /// - [`EDB_LOG_MAX_SIZE`](edb_env::EDB_LOG_MAX_SIZE) - rotation size, `50M` by default
+    /// - [`EDB_LOG_MAX_FILES`](edb_env::EDB_LOG_MAX_FILES) - rotated files kept, 5 by default
+    pub fn from_env() -> Result<Self> {
+        let mut config = Self::default();
+        if let Ok(format) = env::var(edb_env::EDB_LOG_FORMAT) {
+            config.format = format.parse()?;
+        }
+        if let Ok(dir) = env::var(edb_env::EDB_LOG_DIR) {
+            config.dir = PathBuf::from(dir);
+        }
+        if let Ok(size) = env::var(edb_env::EDB_LOG_MAX_SIZE) {
+            config.max_file_size = parse_size(&size)
+                .map_err(|e| eyre!("Invalid {}: {e}", edb_env::EDB_LOG_MAX_SIZE))?;
+        }
+        if let Ok(files) = env::var(edb_env::EDB_LOG_MAX_FILES) {
+            config.max_files =
+                files.parse().map_err(|e| eyre!("Invalid {}: {e}", edb_env::EDB_LOG_MAX_FILES))?;
+        }
+        Ok(config)
+    }
+}
+
 /// Initialize fancy logging for EDB components
 ///
 /// This function sets up:
 /// - Colorful, structured console logging with timestamps
-/// - File logging to a temporary directory with daily rotation
+/// - File logging to the component's log file, rotated by size
 /// - Environment variable support for log levels (RUST_LOG)
 /// - Default INFO level if no RUST_LOG is set
 /// - Beautiful formatting with component names and spans
 ///
+/// The format, directory and rotation of the logs are read from the environment, see
+/// [`LogConfig::from_env`].
+///
 /// # Arguments
 /// * `component_name` - Name of the component (e.g., "edb", "edb-rpc-proxy")
 /// * `enable_file_logging` - Whether to enable file logging (default: true)
@@ -63,36 +181,66 @@ use tracing_subscriber::{
 /// }
 /// ```
 pub fn init_logging(component_name: &str, enable_file_logging: bool) -> Result<()> {
+    init_logging_with_config(component_name, enable_file_logging, &LogConfig::from_env()?)
+}
+
+/// Initialize fancy logging for EDB components with an explicit configuration
+///
+/// See [`init_logging`].
+pub fn init_logging_with_config(
+    component_name: &str,
+    enable_file_logging: bool,
+    config: &LogConfig,
+) -> Result<()> {
     // Create environment filter with default WARN level
     let env_filter = EnvFilter::try_from_default_env()
         .or_else(|_| EnvFilter::try_new(Level::WARN.as_str()))
         .expect("Failed to create environment filter");
 
-    // Create beautiful console layer with colors and formatting
-    let console_layer = fmt::layer()
-        .with_target(true)
-        .with_thread_ids(true)
-        .with_thread_names(true)
-        .with_file(true)
-        .with_line_number(true)
-        .with_span_events(FmtSpan::CLOSE)
-        .with_timer(LocalTime::rfc_3339())
-        .with_ansi(true) // Enable colors
-        .pretty(); // Use pretty formatting
-
-    if enable_file_logging {
-        // Create log directory in temp folder
-        let log_dir = create_log_directory(component_name)?;
-
-        // Create file appender with daily rotation
-        let file_appender = rolling::daily(&log_dir, format!("{component_name}.log"));
-        let (non_blocking_appender, guard) = non_blocking(file_appender);
-
-        // Store guard to prevent it from being dropped
-        std::mem::forget(guard);
-
-        // Create file layer (without colors for file output)
-        let file_layer = fmt::layer()
+    // Keep stdout for command output (e.g., reports)
+    let mut layers = vec![console_layer(config.format, component_name)];
+
+    let log_file = if enable_file_logging {
+        let (writer, log_file) = create_log_writer(component_name, config)?;
+        layers.push(file_layer(config.format, component_name, writer, true));
+        Some(log_file)
+    } else {
+        None
+    };
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(layers)
+        .try_init()
+        .map_err(|e| eyre::eyre!("Failed to initialize tracing subscriber: {}", e))?;
+
+    match log_file {
+        Some(log_file) => tracing::info!(
+            component = component_name,
+            log_file = %log_file.display(),
+            "Logging initialized with console and file output"
+        ),
+        None => {
+            tracing::info!(
+                component = component_name,
+                "Logging initialized with console output only"
+            )
+        }
+    }
+
+    // Log some useful information
+    log_environment_info(component_name);
+
+    Ok(())
+}
+
+/// Console layer, on stderr, muted while console logging is disabled
+fn console_layer<S>(format: LogFormat, component_name: &str) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let layer: Box<dyn Layer<S> + Send + Sync> = match format {
+        LogFormat::Pretty => fmt::layer()
             .with_target(true)
             .with_thread_ids(true)
             .with_thread_names(true)
@@ -100,47 +248,218 @@ pub fn init_logging(component_name: &str, enable_file_logging: bool) -> Result<(
             .with_line_number(true)
             .with_span_events(FmtSpan::CLOSE)
             .with_timer(LocalTime::rfc_3339())
+            .with_ansi(true) // Enable colors
+            .with_writer(io::stderr)
+            .pretty() // Use pretty formatting
+            .boxed(),
+        LogFormat::Json => fmt::layer()
+            .event_format(JsonFormat::new(component_name))
+            .with_writer(io::stderr)
+            .boxed(),
+    };
+    layer.with_filter(filter_fn(|_| CONSOLE_LOGGING.load(Ordering::Relaxed))).boxed()
+}
+
+/// File layer, writing to the component's log file
+///
+/// `thread_info` adds the thread id and name to pretty-printed records.
+fn file_layer<S>(
+    format: LogFormat,
+    component_name: &str,
+    writer: NonBlocking,
+    thread_info: bool,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match format {
+        LogFormat::Pretty => fmt::layer()
+            .with_target(true)
+            .with_thread_ids(thread_info)
+            .with_thread_names(thread_info)
+            .with_file(true)
+            .with_line_number(true)
+            .with_span_events(FmtSpan::CLOSE)
+            .with_timer(LocalTime::rfc_3339())
             .with_ansi(false) // No colors in files
-            .with_writer(non_blocking_appender);
+            .with_writer(writer)
+            .boxed(),
+        LogFormat::Json => {
+            fmt::layer().event_format(JsonFormat::new(component_name)).with_writer(writer).boxed()
+        }
+    }
+}
 
-        // Initialize subscriber with both console and file layers
-        tracing_subscriber::registry()
-            .with(env_filter)
-            .with(console_layer)
-            .with(file_layer)
-            .try_init()
-            .map_err(|e| eyre::eyre!("Failed to initialize tracing subscriber: {}", e))?;
+/// Create the log directory of a component in a log directory
+fn create_log_directory_in(root: &Path, component_name: &str) -> Result<PathBuf> {
+    let log_dir = root.join(component_name);
 
-        tracing::info!(
-            component = component_name,
-            log_dir = %log_dir.display(),
-            "Logging initialized with console and file output"
-        );
-    } else {
-        // Initialize subscriber with only console layer
-        tracing_subscriber::registry()
-            .with(env_filter)
-            .with(console_layer)
-            .try_init()
-            .map_err(|e| eyre::eyre!("Failed to initialize tracing subscriber: {}", e))?;
-
-        tracing::info!(component = component_name, "Logging initialized with console output only");
+    fs::create_dir_all(&log_dir)?;
+
+    Ok(log_dir)
+}
+
+/// Open the component's log file, rotated by size, behind a non-blocking writer
+///
+/// # Returns
+/// The writer, and the path of the log file
+fn create_log_writer(component_name: &str, config: &LogConfig) -> Result<(NonBlocking, PathBuf)> {
+    let log_dir = create_log_directory_in(&config.dir, component_name)?;
+    let log_file = log_dir.join(format!("{component_name}.log"));
+
+    let file = RotatingFile::open(&log_file, config.max_file_size, config.max_files)
+        .map_err(|e| eyre!("Failed to open log file {}: {e}", log_file.display()))?;
+    let (writer, guard) = non_blocking(file);
+
+    // Store guard to prevent it from being dropped
+    std::mem::forget(guard);
+
+    Ok((writer, log_file))
+}
+
+/// Log file rotated once it reaches a size
+///
+/// On rotation, `<name>` is renamed `<name>.1`, `<name>.1` is renamed `<name>.2`, and so
+/// on; at most `max_files` rotated files are kept.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingFile {
+    /// Open a log file, appending to it if it exists
+    pub fn open(path: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path: path.to_path_buf(), file, size, max_size, max_files })
     }
 
-    // Log some useful information
-    log_environment_info(component_name);
+    /// Path of the `index`-th rotated file
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        path.into()
+    }
 
-    Ok(())
+    /// Shift the rotated files, move the current file to `<name>.1` and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for index in (1..self.max_files).rev() {
+                match fs::rename(self.rotated_path(index), self.rotated_path(index + 1)) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                    _ => {}
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
 }
 
-/// Create log directory in system temp folder
-fn create_log_directory(component_name: &str) -> Result<PathBuf> {
-    let temp_dir = env::temp_dir();
-    let log_dir = temp_dir.join("edb-logs").join(component_name);
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size.saturating_add(buf.len() as u64) > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
 
-    fs::create_dir_all(&log_dir)?;
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
 
-    Ok(log_dir)
+/// Event formatter writing one JSON object per line
+///
+/// ```json
+/// {"timestamp":"...","level":"INFO","component":"edb","target":"edb_engine::core",
+///  "spans":["prepare"],"fields":{"message":"Replaying transaction"}}
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonFormat {
+    component: String,
+}
+
+impl JsonFormat {
+    /// Create a formatter tagging every event with the component
+    pub fn new(component_name: &str) -> Self {
+        Self { component: component_name.to_string() }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: fmt::format::Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+
+        let mut line = Map::new();
+        line.insert("timestamp".into(), chrono::Local::now().to_rfc3339().into());
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("component".into(), self.component.clone().into());
+        line.insert("target".into(), metadata.target().into());
+        if let (Some(file), Some(line_number)) = (metadata.file(), metadata.line()) {
+            line.insert("location".into(), format!("{file}:{line_number}").into());
+        }
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<Value> = scope.from_root().map(|span| span.name().into()).collect();
+            line.insert("spans".into(), spans.into());
+        }
+        line.insert("fields".into(), Value::Object(fields.0));
+
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// Fields of an event, as JSON values
+#[derive(Debug, Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.insert(field.name().into(), format!("{value:?}").into());
+    }
 }
 
 /// Log useful environment and system information
@@ -167,6 +486,7 @@ fn log_environment_info(component_name: &str) {
 ///
 /// This function sets up logging that only writes to a file, not to stdout/stderr.
 /// This is essential for TUI applications that need full control over the terminal.
+/// The file is configured like the one of [`init_logging`].
 ///
 /// # Arguments
 /// * `component_name` - Name of the component (e.g., "edb-tui")
@@ -189,33 +509,17 @@ fn log_environment_info(component_name: &str) {
 /// }
 /// ```
 pub fn init_file_only_logging(component_name: &str) -> Result<PathBuf> {
+    let config = LogConfig::from_env()?;
+
     // Create environment filter with default INFO level
     let env_filter = EnvFilter::try_from_default_env()
         .or_else(|_| EnvFilter::try_new(Level::INFO.as_str()))
         .expect("Failed to create environment filter");
 
-    // Create log directory in temp folder
-    let log_dir = create_log_directory(component_name)?;
-    let log_file_path = log_dir.join(format!("{component_name}.log"));
+    let (writer, log_file_path) = create_log_writer(component_name, &config)?;
 
-    // Create file appender with daily rotation
-    let file_appender = rolling::daily(&log_dir, format!("{component_name}.log"));
-    let (non_blocking_appender, guard) = non_blocking(file_appender);
-
-    // Store guard to prevent it from being dropped
-    std::mem::forget(guard);
-
-    // Create file-only layer (no console output)
-    let file_layer = fmt::layer()
-        .with_target(true)
-        .with_thread_ids(false) // Less verbose for TUI logs
-        .with_thread_names(false)
-        .with_file(true)
-        .with_line_number(true)
-        .with_span_events(FmtSpan::CLOSE)
-        .with_timer(LocalTime::rfc_3339())
-        .with_ansi(false) // No colors in files
-        .with_writer(non_blocking_appender);
+    // Create file-only layer (no console output), without thread info for TUI logs
+    let file_layer = file_layer(config.format, component_name, writer, false);
 
     // Initialize subscriber with only file layer (no console output)
     tracing_subscriber::registry()
@@ -323,7 +627,7 @@ mod tests {
     #[test]
     fn test_log_directory_creation() {
         // Test that we can create log directories
-        let result = create_log_directory("test-component");
+        let result = create_log_directory_in(&LogConfig::default().dir, "test-component");
         assert!(result.is_ok());
 
         let log_dir = result.unwrap();
@@ -352,4 +656,67 @@ mod tests {
         // Verify logging still works after initialization attempts
         info!("Test logging after fancy init attempts");
     }
+
+    #[test]
+    fn test_log_format_from_str() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("Pretty".parse::<LogFormat>().unwrap(), LogFormat::Pretty);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_rotating_file() {
+        let log_dir = std::env::temp_dir().join("edb-test-rotating-logs");
+        let _ = fs::remove_dir_all(&log_dir);
+        fs::create_dir_all(&log_dir).unwrap();
+        let path = log_dir.join("test.log");
+
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        // Only the two latest rotated files are kept
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(log_dir.join("test.log.1")).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(log_dir.join("test.log.2")).unwrap(), "second\n");
+        assert!(!log_dir.join("test.log.3").exists());
+    }
+
+    #[test]
+    fn test_json_format() {
+        #[derive(Clone, Default)]
+        struct Buffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry().with(
+            fmt::layer()
+                .event_format(JsonFormat::new("edb-test"))
+                .with_writer(move || writer.clone()),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("prepare").entered();
+            info!(block = 42u64, "Replaying transaction");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["component"], "edb-test");
+        assert_eq!(line["spans"][0], "prepare");
+        assert_eq!(line["fields"]["message"], "Replaying transaction");
+        assert_eq!(line["fields"]["block"], 42);
+    }
 }
@@ -73,6 +73,18 @@ pub trait OpcodeTr {
 
     /// Check if this opcode is a contract creation call
     fn is_creation_call(&self) -> bool;
+
+    /// Check if this opcode only exists in the code sections of EOF containers
+    ///
+    /// # Example
+    /// ```rust
+    /// use revm::bytecode::OpCode;
+    /// use edb_common::{eof, OpcodeTr};
+    ///
+    /// assert!(unsafe { OpCode::new_unchecked(eof::RJUMP) }.is_eof_only());
+    /// assert!(!OpCode::JUMP.is_eof_only());
+    /// ```
+    fn is_eof_only(&self) -> bool;
 }
 
 impl OpcodeTr for OpCode {
@@ -99,6 +111,10 @@ impl OpcodeTr for OpCode {
 
             // Log emissions - add entries to transaction receipt logs
             Self::LOG0 | Self::LOG1 | Self::LOG2 | Self::LOG3 | Self::LOG4
+        ) || matches!(
+            self.get(),
+            eof::EOFCREATE | // Create new contract from an EOF subcontainer
+            eof::EXTCALL // EOF external call that can transfer ETH
         )
     }
 
@@ -118,11 +134,229 @@ impl OpcodeTr for OpCode {
                 | Self::CALLCODE
                 | Self::DELEGATECALL
                 | Self::STATICCALL
+        ) || matches!(
+            self.get(),
+            eof::EOFCREATE | eof::EXTCALL | eof::EXTDELEGATECALL | eof::EXTSTATICCALL
         )
     }
 
     fn is_creation_call(&self) -> bool {
-        matches!(*self, Self::CREATE | Self::CREATE2)
+        matches!(*self, Self::CREATE | Self::CREATE2) || self.get() == eof::EOFCREATE
+    }
+
+    fn is_eof_only(&self) -> bool {
+        eof::name(self.get()).is_some()
+    }
+}
+
+/// EVM Object Format (EOF) containers and the opcodes only valid in their code
+/// sections (EIP-3540, EIP-7692).
+///
+/// REVM does not know these opcodes, so they are identified by their byte values.
+pub mod eof {
+    use std::ops::Range;
+
+    /// Magic prefix of EOF containers
+    pub const MAGIC: [u8; 2] = [0xEF, 0x00];
+    /// The only EOF version defined so far
+    pub const VERSION: u8 = 0x01;
+
+    const KIND_TYPES: u8 = 0x01;
+    const KIND_CODE: u8 = 0x02;
+    const KIND_CONTAINER: u8 = 0x03;
+    const KIND_DATA: u8 = 0xFF;
+    const TERMINATOR: u8 = 0x00;
+
+    /// Load a word of the data section at a dynamic offset
+    pub const DATALOAD: u8 = 0xD0;
+    /// Load a word of the data section at an immediate offset
+    pub const DATALOADN: u8 = 0xD1;
+    /// Size of the data section
+    pub const DATASIZE: u8 = 0xD2;
+    /// Copy the data section into memory
+    pub const DATACOPY: u8 = 0xD3;
+    /// Relative jump by an immediate signed offset
+    pub const RJUMP: u8 = 0xE0;
+    /// Conditional relative jump by an immediate signed offset
+    pub const RJUMPI: u8 = 0xE1;
+    /// Relative jump through an immediate table of signed offsets
+    pub const RJUMPV: u8 = 0xE2;
+    /// Call a code section
+    pub const CALLF: u8 = 0xE3;
+    /// Return from a code section
+    pub const RETF: u8 = 0xE4;
+    /// Jump to a code section without returning
+    pub const JUMPF: u8 = 0xE5;
+    /// Duplicate the stack item at an immediate depth
+    pub const DUPN: u8 = 0xE6;
+    /// Swap the top of the stack with the item at an immediate depth
+    pub const SWAPN: u8 = 0xE7;
+    /// Swap two stack items below the top, at depths packed in the immediate
+    pub const EXCHANGE: u8 = 0xE8;
+    /// Create a contract from a subcontainer
+    pub const EOFCREATE: u8 = 0xEC;
+    /// Return a subcontainer as the deployed code
+    pub const RETURNCONTRACT: u8 = 0xEE;
+    /// Load a word of the return data
+    pub const RETURNDATALOAD: u8 = 0xF7;
+    /// Call without a gas limit argument
+    pub const EXTCALL: u8 = 0xF8;
+    /// Delegatecall without a gas limit argument
+    pub const EXTDELEGATECALL: u8 = 0xF9;
+    /// Staticcall without a gas limit argument
+    pub const EXTSTATICCALL: u8 = 0xFB;
+
+    /// Name of an opcode only valid in EOF code sections
+    pub fn name(opcode: u8) -> Option<&'static str> {
+        let name = match opcode {
+            DATALOAD => "DATALOAD",
+            DATALOADN => "DATALOADN",
+            DATASIZE => "DATASIZE",
+            DATACOPY => "DATACOPY",
+            RJUMP => "RJUMP",
+            RJUMPI => "RJUMPI",
+            RJUMPV => "RJUMPV",
+            CALLF => "CALLF",
+            RETF => "RETF",
+            JUMPF => "JUMPF",
+            DUPN => "DUPN",
+            SWAPN => "SWAPN",
+            EXCHANGE => "EXCHANGE",
+            EOFCREATE => "EOFCREATE",
+            RETURNCONTRACT => "RETURNCONTRACT",
+            RETURNDATALOAD => "RETURNDATALOAD",
+            EXTCALL => "EXTCALL",
+            EXTDELEGATECALL => "EXTDELEGATECALL",
+            EXTSTATICCALL => "EXTSTATICCALL",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    /// Size of the immediate data following the instruction at `pc` of an EOF code
+    /// section, including the data of PUSH instructions
+    pub fn immediate_size(code: &[u8], pc: usize) -> usize {
+        let Some(&opcode) = code.get(pc) else { return 0 };
+        match opcode {
+            0x60..=0x7F => (opcode - 0x5F) as usize,
+            RJUMP | RJUMPI | CALLF | JUMPF | DATALOADN => 2,
+            DUPN | SWAPN | EXCHANGE | EOFCREATE | RETURNCONTRACT => 1,
+            // The max index is followed by a table of `max_index + 1` offsets
+            RJUMPV => code.get(pc + 1).map_or(1, |max_index| 1 + (*max_index as usize + 1) * 2),
+            _ => 0,
+        }
+    }
+
+    /// Whether the code is an EOF container
+    pub fn is_eof(code: &[u8]) -> bool {
+        code.starts_with(&MAGIC)
+    }
+
+    /// Sections of an EOF container, as byte ranges of the container
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct EofContainer {
+        /// Version of the container format
+        pub version: u8,
+        /// Inputs, outputs and max stack height of each code section
+        pub types: Range<usize>,
+        /// Code sections, the first one being the entry point
+        pub code_sections: Vec<Range<usize>>,
+        /// Subcontainers deployed by `EOFCREATE` or returned by `RETURNCONTRACT`
+        pub container_sections: Vec<Range<usize>>,
+        /// Data section, which may be truncated in initcode containers
+        pub data: Range<usize>,
+    }
+
+    impl EofContainer {
+        /// Parse the header of an EOF container, `None` if the code is not one or
+        /// its header is malformed
+        pub fn parse(code: &[u8]) -> Option<Self> {
+            if !is_eof(code) {
+                return None;
+            }
+            let mut header = Header { code, offset: MAGIC.len() };
+
+            let version = header.u8()?;
+            header.expect(KIND_TYPES)?;
+            let types_size = header.u16()?;
+            header.expect(KIND_CODE)?;
+            let code_sizes =
+                (0..header.u16()?).map(|_| header.u16()).collect::<Option<Vec<_>>>()?;
+            let container_sizes = if header.peek()? == KIND_CONTAINER {
+                header.expect(KIND_CONTAINER)?;
+                (0..header.u16()?).map(|_| header.u32()).collect::<Option<Vec<_>>>()?
+            } else {
+                Vec::new()
+            };
+            header.expect(KIND_DATA)?;
+            let data_size = header.u16()?;
+            header.expect(TERMINATOR)?;
+
+            if code_sizes.is_empty() {
+                return None;
+            }
+
+            let mut offset = header.offset;
+            let mut section = |size: usize| {
+                let range = offset..offset + size;
+                offset = range.end;
+                range
+            };
+            let types = section(types_size);
+            let code_sections: Vec<_> = code_sizes.into_iter().map(&mut section).collect();
+            let container_sections: Vec<_> =
+                container_sizes.into_iter().map(&mut section).collect();
+            let data = section(data_size);
+
+            // All sections but the data section must be present
+            let body_end = container_sections.last().or(code_sections.last())?.end;
+            if body_end > code.len() {
+                return None;
+            }
+
+            Some(Self {
+                version,
+                types,
+                code_sections,
+                container_sections,
+                data: data.start..data.end.min(code.len()),
+            })
+        }
+
+        /// Code section containing a byte offset of the container
+        pub fn code_section_at(&self, offset: usize) -> Option<usize> {
+            self.code_sections.iter().position(|section| section.contains(&offset))
+        }
+    }
+
+    /// Cursor over the header of an EOF container
+    struct Header<'a> {
+        code: &'a [u8],
+        offset: usize,
+    }
+
+    impl Header<'_> {
+        fn peek(&self) -> Option<u8> {
+            self.code.get(self.offset).copied()
+        }
+
+        fn u8(&mut self) -> Option<u8> {
+            let value = self.peek()?;
+            self.offset += 1;
+            Some(value)
+        }
+
+        fn expect(&mut self, kind: u8) -> Option<()> {
+            (self.u8()? == kind).then_some(())
+        }
+
+        fn u16(&mut self) -> Option<usize> {
+            Some(((self.u8()? as usize) << 8) | self.u8()? as usize)
+        }
+
+        fn u32(&mut self) -> Option<usize> {
+            Some((self.u16()? << 16) | self.u16()?)
+        }
     }
 }
 
@@ -274,4 +508,62 @@ mod tests {
         // SELFDESTRUCT does modify state
         assert!(OpCode::SELFDESTRUCT.modifies_evm_state());
     }
+
+    #[test]
+    fn test_eof_opcodes() {
+        let opcode = |byte| unsafe { OpCode::new_unchecked(byte) };
+
+        assert!(opcode(eof::EXTCALL).is_message_call());
+        assert!(opcode(eof::EXTCALL).modifies_evm_state());
+        assert!(opcode(eof::EXTDELEGATECALL).is_message_call());
+        assert!(!opcode(eof::EXTSTATICCALL).modifies_evm_state());
+        assert!(opcode(eof::EOFCREATE).is_creation_call());
+        assert!(!opcode(eof::RJUMP).is_message_call());
+
+        assert!(opcode(eof::DUPN).is_eof_only());
+        assert!(!OpCode::DUP1.is_eof_only());
+        assert_eq!(eof::name(eof::EXCHANGE), Some("EXCHANGE"));
+        assert_eq!(eof::name(OpCode::ADD.get()), None);
+    }
+
+    #[test]
+    fn test_eof_immediate_size() {
+        // PUSH2 0x1234, RJUMPI +0, SWAPN 1, RJUMPV with offsets [+0, +1], STOP
+        let code = [0x61, 0x12, 0x34, 0xE1, 0x00, 0x00, 0xE7, 0x01, 0xE2, 0x01, 0, 0, 0, 1, 0x00];
+
+        assert_eq!(eof::immediate_size(&code, 0), 2);
+        assert_eq!(eof::immediate_size(&code, 3), 2);
+        assert_eq!(eof::immediate_size(&code, 6), 1);
+        assert_eq!(eof::immediate_size(&code, 8), 5);
+        assert_eq!(eof::immediate_size(&code, 14), 0);
+    }
+
+    #[test]
+    fn test_eof_container() {
+        let container = [
+            0xEF, 0x00, 0x01, // magic and version
+            0x01, 0x00, 0x08, // types section of two code sections
+            0x02, 0x00, 0x02, 0x00, 0x03, 0x00, 0x01, // code sections of 3 and 1 bytes
+            0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, // one subcontainer of 2 bytes
+            0xFF, 0x00, 0x04, // data section of 4 bytes, truncated below
+            0x00, // terminator
+            0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // types
+            0xE5, 0x00, 0x01, // JUMPF 1
+            0x00, // STOP
+            0xEF, 0x00, // subcontainer
+            0xAA, 0xBB, // data
+        ];
+
+        let parsed = eof::EofContainer::parse(&container).unwrap();
+        assert_eq!(parsed.version, eof::VERSION);
+        assert_eq!(parsed.types, 24..32);
+        assert_eq!(parsed.code_sections, vec![32..35, 35..36]);
+        assert_eq!(parsed.container_sections, vec![36..38]);
+        assert_eq!(parsed.data, 38..40);
+        assert_eq!(parsed.code_section_at(35), Some(1));
+
+        // Legacy code and truncated code sections are not containers
+        assert!(eof::EofContainer::parse(&[0x60, 0x00]).is_none());
+        assert!(eof::EofContainer::parse(&container[..34]).is_none());
+    }
 }
@@ -0,0 +1,211 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prestate cache of replayed transactions.
+//!
+//! Preparing a transaction replays every preceding transaction of its block, which
+//! fetches a large amount of state through the RPC proxy. Once a transaction has been
+//! prepared, the state it touched (as it was right before the transaction) is stored
+//! under `<cache_root>/prestate/<chain>/<block>`, keyed by the transaction hash. When
+//! the same transaction is prepared again, the cached prestate is written into the
+//! fork instead of replaying the block, and only state the transaction did not touch
+//! is still fetched lazily.
+
+use std::{collections::BTreeMap, env};
+
+use alloy_primitives::{Address, Bytes, TxHash, U256};
+use eyre::Result;
+use revm::{
+    database::CacheDB,
+    primitives::KECCAK_EMPTY,
+    state::{AccountInfo, Bytecode, EvmState},
+    DatabaseRef,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{CachePath, EdbCache, EdbCachePath};
+
+/// State of an account right before a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrestateAccount {
+    /// Balance of the account
+    pub balance: U256,
+    /// Nonce of the account
+    pub nonce: u64,
+    /// Deployed code of the account, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Storage slots touched by the transaction
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// State touched by a transaction, as it was right before the transaction.
+pub type Prestate = BTreeMap<Address, PrestateAccount>;
+
+/// Open the prestate cache of a block, if a cache directory is available.
+pub fn prestate_cache(chain_id: u64, block_number: u64) -> Option<EdbCache<Prestate>> {
+    EdbCache::new(
+        EdbCachePath::new(env::var(crate::env::EDB_CACHE_DIR).ok())
+            .prestate_block_cache_dir(chain_id, block_number),
+        None,
+    )
+    .ok()
+    .flatten()
+}
+
+/// Label of the cached prestate of a transaction.
+///
+/// Quick mode does not replay the preceding transactions, so the prestate it sees is
+/// cached separately.
+pub fn prestate_cache_label(tx_hash: TxHash, quick: bool) -> String {
+    if quick {
+        format!("{tx_hash}-quick")
+    } else {
+        tx_hash.to_string()
+    }
+}
+
+/// Collect the prestate of every account and slot in `state`.
+///
+/// `state` is the uncommitted result of executing the transaction on top of `db`, so
+/// account info and code are read from `db`, while storage is taken from the original
+/// values of the touched slots.
+pub fn capture_prestate<DB>(db: &DB, state: &EvmState) -> Result<Prestate>
+where
+    DB: DatabaseRef,
+{
+    let mut prestate = Prestate::new();
+    for (address, account) in state {
+        let info = db
+            .basic_ref(*address)
+            .map_err(|e| eyre::eyre!("Failed to get account info for {}: {}", address, e))?
+            .unwrap_or_default();
+
+        let code = match &info.code {
+            Some(code) => Some(code.original_bytes()),
+            None if info.code_hash != KECCAK_EMPTY => Some(
+                db.code_by_hash_ref(info.code_hash)
+                    .map_err(|e| eyre::eyre!("Failed to get code of {}: {}", address, e))?
+                    .original_bytes(),
+            ),
+            None => None,
+        };
+
+        let storage =
+            account.storage.iter().map(|(slot, value)| (*slot, value.original_value())).collect();
+
+        prestate.insert(
+            *address,
+            PrestateAccount { balance: info.balance, nonce: info.nonce, code, storage },
+        );
+    }
+
+    Ok(prestate)
+}
+
+/// Write a cached prestate into the cache of a database.
+///
+/// State that is not part of the prestate is still read from the underlying database.
+pub fn apply_prestate<DB>(db: &mut CacheDB<DB>, prestate: &Prestate) -> Result<()>
+where
+    DB: DatabaseRef,
+{
+    for (address, account) in prestate {
+        let mut info =
+            AccountInfo { balance: account.balance, nonce: account.nonce, ..Default::default() };
+        if let Some(code) = &account.code {
+            // The code hash is recomputed by `insert_account_info`
+            info.code_hash = KECCAK_EMPTY;
+            info.code = Some(Bytecode::new_raw(code.clone()));
+        }
+        db.insert_account_info(*address, info);
+
+        for (slot, value) in &account.storage {
+            db.insert_account_storage(*address, *slot, *value)
+                .map_err(|e| eyre::eyre!("Failed to set storage of {}: {}", address, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, bytes, keccak256};
+    use revm::database::EmptyDB;
+
+    #[test]
+    fn test_apply_prestate() {
+        let contract = address!("0x00000000000000000000000000000000000000aa");
+        let eoa = address!("0x00000000000000000000000000000000000000bb");
+        let code = bytes!("0x6001600055");
+
+        let mut prestate = Prestate::new();
+        prestate.insert(
+            contract,
+            PrestateAccount {
+                balance: U256::from(1),
+                nonce: 1,
+                code: Some(code.clone()),
+                storage: BTreeMap::from([(U256::ZERO, U256::from(42))]),
+            },
+        );
+        prestate.insert(
+            eoa,
+            PrestateAccount { balance: U256::from(100), nonce: 7, ..Default::default() },
+        );
+
+        let mut db = CacheDB::new(EmptyDB::default());
+        apply_prestate(&mut db, &prestate).unwrap();
+
+        let info = db.basic_ref(contract).unwrap().unwrap();
+        assert_eq!(info.balance, U256::from(1));
+        assert_eq!(info.code_hash, keccak256(&code));
+        assert_eq!(db.storage_ref(contract, U256::ZERO).unwrap(), U256::from(42));
+        assert_eq!(db.storage_ref(contract, U256::from(1)).unwrap(), U256::ZERO);
+
+        let info = db.basic_ref(eoa).unwrap().unwrap();
+        assert_eq!(info.nonce, 7);
+        assert_eq!(info.code_hash, KECCAK_EMPTY);
+    }
+
+    #[test]
+    fn test_prestate_roundtrip() {
+        let mut prestate = Prestate::new();
+        prestate.insert(
+            Address::ZERO,
+            PrestateAccount {
+                balance: U256::from(5),
+                nonce: 0,
+                code: None,
+                storage: BTreeMap::from([(U256::from(3), U256::from(4))]),
+            },
+        );
+
+        let json = serde_json::to_string(&prestate).unwrap();
+        assert!(!json.contains("code"));
+        assert_eq!(serde_json::from_str::<Prestate>(&json).unwrap(), prestate);
+    }
+
+    #[test]
+    fn test_prestate_cache_label() {
+        let hash = TxHash::repeat_byte(0x11);
+        assert_eq!(prestate_cache_label(hash, false), hash.to_string());
+        assert_eq!(prestate_cache_label(hash, true), format!("{hash}-quick"));
+    }
+}
@@ -16,6 +16,7 @@
 
 //! Progress message types for tracking operation progress
 
+use alloy_primitives::TxHash;
 use serde::{Deserialize, Serialize};
 
 /// Progress message with optional step tracking
@@ -47,6 +48,97 @@ impl ProgressMessage {
     }
 }
 
+/// Lifecycle state of a transaction preparation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreparationState {
+    /// The transaction is being prepared
+    Preparing,
+    /// The transaction is ready to be debugged
+    Ready,
+    /// The preparation failed
+    Failed,
+    /// The preparation was cancelled before it finished
+    Cancelled,
+}
+
+/// Status of a transaction preparation, as reported by the `/status` endpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreparationStatus {
+    /// Transaction being prepared
+    pub tx_hash: TxHash,
+    /// Lifecycle state of the preparation
+    pub state: PreparationState,
+    /// Description of the current phase
+    pub phase: String,
+    /// Current step number, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_step: Option<usize>,
+    /// Total number of steps, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_steps: Option<usize>,
+    /// Estimated completion in percent, based on the finished steps
+    pub percent: f64,
+    /// Milliseconds since the preparation started
+    pub elapsed_ms: u64,
+    /// Error message of a failed preparation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PreparationStatus {
+    /// Create the status of a preparation that just started
+    pub fn preparing(tx_hash: TxHash) -> Self {
+        Self {
+            tx_hash,
+            state: PreparationState::Preparing,
+            phase: "Starting preparation".to_string(),
+            current_step: None,
+            total_steps: None,
+            percent: 0.0,
+            elapsed_ms: 0,
+            error: None,
+        }
+    }
+
+    /// Create the status of a finished preparation
+    pub fn ready(tx_hash: TxHash) -> Self {
+        Self {
+            state: PreparationState::Ready,
+            phase: "Ready".to_string(),
+            percent: 100.0,
+            ..Self::preparing(tx_hash)
+        }
+    }
+
+    /// Advance the status to the step described by a progress message
+    pub fn advance(&mut self, message: &ProgressMessage) {
+        self.phase = message.message.clone();
+        if let (Some(current), Some(total)) = (message.current_step, message.total_steps) {
+            self.current_step = Some(current);
+            self.total_steps = Some(total);
+            // A step is reported when it starts, so only the preceding steps are complete
+            self.percent = current.saturating_sub(1) as f64 * 100.0 / total.max(1) as f64;
+        }
+    }
+
+    /// Check whether the preparation is over, successfully or not
+    pub fn is_finished(&self) -> bool {
+        self.state != PreparationState::Preparing
+    }
+}
+
+impl std::fmt::Display for PreparationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.current_step, self.total_steps) {
+            (Some(current), Some(total)) => {
+                write!(f, "[{current}/{total}] {} ({:.0}%)", self.phase, self.percent)
+            }
+            _ => write!(f, "{} ({:.0}%)", self.phase, self.percent),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -84,4 +176,20 @@ mod tests {
         assert!(json.contains("\"current_step\":3"));
         assert!(json.contains("\"total_steps\":10"));
     }
+
+    #[test]
+    fn test_preparation_status_advance() {
+        let mut status = PreparationStatus::preparing(TxHash::ZERO);
+        status.advance(&ProgressMessage::with_steps("Analyzing", 3, 8));
+        assert_eq!(status.current_step, Some(3));
+        assert_eq!(status.percent, 25.0);
+        assert_eq!(status.to_string(), "[3/8] Analyzing (25%)");
+
+        // Messages without steps only change the phase
+        status.advance(&ProgressMessage::new("Finalizing"));
+        assert_eq!(status.phase, "Finalizing");
+        assert_eq!(status.percent, 25.0);
+        assert!(!status.is_finished());
+        assert!(PreparationStatus::ready(TxHash::ZERO).is_finished());
+    }
 }
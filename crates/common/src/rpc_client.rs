@@ -0,0 +1,180 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! HTTP client of the engine RPC servers
+//!
+//! Engine RPC servers may require a bearer token and may be served over HTTPS with a
+//! certificate that is not publicly trusted. [`EngineRpcClient`] handles both for the
+//! clients of the servers, i.e., the TUI, the web UI, `edb attach`, and the Python bindings.
+
+use eyre::{bail, eyre, Result};
+use reqwest::{header::CONTENT_TYPE, Certificate, Client};
+use serde_json::{json, Value};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// Environment variable holding the bearer token of the engine RPC servers
+pub const RPC_AUTH_TOKEN_ENV: &str = "EDB_RPC_AUTH_TOKEN";
+
+/// How to connect to an engine RPC server
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcClientOptions {
+    /// Bearer token sent with every request, if the server requires one
+    pub auth_token: Option<String>,
+    /// PEM certificate to trust in addition to the system roots, e.g., the self-signed
+    /// certificate of the server
+    pub ca_cert: Option<PathBuf>,
+    /// Timeout of a single request
+    pub timeout: Option<Duration>,
+}
+
+/// JSON-RPC client of an engine RPC server reached over HTTP or HTTPS
+#[derive(Debug)]
+pub struct EngineRpcClient {
+    client: Client,
+    url: String,
+    auth_token: Option<String>,
+    next_id: AtomicU64,
+}
+
+impl EngineRpcClient {
+    /// Create a client of the server at `url`
+    pub fn new(url: impl Into<String>, options: &RpcClientOptions) -> Result<Self> {
+        let mut builder = Client::builder();
+        if let Some(path) = &options.ca_cert {
+            let pem = std::fs::read(path)
+                .map_err(|e| eyre!("Failed to read CA certificate {}: {}", path.display(), e))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+        if let Some(timeout) = options.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            url: url.into(),
+            auth_token: options.auth_token.clone(),
+            next_id: AtomicU64::new(1),
+        })
+    }
+
+    /// Get the URL of the server
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Get the bearer token sent to the server, if any
+    pub fn auth_token(&self) -> Option<&str> {
+        self.auth_token.as_deref()
+    }
+
+    /// Make a JSON-RPC call and return its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let mut builder = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&request)?);
+        if let Some(token) = &self.auth_token {
+            builder = builder.bearer_auth(token);
+        }
+
+        let body = builder.send().await?.error_for_status()?.bytes().await?;
+        into_rpc_result(serde_json::from_slice(&body)?)
+    }
+
+    /// Send a raw JSON-RPC payload (a request or a batch) to `path` of the server and
+    /// return the raw response, `None` if the server answered with no content (e.g., to
+    /// notifications)
+    pub async fn forward(&self, path: &str, payload: &Value) -> Result<Option<Value>> {
+        let url = format!("{}{}", self.url.trim_end_matches('/'), path);
+        let mut builder = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(payload)?);
+        if let Some(token) = &self.auth_token {
+            builder = builder.bearer_auth(token);
+        }
+
+        let body = builder.send().await?.error_for_status()?.bytes().await?;
+        if body.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&body)?))
+    }
+
+    /// Fetch a JSON document of the server, e.g., `/status`
+    pub async fn get(&self, path: &str) -> Result<Value> {
+        let url = format!("{}{}", self.url.trim_end_matches('/'), path);
+        let mut builder = self.client.get(url);
+        if let Some(token) = &self.auth_token {
+            builder = builder.bearer_auth(token);
+        }
+
+        let body = builder.send().await?.error_for_status()?.bytes().await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+}
+
+/// Extract the result of a JSON-RPC response, failing with its error
+pub fn into_rpc_result(mut response: Value) -> Result<Value> {
+    if let Some(error) = response.get("error").filter(|error| !error.is_null()) {
+        let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or_default();
+        bail!("RPC error {code}: {message}");
+    }
+    response
+        .get_mut("result")
+        .map(Value::take)
+        .ok_or_else(|| eyre!("RPC response has neither result nor error"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_rpc_result() {
+        let response = json!({ "jsonrpc": "2.0", "id": 1, "result": 42 });
+        assert_eq!(into_rpc_result(response).unwrap(), json!(42));
+
+        let response = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -33009, "message": "read-only" },
+        });
+        let error = into_rpc_result(response).unwrap_err().to_string();
+        assert_eq!(error, "RPC error -33009: read-only");
+
+        assert!(into_rpc_result(json!({ "jsonrpc": "2.0", "id": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_missing_ca_cert() {
+        let options = RpcClientOptions {
+            ca_cert: Some("/nonexistent/cert.pem".into()),
+            ..Default::default()
+        };
+        assert!(EngineRpcClient::new("https://localhost:3030", &options).is_err());
+    }
+}
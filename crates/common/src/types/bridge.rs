@@ -0,0 +1,99 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use alloy_primitives::{Address, Bytes, TxHash, B256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Direction of a message sent through a canonical bridge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BridgeDirection {
+    /// From L1 to L2, executed on L2 as a deposit transaction
+    Deposit,
+    /// From L2 to L1, proven and finalized on L1 after the challenge period
+    Withdrawal,
+}
+
+impl fmt::Display for BridgeDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Deposit => write!(f, "deposit"),
+            Self::Withdrawal => write!(f, "withdrawal"),
+        }
+    }
+}
+
+/// A message sent through a canonical bridge by the debugged transaction.
+///
+/// The correlated transaction is the other half of the bridged flow: the L2 deposit
+/// transaction of a deposit, or the L1 transaction finalizing a withdrawal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeMessage {
+    /// Direction of the message
+    pub direction: BridgeDirection,
+    /// Name of the L2 chain, e.g. `OP Mainnet`
+    pub l2_name: String,
+    /// Chain ID of the L1 chain
+    pub l1_chain_id: u64,
+    /// Chain ID of the L2 chain
+    pub l2_chain_id: u64,
+    /// Trace entry that emitted the bridge event
+    pub trace_entry_id: usize,
+    /// Snapshot that emitted the bridge event, if it could be determined
+    pub snapshot_id: Option<usize>,
+    /// Sender of the message, aliased for deposits from contracts
+    pub sender: Address,
+    /// Target of the message, if it is not a contract creation
+    pub target: Option<Address>,
+    /// Ether sent along with the message
+    pub value: U256,
+    /// Calldata of the message
+    pub data: Bytes,
+    /// Hash identifying the message: the L2 transaction hash of a deposit, or the
+    /// withdrawal hash of a withdrawal
+    pub message_hash: B256,
+    /// Transaction on the other chain, if known
+    pub correlated_tx_hash: Option<TxHash>,
+}
+
+impl BridgeMessage {
+    /// Chain ID of the source chain, where the debugged transaction ran
+    pub fn source_chain_id(&self) -> u64 {
+        match self.direction {
+            BridgeDirection::Deposit => self.l1_chain_id,
+            BridgeDirection::Withdrawal => self.l2_chain_id,
+        }
+    }
+
+    /// Chain ID of the chain the correlated transaction runs on
+    pub fn correlated_chain_id(&self) -> u64 {
+        match self.direction {
+            BridgeDirection::Deposit => self.l2_chain_id,
+            BridgeDirection::Withdrawal => self.l1_chain_id,
+        }
+    }
+
+    /// Short description of where the message goes, e.g. `deposit to OP Mainnet`
+    pub fn summary(&self) -> String {
+        match self.direction {
+            BridgeDirection::Deposit => format!("deposit to {}", self.l2_name),
+            BridgeDirection::Withdrawal => format!("withdrawal from {}", self.l2_name),
+        }
+    }
+}
@@ -14,7 +14,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
 
 use alloy_primitives::Address;
 use derive_more::From;
@@ -57,6 +60,45 @@ pub struct SourceInfo {
     pub sources: HashMap<PathBuf, String>, // file -> source
 }
 
+/// Source range of a single instruction of a contract's original (non-instrumented)
+/// deployed bytecode, decoded from the compiler's source map
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMapEntry {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// Disassembled instruction, including push data
+    pub instruction: String,
+    /// Source file the instruction was compiled from (`None` for compiler-generated code)
+    pub path: Option<PathBuf>,
+    /// Byte offset of the source range within the file
+    pub offset: usize,
+    /// Length of the source range in bytes
+    pub length: usize,
+}
+
+/// Instruction-level source map of a verified contract, linking opcodes to source ranges
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceMapInfo {
+    /// The address where the actual bytecode is stored (may differ from address in proxy patterns)
+    pub bytecode_address: Address,
+    /// One entry per instruction, ordered by program counter
+    pub entries: Vec<SourceMapEntry>,
+}
+
+/// Gas consumed per source line and per instruction of a contract in the debugged transaction
+///
+/// The gas of a step includes the gas of the calls it makes, so lines performing expensive
+/// external calls stand out as well.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GasHeatmap {
+    /// The address where the actual bytecode is stored (may differ from address in proxy patterns)
+    pub bytecode_address: Address,
+    /// Gas per executed line (1-based) of each source file, for contracts with source code
+    pub lines: HashMap<PathBuf, BTreeMap<usize, u64>>,
+    /// Gas per executed program counter, for contracts debugged at the opcode level
+    pub instructions: BTreeMap<usize, u64>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +128,34 @@ mod tests {
         assert_eq!(info.codes.get(&4), Some(&"MSTORE".to_string()));
     }
 
+    #[test]
+    fn test_source_map_info_serde_roundtrip() {
+        let info = SourceMapInfo {
+            bytecode_address: address!("1234567890123456789012345678901234567890"),
+            entries: vec![
+                SourceMapEntry {
+                    pc: 0,
+                    instruction: "PUSH1 0x80".to_string(),
+                    path: Some(PathBuf::from("src/Token.sol")),
+                    offset: 42,
+                    length: 10,
+                },
+                SourceMapEntry {
+                    pc: 2,
+                    instruction: "MSTORE".to_string(),
+                    path: None,
+                    offset: 0,
+                    length: 0,
+                },
+            ],
+        };
+
+        let json = serde_json::to_string(&info).unwrap();
+        let decoded: SourceMapInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.bytecode_address, info.bytecode_address);
+        assert_eq!(decoded.entries, info.entries);
+    }
+
     #[test]
     fn test_source_info_default() {
         let info = SourceInfo::default();
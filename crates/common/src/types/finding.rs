@@ -0,0 +1,190 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+/// Severity of a finding reported by the engine's analysis passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum FindingSeverity {
+    /// Informational, most likely benign
+    Info,
+    /// Unusual pattern that deserves a closer look
+    Low,
+    /// Suspicious pattern that may be exploitable
+    Medium,
+    /// Pattern that is very likely part of an exploit
+    High,
+}
+
+impl fmt::Display for FindingSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Low => write!(f, "low"),
+            Self::Medium => write!(f, "medium"),
+            Self::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Category-specific details of a finding
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FindingKind {
+    /// A call frame re-entered a contract whose outer frame still had state writes pending
+    Reentrancy {
+        /// Trace entry of the outer frame that was re-entered
+        outer_entry_id: usize,
+        /// Trace entry of the external call (direct child of the outer frame) that led to re-entry
+        external_call_entry_id: usize,
+        /// Trace entry of the re-entrant frame
+        reentrant_entry_id: usize,
+        /// Whether the re-entrant frame was a static (read-only) call
+        read_only: bool,
+        /// Storage slots written by the outer frame after the external call returned
+        pending_slots: Vec<U256>,
+    },
+    /// A storage slot of a proxy is interpreted differently by its implementation
+    StorageCollision {
+        /// Address of the proxy (the storage context)
+        proxy: Address,
+        /// Code address of the implementation delegated to by the proxy
+        implementation: Address,
+        /// Storage slot where the collision happens
+        slot: U256,
+        /// Variable (or well-known proxy slot) occupying the slot in the proxy
+        proxy_variable: String,
+        /// Variable occupying the slot in the implementation
+        implementation_variable: String,
+    },
+    /// A state variable was read as zero although it was never written in the transaction
+    UninitializedStorageRead {
+        /// Storage slot that was read
+        slot: U256,
+        /// State variable occupying the slot according to the storage layout
+        variable: String,
+        /// Program counter of the `SLOAD` instruction
+        pc: usize,
+    },
+    /// A local storage pointer is declared without being initialized, so it points to
+    /// slot zero (only possible with compilers before 0.5.0)
+    UninitializedStoragePointer {
+        /// Name of the local storage variable
+        variable: String,
+        /// Function declaring the variable, if known
+        function: Option<String>,
+    },
+}
+
+impl FindingKind {
+    /// Short, human-readable name of the finding category
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::Reentrancy { .. } => "reentrancy",
+            Self::StorageCollision { .. } => "storage-collision",
+            Self::UninitializedStorageRead { .. } | Self::UninitializedStoragePointer { .. } => {
+                "uninitialized-storage"
+            }
+        }
+    }
+}
+
+/// A single issue detected by the engine's analysis passes over a debugging session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Category-specific details
+    pub kind: FindingKind,
+    /// How severe the finding is
+    pub severity: FindingSeverity,
+    /// Contract (storage context) the finding is about
+    pub address: Address,
+    /// Trace entry the finding should be attached to in the UI
+    pub trace_entry_id: Option<usize>,
+    /// First snapshot related to the finding, if any
+    pub snapshot_id: Option<usize>,
+    /// Human-readable description of the finding
+    pub message: String,
+}
+
+impl Finding {
+    /// Short, human-readable name of the finding category
+    pub fn category(&self) -> &'static str {
+        self.kind.category()
+    }
+
+    /// Whether this finding is attached to the given trace entry
+    pub fn is_attached_to(&self, trace_entry_id: usize) -> bool {
+        self.trace_entry_id == Some(trace_entry_id)
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.category(), self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn reentrancy_finding() -> Finding {
+        Finding {
+            kind: FindingKind::Reentrancy {
+                outer_entry_id: 1,
+                external_call_entry_id: 2,
+                reentrant_entry_id: 3,
+                read_only: false,
+                pending_slots: vec![U256::from(7)],
+            },
+            severity: FindingSeverity::High,
+            address: address!("0x1234567890123456789012345678901234567890"),
+            trace_entry_id: Some(3),
+            snapshot_id: Some(42),
+            message: "re-entered while writes were pending".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_finding_serialization() {
+        let finding = reentrancy_finding();
+
+        let json = serde_json::to_string(&finding).expect("Failed to serialize Finding");
+        let deserialized: Finding =
+            serde_json::from_str(&json).expect("Failed to deserialize Finding");
+
+        assert_eq!(deserialized, finding);
+    }
+
+    #[test]
+    fn test_finding_display() {
+        let finding = reentrancy_finding();
+        assert_eq!(finding.category(), "reentrancy");
+        assert!(finding.is_attached_to(3));
+        assert!(!finding.is_attached_to(1));
+        assert_eq!(finding.to_string(), "[high] reentrancy: re-entered while writes were pending");
+    }
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(FindingSeverity::High > FindingSeverity::Medium);
+        assert!(FindingSeverity::Medium > FindingSeverity::Low);
+        assert!(FindingSeverity::Low > FindingSeverity::Info);
+    }
+}
@@ -0,0 +1,140 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use alloy_primitives::{address, Address};
+use serde::{Deserialize, Serialize};
+
+/// Where the label of an address comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LabelSource {
+    /// Built-in table of well-known contracts and precompiles
+    WellKnown,
+    /// ENS reverse record, verified against the forward resolution
+    Ens,
+}
+
+impl fmt::Display for LabelSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WellKnown => write!(f, "well-known"),
+            Self::Ens => write!(f, "ens"),
+        }
+    }
+}
+
+/// Human-readable name of an address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressLabel {
+    /// Labeled address
+    pub address: Address,
+    /// Name shown in place of the address
+    pub name: String,
+    /// Where the name comes from
+    pub source: LabelSource,
+}
+
+impl AddressLabel {
+    /// Create a new label
+    pub fn new(address: Address, name: impl Into<String>, source: LabelSource) -> Self {
+        Self { address, name: name.into(), source }
+    }
+}
+
+/// Precompiles, named the same on every chain
+const PRECOMPILES: &[(Address, &str)] = &[
+    (address!("0x0000000000000000000000000000000000000001"), "ecrecover"),
+    (address!("0x0000000000000000000000000000000000000002"), "sha256"),
+    (address!("0x0000000000000000000000000000000000000003"), "ripemd160"),
+    (address!("0x0000000000000000000000000000000000000004"), "identity"),
+    (address!("0x0000000000000000000000000000000000000005"), "modexp"),
+    (address!("0x0000000000000000000000000000000000000006"), "ecAdd"),
+    (address!("0x0000000000000000000000000000000000000007"), "ecMul"),
+    (address!("0x0000000000000000000000000000000000000008"), "ecPairing"),
+    (address!("0x0000000000000000000000000000000000000009"), "blake2f"),
+    (address!("0x000000000000000000000000000000000000000a"), "pointEvaluation"),
+];
+
+/// Contracts deployed at the same address on every chain
+const CROSS_CHAIN: &[(Address, &str)] = &[
+    (address!("0xcA11bde05977b3631167028862bE2a173976CA11"), "Multicall3"),
+    (address!("0x000000000022D473030F116dDEE9F6B43aC78BA3"), "Permit2"),
+];
+
+/// Well-known Ethereum mainnet contracts
+const MAINNET: &[(Address, &str)] = &[
+    (address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"), "WETH"),
+    (address!("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"), "USDC"),
+    (address!("0xdAC17F958D2ee523a2206206994597C13D831ec7"), "USDT"),
+    (address!("0x6B175474E89094C44Da98b954EedeAC495271d0F"), "DAI"),
+    (address!("0x2260FAC5E5542a773Aa44fBCfeDf7C193bc2C599"), "WBTC"),
+    (address!("0xae7ab96520DE3A18E5e111B5EaAb095312D7fE84"), "Lido: stETH"),
+    (address!("0x5C69bEe701ef814a2B6a3EDD4B1652CB9cc5aA6f"), "Uniswap V2: Factory"),
+    (address!("0x7a250d5630B4cF539739dF2C5dAcb4c659F2488D"), "Uniswap V2: Router 2"),
+    (address!("0x1F98431c8aD98523631AE4a59f267346ea31F984"), "Uniswap V3: Factory"),
+    (address!("0xE592427A0AEce92De3Edee1F18E0157C05861564"), "Uniswap V3: Router"),
+    (address!("0x68b3465833fb72A70ecDF485E0e4C7bD8665Fc45"), "Uniswap V3: Router 2"),
+    (address!("0x3fC91A3afd70395Cd496C647d5a6CC9D4B2b7FAD"), "Uniswap: Universal Router"),
+    (address!("0xBA12222222228d8Ba445958a75a0704d566BF2C8"), "Balancer: Vault"),
+    (address!("0x87870Bca3F3fD6335C3F4ce8392D69350B4fA4E2"), "Aave V3: Pool"),
+    (address!("0xDef1C0ded9bec7F1a1670819833240f027b25EfF"), "0x: Exchange Proxy"),
+    (address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e"), "ENS: Registry"),
+    (address!("0x00000000219ab540356cBB839Cbe05303d7705Fa"), "Beacon Deposit Contract"),
+];
+
+/// Look up the built-in label of an address on the given chain
+pub fn well_known_label(chain_id: u64, address: Address) -> Option<AddressLabel> {
+    let chain_specific: &[(Address, &str)] = match chain_id {
+        1 => MAINNET,
+        _ => &[],
+    };
+
+    PRECOMPILES
+        .iter()
+        .chain(CROSS_CHAIN)
+        .chain(chain_specific)
+        .find(|(known, _)| *known == address)
+        .map(|(_, name)| AddressLabel::new(address, *name, LabelSource::WellKnown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_well_known_label() {
+        let weth = address!("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2");
+        assert_eq!(well_known_label(1, weth).map(|l| l.name), Some("WETH".to_string()));
+        assert_eq!(well_known_label(10, weth), None);
+
+        let ecrecover = address!("0x0000000000000000000000000000000000000001");
+        assert_eq!(well_known_label(10, ecrecover).map(|l| l.name), Some("ecrecover".to_string()));
+        assert_eq!(well_known_label(1, Address::ZERO), None);
+    }
+
+    #[test]
+    fn test_address_label_serialization() {
+        let label = AddressLabel::new(Address::ZERO, "vitalik.eth", LabelSource::Ens);
+
+        let json = serde_json::to_string(&label).expect("Failed to serialize AddressLabel");
+        let deserialized: AddressLabel =
+            serde_json::from_str(&json).expect("Failed to deserialize AddressLabel");
+
+        assert_eq!(deserialized, label);
+    }
+}
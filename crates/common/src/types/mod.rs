@@ -20,6 +20,9 @@ pub use abi::*;
 mod breakpoint;
 pub use breakpoint::*;
 
+mod bridge;
+pub use bridge::*;
+
 mod code;
 pub use code::*;
 
@@ -32,5 +35,14 @@ pub use trace::*;
 mod execution_frame;
 pub use execution_frame::*;
 
+mod finding;
+pub use finding::*;
+
+mod label;
+pub use label::*;
+
 mod sol_value;
 pub use sol_value::*;
+
+mod token;
+pub use token::*;
@@ -14,14 +14,18 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::Arc,
+};
 
 use alloy_primitives::{Address, Bytes, U256};
 use derive_more::From;
 use revm::state::TransientStorage;
 use serde::{Deserialize, Serialize};
 
-use crate::types::{EdbSolValue, ExecutionFrameId};
+use crate::types::{EdbSolValue, ExecutionFrameId, SolTypeHint};
 
 /// Complete snapshot information capturing EVM state at a specific execution point for debugging navigation
 #[derive(Debug, Clone, Serialize, Deserialize, From)]
@@ -36,10 +40,17 @@ pub struct SnapshotInfo {
     pub prev_id: usize,
     /// Detailed snapshot information varying by debugging mode (opcode vs source)
     pub detail: SnapshotInfoDetail,
-    /// Target contract address for this execution step
+    /// Account the frame operates on: its storage is read and written, and it is `this`
     pub target_address: Address,
-    /// Address where the actual bytecode is stored (may differ from target in proxy patterns)
+    /// Account whose code is executed (differs from the target in delegatecall frames)
     pub bytecode_address: Address,
+    /// Whether the frame runs the code of `bytecode_address` against the storage of
+    /// `target_address`, as entered by DELEGATECALL or CALLCODE
+    #[serde(default)]
+    pub delegated: bool,
+    /// Values of the expressions registered to be evaluated at every snapshot
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub watches: BTreeMap<String, Result<EdbSolValue, String>>,
 }
 
 /// Snapshot detail information varying by debugging mode for different levels of analysis
@@ -77,6 +88,17 @@ impl SnapshotInfo {
         &self.detail
     }
 
+    /// Get the account whose storage the snapshot's frame reads and writes
+    pub fn storage_address(&self) -> Address {
+        self.target_address
+    }
+
+    /// Whether the snapshot runs borrowed code in the storage context of another
+    /// account, e.g., an implementation behind a proxy
+    pub fn is_proxy_context(&self) -> bool {
+        self.delegated
+    }
+
     /// Get the source file path for source-level debugging, None for opcode debugging
     pub fn path(&self) -> Option<&PathBuf> {
         match self.detail() {
@@ -108,6 +130,15 @@ impl SnapshotInfo {
             SnapshotInfoDetail::Hook(info) => Some(&info.locals),
         }
     }
+
+    /// Get the rendering hint of a variable for source-level debugging, None for
+    /// opcode debugging or variables rendered from their ABI type alone
+    pub fn variable_hint(&self, name: &str) -> Option<&SolTypeHint> {
+        match self.detail() {
+            SnapshotInfoDetail::Opcode(_) => None,
+            SnapshotInfoDetail::Hook(info) => info.hints.get(name),
+        }
+    }
 }
 
 /// Source-level debugging snapshot with variable states and source location mapping
@@ -127,6 +158,31 @@ pub struct HookSnapshotInfoDetail {
     pub locals: HashMap<String, Option<Arc<EdbSolValue>>>,
     /// Contract state variables and their values at the current bytecode address
     pub state_variables: HashMap<String, Option<Arc<EdbSolValue>>>,
+    /// Virtual frame of the modifier being evaluated, if execution is inside a modifier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modifier: Option<ModifierFrame>,
+    /// Rendering hints of the variables whose source-level type is not captured
+    /// by the ABI type of their value (enums, user-defined value types, etc.)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub hints: HashMap<String, SolTypeHint>,
+}
+
+/// Virtual frame of a modifier evaluation within the frame of the modified function.
+///
+/// Modifiers do not have execution frames of their own, so source-level debugging
+/// shows them as frames nested in the frame of the function they modify.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModifierFrame {
+    /// Name of the modifier
+    pub modifier: String,
+    /// Name of the modified function, unknown if execution never reached its body
+    pub function: Option<String>,
+    /// Position of the modifier among the modifiers of the function, starting at 0
+    pub index: usize,
+    /// Identifier of the snapshot entering the modifier
+    pub entry_snapshot_id: usize,
+    /// Character offset and length of the modifier invocation in the function header
+    pub invocation: Option<(usize, usize)>,
 }
 
 /// Low-level opcode debugging snapshot with complete EVM state for instruction-level analysis
@@ -177,6 +177,14 @@ pub trait SolValueFormatter {
         indent_level: usize,
     ) -> String;
 
+    /// Formats a Solidity value, refining how it is rendered with source-level
+    /// type information (e.g., enum members instead of their ordinal).
+    fn format_value_with_hint(
+        &self,
+        hint: Option<&SolTypeHint>,
+        ctx: &SolValueFormatterContext,
+    ) -> String;
+
     /// Returns the Solidity type of this value as a string.
     ///
     /// # Returns
@@ -185,6 +193,45 @@ pub trait SolValueFormatter {
     fn format_type(&self) -> String;
 }
 
+/// Source-level type information that the ABI type of a value does not carry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SolTypeHint {
+    /// An enum, whose value is the index of its member
+    Enum {
+        /// Name of the enum
+        name: String,
+        /// Members of the enum, in declaration order
+        members: Vec<String>,
+    },
+    /// A user-defined value type wrapping its underlying type
+    UserDefinedValueType {
+        /// Name of the user-defined value type
+        name: String,
+    },
+    /// A fixed-point number, stored as an integer scaled by `10^decimals`
+    FixedPoint {
+        /// Number of fractional digits
+        decimals: u32,
+    },
+    /// A value of the same type as some constants, rendered by the name of
+    /// the constant it equals
+    Constant {
+        /// Name and value of each candidate constant
+        constants: Vec<(String, EdbSolValue)>,
+    },
+    /// An array whose elements need a hint
+    Array {
+        /// Hint of each element
+        element: Box<SolTypeHint>,
+    },
+    /// A struct some of whose fields need a hint
+    Struct {
+        /// Hint of each field, in declaration order
+        fields: Vec<Option<SolTypeHint>>,
+    },
+}
+
 /// Configuration context for formatting Solidity values with various display options and address resolution
 #[derive(Default)]
 pub struct SolValueFormatterContext {
@@ -229,91 +276,15 @@ impl SolValueFormatter for DynSolValue {
         ctx: &SolValueFormatterContext,
         indent_level: usize,
     ) -> String {
-        let value_str = match self {
-            Self::Bool(b) => b.to_string(),
-
-            Self::Int(n, bits) => {
-                if ctx.with_ty {
-                    format!("int{bits}({n})")
-                } else {
-                    n.to_string()
-                }
-            }
-
-            Self::Uint(n, bits) => {
-                if ctx.with_ty {
-                    format!("uint{bits}({n})")
-                } else {
-                    n.to_string()
-                }
-            }
-
-            Self::Address(addr) => {
-                if let Some(label) = ctx.resolve_address.as_ref().and_then(|f| f(*addr)) {
-                    label
-                } else {
-                    let addr_str = if !ctx.shorten_long {
-                        addr.to_checksum(None)
-                    } else if *addr == Address::ZERO {
-                        "0x0000000000000000".to_string()
-                    } else {
-                        let addr_str = addr.to_checksum(None);
-                        // Show more characters for better identification: 8 chars + ... + 6 chars
-                        format!("{}...{}", &addr_str[..8], &addr_str[addr_str.len() - 6..])
-                    };
-
-                    if ctx.with_ty {
-                        format!("address({addr_str})")
-                    } else {
-                        addr_str
-                    }
-                }
-            }
-
-            Self::Function(func) => {
-                format!("0x{}", hex::encode(func.as_slice()))
-            }
-
-            Self::FixedBytes(bytes, size) => {
-                if ctx.with_ty {
-                    format!("bytes{}(0x{})", size, hex::encode(bytes))
-                } else {
-                    format!("0x{}", hex::encode(bytes))
-                }
-            }
-
-            Self::Bytes(bytes) => {
-                if bytes.len() <= 32 || !ctx.shorten_long {
-                    format!("0x{}", hex::encode(bytes))
-                } else {
-                    format!("0x{}...[{} bytes]", hex::encode(&bytes[..16]), bytes.len())
-                }
-            }
-
-            Self::String(s) => {
-                if s.len() <= 64 || !ctx.shorten_long {
-                    format!("\"{}\"", s.replace('\"', "\\\""))
-                } else {
-                    format!("\"{}...\"[{} chars]", &s[..32].replace('\"', "\\\""), s.len())
-                }
-            }
-
-            Self::Array(arr) => format_array(arr, false, ctx, indent_level),
-
-            Self::FixedArray(arr) => format_array(arr, true, ctx, indent_level),
-
-            Self::Tuple(tuple) => format_tuple(tuple, ctx, indent_level),
-
-            Self::CustomStruct { name, prop_names, tuple } => {
-                if prop_names.is_empty() {
-                    format!("{}{}", name, format_tuple(tuple, ctx, indent_level))
-                } else {
-                    format_custom_struct(name, prop_names, tuple, ctx, indent_level)
-                }
-            }
-        };
+        format_hinted(self, None, ctx, indent_level)
+    }
 
-        value_str
+    fn format_value_with_hint(
+        &self,
+        hint: Option<&SolTypeHint>,
+        ctx: &SolValueFormatterContext,
+    ) -> String {
+        format_hinted(self, hint, ctx, 0)
     }
 
     fn format_type(&self) -> String {
@@ -349,6 +320,164 @@ impl SolValueFormatter for DynSolValue {
     }
 }
 
+/// Format a value, applying the hint to it and the nested hints to its elements
+fn format_hinted(
+    value: &DynSolValue,
+    hint: Option<&SolTypeHint>,
+    ctx: &SolValueFormatterContext,
+    indent_level: usize,
+) -> String {
+    match (hint, value) {
+        (Some(SolTypeHint::Enum { name, members }), DynSolValue::Uint(n, _)) => {
+            return match usize::try_from(*n).ok().and_then(|i| members.get(i)) {
+                Some(member) => format!("{name}.{member}"),
+                None => format!("{name}({n})"),
+            };
+        }
+        (Some(SolTypeHint::UserDefinedValueType { name }), _) => {
+            return format!("{name}.wrap({})", format_hinted(value, None, ctx, indent_level));
+        }
+        (Some(SolTypeHint::FixedPoint { decimals }), DynSolValue::Uint(n, bits)) => {
+            let number = format_fixed_point(n.to_string(), *decimals);
+            return if ctx.with_ty { format!("ufixed{bits}x{decimals}({number})") } else { number };
+        }
+        (Some(SolTypeHint::FixedPoint { decimals }), DynSolValue::Int(n, bits)) => {
+            let magnitude = format_fixed_point(n.unsigned_abs().to_string(), *decimals);
+            let number = if n.is_negative() { format!("-{magnitude}") } else { magnitude };
+            return if ctx.with_ty { format!("fixed{bits}x{decimals}({number})") } else { number };
+        }
+        (Some(SolTypeHint::Constant { constants }), _) => {
+            if let Some((name, _)) = constants.iter().find(|(_, constant)| **constant == *value) {
+                return if ctx.with_ty {
+                    format!("{name} ({})", format_hinted(value, None, ctx, indent_level))
+                } else {
+                    name.clone()
+                };
+            }
+        }
+        _ => {}
+    }
+
+    match value {
+        DynSolValue::Bool(b) => b.to_string(),
+
+        DynSolValue::Int(n, bits) => {
+            if ctx.with_ty {
+                format!("int{bits}({n})")
+            } else {
+                n.to_string()
+            }
+        }
+
+        DynSolValue::Uint(n, bits) => {
+            if ctx.with_ty {
+                format!("uint{bits}({n})")
+            } else {
+                n.to_string()
+            }
+        }
+
+        DynSolValue::Address(addr) => {
+            if let Some(label) = ctx.resolve_address.as_ref().and_then(|f| f(*addr)) {
+                label
+            } else {
+                let addr_str = if !ctx.shorten_long {
+                    addr.to_checksum(None)
+                } else if *addr == Address::ZERO {
+                    "0x0000000000000000".to_string()
+                } else {
+                    let addr_str = addr.to_checksum(None);
+                    // Show more characters for better identification: 8 chars + ... + 6 chars
+                    format!("{}...{}", &addr_str[..8], &addr_str[addr_str.len() - 6..])
+                };
+
+                if ctx.with_ty {
+                    format!("address({addr_str})")
+                } else {
+                    addr_str
+                }
+            }
+        }
+
+        DynSolValue::Function(func) => {
+            format!("0x{}", hex::encode(func.as_slice()))
+        }
+
+        DynSolValue::FixedBytes(bytes, size) => {
+            if ctx.with_ty {
+                format!("bytes{}(0x{})", size, hex::encode(bytes))
+            } else {
+                format!("0x{}", hex::encode(bytes))
+            }
+        }
+
+        DynSolValue::Bytes(bytes) => {
+            if bytes.len() <= 32 || !ctx.shorten_long {
+                format!("0x{}", hex::encode(bytes))
+            } else {
+                format!("0x{}...[{} bytes]", hex::encode(&bytes[..16]), bytes.len())
+            }
+        }
+
+        DynSolValue::String(s) => {
+            if s.len() <= 64 || !ctx.shorten_long {
+                format!("\"{}\"", s.replace('\"', "\\\""))
+            } else {
+                format!("\"{}...\"[{} chars]", &s[..32].replace('\"', "\\\""), s.len())
+            }
+        }
+
+        DynSolValue::Array(arr) => format_array(arr, false, element_hint(hint), ctx, indent_level),
+
+        DynSolValue::FixedArray(arr) => {
+            format_array(arr, true, element_hint(hint), ctx, indent_level)
+        }
+
+        DynSolValue::Tuple(tuple) => format_tuple(tuple, field_hints(hint), ctx, indent_level),
+
+        DynSolValue::CustomStruct { name, prop_names, tuple } => {
+            if prop_names.is_empty() {
+                format!("{}{}", name, format_tuple(tuple, field_hints(hint), ctx, indent_level))
+            } else {
+                format_custom_struct(name, prop_names, tuple, field_hints(hint), ctx, indent_level)
+            }
+        }
+    }
+}
+
+/// Hint of the elements of an array
+fn element_hint(hint: Option<&SolTypeHint>) -> Option<&SolTypeHint> {
+    match hint {
+        Some(SolTypeHint::Array { element }) => Some(element),
+        _ => None,
+    }
+}
+
+/// Hints of the fields of a struct
+fn field_hints(hint: Option<&SolTypeHint>) -> &[Option<SolTypeHint>] {
+    match hint {
+        Some(SolTypeHint::Struct { fields }) => fields,
+        _ => &[],
+    }
+}
+
+/// Format the decimal digits of a scaled integer as a fixed-point number
+fn format_fixed_point(digits: String, decimals: u32) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{digits:0>width$}", width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
+
 /// Helper function to create indentation string
 fn make_indent(indent_level: usize) -> String {
     "  ".repeat(indent_level)
@@ -357,6 +486,7 @@ fn make_indent(indent_level: usize) -> String {
 fn format_array(
     arr: &[DynSolValue],
     is_fixed: bool,
+    hint: Option<&SolTypeHint>,
     ctx: &SolValueFormatterContext,
     indent_level: usize,
 ) -> String {
@@ -375,7 +505,7 @@ fn format_array(
                 .iter()
                 .take(3)
                 .map(|v| {
-                    format!("{}{}", child_indent, v.format_value_with_indent(ctx, indent_level + 1))
+                    format!("{}{}", child_indent, format_hinted(v, hint, ctx, indent_level + 1))
                 })
                 .collect::<Vec<_>>();
             let suffix = if is_fixed {
@@ -388,18 +518,18 @@ fn format_array(
         } else {
             arr.iter()
                 .map(|v| {
-                    format!("{}{}", child_indent, v.format_value_with_indent(ctx, indent_level + 1))
+                    format!("{}{}", child_indent, format_hinted(v, hint, ctx, indent_level + 1))
                 })
                 .collect()
         };
         format!("[\n{}\n{}]", items.join(",\n"), current_indent)
     } else if arr.len() <= MAX_DISPLAY_ITEMS || !ctx.shorten_long {
         let items: Vec<String> =
-            arr.iter().map(|v| v.format_value_with_indent(ctx, indent_level)).collect();
+            arr.iter().map(|v| format_hinted(v, hint, ctx, indent_level)).collect();
         format!("[{}]", items.join(", "))
     } else {
         let first_items: Vec<String> =
-            arr.iter().take(3).map(|v| v.format_value_with_indent(ctx, indent_level)).collect();
+            arr.iter().take(3).map(|v| format_hinted(v, hint, ctx, indent_level)).collect();
 
         let suffix = if is_fixed {
             format!(", ...[{} total]", arr.len())
@@ -413,6 +543,7 @@ fn format_array(
 
 fn format_tuple(
     tuple: &[DynSolValue],
+    hints: &[Option<SolTypeHint>],
     ctx: &SolValueFormatterContext,
     indent_level: usize,
 ) -> String {
@@ -421,7 +552,7 @@ fn format_tuple(
     }
 
     if tuple.len() == 1 {
-        return format!("({})", tuple[0].format_value_with_indent(ctx, indent_level));
+        return format!("({})", format_hinted(&tuple[0], field_hint(hints, 0), ctx, indent_level));
     }
 
     const MAX_DISPLAY_FIELDS: usize = 4;
@@ -433,9 +564,14 @@ fn format_tuple(
         let items: Vec<String> = if ctx.shorten_long && tuple.len() > MAX_DISPLAY_FIELDS {
             let mut items = tuple
                 .iter()
+                .enumerate()
                 .take(3)
-                .map(|v| {
-                    format!("{}{}", child_indent, v.format_value_with_indent(ctx, indent_level + 1))
+                .map(|(i, v)| {
+                    format!(
+                        "{}{}",
+                        child_indent,
+                        format_hinted(v, field_hint(hints, i), ctx, indent_level + 1)
+                    )
                 })
                 .collect::<Vec<_>>();
             items.push(format!("{}...[{} fields]", child_indent, tuple.len()));
@@ -443,19 +579,31 @@ fn format_tuple(
         } else {
             tuple
                 .iter()
-                .map(|v| {
-                    format!("{}{}", child_indent, v.format_value_with_indent(ctx, indent_level + 1))
+                .enumerate()
+                .map(|(i, v)| {
+                    format!(
+                        "{}{}",
+                        child_indent,
+                        format_hinted(v, field_hint(hints, i), ctx, indent_level + 1)
+                    )
                 })
                 .collect()
         };
         format!("(\n{}\n{})", items.join(",\n"), current_indent)
     } else if tuple.len() <= MAX_DISPLAY_FIELDS || !ctx.shorten_long {
-        let items: Vec<String> =
-            tuple.iter().map(|v| v.format_value_with_indent(ctx, indent_level)).collect();
+        let items: Vec<String> = tuple
+            .iter()
+            .enumerate()
+            .map(|(i, v)| format_hinted(v, field_hint(hints, i), ctx, indent_level))
+            .collect();
         format!("({})", items.join(", "))
     } else {
-        let first_items: Vec<String> =
-            tuple.iter().take(3).map(|v| v.format_value_with_indent(ctx, indent_level)).collect();
+        let first_items: Vec<String> = tuple
+            .iter()
+            .enumerate()
+            .take(3)
+            .map(|(i, v)| format_hinted(v, field_hint(hints, i), ctx, indent_level))
+            .collect();
         format!("({}, ...[{} fields])", first_items.join(", "), tuple.len())
     }
 }
@@ -464,6 +612,7 @@ fn format_custom_struct(
     name: &str,
     prop_names: &[String],
     tuple: &[DynSolValue],
+    hints: &[Option<SolTypeHint>],
     ctx: &SolValueFormatterContext,
     indent_level: usize,
 ) -> String {
@@ -476,14 +625,15 @@ fn format_custom_struct(
         let fields: Vec<String> = if ctx.shorten_long && tuple.len() > MAX_DISPLAY_FIELDS {
             let mut fields = tuple
                 .iter()
+                .enumerate()
                 .zip(prop_names.iter())
                 .take(3)
-                .map(|(value, field_name)| {
+                .map(|((i, value), field_name)| {
                     format!(
                         "{}{}: {}",
                         child_indent,
                         field_name,
-                        value.format_value_with_indent(ctx, indent_level + 1)
+                        format_hinted(value, field_hint(hints, i), ctx, indent_level + 1)
                     )
                 })
                 .collect::<Vec<_>>();
@@ -492,13 +642,14 @@ fn format_custom_struct(
         } else {
             tuple
                 .iter()
+                .enumerate()
                 .zip(prop_names.iter())
-                .map(|(value, field_name)| {
+                .map(|((i, value), field_name)| {
                     format!(
                         "{}{}: {}",
                         child_indent,
                         field_name,
-                        value.format_value_with_indent(ctx, indent_level + 1)
+                        format_hinted(value, field_hint(hints, i), ctx, indent_level + 1)
                     )
                 })
                 .collect()
@@ -513,10 +664,15 @@ fn format_custom_struct(
         let fields: Vec<String> = if ctx.shorten_long && tuple.len() > MAX_DISPLAY_FIELDS {
             let mut fields = tuple
                 .iter()
+                .enumerate()
                 .zip(prop_names.iter())
                 .take(3)
-                .map(|(value, field_name)| {
-                    format!("{}: {}", field_name, value.format_value_with_indent(ctx, indent_level))
+                .map(|((i, value), field_name)| {
+                    format!(
+                        "{}: {}",
+                        field_name,
+                        format_hinted(value, field_hint(hints, i), ctx, indent_level)
+                    )
                 })
                 .collect::<Vec<_>>();
             fields.push(format!("...[{} fields]", tuple.len()));
@@ -524,9 +680,14 @@ fn format_custom_struct(
         } else {
             tuple
                 .iter()
+                .enumerate()
                 .zip(prop_names.iter())
-                .map(|(value, field_name)| {
-                    format!("{}: {}", field_name, value.format_value_with_indent(ctx, indent_level))
+                .map(|((i, value), field_name)| {
+                    format!(
+                        "{}: {}",
+                        field_name,
+                        format_hinted(value, field_hint(hints, i), ctx, indent_level)
+                    )
                 })
                 .collect()
         };
@@ -539,6 +700,11 @@ fn format_custom_struct(
     }
 }
 
+/// Hint of the field at `index`
+fn field_hint(hints: &[Option<SolTypeHint>], index: usize) -> Option<&SolTypeHint> {
+    hints.get(index).and_then(Option::as_ref)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1049,4 +1215,74 @@ mod tests {
         let expected = "Transaction{\n  from: address(0x742d35...34000A),\n  to: address(0x123F68...A0c2AC),\n  values: [\n    uint256(1),\n    uint256(2),\n    uint256(3),\n    ...[10 items]\n  ]\n}";
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_format_with_hint() {
+        let ctx = SolValueFormatterContext::new();
+        let status = SolTypeHint::Enum {
+            name: "Status".to_string(),
+            members: vec!["Pending".to_string(), "Active".to_string()],
+        };
+        let value = DynSolValue::Uint(U256::from(1), 8);
+        assert_eq!(value.format_value_with_hint(Some(&status), &ctx), "Status.Active");
+        let value = DynSolValue::Uint(U256::from(5), 8);
+        assert_eq!(value.format_value_with_hint(Some(&status), &ctx), "Status(5)");
+
+        let price = SolTypeHint::UserDefinedValueType { name: "Price".to_string() };
+        let value = DynSolValue::Uint(U256::from(100), 256);
+        assert_eq!(value.format_value_with_hint(Some(&price), &ctx), "Price.wrap(100)");
+
+        let fixed = SolTypeHint::FixedPoint { decimals: 18 };
+        let value = DynSolValue::Int(I256::try_from(-1_500_000_000_000_000_000i128).unwrap(), 128);
+        assert_eq!(value.format_value_with_hint(Some(&fixed), &ctx), "-1.5");
+        let value = DynSolValue::Uint(U256::from(25), 128);
+        assert_eq!(
+            value.format_value_with_hint(
+                Some(&fixed),
+                &SolValueFormatterContext::new().with_ty(true)
+            ),
+            "ufixed128x18(0.000000000000000025)"
+        );
+
+        let constant = SolTypeHint::Constant {
+            constants: vec![(
+                "MAX_FEE".to_string(),
+                DynSolValue::Uint(U256::from(100), 256).into(),
+            )],
+        };
+        let value = DynSolValue::Uint(U256::from(100), 256);
+        assert_eq!(value.format_value_with_hint(Some(&constant), &ctx), "MAX_FEE");
+        let value = DynSolValue::Uint(U256::from(99), 256);
+        assert_eq!(value.format_value_with_hint(Some(&constant), &ctx), "99");
+    }
+
+    #[test]
+    fn test_format_with_nested_hint() {
+        let ctx = SolValueFormatterContext::new();
+        let status = SolTypeHint::Enum {
+            name: "Status".to_string(),
+            members: vec!["Pending".to_string(), "Active".to_string()],
+        };
+
+        let hint = SolTypeHint::Array { element: Box::new(status.clone()) };
+        let value = DynSolValue::Array(vec![
+            DynSolValue::Uint(U256::from(0), 8),
+            DynSolValue::Uint(U256::from(1), 8),
+        ]);
+        assert_eq!(
+            value.format_value_with_hint(Some(&hint), &ctx),
+            "[Status.Pending, Status.Active]"
+        );
+
+        let hint = SolTypeHint::Struct { fields: vec![None, Some(status)] };
+        let value = DynSolValue::CustomStruct {
+            name: "Order".to_string(),
+            prop_names: vec!["amount".to_string(), "status".to_string()],
+            tuple: vec![DynSolValue::Uint(U256::from(7), 256), DynSolValue::Uint(U256::from(1), 8)],
+        };
+        assert_eq!(
+            value.format_value_with_hint(Some(&hint), &ctx),
+            "{ amount: 7, status: Status.Active }"
+        );
+    }
 }
@@ -0,0 +1,112 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use alloy_primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of fractional digits shown for amounts of at least one token
+const MAX_FRACTION_DIGITS: usize = 6;
+
+/// Metadata of an ERC-20 token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    /// Address of the token contract
+    pub address: Address,
+    /// Token symbol
+    pub symbol: String,
+    /// Number of decimals of the token amounts
+    pub decimals: u8,
+}
+
+impl TokenInfo {
+    /// Format a raw token amount with the token's decimals and symbol, e.g. `1,250.5 USDC`
+    ///
+    /// Amounts of at least one token are truncated to six fractional digits, while smaller
+    /// amounts keep all of them so that dust is not shown as zero.
+    pub fn format_amount(&self, amount: U256) -> String {
+        if amount == U256::MAX {
+            return format!("max {}", self.symbol);
+        }
+
+        let digits = amount.to_string();
+        let decimals = self.decimals as usize;
+        let (whole, fraction) = if digits.len() > decimals {
+            let (whole, fraction) = digits.split_at(digits.len() - decimals);
+            (whole.to_string(), fraction.to_string())
+        } else {
+            ("0".to_string(), format!("{digits:0>decimals$}"))
+        };
+
+        let fraction = if whole == "0" {
+            &fraction[..]
+        } else {
+            &fraction[..decimals.min(MAX_FRACTION_DIGITS)]
+        };
+        let fraction = fraction.trim_end_matches('0');
+
+        let mut grouped = String::with_capacity(whole.len() + whole.len() / 3);
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+
+        if fraction.is_empty() {
+            format!("{grouped} {}", self.symbol)
+        } else {
+            format!("{grouped}.{fraction} {}", self.symbol)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(symbol: &str, decimals: u8) -> TokenInfo {
+        TokenInfo { address: Address::ZERO, symbol: symbol.to_string(), decimals }
+    }
+
+    #[test]
+    fn test_format_amount() {
+        let usdc = token("USDC", 6);
+        assert_eq!(usdc.format_amount(U256::from(1_250_500_000u64)), "1,250.5 USDC");
+        assert_eq!(usdc.format_amount(U256::from(1u64)), "0.000001 USDC");
+        assert_eq!(usdc.format_amount(U256::ZERO), "0 USDC");
+        assert_eq!(usdc.format_amount(U256::MAX), "max USDC");
+
+        let weth = token("WETH", 18);
+        assert_eq!(weth.format_amount(U256::from(10u64).pow(U256::from(24))), "1,000,000 WETH");
+        assert_eq!(weth.format_amount(U256::from(1_234_567_890_123_456_789u64)), "1.234567 WETH");
+        assert_eq!(weth.format_amount(U256::from(42u64)), "0.000000000000000042 WETH");
+
+        let raw = token("RAW", 0);
+        assert_eq!(raw.format_amount(U256::from(123_456u64)), "123,456 RAW");
+    }
+
+    #[test]
+    fn test_token_info_serialization() {
+        let info = token("DAI", 18);
+
+        let json = serde_json::to_string(&info).expect("Failed to serialize TokenInfo");
+        let deserialized: TokenInfo =
+            serde_json::from_str(&json).expect("Failed to deserialize TokenInfo");
+
+        assert_eq!(deserialized, info);
+    }
+}
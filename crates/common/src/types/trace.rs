@@ -264,6 +264,78 @@ impl<'a> IntoIterator for &'a mut Trace {
     }
 }
 
+/// Query selecting a page of trace entries.
+///
+/// Entries are returned in chronological order, starting at the entry whose id is
+/// `cursor`. Large traces can be loaded lazily, e.g., top-level frames first (`max_depth`)
+/// and the children of a frame once it is expanded (`parent_id`).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TraceQuery {
+    /// Id of the first entry to consider
+    pub cursor: usize,
+    /// Maximum number of entries to return (all matching entries if unset)
+    pub limit: Option<usize>,
+    /// Only return entries at most this deep in the call stack
+    pub max_depth: Option<usize>,
+    /// Only return the direct children of this entry
+    pub parent_id: Option<usize>,
+    /// Return summarized entries, see [`TraceEntry::summarized`]
+    pub summary: bool,
+}
+
+/// Page of trace entries returned for a [`TraceQuery`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracePage {
+    /// Matching entries in chronological order
+    pub entries: Vec<TraceEntry>,
+    /// Total number of entries in the trace
+    pub total: usize,
+    /// Cursor of the next page, if more entries match the query
+    pub next_cursor: Option<usize>,
+}
+
+/// Event emitted during the transaction, in emission order
+///
+/// Each log is attributed to the trace entry whose code emitted it and, when known, to the
+/// snapshot taken right before (opcode snapshots) or at the statement (hook snapshots) that
+/// emitted it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedLog {
+    /// Id of the trace entry that emitted the log
+    pub trace_entry_id: usize,
+    /// Index of the log among the events of its trace entry
+    pub index: usize,
+    /// Address of the contract that emitted the log
+    pub address: Address,
+    /// Topics and data of the log
+    pub log: LogData,
+    /// Id of the emitting snapshot, if it could be determined
+    pub snapshot_id: Option<usize>,
+}
+
+impl Trace {
+    /// Get the page of entries selected by a query
+    pub fn page(&self, query: &TraceQuery) -> TracePage {
+        let mut matching = self.inner.iter().skip(query.cursor).filter(|entry| {
+            query.max_depth.is_none_or(|max_depth| entry.depth <= max_depth)
+                && query.parent_id.is_none_or(|parent_id| entry.parent_id == Some(parent_id))
+        });
+
+        let limit = query.limit.unwrap_or(usize::MAX);
+        let entries: Vec<TraceEntry> = matching
+            .by_ref()
+            .take(limit)
+            .map(|entry| if query.summary { entry.summarized() } else { entry.clone() })
+            .collect();
+        let next_cursor = matching.next().map(|entry| entry.id);
+
+        TracePage { entries, total: self.inner.len(), next_cursor }
+    }
+}
+
 /// Single trace entry representing a call or creation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TraceEntry {
@@ -285,6 +357,10 @@ pub struct TraceEntry {
     pub input: Bytes,
     /// Value transferred
     pub value: U256,
+    /// Gas made available to this frame
+    pub gas_limit: u64,
+    /// Gas spent by this frame, including its children (populated on call_end)
+    pub gas_used: u64,
     /// Result of the call (populated on call_end)
     pub result: Option<CallResult>,
     /// Whether this created a new contract
@@ -297,12 +373,57 @@ pub struct TraceEntry {
     pub target_label: Option<String>,
     /// Self-destruct information
     pub self_destruct: Option<(Address, U256)>,
+    /// Whether the self-destruct destroys the account, removing its code and storage.
+    ///
+    /// Since EIP-6780 (Cancun), only contracts created in the same transaction are
+    /// destroyed; others merely have their balance swept to the beneficiary (SENDALL).
+    #[serde(default)]
+    pub destroyed: bool,
+    /// Whether the frame runs in a static context, entered by STATICCALL or nested in one,
+    /// where any state change reverts
+    #[serde(default)]
+    pub readonly: bool,
+    /// Wei moved by this entry and its descendants that took effect, including
+    /// self-destruct sweeps (populated by [`Trace::annotate_value_flows`])
+    #[serde(default)]
+    pub subtree_value: U256,
     /// Events
     pub events: Vec<LogData>,
     /// The first snapshot id that belongs to this entry
     pub first_snapshot_id: Option<usize>,
 }
 
+impl TraceEntry {
+    /// Get a lightweight copy of this entry for overviews of large traces
+    ///
+    /// The input is truncated to the function selector, and the running bytecode and
+    /// emitted events are dropped.
+    pub fn summarized(&self) -> Self {
+        Self {
+            input: Bytes::copy_from_slice(&self.input[..self.input.len().min(4)]),
+            bytecode: None,
+            events: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    /// Get the wei moved from the caller to the target by this entry.
+    ///
+    /// DELEGATECALL and STATICCALL transfer nothing, and CALLCODE only transfers to the
+    /// caller itself, so their value is not counted.
+    pub fn transferred_value(&self) -> U256 {
+        match self.call_type {
+            CallType::Call(CallScheme::Call) | CallType::Create(_) => self.value,
+            CallType::Call(_) => U256::ZERO,
+        }
+    }
+
+    /// Whether this entry or one of its descendants moved ether
+    pub fn moves_value(&self) -> bool {
+        !self.subtree_value.is_zero()
+    }
+}
+
 // Pretty print for Trace
 impl Trace {
     /// Print the trace tree structure showing parent-child relationships with fancy formatting
@@ -436,7 +557,8 @@ impl Trace {
         // Add self-destruct indicator if present
         if let Some((beneficiary, value)) = &entry.self_destruct {
             print!(
-                " \x1b[91m SELFDESTRUCT → {} ({} ETH)\x1b[0m",
+                " \x1b[91m {} → {} ({} ETH)\x1b[0m",
+                if entry.destroyed { "SELFDESTRUCT" } else { "SENDALL" },
                 format_address_short(*beneficiary),
                 format_ether(*value)
             );
@@ -540,6 +662,60 @@ impl Trace {
     pub fn get_children(&self, trace_id: usize) -> Vec<&TraceEntry> {
         self.inner.iter().filter(|entry| entry.parent_id == Some(trace_id)).collect()
     }
+
+    /// Compute the [`TraceEntry::subtree_value`] of every entry.
+    ///
+    /// Transfers of a reverted frame are undone together with those of its subtree, so
+    /// entries that reverted or whose ancestor reverted move nothing.
+    pub fn annotate_value_flows(&mut self) {
+        // Children always come after their parent, so a forward pass finds the frames
+        // undone by their own revert or by the revert of an ancestor
+        let mut undone = vec![false; self.inner.len()];
+        for (id, entry) in self.inner.iter().enumerate() {
+            let reverted = !matches!(entry.result, Some(CallResult::Success { .. }));
+            let parent_undone = entry.parent_id.and_then(|parent_id| undone.get(parent_id));
+            undone[id] = reverted || parent_undone.copied().unwrap_or(false);
+        }
+
+        // ... and a reverse pass has already added the flows of the children to the parent
+        // when it is reached
+        for entry in &mut self.inner {
+            entry.subtree_value = U256::ZERO;
+        }
+        for id in (0..self.inner.len()).rev() {
+            let entry = &self.inner[id];
+            let value = if undone[id] {
+                U256::ZERO
+            } else {
+                let swept = entry.self_destruct.map(|(_, value)| value).unwrap_or_default();
+                entry.subtree_value.saturating_add(entry.transferred_value()).saturating_add(swept)
+            };
+            let parent_id = entry.parent_id;
+            self.inner[id].subtree_value = value;
+            if let Some(parent) = parent_id.and_then(|parent_id| self.inner.get_mut(parent_id)) {
+                parent.subtree_value = parent.subtree_value.saturating_add(value);
+            }
+        }
+    }
+
+    /// Get the path of failed frames from the top-level call to the origin of its revert
+    ///
+    /// Each step follows the last failed child, whose failure is the one that bubbled up,
+    /// until a frame whose children all succeeded. The path is empty when the top-level
+    /// call succeeded.
+    pub fn revert_path(&self) -> Vec<usize> {
+        let failed = |entry: &&TraceEntry| {
+            matches!(entry.result, Some(CallResult::Revert { .. } | CallResult::Error { .. }))
+        };
+
+        let mut path = Vec::new();
+        let mut current = self.inner.first().filter(failed);
+        while let Some(entry) = current {
+            path.push(entry.id);
+            current = self.get_children(entry.id).into_iter().rev().find(failed);
+        }
+        path
+    }
 }
 
 // Helper functions for formatting
@@ -682,6 +858,8 @@ mod tests {
             code_address: address!("0x1234567890123456789012345678901234567890"),
             input: Bytes::from(hex::decode("deadbeef").unwrap()),
             value: U256::from(1000),
+            gas_limit: 0,
+            gas_used: 0,
             result: Some(CallResult::Success {
                 output: Bytes::from_static(b"return_data"),
                 result: InstructionResult::Return,
@@ -691,6 +869,9 @@ mod tests {
             bytecode: Some(Bytes::from(hex::decode("60806040").unwrap())),
             target_label: Some("TestContract".to_string()),
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: Some(42),
         };
@@ -735,6 +916,8 @@ mod tests {
             code_address: address!("0x1234567890123456789012345678901234567890"),
             input,
             value: U256::from(1000),
+            gas_limit: 0,
+            gas_used: 0,
             result: Some(CallResult::Success {
                 output: Bytes::from_static(b"return_data"),
                 result: InstructionResult::Return,
@@ -744,6 +927,9 @@ mod tests {
             bytecode: Some(bytecode),
             target_label: None,
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: None,
         });
@@ -822,12 +1008,17 @@ mod tests {
             code_address: Address::ZERO,
             input: Bytes::new(),
             value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
             result: None,
             created_contract: false,
             create_scheme: None,
             bytecode: None,
             target_label: None,
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: None,
         };
@@ -857,12 +1048,17 @@ mod tests {
             code_address: Address::ZERO,
             input: Bytes::new(),
             value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
             result: None,
             created_contract: false,
             create_scheme: None,
             bytecode: None,
             target_label: None,
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: None,
         };
@@ -876,12 +1072,17 @@ mod tests {
             code_address: Address::ZERO,
             input: Bytes::new(),
             value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
             result: None,
             created_contract: true,
             create_scheme: Some(CreateScheme::Create),
             bytecode: None,
             target_label: None,
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: None,
         };
@@ -924,12 +1125,17 @@ mod tests {
             code_address: Address::ZERO,
             input: Bytes::new(),
             value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
             result: None,
             created_contract: false,
             create_scheme: None,
             bytecode: None,
             target_label: None,
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: None,
         });
@@ -946,12 +1152,17 @@ mod tests {
                 code_address: Address::ZERO,
                 input: Bytes::new(),
                 value: U256::ZERO,
+                gas_limit: 0,
+                gas_used: 0,
                 result: None,
                 created_contract: false,
                 create_scheme: None,
                 bytecode: None,
                 target_label: None,
                 self_destruct: None,
+                destroyed: false,
+                readonly: false,
+                subtree_value: U256::ZERO,
                 events: vec![],
                 first_snapshot_id: None,
             });
@@ -975,6 +1186,106 @@ mod tests {
         assert!(trace.get_children(3).is_empty());
     }
 
+    #[test]
+    fn test_revert_path() {
+        let entry =
+            |id: usize, parent_id: Option<usize>, depth: usize, reverted: bool| TraceEntry {
+                id,
+                parent_id,
+                depth,
+                call_type: CallType::Call(CallScheme::Call),
+                caller: Address::ZERO,
+                target: Address::ZERO,
+                code_address: Address::ZERO,
+                input: Bytes::new(),
+                value: U256::ZERO,
+                gas_limit: 0,
+                gas_used: 0,
+                result: Some(if reverted {
+                    CallResult::Revert { output: Bytes::new(), result: InstructionResult::Revert }
+                } else {
+                    CallResult::Success { output: Bytes::new(), result: InstructionResult::Stop }
+                }),
+                created_contract: false,
+                create_scheme: None,
+                bytecode: None,
+                target_label: None,
+                self_destruct: None,
+                destroyed: false,
+                readonly: false,
+                subtree_value: U256::ZERO,
+                events: vec![],
+                first_snapshot_id: None,
+            };
+
+        // 0 (reverted) -> 1 (caught revert), 2 (success), 3 (reverted) -> 4 (reverted)
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, 0, true));
+        trace.push(entry(1, Some(0), 1, true));
+        trace.push(entry(2, Some(0), 1, false));
+        trace.push(entry(3, Some(0), 1, true));
+        trace.push(entry(4, Some(3), 2, true));
+        assert_eq!(trace.revert_path(), vec![0, 3, 4]);
+
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, 0, false));
+        trace.push(entry(1, Some(0), 1, true));
+        assert!(trace.revert_path().is_empty());
+    }
+
+    #[test]
+    fn test_annotate_value_flows() {
+        let entry =
+            |id: usize, parent_id: Option<usize>, scheme: CallScheme, value: u64| TraceEntry {
+                id,
+                parent_id,
+                depth: parent_id.map_or(0, |_| 1),
+                call_type: CallType::Call(scheme),
+                caller: Address::ZERO,
+                target: Address::ZERO,
+                code_address: Address::ZERO,
+                input: Bytes::new(),
+                value: U256::from(value),
+                gas_limit: 0,
+                gas_used: 0,
+                result: Some(CallResult::Success {
+                    output: Bytes::new(),
+                    result: InstructionResult::Stop,
+                }),
+                created_contract: false,
+                create_scheme: None,
+                bytecode: None,
+                target_label: None,
+                self_destruct: None,
+                destroyed: false,
+                readonly: scheme == CallScheme::StaticCall,
+                subtree_value: U256::ZERO,
+                events: vec![],
+                first_snapshot_id: None,
+            };
+
+        // 0 (1 wei) -> 1 (2 wei), 2 (delegate, 4 wei apparent), 3 (reverted, 8 wei) -> 4 (16 wei)
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, CallScheme::Call, 1));
+        trace.push(entry(1, Some(0), CallScheme::Call, 2));
+        trace.push(entry(2, Some(0), CallScheme::DelegateCall, 4));
+        let mut reverted = entry(3, Some(0), CallScheme::Call, 8);
+        reverted.result =
+            Some(CallResult::Revert { output: Bytes::new(), result: InstructionResult::Revert });
+        trace.push(reverted);
+        trace.push(entry(4, Some(3), CallScheme::Call, 16));
+        trace.annotate_value_flows();
+
+        let flows: Vec<_> = trace.iter().map(|entry| entry.subtree_value).collect();
+        assert_eq!(
+            flows,
+            [3u64, 2, 0, 0, 0].map(U256::from).to_vec(),
+            "delegate calls and reverted subtrees move nothing"
+        );
+        assert!(trace[0].moves_value());
+        assert!(!trace[2].moves_value());
+    }
+
     #[test]
     fn test_large_trace_serialization() {
         let mut trace = Trace::new();
@@ -991,6 +1302,8 @@ mod tests {
                 code_address: Address::ZERO,
                 input: Bytes::new(),
                 value: U256::from(i),
+                gas_limit: 0,
+                gas_used: 0,
                 result: Some(CallResult::Success {
                     output: Bytes::from_static(b"success"),
                     result: InstructionResult::Return,
@@ -1000,6 +1313,9 @@ mod tests {
                 bytecode: None,
                 target_label: Some(format!("Entry{i}")),
                 self_destruct: None,
+                destroyed: false,
+                readonly: false,
+                subtree_value: U256::ZERO,
                 events: vec![],
                 first_snapshot_id: Some(i),
             });
@@ -1032,12 +1348,17 @@ mod tests {
             code_address: address!("0x1234567890123456789012345678901234567890"),
             input: Bytes::new(),
             value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
             result: None,
             created_contract: false,
             create_scheme: None,
             bytecode: None,
             target_label: None,
             self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![event_data.clone()],
             first_snapshot_id: None,
         };
@@ -1063,6 +1384,8 @@ mod tests {
             code_address: Address::ZERO,
             input: Bytes::new(),
             value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
             result: None,
             created_contract: false,
             create_scheme: None,
@@ -1072,6 +1395,9 @@ mod tests {
                 address!("0x1234567890123456789012345678901234567890"),
                 U256::from(1000),
             )),
+            destroyed: true,
+            readonly: false,
+            subtree_value: U256::ZERO,
             events: vec![],
             first_snapshot_id: None,
         };
@@ -1117,4 +1443,75 @@ mod tests {
             assert_eq!(deserialized, call_type);
         }
     }
+
+    #[test]
+    fn test_trace_page() {
+        let mut trace = Trace::new();
+        for (id, parent_id, depth) in
+            [(0, None, 0), (1, Some(0), 1), (2, Some(1), 2), (3, Some(0), 1)]
+        {
+            trace.push(TraceEntry {
+                id,
+                parent_id,
+                depth,
+                call_type: CallType::Call(CallScheme::Call),
+                caller: Address::ZERO,
+                target: Address::ZERO,
+                code_address: Address::ZERO,
+                input: Bytes::from(vec![0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]),
+                value: U256::ZERO,
+                gas_limit: 0,
+                gas_used: 0,
+                result: None,
+                created_contract: false,
+                create_scheme: None,
+                bytecode: Some(Bytes::from_static(b"code")),
+                target_label: None,
+                self_destruct: None,
+                destroyed: false,
+                readonly: false,
+                subtree_value: U256::ZERO,
+                events: vec![],
+                first_snapshot_id: None,
+            });
+        }
+
+        // Pages follow each other through the cursor
+        let first = trace.page(&TraceQuery { limit: Some(3), ..Default::default() });
+        assert_eq!(first.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(first.total, 4);
+        assert_eq!(first.next_cursor, Some(3));
+        let second = trace.page(&TraceQuery { cursor: 3, limit: Some(3), ..Default::default() });
+        assert_eq!(second.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(second.next_cursor, None);
+
+        // Depth and parent filters
+        let shallow = trace.page(&TraceQuery { max_depth: Some(1), ..Default::default() });
+        assert_eq!(shallow.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![0, 1, 3]);
+        let children = trace.page(&TraceQuery { parent_id: Some(0), ..Default::default() });
+        assert_eq!(children.entries.iter().map(|e| e.id).collect::<Vec<_>>(), vec![1, 3]);
+
+        // Summaries keep the selector only
+        let summary = trace.page(&TraceQuery { summary: true, ..Default::default() });
+        assert_eq!(summary.entries[0].input, Bytes::from(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert!(summary.entries[0].bytecode.is_none());
+    }
+
+    #[test]
+    fn test_captured_log_serialization() {
+        let log = CapturedLog {
+            trace_entry_id: 2,
+            index: 1,
+            address: address!("0x1234567890123456789012345678901234567890"),
+            log: LogData::new_unchecked(vec![B256::ZERO], Bytes::from(vec![0x01])),
+            snapshot_id: Some(42),
+        };
+
+        let json = serde_json::to_value(&log).unwrap();
+        assert_eq!(json["traceEntryId"], 2);
+        assert_eq!(json["snapshotId"], 42);
+
+        let deserialized: CapturedLog = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, log);
+    }
 }
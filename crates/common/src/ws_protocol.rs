@@ -17,14 +17,29 @@
 //! WebSocket protocol definitions for EDB server mode
 //!
 //! This module defines the message protocol used for communication between
-//! WebSocket clients and the EDB server. Clients can request transaction
+//! WebSocket clients (e.g., the TUI) and the EDB server. Clients can request transaction
 //! replays or test debugging sessions, and the server responds with RPC
 //! server connection information.
+//!
+//! Several clients can attach to the session of the same transaction. Clients joining
+//! with [`ClientRequest::Replay`] drive the session and share a navigation cursor,
+//! moved with [`ClientRequest::Navigate`], while clients joining with
+//! [`ClientRequest::Observe`] follow the cursor read-only.
+//!
+//! The RPC server of a session only accepts the bearer token a client receives in
+//! [`ServerResponse::Attached`]. Observers receive a read-only token, with which the
+//! methods changing the state shared by the session cannot be called.
+//!
+//! Once attached, a client receives [`ServerResponse::Attached`] with a resume token. If
+//! its connection drops, the server holds its place in the session for a while and
+//! buffers the updates it misses; reconnecting with [`ClientRequest::Resume`] restores
+//! the place without preparing the transaction again. Resuming while the previous
+//! connection is still open takes it over, and the previous connection receives
+//! [`ServerResponse::TakenOver`].
 
 use serde::{Deserialize, Serialize};
 
-// Re-export ProgressMessage from common crate
-pub use edb_common::ProgressMessage;
+pub use crate::ProgressMessage;
 
 /// Request sent from client to server
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +50,22 @@ pub enum ClientRequest {
         /// Transaction hash to replay (with or without 0x prefix)
         tx_hash: String,
     },
+    /// Request to observe the existing session of a transaction without being able to
+    /// move the shared cursor
+    Observe {
+        /// Transaction hash of the session to observe (with or without 0x prefix)
+        tx_hash: String,
+    },
+    /// Request to resume the place of a client in a session after its connection dropped
+    Resume {
+        /// Resume token received when the client attached
+        token: String,
+    },
+    /// Request to move the shared cursor of the attached session to a snapshot
+    Navigate {
+        /// Snapshot to move the cursor to
+        snapshot_id: usize,
+    },
     /// Request to debug a Foundry test (not yet implemented)
     Test {
         /// Name of the test to debug
@@ -65,6 +96,37 @@ pub enum ServerResponse {
         /// Whether this is a reused existing session
         reused: bool,
     },
+    /// The connection is attached to a session
+    Attached {
+        /// Label of the client in the session
+        client: String,
+        /// Token to resume the session with after the connection drops
+        resume_token: String,
+        /// Whether the client resumed a place it held before
+        resumed: bool,
+        /// Number of session updates missed while disconnected, sent right after
+        missed: usize,
+        /// Bearer token to call the RPC server of the session with
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rpc_token: Option<String>,
+    },
+    /// Another connection resumed the place of this client, this connection is detached
+    TakenOver,
+    /// The shared cursor of the attached session moved
+    Cursor {
+        /// Snapshot the cursor points to
+        snapshot_id: usize,
+        /// Label of the client that moved the cursor
+        #[serde(skip_serializing_if = "Option::is_none")]
+        by: Option<String>,
+    },
+    /// The clients attached to the session changed
+    Participants {
+        /// Number of clients that can move the shared cursor
+        drivers: usize,
+        /// Number of read-only observers
+        observers: usize,
+    },
     /// Error response
     Error {
         /// Error message
@@ -151,6 +213,41 @@ mod tests {
         assert!(json.contains("\"total_steps\":5"));
     }
 
+    #[test]
+    fn test_collaboration_messages_serialization() {
+        let request: ClientRequest =
+            serde_json::from_str(r#"{"type":"navigate","snapshot_id":42}"#).unwrap();
+        assert!(matches!(request, ClientRequest::Navigate { snapshot_id: 42 }));
+
+        let response = ServerResponse::Cursor { snapshot_id: 42, by: Some("client-1".into()) };
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"status\":\"cursor\""));
+        assert!(json.contains("\"by\":\"client-1\""));
+    }
+
+    #[test]
+    fn test_resume_messages_serialization() {
+        let request: ClientRequest =
+            serde_json::from_str(r#"{"type":"resume","token":"abcd"}"#).unwrap();
+        assert!(matches!(request, ClientRequest::Resume { token } if token == "abcd"));
+
+        let response = ServerResponse::Attached {
+            client: "client-1".into(),
+            resume_token: "abcd".into(),
+            resumed: true,
+            missed: 3,
+            rpc_token: Some("observer".into()),
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.contains("\"status\":\"attached\""));
+        assert!(json.contains("\"resume_token\":\"abcd\""));
+        assert!(json.contains("\"missed\":3"));
+        assert!(json.contains("\"rpc_token\":\"observer\""));
+
+        let json = serde_json::to_string(&ServerResponse::TakenOver).unwrap();
+        assert_eq!(json, r#"{"status":"takenover"}"#);
+    }
+
     #[test]
     fn test_client_request_deserialization() {
         let json = r#"{"type":"replay","tx_hash":"0x1234"}"#;
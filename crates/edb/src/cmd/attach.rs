@@ -0,0 +1,109 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Attach command - launch a UI against an engine RPC server that is already running
+//!
+//! Preparing a session is the expensive part of debugging, so it can happen on a headless
+//! box (e.g. `edb server`) while the UI runs on a laptop attached to its RPC server.
+
+use edb_common::{EngineRpcClient, RpcClientOptions, RPC_AUTH_TOKEN_ENV};
+use eyre::{bail, eyre, Result};
+use serde_json::json;
+use std::time::Duration;
+
+use crate::utils::{self, UiMode};
+
+/// Timeout for checking that the engine RPC server is reachable
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Launch the UI against a running engine RPC server, without preparing any session
+///
+/// `rpc_options` hold the bearer token and CA certificate of the server, if it requires
+/// them.
+pub async fn attach(url: &str, rpc_options: &RpcClientOptions, cli: &crate::Cli) -> Result<()> {
+    if cli.ui == UiMode::None {
+        bail!("Nothing to attach with --ui none, choose the tui or web UI");
+    }
+    let url = if url.contains("://") { url.to_string() } else { format!("http://{url}") };
+
+    // Unix sockets are left to the UI to connect to
+    if url.starts_with("http://") || url.starts_with("https://") {
+        check_engine_server(&url, rpc_options).await?;
+    }
+
+    tracing::info!("Attaching to engine RPC server at {}", url);
+    utils::launch_ui(cli, &url, rpc_options).await
+}
+
+/// Check that a debugging session is served at a URL, to fail before the UI starts
+async fn check_engine_server(url: &str, rpc_options: &RpcClientOptions) -> Result<()> {
+    let options = RpcClientOptions { timeout: Some(PROBE_TIMEOUT), ..rpc_options.clone() };
+    let client = EngineRpcClient::new(url, &options)?;
+
+    let Err(e) = client.request("edb_getSnapshotCount", json!([])).await else {
+        return Ok(());
+    };
+    match e.downcast_ref::<reqwest::Error>() {
+        Some(http) if http.status() == Some(reqwest::StatusCode::UNAUTHORIZED) => bail!(
+            "{url} requires a bearer token, pass it with --auth-token or {RPC_AUTH_TOKEN_ENV}"
+        ),
+        Some(http) => bail!("No engine RPC server reachable at {url}: {http}"),
+        None if e.to_string().starts_with("RPC error") => bail!(
+            "{url} does not serve a debugging session ({e}); for a session manager, \
+             attach to the URL of a session, e.g. {}/session/<id>",
+            url.trim_end_matches('/')
+        ),
+        None => Err(eyre!("{url} is not an engine RPC server: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::HeaderMap, http::StatusCode, routing::post, Json, Router};
+    use serde_json::Value;
+
+    /// Serve a fake engine RPC server requiring the bearer token `s3cret`
+    async fn engine_server() -> String {
+        let app = Router::new().route(
+            "/",
+            post(|headers: HeaderMap, Json(request): Json<Value>| async move {
+                if headers.get("authorization").and_then(|v| v.to_str().ok())
+                    != Some("Bearer s3cret")
+                {
+                    return Err(StatusCode::UNAUTHORIZED);
+                }
+                Ok(Json(json!({ "jsonrpc": "2.0", "id": request["id"], "result": 42 })))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_check_engine_server_auth_token() {
+        let url = engine_server().await;
+
+        let error = check_engine_server(&url, &RpcClientOptions::default()).await.unwrap_err();
+        assert!(error.to_string().contains("--auth-token"), "{error}");
+
+        let options =
+            RpcClientOptions { auth_token: Some("s3cret".to_string()), ..Default::default() };
+        check_engine_server(&url, &options).await.unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Cache maintenance commands
+
+use edb_common::{CachePath, EdbCachePath};
+use eyre::{eyre, Result};
+use std::env;
+
+/// Trim the cache directory to a maximum size, removing the least recently used files first
+pub fn gc_cache(max_size: u64) -> Result<()> {
+    let root = EdbCachePath::new(env::var(edb_common::env::EDB_CACHE_DIR).ok())
+        .edb_cache_dir()
+        .ok_or_else(|| eyre!("No cache directory, set --cache-dir"))?;
+
+    let report = edb_common::gc_cache_dir(&root, max_size)?;
+    println!("🗄️  Cache: {} ({} files)", root.display(), report.files);
+    if report.removed_files == 0 {
+        println!(
+            "✅ {} is within the limit of {}",
+            format_size(report.size),
+            format_size(max_size)
+        );
+    } else {
+        println!(
+            "🧹 Removed {} files ({}), {} left",
+            report.removed_files,
+            format_size(report.removed_size),
+            format_size(report.size - report.removed_size)
+        );
+    }
+    Ok(())
+}
+
+/// Parse the `--max-size` argument
+pub fn parse_max_size(size: &str) -> Result<u64, String> {
+    edb_common::parse_size(size).map_err(|e| e.to_string())
+}
+
+/// Format a size in bytes with a binary unit
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
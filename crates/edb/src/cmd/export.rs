@@ -0,0 +1,176 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export command - prepare a debugging session and write its data to files or stdout
+
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::TxHash;
+use edb_common::fork_and_prepare_cached;
+use edb_engine::{Engine, TraceFormat};
+use eyre::{eyre, Result};
+use serde_json::json;
+
+use crate::utils;
+
+/// What to export from a prepared debugging session
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Write the gas profile to this path, as an SVG for `.svg` paths and as folded stacks
+    /// otherwise
+    pub flamegraph: Option<PathBuf>,
+    /// Weight of the flamegraph frames, either `gas` or `time`
+    pub flamegraph_weight: String,
+    /// Write the source line coverage to this path (LCOV for `.info`/`.lcov`, JSON otherwise)
+    pub coverage: Option<PathBuf>,
+    /// Export the decoded call trace in this format
+    pub format: Option<TraceFormat>,
+    /// Write the decoded call trace to this path instead of stdout
+    pub output: Option<PathBuf>,
+    /// Include the state of every snapshot in the decoded call trace
+    pub snapshots: bool,
+}
+
+impl ExportOptions {
+    /// Whether any output was requested
+    pub fn is_empty(&self) -> bool {
+        self.flamegraph.is_none() && self.coverage.is_none() && self.format.is_none()
+    }
+}
+
+/// Prepare a transaction without launching a UI and export the requested data
+pub async fn export_transaction(
+    tx_hash: TxHash,
+    options: &ExportOptions,
+    cli: &crate::Cli,
+    rpc_url: &str,
+) -> Result<()> {
+    if options.is_empty() {
+        return Err(eyre!(
+            "Nothing to export, please specify at least one output (e.g., --format json)"
+        ));
+    }
+    if options.format.is_none() && (options.output.is_some() || options.snapshots) {
+        return Err(eyre!("--output and --snapshots require a trace format (--format)"));
+    }
+
+    tracing::info!("Starting transaction export workflow");
+
+    let fork_result = fork_and_prepare_cached(rpc_url, tx_hash, cli.quick, cli.refetch).await?;
+
+    // Keep stdout clean, since the trace may be written to it
+    let engine = Engine::new(cli.to_engine_config(rpc_url).with_quiet(true));
+    let rpc_server_addr = utils::with_preparation_progress(
+        &engine,
+        tx_hash,
+        cli.status_port,
+        engine.prepare(fork_result, None),
+    )
+    .await?;
+
+    let result = async {
+        if let Some(path) = &options.flamegraph {
+            let weight = options.flamegraph_weight.as_str();
+            let folded =
+                utils::query_debug_server(rpc_server_addr, "edb_getFlamegraph", json!([weight]))
+                    .await?;
+            let folded = folded.as_str().ok_or_else(|| eyre!("Invalid flamegraph response"))?;
+            if path.extension().is_some_and(|ext| ext == "svg") {
+                write_output(path, render_flamegraph(folded, weight)?)?;
+                eprintln!("Flamegraph ({weight}) written to {}", path.display());
+            } else {
+                write_output(path, folded)?;
+                eprintln!("Flamegraph ({weight}, folded stacks) written to {}", path.display());
+            }
+        }
+
+        if let Some(path) = &options.coverage {
+            let lcov = path.extension().is_some_and(|ext| ext == "info" || ext == "lcov");
+            let format = if lcov { "lcov" } else { "json" };
+            let coverage =
+                utils::query_debug_server(rpc_server_addr, "edb_getCoverage", json!([format]))
+                    .await?;
+            let content = match coverage.as_str() {
+                Some(lcov) => lcov.to_string(),
+                None => serde_json::to_string_pretty(&coverage)?,
+            };
+            write_output(path, &content)?;
+            eprintln!("Source line coverage ({format}) written to {}", path.display());
+        }
+
+        if let Some(format) = options.format {
+            let params = json!([format, options.snapshots]);
+            let trace =
+                utils::query_debug_server(rpc_server_addr, "edb_exportTrace", params).await?;
+            let content = match trace.as_str() {
+                Some(text) => text.to_string(),
+                None => serde_json::to_string_pretty(&trace)?,
+            };
+            match &options.output {
+                Some(path) => {
+                    write_output(path, &content)?;
+                    eprintln!("Trace ({format:?}) written to {}", path.display());
+                }
+                None => println!("{content}"),
+            }
+        }
+
+        Ok::<_, eyre::Report>(())
+    }
+    .await;
+
+    tracing::info!("Shutting down EDB...");
+    engine.shutdown_rpc_server(&tx_hash)?;
+
+    result
+}
+
+/// Render folded stacks as an SVG flamegraph, weighted by `gas` or `time` (nanoseconds)
+fn render_flamegraph(folded: &str, weight: &str) -> Result<Vec<u8>> {
+    let mut options = inferno::flamegraph::Options::default();
+    options.title = match weight {
+        "time" => "Replay time".to_string(),
+        _ => "Gas".to_string(),
+    };
+    options.count_name = match weight {
+        "time" => "ns".to_string(),
+        _ => "gas".to_string(),
+    };
+
+    let mut svg = Vec::new();
+    inferno::flamegraph::from_lines(&mut options, folded.lines(), &mut svg)
+        .map_err(|e| eyre!("Failed to render flamegraph: {e}"))?;
+    Ok(svg)
+}
+
+fn write_output(path: &Path, content: impl AsRef<[u8]>) -> Result<()> {
+    std::fs::write(path, content).map_err(|e| eyre!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_flamegraph() {
+        let folded = "0xaa::transfer 21000\n0xaa::transfer;0xbb::balanceOf 2600\n";
+        let svg = String::from_utf8(render_flamegraph(folded, "gas").unwrap()).unwrap();
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("balanceOf"));
+        assert!(svg.contains("gas"));
+    }
+}
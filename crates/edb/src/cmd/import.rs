@@ -0,0 +1,63 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Import command - start a debugging session from `debug_traceTransaction` results
+
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::TxHash;
+use edb_common::fork_and_prepare_cached;
+use edb_engine::{Engine, ExternalTrace};
+use eyre::{eyre, Result};
+use serde_json::Value;
+
+use crate::utils;
+
+/// Debug a transaction using the results of `debug_traceTransaction` instead of replaying it
+pub async fn import_transaction(
+    tx_hash: TxHash,
+    call_trace: &Path,
+    prestate: Option<&PathBuf>,
+    cli: &crate::Cli,
+    rpc_url: &str,
+) -> Result<()> {
+    tracing::info!("Starting transaction import workflow");
+
+    let external_trace =
+        ExternalTrace::from_json(read_json(call_trace)?, prestate.map(read_json).transpose()?)?;
+
+    // Fork at the parent block only to obtain the transaction environment and the state of
+    // accounts not covered by the prestate; preceding transactions are not replayed.
+    let fork_result = fork_and_prepare_cached(rpc_url, tx_hash, true, cli.refetch).await?;
+
+    let engine = Engine::new(cli.to_engine_config(rpc_url));
+    let rpc_server_addr =
+        engine.prepare_from_external_trace(fork_result, external_trace, None).await?;
+
+    utils::start_ui(cli, rpc_server_addr).await?;
+
+    tracing::info!("Shutting down EDB...");
+    engine.shutdown_rpc_server(&tx_hash)?;
+
+    Ok(())
+}
+
+fn read_json(path: impl AsRef<Path>) -> Result<Value> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| eyre!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| eyre!("Invalid JSON in {}: {}", path.display(), e))
+}
@@ -16,12 +16,24 @@
 
 //! Command modules for the EDB CLI
 
+pub mod attach;
+pub mod cache;
 pub mod debug;
+pub mod export;
+pub mod import;
 pub mod proxy_status;
 pub mod replay;
+pub mod report;
 pub mod server;
+pub mod trace;
 
+pub use attach::attach;
+pub use cache::gc_cache;
 pub use debug::debug_foundry_test;
+pub use export::{export_transaction, ExportOptions};
+pub use import::import_transaction;
 pub use proxy_status::show_proxy_status;
 pub use replay::replay_transaction;
+pub use report::{report_exit_code, report_transaction, ReportFormat};
 pub use server::start_server;
+pub use trace::trace_transaction;
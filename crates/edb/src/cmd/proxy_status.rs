@@ -64,6 +64,8 @@ pub async fn show_proxy_status(cli: &crate::Cli) -> Result<()> {
             let response_time = provider["response_time_ms"].as_u64();
             let failures = provider["consecutive_failures"].as_u64().unwrap_or(0);
             let last_check = provider["last_health_check_seconds_ago"].as_u64();
+            let error_rate = provider["error_rate"].as_f64().unwrap_or(0.0);
+            let rate_limited = provider["rate_limited_seconds_left"].as_u64();
 
             let status_emoji = if is_healthy { "✅" } else { "❌" };
             let status_text = if is_healthy { "Healthy" } else { "Unhealthy" };
@@ -79,6 +81,14 @@ pub async fn show_proxy_status(cli: &crate::Cli) -> Result<()> {
                 println!("   Failures: {failures}");
             }
 
+            if error_rate >= 0.01 {
+                println!("   Error Rate: {:.0}%", error_rate * 100.0);
+            }
+
+            if let Some(left) = rate_limited {
+                println!("   Rate Limited: backing off for {left}s");
+            }
+
             if let Some(last) = last_check {
                 if last < 60 {
                     println!("   Last Check: {last}s ago");
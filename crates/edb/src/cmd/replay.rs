@@ -17,7 +17,7 @@
 //! Replay command - replay_transaction function and tests
 
 use alloy_primitives::TxHash;
-use edb_common::fork_and_prepare;
+use edb_common::fork_and_prepare_cached;
 use edb_engine::Engine;
 use eyre::Result;
 
@@ -29,7 +29,7 @@ pub async fn replay_transaction(tx_hash: TxHash, cli: &crate::Cli, rpc_url: &str
 
     // Step 1: Fork the chain and replay earlier transactions in the block
     // Fork and prepare the database/environment for the target transaction
-    let fork_result = fork_and_prepare(rpc_url, tx_hash, cli.quick).await?;
+    let fork_result = fork_and_prepare_cached(rpc_url, tx_hash, cli.quick, cli.refetch).await?;
 
     tracing::info!(
         "Forked chain and prepared database for transaction replay at block {}",
@@ -42,14 +42,49 @@ pub async fn replay_transaction(tx_hash: TxHash, cli: &crate::Cli, rpc_url: &str
     // Step 3: Call engine::prepare with forked database and EVM config
     tracing::info!("Calling engine::prepare with prepared inputs");
     let engine = Engine::new(engine_config);
-    let rpc_server_addr = engine.prepare(fork_result, None).await?;
 
-    // Step 4: Launch TUI and wait for user to exit
-    utils::start_tui(&cli.tui_options, rpc_server_addr).await?;
+    #[cfg(unix)]
+    if let Some(path) = &cli.ipc {
+        let handle = utils::with_preparation_progress(
+            &engine,
+            tx_hash,
+            cli.status_port,
+            engine.prepare_on_unix_socket(fork_result, None, path.clone()),
+        )
+        .await?;
 
-    // Step 5: Shutdown EDB
+        // Step 4: Launch the UI against the socket and wait for user to exit
+        let result =
+            utils::launch_ui(cli, &handle.url(), &edb_common::RpcClientOptions::default()).await;
+
+        tracing::info!("Shutting down EDB...");
+        handle.shutdown()?;
+        return result;
+    }
+
+    let rpc_server_addr = utils::with_preparation_progress(
+        &engine,
+        tx_hash,
+        cli.status_port,
+        engine.prepare(fork_result, None),
+    )
+    .await?;
+
+    // Step 4: Launch the UI and wait for user to exit
+    utils::start_ui(cli, rpc_server_addr).await?;
+
+    // Step 5: Offer to follow a bridged flow to the other chain
+    let bridged = utils::offer_bridged_transaction(cli, rpc_server_addr).await;
+
+    // Step 6: Shutdown EDB
     tracing::info!("Shutting down EDB...");
     engine.shutdown_rpc_server(&tx_hash)?;
 
+    // Step 7: Debug the correlated transaction as a second session
+    if let Some((bridged_tx_hash, bridged_rpc_url)) = bridged {
+        tracing::info!("Following bridged transaction {:?}", bridged_tx_hash);
+        Box::pin(replay_transaction(bridged_tx_hash, cli, &bridged_rpc_url)).await?;
+    }
+
     Ok(())
 }
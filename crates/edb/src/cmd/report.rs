@@ -0,0 +1,82 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Report command - prepare a transaction and print a structured report without any UI
+
+use alloy_primitives::TxHash;
+use clap::ValueEnum;
+use edb_common::fork_and_prepare_cached;
+use edb_engine::{Engine, ExecutionStatus, SessionReport};
+use eyre::{eyre, Result};
+use serde_json::json;
+
+use crate::utils;
+
+/// Output format of a transaction report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Pretty-printed JSON
+    Json,
+}
+
+/// Prepare a transaction without launching a UI and print its report to stdout
+///
+/// Returns the status of the transaction, so that callers can derive an exit code.
+pub async fn report_transaction(
+    tx_hash: TxHash,
+    format: ReportFormat,
+    cli: &crate::Cli,
+    rpc_url: &str,
+) -> Result<ExecutionStatus> {
+    tracing::info!("Starting transaction report workflow");
+
+    let fork_result = fork_and_prepare_cached(rpc_url, tx_hash, cli.quick, cli.refetch).await?;
+
+    // Keep stdout clean for the report
+    let engine = Engine::new(cli.to_engine_config(rpc_url).with_quiet(true));
+    let rpc_server_addr = utils::with_preparation_progress(
+        &engine,
+        tx_hash,
+        cli.status_port,
+        engine.prepare(fork_result, None),
+    )
+    .await?;
+
+    let report = utils::query_debug_server(rpc_server_addr, "edb_getReport", json!([])).await;
+
+    tracing::info!("Shutting down EDB...");
+    engine.shutdown_rpc_server(&tx_hash)?;
+
+    let report: SessionReport = serde_json::from_value(report?)
+        .map_err(|e| eyre!("Invalid report from debug server: {e}"))?;
+
+    match format {
+        ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+
+    Ok(report.status)
+}
+
+/// Exit code of `edb replay --report` for a transaction with the given status
+///
+/// Errors while producing the report exit with `1`.
+pub fn report_exit_code(status: ExecutionStatus) -> i32 {
+    match status {
+        ExecutionStatus::Success => 0,
+        ExecutionStatus::Revert => 2,
+        ExecutionStatus::Halt => 3,
+    }
+}
@@ -15,8 +15,16 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! WebSocket server command - manages remote debugging sessions
+//!
+//! Clients attached to the session of the same transaction collaborate through a shared
+//! navigation cursor: drivers move it, observers only follow it. A client whose
+//! connection drops keeps its place for [`RESUME_GRACE`] and can resume it with its token.
+//!
+//! The RPC servers of sessions always require a bearer token, so that observers can be
+//! told apart from drivers: drivers receive the token of the server, while each observer
+//! receives its own read-only token, valid for as long as it holds its place.
 
-use alloy_primitives::TxHash;
+use alloy_primitives::{hex, TxHash, B256};
 use axum::{
     extract::{
         ws::{Message, WebSocket},
@@ -26,18 +34,31 @@ use axum::{
     routing::get,
     Router,
 };
-use edb_common::fork_and_prepare;
-use edb_engine::Engine;
+use edb_common::fork_and_prepare_cached;
+use edb_engine::{
+    serve_router, Debugger, Engine, EngineEvent, ReadOnlyTokens, RpcSecurityConfig, SessionManager,
+    SessionRpcServer, StatusRpcServer,
+};
 use eyre::Result;
-use futures::{SinkExt, StreamExt};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use futures::{
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::{
     select,
     sync::{mpsc, oneshot, Mutex},
 };
 use tracing::{error, info, warn};
 
-use crate::ws_protocol::{ClientRequest, ProgressMessage, ServerResponse};
+use edb_common::ws_protocol::{ClientRequest, ProgressMessage, ServerResponse};
 
 /// Server state shared across WebSocket connections
 #[derive(Clone)]
@@ -49,42 +70,443 @@ struct ServerState {
     active_connections: Arc<Mutex<HashMap<TxHash, usize>>>,
     /// Channel to send work to the worker thread
     worker_tx: mpsc::UnboundedSender<WorkerMessage>,
+    /// Clients and shared cursor of each session
+    collaboration: Arc<Mutex<HashMap<TxHash, SharedSession>>>,
+    /// Identifier of the next client attached to a session
+    next_client_id: Arc<AtomicU64>,
+    /// Access control of the RPC servers of sessions, always with a bearer token
+    security: RpcSecurityConfig,
+}
+
+/// How long a client whose connection dropped keeps its place in a session
+const RESUME_GRACE: Duration = Duration::from_secs(10 * 60);
+
+/// Maximum number of session updates buffered for a disconnected client
+const MAX_MISSED_UPDATES: usize = 256;
+
+/// Role of a client attached to a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientRole {
+    /// Can move the shared cursor
+    Driver,
+    /// Follows the shared cursor read-only
+    Observer,
+}
+
+/// How a connection attaches to a session
+enum Attach {
+    /// As a new client with the given role
+    Join(ClientRole),
+    /// In the place of the client holding the resume token
+    Resume(String),
+}
+
+/// A client attached to a session
+struct SessionClient {
+    role: ClientRole,
+    /// Bearer token with which the client calls the RPC server of the session
+    rpc_token: Option<String>,
+    /// Token with which the client resumes its place after its connection dropped
+    resume_token: String,
+    /// Connection of the client, if it is connected
+    connection: Option<ClientConnection>,
+    /// Number of connections the client attached with so far
+    generation: u64,
+    /// Session updates missed while disconnected
+    missed: VecDeque<ServerResponse>,
+}
+
+/// The connection currently attached in the place of a client
+struct ClientConnection {
+    /// Channel forwarding session updates to the connection
+    updates: mpsc::UnboundedSender<ServerResponse>,
+    /// Notifies the connection that another connection took over
+    takeover: oneshot::Sender<()>,
+}
+
+/// What a connection needs to serve the client it is attached as
+struct Attachment {
+    tx_hash: TxHash,
+    client_id: u64,
+    role: ClientRole,
+    generation: u64,
+    /// Session updates for the client
+    updates: mpsc::UnboundedReceiver<ServerResponse>,
+    /// Fires when another connection takes over
+    takeover: oneshot::Receiver<()>,
+}
+
+impl SessionClient {
+    /// Send an update to the client, buffering it while the client is disconnected
+    fn send(&mut self, update: ServerResponse) {
+        if let Some(connection) = &self.connection {
+            if connection.updates.send(update.clone()).is_ok() {
+                return;
+            }
+        }
+        if self.missed.len() == MAX_MISSED_UPDATES {
+            self.missed.pop_front();
+        }
+        self.missed.push_back(update);
+    }
+
+    /// Attach a new connection in the place of the client, taking over the previous one
+    fn connect(&mut self) -> (u64, mpsc::UnboundedReceiver<ServerResponse>, oneshot::Receiver<()>) {
+        if let Some(previous) = self.connection.take() {
+            previous.takeover.send(()).ok();
+        }
+
+        let (updates, updates_rx) = mpsc::unbounded_channel();
+        let (takeover, takeover_rx) = oneshot::channel();
+        self.connection = Some(ClientConnection { updates, takeover });
+        self.generation += 1;
+        (self.generation, updates_rx, takeover_rx)
+    }
+}
+
+/// Collaboration state of a session shared by all its clients
+#[derive(Default)]
+struct SharedSession {
+    /// Snapshot the shared cursor points to, once a driver moved it
+    cursor: Option<usize>,
+    /// Attached clients, by client id
+    clients: HashMap<u64, SessionClient>,
+}
+
+impl SharedSession {
+    /// Send an update to all clients except `skip`
+    fn broadcast(&mut self, update: &ServerResponse, skip: Option<u64>) {
+        for (id, client) in &mut self.clients {
+            if Some(*id) != skip {
+                client.send(update.clone());
+            }
+        }
+    }
+
+    /// Build the participants update of the session
+    fn participants(&self) -> ServerResponse {
+        let drivers =
+            self.clients.values().filter(|client| client.role == ClientRole::Driver).count();
+        ServerResponse::Participants { drivers, observers: self.clients.len() - drivers }
+    }
+}
+
+impl ServerState {
+    /// Attach a connection to the session of a transaction, returning its attachment and
+    /// the [`ServerResponse::Attached`] to send first
+    async fn attach(
+        &self,
+        tx_hash: TxHash,
+        attach: Attach,
+    ) -> Option<(Attachment, ServerResponse)> {
+        match attach {
+            Attach::Join(role) => Some(self.join_session(tx_hash, role).await),
+            Attach::Resume(token) => self.resume_session(tx_hash, &token).await,
+        }
+    }
+
+    /// Attach a new client to the session of a transaction
+    async fn join_session(
+        &self,
+        tx_hash: TxHash,
+        role: ClientRole,
+    ) -> (Attachment, ServerResponse) {
+        let client_id = self.next_client_id.fetch_add(1, Ordering::Relaxed);
+        let resume_token = hex::encode(B256::random());
+        let rpc_token = match role {
+            ClientRole::Driver => self.security.auth_token.clone(),
+            ClientRole::Observer => {
+                let token = hex::encode(B256::random());
+                self.security.read_only_tokens.insert(token.clone());
+                Some(token)
+            }
+        };
+        let mut client = SessionClient {
+            role,
+            rpc_token: rpc_token.clone(),
+            resume_token: resume_token.clone(),
+            connection: None,
+            generation: 0,
+            missed: VecDeque::new(),
+        };
+        let (generation, updates, takeover) = client.connect();
+
+        let mut collaboration = self.collaboration.lock().await;
+        let session = collaboration.entry(tx_hash).or_default();
+
+        // Late joiners start at the current position of the shared cursor
+        if let Some(snapshot_id) = session.cursor {
+            client.send(ServerResponse::Cursor { snapshot_id, by: None });
+        }
+        session.clients.insert(client_id, client);
+        let participants = session.participants();
+        session.broadcast(&participants, None);
+
+        info!("Client {} joined session {:?} as {:?}", client_id, tx_hash, role);
+        let attached = ServerResponse::Attached {
+            client: format!("client-{client_id}"),
+            resume_token,
+            resumed: false,
+            missed: 0,
+            rpc_token,
+        };
+        (Attachment { tx_hash, client_id, role, generation, updates, takeover }, attached)
+    }
+
+    /// Find the session in which a client holds a place with the given resume token
+    async fn find_resumable(&self, token: &str) -> Option<TxHash> {
+        let collaboration = self.collaboration.lock().await;
+        collaboration.iter().find_map(|(tx_hash, session)| {
+            session.clients.values().any(|client| client.resume_token == token).then_some(*tx_hash)
+        })
+    }
+
+    /// Attach a connection in the place of the client holding a resume token, replaying
+    /// the updates the client missed
+    async fn resume_session(
+        &self,
+        tx_hash: TxHash,
+        token: &str,
+    ) -> Option<(Attachment, ServerResponse)> {
+        let mut collaboration = self.collaboration.lock().await;
+        let session = collaboration.get_mut(&tx_hash)?;
+        let (&client_id, client) =
+            session.clients.iter_mut().find(|(_, client)| client.resume_token == token)?;
+
+        let taken_over = client.connection.is_some();
+        let (generation, updates, takeover) = client.connect();
+        let missed = std::mem::take(&mut client.missed);
+        let missed_count = missed.len();
+        for update in missed {
+            client.send(update);
+        }
+
+        if taken_over {
+            info!("Client {} of session {:?} taken over by a new connection", client_id, tx_hash);
+        } else {
+            info!(
+                "Client {} resumed session {:?} with {} missed updates",
+                client_id, tx_hash, missed_count
+            );
+        }
+        let attached = ServerResponse::Attached {
+            client: format!("client-{client_id}"),
+            resume_token: token.to_string(),
+            resumed: true,
+            missed: missed_count,
+            rpc_token: client.rpc_token.clone(),
+        };
+        let attachment =
+            Attachment { tx_hash, client_id, role: client.role, generation, updates, takeover };
+        Some((attachment, attached))
+    }
+
+    /// Detach the connection of a client whose connection dropped, holding its place in
+    /// the session until it resumes or the grace period ends
+    async fn detach_client(&self, tx_hash: TxHash, client_id: u64, generation: u64) {
+        {
+            let mut collaboration = self.collaboration.lock().await;
+            let Some(client) =
+                collaboration.get_mut(&tx_hash).and_then(|s| s.clients.get_mut(&client_id))
+            else {
+                return;
+            };
+            if client.generation != generation {
+                return;
+            }
+            client.connection = None;
+        }
+
+        info!(
+            "Connection of client {} dropped, holding its place in session {:?} for {:?}",
+            client_id, tx_hash, RESUME_GRACE
+        );
+        let state = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(RESUME_GRACE).await;
+
+            // Release the place unless the client resumed in the meantime
+            let expired = {
+                let collaboration = state.collaboration.lock().await;
+                collaboration
+                    .get(&tx_hash)
+                    .and_then(|session| session.clients.get(&client_id))
+                    .is_some_and(|client| {
+                        client.generation == generation && client.connection.is_none()
+                    })
+            };
+            if expired {
+                info!("Client {} did not resume session {:?}", client_id, tx_hash);
+                state.release_client(tx_hash, client_id).await;
+            }
+        });
+    }
+
+    /// Remove a client from its session for good, shutting down the RPC server of the
+    /// session once no client is left
+    async fn release_client(&self, tx_hash: TxHash, client_id: u64) {
+        self.leave_session(tx_hash, client_id).await;
+
+        // Decrement connection count
+        let should_shutdown = {
+            let mut connections = self.active_connections.lock().await;
+            if let Some(count) = connections.get_mut(&tx_hash) {
+                *count -= 1;
+                if *count == 0 {
+                    connections.remove(&tx_hash);
+                    true
+                } else {
+                    info!("Still {} active connections for tx: {:?}", count, tx_hash);
+                    false
+                }
+            } else {
+                false
+            }
+        };
+
+        // If no more connections, shutdown the RPC server
+        if should_shutdown {
+            info!("No more connections for tx: {:?}, shutting down RPC server", tx_hash);
+
+            // Shutdown the RPC server using Engine's shutdown method
+            if let Err(e) = self.engine.shutdown_rpc_server(&tx_hash) {
+                warn!("Error shutting down RPC server: {}", e);
+            } else {
+                info!("RPC server shutdown successfully for tx: {:?}", tx_hash);
+            }
+        }
+    }
+
+    /// Detach a client from the session of a transaction
+    async fn leave_session(&self, tx_hash: TxHash, client_id: u64) {
+        let mut collaboration = self.collaboration.lock().await;
+        let Some(session) = collaboration.get_mut(&tx_hash) else { return };
+
+        let Some(client) = session.clients.remove(&client_id) else { return };
+        if client.role == ClientRole::Observer {
+            if let Some(token) = &client.rpc_token {
+                self.security.read_only_tokens.remove(token);
+            }
+        }
+        if session.clients.is_empty() {
+            collaboration.remove(&tx_hash);
+        } else {
+            let participants = session.participants();
+            session.broadcast(&participants, None);
+        }
+    }
+
+    /// Move the shared cursor of a session and notify the other clients
+    async fn move_cursor(&self, tx_hash: TxHash, client_id: u64, snapshot_id: usize) {
+        let by = format!("client-{client_id}");
+
+        let mut collaboration = self.collaboration.lock().await;
+        let Some(session) = collaboration.get_mut(&tx_hash) else { return };
+        session.cursor = Some(snapshot_id);
+        session.broadcast(
+            &ServerResponse::Cursor { snapshot_id, by: Some(by.clone()) },
+            Some(client_id),
+        );
+        drop(collaboration);
+
+        // Also let RPC subscribers of the engine follow the session
+        self.engine.events().publish(EngineEvent::Navigation {
+            tx_hash,
+            snapshot_id,
+            client: Some(by),
+        });
+    }
 }
 
-/// Start the WebSocket server
-pub async fn start_server(ws_port: u16, cli: &crate::Cli, rpc_url: &str) -> Result<()> {
+/// Start the WebSocket server, and the session manager if a session port is given
+pub async fn start_server(
+    ws_port: u16,
+    session_port: Option<u16>,
+    security: RpcSecurityConfig,
+    cli: &crate::Cli,
+    rpc_url: &str,
+) -> Result<()> {
     info!("Starting EDB WebSocket server on port {}", ws_port);
 
+    // Drivers and observers are told apart by their tokens, so the RPC servers of sessions
+    // require one even if none is configured. Clients receive theirs when they attach, and
+    // the read-only tokens of observers are only valid on these servers.
+    let session_security = RpcSecurityConfig {
+        auth_token: Some(
+            security.auth_token.clone().unwrap_or_else(|| hex::encode(B256::random())),
+        ),
+        read_only_tokens: ReadOnlyTokens::default(),
+        ..security.clone()
+    };
+
     // Create the engine with configuration
-    let engine_config = cli.to_engine_config(rpc_url);
+    let engine_config = cli.to_engine_config(rpc_url).with_rpc_security(session_security.clone());
     let engine = Engine::new(engine_config);
+
+    // Serve multiple sessions through a single JSON-RPC endpoint
+    // The handle is kept alive for as long as the server runs
+    let security_scheme = security.scheme();
+    let _session_server = match session_port {
+        Some(port) => {
+            let engine_config = cli.to_engine_config(rpc_url).with_rpc_security(security.clone());
+            let debugger = Debugger::builder().with_config(engine_config).build();
+            let manager = Arc::new(SessionManager::new(debugger));
+            let handle = SessionRpcServer::new(manager)
+                .with_security(security.clone())
+                .start_on_port(port)
+                .await?;
+            println!("Session manager listening on {}://{}", security_scheme, handle.addr());
+            Some(handle)
+        }
+        None => None,
+    };
+
+    // Report the progress of all preparations for as long as the server runs
+    let _status_server = match cli.status_port {
+        Some(port) => {
+            let handle = StatusRpcServer::new(engine.preparations().clone())
+                .with_security(security.clone())
+                .start_on_port(port)
+                .await?;
+            println!(
+                "Preparation status available at {}://{}/status",
+                security_scheme,
+                handle.addr()
+            );
+            Some(handle)
+        }
+        None => None,
+    };
     let engine = Arc::new(engine);
 
     // Spawn the worker thread for handling requests
-    let worker_tx = spawn_worker(Arc::clone(&engine), rpc_url.to_string(), cli.quick);
+    let worker_tx = spawn_worker(Arc::clone(&engine), rpc_url.to_string(), cli.quick, cli.refetch);
 
     // Create shared state
     let state = ServerState {
         engine: Arc::clone(&engine),
         active_connections: Arc::new(Mutex::new(HashMap::new())),
         worker_tx,
+        collaboration: Arc::new(Mutex::new(HashMap::new())),
+        next_client_id: Arc::new(AtomicU64::new(1)),
+        security: session_security,
     };
 
     // Create the Axum router
     let app = Router::new().route("/", get(ws_handler)).with_state(state);
 
-    // Bind and serve
-    let addr = SocketAddr::from(([127, 0, 0, 1], ws_port));
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    let actual_addr = listener.local_addr()?;
+    // Serve with the configured token and TLS, so that the upgrade is authenticated too
+    let handle = serve_router(app, ws_port, &security).await?;
+    let actual_addr = handle.addr();
 
     info!("WebSocket server listening on {}", actual_addr);
 
-    println!("Server listening on {actual_addr}");
-    // Run the server normally - the worker thread handles !Send operations
-    axum::serve(listener, app).await.expect("Server failed");
-
-    Ok(())
+    let ws_scheme = if security.tls.is_some() { "wss" } else { "ws" };
+    println!("Server listening on {ws_scheme}://{actual_addr}");
+    // The worker thread handles !Send operations, serve until interrupted
+    tokio::signal::ctrl_c().await?;
+    info!("Received Ctrl+C, shutting down...");
+    handle.shutdown()
 }
 
 /// WebSocket upgrade handler
@@ -137,11 +559,21 @@ async fn handle_socket(socket: WebSocket, state: ServerState) {
         // Handle the request
         // Replay requests are delegated to the worker thread (handles !Send types)
         // Other requests can be handled directly
+        let attach = match &request {
+            ClientRequest::Observe { .. } => Attach::Join(ClientRole::Observer),
+            ClientRequest::Resume { token } => Attach::Resume(token.clone()),
+            _ => Attach::Join(ClientRole::Driver),
+        };
         let request_task = async {
             match request {
                 ClientRequest::Replay { tx_hash } => {
                     handle_replay_request(tx_hash, &state, progress_tx).await
                 }
+                ClientRequest::Observe { tx_hash } => handle_observe_request(tx_hash, &state).await,
+                ClientRequest::Resume { token } => handle_resume_request(&token, &state).await,
+                ClientRequest::Navigate { .. } => {
+                    ServerResponse::error("Navigate requires joining a session first")
+                }
                 ClientRequest::Test { test_name, block } => {
                     handle_test_request(test_name, block, &state, progress_tx).await
                 }
@@ -181,7 +613,8 @@ async fn handle_socket(socket: WebSocket, state: ServerState) {
         if let ServerResponse::Success { tx_hash, .. } = &response {
             if let Ok(tx_hash) = tx_hash.parse::<TxHash>() {
                 // Keep connection alive and track it
-                track_connection(tx_hash, &state, &mut receiver).await;
+                track_connection(tx_hash, attach, &state, &mut sender, &mut receiver).await;
+                break;
             }
         }
     }
@@ -250,6 +683,41 @@ async fn handle_replay_request(
     ServerResponse::success(port, tx_hash_str, false)
 }
 
+/// Handle observe request, attaching to an existing session only
+async fn handle_observe_request(tx_hash_str: String, state: &ServerState) -> ServerResponse {
+    let tx_hash: TxHash = match tx_hash_str.parse() {
+        Ok(hash) => hash,
+        Err(e) => {
+            return ServerResponse::error(format!("Invalid transaction hash: {e}"));
+        }
+    };
+
+    let Some(addr) = state.engine.get_rpc_server_addr(&tx_hash) else {
+        return ServerResponse::error(format!(
+            "No session for transaction {tx_hash:?}, observers can only join existing sessions"
+        ));
+    };
+
+    info!("Observer attached to RPC server on port {} for tx: {:?}", addr.port(), tx_hash);
+    let mut connections = state.active_connections.lock().await;
+    *connections.entry(tx_hash).or_insert(0) += 1;
+
+    ServerResponse::success(addr.port(), tx_hash_str, true)
+}
+
+/// Handle resume request, returning the session the token holds a place in
+async fn handle_resume_request(token: &str, state: &ServerState) -> ServerResponse {
+    let Some(tx_hash) = state.find_resumable(token).await else {
+        return ServerResponse::error("Unknown or expired resume token");
+    };
+    let Some(addr) = state.engine.get_rpc_server_addr(&tx_hash) else {
+        return ServerResponse::error(format!("Session for transaction {tx_hash:?} has ended"));
+    };
+
+    // The resumed client still holds its place, so the connection count is unchanged
+    ServerResponse::success(addr.port(), tx_hash.to_string(), true)
+}
+
 /// Handle test request (not yet implemented)
 async fn handle_test_request(
     _test_name: String,
@@ -260,49 +728,77 @@ async fn handle_test_request(
     ServerResponse::error("Test debugging not yet implemented")
 }
 
-/// Track connection and handle disconnection
+/// Track connection, relay the shared cursor of its session, and handle disconnection.
+///
+/// A connection that closes cleanly releases its place in the session, while a dropped
+/// connection only detaches from it so that the client can resume.
 async fn track_connection(
     tx_hash: TxHash,
+    attach: Attach,
     state: &ServerState,
-    receiver: &mut futures::stream::SplitStream<WebSocket>,
+    sender: &mut SplitSink<WebSocket, Message>,
+    receiver: &mut SplitStream<WebSocket>,
 ) {
-    // Wait for the connection to close
-    while let Some(msg) = receiver.next().await {
-        match msg {
-            Ok(Message::Close(_)) | Err(_) => break,
-            _ => continue,
+    let Some((mut attachment, attached)) = state.attach(tx_hash, attach).await else {
+        let response = ServerResponse::error("Unknown or expired resume token");
+        if let Ok(json) = serde_json::to_string(&response) {
+            let _ = sender.send(Message::Text(json.into())).await;
+        }
+        return;
+    };
+    let Attachment { client_id, role, generation, .. } = attachment;
+
+    if let Ok(json) = serde_json::to_string(&attached) {
+        if let Err(e) = sender.send(Message::Text(json.into())).await {
+            error!("Failed to send attachment: {}", e);
         }
     }
 
-    info!("Connection closed for tx: {:?}", tx_hash);
+    // Relay session updates until the connection closes
+    let closed = loop {
+        let response = select! {
+            _ = &mut attachment.takeover => {
+                if let Ok(json) = serde_json::to_string(&ServerResponse::TakenOver) {
+                    let _ = sender.send(Message::Text(json.into())).await;
+                }
+                // The place now belongs to the new connection
+                return;
+            }
+            Some(update) = attachment.updates.recv() => update,
+            msg = receiver.next() => {
+                let text = match msg {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) => break true,
+                    Some(Err(_)) | None => break false,
+                    Some(Ok(_)) => continue,
+                };
+                match serde_json::from_str::<ClientRequest>(&text) {
+                    Ok(ClientRequest::Navigate { snapshot_id }) if role == ClientRole::Driver => {
+                        state.move_cursor(tx_hash, client_id, snapshot_id).await;
+                        continue;
+                    }
+                    Ok(ClientRequest::Navigate { .. }) => {
+                        ServerResponse::error("Observers cannot move the shared cursor")
+                    }
+                    Ok(_) => ServerResponse::error("Already attached to a session"),
+                    Err(e) => ServerResponse::error(format!("Invalid request: {e}")),
+                }
+            }
+        };
 
-    // Decrement connection count
-    let should_shutdown = {
-        let mut connections = state.active_connections.lock().await;
-        if let Some(count) = connections.get_mut(&tx_hash) {
-            *count -= 1;
-            if *count == 0 {
-                connections.remove(&tx_hash);
-                true
-            } else {
-                info!("Still {} active connections for tx: {:?}", count, tx_hash);
-                false
+        if let Ok(json) = serde_json::to_string(&response) {
+            if let Err(e) = sender.send(Message::Text(json.into())).await {
+                error!("Failed to send session update: {}", e);
+                break false;
             }
-        } else {
-            false
         }
     };
 
-    // If no more connections, shutdown the RPC server
-    if should_shutdown {
-        info!("No more connections for tx: {:?}, shutting down RPC server", tx_hash);
-
-        // Shutdown the RPC server using Engine's shutdown method
-        if let Err(e) = state.engine.shutdown_rpc_server(&tx_hash) {
-            warn!("Error shutting down RPC server: {}", e);
-        } else {
-            info!("RPC server shutdown successfully for tx: {:?}", tx_hash);
-        }
+    if closed {
+        info!("Connection closed for tx: {:?}", tx_hash);
+        state.release_client(tx_hash, client_id).await;
+    } else {
+        state.detach_client(tx_hash, client_id, generation).await;
     }
 }
 
@@ -322,6 +818,7 @@ pub fn spawn_worker(
     engine: Arc<Engine>,
     rpc_url: String,
     quick: bool,
+    refetch: bool,
 ) -> mpsc::UnboundedSender<WorkerMessage> {
     let (worker_tx, worker_rx) = mpsc::unbounded_channel();
 
@@ -333,7 +830,7 @@ pub fn spawn_worker(
             .expect("Failed to create worker runtime");
 
         rt.block_on(async move {
-            worker_task(worker_rx, engine, rpc_url, quick).await;
+            worker_task(worker_rx, engine, rpc_url, quick, refetch).await;
         });
     });
 
@@ -346,6 +843,7 @@ async fn worker_task(
     engine: Arc<Engine>,
     rpc_url: String,
     quick: bool,
+    refetch: bool,
 ) {
     info!("Worker task started");
 
@@ -356,14 +854,15 @@ async fn worker_task(
 
                 // Fork and prepare - this contains !Send types
                 progress_tx.send(ProgressMessage::new("Forking and preparing database")).ok();
-                let fork_result = match fork_and_prepare(&rpc_url, tx_hash, quick).await {
-                    Ok(result) => result,
-                    Err(e) => {
-                        error!("Failed to fork and prepare: {}", e);
-                        let _ = response_tx.send(Err(e));
-                        continue;
-                    }
-                };
+                let fork_result =
+                    match fork_and_prepare_cached(&rpc_url, tx_hash, quick, refetch).await {
+                        Ok(result) => result,
+                        Err(e) => {
+                            error!("Failed to fork and prepare: {}", e);
+                            let _ = response_tx.send(Err(e));
+                            continue;
+                        }
+                    };
 
                 // Run engine.prepare - this also uses !Send types
                 progress_tx.send(ProgressMessage::new("Preparing engine")).ok();
@@ -386,3 +885,153 @@ async fn worker_task(
 
     info!("Worker task shutting down");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use edb_engine::EngineConfig;
+
+    /// Server state without RPC servers or worker
+    fn server_state() -> ServerState {
+        let (worker_tx, _) = mpsc::unbounded_channel();
+        ServerState {
+            engine: Arc::new(Engine::new(EngineConfig::default())),
+            active_connections: Arc::new(Mutex::new(HashMap::new())),
+            worker_tx,
+            collaboration: Arc::new(Mutex::new(HashMap::new())),
+            next_client_id: Arc::new(AtomicU64::new(1)),
+            security: RpcSecurityConfig::default(),
+        }
+    }
+
+    /// Resume token of an attachment response
+    fn resume_token(attached: &ServerResponse) -> String {
+        match attached {
+            ServerResponse::Attached { resume_token, .. } => resume_token.clone(),
+            other => panic!("expected an attachment, got {other:?}"),
+        }
+    }
+
+    /// Updates already delivered to a connection
+    fn received(attachment: &mut Attachment) -> Vec<ServerResponse> {
+        std::iter::from_fn(|| attachment.updates.try_recv().ok()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_missed_updates() {
+        let state = server_state();
+        let tx_hash = TxHash::repeat_byte(1);
+
+        let (driver, _) = state.join_session(tx_hash, ClientRole::Driver).await;
+        let (observer, attached) = state.join_session(tx_hash, ClientRole::Observer).await;
+        let token = resume_token(&attached);
+        state.detach_client(tx_hash, observer.client_id, observer.generation).await;
+        drop(observer);
+
+        // The session goes on without the observer
+        state.move_cursor(tx_hash, driver.client_id, 3).await;
+        state.move_cursor(tx_hash, driver.client_id, 7).await;
+
+        assert_eq!(state.find_resumable(&token).await, Some(tx_hash));
+        let (mut resumed, attached) = state.resume_session(tx_hash, &token).await.unwrap();
+        assert!(matches!(attached, ServerResponse::Attached { resumed: true, missed: 2, .. }));
+        assert_eq!(resumed.role, ClientRole::Observer);
+
+        let updates = received(&mut resumed);
+        assert!(matches!(
+            updates.as_slice(),
+            [
+                ServerResponse::Cursor { snapshot_id: 3, .. },
+                ServerResponse::Cursor { snapshot_id: 7, .. }
+            ]
+        ));
+
+        // Once resumed, updates are delivered directly
+        state.move_cursor(tx_hash, driver.client_id, 9).await;
+        assert!(matches!(
+            received(&mut resumed).as_slice(),
+            [ServerResponse::Cursor { snapshot_id: 9, .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resume_with_stale_cursor_ends_at_current_cursor() {
+        let state = server_state();
+        let tx_hash = TxHash::repeat_byte(2);
+
+        let (driver, _) = state.join_session(tx_hash, ClientRole::Driver).await;
+        let (observer, attached) = state.join_session(tx_hash, ClientRole::Observer).await;
+        let token = resume_token(&attached);
+        state.detach_client(tx_hash, observer.client_id, observer.generation).await;
+        drop(observer);
+
+        // More moves than can be buffered: the oldest are dropped, the latest is kept
+        for snapshot_id in 0..MAX_MISSED_UPDATES + 10 {
+            state.move_cursor(tx_hash, driver.client_id, snapshot_id).await;
+        }
+
+        let (mut resumed, attached) = state.resume_session(tx_hash, &token).await.unwrap();
+        assert!(matches!(
+            attached,
+            ServerResponse::Attached { missed, .. } if missed == MAX_MISSED_UPDATES
+        ));
+        let updates = received(&mut resumed);
+        let latest = MAX_MISSED_UPDATES + 9;
+        assert_eq!(updates.len(), MAX_MISSED_UPDATES);
+        assert!(matches!(updates.first(), Some(ServerResponse::Cursor { snapshot_id: 10, .. })));
+        assert!(matches!(
+            updates.last(),
+            Some(ServerResponse::Cursor { snapshot_id, .. }) if *snapshot_id == latest
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resume_takes_over_connected_client() {
+        let state = server_state();
+        let tx_hash = TxHash::repeat_byte(3);
+
+        let (mut first, attached) = state.join_session(tx_hash, ClientRole::Driver).await;
+        let token = resume_token(&attached);
+
+        let (second, attached) = state.resume_session(tx_hash, &token).await.unwrap();
+        assert!(matches!(attached, ServerResponse::Attached { resumed: true, missed: 0, .. }));
+        assert_eq!(second.client_id, first.client_id);
+        assert!(second.generation > first.generation);
+        assert!((&mut first.takeover).await.is_ok());
+
+        // The dropped connection of the taken over client does not detach the new one
+        state.detach_client(tx_hash, first.client_id, first.generation).await;
+        let collaboration = state.collaboration.lock().await;
+        let client = &collaboration[&tx_hash].clients[&second.client_id];
+        assert!(client.connection.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resume_unknown_session() {
+        let state = server_state();
+        let tx_hash = TxHash::repeat_byte(4);
+
+        let (_, attached) = state.join_session(tx_hash, ClientRole::Driver).await;
+        let token = resume_token(&attached);
+
+        // Unknown tokens, and tokens of another session, do not resume anything
+        assert_eq!(state.find_resumable("unknown").await, None);
+        assert!(state.resume_session(tx_hash, "unknown").await.is_none());
+        assert!(state.resume_session(TxHash::repeat_byte(5), &token).await.is_none());
+        assert!(matches!(
+            handle_resume_request("unknown", &state).await,
+            ServerResponse::Error { message } if message.contains("Unknown")
+        ));
+
+        // The place is still held, but the RPC server of the session is gone
+        assert!(matches!(
+            handle_resume_request(&token, &state).await,
+            ServerResponse::Error { message } if message.contains("has ended")
+        ));
+
+        // Released clients cannot resume
+        let client_id = state.collaboration.lock().await[&tx_hash].clients.keys().copied().next();
+        state.leave_session(tx_hash, client_id.unwrap()).await;
+        assert_eq!(state.find_resumable(&token).await, None);
+    }
+}
@@ -0,0 +1,46 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Trace command - print the decoded call tree of a transaction without a debugging session
+
+use std::io::IsTerminal;
+
+use alloy_primitives::TxHash;
+use edb_common::fork_and_prepare_cached;
+use edb_engine::{cast_call_tree, colored_call_tree, Engine};
+use eyre::Result;
+
+/// Replay a transaction and print its decoded call tree, like `cast run`
+///
+/// Contracts are neither instrumented nor recompiled, so this is much faster than preparing
+/// a full debugging session.
+pub async fn trace_transaction(tx_hash: TxHash, cli: &crate::Cli, rpc_url: &str) -> Result<()> {
+    tracing::info!("Starting transaction trace workflow");
+
+    let fork_result = fork_and_prepare_cached(rpc_url, tx_hash, cli.quick, cli.refetch).await?;
+
+    let engine = Engine::new(cli.to_engine_config(rpc_url).with_quiet(true));
+    let context = engine.decode_trace(fork_result).await?;
+
+    // Only colorize when printing to a terminal, so that the output can be piped
+    if std::io::stdout().is_terminal() {
+        print!("{}", colored_call_tree(&context));
+    } else {
+        print!("{}", cast_call_tree(&context));
+    }
+
+    Ok(())
+}
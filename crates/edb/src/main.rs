@@ -18,19 +18,24 @@
 //!
 //! A step-by-step debugger for Ethereum transactions.
 
-use std::env;
+use std::{
+    env,
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+};
 
 use alloy_primitives::TxHash;
 use clap::{Parser, Subcommand};
-use edb_engine::EngineConfig;
+use edb_engine::{
+    EngineConfig, MockContract, RpcSecurityConfig, SolcConfig, TraceFocus, TraceFormat,
+};
 use eyre::Result;
 
-use crate::utils::TuiOptions;
+use crate::utils::{self, TuiOptions, UiMode, WebUiOptions};
 
 mod cmd;
 mod proxy;
 mod utils;
-mod ws_protocol;
 
 /// Command-line interface for EDB
 #[derive(Debug, Parser)]
@@ -51,13 +56,78 @@ pub struct Cli {
     pub proxy_port: u16,
 
     /// Etherscan API key for source code download
+    ///
+    /// Etherscan v2 keys work for every chain Etherscan supports. Chains using another
+    /// explorer or key are configured in `~/.edb.toml`, e.g. `[explorers.100]` with
+    /// `api_url = "https://gnosis.blockscout.com/api"`. Several comma-separated keys are
+    /// rotated, and a key hitting the rate limit is set aside for a while.
     #[arg(long, env = "ETHERSCAN_API_KEY")]
     pub etherscan_api_key: Option<String>,
 
+    /// Never query block explorers, only use the verified sources cached by earlier sessions
+    #[arg(long)]
+    pub offline_explorer: bool,
+
+    /// Mirror of `https://binaries.soliditylang.org` to download solc binaries from
+    ///
+    /// Downloaded binaries are verified against the checksums of the mirror's `list.json`.
+    #[arg(long, value_name = "URL", env = edb_common::env::EDB_SOLC_MIRROR)]
+    pub solc_mirror: Option<String>,
+
+    /// Directory of solc binaries checked before any download
+    ///
+    /// It is laid out like a platform directory of `binaries.soliditylang.org` (e.g.,
+    /// `linux-amd64`): a `list.json` and the binaries it lists, verified against its
+    /// checksums.
+    #[arg(long, value_name = "DIR", env = edb_common::env::EDB_SOLC_DIR)]
+    pub solc_dir: Option<PathBuf>,
+
+    /// Never download solc binaries, only use `--solc-dir` and installed versions
+    #[arg(long)]
+    pub offline_solc: bool,
+
+    /// Install a mock contract before replay, compiled from a Solidity source file
+    ///
+    /// Replaces the code at ADDRESS with the runtime code of the contract in PATH, e.g.,
+    /// a fixed-price oracle. CONTRACT selects the contract if the file defines several.
+    /// Can be given multiple times.
+    #[arg(long = "mock", value_name = "ADDRESS=PATH[:CONTRACT]", value_parser = MockContract::from_spec)]
+    pub mocks: Vec<MockContract>,
+
+    /// RPC endpoint of another chain, used to correlate and debug bridged transactions
+    ///
+    /// E.g., `--chain-rpc 10=https://mainnet.optimism.io` to follow deposits from L1 to
+    /// OP Mainnet, or `--chain-rpc 1=<URL>` to find the L1 transaction finalizing a
+    /// withdrawal. Can be given multiple times.
+    #[arg(long = "chain-rpc", value_name = "CHAIN_ID=URL", value_parser = utils::parse_chain_rpc)]
+    pub chain_rpcs: Vec<(u64, String)>,
+
+    /// Focus preparation on the subtree of a single call of the trace
+    ///
+    /// Every step is recorded inside the subtree of the call, and only the first step of
+    /// each frame elsewhere, to keep huge transactions tractable. The call is given as a
+    /// trace entry ID, as shown in the trace tree, or as `ADDRESS#N` for the N-th call
+    /// (starting at 1) to a contract.
+    #[arg(long, value_name = "ENTRY_ID|ADDRESS[#N]", value_parser = TraceFocus::from_spec)]
+    pub focus: Option<TraceFocus>,
+
+    /// Expression evaluated at every snapshot while preparing the transaction
+    ///
+    /// The values are included in the snapshot information sent to the user interface,
+    /// so they are shown at every step without further evaluation requests. Can be given
+    /// multiple times.
+    #[arg(long = "watch", value_name = "EXPR")]
+    pub watches: Vec<String>,
+
     /// Quick mode - skip replaying preceding transactions in the block
     #[arg(long)]
     pub quick: bool,
 
+    /// Refetch - ignore the cached prestate of the transaction and replay the preceding
+    /// transactions again
+    #[arg(long)]
+    pub refetch: bool,
+
     /// Disable cache - do not use cached RPC responses
     #[arg(long)]
     pub disable_cache: bool,
@@ -66,10 +136,32 @@ pub struct Cli {
     #[arg(long, env = edb_common::env::EDB_CACHE_DIR)]
     pub cache_dir: Option<String>,
 
+    /// Serve the preparation progress at `http://127.0.0.1:<port>/status` while a
+    /// transaction is being prepared
+    #[arg(long)]
+    pub status_port: Option<u16>,
+
+    /// Serve the engine RPC server of `replay` on a unix domain socket instead of a TCP
+    /// port, e.g. `/tmp/edb.sock`
+    ///
+    /// The socket is only accessible to the current user, which avoids exposing the
+    /// debugging session to other users of a shared machine.
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH")]
+    pub ipc: Option<PathBuf>,
+
+    /// User interface to debug with
+    #[arg(long, value_enum, default_value_t = UiMode::Tui)]
+    pub ui: UiMode,
+
     /// TUI-specific options
     #[command(flatten)]
     pub tui_options: TuiOptions,
 
+    /// Web UI-specific options
+    #[command(flatten)]
+    pub webui_options: WebUiOptions,
+
     /// Command to execute
     #[command(subcommand)]
     pub command: Commands,
@@ -79,22 +171,63 @@ impl Cli {
     /// Validate CLI arguments and warn about misused options
     pub fn validate(&self) {
         // Warn if TUI options are used with non-TUI mode
-        if !self.command.enables_tui() && self.tui_options.disable_mouse {
+        let launches_ui = self.command.launches_ui();
+        if !(launches_ui && self.ui == UiMode::Tui) && self.tui_options.disable_mouse {
             tracing::warn!("--disable-mouse flag has no effect when not using TUI");
             eprintln!("Warning: --disable-mouse flag has no effect when not using TUI");
         }
+
+        // Warn if Web UI options are used with non-Web UI mode
+        if !(launches_ui && self.ui == UiMode::Web) && self.webui_options.no_browser {
+            tracing::warn!("--no-browser flag has no effect when not using the Web UI");
+            eprintln!("Warning: --no-browser flag has no effect when not using the Web UI");
+        }
+
+        // Warn if the unix socket is requested by a command that does not serve one
+        #[cfg(unix)]
+        if self.ipc.is_some() && !matches!(self.command, Commands::Replay { report: None, .. }) {
+            tracing::warn!("--ipc flag has no effect outside of `replay`");
+            eprintln!("Warning: --ipc flag has no effect outside of `replay`");
+        }
     }
 
     /// Derive EDB engine configuration from CLI arguments
     pub fn to_engine_config(&self, rpc_url: &str) -> EngineConfig {
         let mut engine_config = EngineConfig::default()
             .with_quick_mode(self.quick)
+            .with_refetch(self.refetch)
+            .with_explorer_offline(self.offline_explorer)
+            .with_solc(SolcConfig {
+                mirror: self.solc_mirror.clone(),
+                local_dir: self.solc_dir.clone(),
+                offline: self.offline_solc,
+            })
             .with_rpc_proxy_url(rpc_url.to_string());
         if let Some(api_key) = &self.etherscan_api_key {
             engine_config = engine_config.with_etherscan_api_key(api_key.clone());
         }
+        for (chain_id, explorer) in utils::configured_explorers() {
+            engine_config = engine_config.with_explorer(chain_id, explorer);
+        }
+        for mock in &self.mocks {
+            engine_config = engine_config.with_mock(mock.clone());
+        }
+        for (chain_id, url) in &self.chain_rpcs {
+            engine_config = engine_config.with_chain_rpc_url(*chain_id, url.clone());
+        }
+        if let Some(focus) = self.focus {
+            engine_config = engine_config.with_focus(focus);
+        }
+        for expr in &self.watches {
+            engine_config = engine_config.with_watch(expr.clone());
+        }
         engine_config
     }
+
+    /// RPC endpoint of another chain given with `--chain-rpc`
+    pub fn chain_rpc_url(&self, chain_id: u64) -> Option<&str> {
+        self.chain_rpcs.iter().rev().find(|(id, _)| *id == chain_id).map(|(_, url)| url.as_str())
+    }
 }
 
 /// Available commands
@@ -104,6 +237,18 @@ pub enum Commands {
     Replay {
         /// Transaction hash to replay
         tx_hash: String,
+
+        /// Skip the TUI and print a report of the transaction to stdout instead.
+        ///
+        /// Exits with 0 if the transaction succeeded, 2 if it reverted, 3 if it halted,
+        /// and 1 if the report could not be produced.
+        #[arg(long, value_name = "FORMAT")]
+        report: Option<cmd::ReportFormat>,
+    },
+    /// Print the decoded call tree of a transaction without preparing a debugging session
+    Trace {
+        /// Transaction hash to trace
+        tx_hash: String,
     },
     /// Debug a Foundry test case
     Test {
@@ -113,20 +258,129 @@ pub enum Commands {
         /// Block number to fork at (default: latest)
         block: Option<u64>,
     },
+    /// Export data of a transaction's debugging session without launching a UI
+    Export {
+        /// Transaction hash to export
+        tx_hash: String,
+
+        /// Write the gas spent per call path as a flamegraph: an SVG for `.svg` files,
+        /// folded stacks (for inferno or speedscope) otherwise
+        #[arg(long, value_name = "PATH")]
+        flamegraph: Option<PathBuf>,
+
+        /// Weight flamegraph frames by `gas` or by the wall-clock `time` of the replay
+        #[arg(long, value_name = "WEIGHT", default_value = "gas", value_parser = ["gas", "time"])]
+        flamegraph_weight: String,
+
+        /// Write the source line coverage (LCOV for `.info`/`.lcov` files, JSON otherwise)
+        #[arg(long, value_name = "PATH")]
+        coverage: Option<PathBuf>,
+
+        /// Export the decoded call trace: `json`, `cast`, `tenderly`, or `parity`
+        #[arg(long, value_name = "FORMAT")]
+        format: Option<TraceFormat>,
+
+        /// Write the decoded call trace to this file instead of stdout
+        #[arg(long, short, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Include the state of every snapshot in the decoded call trace (`json` only)
+        #[arg(long)]
+        snapshots: bool,
+    },
+    /// Debug a transaction from `debug_traceTransaction` results instead of replaying it
+    ///
+    /// Only trace navigation and storage diffing are available in such sessions.
+    Import {
+        /// Transaction hash the traces belong to
+        tx_hash: String,
+
+        /// Result (or JSON-RPC response) of the `callTracer`, ideally with `withLog: true`
+        #[arg(long, value_name = "PATH")]
+        call_trace: PathBuf,
+
+        /// Result (or JSON-RPC response) of the `prestateTracer`, preferably with
+        /// `diffMode: true` to enable storage diffing
+        #[arg(long, value_name = "PATH")]
+        prestate: Option<PathBuf>,
+    },
     /// Start WebSocket server for remote debugging sessions
     Server {
         /// Port for the WebSocket server
         #[arg(long, default_value = "9001")]
         ws_port: u16,
+
+        /// Also serve all debugging sessions through a single JSON-RPC session manager on
+        /// this port (`edb_createSession`, `edb_listSessions`, `/session/{id}`)
+        #[arg(long)]
+        session_port: Option<u16>,
+
+        /// Address the WebSocket and debug RPC servers listen on, e.g. `0.0.0.0` to
+        /// accept connections from other machines
+        #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+        bind: IpAddr,
+
+        /// Require clients of the WebSocket and debug RPC servers to present this bearer
+        /// token
+        #[arg(long, env = edb_common::RPC_AUTH_TOKEN_ENV, hide_env_values = true)]
+        auth_token: Option<String>,
+
+        /// PEM certificate chain to serve the WebSocket and debug RPC servers over TLS
+        #[arg(long, requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+
+        /// PEM private key to serve the WebSocket and debug RPC servers over TLS
+        #[arg(long, requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+    },
+    /// Launch the UI against an engine RPC server that is already running
+    ///
+    /// No transaction is prepared and no RPC proxy is started, e.g. to debug from a laptop
+    /// a session prepared by `edb server` on another machine.
+    Attach {
+        /// URL of the engine RPC server, e.g. `http://debug-box:3030` or
+        /// `unix:///tmp/edb.sock`
+        url: String,
+
+        /// Bearer token of the engine RPC server
+        #[arg(long, env = edb_common::RPC_AUTH_TOKEN_ENV, hide_env_values = true)]
+        auth_token: Option<String>,
+
+        /// PEM certificate to trust when the engine RPC server is served over HTTPS with a
+        /// self-signed certificate
+        #[arg(long)]
+        ca_cert: Option<PathBuf>,
     },
     /// Show RPC proxy provider status
     ProxyStatus,
+    /// Manage the cache directory
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+}
+
+/// Cache maintenance commands
+#[derive(Debug, Subcommand)]
+pub enum CacheCommands {
+    /// Remove the least recently used cache files until the cache fits in a size
+    Gc {
+        /// Size to trim the cache to, e.g. `500M` or `10G`
+        #[arg(long, value_parser = cmd::cache::parse_max_size)]
+        max_size: u64,
+    },
 }
 
 impl Commands {
-    /// Whether the command enables a TUI
-    pub fn enables_tui(&self) -> bool {
-        matches!(self, Self::Replay { .. } | Self::Test { .. })
+    /// Whether the command launches the UI chosen with `--ui`
+    pub fn launches_ui(&self) -> bool {
+        matches!(
+            self,
+            Self::Replay { report: None, .. }
+                | Self::Test { .. }
+                | Self::Import { .. }
+                | Self::Attach { .. }
+        )
     }
 }
 
@@ -149,6 +403,22 @@ async fn main() -> Result<()> {
         env::set_var(edb_common::env::EDB_CACHE_DIR, cache_dir);
     }
 
+    // Attaching to a running engine and maintaining the cache need no upstream RPC
+    match &cli.command {
+        Commands::Attach { url, auth_token, ca_cert } => {
+            let rpc_options = edb_common::RpcClientOptions {
+                auth_token: auth_token.clone(),
+                ca_cert: ca_cert.clone(),
+                timeout: None,
+            };
+            return cmd::attach(url, &rpc_options, &cli).await;
+        }
+        Commands::Cache { command: CacheCommands::Gc { max_size } } => {
+            return cmd::gc_cache(*max_size)
+        }
+        _ => {}
+    }
+
     // Set up RPC endpoint (proxy or direct)
     let effective_rpc_url = {
         tracing::info!("Ensuring RPC proxy is running...");
@@ -160,18 +430,79 @@ async fn main() -> Result<()> {
 
     // Execute the command to get RPC server handle
     match &cli.command {
-        Commands::Replay { tx_hash } => {
+        Commands::Replay { tx_hash, report } => {
             tracing::info!("Replaying transaction: {}", tx_hash);
             let tx_hash: TxHash = tx_hash.parse()?;
-            cmd::replay_transaction(tx_hash, &cli, &effective_rpc_url).await
+            match report {
+                Some(format) => {
+                    let status =
+                        cmd::report_transaction(tx_hash, *format, &cli, &effective_rpc_url).await?;
+                    std::process::exit(cmd::report_exit_code(status))
+                }
+                None => cmd::replay_transaction(tx_hash, &cli, &effective_rpc_url).await,
+            }
+        }
+        Commands::Trace { tx_hash } => {
+            tracing::info!("Tracing transaction: {}", tx_hash);
+            let tx_hash: TxHash = tx_hash.parse()?;
+            cmd::trace_transaction(tx_hash, &cli, &effective_rpc_url).await
         }
         Commands::Test { test_name, block } => {
             tracing::info!("Debugging test: {}", test_name);
             cmd::debug_foundry_test(test_name, *block, &cli, &effective_rpc_url).await
         }
-        Commands::Server { ws_port } => {
+        Commands::Export {
+            tx_hash,
+            flamegraph,
+            flamegraph_weight,
+            coverage,
+            format,
+            output,
+            snapshots,
+        } => {
+            tracing::info!("Exporting transaction: {}", tx_hash);
+            let tx_hash: TxHash = tx_hash.parse()?;
+            let options = cmd::ExportOptions {
+                flamegraph: flamegraph.clone(),
+                flamegraph_weight: flamegraph_weight.clone(),
+                coverage: coverage.clone(),
+                format: *format,
+                output: output.clone(),
+                snapshots: *snapshots,
+            };
+            cmd::export_transaction(tx_hash, &options, &cli, &effective_rpc_url).await
+        }
+        Commands::Import { tx_hash, call_trace, prestate } => {
+            tracing::info!("Importing traces of transaction: {}", tx_hash);
+            let tx_hash: TxHash = tx_hash.parse()?;
+            cmd::import_transaction(
+                tx_hash,
+                call_trace,
+                prestate.as_ref(),
+                &cli,
+                &effective_rpc_url,
+            )
+            .await
+        }
+        Commands::Server { ws_port, session_port, bind, auth_token, tls_cert, tls_key } => {
             tracing::info!("Starting WebSocket server on port {}", ws_port);
-            cmd::start_server(*ws_port, &cli, &effective_rpc_url).await
+            let mut security = RpcSecurityConfig::default().with_bind_addr(*bind);
+            if !bind.is_loopback() && auth_token.is_none() {
+                tracing::warn!(
+                    "Listening on {} without --auth-token, anyone reaching it can start sessions",
+                    bind
+                );
+            }
+            if let Some(token) = auth_token {
+                security = security.with_auth_token(token.clone());
+            }
+            if let (Some(cert), Some(key)) = (tls_cert, tls_key) {
+                security = security.with_tls(cert.clone(), key.clone());
+            }
+            cmd::start_server(*ws_port, *session_port, security, &cli, &effective_rpc_url).await
+        }
+        Commands::Attach { .. } | Commands::Cache { .. } => {
+            unreachable!("handled before the RPC proxy is started")
         }
         Commands::ProxyStatus => cmd::show_proxy_status(&cli).await,
     }
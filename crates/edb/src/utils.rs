@@ -16,11 +16,20 @@
 
 //! Utility functions for the EDB binary
 
-use clap::Args;
+use alloy_primitives::TxHash;
+use clap::{Args, ValueEnum};
+use edb_common::{types::BridgeMessage, EngineRpcClient, RpcClientOptions, RPC_AUTH_TOKEN_ENV};
+use edb_engine::{Engine, ExplorerConfig};
+use edb_webui::WebUiServer;
 use eyre::{eyre, Result};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::io::{IsTerminal, Write};
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 use tracing::debug;
 
 /// Find a binary in the following order:
@@ -86,6 +95,167 @@ pub fn find_binary(name: &str) -> Result<PathBuf> {
     ))
 }
 
+/// Interval at which the progress of a preparation is polled
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Await the preparation of a transaction while reporting its progress
+///
+/// The current phase is printed to stderr whenever it changes. If a status port is
+/// given, the progress is also served at `GET /status` until the preparation finishes.
+/// Ctrl+C cancels the preparation at its next checkpoint, and a second Ctrl+C exits
+/// right away.
+pub async fn with_preparation_progress<T>(
+    engine: &Engine,
+    tx_hash: TxHash,
+    status_port: Option<u16>,
+    preparation: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let status_server = match status_port {
+        Some(port) => {
+            let handle = engine.serve_status(port).await?;
+            eprintln!("Preparation status: http://{}/status/{tx_hash}", handle.addr());
+            Some(handle)
+        }
+        None => None,
+    };
+
+    let preparations = engine.preparations().clone();
+    let reporter = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(PROGRESS_POLL_INTERVAL);
+        let mut last_phase = None;
+        loop {
+            ticker.tick().await;
+            let Some(status) = preparations.latest(&tx_hash) else { continue };
+            if status.is_finished() {
+                break;
+            }
+            if last_phase.as_ref() != Some(&status.phase) {
+                eprintln!("{status}");
+                last_phase = Some(status.phase);
+            }
+        }
+    });
+
+    let preparations = engine.preparations().clone();
+    let interrupt = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        if preparations.cancel_transaction(&tx_hash) {
+            eprintln!("Cancelling preparation, press Ctrl+C again to exit immediately...");
+            if tokio::signal::ctrl_c().await.is_err() {
+                return;
+            }
+        }
+        std::process::exit(130);
+    });
+
+    let result = preparation.await;
+    reporter.abort();
+    interrupt.abort();
+    if let Some(handle) = status_server {
+        handle.shutdown()?;
+    }
+    result
+}
+
+/// Block explorers configured in `~/.edb.toml` and the project's `.edb.toml`, by chain ID
+///
+/// An invalid configuration is reported and ignored, so that sources are still fetched
+/// from Etherscan.
+pub fn configured_explorers() -> BTreeMap<u64, ExplorerConfig> {
+    let parse = || -> Result<BTreeMap<u64, ExplorerConfig>> {
+        let config = edb_tui::Config::load()?;
+        let explorers: BTreeMap<String, ExplorerConfig> =
+            serde_json::from_value(serde_json::to_value(&config.explorers)?)?;
+        explorers
+            .into_iter()
+            .map(|(chain_id, explorer)| {
+                let chain_id = chain_id
+                    .parse()
+                    .map_err(|_| eyre!("Expected a chain ID, got [explorers.{chain_id}]"))?;
+                Ok((chain_id, explorer))
+            })
+            .collect()
+    };
+
+    parse().unwrap_or_else(|e| {
+        tracing::warn!("Ignoring the explorers of the config: {}", e);
+        eprintln!("Warning: ignoring the explorers of the config: {e}");
+        BTreeMap::new()
+    })
+}
+
+/// Parse a `--chain-rpc` argument given as `CHAIN_ID=URL`
+pub fn parse_chain_rpc(spec: &str) -> Result<(u64, String), String> {
+    let (chain_id, url) = spec.split_once('=').ok_or("Expected CHAIN_ID=URL")?;
+    let chain_id =
+        chain_id.trim().parse().map_err(|e| format!("Invalid chain ID {chain_id:?}: {e}"))?;
+    Ok((chain_id, url.trim().to_string()))
+}
+
+/// Offer to debug the transaction on the other chain of a bridged flow, once the UI of
+/// the session sending the bridge message exited
+///
+/// Returns the chosen transaction and the RPC endpoint of its chain, if any.
+pub async fn offer_bridged_transaction(
+    cli: &crate::Cli,
+    rpc_server_addr: SocketAddr,
+) -> Option<(TxHash, String)> {
+    let messages = query_debug_server(rpc_server_addr, "edb_getBridgeMessages", json!([]))
+        .await
+        .and_then(|value| Ok(serde_json::from_value::<Vec<BridgeMessage>>(value)?));
+    let messages = match messages {
+        Ok(messages) => messages,
+        Err(e) => {
+            debug!("Failed to get the bridge messages: {}", e);
+            return None;
+        }
+    };
+
+    for message in messages {
+        let chain_id = message.correlated_chain_id();
+        let Some(tx_hash) = message.correlated_tx_hash else {
+            match cli.chain_rpc_url(chain_id) {
+                Some(_) => println!("🌉 Bridge {}: not finalized on L1 yet", message.summary()),
+                None => println!(
+                    "🌉 Bridge {}: pass --chain-rpc {chain_id}=<URL> to find its L1 transaction",
+                    message.summary()
+                ),
+            }
+            continue;
+        };
+        let Some(url) = cli.chain_rpc_url(chain_id) else {
+            println!(
+                "🌉 Bridge {}: correlated transaction {tx_hash} on chain {chain_id}, pass \
+                 --chain-rpc {chain_id}=<URL> to debug it",
+                message.summary()
+            );
+            continue;
+        };
+        if !std::io::stdin().is_terminal() {
+            println!(
+                "🌉 Bridge {}: correlated transaction {tx_hash} on chain {chain_id}",
+                message.summary()
+            );
+            continue;
+        }
+
+        print!(
+            "🌉 Bridge {}: debug correlated transaction {tx_hash} on chain {chain_id}? [y/N] ",
+            message.summary()
+        );
+        std::io::stdout().flush().ok();
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_ok()
+            && matches!(answer.trim(), "y" | "Y" | "yes")
+        {
+            return Some((tx_hash, url.to_string()));
+        }
+    }
+    None
+}
+
 /// Helper function to find the edb-rpc-proxy binary
 pub fn find_proxy_binary() -> Result<PathBuf> {
     find_binary("edb-rpc-proxy")
@@ -96,6 +266,17 @@ pub fn find_tui_binary() -> Result<PathBuf> {
     find_binary("edb-tui")
 }
 
+/// Frontend a debugging session is served to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UiMode {
+    /// Terminal UI
+    Tui,
+    /// Web UI, opened in the browser
+    Web,
+    /// No UI, only the engine RPC server, e.g. for scripts or a later `edb attach`
+    None,
+}
+
 /// TUI-specific options
 #[derive(Debug, Args)]
 #[command(next_help_heading = "Terminal UI Options (only apply with --ui=tui)")]
@@ -105,7 +286,53 @@ pub struct TuiOptions {
     pub disable_mouse: bool,
 }
 
-pub async fn start_tui(options: &TuiOptions, rpc_server_addr: SocketAddr) -> Result<()> {
+/// Web UI-specific options
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Web UI Options (only apply with --ui=web)")]
+pub struct WebUiOptions {
+    /// Port the Web UI is served on
+    #[arg(long, default_value = "8080")]
+    pub web_port: u16,
+
+    /// Do not open the Web UI in a browser, only print its URL
+    #[arg(long)]
+    pub no_browser: bool,
+}
+
+/// Launch the UI chosen with `--ui` against the engine RPC server at an address
+pub async fn start_ui(cli: &crate::Cli, rpc_server_addr: SocketAddr) -> Result<()> {
+    launch_ui(cli, &format!("http://{rpc_server_addr}"), &RpcClientOptions::default()).await
+}
+
+/// Launch the UI chosen with `--ui` against the engine RPC server at a URL, and wait for
+/// it to exit or for Ctrl+C
+///
+/// `rpc_options` hold the bearer token and CA certificate of the server, if it requires
+/// them.
+pub async fn launch_ui(
+    cli: &crate::Cli,
+    rpc_url: &str,
+    rpc_options: &RpcClientOptions,
+) -> Result<()> {
+    match cli.ui {
+        UiMode::Tui => launch_tui(&cli.tui_options, rpc_url, rpc_options).await,
+        UiMode::Web => launch_webui(&cli.webui_options, rpc_url, rpc_options).await,
+        UiMode::None => {
+            println!("Engine RPC server listening on {rpc_url}");
+            println!("Press Ctrl+C to exit.");
+            tokio::signal::ctrl_c().await?;
+            tracing::info!("Received Ctrl+C, shutting down...");
+            Ok(())
+        }
+    }
+}
+
+/// Launch the Terminal UI against the engine RPC server at a URL, and wait for it to exit
+pub async fn launch_tui(
+    options: &TuiOptions,
+    rpc_url: &str,
+    rpc_options: &RpcClientOptions,
+) -> Result<()> {
     // Launch Terminal UI
     tracing::info!("Launching Terminal UI...");
 
@@ -115,20 +342,97 @@ pub async fn start_tui(options: &TuiOptions, rpc_server_addr: SocketAddr) -> Res
 
     // Spawn TUI as a child process with inherited stdio
     let mut cmd = tokio::process::Command::new(&tui_binary);
-    cmd.arg("--url").arg(format!("http://{rpc_server_addr}"));
+    cmd.arg("--url").arg(rpc_url);
+
+    // The token is passed through the environment to keep it out of the process list
+    if let Some(token) = &rpc_options.auth_token {
+        cmd.env(RPC_AUTH_TOKEN_ENV, token);
+    }
+    if let Some(ca_cert) = &rpc_options.ca_cert {
+        cmd.arg("--ca-cert").arg(ca_cert);
+    }
 
     // Only pass --mouse flag if requested and using TUI mode
     if !options.disable_mouse {
         cmd.arg("--mouse");
     }
 
-    let mut ui_handle = cmd
+    let ui_handle = cmd
         .stdin(std::process::Stdio::inherit())
         .stdout(std::process::Stdio::inherit())
         .stderr(std::process::Stdio::inherit())
         .spawn()
         .map_err(|e| eyre::eyre!("Failed to spawn TUI: {}", e))?;
 
+    // Logs written to the terminal would corrupt the TUI, so only keep them in the log
+    // file until it exits
+    edb_common::logging::set_console_logging(false);
+    let result = wait_for_ui(ui_handle).await;
+    edb_common::logging::set_console_logging(true);
+    result
+}
+
+/// Serve the Web UI against the engine RPC server at a URL, open it in the browser, and
+/// wait for Ctrl+C
+///
+/// The Web UI is served from this process, with its frontend assets embedded in the
+/// binary, and reaches the engine RPC server with `rpc_options`.
+pub async fn launch_webui(
+    options: &WebUiOptions,
+    rpc_url: &str,
+    rpc_options: &RpcClientOptions,
+) -> Result<()> {
+    tracing::info!("Launching Web UI...");
+
+    let engine = EngineRpcClient::new(rpc_url, rpc_options)?;
+    let handle = WebUiServer::new(engine)
+        .start_on_port(options.web_port)
+        .await
+        .map_err(|e| eyre!("Failed to serve the Web UI on port {}: {}", options.web_port, e))?;
+
+    let web_url = handle.url();
+    println!("Web UI available at {web_url}");
+    if !options.no_browser {
+        if let Err(e) = open_browser(&web_url).await {
+            tracing::warn!("Failed to open the browser: {}", e);
+            eprintln!("Could not open a browser, visit {web_url} instead");
+        }
+    }
+
+    println!("Press Ctrl+C to exit.");
+    tokio::signal::ctrl_c().await?;
+    tracing::info!("Received Ctrl+C, shutting down...");
+    handle.shutdown()
+}
+
+/// Open a URL in the default browser
+async fn open_browser(url: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut cmd = tokio::process::Command::new("open");
+    #[cfg(windows)]
+    let mut cmd = {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    };
+    #[cfg(not(any(target_os = "macos", windows)))]
+    let mut cmd = tokio::process::Command::new("xdg-open");
+
+    // The openers hand the URL over to the browser and exit
+    let status = cmd
+        .arg(url)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await?;
+    if !status.success() {
+        return Err(eyre!("Browser opener exited with {}", status));
+    }
+    Ok(())
+}
+
+/// Wait for a UI process to exit, or for Ctrl+C
+async fn wait_for_ui(mut ui_handle: tokio::process::Child) -> Result<()> {
     tracing::info!("Both RPC server and UI are running. Press Ctrl+C to exit.");
 
     // Wait for either:
@@ -146,3 +450,35 @@ pub async fn start_tui(options: &TuiOptions, rpc_server_addr: SocketAddr) -> Res
 
     Ok(())
 }
+
+/// Call a JSON-RPC method on a running debug RPC server and return its result
+pub async fn query_debug_server(
+    rpc_server_addr: SocketAddr,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let client = reqwest::Client::new();
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": params,
+        "id": 1
+    });
+
+    let response: serde_json::Value = client
+        .post(format!("http://{rpc_server_addr}"))
+        .json(&request)
+        .send()
+        .await?
+        .json()
+        .await?;
+
+    if let Some(error) = response.get("error") {
+        return Err(eyre!("Debug server returned error for {}: {}", method, error));
+    }
+
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| eyre!("Debug server returned no result for {}", method))
+}
@@ -48,6 +48,15 @@ fn test_replay_subcommand_help() {
         .stdout(predicate::str::contains("Replay an existing transaction"));
 }
 
+#[cfg(unix)]
+#[test]
+fn test_ipc_option_help() {
+    edb_common::logging::ensure_test_logging(None);
+    info!("Running test");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("edb");
+    cmd.arg("--help").assert().success().stdout(predicate::str::contains("--ipc <PATH>"));
+}
+
 #[test]
 fn test_test_subcommand_help() {
     edb_common::logging::ensure_test_logging(None);
@@ -75,3 +84,50 @@ fn test_missing_subcommand() {
     let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("edb");
     cmd.assert().failure().stderr(predicate::str::contains("Usage"));
 }
+
+#[test]
+fn test_attach_unreachable_server() {
+    edb_common::logging::ensure_test_logging(None);
+    info!("Running test");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("edb");
+    cmd.arg("attach")
+        .arg("127.0.0.1:1")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No engine RPC server reachable"));
+}
+
+#[test]
+fn test_invalid_ui() {
+    edb_common::logging::ensure_test_logging(None);
+    info!("Running test");
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("edb");
+    cmd.args(["--ui", "gui", "replay", "invalid_hash"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("possible values: tui, web, none"));
+}
+
+#[test]
+fn test_cache_gc() {
+    edb_common::logging::ensure_test_logging(None);
+    info!("Running test");
+    let cache_dir = std::env::temp_dir().join("edb_test_cli_cache_gc");
+    std::fs::create_dir_all(&cache_dir).unwrap();
+    std::fs::write(cache_dir.join("entry.json"), vec![b'0'; 2048]).unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("edb");
+    cmd.arg("--cache-dir")
+        .arg(&cache_dir)
+        .args(["cache", "gc", "--max-size", "1K"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 files"));
+    assert!(!cache_dir.join("entry.json").exists());
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("edb");
+    cmd.args(["cache", "gc", "--max-size", "lots"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid size"));
+}
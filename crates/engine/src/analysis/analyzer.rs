@@ -29,8 +29,8 @@ use tracing::debug;
 
 use crate::{
     analysis::{
-        ContractRef, FunctionRef, FunctionTypeNameRef, SourceAnalysis, StatementBody, StepRef,
-        UserDefinedTypeRef, VariableRef, VariableScopeRef,
+        Constant, ContractRef, FunctionRef, FunctionTypeNameRef, SourceAnalysis, StatementBody,
+        StepRef, UserDefinedTypeRef, VariableRef, VariableScopeRef,
     },
     utils::{Visitor, VisitorAction, Walk},
 };
@@ -72,6 +72,8 @@ pub struct Analyzer {
     pub(super) variables: HashMap<usize, VariableRef>,
     /// List of all state variables in this file.
     pub(super) state_variables: Vec<VariableRef>,
+    /// Constants with a literal value in this file.
+    pub(super) constants: Vec<Constant>,
     /// State variables that should be made public
     pub(super) private_state_variables: Vec<VariableRef>,
     /// Functions that should be made public
@@ -115,6 +117,7 @@ impl Analyzer {
             functions: Vec::new(),
             variables: HashMap::default(),
             state_variables: Vec::new(),
+            constants: Vec::new(),
             private_state_variables: Vec::new(),
             private_functions: Vec::new(),
             immutable_functions: Vec::new(),
@@ -165,6 +168,7 @@ impl Analyzer {
             contracts: self.contracts,
             private_state_variables: self.private_state_variables,
             state_variables: self.state_variables,
+            constants: self.constants,
             functions,
             private_functions: self.private_functions,
             immutable_functions: self.immutable_functions,
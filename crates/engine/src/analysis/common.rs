@@ -65,12 +65,14 @@ use tracing::debug;
 
 use std::path::PathBuf;
 
+use edb_common::types::SolTypeHint;
 use foundry_compilers::artifacts::SourceUnit;
 
 use crate::{
     analysis::{
-        AnalysisError, Analyzer, ContractRef, FunctionRef, FunctionTypeNameRef, StatementBody,
-        StepRef, UserDefinedTypeRef, VariableScopeRef, UCID, UFID, UTID,
+        dyn_sol_type, sol_type_hint, AnalysisError, Analyzer, Constant, ContractRef, FunctionRef,
+        FunctionTypeNameRef, StatementBody, StepRef, UserDefinedTypeRef, VariableScopeRef, UCID,
+        UFID, UTID,
     },
     ASTPruner, Artifact, VariableRef, USID, UVID,
 };
@@ -112,6 +114,9 @@ pub struct SourceAnalysis {
     pub functions: Vec<FunctionRef>,
     /// List of all state variables in this file.
     pub state_variables: Vec<VariableRef>,
+    /// Constants with a literal value in this file.
+    #[serde(default)]
+    pub constants: Vec<Constant>,
     /// Functions that should be made public
     pub private_functions: Vec<FunctionRef>,
     /// Functions that should be made mutable (i.e., neither pure nor view)
@@ -246,6 +251,29 @@ pub struct AnalysisResult {
     pub utid_to_user_defined_type: HashMap<UTID, UserDefinedTypeRef>,
     /// Maps AST IDs to their corresponding user defined type references
     pub user_defined_types: HashMap<usize, UserDefinedTypeRef>,
+    /// Constants with a literal value in all source files
+    #[serde(default)]
+    pub constants: Vec<Constant>,
+}
+
+impl AnalysisResult {
+    /// Returns the rendering hint of a variable's values.
+    ///
+    /// Besides hints derived from the variable's type, values of a type shared
+    /// with some constants are hinted with these constants.
+    pub fn variable_hint(&self, variable: &VariableRef) -> Option<SolTypeHint> {
+        let type_name = variable.type_name()?;
+        sol_type_hint(&self.user_defined_types, &type_name).or_else(|| {
+            let ty = dyn_sol_type(&self.user_defined_types, &type_name)?;
+            let constants: Vec<_> = self
+                .constants
+                .iter()
+                .filter(|constant| ty.matches(&constant.value))
+                .map(|constant| (constant.name.clone(), constant.value.clone()))
+                .collect();
+            (!constants.is_empty()).then_some(SolTypeHint::Constant { constants })
+        })
+    }
 }
 
 /// Performs comprehensive analysis of Solidity source code.
@@ -333,6 +361,7 @@ pub fn analyze(artifact: &Artifact) -> Result<AnalysisResult, AnalysisError> {
     let mut uvid_to_variable = HashMap::new();
     let mut utid_to_user_defined_type = HashMap::new();
     let mut user_defined_types = HashMap::new();
+    let mut constants = Vec::new();
     for result in source_results.iter() {
         ucid_to_contract.extend(result.contract_table().into_iter());
         ufid_to_function.extend(result.function_table().into_iter());
@@ -340,6 +369,7 @@ pub fn analyze(artifact: &Artifact) -> Result<AnalysisResult, AnalysisError> {
         uvid_to_variable.extend(result.variable_table().into_iter());
         utid_to_user_defined_type.extend(result.user_defined_type_table().into_iter());
         user_defined_types.extend(result.user_defined_types().into_iter());
+        constants.extend(result.constants.iter().cloned());
     }
     let sources = source_results.into_iter().map(|s| (s.id, s)).collect();
 
@@ -351,6 +381,7 @@ pub fn analyze(artifact: &Artifact) -> Result<AnalysisResult, AnalysisError> {
         uvid_to_variable,
         utid_to_user_defined_type,
         user_defined_types,
+        constants,
     })
 }
 
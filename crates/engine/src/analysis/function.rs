@@ -15,7 +15,8 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use foundry_compilers::artifacts::{
-    FunctionDefinition, FunctionTypeName, ModifierDefinition, StateMutability, Visibility,
+    FunctionDefinition, FunctionTypeName, ModifierDefinition, ModifierInvocationKind,
+    StateMutability, Visibility,
 };
 use serde::{Deserialize, Serialize};
 
@@ -70,6 +71,11 @@ impl FunctionRef {
     pub fn src(&self) -> SourceRange {
         self.read().src
     }
+
+    /// Returns the source locations of the modifiers invoked by this function, in order.
+    pub fn modifier_invocations(&self) -> Vec<SourceRange> {
+        self.read().modifier_invocations.clone()
+    }
 }
 
 define_ref! {
@@ -103,6 +109,10 @@ pub struct Function {
     pub state_mutability: Option<StateMutability>,
     /// Whether this is a modifier (true) or a function (false).
     pub is_modifier: bool,
+    /// Source locations of the modifiers invoked by this function, in order
+    /// (empty for modifiers).
+    #[serde(default)]
+    pub modifier_invocations: Vec<SourceRange>,
     /// The scope of this function.
     pub scope: VariableScopeRef,
     /// List of steps in this function.
@@ -131,6 +141,18 @@ impl Function {
             src: definition.src.into(),
             state_mutability: definition.state_mutability.clone(),
             is_modifier: false,
+            // base constructor calls are listed along with modifiers but never evaluated as such
+            modifier_invocations: definition
+                .modifiers
+                .iter()
+                .filter(|invocation| {
+                    !matches!(
+                        invocation.kind,
+                        Some(ModifierInvocationKind::BaseConstructorSpecifier)
+                    )
+                })
+                .map(|invocation| invocation.src.into())
+                .collect(),
             scope,
             steps: vec![],
         }
@@ -150,6 +172,7 @@ impl Function {
             src: definition.src.into(),
             state_mutability: None,
             is_modifier: true,
+            modifier_invocations: vec![],
             scope,
             steps: vec![],
         }
@@ -602,4 +625,52 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_modifier_invocations() {
+        let source = r#"
+        contract Base {
+            constructor(uint256 x) {}
+        }
+
+        contract TestContract is Base {
+            address public owner;
+
+            modifier onlyOwner() {
+                require(msg.sender == owner);
+                _;
+            }
+
+            modifier atLeast(uint256 value) {
+                require(value > 0);
+                _;
+            }
+
+            constructor() Base(1) onlyOwner {}
+
+            function guarded(uint256 value) public onlyOwner atLeast(value) returns (uint256) {
+                return value;
+            }
+        }
+        "#;
+        let (_sources, analysis) = compile_and_analyze(source);
+        let text_of = |name: &str| {
+            let func = analysis
+                .functions
+                .iter()
+                .find(|f| f.name() == name)
+                .unwrap_or_else(|| panic!("Should find {name}"));
+            func.modifier_invocations()
+                .iter()
+                .map(|src| &source[src.start..src.start + src.length])
+                .collect::<Vec<_>>()
+        };
+
+        // Modifier invocations are recorded in order with their source ranges
+        assert_eq!(text_of("guarded"), vec!["onlyOwner", "atLeast(value)"]);
+        // Base constructor calls are not modifier invocations
+        assert_eq!(text_of(""), vec!["onlyOwner"]);
+        // Modifiers do not invoke modifiers
+        assert!(text_of("onlyOwner").is_empty());
+    }
 }
@@ -16,7 +16,7 @@
 
 use foundry_compilers::artifacts::{
     ast::SourceLocation, Expression, FunctionCall, FunctionCallKind, FunctionDefinition,
-    ModifierDefinition, Statement,
+    ModifierDefinition, Statement, TryCatchClause,
 };
 use serde::{Deserialize, Serialize};
 
@@ -150,6 +150,9 @@ pub enum StepKind {
     PlaceholderStatement,
     /// Other statement steps
     OtherStatement,
+    /// An entry step for a `try` clause binding the returned values or the caught error
+    /// data, e.g., `catch Error(string memory reason)`
+    TryClause,
 }
 
 impl Step {
@@ -420,8 +423,9 @@ impl Analyzer {
 
                 // end the try statement step early and then walk the clauses of the try statement.
                 self.exit_current_statement_step(statement)?;
-                for clause in &try_statement.clauses {
-                    clause.block.walk(self)?;
+                for (index, clause) in try_statement.clauses.iter().enumerate() {
+                    // the first clause is the success clause
+                    self.walk_try_clause(clause, index == 0)?;
                 }
 
                 // skip the subtree of the try statement since we have already walked it
@@ -590,6 +594,69 @@ impl Analyzer {
         Ok(VisitorAction::SkipSubtree)
     }
 
+    /// Walk a clause of a `try` statement.
+    ///
+    /// A clause binding values (the returned values of the success clause, or the error data
+    /// of a catch clause) gets an entry step declaring them, so that the caught error data is
+    /// visible and the clause is entered with a snapshot even if its block is empty.
+    pub(super) fn walk_try_clause(
+        &mut self,
+        clause: &TryCatchClause,
+        is_success: bool,
+    ) -> eyre::Result<()> {
+        let Some(parameters) = clause.parameters.as_ref().filter(|p| !p.parameters.is_empty())
+        else {
+            // nothing is bound by the clause, so its block is walked as usual
+            return clause.block.walk(self);
+        };
+        assert!(self.current_step.is_none(), "Step cannot be nested");
+        let current_function = self.current_function();
+
+        // enter a new scope for the clause, its parameters are not visible outside of it
+        self.enter_new_scope(clause.src)?;
+
+        // step is the clause header, i.e., `returns (...)` or `catch ...(...)`
+        let current_scope = self.current_scope();
+        let accessible_variables = current_scope.read().variables_recursive();
+        let src = if is_success {
+            parameters.src.into()
+        } else {
+            sloc_ldiff(clause.src, clause.block.src).into()
+        };
+        let first_stmt_loc = find_index_of_first_statement_in_block(&clause.block)
+            .expect("try clause first statement location not found");
+        let new_step: StepRef = Step::new(
+            current_function.ufid(),
+            src,
+            StepHookLocations {
+                // the before step hook should be instrumented before the first statement of the clause
+                before_step: first_stmt_loc,
+                // the variable update hook should be instrumented before the first statement of the clause
+                after_step: vec![first_stmt_loc],
+            },
+            StepKind::TryClause,
+            current_scope,
+            accessible_variables,
+        )
+        .into();
+        self.current_step = Some(new_step.clone());
+        current_function.write().steps.push(new_step);
+
+        // the clause parameters are bound when entering the clause, just like function parameters
+        let mut single_step_walker = AnalyzerSingleStepWalker { analyzer: self };
+        single_step_walker.analyzer.is_declaring_param = true;
+        parameters.walk(&mut single_step_walker)?;
+        single_step_walker.analyzer.is_declaring_param = false;
+
+        // end the clause step early and then walk the block of the clause.
+        let step = self.current_step.take().unwrap();
+        self.finished_steps.push(step);
+        clause.block.walk(self)?;
+
+        // exit the scope of the clause
+        self.exit_current_scope(clause.src)
+    }
+
     /// Add a function call to the current step, if we are in a step.
     pub(super) fn add_function_call(&mut self, call: &FunctionCall) -> eyre::Result<()> {
         if let Some(step) = self.current_step.as_mut() {
@@ -881,6 +948,63 @@ contract TestContract {
         assert!(steps == 3);
     }
 
+    #[test]
+    fn test_try_clause_steps() {
+        let source = r#"
+contract TestContract {
+    function getValue() public view returns (uint256) {
+        try this.getValue() returns (uint256 value) {
+            return value;
+        } catch Error(string memory reason) {
+            return bytes(reason).length;
+        } catch Panic(uint256 code) {
+            return code;
+        } catch (bytes memory data) {
+        }
+        return 0;
+    }
+}
+"#;
+        let (_sources, analysis) = compile_and_analyze(source);
+
+        // Clauses binding values get their own step declaring them, even with an empty block
+        let clause_steps: Vec<_> =
+            analysis.steps.iter().filter(|s| matches!(s.kind(), StepKind::TryClause)).collect();
+        assert_eq!(clause_steps.len(), 4);
+        let headers: Vec<_> = clause_steps
+            .iter()
+            .map(|s| {
+                let src = s.src();
+                source[src.start..src.start + src.length].trim().to_string()
+            })
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                "(uint256 value)",
+                "catch Error(string memory reason)",
+                "catch Panic(uint256 code)",
+                "catch (bytes memory data)",
+            ]
+        );
+        let declared: Vec<_> = clause_steps
+            .iter()
+            .map(|s| {
+                s.read().declared_variables.iter().map(|v| v.name().clone()).collect::<Vec<_>>()
+            })
+            .collect();
+        assert_eq!(declared, vec![vec!["value"], vec!["reason"], vec!["code"], vec!["data"]]);
+
+        // The caught data is not accessible after the try statement
+        let last_return = analysis
+            .steps
+            .iter()
+            .filter(|s| s.contains_return())
+            .max_by_key(|s| s.src().start)
+            .expect("Should find the last return");
+        assert!(last_return.accessible_variables().iter().all(|v| v.name() != "reason"));
+    }
+
     #[test]
     fn test_if_statement_body() {
         // Create a simple contract with a function to test if statement extraction
@@ -17,6 +17,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use alloy_dyn_abi::DynSolType;
+use edb_common::types::SolTypeHint;
 use foundry_compilers::artifacts::{
     ContractDefinition, EnumDefinition, Expression, StructDefinition, TypeName,
     UserDefinedValueTypeDefinition,
@@ -179,7 +180,11 @@ pub fn dyn_sol_type(
             }
         }
         TypeName::ElementaryTypeName(elementary_type_name) => {
-            DynSolType::parse(&elementary_type_name.name).ok()
+            DynSolType::parse(&elementary_type_name.name).ok().or_else(|| {
+                // fixed-point numbers are ABI-encoded as integers
+                let (signed, bits, _) = parse_fixed_point(&elementary_type_name.name)?;
+                Some(if signed { DynSolType::Int(bits) } else { DynSolType::Uint(bits) })
+            })
         }
         TypeName::FunctionTypeName(_) => Some(DynSolType::Function),
         TypeName::Mapping(_) => None,
@@ -221,6 +226,78 @@ pub fn dyn_sol_type(
     }
 }
 
+/// Converts a TypeName to a SolTypeHint refining how its values are rendered.
+///
+/// # Arguments
+/// * `all_user_defined_types` - A map of all user defined types.
+/// * `type_name` - The type name to convert.
+///
+/// # Returns
+/// The SolTypeHint, or `None` if values of the type render faithfully from
+/// their ABI type alone.
+pub fn sol_type_hint(
+    all_user_defined_types: &HashMap<usize, UserDefinedTypeRef>,
+    type_name: &TypeName,
+) -> Option<SolTypeHint> {
+    match type_name {
+        TypeName::ArrayTypeName(array_type_name) => {
+            let element = sol_type_hint(all_user_defined_types, &array_type_name.base_type)?;
+            Some(SolTypeHint::Array { element: Box::new(element) })
+        }
+        TypeName::ElementaryTypeName(elementary_type_name) => {
+            let (_, _, decimals) = parse_fixed_point(&elementary_type_name.name)?;
+            Some(SolTypeHint::FixedPoint { decimals })
+        }
+        TypeName::FunctionTypeName(_) | TypeName::Mapping(_) => None,
+        TypeName::UserDefinedTypeName(user_defined_type_name) => {
+            if user_defined_type_name.referenced_declaration < 0 {
+                return None;
+            }
+
+            let ty_def = all_user_defined_types
+                .get(&(user_defined_type_name.referenced_declaration as usize))?;
+
+            match ty_def.variant() {
+                UserDefinedTypeVariant::Struct(definition) => {
+                    let fields: Vec<_> = definition
+                        .members
+                        .iter()
+                        .map(|field| {
+                            sol_type_hint(all_user_defined_types, field.type_name.as_ref()?)
+                        })
+                        .collect();
+                    fields.iter().any(Option::is_some).then_some(SolTypeHint::Struct { fields })
+                }
+                UserDefinedTypeVariant::Enum(definition) => Some(SolTypeHint::Enum {
+                    name: definition.name.clone(),
+                    members: definition.members.iter().map(|m| m.name.clone()).collect(),
+                }),
+                UserDefinedTypeVariant::UserDefinedValueType(definition) => {
+                    Some(SolTypeHint::UserDefinedValueType { name: definition.name.clone() })
+                }
+                UserDefinedTypeVariant::Contract(_) => None,
+            }
+        }
+    }
+}
+
+/// Parses a fixed-point type name (e.g., `ufixed128x18`) into its signedness,
+/// bit width, and number of decimals.
+fn parse_fixed_point(name: &str) -> Option<(bool, usize, u32)> {
+    let (signed, suffix) = match name.strip_prefix("ufixed") {
+        Some(suffix) => (false, suffix),
+        None => (true, name.strip_prefix("fixed")?),
+    };
+
+    if suffix.is_empty() {
+        // `fixed` and `ufixed` are aliases for `fixed128x18` and `ufixed128x18`
+        return Some((signed, 128, 18));
+    }
+
+    let (bits, decimals) = suffix.split_once('x')?;
+    Some((signed, bits.parse().ok()?, decimals.parse().ok()?))
+}
+
 /* User defined type analysis */
 impl Analyzer {
     pub(in crate::analysis) fn record_user_defined_value_type(
@@ -305,4 +382,53 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_sol_type_hint() {
+        let source = r#"
+        contract C {
+            enum Status { Pending, Active }
+            type Price is uint256;
+            struct Order {
+                uint256 amount;
+                Status status;
+            }
+            uint256 constant MAX_FEE = 100;
+            address constant OWNER = 0x000000000000000000000000000000000000dEaD;
+            Status internal status;
+            Price internal price;
+            Order[] internal orders;
+            uint256 internal fee;
+            bool internal flag;
+        }
+        "#;
+        let (_sources, analysis) = compile_and_analyze(source);
+        let constants: Vec<_> = analysis.constants.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(constants, vec!["MAX_FEE", "OWNER"]);
+
+        let user_defined_types = analysis.user_defined_types();
+        let hint = |name: &str| {
+            let var =
+                analysis.state_variables.iter().find(|v| v.declaration().name == name).unwrap();
+            sol_type_hint(&user_defined_types, var.type_name().as_ref().unwrap())
+        };
+
+        let status = SolTypeHint::Enum {
+            name: "Status".to_string(),
+            members: vec!["Pending".to_string(), "Active".to_string()],
+        };
+        assert_eq!(hint("status"), Some(status.clone()));
+        assert_eq!(
+            hint("price"),
+            Some(SolTypeHint::UserDefinedValueType { name: "Price".to_string() })
+        );
+        assert_eq!(
+            hint("orders"),
+            Some(SolTypeHint::Array {
+                element: Box::new(SolTypeHint::Struct { fields: vec![None, Some(status)] })
+            })
+        );
+        assert_eq!(hint("fee"), None);
+        assert_eq!(hint("flag"), None);
+    }
 }
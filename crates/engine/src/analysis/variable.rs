@@ -28,6 +28,8 @@
 //! The module is designed to work with the broader analysis framework to provide
 //! comprehensive variable tracking and type information during contract analysis.
 
+use alloy_dyn_abi::DynSolType;
+use edb_common::types::EdbSolValue;
 use foundry_compilers::artifacts::{
     Assignment, Expression, Mutability, StorageLocation, TypeName, VariableDeclaration, Visibility,
 };
@@ -283,6 +285,18 @@ impl Variable {
     }
 }
 
+/// A constant whose value is known from the literal it is initialized with.
+///
+/// Constants are not instrumented as variables; they are only used to render
+/// values equal to a constant by the constant's name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Constant {
+    /// The name of the constant.
+    pub name: String,
+    /// The value of the constant.
+    pub value: EdbSolValue,
+}
+
 /* Variable analysis utils */
 impl Analyzer {
     pub(super) fn declare_variable(
@@ -298,6 +312,9 @@ impl Analyzer {
             || declaration.constant
         {
             // constant and immutable variables are excluded.
+            if declaration.mutability == Some(Mutability::Constant) || declaration.constant {
+                self.record_constant(declaration);
+            }
             return Ok(());
         }
 
@@ -456,6 +473,40 @@ impl Analyzer {
     }
 
     /// Record a declared variable's initial value to the current step's updated variables.
+    fn record_constant(&mut self, declaration: &VariableDeclaration) {
+        let Some(TypeName::ElementaryTypeName(type_name)) = &declaration.type_name else {
+            return;
+        };
+        let Some(Expression::Literal(literal)) = &declaration.value else {
+            return;
+        };
+        let (Ok(ty), Some(value)) = (DynSolType::parse(&type_name.name), &literal.value) else {
+            return;
+        };
+        if !matches!(
+            ty,
+            DynSolType::Int(_)
+                | DynSolType::Uint(_)
+                | DynSolType::Address
+                | DynSolType::FixedBytes(_)
+        ) {
+            // only values of these types are worth naming
+            return;
+        }
+
+        let value = value.replace('_', "");
+        let value = match literal.subdenomination.as_deref() {
+            None => value,
+            Some(unit @ ("wei" | "gwei" | "ether")) => format!("{value} {unit}"),
+            // time units are not understood by the ABI parser
+            Some(_) => return,
+        };
+
+        if let Ok(value) = ty.coerce_str(&value) {
+            self.constants.push(Constant { name: declaration.name.clone(), value: value.into() });
+        }
+    }
+
     pub(super) fn record_declared_varaible(
         &mut self,
         declaration: &VariableDeclaration,
@@ -0,0 +1,547 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Correlation of transactions bridged through canonical OP Stack bridges.
+//!
+//! A bridged flow spans two transactions on two chains, and debugging it usually means
+//! debugging both. The engine recognizes the messages a transaction sends through the
+//! canonical bridge of a known OP Stack chain and finds the transaction on the other
+//! chain:
+//!
+//! - **Deposits** (`TransactionDeposited` emitted by the `OptimismPortal` on L1) are
+//!   executed on L2 as deposit transactions, whose hash is derived from the L1 block
+//!   hash, the index of the event in the L1 block, and the deposited message.
+//! - **Withdrawals** (`MessagePassed` emitted by the `L2ToL1MessagePasser` on L2) are
+//!   finalized on L1 by a transaction emitting `WithdrawalFinalized` with the withdrawal
+//!   hash, which is looked up through the RPC endpoint of L1 configured in
+//!   [`EngineConfig::chain_rpc_urls`].
+
+use alloy_dyn_abi::{DynSolType, DynSolValue};
+use alloy_primitives::{address, b256, keccak256, Address, Bytes, TxHash, B256, U256};
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rlp::Encodable;
+use alloy_rpc_types::{BlockNumberOrTag, Filter};
+use edb_common::{
+    types::{BridgeDirection, BridgeMessage, CapturedLog},
+    ForkInfo,
+};
+use eyre::{eyre, Result};
+use tracing::{debug, warn};
+
+use crate::EngineConfig;
+
+/// Predeploy through which withdrawals are initiated on every OP Stack chain
+pub const L2_TO_L1_MESSAGE_PASSER: Address = address!("0x4200000000000000000000000000000000000016");
+
+/// Version of the `TransactionDeposited` event encoding understood by the engine
+const DEPOSIT_VERSION: U256 = U256::ZERO;
+
+/// Type of deposit transactions
+const DEPOSIT_TX_TYPE: u8 = 0x7e;
+
+/// Blocks covered by each `eth_getLogs` request, within the limits of common providers
+const LOG_PAGE_SIZE: u64 = 10_000;
+
+/// L1 blocks searched for the finalization of a withdrawal, about 90 days
+const FINALIZATION_SEARCH_BLOCKS: u64 = 648_000;
+
+/// `TransactionDeposited(address,address,uint256,bytes)`
+const TRANSACTION_DEPOSITED: B256 =
+    b256!("0xb3813568d9991fc951961fcb4c784893574240a28925604d09fc577c55bb7c32");
+
+/// `MessagePassed(uint256,address,address,uint256,uint256,bytes,bytes32)`
+const MESSAGE_PASSED: B256 =
+    b256!("0x02a52367d10742d8032712c1bb8e0144ff1ec5ffda1ed7d70bb05a2744955054");
+
+/// `WithdrawalFinalized(bytes32,bool)`
+const WITHDRAWAL_FINALIZED: B256 =
+    b256!("0xdb5c7652857aa163daadd670e116628fb42e869d8ac4251ef8971d9e5727df1b");
+
+/// An OP Stack chain with a canonical bridge known to the engine
+#[derive(Debug, Clone, Copy)]
+struct OpStackChain {
+    name: &'static str,
+    l1_chain_id: u64,
+    l2_chain_id: u64,
+    /// `OptimismPortal` proxy on L1
+    portal: Address,
+}
+
+/// Known OP Stack chains
+const OP_STACK_CHAINS: &[OpStackChain] = &[
+    OpStackChain {
+        name: "OP Mainnet",
+        l1_chain_id: 1,
+        l2_chain_id: 10,
+        portal: address!("0xbEb5Fc579115071764c7423A4f12eDde41f106Ed"),
+    },
+    OpStackChain {
+        name: "Base",
+        l1_chain_id: 1,
+        l2_chain_id: 8453,
+        portal: address!("0x49048044D57e1C92A77f79988d21Fa8fAF74E97e"),
+    },
+];
+
+/// Whether a chain is the L1 or L2 of a known OP Stack chain
+pub fn is_bridged_chain(chain_id: u64) -> bool {
+    OP_STACK_CHAINS
+        .iter()
+        .any(|chain| chain.l1_chain_id == chain_id || chain.l2_chain_id == chain_id)
+}
+
+/// A deposit decoded from a `TransactionDeposited` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Deposit {
+    from: Address,
+    to: Option<Address>,
+    mint: U256,
+    value: U256,
+    gas_limit: u64,
+    data: Bytes,
+}
+
+impl Deposit {
+    /// Decode a `TransactionDeposited` event emitted by a portal
+    fn decode(log: &CapturedLog) -> Option<Self> {
+        let topics = log.log.topics();
+        if topics.len() != 4 || topics[0] != TRANSACTION_DEPOSITED {
+            return None;
+        }
+        if U256::from_be_bytes(topics[3].0) != DEPOSIT_VERSION {
+            debug!("Unsupported deposit version {}", topics[3]);
+            return None;
+        }
+
+        let DynSolValue::Bytes(opaque) = DynSolType::Bytes.abi_decode(&log.log.data).ok()? else {
+            return None;
+        };
+        // abi.encodePacked(mint, value, gasLimit, isCreation, data)
+        if opaque.len() < 73 {
+            return None;
+        }
+        let is_creation = opaque[72] != 0;
+        Some(Self {
+            from: Address::from_word(topics[1]),
+            to: (!is_creation).then(|| Address::from_word(topics[2])),
+            mint: U256::from_be_slice(&opaque[..32]),
+            value: U256::from_be_slice(&opaque[32..64]),
+            gas_limit: u64::from_be_bytes(opaque[64..72].try_into().ok()?),
+            data: Bytes::copy_from_slice(&opaque[73..]),
+        })
+    }
+
+    /// Hash of the L2 deposit transaction executing this deposit, given the hash of the
+    /// L1 block and the index of the event in that block
+    fn l2_tx_hash(&self, l1_block_hash: B256, l1_log_index: u64) -> TxHash {
+        let deposit_id =
+            keccak256([l1_block_hash.0, U256::from(l1_log_index).to_be_bytes::<32>()].concat());
+        // User deposits use the zero domain
+        let source_hash = keccak256([[0u8; 32], deposit_id.0].concat());
+
+        keccak256(self.encode_l2_tx(source_hash))
+    }
+
+    /// EIP-2718 encoding of the L2 deposit transaction executing this deposit
+    fn encode_l2_tx(&self, source_hash: B256) -> Vec<u8> {
+        // Contract creations have an empty target
+        let no_target = Bytes::new();
+        let to: &dyn Encodable = match &self.to {
+            Some(to) => to,
+            None => &no_target,
+        };
+        let fields: [&dyn Encodable; 8] = [
+            &source_hash,
+            &self.from,
+            to,
+            &self.mint,
+            &self.value,
+            &self.gas_limit,
+            // Not a system transaction
+            &false,
+            &self.data,
+        ];
+
+        let mut encoded = vec![DEPOSIT_TX_TYPE];
+        alloy_rlp::encode_list::<_, dyn Encodable>(&fields, &mut encoded);
+        encoded
+    }
+}
+
+/// A withdrawal decoded from a `MessagePassed` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Withdrawal {
+    sender: Address,
+    target: Address,
+    value: U256,
+    data: Bytes,
+    withdrawal_hash: B256,
+}
+
+impl Withdrawal {
+    /// Decode a `MessagePassed` event emitted by the message passer
+    fn decode(log: &CapturedLog) -> Option<Self> {
+        let topics = log.log.topics();
+        if log.address != L2_TO_L1_MESSAGE_PASSER
+            || topics.len() != 4
+            || topics[0] != MESSAGE_PASSED
+        {
+            return None;
+        }
+
+        let params = DynSolType::Tuple(vec![
+            DynSolType::Uint(256),
+            DynSolType::Uint(256),
+            DynSolType::Bytes,
+            DynSolType::FixedBytes(32),
+        ]);
+        let DynSolValue::Tuple(values) = params.abi_decode_params(&log.log.data).ok()? else {
+            return None;
+        };
+        let [DynSolValue::Uint(value, _), _, DynSolValue::Bytes(data), DynSolValue::FixedBytes(hash, 32)] =
+            values.as_slice()
+        else {
+            return None;
+        };
+        Some(Self {
+            sender: Address::from_word(topics[2]),
+            target: Address::from_word(topics[3]),
+            value: *value,
+            data: data.clone().into(),
+            withdrawal_hash: *hash,
+        })
+    }
+}
+
+/// Find the messages a transaction sent through the canonical bridges of known OP Stack
+/// chains, along with the correlated transactions on the other chains.
+///
+/// Correlating a withdrawal requires the RPC endpoint of L1 in
+/// [`EngineConfig::chain_rpc_urls`]; without it, the withdrawal is reported without a
+/// correlated transaction.
+pub async fn find_bridge_messages(
+    config: &EngineConfig,
+    fork_info: &ForkInfo,
+    tx_hash: TxHash,
+    logs: &[CapturedLog],
+) -> Result<Vec<BridgeMessage>> {
+    let mut messages = Vec::new();
+
+    // Deposits sent from L1
+    for chain in OP_STACK_CHAINS.iter().filter(|chain| chain.l1_chain_id == fork_info.chain_id) {
+        let deposits: Vec<_> = logs
+            .iter()
+            .filter(|log| log.address == chain.portal)
+            .filter_map(|log| Some((log, Deposit::decode(log)?)))
+            .collect();
+        if deposits.is_empty() {
+            continue;
+        }
+
+        // The deposit transaction hash depends on the index of the event in the block
+        let log_indices =
+            deposit_log_indices(&config.rpc_proxy_url, fork_info.block_hash, chain.portal, tx_hash)
+                .await?;
+        if log_indices.len() != deposits.len() {
+            warn!(
+                "Found {} deposit(s) to {} in the trace but {} in the receipt",
+                deposits.len(),
+                chain.name,
+                log_indices.len()
+            );
+            continue;
+        }
+
+        for ((log, deposit), log_index) in deposits.into_iter().zip(log_indices) {
+            let l2_tx_hash = deposit.l2_tx_hash(fork_info.block_hash, log_index);
+            messages.push(BridgeMessage {
+                direction: BridgeDirection::Deposit,
+                l2_name: chain.name.to_string(),
+                l1_chain_id: chain.l1_chain_id,
+                l2_chain_id: chain.l2_chain_id,
+                trace_entry_id: log.trace_entry_id,
+                snapshot_id: log.snapshot_id,
+                sender: deposit.from,
+                target: deposit.to,
+                value: deposit.value,
+                data: deposit.data,
+                message_hash: l2_tx_hash,
+                correlated_tx_hash: Some(l2_tx_hash),
+            });
+        }
+    }
+
+    // Withdrawals sent from L2
+    if let Some(chain) =
+        OP_STACK_CHAINS.iter().find(|chain| chain.l2_chain_id == fork_info.chain_id)
+    {
+        for log in logs {
+            let Some(withdrawal) = Withdrawal::decode(log) else {
+                continue;
+            };
+
+            let correlated_tx_hash = match config.chain_rpc_urls.get(&chain.l1_chain_id) {
+                Some(l1_rpc_url) => finalizing_tx(
+                    l1_rpc_url,
+                    chain.portal,
+                    withdrawal.withdrawal_hash,
+                    fork_info.timestamp,
+                )
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to look up the finalization of a withdrawal: {}", e);
+                    None
+                }),
+                None => {
+                    debug!(
+                        "No RPC endpoint for chain {}, withdrawal left uncorrelated",
+                        chain.l1_chain_id
+                    );
+                    None
+                }
+            };
+
+            messages.push(BridgeMessage {
+                direction: BridgeDirection::Withdrawal,
+                l2_name: chain.name.to_string(),
+                l1_chain_id: chain.l1_chain_id,
+                l2_chain_id: chain.l2_chain_id,
+                trace_entry_id: log.trace_entry_id,
+                snapshot_id: log.snapshot_id,
+                sender: withdrawal.sender,
+                target: Some(withdrawal.target),
+                value: withdrawal.value,
+                data: withdrawal.data,
+                message_hash: withdrawal.withdrawal_hash,
+                correlated_tx_hash,
+            });
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Indices in their block of the `TransactionDeposited` events emitted by a portal in a
+/// transaction, in emission order
+async fn deposit_log_indices(
+    rpc_url: &str,
+    block_hash: B256,
+    portal: Address,
+    tx_hash: TxHash,
+) -> Result<Vec<u64>> {
+    let provider = ProviderBuilder::new().connect(rpc_url).await?;
+    let filter = Filter::new()
+        .at_block_hash(block_hash)
+        .address(portal)
+        .event_signature(TRANSACTION_DEPOSITED);
+    let mut indices: Vec<u64> = provider
+        .get_logs(&filter)
+        .await?
+        .into_iter()
+        .filter(|log| log.transaction_hash == Some(tx_hash))
+        .map(|log| log.log_index.ok_or_else(|| eyre!("Deposit event without a log index")))
+        .collect::<Result<_>>()?;
+    indices.sort_unstable();
+    Ok(indices)
+}
+
+/// Transaction finalizing a withdrawal on L1, if it was finalized yet.
+///
+/// A withdrawal cannot be finalized before it is initiated, so the search starts at the
+/// first L1 block not older than the L2 block of the withdrawal, and spans at most
+/// [`FINALIZATION_SEARCH_BLOCKS`] blocks from there, a page at a time.
+async fn finalizing_tx(
+    l1_rpc_url: &str,
+    portal: Address,
+    withdrawal_hash: B256,
+    initiated_at: u64,
+) -> Result<Option<TxHash>> {
+    let provider = ProviderBuilder::new().connect(l1_rpc_url).await?;
+    let latest = provider.get_block_number().await?;
+    let first = first_block_since(&provider, initiated_at, latest).await?;
+    let last = latest.min(first.saturating_add(FINALIZATION_SEARCH_BLOCKS));
+
+    for (from, to) in log_pages(first, last) {
+        let filter = Filter::new()
+            .from_block(from)
+            .to_block(to)
+            .address(portal)
+            .event_signature(WITHDRAWAL_FINALIZED)
+            .topic1(withdrawal_hash);
+        let logs = provider.get_logs(&filter).await?;
+        if let Some(tx_hash) = logs.first().and_then(|log| log.transaction_hash) {
+            return Ok(Some(tx_hash));
+        }
+    }
+    Ok(None)
+}
+
+/// First block with a timestamp not before `timestamp`, or `latest` if there is none
+async fn first_block_since(provider: &impl Provider, timestamp: u64, latest: u64) -> Result<u64> {
+    let (mut low, mut high) = (0, latest);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        let block = provider
+            .get_block_by_number(BlockNumberOrTag::Number(mid))
+            .await?
+            .ok_or_else(|| eyre!("Block {} not found", mid))?;
+        if block.header.timestamp < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(low)
+}
+
+/// Inclusive block ranges of at most [`LOG_PAGE_SIZE`] blocks covering `first..=last`
+fn log_pages(first: u64, last: u64) -> impl Iterator<Item = (u64, u64)> {
+    (first..=last)
+        .step_by(LOG_PAGE_SIZE as usize)
+        .map(move |from| (from, last.min(from + LOG_PAGE_SIZE - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{hex, LogData};
+    use alloy_rlp::{Decodable, Header};
+
+    fn captured_log(address: Address, topics: Vec<B256>, data: Bytes) -> CapturedLog {
+        CapturedLog {
+            trace_entry_id: 3,
+            index: 0,
+            address,
+            log: LogData::new_unchecked(topics, data),
+            snapshot_id: Some(42),
+        }
+    }
+
+    #[test]
+    fn test_deposit_tx_encoding() {
+        let mut deposit = Deposit {
+            from: address!("0x1111111111111111111111111111111111111111"),
+            to: Some(address!("0x2222222222222222222222222222222222222222")),
+            mint: U256::from(5),
+            value: U256::from(0x400),
+            gas_limit: 100_000,
+            data: Bytes::from(hex!("deadbeef")),
+        };
+        let source_hash = B256::repeat_byte(0xab);
+
+        let encoded = deposit.encode_l2_tx(source_hash);
+        assert_eq!(encoded[0], DEPOSIT_TX_TYPE);
+        let mut buf = &encoded[1..];
+        let header = Header::decode(&mut buf).unwrap();
+        assert!(header.list);
+        assert_eq!(header.payload_length, buf.len());
+        assert_eq!(B256::decode(&mut buf).unwrap(), source_hash);
+        assert_eq!(Address::decode(&mut buf).unwrap(), deposit.from);
+        assert_eq!(Address::decode(&mut buf).unwrap(), deposit.to.unwrap());
+        assert_eq!(U256::decode(&mut buf).unwrap(), deposit.mint);
+        assert_eq!(U256::decode(&mut buf).unwrap(), deposit.value);
+        assert_eq!(u64::decode(&mut buf).unwrap(), deposit.gas_limit);
+        assert!(!bool::decode(&mut buf).unwrap());
+        assert_eq!(Bytes::decode(&mut buf).unwrap(), deposit.data);
+        assert!(buf.is_empty());
+
+        // Contract creations are encoded with an empty target
+        deposit.to = None;
+        let encoded = deposit.encode_l2_tx(source_hash);
+        let mut buf = &encoded[1..];
+        Header::decode(&mut buf).unwrap();
+        B256::decode(&mut buf).unwrap();
+        Address::decode(&mut buf).unwrap();
+        assert!(Bytes::decode(&mut buf).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_log_pages() {
+        assert_eq!(log_pages(5, 5).collect::<Vec<_>>(), vec![(5, 5)]);
+        assert_eq!(
+            log_pages(100, 25_000).collect::<Vec<_>>(),
+            vec![(100, 10_099), (10_100, 20_099), (20_100, 25_000)]
+        );
+        assert_eq!(log_pages(0, 9_999).collect::<Vec<_>>(), vec![(0, 9_999)]);
+    }
+
+    #[test]
+    fn test_decode_deposit() {
+        let portal = OP_STACK_CHAINS[0].portal;
+        let from = address!("0x1111111111111111111111111111111111111111");
+        let to = address!("0x2222222222222222222222222222222222222222");
+
+        let mut opaque = Vec::new();
+        opaque.extend_from_slice(&U256::from(5).to_be_bytes::<32>());
+        opaque.extend_from_slice(&U256::from(5).to_be_bytes::<32>());
+        opaque.extend_from_slice(&100_000u64.to_be_bytes());
+        opaque.push(0);
+        opaque.extend_from_slice(&hex!("deadbeef"));
+        let data = DynSolValue::Bytes(opaque).abi_encode();
+
+        let log = captured_log(
+            portal,
+            vec![TRANSACTION_DEPOSITED, from.into_word(), to.into_word(), B256::ZERO],
+            data.into(),
+        );
+        let deposit = Deposit::decode(&log).unwrap();
+        assert_eq!(deposit.from, from);
+        assert_eq!(deposit.to, Some(to));
+        assert_eq!(deposit.mint, U256::from(5));
+        assert_eq!(deposit.gas_limit, 100_000);
+        assert_eq!(deposit.data, Bytes::from(hex!("deadbeef")));
+
+        // The L2 transaction hash depends on where the event was emitted
+        let block_hash = B256::repeat_byte(0xab);
+        assert_eq!(deposit.l2_tx_hash(block_hash, 7), deposit.l2_tx_hash(block_hash, 7));
+        assert_ne!(deposit.l2_tx_hash(block_hash, 7), deposit.l2_tx_hash(block_hash, 8));
+    }
+
+    #[test]
+    fn test_decode_withdrawal() {
+        let sender = address!("0x3333333333333333333333333333333333333333");
+        let target = address!("0x4444444444444444444444444444444444444444");
+        let withdrawal_hash = B256::repeat_byte(0x55);
+        let data = DynSolValue::Tuple(vec![
+            DynSolValue::Uint(U256::from(1), 256),
+            DynSolValue::Uint(U256::from(200_000), 256),
+            DynSolValue::Bytes(vec![1, 2, 3]),
+            DynSolValue::FixedBytes(withdrawal_hash, 32),
+        ])
+        .abi_encode_params();
+
+        let topics =
+            vec![MESSAGE_PASSED, B256::with_last_byte(9), sender.into_word(), target.into_word()];
+        let log = captured_log(L2_TO_L1_MESSAGE_PASSER, topics.clone(), data.clone().into());
+        let withdrawal = Withdrawal::decode(&log).unwrap();
+        assert_eq!(withdrawal.sender, sender);
+        assert_eq!(withdrawal.target, target);
+        assert_eq!(withdrawal.value, U256::from(1));
+        assert_eq!(withdrawal.withdrawal_hash, withdrawal_hash);
+
+        // Only the message passer initiates withdrawals
+        let log = captured_log(Address::ZERO, topics, data.into());
+        assert!(Withdrawal::decode(&log).is_none());
+    }
+
+    #[test]
+    fn test_is_bridged_chain() {
+        assert!(is_bridged_chain(1));
+        assert!(is_bridged_chain(10));
+        assert!(is_bridged_chain(8453));
+        assert!(!is_bridged_chain(137));
+    }
+}
@@ -0,0 +1,104 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Cancellation of long-running engine operations.
+//!
+//! Preparing a transaction replays it several times and recompiles every verified
+//! contract it touches, which can take minutes. Each preparation is given a
+//! [`CancellationToken`] by the [`crate::PreparationTracker`], which the engine checks
+//! between phases and while recompiling contracts, so that a preparation can be stopped
+//! (e.g., with `edb_cancel` or Ctrl+C) without killing the process.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use eyre::Result;
+
+/// Error returned by an operation that stopped because it was cancelled.
+///
+/// It can be told apart from other failures with [`is_cancelled`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Preparation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Check whether an error is the result of a cancellation
+pub fn is_cancelled(error: &eyre::Report) -> bool {
+    error.downcast_ref::<Cancelled>().is_some()
+}
+
+/// Shared flag requesting a long-running operation to stop.
+///
+/// Cloning the token yields a handle to the same flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request the operation to stop
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the operation was requested to stop
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return a [`Cancelled`] error if the operation was requested to stop
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Cancelled.into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancellation_token() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+
+        clone.cancel();
+        assert!(token.is_cancelled());
+        let error = token.check().unwrap_err();
+        assert!(is_cancelled(&error));
+        assert!(!is_cancelled(&eyre::eyre!("Compilation failed")));
+    }
+}
@@ -22,13 +22,17 @@ use edb_common::{
 };
 use eyre::{eyre, Result};
 use revm::{
-    context::{result::ExecutionResult, tx::TxEnvBuilder},
+    context::{result::ExecutionResult, tx::TxEnvBuilder, ContextTr},
     database::CacheDB,
-    Context, Database, DatabaseCommit, DatabaseRef, ExecuteEvm, MainBuilder, MainContext,
-    MainnetEvm,
+    primitives::KECCAK_EMPTY,
+    Context, Database, DatabaseCommit, DatabaseRef, ExecuteEvm, InspectEvm, MainBuilder,
+    MainContext, MainnetEvm,
 };
 
-use crate::EngineContext;
+use crate::{
+    ContextQueryTr, EngineContext, OverridePoint, RerunResult, SnapshotDetail, StateOverride,
+    StateOverrideInspector,
+};
 
 /// Trait providing EVM creation and expression evaluation capabilities on the EngineContext.
 /// This trait allows creating derived EVM instances for specific snapshots,
@@ -102,6 +106,48 @@ where
         args: &[DynSolValue],
         value: Option<U256>,
     ) -> Result<DynSolValue>;
+
+    /// Invoke a contract function call in the execution context of a snapshot's frame.
+    ///
+    /// The call is sent to the target address of the frame, running the code of its
+    /// bytecode address. In a delegatecall frame, state variables of the executing code
+    /// are hence read from the storage of the proxy they actually live in, instead of
+    /// through whatever function the proxy code dispatches the call to.
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot_id` - The snapshot ID to use as the base state and frame
+    /// * `function` - The ABI function definition
+    /// * `args` - The function arguments
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded function result or an error if the call fails.
+    fn call_in_frame_context(
+        &self,
+        snapshot_id: usize,
+        function: &Function,
+        args: &[DynSolValue],
+    ) -> Result<DynSolValue>;
+
+    /// Re-execute the transaction with state overrides.
+    ///
+    /// The transaction is replayed from the state before it, and each override is written
+    /// into the state when execution reaches its snapshot. Opcode-level snapshots are
+    /// located by program counter within their frame, while source-level snapshots, whose
+    /// position in the original bytecode is unknown, take effect when execution enters or
+    /// resumes the frame containing them.
+    ///
+    /// # Arguments
+    ///
+    /// * `overrides` - The state overrides to apply, in the order they are applied at a
+    ///   common snapshot
+    ///
+    /// # Returns
+    ///
+    /// Returns the outcome and trace of the re-execution, or an error if a snapshot does
+    /// not exist or the state before the transaction is not available.
+    fn rerun_with_overrides(&self, overrides: &[StateOverride]) -> Result<RerunResult>;
 }
 
 // EVM creation and expression evaluation methods
@@ -143,15 +189,7 @@ where
             .create_evm_for_snapshot(snapshot_id)
             .ok_or(eyre!("No EVM found at snapshot {}", snapshot_id))?;
 
-        let mut tx_env = TxEnvBuilder::new()
-            .caller(self.tx.caller)
-            .call(to)
-            .value(value)
-            .data(Bytes::copy_from_slice(data))
-            .build_fill();
-        relax_evm_tx_constraints(&mut tx_env);
-
-        evm.transact_one(tx_env).map_err(|e| eyre!(e.to_string()))
+        self.transact_in_evm(&mut evm, to, data, value)
     }
 
     fn call_in_derived_evm(
@@ -167,22 +205,211 @@ where
 
         let result = self.send_transaction_in_derived_evm(snapshot_id, to, &data, value)?;
 
-        match result {
-            ExecutionResult::Success { output, .. } => {
-                let decoded =
-                    function.abi_decode_output(output.data()).map_err(|e| eyre!(e.to_string()))?;
-                if decoded.len() == 1 {
-                    Ok(decoded.into_iter().next().unwrap())
-                } else {
-                    Ok(DynSolValue::Tuple(decoded))
-                }
+        decode_call_result(function, result)
+    }
+
+    fn call_in_frame_context(
+        &self,
+        snapshot_id: usize,
+        function: &Function,
+        args: &[DynSolValue],
+    ) -> Result<DynSolValue> {
+        let (_, snapshot) =
+            self.snapshots.get(snapshot_id).ok_or(eyre!("Snapshot {} not found", snapshot_id))?;
+        let target_address = snapshot.target_address();
+        let bytecode_address = snapshot.bytecode_address();
+
+        let mut evm = self
+            .create_evm_for_snapshot(snapshot_id)
+            .ok_or(eyre!("No EVM found at snapshot {}", snapshot_id))?;
+
+        if self.is_delegated(snapshot_id) {
+            // Run the executing code against the storage of the frame, as the
+            // delegatecall that entered it does
+            let db = evm.ctx.db_mut();
+            let code_info = db
+                .basic(bytecode_address)
+                .map_err(|e| eyre!("Failed to get account info for {}: {}", bytecode_address, e))?
+                .unwrap_or_default();
+            let code = match code_info.code {
+                Some(code) => code,
+                None => db
+                    .code_by_hash(code_info.code_hash)
+                    .map_err(|e| eyre!("Failed to get code of {}: {}", bytecode_address, e))?,
+            };
+
+            let mut info = db
+                .basic(target_address)
+                .map_err(|e| eyre!("Failed to get account info for {}: {}", target_address, e))?
+                .unwrap_or_default();
+            // Code hash will be recomputed within `db.insert_account_info`
+            info.code_hash = KECCAK_EMPTY;
+            info.code = Some(code);
+            db.insert_account_info(target_address, info);
+        }
+
+        let data = function.abi_encode_input(args).map_err(|e| eyre!(e.to_string()))?;
+        let result = self.transact_in_evm(&mut evm, target_address, &data, U256::ZERO)?;
+
+        decode_call_result(function, result)
+    }
+
+    fn rerun_with_overrides(&self, overrides: &[StateOverride]) -> Result<RerunResult> {
+        let initial_db = self
+            .initial_db
+            .clone()
+            .ok_or(eyre!("The state before the transaction is not available in this session"))?;
+
+        let points = overrides
+            .iter()
+            .map(|state_override| {
+                let point = self
+                    .override_point(state_override.snapshot_id)
+                    .ok_or(eyre!("Snapshot {} not found", state_override.snapshot_id))?;
+                Ok((point, state_override.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut inspector = StateOverrideInspector::new(points);
+        let ctx = Context::mainnet()
+            .with_db(CacheDB::new(CacheDB::new(initial_db)))
+            .with_cfg(self.cfg.clone())
+            .with_block(self.block.clone());
+        let mut evm = ctx.build_mainnet_with_inspector(&mut inspector);
+        let result = evm.inspect_one_tx(self.tx.clone()).map_err(|e| eyre!(e.to_string()))?;
+        drop(evm);
+
+        let (success, output, halt_reason) = match &result {
+            ExecutionResult::Success { output, .. } => (true, output.data().clone(), None),
+            ExecutionResult::Revert { output, .. } => (false, output.clone(), None),
+            ExecutionResult::Halt { reason, .. } => {
+                (false, Bytes::new(), Some(format!("{reason:?}")))
             }
-            ExecutionResult::Revert { output, .. } => {
-                Err(eyre!("Call reverted with output: 0x{}", hex::encode(output)))
+        };
+        let unapplied_overrides = inspector.unapplied_overrides().cloned().collect();
+
+        Ok(RerunResult {
+            success,
+            gas_used: result.gas_used(),
+            output,
+            halt_reason,
+            unapplied_overrides,
+            trace: inspector.into_replay_result().execution_trace,
+        })
+    }
+}
+
+impl<DB> EngineContext<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    /// Send a mock transaction from the transaction sender in a derived EVM
+    fn transact_in_evm(
+        &self,
+        evm: &mut MainnetEvm<DerivedContext<DB>>,
+        to: Address,
+        data: &[u8],
+        value: U256,
+    ) -> Result<ExecutionResult> {
+        let mut tx_env = TxEnvBuilder::new()
+            .caller(self.tx.caller)
+            .call(to)
+            .value(value)
+            .data(Bytes::copy_from_slice(data))
+            .build_fill();
+        relax_evm_tx_constraints(&mut tx_env);
+
+        evm.transact_one(tx_env).map_err(|e| eyre!(e.to_string()))
+    }
+
+    /// Locate a snapshot in the execution, for re-executing the transaction up to it
+    fn override_point(&self, snapshot_id: usize) -> Option<OverridePoint> {
+        let (frame_id, snapshot) = self.snapshots.get(snapshot_id)?;
+
+        // Opcode-level snapshots are taken at every instruction of their frame, so the
+        // snapshots of the frame at the same PC count the earlier executions of it
+        let instruction = match snapshot.detail() {
+            SnapshotDetail::Opcode(detail) => {
+                let occurrence = self.snapshots[..snapshot_id]
+                    .iter()
+                    .filter(|(other_frame_id, other)| {
+                        other_frame_id == frame_id
+                            && matches!(other.detail(), SnapshotDetail::Opcode(o) if o.pc == detail.pc)
+                    })
+                    .count();
+                Some((detail.pc, occurrence))
             }
-            ExecutionResult::Halt { reason, .. } => {
-                Err(eyre!("Call halted with reason: {:?}", reason))
+            SnapshotDetail::Hook(_) => None,
+        };
+
+        Some(OverridePoint { frame_id: *frame_id, instruction })
+    }
+}
+
+/// Decode the output of a function call, unwrapping single return values
+fn decode_call_result(function: &Function, result: ExecutionResult) -> Result<DynSolValue> {
+    match result {
+        ExecutionResult::Success { output, .. } => {
+            let decoded =
+                function.abi_decode_output(output.data()).map_err(|e| eyre!(e.to_string()))?;
+            if decoded.len() == 1 {
+                Ok(decoded.into_iter().next().unwrap())
+            } else {
+                Ok(DynSolValue::Tuple(decoded))
             }
         }
+        ExecutionResult::Revert { output, .. } => {
+            Err(eyre!("Call reverted with output: 0x{}", hex::encode(output)))
+        }
+        ExecutionResult::Halt { reason, .. } => Err(eyre!("Call halted with reason: {:?}", reason)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{build_session, opcode_snapshot_at, state_with_contracts};
+    use alloy_primitives::{address, bytes};
+    use edb_common::types::ExecutionFrameId;
+    use revm::primitives::hardfork::SpecId;
+
+    const PROXY: Address = address!("0x00000000000000000000000000000000000000aa");
+    const IMPLEMENTATION: Address = address!("0x00000000000000000000000000000000000000bb");
+
+    /// PC of the `SLOAD` the implementation executes when entered without calldata
+    const SLOAD_PC: usize = 6;
+
+    #[test]
+    fn test_call_in_frame_context_reads_proxy_storage() {
+        // The proxy delegatecalls the implementation without calldata, whatever it is
+        // called with
+        let proxy_code = bytes!(
+            "0x6000600060006000"
+            "7300000000000000000000000000000000000000bb5af400"
+        );
+        // Without calldata, the implementation loads slot 0 and stops. With calldata,
+        // it returns slot 0, as the getter of the state variable living there does.
+        let implementation_code = bytes!("0x36600857600054005b60005460005260206000f3");
+
+        let mut db =
+            state_with_contracts(&[(PROXY, proxy_code), (IMPLEMENTATION, implementation_code)]);
+        db.insert_account_storage(PROXY, U256::ZERO, U256::from(42)).unwrap();
+        db.insert_account_storage(IMPLEMENTATION, U256::ZERO, U256::from(1)).unwrap();
+        let context = build_session(db, PROXY, SpecId::CANCUN);
+
+        let snapshot_id = opcode_snapshot_at(&context, ExecutionFrameId::new(1, 0), SLOAD_PC);
+        assert!(context.is_delegated(snapshot_id));
+        assert_eq!(context.get_target_address(snapshot_id), Some(PROXY));
+        assert_eq!(context.get_bytecode_address(snapshot_id), Some(IMPLEMENTATION));
+
+        let getter = Function::parse("function value() view returns (uint256)").unwrap();
+        let value = context.call_in_frame_context(snapshot_id, &getter, &[]).unwrap();
+        assert_eq!(value, DynSolValue::Uint(U256::from(42), 256));
+
+        // The proxy itself is not delegated to
+        let proxy_snapshot = opcode_snapshot_at(&context, ExecutionFrameId::new(0, 0), 0);
+        assert!(!context.is_delegated(proxy_snapshot));
     }
 }
@@ -0,0 +1,159 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{env, time::Duration};
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_json_abi::Function;
+use alloy_primitives::{address, keccak256, Address, B256};
+use edb_common::{
+    types::{well_known_label, AddressLabel, LabelSource},
+    Cache, CachePath, EdbCache, EdbCachePath, DEFAULT_ENS_CACHE_TTL,
+};
+use eyre::{eyre, Result};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use tracing::debug;
+
+use crate::{ContextEvmTr, EngineContext};
+
+/// ENS registry, deployed at the same address on mainnet and its testnets
+const ENS_REGISTRY: Address = address!("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// Chains where [`ENS_REGISTRY`] is deployed: mainnet, Sepolia, and Holesky
+const ENS_CHAINS: &[u64] = &[1, 11155111, 17000];
+
+/// Trait providing human-readable labels for addresses on the EngineContext.
+pub trait ContextLabelTr {
+    /// Get the label of an address.
+    ///
+    /// Well-known contracts and precompiles are labeled from a built-in table. Other
+    /// addresses are looked up through their ENS reverse record in the state before the
+    /// transaction, and the name is only accepted if it resolves back to the address.
+    /// ENS results, including the absence of a name, are cached under `~/.edb/cache/labels`.
+    fn address_label(&self, address: Address) -> Option<AddressLabel>;
+}
+
+impl<DB> ContextLabelTr for EngineContext<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    fn address_label(&self, address: Address) -> Option<AddressLabel> {
+        let chain_id = self.fork_info.chain_id;
+        if let Some(label) = well_known_label(chain_id, address) {
+            return Some(label);
+        }
+
+        if !ENS_CHAINS.contains(&chain_id) {
+            return None;
+        }
+
+        let cache = EdbCache::<Option<String>>::new(
+            EdbCachePath::new(env::var(edb_common::env::EDB_CACHE_DIR).ok())
+                .label_chain_cache_dir(chain_id),
+            Some(Duration::from_secs(DEFAULT_ENS_CACHE_TTL)),
+        )
+        .ok()
+        .flatten();
+        let cache_key = address.to_checksum(None);
+
+        let name = match cache.load_cache(&cache_key) {
+            Some(name) => name,
+            None => {
+                let name = ens_reverse_lookup(self, address).unwrap_or_else(|e| {
+                    debug!("ENS reverse lookup failed for {}: {}", address, e);
+                    None
+                });
+                if let Err(e) = cache.save_cache(&cache_key, &name) {
+                    debug!("Failed to cache ENS name of {}: {}", address, e);
+                }
+                name
+            }
+        };
+
+        name.map(|name| AddressLabel::new(address, name, LabelSource::Ens))
+    }
+}
+
+/// Compute the ENS namehash of a dot-separated name
+fn namehash(name: &str) -> B256 {
+    name.rsplit('.').filter(|label| !label.is_empty()).fold(B256::ZERO, |node, label| {
+        keccak256([node.as_slice(), keccak256(label.as_bytes()).as_slice()].concat())
+    })
+}
+
+/// Resolve the primary ENS name of an address, verified against the forward resolution
+fn ens_reverse_lookup<DB>(context: &EngineContext<DB>, address: Address) -> Result<Option<String>>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let resolver_fn = Function::parse("function resolver(bytes32) view returns (address)")?;
+    let name_fn = Function::parse("function name(bytes32) view returns (string)")?;
+    let addr_fn = Function::parse("function addr(bytes32) view returns (address)")?;
+
+    // Every lookup runs against the state before the transaction
+    let call = |to: Address, function: &Function, node: B256| {
+        context.call_in_derived_evm(0, to, function, &[DynSolValue::FixedBytes(node, 32)], None)
+    };
+    let resolver_of = |node: B256| -> Result<Option<Address>> {
+        match call(ENS_REGISTRY, &resolver_fn, node)? {
+            DynSolValue::Address(resolver) if !resolver.is_zero() => Ok(Some(resolver)),
+            DynSolValue::Address(_) => Ok(None),
+            other => Err(eyre!("Unexpected resolver value: {:?}", other)),
+        }
+    };
+
+    let reverse_node = namehash(&format!("{}.addr.reverse", hex::encode(address)));
+    let Some(reverse_resolver) = resolver_of(reverse_node)? else {
+        return Ok(None);
+    };
+    let name = match call(reverse_resolver, &name_fn, reverse_node)? {
+        DynSolValue::String(name) if !name.is_empty() => name,
+        _ => return Ok(None),
+    };
+
+    // The reverse record can be set to any name, so it must resolve back to the address
+    let node = namehash(&name);
+    let Some(resolver) = resolver_of(node)? else {
+        return Ok(None);
+    };
+    match call(resolver, &addr_fn, node)? {
+        DynSolValue::Address(resolved) if resolved == address => Ok(Some(name)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::b256;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("0x93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("foo.eth"),
+            b256!("0xde9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+    }
+}
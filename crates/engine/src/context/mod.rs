@@ -31,6 +31,8 @@
 //! - **Artifacts**: Original and recompiled contract artifacts with source code
 //! - **Analysis Results**: Instrumentation points and debugging metadata
 //! - **Execution Trace**: Call hierarchy and frame structure
+//! - **Logs**: Emitted events attributed to their emitting snapshots
+//! - **Findings**: Security-relevant patterns detected over the finalized session
 //!
 //! ## EVM Instantiation
 //! The context provides methods to create derived EVMs for expression evaluation:
@@ -38,6 +40,11 @@
 //! - **Transaction replay**: Send mock transactions in derived state
 //! - **Function calls**: Invoke contract functions for expression evaluation
 //!
+//! ## Address Labels
+//! Addresses are labeled from a built-in table of well-known contracts, or through their
+//! verified ENS reverse record (see [`ContextLabelTr`]). ERC-20 tokens additionally
+//! expose their symbol and decimals (see [`ContextTokenTr`]).
+//!
 //! # Workflow Integration
 //!
 //! 1. **Context Building**: Constructed after analysis and snapshot collection
@@ -54,9 +61,15 @@
 mod evm;
 pub use evm::*;
 
+mod label;
+pub use label::*;
+
 mod query;
 pub use query::*;
 
+mod token;
+pub use token::*;
+
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
@@ -64,7 +77,7 @@ use std::{
 
 use alloy_primitives::{Address, TxHash};
 use edb_common::{
-    types::{parse_callable_abi_entries, Trace},
+    types::{parse_callable_abi_entries, BridgeMessage, CapturedLog, Finding, Trace},
     ForkInfo,
 };
 use eyre::{eyre, Result};
@@ -78,7 +91,10 @@ use revm::{
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error};
 
-use crate::{analysis::AnalysisResult, Artifact, SnapshotDetail, Snapshots};
+use crate::{
+    analysis::AnalysisResult, findings, AccessLists, Artifact, EngineMetrics, SnapshotDetail,
+    Snapshots, UninitializedStorageRead, WatchedExpressions,
+};
 
 /// Complete debugging context containing all analysis results and state snapshots
 ///
@@ -113,6 +129,32 @@ where
     pub analysis_results: HashMap<Address, AnalysisResult>,
     /// Execution trace showing call hierarchy and frame structure
     pub trace: Trace,
+    /// Zero-valued storage reads of slots not written before in the transaction
+    pub uninitialized_reads: Vec<UninitializedStorageRead>,
+    /// Accounts and storage slots touched by each frame
+    #[serde(default)]
+    pub access_lists: AccessLists,
+    /// Wall-clock time spent replaying each frame and its children, in nanoseconds,
+    /// indexed by trace entry id. Empty if the trace was not replayed locally.
+    #[serde(default)]
+    pub frame_times: Vec<u64>,
+    /// Events emitted during the transaction, in emission order
+    #[serde(default)]
+    pub logs: Vec<CapturedLog>,
+    /// Findings reported by the detectors once the context is finalized
+    pub findings: Vec<Finding>,
+    /// Messages sent through canonical bridges, with the correlated transactions
+    #[serde(default)]
+    pub bridge_messages: Vec<BridgeMessage>,
+    /// Performance metrics recorded while preparing the transaction
+    #[serde(default)]
+    pub metrics: EngineMetrics,
+    /// State before the transaction, from which it is re-executed with state overrides
+    #[serde(skip)]
+    pub initial_db: Option<Arc<CacheDB<DB>>>,
+    /// Expressions evaluated at every snapshot, shared by all clients of the context
+    #[serde(skip)]
+    pub watches: WatchedExpressions,
     /// Relation between target addresses and their (delegated) code addresses
     #[serde(skip)]
     address_code_address_map: OnceCell<HashMap<Address, HashSet<Address>>>,
@@ -141,6 +183,8 @@ where
     /// * `recompiled_artifacts` - Recompiled artifacts with instrumentation
     /// * `analysis_results` - Analysis results identifying instrumentation points
     /// * `trace` - Execution trace showing call hierarchy
+    /// * `uninitialized_reads` - Zero-valued storage reads collected during replay
+    /// * `access_lists` - Accounts and storage slots touched by each frame
     ///
     /// # Returns
     ///
@@ -157,6 +201,8 @@ where
         recompiled_artifacts: HashMap<Address, Artifact>,
         analysis_results: HashMap<Address, AnalysisResult>,
         trace: Trace,
+        uninitialized_reads: Vec<UninitializedStorageRead>,
+        access_lists: AccessLists,
     ) -> Result<Self> {
         let mut context = Self {
             fork_info,
@@ -169,6 +215,15 @@ where
             recompiled_artifacts,
             analysis_results,
             trace,
+            uninitialized_reads,
+            access_lists,
+            frame_times: Vec::new(),
+            logs: Vec::new(),
+            findings: Vec::new(),
+            bridge_messages: Vec::new(),
+            metrics: EngineMetrics::default(),
+            initial_db: None,
+            watches: WatchedExpressions::default(),
             address_code_address_map: OnceCell::new(),
         };
 
@@ -181,11 +236,15 @@ where
     ///
     /// This method performs post-processing on the collected debugging data:
     /// 1. Links trace entries with their corresponding snapshot IDs
-    /// 2. Pre-evaluates state variables for all hook-based snapshots
-    /// 3. Populates derived mappings for efficient lookups
+    /// 2. Attributes emitted events to their emitting snapshots
+    /// 3. Pre-evaluates state variables for all hook-based snapshots
+    /// 4. Populates derived mappings for efficient lookups
+    /// 5. Runs the detectors to collect findings
     fn finalize(&mut self) -> Result<()> {
         self.finalize_trace()?;
+        self.finalize_logs()?;
         self.finalize_snapshots()?;
+        self.finalize_findings()?;
 
         Ok(())
     }
@@ -216,6 +275,73 @@ where
         Ok(())
     }
 
+    /// Finalize logs by pairing the logs captured during snapshot collection with the
+    /// events recorded in the trace.
+    ///
+    /// If no log was captured (e.g., for imported traces), the events are listed in trace
+    /// order and attributed to the first snapshot of their trace entry instead.
+    fn finalize_logs(&mut self) -> Result<()> {
+        let captured = self.snapshots.logs();
+
+        if captured.is_empty() {
+            self.logs = self
+                .trace
+                .iter()
+                .flat_map(|entry| {
+                    entry.events.iter().enumerate().map(|(index, log)| CapturedLog {
+                        trace_entry_id: entry.id,
+                        index,
+                        address: entry.target,
+                        log: log.clone(),
+                        snapshot_id: entry.first_snapshot_id,
+                    })
+                })
+                .collect();
+            return Ok(());
+        }
+
+        let mut next_index: HashMap<usize, usize> = HashMap::new();
+        for (frame_id, snapshot_id) in captured {
+            let trace_entry_id = frame_id.trace_entry_id();
+            let index = next_index.entry(trace_entry_id).or_default();
+
+            let Some(entry) = self.trace.get(trace_entry_id) else {
+                error!("Log captured for unknown trace entry {}", trace_entry_id);
+                continue;
+            };
+            let Some(log) = entry.events.get(*index) else {
+                error!("Trace entry {} has fewer events than captured logs", trace_entry_id);
+                continue;
+            };
+
+            self.logs.push(CapturedLog {
+                trace_entry_id,
+                index: *index,
+                address: entry.target,
+                log: log.clone(),
+                snapshot_id: *snapshot_id,
+            });
+            *index += 1;
+        }
+
+        debug!("Captured {} log(s)", self.logs.len());
+        Ok(())
+    }
+
+    /// Finalize findings by running all detectors over the trace and snapshots.
+    ///
+    /// This must run after the trace and snapshots are finalized, since detectors
+    /// rely on the snapshot ids linked to trace entries.
+    fn finalize_findings(&mut self) -> Result<()> {
+        self.findings = findings::detect_findings(self);
+
+        for finding in &self.findings {
+            debug!("Finding: {}", finding);
+        }
+
+        Ok(())
+    }
+
     /// Finalize snapshots by pre-evaluating state variables.
     ///
     /// This method processes all hook-based snapshots to pre-evaluate their state
@@ -256,13 +382,7 @@ where
                 .into_iter()
                 .filter(|v| v.is_state_variable() && v.inputs.is_empty())
             {
-                match self.call_in_derived_evm(
-                    snapshot_id,
-                    snapshot.target_address(),
-                    &state_variable.abi,
-                    &[],
-                    None,
-                ) {
+                match self.call_in_frame_context(snapshot_id, &state_variable.abi, &[]) {
                     Ok(value) => {
                         states.insert(state_variable.name.clone(), Some(Arc::new(value.into())));
                     }
@@ -17,7 +17,8 @@
 use std::collections::{HashMap, HashSet};
 
 use alloy_primitives::Address;
-use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use edb_common::types::CallType;
+use revm::{database::CacheDB, interpreter::CallScheme, Database, DatabaseCommit, DatabaseRef};
 
 use crate::EngineContext;
 
@@ -38,6 +39,12 @@ pub trait ContextQueryTr {
     /// receiving the call in the current execution frame.
     fn get_target_address(&self, snapshot_id: usize) -> Option<Address>;
 
+    /// Check if a snapshot belongs to a frame entered by DELEGATECALL or CALLCODE.
+    ///
+    /// Such frames run the code of the bytecode address against the storage of the
+    /// target address.
+    fn is_delegated(&self, snapshot_id: usize) -> bool;
+
     /// Check if one trace entry is the parent of another.
     ///
     /// This method determines the parent-child relationship between trace entries,
@@ -68,6 +75,18 @@ where
         self.trace.get(frame_id.trace_entry_id()).map(|entry| entry.target)
     }
 
+    fn is_delegated(&self, snapshot_id: usize) -> bool {
+        let Some((frame_id, _)) = self.snapshots.get(snapshot_id) else {
+            return false;
+        };
+        self.trace.get(frame_id.trace_entry_id()).is_some_and(|entry| {
+            matches!(
+                entry.call_type,
+                CallType::Call(CallScheme::DelegateCall | CallScheme::CallCode)
+            )
+        })
+    }
+
     fn is_parent_trace(&self, parent_id: usize, child_id: usize) -> bool {
         match self.trace.get(child_id) {
             Some(child_entry) => child_entry.parent_id == Some(parent_id),
@@ -0,0 +1,98 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_json_abi::Function;
+use alloy_primitives::Address;
+use edb_common::types::TokenInfo;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use tracing::debug;
+
+use crate::{ContextEvmTr, ContextQueryTr, EngineContext};
+
+/// Trait providing ERC-20 token metadata on the EngineContext.
+pub trait ContextTokenTr {
+    /// Get the symbol and decimals of an ERC-20 token.
+    ///
+    /// Both are read in the state before the transaction. If the address is the
+    /// implementation of a single proxy in the trace, the metadata of the proxy is
+    /// returned instead, since the implementation's own storage is usually empty.
+    fn token_info(&self, address: Address) -> Option<TokenInfo>;
+}
+
+impl<DB> ContextTokenTr for EngineContext<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    fn token_info(&self, address: Address) -> Option<TokenInfo> {
+        if let Some(info) = read_token_info(self, address) {
+            return Some(info);
+        }
+
+        let mut proxies = self
+            .address_code_address_map()
+            .iter()
+            .filter(|(target, code_addresses)| {
+                **target != address && code_addresses.contains(&address)
+            })
+            .map(|(target, _)| *target);
+        match (proxies.next(), proxies.next()) {
+            (Some(proxy), None) => read_token_info(self, proxy),
+            _ => None,
+        }
+    }
+}
+
+/// Call `symbol()` and `decimals()` on an address
+fn read_token_info<DB>(context: &EngineContext<DB>, address: Address) -> Option<TokenInfo>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let call = |signature: &str| {
+        let function = Function::parse(signature).ok()?;
+        context
+            .call_in_derived_evm(0, address, &function, &[], None)
+            .inspect_err(|e| debug!("Failed to call {} on {}: {}", function.name, address, e))
+            .ok()
+    };
+
+    let decimals = match call("function decimals() view returns (uint8)")? {
+        DynSolValue::Uint(decimals, _) => u8::try_from(decimals).ok()?,
+        _ => return None,
+    };
+
+    // Some early tokens (e.g., MKR) return their symbol as bytes32
+    let symbol = match call("function symbol() view returns (string)") {
+        Some(DynSolValue::String(symbol)) => symbol,
+        _ => match call("function symbol() view returns (bytes32)")? {
+            DynSolValue::FixedBytes(bytes, _) => {
+                String::from_utf8_lossy(bytes.as_slice()).trim_end_matches('\0').to_string()
+            }
+            _ => return None,
+        },
+    };
+
+    let symbol = symbol.trim();
+    if symbol.is_empty() {
+        return None;
+    }
+
+    Some(TokenInfo { address, symbol: symbol.to_string(), decimals })
+}
@@ -43,23 +43,41 @@
 //! - **Multi-contract analysis**: Analyze all contracts involved in execution
 //! - **Source fetching**: Automatic download from Etherscan and verification
 //! - **Quick mode**: Fast analysis with reduced operations
+//! - **Focused preparation**: Step-by-step snapshots of a single subtree of the trace
 //! - **Instrumentation**: Automatic debugging hook injection
 //! - **Comprehensive inspection**: Opcode and source-level snapshot collection
 
 use alloy_primitives::TxHash;
 use dashmap::DashMap;
 use edb_common::ForkResult;
-use eyre::Result;
-use revm::{context::Host, database::CacheDB, Database, DatabaseCommit, DatabaseRef};
-use std::{net::SocketAddr, sync::Arc};
+use eyre::{eyre, Result};
+use revm::{
+    context::{ContextTr, Host},
+    database::CacheDB,
+    Database, DatabaseCommit, DatabaseRef,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+};
 use tokio::sync::{mpsc, Mutex};
-use tracing::info;
+use tracing::{info, warn};
+
+#[cfg(unix)]
+use crate::rpc::UnixRpcServerHandle;
+#[cfg(unix)]
+use std::path::PathBuf;
 
 use crate::{
-    orchestration,
-    rpc::{start_debug_server, RpcServerHandle},
-    utils::next_etherscan_api_key,
-    EngineContext, SnapshotAnalysis,
+    build_external_snapshots, evaluate_watches, find_bridge_messages, install_mock_contracts,
+    is_bridged_chain, is_cancelled, orchestration, prefetch_solc, register_watches,
+    rpc::{DebugRpcServer, RpcSecurityConfig, RpcServerHandle, StatusRpcServer},
+    set_solc_config,
+    utils::ExplorerConfig,
+    AccessLists, CancellationToken, EngineContext, EngineEvent, EventBus, ExternalTrace,
+    MetricsRecorder, MockContract, PreparationId, PreparationPhase, PreparationTracker,
+    SnapshotAnalysis, Snapshots, SolcConfig, TraceFocus,
 };
 
 /// Configuration for the EDB debugging engine.
@@ -70,10 +88,34 @@ use crate::{
 pub struct EngineConfig {
     /// RPC provider URL for blockchain interaction (typically a proxy or archive node)
     pub rpc_proxy_url: String,
-    /// Optional Etherscan API key for automatic source code downloading and verification
+    /// Optional Etherscan API key for automatic source code downloading and verification,
+    /// used for every chain without a key of its own in `explorers`
     pub etherscan_api_key: Option<String>,
+    /// Block explorer of each chain using another explorer or key, by chain ID
+    pub explorers: BTreeMap<u64, ExplorerConfig>,
+    /// Offline flag - when enabled, block explorers are never queried and only cached
+    /// verified sources are used
+    pub explorer_offline: bool,
     /// Quick mode flag - when enabled, skips time-intensive operations for faster analysis
     pub quick: bool,
+    /// Refetch flag - when enabled, the cached prestate of the transaction is ignored and
+    /// the preceding transactions are replayed again
+    pub refetch: bool,
+    /// Quiet flag - when enabled, no trace tree or snapshot summary is printed to stdout
+    pub quiet: bool,
+    /// Authentication and TLS settings of the RPC servers started by the engine
+    pub rpc_security: RpcSecurityConfig,
+    /// Mock contracts installed in place of deployed contracts before replay
+    pub mocks: Vec<MockContract>,
+    /// RPC endpoints of other chains, by chain ID, used to correlate bridged transactions
+    pub chain_rpc_urls: BTreeMap<u64, String>,
+    /// Call of the trace whose subtree is recorded step by step, while only the first
+    /// step of each frame is recorded elsewhere
+    pub focus: Option<TraceFocus>,
+    /// Expressions evaluated at every snapshot once the transaction is prepared
+    pub watches: Vec<String>,
+    /// Where the solc binaries used to compile contracts come from
+    pub solc: SolcConfig,
 }
 
 impl Default for EngineConfig {
@@ -81,7 +123,17 @@ impl Default for EngineConfig {
         Self {
             rpc_proxy_url: "http://localhost:8545".into(),
             etherscan_api_key: None,
+            explorers: BTreeMap::new(),
+            explorer_offline: false,
             quick: false,
+            refetch: false,
+            quiet: false,
+            rpc_security: RpcSecurityConfig::default(),
+            mocks: Vec::new(),
+            chain_rpc_urls: BTreeMap::new(),
+            focus: None,
+            watches: Vec::new(),
+            solc: SolcConfig::default(),
         }
     }
 }
@@ -93,21 +145,86 @@ impl EngineConfig {
         self
     }
 
+    /// Set the block explorer of a chain, instead of Etherscan with the default key
+    pub fn with_explorer(mut self, chain_id: u64, explorer: ExplorerConfig) -> Self {
+        self.explorers.insert(chain_id, explorer);
+        self
+    }
+
+    /// Only use cached verified sources instead of querying the block explorers
+    pub fn with_explorer_offline(mut self, offline: bool) -> Self {
+        self.explorer_offline = offline;
+        self
+    }
+
     /// Enable or disable quick mode for faster analysis
     pub fn with_quick_mode(mut self, quick: bool) -> Self {
         self.quick = quick;
         self
     }
 
+    /// Ignore and overwrite the cached prestate of the transaction
+    pub fn with_refetch(mut self, refetch: bool) -> Self {
+        self.refetch = refetch;
+        self
+    }
+
+    /// Enable or disable printing of the trace tree and snapshot summaries
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
+
+    /// Require authentication and/or TLS on the RPC servers started by the engine
+    pub fn with_rpc_security(mut self, security: RpcSecurityConfig) -> Self {
+        self.rpc_security = security;
+        self
+    }
+
+    /// Install a mock contract at its address before replaying transactions
+    pub fn with_mock(mut self, mock: MockContract) -> Self {
+        self.mocks.push(mock);
+        self
+    }
+
+    /// Set the RPC endpoint of another chain, used to correlate bridged transactions
+    pub fn with_chain_rpc_url(mut self, chain_id: u64, url: String) -> Self {
+        self.chain_rpc_urls.insert(chain_id, url);
+        self
+    }
+
+    /// Focus preparation on the subtree of a call of the trace
+    pub fn with_focus(mut self, focus: TraceFocus) -> Self {
+        self.focus = Some(focus);
+        self
+    }
+
+    /// Evaluate an expression at every snapshot of each prepared transaction
+    pub fn with_watch(mut self, expr: String) -> Self {
+        self.watches.push(expr);
+        self
+    }
+
+    /// Set where the solc binaries used to compile contracts come from
+    pub fn with_solc(mut self, solc: SolcConfig) -> Self {
+        self.solc = solc;
+        self
+    }
+
     /// Set the RPC proxy URL for blockchain interactions
     pub fn with_rpc_proxy_url(mut self, url: String) -> Self {
         self.rpc_proxy_url = url;
         self
     }
 
-    /// Get the Etherscan API key, either from config or rotate to the next available key
-    pub fn get_etherscan_api_key(&self) -> String {
-        self.etherscan_api_key.clone().unwrap_or(next_etherscan_api_key())
+    /// Get the block explorer of a chain, falling back to Etherscan with the default key
+    pub fn explorer(&self, chain_id: u64) -> ExplorerConfig {
+        let mut explorer = self.explorers.get(&chain_id).cloned().unwrap_or_default();
+        if !explorer.has_keys() && explorer.api_url.is_none() {
+            explorer.api_key = self.etherscan_api_key.clone();
+        }
+        explorer.offline |= self.explorer_offline;
+        explorer
     }
 }
 
@@ -127,6 +244,13 @@ pub struct Engine {
 
     /// Configuration for the engine
     config: EngineConfig,
+
+    /// Events published while preparing and debugging transactions, shared with all
+    /// RPC servers started by this engine
+    events: EventBus,
+
+    /// Status of the preparations started by this engine
+    preparations: PreparationTracker,
 }
 
 impl Default for Engine {
@@ -137,14 +261,37 @@ impl Default for Engine {
 
 impl Engine {
     /// Create a new Engine instance from configuration
+    ///
+    /// The solc configuration applies to the whole process, see [`set_solc_config`].
     pub fn new(config: EngineConfig) -> Self {
+        set_solc_config(config.solc.clone());
         Self {
             server_handles: Arc::new(DashMap::new()),
             in_flight: Arc::new(DashMap::new()),
             config,
+            events: EventBus::default(),
+            preparations: PreparationTracker::default(),
         }
     }
 
+    /// Get the event bus on which this engine publishes [`EngineEvent`]s
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Get the status tracker of the preparations started by this engine
+    pub fn preparations(&self) -> &PreparationTracker {
+        &self.preparations
+    }
+
+    /// Serve the status of all preparations over HTTP (`GET /status`) on the given port
+    pub async fn serve_status(&self, port: u16) -> Result<RpcServerHandle> {
+        StatusRpcServer::new(self.preparations.clone())
+            .with_security(self.config.rpc_security.clone())
+            .start_on_port(port)
+            .await
+    }
+
     /// Get the RPC server address for a given transaction hash, if it exists
     pub fn get_rpc_server_addr(&self, tx_hash: &TxHash) -> Option<SocketAddr> {
         self.server_handles.get(tx_hash).map(|handle| handle.addr())
@@ -191,26 +338,6 @@ impl Engine {
         <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
         <DB as Database>::Error: Clone + Send + Sync,
     {
-        // a utility macro to send progress message to the progress channel, if it exists
-        macro_rules! send_progress {
-            // With step tracking: send_progress!(current, total, "message")
-            ($current:expr, $total:expr, $($message:tt)*) => {
-                progress_tx.as_ref().map(|tx| {
-                    tx.send(edb_common::ProgressMessage::with_steps(
-                        format!($($message)*),
-                        $current,
-                        $total
-                    )).ok()
-                });
-            };
-            // Without step tracking: send_progress!("message")
-            ($($message:tt)*) => {
-                progress_tx.as_ref().map(|tx| {
-                    tx.send(edb_common::ProgressMessage::new(format!($($message)*))).ok()
-                });
-            };
-        }
-
         let tx_hash = fork_result.target_tx_hash;
 
         // Get or create per-transaction lock
@@ -227,15 +354,217 @@ impl Engine {
                 tx_hash,
                 existing_handle.addr()
             );
-            send_progress!("Transaction {:?} already analyzed", tx_hash);
+            progress_tx.as_ref().map(|tx| {
+                tx.send(edb_common::ProgressMessage::new(format!(
+                    "Transaction {tx_hash:?} already analyzed"
+                )))
+                .ok()
+            });
             return Ok(existing_handle.addr());
         }
 
-        info!("Starting engine preparation for transaction: {:?}", tx_hash);
+        let (preparation, _) = self.preparations.start(tx_hash);
+        let context = self
+            .build_tracked_context(fork_result, progress_tx, &[], self.config.focus, preparation)
+            .await?;
+
+        let rpc_handle = DebugRpcServer::new(context)
+            .with_events(self.events.clone())
+            .with_preparation(self.preparations.clone(), preparation)
+            .with_security(self.config.rpc_security.clone())
+            .start()
+            .await?;
+        info!("Debug RPC server started on {}", rpc_handle.addr());
+
+        // Store the server handle for future reference
+        let addr = rpc_handle.addr();
+        self.server_handles.insert(tx_hash, rpc_handle);
+
+        Ok(addr)
+    }
+
+    /// Prepare a transaction like [`Engine::prepare`], but serve the JSON-RPC server on a
+    /// unix domain socket instead of a TCP port
+    ///
+    /// The server is owned by the caller through the returned handle rather than kept by
+    /// the engine, so each call prepares the transaction anew.
+    #[cfg(unix)]
+    pub async fn prepare_on_unix_socket<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+        path: impl Into<PathBuf>,
+    ) -> Result<UnixRpcServerHandle>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        let (preparation, _) = self.preparations.start(fork_result.target_tx_hash);
+        let context = self
+            .build_tracked_context(fork_result, progress_tx, &[], self.config.focus, preparation)
+            .await?;
+
+        DebugRpcServer::new(context)
+            .with_events(self.events.clone())
+            .with_preparation(self.preparations.clone(), preparation)
+            .with_security(self.config.rpc_security.clone())
+            .start_on_unix_socket(path)
+            .await
+    }
+
+    /// Build the debugging context of a transaction without starting an RPC server.
+    ///
+    /// This runs the complete preparation workflow of [`Engine::prepare`] (steps 1 to 8)
+    /// and returns the finalized [`EngineContext`], which can be consumed in-process, e.g.,
+    /// through a [`crate::Session`]. Unlike [`Engine::prepare`], no per-transaction caching
+    /// is performed, so each call prepares the transaction anew.
+    pub async fn build_context<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+    ) -> Result<EngineContext<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        self.build_context_with_mocks(fork_result, progress_tx, &[]).await
+    }
+
+    /// Build the debugging context of a transaction with additional mock contracts.
+    ///
+    /// Like [`Engine::build_context`], but the given mocks are installed along with the
+    /// ones of the engine configuration, e.g., to prepare a session with its own mocks.
+    pub async fn build_context_with_mocks<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+        mocks: &[MockContract],
+    ) -> Result<EngineContext<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        self.build_context_with_focus(fork_result, progress_tx, mocks, self.config.focus).await
+    }
+
+    /// Build the debugging context of a transaction focused on a subtree of its trace.
+    ///
+    /// Like [`Engine::build_context_with_mocks`], but the given focus is used instead of
+    /// the one of the engine configuration. With no focus, every step is recorded.
+    pub async fn build_context_with_focus<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+        mocks: &[MockContract],
+        focus: Option<TraceFocus>,
+    ) -> Result<EngineContext<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        let (preparation, _) = self.preparations.start(fork_result.target_tx_hash);
+        self.build_tracked_context(fork_result, progress_tx, mocks, focus, preparation).await
+    }
+
+    /// Build the debugging context of a transaction as a preparation started before in
+    /// the tracker of the engine (see [`Engine::preparations`]).
+    ///
+    /// Like [`Engine::build_context_with_focus`], but the progress and outcome are
+    /// recorded under the given preparation, which can thus be started and cancelled
+    /// before the transaction is forked, e.g., by a [`crate::SessionManager`].
+    pub async fn build_tracked_context<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+        mocks: &[MockContract],
+        focus: Option<TraceFocus>,
+        preparation: PreparationId,
+    ) -> Result<EngineContext<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        let tx_hash = fork_result.target_tx_hash;
+        let cancel = self
+            .preparations
+            .cancellation_token(preparation)
+            .ok_or_else(|| eyre!("Unknown preparation {}", preparation))?;
+
+        let mocks: Vec<_> = self.config.mocks.iter().chain(mocks).cloned().collect();
+        let result = self
+            .build_context_inner(fork_result, progress_tx, mocks, focus, preparation, &cancel)
+            .await;
+        match &result {
+            Ok(_) => self.preparations.finish(preparation),
+            Err(e) if is_cancelled(e) => {
+                info!("Cancelled preparation of transaction {:?}", tx_hash);
+                self.preparations.cancelled(preparation);
+            }
+            Err(e) => self.preparations.fail(preparation, e),
+        }
+        result
+    }
+
+    /// Run the preparation workflow of [`Engine::build_context`]
+    async fn build_context_inner<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+        mocks: Vec<MockContract>,
+        focus: Option<TraceFocus>,
+        preparation: PreparationId,
+        cancel: &CancellationToken,
+    ) -> Result<EngineContext<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        let target_tx_hash = fork_result.target_tx_hash;
+        let report_progress = |message: edb_common::ProgressMessage| {
+            self.preparations.advance(preparation, &message);
+            self.events.publish(EngineEvent::progress(target_tx_hash, message.clone()));
+            progress_tx.as_ref().map(|tx| tx.send(message).ok());
+        };
+
+        // a utility macro to report progress to the progress channel, if it exists, and to
+        // the subscribers of the event bus. Every phase starts with a progress report, so
+        // this is also where a cancelled preparation stops.
+        macro_rules! send_progress {
+            // With step tracking: send_progress!(current, total, "message")
+            ($current:expr, $total:expr, $($message:tt)*) => {
+                cancel.check()?;
+                report_progress(edb_common::ProgressMessage::with_steps(
+                    format!($($message)*),
+                    $current,
+                    $total
+                ));
+            };
+            // Without step tracking: send_progress!("message")
+            ($($message:tt)*) => {
+                cancel.check()?;
+                report_progress(edb_common::ProgressMessage::new(format!($($message)*)));
+            };
+        }
+
+        info!("Starting engine preparation for transaction: {:?}", fork_result.target_tx_hash);
 
         // Step 0: Initialize context and database
-        let ForkResult { context: mut ctx, target_tx_env: tx, target_tx_hash: tx_hash, fork_info } =
-            fork_result;
+        let ForkResult {
+            context: mut ctx,
+            target_tx_env: tx,
+            target_tx_hash: tx_hash,
+            fork_info,
+            fork_duration,
+        } = fork_result;
+        let mut metrics = MetricsRecorder::start(fork_duration);
+        install_mock_contracts(&mut ctx, &mocks)?;
+        let initial_db = Arc::new(ctx.db().clone());
 
         // Step 1: Replay the target transaction to collect call trace and touched contracts
         send_progress!(
@@ -244,24 +573,48 @@ impl Engine {
             "Replaying the target transaction to collect call trace and touched contracts..."
         );
         let replay_result = orchestration::replay_and_collect_trace(ctx.clone(), tx.clone())?;
+        metrics.finish_phase(PreparationPhase::Replay);
+        if !self.config.quiet {
+            replay_result.execution_trace.print_trace_tree();
+        }
+        let focus = focus.map(|focus| focus.entries(&replay_result.execution_trace)).transpose()?;
+        if let Some(entries) = &focus {
+            info!(
+                "Focusing preparation on {} of {} trace entries",
+                entries.len(),
+                replay_result.execution_trace.len()
+            );
+        }
 
         // Step 2: Download verified source code for each contract
         send_progress!(2, 8, "Downloading verified source code for each contract...");
-        let artifacts = orchestration::download_verified_source_code(
+        let mut artifacts = orchestration::download_verified_source_code(
             &self.config,
             &replay_result,
             ctx.chain_id().to::<u64>(),
+            &mocks,
         )
         .await?;
+        orchestration::link_external_libraries(&mut ctx, &replay_result, &mut artifacts)?;
+        metrics.finish_phase(PreparationPhase::Download);
 
         // Step 3: Analyze source code to identify instrumentation points
         send_progress!(3, 8, "Analyzing source code to identify instrumentation points...");
         let analysis_results = orchestration::analyze_source_code(&artifacts)?;
+        metrics.finish_phase(PreparationPhase::Analyze);
 
         // Step 4: Instrument source code
         send_progress!(4, 8, "Instrumenting source code...");
-        let recompiled_artifacts =
-            orchestration::instrument_and_recompile_source_code(&artifacts, &analysis_results)?;
+        prefetch_solc(
+            artifacts.values().filter_map(|artifact| artifact.meta.compiler_version().ok()),
+        )
+        .await?;
+        let recompiled_artifacts = orchestration::instrument_and_recompile_source_code(
+            &artifacts,
+            &analysis_results,
+            cancel,
+        )?;
+        metrics.finish_phase(PreparationPhase::Compile);
 
         // Step 5: Collect opcode-level step execution results
         send_progress!(5, 8, "Collecting opcode-level step execution results...");
@@ -270,7 +623,12 @@ impl Engine {
             tx.clone(),
             artifacts.keys().cloned().collect(),
             &replay_result.execution_trace,
+            focus.as_ref(),
         )?;
+        metrics.finish_phase(PreparationPhase::OpcodeSnapshots);
+        if !self.config.quiet {
+            opcode_snapshots.print_summary();
+        }
 
         // Step 6: Replace original bytecode with instrumented versions
         send_progress!(6, 8, "Replacing original bytecode with instrumented versions...");
@@ -282,6 +640,7 @@ impl Engine {
             tx_hash,
         )
         .await?;
+        metrics.finish_phase(PreparationPhase::Instrument);
 
         // Step 7: Re-execute the transaction with snapshot collection
         send_progress!(7, 8, "Collecting creation hooks for contracts in transaction...");
@@ -296,16 +655,26 @@ impl Engine {
             hook_creation,
             &replay_result.execution_trace,
             &analysis_results,
+            focus.as_ref(),
         )?;
+        metrics.finish_phase(PreparationPhase::HookSnapshots);
+        if !self.config.quiet {
+            hook_snapshots.print_summary();
+        }
 
-        // Step 8: Start RPC server with analysis results and snapshots
+        // Step 8: Merge opcode-level and hook-level snapshots for time travel
         send_progress!(8, 8, "Collecting opcode-level and hook-level snapshots...");
         let mut snapshots =
             orchestration::get_time_travel_snapshots(opcode_snapshots, hook_snapshots)?;
+        if !self.config.quiet {
+            snapshots.print_summary();
+        }
         snapshots.analyze(&replay_result.execution_trace, &analysis_results)?;
+        metrics.finish_phase(PreparationPhase::SnapshotMerge);
 
         // Let's pack the debug context
-        let context = EngineContext::build(
+        send_progress!("Finalizing {} steps...", snapshots.len());
+        let mut context = EngineContext::build(
             fork_info,
             ctx.cfg.clone(),
             ctx.block.clone(),
@@ -316,12 +685,177 @@ impl Engine {
             recompiled_artifacts,
             analysis_results,
             replay_result.execution_trace,
+            replay_result.uninitialized_reads,
+            replay_result.access_lists,
         )?;
+        context.frame_times = replay_result.frame_times;
+        cancel.check()?;
+        if is_bridged_chain(context.fork_info.chain_id) {
+            context.bridge_messages =
+                find_bridge_messages(&self.config, &context.fork_info, tx_hash, &context.logs)
+                    .await
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to correlate bridged transactions: {}", e);
+                        Vec::new()
+                    });
+        }
+        metrics.finish_phase(PreparationPhase::Finalize);
+        context.metrics = metrics.finish(context.snapshots.len(), context.trace.len());
+        context.initial_db = Some(initial_db);
+        if !self.config.watches.is_empty() {
+            send_progress!("Evaluating {} watched expressions...", self.config.watches.len());
+            let shared = Arc::new(context);
+            let exprs = register_watches(&shared, &self.config.watches);
+            evaluate_watches(&shared, &exprs);
+            context = Arc::try_unwrap(shared)
+                .map_err(|_| eyre!("Engine context is still shared after evaluating watches"))?;
+        }
+        info!("Prepared transaction {:?} in {:.0}ms", tx_hash, context.metrics.total_ms);
+
+        for finding in &context.findings {
+            self.events.publish(EngineEvent::Finding { tx_hash, finding: finding.clone() });
+        }
+
+        Ok(context)
+    }
+
+    /// Replay a transaction and decode its call trace, without instrumentation.
+    ///
+    /// Only steps 1 and 2 of [`Engine::prepare`] are performed: the call trace is collected
+    /// and the verified source code of the touched contracts is downloaded to decode calls,
+    /// events, and return values. No snapshots are captured and no RPC server is started,
+    /// so the returned context is only suitable for rendering the trace
+    /// (e.g., with [`crate::colored_call_tree`]).
+    pub async fn decode_trace<DB>(&self, fork_result: ForkResult<DB>) -> Result<EngineContext<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        info!("Decoding call trace for transaction: {:?}", fork_result.target_tx_hash);
+        let ForkResult {
+            context: mut ctx,
+            target_tx_env: tx,
+            target_tx_hash: tx_hash,
+            fork_info,
+            ..
+        } = fork_result;
+        let mocks = &self.config.mocks;
+        install_mock_contracts(&mut ctx, mocks)?;
+
+        let replay_result = orchestration::replay_and_collect_trace(ctx.clone(), tx.clone())?;
+        let mut artifacts = orchestration::download_verified_source_code(
+            &self.config,
+            &replay_result,
+            ctx.chain_id().to::<u64>(),
+            mocks,
+        )
+        .await?;
+        orchestration::link_external_libraries(&mut ctx, &replay_result, &mut artifacts)?;
+
+        EngineContext::build(
+            fork_info,
+            ctx.cfg.clone(),
+            ctx.block.clone(),
+            tx,
+            tx_hash,
+            Snapshots::new(),
+            artifacts,
+            HashMap::new(),
+            HashMap::new(),
+            replay_result.execution_trace,
+            replay_result.uninitialized_reads,
+            replay_result.access_lists,
+        )
+    }
+
+    /// Prepare a reduced debugging session from an external trace instead of a local replay.
+    ///
+    /// The call structure is taken from the `callTracer` result and the account states
+    /// from the `prestateTracer` result of `debug_traceTransaction`, applied on top of the
+    /// forked database. No source code is downloaded and no contract is instrumented, so the
+    /// session only supports trace navigation and storage diffing. See [`crate::import`].
+    ///
+    /// Like [`Engine::prepare`], this method reuses the RPC server of a transaction that
+    /// has already been prepared.
+    pub async fn prepare_from_external_trace<DB>(
+        &self,
+        fork_result: ForkResult<DB>,
+        external_trace: ExternalTrace,
+        progress_tx: Option<mpsc::UnboundedSender<edb_common::ProgressMessage>>,
+    ) -> Result<SocketAddr>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        let send_progress = |current: usize, message: &str| {
+            progress_tx.as_ref().map(|tx| {
+                tx.send(edb_common::ProgressMessage::with_steps(message.to_string(), current, 2))
+                    .ok()
+            });
+        };
+
+        let tx_hash = fork_result.target_tx_hash;
 
-        let rpc_handle = start_debug_server(context).await?;
+        // Get or create per-transaction lock
+        let lock =
+            self.in_flight.entry(tx_hash).or_insert_with(|| Arc::new(Mutex::new(()))).clone();
+        let _guard = lock.lock().await;
+
+        if let Some(existing_handle) = self.server_handles.get(&tx_hash) {
+            info!(
+                "Transaction {:?} already analyzed, returning existing RPC server at {}",
+                tx_hash,
+                existing_handle.addr()
+            );
+            return Ok(existing_handle.addr());
+        }
+
+        info!("Importing external trace for transaction: {:?}", tx_hash);
+        let ForkResult {
+            context: ctx, target_tx_env: tx, target_tx_hash: tx_hash, fork_info, ..
+        } = fork_result;
+
+        // Step 1: Convert the call frames into an execution trace
+        send_progress(1, "Converting the external call trace...");
+        let trace = external_trace.trace();
+        if !self.config.quiet {
+            trace.print_trace_tree();
+        }
+
+        // Step 2: Build synthetic snapshots from the pre- and post-state
+        send_progress(2, "Building snapshots from the external prestate...");
+        let snapshots =
+            build_external_snapshots(ctx.db().clone(), &trace, external_trace.prestate.as_ref())?;
+        if !self.config.quiet {
+            snapshots.print_summary();
+        }
+
+        let mut context = EngineContext::build(
+            fork_info,
+            ctx.cfg.clone(),
+            ctx.block.clone(),
+            tx,
+            tx_hash,
+            snapshots,
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            trace,
+            vec![],
+            AccessLists::default(),
+        )?;
+        // Detectors compare intermediate states, which are unknown for imported traces
+        context.findings.clear();
+
+        let rpc_handle = DebugRpcServer::new(context)
+            .with_events(self.events.clone())
+            .with_security(self.config.rpc_security.clone())
+            .start()
+            .await?;
         info!("Debug RPC server started on {}", rpc_handle.addr());
 
-        // Store the server handle for future reference
         let addr = rpc_handle.addr();
         self.server_handles.insert(tx_hash, rpc_handle);
 
@@ -0,0 +1,382 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Programmatic API for embedding the debugger in other tools.
+//!
+//! The [`Debugger`] prepares debugging sessions in-process, without spawning the `edb`
+//! binary or speaking JSON-RPC. Each prepared [`Session`] gives direct access to the
+//! execution trace, the time-travel snapshots, expression evaluation, and findings.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use edb_engine::Debugger;
+//!
+//! let debugger = Debugger::builder()
+//!     .with_rpc_url("http://localhost:8545".to_string())
+//!     .with_etherscan_api_key(api_key)
+//!     .build();
+//!
+//! let session = debugger.prepare(tx_hash).await?;
+//! for entry in session.trace().iter() {
+//!     println!("{} -> {}", entry.caller, entry.target);
+//! }
+//!
+//! // Walk through the execution and evaluate an expression at every step
+//! let mut snapshot_id = Some(0);
+//! while let Some(id) = snapshot_id {
+//!     if let Ok(value) = session.eval("msg.sender", id) {
+//!         println!("#{id}: {value:?}");
+//!     }
+//!     snapshot_id = session.next_snapshot(id);
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::TxHash;
+use edb_common::{
+    fork_and_prepare_cached,
+    types::{CapturedLog, Finding, Trace},
+    EdbDB, ForkResult,
+};
+use eyre::Result;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+
+#[cfg(unix)]
+use crate::UnixRpcServerHandle;
+use crate::{
+    eval, session_report, stack_trace, DebugRpcServer, Engine, EngineConfig, EngineContext,
+    EngineMetrics, EventBus, MockContract, PreparationId, PreparationTracker, RpcServerHandle,
+    SessionReport, Snapshot, StackTrace, TraceFocus,
+};
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// Builder for a [`Debugger`].
+///
+/// Unlike the `edb` binary, the debugger is quiet by default, i.e., it does not print the
+/// trace tree or snapshot summaries to stdout.
+#[derive(Debug, Clone)]
+pub struct DebuggerBuilder {
+    config: EngineConfig,
+}
+
+impl Default for DebuggerBuilder {
+    fn default() -> Self {
+        Self { config: EngineConfig::default().with_quiet(true) }
+    }
+}
+
+impl DebuggerBuilder {
+    /// Set the RPC URL of the node (or EDB RPC proxy) to fork from
+    pub fn with_rpc_url(mut self, url: String) -> Self {
+        self.config = self.config.with_rpc_proxy_url(url);
+        self
+    }
+
+    /// Set the Etherscan API key for source code download
+    pub fn with_etherscan_api_key(mut self, key: String) -> Self {
+        self.config = self.config.with_etherscan_api_key(key);
+        self
+    }
+
+    /// Enable or disable quick mode, which skips replaying the preceding transactions of
+    /// the block
+    pub fn with_quick_mode(mut self, quick: bool) -> Self {
+        self.config = self.config.with_quick_mode(quick);
+        self
+    }
+
+    /// Enable or disable printing of the trace tree and snapshot summaries
+    pub fn with_quiet(mut self, quiet: bool) -> Self {
+        self.config = self.config.with_quiet(quiet);
+        self
+    }
+
+    /// Only record every step in the subtree of a call of the trace
+    pub fn with_focus(mut self, focus: TraceFocus) -> Self {
+        self.config = self.config.with_focus(focus);
+        self
+    }
+
+    /// Use the given engine configuration as is
+    pub fn with_config(mut self, config: EngineConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Build the debugger
+    pub fn build(self) -> Debugger {
+        Debugger { engine: Engine::new(self.config.clone()), config: self.config }
+    }
+}
+
+/// Entry point for using EDB as a library.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug)]
+pub struct Debugger {
+    engine: Engine,
+    config: EngineConfig,
+}
+
+impl Debugger {
+    /// Create a builder for a debugger
+    pub fn builder() -> DebuggerBuilder {
+        DebuggerBuilder::default()
+    }
+
+    /// Get the configuration of the debugger
+    pub fn config(&self) -> &EngineConfig {
+        &self.config
+    }
+
+    /// Get the event bus on which preparation progress and findings are published
+    pub fn events(&self) -> &EventBus {
+        self.engine.events()
+    }
+
+    /// Get the status of the preparations started by this debugger
+    pub fn preparations(&self) -> &PreparationTracker {
+        self.engine.preparations()
+    }
+
+    /// Fork the chain at the given transaction and prepare a debugging session for it.
+    pub async fn prepare(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<
+        Session<
+            EdbDB<impl Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static>,
+        >,
+    > {
+        let fork_result = fork_and_prepare_cached(
+            &self.config.rpc_proxy_url,
+            tx_hash,
+            self.config.quick,
+            self.config.refetch,
+        )
+        .await?;
+        self.prepare_fork(fork_result).await
+    }
+
+    /// Prepare a debugging session with mock contracts installed before replay.
+    ///
+    /// The mocks are installed in addition to the ones of the engine configuration.
+    pub async fn prepare_with_mocks(
+        &self,
+        tx_hash: TxHash,
+        mocks: &[MockContract],
+    ) -> Result<
+        Session<
+            EdbDB<impl Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static>,
+        >,
+    > {
+        self.prepare_with_focus(tx_hash, mocks, self.config.focus).await
+    }
+
+    /// Prepare a debugging session with mock contracts, focused on a subtree of the trace.
+    ///
+    /// The focus replaces the one of the engine configuration. With no focus, every step
+    /// of the transaction is recorded.
+    pub async fn prepare_with_focus(
+        &self,
+        tx_hash: TxHash,
+        mocks: &[MockContract],
+        focus: Option<TraceFocus>,
+    ) -> Result<
+        Session<
+            EdbDB<impl Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static>,
+        >,
+    > {
+        let (preparation, _) = self.preparations().start(tx_hash);
+        self.prepare_tracked(tx_hash, mocks, focus, preparation).await
+    }
+
+    /// Prepare a debugging session as a preparation started before in
+    /// [`Debugger::preparations`], so that it can be followed and cancelled while the
+    /// transaction is forked.
+    pub async fn prepare_tracked(
+        &self,
+        tx_hash: TxHash,
+        mocks: &[MockContract],
+        focus: Option<TraceFocus>,
+        preparation: PreparationId,
+    ) -> Result<
+        Session<
+            EdbDB<impl Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static>,
+        >,
+    > {
+        let fork_result = match fork_and_prepare_cached(
+            &self.config.rpc_proxy_url,
+            tx_hash,
+            self.config.quick,
+            self.config.refetch,
+        )
+        .await
+        {
+            Ok(fork_result) => fork_result,
+            Err(e) => {
+                self.preparations().fail(preparation, &e);
+                return Err(e);
+            }
+        };
+        let context =
+            self.engine.build_tracked_context(fork_result, None, mocks, focus, preparation).await?;
+        Ok(Session::new(context))
+    }
+
+    /// Prepare a debugging session for an already forked transaction.
+    pub async fn prepare_fork<DB>(&self, fork_result: ForkResult<DB>) -> Result<Session<DB>>
+    where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+        <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+        <DB as Database>::Error: Clone + Send + Sync,
+    {
+        let context = self.engine.build_context(fork_result, None).await?;
+        Ok(Session::new(context))
+    }
+}
+
+/// A prepared debugging session of a single transaction.
+///
+/// Sessions are cheap to clone and can be shared across threads.
+#[derive(Clone)]
+pub struct Session<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    context: Arc<EngineContext<DB>>,
+}
+
+impl<DB> Session<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    /// Create a session from a finalized engine context
+    pub fn new(context: EngineContext<DB>) -> Self {
+        Self { context: Arc::new(context) }
+    }
+
+    /// Get the underlying engine context for lower-level access
+    pub fn context(&self) -> &Arc<EngineContext<DB>> {
+        &self.context
+    }
+
+    /// Get the hash of the debugged transaction
+    pub fn tx_hash(&self) -> TxHash {
+        self.context.tx_hash
+    }
+
+    /// Get the execution trace of the transaction
+    pub fn trace(&self) -> &Trace {
+        &self.context.trace
+    }
+
+    /// Get the events emitted during the transaction, in emission order
+    pub fn logs(&self) -> &[CapturedLog] {
+        &self.context.logs
+    }
+
+    /// Get the findings reported by the detectors
+    pub fn findings(&self) -> &[Finding] {
+        &self.context.findings
+    }
+
+    /// Get the performance metrics recorded while preparing the transaction
+    pub fn metrics(&self) -> &EngineMetrics {
+        &self.context.metrics
+    }
+
+    /// Get the total number of snapshots
+    pub fn snapshot_count(&self) -> usize {
+        self.context.snapshots.len()
+    }
+
+    /// Get a snapshot by its id
+    pub fn snapshot(&self, snapshot_id: usize) -> Option<&Snapshot<DB>> {
+        self.context.snapshots.get(snapshot_id).map(|(_, snapshot)| snapshot)
+    }
+
+    /// Get the id of the snapshot following the given one, stepping over calls
+    pub fn next_snapshot(&self, snapshot_id: usize) -> Option<usize> {
+        self.snapshot(snapshot_id)?.next_id().filter(|next_id| *next_id != snapshot_id)
+    }
+
+    /// Get the id of the snapshot preceding the given one, stepping over calls
+    pub fn prev_snapshot(&self, snapshot_id: usize) -> Option<usize> {
+        self.snapshot(snapshot_id)?.prev_id().filter(|prev_id| *prev_id != snapshot_id)
+    }
+
+    /// Get the id of the first snapshot of a trace entry
+    pub fn entry_snapshot(&self, trace_entry_id: usize) -> Option<usize> {
+        self.context.trace.get(trace_entry_id)?.first_snapshot_id
+    }
+
+    /// Evaluate a Solidity-like expression against a snapshot
+    pub fn eval(&self, expr: &str, snapshot_id: usize) -> Result<DynSolValue> {
+        eval::eval_on_snapshot(self.context.clone(), expr, snapshot_id)
+    }
+
+    /// Build a structured report of the transaction
+    pub fn report(&self) -> SessionReport {
+        session_report(&self.context)
+    }
+
+    /// Build the Solidity stack trace of a snapshot
+    pub fn stack_trace(&self, snapshot_id: usize) -> Option<StackTrace> {
+        stack_trace(&self.context, snapshot_id)
+    }
+
+    /// Expose this session through the JSON-RPC debugging API, e.g., to attach the TUI
+    pub async fn serve(&self) -> Result<RpcServerHandle> {
+        DebugRpcServer::from_shared(self.context.clone()).start().await
+    }
+
+    /// Expose this session through the JSON-RPC debugging API on a unix domain socket
+    #[cfg(unix)]
+    pub async fn serve_on_unix_socket(
+        &self,
+        path: impl Into<PathBuf>,
+    ) -> Result<UnixRpcServerHandle> {
+        DebugRpcServer::from_shared(self.context.clone()).start_on_unix_socket(path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let debugger = Debugger::builder()
+            .with_rpc_url("http://127.0.0.1:8546".to_string())
+            .with_quick_mode(true)
+            .build();
+
+        let config = debugger.config();
+        assert_eq!(config.rpc_proxy_url, "http://127.0.0.1:8546");
+        assert!(config.quick);
+        // Library users get no console output by default
+        assert!(config.quiet);
+    }
+}
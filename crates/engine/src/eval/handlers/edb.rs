@@ -295,15 +295,9 @@ where
         snapshot_id: usize,
     ) -> Result<DynSolValue> {
         if let Some(abi_info) = into_abi_info(&root) {
-            let (_, info) = self.0.context.snapshots.get(snapshot_id).ok_or_else(|| {
-                eyre::eyre!(
-                    "Snapshot ID {} not found in EdbHandler::get_mapping_or_array_value",
-                    snapshot_id
-                )
-            })?;
-            let to = info.target_address();
-
-            self.0.context.call_in_derived_evm(snapshot_id, to, &abi_info.abi, &indices, None)
+            // The getter belongs to the code of the frame, which in a delegatecall
+            // frame reads the storage of the proxy
+            self.0.context.call_in_frame_context(snapshot_id, &abi_info.abi, &indices)
         } else {
             // Handle direct DynSolValue types recursively
             if indices.is_empty() {
@@ -693,6 +687,15 @@ where
             .collect::<Vec<_>>();
         address_candidates.insert(0, to); // Prioritize the direct address
 
+        // Functions of the running code come first when no callee is given, since in a
+        // delegatecall frame they execute against the proxy storage rather than the
+        // functions of the proxy code
+        let frame_code_address = (callee.is_none() && self.0.context.is_delegated(snapshot_id))
+            .then(|| snapshot.bytecode_address());
+        if let Some(code_address) = frame_code_address {
+            address_candidates.insert(0, code_address);
+        }
+
         let mut errors = Vec::new();
         for address_candidate in address_candidates {
             if let Some(contract) = self
@@ -704,13 +707,18 @@ where
             {
                 for entry in parse_callable_abi_entries(contract) {
                     if entry.name == name && entry.inputs.len() == args.len() {
-                        match self.0.context.call_in_derived_evm(
-                            snapshot_id,
-                            to,
-                            &entry.abi,
-                            args,
-                            None,
-                        ) {
+                        let result = if frame_code_address == Some(address_candidate) {
+                            self.0.context.call_in_frame_context(snapshot_id, &entry.abi, args)
+                        } else {
+                            self.0.context.call_in_derived_evm(
+                                snapshot_id,
+                                to,
+                                &entry.abi,
+                                args,
+                                None,
+                            )
+                        };
+                        match result {
                             Ok(result) => return Ok(result),
                             Err(e) => {
                                 errors.push(e);
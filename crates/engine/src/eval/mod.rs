@@ -24,6 +24,7 @@
 //!
 //! - [`ExpressionEvaluator`] - Main evaluator for parsing and executing expressions
 //! - [`handlers`] - Handler traits and implementations for different evaluation contexts
+//! - [`WatchedExpressions`] - Expressions registered to be evaluated at every snapshot
 //! - Common types and utilities for expression evaluation
 //!
 //! # Basic Usage
@@ -60,5 +61,7 @@ pub use common::*;
 mod evaluator;
 pub mod handlers;
 mod utils;
+mod watch;
 
 pub use evaluator::ExpressionEvaluator;
+pub use watch::*;
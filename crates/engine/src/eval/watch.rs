@@ -0,0 +1,312 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Expressions registered to be evaluated at every snapshot.
+//!
+//! Frontends showing the value of a few expressions at every step (e.g., a watch list)
+//! would otherwise send one `edb_evalOnSnapshot` request per expression and step. Once
+//! registered, the expressions are evaluated eagerly at every snapshot, in parallel, and
+//! their values are included in the snapshot information returned by
+//! `edb_getSnapshotInfo`. Values not computed yet when a snapshot is requested are
+//! evaluated on the spot.
+//!
+//! Background sweeps of a context run one at a time on a single worker thread, and stop
+//! evaluating an expression as soon as it is unregistered.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, OnceLock, RwLock,
+    },
+    thread,
+};
+
+use edb_common::types::EdbSolValue;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use tracing::{info, warn};
+
+use crate::{eval_on_snapshot, EngineContext};
+
+/// Value of a watched expression at a snapshot, or why it could not be evaluated
+pub type WatchValue = Result<EdbSolValue, String>;
+
+/// A background evaluation of watched expressions
+type Sweep = Box<dyn FnOnce() + Send>;
+
+/// An expression registered to be evaluated at every snapshot
+#[derive(Debug)]
+struct Watch {
+    /// Values of the expression, by snapshot ID
+    values: Vec<OnceLock<WatchValue>>,
+    /// Set once the expression is unregistered, so that sweeps stop evaluating it
+    cancelled: AtomicBool,
+}
+
+impl Watch {
+    fn new(snapshot_count: usize) -> Self {
+        Self {
+            values: (0..snapshot_count).map(|_| OnceLock::new()).collect(),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Registry of the expressions evaluated at every snapshot of a context.
+///
+/// Clones share the same registry.
+#[derive(Debug, Clone, Default)]
+pub struct WatchedExpressions {
+    inner: Arc<RwLock<BTreeMap<String, Arc<Watch>>>>,
+    /// Queue of the worker running background sweeps, started on the first sweep
+    worker: Arc<OnceLock<mpsc::Sender<Sweep>>>,
+}
+
+impl WatchedExpressions {
+    /// Get the registered expressions, in lexicographic order
+    pub fn expressions(&self) -> Vec<String> {
+        self.inner.read().map(|inner| inner.keys().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Register expressions for the given number of snapshots, returning the ones that
+    /// were not registered yet
+    fn insert(&self, exprs: &[String], snapshot_count: usize) -> Vec<String> {
+        let Ok(mut inner) = self.inner.write() else { return Vec::new() };
+
+        let mut added = Vec::new();
+        for expr in exprs.iter().map(|expr| expr.trim()).filter(|expr| !expr.is_empty()) {
+            if !inner.contains_key(expr) {
+                inner.insert(expr.to_string(), Arc::new(Watch::new(snapshot_count)));
+                added.push(expr.to_string());
+            }
+        }
+        added
+    }
+
+    /// Unregister expressions, or all of them if `exprs` is `None`, returning how many
+    /// were removed.
+    ///
+    /// Running sweeps stop evaluating the removed expressions.
+    pub fn remove(&self, exprs: Option<&[String]>) -> usize {
+        let Ok(mut inner) = self.inner.write() else { return 0 };
+
+        let count = inner.len();
+        inner.retain(|expr, watch| {
+            let keep = exprs.is_some_and(|exprs| !exprs.iter().any(|e| e.trim() == expr));
+            if !keep {
+                watch.cancel();
+            }
+            keep
+        });
+        count - inner.len()
+    }
+
+    /// Get an expression, if it is registered
+    fn watch(&self, expr: &str) -> Option<Arc<Watch>> {
+        self.inner.read().ok()?.get(expr).cloned()
+    }
+
+    /// Get all registered expressions
+    fn all_watches(&self) -> Vec<(String, Arc<Watch>)> {
+        self.inner
+            .read()
+            .map(|inner| inner.iter().map(|(expr, watch)| (expr.clone(), watch.clone())).collect())
+            .unwrap_or_default()
+    }
+
+    /// Queue a sweep on the worker of the registry, which runs sweeps one at a time and
+    /// stops once the registry is dropped
+    fn queue_sweep(&self, sweep: impl FnOnce() + Send + 'static) {
+        let worker = self.worker.get_or_init(|| {
+            let (queue, sweeps) = mpsc::channel::<Sweep>();
+            let spawned = thread::Builder::new().name("edb-watches".to_string()).spawn(move || {
+                for sweep in sweeps {
+                    sweep();
+                }
+            });
+            if let Err(e) = spawned {
+                warn!("Failed to start the worker evaluating watched expressions: {}", e);
+            }
+            queue
+        });
+
+        // Without a worker, values are still evaluated on demand
+        if worker.send(Box::new(sweep)).is_err() {
+            warn!("Watched expressions are not evaluated in the background");
+        }
+    }
+}
+
+/// Register expressions to be evaluated at every snapshot of a context, returning the
+/// ones that were not registered yet.
+///
+/// The new expressions still have to be evaluated with [`evaluate_watches`] or
+/// [`evaluate_watches_in_background`], otherwise they are evaluated on demand by
+/// [`watch_values`].
+pub fn register_watches<DB>(context: &Arc<EngineContext<DB>>, exprs: &[String]) -> Vec<String>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    context.watches.insert(exprs, context.snapshots.len())
+}
+
+/// Evaluate registered expressions at every snapshot, in parallel.
+///
+/// Values computed before, e.g., by [`watch_values`], are kept. Expressions that are
+/// unregistered, before or during the evaluation, are skipped.
+pub fn evaluate_watches<DB>(context: &Arc<EngineContext<DB>>, exprs: &[String])
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let watches: Vec<_> = exprs
+        .iter()
+        .filter_map(|expr| Some((expr.as_str(), context.watches.watch(expr)?)))
+        .collect();
+    if watches.is_empty() {
+        return;
+    }
+
+    info!(
+        "Evaluating {} watched expressions at {} snapshots",
+        watches.len(),
+        context.snapshots.len()
+    );
+    (0..context.snapshots.len()).into_par_iter().for_each(|snapshot_id| {
+        for (expr, watch) in &watches {
+            if !watch.is_cancelled() {
+                watch.values[snapshot_id].get_or_init(|| evaluate(context, expr, snapshot_id));
+            }
+        }
+    });
+}
+
+/// Evaluate registered expressions at every snapshot in the background, after the
+/// evaluations queued before for the same context
+pub fn evaluate_watches_in_background<DB>(context: &Arc<EngineContext<DB>>, exprs: Vec<String>)
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let sweep_context = Arc::clone(context);
+    context.watches.queue_sweep(move || evaluate_watches(&sweep_context, &exprs));
+}
+
+/// Get the values of all registered expressions at a snapshot, evaluating the ones not
+/// computed yet
+pub fn watch_values<DB>(
+    context: &Arc<EngineContext<DB>>,
+    snapshot_id: usize,
+) -> BTreeMap<String, WatchValue>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    context
+        .watches
+        .all_watches()
+        .into_iter()
+        .filter_map(|(expr, watch)| {
+            let value = watch
+                .values
+                .get(snapshot_id)?
+                .get_or_init(|| evaluate(context, &expr, snapshot_id));
+            Some((expr, value.clone()))
+        })
+        .collect()
+}
+
+/// Evaluate an expression at a snapshot
+fn evaluate<DB>(context: &Arc<EngineContext<DB>>, expr: &str, snapshot_id: usize) -> WatchValue
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    eval_on_snapshot(context.clone(), expr, snapshot_id).map(Into::into).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_and_remove() {
+        let watches = WatchedExpressions::default();
+        let shared = watches.clone();
+
+        let added = watches.insert(&[" x + 1 ".into(), "y".into(), "".into()], 3);
+        assert_eq!(added, vec!["x + 1".to_string(), "y".to_string()]);
+        assert_eq!(watches.insert(&["y".into(), "z".into()], 3), vec!["z".to_string()]);
+        assert_eq!(shared.expressions(), vec!["x + 1", "y", "z"]);
+        assert_eq!(shared.watch("y").unwrap().values.len(), 3);
+
+        assert_eq!(watches.remove(Some(&["y".into(), "w".into()])), 1);
+        assert_eq!(watches.expressions(), vec!["x + 1", "z"]);
+        assert_eq!(watches.remove(None), 2);
+        assert!(shared.expressions().is_empty());
+    }
+
+    #[test]
+    fn test_remove_cancels_sweeps() {
+        let watches = WatchedExpressions::default();
+        watches.insert(&["x".into(), "y".into()], 2);
+        let x = watches.watch("x").unwrap();
+        let y = watches.watch("y").unwrap();
+
+        watches.remove(Some(&["x".into()]));
+        assert!(x.is_cancelled());
+        assert!(!y.is_cancelled());
+
+        // Registering the expression again starts over
+        watches.insert(&["x".into()], 2);
+        assert!(!watches.watch("x").unwrap().is_cancelled());
+
+        watches.remove(None);
+        assert!(y.is_cancelled());
+    }
+
+    #[test]
+    fn test_sweeps_run_one_at_a_time() {
+        let watches = WatchedExpressions::default();
+        let (done, finished) = mpsc::channel();
+
+        for id in 0..4 {
+            let done = done.clone();
+            watches.queue_sweep(move || {
+                done.send((id, thread::current().name().map(str::to_string))).unwrap();
+            });
+        }
+
+        let finished: Vec<_> = finished.iter().take(4).collect();
+        let expected: Vec<_> = (0..4).map(|id| (id, Some("edb-watches".to_string()))).collect();
+        assert_eq!(finished, expected);
+    }
+}
@@ -0,0 +1,180 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Engine events published to live subscribers.
+//!
+//! The engine publishes [`EngineEvent`]s on an [`EventBus`] while it prepares
+//! transactions and while clients debug them. Frontends receive these events through
+//! `edb_subscribe` on the WebSocket endpoint of the RPC server (see
+//! [`crate::rpc::subscription`]) instead of polling for changes.
+//!
+//! # Topics
+//!
+//! - [`EventTopic::Progress`] - Preparation progress, including the finalization phase
+//! - [`EventTopic::Navigation`] - Snapshot positions reported by debugging clients
+//! - [`EventTopic::Findings`] - Findings reported by the detectors once a session is ready
+
+use alloy_primitives::TxHash;
+use edb_common::{types::Finding, ProgressMessage};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Number of events buffered for slow subscribers before they start lagging behind
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// Topic of [`EngineEvent`]s a client can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventTopic {
+    /// Preparation progress of transactions
+    Progress,
+    /// Navigation position changes of debugging clients
+    Navigation,
+    /// Findings of newly prepared sessions
+    Findings,
+}
+
+/// Event published by the engine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EngineEvent {
+    /// A preparation step of a transaction started
+    Progress {
+        /// Transaction being prepared
+        tx_hash: TxHash,
+        /// Description of the current step
+        message: String,
+        /// Current step number, if the step is part of a known sequence
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current_step: Option<usize>,
+        /// Total number of steps, if known
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total_steps: Option<usize>,
+    },
+    /// A debugging client moved to another snapshot
+    Navigation {
+        /// Transaction being debugged
+        tx_hash: TxHash,
+        /// Snapshot the client moved to
+        snapshot_id: usize,
+        /// Optional label identifying the client
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client: Option<String>,
+    },
+    /// A detector reported a finding
+    Finding {
+        /// Transaction the finding belongs to
+        tx_hash: TxHash,
+        /// The reported finding
+        finding: Finding,
+    },
+}
+
+impl EngineEvent {
+    /// Create a progress event from a progress message
+    pub fn progress(tx_hash: TxHash, message: ProgressMessage) -> Self {
+        Self::Progress {
+            tx_hash,
+            message: message.message,
+            current_step: message.current_step,
+            total_steps: message.total_steps,
+        }
+    }
+
+    /// Get the topic this event belongs to
+    pub fn topic(&self) -> EventTopic {
+        match self {
+            Self::Progress { .. } => EventTopic::Progress,
+            Self::Navigation { .. } => EventTopic::Navigation,
+            Self::Finding { .. } => EventTopic::Findings,
+        }
+    }
+
+    /// Get the transaction this event belongs to
+    pub fn tx_hash(&self) -> TxHash {
+        match self {
+            Self::Progress { tx_hash, .. }
+            | Self::Navigation { tx_hash, .. }
+            | Self::Finding { tx_hash, .. } => *tx_hash,
+        }
+    }
+}
+
+/// Broadcast channel distributing [`EngineEvent`]s to any number of subscribers.
+///
+/// Cloning the bus yields a handle to the same channel, so the engine and all RPC
+/// servers it starts share a single stream of events.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<EngineEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(EVENT_BUS_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// Create a new event bus buffering up to `capacity` events per subscriber
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Publish an event to all current subscribers.
+    ///
+    /// Events published while nobody is subscribed are dropped.
+    pub fn publish(&self, event: EngineEvent) {
+        self.sender.send(event).ok();
+    }
+
+    /// Subscribe to all events published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<EngineEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Get the number of active subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_receive_published_events() {
+        let bus = EventBus::default();
+        // Events without subscribers are dropped silently
+        bus.publish(EngineEvent::progress(TxHash::ZERO, ProgressMessage::new("dropped")));
+
+        let mut rx = bus.subscribe();
+        bus.clone().publish(EngineEvent::Navigation {
+            tx_hash: TxHash::ZERO,
+            snapshot_id: 42,
+            client: None,
+        });
+
+        let event = rx.recv().await.expect("event is delivered");
+        assert_eq!(event.topic(), EventTopic::Navigation);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap()["type"],
+            serde_json::Value::String("navigation".to_string())
+        );
+    }
+}
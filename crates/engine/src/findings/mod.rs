@@ -0,0 +1,195 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Security-relevant findings detected over a prepared debugging session.
+//!
+//! Detectors in this module run once the [`EngineContext`] has been finalized, i.e.,
+//! when the execution trace, the merged snapshots, and the source analysis results are
+//! all available. Each detector inspects this data and reports zero or more
+//! [`Finding`]s, which are stored in the context and exposed through `edb_getFindings`.
+//!
+//! # Detectors
+//!
+//! - [`detect_reentrancy`] - Frames re-entering a contract whose outer frame still had
+//!   pending storage writes
+//! - [`detect_storage_collisions`] - Proxy storage slots interpreted differently by the
+//!   implementation the proxy delegates to
+//! - [`detect_uninitialized_storage`] - State variables read as zero before ever being
+//!   written, and storage pointers declared without initializer
+
+mod reentrancy;
+pub use reentrancy::*;
+
+mod storage_collision;
+pub use storage_collision::*;
+
+mod uninitialized_storage;
+pub use uninitialized_storage::*;
+
+use alloy_primitives::{map::HashMap, Address, U256};
+use edb_common::types::{Finding, Trace};
+use foundry_compilers::artifacts::{Storage, StorageLayout};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use tracing::debug;
+
+use crate::{EngineContext, Snapshots};
+
+/// Run all detectors over the given engine context and collect their findings.
+///
+/// Findings are sorted by severity (most severe first) and then by the trace entry
+/// they are attached to.
+pub fn detect_findings<DB>(context: &EngineContext<DB>) -> Vec<Finding>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let mut findings = detect_reentrancy(context);
+    findings.extend(detect_storage_collisions(context));
+    findings.extend(detect_uninitialized_storage(context));
+
+    findings.sort_by(|a, b| {
+        b.severity.cmp(&a.severity).then_with(|| a.trace_entry_id.cmp(&b.trace_entry_id))
+    });
+
+    debug!("Detected {} finding(s)", findings.len());
+    findings
+}
+
+/// Check whether `entry_id` is `root_id` itself or one of its descendants in the trace.
+pub(crate) fn is_in_subtree(trace: &Trace, entry_id: usize, root_id: usize) -> bool {
+    let mut current = Some(entry_id);
+    while let Some(id) = current {
+        if id == root_id {
+            return true;
+        }
+        current = trace.get(id).and_then(|entry| entry.parent_id);
+    }
+    false
+}
+
+/// Get the range of snapshot ids (inclusive) covering the execution of a trace entry
+/// and all of its descendants.
+///
+/// Snapshots are stored in execution order and a call subtree executes contiguously,
+/// so the snapshots of a subtree always form a single range.
+pub(crate) fn subtree_snapshot_range<DB>(
+    snapshots: &Snapshots<DB>,
+    trace: &Trace,
+    root_id: usize,
+) -> Option<(usize, usize)>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone,
+    <CacheDB<DB> as Database>::Error: Clone,
+    <DB as Database>::Error: Clone,
+{
+    let mut range: Option<(usize, usize)> = None;
+    for (snapshot_id, (frame_id, _)) in snapshots.iter().enumerate() {
+        if is_in_subtree(trace, frame_id.trace_entry_id(), root_id) {
+            range =
+                Some(range.map_or((snapshot_id, snapshot_id), |(first, _)| (first, snapshot_id)));
+        } else if range.is_some() {
+            break;
+        }
+    }
+    range
+}
+
+/// Collect the storage slots of `address` whose values differ between two databases.
+///
+/// Only slots present in the cache of `after` are considered, since any slot written
+/// during the transaction is committed into the snapshot's cache.
+pub(crate) fn changed_storage_slots<DB>(
+    before: &CacheDB<DB>,
+    after: &CacheDB<DB>,
+    address: Address,
+) -> Vec<U256>
+where
+    DB: DatabaseRef,
+{
+    let empty_storage = HashMap::default();
+    let after_storage =
+        after.cache.accounts.get(&address).map(|acc| &acc.storage).unwrap_or(&empty_storage);
+
+    let mut slots: Vec<U256> = after_storage
+        .iter()
+        .filter(|(slot, value)| {
+            before.storage_ref(address, **slot).map(|old| old != **value).unwrap_or(false)
+        })
+        .map(|(slot, _)| *slot)
+        .collect();
+    slots.sort();
+    slots
+}
+
+/// Storage occupied by a single state variable of a compiler storage layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StorageSpan {
+    /// First slot occupied by the variable
+    pub first_slot: U256,
+    /// Last slot (inclusive) occupied by the variable
+    pub last_slot: U256,
+    /// Byte offset within `first_slot`
+    pub offset: u64,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl StorageSpan {
+    /// Compute the span of a variable, returning `None` if its slot cannot be parsed.
+    pub fn new(var: &Storage, layout: &StorageLayout) -> Option<Self> {
+        let first_slot = var.slot.parse::<U256>().ok()?;
+        let offset = u64::try_from(var.offset).ok()?;
+        let size = layout
+            .types
+            .get(&var.storage_type)
+            .and_then(|ty| ty.number_of_bytes.parse::<u64>().ok())
+            .unwrap_or(32)
+            .max(1);
+        let extra_slots = (offset + size - 1) / 32;
+        let last_slot = first_slot.checked_add(U256::from(extra_slots))?;
+        Some(Self { first_slot, last_slot, offset, size })
+    }
+
+    /// Whether the variable occupies (part of) the given slot.
+    pub fn contains_slot(&self, slot: U256) -> bool {
+        self.first_slot <= slot && slot <= self.last_slot
+    }
+
+    /// Whether two variables share at least one byte of storage.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        if self.last_slot < other.first_slot || other.last_slot < self.first_slot {
+            return false;
+        }
+
+        // Variables packed into the same single slot only collide if their bytes overlap
+        if self.first_slot == self.last_slot && other.first_slot == other.last_slot {
+            return self.offset < other.offset + other.size
+                && other.offset < self.offset + self.size;
+        }
+        true
+    }
+}
+
+/// Type label (e.g., `uint256`) of a storage layout variable.
+pub(crate) fn storage_type_label<'a>(var: &'a Storage, layout: &'a StorageLayout) -> &'a str {
+    layout.types.get(&var.storage_type).map(|ty| ty.label.as_str()).unwrap_or(&var.storage_type)
+}
+
+/// Human-readable description of a storage layout variable, e.g., `uint256 Token.sol:Token.supply`.
+pub(crate) fn describe_storage_variable(var: &Storage, layout: &StorageLayout) -> String {
+    format!("{} {}.{}", storage_type_label(var, layout), var.contract, var.label)
+}
@@ -0,0 +1,287 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reentrancy pattern detection over the execution trace.
+//!
+//! A frame is considered re-entrant when control flow leaves a contract through an
+//! external call and later enters the same contract (i.e., the same storage context)
+//! again while the outer frame is still executing. Such a re-entry is only reported
+//! when the outer frame writes storage *after* the external call returned, which is
+//! the classic checks-effects-interactions violation exploited by reentrancy attacks.
+
+use alloy_primitives::U256;
+use edb_common::types::{CallResult, CallType, Finding, FindingKind, FindingSeverity, Trace};
+use revm::{database::CacheDB, interpreter::CallScheme, Database, DatabaseCommit, DatabaseRef};
+use tracing::debug;
+
+use crate::{
+    findings::{changed_storage_slots, subtree_snapshot_range},
+    EngineContext,
+};
+
+/// A re-entry of a contract identified purely from the call structure of a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReentrancyCandidate {
+    /// Trace entry of the outer frame that was re-entered
+    pub outer_entry_id: usize,
+    /// Trace entry of the direct child of the outer frame that led to the re-entry
+    pub external_call_entry_id: usize,
+    /// Trace entry of the re-entrant frame
+    pub reentrant_entry_id: usize,
+}
+
+/// Find all frames that re-enter a contract which is still executing further up the
+/// call stack.
+///
+/// Only frames where control flow actually enters the contract from another contract
+/// are considered, so self-calls and delegate calls within the same storage context
+/// are not reported. For each re-entrant frame, the nearest enclosing frame of the
+/// same contract is reported as the outer frame.
+pub fn find_reentrancy_candidates(trace: &Trace) -> Vec<ReentrancyCandidate> {
+    let mut candidates = Vec::new();
+
+    for entry in trace {
+        if matches!(entry.call_type, CallType::Create(_)) {
+            continue;
+        }
+
+        // Control flow must enter the contract from a different storage context
+        let Some(parent) = trace.get_parent(entry.id) else { continue };
+        if parent.target == entry.target {
+            continue;
+        }
+
+        // Walk up the call stack to find the nearest frame of the same contract
+        let mut child = parent;
+        while let Some(ancestor) = trace.get_parent(child.id) {
+            if ancestor.target == entry.target {
+                candidates.push(ReentrancyCandidate {
+                    outer_entry_id: ancestor.id,
+                    external_call_entry_id: child.id,
+                    reentrant_entry_id: entry.id,
+                });
+                break;
+            }
+            child = ancestor;
+        }
+    }
+
+    candidates
+}
+
+/// Detect re-entrant frames whose outer frame still had pending storage writes.
+///
+/// The storage of the re-entered contract is compared between the first snapshot after
+/// the external call returned and the first snapshot after the outer frame finished.
+/// Any slot that differs was written by the outer frame after the interaction.
+pub fn detect_reentrancy<DB>(context: &EngineContext<DB>) -> Vec<Finding>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let trace = &context.trace;
+
+    let mut findings = Vec::new();
+    for candidate in find_reentrancy_candidates(trace) {
+        let Some(pending_slots) = pending_storage_writes(context, &candidate) else {
+            debug!("Cannot determine pending writes for re-entry {:?}", candidate);
+            continue;
+        };
+        if pending_slots.is_empty() {
+            continue;
+        }
+
+        let (Some(outer), Some(reentrant)) =
+            (trace.get(candidate.outer_entry_id), trace.get(candidate.reentrant_entry_id))
+        else {
+            continue;
+        };
+
+        let read_only = matches!(reentrant.call_type, CallType::Call(CallScheme::StaticCall));
+        let severity = if read_only { FindingSeverity::Medium } else { FindingSeverity::High };
+        let message = format!(
+            "{}{} re-entered (trace #{}) while frame #{} still had {} pending storage write(s)",
+            if read_only { "read-only: " } else { "" },
+            outer.target,
+            reentrant.id,
+            outer.id,
+            pending_slots.len()
+        );
+
+        findings.push(Finding {
+            kind: FindingKind::Reentrancy {
+                outer_entry_id: candidate.outer_entry_id,
+                external_call_entry_id: candidate.external_call_entry_id,
+                reentrant_entry_id: candidate.reentrant_entry_id,
+                read_only,
+                pending_slots,
+            },
+            severity,
+            address: outer.target,
+            trace_entry_id: Some(reentrant.id),
+            snapshot_id: reentrant.first_snapshot_id,
+            message,
+        });
+    }
+
+    findings
+}
+
+/// Compute the storage slots written by the outer frame after the external call returned.
+///
+/// Returns `None` if the snapshots do not cover the frames involved.
+fn pending_storage_writes<DB>(
+    context: &EngineContext<DB>,
+    candidate: &ReentrancyCandidate,
+) -> Option<Vec<U256>>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let trace = &context.trace;
+    let snapshots = &context.snapshots;
+    let outer = trace.get(candidate.outer_entry_id)?;
+
+    let (_, external_last) =
+        subtree_snapshot_range(snapshots, trace, candidate.external_call_entry_id)?;
+    let (_, outer_last) = subtree_snapshot_range(snapshots, trace, candidate.outer_entry_id)?;
+
+    // When the outer frame reverted, the first snapshot after it would not contain its
+    // writes, so we fall back to its own last snapshot.
+    let outer_succeeded = matches!(outer.result, Some(CallResult::Success { .. }));
+    let after_id = if outer_succeeded && outer_last + 1 < snapshots.len() {
+        outer_last + 1
+    } else {
+        outer_last
+    };
+    let before_id = external_last + 1;
+    if before_id > after_id {
+        return Some(vec![]);
+    }
+
+    let before = snapshots.get(before_id)?.1.db();
+    let after = snapshots.get(after_id)?.1.db();
+    Some(changed_storage_slots(&before, &after, outer.target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Address, Bytes};
+    use edb_common::types::TraceEntry;
+
+    fn entry(
+        id: usize,
+        parent_id: Option<usize>,
+        target: Address,
+        scheme: CallScheme,
+    ) -> TraceEntry {
+        TraceEntry {
+            id,
+            parent_id,
+            depth: 0,
+            call_type: CallType::Call(scheme),
+            caller: Address::ZERO,
+            target,
+            code_address: target,
+            input: Bytes::new(),
+            value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
+            result: None,
+            created_contract: false,
+            create_scheme: None,
+            bytecode: None,
+            target_label: None,
+            self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
+            events: vec![],
+            first_snapshot_id: None,
+        }
+    }
+
+    #[test]
+    fn test_classic_reentrancy_candidate() {
+        let vault = address!("0x1111111111111111111111111111111111111111");
+        let attacker = address!("0x2222222222222222222222222222222222222222");
+
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, attacker, CallScheme::Call));
+        trace.push(entry(1, Some(0), vault, CallScheme::Call));
+        trace.push(entry(2, Some(1), attacker, CallScheme::Call));
+        trace.push(entry(3, Some(2), vault, CallScheme::Call));
+
+        let candidates = find_reentrancy_candidates(&trace);
+        assert_eq!(
+            candidates,
+            vec![
+                // The attacker contract is re-entered by the vault as well
+                ReentrancyCandidate {
+                    outer_entry_id: 0,
+                    external_call_entry_id: 1,
+                    reentrant_entry_id: 2,
+                },
+                ReentrancyCandidate {
+                    outer_entry_id: 1,
+                    external_call_entry_id: 2,
+                    reentrant_entry_id: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_self_and_delegate_calls_are_not_reentrancy() {
+        let proxy = address!("0x1111111111111111111111111111111111111111");
+        let token = address!("0x3333333333333333333333333333333333333333");
+
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, proxy, CallScheme::Call));
+        // Delegate call keeps the storage context of the proxy
+        trace.push(entry(1, Some(0), proxy, CallScheme::DelegateCall));
+        // Self call through `this.f()`
+        trace.push(entry(2, Some(1), proxy, CallScheme::Call));
+        // Plain external call without coming back
+        trace.push(entry(3, Some(2), token, CallScheme::Call));
+
+        assert!(find_reentrancy_candidates(&trace).is_empty());
+    }
+
+    #[test]
+    fn test_nearest_outer_frame_is_reported() {
+        let vault = address!("0x1111111111111111111111111111111111111111");
+        let router = address!("0x4444444444444444444444444444444444444444");
+
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, vault, CallScheme::Call));
+        trace.push(entry(1, Some(0), router, CallScheme::Call));
+        trace.push(entry(2, Some(1), vault, CallScheme::StaticCall));
+
+        let candidates = find_reentrancy_candidates(&trace);
+        assert_eq!(
+            candidates,
+            vec![ReentrancyCandidate {
+                outer_entry_id: 0,
+                external_call_entry_id: 1,
+                reentrant_entry_id: 2,
+            }]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Storage collision detection between proxies and their implementations.
+//!
+//! A proxy executes the code of its implementation through `DELEGATECALL`, so both
+//! contracts share the proxy's storage. When the compiler storage layouts of both
+//! contracts are available, this detector reports slots that the implementation
+//! interprets differently from the proxy, either because a proxy variable overlaps an
+//! implementation variable with a different name or type, or because an implementation
+//! variable occupies one of the well-known EIP-1967 proxy slots.
+
+use std::collections::BTreeSet;
+
+use alloy_primitives::{b256, Address, B256, U256};
+use edb_common::types::{CallType, Finding, FindingKind, FindingSeverity};
+use foundry_compilers::artifacts::StorageLayout;
+use revm::{database::CacheDB, interpreter::CallScheme, Database, DatabaseCommit, DatabaseRef};
+use tracing::debug;
+
+use crate::{
+    findings::{describe_storage_variable, storage_type_label, StorageSpan},
+    ContextQueryTr, EngineContext,
+};
+
+/// EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`
+pub const EIP1967_IMPLEMENTATION_SLOT: B256 =
+    b256!("0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc");
+
+/// EIP-1967 admin slot, `keccak256("eip1967.proxy.admin") - 1`
+pub const EIP1967_ADMIN_SLOT: B256 =
+    b256!("0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103");
+
+/// EIP-1967 beacon slot, `keccak256("eip1967.proxy.beacon") - 1`
+pub const EIP1967_BEACON_SLOT: B256 =
+    b256!("0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50");
+
+/// A storage slot interpreted differently by a proxy and its implementation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StorageCollision {
+    /// Storage slot where the collision happens
+    pub slot: U256,
+    /// Variable (or well-known proxy slot) occupying the slot in the proxy
+    pub proxy_variable: String,
+    /// Variable occupying the slot in the implementation
+    pub implementation_variable: String,
+    /// Whether the collision involves one of the EIP-1967 proxy slots
+    pub proxy_slot: bool,
+}
+
+/// Compare the storage layouts of a proxy and its implementation.
+///
+/// Variables declared identically in both layouts (e.g., inherited from a common base
+/// contract) are not reported. Collisions are returned ordered by slot.
+pub fn find_storage_collisions(
+    proxy_layout: &StorageLayout,
+    implementation_layout: &StorageLayout,
+) -> Vec<StorageCollision> {
+    let mut collisions = Vec::new();
+
+    for impl_var in &implementation_layout.storage {
+        let Some(impl_span) = StorageSpan::new(impl_var, implementation_layout) else {
+            continue;
+        };
+
+        for proxy_var in &proxy_layout.storage {
+            let Some(proxy_span) = StorageSpan::new(proxy_var, proxy_layout) else { continue };
+            if !proxy_span.overlaps(&impl_span) {
+                continue;
+            }
+
+            let same_declaration = proxy_var.label == impl_var.label
+                && proxy_span == impl_span
+                && storage_type_label(proxy_var, proxy_layout)
+                    == storage_type_label(impl_var, implementation_layout);
+            if !same_declaration {
+                collisions.push(StorageCollision {
+                    slot: proxy_span.first_slot.max(impl_span.first_slot),
+                    proxy_variable: describe_storage_variable(proxy_var, proxy_layout),
+                    implementation_variable: describe_storage_variable(
+                        impl_var,
+                        implementation_layout,
+                    ),
+                    proxy_slot: false,
+                });
+            }
+        }
+
+        for (name, slot) in [
+            ("EIP-1967 implementation slot", EIP1967_IMPLEMENTATION_SLOT),
+            ("EIP-1967 admin slot", EIP1967_ADMIN_SLOT),
+            ("EIP-1967 beacon slot", EIP1967_BEACON_SLOT),
+        ] {
+            let slot = U256::from_be_bytes(slot.0);
+            if impl_span.contains_slot(slot) {
+                collisions.push(StorageCollision {
+                    slot,
+                    proxy_variable: name.to_string(),
+                    implementation_variable: describe_storage_variable(
+                        impl_var,
+                        implementation_layout,
+                    ),
+                    proxy_slot: true,
+                });
+            }
+        }
+    }
+
+    collisions.sort_by(|a, b| a.slot.cmp(&b.slot));
+    collisions
+}
+
+/// Detect storage collisions between every proxy in the trace and the implementations
+/// it delegated to, as long as verified artifacts are available for both.
+pub fn detect_storage_collisions<DB>(context: &EngineContext<DB>) -> Vec<Finding>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    // Sort the pairs so that findings are reported deterministically
+    let pairs: BTreeSet<(Address, Address)> = context
+        .address_code_address_map()
+        .iter()
+        .flat_map(|(proxy, code_addresses)| {
+            code_addresses
+                .iter()
+                .filter(move |implementation| *implementation != proxy)
+                .map(move |implementation| (*proxy, *implementation))
+        })
+        .collect();
+
+    let mut findings = Vec::new();
+    for (proxy, implementation) in pairs {
+        let (Some(proxy_contract), Some(impl_contract)) = (
+            context.artifacts.get(&proxy).and_then(|artifact| artifact.contract()),
+            context.artifacts.get(&implementation).and_then(|artifact| artifact.contract()),
+        ) else {
+            continue;
+        };
+
+        let proxy_layout = &proxy_contract.storage_layout;
+        let impl_layout = &impl_contract.storage_layout;
+        if impl_layout.storage.is_empty() {
+            debug!("No storage layout available for implementation {}", implementation);
+            continue;
+        }
+
+        // Attach the findings to the first delegate call from the proxy into the
+        // implementation, which is where the shared storage is first used.
+        let delegate_entry = context.trace.iter().find(|entry| {
+            entry.target == proxy
+                && entry.code_address == implementation
+                && matches!(entry.call_type, CallType::Call(CallScheme::DelegateCall))
+        });
+
+        for collision in find_storage_collisions(proxy_layout, impl_layout) {
+            let severity =
+                if collision.proxy_slot { FindingSeverity::High } else { FindingSeverity::Medium };
+            let message = format!(
+                "slot {:#x} of proxy {} holds `{}` but implementation {} uses it for `{}`",
+                collision.slot,
+                proxy,
+                collision.proxy_variable,
+                implementation,
+                collision.implementation_variable
+            );
+
+            findings.push(Finding {
+                kind: FindingKind::StorageCollision {
+                    proxy,
+                    implementation,
+                    slot: collision.slot,
+                    proxy_variable: collision.proxy_variable,
+                    implementation_variable: collision.implementation_variable,
+                },
+                severity,
+                address: proxy,
+                trace_entry_id: delegate_entry.map(|entry| entry.id),
+                snapshot_id: delegate_entry.and_then(|entry| entry.first_snapshot_id),
+                message,
+            });
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layout(vars: &[(&str, &str, u64, &str)]) -> StorageLayout {
+        let storage: Vec<_> = vars
+            .iter()
+            .enumerate()
+            .map(|(i, (label, slot, offset, ty))| {
+                json!({
+                    "astId": i,
+                    "contract": "C.sol:C",
+                    "label": label,
+                    "offset": offset,
+                    "slot": slot,
+                    "type": ty,
+                })
+            })
+            .collect();
+
+        serde_json::from_value(json!({
+            "storage": storage,
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+            },
+        }))
+        .expect("valid storage layout")
+    }
+
+    #[test]
+    fn test_inherited_layout_does_not_collide() {
+        let proxy = layout(&[("_owner", "0", 0, "t_address")]);
+        let implementation =
+            layout(&[("_owner", "0", 0, "t_address"), ("totalSupply", "1", 0, "t_uint256")]);
+
+        assert!(find_storage_collisions(&proxy, &implementation).is_empty());
+    }
+
+    #[test]
+    fn test_overlapping_variables_collide() {
+        let proxy =
+            layout(&[("implementation", "0", 0, "t_address"), ("admin", "1", 0, "t_address")]);
+        let implementation =
+            layout(&[("initialized", "0", 0, "t_bool"), ("paused", "0", 20, "t_bool")]);
+
+        let collisions = find_storage_collisions(&proxy, &implementation);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].slot, U256::ZERO);
+        assert_eq!(collisions[0].proxy_variable, "address C.sol:C.implementation");
+        assert_eq!(collisions[0].implementation_variable, "bool C.sol:C.initialized");
+        assert!(!collisions[0].proxy_slot);
+    }
+
+    #[test]
+    fn test_eip1967_slot_collision() {
+        let slot = U256::from_be_bytes(EIP1967_ADMIN_SLOT.0).to_string();
+        let implementation = layout(&[("owner", &slot, 0, "t_address")]);
+
+        let collisions = find_storage_collisions(&StorageLayout::default(), &implementation);
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].proxy_variable, "EIP-1967 admin slot");
+        assert!(collisions[0].proxy_slot);
+    }
+}
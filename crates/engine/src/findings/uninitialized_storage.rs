@@ -0,0 +1,226 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Detection of reads from uninitialized storage.
+//!
+//! Two kinds of issues are reported:
+//! - Dynamic: `SLOAD`s recorded during replay that returned zero from a slot never
+//!   written earlier in the transaction, where the storage layout of the executing code
+//!   maps the slot to a declared state variable. Reads of mapping or array elements are
+//!   not reported, since those default to zero by design.
+//! - Structural: local storage pointers declared without an initializer, as found by the
+//!   analysis pass. Compilers before 0.5.0 let such pointers alias slot zero.
+
+use std::collections::HashSet;
+
+use alloy_primitives::{Address, U256};
+use edb_common::types::{Finding, FindingKind, FindingSeverity};
+use foundry_compilers::artifacts::{Storage, StorageLayout, StorageLocation};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use semver::Version;
+use tracing::debug;
+
+use crate::{
+    analysis::{AnalysisResult, VariableKind, VariableRef},
+    findings::{describe_storage_variable, StorageSpan},
+    EngineContext,
+};
+
+/// Find the state variable of a storage layout that occupies the given slot.
+pub fn find_layout_variable(layout: &StorageLayout, slot: U256) -> Option<&Storage> {
+    layout.storage.iter().find(|var| {
+        StorageSpan::new(var, layout).map(|span| span.contains_slot(slot)).unwrap_or(false)
+    })
+}
+
+/// Find all local storage pointers that are declared without an initializer.
+pub fn find_uninitialized_storage_pointers(analysis: &AnalysisResult) -> Vec<VariableRef> {
+    let mut pointers: Vec<_> = analysis
+        .uvid_to_variable
+        .values()
+        .filter(|var| {
+            var.kind() == VariableKind::Local
+                && var.declaration().storage_location == StorageLocation::Storage
+                && !var.initialized()
+        })
+        .cloned()
+        .collect();
+    pointers.sort_by_key(|var| var.id());
+    pointers
+}
+
+/// Detect reads of uninitialized state variables and uninitialized storage pointers.
+pub fn detect_uninitialized_storage<DB>(context: &EngineContext<DB>) -> Vec<Finding>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let mut findings = detect_uninitialized_reads(context);
+    findings.extend(detect_uninitialized_pointers(context));
+    findings
+}
+
+fn detect_uninitialized_reads<DB>(context: &EngineContext<DB>) -> Vec<Finding>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    // Only the first read of each slot is reported
+    let mut reported: HashSet<(Address, U256)> = HashSet::new();
+
+    let mut findings = Vec::new();
+    for read in &context.uninitialized_reads {
+        let Some(entry) = context.trace.get(read.trace_entry_id) else { continue };
+
+        // The layout is given by the code being executed, which may be an
+        // implementation running in the storage context of a proxy.
+        let Some(contract) =
+            context.artifacts.get(&entry.code_address).and_then(|artifact| artifact.contract())
+        else {
+            continue;
+        };
+        let layout = &contract.storage_layout;
+        let Some(var) = find_layout_variable(layout, read.slot) else { continue };
+
+        if !reported.insert((read.address, read.slot)) {
+            continue;
+        }
+
+        let variable = describe_storage_variable(var, layout);
+        let message = format!(
+            "`{}` of {} is read as zero (trace #{}, pc {}) but was never initialized",
+            variable, read.address, read.trace_entry_id, read.pc
+        );
+        findings.push(Finding {
+            kind: FindingKind::UninitializedStorageRead { slot: read.slot, variable, pc: read.pc },
+            severity: FindingSeverity::Info,
+            address: read.address,
+            trace_entry_id: Some(read.trace_entry_id),
+            snapshot_id: entry.first_snapshot_id,
+            message,
+        });
+    }
+
+    findings
+}
+
+fn detect_uninitialized_pointers<DB>(context: &EngineContext<DB>) -> Vec<Finding>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let mut addresses: Vec<_> = context.analysis_results.keys().copied().collect();
+    addresses.sort();
+
+    let mut findings = Vec::new();
+    for address in addresses {
+        let Some(artifact) = context.artifacts.get(&address) else { continue };
+
+        // Since 0.5.0, the compiler rejects storage pointers that may be used before
+        // being assigned, so declarations without initializer are safe.
+        let Ok(version) = Version::parse(artifact.compiler_version().trim_start_matches('v'))
+        else {
+            debug!("Cannot parse compiler version of {}", address);
+            continue;
+        };
+        if version >= Version::new(0, 5, 0) {
+            continue;
+        }
+
+        let entry = context.trace.iter().find(|entry| entry.code_address == address);
+        for var in find_uninitialized_storage_pointers(&context.analysis_results[&address]) {
+            let function = var.function().map(|function| function.name());
+            let message = format!(
+                "storage pointer `{}`{} is declared without initializer and aliases slot 0",
+                var.name(),
+                function.as_ref().map(|name| format!(" in `{name}`")).unwrap_or_default()
+            );
+            findings.push(Finding {
+                kind: FindingKind::UninitializedStoragePointer { variable: var.name(), function },
+                severity: FindingSeverity::Medium,
+                address,
+                trace_entry_id: entry.map(|entry| entry.id),
+                snapshot_id: entry.and_then(|entry| entry.first_snapshot_id),
+                message,
+            });
+        }
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_find_layout_variable() {
+        let layout: StorageLayout = serde_json::from_value(json!({
+            "storage": [
+                {
+                    "astId": 1,
+                    "contract": "C.sol:C",
+                    "label": "owner",
+                    "offset": 0,
+                    "slot": "0",
+                    "type": "t_address",
+                },
+                {
+                    "astId": 2,
+                    "contract": "C.sol:C",
+                    "label": "paused",
+                    "offset": 20,
+                    "slot": "0",
+                    "type": "t_bool",
+                },
+                {
+                    "astId": 3,
+                    "contract": "C.sol:C",
+                    "label": "pair",
+                    "offset": 0,
+                    "slot": "1",
+                    "type": "t_struct",
+                },
+            ],
+            "types": {
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+                "t_struct": {
+                    "encoding": "inplace",
+                    "label": "struct C.Pair",
+                    "numberOfBytes": "64",
+                },
+            },
+        }))
+        .expect("valid storage layout");
+
+        assert_eq!(
+            find_layout_variable(&layout, U256::ZERO).map(|v| v.label.as_str()),
+            Some("owner")
+        );
+        // Structs span multiple slots
+        assert_eq!(
+            find_layout_variable(&layout, U256::from(2)).map(|v| v.label.as_str()),
+            Some("pair")
+        );
+        // Slots outside of the layout belong to dynamic data
+        assert!(find_layout_variable(&layout, U256::from(3)).is_none());
+    }
+}
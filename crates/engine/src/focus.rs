@@ -0,0 +1,235 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Focused preparation of a single subtree of the trace.
+//!
+//! Huge transactions produce millions of snapshots when every step of every call is
+//! recorded. A [`TraceFocus`] selects one call of the trace, e.g., the 3rd call to a
+//! given contract, and preparation then records dense snapshots only in the subtree of
+//! that call. Everywhere else, only the first step of each execution frame is recorded,
+//! so the whole transaction can still be navigated, just at a coarser granularity.
+//!
+//! # Usage Example
+//!
+//! ```rust,ignore
+//! let focus = TraceFocus::from_spec("0xa0b8...eb48#3")?;
+//! let config = EngineConfig::default().with_focus(focus);
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+use alloy_primitives::Address;
+use edb_common::types::Trace;
+use eyre::{bail, eyre, Result};
+use serde::{Deserialize, Serialize};
+
+/// Call of the trace on whose subtree preparation is focused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceFocus {
+    /// Trace entry with the given ID, as shown in the trace tree
+    Entry(usize),
+    /// N-th call (1-based, in execution order) to a contract
+    Call {
+        /// Address of the called contract
+        address: Address,
+        /// Index of the call among the calls to the contract, starting at 1
+        index: usize,
+    },
+}
+
+impl fmt::Display for TraceFocus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Entry(id) => write!(f, "{id}"),
+            Self::Call { address, index } => write!(f, "{address}#{index}"),
+        }
+    }
+}
+
+impl TraceFocus {
+    /// Parse a focus given as `ENTRY_ID`, `ADDRESS` or `ADDRESS#N`
+    ///
+    /// `ADDRESS` alone focuses on the first call to the contract.
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if let Ok(id) = spec.parse::<usize>() {
+            return Ok(Self::Entry(id));
+        }
+
+        let (address, index) = match spec.split_once('#') {
+            Some((address, index)) => {
+                let index = index
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|e| eyre!("Invalid call index {index:?}: {e}"))?;
+                (address, index)
+            }
+            None => (spec, 1),
+        };
+        if index == 0 {
+            bail!("Call indices start at 1");
+        }
+        let address: Address =
+            address.trim().parse().map_err(|e| eyre!("Invalid address {address:?}: {e}"))?;
+
+        Ok(Self::Call { address, index })
+    }
+
+    /// Find the trace entry the focus points to
+    pub fn root(&self, trace: &Trace) -> Result<usize> {
+        match *self {
+            Self::Entry(id) => {
+                if id >= trace.len() {
+                    bail!("Trace entry {id} does not exist, the trace has {} entries", trace.len());
+                }
+                Ok(id)
+            }
+            Self::Call { address, index } => {
+                let calls: Vec<usize> =
+                    trace.iter().filter(|entry| entry.target == address).map(|e| e.id).collect();
+                calls.get(index - 1).copied().ok_or_else(|| {
+                    eyre!(
+                        "Call #{index} to {address} does not exist, it is called {} times",
+                        calls.len()
+                    )
+                })
+            }
+        }
+    }
+
+    /// Get the IDs of the trace entries in the focused subtree
+    pub fn entries(&self, trace: &Trace) -> Result<HashSet<usize>> {
+        let root = self.root(trace)?;
+
+        // Entries are in execution order, so the subtree follows its root
+        let mut entries = HashSet::from([root]);
+        for entry in trace.iter().skip(root + 1) {
+            match entry.parent_id {
+                Some(parent) if entries.contains(&parent) => entries.insert(entry.id),
+                _ => break,
+            };
+        }
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U256};
+    use edb_common::types::{CallType, TraceEntry};
+    use revm::interpreter::CallScheme;
+
+    fn entry(id: usize, parent_id: Option<usize>, target: Address) -> TraceEntry {
+        TraceEntry {
+            id,
+            parent_id,
+            depth: 0,
+            call_type: CallType::Call(CallScheme::Call),
+            caller: Address::ZERO,
+            target,
+            code_address: target,
+            input: Bytes::new(),
+            value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
+            result: None,
+            created_contract: false,
+            create_scheme: None,
+            bytecode: None,
+            target_label: None,
+            self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
+            events: vec![],
+            first_snapshot_id: None,
+        }
+    }
+
+    /// 0 -> A
+    ///   1 -> B
+    ///     2 -> A
+    ///   3 -> B
+    ///     4 -> C
+    ///   5 -> C
+    fn trace() -> Trace {
+        let (a, b, c) =
+            (Address::repeat_byte(0xa), Address::repeat_byte(0xb), Address::repeat_byte(0xc));
+        let mut trace = Trace::new();
+        for (id, parent, target) in [
+            (0, None, a),
+            (1, Some(0), b),
+            (2, Some(1), a),
+            (3, Some(0), b),
+            (4, Some(3), c),
+            (5, Some(0), c),
+        ] {
+            trace.push(entry(id, parent, target));
+        }
+        trace
+    }
+
+    #[test]
+    fn test_from_spec() {
+        let address = Address::repeat_byte(0xb);
+        assert_eq!(TraceFocus::from_spec("12").unwrap(), TraceFocus::Entry(12));
+        assert_eq!(
+            TraceFocus::from_spec(&format!("{address}#2")).unwrap(),
+            TraceFocus::Call { address, index: 2 }
+        );
+        assert_eq!(
+            TraceFocus::from_spec(&address.to_string()).unwrap(),
+            TraceFocus::Call { address, index: 1 }
+        );
+        assert!(TraceFocus::from_spec(&format!("{address}#0")).is_err());
+        assert!(TraceFocus::from_spec("0x12#1").is_err());
+
+        let focus = TraceFocus::Call { address, index: 2 };
+        assert_eq!(TraceFocus::from_spec(&focus.to_string()).unwrap(), focus);
+    }
+
+    #[test]
+    fn test_entries() {
+        let trace = trace();
+        let b = Address::repeat_byte(0xb);
+
+        let focus = TraceFocus::Call { address: b, index: 2 };
+        assert_eq!(focus.root(&trace).unwrap(), 3);
+        assert_eq!(focus.entries(&trace).unwrap(), HashSet::from([3, 4]));
+
+        assert_eq!(TraceFocus::Entry(1).entries(&trace).unwrap(), HashSet::from([1, 2]));
+        assert_eq!(TraceFocus::Entry(0).entries(&trace).unwrap().len(), 6);
+        assert_eq!(TraceFocus::Entry(5).entries(&trace).unwrap(), HashSet::from([5]));
+
+        assert!(TraceFocus::Entry(6).root(&trace).is_err());
+        assert!(TraceFocus::Call { address: b, index: 3 }.root(&trace).is_err());
+    }
+
+    #[test]
+    fn test_serialization() {
+        let focus = TraceFocus::Call { address: Address::repeat_byte(0xb), index: 2 };
+        let json = serde_json::to_value(focus).unwrap();
+        assert_eq!(json["call"]["index"], 2);
+        assert_eq!(serde_json::from_value::<TraceFocus>(json).unwrap(), focus);
+        assert_eq!(
+            serde_json::to_value(TraceFocus::Entry(7)).unwrap(),
+            serde_json::json!({"entry": 7})
+        );
+    }
+}
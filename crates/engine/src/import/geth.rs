@@ -0,0 +1,310 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Conversion of Geth `callTracer` results into an execution trace.
+//!
+//! The call tracer reports one nested frame per message call, which maps onto the
+//! [`Trace`] collected during local replay with a few caveats:
+//! - `SELFDESTRUCT` frames are folded into the [`TraceEntry::self_destruct`] of the
+//!   frame executing them, as done by the local call tracer
+//! - Delegate calls report the calling contract in `from`, so the original message
+//!   sender is recovered from the parent frame
+//! - The salt of `CREATE2` is not reported and is recorded as zero
+
+use alloy_primitives::{Address, Bytes, LogData, B256, U256, U64};
+use edb_common::types::{CallResult, CallType, Trace, TraceEntry};
+use revm::{
+    context::CreateScheme,
+    interpreter::{CallScheme, InstructionResult},
+};
+use serde::{Deserialize, Serialize};
+
+/// A single call frame as returned by Geth's `callTracer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethCallFrame {
+    /// Call type, e.g., `CALL`, `DELEGATECALL`, `CREATE2`, or `SELFDESTRUCT`
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Address of the calling contract
+    pub from: Address,
+    /// Address of the called (or created) contract, missing for failed creations
+    #[serde(default)]
+    pub to: Option<Address>,
+    /// Value transferred
+    #[serde(default)]
+    pub value: Option<U256>,
+    /// Gas made available to this frame
+    #[serde(default)]
+    pub gas: U64,
+    /// Gas spent by this frame, including its children
+    #[serde(default)]
+    pub gas_used: U64,
+    /// Call data, or init code for creations
+    #[serde(default)]
+    pub input: Bytes,
+    /// Return data, or deployed code for creations
+    #[serde(default)]
+    pub output: Option<Bytes>,
+    /// Error message if the frame failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Decoded revert reason if the frame reverted with `Error(string)`
+    #[serde(default)]
+    pub revert_reason: Option<String>,
+    /// Nested call frames
+    #[serde(default)]
+    pub calls: Vec<GethCallFrame>,
+    /// Logs emitted by this frame, only reported with `withLog: true`
+    #[serde(default)]
+    pub logs: Vec<GethCallLog>,
+}
+
+/// A log emitted within a [`GethCallFrame`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GethCallLog {
+    /// Address of the emitting contract
+    pub address: Address,
+    /// Log topics
+    #[serde(default)]
+    pub topics: Vec<B256>,
+    /// Log data
+    #[serde(default)]
+    pub data: Bytes,
+}
+
+impl GethCallFrame {
+    /// Whether this frame is a self-destruct rather than a message call.
+    pub fn is_self_destruct(&self) -> bool {
+        self.ty.eq_ignore_ascii_case("SELFDESTRUCT")
+    }
+}
+
+/// Convert the root frame of a `callTracer` result into an execution trace.
+///
+/// Entries are numbered in pre-order, which is the order in which the frames were
+/// entered during execution.
+pub fn call_frame_to_trace(root: &GethCallFrame) -> Trace {
+    let mut trace = Trace::new();
+    push_call_frame(&mut trace, root, None);
+    trace.annotate_value_flows();
+    trace
+}
+
+fn push_call_frame(trace: &mut Trace, frame: &GethCallFrame, parent_id: Option<usize>) {
+    let parent = parent_id.and_then(|id| trace.get(id));
+    let to = frame.to.unwrap_or_default();
+
+    let (call_type, caller, target, code_address) = match frame.ty.to_ascii_uppercase().as_str() {
+        "DELEGATECALL" => (
+            CallType::Call(CallScheme::DelegateCall),
+            // The message sender is preserved across delegate calls
+            parent.map(|parent| parent.caller).unwrap_or(frame.from),
+            frame.from,
+            to,
+        ),
+        "CALLCODE" => (CallType::Call(CallScheme::CallCode), frame.from, frame.from, to),
+        "STATICCALL" => (CallType::Call(CallScheme::StaticCall), frame.from, to, to),
+        "CREATE" => (CallType::Create(CreateScheme::Create), frame.from, to, to),
+        "CREATE2" => {
+            (CallType::Create(CreateScheme::Create2 { salt: U256::ZERO }), frame.from, to, to)
+        }
+        _ => (CallType::Call(CallScheme::Call), frame.from, to, to),
+    };
+    let create_scheme = match &call_type {
+        CallType::Create(scheme) => Some(*scheme),
+        CallType::Call(_) => None,
+    };
+    let is_create = create_scheme.is_some();
+    let depth = parent.map(|parent| parent.depth + 1).unwrap_or_default();
+    let readonly = matches!(call_type, CallType::Call(CallScheme::StaticCall))
+        || parent.is_some_and(|parent| parent.readonly);
+
+    let self_destruct = frame
+        .calls
+        .iter()
+        .find(|call| call.is_self_destruct())
+        .map(|call| (call.to.unwrap_or_default(), call.value.unwrap_or_default()));
+
+    let id = trace.len();
+    trace.push(TraceEntry {
+        id,
+        parent_id,
+        depth,
+        call_type,
+        caller,
+        target,
+        code_address,
+        input: frame.input.clone(),
+        value: frame.value.unwrap_or_default(),
+        gas_limit: frame.gas.to(),
+        gas_used: frame.gas_used.to(),
+        result: Some(call_result(frame, self_destruct.is_some())),
+        created_contract: is_create && frame.error.is_none(),
+        create_scheme,
+        bytecode: is_create.then(|| frame.input.clone()),
+        target_label: None,
+        self_destruct,
+        // geth does not tell whether the account is destroyed or only swept
+        destroyed: false,
+        readonly,
+        subtree_value: U256::ZERO,
+        events: frame
+            .logs
+            .iter()
+            .map(|log| LogData::new_unchecked(log.topics.clone(), log.data.clone()))
+            .collect(),
+        first_snapshot_id: None,
+    });
+
+    for call in frame.calls.iter().filter(|call| !call.is_self_destruct()) {
+        push_call_frame(trace, call, Some(id));
+    }
+}
+
+/// Result of a frame, derived from the error message reported by the tracer.
+fn call_result(frame: &GethCallFrame, self_destructed: bool) -> CallResult {
+    let output = frame.output.clone().unwrap_or_default();
+    match frame.error.as_deref() {
+        None => {
+            let result = if self_destructed {
+                InstructionResult::SelfDestruct
+            } else if output.is_empty() {
+                InstructionResult::Stop
+            } else {
+                InstructionResult::Return
+            };
+            CallResult::Success { output, result }
+        }
+        Some("execution reverted") => {
+            CallResult::Revert { output, result: InstructionResult::Revert }
+        }
+        Some(error) => CallResult::Error { output, result: instruction_result(error) },
+    }
+}
+
+/// Map an error message of Geth's EVM onto the closest [`InstructionResult`].
+pub fn instruction_result(error: &str) -> InstructionResult {
+    let error = error.to_ascii_lowercase();
+    if error.starts_with("out of gas") || error.contains("gas uint64 overflow") {
+        InstructionResult::OutOfGas
+    } else if error.starts_with("invalid jump") {
+        InstructionResult::InvalidJump
+    } else if error.starts_with("invalid opcode") {
+        InstructionResult::OpcodeNotFound
+    } else if error.starts_with("stack underflow") {
+        InstructionResult::StackUnderflow
+    } else if error.starts_with("stack limit reached") {
+        InstructionResult::StackOverflow
+    } else if error.starts_with("write protection") {
+        InstructionResult::StateChangeDuringStaticCall
+    } else if error.starts_with("return data out of bounds") {
+        InstructionResult::OutOfOffset
+    } else if error.starts_with("max call depth") {
+        InstructionResult::CallTooDeep
+    } else if error.starts_with("insufficient balance") {
+        InstructionResult::OutOfFunds
+    } else if error.starts_with("max code size") {
+        InstructionResult::CreateContractSizeLimit
+    } else if error.starts_with("max initcode size") {
+        InstructionResult::CreateInitCodeSizeLimit
+    } else if error.starts_with("invalid code: must not begin with 0xef") {
+        InstructionResult::CreateContractStartingWithEF
+    } else if error.contains("address collision") {
+        InstructionResult::CreateCollision
+    } else if error.starts_with("nonce uint64 overflow") {
+        InstructionResult::NonceOverflow
+    } else {
+        InstructionResult::FatalExternalError
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use serde_json::json;
+
+    #[test]
+    fn test_call_frame_to_trace() {
+        let frame: GethCallFrame = serde_json::from_value(json!({
+            "type": "CALL",
+            "from": "0x1111111111111111111111111111111111111111",
+            "to": "0x2222222222222222222222222222222222222222",
+            "value": "0x1",
+            "gas": "0x10000",
+            "gasUsed": "0x5000",
+            "input": "0x12345678",
+            "output": "0x",
+            "calls": [
+                {
+                    "type": "DELEGATECALL",
+                    "from": "0x2222222222222222222222222222222222222222",
+                    "to": "0x3333333333333333333333333333333333333333",
+                    "gas": "0x8000",
+                    "gasUsed": "0x100",
+                    "input": "0x",
+                    "error": "execution reverted",
+                },
+                {
+                    "type": "SELFDESTRUCT",
+                    "from": "0x2222222222222222222222222222222222222222",
+                    "to": "0x4444444444444444444444444444444444444444",
+                    "value": "0x2",
+                    "gas": "0x0",
+                    "gasUsed": "0x0",
+                    "input": "0x",
+                },
+            ],
+        }))
+        .expect("valid call frame");
+
+        let trace = call_frame_to_trace(&frame);
+        assert_eq!(trace.len(), 2);
+
+        let root = &trace[0];
+        assert_eq!(root.gas_limit, 0x10000);
+        assert_eq!(
+            root.self_destruct,
+            Some((address!("0x4444444444444444444444444444444444444444"), U256::from(2)))
+        );
+        assert_eq!(root.result.as_ref().map(|r| r.result()), Some(InstructionResult::SelfDestruct));
+        assert_eq!(root.subtree_value, U256::from(3));
+        assert!(!root.readonly);
+
+        let delegate = &trace[1];
+        assert_eq!(delegate.parent_id, Some(0));
+        assert_eq!(delegate.depth, 1);
+        assert_eq!(delegate.caller, address!("0x1111111111111111111111111111111111111111"));
+        assert_eq!(delegate.target, address!("0x2222222222222222222222222222222222222222"));
+        assert_eq!(delegate.code_address, address!("0x3333333333333333333333333333333333333333"));
+        assert!(matches!(delegate.result, Some(CallResult::Revert { .. })));
+    }
+
+    #[test]
+    fn test_instruction_result() {
+        assert_eq!(instruction_result("out of gas"), InstructionResult::OutOfGas);
+        assert_eq!(
+            instruction_result("invalid opcode: INVALID"),
+            InstructionResult::OpcodeNotFound
+        );
+        assert_eq!(
+            instruction_result("contract address collision"),
+            InstructionResult::CreateCollision
+        );
+        assert_eq!(instruction_result("something else"), InstructionResult::FatalExternalError);
+    }
+}
@@ -0,0 +1,173 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Import of externally produced execution traces as a debugging session source.
+//!
+//! For nodes or transactions where a full local replay is impractical, a session can be
+//! built from the results of `debug_traceTransaction` instead: the `callTracer` result
+//! provides the call structure, and the optional `prestateTracer` result provides the
+//! state of the touched accounts before (and, in diff mode, after) the transaction.
+//!
+//! The resulting session is reduced compared to a replayed one. Every trace entry is
+//! represented by a single synthetic snapshot at the beginning of its frame, holding the
+//! pre-transaction state, and a final snapshot of the top-level frame holds the
+//! post-transaction state when the prestate tracer ran in diff mode. This is enough for
+//! trace navigation and storage diffing, while source-level stepping and the inspection
+//! of intermediate states are unavailable.
+
+mod geth;
+pub use geth::*;
+
+mod prestate;
+pub use prestate::*;
+
+use std::{collections::HashMap, sync::Arc};
+
+use edb_common::types::{ExecutionFrameId, Trace};
+use eyre::{bail, eyre, Result};
+use revm::{
+    bytecode::opcode::STOP, database::CacheDB, state::TransientStorage, Database, DatabaseCommit,
+    DatabaseRef,
+};
+use serde_json::Value;
+
+use crate::{OpcodeSnapshot, Snapshot, SnapshotAnalysis, Snapshots, Stack};
+
+/// Results of `debug_traceTransaction` used as the source of a debugging session.
+#[derive(Debug, Clone)]
+pub struct ExternalTrace {
+    /// Result of the `callTracer`, ideally collected with `withLog: true`
+    pub call_frame: GethCallFrame,
+    /// Result of the `prestateTracer`, in either default or diff mode
+    pub prestate: Option<GethPrestate>,
+}
+
+impl ExternalTrace {
+    /// Parse tracer results, given either as raw results or as complete JSON-RPC
+    /// responses of `debug_traceTransaction`.
+    pub fn from_json(call_trace: Value, prestate: Option<Value>) -> Result<Self> {
+        let call_frame = serde_json::from_value(unwrap_rpc_response(call_trace)?)
+            .map_err(|e| eyre!("Invalid callTracer result: {}", e))?;
+        let prestate = prestate
+            .map(|prestate| {
+                serde_json::from_value(unwrap_rpc_response(prestate)?)
+                    .map_err(|e| eyre!("Invalid prestateTracer result: {}", e))
+            })
+            .transpose()?;
+
+        Ok(Self { call_frame, prestate })
+    }
+
+    /// Convert the call frames into an execution trace.
+    ///
+    /// Runtime bytecode is taken from the pre-state when available.
+    pub fn trace(&self) -> Trace {
+        let mut trace = call_frame_to_trace(&self.call_frame);
+        if let Some(prestate) = &self.prestate {
+            for entry in trace.iter_mut().filter(|entry| entry.bytecode.is_none()) {
+                entry.bytecode =
+                    prestate.pre().get(&entry.code_address).and_then(|state| state.code.clone());
+            }
+        }
+        trace
+    }
+}
+
+/// Extract the `result` of a JSON-RPC response, or return the value itself if it is not
+/// a response.
+fn unwrap_rpc_response(value: Value) -> Result<Value> {
+    match value {
+        Value::Object(mut object) if object.contains_key("jsonrpc") => {
+            if let Some(error) = object.remove("error") {
+                bail!("Tracer request failed: {}", error);
+            }
+            object.remove("result").ok_or_else(|| eyre!("JSON-RPC response has no result"))
+        }
+        value => Ok(value),
+    }
+}
+
+/// Build the synthetic snapshots of an imported trace.
+///
+/// The prestate is applied on top of `db`, which should hold the state of the parent
+/// block so that accounts omitted by the tracer are still available.
+pub fn build_external_snapshots<DB>(
+    db: CacheDB<DB>,
+    trace: &Trace,
+    prestate: Option<&GethPrestate>,
+) -> Result<Snapshots<DB>>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone,
+    <CacheDB<DB> as Database>::Error: Clone,
+    <DB as Database>::Error: Clone,
+{
+    let mut pre_db = db;
+    if let Some(prestate) = prestate {
+        apply_account_states(&mut pre_db, prestate.pre())?;
+    }
+    let post_db = match prestate.and_then(|prestate| prestate.post_changes()) {
+        Some(changes) => {
+            let mut post_db = pre_db.clone();
+            apply_account_states(&mut post_db, &changes)?;
+            Some(Arc::new(post_db))
+        }
+        None => None,
+    };
+    let pre_db = Arc::new(pre_db);
+
+    let mut snapshots = Snapshots::new();
+    for entry in trace {
+        let frame_id = ExecutionFrameId::new(entry.id, 0);
+        let opcode = entry.bytecode.as_ref().and_then(|code| code.first()).copied();
+        let detail = OpcodeSnapshot {
+            pc: 0,
+            target_address: entry.target,
+            bytecode_address: entry.code_address,
+            opcode: opcode.unwrap_or(STOP),
+            gas_remaining: entry.gas_limit,
+            memory: Arc::new(Vec::new()),
+            stack: Stack::new(),
+            calldata: Arc::new(entry.input.clone()),
+            database: pre_db.clone(),
+            transient_storage: Arc::new(TransientStorage::default()),
+        };
+        let id = snapshots.len();
+        snapshots.push((frame_id, Snapshot::new_opcode(id, frame_id, detail)));
+    }
+
+    // The post-state is attached to the top-level frame after all of its children returned
+    if let (Some(post_db), Some(root)) = (post_db, trace.first()) {
+        let children = trace.iter().filter(|entry| entry.parent_id == Some(root.id)).count();
+        let frame_id = ExecutionFrameId::new(root.id, children);
+        let detail = OpcodeSnapshot {
+            pc: root.bytecode.as_ref().map(|code| code.len()).unwrap_or_default(),
+            target_address: root.target,
+            bytecode_address: root.code_address,
+            opcode: STOP,
+            gas_remaining: root.gas_limit.saturating_sub(root.gas_used),
+            memory: Arc::new(Vec::new()),
+            stack: Stack::new(),
+            calldata: Arc::new(root.input.clone()),
+            database: post_db,
+            transient_storage: Arc::new(TransientStorage::default()),
+        };
+        let id = snapshots.len();
+        snapshots.push((frame_id, Snapshot::new_opcode(id, frame_id, detail)));
+    }
+
+    snapshots.analyze(trace, &HashMap::new())?;
+    Ok(snapshots)
+}
@@ -0,0 +1,198 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Application of Geth `prestateTracer` results onto a forked database.
+//!
+//! In its default mode, the tracer reports the state of every account touched by the
+//! transaction before it executed. With `diffMode: true`, it reports the pre-state of
+//! modified accounts along with their post-state, where unchanged fields are omitted,
+//! storage slots cleared to zero are omitted, and deleted accounts are missing entirely.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+use eyre::Result;
+use revm::{database::CacheDB, primitives::KECCAK_EMPTY, state::Bytecode, DatabaseRef};
+use serde::{Deserialize, Serialize};
+
+/// State of a single account as reported by Geth's `prestateTracer`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GethAccountState {
+    /// Account balance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    /// Account nonce
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// Runtime bytecode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Storage slots touched by the transaction
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<B256, B256>,
+}
+
+/// Result of Geth's `prestateTracer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GethPrestate {
+    /// Result with `diffMode: true`
+    Diff {
+        /// State of modified accounts before the transaction
+        pre: BTreeMap<Address, GethAccountState>,
+        /// Changed state of modified accounts after the transaction
+        post: BTreeMap<Address, GethAccountState>,
+    },
+    /// Result in the default mode, i.e., the state of all touched accounts before the
+    /// transaction
+    Default(BTreeMap<Address, GethAccountState>),
+}
+
+impl GethPrestate {
+    /// State of the accounts before the transaction.
+    pub fn pre(&self) -> &BTreeMap<Address, GethAccountState> {
+        match self {
+            Self::Diff { pre, .. } => pre,
+            Self::Default(pre) => pre,
+        }
+    }
+
+    /// Changes that turn the pre-state into the post-state of the transaction, or `None`
+    /// if the tracer did not run in diff mode.
+    ///
+    /// Omissions of the diff mode are made explicit: slots missing from the post-state are
+    /// set to zero, and accounts missing from the post-state are emptied.
+    pub fn post_changes(&self) -> Option<BTreeMap<Address, GethAccountState>> {
+        let Self::Diff { pre, post } = self else { return None };
+
+        let mut changes = post.clone();
+        for (address, before) in pre {
+            let after = changes.entry(*address).or_insert_with(|| GethAccountState {
+                balance: Some(U256::ZERO),
+                nonce: Some(0),
+                code: Some(Bytes::new()),
+                storage: BTreeMap::new(),
+            });
+            for slot in before.storage.keys() {
+                after.storage.entry(*slot).or_insert(B256::ZERO);
+            }
+        }
+        Some(changes)
+    }
+}
+
+/// Write the given account states into the cache of a database.
+///
+/// Fields missing from an account state keep the value found in the database.
+pub fn apply_account_states<DB>(
+    db: &mut CacheDB<DB>,
+    accounts: &BTreeMap<Address, GethAccountState>,
+) -> Result<()>
+where
+    DB: DatabaseRef,
+{
+    for (address, state) in accounts {
+        let mut info = db
+            .basic_ref(*address)
+            .map_err(|e| eyre::eyre!("Failed to get account info for {}: {}", address, e))?
+            .unwrap_or_default();
+        if let Some(balance) = state.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = state.nonce {
+            info.nonce = nonce;
+        }
+        if let Some(code) = &state.code {
+            // The code hash is recomputed by `insert_account_info`
+            info.code_hash = KECCAK_EMPTY;
+            info.code = Some(Bytecode::new_raw(code.clone()));
+        }
+        db.insert_account_info(*address, info);
+
+        for (slot, value) in &state.storage {
+            db.insert_account_storage(
+                *address,
+                U256::from_be_bytes(slot.0),
+                U256::from_be_bytes(value.0),
+            )
+            .map_err(|e| eyre::eyre!("Failed to set storage of {}: {}", address, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+    use serde_json::json;
+
+    #[test]
+    fn test_post_changes_of_diff_mode() {
+        let prestate: GethPrestate = serde_json::from_value(json!({
+            "pre": {
+                "0x1111111111111111111111111111111111111111": {
+                    "balance": "0x10",
+                    "nonce": 1,
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            "0x0000000000000000000000000000000000000000000000000000000000000005",
+                        "0x0000000000000000000000000000000000000000000000000000000000000002":
+                            "0x0000000000000000000000000000000000000000000000000000000000000006",
+                    },
+                },
+                "0x2222222222222222222222222222222222222222": { "balance": "0x1" },
+            },
+            "post": {
+                "0x1111111111111111111111111111111111111111": {
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000001":
+                            "0x0000000000000000000000000000000000000000000000000000000000000007",
+                    },
+                },
+            },
+        }))
+        .expect("valid prestate");
+
+        let changes = prestate.post_changes().expect("diff mode");
+        let token = &changes[&address!("0x1111111111111111111111111111111111111111")];
+        assert_eq!(token.balance, None);
+        assert_eq!(
+            token.storage.values().copied().collect::<Vec<_>>(),
+            vec![
+                b256!("0x0000000000000000000000000000000000000000000000000000000000000007"),
+                // Cleared slots are omitted from the post-state
+                B256::ZERO,
+            ]
+        );
+
+        // Deleted accounts are omitted from the post-state
+        let deleted = &changes[&address!("0x2222222222222222222222222222222222222222")];
+        assert_eq!(deleted.balance, Some(U256::ZERO));
+    }
+
+    #[test]
+    fn test_default_mode_has_no_post_state() {
+        let prestate: GethPrestate = serde_json::from_value(json!({
+            "0x1111111111111111111111111111111111111111": { "balance": "0x10", "nonce": 1 },
+        }))
+        .expect("valid prestate");
+
+        assert_eq!(prestate.pre().len(), 1);
+        assert!(prestate.post_changes().is_none());
+    }
+}
@@ -0,0 +1,216 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Access list inspector for collecting the state footprint of a transaction
+//!
+//! This inspector records, for every call frame, the accounts and storage slots the frame
+//! touches, in the shape of an EIP-2930 access list. An account is touched when the frame
+//! executes in it, calls or creates it, queries it with `BALANCE`, `EXTCODESIZE`,
+//! `EXTCODECOPY` or `EXTCODEHASH`, or sends it its balance with `SELFDESTRUCT`. A storage
+//! slot is touched when the frame reads or writes it with `SLOAD` or `SSTORE`.
+//!
+//! Frames are identified by their trace entry id, assigned in the same order as
+//! [`crate::CallTracer`] assigns them, so that both can be collected in the same replay.
+
+use alloy_primitives::{Address, B256, U256};
+use alloy_rpc_types::{AccessList, AccessListItem};
+use revm::{
+    bytecode::opcode::{BALANCE, EXTCODECOPY, EXTCODEHASH, EXTCODESIZE, SLOAD, SSTORE},
+    context::ContextTr,
+    interpreter::{
+        interpreter_types::{InputsTr, Jumps},
+        CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    },
+    Inspector,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::error;
+
+/// Accounts and storage slots touched by a frame, sorted for deterministic output
+type FrameAccesses = BTreeMap<Address, BTreeSet<B256>>;
+
+/// Access lists collected during transaction replay
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessLists {
+    /// Access list of each frame, indexed by trace entry id
+    ///
+    /// A frame's list only holds what the frame touched itself, not what its child
+    /// frames touched.
+    pub frames: Vec<AccessList>,
+}
+
+impl AccessLists {
+    /// Get the access list of a single frame
+    pub fn frame(&self, trace_entry_id: usize) -> Option<&AccessList> {
+        self.frames.get(trace_entry_id)
+    }
+
+    /// Get the access list of the whole transaction, i.e., the union of all frames
+    pub fn transaction(&self) -> AccessList {
+        let mut accesses = FrameAccesses::new();
+        for item in self.frames.iter().flat_map(|list| list.iter()) {
+            accesses.entry(item.address).or_default().extend(item.storage_keys.iter().copied());
+        }
+        into_access_list(accesses)
+    }
+}
+
+/// Inspector recording the accounts and storage slots touched by each frame
+#[derive(Debug, Default)]
+pub struct AccessListInspector {
+    /// Accesses of each frame, indexed by trace entry id
+    frames: Vec<FrameAccesses>,
+    /// Stack of the trace entry ids of the running frames
+    frame_stack: Vec<usize>,
+}
+
+impl AccessListInspector {
+    /// Create a new access list inspector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Convert the inspector into the collected access lists
+    pub fn into_access_lists(self) -> AccessLists {
+        AccessLists { frames: self.frames.into_iter().map(into_access_list).collect() }
+    }
+
+    /// Record an account touched by the running frame
+    fn touch_account(&mut self, address: Address) {
+        if let Some(frame) = self.frame_stack.last().and_then(|id| self.frames.get_mut(*id)) {
+            frame.entry(address).or_default();
+        }
+    }
+
+    /// Record a storage slot touched by the running frame
+    fn touch_slot(&mut self, address: Address, slot: U256) {
+        if let Some(frame) = self.frame_stack.last().and_then(|id| self.frames.get_mut(*id)) {
+            frame.entry(address).or_default().insert(B256::from(slot));
+        }
+    }
+
+    /// Enter a new frame, touching the accounts it runs in
+    fn enter_frame(&mut self, accounts: &[Address]) {
+        // The accounts of a call are also touched by the frame making it
+        for address in accounts {
+            self.touch_account(*address);
+        }
+
+        self.frame_stack.push(self.frames.len());
+        self.frames.push(FrameAccesses::new());
+        for address in accounts {
+            self.touch_account(*address);
+        }
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX> for AccessListInspector {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        let opcode = interp.bytecode.opcode();
+        let Ok(operand) = interp.stack.peek(0) else { return };
+
+        match opcode {
+            SLOAD | SSTORE => self.touch_slot(interp.input.target_address(), operand),
+            BALANCE | EXTCODESIZE | EXTCODECOPY | EXTCODEHASH => {
+                self.touch_account(Address::from_word(B256::from(operand)))
+            }
+            _ => {}
+        }
+    }
+
+    fn call(&mut self, _context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.enter_frame(&[inputs.target_address, inputs.bytecode_address]);
+        None
+    }
+
+    fn call_end(&mut self, _context: &mut CTX, _inputs: &CallInputs, _outcome: &mut CallOutcome) {
+        if self.frame_stack.pop().is_none() {
+            error!("Frame stack underflow - no matching call frame found");
+        }
+    }
+
+    fn create(&mut self, _context: &mut CTX, _inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        // The created address is only known once the creation ends
+        self.enter_frame(&[]);
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut CTX,
+        _inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        let Some(trace_id) = self.frame_stack.pop() else {
+            error!("Frame stack underflow - no matching create frame found");
+            return;
+        };
+
+        if let Some(created_address) = outcome.address {
+            if let Some(frame) = self.frames.get_mut(trace_id) {
+                frame.entry(created_address).or_default();
+            }
+            self.touch_account(created_address);
+        }
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, _value: U256) {
+        self.touch_account(contract);
+        self.touch_account(target);
+    }
+}
+
+fn into_access_list(accesses: FrameAccesses) -> AccessList {
+    AccessList(
+        accesses
+            .into_iter()
+            .map(|(address, keys)| AccessListItem {
+                address,
+                storage_keys: keys.into_iter().collect(),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transaction_access_list_is_union_of_frames() {
+        let (a, b) = (Address::repeat_byte(0xaa), Address::repeat_byte(0xbb));
+        let mut outer = FrameAccesses::new();
+        outer.entry(a).or_default().insert(B256::with_last_byte(1));
+        outer.entry(b).or_default();
+        let mut inner = FrameAccesses::new();
+        inner.entry(a).or_default().insert(B256::with_last_byte(2));
+        inner.entry(b).or_default().insert(B256::with_last_byte(1));
+
+        let lists = AccessLists { frames: vec![into_access_list(outer), into_access_list(inner)] };
+        assert_eq!(lists.frame(0).unwrap().len(), 2);
+        assert!(lists.frame(2).is_none());
+
+        let transaction = lists.transaction();
+        assert_eq!(transaction.len(), 2);
+        assert_eq!(transaction.0[0].address, a);
+        assert_eq!(
+            transaction.0[0].storage_keys,
+            vec![B256::with_last_byte(1), B256::with_last_byte(2)]
+        );
+        assert_eq!(transaction.0[1].storage_keys, vec![B256::with_last_byte(1)]);
+    }
+}
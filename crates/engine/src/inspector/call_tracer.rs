@@ -19,15 +19,32 @@
 //! This inspector captures the complete call trace including call stack, creation events,
 //! and execution flow. The trace can be replayed later to determine execution paths
 //! without needing to re-examine transaction inputs/outputs.
+//!
+//! Alongside the call structure, the tracer records storage reads that return zero from
+//! slots not written earlier in the transaction (writes rolled back by a revert do not
+//! count), which are used to detect reads of
+//! uninitialized storage, and collects the access list of every frame with an embedded
+//! [`AccessListInspector`].
 
+use super::{AccessListInspector, AccessLists};
 use alloy_primitives::{Address, Log, U256};
-use edb_common::types::{CallResult, Trace, TraceEntry};
+use edb_common::types::{CallResult, CallType, Trace, TraceEntry};
 use revm::{
-    context::ContextTr,
-    interpreter::{CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter},
+    bytecode::opcode::{SLOAD, SSTORE},
+    context::{Cfg, ContextTr},
+    interpreter::{
+        interpreter_types::{InputsTr, Jumps},
+        CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, Interpreter,
+    },
+    primitives::hardfork::SpecId,
     Inspector,
 };
-use std::{collections::HashMap, ops::Deref};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Deref,
+    time::Instant,
+};
 use tracing::{debug, error};
 
 /// Result of transaction replay with call trace
@@ -37,6 +54,27 @@ pub struct TraceReplayResult {
     pub visited_addresses: HashMap<Address, bool>,
     /// Complete execution trace with call/create details
     pub execution_trace: Trace,
+    /// Storage reads that returned zero from slots not written before in the transaction
+    pub uninitialized_reads: Vec<UninitializedStorageRead>,
+    /// Accounts and storage slots touched by each frame
+    pub access_lists: AccessLists,
+    /// Wall-clock time spent replaying each frame and its children, in nanoseconds,
+    /// indexed by trace entry id
+    pub frame_times: Vec<u64>,
+}
+
+/// A `SLOAD` that returned zero from a slot which had not been written in the
+/// transaction so far
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UninitializedStorageRead {
+    /// Trace entry of the frame executing the read
+    pub trace_entry_id: usize,
+    /// Storage context the slot was read from
+    pub address: Address,
+    /// Storage slot that was read
+    pub slot: U256,
+    /// Program counter of the `SLOAD` instruction
+    pub pc: usize,
 }
 
 /// Complete call tracer that captures execution flow
@@ -48,12 +86,30 @@ pub struct CallTracer {
     pub visited_addresses: HashMap<Address, bool>,
     /// Stack to track call indices for proper nesting
     call_stack: Vec<usize>,
+    /// Contracts successfully created in this transaction
+    created: HashSet<Address>,
+    /// Zero-valued reads of slots not written before in the transaction
+    uninitialized_reads: Vec<UninitializedStorageRead>,
+    /// Storage slots written by frames that have returned successfully
+    written_slots: HashSet<(Address, U256)>,
+    /// Storage slots written by each running frame, which are discarded if it reverts
+    frame_writes: Vec<HashSet<(Address, U256)>>,
+    /// Start time of each running frame
+    frame_starts: Vec<Instant>,
+    /// Wall-clock time spent by each returned frame, in nanoseconds
+    frame_times: Vec<u64>,
+    /// `SLOAD` being executed, whose result is checked in `step_end`
+    pending_read: Option<UninitializedStorageRead>,
+    /// Access lists of the frames, collected alongside the trace
+    access_lists: AccessListInspector,
+    /// Hardfork the transaction executes under, deciding self-destruct semantics
+    spec: SpecId,
 }
 
 impl CallTracer {
     /// Create a new call tracer
     pub fn new() -> Self {
-        Self { trace: Trace::default(), visited_addresses: HashMap::new(), call_stack: Vec::new() }
+        Self::default()
     }
 
     /// Get all visited addresses
@@ -68,7 +124,21 @@ impl CallTracer {
 
     /// Convert the call tracer into a replay result
     pub fn into_replay_result(self) -> TraceReplayResult {
-        TraceReplayResult { visited_addresses: self.visited_addresses, execution_trace: self.trace }
+        let mut execution_trace = self.trace;
+        execution_trace.annotate_value_flows();
+
+        TraceReplayResult {
+            visited_addresses: self.visited_addresses,
+            execution_trace,
+            uninitialized_reads: self.uninitialized_reads,
+            access_lists: self.access_lists.into_access_lists(),
+            frame_times: self.frame_times,
+        }
+    }
+
+    /// Get all zero-valued reads of slots not written before in the transaction
+    pub fn uninitialized_reads(&self) -> &[UninitializedStorageRead] {
+        &self.uninitialized_reads
     }
 
     /// Add an address to the visited set
@@ -78,10 +148,86 @@ impl CallTracer {
             .and_modify(|existing| *existing |= deployed)
             .or_insert(deployed);
     }
+
+    /// Whether the account is being deployed by a frame that has not returned yet
+    fn is_under_construction(&self, address: Address) -> bool {
+        for entry in self.call_stack.iter().rev().filter_map(|id| self.trace.get(*id)) {
+            match entry.call_type {
+                CallType::Call(CallScheme::DelegateCall | CallScheme::CallCode)
+                    if entry.target == address => {}
+                CallType::Create(_) => return entry.target == Address::ZERO,
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Whether the slot holds a value written earlier in the transaction and not
+    /// rolled back by a revert
+    fn is_written(&self, key: &(Address, U256)) -> bool {
+        self.written_slots.contains(key) || self.frame_writes.iter().any(|w| w.contains(key))
+    }
+
+    /// Start collecting the storage writes and timing of a new frame
+    fn enter_frame(&mut self) {
+        self.frame_writes.push(HashSet::new());
+        self.frame_starts.push(Instant::now());
+        self.frame_times.push(0);
+    }
+
+    /// Record the time spent by the returning frame, which is the innermost one on the
+    /// call stack, and keep its storage writes only if it succeeded, in which case they
+    /// are handed to the parent frame whose own outcome is still pending
+    fn exit_frame(&mut self, succeeded: bool) {
+        if let (Some(started), Some(id)) = (self.frame_starts.pop(), self.call_stack.last()) {
+            if let Some(time) = self.frame_times.get_mut(*id) {
+                *time = started.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+            }
+        }
+
+        let Some(writes) = self.frame_writes.pop() else { return };
+        if !succeeded {
+            return;
+        }
+        match self.frame_writes.last_mut() {
+            Some(parent) => parent.extend(writes),
+            None => self.written_slots.extend(writes),
+        }
+    }
+
+    /// Track storage accesses of the instruction about to be executed
+    fn track_storage_access(&mut self, interp: &Interpreter) {
+        self.pending_read = None;
+
+        let opcode = interp.bytecode.opcode();
+        if opcode != SLOAD && opcode != SSTORE {
+            return;
+        }
+
+        let Ok(slot) = interp.stack.peek(0) else { return };
+        let address = interp.input.target_address();
+
+        if opcode == SSTORE {
+            match self.frame_writes.last_mut() {
+                Some(writes) => writes.insert((address, slot)),
+                None => self.written_slots.insert((address, slot)),
+            };
+        } else if let Some(trace_entry_id) = self.call_stack.last().copied() {
+            self.pending_read = Some(UninitializedStorageRead {
+                trace_entry_id,
+                address,
+                slot,
+                pc: interp.bytecode.pc(),
+            });
+        }
+    }
 }
 
 impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
-    fn step(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        self.track_storage_access(interp);
+        self.access_lists.step(interp, context);
+
         let Some(entry) = self.trace.last_mut() else {
             debug!("Trace is empty, cannot step");
             return;
@@ -95,7 +241,20 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         entry.bytecode = Some(interp.bytecode.bytes());
     }
 
+    fn step_end(&mut self, interp: &mut Interpreter, _context: &mut CTX) {
+        let Some(read) = self.pending_read.take() else { return };
+
+        // The loaded value is on top of the stack after SLOAD
+        let loaded_zero = interp.stack.peek(0).map(|value| value.is_zero()).unwrap_or(false);
+        if loaded_zero && !self.is_written(&(read.address, read.slot)) {
+            self.uninitialized_reads.push(read);
+        }
+    }
+
     fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.access_lists.call(context, inputs);
+        self.spec = context.cfg().spec().into();
+
         let call_type = inputs.into();
         let target = inputs.target_address;
         let code_address = inputs.bytecode_address;
@@ -121,9 +280,14 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
             code_address,
             input: inputs.input.bytes(context),
             value: inputs.transfer_value().unwrap_or(U256::ZERO),
+            gas_limit: inputs.gas_limit,
+            gas_used: 0,         // Will be filled in call_end
             result: None,        // Will be filled in call_end
             events: vec![],      // Will be filled in log
             self_destruct: None, // Will be filled in self_destruct
+            destroyed: false,
+            readonly: inputs.is_static,
+            subtree_value: U256::ZERO, // Will be set in post-analysis
             created_contract: false,
             create_scheme: None,
             bytecode: None,          // Will be set in step
@@ -134,11 +298,17 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         // Add to trace and update stack
         self.trace.push(trace_entry);
         self.call_stack.push(trace_id);
+        self.enter_frame();
 
         None // Continue with normal execution
     }
 
-    fn call_end(&mut self, _context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+    fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.access_lists.call_end(context, inputs, outcome);
+
+        // Storage writes of the frame only persist if it succeeded
+        self.exit_frame(outcome.result.result.is_ok());
+
         // Pop from call stack and update result
         let Some(trace_index) = self.call_stack.pop() else {
             error!("Call stack underflow - no matching call entry found");
@@ -151,6 +321,7 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         };
 
         trace_entry.result = Some(outcome.into());
+        trace_entry.gas_used = outcome.result.gas.spent();
 
         let target = inputs.target_address;
         let code_address = inputs.bytecode_address;
@@ -163,7 +334,10 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         }
     }
 
-    fn create(&mut self, _context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.access_lists.create(context, inputs);
+        self.spec = context.cfg().spec().into();
+
         let call_type = inputs.into();
         let caller = inputs.caller();
 
@@ -185,10 +359,15 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
             code_address: Address::ZERO, // Code address is not known yet
             input: inputs.init_code().clone(),
             value: inputs.value(),
-            result: None,            // Will be filled in create_end
-            events: vec![],          // Will be filled in log
-            self_destruct: None,     // Will be filled in self_destruct
-            created_contract: false, // Will be updated in create_end
+            gas_limit: inputs.gas_limit(),
+            gas_used: 0,         // Will be filled in create_end
+            result: None,        // Will be filled in create_end
+            events: vec![],      // Will be filled in log
+            self_destruct: None, // Will be filled in self_destruct
+            destroyed: false,
+            readonly: false,           // Creations always fail in a static context
+            subtree_value: U256::ZERO, // Will be set in post-analysis
+            created_contract: false,   // Will be updated in create_end
             create_scheme: Some(inputs.scheme()),
             bytecode: None,          // Will be set in step
             target_label: None,      // Will be set in post-analysis
@@ -198,16 +377,22 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         // Add to trace and update stack
         self.trace.push(trace_entry);
         self.call_stack.push(trace_id);
+        self.enter_frame();
 
         None // Continue with normal execution
     }
 
     fn create_end(
         &mut self,
-        _context: &mut CTX,
+        context: &mut CTX,
         inputs: &CreateInputs,
         outcome: &mut CreateOutcome,
     ) {
+        self.access_lists.create_end(context, inputs, outcome);
+
+        // Storage writes of the frame only persist if it succeeded
+        self.exit_frame(outcome.result.result.is_ok());
+
         // Pop from call stack and update result
         let Some(trace_index) = self.call_stack.pop() else {
             error!("Call stack underflow - no matching create entry found");
@@ -226,6 +411,7 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         }
 
         trace_entry.result = Some(outcome.into());
+        trace_entry.gas_used = outcome.result.gas.spent();
 
         if matches!(trace_entry.result, Some(CallResult::Revert { .. })) {
             debug!("Creation failed");
@@ -241,36 +427,189 @@ impl<CTX: ContextTr> Inspector<CTX> for CallTracer {
         trace_entry.code_address = created_address;
         trace_entry.created_contract = true;
 
-        let created_address_for_marking = trace_entry.target;
-        // Mark address after trace_entry is no longer borrowed
-        let _ = trace_entry;
-
-        self.mark_address_visited(created_address_for_marking, true);
+        self.created.insert(created_address);
+        self.mark_address_visited(created_address, true);
     }
 
     fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        Inspector::<CTX>::selfdestruct(&mut self.access_lists, contract, target, value);
+
         // Mark both addresses as visited
         self.mark_address_visited(contract, false);
         self.mark_address_visited(target, false);
 
-        let Some(entry) = self.trace.last_mut() else {
-            error!("Trace is empty, cannot step");
+        // Before Cancun every self-destruct removes the account; since EIP-6780 only
+        // contracts created in the same transaction are removed, others are swept
+        let under_construction = self.is_under_construction(contract);
+        let destroyed = !self.spec.is_enabled_in(SpecId::CANCUN)
+            || under_construction
+            || self.created.contains(&contract);
+
+        // The destructing frame is the innermost active one, not the last traced
+        let Some(entry) = self.call_stack.last().and_then(|id| self.trace.get_mut(*id)) else {
+            error!("Call stack is empty, cannot self-destruct");
             return;
         };
 
-        if entry.target != contract {
+        if entry.target != contract && !under_construction {
             error!("Self-destruct entry mismatch");
             return;
         }
         entry.self_destruct = Some((target, value));
+        entry.destroyed = destroyed;
     }
 
     fn log(&mut self, _context: &mut CTX, log: Log) {
-        let Some(entry) = self.trace.last_mut() else {
-            error!("Trace is empty, cannot log");
+        // The log belongs to the running frame, which is not the last entry once a
+        // child call has returned
+        let Some(entry) = self.call_stack.last().and_then(|id| self.trace.get_mut(*id)) else {
+            error!("No running trace entry, cannot log");
             return;
         };
 
         entry.events.push(log.deref().clone());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, bytes, Bytes, TxKind};
+    use revm::{
+        bytecode::Bytecode,
+        context::{Context, TxEnv},
+        database::{CacheDB, EmptyDB},
+        state::AccountInfo,
+        InspectEvm, MainBuilder, MainContext,
+    };
+
+    const CALLER: Address = address!("0x00000000000000000000000000000000000000c0");
+
+    /// Execute a call to `target` against the given contracts, collecting the trace
+    fn trace_call(contracts: &[(Address, Bytes)], target: Address) -> TraceReplayResult {
+        trace_call_with_spec(contracts, target, SpecId::default())
+    }
+
+    /// Execute a call to `target` under the given hardfork, collecting the trace
+    fn trace_call_with_spec(
+        contracts: &[(Address, Bytes)],
+        target: Address,
+        spec: SpecId,
+    ) -> TraceReplayResult {
+        let mut db = CacheDB::new(EmptyDB::default());
+        for (address, code) in contracts {
+            let info = AccountInfo::from_bytecode(Bytecode::new_raw(code.clone()));
+            db.insert_account_info(*address, info);
+        }
+
+        let tx = TxEnv {
+            caller: CALLER,
+            kind: TxKind::Call(target),
+            gas_limit: 1_000_000,
+            ..Default::default()
+        };
+
+        let mut tracer = CallTracer::new();
+        let mut ctx = Context::mainnet().with_db(db);
+        ctx.cfg.spec = spec;
+        let mut evm = ctx.build_mainnet_with_inspector(&mut tracer);
+        evm.inspect_one_tx(tx).expect("transaction executes");
+        tracer.into_replay_result()
+    }
+
+    #[test]
+    fn test_writes_of_reverted_frames_are_not_initializations() {
+        let outer = address!("0x00000000000000000000000000000000000000aa");
+        let inner = address!("0x00000000000000000000000000000000000000bb");
+
+        // Without calldata, `inner` stores 1 into slot 1 and reverts. With calldata, it
+        // loads slot 1.
+        let inner_code = bytes!("0x36600d576001600155600080fd5b60015450");
+        // `outer` calls `inner` once without and once with calldata
+        let outer_code = bytes!(
+            "0x600060006000600060007300000000000000000000000000000000000000bb5af150"
+            "600060006001600060007300000000000000000000000000000000000000bb5af15000"
+        );
+
+        let result = trace_call(&[(outer, outer_code), (inner, inner_code)], outer);
+
+        assert_eq!(result.execution_trace.len(), 3);
+        assert!(matches!(result.execution_trace[1].result, Some(CallResult::Revert { .. })));
+        assert_eq!(result.uninitialized_reads.len(), 1);
+        let read = &result.uninitialized_reads[0];
+        assert_eq!((read.trace_entry_id, read.address, read.slot), (2, inner, U256::from(1)));
+    }
+
+    #[test]
+    fn test_writes_of_successful_frames_are_initializations() {
+        let outer = address!("0x00000000000000000000000000000000000000aa");
+        let inner = address!("0x00000000000000000000000000000000000000bb");
+
+        // Without calldata, `inner` stores 1 into slot 1 and returns. With calldata, it
+        // loads slot 1.
+        let inner_code = bytes!("0x36600a576001600155005b60015450");
+        let outer_code = bytes!(
+            "0x600060006000600060007300000000000000000000000000000000000000bb5af150"
+            "600060006001600060007300000000000000000000000000000000000000bb5af15000"
+        );
+
+        let result = trace_call(&[(outer, outer_code), (inner, inner_code)], outer);
+
+        assert_eq!(result.execution_trace.len(), 3);
+        assert!(result.uninitialized_reads.is_empty());
+    }
+
+    /// The trace entry of the frame that self-destructed
+    fn self_destruct_entry(result: &TraceReplayResult) -> &TraceEntry {
+        result
+            .execution_trace
+            .iter()
+            .find(|entry| entry.self_destruct.is_some())
+            .expect("a frame self-destructs")
+    }
+
+    #[test]
+    fn test_self_destruct_destroys_before_cancun() {
+        let target = address!("0x00000000000000000000000000000000000000aa");
+
+        // SELFDESTRUCT to the caller
+        let code = bytes!("60c0ff");
+        let result = trace_call_with_spec(&[(target, code)], target, SpecId::SHANGHAI);
+
+        let entry = self_destruct_entry(&result);
+        assert_eq!(entry.self_destruct, Some((CALLER, U256::ZERO)));
+        assert!(entry.destroyed);
+    }
+
+    #[test]
+    fn test_self_destruct_of_existing_contract_sweeps_since_cancun() {
+        let target = address!("0x00000000000000000000000000000000000000aa");
+
+        let code = bytes!("60c0ff");
+        let result = trace_call_with_spec(&[(target, code)], target, SpecId::CANCUN);
+
+        let entry = self_destruct_entry(&result);
+        assert_eq!(entry.self_destruct, Some((CALLER, U256::ZERO)));
+        assert!(!entry.destroyed);
+    }
+
+    #[test]
+    fn test_self_destruct_of_contract_created_in_transaction_destroys_since_cancun() {
+        let factory = address!("0x00000000000000000000000000000000000000aa");
+
+        // CREATE a contract whose runtime code is `60c0ff`, then CALL it. The init code
+        // `6260c0ff6000526003601df3` returns the three bytes of the runtime code.
+        let code = bytes!(
+            "6b6260c0ff6000526003601df3600052600c60146000f0600060006000600060008562fffffff100"
+        );
+        let result = trace_call_with_spec(&[(factory, code)], factory, SpecId::CANCUN);
+
+        assert_eq!(result.execution_trace.len(), 3);
+        let created = result.execution_trace[1].target;
+        assert!(result.execution_trace[1].created_contract);
+
+        let entry = self_destruct_entry(&result);
+        assert_eq!(entry.target, created);
+        assert!(entry.destroyed);
+    }
+}
@@ -26,7 +26,7 @@
 //! making it more efficient for tracking specific execution states.
 
 use alloy_dyn_abi::{DynSolType, DynSolValue};
-use alloy_primitives::{Address, Bytes, U256};
+use alloy_primitives::{Address, Bytes, Log, U256};
 use edb_common::{
     types::{CallResult, EdbSolValue, ExecutionFrameId, Trace},
     EdbContext, OpcodeTr,
@@ -46,7 +46,7 @@ use revm::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -54,7 +54,7 @@ use tracing::{debug, error};
 
 use crate::{
     analysis::{dyn_sol_type, AnalysisResult, UserDefinedTypeRef, VariableRef, UVID},
-    inspector::utils::relax_gas_limit_at_callsite,
+    inspector::utils::{relax_gas_limit_at_callsite, snapshot_database},
     USID,
 };
 
@@ -93,6 +93,9 @@ where
     pub state_variables: HashMap<String, Option<Arc<EdbSolValue>>>,
     /// User-defined snapshot ID from call data
     pub usid: USID,
+    /// Gas remaining at the hook point
+    #[serde(default)]
+    pub gas_remaining: u64,
 }
 
 /// Collection of hook snapshots organized by execution order
@@ -109,6 +112,9 @@ where
     /// Vector of (frame_id, optional_snapshot) pairs in execution order
     /// None indicates a frame where no hook was triggered
     snapshots: Vec<(ExecutionFrameId, Option<HookSnapshot<DB>>)>,
+    /// Position in `snapshots` of the running frame's latest entry when each log was
+    /// emitted, in emission order
+    log_positions: Vec<usize>,
 }
 
 impl<DB> Default for HookSnapshots<DB>
@@ -118,7 +124,7 @@ where
     <DB as Database>::Error: Clone,
 {
     fn default() -> Self {
-        Self { snapshots: Vec::new() }
+        Self { snapshots: Vec::new(), log_positions: Vec::new() }
     }
 }
 
@@ -190,6 +196,24 @@ where
             .collect()
     }
 
+    /// Get the positions at which logs were emitted, in emission order
+    ///
+    /// Each position indexes the entry of the emitting frame that was the latest one when
+    /// the log was emitted: either the hook snapshot of the emitting statement, or a
+    /// placeholder if no hook was triggered in the frame yet.
+    pub fn log_positions(&self) -> &[usize] {
+        &self.log_positions
+    }
+
+    /// Record a log emitted by the frame of the latest entry
+    fn record_log(&mut self) {
+        if let Some(position) = self.snapshots.len().checked_sub(1) {
+            self.log_positions.push(position);
+        } else {
+            error!("No execution frame is running, cannot record log");
+        }
+    }
+
     /// Add a frame placeholder (will be None if no hook is triggered)
     fn add_frame_placeholder(&mut self, frame_id: ExecutionFrameId) {
         self.snapshots.push((frame_id, None));
@@ -233,6 +257,10 @@ where
     /// Collection of hook snapshots
     pub snapshots: HookSnapshots<DB>,
 
+    /// Trace entries in which every hook is recorded, or `None` to record every hook of
+    /// every entry. Only the first hook of each frame is recorded in the other entries.
+    pub focus: Option<HashSet<usize>>,
+
     /// Stack to track current execution frames
     frame_stack: Vec<ExecutionFrameId>,
 
@@ -271,6 +299,7 @@ where
             trace,
             analysis,
             snapshots: HookSnapshots::default(),
+            focus: None,
             frame_stack: Vec::new(),
             current_trace_id: 0,
             creation_hooks: Vec::new(),
@@ -305,6 +334,11 @@ where
         Ok(())
     }
 
+    /// Only record every hook in the given trace entries
+    pub fn with_focus(&mut self, entries: HashSet<usize>) {
+        self.focus = Some(entries);
+    }
+
     /// Consume the inspector and return the collected snapshots
     pub fn into_snapshots(self) -> HookSnapshots<DB> {
         self.snapshots
@@ -315,6 +349,11 @@ where
         self.frame_stack.last().copied()
     }
 
+    /// Check if every hook of the given trace entry is recorded
+    fn is_focused(&self, trace_id: usize) -> bool {
+        self.focus.as_ref().is_none_or(|entries| entries.contains(&trace_id))
+    }
+
     /// Start tracking a new execution frame
     fn push_frame(&mut self, trace_id: usize) {
         let frame_id = ExecutionFrameId::new(trace_id, 0);
@@ -351,13 +390,7 @@ where
                 .map(|c| c.modifies_evm_state() && !c.is_message_call())
                 .unwrap_or(force_update)
         {
-            // Clone current database state
-            let mut inner = ctx.journal().to_inner();
-            let changes = inner.finalize();
-            let mut snap = ctx.db().clone();
-            snap.commit(changes);
-
-            self.database = Arc::new(snap);
+            self.database = Arc::new(snapshot_database(ctx));
         }
 
         if force_update
@@ -378,6 +411,15 @@ where
         interp: &Interpreter,
         _ctx: &mut EdbContext<DB>,
     ) {
+        // Outside of the focused subtree, only the first hook of each frame is recorded
+        if let Some(frame_id) = self.current_frame_id() {
+            if !self.is_focused(frame_id.trace_entry_id())
+                && matches!(self.snapshots.last(), Some((id, Some(_))) if *id == frame_id)
+            {
+                return;
+            }
+        }
+
         let address = self
             .current_frame_id()
             .and_then(|frame_id| self.trace.get(frame_id.trace_entry_id()))
@@ -429,6 +471,7 @@ where
                     locals,
                     usid,
                     state_variables: HashMap::new(), // State variables can be filled in later
+                    gas_remaining: interp.gas.remaining(),
                 };
 
                 self.snapshots.update_last_frame_with_snapshot(current_frame_id, hook_snapshot);
@@ -598,6 +641,7 @@ where
     /// Clear all recorded data
     pub fn clear(&mut self) {
         self.snapshots.snapshots.clear();
+        self.snapshots.log_positions.clear();
         self.frame_stack.clear();
         self.current_trace_id = 0;
     }
@@ -657,6 +701,12 @@ where
         self.update_database(context, false);
     }
 
+    fn log(&mut self, _context: &mut EdbContext<DB>, _log: Log) {
+        // The latest entry always belongs to the running frame, since a placeholder is
+        // added whenever a frame starts or resumes
+        self.snapshots.record_log();
+    }
+
     fn call(
         &mut self,
         context: &mut EdbContext<DB>,
@@ -27,6 +27,11 @@
 //! that captures the complete execution flow including internal calls, delegate calls,
 //! and create operations.
 //!
+//! ## [`AccessListInspector`]
+//! Records the accounts and storage slots touched by each frame as EIP-2930 access
+//! lists, describing the state footprint of the transaction. It is embedded in the
+//! [`CallTracer`] so that access lists are collected in the same replay as the trace.
+//!
 //! ## [`HookSnapshotInspector`]
 //! Creates detailed snapshots at specific hook points during execution, capturing
 //! local variables, state variables, and execution context for source-level debugging.
@@ -38,6 +43,11 @@
 //! stack, memory, storage, and transient storage. Useful for detailed execution
 //! analysis and opcode-level debugging.
 //!
+//! ## [`StateOverrideInspector`]
+//! Applies user-provided storage, balance, and code overrides when a replay reaches the
+//! snapshot they were set at, so that the remainder of the transaction can be re-executed
+//! against the modified state. It embeds a [`CallTracer`] to collect the alternative trace.
+//!
 //! ## [`TweakInspector`]
 //! Allows runtime modification of contract bytecode and behavior for debugging
 //! purposes. Can inject custom logic, modify return values, and alter execution
@@ -59,13 +69,17 @@
 //! on a specific aspect of execution analysis while maintaining minimal overhead
 //! when not actively collecting data.
 
+mod access_list;
 mod call_tracer;
 mod hook_snapshot_inspector;
 mod opcode_snapshot_inspector;
+mod state_override_inspector;
 mod tweak_inspector;
 mod utils;
 
+pub use access_list::*;
 pub use call_tracer::*;
 pub use hook_snapshot_inspector::*;
 pub use opcode_snapshot_inspector::*;
+pub use state_override_inspector::*;
 pub use tweak_inspector::*;
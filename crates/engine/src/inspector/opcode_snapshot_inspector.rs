@@ -53,7 +53,7 @@ use std::{
 };
 use tracing::{debug, error};
 
-use crate::Stack;
+use crate::{inspector::utils::snapshot_database, Stack};
 
 /// Single opcode execution snapshot
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +71,9 @@ where
     pub bytecode_address: Address,
     /// Current opcode
     pub opcode: u8,
+    /// Gas remaining before executing the opcode
+    #[serde(default)]
+    pub gas_remaining: u64,
     /// Memory state (shared via Arc when unchanged)
     pub memory: Arc<Vec<u8>>,
     /// Stack state (persistent stack)
@@ -193,6 +196,10 @@ where
     /// Set of addresses to exclude from recording (verified source code)
     pub excluded_addresses: HashSet<Address>,
 
+    /// Trace entries in which every step is recorded, or `None` to record every step of
+    /// every entry. Only the first step of each frame is recorded in the other entries.
+    pub focus: Option<HashSet<usize>>,
+
     /// Stack to track current execution frames
     frame_stack: Vec<ExecutionFrameId>,
 
@@ -227,6 +234,7 @@ where
             trace,
             snapshots: OpcodeSnapshots::<DB>::default(),
             excluded_addresses: HashSet::new(),
+            focus: None,
             frame_stack: Vec::new(),
             current_trace_id: 0,
             frame_states: HashMap::new(),
@@ -242,6 +250,11 @@ where
         self.excluded_addresses = excluded_addresses;
     }
 
+    /// Only record every step in the given trace entries
+    pub fn with_focus(&mut self, entries: HashSet<usize>) {
+        self.focus = Some(entries);
+    }
+
     /// Consume the inspector and return the collected snapshots
     pub fn into_snapshots(self) -> OpcodeSnapshots<DB> {
         self.snapshots
@@ -262,6 +275,11 @@ where
         !self.excluded_addresses.contains(&address)
     }
 
+    /// Check if every step of the given trace entry is recorded
+    fn is_focused(&self, trace_id: usize) -> bool {
+        self.focus.as_ref().is_none_or(|entries| entries.contains(&trace_id))
+    }
+
     /// Update the persistent stack based on the interpreter's current stack
     fn update_stack(&mut self, interp: &Interpreter, ctx: &mut EdbContext<DB>) {
         let Some(opcode) = self.last_opcode else { return };
@@ -332,11 +350,7 @@ where
                 .map(|c| c.modifies_evm_state() && !c.is_message_call())
                 .unwrap_or(force_update)
         {
-            let mut inner = ctx.journal().to_inner();
-            let changes = inner.finalize();
-            let mut snap = ctx.db().clone();
-            snap.commit(changes);
-            self.database = Arc::new(snap);
+            self.database = Arc::new(snapshot_database(ctx));
         }
 
         if force_update
@@ -372,6 +386,13 @@ where
             return;
         }
 
+        // Outside of the focused subtree, only the first step of each frame is recorded
+        if !self.is_focused(frame_id.trace_entry_id())
+            && self.snapshots.get(&frame_id).is_some_and(|snapshots| !snapshots.is_empty())
+        {
+            return;
+        }
+
         let address = interp.input.target_address();
 
         // Get or create frame state
@@ -397,6 +418,7 @@ where
             bytecode_address: entry.map(|t| t.code_address).unwrap_or(address),
             target_address: entry.map(|t| t.target).unwrap_or(address),
             opcode: opcode.get(),
+            gas_remaining: interp.gas.remaining(),
             memory,
             stack: self
                 .trace_state
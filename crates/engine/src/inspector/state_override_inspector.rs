@@ -0,0 +1,259 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! State override inspector for re-executing a transaction with modified state
+//!
+//! This inspector replays a transaction and, when execution reaches a given point, writes
+//! user-provided storage values, balances, or code into the state before continuing. It
+//! lets a user explore how the remainder of the transaction would have unfolded had the
+//! state been different at a snapshot.
+//!
+//! An override is applied at an [`OverridePoint`]: an execution frame (identified the same
+//! way as by the snapshot inspectors), and optionally the program counter and occurrence of
+//! the instruction within the frame. The inspector embeds a [`CallTracer`] so that the
+//! trace of the re-execution is collected in the same replay.
+
+use alloy_primitives::{Address, Bytes, Log, U256};
+use edb_common::types::{ExecutionFrameId, Trace};
+use revm::{
+    context::{ContextTr, JournalTr},
+    interpreter::{
+        interpreter_types::Jumps, CallInputs, CallOutcome, CreateInputs, CreateOutcome, Interpreter,
+    },
+    state::Bytecode,
+    Inspector,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, error};
+
+use super::{CallTracer, TraceReplayResult};
+
+/// A modification of the state, applied at a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateOverride {
+    /// Snapshot at which the state is modified
+    pub snapshot_id: usize,
+    /// Modification to apply
+    pub kind: StateOverrideKind,
+}
+
+/// Kind of state modification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum StateOverrideKind {
+    /// Set a storage slot of an account
+    Storage {
+        /// Account whose storage is modified
+        address: Address,
+        /// Storage slot to set
+        slot: U256,
+        /// New value of the slot
+        value: U256,
+    },
+    /// Set the balance of an account
+    Balance {
+        /// Account whose balance is modified
+        address: Address,
+        /// New balance of the account
+        balance: U256,
+    },
+    /// Replace the code of an account
+    Code {
+        /// Account whose code is replaced
+        address: Address,
+        /// New runtime bytecode of the account
+        code: Bytes,
+    },
+}
+
+/// Point of the execution at which an override is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverridePoint {
+    /// Execution frame in which the override is applied
+    pub frame_id: ExecutionFrameId,
+    /// Program counter and occurrence (0-based) within the frame of the instruction before
+    /// which the override is applied, or `None` to apply it before the first instruction
+    /// of the frame
+    pub instruction: Option<(usize, usize)>,
+}
+
+/// Outcome of re-executing a transaction with state overrides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RerunResult {
+    /// Whether the transaction succeeded
+    pub success: bool,
+    /// Gas used by the transaction
+    pub gas_used: u64,
+    /// Return or revert data of the transaction
+    pub output: Bytes,
+    /// Halt reason, if the transaction halted
+    pub halt_reason: Option<String>,
+    /// Overrides whose point was never reached during the re-execution
+    pub unapplied_overrides: Vec<StateOverride>,
+    /// Execution trace of the re-execution
+    pub trace: Trace,
+}
+
+/// Inspector applying state overrides while replaying a transaction
+#[derive(Debug, Default)]
+pub struct StateOverrideInspector {
+    /// Overrides not applied yet, with the point they are applied at
+    pending: Vec<(OverridePoint, StateOverride)>,
+    /// Stack of the running frames
+    frame_stack: Vec<ExecutionFrameId>,
+    /// Number of frames started so far, i.e., the trace entry id of the next frame
+    frame_count: usize,
+    /// Whether the running frame has not executed an instruction since it started or resumed
+    segment_start: bool,
+    /// Occurrences of each program counter executed in each frame
+    pc_hits: HashMap<(ExecutionFrameId, usize), usize>,
+    /// Tracer collecting the trace of the re-execution
+    tracer: CallTracer,
+}
+
+impl StateOverrideInspector {
+    /// Create an inspector applying the given overrides at their points
+    pub fn new(overrides: Vec<(OverridePoint, StateOverride)>) -> Self {
+        Self { pending: overrides, ..Default::default() }
+    }
+
+    /// Get the overrides whose point has not been reached
+    pub fn unapplied_overrides(&self) -> impl Iterator<Item = &StateOverride> {
+        self.pending.iter().map(|(_, state_override)| state_override)
+    }
+
+    /// Convert the inspector into the trace of the re-execution
+    pub fn into_replay_result(self) -> TraceReplayResult {
+        self.tracer.into_replay_result()
+    }
+
+    /// Start tracking a new execution frame
+    fn push_frame(&mut self) {
+        self.frame_stack.push(ExecutionFrameId::new(self.frame_count, 0));
+        self.frame_count += 1;
+        self.segment_start = true;
+    }
+
+    /// Stop tracking the running frame, resuming its parent
+    fn pop_frame(&mut self) {
+        if self.frame_stack.pop().is_none() {
+            error!("Frame stack underflow - no matching frame found");
+        }
+        if let Some(parent) = self.frame_stack.last_mut() {
+            parent.increment_re_entry();
+            self.segment_start = true;
+        }
+    }
+
+    /// Apply the overrides whose point is the instruction about to be executed
+    fn apply_overrides<CTX: ContextTr>(&mut self, interp: &Interpreter, context: &mut CTX) {
+        let Some(frame_id) = self.frame_stack.last().copied() else { return };
+        let pc = interp.bytecode.pc();
+        let occurrence = {
+            let hits = self.pc_hits.entry((frame_id, pc)).or_default();
+            *hits += 1;
+            *hits - 1
+        };
+        let segment_start = std::mem::take(&mut self.segment_start);
+
+        let (due, pending): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.pending).into_iter().partition(|(point, _)| {
+                point.frame_id == frame_id
+                    && match point.instruction {
+                        Some(instruction) => instruction == (pc, occurrence),
+                        None => segment_start,
+                    }
+            });
+        self.pending = pending;
+
+        for (_, state_override) in due {
+            debug!("Applying {:?} at frame {} and PC {}", state_override.kind, frame_id, pc);
+            apply_override(context, &state_override.kind);
+        }
+    }
+}
+
+/// Write a state override into the journaled state of the EVM
+fn apply_override<CTX: ContextTr>(context: &mut CTX, kind: &StateOverrideKind) {
+    let journal = context.journal_mut();
+    let result = match kind {
+        StateOverrideKind::Storage { address, slot, value } => {
+            let loaded = journal.load_account(*address).map(|_| ());
+            loaded.and_then(|()| journal.sstore(*address, *slot, *value).map(|_| ()))
+        }
+        StateOverrideKind::Balance { address, balance } => {
+            journal.load_account(*address).map(|account| {
+                account.data.info.balance = *balance;
+                account.data.mark_touch();
+            })
+        }
+        StateOverrideKind::Code { address, code } => {
+            let loaded = journal.load_account(*address).map(|_| ());
+            loaded.map(|()| journal.set_code(*address, Bytecode::new_raw(code.clone())))
+        }
+    };
+
+    if let Err(e) = result {
+        error!("Failed to apply state override {:?}: {:?}", kind, e);
+    }
+}
+
+impl<CTX: ContextTr> Inspector<CTX> for StateOverrideInspector {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        self.apply_overrides(interp, context);
+        self.tracer.step(interp, context);
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut CTX) {
+        self.tracer.step_end(interp, context);
+    }
+
+    fn log(&mut self, context: &mut CTX, log: Log) {
+        self.tracer.log(context, log);
+    }
+
+    fn call(&mut self, context: &mut CTX, inputs: &mut CallInputs) -> Option<CallOutcome> {
+        self.push_frame();
+        self.tracer.call(context, inputs)
+    }
+
+    fn call_end(&mut self, context: &mut CTX, inputs: &CallInputs, outcome: &mut CallOutcome) {
+        self.pop_frame();
+        self.tracer.call_end(context, inputs, outcome);
+    }
+
+    fn create(&mut self, context: &mut CTX, inputs: &mut CreateInputs) -> Option<CreateOutcome> {
+        self.push_frame();
+        self.tracer.create(context, inputs)
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut CTX,
+        inputs: &CreateInputs,
+        outcome: &mut CreateOutcome,
+    ) {
+        self.pop_frame();
+        self.tracer.create_end(context, inputs, outcome);
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        Inspector::<CTX>::selfdestruct(&mut self.tracer, contract, target, value);
+    }
+}
@@ -17,12 +17,28 @@
 //! Utility functions for inspectors.
 
 use alloy_primitives::U256;
-use edb_common::OpcodeTr;
+use edb_common::{EdbContext, OpcodeTr};
 use revm::{
     bytecode::OpCode,
+    context::ContextTr,
+    database::CacheDB,
     interpreter::{interpreter_types::Jumps, Interpreter},
+    Database, DatabaseCommit, DatabaseRef,
 };
 
+/// Build the database a snapshot observes: the pre-transaction state with all
+/// journaled changes so far committed on top.
+pub fn snapshot_database<DB>(ctx: &EdbContext<DB>) -> CacheDB<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone,
+{
+    let mut inner = ctx.journal().to_inner();
+    let changes = inner.finalize();
+    let mut snap = ctx.db().clone();
+    snap.commit(changes);
+    snap
+}
+
 #[inline]
 pub fn relax_gas_limit_at_callsite(interp: &mut Interpreter) {
     let opcode = unsafe { OpCode::new_unchecked(interp.bytecode.opcode()) };
@@ -33,7 +49,9 @@ pub fn relax_gas_limit_at_callsite(interp: &mut Interpreter) {
     // 2. CALLCODE: gas, address, value, argsOffset, argsSize, retOffset, retSize
     // 3. DELEGATECALL: gas, address, argsOffset, argsSize, retOffset, retSize
     // 4. STATICCALL: gas, address, argsOffset, argsSize, retOffset, retSize
-    if !opcode.is_message_call() || opcode.is_creation_call() {
+    //
+    // The EOF calls (EXTCALL, EXTDELEGATECALL, EXTSTATICCALL) take no gas limit.
+    if !opcode.is_message_call() || opcode.is_creation_call() || opcode.is_eof_only() {
         return;
     }
 
@@ -26,10 +26,10 @@ use eyre::Result;
 use semver::Version;
 
 const LEFT_BRACKET_PRIORITY: u8 = 255; // used for the left bracket of the block
-const FUNCTION_ENTRY_PRIORITY: u8 = 191; // used for the before step hook of function and modifier entry
+const FUNCTION_ENTRY_PRIORITY: u8 = 191; // used for the before step hook of function, modifier, and try clause entry
 const VISIBILITY_PRIORITY: u8 = 128; // used for the visibility of state variables and functions
 const VARIABLE_UPDATE_PRIORITY: u8 = 127; // used for the variable update hook
-const BEFORE_STEP_PRIORITY: u8 = 63; // used for the before step hook of statements other than function and modifier entry.
+const BEFORE_STEP_PRIORITY: u8 = 63; // used for the before step hook of statements other than function, modifier, and try clause entry.
 const RIGHT_BRACKET_PRIORITY: u8 = 0; // used for the right bracket of the block
 
 /// A reference to a version.
@@ -259,7 +259,7 @@ impl SourceModifications {
             let usid = step.usid();
             let function_calls = step.function_calls();
             let loc = step.hook_locations().before_step;
-            let priority = if matches!(step.kind(), StepKind::Entry(_)) {
+            let priority = if matches!(step.kind(), StepKind::Entry(_) | StepKind::TryClause) {
                 FUNCTION_ENTRY_PRIORITY
             } else {
                 BEFORE_STEP_PRIORITY
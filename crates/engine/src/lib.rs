@@ -55,30 +55,44 @@
 //! # Core Modules
 //!
 //! - [`analysis`] - Source code analysis and contract instrumentation
+//! - [`bridge`] - Correlation of transactions bridged through canonical OP Stack bridges
+//! - [`cancel`] - Cancellation of long-running transaction preparations
 //! - [`core`] - Core engine types and execution management
 //! - [`context`] - Engine context and state management
+//! - [`debugger`] - Programmatic API for embedding the debugger as a library
 //! - [`eval`] - Expression evaluation system
+//! - [`events`] - Live engine events for subscribed frontends
+//! - [`findings`] - Security-relevant pattern detection over a debugging session
+//! - [`focus`] - Focused preparation of a single subtree of the trace
+//! - [`import`] - Debugging sessions built from external `debug_traceTransaction` results
 //! - [`inspector`] - EVM execution inspectors for data collection
 //! - [`instrumentation`] - Contract instrumentation and code generation
+//! - [`metrics`] - Performance metrics of transaction preparation
+//! - [`mock`] - Mock contracts installed in place of deployed contracts before replay
+//! - [`profile`] - Execution profiles such as gas flamegraphs
+//! - [`report`] - Structured transaction reports for UI-less consumers
 //! - [`rpc`] - JSON-RPC debugging API
 //! - [`snapshot`] - Snapshot management and analysis
+//! - [`status`] - Status tracking of long-running transaction preparations
 //! - [`tweak`] - Runtime contract modification for debugging
 //! - [`utils`] - Utility functions and helpers
 //!
 //! # Quick Start
 //!
 //! ```rust,ignore
-//! use edb_engine::{EngineContext, DebugRpcServer};
-//! use revm::primitives::Address;
+//! use edb_engine::Debugger;
 //!
-//! // Create engine context with debugging data
-//! let context = EngineContext::new(db, snapshots, trace, artifacts);
+//! // Prepare a debugging session in-process
+//! let debugger = Debugger::builder().with_rpc_url(rpc_url).build();
+//! let session = debugger.prepare(tx_hash).await?;
 //!
-//! // Start RPC server for debugging API
-//! let server = DebugRpcServer::new(context);
-//! let handle = server.start().await?;
+//! // Inspect the trace and evaluate expressions against any snapshot
+//! println!("{} trace entries", session.trace().len());
+//! let value = session.eval("balances[msg.sender]", 42)?;
 //!
-//! println!("Debug server running on port {}", handle.port());
+//! // Optionally expose the session through the JSON-RPC debugging API
+//! let handle = session.serve().await?;
+//! println!("Debug server running on {}", handle.addr());
 //! ```
 //!
 //! # Architecture
@@ -95,32 +109,71 @@
 pub mod analysis;
 use analysis::*;
 
+pub mod bridge;
+pub use bridge::*;
+
+pub mod cancel;
+pub use cancel::*;
+
 pub mod core;
 pub use core::*;
 
 pub mod context;
 pub use context::*;
 
+pub mod debugger;
+pub use debugger::*;
+
 pub mod eval;
 pub use eval::*;
 
+pub mod events;
+pub use events::*;
+
+pub mod findings;
+pub use findings::*;
+
+pub mod focus;
+pub use focus::*;
+
+pub mod import;
+pub use import::*;
+
 pub mod inspector;
 pub use inspector::*;
 
 pub mod instrumentation;
 pub use instrumentation::*;
 
+pub mod metrics;
+pub use metrics::*;
+
+pub mod mock;
+pub use mock::*;
+
 pub mod orchestration;
 pub use orchestration::*;
 
+pub mod profile;
+pub use profile::*;
+
+pub mod report;
+pub use report::*;
+
 pub mod rpc;
 pub use rpc::*;
 
 pub mod snapshot;
 pub use snapshot::*;
 
+pub mod status;
+pub use status::*;
+
 pub mod tweak;
 pub use tweak::*;
 
 pub mod utils;
 pub use utils::*;
+
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,247 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Performance metrics of transaction preparation.
+//!
+//! While preparing a transaction, the engine records how long each phase took, how
+//! many cache lookups were served from disk, and how much memory the process uses
+//! afterwards. The resulting [`EngineMetrics`] are stored in the
+//! [`crate::EngineContext`] and exposed through `edb_getMetrics`, so that performance
+//! regressions can be tracked across versions on real transactions.
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use edb_common::CacheStats;
+use serde::{Deserialize, Serialize};
+
+/// Phase of the transaction preparation workflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreparationPhase {
+    /// Forking the chain and replaying the preceding transactions of the block
+    Fork,
+    /// Replaying the target transaction to collect the call trace
+    Replay,
+    /// Downloading verified source code
+    Download,
+    /// Analyzing source code to identify instrumentation points
+    Analyze,
+    /// Instrumenting and recompiling source code
+    Compile,
+    /// Collecting opcode-level snapshots
+    OpcodeSnapshots,
+    /// Replacing the original bytecode with the instrumented versions
+    Instrument,
+    /// Collecting hook-based snapshots
+    HookSnapshots,
+    /// Merging opcode-level and hook-based snapshots
+    SnapshotMerge,
+    /// Building the debugging context and running the detectors
+    Finalize,
+}
+
+/// Time spent in a single preparation phase.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    /// Phase of the preparation workflow
+    pub phase: PreparationPhase,
+    /// Wall-clock duration of the phase in milliseconds
+    pub duration_ms: f64,
+}
+
+/// Cache lookups made while preparing a transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheMetrics {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that found no valid cache entry
+    pub misses: u64,
+    /// Fraction of lookups served from the cache, if any lookup was made
+    pub hit_rate: Option<f64>,
+    /// Lookups of each cache, by cache name (e.g., `etherscan/mainnet/verified_sources`
+    /// or `prestate/mainnet`), see [`CacheStats::by_cache`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_cache: BTreeMap<String, CacheMetrics>,
+}
+
+impl From<CacheStats> for CacheMetrics {
+    fn from(stats: CacheStats) -> Self {
+        Self {
+            hits: stats.hits,
+            misses: stats.misses,
+            hit_rate: stats.hit_rate(),
+            by_cache: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<BTreeMap<String, CacheStats>> for CacheMetrics {
+    fn from(by_cache: BTreeMap<String, CacheStats>) -> Self {
+        let total = by_cache.values().fold(CacheStats::default(), |total, stats| CacheStats {
+            hits: total.hits + stats.hits,
+            misses: total.misses + stats.misses,
+        });
+
+        Self {
+            by_cache: by_cache.into_iter().map(|(name, stats)| (name, stats.into())).collect(),
+            ..total.into()
+        }
+    }
+}
+
+/// Performance metrics of a prepared transaction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineMetrics {
+    /// Version of the engine that prepared the transaction
+    pub engine_version: String,
+    /// Duration of each phase, in execution order
+    pub phases: Vec<PhaseTiming>,
+    /// Total wall-clock duration of the preparation in milliseconds
+    pub total_ms: f64,
+    /// Cache lookups made during the preparation
+    ///
+    /// Lookups are counted process-wide, so transactions prepared concurrently
+    /// contribute to each other's counts.
+    pub cache: CacheMetrics,
+    /// Resident set size of the process once the preparation finished, if known
+    pub memory_rss_bytes: Option<u64>,
+    /// Number of snapshots collected
+    pub snapshot_count: usize,
+    /// Number of trace entries collected
+    pub trace_entries: usize,
+}
+
+impl EngineMetrics {
+    /// Get the duration of a phase in milliseconds, if it was recorded
+    pub fn phase_ms(&self, phase: PreparationPhase) -> Option<f64> {
+        self.phases.iter().find(|timing| timing.phase == phase).map(|timing| timing.duration_ms)
+    }
+}
+
+/// Records [`EngineMetrics`] while a transaction is prepared.
+#[derive(Debug)]
+pub(crate) struct MetricsRecorder {
+    started: Instant,
+    phase_started: Instant,
+    cache_before: BTreeMap<String, CacheStats>,
+    phases: Vec<PhaseTiming>,
+}
+
+impl MetricsRecorder {
+    /// Start recording, counting `fork_duration` as the already finished fork phase
+    pub(crate) fn start(fork_duration: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            phase_started: now,
+            cache_before: CacheStats::by_cache(),
+            phases: vec![PhaseTiming {
+                phase: PreparationPhase::Fork,
+                duration_ms: millis(fork_duration),
+            }],
+        }
+    }
+
+    /// Record the end of a phase, which started when the previous phase ended
+    pub(crate) fn finish_phase(&mut self, phase: PreparationPhase) {
+        let now = Instant::now();
+        self.phases.push(PhaseTiming { phase, duration_ms: millis(now - self.phase_started) });
+        self.phase_started = now;
+    }
+
+    /// Stop recording and collect the metrics
+    pub(crate) fn finish(self, snapshot_count: usize, trace_entries: usize) -> EngineMetrics {
+        let fork_ms = self.phases.first().map(|timing| timing.duration_ms).unwrap_or_default();
+
+        EngineMetrics {
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            phases: self.phases,
+            total_ms: fork_ms + millis(self.started.elapsed()),
+            cache: CacheStats::by_cache_since(&self.cache_before).into(),
+            memory_rss_bytes: resident_set_size(),
+            snapshot_count,
+            trace_entries,
+        }
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Get the resident set size of the current process
+#[cfg(target_os = "linux")]
+fn resident_set_size() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Get the resident set size of the current process
+#[cfg(not(target_os = "linux"))]
+fn resident_set_size() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_recorder() {
+        let mut recorder = MetricsRecorder::start(Duration::from_secs(2));
+        recorder.finish_phase(PreparationPhase::Replay);
+        recorder.finish_phase(PreparationPhase::Finalize);
+        let metrics = recorder.finish(3, 2);
+
+        let phases: Vec<_> = metrics.phases.iter().map(|timing| timing.phase).collect();
+        assert_eq!(
+            phases,
+            vec![PreparationPhase::Fork, PreparationPhase::Replay, PreparationPhase::Finalize]
+        );
+        assert_eq!(metrics.phase_ms(PreparationPhase::Fork), Some(2000.0));
+        assert!(metrics.total_ms >= 2000.0);
+        assert_eq!(metrics.snapshot_count, 3);
+        assert_eq!(metrics.trace_entries, 2);
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(json["phases"][0]["phase"], "fork");
+    }
+
+    #[test]
+    fn test_cache_metrics_by_cache() {
+        let by_cache = BTreeMap::from([
+            ("etherscan/mainnet/verified_sources".to_string(), CacheStats { hits: 3, misses: 1 }),
+            ("prestate/mainnet".to_string(), CacheStats { hits: 0, misses: 1 }),
+        ]);
+        let metrics = CacheMetrics::from(by_cache);
+
+        assert_eq!((metrics.hits, metrics.misses), (3, 2));
+        assert_eq!(metrics.hit_rate, Some(0.6));
+        assert_eq!(metrics.by_cache["etherscan/mainnet/verified_sources"].hit_rate, Some(0.75));
+        assert_eq!(metrics.by_cache["prestate/mainnet"].hit_rate, Some(0.0));
+
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(json["byCache"]["prestate/mainnet"]["misses"], 1);
+    }
+}
@@ -0,0 +1,385 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mock contracts installed in place of deployed contracts before replay.
+//!
+//! A [`MockContract`] is Solidity source code that the engine compiles and whose runtime
+//! bytecode it installs at a chosen address before the transaction is replayed, e.g., to
+//! replace a price oracle with one returning a fixed price. This isolates the behavior of
+//! the contract under study from the contracts it depends on.
+//!
+//! Only the runtime bytecode is installed: the constructor of the mock is not run, and the
+//! balance, nonce and storage of the address are kept. Mocks should thus not rely on
+//! immutable variables or on storage set by their constructor.
+//!
+//! Mocked addresses are not instrumented, since their verified source code (if any) does
+//! not match the mock. They are debugged at the opcode level.
+//!
+//! # Usage Example
+//!
+//! ```rust,ignore
+//! let mock = MockContract::from_spec("0x5f4e...8419=mocks/FixedOracle.sol:FixedOracle")?;
+//! let config = EngineConfig::default().with_mock(mock);
+//! ```
+
+use std::{fmt, fs, path::PathBuf};
+
+use alloy_primitives::{Address, Bytes};
+use edb_common::EdbContext;
+use eyre::{bail, eyre, Result};
+use foundry_compilers::{
+    artifacts::{
+        output_selection::OutputSelection, EvmVersion, Settings, Severity, SolcInput, Source,
+        Sources,
+    },
+    solc::{Solc, SolcLanguage},
+    Artifact,
+};
+use revm::{
+    context::ContextTr, database::CacheDB, primitives::KECCAK_EMPTY, state::Bytecode, Database,
+    DatabaseCommit, DatabaseRef,
+};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::find_or_install_solc;
+
+/// Solidity source code compiled and installed at an address before replay.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockContract {
+    /// Address the mock is installed at
+    pub address: Address,
+    /// Solidity source code of the mock
+    pub source: String,
+    /// Name of the contract to install, required if the source defines several
+    /// deployable contracts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract: Option<String>,
+    /// Version of solc to compile with, defaults to the lowest version allowed by the
+    /// `pragma solidity` of the source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solc_version: Option<String>,
+}
+
+impl MockContract {
+    /// Create a mock from its source code
+    pub fn new(address: Address, source: impl Into<String>) -> Self {
+        Self { address, source: source.into(), contract: None, solc_version: None }
+    }
+
+    /// Parse a mock given as `ADDRESS=PATH[:CONTRACT]`, reading its source from `PATH`
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        let (address, path) =
+            spec.split_once('=').ok_or_else(|| eyre!("Expected ADDRESS=PATH[:CONTRACT]"))?;
+        let address: Address =
+            address.trim().parse().map_err(|e| eyre!("Invalid mock address {address:?}: {e}"))?;
+
+        // A trailing `:NAME` selects the contract, unless it is part of the path
+        let (path, contract) = match path.rsplit_once(':') {
+            Some((path, name))
+                if !name.is_empty()
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$') =>
+            {
+                (path, Some(name.to_string()))
+            }
+            _ => (path, None),
+        };
+
+        let source = fs::read_to_string(PathBuf::from(path))
+            .map_err(|e| eyre!("Failed to read mock source {path}: {e}"))?;
+        Ok(Self { contract, ..Self::new(address, source) })
+    }
+
+    /// Select the contract to install, if the source defines several
+    pub fn with_contract(mut self, contract: impl Into<String>) -> Self {
+        self.contract = Some(contract.into());
+        self
+    }
+
+    /// Set the version of solc to compile the mock with
+    pub fn with_solc_version(mut self, version: impl Into<String>) -> Self {
+        self.solc_version = Some(version.into());
+        self
+    }
+
+    /// Compile the mock and get the runtime bytecode to install
+    pub fn compile(&self) -> Result<Bytes> {
+        let version = match &self.solc_version {
+            Some(version) => Version::parse(version.trim_start_matches('v'))
+                .map_err(|e| eyre!("Invalid solc version {version:?}: {e}"))?,
+            None => detect_solc_version(&self.source)?,
+        };
+
+        let sources =
+            Sources::from_iter([(PathBuf::from("Mock.sol"), Source::new(self.source.clone()))]);
+        let mut settings = Settings::new(OutputSelection::default_output_selection());
+        settings.evm_version = EvmVersion::default_version_solc(&version);
+        let input = SolcInput::new(SolcLanguage::Solidity, sources, settings);
+        let output = find_or_install_solc(&version)?.compile_exact(&input)?;
+
+        let errors = output
+            .errors
+            .iter()
+            .filter(|e| e.severity() == Severity::Error)
+            .map(|e| format!("{e}"))
+            .collect::<Vec<_>>();
+        if !errors.is_empty() {
+            bail!("Compiler error: {}", errors.join("\n"));
+        }
+
+        // Interfaces and abstract contracts have no runtime code
+        let mut deployable = output
+            .contracts
+            .values()
+            .flatten()
+            .filter_map(|(name, contract)| {
+                let code = contract.get_deployed_bytecode_bytes()?.into_owned();
+                (!code.is_empty()).then_some((name.as_str(), code))
+            })
+            .collect::<Vec<_>>();
+
+        match &self.contract {
+            Some(contract) => deployable
+                .into_iter()
+                .find(|(name, _)| name == contract)
+                .map(|(_, code)| code)
+                .ok_or_else(|| eyre!("No deployable contract {contract} in the mock source")),
+            None if deployable.len() == 1 => Ok(deployable.remove(0).1),
+            None if deployable.is_empty() => bail!("No deployable contract in the mock source"),
+            None => bail!(
+                "Several deployable contracts in the mock source, select one of {}",
+                deployable.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+}
+
+impl fmt::Display for MockContract {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.contract {
+            Some(contract) => write!(f, "{contract} at {}", self.address),
+            None => write!(f, "mock at {}", self.address),
+        }
+    }
+}
+
+/// Get the lowest released solc version allowed by the `pragma solidity` of a source
+fn detect_solc_version(source: &str) -> Result<Version> {
+    let mut versions = Solc::released_versions();
+    versions.extend(Solc::installed_versions());
+    lowest_allowed_version(source, &versions)
+}
+
+/// Get the lowest of some solc versions allowed by the `pragma solidity` of a source
+fn lowest_allowed_version(source: &str, versions: &[Version]) -> Result<Version> {
+    let pragma = solidity_pragma(source)
+        .ok_or_else(|| eyre!("No `pragma solidity` in the mock source, set its solc version"))?;
+    let requirements = parse_pragma(&pragma).ok_or_else(|| {
+        eyre!("Unsupported `pragma solidity {pragma}`, set the mock's solc version")
+    })?;
+
+    versions
+        .iter()
+        .filter(|version| version.pre.is_empty())
+        .filter(|version| requirements.iter().any(|requirement| requirement.matches(version)))
+        .min()
+        .cloned()
+        .ok_or_else(|| eyre!("No solc release satisfies `pragma solidity {pragma}`"))
+}
+
+/// Get the version requirement of the first `pragma solidity` of a source
+fn solidity_pragma(source: &str) -> Option<String> {
+    strip_comments(source).split(';').find_map(|statement| {
+        let rest = statement.trim_start().strip_prefix("pragma")?;
+        let rest = rest.strip_prefix(char::is_whitespace)?.trim_start();
+        let pragma = rest.strip_prefix("solidity")?;
+        pragma.starts_with(char::is_whitespace).then(|| pragma.trim().to_string())
+    })
+}
+
+/// Parse a solidity version requirement, e.g., `>=0.7.6 <0.9.0 || ^0.6.12`, into the
+/// requirements of its alternatives
+fn parse_pragma(pragma: &str) -> Option<Vec<VersionReq>> {
+    pragma
+        .split("||")
+        .map(|range| {
+            // Comparators are separated by spaces, and a bare version is an exact one
+            let mut comparators = Vec::new();
+            let mut operator = String::new();
+            for token in range.split_whitespace() {
+                let split = token.find(|c: char| c.is_ascii_digit()).unwrap_or(token.len());
+                operator.push_str(&token[..split]);
+                if split == token.len() {
+                    continue;
+                }
+                let operator = std::mem::take(&mut operator);
+                let operator = if operator.is_empty() { "=" } else { operator.as_str() };
+                comparators.push(format!("{operator}{}", &token[split..]));
+            }
+            if comparators.is_empty() || !operator.is_empty() {
+                return None;
+            }
+            VersionReq::parse(&comparators.join(", ")).ok()
+        })
+        .collect()
+}
+
+/// Replace the comments of a source with spaces, leaving string literals untouched
+fn strip_comments(source: &str) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+                code.push(' ');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+                code.push(' ');
+            }
+            '"' | '\'' => {
+                code.push(c);
+                while let Some(next) = chars.next() {
+                    code.push(next);
+                    match next {
+                        '\\' => code.extend(chars.next()),
+                        next if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            c => code.push(c),
+        }
+    }
+    code
+}
+
+/// Install mock contracts in the database of a context
+///
+/// # Arguments
+///
+/// * `ctx` - Context whose database receives the mocks
+/// * `mocks` - Mocks to compile and install
+pub fn install_mock_contracts<DB>(ctx: &mut EdbContext<DB>, mocks: &[MockContract]) -> Result<()>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone,
+    <CacheDB<DB> as Database>::Error: Clone,
+    <DB as Database>::Error: Clone,
+{
+    for mock in mocks {
+        let code = mock.compile().map_err(|e| eyre!("Failed to compile {mock}: {e}"))?;
+
+        let db = ctx.db_mut();
+        let mut info = db
+            .basic(mock.address)
+            .map_err(|e| eyre!("Failed to get account info for {}: {}", mock.address, e))?
+            .unwrap_or_default();
+        // Code hash will be update within `db.insert_account_info(&mut info);`
+        info.code_hash = KECCAK_EMPTY;
+        info.code = Some(Bytecode::new_raw(code));
+        db.insert_account_info(mock.address, info);
+
+        info!("Installed {}", mock);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_solc_version() {
+        let source = "// SPDX-License-Identifier: MIT\npragma solidity ^0.8.19;\ncontract A {}";
+        assert_eq!(detect_solc_version(source).unwrap(), Version::new(0, 8, 19));
+        let source = "pragma solidity >=0.7.6 <0.9.0;\ncontract A {}";
+        assert_eq!(detect_solc_version(source).unwrap(), Version::new(0, 7, 6));
+        assert!(detect_solc_version("contract A {}").is_err());
+    }
+
+    #[test]
+    fn test_lowest_allowed_version() {
+        let versions: Vec<Version> = ["0.8.30", "0.7.6", "0.8.0", "0.8.1", "0.8.19", "0.6.12"]
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect();
+        let lowest = |pragma: &str| {
+            let source = format!("pragma solidity {pragma};\ncontract A {{}}");
+            lowest_allowed_version(&source, &versions).map(|version| version.to_string())
+        };
+
+        assert_eq!(lowest("^0.8.19").unwrap(), "0.8.19");
+        assert_eq!(lowest("0.8.19").unwrap(), "0.8.19");
+        assert_eq!(lowest("=0.8.0").unwrap(), "0.8.0");
+        assert_eq!(lowest("~0.8.1").unwrap(), "0.8.1");
+        assert_eq!(lowest(">=0.7.6 <0.9.0").unwrap(), "0.7.6");
+        assert_eq!(lowest("^0.7.0 || ^0.8.0").unwrap(), "0.7.6");
+
+        // Exclusive bounds
+        assert_eq!(lowest(">0.8.0").unwrap(), "0.8.1");
+        assert_eq!(lowest("> 0.8.0").unwrap(), "0.8.1");
+        assert_eq!(lowest("<0.9.0").unwrap(), "0.6.12");
+        assert_eq!(lowest(">0.6.12 <0.8.1").unwrap(), "0.7.6");
+        assert_eq!(lowest("<0.8.0 >0.7.0").unwrap(), "0.7.6");
+
+        assert!(lowest(">0.9.0").unwrap_err().to_string().contains("No solc release"));
+        assert!(lowest(">=").unwrap_err().to_string().contains("Unsupported"));
+    }
+
+    #[test]
+    fn test_pragma_in_comments_is_ignored() {
+        let versions = [Version::new(0, 4, 26), Version::new(0, 8, 19)];
+        let source = "// pragma solidity ^0.4.0;\n/* pragma solidity ^0.4.0; */\n\
+                      pragma solidity ^0.8.0;\ncontract A { string s = \"//\"; }";
+        assert_eq!(lowest_allowed_version(source, &versions).unwrap(), Version::new(0, 8, 19));
+
+        let source = "contract A { string s = \"pragma solidity ^0.4.0;\"; }";
+        assert!(lowest_allowed_version(source, &versions).is_err());
+    }
+
+    #[test]
+    fn test_from_spec() {
+        let dir = std::env::temp_dir().join(format!("edb-mock-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Oracle.sol");
+        fs::write(&path, "pragma solidity 0.8.19;\ncontract Oracle {}").unwrap();
+        let address = Address::repeat_byte(0x11);
+
+        let mock = MockContract::from_spec(&format!("{address}={}", path.display())).unwrap();
+        assert_eq!(mock.address, address);
+        assert_eq!(mock.contract, None);
+        assert!(mock.source.contains("contract Oracle"));
+
+        let mock =
+            MockContract::from_spec(&format!("{address}={}:Oracle", path.display())).unwrap();
+        assert_eq!(mock.contract.as_deref(), Some("Oracle"));
+
+        assert!(MockContract::from_spec(&path.display().to_string()).is_err());
+        assert!(MockContract::from_spec("0x11=missing.sol").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
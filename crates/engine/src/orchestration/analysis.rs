@@ -26,7 +26,7 @@ use eyre::Result;
 use revm::{
     context::{
         result::{ExecutionResult, HaltReason},
-        TxEnv,
+        Cfg, ContextTr, TxEnv,
     },
     database::CacheDB,
     Database, DatabaseCommit, DatabaseRef, InspectEvm, MainBuilder,
@@ -70,9 +70,6 @@ where
         }
     }
 
-    // Print the trace tree structure
-    result.execution_trace.print_trace_tree();
-
     Ok(result)
 }
 
@@ -108,8 +105,8 @@ where
 {
     info!("Tweaking bytecode");
 
-    let mut tweaker =
-        CodeTweaker::new(ctx, config.rpc_proxy_url.clone(), config.etherscan_api_key.clone());
+    let explorer = config.explorer(ctx.cfg().chain_id());
+    let mut tweaker = CodeTweaker::new(ctx, config.rpc_proxy_url.clone(), explorer);
 
     let mut contracts_in_tx = Vec::new();
 
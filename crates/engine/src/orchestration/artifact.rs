@@ -21,7 +21,6 @@ use std::{collections::HashMap, env, fs, time::Duration};
 use alloy_primitives::Address;
 use edb_common::{CachePath, EdbCachePath, DEFAULT_ETHERSCAN_CACHE_TTL};
 use eyre::{bail, Result};
-use foundry_block_explorers::Client;
 use futures::future::join_all;
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
@@ -30,14 +29,16 @@ use tracing::{debug, error, info, warn};
 
 use crate::{
     analysis::AnalysisResult, dump_source_for_debugging, find_or_install_solc,
-    format_compiler_errors, instrument, Artifact, EngineConfig, OnchainCompiler, TraceReplayResult,
+    format_compiler_errors, instrument, Artifact, CancellationToken, Cancelled, EngineConfig,
+    MockContract, OnchainCompiler, TraceReplayResult,
 };
 
-/// Download and compile verified source code for each contract
+/// Download and compile verified source code for each contract, except mocked ones
 pub async fn download_verified_source_code(
     config: &EngineConfig,
     replay_result: &TraceReplayResult,
     chain_id: u64,
+    mocks: &[MockContract],
 ) -> Result<HashMap<Address, Artifact>> {
     info!("Downloading verified source code for touched contracts");
 
@@ -48,7 +49,13 @@ pub async fn download_verified_source_code(
     let etherscan_cache_root = EdbCachePath::new(env::var(edb_common::env::EDB_CACHE_DIR).ok())
         .etherscan_chain_cache_dir(chain_id);
 
-    let addresses: Vec<_> = replay_result.visited_addresses.keys().copied().collect();
+    // The verified source of a mocked address does not match the installed mock
+    let addresses: Vec<_> = replay_result
+        .visited_addresses
+        .keys()
+        .filter(|address| !mocks.iter().any(|mock| mock.address == **address))
+        .copied()
+        .collect();
     let total_contracts = addresses.len();
 
     let console_bar = std::sync::Arc::new(ProgressBar::new(total_contracts as u64));
@@ -68,7 +75,7 @@ pub async fn download_verified_source_code(
     // Create all download futures
     let download_futures = addresses.iter().map(|address| {
         let pb = console_bar.clone();
-        let api_key = config.get_etherscan_api_key();
+        let explorer = config.explorer(chain_id);
         let etherscan_cache_root = etherscan_cache_root.clone();
         let compiler = compiler.clone();
 
@@ -76,13 +83,10 @@ pub async fn download_verified_source_code(
             let short_addr = &address.to_string()[2..10]; // Skip 0x, take 8 chars
             pb.set_message(format!("Downloading: 0x{short_addr}..."));
 
-            let etherscan = Client::builder()
-                .with_api_key(api_key)
-                .with_cache(etherscan_cache_root, Duration::from_secs(cache_ttl))
-                .chain(chain_id.into())?
-                .build()?;
+            let mut etherscan =
+                explorer.client(chain_id, etherscan_cache_root, Duration::from_secs(cache_ttl))?;
 
-            let result = match compiler.compile(&etherscan, *address).await {
+            let result = match compiler.compile(&mut etherscan, *address).await {
                 Ok(Some(artifact)) => {
                     pb.set_message(format!("✅ 0x{short_addr}... compiled"));
                     Some(artifact)
@@ -127,9 +131,13 @@ pub async fn download_verified_source_code(
 }
 
 /// Instrument and recompile the source code
+///
+/// Contracts whose recompilation has not started yet are skipped once `cancel` is
+/// cancelled, and a [`crate::Cancelled`] error is returned.
 pub fn instrument_and_recompile_source_code(
     artifacts: &HashMap<Address, Artifact>,
     analysis_result: &HashMap<Address, AnalysisResult>,
+    cancel: &CancellationToken,
 ) -> Result<HashMap<Address, Artifact>> {
     info!("Instrumenting source code based on analysis results");
 
@@ -151,6 +159,7 @@ pub fn instrument_and_recompile_source_code(
                 pb.set_message(format!("Recompiling: 0x{short_addr}..."));
 
                 let result = (|| -> Result<Artifact> {
+                    cancel.check()?;
                     let compiler_version =
                         Version::parse(artifact.compiler_version().trim_start_matches('v'))?;
 
@@ -239,6 +248,10 @@ pub fn instrument_and_recompile_source_code(
             })
             .collect();
 
+    if cancel.is_cancelled() {
+        progress_bar.abandon_with_message("Instrumentation cancelled");
+        return Err(Cancelled.into());
+    }
     progress_bar.finish_with_message("✨ Instrumentation complete!");
 
     // Process results and collect errors
@@ -0,0 +1,207 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Orchestration module that links external libraries of downloaded artifacts
+//! and attributes library frames to the artifacts compiling them.
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use alloy_primitives::{Address, Bytes};
+use edb_common::EdbContext;
+use eyre::{bail, eyre, Result};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use tracing::{debug, info, warn};
+
+use crate::{find_or_install_solc, Artifact, TraceReplayResult};
+
+/// Resolve the external libraries of downloaded artifacts.
+///
+/// Artifacts whose runtime code still has unresolved link references are
+/// recompiled with the library addresses read from the on-chain code. Visited
+/// library addresses without verified source are then given the artifact of a
+/// contract linking against them, with the library as subject contract, as
+/// long as the compiled library matches the on-chain code.
+pub fn link_external_libraries<DB>(
+    ctx: &mut EdbContext<DB>,
+    replay_result: &TraceReplayResult,
+    artifacts: &mut HashMap<Address, Artifact>,
+) -> Result<()>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone,
+    <CacheDB<DB> as Database>::Error: Clone,
+    <DB as Database>::Error: Clone,
+{
+    info!("Resolving external libraries of downloaded contracts");
+
+    let mut linked = Vec::new();
+    let addresses: Vec<_> = artifacts.keys().copied().collect();
+    for address in addresses {
+        let code = runtime_code(ctx, address)?;
+        let artifact = &artifacts[&address];
+        let libraries = artifact.linked_libraries(&code);
+        if libraries.is_empty() {
+            continue;
+        }
+
+        if artifact.has_unlinked_libraries() && !code.is_empty() {
+            match relink(artifact, &libraries) {
+                Ok(relinked) => {
+                    debug!("Relinked contract {} against {} libraries", address, libraries.len());
+                    artifacts.insert(address, relinked);
+                }
+                Err(e) => warn!("Failed to link libraries of contract {}: {:?}", address, e),
+            }
+        }
+
+        linked.extend(libraries.into_iter().map(|((_, name), library)| (library, address, name)));
+    }
+
+    for (library, linker, name) in linked {
+        if artifacts.contains_key(&library)
+            || !replay_result.visited_addresses.contains_key(&library)
+        {
+            continue;
+        }
+
+        let Some(artifact) = artifacts[&linker].library_artifact(&name) else {
+            debug!("Library {} linked by {} is not part of its sources", name, linker);
+            continue;
+        };
+
+        let code = runtime_code(ctx, library)?;
+        let matches = artifact
+            .contract()
+            .and_then(|c| c.get_deployed_bytecode_bytes())
+            .is_some_and(|compiled| same_library_code(&compiled, &code));
+        if !matches {
+            warn!("Library {} at {} does not match the sources of {}", name, library, linker);
+            continue;
+        }
+
+        info!("Attributing library {} at {} to the sources of {}", name, library, linker);
+        artifacts.insert(library, artifact);
+    }
+
+    Ok(())
+}
+
+/// Recompile an artifact with the given libraries added to its settings.
+fn relink(
+    artifact: &Artifact,
+    libraries: &BTreeMap<(String, String), Address>,
+) -> Result<Artifact> {
+    let mut input = artifact.input.clone();
+    for ((file, name), address) in libraries {
+        input
+            .settings
+            .libraries
+            .libs
+            .entry(PathBuf::from(file))
+            .or_default()
+            .entry(name.clone())
+            .or_insert_with(|| address.to_string());
+    }
+
+    let compiler = find_or_install_solc(&artifact.meta.compiler_version()?)?;
+    let output = compiler.compile_exact(&input)?;
+    if output.errors.iter().any(|e| e.is_error()) {
+        bail!("Compilation failed with {} error(s)", output.errors.len());
+    }
+
+    Ok(Artifact { meta: artifact.meta.clone(), input, output })
+}
+
+/// Runtime code of an account in the context database.
+fn runtime_code<DB>(ctx: &mut EdbContext<DB>, address: Address) -> Result<Bytes>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone,
+    <CacheDB<DB> as Database>::Error: Clone,
+    <DB as Database>::Error: Clone,
+{
+    let db = ctx.db_mut();
+    let info = db
+        .basic(address)
+        .map_err(|e| eyre!("Failed to get account info for {}: {}", address, e))?
+        .unwrap_or_default();
+
+    let code = match info.code {
+        Some(code) => code,
+        None => db
+            .code_by_hash(info.code_hash)
+            .map_err(|e| eyre!("Failed to get code of {}: {}", address, e))?,
+    };
+
+    Ok(code.original_bytes())
+}
+
+/// Whether compiled library runtime code matches the deployed one.
+///
+/// The deployed code embeds the library address right after its leading
+/// `PUSH20` (the call protection), and the trailing CBOR metadata depends on
+/// the settings the library was compiled with, so both are ignored.
+fn same_library_code(compiled: &[u8], deployed: &[u8]) -> bool {
+    const ADDRESS_END: usize = 1 + Address::len_bytes();
+
+    let compiled = strip_metadata(compiled);
+    let deployed = strip_metadata(deployed);
+    if compiled.len() != deployed.len() || compiled.len() < ADDRESS_END {
+        return false;
+    }
+
+    compiled[0] == deployed[0] && compiled[ADDRESS_END..] == deployed[ADDRESS_END..]
+}
+
+/// Strip the CBOR metadata appended to runtime code, if any.
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    let Some(len) = code.len().checked_sub(2) else {
+        return code;
+    };
+
+    let metadata_len = u16::from_be_bytes([code[len], code[len + 1]]) as usize;
+    match len.checked_sub(metadata_len) {
+        Some(end) if metadata_len > 0 => &code[..end],
+        _ => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_library_code() {
+        let mut compiled = vec![0x73];
+        compiled.extend([0u8; 20]);
+        compiled.extend([0x30, 0x14, 0x60, 0x80]);
+
+        let mut deployed = compiled.clone();
+        deployed[1..21].copy_from_slice(&[0xab; 20]);
+        assert!(same_library_code(&compiled, &deployed));
+
+        // Differing metadata is ignored
+        let mut compiled_with_metadata = compiled.clone();
+        compiled_with_metadata.extend([0xa2, 0x64, 0x00, 0x02]);
+        let mut deployed_with_metadata = deployed.clone();
+        deployed_with_metadata.extend([0xa2, 0x65, 0x00, 0x02]);
+        assert!(same_library_code(&compiled_with_metadata, &deployed_with_metadata));
+
+        deployed[22] = 0x00;
+        assert!(!same_library_code(&compiled, &deployed));
+        assert!(!same_library_code(&compiled, &deployed[..10]));
+    }
+}
@@ -19,8 +19,10 @@
 //! and snapshot generation.
 pub mod analysis;
 pub mod artifact;
+pub mod library;
 pub mod snapshot;
 
 pub use analysis::*;
 pub use artifact::*;
+pub use library::*;
 pub use snapshot::*;
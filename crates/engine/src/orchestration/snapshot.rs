@@ -35,11 +35,15 @@ use crate::{
 
 /// Time travel (i.e., snapshotting) at the opcode level for contracts we do not
 /// have source code.
+///
+/// If `focus` is given, only the first step of each frame is recorded outside of the
+/// focused trace entries.
 pub fn capture_opcode_level_snapshots<DB>(
     ctx: EdbContext<DB>,
     tx: TxEnv,
     excluded_addresses: HashSet<Address>,
     trace: &Trace,
+    focus: Option<&HashSet<usize>>,
 ) -> Result<OpcodeSnapshots<DB>>
 where
     DB: Database + DatabaseCommit + DatabaseRef + Clone,
@@ -50,6 +54,9 @@ where
 
     let mut inspector = OpcodeSnapshotInspector::new(&ctx, trace);
     inspector.with_excluded_addresses(excluded_addresses);
+    if let Some(entries) = focus {
+        inspector.with_focus(entries.clone());
+    }
     let mut evm = ctx.build_mainnet_with_inspector(&mut inspector);
 
     evm.inspect_one_tx(tx)
@@ -57,8 +64,6 @@ where
 
     let snapshots = inspector.into_snapshots();
 
-    snapshots.print_summary();
-
     Ok(snapshots)
 }
 
@@ -87,12 +92,16 @@ pub fn collect_creation_hooks<'a>(
 }
 
 /// Time travel (i.e., snapshotting) at hooks for contracts we have source code
+///
+/// If `focus` is given, only the first hook of each frame is recorded outside of the
+/// focused trace entries.
 pub fn capture_hook_snapshots<'a, DB>(
     mut ctx: EdbContext<DB>,
     mut tx: TxEnv,
     creation_hooks: Vec<(&'a Contract, &'a Contract, &'a Bytes)>,
     trace: &Trace,
     analysis_results: &HashMap<Address, AnalysisResult>,
+    focus: Option<&HashSet<usize>>,
 ) -> Result<HookSnapshots<DB>>
 where
     DB: Database + DatabaseCommit + DatabaseRef + Clone,
@@ -108,6 +117,9 @@ where
 
     let mut inspector = HookSnapshotInspector::new(&ctx, trace, analysis_results);
     inspector.with_creation_hooks(creation_hooks)?;
+    if let Some(entries) = focus {
+        inspector.with_focus(entries.clone());
+    }
     let mut evm = ctx.build_mainnet_with_inspector(&mut inspector);
 
     evm.inspect_one_tx(tx)
@@ -115,8 +127,6 @@ where
 
     let snapshots = inspector.into_snapshots();
 
-    snapshots.print_summary();
-
     Ok(snapshots)
 }
 
@@ -134,7 +144,5 @@ where
     info!("Merging opcode-level and hook-level snapshots");
 
     let snapshots = Snapshots::merge(opcode_snapshots, hook_snapshots);
-    snapshots.print_summary();
-
     Ok(snapshots)
 }
@@ -0,0 +1,197 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Source line coverage of a debugged transaction.
+//!
+//! Every step found by the analysis pass marks an executable source line. A line is
+//! covered when a hook snapshot of one of its steps was recorded during the replay, so
+//! the report answers which code paths the transaction actually touched. Contracts
+//! without verified source code are not part of the report.
+
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+use alloy_primitives::Address;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde::{Deserialize, Serialize};
+
+use crate::{analysis::SourceAnalysis, EngineContext, SnapshotDetail};
+
+/// Line coverage of all contracts with source code executed in a transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    /// Coverage per contract, ordered by address
+    pub contracts: Vec<ContractCoverage>,
+}
+
+/// Line coverage of a single contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractCoverage {
+    /// Address of the executed code
+    pub address: Address,
+    /// Name of the contract
+    pub contract_name: String,
+    /// Coverage per source file, ordered by path
+    pub files: Vec<FileCoverage>,
+}
+
+/// Line coverage of a single source file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileCoverage {
+    /// Path of the source file
+    pub path: PathBuf,
+    /// Execution count of each executable line (1-based)
+    pub lines: BTreeMap<usize, u64>,
+}
+
+impl FileCoverage {
+    /// Number of executable lines
+    pub fn lines_found(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Number of executable lines executed at least once
+    pub fn lines_hit(&self) -> usize {
+        self.lines.values().filter(|count| **count > 0).count()
+    }
+}
+
+impl CoverageReport {
+    /// Render the report in the LCOV tracefile format.
+    ///
+    /// Each contract is emitted as a separate test (`TN`) so that files shared by
+    /// several contracts are reported once per contract.
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for contract in &self.contracts {
+            for file in &contract.files {
+                let _ = writeln!(lcov, "TN:{}@{}", contract.contract_name, contract.address);
+                let _ = writeln!(lcov, "SF:{}", file.path.display());
+                for (line, count) in &file.lines {
+                    let _ = writeln!(lcov, "DA:{line},{count}");
+                }
+                let _ = writeln!(lcov, "LF:{}", file.lines_found());
+                let _ = writeln!(lcov, "LH:{}", file.lines_hit());
+                let _ = writeln!(lcov, "end_of_record");
+            }
+        }
+        lcov
+    }
+}
+
+/// Compute the source line coverage of the debugged transaction.
+pub fn coverage_report<DB>(context: &EngineContext<DB>) -> CoverageReport
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    // Count how often each step of each contract was executed
+    let mut hits: BTreeMap<Address, BTreeMap<_, u64>> = BTreeMap::new();
+    for (_, snapshot) in context.snapshots.iter() {
+        if let SnapshotDetail::Hook(detail) = snapshot.detail() {
+            *hits.entry(detail.bytecode_address).or_default().entry(detail.usid).or_default() += 1;
+        }
+    }
+
+    let mut addresses: Vec<_> = context.analysis_results.keys().copied().collect();
+    addresses.sort();
+
+    let mut report = CoverageReport::default();
+    for address in addresses {
+        // Only report contracts that were executed in the transaction
+        let Some(step_hits) = hits.get(&address) else { continue };
+        let analysis = &context.analysis_results[&address];
+
+        let mut sources: Vec<&SourceAnalysis> = analysis.sources.values().collect();
+        sources.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let files = sources
+            .into_iter()
+            .filter(|source| !source.steps.is_empty())
+            .map(|source| {
+                let line_starts = line_starts(&source.source);
+                let mut lines = BTreeMap::new();
+                for step in &source.steps {
+                    let line = line_of(&line_starts, step.src().start);
+                    let count = step_hits.get(&step.usid()).copied().unwrap_or_default();
+                    *lines.entry(line).or_default() += count;
+                }
+                FileCoverage { path: source.path.clone(), lines }
+            })
+            .collect();
+
+        let contract_name = context
+            .artifacts
+            .get(&address)
+            .map(|artifact| artifact.contract_name().to_string())
+            .unwrap_or_default();
+        report.contracts.push(ContractCoverage { address, contract_name, files });
+    }
+
+    report
+}
+
+/// Byte offsets at which each line of `source` starts.
+pub(crate) fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1)).collect()
+}
+
+/// 1-based line number of a byte offset.
+pub(crate) fn line_of(line_starts: &[usize], offset: usize) -> usize {
+    line_starts.partition_point(|start| *start <= offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    #[test]
+    fn test_line_of() {
+        let starts = line_starts("a;\nb;\n\nc;");
+        assert_eq!(starts, vec![0, 3, 6, 7]);
+        assert_eq!(line_of(&starts, 0), 1);
+        assert_eq!(line_of(&starts, 2), 1);
+        assert_eq!(line_of(&starts, 3), 2);
+        assert_eq!(line_of(&starts, 8), 4);
+    }
+
+    #[test]
+    fn test_to_lcov() {
+        let report = CoverageReport {
+            contracts: vec![ContractCoverage {
+                address: address!("0x1111111111111111111111111111111111111111"),
+                contract_name: "Token".to_string(),
+                files: vec![FileCoverage {
+                    path: PathBuf::from("src/Token.sol"),
+                    lines: BTreeMap::from([(10, 2), (11, 0), (12, 1)]),
+                }],
+            }],
+        };
+
+        assert_eq!(
+            report.to_lcov(),
+            "TN:Token@0x1111111111111111111111111111111111111111\n\
+             SF:src/Token.sol\n\
+             DA:10,2\n\
+             DA:11,0\n\
+             DA:12,1\n\
+             LF:3\n\
+             LH:2\n\
+             end_of_record\n"
+        );
+    }
+}
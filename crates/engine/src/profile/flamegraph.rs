@@ -0,0 +1,256 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Gas and wall-clock flamegraphs in folded-stack format.
+//!
+//! Each line of the output is a call path from the outermost frame to a frame, with
+//! frames separated by `;`, followed by the gas spent by the frame itself (i.e.,
+//! excluding the gas spent by its children):
+//!
+//! ```text
+//! Router::swap;Pair::swap 48210
+//! Router::swap;Pair::swap;Token::transfer 29714
+//! ```
+//!
+//! Alternatively, frames can be weighted by the nanoseconds their replay took. The
+//! output can be rendered with `inferno-flamegraph` or loaded into speedscope.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::Selector;
+use edb_common::types::{CallType, Trace, TraceEntry};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+
+use crate::EngineContext;
+
+/// Render the gas profile of a session as folded stacks.
+///
+/// Frames are named `Contract::function` whenever verified source code is available
+/// for the executed code, and fall back to addresses and raw selectors otherwise.
+pub fn folded_stacks<DB>(context: &EngineContext<DB>) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    fold_trace(&context.trace, |entry| frame_name(context, entry))
+}
+
+/// Render the wall-clock profile of the replay of a session as folded stacks, in
+/// nanoseconds.
+///
+/// Returns `None` if frame times were not recorded, e.g., for imported traces.
+pub fn wall_clock_folded_stacks<DB>(context: &EngineContext<DB>) -> Option<String>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    if context.frame_times.len() != context.trace.len() {
+        return None;
+    }
+
+    Some(fold_trace_by(
+        &context.trace,
+        |entry| context.frame_times[entry.id],
+        |entry| frame_name(context, entry),
+    ))
+}
+
+/// Fold a trace into stacks of self gas, naming each frame with `name`.
+///
+/// Identical call paths are merged and their gas summed up. Lines are sorted by call
+/// path and frames without any self gas are omitted.
+pub fn fold_trace(trace: &Trace, name: impl Fn(&TraceEntry) -> String) -> String {
+    fold_trace_by(trace, |entry| entry.gas_used, name)
+}
+
+/// Fold a trace into stacks weighted by `weight`, naming each frame with `name`.
+///
+/// The weight of a frame includes the weight of its children, which is subtracted to
+/// get the self weight of each stack.
+pub fn fold_trace_by(
+    trace: &Trace,
+    weight: impl Fn(&TraceEntry) -> u64,
+    name: impl Fn(&TraceEntry) -> String,
+) -> String {
+    let self_weight = self_weights(trace, weight);
+
+    let mut paths: Vec<String> = Vec::with_capacity(trace.len());
+    let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+
+    for entry in trace {
+        // Parents always precede their children in the trace
+        let frame = name(entry).replace(';', ":");
+        let path = match entry.parent_id.and_then(|id| paths.get(id)) {
+            Some(parent) => format!("{parent};{frame}"),
+            None => frame,
+        };
+
+        if self_weight[entry.id] > 0 {
+            *stacks.entry(path.clone()).or_default() += self_weight[entry.id];
+        }
+
+        paths.push(path);
+    }
+
+    stacks.into_iter().map(|(path, gas)| format!("{path} {gas}\n")).collect()
+}
+
+/// Gas spent by each frame itself, excluding the gas spent by its children, indexed
+/// by trace entry id.
+pub fn self_gas_used(trace: &Trace) -> Vec<u64> {
+    self_weights(trace, |entry| entry.gas_used)
+}
+
+/// Weight of each frame itself, excluding the weight of its children, indexed by
+/// trace entry id.
+fn self_weights(trace: &Trace, weight: impl Fn(&TraceEntry) -> u64) -> Vec<u64> {
+    let mut children = vec![0u64; trace.len()];
+    for entry in trace {
+        if let Some(sum) = entry.parent_id.and_then(|id| children.get_mut(id)) {
+            *sum = sum.saturating_add(weight(entry));
+        }
+    }
+
+    trace.iter().map(|entry| weight(entry).saturating_sub(children[entry.id])).collect()
+}
+
+/// Name a frame as `Contract::function`.
+fn frame_name<DB>(context: &EngineContext<DB>, entry: &TraceEntry) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    format!("{}::{}", contract_label(context, entry), function_label(context, entry))
+}
+
+/// Name of the contract executed by a frame, falling back to its label or address.
+pub(crate) fn contract_label<DB>(context: &EngineContext<DB>, entry: &TraceEntry) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    context
+        .artifacts
+        .get(&entry.code_address)
+        .map(|artifact| artifact.contract_name().to_string())
+        .or_else(|| entry.target_label.clone())
+        .unwrap_or_else(|| entry.target.to_string())
+}
+
+/// Name of the function called by a frame, falling back to its raw selector.
+pub(crate) fn function_label<DB>(context: &EngineContext<DB>, entry: &TraceEntry) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    if matches!(entry.call_type, CallType::Create(_)) {
+        "constructor".to_string()
+    } else if entry.input.len() < 4 {
+        if entry.value.is_zero() { "fallback" } else { "receive" }.to_string()
+    } else {
+        let selector = Selector::from_slice(&entry.input[..4]);
+        context
+            .artifacts
+            .get(&entry.code_address)
+            .and_then(|artifact| artifact.contract())
+            .and_then(|contract| contract.abi.as_ref())
+            .and_then(|abi| abi.function_by_selector(selector))
+            .map(|function| function.name.clone())
+            .unwrap_or_else(|| selector.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Address, Bytes, U256};
+    use revm::interpreter::CallScheme;
+
+    fn entry(id: usize, parent_id: Option<usize>, gas_used: u64) -> TraceEntry {
+        TraceEntry {
+            id,
+            parent_id,
+            depth: 0,
+            call_type: CallType::Call(CallScheme::Call),
+            caller: Address::ZERO,
+            target: address!("0x1111111111111111111111111111111111111111"),
+            code_address: address!("0x1111111111111111111111111111111111111111"),
+            input: Bytes::new(),
+            value: U256::ZERO,
+            gas_limit: 1_000_000,
+            gas_used,
+            result: None,
+            created_contract: false,
+            create_scheme: None,
+            bytecode: None,
+            target_label: Some(format!("C{id}")),
+            self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
+            events: vec![],
+            first_snapshot_id: None,
+        }
+    }
+
+    #[test]
+    fn test_fold_trace_self_gas() {
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, 100_000));
+        trace.push(entry(1, Some(0), 30_000));
+        trace.push(entry(2, Some(1), 30_000));
+        trace.push(entry(3, Some(0), 20_000));
+
+        let folded = fold_trace(&trace, |entry| entry.target_label.clone().unwrap());
+        // C2 spends all gas of C1, so C1 has no self gas and is omitted
+        assert_eq!(folded, "C0 50000\nC0;C1;C2 30000\nC0;C3 20000\n");
+    }
+
+    #[test]
+    fn test_fold_trace_merges_identical_paths() {
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, 10_000));
+        trace.push(entry(1, Some(0), 2_000));
+        trace.push(entry(2, Some(0), 3_000));
+
+        let folded = fold_trace(&trace, |entry| {
+            if entry.parent_id.is_some() { "A;B" } else { "Root" }.to_string()
+        });
+        assert_eq!(folded, "Root 5000\nRoot;A:B 5000\n");
+    }
+
+    #[test]
+    fn test_fold_trace_by_wall_clock() {
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, 100_000));
+        trace.push(entry(1, Some(0), 30_000));
+        trace.push(entry(2, Some(0), 20_000));
+
+        // Frame times are unrelated to the gas spent
+        let times = [9_000u64, 1_000, 5_000];
+        let folded = fold_trace_by(
+            &trace,
+            |entry| times[entry.id],
+            |entry| entry.target_label.clone().unwrap(),
+        );
+        assert_eq!(folded, "C0 3000\nC0;C1 1000\nC0;C2 5000\n");
+    }
+}
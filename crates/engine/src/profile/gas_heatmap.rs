@@ -0,0 +1,144 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Gas consumed per source line and per instruction of a debugged transaction.
+//!
+//! The gas of a step is the gas spent between its snapshot and the next snapshot of
+//! the same trace entry, so it includes the gas of the calls the step makes. The last
+//! step of a trace entry is charged with the rest of the gas used by the entry.
+//!
+//! Source-level gas is measured on the instrumented replay, so each step carries a
+//! small overhead of the instrumentation. The figures are meant to spot expensive
+//! lines rather than to reproduce exact gas costs.
+
+use std::collections::{BTreeMap, HashMap};
+
+use alloy_primitives::Address;
+use edb_common::types::GasHeatmap;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+
+use super::coverage::{line_of, line_starts};
+use crate::{EngineContext, SnapshotDetail};
+
+/// A snapshot as seen by the gas accounting: its trace entry, whether it is a hook
+/// snapshot, and the gas remaining when it was taken.
+type GasSample = (usize, bool, u64);
+
+/// Compute the gas heatmap of the code at `bytecode_address`.
+pub fn gas_heatmap<DB>(context: &EngineContext<DB>, bytecode_address: Address) -> GasHeatmap
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let samples: Vec<GasSample> = context
+        .snapshots
+        .iter()
+        .map(|(frame_id, snapshot)| {
+            (frame_id.trace_entry_id(), snapshot.is_hook(), snapshot.gas_remaining())
+        })
+        .collect();
+    let costs = step_gas(&samples, |id| context.trace.get(id).map(|entry| entry.gas_used));
+
+    // Map each step of the contract to its source line
+    let mut step_lines = BTreeMap::new();
+    if let Some(analysis) = context.analysis_results.get(&bytecode_address) {
+        for source in analysis.sources.values() {
+            let line_starts = line_starts(&source.source);
+            for step in &source.steps {
+                let line = line_of(&line_starts, step.src().start);
+                step_lines.insert(step.usid(), (source.path.clone(), line));
+            }
+        }
+    }
+
+    let mut heatmap = GasHeatmap { bytecode_address, ..Default::default() };
+    for ((_, snapshot), cost) in context.snapshots.iter().zip(costs) {
+        if snapshot.bytecode_address() != bytecode_address {
+            continue;
+        }
+
+        match snapshot.detail() {
+            SnapshotDetail::Opcode(detail) => {
+                *heatmap.instructions.entry(detail.pc).or_default() += cost;
+            }
+            SnapshotDetail::Hook(detail) => {
+                if let Some((path, line)) = step_lines.get(&detail.usid) {
+                    *heatmap.lines.entry(path.clone()).or_default().entry(*line).or_default() +=
+                        cost;
+                }
+            }
+        }
+    }
+
+    heatmap
+}
+
+/// Gas spent by each step, indexed like `samples`.
+///
+/// `gas_used` gives the total gas used by a trace entry, which is used to charge its
+/// last step. Consecutive snapshots of different kinds come from different replays,
+/// so no gas is attributed between them.
+fn step_gas(samples: &[GasSample], gas_used: impl Fn(usize) -> Option<u64>) -> Vec<u64> {
+    let mut costs = vec![0; samples.len()];
+    let mut first_step: HashMap<usize, usize> = HashMap::new();
+    let mut last_step: HashMap<usize, usize> = HashMap::new();
+
+    for (index, &(entry, is_hook, gas)) in samples.iter().enumerate() {
+        if let Some(prev) = last_step.insert(entry, index) {
+            let (_, prev_is_hook, prev_gas) = samples[prev];
+            if prev_is_hook == is_hook {
+                costs[prev] = prev_gas.saturating_sub(gas);
+            }
+        }
+        first_step.entry(entry).or_insert(index);
+    }
+
+    // Charge the last step of each entry with the gas not measured between its steps
+    for (entry, last) in last_step {
+        let Some(total) = gas_used(entry) else { continue };
+        let measured: u64 = (first_step[&entry]..last)
+            .filter(|&index| samples[index].0 == entry)
+            .map(|index| costs[index])
+            .sum();
+        costs[last] = total.saturating_sub(measured);
+    }
+
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_gas() {
+        // Entry 0 calls entry 1 between its first and second step
+        let samples = vec![(0, false, 1000), (1, false, 900), (1, false, 850), (0, false, 700)];
+        let gas_used = HashMap::from([(0, 320), (1, 60)]);
+        let costs = step_gas(&samples, |entry| gas_used.get(&entry).copied());
+
+        // The first step of entry 0 includes the call, the last steps get the remainder
+        assert_eq!(costs, vec![300, 50, 10, 20]);
+    }
+
+    #[test]
+    fn test_step_gas_mixed_replays() {
+        let samples = vec![(0, true, 5000), (0, false, 1000)];
+        let costs = step_gas(&samples, |_| None);
+        assert_eq!(costs, vec![0, 0]);
+    }
+}
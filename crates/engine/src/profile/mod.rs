@@ -0,0 +1,41 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Execution profiles derived from a prepared debugging session.
+//!
+//! Profiles summarize where a transaction spends its resources so that users can
+//! quickly spot the expensive parts of an execution without stepping through it.
+//!
+//! # Profiles
+//!
+//! - [`folded_stacks`] - Gas spent per call path in the folded-stack format used by
+//!   flamegraph tools such as inferno and speedscope, or the wall-clock time of the
+//!   replay with [`wall_clock_folded_stacks`]
+//! - [`coverage_report`] - Source lines executed by the transaction, exportable as LCOV
+//! - [`opcode_histograms`] - Executed program counters and basic blocks per contract
+//! - [`gas_heatmap`] - Gas spent per source line and per instruction of a contract
+
+mod coverage;
+pub use coverage::*;
+
+mod flamegraph;
+pub use flamegraph::*;
+
+mod gas_heatmap;
+pub use gas_heatmap::*;
+
+mod opcode_histogram;
+pub use opcode_histogram::*;
@@ -0,0 +1,224 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Opcode-level hot spots of a debugged transaction.
+//!
+//! Opcode snapshots record every instruction executed by contracts without verified
+//! source code. Their program counters are aggregated into a histogram per contract,
+//! and further grouped into basic blocks so that optimization work can focus on the
+//! hottest straight-line code of a production transaction.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::Address;
+use revm::{
+    bytecode::opcode::{
+        INVALID, JUMP, JUMPDEST, JUMPI, PUSH1, PUSH32, RETURN, REVERT, SELFDESTRUCT, STOP,
+    },
+    database::CacheDB,
+    Database, DatabaseCommit, DatabaseRef,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{EngineContext, SnapshotDetail};
+
+/// Execution counts of the instructions of a single contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpcodeHistogram {
+    /// Address of the executed code
+    pub bytecode_address: Address,
+    /// Total number of instructions executed
+    pub total_steps: u64,
+    /// Execution count per program counter, ordered by program counter
+    pub pcs: Vec<PcCount>,
+    /// Basic blocks that were executed, hottest first
+    pub blocks: Vec<BasicBlockCount>,
+}
+
+/// Execution count of a single instruction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PcCount {
+    /// Program counter of the instruction
+    pub pc: usize,
+    /// Opcode of the instruction
+    pub opcode: u8,
+    /// Number of times the instruction was executed
+    pub count: u64,
+}
+
+/// Execution statistics of a basic block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BasicBlockCount {
+    /// Program counter of the first instruction of the block
+    pub start_pc: usize,
+    /// Program counter of the last instruction of the block
+    pub end_pc: usize,
+    /// Number of times the block was entered
+    pub count: u64,
+    /// Number of instructions executed within the block
+    pub steps: u64,
+}
+
+/// Aggregate the opcode snapshots of a session into one histogram per contract.
+///
+/// Histograms are ordered by the number of executed instructions, busiest first.
+pub fn opcode_histograms<DB>(context: &EngineContext<DB>) -> Vec<OpcodeHistogram>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let mut counts: BTreeMap<Address, BTreeMap<usize, (u8, u64)>> = BTreeMap::new();
+    for (_, snapshot) in context.snapshots.iter() {
+        if let SnapshotDetail::Opcode(detail) = snapshot.detail() {
+            let entry = counts
+                .entry(detail.bytecode_address)
+                .or_default()
+                .entry(detail.pc)
+                .or_insert((detail.opcode, 0));
+            entry.1 += 1;
+        }
+    }
+
+    let mut histograms: Vec<_> = counts
+        .into_iter()
+        .map(|(bytecode_address, pcs)| {
+            let pcs: Vec<_> = pcs
+                .into_iter()
+                .map(|(pc, (opcode, count))| PcCount { pc, opcode, count })
+                .collect();
+
+            let code = context
+                .trace
+                .iter()
+                .find(|entry| entry.code_address == bytecode_address)
+                .and_then(|entry| entry.bytecode.as_ref());
+            let blocks = code.map(|code| block_counts(code, &pcs)).unwrap_or_default();
+
+            OpcodeHistogram {
+                bytecode_address,
+                total_steps: pcs.iter().map(|pc| pc.count).sum(),
+                pcs,
+                blocks,
+            }
+        })
+        .collect();
+
+    histograms.sort_by(|a, b| b.total_steps.cmp(&a.total_steps));
+    histograms
+}
+
+/// Split bytecode into basic blocks, returned as `(start_pc, end_pc)` pairs.
+///
+/// A block starts at the beginning of the code, at every `JUMPDEST`, and after every
+/// instruction that ends the straight-line control flow.
+pub fn basic_blocks(code: &[u8]) -> Vec<(usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut pc = 0;
+
+    while pc < code.len() {
+        let opcode = code[pc];
+        if opcode == JUMPDEST && pc != start {
+            blocks.push((start, previous_pc(code, start, pc)));
+            start = pc;
+        }
+
+        let next = pc + 1 + immediate_size(opcode);
+        if matches!(opcode, STOP | JUMP | JUMPI | RETURN | REVERT | INVALID | SELFDESTRUCT) {
+            blocks.push((start, pc));
+            start = next;
+        }
+        pc = next;
+    }
+
+    if start < code.len() {
+        blocks.push((start, previous_pc(code, start, code.len())));
+    }
+    blocks
+}
+
+/// Aggregate instruction counts into basic blocks, hottest (most steps) first.
+fn block_counts(code: &[u8], pcs: &[PcCount]) -> Vec<BasicBlockCount> {
+    let mut blocks: Vec<_> = basic_blocks(code)
+        .into_iter()
+        .filter_map(|(start_pc, end_pc)| {
+            let executed = pcs.iter().filter(|pc| start_pc <= pc.pc && pc.pc <= end_pc);
+            let (count, steps) = executed.fold((0, 0), |(count, steps), pc| {
+                (if pc.pc == start_pc { pc.count } else { count }, steps + pc.count)
+            });
+            (steps > 0).then_some(BasicBlockCount { start_pc, end_pc, count, steps })
+        })
+        .collect();
+
+    blocks.sort_by(|a, b| b.steps.cmp(&a.steps).then_with(|| a.start_pc.cmp(&b.start_pc)));
+    blocks
+}
+
+/// Number of immediate bytes following an opcode.
+fn immediate_size(opcode: u8) -> usize {
+    if (PUSH1..=PUSH32).contains(&opcode) {
+        (opcode - PUSH1 + 1) as usize
+    } else {
+        0
+    }
+}
+
+/// Program counter of the last instruction starting before `end` within a block.
+fn previous_pc(code: &[u8], start: usize, end: usize) -> usize {
+    let mut pc = start;
+    let mut last = start;
+    while pc < end {
+        last = pc;
+        pc += 1 + immediate_size(code[pc]);
+    }
+    last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revm::bytecode::opcode::{ADD, PUSH2};
+
+    // 0: PUSH1 0x04, 2: JUMP, 3: STOP, 4: JUMPDEST, 5: PUSH2 0x5b5b, 8: ADD, 9: STOP
+    const CODE: [u8; 10] = [PUSH1, 0x04, JUMP, STOP, JUMPDEST, PUSH2, 0x5b, 0x5b, ADD, STOP];
+
+    #[test]
+    fn test_basic_blocks() {
+        // JUMPDEST bytes in push data do not start a block
+        assert_eq!(basic_blocks(&CODE), vec![(0, 2), (3, 3), (4, 9)]);
+    }
+
+    #[test]
+    fn test_block_counts() {
+        let pcs = vec![
+            PcCount { pc: 0, opcode: PUSH1, count: 1 },
+            PcCount { pc: 2, opcode: JUMP, count: 1 },
+            PcCount { pc: 4, opcode: JUMPDEST, count: 3 },
+            PcCount { pc: 5, opcode: PUSH2, count: 3 },
+            PcCount { pc: 8, opcode: ADD, count: 3 },
+            PcCount { pc: 9, opcode: STOP, count: 3 },
+        ];
+
+        assert_eq!(
+            block_counts(&CODE, &pcs),
+            vec![
+                BasicBlockCount { start_pc: 4, end_pc: 9, count: 3, steps: 12 },
+                BasicBlockCount { start_pc: 0, end_pc: 2, count: 1, steps: 2 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Extraction of ether and token transfers from an execution trace.
+
+use alloy_primitives::{b256, Address, LogData, B256, U256};
+use edb_common::types::{CallResult, CallType, Trace, TraceEntry};
+use revm::interpreter::CallScheme;
+use serde::{Deserialize, Serialize};
+
+/// Topic of `Transfer(address,address,uint256)`, shared by ERC-20 and ERC-721
+pub const TRANSFER_EVENT_TOPIC: B256 =
+    b256!("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Asset moved by a transfer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Asset {
+    /// Native ether
+    Ether,
+    /// Fungible token
+    Erc20 {
+        /// Token contract
+        token: Address,
+    },
+    /// Non-fungible token
+    Erc721 {
+        /// Token contract
+        token: Address,
+        /// Transferred token
+        token_id: U256,
+    },
+}
+
+/// A transfer of an asset between two accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetFlow {
+    /// Trace entry in which the transfer happened
+    pub trace_entry_id: usize,
+    /// Transferred asset
+    pub asset: Asset,
+    /// Sender
+    pub from: Address,
+    /// Recipient
+    pub to: Address,
+    /// Transferred amount (always one for non-fungible tokens)
+    pub amount: U256,
+}
+
+/// Collect the asset transfers of a trace, in execution order.
+///
+/// Ether transfers are taken from call values and self-destructs, token transfers from
+/// `Transfer` events. Transfers made by frames that were reverted, directly or through
+/// one of their ancestors, did not take effect and are omitted.
+pub fn asset_flows(trace: &Trace) -> Vec<AssetFlow> {
+    let mut flows = Vec::new();
+
+    for entry in trace {
+        if !is_effective(trace, entry) {
+            continue;
+        }
+
+        // Delegate calls and call codes keep the value within the caller
+        let moves_value = match entry.call_type {
+            CallType::Call(CallScheme::DelegateCall | CallScheme::CallCode) => false,
+            CallType::Call(_) | CallType::Create(_) => true,
+        };
+        if moves_value && !entry.value.is_zero() {
+            flows.push(AssetFlow {
+                trace_entry_id: entry.id,
+                asset: Asset::Ether,
+                from: entry.caller,
+                to: entry.target,
+                amount: entry.value,
+            });
+        }
+
+        flows.extend(entry.events.iter().filter_map(|event| token_transfer(entry, event)));
+
+        if let Some((beneficiary, value)) = entry.self_destruct {
+            if !value.is_zero() {
+                flows.push(AssetFlow {
+                    trace_entry_id: entry.id,
+                    asset: Asset::Ether,
+                    from: entry.target,
+                    to: beneficiary,
+                    amount: value,
+                });
+            }
+        }
+    }
+
+    flows
+}
+
+/// Decode a `Transfer` event emitted by a frame.
+fn token_transfer(entry: &TraceEntry, event: &LogData) -> Option<AssetFlow> {
+    let topics = event.topics();
+    if topics.first() != Some(&TRANSFER_EVENT_TOPIC) {
+        return None;
+    }
+
+    let from = Address::from_word(*topics.get(1)?);
+    let to = Address::from_word(*topics.get(2)?);
+    let token = entry.target;
+
+    // ERC-721 indexes the token id, while ERC-20 puts the amount into the data
+    let (asset, amount) = match topics.get(3) {
+        Some(token_id) => {
+            (Asset::Erc721 { token, token_id: U256::from_be_bytes(token_id.0) }, U256::from(1))
+        }
+        None => (Asset::Erc20 { token }, U256::try_from_be_slice(event.data.get(..32)?)?),
+    };
+
+    Some(AssetFlow { trace_entry_id: entry.id, asset, from, to, amount })
+}
+
+/// Whether a frame and all of its ancestors completed successfully.
+fn is_effective(trace: &Trace, entry: &TraceEntry) -> bool {
+    let mut current = Some(entry);
+    while let Some(entry) = current {
+        if !matches!(entry.result, Some(CallResult::Success { .. })) {
+            return false;
+        }
+        current = entry.parent_id.and_then(|id| trace.get(id));
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Bytes};
+    use revm::interpreter::InstructionResult;
+
+    fn entry(id: usize, parent_id: Option<usize>, target: Address, success: bool) -> TraceEntry {
+        let output = Bytes::new();
+        let result = if success {
+            CallResult::Success { output, result: InstructionResult::Return }
+        } else {
+            CallResult::Revert { output, result: InstructionResult::Revert }
+        };
+        TraceEntry {
+            id,
+            parent_id,
+            depth: 0,
+            call_type: CallType::Call(CallScheme::Call),
+            caller: Address::ZERO,
+            target,
+            code_address: target,
+            input: Bytes::new(),
+            value: U256::ZERO,
+            gas_limit: 0,
+            gas_used: 0,
+            result: Some(result),
+            created_contract: false,
+            create_scheme: None,
+            bytecode: None,
+            target_label: None,
+            self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
+            events: vec![],
+            first_snapshot_id: None,
+        }
+    }
+
+    fn transfer_event(from: Address, to: Address, amount: U256) -> LogData {
+        LogData::new_unchecked(
+            vec![TRANSFER_EVENT_TOPIC, from.into_word(), to.into_word()],
+            Bytes::from(amount.to_be_bytes::<32>()),
+        )
+    }
+
+    #[test]
+    fn test_ether_and_erc20_flows() {
+        let user = address!("0x1111111111111111111111111111111111111111");
+        let token = address!("0x2222222222222222222222222222222222222222");
+        let pool = address!("0x3333333333333333333333333333333333333333");
+
+        let mut trace = Trace::new();
+        let mut root = entry(0, None, pool, true);
+        root.caller = user;
+        root.value = U256::from(100);
+        trace.push(root);
+        let mut transfer = entry(1, Some(0), token, true);
+        transfer.events.push(transfer_event(pool, user, U256::from(42)));
+        trace.push(transfer);
+
+        assert_eq!(
+            asset_flows(&trace),
+            vec![
+                AssetFlow {
+                    trace_entry_id: 0,
+                    asset: Asset::Ether,
+                    from: user,
+                    to: pool,
+                    amount: U256::from(100),
+                },
+                AssetFlow {
+                    trace_entry_id: 1,
+                    asset: Asset::Erc20 { token },
+                    from: pool,
+                    to: user,
+                    amount: U256::from(42),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reverted_flows_are_omitted() {
+        let user = address!("0x1111111111111111111111111111111111111111");
+        let token = address!("0x2222222222222222222222222222222222222222");
+
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, user, true));
+        trace.push(entry(1, Some(0), user, false));
+        let mut transfer = entry(2, Some(1), token, true);
+        transfer.events.push(transfer_event(user, token, U256::from(1)));
+        trace.push(transfer);
+
+        assert!(asset_flows(&trace).is_empty());
+    }
+}
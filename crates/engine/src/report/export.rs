@@ -0,0 +1,756 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export of the decoded call trace in formats understood by other tools.
+//!
+//! - [`TraceFormat::Json`] - EDB's own decoded trace (see [`CallReport`])
+//! - [`TraceFormat::Cast`] - Call tree as printed by Foundry's `cast run`
+//! - [`TraceFormat::Tenderly`] - Nested call trace as returned by Tenderly simulations
+//! - [`TraceFormat::Parity`] - Flat traces as returned by `trace_transaction`
+
+use std::{fmt::Write, str::FromStr};
+
+use alloy_dyn_abi::{EventExt, FunctionExt, JsonAbiExt};
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::{hex, Address, Bytes, LogData, TxHash, B256, U256};
+use edb_common::types::{
+    CallResult, CallType, SolValueFormatter, SolValueFormatterContext, Trace, TraceEntry,
+};
+use revm::{
+    context::CreateScheme,
+    database::CacheDB,
+    interpreter::{CallScheme, InstructionResult},
+    Database, DatabaseCommit, DatabaseRef,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    profile::{contract_label, function_label},
+    report::{called_function, code_abi, decoded_arguments, describe_failure},
+    EngineContext,
+};
+
+/// Format of an exported call trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceFormat {
+    /// EDB's decoded trace as JSON
+    Json,
+    /// Foundry `cast run` call tree as text
+    Cast,
+    /// Tenderly call trace as JSON
+    Tenderly,
+    /// Parity/OpenEthereum `trace_transaction` traces as JSON
+    Parity,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "cast" => Ok(Self::Cast),
+            "tenderly" => Ok(Self::Tenderly),
+            "parity" => Ok(Self::Parity),
+            _ => Err(format!(
+                "Unsupported trace format '{s}', expected 'json', 'cast', 'tenderly', or 'parity'"
+            )),
+        }
+    }
+}
+
+/// Name of the opcode behind a call or creation, e.g., `DELEGATECALL`.
+pub fn call_type_name(call_type: &CallType) -> &'static str {
+    match call_type {
+        CallType::Call(CallScheme::Call) => "CALL",
+        CallType::Call(CallScheme::CallCode) => "CALLCODE",
+        CallType::Call(CallScheme::DelegateCall) => "DELEGATECALL",
+        CallType::Call(CallScheme::StaticCall) => "STATICCALL",
+        CallType::Create(CreateScheme::Create2 { .. }) => "CREATE2",
+        CallType::Create(_) => "CREATE",
+    }
+}
+
+/// Render the call trace of a session like Foundry's `cast run`.
+///
+/// Events of a frame are listed after the calls it made, since the trace does not
+/// record how both are interleaved.
+pub fn cast_call_tree<DB>(context: &EngineContext<DB>) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    render_call_tree(context, false)
+}
+
+/// Render the call trace like [`cast_call_tree`], highlighted with ANSI colors for
+/// terminal output.
+///
+/// Successful frames are shown in green and failed frames in red, as done by `cast run`.
+pub fn colored_call_tree<DB>(context: &EngineContext<DB>) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    render_call_tree(context, true)
+}
+
+fn render_call_tree<DB>(context: &EngineContext<DB>, colored: bool) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let children = children_of(&context.trace);
+
+    let mut out = String::from("Traces:\n");
+    for root in context.trace.iter().filter(|entry| entry.parent_id.is_none()) {
+        let _ = writeln!(out, "  {}", cast_call_line(context, root, colored));
+        render_cast_frame(context, &children, root, "    ", colored, &mut out);
+    }
+    out
+}
+
+fn render_cast_frame<DB>(
+    context: &EngineContext<DB>,
+    children: &[Vec<usize>],
+    entry: &TraceEntry,
+    indent: &str,
+    colored: bool,
+    out: &mut String,
+) where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let calls = &children[entry.id];
+    let items = calls.len() + entry.events.len() + 1;
+
+    for index in 0..items {
+        let (branch, nested) =
+            if index + 1 == items { ("└─ ", "    ") } else { ("├─ ", "│   ") };
+
+        if let Some(child) = calls.get(index).and_then(|id| context.trace.get(*id)) {
+            let _ = writeln!(out, "{indent}{branch}{}", cast_call_line(context, child, colored));
+            render_cast_frame(context, children, child, &format!("{indent}{nested}"), colored, out);
+        } else if let Some(event) = entry.events.get(index - calls.len()) {
+            let event = format_event(code_abi(context, entry), event);
+            let _ =
+                writeln!(out, "{indent}{branch}{}", paint(colored, BLUE, format!("emit {event}")));
+        } else {
+            let _ = writeln!(out, "{indent}{branch}← {}", cast_return(context, entry, colored));
+        }
+    }
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BLUE: &str = "\x1b[34m";
+const CYAN: &str = "\x1b[36m";
+const GRAY: &str = "\x1b[90m";
+
+/// Wrap `text` in the given ANSI color if coloring is enabled.
+fn paint(colored: bool, color: &str, text: impl std::fmt::Display) -> String {
+    if colored {
+        format!("{color}{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// Color of a frame depending on whether it succeeded.
+fn status_color(entry: &TraceEntry) -> &'static str {
+    match entry.result {
+        Some(CallResult::Success { .. }) => GREEN,
+        _ => RED,
+    }
+}
+
+/// Header line of a frame, e.g., `[29714] Token::transfer(0x..., 100)`.
+fn cast_call_line<DB>(context: &EngineContext<DB>, entry: &TraceEntry, colored: bool) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let gas = paint(colored, GRAY, format!("[{}]", entry.gas_used));
+    let contract = paint(colored, status_color(entry), contract_label(context, entry));
+    if matches!(entry.call_type, CallType::Create(_)) {
+        return format!("{gas} → {} {contract}@{}", paint(colored, YELLOW, "new"), entry.target);
+    }
+
+    let value = if entry.value.is_zero() {
+        String::new()
+    } else {
+        paint(colored, YELLOW, format!("{{value: {}}}", entry.value))
+    };
+    let arguments = match called_function(context, entry) {
+        Some(_) => decoded_arguments(context, entry).join(", "),
+        None if entry.input.len() > 4 => format!("0x{}", hex::encode(&entry.input[4..])),
+        None => String::new(),
+    };
+    let scheme = match entry.call_type {
+        CallType::Call(CallScheme::DelegateCall) => Some("[delegatecall]"),
+        CallType::Call(CallScheme::StaticCall) => Some("[staticcall]"),
+        CallType::Call(CallScheme::CallCode) => Some("[callcode]"),
+        _ => None,
+    };
+    let scheme =
+        scheme.map(|scheme| format!(" {}", paint(colored, CYAN, scheme))).unwrap_or_default();
+
+    format!(
+        "{} {}::{}{}({}){}",
+        gas,
+        contract,
+        paint(colored, status_color(entry), function_label(context, entry)),
+        value,
+        arguments,
+        scheme
+    )
+}
+
+/// Return line of a frame, e.g., `[Return] true` or `[Revert] insufficient balance`.
+fn cast_return<DB>(context: &EngineContext<DB>, entry: &TraceEntry, colored: bool) -> String
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let Some(result) = &entry.result else { return "[Unknown]".to_string() };
+
+    let data = match result {
+        CallResult::Success { output, .. } if matches!(entry.call_type, CallType::Create(_)) => {
+            format!("{} bytes of code", output.len())
+        }
+        CallResult::Success { output, .. } => called_function(context, entry)
+            .and_then(|function| function.abi_decode_output(output).ok())
+            .map(|values| {
+                let ctx = SolValueFormatterContext::new();
+                values.iter().map(|value| value.format_value(&ctx)).collect::<Vec<_>>().join(", ")
+            })
+            .unwrap_or_else(|| format_bytes(output)),
+        CallResult::Revert { .. } => describe_failure(result, code_abi(context, entry)),
+        CallResult::Error { .. } => String::new(),
+    };
+
+    let result = paint(colored, status_color(entry), format!("[{:?}]", result.result()));
+    if data.is_empty() {
+        result
+    } else {
+        format!("{result} {data}")
+    }
+}
+
+/// Format an event, decoding it with the ABI of the emitting code if possible.
+fn format_event(abi: Option<&JsonAbi>, log: &LogData) -> String {
+    let decoded = log.topics().first().and_then(|topic| {
+        let event = abi?.events().find(|event| !event.anonymous && event.selector() == *topic)?;
+        let decoded = event.decode_log(log).ok()?;
+
+        let (mut indexed, mut body) = (decoded.indexed.iter(), decoded.body.iter());
+        let ctx = SolValueFormatterContext::new();
+        let params = event
+            .inputs
+            .iter()
+            .map(|input| {
+                let value = if input.indexed { indexed.next() } else { body.next() }?;
+                Some(format!("{}: {}", input.name, value.format_value(&ctx)))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(format!("{}({})", event.name, params.join(", ")))
+    });
+
+    decoded.unwrap_or_else(|| {
+        let topics: Vec<_> = log.topics().iter().map(|topic| topic.to_string()).collect();
+        format!("topics: [{}], data: {}", topics.join(", "), format_bytes(&log.data))
+    })
+}
+
+fn format_bytes(data: &Bytes) -> String {
+    if data.is_empty() {
+        String::new()
+    } else {
+        format!("0x{}", hex::encode(data))
+    }
+}
+
+/// A call frame in Tenderly's call trace format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenderlyCall {
+    /// Opcode of the call, e.g., `CALL` or `CREATE2`
+    pub call_type: String,
+    /// Address making the call
+    pub from: Address,
+    /// Storage context of the frame
+    pub to: Address,
+    /// Address of the executed code
+    pub address: Address,
+    /// Gas made available to the frame
+    pub gas: u64,
+    /// Gas spent by the frame, including its children
+    pub gas_used: u64,
+    /// Value transferred
+    pub value: U256,
+    /// Call data or init code
+    pub input: Bytes,
+    /// Return or revert data
+    pub output: Bytes,
+    /// Why the frame failed, if it did
+    pub error: Option<String>,
+    /// Decoded reason of a revert
+    pub error_reason: Option<String>,
+    /// Name of the executed contract, if known
+    pub contract_name: Option<String>,
+    /// Name of the called function, if known
+    pub function_name: Option<String>,
+    /// Decoded arguments of the called function
+    pub decoded_input: Vec<TenderlyDecodedValue>,
+    /// Calls made by this frame
+    pub calls: Vec<TenderlyCall>,
+}
+
+/// A decoded argument in Tenderly's call trace format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenderlyDecodedValue {
+    /// Declaration of the argument
+    pub soltype: TenderlySolType,
+    /// Formatted value
+    pub value: String,
+}
+
+/// Declaration of an argument in Tenderly's call trace format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenderlySolType {
+    /// Name of the argument
+    pub name: String,
+    /// Solidity type of the argument
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// Build the call trace of a session in Tenderly's nested format.
+///
+/// Returns one call per top-level frame.
+pub fn tenderly_call_trace<DB>(context: &EngineContext<DB>) -> Vec<TenderlyCall>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let children = children_of(&context.trace);
+    context
+        .trace
+        .iter()
+        .filter(|entry| entry.parent_id.is_none())
+        .map(|root| tenderly_call(context, &children, root))
+        .collect()
+}
+
+fn tenderly_call<DB>(
+    context: &EngineContext<DB>,
+    children: &[Vec<usize>],
+    entry: &TraceEntry,
+) -> TenderlyCall
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let function = called_function(context, entry);
+    let decoded_input = function
+        .and_then(|function| {
+            let values = function.abi_decode_input(&entry.input[4..]).ok()?;
+            let ctx = SolValueFormatterContext::new();
+            Some(
+                function
+                    .inputs
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(param, value)| TenderlyDecodedValue {
+                        soltype: TenderlySolType { name: param.name.clone(), ty: param.ty.clone() },
+                        value: value.format_value(&ctx),
+                    })
+                    .collect(),
+            )
+        })
+        .unwrap_or_default();
+
+    let (error, error_reason) = match &entry.result {
+        Some(result @ CallResult::Revert { .. }) => (
+            Some(execution_error(result.result())),
+            Some(describe_failure(result, code_abi(context, entry))),
+        ),
+        Some(result @ CallResult::Error { .. }) => (Some(execution_error(result.result())), None),
+        _ => (None, None),
+    };
+
+    TenderlyCall {
+        call_type: call_type_name(&entry.call_type).to_string(),
+        from: call_sender(entry),
+        to: entry.target,
+        address: entry.code_address,
+        gas: entry.gas_limit,
+        gas_used: entry.gas_used,
+        value: entry.value,
+        input: entry.input.clone(),
+        output: entry.result.as_ref().map(|result| result.output().clone()).unwrap_or_default(),
+        error,
+        error_reason,
+        contract_name: context
+            .artifacts
+            .get(&entry.code_address)
+            .map(|artifact| artifact.contract_name().to_string()),
+        function_name: function.map(|function| function.name.clone()),
+        decoded_input,
+        calls: children[entry.id]
+            .iter()
+            .filter_map(|id| context.trace.get(*id))
+            .map(|child| tenderly_call(context, children, child))
+            .collect(),
+    }
+}
+
+/// A single trace in the Parity/OpenEthereum `trace_transaction` format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParityTrace {
+    /// What the frame did
+    pub action: ParityAction,
+    /// Outcome of a successful frame
+    pub result: Option<ParityResult>,
+    /// Why the frame failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Number of direct children of the frame
+    pub subtraces: usize,
+    /// Position of the frame in the call tree
+    pub trace_address: Vec<usize>,
+    /// Kind of the trace: `call`, `create`, or `suicide`
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Hash of the block containing the transaction
+    pub block_hash: B256,
+    /// Number of the block containing the transaction
+    pub block_number: u64,
+    /// Hash of the transaction
+    pub transaction_hash: TxHash,
+}
+
+/// Action of a Parity trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParityAction {
+    /// A message call
+    #[serde(rename_all = "camelCase")]
+    Call {
+        /// Lowercase opcode name, e.g., `delegatecall`
+        call_type: String,
+        /// Address making the call
+        from: Address,
+        /// Called address
+        to: Address,
+        /// Gas made available to the call
+        gas: U256,
+        /// Call data
+        input: Bytes,
+        /// Value transferred
+        value: U256,
+    },
+    /// A contract creation
+    #[serde(rename_all = "camelCase")]
+    Create {
+        /// Lowercase opcode name, e.g., `create2`
+        creation_method: String,
+        /// Address creating the contract
+        from: Address,
+        /// Gas made available to the creation
+        gas: U256,
+        /// Init code
+        init: Bytes,
+        /// Value transferred
+        value: U256,
+    },
+    /// A self-destruct
+    #[serde(rename_all = "camelCase")]
+    Suicide {
+        /// Self-destructed contract
+        address: Address,
+        /// Receiver of the remaining balance
+        refund_address: Address,
+        /// Remaining balance
+        balance: U256,
+    },
+}
+
+/// Result of a successful Parity trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParityResult {
+    /// Result of a message call
+    #[serde(rename_all = "camelCase")]
+    Call {
+        /// Gas spent by the call
+        gas_used: U256,
+        /// Return data
+        output: Bytes,
+    },
+    /// Result of a contract creation
+    #[serde(rename_all = "camelCase")]
+    Create {
+        /// Address of the created contract
+        address: Address,
+        /// Deployed code
+        code: Bytes,
+        /// Gas spent by the creation
+        gas_used: U256,
+    },
+}
+
+/// Build the traces of a transaction in the Parity `trace_transaction` format.
+///
+/// Traces are listed depth-first, with self-destructs reported as the last child of
+/// the frame that executed them.
+pub fn parity_traces(
+    trace: &Trace,
+    tx_hash: TxHash,
+    block_number: u64,
+    block_hash: B256,
+) -> Vec<ParityTrace> {
+    let children = children_of(trace);
+
+    let mut traces = Vec::with_capacity(trace.len());
+    for root in trace.iter().filter(|entry| entry.parent_id.is_none()) {
+        push_parity_traces(trace, &children, root, vec![], &mut traces);
+    }
+
+    for parity in &mut traces {
+        parity.block_hash = block_hash;
+        parity.block_number = block_number;
+        parity.transaction_hash = tx_hash;
+    }
+    traces
+}
+
+fn push_parity_traces(
+    trace: &Trace,
+    children: &[Vec<usize>],
+    entry: &TraceEntry,
+    trace_address: Vec<usize>,
+    traces: &mut Vec<ParityTrace>,
+) {
+    let calls = &children[entry.id];
+    let subtraces = calls.len() + usize::from(entry.self_destruct.is_some());
+    traces.push(parity_trace(entry, trace_address.clone(), subtraces));
+
+    for (index, child) in calls.iter().enumerate() {
+        let Some(child) = trace.get(*child) else { continue };
+        let mut child_address = trace_address.clone();
+        child_address.push(index);
+        push_parity_traces(trace, children, child, child_address, traces);
+    }
+
+    if let Some((refund_address, balance)) = entry.self_destruct {
+        let mut suicide_address = trace_address;
+        suicide_address.push(calls.len());
+        traces.push(ParityTrace {
+            action: ParityAction::Suicide { address: entry.target, refund_address, balance },
+            result: None,
+            error: None,
+            subtraces: 0,
+            trace_address: suicide_address,
+            ty: "suicide".to_string(),
+            block_hash: B256::ZERO,
+            block_number: 0,
+            transaction_hash: TxHash::ZERO,
+        });
+    }
+}
+
+fn parity_trace(entry: &TraceEntry, trace_address: Vec<usize>, subtraces: usize) -> ParityTrace {
+    let method = call_type_name(&entry.call_type).to_lowercase();
+    let gas = U256::from(entry.gas_limit);
+    let gas_used = U256::from(entry.gas_used);
+
+    let (action, ty) = match entry.call_type {
+        CallType::Create(_) => (
+            ParityAction::Create {
+                creation_method: method,
+                from: entry.caller,
+                gas,
+                init: entry.input.clone(),
+                value: entry.value,
+            },
+            "create",
+        ),
+        CallType::Call(_) => (
+            ParityAction::Call {
+                call_type: method,
+                from: call_sender(entry),
+                to: entry.code_address,
+                gas,
+                input: entry.input.clone(),
+                value: entry.value,
+            },
+            "call",
+        ),
+    };
+
+    let (result, error) = match &entry.result {
+        Some(CallResult::Success { output, .. }) => {
+            let result = match entry.call_type {
+                CallType::Create(_) => {
+                    ParityResult::Create { address: entry.target, code: output.clone(), gas_used }
+                }
+                CallType::Call(_) => ParityResult::Call { gas_used, output: output.clone() },
+            };
+            (Some(result), None)
+        }
+        Some(result) => (None, Some(execution_error(result.result()))),
+        None => (None, None),
+    };
+
+    ParityTrace {
+        action,
+        result,
+        error,
+        subtraces,
+        trace_address,
+        ty: ty.to_string(),
+        block_hash: B256::ZERO,
+        block_number: 0,
+        transaction_hash: TxHash::ZERO,
+    }
+}
+
+/// Account sending the message of a frame.
+///
+/// The caller of a delegate call is the original sender, so the delegating contract
+/// (sharing its storage context with the frame) is reported instead.
+fn call_sender(entry: &TraceEntry) -> Address {
+    match entry.call_type {
+        CallType::Call(CallScheme::DelegateCall) => entry.target,
+        _ => entry.caller,
+    }
+}
+
+/// Error message of a failed frame, following the wording of Parity traces.
+fn execution_error(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::Revert => "Reverted".to_string(),
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "Out of gas".to_string(),
+        InstructionResult::InvalidJump => "Bad jump destination".to_string(),
+        InstructionResult::OpcodeNotFound => "Bad instruction".to_string(),
+        InstructionResult::StackUnderflow => "Stack underflow".to_string(),
+        InstructionResult::StackOverflow => "Out of stack".to_string(),
+        InstructionResult::StateChangeDuringStaticCall => {
+            "Mutable call in static context".to_string()
+        }
+        _ => format!("{result:?}"),
+    }
+}
+
+/// Direct children of every trace entry, indexed by trace entry id.
+fn children_of(trace: &Trace) -> Vec<Vec<usize>> {
+    let mut children = vec![Vec::new(); trace.len()];
+    for entry in trace {
+        if let Some(siblings) = entry.parent_id.and_then(|id| children.get_mut(id)) {
+            siblings.push(entry.id);
+        }
+    }
+    children
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn entry(id: usize, parent_id: Option<usize>, call_type: CallType) -> TraceEntry {
+        TraceEntry {
+            id,
+            parent_id,
+            depth: 0,
+            call_type,
+            caller: address!("0x1111111111111111111111111111111111111111"),
+            target: address!("0x2222222222222222222222222222222222222222"),
+            code_address: address!("0x3333333333333333333333333333333333333333"),
+            input: Bytes::new(),
+            value: U256::ZERO,
+            gas_limit: 100_000,
+            gas_used: 21_000,
+            result: Some(CallResult::Success {
+                output: Bytes::new(),
+                result: InstructionResult::Stop,
+            }),
+            created_contract: false,
+            create_scheme: None,
+            bytecode: None,
+            target_label: None,
+            self_destruct: None,
+            destroyed: false,
+            readonly: false,
+            subtree_value: U256::ZERO,
+            events: vec![],
+            first_snapshot_id: None,
+        }
+    }
+
+    #[test]
+    fn test_parity_trace_addresses() {
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, CallType::Call(CallScheme::Call)));
+        trace.push(entry(1, Some(0), CallType::Call(CallScheme::DelegateCall)));
+        let mut destructed = entry(2, Some(1), CallType::Create(CreateScheme::Create));
+        destructed.self_destruct = Some((Address::ZERO, U256::from(7)));
+        trace.push(destructed);
+        let mut reverted = entry(3, Some(0), CallType::Call(CallScheme::StaticCall));
+        reverted.result =
+            Some(CallResult::Revert { output: Bytes::new(), result: InstructionResult::Revert });
+        trace.push(reverted);
+
+        let traces = parity_traces(&trace, TxHash::ZERO, 1, B256::ZERO);
+        let addresses: Vec<_> = traces.iter().map(|t| t.trace_address.clone()).collect();
+        assert_eq!(addresses, vec![vec![], vec![0], vec![0, 0], vec![0, 0, 0], vec![1]]);
+        assert_eq!(traces[0].subtraces, 2);
+        assert_eq!(traces[2].subtraces, 1);
+        assert_eq!(traces[2].ty, "create");
+        assert_eq!(traces[3].ty, "suicide");
+        assert_eq!(traces[4].error.as_deref(), Some("Reverted"));
+        assert!(traces[4].result.is_none());
+
+        // Delegate calls are sent by the delegating contract to the implementation
+        let ParityAction::Call { call_type, from, to, .. } = &traces[1].action else {
+            panic!("expected a call action");
+        };
+        assert_eq!(call_type, "delegatecall");
+        assert_eq!(*from, trace[1].target);
+        assert_eq!(*to, trace[1].code_address);
+    }
+
+    #[test]
+    fn test_parse_trace_format() {
+        assert_eq!("tenderly".parse::<TraceFormat>(), Ok(TraceFormat::Tenderly));
+        assert!("geth".parse::<TraceFormat>().is_err());
+    }
+}
@@ -0,0 +1,366 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Structured reports summarizing a debugged transaction.
+//!
+//! A [`SessionReport`] condenses a prepared debugging session into a single document
+//! that can be consumed without any UI, e.g., by monitoring or CI pipelines. It covers
+//! the outcome of the transaction, its decoded call trace, the assets it moved, the
+//! storage it changed, and where it spent its gas.
+//!
+//! The decoded call trace can also be exported in formats understood by other tools,
+//! such as Foundry's `cast run`, Tenderly, and Parity's `trace_transaction` (see
+//! [`TraceFormat`]).
+//!
+//! A [`StackTrace`] lists the Solidity frames leading to any snapshot, formatted like
+//! the exception trace of a conventional language.
+
+mod asset_flow;
+pub use asset_flow::*;
+
+mod export;
+pub use export::*;
+
+mod revert;
+pub use revert::*;
+
+mod stack_trace;
+pub use stack_trace::*;
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::{Function, JsonAbi};
+use alloy_primitives::{Address, Selector, TxHash, U256};
+use edb_common::types::{
+    CallResult, CallType, Finding, SolValueFormatter, SolValueFormatterContext, TraceEntry,
+};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    findings::changed_storage_slots,
+    profile::{contract_label, folded_stacks, function_label, self_gas_used},
+    EngineContext,
+};
+
+/// Outcome of a transaction or of a single call frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionStatus {
+    /// Execution completed successfully
+    Success,
+    /// Execution was reverted by the code
+    Revert,
+    /// Execution was aborted by the EVM (e.g., out of gas or invalid opcode)
+    Halt,
+}
+
+impl ExecutionStatus {
+    /// Get the status of a call frame, treating frames without result as halted
+    pub fn of(entry: &TraceEntry) -> Self {
+        match entry.result {
+            Some(CallResult::Success { .. }) => Self::Success,
+            Some(CallResult::Revert { .. }) => Self::Revert,
+            Some(CallResult::Error { .. }) | None => Self::Halt,
+        }
+    }
+}
+
+/// Structured summary of a debugged transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionReport {
+    /// Hash of the transaction
+    pub tx_hash: TxHash,
+    /// Block the transaction was replayed in
+    pub block_number: u64,
+    /// Outcome of the transaction
+    pub status: ExecutionStatus,
+    /// Decoded reason of a failed transaction
+    pub revert_reason: Option<String>,
+    /// Decoded call trace, in execution order
+    pub trace: Vec<CallReport>,
+    /// Ether and token transfers that took effect
+    pub asset_flows: Vec<AssetFlow>,
+    /// Storage changed by the transaction, per contract
+    pub storage_diffs: Vec<StorageDiff>,
+    /// Gas spent by the transaction
+    pub gas_profile: GasProfile,
+    /// Findings of the engine's detectors
+    pub findings: Vec<Finding>,
+}
+
+/// A decoded call frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallReport {
+    /// Trace entry of the frame
+    pub id: usize,
+    /// Trace entry of the parent frame
+    pub parent_id: Option<usize>,
+    /// Depth in the call stack (0 = top level)
+    pub depth: usize,
+    /// Type of the call or creation
+    pub call_type: CallType,
+    /// Address making the call
+    pub caller: Address,
+    /// Storage context of the frame
+    pub target: Address,
+    /// Address of the executed code
+    pub code_address: Address,
+    /// Name of the executed contract, or its label or address
+    pub contract: String,
+    /// Name of the called function, or its raw selector
+    pub function: String,
+    /// Decoded arguments, empty if they could not be decoded
+    pub arguments: Vec<String>,
+    /// Value transferred
+    pub value: U256,
+    /// Gas spent by the frame, including its children
+    pub gas_used: u64,
+    /// Outcome of the frame
+    pub status: ExecutionStatus,
+    /// Decoded reason of a failed frame
+    pub revert_reason: Option<String>,
+}
+
+/// Storage slots of a contract changed by the transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageDiff {
+    /// Contract whose storage changed
+    pub address: Address,
+    /// Changed slots, ordered by slot
+    pub slots: Vec<SlotDiff>,
+}
+
+/// A single changed storage slot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotDiff {
+    /// Storage slot
+    pub slot: U256,
+    /// Value before the transaction
+    pub before: U256,
+    /// Value after the transaction
+    pub after: U256,
+}
+
+/// Gas spent by a transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasProfile {
+    /// Gas spent by the top-level frame
+    pub gas_used: u64,
+    /// Gas spent per function, most expensive first
+    pub functions: Vec<FunctionGas>,
+    /// Gas spent per call path in folded-stack format
+    pub folded_stacks: String,
+}
+
+/// Gas spent by a single function over all of its calls
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionGas {
+    /// Frame name, as `Contract::function`
+    pub name: String,
+    /// Number of calls
+    pub calls: usize,
+    /// Gas spent by the function itself, excluding its children
+    pub self_gas: u64,
+}
+
+/// Build the report of a prepared debugging session.
+pub fn session_report<DB>(context: &EngineContext<DB>) -> SessionReport
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let trace = decoded_trace(context);
+
+    let (status, revert_reason) = trace
+        .iter()
+        .find(|call| call.parent_id.is_none())
+        .map(|root| (root.status, root.revert_reason.clone()))
+        .unwrap_or((ExecutionStatus::Halt, None));
+
+    // A failed transaction does not change any state beyond the sender's nonce and balance
+    let storage_diffs =
+        if status == ExecutionStatus::Success { storage_diffs(context) } else { vec![] };
+
+    SessionReport {
+        tx_hash: context.tx_hash,
+        block_number: context.fork_info.block_number,
+        status,
+        revert_reason,
+        asset_flows: asset_flows(&context.trace),
+        gas_profile: gas_profile(context, &trace),
+        trace,
+        storage_diffs,
+        findings: context.findings.clone(),
+    }
+}
+
+/// Decode every frame of the trace of a session, in execution order.
+pub fn decoded_trace<DB>(context: &EngineContext<DB>) -> Vec<CallReport>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    context.trace.iter().map(|entry| call_report(context, entry)).collect()
+}
+
+/// ABI of the code executed by a frame, if verified source code is available.
+pub(crate) fn code_abi<'a, DB>(
+    context: &'a EngineContext<DB>,
+    entry: &TraceEntry,
+) -> Option<&'a JsonAbi>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    context
+        .artifacts
+        .get(&entry.code_address)
+        .and_then(|artifact| artifact.contract())
+        .and_then(|contract| contract.abi.as_ref())
+}
+
+/// ABI of the function called by a frame, if it can be resolved.
+pub(crate) fn called_function<'a, DB>(
+    context: &'a EngineContext<DB>,
+    entry: &TraceEntry,
+) -> Option<&'a Function>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    if !matches!(entry.call_type, CallType::Call(_)) || entry.input.len() < 4 {
+        return None;
+    }
+    code_abi(context, entry)?.function_by_selector(Selector::from_slice(&entry.input[..4]))
+}
+
+/// Decode and format the arguments of the function called by a frame.
+pub(crate) fn decoded_arguments<DB>(context: &EngineContext<DB>, entry: &TraceEntry) -> Vec<String>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    called_function(context, entry)
+        .and_then(|function| function.abi_decode_input(&entry.input[4..]).ok())
+        .map(|values| {
+            let ctx = SolValueFormatterContext::new();
+            values.iter().map(|value| value.format_value(&ctx)).collect()
+        })
+        .unwrap_or_default()
+}
+
+fn call_report<DB>(context: &EngineContext<DB>, entry: &TraceEntry) -> CallReport
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let abi = code_abi(context, entry);
+    let arguments = decoded_arguments(context, entry);
+
+    let status = ExecutionStatus::of(entry);
+    let revert_reason = match (&entry.result, status) {
+        (Some(result), ExecutionStatus::Revert | ExecutionStatus::Halt) => {
+            Some(describe_failure(result, abi))
+        }
+        _ => None,
+    };
+
+    CallReport {
+        id: entry.id,
+        parent_id: entry.parent_id,
+        depth: entry.depth,
+        call_type: entry.call_type.clone(),
+        caller: entry.caller,
+        target: entry.target,
+        code_address: entry.code_address,
+        contract: contract_label(context, entry),
+        function: function_label(context, entry),
+        arguments,
+        value: entry.value,
+        gas_used: entry.gas_used,
+        status,
+        revert_reason,
+    }
+}
+
+/// Compare the storage of every contract in the trace between the first and the last
+/// snapshot of the session.
+///
+/// Writes happening after the last snapshot (e.g., in the final steps of a contract
+/// without source code) are not captured.
+fn storage_diffs<DB>(context: &EngineContext<DB>) -> Vec<StorageDiff>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let (Some((_, first)), Some((_, last))) = (context.snapshots.first(), context.snapshots.last())
+    else {
+        return vec![];
+    };
+    let (before, after) = (first.db(), last.db());
+
+    let addresses: BTreeSet<Address> = context.trace.iter().map(|entry| entry.target).collect();
+    addresses
+        .into_iter()
+        .filter_map(|address| {
+            let slots: Vec<_> = changed_storage_slots(&before, &after, address)
+                .into_iter()
+                .map(|slot| SlotDiff {
+                    slot,
+                    before: before.storage_ref(address, slot).unwrap_or_default(),
+                    after: after.storage_ref(address, slot).unwrap_or_default(),
+                })
+                .collect();
+            (!slots.is_empty()).then_some(StorageDiff { address, slots })
+        })
+        .collect()
+}
+
+fn gas_profile<DB>(context: &EngineContext<DB>, trace: &[CallReport]) -> GasProfile
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let self_gas = self_gas_used(&context.trace);
+
+    let mut functions: BTreeMap<String, FunctionGas> = BTreeMap::new();
+    for call in trace {
+        let name = format!("{}::{}", call.contract, call.function);
+        let function =
+            functions.entry(name.clone()).or_insert(FunctionGas { name, calls: 0, self_gas: 0 });
+        function.calls += 1;
+        function.self_gas += self_gas[call.id];
+    }
+
+    let mut functions: Vec<_> = functions.into_values().collect();
+    functions.sort_by(|a, b| b.self_gas.cmp(&a.self_gas));
+
+    GasProfile {
+        gas_used: trace.iter().find(|call| call.parent_id.is_none()).map_or(0, |c| c.gas_used),
+        functions,
+        folded_stacks: folded_stacks(context),
+    }
+}
@@ -0,0 +1,105 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Decoding of revert data into human-readable reasons.
+
+use alloy_dyn_abi::JsonAbiExt;
+use alloy_json_abi::JsonAbi;
+use alloy_primitives::{hex, Selector};
+use edb_common::types::{CallResult, SolValueFormatter, SolValueFormatterContext};
+
+/// Decode the revert data of a call.
+///
+/// Custom errors declared in `abi` are tried first, followed by the built-in
+/// `Error(string)` and `Panic(uint256)` errors and plain UTF-8 messages. Returns `None`
+/// if the data matches none of them.
+pub fn decode_revert_reason(output: &[u8], abi: Option<&JsonAbi>) -> Option<String> {
+    if output.len() >= 4 {
+        let selector = Selector::from_slice(&output[..4]);
+        let custom = abi
+            .into_iter()
+            .flat_map(|abi| abi.errors())
+            .filter(|error| error.selector() == selector)
+            .find_map(|error| {
+                let values = error.abi_decode_input(&output[4..]).ok()?;
+                let ctx = SolValueFormatterContext::new();
+                let args: Vec<_> = values.iter().map(|value| value.format_value(&ctx)).collect();
+                Some(format!("{}({})", error.name, args.join(", ")))
+            });
+        if custom.is_some() {
+            return custom;
+        }
+    }
+
+    alloy_sol_types::decode_revert_reason(output)
+}
+
+/// Describe why a call failed, falling back to the raw revert data.
+pub fn describe_failure(result: &CallResult, abi: Option<&JsonAbi>) -> String {
+    match result {
+        CallResult::Error { result, .. } => format!("halted: {result:?}"),
+        _ => {
+            let output = result.output();
+            if output.is_empty() {
+                "empty revert".to_string()
+            } else {
+                decode_revert_reason(output, abi)
+                    .unwrap_or_else(|| format!("0x{}", hex::encode(output)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U256};
+    use alloy_sol_types::{Panic, Revert, SolError};
+    use revm::interpreter::InstructionResult;
+
+    #[test]
+    fn test_decode_builtin_errors() {
+        let revert = Revert { reason: "insufficient balance".into() }.abi_encode();
+        assert_eq!(decode_revert_reason(&revert, None).as_deref(), Some("insufficient balance"));
+
+        let panic = Panic { code: U256::from(0x11) }.abi_encode();
+        assert!(
+            decode_revert_reason(&panic, None).is_some_and(|reason| reason.contains("overflow"))
+        );
+
+        assert!(decode_revert_reason(&[0xde, 0xad, 0xbe, 0xef], None).is_none());
+    }
+
+    #[test]
+    fn test_decode_custom_error() {
+        let abi =
+            JsonAbi::parse(["error InsufficientBalance(uint256 available, uint256 required)"])
+                .expect("valid abi");
+        let error = abi.errors().next().unwrap();
+        let mut output = error.selector().to_vec();
+        output.extend(U256::from(1).to_be_bytes::<32>());
+        output.extend(U256::from(2).to_be_bytes::<32>());
+
+        assert_eq!(
+            decode_revert_reason(&output, Some(&abi)).as_deref(),
+            Some("InsufficientBalance(1, 2)")
+        );
+
+        let result =
+            CallResult::Revert { output: Bytes::from(output), result: InstructionResult::Revert };
+        assert_eq!(describe_failure(&result, Some(&abi)), "InsufficientBalance(1, 2)");
+    }
+}
@@ -0,0 +1,265 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Solidity stack traces of snapshots.
+//!
+//! A [`StackTrace`] lists the frames that lead to a snapshot, innermost first, the way
+//! an exception trace of a conventional language does. Every call frame contributes
+//! its location, the snapshot itself for the innermost frame and the call site for the
+//! others, along with the internal function calls pending at that location. Rendered
+//! with [`std::fmt::Display`], the trace can be pasted into an issue report as is.
+
+use std::{fmt, path::PathBuf};
+
+use alloy_primitives::Address;
+use edb_common::types::TraceEntry;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    profile::{contract_label, function_label, line_of, line_starts},
+    EngineContext, ExecutionStatus, Snapshot, SnapshotDetail,
+};
+
+use super::{code_abi, describe_failure};
+
+/// Frames leading to a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackTrace {
+    /// Snapshot the trace leads to
+    pub snapshot_id: usize,
+    /// Decoded reason if the call frame of the snapshot fails
+    pub error: Option<String>,
+    /// Frames of the trace, innermost first
+    pub frames: Vec<StackFrame>,
+}
+
+/// A frame of a [`StackTrace`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StackFrame {
+    /// Trace entry of the call frame
+    pub trace_entry_id: usize,
+    /// Snapshot the location is taken from, if the call frame has one
+    pub snapshot_id: Option<usize>,
+    /// Address of the executed code
+    pub address: Address,
+    /// Name of the contract or library the function belongs to
+    pub contract: String,
+    /// Name of the function
+    pub function: String,
+    /// Source file of the location, if the code has verified source
+    pub path: Option<PathBuf>,
+    /// Line of the location (1-based)
+    pub line: Option<usize>,
+    /// Column of the location (1-based)
+    pub column: Option<usize>,
+    /// Program counter of the location, if the code has no verified source
+    pub pc: Option<usize>,
+}
+
+impl fmt::Display for StackFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{} (", self.contract, self.function)?;
+        match (&self.path, self.line, self.column, self.pc) {
+            (Some(path), Some(line), Some(column), _) => {
+                write!(f, "{}:{line}:{column}", path.display())?
+            }
+            (_, _, _, Some(pc)) => write!(f, "{}, pc {pc}", self.address)?,
+            _ => write!(f, "{}", self.address)?,
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for StackTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => writeln!(f, "Error: {error} (snapshot {})", self.snapshot_id)?,
+            None => writeln!(f, "Stack trace at snapshot {}", self.snapshot_id)?,
+        }
+        for frame in &self.frames {
+            writeln!(f, "    at {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Build the stack trace of a snapshot, or `None` if the snapshot does not exist.
+pub fn stack_trace<DB>(context: &EngineContext<DB>, snapshot_id: usize) -> Option<StackTrace>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let (frame_id, _) = context.snapshots.get(snapshot_id)?;
+    let innermost = context.trace.get(frame_id.trace_entry_id())?;
+
+    let error = match (&innermost.result, ExecutionStatus::of(innermost)) {
+        (Some(result), ExecutionStatus::Revert | ExecutionStatus::Halt) => {
+            Some(describe_failure(result, code_abi(context, innermost)))
+        }
+        _ => None,
+    };
+
+    let mut frames = Vec::new();
+    let mut next_entry = Some(innermost);
+    while let Some(entry) = next_entry {
+        // The latest snapshot of the frame up to the traced one: the snapshot itself for
+        // the innermost frame, the call site of the child frame otherwise
+        let location = context.snapshots[..=snapshot_id]
+            .iter()
+            .rev()
+            .find(|(frame_id, _)| frame_id.trace_entry_id() == entry.id)
+            .map(|(_, snapshot)| snapshot);
+
+        match location {
+            Some(location) => {
+                frames.push(snapshot_frame(context, entry, location));
+
+                // Internal calls pending at the location are the earlier snapshots of the
+                // frame whose next step comes after it, innermost first
+                let callers = context.snapshots[..location.id()].iter().rev().filter(
+                    |(frame_id, snapshot)| {
+                        frame_id.trace_entry_id() == entry.id
+                            && snapshot.is_hook()
+                            && snapshot.next_id().is_some_and(|next_id| next_id > location.id())
+                    },
+                );
+                for (_, caller) in callers {
+                    frames.push(snapshot_frame(context, entry, caller));
+                }
+            }
+            None => frames.push(StackFrame {
+                trace_entry_id: entry.id,
+                snapshot_id: None,
+                address: entry.code_address,
+                contract: contract_label(context, entry),
+                function: function_label(context, entry),
+                path: None,
+                line: None,
+                column: None,
+                pc: None,
+            }),
+        }
+
+        next_entry = entry.parent_id.and_then(|id| context.trace.get(id));
+    }
+
+    Some(StackTrace { snapshot_id, error, frames })
+}
+
+/// Locate a snapshot of a call frame in source code, or by program counter.
+fn snapshot_frame<DB>(
+    context: &EngineContext<DB>,
+    entry: &TraceEntry,
+    snapshot: &Snapshot<DB>,
+) -> StackFrame
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let mut frame = StackFrame {
+        trace_entry_id: entry.id,
+        snapshot_id: Some(snapshot.id()),
+        address: entry.code_address,
+        contract: contract_label(context, entry),
+        function: function_label(context, entry),
+        path: None,
+        line: None,
+        column: None,
+        pc: None,
+    };
+
+    match snapshot.detail() {
+        SnapshotDetail::Opcode(detail) => frame.pc = Some(detail.pc),
+        SnapshotDetail::Hook(detail) => {
+            let Some(analysis) = context.analysis_results.get(&entry.code_address) else {
+                return frame;
+            };
+            let Some(step) = analysis.usid_to_step.get(&detail.usid) else { return frame };
+
+            // Internal functions may belong to a library or be free functions, and the
+            // constructor, fallback, and receive functions have no name
+            if let Some(function) = analysis.ufid_to_function.get(&step.ufid()) {
+                if let Some(contract) = function.contract() {
+                    frame.contract = contract.name();
+                }
+                let name = function.name();
+                if !name.is_empty() {
+                    frame.function = name;
+                }
+            }
+
+            let src = step.src();
+            if let Some(source) = analysis.sources.get(&src.file) {
+                let starts = line_starts(&source.source);
+                let line = line_of(&starts, src.start);
+                frame.path = Some(source.path.clone());
+                frame.line = Some(line);
+                frame.column = Some(src.start - starts[line - 1] + 1);
+            }
+        }
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    fn frame(contract: &str, function: &str) -> StackFrame {
+        StackFrame {
+            trace_entry_id: 0,
+            snapshot_id: None,
+            address: address!("0x1111111111111111111111111111111111111111"),
+            contract: contract.to_string(),
+            function: function.to_string(),
+            path: None,
+            line: None,
+            column: None,
+            pc: None,
+        }
+    }
+
+    #[test]
+    fn test_stack_trace_display() {
+        let trace = StackTrace {
+            snapshot_id: 42,
+            error: Some("insufficient balance".to_string()),
+            frames: vec![
+                StackFrame {
+                    path: Some(PathBuf::from("src/Token.sol")),
+                    line: Some(88),
+                    column: Some(9),
+                    ..frame("Token", "_transfer")
+                },
+                StackFrame { pc: Some(1234), ..frame("Router", "swap") },
+                frame("Proxy", "fallback"),
+            ],
+        };
+
+        assert_eq!(
+            trace.to_string(),
+            "Error: insufficient balance (snapshot 42)\n\
+             \x20   at Token._transfer (src/Token.sol:88:9)\n\
+             \x20   at Router.swap (0x1111111111111111111111111111111111111111, pc 1234)\n\
+             \x20   at Proxy.fallback (0x1111111111111111111111111111111111111111)\n"
+        );
+    }
+}
@@ -20,12 +20,16 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use alloy_primitives::{Address, Bytes};
-use edb_common::types::{Code, OpcodeInfo, SourceInfo};
+use edb_common::types::{Code, OpcodeInfo, SourceInfo, SourceMapEntry, SourceMapInfo};
 use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
 use serde_json::Value;
 use tracing::debug;
 
-use crate::{error_codes, utils::disasm::disassemble, EngineContext, SnapshotDetail};
+use crate::{
+    error_codes,
+    utils::disasm::{self, disassemble, DisassemblyInstruction},
+    EngineContext, SnapshotDetail,
+};
 
 use super::super::types::RpcError;
 
@@ -188,6 +192,100 @@ where
     Ok(json_value)
 }
 
+/// Get the instruction-level source map of a verified contract
+///
+/// This method decodes the compiler source map of the contract's original
+/// (non-instrumented) deployed bytecode, so that clients can link each opcode
+/// to the source range it was compiled from.
+///
+/// # Parameters
+/// - `address`: The bytecode address of the contract
+///
+/// # Returns
+/// - The source map with one entry per instruction, or null if the contract is
+///   not verified or its artifact carries no source map
+pub fn get_source_map<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<serde_json::Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    // Parse the address as the first argument
+    let address: Address = params
+        .as_ref()
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [address]".to_string(),
+            data: None,
+        })?;
+
+    let Some(artifact) = context.artifacts.get(&address) else {
+        debug!("No artifact for address {}, no source map available", address);
+        return Ok(Value::Null);
+    };
+
+    let Some(bytecode) = artifact
+        .contract()
+        .and_then(|c| c.evm.as_ref())
+        .and_then(|e| e.deployed_bytecode.as_ref())
+        .and_then(|d| d.bytecode.as_ref())
+    else {
+        return Ok(Value::Null);
+    };
+
+    let source_map = match bytecode.source_map() {
+        Some(Ok(source_map)) => source_map,
+        Some(Err(e)) => {
+            return Err(RpcError {
+                code: error_codes::INTERNAL_ERROR,
+                message: format!("Failed to parse source map of {address}: {e}"),
+                data: None,
+            })
+        }
+        None => return Ok(Value::Null),
+    };
+
+    // Prefer the compiled bytecode; fall back to the on-chain code (immutables only change
+    // push data, so instruction boundaries are the same)
+    let Some(code) = bytecode.bytes().cloned().or_else(|| {
+        context.trace.iter().find(|e| e.code_address == address).and_then(|e| e.bytecode.clone())
+    }) else {
+        return Ok(Value::Null);
+    };
+
+    // Source ids used by the source map
+    let paths: HashMap<u32, _> =
+        artifact.output.sources.iter().map(|(path, file)| (file.id, path.clone())).collect();
+
+    let entries = disassemble(&code)
+        .instructions
+        .iter()
+        .zip(source_map.iter())
+        .map(|(instruction, element)| SourceMapEntry {
+            pc: instruction.pc,
+            instruction: format_instruction(instruction),
+            path: element.index().and_then(|id| paths.get(&id).cloned()),
+            offset: element.offset() as usize,
+            length: element.length() as usize,
+        })
+        .collect();
+
+    let json_value = serde_json::to_value(SourceMapInfo { bytecode_address: address, entries })
+        .map_err(|e| RpcError {
+            code: error_codes::INTERNAL_ERROR,
+            message: format!("Failed to serialize source map: {e}"),
+            data: None,
+        })?;
+    debug!("Retrieved source map for address {}", address);
+    Ok(json_value)
+}
+
 /// Get constructor arguments for a contract at a specific address
 ///
 /// This method retrieves the constructor arguments used during the deployment
@@ -237,15 +335,20 @@ fn get_disassembled_code(bytecode: &Bytes) -> HashMap<usize, String> {
 
     let mut codes = HashMap::new();
     for instruction in disasm_result.instructions {
-        let pc = instruction.pc;
-        let opcode_str = if instruction.is_push() && !instruction.push_data.is_empty() {
-            // Format PUSH instructions with their data
-            let data_hex = hex::encode(&instruction.push_data);
-            format!("{} 0x{}", instruction.opcode, data_hex)
-        } else {
-            instruction.opcode.to_string()
-        };
-        codes.insert(pc, opcode_str);
+        codes.insert(instruction.pc, format_instruction(&instruction));
     }
     codes
 }
+
+fn format_instruction(instruction: &DisassemblyInstruction) -> String {
+    if instruction.is_push() && !instruction.push_data.is_empty() {
+        // Format PUSH instructions with their data
+        let data_hex = hex::encode(&instruction.push_data);
+        format!("{} 0x{}", instruction.opcode, data_hex)
+    } else if instruction.is_eof_only() {
+        // EOF-only opcodes are unknown to revm, and may carry immediates
+        disasm::format_instruction(instruction, false)
+    } else {
+        instruction.opcode.to_string()
+    }
+}
@@ -0,0 +1,52 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Findings RPC methods.
+//!
+//! This module exposes the findings reported by the engine's detectors, e.g.,
+//! reentrancy patterns observed in the execution trace.
+//!
+//! # Available Methods
+//!
+//! - `edb_getFindings` - Get all findings of the debugging session
+
+use std::sync::Arc;
+
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{error_codes, EngineContext, RpcError};
+
+/// Get all findings detected during session preparation
+///
+/// # Returns
+/// - A list of findings, sorted by severity (most severe first)
+pub fn get_findings<DB>(context: &Arc<EngineContext<DB>>) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let json_value = serde_json::to_value(&context.findings).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize findings: {e}"),
+        data: None,
+    })?;
+
+    debug!("Retrieved {} finding(s)", context.findings.len());
+    Ok(json_value)
+}
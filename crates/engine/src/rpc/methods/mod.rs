@@ -24,17 +24,43 @@
 //! ## Artifact Management ([`artifact`])
 //! - `edb_getCode` - Retrieve contract bytecode
 //! - `edb_getConstructorArgs` - Get constructor arguments
+//! - `edb_getSourceMap` - Get the instruction-level source map of a contract
+//!
+//! ## Bridges ([`bridge`])
+//! - `edb_getBridgeMessages` - Get canonical bridge messages and their correlated transactions
 //!
 //! ## Expression Evaluation ([`expr`])
 //! - `edb_evalOnSnapshot` - Evaluate expressions against snapshots
 //!
+//! ## Watched Expressions ([`watch`])
+//! - `edb_registerExpressions` - Register expressions evaluated at every snapshot
+//! - `edb_unregisterExpressions` - Unregister some or all watched expressions
+//!
+//! ## Findings ([`finding`])
+//! - `edb_getFindings` - Get security-relevant findings (e.g., reentrancy)
+//!
 //! ## Navigation ([`navigation`])
 //! - `edb_getNextCall` - Navigate to next function call
 //! - `edb_getPrevCall` - Navigate to previous function call
+//! - `edb_setPosition` - Publish the snapshot a client navigated to
+//!
+//! ## Profiling ([`profile`])
+//! - `edb_getFlamegraph` - Get gas or wall-clock time spent per call path as folded stacks
+//! - `edb_getCoverage` - Get source line coverage as JSON or LCOV
+//! - `edb_getOpcodeHistogram` - Get per-contract program counter histograms
+//! - `edb_getGasHeatmap` - Get gas spent per source line and instruction of a contract
+//!
+//! ## Reporting ([`report`])
+//! - `edb_getReport` - Get a structured summary of the transaction
+//! - `edb_exportTrace` - Export the decoded trace as JSON, `cast`, Tenderly, or Parity traces
+//! - `edb_getMetrics` - Get phase timings, cache hit rates, and memory usage of the preparation
+//! - `edb_getStackTrace` - Get the Solidity stack trace of a snapshot, as JSON or text
 //!
 //! ## Resolution ([`resolve`])
 //! - `edb_getContractABI` - Resolve contract ABI information
 //! - `edb_getCallableABI` - Get callable function ABI details
+//! - `edb_getAddressLabel` - Get the well-known or ENS name of an address
+//! - `edb_getTokenInfo` - Get the symbol and decimals of an ERC-20 token
 //!
 //! ## Snapshot Management ([`snapshot`])
 //! - `edb_getSnapshotCount` - Get total number of snapshots
@@ -45,37 +71,88 @@
 //! - `edb_getStorageDiff` - Compare storage between snapshots
 //!
 //! ## Trace Analysis ([`trace`])
-//! - `edb_getTrace` - Get complete execution trace
+//! - `edb_getTrace` - Get complete execution trace, or a page of it
+//! - `edb_getLogs` - Get emitted events with their emitting snapshots
+//! - `edb_getAccessList` - Get the accounts and storage slots touched by the transaction or a frame
+//!
+//! ## State Tweaking ([`tweak`])
+//! - `edb_setStorageOverride` - Set a storage slot at a snapshot and re-execute the transaction
+//! - `edb_setBalanceOverride` - Set an account balance at a snapshot and re-execute the transaction
+//! - `edb_setCodeOverride` - Replace an account's code at a snapshot and re-execute the transaction
+//! - `edb_clearOverrides` - Remove all state overrides
+//!
+//! ## Session Management ([`crate::rpc::session`], session manager only)
+//! - `edb_createSession` - Start preparing a session for a transaction
+//! - `edb_listSessions` - List all sessions and their preparation status
+//! - `edb_closeSession` - Close a session
+//!
+//! ## Subscriptions ([`crate::rpc::subscription`], WebSocket only)
+//! - `edb_subscribe` - Subscribe to progress, navigation, or findings events
+//! - `edb_unsubscribe` - Cancel an event subscription
+//!
+//! ## Service Discovery ([`crate::rpc::openrpc`])
+//! - `rpc.discover` - Get the OpenRPC document describing all methods above
 //!
 //! # Architecture
 //!
-//! All methods are stateless and operate through the [`MethodHandler`] which
-//! provides access to the immutable debugging context. Methods follow a consistent
-//! pattern of parameter validation, operation execution, and result serialization.
+//! All methods operate through the [`MethodHandler`] which provides access to the
+//! immutable debugging context. Apart from the state overrides accumulated by the
+//! tweaking methods, methods are stateless. Methods follow a consistent pattern of
+//! parameter validation, operation execution, and result serialization.
+//!
+//! Methods changing state shared by all clients of a session are listed by
+//! [`is_mutating_method`], and are rejected for clients with read-only access.
 
 mod artifact;
 mod breakpoint;
+mod bridge;
 mod expr;
+mod finding;
 mod navigation;
+mod profile;
+mod report;
 mod resolve;
 mod snapshot;
 mod storage;
 mod trace;
+mod tweak;
+mod watch;
 
+use super::openrpc::openrpc_document;
 use super::types::RpcError;
-use crate::{error_codes, EngineContext};
+use crate::{error_codes, EngineContext, EventBus};
 use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
 use std::sync::Arc;
 use tracing::debug;
 
-/// Stateless RPC method dispatcher for EDB debugging API.
+/// Whether a method changes state shared by all clients of a session.
+///
+/// Such methods are rejected for clients authenticated with a read-only token, e.g.,
+/// the observers of a collaborative session.
+pub fn is_mutating_method(method: &str) -> bool {
+    matches!(
+        method,
+        "edb_setPosition"
+            | "edb_registerExpressions"
+            | "edb_unregisterExpressions"
+            | "edb_setStorageOverride"
+            | "edb_setBalanceOverride"
+            | "edb_setCodeOverride"
+            | "edb_clearOverrides"
+            | "edb_createSession"
+            | "edb_closeSession"
+    )
+}
+
+/// RPC method dispatcher for EDB debugging API.
 ///
 /// This handler provides a centralized entry point for all RPC methods.
 /// It maintains a reference to the immutable debugging context and routes
 /// method calls to their appropriate implementation modules.
 ///
-/// The handler is designed to be thread-safe and stateless, allowing
-/// concurrent processing of RPC requests without side effects.
+/// The handler is designed to be thread-safe, allowing concurrent processing of
+/// RPC requests. Its only mutable state is the set of state overrides used to
+/// re-execute the transaction, which is guarded by a mutex.
 pub struct MethodHandler<DB>
 where
     DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
@@ -84,6 +161,10 @@ where
 {
     /// Immutable debugging context providing read-only access to debugging data
     context: Arc<EngineContext<DB>>,
+    /// Event bus on which client-reported events are published
+    events: EventBus,
+    /// State overrides set so far, applied when re-executing the transaction
+    overrides: tweak::SessionOverrides,
 }
 
 impl<DB> MethodHandler<DB>
@@ -94,7 +175,18 @@ where
 {
     /// Create a new method handler
     pub fn new(context: Arc<EngineContext<DB>>) -> Self {
-        Self { context }
+        Self { context, events: EventBus::default(), overrides: tweak::SessionOverrides::default() }
+    }
+
+    /// Get the debugging context the methods operate on
+    pub fn context(&self) -> &Arc<EngineContext<DB>> {
+        &self.context
+    }
+
+    /// Publish client-reported events on the given event bus
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
     }
 
     /// Handle an RPC method call with client-provided state
@@ -106,20 +198,49 @@ where
         debug!("Handling RPC method: {}", method);
 
         match method {
-            "edb_getTrace" => trace::get_trace(&self.context),
+            "edb_getTrace" => trace::get_trace(&self.context, params),
+            "edb_getLogs" => trace::get_logs(&self.context),
+            "edb_getAccessList" => trace::get_access_list(&self.context, params),
             "edb_getCode" => artifact::get_code(&self.context, params),
             "edb_getCodeByAddress" => artifact::get_code_by_address(&self.context, params),
             "edb_getConstructorArgs" => artifact::get_constructor_args(&self.context, params),
+            "edb_getSourceMap" => artifact::get_source_map(&self.context, params),
             "edb_getSnapshotCount" => snapshot::get_snapshot_count(&self.context),
             "edb_getSnapshotInfo" => snapshot::get_snapshot_info(&self.context, params),
             "edb_getContractABI" => resolve::get_contract_abi(&self.context, params),
             "edb_getCallableABI" => resolve::get_callable_abi(&self.context, params),
+            "edb_getAddressLabel" => resolve::get_address_label(&self.context, params),
+            "edb_getTokenInfo" => resolve::get_token_info(&self.context, params),
             "edb_getNextCall" => navigation::get_next_call(&self.context, params),
             "edb_getPrevCall" => navigation::get_prev_call(&self.context, params),
+            "edb_setPosition" => navigation::set_position(&self.context, &self.events, params),
             "edb_getStorage" => storage::get_storage(&self.context, params),
             "edb_getStorageDiff" => storage::get_storage_diff(&self.context, params),
             "edb_evalOnSnapshot" => expr::eval_on_snapshot(&self.context, params),
+            "edb_registerExpressions" => watch::register_expressions(&self.context, params),
+            "edb_unregisterExpressions" => watch::unregister_expressions(&self.context, params),
             "edb_getBreakpointHits" => breakpoint::get_breakpoint_hits(&self.context, params),
+            "edb_getFindings" => finding::get_findings(&self.context),
+            "edb_getBridgeMessages" => bridge::get_bridge_messages(&self.context),
+            "edb_getFlamegraph" => profile::get_flamegraph(&self.context, params),
+            "edb_getCoverage" => profile::get_coverage(&self.context, params),
+            "edb_getOpcodeHistogram" => profile::get_opcode_histogram(&self.context, params),
+            "edb_getGasHeatmap" => profile::get_gas_heatmap(&self.context, params),
+            "edb_getReport" => report::get_report(&self.context),
+            "edb_exportTrace" => report::export_trace(&self.context, params),
+            "edb_getMetrics" => report::get_metrics(&self.context),
+            "edb_getStackTrace" => report::get_stack_trace(&self.context, params),
+            "edb_setStorageOverride" => {
+                tweak::set_storage_override(&self.context, &self.overrides, params)
+            }
+            "edb_setBalanceOverride" => {
+                tweak::set_balance_override(&self.context, &self.overrides, params)
+            }
+            "edb_setCodeOverride" => {
+                tweak::set_code_override(&self.context, &self.overrides, params)
+            }
+            "edb_clearOverrides" => tweak::clear_overrides(&self.overrides),
+            "rpc.discover" => Ok(openrpc_document()),
             // Unimplemented methods
             _ => Err(RpcError {
                 code: error_codes::METHOD_NOT_FOUND,
@@ -16,10 +16,13 @@
 
 //! Navigation RPC methods
 //!
-//! This module implements RPC methods for navigating through snapshots.
+//! This module implements RPC methods for navigating through snapshots and for
+//! sharing the navigation position of a client with other subscribers.
 
 use crate::rpc::types::RpcError;
-use crate::{error_codes, ContextQueryTr, EngineContext, Snapshot, SnapshotDetail};
+use crate::{
+    error_codes, ContextQueryTr, EngineContext, EngineEvent, EventBus, Snapshot, SnapshotDetail,
+};
 use edb_common::types::ExecutionFrameId;
 use edb_common::OpcodeTr;
 use revm::bytecode::OpCode;
@@ -124,6 +127,54 @@ where
 }
 
 // Helper function
+/// Report the snapshot a debugging client navigated to
+///
+/// # Parameters
+/// - `snapshot_id`: Snapshot the client is now positioned at
+/// - `client` (optional): Label identifying the client to other subscribers
+///
+/// # Returns
+/// - `true` once the position has been published to `navigation` subscribers
+pub fn set_position<DB>(
+    context: &Arc<EngineContext<DB>>,
+    events: &EventBus,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let params = params.as_ref().and_then(|p| p.as_array());
+    let snapshot_id =
+        params.and_then(|arr| arr.first()).and_then(|v| v.as_u64()).ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [snapshot_id, client?]".to_string(),
+            data: None,
+        })? as usize;
+    let client = match params.and_then(|arr| arr.get(1)) {
+        None | Some(Value::Null) => None,
+        Some(value) => Some(value.as_str().map(str::to_string).ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [snapshot_id, client?]".to_string(),
+            data: None,
+        })?),
+    };
+
+    if snapshot_id >= context.snapshots.len() {
+        return Err(RpcError {
+            code: error_codes::SNAPSHOT_OUT_OF_BOUNDS,
+            message: format!("Snapshot with id {snapshot_id} not found"),
+            data: None,
+        });
+    }
+
+    events.publish(EngineEvent::Navigation { tx_hash: context.tx_hash, snapshot_id, client });
+
+    debug!("Published navigation to snapshot {}", snapshot_id);
+    Ok(Value::Bool(true))
+}
+
 fn snapshot_is_call<DB>(
     context: &Arc<EngineContext<DB>>,
     snapshot: &Snapshot<DB>,
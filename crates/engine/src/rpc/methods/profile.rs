@@ -0,0 +1,214 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Execution profile RPC methods.
+//!
+//! This module exposes profiles summarizing the resource usage of the debugged
+//! transaction.
+//!
+//! # Available Methods
+//!
+//! - `edb_getFlamegraph` - Get the gas or wall-clock profile as folded stacks
+//! - `edb_getCoverage` - Get the source line coverage as JSON or LCOV
+//! - `edb_getOpcodeHistogram` - Get executed program counters and hot basic blocks
+//! - `edb_getGasHeatmap` - Get the gas spent per source line and per instruction of a contract
+
+use std::sync::Arc;
+
+use alloy_primitives::Address;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    coverage_report, error_codes, folded_stacks, gas_heatmap, opcode_histograms,
+    wall_clock_folded_stacks, EngineContext, RpcError,
+};
+
+/// Get the gas or wall-clock time spent per call path in folded-stack format
+///
+/// # Parameters
+/// - `weight` (optional): Either `"gas"` (default) or `"time"`, the nanoseconds the
+///   replay of each frame took
+///
+/// # Returns
+/// - A string with one `frame;frame;... weight` line per call path, consumable by
+///   flamegraph tools such as inferno and speedscope
+pub fn get_flamegraph<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let weight = match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.first()) {
+        None => "gas",
+        Some(value) => value.as_str().ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [weight]".to_string(),
+            data: None,
+        })?,
+    };
+
+    let folded = match weight {
+        "gas" => folded_stacks(context),
+        "time" => wall_clock_folded_stacks(context).ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "No frame times were recorded for this transaction".to_string(),
+            data: None,
+        })?,
+        _ => {
+            return Err(RpcError {
+                code: error_codes::INVALID_PARAMS,
+                message: format!(
+                    "Unsupported flamegraph weight '{weight}', expected 'gas' or 'time'"
+                ),
+                data: None,
+            })
+        }
+    };
+
+    debug!("Generated flamegraph with {} call path(s)", folded.lines().count());
+    Ok(Value::String(folded))
+}
+
+/// Get the source lines executed by the transaction
+///
+/// # Parameters
+/// - `format` (optional): Either `"json"` (default) or `"lcov"`
+///
+/// # Returns
+/// - A [`crate::CoverageReport`] object for `"json"`, or an LCOV tracefile string for `"lcov"`
+pub fn get_coverage<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let format = match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.first()) {
+        None => "json",
+        Some(value) => value.as_str().ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [format]".to_string(),
+            data: None,
+        })?,
+    };
+
+    let report = coverage_report(context);
+    debug!("Generated coverage report for {} contract(s)", report.contracts.len());
+
+    match format {
+        "json" => serde_json::to_value(&report).map_err(|e| RpcError {
+            code: error_codes::INTERNAL_ERROR,
+            message: format!("Failed to serialize coverage report: {e}"),
+            data: None,
+        }),
+        "lcov" => Ok(Value::String(report.to_lcov())),
+        _ => Err(RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: format!("Unsupported coverage format '{format}', expected 'json' or 'lcov'"),
+            data: None,
+        }),
+    }
+}
+
+/// Get the program counter histogram of contracts executed at the opcode level
+///
+/// # Parameters
+/// - `address` (optional): Only return the histogram of this bytecode address
+///
+/// # Returns
+/// - A list of [`crate::OpcodeHistogram`]s, busiest contract first
+pub fn get_opcode_histogram<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let address: Option<Address> =
+        match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.first()) {
+            None => None,
+            Some(value) => Some(serde_json::from_value(value.clone()).map_err(|_| RpcError {
+                code: error_codes::INVALID_PARAMS,
+                message: "Invalid params: expected [address]".to_string(),
+                data: None,
+            })?),
+        };
+
+    let mut histograms = opcode_histograms(context);
+    if let Some(address) = address {
+        histograms.retain(|histogram| histogram.bytecode_address == address);
+    }
+
+    let json_value = serde_json::to_value(&histograms).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize opcode histogram: {e}"),
+        data: None,
+    })?;
+
+    debug!("Generated opcode histogram for {} contract(s)", histograms.len());
+    Ok(json_value)
+}
+
+/// Get the gas spent per source line and per instruction of a contract
+///
+/// # Parameters
+/// - `address`: The bytecode address of the contract
+///
+/// # Returns
+/// - An [`edb_common::types::GasHeatmap`] of the contract, empty if it was not executed
+pub fn get_gas_heatmap<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let address: Address = params
+        .as_ref()
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [address]".to_string(),
+            data: None,
+        })?;
+
+    let heatmap = gas_heatmap(context, address);
+    debug!(
+        "Generated gas heatmap of {} with {} file(s) and {} instruction(s)",
+        address,
+        heatmap.lines.len(),
+        heatmap.instructions.len()
+    );
+
+    serde_json::to_value(&heatmap).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize gas heatmap: {e}"),
+        data: None,
+    })
+}
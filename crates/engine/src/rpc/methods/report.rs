@@ -0,0 +1,220 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Report RPC methods.
+//!
+//! This module exposes a structured summary of the debugged transaction for
+//! consumers that do not need an interactive UI, e.g., monitoring or CI pipelines.
+//!
+//! # Available Methods
+//!
+//! - `edb_getReport` - Get the report of the debugging session
+//! - `edb_exportTrace` - Export the decoded call trace in a format understood by other tools
+//! - `edb_getMetrics` - Get the performance metrics recorded while preparing the transaction
+//! - `edb_getStackTrace` - Get the Solidity stack trace of a snapshot
+
+use std::sync::Arc;
+
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::{
+    cast_call_tree, decoded_trace, error_codes, parity_traces, session_report, stack_trace,
+    tenderly_call_trace, EngineContext, RpcError, TraceFormat,
+};
+
+use super::snapshot::get_snapshot_info;
+
+/// Get the report of the debugged transaction
+///
+/// # Returns
+/// - A [`crate::SessionReport`] with the status, revert reason, decoded trace, asset
+///   flows, storage diffs, gas profile, and findings of the transaction
+pub fn get_report<DB>(context: &Arc<EngineContext<DB>>) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let report = session_report(context);
+
+    let json_value = serde_json::to_value(&report).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize report: {e}"),
+        data: None,
+    })?;
+
+    debug!("Generated report for transaction {}", report.tx_hash);
+    Ok(json_value)
+}
+
+/// Get the performance metrics recorded while preparing the transaction
+///
+/// # Returns
+/// - An [`crate::EngineMetrics`] object with the phase timings, cache hit rate, and memory
+///   usage of the preparation
+pub fn get_metrics<DB>(context: &Arc<EngineContext<DB>>) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    serde_json::to_value(&context.metrics).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize metrics: {e}"),
+        data: None,
+    })
+}
+
+/// Get the Solidity stack trace of a snapshot
+///
+/// # Parameters
+/// - `snapshot_id`: The snapshot ID (0-indexed)
+/// - `format` (optional): Either `"json"` (default) or `"text"`
+///
+/// # Returns
+/// - A [`crate::StackTrace`] object for `"json"`, or the trace formatted like an
+///   exception trace for `"text"`
+pub fn get_stack_trace<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let params = params.as_ref().and_then(|p| p.as_array());
+    let snapshot_id =
+        params.and_then(|arr| arr.first()).and_then(|v| v.as_u64()).ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [snapshot_id, format?]".to_string(),
+            data: None,
+        })? as usize;
+    let format = match params.and_then(|arr| arr.get(1)) {
+        None => "json",
+        Some(value) => value.as_str().ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [snapshot_id, format?]".to_string(),
+            data: None,
+        })?,
+    };
+
+    let trace = stack_trace(context, snapshot_id).ok_or_else(|| RpcError {
+        code: error_codes::SNAPSHOT_OUT_OF_BOUNDS,
+        message: format!("Snapshot with id {snapshot_id} not found"),
+        data: None,
+    })?;
+    debug!("Built stack trace of {} frame(s) for snapshot {}", trace.frames.len(), snapshot_id);
+
+    match format {
+        "json" => serde_json::to_value(&trace).map_err(|e| RpcError {
+            code: error_codes::INTERNAL_ERROR,
+            message: format!("Failed to serialize stack trace: {e}"),
+            data: None,
+        }),
+        "text" => Ok(Value::String(trace.to_string())),
+        _ => Err(RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: format!(
+                "Unsupported stack trace format '{format}', expected 'json' or 'text'"
+            ),
+            data: None,
+        }),
+    }
+}
+
+/// Export the decoded call trace in a format understood by other tools
+///
+/// # Parameters
+/// - `format`: One of `"json"`, `"cast"`, `"tenderly"`, or `"parity"`
+/// - `include_snapshots` (optional): Whether to include the state of every snapshot,
+///   only supported by the `"json"` format (default: `false`)
+///
+/// # Returns
+/// - `"json"`: An object with the transaction hash, the list of [`crate::CallReport`]s,
+///   and, if requested, the [`edb_common::types::SnapshotInfo`] of every snapshot
+/// - `"cast"`: The call tree as printed by `cast run`
+/// - `"tenderly"`: A list of nested [`crate::TenderlyCall`]s, one per top-level frame
+/// - `"parity"`: A list of [`crate::ParityTrace`]s as returned by `trace_transaction`
+pub fn export_trace<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let params = params.as_ref().and_then(|p| p.as_array());
+    let format = params
+        .and_then(|arr| arr.first())
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [format, include_snapshots?]".to_string(),
+            data: None,
+        })?
+        .parse::<TraceFormat>()
+        .map_err(|message| RpcError { code: error_codes::INVALID_PARAMS, message, data: None })?;
+    let include_snapshots =
+        params.and_then(|arr| arr.get(1)).and_then(|v| v.as_bool()).unwrap_or(false);
+
+    if include_snapshots && format != TraceFormat::Json {
+        return Err(RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Snapshots can only be exported in the 'json' format".to_string(),
+            data: None,
+        });
+    }
+
+    let serialize_error = |e: serde_json::Error| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize trace: {e}"),
+        data: None,
+    };
+
+    let json_value = match format {
+        TraceFormat::Json => {
+            let mut export = json!({
+                "tx_hash": context.tx_hash,
+                "trace": serde_json::to_value(decoded_trace(context)).map_err(serialize_error)?,
+            });
+            if include_snapshots {
+                let snapshots = (0..context.snapshots.len())
+                    .map(|id| get_snapshot_info(context, Some(json!([id]))))
+                    .collect::<Result<Vec<_>, _>>()?;
+                export["snapshots"] = Value::Array(snapshots);
+            }
+            export
+        }
+        TraceFormat::Cast => Value::String(cast_call_tree(context)),
+        TraceFormat::Tenderly => {
+            serde_json::to_value(tenderly_call_trace(context)).map_err(serialize_error)?
+        }
+        TraceFormat::Parity => serde_json::to_value(parity_traces(
+            &context.trace,
+            context.tx_hash,
+            context.fork_info.block_number,
+            context.fork_info.block_hash,
+        ))
+        .map_err(serialize_error)?,
+    };
+
+    debug!("Exported trace with {} entries as {:?}", context.trace.len(), format);
+    Ok(json_value)
+}
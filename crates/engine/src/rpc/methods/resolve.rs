@@ -22,7 +22,7 @@ use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
 use serde_json::Value;
 use tracing::debug;
 
-use crate::{error_codes, ContextQueryTr, EngineContext, RpcError};
+use crate::{error_codes, ContextLabelTr, ContextQueryTr, ContextTokenTr, EngineContext, RpcError};
 
 pub fn get_contract_abi<DB>(
     context: &Arc<EngineContext<DB>>,
@@ -157,3 +157,87 @@ where
     debug!("Retrieved contract ABI for address {}", address);
     Ok(json_value)
 }
+
+/// Get the label of an address.
+///
+/// This method returns the name of a well-known contract or precompile, or the
+/// primary ENS name of the address if it resolves back to the address.
+///
+/// # Parameters
+/// - `address`: The address to label
+///
+/// # Returns
+/// - The address label, or null if the address has no known name.
+pub fn get_address_label<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let address: Address = params
+        .as_ref()
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [address]".to_string(),
+            data: None,
+        })?;
+
+    let label = context.address_label(address);
+
+    let json_value = serde_json::to_value(label).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize address label: {e}"),
+        data: None,
+    })?;
+
+    debug!("Retrieved address label for address {}", address);
+    Ok(json_value)
+}
+
+/// Get ERC-20 token metadata for an address.
+///
+/// This method returns the symbol and decimals of the token, read in the state
+/// before the transaction.
+///
+/// # Parameters
+/// - `address`: The token address (or the implementation of a token proxy)
+///
+/// # Returns
+/// - The token metadata, or null if the address is not an ERC-20 token.
+pub fn get_token_info<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let address: Address = params
+        .as_ref()
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.first())
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [address]".to_string(),
+            data: None,
+        })?;
+
+    let token_info = context.token_info(address);
+
+    let json_value = serde_json::to_value(token_info).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize token info: {e}"),
+        data: None,
+    })?;
+
+    debug!("Retrieved token info for address {}", address);
+    Ok(json_value)
+}
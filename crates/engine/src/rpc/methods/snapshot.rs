@@ -31,7 +31,10 @@
 //! - **Opcode snapshots** - Low-level EVM state (PC, stack, memory, storage)
 //! - **Hook snapshots** - High-level source information (variables, source location)
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use edb_common::types::{
     HookSnapshotInfoDetail, OpcodeSnapshotInfoDetail, SnapshotInfo, SnapshotInfoDetail,
@@ -40,7 +43,7 @@ use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
 use serde_json::Value;
 use tracing::debug;
 
-use crate::{error_codes, EngineContext, SnapshotDetail};
+use crate::{error_codes, watch_values, ContextQueryTr, EngineContext, SnapshotDetail};
 
 use super::super::types::RpcError;
 
@@ -49,6 +52,7 @@ use super::super::types::RpcError;
 /// This method returns structured information about a snapshot, including:
 /// - For opcode snapshots: PC, opcode, memory, stack, calldata, and storage state
 /// - For hook snapshots: Source location information (path, offset, length)
+/// - The values of the expressions registered with `edb_registerExpressions`
 ///
 /// # Parameters
 /// - `id`: The snapshot ID (0-indexed)
@@ -90,7 +94,11 @@ where
         data: None,
     })?;
 
-    let snapshot_info = match snapshot.detail() {
+    // State variables of a delegatecall frame live in the storage of the target
+    // address, while the code (and its source) belongs to the bytecode address
+    let delegated = context.is_delegated(snapshot_id);
+
+    let mut snapshot_info = match snapshot.detail() {
         SnapshotDetail::Opcode(ref opcode_snapshot) => {
             // For opcode snapshots, return complete execution state
             SnapshotInfo {
@@ -108,6 +116,8 @@ where
                 })?,
                 target_address: snapshot.target_address(),
                 bytecode_address: snapshot.bytecode_address(),
+                delegated,
+                watches: BTreeMap::new(),
                 detail: SnapshotInfoDetail::Opcode(OpcodeSnapshotInfoDetail {
                     id: snapshot.id(),
                     frame_id: *frame_id,
@@ -158,6 +168,22 @@ where
             // Find state variables at this snapshot
             let state_variables = hook_snapshot.state_variables.clone();
 
+            // Find how to render variables of enum, user-defined value, or
+            // fixed-point types, as well as of values equal to a constant. As
+            // in `locals`, later declarations shadow earlier ones of the same name.
+            let mut hints = HashMap::new();
+            let (states, local_variables): (Vec<_>, Vec<_>) = step_ref
+                .accessible_variables()
+                .iter()
+                .partition(|variable| variable.declaration().state_variable);
+            for variable in states.into_iter().chain(local_variables) {
+                let name = variable.declaration().name;
+                match analysis_result.variable_hint(variable) {
+                    Some(hint) => hints.insert(name, hint),
+                    None => hints.remove(&name),
+                };
+            }
+
             SnapshotInfo {
                 id: snapshot.id(),
                 frame_id: snapshot.frame_id(),
@@ -173,6 +199,8 @@ where
                 })?,
                 target_address: snapshot.target_address(),
                 bytecode_address: snapshot.bytecode_address(),
+                delegated,
+                watches: BTreeMap::new(),
                 detail: SnapshotInfoDetail::Hook(HookSnapshotInfoDetail {
                     id: snapshot.id(),
                     frame_id: *frame_id,
@@ -181,10 +209,13 @@ where
                     path: source_analysis.path.clone(),
                     offset: source_location.start,
                     length: source_location.length,
+                    modifier: snapshot.modifier_frame().cloned(),
+                    hints,
                 }),
             }
         }
     };
+    snapshot_info.watches = watch_values(context, snapshot_id);
 
     // Serialize the SnapshotInfo enum to JSON
     let json_value = serde_json::to_value(&snapshot_info).map_err(|e| RpcError {
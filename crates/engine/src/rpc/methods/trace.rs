@@ -14,19 +14,129 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+//! Execution trace RPC methods.
+//!
+//! # Available Methods
+//!
+//! - `edb_getTrace` - Get the complete execution trace, or a page of it
+//! - `edb_getLogs` - Get the events emitted during the transaction
+//! - `edb_getAccessList` - Get the accounts and storage slots touched by the transaction
+
 use std::sync::Arc;
 
+use edb_common::types::TraceQuery;
 use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde_json::Value;
+use tracing::debug;
 
-use crate::{EngineContext, RpcError};
+use crate::{error_codes, EngineContext, RpcError};
 
-pub fn get_trace<DB>(context: &Arc<EngineContext<DB>>) -> Result<serde_json::Value, RpcError>
+/// Get the execution trace
+///
+/// # Parameters
+/// - `query` (optional): A [`TraceQuery`] object selecting a page of entries, e.g.,
+///   `{"cursor": 0, "limit": 500, "maxDepth": 1, "summary": true}`
+///
+/// # Returns
+/// - The complete trace if no query is given, otherwise a [`edb_common::types::TracePage`]
+///   whose `nextCursor` continues the query
+pub fn get_trace<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
 where
     DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
     <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
     <DB as Database>::Error: Clone + Send + Sync,
 {
-    // Implementation goes here
     let trace = &context.trace;
-    Ok(serde_json::json!(trace))
+
+    let query: TraceQuery =
+        match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.first()) {
+            None => return Ok(serde_json::json!(trace)),
+            Some(value) => serde_json::from_value(value.clone()).map_err(|e| RpcError {
+                code: error_codes::INVALID_PARAMS,
+                message: format!("Invalid params: expected [query]: {e}"),
+                data: None,
+            })?,
+        };
+
+    let page = trace.page(&query);
+    debug!(
+        "Returning {} of {} trace entries from cursor {}",
+        page.entries.len(),
+        page.total,
+        query.cursor
+    );
+
+    serde_json::to_value(&page).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize trace page: {e}"),
+        data: None,
+    })
+}
+
+/// Get the events emitted during the transaction
+///
+/// # Returns
+/// - A list of [`edb_common::types::CapturedLog`]s in emission order, each attributed to its
+///   emitting trace entry and snapshot
+pub fn get_logs<DB>(context: &Arc<EngineContext<DB>>) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let json_value = serde_json::to_value(&context.logs).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize logs: {e}"),
+        data: None,
+    })?;
+
+    debug!("Retrieved {} log(s)", context.logs.len());
+    Ok(json_value)
+}
+
+/// Get the accounts and storage slots touched by the transaction, or by one of its frames
+///
+/// # Parameters
+/// - `trace_entry_id` (optional): Trace entry of the frame, whose own accesses (excluding
+///   those of its child frames) are returned
+///
+/// # Returns
+/// - An EIP-2930 access list, i.e., a list of `{"address", "storageKeys"}` items
+pub fn get_access_list<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let trace_entry_id =
+        match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.first()) {
+            None | Some(Value::Null) => None,
+            Some(value) => Some(value.as_u64().ok_or_else(|| RpcError {
+                code: error_codes::INVALID_PARAMS,
+                message: "Invalid params: expected [trace_entry_id]".to_string(),
+                data: None,
+            })? as usize),
+        };
+
+    let access_list = match trace_entry_id {
+        None => context.access_lists.transaction(),
+        Some(id) => context.access_lists.frame(id).cloned().ok_or_else(|| RpcError {
+            code: error_codes::TRACE_ENTRY_NOT_FOUND,
+            message: format!("No access list recorded for trace entry {id}"),
+            data: None,
+        })?,
+    };
+
+    debug!("Retrieved access list of {} account(s)", access_list.len());
+    serde_json::to_value(&access_list).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize access list: {e}"),
+        data: None,
+    })
 }
@@ -0,0 +1,337 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! State tweaking RPC methods.
+//!
+//! These methods modify the state at a snapshot and re-execute the transaction, so that a
+//! user can explore how its remainder would have unfolded with different storage, balances,
+//! or code. Overrides accumulate across calls: each call adds an override and re-executes
+//! the transaction with all overrides set so far.
+//!
+//! # Available Methods
+//!
+//! - `edb_setStorageOverride` - Set a storage slot at a snapshot and re-execute
+//! - `edb_setBalanceOverride` - Set the balance of an account at a snapshot and re-execute
+//! - `edb_setCodeOverride` - Replace the code of an account at a snapshot and re-execute
+//! - `edb_clearOverrides` - Remove all overrides
+
+use std::sync::{Arc, Mutex};
+
+use alloy_primitives::{Address, Bytes, U256};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{error_codes, ContextEvmTr, EngineContext, RpcError, StateOverride, StateOverrideKind};
+
+/// State overrides of a session, accumulated across calls
+#[derive(Default)]
+pub struct SessionOverrides {
+    /// Overrides set so far
+    overrides: Mutex<Vec<StateOverride>>,
+    /// Held while the overrides are changed, so that concurrent calls apply their changes
+    /// one after the other instead of replacing each other's
+    changes: Mutex<()>,
+}
+
+/// Set a storage slot of an account at a snapshot, and re-execute the transaction
+///
+/// # Parameters
+/// - `snapshot_id`: Snapshot at which the slot is set
+/// - `address`: Account whose storage is modified
+/// - `slot`: Storage slot to set
+/// - `value`: New value of the slot
+///
+/// # Returns
+/// - The [`crate::RerunResult`] of the re-execution with all overrides set so far
+pub fn set_storage_override<DB>(
+    context: &Arc<EngineContext<DB>>,
+    overrides: &SessionOverrides,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    const EXPECTED: &str = "[snapshot_id, address, slot, value]";
+    let kind = StateOverrideKind::Storage {
+        address: parse_param::<Address>(&params, 1, EXPECTED)?,
+        slot: parse_param::<U256>(&params, 2, EXPECTED)?,
+        value: parse_param::<U256>(&params, 3, EXPECTED)?,
+    };
+    add_override_and_rerun(context, overrides, parse_param(&params, 0, EXPECTED)?, kind)
+}
+
+/// Set the balance of an account at a snapshot, and re-execute the transaction
+///
+/// # Parameters
+/// - `snapshot_id`: Snapshot at which the balance is set
+/// - `address`: Account whose balance is modified
+/// - `balance`: New balance of the account
+///
+/// # Returns
+/// - The [`crate::RerunResult`] of the re-execution with all overrides set so far
+pub fn set_balance_override<DB>(
+    context: &Arc<EngineContext<DB>>,
+    overrides: &SessionOverrides,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    const EXPECTED: &str = "[snapshot_id, address, balance]";
+    let kind = StateOverrideKind::Balance {
+        address: parse_param::<Address>(&params, 1, EXPECTED)?,
+        balance: parse_param::<U256>(&params, 2, EXPECTED)?,
+    };
+    add_override_and_rerun(context, overrides, parse_param(&params, 0, EXPECTED)?, kind)
+}
+
+/// Replace the code of an account at a snapshot, and re-execute the transaction
+///
+/// # Parameters
+/// - `snapshot_id`: Snapshot at which the code is replaced
+/// - `address`: Account whose code is replaced
+/// - `code`: New runtime bytecode of the account
+///
+/// # Returns
+/// - The [`crate::RerunResult`] of the re-execution with all overrides set so far
+pub fn set_code_override<DB>(
+    context: &Arc<EngineContext<DB>>,
+    overrides: &SessionOverrides,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    const EXPECTED: &str = "[snapshot_id, address, code]";
+    let kind = StateOverrideKind::Code {
+        address: parse_param::<Address>(&params, 1, EXPECTED)?,
+        code: parse_param::<Bytes>(&params, 2, EXPECTED)?,
+    };
+    add_override_and_rerun(context, overrides, parse_param(&params, 0, EXPECTED)?, kind)
+}
+
+/// Remove all overrides set so far
+///
+/// # Returns
+/// - The number of overrides removed
+pub fn clear_overrides(overrides: &SessionOverrides) -> Result<Value, RpcError> {
+    let _change = overrides.changes.lock().unwrap();
+    let removed = std::mem::take(&mut *overrides.overrides.lock().unwrap()).len();
+
+    debug!("Cleared {} state override(s)", removed);
+    Ok(serde_json::json!(removed))
+}
+
+/// Add an override to the ones set so far and re-execute the transaction with all of them
+///
+/// The re-execution runs on a copy of the overrides, so that other requests of the session
+/// are not blocked while it runs. The copy with the new override replaces the overrides
+/// set so far once the re-execution succeeds, and is discarded if it fails. Concurrent
+/// changes to the overrides wait for the re-execution, so that none of them is lost.
+fn add_override_and_rerun<DB>(
+    context: &Arc<EngineContext<DB>>,
+    overrides: &SessionOverrides,
+    snapshot_id: usize,
+    kind: StateOverrideKind,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    if snapshot_id >= context.snapshots.len() {
+        return Err(RpcError {
+            code: error_codes::SNAPSHOT_OUT_OF_BOUNDS,
+            message: format!("Snapshot with id {snapshot_id} not found"),
+            data: None,
+        });
+    }
+
+    let _change = overrides.changes.lock().unwrap();
+    let mut candidate = overrides.overrides.lock().unwrap().clone();
+    candidate.push(StateOverride { snapshot_id, kind });
+
+    let result = context.rerun_with_overrides(&candidate).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to re-execute the transaction: {e}"),
+        data: None,
+    })?;
+
+    debug!(
+        "Re-executed transaction with {} override(s): success={}, gas used={}",
+        candidate.len(),
+        result.success,
+        result.gas_used
+    );
+    *overrides.overrides.lock().unwrap() = candidate;
+
+    serde_json::to_value(&result).map_err(|e| RpcError {
+        code: error_codes::INTERNAL_ERROR,
+        message: format!("Failed to serialize re-execution result: {e}"),
+        data: None,
+    })
+}
+
+/// Parse a positional parameter
+fn parse_param<T: DeserializeOwned>(
+    params: &Option<Value>,
+    index: usize,
+    expected: &str,
+) -> Result<T, RpcError> {
+    params
+        .as_ref()
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.get(index))
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: format!("Invalid params: expected {expected}"),
+            data: None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{build_session, opcode_snapshot_at, state_with_contracts, TestDb},
+        RerunResult,
+    };
+    use alloy_primitives::{address, bytes};
+    use edb_common::types::{CallResult, ExecutionFrameId};
+    use revm::primitives::hardfork::SpecId;
+    use serde_json::json;
+
+    const TARGET: Address = address!("0x00000000000000000000000000000000000000aa");
+    const CALLEE: Address = address!("0x00000000000000000000000000000000000000bb");
+
+    /// PC of the `SLOAD` of `TARGET`, after the call to `CALLEE`
+    const SLOAD_PC: usize = 39;
+
+    /// Session of a call to `TARGET`, which calls `CALLEE` (returning 7) and returns the
+    /// sum of its result, of its slot 0 (10) and of its balance (5)
+    fn session() -> Arc<EngineContext<TestDb>> {
+        let target_code = bytes!(
+            "0x60206000600060006000"
+            "7300000000000000000000000000000000000000bb5af150"
+            "600051600054014701600052"
+            "60206000f3"
+        );
+        let callee_code = bytes!("0x600760005260206000f3");
+
+        let mut db = state_with_contracts(&[(TARGET, target_code), (CALLEE, callee_code)]);
+        db.insert_account_storage(TARGET, U256::ZERO, U256::from(10)).unwrap();
+        db.cache.accounts.get_mut(&TARGET).unwrap().info.balance = U256::from(5);
+        Arc::new(build_session(db, TARGET, SpecId::PRAGUE))
+    }
+
+    fn rerun_output(result: Value) -> (RerunResult, U256) {
+        let result: RerunResult = serde_json::from_value(result).unwrap();
+        let output = U256::from_be_slice(&result.output);
+        (result, output)
+    }
+
+    #[test]
+    fn test_overrides_accumulate_across_reruns() {
+        let context = session();
+        let overrides = SessionOverrides::default();
+        let sload = opcode_snapshot_at(&context, ExecutionFrameId::new(0, 1), SLOAD_PC);
+
+        // The balance is set before the first instruction
+        let params = json!([0, TARGET, "0x64"]);
+        let result = set_balance_override(&context, &overrides, Some(params)).unwrap();
+        let (result, output) = rerun_output(result);
+        assert!(result.success);
+        assert_eq!(output, U256::from(7 + 10 + 100));
+        assert_eq!(result.trace.len(), 2);
+        assert!(matches!(result.trace[1].result, Some(CallResult::Success { .. })));
+
+        // The slot is set after the call, right before it is read
+        let params = json!([sload, TARGET, "0x0", "0x3e8"]);
+        let (_, output) =
+            rerun_output(set_storage_override(&context, &overrides, Some(params)).unwrap());
+        assert_eq!(output, U256::from(7 + 1000 + 100));
+
+        // The callee reverts once its code is replaced before it is called
+        let params = json!([0, CALLEE, "0x60006000fd"]);
+        let (result, output) =
+            rerun_output(set_code_override(&context, &overrides, Some(params)).unwrap());
+        assert!(result.success);
+        assert_eq!(output, U256::from(1000 + 100));
+        assert!(matches!(result.trace[1].result, Some(CallResult::Revert { .. })));
+        assert!(result.unapplied_overrides.is_empty());
+
+        // The snapshots of the session are not modified by the re-executions
+        let db = context.snapshots[sload].1.db();
+        assert_eq!(db.storage_ref(TARGET, U256::ZERO).unwrap(), U256::from(10));
+        assert_eq!(db.basic_ref(TARGET).unwrap().unwrap().balance, U256::from(5));
+
+        assert_eq!(clear_overrides(&overrides).unwrap(), json!(3));
+    }
+
+    #[test]
+    fn test_invalid_override_is_discarded() {
+        let context = session();
+        let overrides = SessionOverrides::default();
+
+        let params = json!([context.snapshots.len(), TARGET, "0x64"]);
+        let error = set_balance_override(&context, &overrides, Some(params)).unwrap_err();
+        assert_eq!(error.code, error_codes::SNAPSHOT_OUT_OF_BOUNDS);
+        let error = set_balance_override(&context, &overrides, Some(json!([0]))).unwrap_err();
+        assert_eq!(error.code, error_codes::INVALID_PARAMS);
+        assert!(overrides.overrides.lock().unwrap().is_empty());
+
+        // An override keeping the state re-executes the transaction as in the session
+        let params = json!([0, TARGET, "0x5"]);
+        let (result, output) =
+            rerun_output(set_balance_override(&context, &overrides, Some(params)).unwrap());
+        assert_eq!(output, U256::from(7 + 10 + 5));
+        assert_eq!(result.trace.len(), context.trace.len());
+    }
+
+    #[test]
+    fn test_concurrent_overrides_are_all_kept() {
+        let context = session();
+        let overrides = SessionOverrides::default();
+        let sload = opcode_snapshot_at(&context, ExecutionFrameId::new(0, 1), SLOAD_PC);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let params = json!([0, TARGET, "0x64"]);
+                set_balance_override(&context, &overrides, Some(params)).unwrap();
+            });
+            scope.spawn(|| {
+                let params = json!([sload, TARGET, "0x0", "0x3e8"]);
+                set_storage_override(&context, &overrides, Some(params)).unwrap();
+            });
+        });
+        assert_eq!(overrides.overrides.lock().unwrap().len(), 2);
+
+        // Both overrides apply to the next re-execution
+        let params = json!([0, CALLEE, "0x600760005260206000f3"]);
+        let (_, output) =
+            rerun_output(set_code_override(&context, &overrides, Some(params)).unwrap());
+        assert_eq!(output, U256::from(7 + 1000 + 100));
+    }
+}
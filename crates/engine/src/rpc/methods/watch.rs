@@ -0,0 +1,111 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Watched expression RPC methods.
+//!
+//! This module implements RPC methods to register expressions that are evaluated at
+//! every snapshot. Their values are then included in the `watches` of the snapshot
+//! information returned by `edb_getSnapshotInfo`, instead of being requested one by one
+//! with `edb_evalOnSnapshot`.
+//!
+//! # Available Methods
+//!
+//! - `edb_registerExpressions` - Register expressions and evaluate them in the background
+//! - `edb_unregisterExpressions` - Unregister some or all expressions
+
+use std::sync::Arc;
+
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    error_codes, evaluate_watches_in_background, register_watches, EngineContext, RpcError,
+};
+
+/// Register expressions to be evaluated at every snapshot
+///
+/// The new expressions are evaluated at all snapshots in the background, in parallel,
+/// after the expressions registered before.
+/// Snapshots requested before their values are ready are evaluated on the spot.
+///
+/// # Parameters
+/// - `exprs`: Expressions to register
+///
+/// # Returns
+/// - All registered expressions, including the ones registered before
+pub fn register_expressions<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let exprs = parse_expressions(params.as_ref(), "Invalid params: expected [exprs]")?
+        .ok_or_else(|| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [exprs]".to_string(),
+            data: None,
+        })?;
+
+    let added = register_watches(context, &exprs);
+    if !added.is_empty() {
+        evaluate_watches_in_background(context, added);
+    }
+
+    debug!("Registered watched expressions {:?}", exprs);
+    Ok(Value::from(context.watches.expressions()))
+}
+
+/// Unregister expressions evaluated at every snapshot
+///
+/// # Parameters
+/// - `exprs` (optional): Expressions to unregister, all of them if omitted
+///
+/// # Returns
+/// - The expressions still registered
+pub fn unregister_expressions<DB>(
+    context: &Arc<EngineContext<DB>>,
+    params: Option<Value>,
+) -> Result<Value, RpcError>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let exprs = parse_expressions(params.as_ref(), "Invalid params: expected [exprs?]")?;
+    let removed = context.watches.remove(exprs.as_deref());
+
+    debug!("Unregistered {} watched expressions", removed);
+    Ok(Value::from(context.watches.expressions()))
+}
+
+/// Parse the optional list of expressions given as first parameter
+fn parse_expressions(
+    params: Option<&Value>,
+    message: &str,
+) -> Result<Option<Vec<String>>, RpcError> {
+    match params.and_then(|p| p.as_array()).and_then(|arr| arr.first()) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => serde_json::from_value(value.clone()).map(Some).map_err(|_| RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: message.to_string(),
+            data: None,
+        }),
+    }
+}
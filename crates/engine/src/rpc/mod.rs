@@ -26,7 +26,12 @@
 //! The RPC system consists of several key components:
 //!
 //! - **Server** ([`server`]) - HTTP/WebSocket server handling client connections
+//! - **Security** ([`security`]) - Bearer-token authentication and TLS for the servers
 //! - **Methods** ([`methods`]) - RPC method implementations organized by functionality
+//! - **OpenRPC** ([`openrpc`]) - Machine-readable description of all methods for client generation
+//! - **Sessions** ([`session`]) - Several debugging sessions served by one process
+//! - **Status** ([`status_server`]) - Progress of preparations whose debug server has not started yet
+//! - **Subscriptions** ([`subscription`]) - WebSocket transport streaming live engine events
 //! - **Types** ([`types`]) - Request/response data structures and protocol types
 //! - **Utils** ([`utils`]) - Common utilities for RPC operations
 //!
@@ -75,9 +80,17 @@
 //! with structured request/response formats defined in the [`types`] module.
 
 pub mod methods;
+pub mod openrpc;
+pub mod security;
 pub mod server;
+pub mod session;
+pub mod status_server;
+pub mod subscription;
 pub mod types;
 pub mod utils;
 
+pub use security::*;
 pub use server::*;
+pub use session::*;
+pub use status_server::*;
 pub use types::*;
@@ -0,0 +1,1064 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! OpenRPC description of the debugging API.
+//!
+//! This module describes every `edb_*` method exposed by the RPC server, together with
+//! its positional parameters and result type, as an [OpenRPC](https://open-rpc.org)
+//! document. Frontends such as the WebUI can use the document to generate typed
+//! clients instead of hand-writing request and response types.
+//!
+//! Events delivered to `edb_subscribe` subscribers are sent as `edb_subscription`
+//! notifications, see [`crate::rpc::subscription`].
+//!
+//! The document is served in two ways:
+//! - Through the `rpc.discover` method, as mandated by the OpenRPC specification
+//! - Through `GET /openrpc.json` on the HTTP server
+//!
+//! Parameter and result schemas reference the shared schemas under
+//! `components/schemas`, which mirror the serialized form of the types in
+//! [`edb_common::types`] and [`crate::report`].
+
+use serde_json::{json, Map, Value};
+
+/// OpenRPC specification version the generated document conforms to
+pub const OPENRPC_VERSION: &str = "1.2.6";
+
+/// Description of a positional method parameter.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec {
+    /// Parameter name
+    pub name: &'static str,
+    /// Short description of the parameter
+    pub description: &'static str,
+    /// Name of the schema under `components/schemas`
+    pub schema: &'static str,
+    /// Whether the parameter must be provided
+    pub required: bool,
+}
+
+/// Description of a single RPC method.
+#[derive(Debug, Clone, Copy)]
+pub struct MethodSpec {
+    /// Method name as dispatched by [`crate::rpc::methods::MethodHandler`]
+    pub name: &'static str,
+    /// One-line summary of what the method does
+    pub summary: &'static str,
+    /// Tag grouping related methods (matches the method modules)
+    pub tag: &'static str,
+    /// Positional parameters, in order
+    pub params: &'static [ParamSpec],
+    /// Name of the result schema under `components/schemas`
+    pub result: &'static str,
+}
+
+const fn param(name: &'static str, description: &'static str, schema: &'static str) -> ParamSpec {
+    ParamSpec { name, description, schema, required: true }
+}
+
+const fn optional(
+    name: &'static str,
+    description: &'static str,
+    schema: &'static str,
+) -> ParamSpec {
+    ParamSpec { name, description, schema, required: false }
+}
+
+const SNAPSHOT_ID: ParamSpec = param("snapshot_id", "Snapshot to operate on", "SnapshotId");
+const ADDRESS: ParamSpec = param("address", "Contract address", "Address");
+
+/// All methods of the debugging API, in the order they are documented.
+pub const METHODS: &[MethodSpec] = &[
+    MethodSpec {
+        name: "edb_getTrace",
+        summary: "Get the complete execution trace, or a page of it if a query is given",
+        tag: "trace",
+        params: &[optional("query", "Page of entries to return", "TraceQuery")],
+        result: "TraceOrPage",
+    },
+    MethodSpec {
+        name: "edb_getLogs",
+        summary: "Get the events emitted during the transaction with their emitting snapshots",
+        tag: "trace",
+        params: &[],
+        result: "CapturedLogList",
+    },
+    MethodSpec {
+        name: "edb_getAccessList",
+        summary: "Get the accounts and storage slots touched by the transaction, or by a frame",
+        tag: "trace",
+        params: &[optional(
+            "trace_entry_id",
+            "Only include the accesses of this frame, excluding its child frames",
+            "TraceEntryId",
+        )],
+        result: "AccessList",
+    },
+    MethodSpec {
+        name: "edb_getCode",
+        summary: "Get the code executed at a snapshot",
+        tag: "artifact",
+        params: &[SNAPSHOT_ID],
+        result: "Code",
+    },
+    MethodSpec {
+        name: "edb_getCodeByAddress",
+        summary: "Get the code deployed at an address",
+        tag: "artifact",
+        params: &[ADDRESS],
+        result: "Code",
+    },
+    MethodSpec {
+        name: "edb_getSourceMap",
+        summary: "Get the instruction-level source map of a verified contract",
+        tag: "artifact",
+        params: &[ADDRESS],
+        result: "SourceMap",
+    },
+    MethodSpec {
+        name: "edb_getConstructorArgs",
+        summary: "Get the constructor arguments of a contract",
+        tag: "artifact",
+        params: &[ADDRESS],
+        result: "NullableBytes",
+    },
+    MethodSpec {
+        name: "edb_getSnapshotCount",
+        summary: "Get the total number of snapshots",
+        tag: "snapshot",
+        params: &[],
+        result: "SnapshotId",
+    },
+    MethodSpec {
+        name: "edb_getSnapshotInfo",
+        summary: "Get detailed information about a snapshot",
+        tag: "snapshot",
+        params: &[SNAPSHOT_ID],
+        result: "SnapshotInfo",
+    },
+    MethodSpec {
+        name: "edb_getContractABI",
+        summary: "Resolve the ABI of a contract",
+        tag: "resolve",
+        params: &[
+            ADDRESS,
+            param("recompiled", "Use the ABI of the instrumented recompilation", "Boolean"),
+        ],
+        result: "Abi",
+    },
+    MethodSpec {
+        name: "edb_getCallableABI",
+        summary: "Get the callable functions of a contract",
+        tag: "resolve",
+        params: &[ADDRESS],
+        result: "CallableAbiInfoList",
+    },
+    MethodSpec {
+        name: "edb_getAddressLabel",
+        summary: "Get the well-known or verified ENS name of an address",
+        tag: "resolve",
+        params: &[ADDRESS],
+        result: "NullableAddressLabel",
+    },
+    MethodSpec {
+        name: "edb_getTokenInfo",
+        summary: "Get the symbol and decimals of an ERC-20 token",
+        tag: "resolve",
+        params: &[ADDRESS],
+        result: "NullableTokenInfo",
+    },
+    MethodSpec {
+        name: "edb_getNextCall",
+        summary: "Find the next snapshot performing an external call",
+        tag: "navigation",
+        params: &[SNAPSHOT_ID],
+        result: "SnapshotId",
+    },
+    MethodSpec {
+        name: "edb_getPrevCall",
+        summary: "Find the previous snapshot performing an external call",
+        tag: "navigation",
+        params: &[SNAPSHOT_ID],
+        result: "SnapshotId",
+    },
+    MethodSpec {
+        name: "edb_setPosition",
+        summary: "Publish the snapshot a client navigated to",
+        tag: "navigation",
+        params: &[
+            SNAPSHOT_ID,
+            optional("client", "Label identifying the client to other subscribers", "String"),
+        ],
+        result: "Boolean",
+    },
+    MethodSpec {
+        name: "edb_getStorage",
+        summary: "Read a storage slot of the executing contract at a snapshot",
+        tag: "storage",
+        params: &[SNAPSHOT_ID, param("slot", "Storage slot", "U256")],
+        result: "U256",
+    },
+    MethodSpec {
+        name: "edb_getStorageDiff",
+        summary: "Get the storage slots changed between the first snapshot and a snapshot",
+        tag: "storage",
+        params: &[SNAPSHOT_ID],
+        result: "StorageDiff",
+    },
+    MethodSpec {
+        name: "edb_evalOnSnapshot",
+        summary: "Evaluate a Solidity-like expression at a snapshot",
+        tag: "expr",
+        params: &[SNAPSHOT_ID, param("expr", "Expression to evaluate", "String")],
+        result: "EvalResult",
+    },
+    MethodSpec {
+        name: "edb_registerExpressions",
+        summary:
+            "Register expressions evaluated at every snapshot and returned by edb_getSnapshotInfo",
+        tag: "expr",
+        params: &[param("exprs", "Expressions to register", "StringList")],
+        result: "StringList",
+    },
+    MethodSpec {
+        name: "edb_unregisterExpressions",
+        summary: "Unregister watched expressions",
+        tag: "expr",
+        params: &[optional("exprs", "Expressions to unregister, all if omitted", "StringList")],
+        result: "StringList",
+    },
+    MethodSpec {
+        name: "edb_getBreakpointHits",
+        summary: "Get the snapshots hitting a breakpoint",
+        tag: "breakpoint",
+        params: &[param("breakpoint", "Breakpoint to evaluate", "Breakpoint")],
+        result: "SnapshotIdList",
+    },
+    MethodSpec {
+        name: "edb_getFindings",
+        summary: "Get security-relevant findings",
+        tag: "finding",
+        params: &[],
+        result: "FindingList",
+    },
+    MethodSpec {
+        name: "edb_getBridgeMessages",
+        summary: "Get canonical bridge messages and their correlated transactions",
+        tag: "bridge",
+        params: &[],
+        result: "BridgeMessageList",
+    },
+    MethodSpec {
+        name: "edb_getFlamegraph",
+        summary: "Get gas or wall-clock time spent per call path as folded stacks",
+        tag: "profile",
+        params: &[optional("weight", "Weight of each frame", "FlamegraphWeight")],
+        result: "String",
+    },
+    MethodSpec {
+        name: "edb_getCoverage",
+        summary: "Get source line coverage as JSON or LCOV",
+        tag: "profile",
+        params: &[optional("format", "Output format", "CoverageFormat")],
+        result: "Coverage",
+    },
+    MethodSpec {
+        name: "edb_getOpcodeHistogram",
+        summary: "Get per-contract program counter histograms",
+        tag: "profile",
+        params: &[optional("address", "Only include this bytecode address", "Address")],
+        result: "OpcodeHistogramList",
+    },
+    MethodSpec {
+        name: "edb_getGasHeatmap",
+        summary: "Get gas spent per source line and per instruction of a contract",
+        tag: "profile",
+        params: &[ADDRESS],
+        result: "GasHeatmap",
+    },
+    MethodSpec {
+        name: "edb_getReport",
+        summary: "Get a structured summary of the transaction",
+        tag: "report",
+        params: &[],
+        result: "Report",
+    },
+    MethodSpec {
+        name: "edb_exportTrace",
+        summary: "Export the decoded trace as JSON, cast, Tenderly, or Parity traces",
+        tag: "report",
+        params: &[
+            param("format", "Export format", "TraceFormat"),
+            optional("include_snapshots", "Include snapshot details in the JSON export", "Boolean"),
+        ],
+        result: "ExportedTrace",
+    },
+    MethodSpec {
+        name: "edb_getMetrics",
+        summary: "Get phase timings, cache hit rates, and memory usage of the preparation",
+        tag: "report",
+        params: &[],
+        result: "Metrics",
+    },
+    MethodSpec {
+        name: "edb_getStackTrace",
+        summary: "Get the Solidity stack trace of a snapshot, as JSON or text",
+        tag: "report",
+        params: &[SNAPSHOT_ID, optional("format", "Output format", "StackTraceFormat")],
+        result: "StackTrace",
+    },
+    MethodSpec {
+        name: "edb_setStorageOverride",
+        summary: "Set a storage slot at a snapshot and re-execute the transaction",
+        tag: "tweak",
+        params: &[
+            SNAPSHOT_ID,
+            param("address", "Account whose storage is modified", "Address"),
+            param("slot", "Storage slot", "U256"),
+            param("value", "New value of the slot", "U256"),
+        ],
+        result: "RerunResult",
+    },
+    MethodSpec {
+        name: "edb_setBalanceOverride",
+        summary: "Set the balance of an account at a snapshot and re-execute the transaction",
+        tag: "tweak",
+        params: &[
+            SNAPSHOT_ID,
+            param("address", "Account whose balance is modified", "Address"),
+            param("balance", "New balance of the account", "U256"),
+        ],
+        result: "RerunResult",
+    },
+    MethodSpec {
+        name: "edb_setCodeOverride",
+        summary: "Replace the code of an account at a snapshot and re-execute the transaction",
+        tag: "tweak",
+        params: &[
+            SNAPSHOT_ID,
+            param("address", "Account whose code is replaced", "Address"),
+            param("code", "New runtime bytecode", "Bytes"),
+        ],
+        result: "RerunResult",
+    },
+    MethodSpec {
+        name: "edb_clearOverrides",
+        summary: "Remove all state overrides",
+        tag: "tweak",
+        params: &[],
+        result: "Integer",
+    },
+    MethodSpec {
+        name: "edb_createSession",
+        summary: "Start preparing a session for a transaction (session manager only)",
+        tag: "session",
+        params: &[
+            param("tx_hash", "Transaction to debug", "Hash"),
+            optional("mocks", "Mock contracts installed before replay", "MockContractList"),
+            optional("focus", "Call whose subtree is recorded step by step", "TraceFocus"),
+        ],
+        result: "SessionInfo",
+    },
+    MethodSpec {
+        name: "edb_listSessions",
+        summary: "List all sessions and their preparation status (session manager only)",
+        tag: "session",
+        params: &[],
+        result: "SessionInfoList",
+    },
+    MethodSpec {
+        name: "edb_cancel",
+        summary: "Stop preparing a session, returning whether it was still preparing (session manager only)",
+        tag: "session",
+        params: &[param("session_id", "Session to cancel", "SessionId")],
+        result: "Boolean",
+    },
+    MethodSpec {
+        name: "edb_closeSession",
+        summary: "Close a session, cancelling its preparation (session manager only)",
+        tag: "session",
+        params: &[param("session_id", "Session to close", "SessionId")],
+        result: "Boolean",
+    },
+    MethodSpec {
+        name: "edb_subscribe",
+        summary: "Subscribe to engine events (WebSocket only)",
+        tag: "subscription",
+        params: &[
+            param("topic", "Event topic", "EventTopic"),
+            optional(
+                "tx_hash",
+                "Transaction to receive events of, defaults to the session",
+                "Hash",
+            ),
+        ],
+        result: "SubscriptionId",
+    },
+    MethodSpec {
+        name: "edb_unsubscribe",
+        summary: "Cancel an event subscription (WebSocket only)",
+        tag: "subscription",
+        params: &[param("subscription_id", "Subscription to cancel", "SubscriptionId")],
+        result: "Boolean",
+    },
+];
+
+/// Look up the description of a method by name.
+pub fn find_method(name: &str) -> Option<&'static MethodSpec> {
+    METHODS.iter().find(|method| method.name == name)
+}
+
+/// Build the OpenRPC document describing the debugging API.
+pub fn openrpc_document() -> Value {
+    let mut methods: Vec<Value> = METHODS.iter().map(method_object).collect();
+    methods.push(json!({
+        "name": "rpc.discover",
+        "summary": "Get the OpenRPC document describing this API",
+        "params": [],
+        "result": { "name": "document", "schema": { "type": "object" } },
+    }));
+
+    json!({
+        "openrpc": OPENRPC_VERSION,
+        "info": {
+            "title": "EDB Debugging API",
+            "description": "JSON-RPC API for inspecting a transaction replayed by the EDB engine",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "methods": methods,
+        "components": { "schemas": component_schemas() },
+    })
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn method_object(method: &MethodSpec) -> Value {
+    let params: Vec<Value> = method
+        .params
+        .iter()
+        .map(|param| {
+            json!({
+                "name": param.name,
+                "description": param.description,
+                "required": param.required,
+                "schema": schema_ref(param.schema),
+            })
+        })
+        .collect();
+
+    json!({
+        "name": method.name,
+        "summary": method.summary,
+        "tags": [{ "name": method.tag }],
+        "paramStructure": "by-position",
+        "params": params,
+        "result": { "name": "result", "schema": schema_ref(method.result) },
+    })
+}
+
+/// Shared schemas, keyed by the names referenced from [`METHODS`].
+fn component_schemas() -> Map<String, Value> {
+    let hex = |description: &str, pattern: &str| json!({ "type": "string", "pattern": pattern, "description": description });
+
+    let schemas = json!({
+        "Address": hex("20-byte address", "^0x[0-9a-fA-F]{40}$"),
+        "Hash": hex("32-byte hash", "^0x[0-9a-fA-F]{64}$"),
+        "Bytes": hex("Hex-encoded bytes", "^0x([0-9a-fA-F]{2})*$"),
+        "NullableBytes": {
+            "oneOf": [schema_ref("Bytes"), { "type": "null" }],
+        },
+        "U256": hex("Hex-encoded 256-bit unsigned integer", "^0x[0-9a-fA-F]{1,64}$"),
+        "Boolean": { "type": "boolean" },
+        "String": { "type": "string" },
+        "StringList": { "type": "array", "items": { "type": "string" } },
+        "SnapshotId": { "type": "integer", "minimum": 0 },
+        "SnapshotIdList": { "type": "array", "items": schema_ref("SnapshotId") },
+        "ExecutionFrameId": {
+            "description": "Trace entry id and re-entry count of an execution frame",
+            "type": "array",
+            "items": { "type": "integer", "minimum": 0 },
+            "minItems": 2,
+            "maxItems": 2,
+        },
+        "TraceEntry": {
+            "type": "object",
+            "required": ["id", "depth", "call_type", "caller", "target", "code_address"],
+            "properties": {
+                "id": { "type": "integer" },
+                "parent_id": { "type": ["integer", "null"] },
+                "depth": { "type": "integer" },
+                "call_type": { "description": "Call scheme or create scheme of the frame" },
+                "caller": schema_ref("Address"),
+                "target": schema_ref("Address"),
+                "code_address": schema_ref("Address"),
+                "input": schema_ref("Bytes"),
+                "value": schema_ref("U256"),
+                "readonly": { "type": "boolean", "description": "Whether the frame runs in a static context" },
+                "subtree_value": schema_ref("U256"),
+                "gas_limit": { "type": "integer" },
+                "gas_used": { "type": "integer" },
+                "result": { "description": "Success, revert, or error outcome of the frame" },
+                "created_contract": { "type": "boolean" },
+                "bytecode": { "oneOf": [schema_ref("Bytes"), { "type": "null" }] },
+                "target_label": { "type": ["string", "null"] },
+                "events": { "type": "array" },
+                "first_snapshot_id": { "type": ["integer", "null"] },
+            },
+        },
+        "Trace": {
+            "type": "object",
+            "properties": {
+                "inner": { "type": "array", "items": schema_ref("TraceEntry") },
+            },
+        },
+        "TraceQuery": {
+            "type": "object",
+            "properties": {
+                "cursor": { "type": "integer", "minimum": 0, "description": "Id of the first entry to consider" },
+                "limit": { "type": ["integer", "null"], "minimum": 0 },
+                "maxDepth": { "type": ["integer", "null"], "minimum": 0 },
+                "parentId": { "type": ["integer", "null"], "minimum": 0 },
+                "summary": { "type": "boolean", "description": "Drop bytecode and events and truncate inputs" },
+            },
+        },
+        "TracePage": {
+            "type": "object",
+            "required": ["entries", "total"],
+            "properties": {
+                "entries": { "type": "array", "items": schema_ref("TraceEntry") },
+                "total": { "type": "integer" },
+                "nextCursor": { "type": ["integer", "null"] },
+            },
+        },
+        "TraceOrPage": {
+            "oneOf": [schema_ref("Trace"), schema_ref("TracePage")],
+        },
+        "CapturedLog": {
+            "type": "object",
+            "required": ["traceEntryId", "index", "address", "log"],
+            "properties": {
+                "traceEntryId": { "type": "integer", "description": "Trace entry that emitted the log" },
+                "index": { "type": "integer", "description": "Index among the events of the trace entry" },
+                "address": schema_ref("Address"),
+                "log": {
+                    "type": "object",
+                    "properties": {
+                        "topics": { "type": "array", "items": schema_ref("Hash") },
+                        "data": schema_ref("Bytes"),
+                    },
+                },
+                "snapshotId": { "type": ["integer", "null"], "description": "Emitting snapshot, if known" },
+            },
+        },
+        "CapturedLogList": { "type": "array", "items": schema_ref("CapturedLog") },
+        "Code": {
+            "description": "Disassembled opcodes or verified source files of a contract",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["Opcode"],
+                    "properties": {
+                        "Opcode": {
+                            "type": "object",
+                            "properties": {
+                                "bytecode_address": schema_ref("Address"),
+                                "codes": {
+                                    "description": "Program counter to opcode",
+                                    "type": "object",
+                                    "additionalProperties": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                },
+                {
+                    "type": "object",
+                    "required": ["Source"],
+                    "properties": {
+                        "Source": {
+                            "type": "object",
+                            "properties": {
+                                "bytecode_address": schema_ref("Address"),
+                                "sources": {
+                                    "description": "File path to source content",
+                                    "type": "object",
+                                    "additionalProperties": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                },
+            ],
+        },
+        "SourceMap": {
+            "description": "Per-instruction source ranges, or null for unverified contracts",
+            "type": ["object", "null"],
+            "required": ["bytecode_address", "entries"],
+            "properties": {
+                "bytecode_address": schema_ref("Address"),
+                "entries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["pc", "instruction", "offset", "length"],
+                        "properties": {
+                            "pc": { "type": "integer", "minimum": 0 },
+                            "instruction": { "type": "string" },
+                            "path": { "type": ["string", "null"] },
+                            "offset": { "type": "integer", "minimum": 0 },
+                            "length": { "type": "integer", "minimum": 0 },
+                        },
+                    },
+                },
+            },
+        },
+        "SnapshotInfo": {
+            "type": "object",
+            "required": [
+                "id", "frame_id", "next_id", "prev_id", "detail",
+                "target_address", "bytecode_address",
+            ],
+            "properties": {
+                "id": schema_ref("SnapshotId"),
+                "frame_id": schema_ref("ExecutionFrameId"),
+                "next_id": schema_ref("SnapshotId"),
+                "prev_id": schema_ref("SnapshotId"),
+                "detail": {
+                    "description": "Either an `Opcode` detail (pc, stack, memory) or a `Hook` detail (source location, variables, the virtual frame of the modifier being evaluated if any, and `hints` rendering enum, user-defined value type, fixed-point, and constant-valued variables)",
+                    "type": "object",
+                },
+                "target_address": {
+                    "description": "Account the frame operates on, whose storage state variables are read from",
+                    "$ref": "#/components/schemas/Address",
+                },
+                "bytecode_address": {
+                    "description": "Account whose code (and source) is executed; differs from `target_address` in delegatecall frames",
+                    "$ref": "#/components/schemas/Address",
+                },
+                "delegated": {
+                    "description": "Whether the frame was entered by DELEGATECALL or CALLCODE, running borrowed code in the storage context of `target_address`",
+                    "type": "boolean",
+                },
+                "watches": {
+                    "description": "Values of the expressions registered with `edb_registerExpressions`, by expression",
+                    "type": "object",
+                    "additionalProperties": schema_ref("EvalResult"),
+                },
+            },
+        },
+        "Abi": {
+            "description": "Solidity JSON ABI",
+            "type": "array",
+            "items": { "type": "object" },
+        },
+        "CallableAbiInfoList": {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "properties": {
+                    "address": schema_ref("Address"),
+                    "contract_ty": { "type": "string" },
+                    "entries": { "type": "array", "items": { "type": "object" } },
+                },
+            },
+        },
+        "NullableAddressLabel": {
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["address", "name", "source"],
+                    "properties": {
+                        "address": schema_ref("Address"),
+                        "name": { "type": "string" },
+                        "source": { "type": "string", "enum": ["WellKnown", "Ens"] },
+                    },
+                },
+                { "type": "null" },
+            ],
+        },
+        "NullableTokenInfo": {
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["address", "symbol", "decimals"],
+                    "properties": {
+                        "address": schema_ref("Address"),
+                        "symbol": { "type": "string" },
+                        "decimals": { "type": "integer", "minimum": 0, "maximum": 255 },
+                    },
+                },
+                { "type": "null" },
+            ],
+        },
+        "StorageDiff": {
+            "description": "Changed slot to its `[before, after]` values",
+            "type": "object",
+            "additionalProperties": {
+                "type": "array",
+                "items": schema_ref("U256"),
+                "minItems": 2,
+                "maxItems": 2,
+            },
+        },
+        "EvalResult": {
+            "description": "`Ok` with the evaluated value, or `Err` with an error message",
+            "oneOf": [
+                { "type": "object", "required": ["Ok"], "properties": { "Ok": {} } },
+                {
+                    "type": "object",
+                    "required": ["Err"],
+                    "properties": { "Err": { "type": "string" } },
+                },
+            ],
+        },
+        "Breakpoint": {
+            "type": "object",
+            "properties": {
+                "loc": {
+                    "oneOf": [
+                        {
+                            "type": "object",
+                            "required": ["Opcode"],
+                            "properties": {
+                                "Opcode": {
+                                    "type": "object",
+                                    "required": ["bytecode_address", "pc"],
+                                    "properties": {
+                                        "bytecode_address": schema_ref("Address"),
+                                        "pc": { "type": "integer", "minimum": 0 },
+                                    },
+                                },
+                            },
+                        },
+                        {
+                            "type": "object",
+                            "required": ["Source"],
+                            "properties": {
+                                "Source": {
+                                    "type": "object",
+                                    "required": ["bytecode_address", "line_number", "file_path"],
+                                    "properties": {
+                                        "bytecode_address": schema_ref("Address"),
+                                        "line_number": { "type": "integer", "minimum": 1 },
+                                        "file_path": { "type": "string" },
+                                    },
+                                },
+                            },
+                        },
+                        { "type": "null" },
+                    ],
+                },
+                "condition": {
+                    "description": "Expression that must evaluate to true for the breakpoint to hit",
+                    "type": ["string", "null"],
+                },
+            },
+        },
+        "Finding": {
+            "type": "object",
+            "required": ["kind", "severity", "address", "message"],
+            "properties": {
+                "kind": { "description": "Detector-specific details", "type": "object" },
+                "severity": { "enum": ["Info", "Low", "Medium", "High"] },
+                "address": schema_ref("Address"),
+                "trace_entry_id": { "type": ["integer", "null"] },
+                "snapshot_id": { "type": ["integer", "null"] },
+                "message": { "type": "string" },
+            },
+        },
+        "FindingList": { "type": "array", "items": schema_ref("Finding") },
+        "BridgeMessage": {
+            "type": "object",
+            "required": [
+                "direction", "l2Name", "l1ChainId", "l2ChainId", "traceEntryId", "sender",
+                "value", "data", "messageHash",
+            ],
+            "properties": {
+                "direction": { "enum": ["deposit", "withdrawal"] },
+                "l2Name": { "type": "string", "description": "Name of the L2 chain" },
+                "l1ChainId": { "type": "integer" },
+                "l2ChainId": { "type": "integer" },
+                "traceEntryId": { "type": "integer", "description": "Trace entry that emitted the bridge event" },
+                "snapshotId": { "type": ["integer", "null"], "description": "Emitting snapshot, if known" },
+                "sender": schema_ref("Address"),
+                "target": { "description": "Target of the message, null for contract creations", "oneOf": [schema_ref("Address"), { "type": "null" }] },
+                "value": schema_ref("U256"),
+                "data": schema_ref("Bytes"),
+                "messageHash": {
+                    "description": "L2 transaction hash of a deposit, or withdrawal hash of a withdrawal",
+                    "$ref": "#/components/schemas/Hash",
+                },
+                "correlatedTxHash": {
+                    "description": "Transaction on the other chain, if known",
+                    "oneOf": [schema_ref("Hash"), { "type": "null" }],
+                },
+            },
+        },
+        "BridgeMessageList": { "type": "array", "items": schema_ref("BridgeMessage") },
+        "TraceEntryId": { "type": "integer", "minimum": 0 },
+        "Integer": { "type": "integer", "minimum": 0 },
+        "RerunResult": {
+            "type": "object",
+            "required": ["success", "gasUsed", "output", "unappliedOverrides", "trace"],
+            "properties": {
+                "success": { "type": "boolean" },
+                "gasUsed": { "type": "integer" },
+                "output": schema_ref("Bytes"),
+                "haltReason": { "type": ["string", "null"] },
+                "unappliedOverrides": {
+                    "description": "Overrides whose snapshot was not reached by the re-execution",
+                    "type": "array",
+                    "items": { "type": "object" },
+                },
+                "trace": { "type": "array", "items": schema_ref("TraceEntry") },
+            },
+        },
+        "AccessList": {
+            "description": "EIP-2930 access list",
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["address", "storageKeys"],
+                "properties": {
+                    "address": schema_ref("Address"),
+                    "storageKeys": { "type": "array", "items": schema_ref("Hash") },
+                },
+            },
+        },
+        "CoverageFormat": { "enum": ["json", "lcov"] },
+        "FlamegraphWeight": { "enum": ["gas", "time"] },
+        "Coverage": {
+            "description": "Coverage report object for `json`, or an LCOV tracefile for `lcov`",
+            "oneOf": [{ "type": "object" }, { "type": "string" }],
+        },
+        "GasHeatmap": {
+            "type": "object",
+            "required": ["bytecode_address", "lines", "instructions"],
+            "properties": {
+                "bytecode_address": schema_ref("Address"),
+                "lines": {
+                    "description": "Source path to gas spent per 1-based line number",
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "object",
+                        "additionalProperties": { "type": "integer" },
+                    },
+                },
+                "instructions": {
+                    "description": "Program counter to gas spent",
+                    "type": "object",
+                    "additionalProperties": { "type": "integer" },
+                },
+            },
+        },
+        "OpcodeHistogramList": {
+            "description": "Executed program counters and hot basic blocks per bytecode address",
+            "type": "array",
+            "items": { "type": "object" },
+        },
+        "Report": {
+            "description": "Structured summary of the transaction",
+            "type": "object",
+        },
+        "Metrics": {
+            "description": "Performance metrics recorded while preparing the transaction",
+            "type": "object",
+            "properties": {
+                "engineVersion": { "type": "string" },
+                "phases": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["phase", "durationMs"],
+                        "properties": {
+                            "phase": {
+                                "enum": [
+                                    "fork", "replay", "download", "analyze", "compile",
+                                    "opcodeSnapshots", "instrument", "hookSnapshots",
+                                    "snapshotMerge", "finalize",
+                                ],
+                            },
+                            "durationMs": { "type": "number" },
+                        },
+                    },
+                },
+                "totalMs": { "type": "number" },
+                "cache": {
+                    "type": "object",
+                    "properties": {
+                        "hits": { "type": "integer" },
+                        "misses": { "type": "integer" },
+                        "hitRate": { "type": ["number", "null"] },
+                        "byCache": {
+                            "description": "Lookups of each cache, by cache name (e.g. `etherscan/mainnet/verified_sources`)",
+                            "type": "object",
+                            "additionalProperties": {
+                                "type": "object",
+                                "properties": {
+                                    "hits": { "type": "integer" },
+                                    "misses": { "type": "integer" },
+                                    "hitRate": { "type": ["number", "null"] },
+                                },
+                            },
+                        },
+                    },
+                },
+                "memoryRssBytes": { "type": ["integer", "null"] },
+                "snapshotCount": { "type": "integer" },
+                "traceEntries": { "type": "integer" },
+            },
+        },
+        "TraceFormat": { "enum": ["json", "cast", "tenderly", "parity"] },
+        "StackTraceFormat": { "enum": ["json", "text"] },
+        "StackTrace": {
+            "description": "Stack trace object for `json`, or the trace formatted like an exception trace for `text`",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["snapshot_id", "frames"],
+                    "properties": {
+                        "snapshot_id": schema_ref("SnapshotId"),
+                        "error": {
+                            "description": "Decoded reason if the frame of the snapshot fails",
+                            "type": ["string", "null"],
+                        },
+                        "frames": {
+                            "description": "Frames leading to the snapshot, innermost first",
+                            "type": "array",
+                            "items": schema_ref("StackFrame"),
+                        },
+                    },
+                },
+                { "type": "string" },
+            ],
+        },
+        "StackFrame": {
+            "type": "object",
+            "required": ["trace_entry_id", "address", "contract", "function"],
+            "properties": {
+                "trace_entry_id": schema_ref("TraceEntryId"),
+                "snapshot_id": {
+                    "description": "Snapshot the location is taken from",
+                    "oneOf": [schema_ref("SnapshotId"), { "type": "null" }],
+                },
+                "address": schema_ref("Address"),
+                "contract": { "type": "string" },
+                "function": { "type": "string" },
+                "path": { "type": ["string", "null"] },
+                "line": { "description": "1-based", "type": ["integer", "null"] },
+                "column": { "description": "1-based", "type": ["integer", "null"] },
+                "pc": {
+                    "description": "Program counter, for code without verified source",
+                    "type": ["integer", "null"],
+                },
+            },
+        },
+        "SessionId": { "type": "integer", "minimum": 1 },
+        "SessionInfo": {
+            "type": "object",
+            "required": ["id", "tx_hash", "created_at", "status"],
+            "properties": {
+                "id": schema_ref("SessionId"),
+                "tx_hash": schema_ref("Hash"),
+                "created_at": { "description": "Seconds since the Unix epoch", "type": "integer" },
+                "status": { "enum": ["preparing", "ready", "failed", "cancelled"] },
+                "snapshot_count": { "description": "Set once ready", "type": "integer" },
+                "error": { "description": "Set if preparation failed", "type": "string" },
+                "mocks": schema_ref("MockContractList"),
+                "focus": schema_ref("TraceFocus"),
+            },
+        },
+        "MockContract": {
+            "type": "object",
+            "required": ["address", "source"],
+            "properties": {
+                "address": schema_ref("Address"),
+                "source": { "description": "Solidity source code", "type": "string" },
+                "contract": { "description": "Contract to install if the source defines several", "type": "string" },
+                "solcVersion": { "description": "Compiler version, detected from the `pragma solidity` if omitted", "type": "string" },
+            },
+        },
+        "MockContractList": { "type": "array", "items": schema_ref("MockContract") },
+        "TraceFocus": {
+            "description": "Trace entry, or n-th call (starting at 1) to a contract, whose subtree is recorded step by step",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "required": ["entry"],
+                    "properties": { "entry": { "type": "integer" } },
+                },
+                {
+                    "type": "object",
+                    "required": ["call"],
+                    "properties": {
+                        "call": {
+                            "type": "object",
+                            "required": ["address", "index"],
+                            "properties": {
+                                "address": schema_ref("Address"),
+                                "index": { "type": "integer", "minimum": 1 },
+                            },
+                        },
+                    },
+                },
+            ],
+        },
+        "SessionInfoList": { "type": "array", "items": schema_ref("SessionInfo") },
+        "EventTopic": { "enum": ["progress", "navigation", "findings"] },
+        "SubscriptionId": { "type": "string" },
+        "ExportedTrace": {
+            "description": "Decoded trace object for `json`, `tenderly`, and `parity`, or a call tree string for `cast`",
+            "oneOf": [{ "type": "object" }, { "type": "array" }, { "type": "string" }],
+        },
+    });
+
+    match schemas {
+        Value::Object(map) => map,
+        _ => unreachable!("schemas are built from an object literal"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_method_names_are_unique() {
+        let mut names = HashSet::new();
+        for method in METHODS {
+            assert!(method.name.starts_with("edb_"), "{} is not an edb_ method", method.name);
+            assert!(names.insert(method.name), "{} is documented twice", method.name);
+        }
+    }
+
+    #[test]
+    fn test_schema_references_resolve() {
+        let schemas = component_schemas();
+        for method in METHODS {
+            assert!(schemas.contains_key(method.result), "missing schema {}", method.result);
+            for param in method.params {
+                assert!(schemas.contains_key(param.schema), "missing schema {}", param.schema);
+            }
+        }
+
+        let document = openrpc_document();
+        assert_eq!(document["openrpc"], OPENRPC_VERSION);
+        assert_eq!(document["methods"].as_array().unwrap().len(), METHODS.len() + 1);
+    }
+}
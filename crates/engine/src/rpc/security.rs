@@ -0,0 +1,220 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Authentication and TLS for the RPC servers.
+//!
+//! By default, RPC servers listen on the loopback interface and accept any request over
+//! plain HTTP. [`RpcSecurityConfig`] can make them reachable from other machines, in
+//! which case they should require clients to present a bearer token and terminate TLS
+//! with rustls:
+//!
+//! - **Authentication**: every request except `GET /health` must carry an
+//!   `Authorization: Bearer <token>` header. Since browsers cannot set headers on
+//!   WebSocket handshakes, `/ws` also accepts the token as a `token` query parameter.
+//! - **Read-only access**: additional tokens registered in [`ReadOnlyTokens`] are
+//!   accepted too, but requests carrying them cannot call methods that change the state
+//!   shared by the clients of a session (see [`crate::rpc::methods::is_mutating_method`]).
+//! - **TLS**: the server is served over HTTPS (and WSS) with the given PEM certificate
+//!   chain and private key.
+
+use std::{
+    collections::HashSet,
+    net::{IpAddr, Ipv4Addr},
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// TLS certificate and key used to serve an RPC server over HTTPS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// Path to the PEM-encoded certificate chain
+    pub cert_path: PathBuf,
+    /// Path to the PEM-encoded private key
+    pub key_path: PathBuf,
+}
+
+/// Bearer tokens granting read-only access, shared by all servers of a configuration.
+///
+/// Tokens can be added and removed while the servers run, e.g., when observers join
+/// and leave a session.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOnlyTokens(Arc<RwLock<HashSet<String>>>);
+
+impl ReadOnlyTokens {
+    /// Accept a token for read-only access
+    pub fn insert(&self, token: impl Into<String>) {
+        self.0.write().expect("read-only tokens poisoned").insert(token.into());
+    }
+
+    /// Stop accepting a token
+    pub fn remove(&self, token: &str) {
+        self.0.write().expect("read-only tokens poisoned").remove(token);
+    }
+
+    /// Whether a token grants read-only access
+    pub fn contains(&self, token: &str) -> bool {
+        let tokens = self.0.read().expect("read-only tokens poisoned");
+        tokens.iter().any(|candidate| constant_time_eq(candidate.as_bytes(), token.as_bytes()))
+    }
+}
+
+impl PartialEq for ReadOnlyTokens {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for ReadOnlyTokens {}
+
+/// Access control of an RPC server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcSecurityConfig {
+    /// Bearer token clients must present, if any
+    pub auth_token: Option<String>,
+    /// Tokens accepted in place of `auth_token` for read-only access
+    pub read_only_tokens: ReadOnlyTokens,
+    /// TLS configuration, if the server should be served over HTTPS
+    pub tls: Option<TlsConfig>,
+    /// Address the server listens on, the loopback interface if not set
+    pub bind_addr: Option<IpAddr>,
+}
+
+impl RpcSecurityConfig {
+    /// Listen on the given address, e.g., `0.0.0.0` to accept remote connections
+    pub fn with_bind_addr(mut self, addr: IpAddr) -> Self {
+        self.bind_addr = Some(addr);
+        self
+    }
+
+    /// Get the address the server listens on
+    pub fn bind_addr(&self) -> IpAddr {
+        self.bind_addr.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+
+    /// Require clients to present the given bearer token
+    pub fn with_auth_token(mut self, token: String) -> Self {
+        self.auth_token = Some(token);
+        self
+    }
+
+    /// Serve over HTTPS with the given PEM certificate chain and private key
+    pub fn with_tls(mut self, cert_path: PathBuf, key_path: PathBuf) -> Self {
+        self.tls = Some(TlsConfig { cert_path, key_path });
+        self
+    }
+
+    /// Get the URL scheme clients should use to connect
+    pub fn scheme(&self) -> &'static str {
+        if self.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        }
+    }
+}
+
+/// Tokens accepted by [`require_bearer_token`]
+#[derive(Clone)]
+pub(crate) struct BearerTokens {
+    /// Token granting full access
+    pub(crate) token: Arc<str>,
+    /// Tokens granting read-only access
+    pub(crate) read_only: ReadOnlyTokens,
+}
+
+/// Marks a request authenticated with a read-only token
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReadOnlyAccess;
+
+/// Middleware rejecting requests without an accepted bearer token
+///
+/// Requests authenticated with a read-only token are marked with [`ReadOnlyAccess`].
+pub(crate) async fn require_bearer_token(
+    State(tokens): State<BearerTokens>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if request.uri().path() == "/health" {
+        return next.run(request).await;
+    }
+
+    let header_token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let query_token = request.uri().query().and_then(|query| {
+        query.split('&').find_map(|pair| pair.strip_prefix("token=")).map(str::to_string)
+    });
+
+    let Some(token) = header_token.map(str::to_string).or(query_token) else {
+        return unauthorized();
+    };
+    if !constant_time_eq(token.as_bytes(), tokens.token.as_bytes()) {
+        if !tokens.read_only.contains(&token) {
+            return unauthorized();
+        }
+        request.extensions_mut().insert(ReadOnlyAccess);
+    }
+
+    next.run(request).await
+}
+
+/// Response to requests without an accepted bearer token
+fn unauthorized() -> Response {
+    (StatusCode::UNAUTHORIZED, [(header::WWW_AUTHENTICATE, "Bearer")]).into_response()
+}
+
+/// Compare two byte strings in time independent of where they differ
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn test_read_only_tokens() {
+        let tokens = ReadOnlyTokens::default();
+        tokens.insert("observer");
+        assert!(tokens.contains("observer"));
+        assert!(!tokens.contains("driver"));
+
+        // Clones share the same set
+        let config = RpcSecurityConfig { read_only_tokens: tokens.clone(), ..Default::default() };
+        config.read_only_tokens.remove("observer");
+        assert!(!tokens.contains("observer"));
+    }
+}
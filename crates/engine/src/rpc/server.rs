@@ -26,6 +26,8 @@
 //! - **Thread-safe**: Uses Arc-wrapped EngineContext for safe shared access
 //! - **Graceful shutdown**: Supports clean server termination
 //! - **Health monitoring**: Provides health check endpoint for monitoring
+//! - **Access control**: Optional bearer-token authentication and TLS, see [`super::security`]
+//! - **Unix sockets**: Can listen on a unix domain socket instead of a TCP port
 //! - **Error handling**: Comprehensive error reporting with JSON-RPC 2.0 compliance
 //!
 //! # Server Architecture
@@ -38,26 +40,39 @@
 //!
 //! # Endpoints
 //!
-//! - `POST /` - Main JSON-RPC endpoint for debugging methods
+//! - `POST /` - Main JSON-RPC endpoint for debugging methods, accepting single and batch requests
 //! - `GET /health` - Health check endpoint returning server status
+//! - `GET /status` - Preparation status of the transaction, as recorded by the engine that prepared it (see [`super::status_server`])
+//! - `GET /openrpc.json` - OpenRPC document describing all debugging methods
+//! - `GET /ws` - WebSocket endpoint for JSON-RPC requests and `edb_subscribe` event streams
 
-use super::methods::MethodHandler;
-use super::types::{RpcError, RpcRequest, RpcResponse};
+use super::methods::{is_mutating_method, MethodHandler};
+use super::openrpc::openrpc_document;
+use super::security::{require_bearer_token, BearerTokens, ReadOnlyAccess, RpcSecurityConfig};
+use super::subscription::handle_socket;
+use super::types::{error_codes, RpcError, RpcId, RpcRequest, RpcResponse, RpcResponsePayload};
 use super::utils::get_default_rpc_port;
-use crate::EngineContext;
+use crate::{EngineContext, EventBus, PreparationId, PreparationTracker};
 use axum::{
-    extract::{Json as JsonExtract, State},
-    response::Json as JsonResponse,
+    extract::{ws::WebSocketUpgrade, Json as JsonExtract, State},
+    http::StatusCode,
+    middleware,
+    response::{IntoResponse, Json as JsonResponse, Response},
     routing::{get, post},
-    Router,
+    Extension, Router,
 };
+use axum_server::tls_rustls::RustlsConfig;
+use edb_common::PreparationStatus;
 use eyre::Result;
+use futures::future::{join_all, BoxFuture};
 use revm::database::CacheDB;
 use revm::{Database, DatabaseCommit, DatabaseRef};
 use std::net::SocketAddr;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::oneshot;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 /// Handle to control a running RPC server.
 ///
@@ -91,6 +106,40 @@ impl RpcServerHandle {
     }
 }
 
+/// Handle to control an RPC server listening on a unix domain socket.
+///
+/// Access to the socket is governed by filesystem permissions: the socket file is
+/// created readable and writable by its owner only, and removed once the server stops.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct UnixRpcServerHandle {
+    /// Path of the socket the server is listening on
+    pub path: PathBuf,
+    /// Shutdown signal sender (consumed when shutting down)
+    shutdown_tx: oneshot::Sender<()>,
+}
+
+#[cfg(unix)]
+impl UnixRpcServerHandle {
+    /// Get the socket path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the URL clients such as the TUI use to connect, e.g., `unix:///tmp/edb.sock`
+    pub fn url(&self) -> String {
+        format!("unix://{}", self.path.display())
+    }
+
+    /// Gracefully shutdown the RPC server
+    pub fn shutdown(self) -> Result<()> {
+        if self.shutdown_tx.send(()).is_err() {
+            warn!("RPC server already shut down");
+        }
+        Ok(())
+    }
+}
+
 /// Thread-safe RPC state for Axum request handling.
 ///
 /// This wrapper provides the shared state needed by Axum handlers.
@@ -124,6 +173,13 @@ where
     context: Arc<EngineContext<DB>>,
     /// Method handler for dispatching RPC requests to appropriate implementations
     method_handler: Arc<MethodHandler<DB>>,
+    /// Event bus streamed to WebSocket subscribers
+    events: EventBus,
+    /// Authentication and TLS settings
+    security: RpcSecurityConfig,
+    /// Preparation of the served context and the tracker recording it, reported at
+    /// `/status`
+    preparation: Option<(PreparationTracker, PreparationId)>,
 }
 
 impl<DB> DebugRpcServer<DB>
@@ -134,10 +190,46 @@ where
 {
     /// Create a new debug RPC server
     pub fn new(context: EngineContext<DB>) -> Self {
-        let context = Arc::new(context);
-        let method_handler = Arc::new(MethodHandler::new(context.clone()));
+        Self::from_shared(Arc::new(context))
+    }
+
+    /// Create a new debug RPC server for a context that is also used elsewhere
+    pub fn from_shared(context: Arc<EngineContext<DB>>) -> Self {
+        let events = EventBus::default();
+        let method_handler =
+            Arc::new(MethodHandler::new(context.clone()).with_events(events.clone()));
+
+        Self {
+            context,
+            method_handler,
+            events,
+            security: RpcSecurityConfig::default(),
+            preparation: None,
+        }
+    }
 
-        Self { context, method_handler }
+    /// Require authentication and/or TLS as configured
+    pub fn with_security(mut self, security: RpcSecurityConfig) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Report the status of the given preparation at `/status`, as recorded by the
+    /// tracker of the [`crate::Engine`] that prepared the context
+    ///
+    /// Without a preparation, the transaction is reported as ready.
+    pub fn with_preparation(mut self, preparations: PreparationTracker, id: PreparationId) -> Self {
+        self.preparation = Some((preparations, id));
+        self
+    }
+
+    /// Share the given event bus with WebSocket subscribers, e.g., the bus of the
+    /// [`crate::Engine`] that prepared the context
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.method_handler =
+            Arc::new(MethodHandler::new(self.context.clone()).with_events(events.clone()));
+        self.events = events;
+        self
     }
 
     /// Start the RPC server
@@ -151,47 +243,38 @@ where
     /// This method creates the Axum server with Send+Sync state, leveraging
     /// the now thread-safe EngineContext.
     pub async fn start_on_port(self, port: u16) -> Result<RpcServerHandle> {
-        // Create the Axum app with the server as state
-        let app = Router::new()
-            .route("/", post(handle_rpc_request))
-            .route("/health", get(health_check))
-            .with_state(RpcState { server: Arc::new(self) });
-
-        let addr = SocketAddr::from(([127, 0, 0, 1], port));
-        let listener = tokio::net::TcpListener::bind(addr).await?;
-        let actual_addr = listener.local_addr()?;
-
-        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let security = self.security.clone();
+        let handle = serve_router(self.into_router(), port, &security).await?;
+        info!("Debug RPC server started on {}", handle.addr());
 
-        // Spawn the Axum server
-        tokio::spawn(async move {
-            axum::serve(listener, app)
-                .with_graceful_shutdown(async {
-                    shutdown_rx.await.ok();
-                })
-                .await
-                .expect("RPC server failed");
-        });
+        Ok(handle)
+    }
 
-        info!("Debug RPC server started on {}", actual_addr);
+    /// Start the RPC server on a unix domain socket
+    ///
+    /// This avoids opening a TCP port on shared machines. A stale socket file left at
+    /// `path` by a previous server is replaced.
+    #[cfg(unix)]
+    pub async fn start_on_unix_socket(
+        self,
+        path: impl Into<PathBuf>,
+    ) -> Result<UnixRpcServerHandle> {
+        let security = self.security.clone();
+        let handle = serve_router_unix(self.into_router(), path.into(), &security).await?;
+        info!("Debug RPC server started on {}", handle.url());
 
-        Ok(RpcServerHandle { addr: actual_addr, shutdown_tx })
+        Ok(handle)
     }
 
-    /// Handle an RPC request (called from worker thread)
-    async fn handle_request(&self, request: RpcRequest) -> RpcResponse {
-        let id = request.id.clone();
-
-        // Dispatch to method handler
-        match self.method_handler.handle_method(&request.method, request.params).await {
-            Ok(result) => {
-                RpcResponse { jsonrpc: "2.0".to_string(), result: Some(result), error: None, id }
-            }
-            Err(err) => {
-                error!(target: "rpc", "Error handling RPC request: {:?}", err);
-                RpcResponse { jsonrpc: "2.0".to_string(), result: None, error: Some(err), id }
-            }
-        }
+    /// Create the Axum app with the server as state
+    fn into_router(self) -> Router {
+        Router::new()
+            .route("/", post(handle_rpc_request))
+            .route("/health", get(health_check))
+            .route("/status", get(preparation_status))
+            .route("/openrpc.json", get(openrpc_schema))
+            .route("/ws", get(handle_ws_upgrade))
+            .with_state(RpcState { server: Arc::new(self) })
     }
 
     /// Get total snapshot count (stateless)
@@ -215,35 +298,377 @@ where
     pub fn context(&self) -> &Arc<EngineContext<DB>> {
         &self.context
     }
+
+    /// Get the event bus streamed to WebSocket subscribers
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+}
+
+impl<DB> RequestHandler for DebugRpcServer<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    fn handle_request(&self, request: RpcRequest) -> BoxFuture<'_, RpcResponse> {
+        Box::pin(async move {
+            // Dispatch to method handler
+            let result = self.method_handler.handle_method(&request.method, request.params).await;
+            into_response(request.id, result)
+        })
+    }
+}
+
+/// Handler rejecting the methods that change shared session state, serving clients
+/// authenticated with a read-only token
+pub(crate) struct ReadOnly<H>(pub(crate) Arc<H>);
+
+impl<H: RequestHandler> RequestHandler for ReadOnly<H> {
+    fn handle_request(&self, request: RpcRequest) -> BoxFuture<'_, RpcResponse> {
+        if is_mutating_method(&request.method) {
+            let error = RpcError {
+                code: error_codes::READ_ONLY_ACCESS,
+                message: format!(
+                    "Method '{}' is not available with read-only access",
+                    request.method
+                ),
+                data: None,
+            };
+            return Box::pin(async move { into_response(request.id, Err(error)) });
+        }
+        self.0.handle_request(request)
+    }
+}
+
+/// Dispatch a payload, rejecting mutating methods if the request has read-only access
+pub(crate) async fn dispatch_with_access<H: RequestHandler>(
+    handler: Arc<H>,
+    access: Option<Extension<ReadOnlyAccess>>,
+    payload: serde_json::Value,
+) -> Option<RpcResponsePayload> {
+    match access {
+        Some(_) => dispatch_payload(Arc::new(ReadOnly(handler)), payload).await,
+        None => dispatch_payload(handler, payload).await,
+    }
+}
+
+/// Transport-independent handler of validated JSON-RPC requests.
+///
+/// Implemented by every server exposing a JSON-RPC endpoint, so that batching and the
+/// WebSocket transport are shared between them.
+pub(crate) trait RequestHandler: Send + Sync + 'static {
+    /// Handle a single request whose JSON-RPC version has already been validated
+    fn handle_request(&self, request: RpcRequest) -> BoxFuture<'_, RpcResponse>;
+}
+
+/// Build the response to a request from the result of its method
+pub(crate) fn into_response(id: RpcId, result: Result<serde_json::Value, RpcError>) -> RpcResponse {
+    match result {
+        Ok(result) => {
+            RpcResponse { jsonrpc: "2.0".to_string(), result: Some(result), error: None, id }
+        }
+        Err(err) => {
+            error!(target: "rpc", "Error handling RPC request: {:?}", err);
+            RpcResponse { jsonrpc: "2.0".to_string(), result: None, error: Some(err), id }
+        }
+    }
+}
+
+/// Serve a router on a port in a background task
+///
+/// The server listens on the bind address of `security`, and requests are authenticated
+/// and served over TLS according to it. Besides the RPC servers of the engine, this
+/// serves e.g. the WebSocket server of `edb server`, so that all servers share the same
+/// access control.
+pub async fn serve_router(
+    app: Router,
+    port: u16,
+    security: &RpcSecurityConfig,
+) -> Result<RpcServerHandle> {
+    let app = with_authentication(app, security);
+
+    let addr = SocketAddr::new(security.bind_addr(), port);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let actual_addr = listener.local_addr()?;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+    match &security.tls {
+        None => {
+            // Spawn the Axum server
+            tokio::spawn(async move {
+                axum::serve(listener, app)
+                    .with_graceful_shutdown(async {
+                        shutdown_rx.await.ok();
+                    })
+                    .await
+                    .expect("RPC server failed");
+            });
+        }
+        Some(tls) => {
+            let config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+            let handle = axum_server::Handle::new();
+
+            tokio::spawn({
+                let handle = handle.clone();
+                async move {
+                    shutdown_rx.await.ok();
+                    handle.graceful_shutdown(None);
+                }
+            });
+
+            // Spawn the TLS-terminating server on the already bound listener
+            let listener = listener.into_std()?;
+            tokio::spawn(async move {
+                axum_server::from_tcp_rustls(listener, config)
+                    .handle(handle)
+                    .serve(app.into_make_service())
+                    .await
+                    .expect("RPC server failed");
+            });
+        }
+    }
+
+    Ok(RpcServerHandle { addr: actual_addr, shutdown_tx })
+}
+
+/// Serve a router on a unix domain socket in a background task
+///
+/// TLS is not applied, since traffic never leaves the machine; bearer-token
+/// authentication still is, if configured.
+#[cfg(unix)]
+pub(crate) async fn serve_router_unix(
+    app: Router,
+    path: PathBuf,
+    security: &RpcSecurityConfig,
+) -> Result<UnixRpcServerHandle> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let app = with_authentication(app, security);
+
+    // Only a stale socket is replaced, never a file the path points at by mistake
+    match std::fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(&path)?,
+        Ok(_) => {
+            return Err(eyre::eyre!(
+                "{} already exists and is not a socket, refusing to replace it",
+                path.display()
+            ))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    // The socket is bound in a directory only the owner can enter, and moved into place
+    // once restricted to the owner, so it is never reachable with the umask's permissions
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let private_dir = tempfile::Builder::new().prefix(".edb-rpc").tempdir_in(parent)?;
+    let bound_path = private_dir.path().join("rpc.sock");
+    let listener = tokio::net::UnixListener::bind(&bound_path)?;
+    std::fs::set_permissions(&bound_path, std::fs::Permissions::from_mode(0o600))?;
+    std::fs::rename(&bound_path, &path)?;
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+    let socket_path = path.clone();
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                shutdown_rx.await.ok();
+            })
+            .await
+        {
+            error!("RPC server on {} failed: {}", socket_path.display(), e);
+        }
+
+        if let Err(e) = std::fs::remove_file(&socket_path) {
+            debug!("Failed to remove socket {}: {}", socket_path.display(), e);
+        }
+    });
+
+    Ok(UnixRpcServerHandle { path, shutdown_tx })
+}
+
+/// Require the configured bearer token, or one of the read-only tokens, on every
+/// request to the router
+fn with_authentication(app: Router, security: &RpcSecurityConfig) -> Router {
+    match &security.auth_token {
+        Some(token) => {
+            let tokens = BearerTokens {
+                token: Arc::from(token.as_str()),
+                read_only: security.read_only_tokens.clone(),
+            };
+            app.layer(middleware::from_fn_with_state(tokens, require_bearer_token))
+        }
+        None => app,
+    }
 }
 
 /// Handle RPC requests directly with the thread-safe server
 async fn handle_rpc_request<DB>(
     State(state): State<RpcState<DB>>,
-    JsonExtract(request): JsonExtract<RpcRequest>,
-) -> JsonResponse<RpcResponse>
+    access: Option<Extension<ReadOnlyAccess>>,
+    JsonExtract(payload): JsonExtract<serde_json::Value>,
+) -> Response
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    into_http_response(dispatch_with_access(state.server, access, payload).await)
+}
+
+/// Answer an HTTP request with the JSON-RPC response payload, or with `204 No Content`
+/// if the payload only consisted of notifications
+pub(crate) fn into_http_response(payload: Option<RpcResponsePayload>) -> Response {
+    match payload {
+        Some(payload) => JsonResponse(payload).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+/// Upgrade a connection to the WebSocket endpoint
+async fn handle_ws_upgrade<DB>(
+    State(state): State<RpcState<DB>>,
+    access: Option<Extension<ReadOnlyAccess>>,
+    ws: WebSocketUpgrade,
+) -> Response
 where
     DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
     <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
     <DB as Database>::Error: Clone + Send + Sync,
 {
+    let events = state.server.events().clone();
+    let tx_hash = Some(state.server.context().tx_hash);
+    ws.on_upgrade(move |socket| async move {
+        match access {
+            Some(_) => {
+                handle_socket(socket, Arc::new(ReadOnly(state.server)), events, tx_hash).await
+            }
+            None => handle_socket(socket, state.server, events, tx_hash).await,
+        }
+    })
+}
+
+/// Status endpoint, reporting the status recorded by the preparation tracker of the
+/// server, or the transaction as ready if it has none
+async fn preparation_status<DB>(
+    State(state): State<RpcState<DB>>,
+) -> JsonResponse<PreparationStatus>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    let tx_hash = state.server.context().tx_hash;
+    let status = state
+        .server
+        .preparation
+        .as_ref()
+        .and_then(|(preparations, id)| preparations.status(*id))
+        .unwrap_or_else(|| PreparationStatus::ready(tx_hash));
+    JsonResponse(status)
+}
+
+/// Dispatch a JSON-RPC payload received over any transport
+///
+/// Accepts either a single request object or a batch (a JSON array of request
+/// objects). Requests of a batch are processed concurrently and answered in order.
+///
+/// Notifications are executed but not answered, so `None` is returned if the payload
+/// only consisted of notifications.
+pub(crate) async fn dispatch_payload<H: RequestHandler>(
+    handler: Arc<H>,
+    payload: serde_json::Value,
+) -> Option<RpcResponsePayload> {
+    let requests = match payload {
+        serde_json::Value::Array(requests) => requests,
+        request => {
+            return handle_single_request(handler, request).await.map(RpcResponsePayload::Single)
+        }
+    };
+
+    // An empty batch is answered with a single error, as per the JSON-RPC 2.0 spec
+    if requests.is_empty() {
+        return Some(RpcResponsePayload::Single(invalid_request(
+            RpcId::Null,
+            "Invalid Request - empty batch",
+        )));
+    }
+
+    debug!("Handling batch of {} RPC request(s)", requests.len());
+
+    // Spawn each request so that the methods of a batch run in parallel
+    let handles: Vec<_> = requests
+        .into_iter()
+        .map(|request| tokio::spawn(handle_single_request(handler.clone(), request)))
+        .collect();
+
+    let mut responses = Vec::with_capacity(handles.len());
+    for handle in join_all(handles).await {
+        let response = handle.unwrap_or_else(|e| {
+            Some(RpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: None,
+                error: Some(RpcError {
+                    code: error_codes::INTERNAL_ERROR,
+                    message: format!("Request handler failed: {e}"),
+                    data: None,
+                }),
+                id: RpcId::Null,
+            })
+        });
+        responses.extend(response);
+    }
+
+    // A batch made only of notifications is not answered at all
+    (!responses.is_empty()).then_some(RpcResponsePayload::Batch(responses))
+}
+
+/// Validate and dispatch a single request object, returning no response for notifications
+async fn handle_single_request<H: RequestHandler>(
+    handler: Arc<H>,
+    request: serde_json::Value,
+) -> Option<RpcResponse> {
+    let notification = RpcRequest::is_notification(&request);
+
+    // Invalid requests are always answered, since their id cannot be trusted
+    let request: RpcRequest = match serde_json::from_value(request) {
+        Ok(request) => request,
+        Err(e) => return Some(invalid_request(RpcId::Null, &format!("Invalid Request - {e}"))),
+    };
+
     // Validate JSON-RPC version
     if request.jsonrpc != "2.0" {
-        return JsonResponse(RpcResponse {
-            jsonrpc: "2.0".to_string(),
-            result: None,
-            error: Some(RpcError {
-                code: -32600,
-                message: "Invalid Request - JSON-RPC version must be 2.0".to_string(),
-                data: None,
-            }),
-            id: request.id.clone(),
-        });
+        return Some(invalid_request(request.id, "Invalid Request - JSON-RPC version must be 2.0"));
     }
 
     // Handle request directly (no channel proxy needed)
-    let response = state.server.handle_request(request).await;
-    JsonResponse(response)
+    let response = handler.handle_request(request).await;
+    (!notification).then_some(response)
+}
+
+/// Build an invalid request error response
+pub(crate) fn invalid_request(id: RpcId, message: &str) -> RpcResponse {
+    RpcResponse {
+        jsonrpc: "2.0".to_string(),
+        result: None,
+        error: Some(RpcError {
+            code: error_codes::INVALID_REQUEST,
+            message: message.to_string(),
+            data: None,
+        }),
+        id,
+    }
+}
+
+/// OpenRPC document endpoint
+pub(crate) async fn openrpc_schema() -> JsonResponse<serde_json::Value> {
+    JsonResponse(openrpc_document())
 }
 
 /// Health check endpoint
@@ -266,3 +691,179 @@ where
     let server = DebugRpcServer::new(context);
     server.start().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Handler answering every request with its method name
+    struct EchoHandler;
+
+    impl RequestHandler for EchoHandler {
+        fn handle_request(&self, request: RpcRequest) -> BoxFuture<'_, RpcResponse> {
+            Box::pin(async move { into_response(request.id, Ok(json!(request.method))) })
+        }
+    }
+
+    async fn dispatch(payload: serde_json::Value) -> Option<serde_json::Value> {
+        let response = dispatch_payload(Arc::new(EchoHandler), payload).await;
+        response.map(|response| serde_json::to_value(response).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_not_answered() {
+        let notification = json!({ "jsonrpc": "2.0", "method": "edb_ping" });
+        assert_eq!(dispatch(notification.clone()).await, None);
+        assert_eq!(dispatch(json!([notification.clone(), notification.clone()])).await, None);
+
+        let batch = json!([
+            notification,
+            { "jsonrpc": "2.0", "method": "edb_a", "id": 1 },
+            { "jsonrpc": "2.0", "method": "edb_b", "id": null },
+        ]);
+        let responses = dispatch(batch).await.unwrap();
+        assert_eq!(
+            responses,
+            json!([
+                { "jsonrpc": "2.0", "result": "edb_a", "id": 1 },
+                { "jsonrpc": "2.0", "result": "edb_b", "id": null },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_batch_entries_are_answered() {
+        let responses = dispatch(json!([1])).await.unwrap();
+        assert_eq!(responses[0]["error"]["code"], error_codes::INVALID_REQUEST);
+
+        let response = dispatch(json!([])).await.unwrap();
+        assert_eq!(response["error"]["code"], error_codes::INVALID_REQUEST);
+    }
+
+    async fn echo(
+        access: Option<Extension<ReadOnlyAccess>>,
+        JsonExtract(payload): JsonExtract<serde_json::Value>,
+    ) -> Response {
+        into_http_response(dispatch_with_access(Arc::new(EchoHandler), access, payload).await)
+    }
+
+    #[tokio::test]
+    async fn test_read_only_tokens_cannot_call_mutating_methods() {
+        let security = RpcSecurityConfig::default().with_auth_token("driver".to_string());
+        security.read_only_tokens.insert("observer");
+        let app = Router::new().route("/", post(echo));
+        let handle = serve_router(app, 0, &security).await.unwrap();
+        let url = format!("http://{}/", handle.addr());
+
+        let call = |token: &'static str, method: &'static str| {
+            let url = url.clone();
+            async move {
+                let request = json!({ "jsonrpc": "2.0", "method": method, "id": 1 });
+                let response = reqwest::Client::new()
+                    .post(url)
+                    .bearer_auth(token)
+                    .header("content-type", "application/json")
+                    .body(request.to_string())
+                    .send()
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = response.text().await.unwrap();
+                (status, serde_json::from_str(&body).unwrap_or(serde_json::Value::Null))
+            }
+        };
+
+        let (_, response) = call("driver", "edb_setStorageOverride").await;
+        assert_eq!(response["result"], "edb_setStorageOverride");
+
+        let (_, response) = call("observer", "edb_getSnapshotCount").await;
+        assert_eq!(response["result"], "edb_getSnapshotCount");
+        let (_, response) = call("observer", "edb_setStorageOverride").await;
+        assert_eq!(response["error"]["code"], error_codes::READ_ONLY_ACCESS);
+
+        let (status, _) = call("stranger", "edb_getSnapshotCount").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        // Observers that left are no longer accepted
+        security.read_only_tokens.remove("observer");
+        let (status, _) = call("observer", "edb_getSnapshotCount").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        handle.shutdown().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_on_unix_socket() {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("edb.sock");
+        let app = Router::new().route("/", post(echo));
+        let handle =
+            serve_router_unix(app, path.clone(), &RpcSecurityConfig::default()).await.unwrap();
+        assert_eq!(handle.url(), format!("unix://{}", path.display()));
+
+        // Only the owner may connect
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        let body = json!({ "jsonrpc": "2.0", "method": "edb_getSnapshotCount", "id": 1 });
+        let body = body.to_string();
+        let request = format!(
+            "POST / HTTP/1.1\r\nhost: localhost\r\ncontent-type: application/json\r\n\
+             content-length: {}\r\nconnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        let body: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["result"], "edb_getSnapshotCount");
+
+        // The socket file is removed once the server stops
+        handle.shutdown().unwrap();
+        for _ in 0..50 {
+            if !path.exists() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_on_unix_socket_keeps_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "notes").unwrap();
+
+        let app = Router::new().route("/", post(echo));
+        let error =
+            serve_router_unix(app, path.clone(), &RpcSecurityConfig::default()).await.unwrap_err();
+        assert!(error.to_string().contains("not a socket"), "{error}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "notes");
+
+        // A stale socket left by an earlier server is replaced
+        let stale = dir.path().join("edb.sock");
+        drop(std::os::unix::net::UnixListener::bind(&stale).unwrap());
+        let app = Router::new().route("/", post(echo));
+        let handle =
+            serve_router_unix(app, stale.clone(), &RpcSecurityConfig::default()).await.unwrap();
+        tokio::net::UnixStream::connect(&stale).await.unwrap();
+        handle.shutdown().unwrap();
+
+        // The directory the socket was bound in is gone
+        for entry in std::fs::read_dir(dir.path()).unwrap() {
+            let name = entry.unwrap().file_name();
+            assert!(name == "notes.txt" || name == "edb.sock", "{name:?} was left behind");
+        }
+    }
+}
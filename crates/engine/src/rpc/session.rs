@@ -0,0 +1,641 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Multiple debugging sessions served by a single engine process.
+//!
+//! Without a session manager, every prepared transaction gets its own
+//! [`DebugRpcServer`](super::DebugRpcServer) on its own port. The [`SessionManager`]
+//! instead keeps any number of prepared transactions in one long-running process and
+//! the [`SessionRpcServer`] serves all of them on a single port:
+//!
+//! - `POST /` - Session management methods:
+//!   - `edb_createSession` - `[tx_hash, mocks?, focus?]`, starts preparing a transaction in
+//!     the background, with optional mock contracts and [`TraceFocus`], and returns its
+//!     [`SessionInfo`] right away
+//!   - `edb_listSessions` - Lists all sessions with their preparation status
+//!   - `edb_cancel` - `[session_id]`, stops preparing a session, which then fails
+//!   - `edb_closeSession` - `[session_id]`, drops a session, cancelling its preparation
+//! - `POST /session/{id}` - All debugging methods (e.g., `edb_getTrace`) of a session
+//! - `GET /session/{id}/status` - Preparation phase and progress of a session
+//! - `GET /ws` - Session management methods and `edb_subscribe` event streams of all
+//!   sessions, e.g., to follow the preparation progress of a new session
+//!
+//! Creating a session for a transaction that already has a session (ready or still
+//! preparing) returns the existing one.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy_primitives::TxHash;
+use axum::{
+    extract::{ws::WebSocketUpgrade, Json as JsonExtract, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as JsonResponse, Response},
+    routing::{get, post},
+    Extension, Router,
+};
+use dashmap::DashMap;
+use edb_common::{PreparationState, PreparationStatus};
+use eyre::Result;
+use futures::future::BoxFuture;
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info};
+
+use super::{
+    methods::MethodHandler,
+    security::{ReadOnlyAccess, RpcSecurityConfig},
+    server::{
+        dispatch_with_access, into_http_response, into_response, openrpc_schema, serve_router,
+        ReadOnly, RequestHandler,
+    },
+    subscription::handle_socket,
+    types::{error_codes, RpcError, RpcRequest, RpcResponse},
+    RpcServerHandle,
+};
+use crate::{is_cancelled, Debugger, EventBus, MockContract, PreparationId, TraceFocus};
+
+/// Identifier of a debugging session managed by a [`SessionManager`]
+pub type SessionId = u64;
+
+/// Preparation status of a debugging session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum SessionStatus {
+    /// The transaction is still being prepared
+    Preparing,
+    /// The session is ready to be debugged
+    Ready {
+        /// Number of snapshots of the session
+        snapshot_count: usize,
+    },
+    /// Preparation failed
+    Failed {
+        /// Reason of the failure
+        error: String,
+    },
+    /// Preparation was cancelled before the session was ready
+    Cancelled,
+}
+
+/// Public information about a debugging session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    /// Identifier of the session, used in `/session/{id}`
+    pub id: SessionId,
+    /// Transaction debugged in the session
+    pub tx_hash: TxHash,
+    /// Creation time as seconds since the Unix epoch
+    pub created_at: u64,
+    /// Preparation status
+    #[serde(flatten)]
+    pub status: SessionStatus,
+    /// Mock contracts installed before replaying the transaction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mocks: Vec<MockContract>,
+    /// Call of the trace whose subtree is recorded step by step
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus: Option<TraceFocus>,
+}
+
+/// Debugging methods of a prepared session, independent of its database type.
+pub trait SessionHandler: Send + Sync {
+    /// Get the hash of the debugged transaction
+    fn tx_hash(&self) -> TxHash;
+
+    /// Get the number of snapshots of the session
+    fn snapshot_count(&self) -> usize;
+
+    /// Handle a debugging method call
+    fn handle_method<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Value>,
+    ) -> BoxFuture<'a, Result<Value, RpcError>>;
+}
+
+impl<DB> SessionHandler for MethodHandler<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    fn tx_hash(&self) -> TxHash {
+        self.context().tx_hash
+    }
+
+    fn snapshot_count(&self) -> usize {
+        self.context().snapshots.len()
+    }
+
+    fn handle_method<'a>(
+        &'a self,
+        method: &'a str,
+        params: Option<Value>,
+    ) -> BoxFuture<'a, Result<Value, RpcError>> {
+        Box::pin(MethodHandler::handle_method(self, method, params))
+    }
+}
+
+/// A managed session and, once prepared, its method handler
+struct ManagedSession {
+    info: SessionInfo,
+    /// Preparation of the session in the tracker of the debugger
+    preparation: PreparationId,
+    handler: Option<Arc<dyn SessionHandler>>,
+}
+
+/// Registry of the debugging sessions of one engine process.
+pub struct SessionManager {
+    /// Debugger preparing new sessions
+    debugger: Arc<Debugger>,
+    /// Identifier of the next session
+    next_id: AtomicU64,
+    /// All sessions, by identifier
+    sessions: DashMap<SessionId, ManagedSession>,
+}
+
+impl SessionManager {
+    /// Create a session manager preparing transactions with the given debugger
+    pub fn new(debugger: Debugger) -> Self {
+        Self { debugger: Arc::new(debugger), next_id: AtomicU64::new(1), sessions: DashMap::new() }
+    }
+
+    /// Get the event bus on which the preparation progress of sessions is published
+    pub fn events(&self) -> &EventBus {
+        self.debugger.events()
+    }
+
+    /// Create a session for a transaction and start preparing it in the background.
+    ///
+    /// The given mock contracts are installed before replay, in addition to the ones of
+    /// the engine configuration, and the given focus replaces the one of the engine
+    /// configuration. If the transaction already has a session with the same mocks and
+    /// focus that is ready or still preparing, that session is returned instead.
+    pub fn create_session(
+        self: &Arc<Self>,
+        tx_hash: TxHash,
+        mocks: Vec<MockContract>,
+        focus: Option<TraceFocus>,
+    ) -> SessionInfo {
+        let focus = focus.or(self.debugger.config().focus);
+        if let Some(existing) = self.sessions.iter().find(|session| {
+            session.info.tx_hash == tx_hash
+                && session.info.mocks == mocks
+                && session.info.focus == focus
+                && !matches!(
+                    session.info.status,
+                    SessionStatus::Failed { .. } | SessionStatus::Cancelled
+                )
+        }) {
+            return existing.info.clone();
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let created_at =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        let info = SessionInfo {
+            id,
+            tx_hash,
+            created_at,
+            status: SessionStatus::Preparing,
+            mocks: mocks.clone(),
+            focus,
+        };
+        // The preparation is tracked from the start, so that it can be cancelled while the
+        // transaction is forked
+        let (preparation, _) = self.debugger.preparations().start(tx_hash);
+        self.sessions.insert(id, ManagedSession { info: info.clone(), preparation, handler: None });
+        info!("Created session {} for transaction {:?}", id, tx_hash);
+
+        // Forking involves types that are not `Send`, so preparation runs on a dedicated
+        // runtime instead of being spawned onto the server's runtime
+        let manager = Arc::clone(self);
+        std::thread::spawn(move || {
+            let result = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .map_err(eyre::Report::from)
+                .and_then(|runtime| {
+                    runtime.block_on(manager.prepare(tx_hash, &mocks, focus, preparation))
+                });
+            manager.finish(id, result);
+        });
+
+        info
+    }
+
+    /// Prepare the session of a transaction
+    async fn prepare(
+        &self,
+        tx_hash: TxHash,
+        mocks: &[MockContract],
+        focus: Option<TraceFocus>,
+        preparation: PreparationId,
+    ) -> Result<Arc<dyn SessionHandler>> {
+        let session = self.debugger.prepare_tracked(tx_hash, mocks, focus, preparation).await?;
+        let handler: Arc<dyn SessionHandler> = Arc::new(
+            MethodHandler::new(session.context().clone()).with_events(self.events().clone()),
+        );
+        Ok(handler)
+    }
+
+    /// Record the outcome of a preparation
+    fn finish(&self, id: SessionId, result: Result<Arc<dyn SessionHandler>>) {
+        // The session may have been closed while it was being prepared
+        let Some(mut session) = self.sessions.get_mut(&id) else { return };
+
+        match result {
+            Ok(handler) => {
+                info!("Session {} is ready", id);
+                session.info.status =
+                    SessionStatus::Ready { snapshot_count: handler.snapshot_count() };
+                session.handler = Some(handler);
+            }
+            Err(e) if is_cancelled(&e) => {
+                info!("Cancelled preparation of session {}", id);
+                session.info.status = SessionStatus::Cancelled;
+            }
+            Err(e) => {
+                error!("Failed to prepare session {}: {}", id, e);
+                session.info.status = SessionStatus::Failed { error: e.to_string() };
+            }
+        }
+    }
+
+    /// Get the information of a session
+    pub fn session(&self, id: SessionId) -> Option<SessionInfo> {
+        self.sessions.get(&id).map(|session| session.info.clone())
+    }
+
+    /// Get the preparation status of a session, including the current phase while preparing
+    pub fn preparation_status(&self, id: SessionId) -> Option<PreparationStatus> {
+        let (info, preparation) =
+            self.sessions.get(&id).map(|session| (session.info.clone(), session.preparation))?;
+        let status = match info.status {
+            SessionStatus::Preparing => self
+                .debugger
+                .preparations()
+                .status(preparation)
+                .unwrap_or_else(|| PreparationStatus::preparing(info.tx_hash)),
+            SessionStatus::Ready { .. } => PreparationStatus::ready(info.tx_hash),
+            SessionStatus::Failed { error } => PreparationStatus {
+                state: PreparationState::Failed,
+                error: Some(error),
+                ..PreparationStatus::preparing(info.tx_hash)
+            },
+            SessionStatus::Cancelled => PreparationStatus {
+                state: PreparationState::Cancelled,
+                phase: "Cancelled".to_string(),
+                ..PreparationStatus::preparing(info.tx_hash)
+            },
+        };
+        Some(status)
+    }
+
+    /// List all sessions, ordered by identifier
+    pub fn list_sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<_> =
+            self.sessions.iter().map(|session| session.info.clone()).collect();
+        sessions.sort_by_key(|info| info.id);
+        sessions
+    }
+
+    /// Cancel the preparation of a session, returning whether it was still preparing
+    pub fn cancel_session(&self, id: SessionId) -> bool {
+        let Some((status, preparation)) = self
+            .sessions
+            .get(&id)
+            .map(|session| (session.info.status.clone(), session.preparation))
+        else {
+            return false;
+        };
+        let cancelled = matches!(status, SessionStatus::Preparing)
+            && self.debugger.preparations().cancel(preparation);
+        if cancelled {
+            info!("Cancelling preparation of session {}", id);
+        }
+        cancelled
+    }
+
+    /// Close a session, returning whether it existed.
+    ///
+    /// A session that is still being prepared is cancelled first.
+    pub fn close_session(&self, id: SessionId) -> bool {
+        self.cancel_session(id);
+        let existed = self.sessions.remove(&id).is_some();
+        if existed {
+            info!("Closed session {}", id);
+        }
+        existed
+    }
+
+    /// Get the method handler of a prepared session
+    pub fn handler(&self, id: SessionId) -> Result<Arc<dyn SessionHandler>, RpcError> {
+        let session = self.sessions.get(&id).ok_or_else(|| RpcError {
+            code: error_codes::SESSION_NOT_FOUND,
+            message: format!("Session {id} not found"),
+            data: None,
+        })?;
+
+        session.handler.clone().ok_or_else(|| RpcError {
+            code: error_codes::SESSION_NOT_READY,
+            message: match &session.info.status {
+                SessionStatus::Failed { error } => format!("Session {id} failed: {error}"),
+                SessionStatus::Cancelled => format!("Session {id} was cancelled"),
+                _ => format!("Session {id} is still being prepared"),
+            },
+            data: None,
+        })
+    }
+
+    /// Handle a session management method
+    fn handle_management_method(
+        self: &Arc<Self>,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<Value, RpcError> {
+        let first_param = params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.first());
+
+        let result = match method {
+            "edb_createSession" => {
+                let tx_hash: TxHash = first_param
+                    .and_then(|v| serde_json::from_value(v.clone()).ok())
+                    .ok_or_else(|| RpcError {
+                        code: error_codes::INVALID_PARAMS,
+                        message: "Invalid params: expected [tx_hash, mocks?, focus?]".to_string(),
+                        data: None,
+                    })?;
+                let mocks: Vec<MockContract> =
+                    match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.get(1)) {
+                        Some(value) if !value.is_null() => serde_json::from_value(value.clone())
+                            .map_err(|e| RpcError {
+                                code: error_codes::INVALID_PARAMS,
+                                message: format!("Invalid mocks: {e}"),
+                                data: None,
+                            })?,
+                        _ => Vec::new(),
+                    };
+                let focus: Option<TraceFocus> =
+                    match params.as_ref().and_then(|p| p.as_array()).and_then(|arr| arr.get(2)) {
+                        Some(value) if !value.is_null() => {
+                            Some(serde_json::from_value(value.clone()).map_err(|e| RpcError {
+                                code: error_codes::INVALID_PARAMS,
+                                message: format!("Invalid focus: {e}"),
+                                data: None,
+                            })?)
+                        }
+                        _ => None,
+                    };
+                serde_json::to_value(self.create_session(tx_hash, mocks, focus))
+            }
+            "edb_listSessions" => serde_json::to_value(self.list_sessions()),
+            "edb_cancel" => {
+                let id = first_param.and_then(|v| v.as_u64()).ok_or_else(|| RpcError {
+                    code: error_codes::INVALID_PARAMS,
+                    message: "Invalid params: expected [session_id]".to_string(),
+                    data: None,
+                })?;
+                Ok(Value::Bool(self.cancel_session(id)))
+            }
+            "edb_closeSession" => {
+                let id = first_param.and_then(|v| v.as_u64()).ok_or_else(|| RpcError {
+                    code: error_codes::INVALID_PARAMS,
+                    message: "Invalid params: expected [session_id]".to_string(),
+                    data: None,
+                })?;
+                Ok(Value::Bool(self.close_session(id)))
+            }
+            _ => {
+                return Err(RpcError {
+                    code: error_codes::METHOD_NOT_FOUND,
+                    message: format!(
+                    "Method '{method}' not found, debugging methods are served on /session/{{id}}"
+                ),
+                    data: None,
+                })
+            }
+        };
+
+        result.map_err(|e| RpcError {
+            code: error_codes::INTERNAL_ERROR,
+            message: format!("Failed to serialize session info: {e}"),
+            data: None,
+        })
+    }
+}
+
+/// Request handler for session management methods on `/` and `/ws`
+struct ManagementHandler(Arc<SessionManager>);
+
+impl RequestHandler for ManagementHandler {
+    fn handle_request(&self, request: RpcRequest) -> BoxFuture<'_, RpcResponse> {
+        let result = self.0.handle_management_method(&request.method, request.params);
+        Box::pin(async move { into_response(request.id, result) })
+    }
+}
+
+/// Request handler for the debugging methods of a single session on `/session/{id}`
+struct SessionRequestHandler {
+    manager: Arc<SessionManager>,
+    id: SessionId,
+}
+
+impl RequestHandler for SessionRequestHandler {
+    fn handle_request(&self, request: RpcRequest) -> BoxFuture<'_, RpcResponse> {
+        Box::pin(async move {
+            let result = match self.manager.handler(self.id) {
+                Ok(handler) => handler.handle_method(&request.method, request.params).await,
+                Err(err) => Err(err),
+            };
+            into_response(request.id, result)
+        })
+    }
+}
+
+/// JSON-RPC server exposing all sessions of a [`SessionManager`] on a single port.
+pub struct SessionRpcServer {
+    manager: Arc<SessionManager>,
+    security: RpcSecurityConfig,
+}
+
+impl SessionRpcServer {
+    /// Create a server for the sessions of the given manager
+    pub fn new(manager: Arc<SessionManager>) -> Self {
+        Self { manager, security: RpcSecurityConfig::default() }
+    }
+
+    /// Require authentication and/or TLS as configured
+    pub fn with_security(mut self, security: RpcSecurityConfig) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Start the server on a specific port
+    pub async fn start_on_port(self, port: u16) -> Result<RpcServerHandle> {
+        let app = Router::new()
+            .route("/", post(handle_management_request))
+            .route("/session/{id}", post(handle_session_request))
+            .route("/session/{id}/status", get(session_status))
+            .route("/ws", get(handle_ws_upgrade))
+            .route("/health", get(health_check))
+            .route("/openrpc.json", get(openrpc_schema))
+            .with_state(self.manager.clone());
+
+        let handle = serve_router(app, port, &self.security).await?;
+        info!("Session RPC server started on {}", handle.addr());
+
+        Ok(handle)
+    }
+}
+
+/// Handle session management requests
+async fn handle_management_request(
+    State(manager): State<Arc<SessionManager>>,
+    access: Option<Extension<ReadOnlyAccess>>,
+    JsonExtract(payload): JsonExtract<Value>,
+) -> Response {
+    let handler = Arc::new(ManagementHandler(manager));
+    into_http_response(dispatch_with_access(handler, access, payload).await)
+}
+
+/// Handle debugging requests of a single session
+async fn handle_session_request(
+    State(manager): State<Arc<SessionManager>>,
+    Path(id): Path<SessionId>,
+    access: Option<Extension<ReadOnlyAccess>>,
+    JsonExtract(payload): JsonExtract<Value>,
+) -> Response {
+    let handler = Arc::new(SessionRequestHandler { manager, id });
+    into_http_response(dispatch_with_access(handler, access, payload).await)
+}
+
+/// Report the preparation status of a single session
+async fn session_status(
+    State(manager): State<Arc<SessionManager>>,
+    Path(id): Path<SessionId>,
+) -> Response {
+    match manager.preparation_status(id) {
+        Some(status) => JsonResponse(status).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("Session {id} not found")).into_response(),
+    }
+}
+
+/// Upgrade a connection to the WebSocket endpoint, streaming the events of all sessions
+async fn handle_ws_upgrade(
+    State(manager): State<Arc<SessionManager>>,
+    access: Option<Extension<ReadOnlyAccess>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let events = manager.events().clone();
+    let handler = Arc::new(ManagementHandler(manager));
+    ws.on_upgrade(move |socket| async move {
+        match access {
+            Some(_) => handle_socket(socket, Arc::new(ReadOnly(handler)), events, None).await,
+            None => handle_socket(socket, handler, events, None).await,
+        }
+    })
+}
+
+/// Health check endpoint
+async fn health_check(State(manager): State<Arc<SessionManager>>) -> JsonResponse<Value> {
+    JsonResponse(serde_json::json!({
+        "status": "healthy",
+        "service": "edb-session-rpc-server",
+        "version": env!("CARGO_PKG_VERSION"),
+        "sessions": manager.sessions.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_info_serialization() {
+        let info = SessionInfo {
+            id: 1,
+            tx_hash: TxHash::ZERO,
+            created_at: 0,
+            status: SessionStatus::Ready { snapshot_count: 42 },
+            mocks: Vec::new(),
+            focus: Some(TraceFocus::Entry(3)),
+        };
+
+        let value = serde_json::to_value(&info).unwrap();
+        assert_eq!(value["id"], 1);
+        assert_eq!(value["status"], "ready");
+        assert_eq!(value["snapshot_count"], 42);
+        assert_eq!(value["focus"]["entry"], 3);
+        assert_eq!(serde_json::from_value::<SessionInfo>(value).unwrap(), info);
+
+        let value = serde_json::to_value(SessionStatus::Cancelled).unwrap();
+        assert_eq!(value, serde_json::json!({ "status": "cancelled" }));
+    }
+
+    /// Register a session of a transaction that is still being prepared
+    fn preparing_session(manager: &SessionManager, id: SessionId, tx_hash: TxHash) {
+        let info = SessionInfo {
+            id,
+            tx_hash,
+            created_at: 0,
+            status: SessionStatus::Preparing,
+            mocks: Vec::new(),
+            focus: None,
+        };
+        let (preparation, _) = manager.debugger.preparations().start(tx_hash);
+        manager.sessions.insert(id, ManagedSession { info, preparation, handler: None });
+    }
+
+    #[test]
+    fn test_cancel_session() {
+        let manager = SessionManager::new(Debugger::builder().build());
+        let tx_hash = TxHash::repeat_byte(1);
+
+        // Two sessions of the same transaction, e.g., with different mocks
+        preparing_session(&manager, 1, tx_hash);
+        preparing_session(&manager, 2, tx_hash);
+        assert!(!manager.cancel_session(3));
+
+        assert!(manager.cancel_session(1));
+        let token = |id: SessionId| {
+            let preparation = manager.sessions.get(&id).unwrap().preparation;
+            manager.debugger.preparations().cancellation_token(preparation).unwrap()
+        };
+        assert!(token(1).is_cancelled());
+        assert!(!token(2).is_cancelled());
+
+        // The session is cancelled once its preparation stopped
+        manager.finish(1, Err(crate::Cancelled.into()));
+        assert_eq!(manager.session(1).unwrap().status, SessionStatus::Cancelled);
+        assert_eq!(manager.preparation_status(1).unwrap().state, PreparationState::Cancelled);
+        assert!(!manager.cancel_session(1));
+        assert!(matches!(
+            manager.handler(1),
+            Err(RpcError { code: error_codes::SESSION_NOT_READY, .. })
+        ));
+
+        // Failures are still told apart from cancellations
+        manager.finish(2, Err(eyre::eyre!("boom")));
+        assert!(matches!(manager.session(2).unwrap().status, SessionStatus::Failed { .. }));
+    }
+}
@@ -0,0 +1,122 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! HTTP status endpoint reporting the progress of long-running preparations.
+//!
+//! The debug RPC server of a transaction only starts once the transaction is prepared.
+//! Until then, clients can follow the preparation through the [`StatusRpcServer`]
+//! started by [`crate::Engine::serve_status`]:
+//!
+//! - `GET /status` - Status of all preparations, most recently started first
+//! - `GET /status/{tx_hash}` - Status of the latest preparation of a transaction
+//! - `POST /status/{tx_hash}/cancel` - Stop all running preparations of a transaction,
+//!   returning whether any was still running
+//! - `GET /health` - Health check endpoint
+//!
+//! Debug RPC servers and sessions of the session manager serve the same
+//! [`PreparationStatus`] at `GET /status` and `GET /session/{id}/status`, so clients can
+//! poll a single URL until the transaction is ready.
+
+use alloy_primitives::TxHash;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json as JsonResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use edb_common::PreparationStatus;
+use eyre::Result;
+use serde_json::Value;
+use tracing::info;
+
+use super::{security::RpcSecurityConfig, server::serve_router, RpcServerHandle};
+use crate::PreparationTracker;
+
+/// HTTP server exposing the preparation status of an engine.
+pub struct StatusRpcServer {
+    preparations: PreparationTracker,
+    security: RpcSecurityConfig,
+}
+
+impl StatusRpcServer {
+    /// Create a server reporting the preparations of the given tracker
+    pub fn new(preparations: PreparationTracker) -> Self {
+        Self { preparations, security: RpcSecurityConfig::default() }
+    }
+
+    /// Require authentication and/or TLS as configured
+    pub fn with_security(mut self, security: RpcSecurityConfig) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Start the server on a specific port
+    pub async fn start_on_port(self, port: u16) -> Result<RpcServerHandle> {
+        let app = Router::new()
+            .route("/status", get(all_statuses))
+            .route("/status/{tx_hash}", get(transaction_status))
+            .route("/status/{tx_hash}/cancel", post(cancel_preparation))
+            .route("/health", get(health_check))
+            .with_state(self.preparations);
+
+        let handle = serve_router(app, port, &self.security).await?;
+        info!("Status server started on {}", handle.addr());
+
+        Ok(handle)
+    }
+}
+
+/// Report the status of all preparations
+async fn all_statuses(
+    State(preparations): State<PreparationTracker>,
+) -> JsonResponse<Vec<PreparationStatus>> {
+    JsonResponse(preparations.all())
+}
+
+/// Report the status of the latest preparation of a transaction
+async fn transaction_status(
+    State(preparations): State<PreparationTracker>,
+    Path(tx_hash): Path<TxHash>,
+) -> Response {
+    match preparations.latest(&tx_hash) {
+        Some(status) => JsonResponse(status).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("No preparation of transaction {tx_hash}"))
+            .into_response(),
+    }
+}
+
+/// Cancel the running preparations of a transaction
+async fn cancel_preparation(
+    State(preparations): State<PreparationTracker>,
+    Path(tx_hash): Path<TxHash>,
+) -> Response {
+    match preparations.latest(&tx_hash) {
+        Some(_) => JsonResponse(preparations.cancel_transaction(&tx_hash)).into_response(),
+        None => (StatusCode::NOT_FOUND, format!("No preparation of transaction {tx_hash}"))
+            .into_response(),
+    }
+}
+
+/// Health check endpoint
+async fn health_check(State(preparations): State<PreparationTracker>) -> JsonResponse<Value> {
+    JsonResponse(serde_json::json!({
+        "status": "healthy",
+        "service": "edb-status-server",
+        "version": env!("CARGO_PKG_VERSION"),
+        "preparations": preparations.all().len(),
+    }))
+}
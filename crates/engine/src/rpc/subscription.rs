@@ -0,0 +1,261 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! WebSocket transport with live event subscriptions.
+//!
+//! Clients connected to `GET /ws` can send any JSON-RPC request (or batch) supported
+//! over HTTP, and can additionally subscribe to [`EngineEvent`]s instead of polling:
+//!
+//! - `edb_subscribe` - `[topic, tx_hash?]`, where `topic` is one of `progress`,
+//!   `navigation`, or `findings`. On a session server, events are filtered to the
+//!   transaction of the session unless another `tx_hash` is given. On the session
+//!   manager, events of all sessions are delivered unless a `tx_hash` is given.
+//!   Returns a subscription id.
+//! - `edb_unsubscribe` - `[subscription_id]`. Returns whether the subscription existed.
+//!
+//! Events are delivered as notifications in the style of `eth_subscribe`:
+//!
+//! ```json
+//! {
+//!   "jsonrpc": "2.0",
+//!   "method": "edb_subscription",
+//!   "params": { "subscription": "0x1", "result": { "type": "navigation", ... } }
+//! }
+//! ```
+//!
+//! Subscriptions are bound to the connection and end when the socket closes.
+
+use std::{collections::HashMap, sync::Arc};
+
+use alloy_primitives::TxHash;
+use axum::extract::ws::{Message, WebSocket};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{
+    sync::{broadcast::error::RecvError, mpsc},
+    task::AbortHandle,
+};
+use tracing::{debug, warn};
+
+use super::{
+    server::{dispatch_payload, into_response, invalid_request, RequestHandler},
+    types::{error_codes, RpcError, RpcId, RpcRequest, RpcResponse},
+};
+use crate::{EngineEvent, EventBus, EventTopic};
+
+/// Method name of event notifications sent to subscribers
+pub const SUBSCRIPTION_NOTIFICATION: &str = "edb_subscription";
+
+/// Subscriptions of a single WebSocket connection.
+struct Subscriptions {
+    /// Bus the subscriptions listen to
+    events: EventBus,
+    /// Outgoing messages of the connection
+    outgoing: mpsc::UnboundedSender<String>,
+    /// Transaction events are filtered to by default, if any
+    tx_hash: Option<TxHash>,
+    /// Counter used to derive subscription ids
+    next_id: u64,
+    /// Forwarding tasks of active subscriptions, by subscription id
+    active: HashMap<String, AbortHandle>,
+}
+
+impl Subscriptions {
+    fn new(
+        events: EventBus,
+        outgoing: mpsc::UnboundedSender<String>,
+        tx_hash: Option<TxHash>,
+    ) -> Self {
+        Self { events, outgoing, tx_hash, next_id: 1, active: HashMap::new() }
+    }
+
+    /// Handle `edb_subscribe`, returning the id of the new subscription
+    fn subscribe(&mut self, params: Option<Value>) -> Result<Value, RpcError> {
+        let invalid_params = || RpcError {
+            code: error_codes::INVALID_PARAMS,
+            message: "Invalid params: expected [topic, tx_hash?]".to_string(),
+            data: None,
+        };
+
+        let params = params.as_ref().and_then(|p| p.as_array()).ok_or_else(invalid_params)?;
+        let topic: EventTopic = params
+            .first()
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .ok_or_else(invalid_params)?;
+        let tx_hash = match params.get(1) {
+            None | Some(Value::Null) => self.tx_hash,
+            Some(value) => {
+                Some(serde_json::from_value(value.clone()).map_err(|_| invalid_params())?)
+            }
+        };
+
+        let id = format!("{:#x}", self.next_id);
+        self.next_id += 1;
+
+        let mut receiver = self.events.subscribe();
+        let outgoing = self.outgoing.clone();
+        let subscription = id.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Subscription {} skipped {} event(s)", subscription, skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if event.topic() != topic || tx_hash.is_some_and(|hash| hash != event.tx_hash()) {
+                    continue;
+                }
+
+                if outgoing.send(notification(&subscription, &event)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        debug!("Created subscription {} for {:?} events of {:?}", id, topic, tx_hash);
+        self.active.insert(id.clone(), task.abort_handle());
+        Ok(Value::String(id))
+    }
+
+    /// Handle `edb_unsubscribe`, returning whether the subscription existed
+    fn unsubscribe(&mut self, params: Option<Value>) -> Result<Value, RpcError> {
+        let id = params
+            .as_ref()
+            .and_then(|p| p.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| RpcError {
+                code: error_codes::INVALID_PARAMS,
+                message: "Invalid params: expected [subscription_id]".to_string(),
+                data: None,
+            })?;
+
+        let existed = self.active.remove(id).map(|task| task.abort()).is_some();
+        Ok(Value::Bool(existed))
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for task in self.active.values() {
+            task.abort();
+        }
+    }
+}
+
+/// Build the notification delivering an event to a subscription
+fn notification(subscription: &str, event: &EngineEvent) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "method": SUBSCRIPTION_NOTIFICATION,
+        "params": { "subscription": subscription, "result": event },
+    })
+    .to_string()
+}
+
+/// Serve JSON-RPC requests and subscriptions over an upgraded WebSocket connection
+///
+/// Subscriptions without an explicit transaction receive the events of `tx_hash`, or of
+/// all transactions if `tx_hash` is `None`.
+pub(crate) async fn handle_socket<H: RequestHandler>(
+    socket: WebSocket,
+    handler: Arc<H>,
+    events: EventBus,
+    tx_hash: Option<TxHash>,
+) {
+    let (mut sink, mut stream) = socket.split();
+
+    // Responses and notifications are produced concurrently, so a single task owns the sink
+    let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+    let writer = tokio::spawn(async move {
+        while let Some(text) = outgoing_rx.recv().await {
+            if sink.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut subscriptions = Subscriptions::new(events, outgoing.clone(), tx_hash);
+
+    while let Some(Ok(message)) = stream.next().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let payload: Value = match serde_json::from_str(text.as_str()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                let response = RpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    result: None,
+                    error: Some(RpcError {
+                        code: error_codes::PARSE_ERROR,
+                        message: format!("Parse error - {e}"),
+                        data: None,
+                    }),
+                    id: RpcId::Null,
+                };
+                outgoing.send(serde_json::to_string(&response).unwrap_or_default()).ok();
+                continue;
+            }
+        };
+
+        // Subscriptions are connection state and therefore handled here rather than
+        // by the method handler
+        let subscription_method = payload
+            .get("method")
+            .and_then(|m| m.as_str())
+            .filter(|m| matches!(*m, "edb_subscribe" | "edb_unsubscribe"))
+            .map(str::to_string);
+        if let Some(method) = subscription_method {
+            let notification = RpcRequest::is_notification(&payload);
+            let response = match serde_json::from_value::<RpcRequest>(payload) {
+                Ok(request) => {
+                    let result = if method == "edb_subscribe" {
+                        subscriptions.subscribe(request.params)
+                    } else {
+                        subscriptions.unsubscribe(request.params)
+                    };
+                    into_response(request.id, result)
+                }
+                Err(e) => invalid_request(RpcId::Null, &format!("Invalid Request - {e}")),
+            };
+            if !notification || response.error.is_some() {
+                outgoing.send(serde_json::to_string(&response).unwrap_or_default()).ok();
+            }
+            continue;
+        }
+
+        // Regular requests may take a while, so they do not block the connection
+        let handler = handler.clone();
+        let outgoing = outgoing.clone();
+        tokio::spawn(async move {
+            // Notifications are not answered
+            if let Some(response) = dispatch_payload(handler, payload).await {
+                outgoing.send(serde_json::to_string(&response).unwrap_or_default()).ok();
+            }
+        });
+    }
+
+    debug!("WebSocket connection closed");
+    drop(subscriptions);
+    writer.abort();
+}
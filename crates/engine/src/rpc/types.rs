@@ -26,6 +26,7 @@
 //! - [`RpcResponse`] - Outgoing JSON-RPC response with result or error
 //! - [`RpcError`] - Structured error information following JSON-RPC error format
 //! - [`RpcId`] - Request/response identifier (string or number)
+//! - [`RpcResponsePayload`] - Response to a single request or to a batch of requests
 //!
 //! # Error Handling
 //!
@@ -46,10 +47,20 @@ pub struct RpcRequest {
     pub method: String,
     /// Optional method parameters as JSON value
     pub params: Option<serde_json::Value>,
-    /// Request identifier for matching with response
+    /// Request identifier for matching with response, `Null` for notifications
+    /// (see [`RpcRequest::is_notification`])
+    #[serde(default)]
     pub id: RpcId,
 }
 
+impl RpcRequest {
+    /// Whether a request object is a notification, i.e., has no `id` member and must not
+    /// be answered
+    pub fn is_notification(request: &serde_json::Value) -> bool {
+        request.is_object() && request.get("id").is_none()
+    }
+}
+
 /// JSON-RPC 2.0 response structure.
 ///
 /// Represents an outgoing RPC response to a debugging client.
@@ -87,13 +98,29 @@ pub struct RpcError {
 ///
 /// Can be either a string or number as per JSON-RPC 2.0 specification.
 /// Used to match responses with their corresponding requests.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcId {
     /// Numeric identifier
     Number(u64),
     /// String identifier
     String(String),
+    /// Null identifier, used when the id of an invalid request cannot be determined
+    #[default]
+    Null,
+}
+
+/// JSON-RPC 2.0 response payload.
+///
+/// A single request is answered with a single response, while a batch request
+/// (a JSON array of requests) is answered with an array of responses.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum RpcResponsePayload {
+    /// Response to a single request
+    Single(RpcResponse),
+    /// Responses to a batch of requests, in the order of the requests
+    Batch(Vec<RpcResponse>),
 }
 
 /// JSON-RPC error codes for consistent error reporting.
@@ -128,4 +155,10 @@ pub mod error_codes {
     pub const USID_NOT_FOUND: i32 = -33005;
     /// Expression evaluation failed
     pub const EVAL_FAILED: i32 = -33006;
+    /// Debugging session not found for the given ID
+    pub const SESSION_NOT_FOUND: i32 = -33007;
+    /// Debugging session is still being prepared or failed to prepare
+    pub const SESSION_NOT_READY: i32 = -33008;
+    /// Method changes shared session state, but the client only has read-only access
+    pub const READ_ONLY_ACCESS: i32 = -33009;
 }
@@ -52,7 +52,7 @@
 use std::collections::{HashMap, HashSet};
 
 use alloy_primitives::Address;
-use edb_common::types::{ExecutionFrameId, Trace};
+use edb_common::types::{ExecutionFrameId, ModifierFrame, Trace};
 use eyre::Result;
 use itertools::Itertools;
 use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
@@ -259,11 +259,13 @@ where
             return Ok(());
         }
 
+        let mut modifier_frames = ModifierFrameTracker::default();
         let mut stack: Vec<CallStackEntry> = Vec::new();
         stack.push(CallStackEntry {
             func_info: FunctionInfo::Unknown,
             callsite: None,
             return_after_callsite: false,
+            invocation: modifier_frames.new_invocation(),
         });
 
         let is_entry =
@@ -301,6 +303,7 @@ where
                     func_info: FunctionInfo::Unknown,
                     callsite: None,
                     return_after_callsite: false,
+                    invocation: modifier_frames.new_invocation(),
                 });
             }
 
@@ -318,6 +321,7 @@ where
                 // We output the error but do not stop here.
                 error!("Invalid function info in call stack");
             }
+            modifier_frames.record(i, snapshots[i].1.id(), stack_entry, step, analysis);
 
             // Step 2: check whether this step contains any valid internal call
             //  a) The step contains internal calls
@@ -335,6 +339,7 @@ where
                                 - snapshots[i].0.re_entry_count()),
                     }),
                     return_after_callsite: step.contains_return(),
+                    invocation: modifier_frames.new_invocation(),
                 });
                 continue;
             }
@@ -349,6 +354,7 @@ where
                     func_info: FunctionInfo::Unknown,
                     callsite: Some(Callsite { id: i, callees: usize::MAX }),
                     return_after_callsite: step.contains_return(),
+                    invocation: modifier_frames.new_invocation(),
                 });
                 continue;
             }
@@ -404,6 +410,7 @@ where
                 if (callsite.callees > 0 || callsite_certainly_not_done) && !callsite_certainly_done
                 {
                     stack_entry.func_info = FunctionInfo::Unknown;
+                    stack_entry.invocation = modifier_frames.new_invocation();
                     stack.push(stack_entry);
                     break;
                 }
@@ -427,6 +434,21 @@ where
             }
         }
 
+        // The last snapshot (e.g., a failing `require` in a modifier) is not visited above
+        let last = snapshots.len() - 1;
+        if let (Some(usid), Some(stack_entry)) = (snapshots[last].1.usid(), stack.last_mut()) {
+            if let Some(step) = analysis.usid_to_step.get(&usid) {
+                if let Some(ufid) = step.function_entry() {
+                    stack_entry.func_info.with_function(ufid);
+                }
+                if let Some(ufid) = step.modifier_entry() {
+                    stack_entry.func_info.with_modifier(ufid);
+                }
+                modifier_frames.record(last, snapshots[last].1.id(), stack_entry, step, analysis);
+            }
+        }
+        modifier_frames.assign(&mut snapshots, analysis);
+
         while let Some(CallStackEntry { callsite: Some(Callsite { id, .. }), .. }) = stack.pop() {
             debug!("Add snapshot as a hole: {}", snapshots[id].1.id());
             holed_snapshots.insert(snapshots[id].1.id());
@@ -441,6 +463,104 @@ struct CallStackEntry {
     func_info: FunctionInfo,
     callsite: Option<Callsite>,
     return_after_callsite: bool,
+    // Identifier of the function invocation, shared by its modifier evaluations
+    invocation: usize,
+}
+
+/// Collects the virtual frames of modifier evaluations during the call stack analysis.
+///
+/// A modifier runs before the body of the function it modifies, so the function is
+/// only known once its entry step is reached (or never, if a modifier reverts). Frames
+/// are therefore assigned to the snapshots after the whole trace entry is analyzed.
+#[derive(Debug, Default)]
+struct ModifierFrameTracker {
+    // Number of function invocations seen so far
+    invocations: usize,
+    // The modified function of each invocation
+    functions: HashMap<usize, UFID>,
+    // The entry snapshot of each modifier evaluation, keyed by invocation and modifier index
+    entries: HashMap<(usize, usize), usize>,
+    // Snapshots inside modifiers: (index in the snapshot list, invocation, modifier, modifier index)
+    pending: Vec<(usize, usize, UFID, usize)>,
+}
+
+impl ModifierFrameTracker {
+    fn new_invocation(&mut self) -> usize {
+        self.invocations += 1;
+        self.invocations
+    }
+
+    /// Record the step of a snapshot, after the call stack entry has been updated with it
+    fn record(
+        &mut self,
+        index: usize,
+        snapshot_id: usize,
+        entry: &CallStackEntry,
+        step: &StepRef,
+        analysis: &AnalysisResult,
+    ) {
+        if let Some(ufid) = step.function_entry() {
+            self.functions.insert(entry.invocation, ufid);
+        }
+
+        let ufid = step.ufid();
+        if !analysis.ufid_to_function.get(&ufid).is_some_and(|f| f.is_modifier()) {
+            return;
+        }
+
+        // The same modifier may be invoked several times, the latest evaluation is the one running
+        let modifier_index = match &entry.func_info {
+            FunctionInfo::ModifierOnly(modifiers)
+            | FunctionInfo::ModifiedFunction { modifiers, .. } => {
+                modifiers.iter().rposition(|id| *id == ufid)
+            }
+            _ => None,
+        };
+        let Some(modifier_index) = modifier_index else {
+            debug!("Snapshot {} is in a modifier that was not entered", snapshot_id);
+            return;
+        };
+
+        if step.modifier_entry().is_some() {
+            self.entries.insert((entry.invocation, modifier_index), snapshot_id);
+        }
+        self.pending.push((index, entry.invocation, ufid, modifier_index));
+    }
+
+    /// Assign the collected modifier frames to their snapshots
+    fn assign<DB>(
+        self,
+        snapshots: &mut [&mut (ExecutionFrameId, Snapshot<DB>)],
+        analysis: &AnalysisResult,
+    ) where
+        DB: Database + DatabaseCommit + DatabaseRef + Clone,
+        <CacheDB<DB> as Database>::Error: Clone,
+        <DB as Database>::Error: Clone,
+    {
+        for (index, invocation, ufid, modifier_index) in self.pending {
+            let snapshot = &mut snapshots[index].1;
+            let function =
+                self.functions.get(&invocation).and_then(|f| analysis.ufid_to_function.get(f));
+            let frame = ModifierFrame {
+                modifier: analysis
+                    .ufid_to_function
+                    .get(&ufid)
+                    .map(|f| f.name())
+                    .unwrap_or_default(),
+                function: function.map(|f| f.name()),
+                index: modifier_index,
+                entry_snapshot_id: self
+                    .entries
+                    .get(&(invocation, modifier_index))
+                    .copied()
+                    .unwrap_or(snapshot.id()),
+                invocation: function
+                    .and_then(|f| f.modifier_invocations().get(modifier_index).copied())
+                    .map(|src| (src.start, src.length)),
+            };
+            snapshot.set_modifier_frame(frame);
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -39,8 +39,13 @@ use std::{
     sync::Arc,
 };
 
-use edb_common::types::ExecutionFrameId;
-use revm::{database::CacheDB, state::TransientStorage, Database, DatabaseCommit, DatabaseRef};
+use edb_common::types::{ExecutionFrameId, ModifierFrame};
+use revm::{
+    bytecode::opcode::{LOG0, LOG4},
+    database::CacheDB,
+    state::TransientStorage,
+    Database, DatabaseCommit, DatabaseRef,
+};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error};
 
@@ -63,6 +68,8 @@ where
     frame_id: ExecutionFrameId,
     next_id: Option<usize>,
     prev_id: Option<usize>,
+    #[serde(default)]
+    modifier_frame: Option<ModifierFrame>,
 
     /// Detail of the snapshot
     detail: SnapshotDetail<DB>,
@@ -95,12 +102,26 @@ where
 {
     /// Create an opcode snapshot
     pub fn new_opcode(id: usize, frame_id: ExecutionFrameId, detail: OpcodeSnapshot<DB>) -> Self {
-        Self { id, frame_id, next_id: None, prev_id: None, detail: SnapshotDetail::Opcode(detail) }
+        Self {
+            id,
+            frame_id,
+            next_id: None,
+            prev_id: None,
+            modifier_frame: None,
+            detail: SnapshotDetail::Opcode(detail),
+        }
     }
 
     /// Create a hook snapshot
     pub fn new_hook(id: usize, frame_id: ExecutionFrameId, detail: HookSnapshot<DB>) -> Self {
-        Self { id, frame_id, next_id: None, prev_id: None, detail: SnapshotDetail::Hook(detail) }
+        Self {
+            id,
+            frame_id,
+            next_id: None,
+            prev_id: None,
+            modifier_frame: None,
+            detail: SnapshotDetail::Hook(detail),
+        }
     }
 
     /// Set the id of the next snapshot
@@ -123,6 +144,16 @@ where
         self.prev_id
     }
 
+    /// Set the virtual frame of the modifier this snapshot is in
+    pub fn set_modifier_frame(&mut self, frame: ModifierFrame) {
+        self.modifier_frame = Some(frame);
+    }
+
+    /// Get the virtual frame of the modifier this snapshot is in, if any
+    pub fn modifier_frame(&self) -> Option<&ModifierFrame> {
+        self.modifier_frame.as_ref()
+    }
+
     /// Get the snapshot id
     pub fn id(&self) -> usize {
         self.id
@@ -183,6 +214,14 @@ where
         }
     }
 
+    /// Get the gas remaining in the frame when this snapshot was taken
+    pub fn gas_remaining(&self) -> u64 {
+        match &self.detail {
+            SnapshotDetail::Opcode(snapshot) => snapshot.gas_remaining,
+            SnapshotDetail::Hook(snapshot) => snapshot.gas_remaining,
+        }
+    }
+
     /// Check if this is a hook snapshot
     pub fn is_hook(&self) -> bool {
         matches!(self.detail, SnapshotDetail::Hook(_))
@@ -213,6 +252,9 @@ where
 {
     /// Vector of (frame_id, snapshot) pairs in execution order
     inner: Vec<(ExecutionFrameId, Snapshot<DB>)>,
+    /// Emitting frame and snapshot id of each log, in emission order
+    #[serde(default)]
+    logs: Vec<(ExecutionFrameId, Option<usize>)>,
 }
 
 impl<DB> Deref for Snapshots<DB>
@@ -300,7 +342,7 @@ where
 {
     /// Create a new empty snapshots collection
     pub fn new() -> Self {
-        Self { inner: Vec::new() }
+        Self { inner: Vec::new(), logs: Vec::new() }
     }
 
     /// Merge hook snapshots and opcode snapshots into a unified collection
@@ -312,14 +354,22 @@ where
     ///
     /// Hook snapshots are preferred because they represent intentional instrumentation
     /// points, while opcode snapshots provide comprehensive coverage for uninstrumented code.
+    ///
+    /// Logs recorded by the hook inspector are attributed to the hook snapshot of the
+    /// emitting statement, or to the matching `LOG*` opcode snapshot of the emitting frame.
     pub fn merge(
         mut opcode_snapshots: OpcodeSnapshots<DB>,
         hook_snapshots: HookSnapshots<DB>,
     ) -> Self {
         let mut inner = Vec::new();
+        let mut logs = Vec::new();
+        let mut log_positions = hook_snapshots.log_positions().to_vec().into_iter().peekable();
 
         // Process hook snapshots first (they take priority)
-        for (frame_id, snapshot_opt) in hook_snapshots {
+        for (position, (frame_id, snapshot_opt)) in hook_snapshots.into_iter().enumerate() {
+            let start = inner.len();
+            let is_hook = snapshot_opt.is_some();
+
             match snapshot_opt {
                 Some(snapshot) => {
                     // We have a valid hook snapshot - this takes priority
@@ -347,6 +397,23 @@ where
                     }
                 }
             }
+
+            // Attribute the logs emitted while this was the latest entry of the frame
+            let log_opcode_ids: Vec<usize> = inner[start..]
+                .iter()
+                .filter(|(_, snapshot)| match snapshot.detail() {
+                    SnapshotDetail::Opcode(detail) => (LOG0..=LOG4).contains(&detail.opcode),
+                    SnapshotDetail::Hook(_) => false,
+                })
+                .map(|(_, snapshot)| snapshot.id())
+                .collect();
+            let mut index = 0;
+            while log_positions.next_if_eq(&position).is_some() {
+                let snapshot_id =
+                    if is_hook { Some(start) } else { log_opcode_ids.get(index).copied() };
+                logs.push((frame_id, snapshot_id));
+                index += 1;
+            }
         }
 
         // Include any remaining opcode snapshots for frames not covered by hooks
@@ -357,7 +424,15 @@ where
             );
         }
 
-        Self { inner }
+        Self { inner, logs }
+    }
+
+    /// Get the emitting frame and snapshot id of each log, in emission order
+    ///
+    /// The snapshot id is `None` if the emitting frame has no snapshot of the `LOG*`
+    /// instruction, e.g., when its opcode snapshots were skipped.
+    pub fn logs(&self) -> &[(ExecutionFrameId, Option<usize>)] {
+        &self.logs
     }
 
     /// Get all snapshots for a specific execution frame
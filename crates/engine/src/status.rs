@@ -0,0 +1,254 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Status tracking of transaction preparations.
+//!
+//! Preparing a large transaction can take minutes, while the debug RPC server of a
+//! transaction only starts once it is ready. The engine therefore records the current
+//! phase of every preparation in a [`PreparationTracker`], which the CLI polls to
+//! display progress and which [`crate::rpc::StatusRpcServer`] exposes over HTTP. The
+//! tracker also holds the [`CancellationToken`] of every running preparation, so that
+//! any holder of the tracker can cancel it.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use alloy_primitives::TxHash;
+use dashmap::DashMap;
+use edb_common::{PreparationState, PreparationStatus, ProgressMessage};
+
+use crate::CancellationToken;
+
+/// Identifier of a preparation in a [`PreparationTracker`].
+///
+/// A transaction can be prepared several times, e.g., by sessions with different mocks,
+/// so preparations are told apart by their identifier rather than their transaction.
+pub type PreparationId = u64;
+
+/// Status of a preparation along with the time it started
+#[derive(Debug, Clone)]
+struct TrackedPreparation {
+    status: PreparationStatus,
+    started: Instant,
+    cancel: CancellationToken,
+}
+
+/// Shared record of the preparations of an engine.
+///
+/// Cloning the tracker yields a handle to the same record.
+#[derive(Debug, Clone, Default)]
+pub struct PreparationTracker {
+    preparations: Arc<DashMap<PreparationId, TrackedPreparation>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl PreparationTracker {
+    /// Record that the preparation of a transaction started, returning its identifier
+    /// and the token that cancels it
+    pub fn start(&self, tx_hash: TxHash) -> (PreparationId, CancellationToken) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let cancel = CancellationToken::new();
+        self.preparations.insert(
+            id,
+            TrackedPreparation {
+                status: PreparationStatus::preparing(tx_hash),
+                started: Instant::now(),
+                cancel: cancel.clone(),
+            },
+        );
+        (id, cancel)
+    }
+
+    /// Get the token that cancels a preparation
+    pub fn cancellation_token(&self, id: PreparationId) -> Option<CancellationToken> {
+        self.preparations.get(&id).map(|preparation| preparation.cancel.clone())
+    }
+
+    /// Request a running preparation to stop, returning whether it was running.
+    ///
+    /// The preparation stops at its next cancellation check and is then recorded as
+    /// cancelled.
+    pub fn cancel(&self, id: PreparationId) -> bool {
+        match self.preparations.get(&id) {
+            Some(preparation) if !preparation.status.is_finished() => {
+                preparation.cancel.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Request all running preparations of a transaction to stop, returning whether any
+    /// was running
+    pub fn cancel_transaction(&self, tx_hash: &TxHash) -> bool {
+        let running: Vec<_> = self
+            .preparations
+            .iter()
+            .filter(|preparation| preparation.status.tx_hash == *tx_hash)
+            .map(|preparation| *preparation.key())
+            .collect();
+        running.into_iter().fold(false, |cancelled, id| self.cancel(id) || cancelled)
+    }
+
+    /// Record the progress of a preparation
+    pub fn advance(&self, id: PreparationId, message: &ProgressMessage) {
+        if let Some(mut preparation) = self.preparations.get_mut(&id) {
+            preparation.status.advance(message);
+        }
+    }
+
+    /// Record that a preparation finished successfully
+    pub fn finish(&self, id: PreparationId) {
+        self.update(id, |status| {
+            status.state = PreparationState::Ready;
+            status.phase = "Ready".to_string();
+            status.percent = 100.0;
+        });
+    }
+
+    /// Record that a preparation failed
+    pub fn fail(&self, id: PreparationId, error: impl ToString) {
+        self.update(id, |status| {
+            status.state = PreparationState::Failed;
+            status.error = Some(error.to_string());
+        });
+    }
+
+    /// Record that a preparation stopped after being cancelled
+    pub fn cancelled(&self, id: PreparationId) {
+        self.update(id, |status| {
+            status.state = PreparationState::Cancelled;
+            status.phase = "Cancelled".to_string();
+        });
+    }
+
+    /// Get the status of a preparation
+    pub fn status(&self, id: PreparationId) -> Option<PreparationStatus> {
+        self.preparations.get(&id).map(|preparation| Self::snapshot(&preparation))
+    }
+
+    /// Get the status of the most recently started preparation of a transaction
+    pub fn latest(&self, tx_hash: &TxHash) -> Option<PreparationStatus> {
+        self.preparations
+            .iter()
+            .filter(|preparation| preparation.status.tx_hash == *tx_hash)
+            .max_by_key(|preparation| (preparation.started, *preparation.key()))
+            .map(|preparation| Self::snapshot(&preparation))
+    }
+
+    /// Get the status of all preparations, most recently started first
+    pub fn all(&self) -> Vec<PreparationStatus> {
+        let mut preparations: Vec<_> = self
+            .preparations
+            .iter()
+            .map(|preparation| (*preparation.key(), preparation.value().clone()))
+            .collect();
+        preparations.sort_by_key(|(id, preparation)| std::cmp::Reverse((preparation.started, *id)));
+        preparations.iter().map(|(_, preparation)| Self::snapshot(preparation)).collect()
+    }
+
+    /// Finish a preparation with a final update, freezing its elapsed time
+    fn update(&self, id: PreparationId, apply: impl FnOnce(&mut PreparationStatus)) {
+        if let Some(mut preparation) = self.preparations.get_mut(&id) {
+            preparation.status.elapsed_ms = preparation.started.elapsed().as_millis() as u64;
+            apply(&mut preparation.status);
+        }
+    }
+
+    /// Get the status of a preparation, updating the elapsed time if it is still running
+    fn snapshot(preparation: &TrackedPreparation) -> PreparationStatus {
+        let mut status = preparation.status.clone();
+        if !status.is_finished() {
+            status.elapsed_ms = preparation.started.elapsed().as_millis() as u64;
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preparation_tracker() {
+        let tracker = PreparationTracker::default();
+        let tx_hash = TxHash::repeat_byte(1);
+        assert!(tracker.status(0).is_none());
+        assert!(tracker.latest(&tx_hash).is_none());
+
+        let (id, _) = tracker.start(tx_hash);
+        tracker.advance(id, &ProgressMessage::with_steps("Replaying", 1, 8));
+        let status = tracker.status(id).unwrap();
+        assert_eq!(status.state, PreparationState::Preparing);
+        assert_eq!(status.phase, "Replaying");
+        assert_eq!(tracker.latest(&tx_hash).unwrap().phase, "Replaying");
+
+        tracker.fail(id, "boom");
+        let status = tracker.status(id).unwrap();
+        assert_eq!(status.state, PreparationState::Failed);
+        assert_eq!(status.error.as_deref(), Some("boom"));
+        assert_eq!(tracker.all().len(), 1);
+    }
+
+    #[test]
+    fn test_cancel_preparation() {
+        let tracker = PreparationTracker::default();
+        let tx_hash = TxHash::repeat_byte(1);
+        assert!(!tracker.cancel(0));
+
+        let (id, token) = tracker.start(tx_hash);
+        assert!(tracker.cancel(id));
+        assert!(token.is_cancelled());
+        // The preparation is only recorded as cancelled once it stopped
+        assert_eq!(tracker.status(id).unwrap().state, PreparationState::Preparing);
+
+        tracker.cancelled(id);
+        assert_eq!(tracker.status(id).unwrap().state, PreparationState::Cancelled);
+        assert!(!tracker.cancel(id));
+    }
+
+    #[test]
+    fn test_preparations_of_same_transaction_are_independent() {
+        let tracker = PreparationTracker::default();
+        let tx_hash = TxHash::repeat_byte(1);
+
+        let (first, first_token) = tracker.start(tx_hash);
+        let (second, second_token) = tracker.start(tx_hash);
+        assert_ne!(first, second);
+        assert_eq!(tracker.all().len(), 2);
+
+        // Cancelling one preparation leaves the other running
+        assert!(tracker.cancel(first));
+        assert!(first_token.is_cancelled());
+        assert!(!second_token.is_cancelled());
+        tracker.cancelled(first);
+        tracker.advance(second, &ProgressMessage::new("Replaying"));
+        assert_eq!(tracker.status(second).unwrap().state, PreparationState::Preparing);
+        assert_eq!(tracker.latest(&tx_hash).unwrap().phase, "Replaying");
+
+        // Only running preparations of the transaction are cancelled at once
+        assert!(tracker.cancel_transaction(&tx_hash));
+        assert!(second_token.is_cancelled());
+        tracker.finish(second);
+        assert!(!tracker.cancel_transaction(&tx_hash));
+        assert!(!tracker.cancel_transaction(&TxHash::repeat_byte(2)));
+    }
+}
@@ -0,0 +1,126 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Test fixtures: debugging sessions of transactions executed on an in-memory state.
+//!
+//! Sessions are prepared like the ones of [`crate::Engine`], without any source code: the
+//! trace is collected by replaying the transaction and every contract is snapshotted at
+//! the opcode level.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use alloy_primitives::{Address, Bytes, TxHash, TxKind};
+use edb_common::{types::ExecutionFrameId, EdbContext, ForkInfo};
+use revm::{
+    bytecode::Bytecode,
+    context::TxEnv,
+    database::{CacheDB, EmptyDB},
+    primitives::hardfork::SpecId,
+    state::AccountInfo,
+    Context, MainContext,
+};
+
+use crate::{
+    capture_opcode_level_snapshots, get_time_travel_snapshots, replay_and_collect_trace,
+    EngineContext, HookSnapshots, SnapshotDetail,
+};
+
+/// Database of the test sessions
+pub(crate) type TestDb = CacheDB<EmptyDB>;
+
+/// Sender of the test transactions
+pub(crate) const CALLER: Address = Address::with_last_byte(0xc0);
+
+/// State holding the given contracts
+pub(crate) fn state_with_contracts(contracts: &[(Address, Bytes)]) -> CacheDB<TestDb> {
+    let mut db = CacheDB::new(CacheDB::new(EmptyDB::default()));
+    for (address, code) in contracts {
+        let info = AccountInfo::from_bytecode(Bytecode::new_raw(code.clone()));
+        db.insert_account_info(*address, info);
+    }
+    db
+}
+
+/// Prepare the debugging session of a call from [`CALLER`] to `target` without calldata
+pub(crate) fn build_session(
+    db: CacheDB<TestDb>,
+    target: Address,
+    spec: SpecId,
+) -> EngineContext<TestDb> {
+    let mut ctx: EdbContext<TestDb> = Context::mainnet().with_db(db);
+    ctx.cfg.spec = spec;
+    let tx = TxEnv {
+        caller: CALLER,
+        kind: TxKind::Call(target),
+        gas_limit: 1_000_000,
+        ..Default::default()
+    };
+
+    let replay = replay_and_collect_trace(ctx.clone(), tx.clone()).expect("transaction replays");
+    let opcode_snapshots = capture_opcode_level_snapshots(
+        ctx.clone(),
+        tx.clone(),
+        HashSet::new(),
+        &replay.execution_trace,
+        None,
+    )
+    .expect("opcode snapshots are captured");
+    let snapshots = get_time_travel_snapshots(opcode_snapshots, HookSnapshots::default())
+        .expect("snapshots are merged");
+
+    let fork_info = ForkInfo {
+        block_number: 0,
+        block_hash: Default::default(),
+        timestamp: 0,
+        chain_id: 1,
+        spec_id: spec,
+    };
+    let mut context = EngineContext::build(
+        fork_info,
+        ctx.cfg.clone(),
+        ctx.block.clone(),
+        tx,
+        TxHash::ZERO,
+        snapshots,
+        HashMap::new(),
+        HashMap::new(),
+        HashMap::new(),
+        replay.execution_trace,
+        replay.uninitialized_reads,
+        replay.access_lists,
+    )
+    .expect("session is built");
+    context.initial_db = Some(Arc::new(ctx.db().clone()));
+    context
+}
+
+/// Id of the first snapshot of a frame before the instruction at `pc`
+pub(crate) fn opcode_snapshot_at(
+    context: &EngineContext<TestDb>,
+    frame_id: ExecutionFrameId,
+    pc: usize,
+) -> usize {
+    context
+        .snapshots
+        .iter()
+        .position(|(id, snapshot)| {
+            *id == frame_id && matches!(snapshot.detail(), SnapshotDetail::Opcode(o) if o.pc == pc)
+        })
+        .unwrap_or_else(|| panic!("no snapshot of frame {frame_id} at PC {pc}"))
+}
@@ -32,7 +32,7 @@
 //! ## Etherscan Integration
 //! - **Creation Data Caching**: Local caching of contract creation transaction data
 //! - **API Key Management**: Automatic API key rotation for rate limit handling
-//! - **Chain Support**: Multi-chain support through configurable Etherscan endpoints
+//! - **Chain Support**: Multi-chain support through per-chain explorer endpoints
 //!
 //! # Workflow Integration
 //!
@@ -44,14 +44,14 @@
 //! # Usage Example
 //!
 //! ```rust,ignore
-//! let mut tweaker = CodeTweaker::new(&mut edb_context, rpc_url, etherscan_api_key);
+//! let mut tweaker = CodeTweaker::new(&mut edb_context, rpc_url, config.explorer(chain_id));
 //! tweaker.tweak(&contract_address, &original_artifact, &instrumented_artifact, false).await?;
 //! ```
 //!
 //! This replaces the deployed bytecode at `contract_address` with the instrumented version,
 //! enabling advanced debugging features on the modified contract.
 
-use std::env;
+use std::{env, time::Duration};
 
 use alloy_primitives::{Address, Bytes, TxHash};
 use edb_common::{
@@ -59,7 +59,7 @@ use edb_common::{
     ForkResult,
 };
 use eyre::Result;
-use foundry_block_explorers::{contract::ContractCreationData, Client};
+use foundry_block_explorers::contract::ContractCreationData;
 use revm::{
     context::{Cfg, ContextTr},
     database::CacheDB,
@@ -69,7 +69,7 @@ use revm::{
 };
 use tracing::{debug, error};
 
-use crate::{next_etherscan_api_key, Artifact, TweakInspector};
+use crate::{Artifact, ExplorerConfig, TweakInspector};
 
 /// Utility for modifying deployed contract bytecode through creation transaction replay.
 ///
@@ -88,7 +88,7 @@ where
 {
     ctx: &'a mut EdbContext<DB>,
     rpc_url: String,
-    etherscan_api_key: Option<String>,
+    explorer: ExplorerConfig,
 }
 
 impl<'a, DB> CodeTweaker<'a, DB>
@@ -103,13 +103,9 @@ where
     ///
     /// * `ctx` - Mutable reference to the EDB context containing the database
     /// * `rpc_url` - RPC endpoint URL for fetching blockchain data
-    /// * `etherscan_api_key` - Optional Etherscan API key for fetching contract creation data
-    pub fn new(
-        ctx: &'a mut EdbContext<DB>,
-        rpc_url: String,
-        etherscan_api_key: Option<String>,
-    ) -> Self {
-        Self { ctx, rpc_url, etherscan_api_key }
+    /// * `explorer` - Block explorer of the chain, for fetching contract creation data
+    pub fn new(ctx: &'a mut EdbContext<DB>, rpc_url: String, explorer: ExplorerConfig) -> Self {
+        Self { ctx, rpc_url, explorer }
     }
 
     /// Replaces deployed contract bytecode with instrumented bytecode from artifacts.
@@ -225,17 +221,14 @@ where
         if let Some(creation_data) = cache.load_cache(&label) {
             Ok(creation_data.transaction_hash)
         } else {
-            let etherscan_api_key =
-                self.etherscan_api_key.clone().unwrap_or(next_etherscan_api_key());
-
             // Build client
-            let etherscan = Client::builder()
-                .with_api_key(etherscan_api_key)
-                .chain(chain_id.into())?
-                .build()?;
+            let mut etherscan = self.explorer.client(chain_id, None, Duration::ZERO)?;
 
             // Get creation tx
-            let creation_data = etherscan.contract_creation_data(*addr).await?;
+            let addr = *addr;
+            let creation_data = etherscan
+                .request(|client| async move { client.contract_creation_data(addr).await })
+                .await?;
             cache.save_cache(&label, &creation_data)?;
             Ok(creation_data.transaction_hash)
         }
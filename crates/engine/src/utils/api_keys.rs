@@ -0,0 +1,168 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API key pools with rotation and cooldown.
+//!
+//! Services such as Etherscan rate limit each API key. A provider holding several keys
+//! spreads its requests over all of them, round robin, and a key reported as rate limited
+//! is put on cooldown: it is skipped until the cooldown ends, unless every key of the pool
+//! is cooling down, in which case the key available the soonest is handed out.
+//!
+//! Pools built from the same keys are shared through [`shared_key_pool`], so that the
+//! rotation and cooldowns hold across all clients of a provider.
+
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Time a rate-limited key is skipped for
+pub const DEFAULT_KEY_COOLDOWN: Duration = Duration::from_secs(2);
+
+/// Pools built from configured keys, by their keys
+static SHARED_POOLS: Lazy<Mutex<HashMap<Vec<String>, Arc<ApiKeyPool>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Keys of a provider, rotated round robin
+#[derive(Debug)]
+pub struct ApiKeyPool {
+    /// Keys of the pool, each with the end of its cooldown if rate limited
+    keys: Mutex<Vec<(String, Option<Instant>)>>,
+    /// Index of the next key to hand out
+    next: Mutex<usize>,
+    /// Time a rate-limited key is skipped for
+    cooldown: Duration,
+}
+
+impl ApiKeyPool {
+    /// Create a pool of keys, duplicates and empty keys being dropped
+    pub fn new(keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut unique: Vec<(String, Option<Instant>)> = Vec::new();
+        for key in keys.into_iter().map(Into::into) {
+            let key = key.trim().to_string();
+            if !key.is_empty() && !unique.iter().any(|(other, _)| *other == key) {
+                unique.push((key, None));
+            }
+        }
+        Self { keys: Mutex::new(unique), next: Mutex::new(0), cooldown: DEFAULT_KEY_COOLDOWN }
+    }
+
+    /// Set the time a rate-limited key is skipped for
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Number of keys in the pool
+    pub fn len(&self) -> usize {
+        self.keys.lock().unwrap().len()
+    }
+
+    /// Whether the pool holds no key
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hand out the next key not cooling down, or the key available the soonest if all are
+    pub fn next_key(&self) -> Option<String> {
+        let keys = self.keys.lock().unwrap();
+        if keys.is_empty() {
+            return None;
+        }
+
+        let now = Instant::now();
+        let mut next = self.next.lock().unwrap();
+        let start = *next;
+        let index = (0..keys.len())
+            .map(|offset| (start + offset) % keys.len())
+            .find(|&index| keys[index].1.is_none_or(|until| until <= now))
+            .unwrap_or_else(|| {
+                (0..keys.len()).min_by_key(|&index| keys[index].1).unwrap_or_default()
+            });
+        *next = index + 1;
+
+        Some(keys[index].0.clone())
+    }
+
+    /// Put a key on cooldown after it was rate limited
+    pub fn report_rate_limited(&self, key: &str) {
+        let mut keys = self.keys.lock().unwrap();
+        if let Some((_, until)) = keys.iter_mut().find(|(other, _)| other == key) {
+            *until = Some(Instant::now() + self.cooldown);
+        }
+    }
+
+    /// Time until a key is out of cooldown, zero if it is not cooling down
+    pub fn cooldown_remaining(&self, key: &str) -> Duration {
+        let keys = self.keys.lock().unwrap();
+        keys.iter()
+            .find(|(other, _)| other == key)
+            .and_then(|(_, until)| *until)
+            .map_or(Duration::ZERO, |until| until.saturating_duration_since(Instant::now()))
+    }
+}
+
+/// Get the pool of a set of keys, shared by every caller giving the same keys
+pub fn shared_key_pool(keys: &[String]) -> Arc<ApiKeyPool> {
+    let mut pools = SHARED_POOLS.lock().unwrap();
+    pools.entry(keys.to_vec()).or_insert_with(|| Arc::new(ApiKeyPool::new(keys.to_vec()))).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation() {
+        let pool = ApiKeyPool::new(["a", "b", "", "a", "c"]);
+        assert_eq!(pool.len(), 3);
+
+        let keys: Vec<_> = (0..4).map(|_| pool.next_key().unwrap()).collect();
+        assert_eq!(keys, ["a", "b", "c", "a"]);
+        assert!(ApiKeyPool::new(Vec::<String>::new()).next_key().is_none());
+    }
+
+    #[test]
+    fn test_cooldown() {
+        let pool = ApiKeyPool::new(["a", "b"]).with_cooldown(Duration::from_secs(60));
+
+        // A rate-limited key is skipped
+        pool.report_rate_limited("a");
+        assert_eq!(pool.next_key().as_deref(), Some("b"));
+        assert_eq!(pool.next_key().as_deref(), Some("b"));
+        assert!(pool.cooldown_remaining("a") > Duration::ZERO);
+        assert_eq!(pool.cooldown_remaining("b"), Duration::ZERO);
+
+        // Once all keys are cooling down, the first one to recover is handed out
+        pool.report_rate_limited("b");
+        assert_eq!(pool.next_key().as_deref(), Some("a"));
+
+        // Cooldowns end
+        let pool = ApiKeyPool::new(["a", "b"]).with_cooldown(Duration::ZERO);
+        pool.report_rate_limited("a");
+        assert_eq!(pool.next_key().as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_shared_pool() {
+        let keys = vec!["shared-1".to_string(), "shared-2".to_string()];
+        let pool = shared_key_pool(&keys);
+        pool.report_rate_limited("shared-1");
+        assert!(shared_key_pool(&keys).cooldown_remaining("shared-1") > Duration::ZERO);
+    }
+}
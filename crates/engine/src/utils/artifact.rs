@@ -35,7 +35,9 @@
 //! - **Code Tweaking**: Supporting bytecode replacement through recompilation
 //! - **Analysis Engine**: Providing source code and ABI data for instrumentation
 
-use alloy_primitives::Bytes;
+use std::collections::BTreeMap;
+
+use alloy_primitives::{Address, Bytes};
 use foundry_block_explorers::contract::Metadata;
 use foundry_compilers::artifacts::{CompilerOutput, Contract, SolcInput};
 use serde::{Deserialize, Serialize};
@@ -106,4 +108,68 @@ impl Artifact {
 
         hooks
     }
+
+    /// Libraries the subject contract is linked against, as `(file, name) -> address`.
+    ///
+    /// Libraries configured in the compiler settings are returned as is. Link
+    /// references left unresolved in the compiled runtime code are resolved by
+    /// reading the addresses embedded at the same offsets of `deployed_code`.
+    pub fn linked_libraries(&self, deployed_code: &[u8]) -> BTreeMap<(String, String), Address> {
+        let mut libraries = BTreeMap::new();
+
+        for (file, libs) in &self.input.settings.libraries.libs {
+            for (name, address) in libs {
+                if let Ok(address) = address.parse::<Address>() {
+                    libraries.insert((file.to_string_lossy().to_string(), name.clone()), address);
+                }
+            }
+        }
+
+        let Some(bytecode) = self
+            .contract()
+            .and_then(|c| c.evm.as_ref())
+            .and_then(|e| e.deployed_bytecode.as_ref())
+            .and_then(|d| d.bytecode.as_ref())
+        else {
+            return libraries;
+        };
+
+        for (file, libs) in &bytecode.link_references {
+            for (name, offsets) in libs {
+                let Some(offset) = offsets.first() else { continue };
+                let start = offset.start as usize;
+                let Some(address) = deployed_code.get(start..start + Address::len_bytes()) else {
+                    continue;
+                };
+                libraries
+                    .entry((file.clone(), name.clone()))
+                    .or_insert(Address::from_slice(address));
+            }
+        }
+
+        libraries
+    }
+
+    /// Whether the subject contract still has unresolved link references.
+    pub fn has_unlinked_libraries(&self) -> bool {
+        self.contract()
+            .and_then(|c| c.evm.as_ref())
+            .and_then(|e| e.deployed_bytecode.as_ref())
+            .and_then(|d| d.bytecode.as_ref())
+            .is_some_and(|b| !b.link_references.is_empty())
+    }
+
+    /// Artifact of library `name` compiled within the same compilation unit.
+    ///
+    /// The returned artifact shares the compiler input and output of this one,
+    /// with the library as its subject contract.
+    pub fn library_artifact(&self, name: &str) -> Option<Self> {
+        let contract = self.output.contracts.values().find_map(|c| c.get(name))?;
+        contract.evm.as_ref()?;
+
+        let mut artifact = self.clone();
+        artifact.meta.contract_name = name.to_string();
+        artifact.meta.constructor_arguments = Bytes::new();
+        Some(artifact)
+    }
 }
@@ -235,7 +235,10 @@ mod tests {
     use foundry_block_explorers::Client;
     use semver::Version;
 
-    use crate::{test_utils::compile_contract_source_to_source_unit, utils::OnchainCompiler};
+    use crate::{
+        test_utils::compile_contract_source_to_source_unit,
+        utils::{ExplorerClient, OnchainCompiler},
+    };
 
     use super::*;
 
@@ -245,15 +248,16 @@ mod tests {
         let cache_path = EdbCachePath::new(env::var(edb_common::env::EDB_CACHE_DIR).ok());
         let cache_ttl = Duration::from_secs(u32::MAX as u64); // we don't want the cache to expire
 
-        let client = Client::builder()
+        let mut client: ExplorerClient = Client::builder()
             .chain(chain)?
             .with_cache(cache_path.etherscan_chain_cache_dir(chain), cache_ttl)
-            .build()?;
+            .build()?
+            .into();
 
         let compiler = OnchainCompiler::new(cache_path.compiler_chain_cache_dir(chain))?;
 
         let mut artifact =
-            compiler.compile(&client, addr).await?.ok_or_eyre("missing compiler output")?;
+            compiler.compile(&mut client, addr).await?.ok_or_eyre("missing compiler output")?;
         for (_, contract) in artifact.output.sources.iter_mut() {
             ASTPruner::convert(contract.ast.as_mut().ok_or_eyre("AST does not exist")?, true)?;
         }
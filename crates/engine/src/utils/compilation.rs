@@ -41,7 +41,7 @@ use std::{collections::HashSet, env, path::PathBuf, sync::Mutex, thread, time::D
 use alloy_primitives::Address;
 use edb_common::{Cache, EdbCache};
 use eyre::Result;
-use foundry_block_explorers::{contract::Metadata, errors::EtherscanError, Client};
+use foundry_block_explorers::{contract::Metadata, errors::EtherscanError};
 use foundry_compilers::{
     artifacts::{output_selection::OutputSelection, Libraries, SolcInput, Source, Sources},
     solc::{Solc, SolcLanguage},
@@ -51,7 +51,7 @@ use once_cell::sync::Lazy;
 use semver::Version;
 use tracing::{debug, error, info, trace};
 
-use crate::{etherscan_rate_limit_guard, Artifact};
+use crate::{find_configured_solc, Artifact, ExplorerClient};
 
 /// Onchain compiler.
 #[derive(Debug, Clone)]
@@ -70,10 +70,16 @@ impl OnchainCompiler {
     }
 
     /// Compile the contract at the given address.
+    /// Requests rate limited by the explorer are retried with another key of its pool, and
+    /// its verified source is served from the cache if the explorer cannot be reached.
     /// Returns `Some`` if the contract is successfully compiled.
     /// Returns `None` if the contract is not verified, is a Vyper contract, or it is a Solidity
     /// 0.4.x contract which does not support --stand-json option.
-    pub async fn compile(&self, etherscan: &Client, addr: Address) -> Result<Option<Artifact>> {
+    pub async fn compile(
+        &self,
+        etherscan: &mut ExplorerClient,
+        addr: Address,
+    ) -> Result<Option<Artifact>> {
         // Get the cache_root. If not provided, use the default cache directory.
         if let Some(output) = self.cache.load_cache(addr.to_string()) {
             Ok(output)
@@ -85,21 +91,20 @@ impl OnchainCompiler {
                 return Ok(None);
             }
 
-            let mut meta =
-                match etherscan_rate_limit_guard!(etherscan.contract_source_code(addr).await) {
-                    Ok(meta) => meta,
-                    Err(EtherscanError::ContractCodeNotVerified(_)) => {
-                        // We do not cache the fact that the contract is not verified, since it may be
-                        // verified later.
-                        info!(address=?addr, "contract is not verified");
-                        return Ok(None);
-                    }
-                    Err(e) => {
-                        // We do not cache since it could be caused by network issues.
-                        error!(address=?addr, "failed to query Etherscan: {e}");
-                        return Ok(None);
-                    }
-                };
+            let mut meta = match etherscan.contract_source_code(addr).await {
+                Ok(meta) => meta,
+                Err(EtherscanError::ContractCodeNotVerified(_)) => {
+                    // We do not cache the fact that the contract is not verified, since it may be
+                    // verified later.
+                    info!(address=?addr, "contract is not verified");
+                    return Ok(None);
+                }
+                Err(e) => {
+                    // We do not cache since it could be caused by network issues.
+                    error!(address=?addr, "failed to query Etherscan: {e}");
+                    return Ok(None);
+                }
+            };
             eyre::ensure!(meta.items.len() == 1, "contract not found or ill-formed");
             let meta = meta.items.remove(0);
 
@@ -112,9 +117,13 @@ impl OnchainCompiler {
 
             let input = get_compilation_input_from_metadata(&meta, addr)?;
 
-            // prepare the compiler
+            // prepare the compiler, without blocking the compilation of other contracts while
+            // it is being installed
             let version = meta.compiler_version()?;
-            let compiler = find_or_install_solc(&version)?;
+            let compiler = {
+                let version = version.clone();
+                tokio::task::spawn_blocking(move || find_or_install_solc(&version)).await??
+            };
             trace!(addr=?addr, compiler=?compiler, "using compiler");
 
             // compile the source code
@@ -185,6 +194,9 @@ pub fn get_compilation_input_from_metadata(meta: &Metadata, addr: Address) -> Re
 static INSTALL_LOCKS: Lazy<Mutex<HashSet<Version>>> = Lazy::new(|| Mutex::new(HashSet::new()));
 
 /// Find or install the specified solc version (lock when concurrent).
+///
+/// Binaries are taken from the local directory or mirror of the [`crate::SolcConfig`] of
+/// the process if one is configured, and installed by svm otherwise.
 pub fn find_or_install_solc(version: &Version) -> Result<Solc> {
     // Acquire global lock to check/insert version lock
     let mut locks = INSTALL_LOCKS.lock().unwrap();
@@ -201,12 +213,15 @@ pub fn find_or_install_solc(version: &Version) -> Result<Solc> {
     drop(locks);
 
     // Do the actual find/install
-    let result = Solc::find_or_install(version);
+    let result = find_configured_solc(version).and_then(|solc| match solc {
+        Some(solc) => Ok(solc),
+        None => Solc::find_or_install(version).map_err(Into::into),
+    });
 
     // Remove the lock
     INSTALL_LOCKS.lock().unwrap().remove(version);
 
-    result.map_err(Into::into)
+    result
 }
 
 #[cfg(test)]
@@ -216,6 +231,8 @@ mod tests {
     use alloy_chains::Chain;
     use serial_test::serial;
 
+    use foundry_block_explorers::Client;
+
     use crate::utils::next_etherscan_api_key;
 
     use super::*;
@@ -224,15 +241,16 @@ mod tests {
         let etherscan_cache_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
             .join("../../testdata/cache/etherscan")
             .join(chain_id.to_string());
-        let etherscan = Client::builder()
+        let mut etherscan: ExplorerClient = Client::builder()
             .with_api_key(next_etherscan_api_key())
             .with_cache(Some(etherscan_cache_root), Duration::from_secs(24 * 60 * 60)) // 24 hours
             .chain(chain_id)?
-            .build()?;
+            .build()?
+            .into();
 
         // We disable the cache for testing.
         let compiler = OnchainCompiler::new(None)?;
-        compiler.compile(&etherscan, Address::from_str(addr)?).await
+        compiler.compile(&mut etherscan, Address::from_str(addr)?).await
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -25,31 +25,45 @@
 //! - PUSH instructions with their immediate values (PUSH1 through PUSH32)
 //! - Proper instruction boundary detection
 //! - Invalid opcodes identification
+//! - EOF containers, whose code sections are disassembled with the immediates of the
+//!   EOF-only opcodes (RJUMP family, CALLF, DUPN/SWAPN/EXCHANGE, ...)
 
 use alloy_primitives::{Bytes, U256};
+use edb_common::eof::{self, EofContainer};
 use revm::bytecode::opcode::OpCode;
 
 /// A single disassembled instruction with its associated data
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DisassemblyInstruction {
-    /// Program counter offset where this instruction starts
+    /// Program counter offset where this instruction starts (within the container for
+    /// EOF code sections)
     pub pc: usize,
     /// The opcode for this instruction
     pub opcode: OpCode,
     /// For PUSHX instructions, this contains the immediate value bytes
     /// For other instructions, this is empty
     pub push_data: Vec<u8>,
+    /// For EOF-only instructions, this contains the immediate bytes
+    /// For other instructions, this is empty
+    pub immediates: Vec<u8>,
+    /// For instructions of an EOF container, the code section they belong to
+    pub code_section: Option<usize>,
 }
 
 impl DisassemblyInstruction {
     /// Create a new instruction without push data
     pub fn new(pc: usize, opcode: OpCode) -> Self {
-        Self { pc, opcode, push_data: Vec::new() }
+        Self { pc, opcode, push_data: Vec::new(), immediates: Vec::new(), code_section: None }
     }
 
     /// Create a new instruction with push data
     pub fn with_push_data(pc: usize, opcode: OpCode, push_data: Vec<u8>) -> Self {
-        Self { pc, opcode, push_data }
+        Self { pc, opcode, push_data, immediates: Vec::new(), code_section: None }
+    }
+
+    /// Check if this instruction is an EOF-only instruction
+    pub fn is_eof_only(&self) -> bool {
+        self.code_section.is_some() && eof::name(self.opcode.get()).is_some()
     }
 
     /// Check if this instruction is a PUSH instruction
@@ -70,7 +84,7 @@ impl DisassemblyInstruction {
 
     /// Get the total instruction size (opcode + immediate data)
     pub fn instruction_size(&self) -> usize {
-        1 + self.push_size()
+        1 + self.push_size() + self.immediates.len()
     }
 }
 
@@ -81,12 +95,14 @@ pub struct DisassemblyResult {
     pub bytecode: Bytes,
     /// List of disassembled instructions in order
     pub instructions: Vec<DisassemblyInstruction>,
+    /// Sections of the bytecode, if it is an EOF container
+    pub container: Option<EofContainer>,
 }
 
 impl DisassemblyResult {
     /// Create a new disassembly result
     pub fn new(bytecode: Bytes, instructions: Vec<DisassemblyInstruction>) -> Self {
-        Self { bytecode, instructions }
+        Self { bytecode, instructions, container: None }
     }
 
     /// Get the total number of instructions
@@ -121,6 +137,9 @@ impl DisassemblyResult {
 /// associated immediate data (for PUSH instructions). It properly handles
 /// instruction boundaries and invalid opcodes.
 ///
+/// EOF containers are disassembled section by section: only their code sections hold
+/// instructions, whose `pc` is the offset within the container.
+///
 /// # Arguments
 /// * `bytecode` - The bytecode to disassemble
 ///
@@ -139,6 +158,10 @@ impl DisassemblyResult {
 /// assert_eq!(result.instructions[0].push_data, vec![0x42]);
 /// ```
 pub fn disassemble(bytecode: &Bytes) -> DisassemblyResult {
+    if let Some(container) = EofContainer::parse(bytecode) {
+        return disassemble_eof(bytecode, container);
+    }
+
     let mut instructions = Vec::new();
     let mut pc = 0;
 
@@ -177,6 +200,41 @@ pub fn disassemble(bytecode: &Bytes) -> DisassemblyResult {
     DisassemblyResult::new(bytecode.clone(), instructions)
 }
 
+/// Disassemble the code sections of an EOF container
+fn disassemble_eof(bytecode: &Bytes, container: EofContainer) -> DisassemblyResult {
+    let mut instructions = Vec::new();
+
+    for (index, section) in container.code_sections.iter().enumerate() {
+        let code = &bytecode[section.clone()];
+        let mut pc = 0;
+
+        while pc < code.len() {
+            let opcode_byte = code[pc];
+            let opcode = unsafe { OpCode::new_unchecked(opcode_byte) };
+
+            // Extract immediate data, padding with zeros if the section is truncated
+            let immediate_size = eof::immediate_size(code, pc);
+            let data = (pc + 1..pc + 1 + immediate_size)
+                .map(|i| code.get(i).copied().unwrap_or_default())
+                .collect();
+
+            let mut instruction = if (0x60..=0x7F).contains(&opcode_byte) {
+                DisassemblyInstruction::with_push_data(section.start + pc, opcode, data)
+            } else {
+                let mut instruction = DisassemblyInstruction::new(section.start + pc, opcode);
+                instruction.immediates = data;
+                instruction
+            };
+            instruction.code_section = Some(index);
+
+            instructions.push(instruction);
+            pc += 1 + immediate_size;
+        }
+    }
+
+    DisassemblyResult { bytecode: bytecode.clone(), instructions, container: Some(container) }
+}
+
 /// Extract immediate value from a PUSH instruction as a big-endian integer
 ///
 /// This helper function converts the bytes from a PUSH instruction into a big-endian
@@ -241,7 +299,9 @@ pub fn extract_push_value(instruction: &DisassemblyInstruction) -> Option<U256>
 pub fn format_instruction(instruction: &DisassemblyInstruction, show_pc: bool) -> String {
     let pc_part = if show_pc { format!("{:04x}: ", instruction.pc) } else { String::new() };
 
-    let opcode_name = if instruction.opcode.is_valid() {
+    let opcode_name = if instruction.is_eof_only() {
+        eof::name(instruction.opcode.get()).unwrap_or_default().to_string()
+    } else if instruction.opcode.is_valid() {
         instruction.opcode.as_str().to_string()
     } else {
         format!("'{:x}'(Unknown Opcode)", instruction.opcode.get())
@@ -250,11 +310,35 @@ pub fn format_instruction(instruction: &DisassemblyInstruction, show_pc: bool) -
     if instruction.is_push() && !instruction.push_data.is_empty() {
         let hex_data = instruction.push_data.iter().map(|b| format!("{b:02x}")).collect::<String>();
         format!("{pc_part}{opcode_name} 0x{hex_data}")
+    } else if !instruction.immediates.is_empty() {
+        format!("{pc_part}{opcode_name} {}", format_immediates(instruction))
     } else {
         format!("{pc_part}{opcode_name}")
     }
 }
 
+/// Format the immediates of an EOF-only instruction
+///
+/// Relative jumps show their signed offsets, and EXCHANGE the two stack depths
+/// packed in its immediate. Other immediates are shown as unsigned integers.
+fn format_immediates(instruction: &DisassemblyInstruction) -> String {
+    let immediates = &instruction.immediates;
+    let offset = |bytes: &[u8]| format!("{:+}", i16::from_be_bytes([bytes[0], bytes[1]]));
+
+    match instruction.opcode.get() {
+        eof::RJUMP | eof::RJUMPI if immediates.len() == 2 => offset(immediates),
+        eof::RJUMPV => {
+            let offsets: Vec<_> = immediates[1..].chunks_exact(2).map(offset).collect();
+            format!("[{}]", offsets.join(", "))
+        }
+        eof::EXCHANGE => {
+            let packed = immediates[0];
+            format!("{} {}", (packed >> 4) + 1, (packed & 0x0F) + 1)
+        }
+        _ => immediates.iter().fold(0u64, |value, byte| (value << 8) | *byte as u64).to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,4 +468,49 @@ mod tests {
         let regular_formatted = format_instruction(&regular_inst, true);
         assert_eq!(regular_formatted, "0005: DUP1");
     }
+
+    #[test]
+    fn test_disassemble_eof() {
+        let bytecode = Bytes::from(vec![
+            0xEF, 0x00, 0x01, // magic and version
+            0x01, 0x00, 0x04, // types section
+            0x02, 0x00, 0x01, 0x00, 0x0B, // one code section of 11 bytes
+            0xFF, 0x00, 0x00, // empty data section
+            0x00, // terminator
+            0x00, 0x80, 0x00, 0x02, // types
+            0x60, 0x01, // PUSH1 0x01
+            0xE1, 0xFF, 0xFB, // RJUMPI -5
+            0xE6, 0x00, // DUPN 0
+            0xE8, 0x12, // EXCHANGE 2 3
+            0xE2, 0x00, // RJUMPV with a truncated table
+        ]);
+        let result = disassemble(&bytecode);
+
+        assert!(result.container.is_some());
+        let pcs: Vec<_> = result.instructions.iter().map(|inst| inst.pc).collect();
+        assert_eq!(pcs, vec![19, 21, 24, 26, 28]);
+        assert!(result.instructions.iter().all(|inst| inst.code_section == Some(0)));
+
+        let formatted: Vec<_> =
+            result.instructions.iter().map(|inst| format_instruction(inst, false)).collect();
+        assert_eq!(
+            formatted,
+            vec!["PUSH1 0x01", "RJUMPI -5", "DUPN 0", "EXCHANGE 2 3", "RJUMPV [+0]"]
+        );
+
+        // The truncated jump table is padded with zeros
+        assert_eq!(result.instructions[4].instruction_size(), 4);
+        assert_eq!(result.find_instruction_containing_pc(22).unwrap().pc, 21);
+    }
+
+    #[test]
+    fn test_eof_opcodes_in_legacy_code() {
+        // Bytes of EOF-only opcodes are invalid in legacy code, without immediates
+        let bytecode = Bytes::from(vec![0xE0, 0x00, 0x00]);
+        let result = disassemble(&bytecode);
+
+        assert!(result.container.is_none());
+        assert_eq!(result.instructions.len(), 3);
+        assert!(!result.instructions[0].is_eof_only());
+    }
 }
@@ -26,18 +26,52 @@
 //! - **API Key Rotation**: Automatic rotation between multiple API keys
 //! - **Rate Limit Handling**: Built-in support for managing API rate limits
 //! - **Randomized Key Pool**: Shuffled key order to distribute load
+//! - **Per-Chain Explorers**: Custom explorer endpoints and keys by chain ID
+//! - **Cooldown**: Rate-limited keys are skipped for a while, see [`ApiKeyPool`]
+//! - **Retries**: Transient failures (timeouts, server errors, Cloudflare) are retried with
+//!   exponential backoff and jitter
+//! - **Offline Mode**: Verified sources are kept in a persistent cache, which serves them
+//!   when the explorer is unreachable or configured offline
 //!
 //! # Usage
 //!
 //! The module provides a simple interface through [`next_etherscan_api_key()`]
 //! which returns the next available API key in the rotation pool.
+//!
+//! Etherscan's v2 API serves every chain it supports with a single key, so chains only
+//! need an [`ExplorerConfig`] of their own to use another explorer (e.g. a Blockscout
+//! instance) or another key.
 
+use alloy_primitives::Address;
+use edb_common::{Cache, EdbCache};
+use eyre::Result;
+use foundry_block_explorers::{contract::ContractMetadata, errors::EtherscanError, Client};
 use once_cell::sync::Lazy;
 use rand::seq::SliceRandom;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, path::PathBuf, sync::Arc, time::Duration};
+use tracing::{debug, warn};
+
+use super::{shared_key_pool, ApiKeyPool};
+
+/// Delay before retrying a rate-limited request when there is no other key to switch to
+const RATE_LIMIT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Retries of a request failing with a transient error, before giving up
+const MAX_TRANSIENT_RETRIES: u32 = 4;
 
-// List of etherscan keys for mainnet
-static ETHERSCAN_MAINNET_KEYS: Lazy<Vec<&'static str>> = Lazy::new(|| {
+/// Delay before the first retry of a request failing with a transient error, doubled on
+/// every further retry
+const TRANSIENT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Longest delay between two retries of a request failing with a transient error
+const TRANSIENT_RETRY_MAX_DELAY: Duration = Duration::from_secs(8);
+
+/// Subdirectory of the explorer cache of a chain holding the verified sources
+const VERIFIED_SOURCES_DIR: &str = "verified_sources";
+
+// Pool of the built-in etherscan keys
+static ETHERSCAN_KEYS: Lazy<Arc<ApiKeyPool>> = Lazy::new(|| {
     let mut keys = vec![
         "MCAUM7WPE9XP5UQMZPCKIBUJHPM1C24FP6",
         "JW6RWCG2C5QF8TANH4KC7AYIF1CX7RB5D1",
@@ -52,24 +86,285 @@ static ETHERSCAN_MAINNET_KEYS: Lazy<Vec<&'static str>> = Lazy::new(|| {
 
     keys.shuffle(&mut rand::rng());
 
-    keys
+    Arc::new(ApiKeyPool::new(keys))
 });
 
-// counts the next etherscan key to use
-static NEXT_ETHERSCAN_MAINNET_KEY: AtomicUsize = AtomicUsize::new(0);
-
-// returns the current value of the atomic counter and increments it
-fn next(c: &AtomicUsize) -> usize {
-    c.fetch_add(1, Ordering::SeqCst)
+/// Returns the pool of the built-in Etherscan API keys.
+pub fn etherscan_key_pool() -> Arc<ApiKeyPool> {
+    ETHERSCAN_KEYS.clone()
 }
 
 /// Returns the next Etherscan API key to use from the rotation pool.
 ///
 /// This function implements a round-robin rotation strategy across the available
 /// API keys to distribute load and avoid hitting rate limits on any single key.
+/// Keys reported as rate limited are skipped while on cooldown.
 pub fn next_etherscan_api_key() -> String {
-    let idx = next(&NEXT_ETHERSCAN_MAINNET_KEY) % ETHERSCAN_MAINNET_KEYS.len();
-    ETHERSCAN_MAINNET_KEYS[idx].to_string()
+    ETHERSCAN_KEYS.next_key().unwrap_or_default()
+}
+
+/// Block explorer API of a chain
+///
+/// ```toml
+/// [explorers.8453]
+/// api_keys = ["...", "..."]
+///
+/// [explorers.100]
+/// api_url = "https://gnosis.blockscout.com/api"
+/// url = "https://gnosis.blockscout.com"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExplorerConfig {
+    /// API key of the explorer, or several comma-separated keys
+    ///
+    /// Keys of the built-in pool are used if neither this nor `api_keys` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// More API keys of the explorer, rotated with `api_key`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    /// URL of an Etherscan-compatible explorer API, Etherscan's API of the chain if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    /// URL of the explorer website, the API URL if unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Never query the explorer, only serve the sources it returned before from the cache
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+}
+
+impl ExplorerConfig {
+    /// Whether keys are configured for the explorer
+    pub fn has_keys(&self) -> bool {
+        !self.keys().is_empty()
+    }
+
+    /// Pool of the keys of the explorer, the built-in pool if none is configured
+    pub fn key_pool(&self) -> Arc<ApiKeyPool> {
+        let keys = self.keys();
+        if keys.is_empty() {
+            etherscan_key_pool()
+        } else {
+            shared_key_pool(&keys)
+        }
+    }
+
+    /// Build a client of the explorer for a chain, using the next key of its pool
+    ///
+    /// # Arguments
+    /// * `chain_id` - Chain the explorer is queried about
+    /// * `cache` - Directory caching the responses of the explorer, if any
+    /// * `cache_ttl` - Time the cached responses are valid for; the verified sources are
+    ///   kept regardless, to be served when the explorer cannot be reached
+    pub fn client(
+        &self,
+        chain_id: u64,
+        cache: Option<PathBuf>,
+        cache_ttl: Duration,
+    ) -> Result<ExplorerClient> {
+        let pool = self.key_pool();
+        let key = pool.next_key().unwrap_or_default();
+        let client = self.build_client(chain_id, &key, cache.clone(), cache_ttl)?;
+        let sources = EdbCache::new(cache.as_ref().map(|c| c.join(VERIFIED_SOURCES_DIR)), None)?;
+
+        Ok(ExplorerClient {
+            client,
+            sources,
+            offline: self.offline,
+            rotation: Some(KeyRotation {
+                pool,
+                key,
+                explorer: self.clone(),
+                chain_id,
+                cache,
+                cache_ttl,
+            }),
+        })
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.api_key
+            .iter()
+            .flat_map(|keys| keys.split(','))
+            .chain(self.api_keys.iter().map(String::as_str))
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn build_client(
+        &self,
+        chain_id: u64,
+        key: &str,
+        cache: Option<PathBuf>,
+        cache_ttl: Duration,
+    ) -> Result<Client> {
+        let builder = Client::builder().with_api_key(key).with_cache(cache, cache_ttl);
+        let builder = match &self.api_url {
+            Some(api_url) => builder
+                .with_api_url(api_url.as_str())?
+                .with_url(self.url.as_deref().unwrap_or(api_url))?,
+            None => builder.chain(chain_id.into())?,
+        };
+        Ok(builder.build()?)
+    }
+}
+
+/// Client of a block explorer, switching to another key of its pool when rate limited and
+/// retrying requests failing with transient errors
+#[derive(Debug, Clone)]
+pub struct ExplorerClient {
+    client: Client,
+    /// Verified sources returned by the explorer, by address, kept without expiry
+    sources: Option<EdbCache<ContractMetadata>>,
+    /// Whether requests are refused instead of sent to the explorer
+    offline: bool,
+    /// Key the client uses and how to rebuild it with another one, unless built elsewhere
+    rotation: Option<KeyRotation>,
+}
+
+#[derive(Debug, Clone)]
+struct KeyRotation {
+    pool: Arc<ApiKeyPool>,
+    key: String,
+    explorer: ExplorerConfig,
+    chain_id: u64,
+    cache: Option<PathBuf>,
+    cache_ttl: Duration,
+}
+
+impl From<Client> for ExplorerClient {
+    /// Wrap a client built elsewhere, which retries rate-limited requests with its own key
+    fn from(client: Client) -> Self {
+        Self { client, sources: None, offline: false, rotation: None }
+    }
+}
+
+impl ExplorerClient {
+    /// The underlying client
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Whether requests are refused instead of sent to the explorer
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Send a request, retrying it with another key of the pool whenever it is rate limited,
+    /// and with exponential backoff when it fails with a transient error
+    pub async fn request<T, F, Fut>(&mut self, request: F) -> Result<T, EtherscanError>
+    where
+        F: Fn(Client) -> Fut,
+        Fut: Future<Output = Result<T, EtherscanError>>,
+    {
+        if self.offline {
+            return Err(EtherscanError::Unknown("the block explorer is offline".to_string()));
+        }
+
+        let mut retries = 0;
+        loop {
+            match request(self.client.clone()).await {
+                Err(EtherscanError::RateLimitExceeded) => self.rotate_key().await,
+                Err(e) if is_transient(&e) && retries < MAX_TRANSIENT_RETRIES => {
+                    let delay = transient_retry_delay(retries);
+                    debug!("Explorer request failed ({}), retrying in {:?}", e, delay);
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Get the verified source of a contract
+    ///
+    /// Sources returned by the explorer are cached without expiry. The cached source is
+    /// served when the client is offline, or when the explorer still fails after retries.
+    pub async fn contract_source_code(
+        &mut self,
+        addr: Address,
+    ) -> Result<ContractMetadata, EtherscanError> {
+        let label = addr.to_string();
+        if self.offline {
+            return self.sources.load_cache(&label).ok_or_else(|| {
+                EtherscanError::Unknown(format!(
+                    "the block explorer is offline and the source of {addr} is not cached"
+                ))
+            });
+        }
+
+        match self.request(|client| async move { client.contract_source_code(addr).await }).await {
+            Ok(meta) => {
+                if let Err(e) = self.sources.save_cache(&label, &meta) {
+                    warn!("Failed to cache the verified source of {}: {}", addr, e);
+                }
+                Ok(meta)
+            }
+            Err(e) if is_transient(&e) => match self.sources.load_cache(&label) {
+                Some(meta) => {
+                    warn!("Block explorer unavailable ({}), using cached source of {}", e, addr);
+                    Ok(meta)
+                }
+                None => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Put the current key on cooldown and switch to the next key of the pool
+    async fn rotate_key(&mut self) {
+        let Some(rotation) = &mut self.rotation else {
+            tokio::time::sleep(RATE_LIMIT_RETRY_DELAY).await;
+            return;
+        };
+
+        rotation.pool.report_rate_limited(&rotation.key);
+        let Some(key) = rotation.pool.next_key() else {
+            tokio::time::sleep(RATE_LIMIT_RETRY_DELAY).await;
+            return;
+        };
+
+        // All keys may be cooling down, wait for the one handed out
+        tokio::time::sleep(rotation.pool.cooldown_remaining(&key)).await;
+        if key == rotation.key {
+            return;
+        }
+        let cache = rotation.cache.clone();
+        match rotation.explorer.build_client(rotation.chain_id, &key, cache, rotation.cache_ttl) {
+            Ok(client) => {
+                self.client = client;
+                rotation.key = key;
+            }
+            Err(e) => warn!("Failed to switch to another explorer API key: {}", e),
+        }
+    }
+}
+
+/// Whether a request failed for a reason that may be gone when it is retried: the explorer
+/// timing out, answering with a server error, or being behind a Cloudflare challenge
+fn is_transient(error: &EtherscanError) -> bool {
+    matches!(
+        error,
+        EtherscanError::Reqwest(_)
+            | EtherscanError::BadStatusCode(_)
+            | EtherscanError::PageNotFound
+            | EtherscanError::BlockedByCloudflare
+            | EtherscanError::CloudFlareSecurityChallenge
+    )
+}
+
+/// Delay before the given retry of a request failing with a transient error
+///
+/// The delay doubles with every retry up to [`TRANSIENT_RETRY_MAX_DELAY`], and is jittered
+/// down by up to half so that concurrent downloads do not retry in lockstep.
+fn transient_retry_delay(retry: u32) -> Duration {
+    let delay = TRANSIENT_RETRY_BASE_DELAY
+        .saturating_mul(1 << retry.min(16))
+        .min(TRANSIENT_RETRY_MAX_DELAY);
+    delay.mul_f64(1.0 - rand::random::<f64>() / 2.0)
 }
 
 /// Automaticall pause the request if the rate limit is reached
@@ -102,3 +397,43 @@ macro_rules! etherscan_rate_limit_guard {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_retry_delay() {
+        for retry in 0..8 {
+            let delay = transient_retry_delay(retry);
+            let full = TRANSIENT_RETRY_BASE_DELAY * 2u32.pow(retry);
+            let full = full.min(TRANSIENT_RETRY_MAX_DELAY);
+            assert!(delay <= full && delay >= full / 2, "retry {retry}: {delay:?}");
+        }
+
+        assert!(is_transient(&EtherscanError::PageNotFound));
+        assert!(!is_transient(&EtherscanError::InvalidApiKey));
+        assert!(!is_transient(&EtherscanError::ContractCodeNotVerified(Address::ZERO)));
+    }
+
+    #[tokio::test]
+    async fn test_offline_client_serves_cached_sources() {
+        let cache = std::env::temp_dir().join(format!("edb-explorer-test-{}", std::process::id()));
+        let explorer = ExplorerConfig { offline: true, ..Default::default() };
+        let mut client = explorer.client(1, Some(cache.clone()), Duration::ZERO).unwrap();
+        assert!(client.is_offline());
+
+        let addr = Address::repeat_byte(0x42);
+        assert!(client.contract_source_code(addr).await.is_err());
+        assert!(client
+            .request(|client| async move { client.contract_abi(addr).await })
+            .await
+            .is_err());
+
+        let meta = ContractMetadata { items: vec![] };
+        client.sources.save_cache(addr.to_string(), &meta).unwrap();
+        assert!(client.contract_source_code(addr).await.unwrap().items.is_empty());
+
+        let _ = std::fs::remove_dir_all(cache);
+    }
+}
@@ -25,6 +25,7 @@
 //! ## Contract and Artifact Management
 //! - [`artifact`] - Contract artifact handling and metadata management
 //! - [`compilation`] - Solidity compilation utilities and configuration
+//! - [`solc`] - Solc binaries from local directories and mirrors, with verified checksums
 //! - [`abi`] - ABI processing and type conversion utilities
 //!
 //! ## Source Code Processing
@@ -36,6 +37,7 @@
 //!
 //! ## External Service Integration
 //! - [`etherscan`] - Etherscan API integration and data fetching utilities
+//! - [`api_keys`] - API key pools with rotation and cooldown
 //!
 //! # Design Philosophy
 //!
@@ -60,12 +62,18 @@ pub use errors::*;
 pub mod disasm;
 pub use disasm::*;
 
+mod api_keys;
+pub use api_keys::*;
+
 mod etherscan;
 pub use etherscan::*;
 
 mod compilation;
 pub use compilation::*;
 
+mod solc;
+pub use solc::*;
+
 mod source;
 pub use source::*;
 
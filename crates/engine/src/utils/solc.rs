@@ -0,0 +1,400 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Management of the solc binaries used to compile and recompile verified contracts.
+//!
+//! By default, solc binaries are installed on demand by svm from
+//! `https://binaries.soliditylang.org`. A [`SolcConfig`] can instead point the engine at a
+//! mirror of it, or at a local directory of binaries for air-gapped environments:
+//!
+//! 1. The local directory is checked first
+//! 2. Then the versions installed by svm
+//! 3. Then the binaries downloaded from the mirror by earlier sessions
+//! 4. Finally, the binary is downloaded from the mirror, or installed by svm without one
+//!
+//! Local directories and mirrors have the layout of a platform directory of
+//! `binaries.soliditylang.org` (e.g., `linux-amd64`): a `list.json` and the binaries it
+//! lists. Every binary taken from them is verified against the keccak256 checksum of
+//! `list.json` before use. [`prefetch_solc`] makes all versions a preparation needs
+//! available in parallel, before compilation starts.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    env, fs,
+    io::Write,
+    path::{Component, Path, PathBuf},
+    sync::{Mutex, RwLock},
+    thread,
+};
+
+use alloy_primitives::{keccak256, B256};
+use edb_common::{CachePath, EdbCachePath};
+use eyre::{bail, eyre, Result};
+use foundry_compilers::solc::Solc;
+use futures::future::join_all;
+use once_cell::sync::Lazy;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::find_or_install_solc;
+
+/// Name of the file listing the binaries of a platform directory with their checksums
+const SOLC_LIST_FILE: &str = "list.json";
+
+/// Where the solc binaries used to compile contracts come from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SolcConfig {
+    /// Base URL of a mirror of `https://binaries.soliditylang.org`, used instead of it
+    pub mirror: Option<String>,
+    /// Local directory of binaries, laid out like a platform directory of the mirror
+    pub local_dir: Option<PathBuf>,
+    /// Offline flag - when enabled, binaries are never downloaded
+    pub offline: bool,
+}
+
+/// Solc configuration of the process, set by [`set_solc_config`]
+static SOLC_CONFIG: Lazy<RwLock<SolcConfig>> = Lazy::new(Default::default);
+
+/// Binaries found in a local directory or downloaded from a mirror, already verified
+static RESOLVED: Lazy<Mutex<HashMap<Version, Solc>>> = Lazy::new(Default::default);
+
+/// Set where the solc binaries of the process come from.
+///
+/// Installed binaries are shared by all engines of the process, so this configuration is
+/// global too.
+pub fn set_solc_config(config: SolcConfig) {
+    let mut current = SOLC_CONFIG.write().unwrap();
+    if *current != config {
+        *current = config;
+        RESOLVED.lock().unwrap().clear();
+    }
+}
+
+/// Get where the solc binaries of the process come from
+pub fn solc_config() -> SolcConfig {
+    SOLC_CONFIG.read().unwrap().clone()
+}
+
+/// Build of solc listed in `list.json`
+#[derive(Debug, Clone, Deserialize)]
+struct SolcBuild {
+    /// File name of the binary, relative to the platform directory
+    path: String,
+    /// Version without build metadata, e.g., `0.8.20`
+    version: String,
+    /// Prerelease tag of nightly builds
+    #[serde(default)]
+    prerelease: Option<String>,
+    /// Checksum of the binary
+    keccak256: B256,
+}
+
+impl SolcBuild {
+    /// File name of the binary in the platform directory
+    ///
+    /// `list.json` comes from the mirror, so anything but a plain file name (an absolute
+    /// path, or one with `..` or other components) is rejected rather than joined onto the
+    /// directory.
+    fn file_name(&self) -> Result<&str> {
+        let mut components = Path::new(&self.path).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) if name == self.path.as_str() => Ok(&self.path),
+            _ => {
+                bail!("Invalid path {:?} for solc {} in {SOLC_LIST_FILE}", self.path, self.version)
+            }
+        }
+    }
+}
+
+/// Content of `list.json`
+#[derive(Debug, Clone, Deserialize)]
+struct SolcList {
+    builds: Vec<SolcBuild>,
+}
+
+impl SolcList {
+    /// Get the release build of a version
+    fn build(&self, version: &Version) -> Option<&SolcBuild> {
+        let version = format!("{}.{}.{}", version.major, version.minor, version.patch);
+        self.builds.iter().find(|build| build.version == version && build.prerelease.is_none())
+    }
+}
+
+/// Platform directory of `binaries.soliditylang.org` matching the current platform
+fn platform() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macosx-amd64"
+    } else if cfg!(target_os = "windows") {
+        "windows-amd64"
+    } else {
+        "linux-amd64"
+    }
+}
+
+/// Directory binaries downloaded from a mirror are stored in:
+/// `<cache_root>/solc/bin/<platform>`
+fn download_dir() -> Option<PathBuf> {
+    let cache = EdbCachePath::new(env::var(edb_common::env::EDB_CACHE_DIR).ok());
+    Some(cache.compiler_cache_dir()?.join("bin").join(platform()))
+}
+
+/// Find a solc binary from the configured local directory or mirror.
+///
+/// Returns `None` when svm should install the binary, i.e., if it is available neither
+/// locally nor from a mirror, and binaries may be downloaded.
+pub(crate) fn find_configured_solc(version: &Version) -> Result<Option<Solc>> {
+    if let Some(solc) = RESOLVED.lock().unwrap().get(version) {
+        return Ok(Some(solc.clone()));
+    }
+
+    let config = solc_config();
+    let solc = match config.local_dir.as_deref().map(|dir| find_in_dir(dir, version)) {
+        Some(Ok(Some(solc))) => solc,
+        Some(Err(e)) => return Err(e),
+        _ => {
+            if let Some(solc) = Solc::find_svm_installed_version(version)? {
+                return Ok(Some(solc));
+            }
+            let dir = download_dir();
+            match dir.as_deref().map(|dir| find_in_dir(dir, version)).transpose()?.flatten() {
+                Some(solc) => solc,
+                None if config.offline => bail!(
+                    "solc {version} is not available offline, add it to the local solc directory"
+                ),
+                None => match (&config.mirror, dir) {
+                    (Some(mirror), Some(dir)) => download_blocking(mirror, &dir, version)?,
+                    (Some(_), None) => bail!("No cache directory to store solc {version} in"),
+                    (None, _) => return Ok(None),
+                },
+            }
+        }
+    };
+
+    RESOLVED.lock().unwrap().insert(version.clone(), solc.clone());
+    Ok(Some(solc))
+}
+
+/// Find the binary of a version in a directory laid out like a platform directory of
+/// `binaries.soliditylang.org`, verifying its checksum
+fn find_in_dir(dir: &Path, version: &Version) -> Result<Option<Solc>> {
+    let list_path = dir.join(SOLC_LIST_FILE);
+    if !list_path.exists() {
+        return Ok(None);
+    }
+    let list: SolcList = serde_json::from_slice(&fs::read(&list_path)?)
+        .map_err(|e| eyre!("Invalid solc list {}: {e}", list_path.display()))?;
+    let Some(build) = list.build(version) else { return Ok(None) };
+
+    let path = dir.join(build.file_name()?);
+    if !path.exists() {
+        return Ok(None);
+    }
+    verify_checksum(&fs::read(&path)?, build)?;
+    make_executable(&path)?;
+    debug!("Using solc {} at {}", version, path.display());
+
+    Ok(Some(Solc::new_with_version(path, version.clone())))
+}
+
+/// Check a binary against the checksum of its build
+fn verify_checksum(binary: &[u8], build: &SolcBuild) -> Result<()> {
+    let checksum = keccak256(binary);
+    if checksum != build.keccak256 {
+        bail!(
+            "Checksum mismatch for solc {} ({}): expected {}, got {}",
+            build.version,
+            build.path,
+            build.keccak256,
+            checksum
+        );
+    }
+    Ok(())
+}
+
+/// Allow a binary to be executed, if it is not yet
+#[cfg(unix)]
+fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    if permissions.mode() & 0o111 != 0o111 {
+        permissions.set_mode(permissions.mode() | 0o111);
+        fs::set_permissions(path, permissions)?;
+    }
+    Ok(())
+}
+
+/// Allow a binary to be executed, if it is not yet
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Download the binary of a version from a mirror into a directory
+async fn download(mirror: &str, dir: &Path, version: &Version) -> Result<Solc> {
+    let base = format!("{}/{}", mirror.trim_end_matches('/'), platform());
+    let client = reqwest::Client::new();
+
+    let list_bytes = client
+        .get(format!("{base}/{SOLC_LIST_FILE}"))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    let list: SolcList = serde_json::from_slice(&list_bytes)
+        .map_err(|e| eyre!("Invalid solc list at {base}: {e}"))?;
+    let build = list.build(version).ok_or_else(|| eyre!("solc {version} is not on {base}"))?;
+    let file_name = build.file_name()?;
+
+    info!("Downloading solc {} from {}", version, base);
+    let binary =
+        client.get(format!("{base}/{file_name}")).send().await?.error_for_status()?.bytes().await?;
+    verify_checksum(&binary, build)?;
+
+    fs::create_dir_all(dir)?;
+    persist(dir, file_name, &binary)?;
+    persist(dir, SOLC_LIST_FILE, &list_bytes)?;
+
+    find_in_dir(dir, version)?
+        .ok_or_else(|| eyre!("solc {version} is missing from {} after download", dir.display()))
+}
+
+/// Download the binary of a version from a mirror, blocking until it is stored
+fn download_blocking(mirror: &str, dir: &Path, version: &Version) -> Result<Solc> {
+    // Compilation is synchronous and may run on a runtime thread, so the download runs on
+    // a dedicated runtime of its own
+    thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?
+                    .block_on(download(mirror, dir, version))
+            })
+            .join()
+            .map_err(|_| eyre!("Download of solc {version} panicked"))?
+    })
+}
+
+/// Write a file through a temporary file, so that concurrent readers never see it partially
+fn persist(dir: &Path, name: &str, contents: &[u8]) -> Result<()> {
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(contents)?;
+    file.persist(dir.join(name))?;
+    Ok(())
+}
+
+/// Make every given solc version available, installing missing ones in parallel.
+///
+/// Compilation installs missing versions one at a time as it reaches them, so this
+/// saves time when several versions are missing, and reports unavailable versions before
+/// any compilation starts.
+pub async fn prefetch_solc(versions: impl IntoIterator<Item = Version>) -> Result<()> {
+    let versions: BTreeSet<Version> = versions.into_iter().collect();
+    let installs = versions.into_iter().map(|version| async move {
+        let install = version.clone();
+        let result = tokio::task::spawn_blocking(move || find_or_install_solc(&install)).await;
+        (version, result)
+    });
+
+    let failures: Vec<String> = join_all(installs)
+        .await
+        .into_iter()
+        .filter_map(|(version, result)| match result {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => Some(format!("solc {version}: {e}")),
+            Err(e) => Some(format!("solc {version}: {e}")),
+        })
+        .collect();
+    if !failures.is_empty() {
+        bail!("Failed to install {} solc version(s):\n{}", failures.len(), failures.join("\n"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(dir: &Path, binary: &[u8]) {
+        let list = serde_json::json!({
+            "builds": [
+                {
+                    "path": "solc-v0.8.20+commit.a1b79de6",
+                    "version": "0.8.20",
+                    "prerelease": "nightly.2023.5.10",
+                    "keccak256": B256::ZERO,
+                },
+                {
+                    "path": "solc-v0.8.20+commit.a1b79de6",
+                    "version": "0.8.20",
+                    "keccak256": keccak256(binary),
+                },
+            ],
+        });
+        fs::write(dir.join(SOLC_LIST_FILE), list.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_find_in_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let version = Version::new(0, 8, 20);
+        assert!(find_in_dir(dir.path(), &version).unwrap().is_none());
+
+        list(dir.path(), b"solc");
+        assert!(find_in_dir(dir.path(), &version).unwrap().is_none());
+
+        fs::write(dir.path().join("solc-v0.8.20+commit.a1b79de6"), b"solc").unwrap();
+        let solc = find_in_dir(dir.path(), &version).unwrap().unwrap();
+        assert_eq!(solc.version, version);
+        assert!(find_in_dir(dir.path(), &Version::new(0, 8, 21)).unwrap().is_none());
+
+        fs::write(dir.path().join("solc-v0.8.20+commit.a1b79de6"), b"tampered").unwrap();
+        let error = find_in_dir(dir.path(), &version).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn test_build_paths_outside_the_directory_are_rejected() {
+        let build = |path: &str| SolcBuild {
+            path: path.to_string(),
+            version: "0.8.20".to_string(),
+            prerelease: None,
+            keccak256: B256::ZERO,
+        };
+        assert_eq!(
+            build("solc-v0.8.20+commit.a1b79de6").file_name().unwrap(),
+            "solc-v0.8.20+commit.a1b79de6"
+        );
+        for path in ["", ".", "..", "../solc", "/usr/bin/solc", "bin/solc", "./solc", "solc/"] {
+            assert!(build(path).file_name().is_err(), "{path:?} was accepted");
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let list = serde_json::json!({
+            "builds": [{
+                "path": "../solc-v0.8.20+commit.a1b79de6",
+                "version": "0.8.20",
+                "keccak256": keccak256(b"solc"),
+            }],
+        });
+        fs::write(dir.path().join(SOLC_LIST_FILE), list.to_string()).unwrap();
+        let error = find_in_dir(dir.path(), &Version::new(0, 8, 20)).unwrap_err();
+        assert!(error.to_string().contains("Invalid path"));
+    }
+}
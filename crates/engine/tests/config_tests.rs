@@ -21,7 +21,9 @@
 //! The tests cover configuration creation, cloning behavior, and proper handling of various
 //! configuration parameters including RPC proxy URLs, Etherscan API keys, and execution modes.
 
-use edb_engine::EngineConfig;
+use std::time::Duration;
+
+use edb_engine::{EngineConfig, ExplorerConfig};
 use tracing::info;
 
 #[test]
@@ -33,6 +35,8 @@ fn test_default_config() {
     assert_eq!(config.rpc_proxy_url, "http://localhost:8545");
     assert_eq!(config.etherscan_api_key, None);
     assert!(!config.quick);
+    assert!(!config.refetch);
+    assert!(!config.quiet);
 }
 
 #[test]
@@ -43,11 +47,14 @@ fn test_config_with_custom_values() {
         rpc_proxy_url: "http://localhost:9545".to_string(),
         etherscan_api_key: Some("test_key".to_string()),
         quick: true,
+        quiet: true,
+        ..Default::default()
     };
 
     assert_eq!(config.rpc_proxy_url, "http://localhost:9545");
     assert_eq!(config.etherscan_api_key, Some("test_key".to_string()));
     assert!(config.quick);
+    assert!(config.quiet);
 }
 
 #[test]
@@ -58,6 +65,8 @@ fn test_config_clone() {
         rpc_proxy_url: "http://localhost:8080".to_string(),
         etherscan_api_key: Some("key".to_string()),
         quick: false,
+        quiet: false,
+        ..Default::default()
     };
 
     let cloned = config.clone();
@@ -66,3 +75,34 @@ fn test_config_clone() {
     assert_eq!(config.etherscan_api_key, cloned.etherscan_api_key);
     assert_eq!(config.quick, cloned.quick);
 }
+
+#[test]
+fn test_explorer_per_chain() {
+    edb_common::logging::ensure_test_logging(None);
+    info!("Running test");
+    let blockscout = ExplorerConfig {
+        api_url: Some("https://gnosis.blockscout.com/api".to_string()),
+        ..Default::default()
+    };
+    let config = EngineConfig::default()
+        .with_etherscan_api_key("default_key".to_string())
+        .with_explorer(
+            8453,
+            ExplorerConfig { api_key: Some("base_key".to_string()), ..Default::default() },
+        )
+        .with_explorer(100, blockscout.clone());
+
+    // Etherscan chains share the default key unless they have their own
+    assert_eq!(config.explorer(1).api_key.as_deref(), Some("default_key"));
+    assert_eq!(config.explorer(8453).api_key.as_deref(), Some("base_key"));
+
+    // Other explorers are not sent the Etherscan key
+    assert_eq!(config.explorer(100), blockscout);
+    assert!(config.explorer(100).client(100, None, Duration::ZERO).is_ok());
+    assert!(config.explorer(1).client(1, None, Duration::ZERO).is_ok());
+
+    // Offline mode applies to every explorer
+    let config = config.with_explorer_offline(true);
+    assert!(config.explorer(1).offline);
+    assert!(config.explorer(100).client(100, None, Duration::ZERO).unwrap().is_offline());
+}
@@ -18,7 +18,9 @@
 
 use alloy_json_abi::JsonAbi;
 use alloy_primitives::{Address, Bytes, TxHash, U256};
-use edb_common::types::{Breakpoint, CallableAbiInfo, Code, EdbSolValue, SnapshotInfo, Trace};
+use edb_common::types::{
+    Breakpoint, CallableAbiInfo, Code, EdbSolValue, SnapshotInfo, Trace, TracePage, TraceQuery,
+};
 use eyre::Result;
 use futures::future::join_all;
 use once_cell::sync::Lazy;
@@ -230,6 +232,12 @@ impl RpcTestClient {
         serde_json::from_value(value).map_err(Into::into)
     }
 
+    /// Get a page of the execution trace selected by a query.
+    pub async fn get_trace_page(&self, query: &TraceQuery) -> Result<TracePage> {
+        let value = self.call_raw("edb_getTrace", Some(json!([query]))).await?;
+        serde_json::from_value(value).map_err(Into::into)
+    }
+
     /// Get the contract code at a specific snapshot.
     /// Returns either opcode or source code information depending on availability.
     pub async fn get_code(&self, snapshot_id: usize) -> Result<Code> {
@@ -828,6 +836,13 @@ async fn analyze_basic_stats(client: &RpcTestClient, perf: &mut PerformanceMetri
     let trace = client.get_trace().await.unwrap();
     record_rpc_call(perf, "edb_getTrace", start, serde_json::to_string(&trace).unwrap().len());
 
+    let start = Instant::now();
+    let query = TraceQuery { limit: Some(100), summary: true, ..Default::default() };
+    let page = client.get_trace_page(&query).await.unwrap();
+    record_rpc_call(perf, "edb_getTrace_page", start, serde_json::to_string(&page).unwrap().len());
+    assert_eq!(page.total, trace.len(), "Trace page reports a different trace length");
+    assert_eq!(page.entries.len(), trace.len().min(100));
+
     BasicStats {
         snapshot_count,
         trace_entries: trace.len(),
@@ -0,0 +1,349 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Python bindings for EDB.
+//!
+//! This crate exposes the [`edb_engine::Debugger`] API to Python, so that post-mortems can
+//! be scripted, e.g., from notebooks:
+//!
+//! ```python
+//! import edb_py
+//!
+//! debugger = edb_py.Debugger("http://localhost:8545", etherscan_api_key="...")
+//! session = debugger.prepare("0x...")
+//!
+//! for entry in session.trace():
+//!     print(entry["id"], entry["target"])
+//!
+//! snapshot_id = 0
+//! while snapshot_id is not None:
+//!     print(snapshot_id, session.eval("msg.sender", snapshot_id))
+//!     snapshot_id = session.next_snapshot(snapshot_id)
+//! ```
+//!
+//! Sessions prepared elsewhere, e.g., by `edb server` on a remote machine, are reached
+//! through their engine RPC server, with a bearer token and over HTTPS if required:
+//!
+//! ```python
+//! session = edb_py.RemoteSession("https://debug-box:3030", auth_token="...", ca_cert="cert.pem")
+//! print(session.snapshot_count())
+//! ```
+//!
+//! Complex values (trace entries, snapshots, findings, reports) are returned as plain
+//! Python objects decoded from the JSON representation used by the debugging RPC API.
+
+use std::{path::PathBuf, sync::Arc};
+
+use alloy_primitives::TxHash;
+use edb_common::{types::EdbSolValue, EngineRpcClient, RpcClientOptions, RPC_AUTH_TOKEN_ENV};
+use edb_engine::{
+    rpc::methods::MethodHandler, Debugger as EngineDebugger, Session as EngineSession,
+};
+use pyo3::{exceptions::PyRuntimeError, prelude::*, types::PyModule};
+use revm::{database::CacheDB, Database, DatabaseCommit, DatabaseRef};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+/// Type-erased access to a debugging session, since Python classes cannot be generic
+/// over the database of the session.
+trait DynSession: Send + Sync {
+    fn tx_hash(&self) -> TxHash;
+    fn snapshot_count(&self) -> usize;
+    fn next_snapshot(&self, snapshot_id: usize) -> Option<usize>;
+    fn prev_snapshot(&self, snapshot_id: usize) -> Option<usize>;
+    fn entry_snapshot(&self, trace_entry_id: usize) -> Option<usize>;
+    fn trace(&self) -> eyre::Result<Value>;
+    fn findings(&self) -> eyre::Result<Value>;
+    fn report(&self) -> eyre::Result<Value>;
+    fn eval(&self, expr: &str, snapshot_id: usize) -> eyre::Result<Value>;
+    fn call(&self, runtime: &Runtime, method: &str, params: Option<Value>) -> eyre::Result<Value>;
+}
+
+impl<DB> DynSession for EngineSession<DB>
+where
+    DB: Database + DatabaseCommit + DatabaseRef + Clone + Send + Sync + 'static,
+    <CacheDB<DB> as Database>::Error: Clone + Send + Sync,
+    <DB as Database>::Error: Clone + Send + Sync,
+{
+    fn tx_hash(&self) -> TxHash {
+        Self::tx_hash(self)
+    }
+
+    fn snapshot_count(&self) -> usize {
+        Self::snapshot_count(self)
+    }
+
+    fn next_snapshot(&self, snapshot_id: usize) -> Option<usize> {
+        Self::next_snapshot(self, snapshot_id)
+    }
+
+    fn prev_snapshot(&self, snapshot_id: usize) -> Option<usize> {
+        Self::prev_snapshot(self, snapshot_id)
+    }
+
+    fn entry_snapshot(&self, trace_entry_id: usize) -> Option<usize> {
+        Self::entry_snapshot(self, trace_entry_id)
+    }
+
+    fn trace(&self) -> eyre::Result<Value> {
+        Ok(Self::trace(self).to_json_value()?)
+    }
+
+    fn findings(&self) -> eyre::Result<Value> {
+        Ok(serde_json::to_value(Self::findings(self))?)
+    }
+
+    fn report(&self) -> eyre::Result<Value> {
+        Ok(serde_json::to_value(Self::report(self))?)
+    }
+
+    fn eval(&self, expr: &str, snapshot_id: usize) -> eyre::Result<Value> {
+        let value: EdbSolValue = Self::eval(self, expr, snapshot_id)?.into();
+        Ok(serde_json::to_value(value)?)
+    }
+
+    fn call(&self, runtime: &Runtime, method: &str, params: Option<Value>) -> eyre::Result<Value> {
+        let handler = MethodHandler::new(self.context().clone());
+        runtime
+            .block_on(handler.handle_method(method, params))
+            .map_err(|e| eyre::eyre!("{} (code {})", e.message, e.code))
+    }
+}
+
+fn to_py_err(err: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+/// Convert a JSON value into the equivalent Python object.
+fn to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    let json = PyModule::import(py, "json")?;
+    Ok(json.call_method1("loads", (value.to_string(),))?.unbind())
+}
+
+/// Convert a Python object into the equivalent JSON value.
+fn from_py(py: Python<'_>, value: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let json = PyModule::import(py, "json")?;
+    let text: String = json.call_method1("dumps", (value,))?.extract()?;
+    serde_json::from_str(&text).map_err(to_py_err)
+}
+
+/// Prepares debugging sessions of transactions.
+#[pyclass(module = "edb_py")]
+struct Debugger {
+    inner: EngineDebugger,
+    runtime: Arc<Runtime>,
+}
+
+#[pymethods]
+impl Debugger {
+    #[new]
+    #[pyo3(signature = (rpc_url, etherscan_api_key = None, quick = false))]
+    fn new(rpc_url: String, etherscan_api_key: Option<String>, quick: bool) -> PyResult<Self> {
+        let mut builder = EngineDebugger::builder().with_rpc_url(rpc_url).with_quick_mode(quick);
+        if let Some(key) = etherscan_api_key {
+            builder = builder.with_etherscan_api_key(key);
+        }
+
+        let runtime = Runtime::new().map_err(to_py_err)?;
+        Ok(Self { inner: builder.build(), runtime: Arc::new(runtime) })
+    }
+
+    /// Fork the chain at the given transaction and prepare a debugging session for it.
+    fn prepare(&self, py: Python<'_>, tx_hash: &str) -> PyResult<Session> {
+        let tx_hash: TxHash = tx_hash.parse().map_err(to_py_err)?;
+        let session = py
+            .allow_threads(|| self.runtime.block_on(self.inner.prepare(tx_hash)))
+            .map_err(to_py_err)?;
+
+        Ok(Session { inner: Arc::new(session), runtime: self.runtime.clone() })
+    }
+}
+
+/// A prepared debugging session of a single transaction.
+#[pyclass(module = "edb_py")]
+struct Session {
+    inner: Arc<dyn DynSession>,
+    runtime: Arc<Runtime>,
+}
+
+#[pymethods]
+impl Session {
+    /// Hash of the debugged transaction.
+    #[getter]
+    fn tx_hash(&self) -> String {
+        self.inner.tx_hash().to_string()
+    }
+
+    /// Total number of snapshots.
+    fn snapshot_count(&self) -> usize {
+        self.inner.snapshot_count()
+    }
+
+    /// Id of the snapshot following the given one, or `None` at the end.
+    fn next_snapshot(&self, snapshot_id: usize) -> Option<usize> {
+        self.inner.next_snapshot(snapshot_id)
+    }
+
+    /// Id of the snapshot preceding the given one, or `None` at the beginning.
+    fn prev_snapshot(&self, snapshot_id: usize) -> Option<usize> {
+        self.inner.prev_snapshot(snapshot_id)
+    }
+
+    /// Id of the first snapshot of a trace entry.
+    fn entry_snapshot(&self, trace_entry_id: usize) -> Option<usize> {
+        self.inner.entry_snapshot(trace_entry_id)
+    }
+
+    /// Execution trace as a list of call entries.
+    fn trace(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.trace().map_err(to_py_err)?)
+    }
+
+    /// Detailed information about a snapshot, as returned by `edb_getSnapshotInfo`.
+    fn snapshot(&self, py: Python<'_>, snapshot_id: usize) -> PyResult<PyObject> {
+        self.call_method(py, "edb_getSnapshotInfo", Some(json!([snapshot_id])))
+    }
+
+    /// Findings reported by the detectors.
+    fn findings(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.findings().map_err(to_py_err)?)
+    }
+
+    /// Structured report of the transaction.
+    fn report(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.inner.report().map_err(to_py_err)?)
+    }
+
+    /// Evaluate a Solidity-like expression against a snapshot.
+    fn eval(&self, py: Python<'_>, expr: &str, snapshot_id: usize) -> PyResult<PyObject> {
+        let value = py.allow_threads(|| self.inner.eval(expr, snapshot_id)).map_err(to_py_err)?;
+        to_py(py, &value)
+    }
+
+    /// Call any method of the debugging RPC API in-process, e.g., `edb_getStorageDiff`.
+    #[pyo3(signature = (method, params = None))]
+    fn call(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        params: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let params = params.map(|params| from_py(py, &params)).transpose()?;
+        self.call_method(py, method, params)
+    }
+}
+
+impl Session {
+    fn call_method(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        params: Option<Value>,
+    ) -> PyResult<PyObject> {
+        let value = py
+            .allow_threads(|| self.inner.call(&self.runtime, method, params))
+            .map_err(to_py_err)?;
+        to_py(py, &value)
+    }
+}
+
+/// A debugging session served by a running engine RPC server.
+#[pyclass(module = "edb_py")]
+struct RemoteSession {
+    client: Arc<EngineRpcClient>,
+    runtime: Arc<Runtime>,
+}
+
+#[pymethods]
+impl RemoteSession {
+    /// Connect to the engine RPC server at `url`.
+    ///
+    /// The bearer token defaults to the `EDB_RPC_AUTH_TOKEN` environment variable, and
+    /// `ca_cert` is a PEM certificate to trust, e.g., the self-signed one of the server.
+    #[new]
+    #[pyo3(signature = (url, auth_token = None, ca_cert = None))]
+    fn new(url: String, auth_token: Option<String>, ca_cert: Option<PathBuf>) -> PyResult<Self> {
+        let options = RpcClientOptions {
+            auth_token: auth_token.or_else(|| std::env::var(RPC_AUTH_TOKEN_ENV).ok()),
+            ca_cert,
+            timeout: None,
+        };
+        let client = EngineRpcClient::new(url, &options).map_err(to_py_err)?;
+
+        let runtime = Runtime::new().map_err(to_py_err)?;
+        Ok(Self { client: Arc::new(client), runtime: Arc::new(runtime) })
+    }
+
+    /// URL of the engine RPC server.
+    #[getter]
+    fn url(&self) -> String {
+        self.client.url().to_string()
+    }
+
+    /// Total number of snapshots.
+    fn snapshot_count(&self, py: Python<'_>) -> PyResult<usize> {
+        let value = self.request(py, "edb_getSnapshotCount", json!([]))?;
+        serde_json::from_value(value).map_err(to_py_err)
+    }
+
+    /// Execution trace as a list of call entries.
+    fn trace(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.request(py, "edb_getTrace", json!([]))?)
+    }
+
+    /// Detailed information about a snapshot, as returned by `edb_getSnapshotInfo`.
+    fn snapshot(&self, py: Python<'_>, snapshot_id: usize) -> PyResult<PyObject> {
+        to_py(py, &self.request(py, "edb_getSnapshotInfo", json!([snapshot_id]))?)
+    }
+
+    /// Findings reported by the detectors.
+    fn findings(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.request(py, "edb_getFindings", json!([]))?)
+    }
+
+    /// Structured report of the transaction.
+    fn report(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py(py, &self.request(py, "edb_getReport", json!([]))?)
+    }
+
+    /// Call any method of the debugging RPC API on the server, e.g., `edb_getStorageDiff`.
+    #[pyo3(signature = (method, params = None))]
+    fn call(
+        &self,
+        py: Python<'_>,
+        method: &str,
+        params: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<PyObject> {
+        let params = params.map(|params| from_py(py, &params)).transpose()?;
+        to_py(py, &self.request(py, method, params.unwrap_or_else(|| json!([])))?)
+    }
+}
+
+impl RemoteSession {
+    fn request(&self, py: Python<'_>, method: &str, params: Value) -> PyResult<Value> {
+        py.allow_threads(|| self.runtime.block_on(self.client.request(method, params)))
+            .map_err(to_py_err)
+    }
+}
+
+/// Python module `edb_py`.
+#[pymodule]
+fn edb_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Debugger>()?;
+    m.add_class::<Session>()?;
+    m.add_class::<RemoteSession>()?;
+    Ok(())
+}
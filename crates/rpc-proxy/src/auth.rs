@@ -0,0 +1,355 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! API-token authentication and per-token usage accounting
+//!
+//! A proxy shared by a team can require every JSON-RPC request to carry one of a set of
+//! named tokens, either as an `Authorization: Bearer <token>` header or as a `token` query
+//! parameter for clients that cannot set headers (`http://proxy:8546/?token=<token>`).
+//!
+//! Requests are accounted to the token they carry: how many were served, and how many had
+//! to go upstream, i.e. burnt the shared provider keys. Upstream requests can be capped per
+//! token and per hour; once a token is over its quota, its requests are still served from
+//! the cache but cache misses get a `-32005` error instead of going upstream.
+//!
+//! Tokens are either user tokens, for debugging, or admin tokens, which can also manage
+//! the proxy: shut it down, delete or pin cache entries, and read the usage of every token
+//! (a user token only reads its own, see [`ADMIN_METHODS`]).
+//!
+//! The token a request is served for is carried by the task serving it (see [`scope`]), so
+//! that upstream requests are charged to it wherever they are sent from.
+
+use eyre::{bail, eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    future::Future,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Error code of the requests whose token is over its upstream quota
+pub const QUOTA_EXCEEDED_ERROR_CODE: i64 = -32005;
+
+/// Window over which the upstream requests of a token are capped
+const QUOTA_WINDOW: Duration = Duration::from_secs(3600);
+
+/// Methods managing the proxy, only allowed to admin tokens when authentication is required
+pub const ADMIN_METHODS: &[&str] =
+    &["edb_shutdown", "edb_deleteCache", "edb_cache_delete", "edb_cache_pin", "edb_cache_unpin"];
+
+/// Role of a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenRole {
+    /// Debugging only
+    #[default]
+    User,
+    /// Debugging and managing the proxy
+    Admin,
+}
+
+/// Error of an upstream request refused because its token is over its quota
+#[derive(Debug)]
+pub struct QuotaExceeded(String);
+
+impl fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
+tokio::task_local! {
+    /// Usage of the token the current request is served for
+    static CURRENT_TOKEN: Arc<TokenUsage>;
+}
+
+/// Tokens allowed to use the proxy
+#[derive(Debug, Clone)]
+pub struct TokenAuth {
+    /// Usage of each token, along with the token itself
+    tokens: Vec<(String, Arc<TokenUsage>)>,
+}
+
+impl TokenAuth {
+    /// Load tokens from a file holding one `NAME=TOKEN [ROLE]` entry per line
+    ///
+    /// `ROLE` is `user` (the default) or `admin`. Empty lines and lines starting with `#`
+    /// are ignored.
+    ///
+    /// # Arguments
+    /// * `path` - File holding the tokens
+    /// * `hourly_quota` - Upstream requests allowed to each token per hour, unlimited if `None`
+    pub fn load(path: &Path, hourly_quota: Option<u64>) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| eyre!("Failed to read auth tokens {}: {e}", path.display()))?;
+        Self::parse(&content, hourly_quota)
+    }
+
+    /// Parse tokens from `NAME=TOKEN [ROLE]` lines, see [`Self::load`]
+    pub fn parse(content: &str, hourly_quota: Option<u64>) -> Result<Self> {
+        let mut tokens: Vec<(String, Arc<TokenUsage>)> = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, token)) = line.split_once('=') else {
+                bail!("Expected NAME=TOKEN [ROLE], got {line:?}");
+            };
+            let mut fields = token.split_whitespace();
+            let (name, token) = (name.trim(), fields.next().unwrap_or_default());
+            if name.is_empty() || token.is_empty() {
+                bail!("Expected NAME=TOKEN [ROLE], got {line:?}");
+            }
+            let role = match (fields.next(), fields.next()) {
+                (None | Some("user"), None) => TokenRole::User,
+                (Some("admin"), None) => TokenRole::Admin,
+                _ => bail!("Expected NAME=TOKEN [ROLE] with ROLE user or admin, got {line:?}"),
+            };
+            if tokens.iter().any(|(other, usage)| other == token || usage.name == name) {
+                bail!("Duplicate auth token or name {name:?}");
+            }
+            tokens.push((token.to_string(), Arc::new(TokenUsage::new(name, role, hourly_quota))));
+        }
+
+        if tokens.is_empty() {
+            bail!("No auth tokens configured");
+        }
+        Ok(Self { tokens })
+    }
+
+    /// Get the usage of the token presented by a request, if it is a valid token
+    pub fn authenticate(&self, token: &str) -> Option<Arc<TokenUsage>> {
+        // Compare every token in constant time, not to leak how much of one was guessed
+        let mut found = None;
+        for (candidate, usage) in &self.tokens {
+            if constant_time_eq(candidate.as_bytes(), token.as_bytes()) {
+                found = Some(usage.clone());
+            }
+        }
+        found
+    }
+
+    /// Usage of every token, by name
+    pub fn usage(&self) -> Vec<TokenUsageReport> {
+        let mut reports: Vec<_> = self.tokens.iter().map(|(_, usage)| usage.report()).collect();
+        reports.sort_by(|a, b| a.name.cmp(&b.name));
+        reports
+    }
+}
+
+/// Usage of a token
+#[derive(Debug)]
+pub struct TokenUsage {
+    /// Name of the token, e.g. its owner
+    name: String,
+    /// What the token is allowed to do
+    role: TokenRole,
+    /// Requests served
+    requests: AtomicU64,
+    /// Requests sent upstream
+    upstream_requests: AtomicU64,
+    /// Requests refused for being over the upstream quota
+    rejected_requests: AtomicU64,
+    /// Unix timestamp of the last request
+    last_used: AtomicU64,
+    /// Upstream requests allowed per hour, unlimited if `None`
+    hourly_quota: Option<u64>,
+    /// Start of the current quota window, and upstream requests sent in it
+    quota_window: Mutex<(Instant, u64)>,
+}
+
+impl TokenUsage {
+    fn new(name: &str, role: TokenRole, hourly_quota: Option<u64>) -> Self {
+        Self {
+            name: name.to_string(),
+            role,
+            requests: AtomicU64::new(0),
+            upstream_requests: AtomicU64::new(0),
+            rejected_requests: AtomicU64::new(0),
+            last_used: AtomicU64::new(0),
+            hourly_quota,
+            quota_window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Name of the token
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the token can call the methods managing the proxy ([`ADMIN_METHODS`])
+    pub fn is_admin(&self) -> bool {
+        self.role == TokenRole::Admin
+    }
+
+    /// Record a request served for the token
+    pub fn record_request(&self) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.last_used.store(chrono::Utc::now().timestamp() as u64, Ordering::Relaxed);
+    }
+
+    /// Charge an upstream request to the token, unless it is over its quota
+    fn charge_upstream_request(&self) -> Result<(), QuotaExceeded> {
+        if let Some(quota) = self.hourly_quota {
+            let mut window = self.quota_window.lock().unwrap();
+            if window.0.elapsed() >= QUOTA_WINDOW {
+                *window = (Instant::now(), 0);
+            }
+            if window.1 >= quota {
+                self.rejected_requests.fetch_add(1, Ordering::Relaxed);
+                let resets_in = QUOTA_WINDOW.saturating_sub(window.0.elapsed()).as_secs();
+                return Err(QuotaExceeded(format!(
+                    "Upstream quota of token {} exhausted ({} requests per hour), only cached \
+                     responses are served for the next {}s",
+                    self.name, quota, resets_in
+                )));
+            }
+            window.1 += 1;
+        }
+
+        self.upstream_requests.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Usage of the token, as reported by `edb_token_usage`
+    pub fn report(&self) -> TokenUsageReport {
+        let quota_remaining = self.hourly_quota.map(|quota| {
+            let window = self.quota_window.lock().unwrap();
+            if window.0.elapsed() >= QUOTA_WINDOW {
+                quota
+            } else {
+                quota.saturating_sub(window.1)
+            }
+        });
+        let last_used = self.last_used.load(Ordering::Relaxed);
+
+        TokenUsageReport {
+            name: self.name.clone(),
+            role: self.role,
+            requests: self.requests.load(Ordering::Relaxed),
+            upstream_requests: self.upstream_requests.load(Ordering::Relaxed),
+            rejected_requests: self.rejected_requests.load(Ordering::Relaxed),
+            last_used: (last_used > 0).then_some(last_used),
+            hourly_quota: self.hourly_quota,
+            quota_remaining,
+        }
+    }
+}
+
+/// Usage of a token, as reported by `edb_token_usage`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenUsageReport {
+    /// Name of the token
+    pub name: String,
+    /// What the token is allowed to do
+    #[serde(default)]
+    pub role: TokenRole,
+    /// Requests served
+    pub requests: u64,
+    /// Requests sent upstream
+    pub upstream_requests: u64,
+    /// Requests refused for being over the upstream quota
+    pub rejected_requests: u64,
+    /// Unix timestamp of the last request
+    pub last_used: Option<u64>,
+    /// Upstream requests allowed per hour
+    pub hourly_quota: Option<u64>,
+    /// Upstream requests left in the current hour
+    pub quota_remaining: Option<u64>,
+}
+
+/// Serve a request for a token, charging the upstream requests it sends to the token
+pub async fn scope<F: Future>(usage: Option<Arc<TokenUsage>>, request: F) -> F::Output {
+    match usage {
+        Some(usage) => CURRENT_TOKEN.scope(usage, request).await,
+        None => request.await,
+    }
+}
+
+/// Charge an upstream request to the token of the request being served, if any
+///
+/// # Returns
+/// An error if the token is over its upstream quota
+pub fn charge_upstream_request() -> Result<(), QuotaExceeded> {
+    CURRENT_TOKEN.try_with(|usage| usage.charge_upstream_request()).unwrap_or(Ok(()))
+}
+
+/// Compare two byte strings in time depending only on their lengths
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tokens() {
+        let auth = TokenAuth::parse("# team\nalice = secret-a\n\nbob=secret-b\n", None).unwrap();
+        assert_eq!(auth.authenticate("secret-a").unwrap().name(), "alice");
+        assert_eq!(auth.authenticate("secret-b").unwrap().name(), "bob");
+        assert!(auth.authenticate("secret-c").is_none());
+        assert!(auth.authenticate("").is_none());
+
+        assert!(!auth.authenticate("secret-a").unwrap().is_admin());
+
+        let auth = TokenAuth::parse("ops=secret-o admin\nci=secret-c user", None).unwrap();
+        assert!(auth.authenticate("secret-o").unwrap().is_admin());
+        assert!(!auth.authenticate("secret-c").unwrap().is_admin());
+        assert!(auth.authenticate("secret-o admin").is_none());
+        assert_eq!(auth.usage()[1].role, TokenRole::Admin);
+
+        assert!(TokenAuth::parse("alice", None).is_err());
+        assert!(TokenAuth::parse("alice=a root", None).is_err());
+        assert!(TokenAuth::parse("alice=a admin user", None).is_err());
+        assert!(TokenAuth::parse("alice=a\nbob=a", None).is_err());
+        assert!(TokenAuth::parse("# nobody", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_upstream_quota() {
+        let auth = TokenAuth::parse("alice=secret-a\nbob=secret-b", Some(2)).unwrap();
+        let alice = auth.authenticate("secret-a");
+
+        // Upstream requests are charged to the token of the request being served
+        scope(alice.clone(), async {
+            assert!(charge_upstream_request().is_ok());
+            assert!(charge_upstream_request().is_ok());
+            assert!(charge_upstream_request().is_err());
+        })
+        .await;
+        assert!(charge_upstream_request().is_ok());
+        scope(auth.authenticate("secret-b"), async {
+            assert!(charge_upstream_request().is_ok());
+        })
+        .await;
+
+        let usage = auth.usage();
+        assert_eq!(usage[0].name, "alice");
+        assert_eq!(usage[0].upstream_requests, 2);
+        assert_eq!(usage[0].rejected_requests, 1);
+        assert_eq!(usage[0].quota_remaining, Some(0));
+        assert_eq!(usage[1].upstream_requests, 1);
+        assert_eq!(usage[1].quota_remaining, Some(1));
+    }
+}
@@ -20,18 +20,26 @@
 //! Provides intelligent caching of immutable RPC responses to improve performance and reduce
 //! network overhead for multiple debugging sessions.
 
-use clap::{Parser, Subcommand};
+use alloy_primitives::TxHash;
+use clap::{ArgGroup, Parser, Subcommand};
 use edb_common::init_file_only_logging;
 use edb_common::init_logging;
-use eyre::Result;
+use eyre::{bail, eyre, Result};
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
+use tokio::net::TcpListener;
 use tracing::{info, warn};
 
 use edb_rpc_proxy::proxy;
 use edb_rpc_proxy::tui;
 
+use edb_rpc_proxy::cache::{CacheManager, CachePolicyConfig, EvictionPolicy};
+use edb_rpc_proxy::export::{CacheBundle, ExportFilter};
+use edb_rpc_proxy::journal::{self, ReplayOptions};
+use edb_rpc_proxy::ratelimit::RateLimitConfig;
+use edb_rpc_proxy::warm::{self, WarmSummary, WarmTarget};
 use proxy::ProxyServerBuilder;
 
 /// EDB RPC Caching Proxy Server
@@ -51,6 +59,14 @@ enum Commands {
     Server(ServerArgs),
     /// Monitor existing proxy via TUI
     Monitor(MonitorArgs),
+    /// Prefetch into the cache what a debugging session on a transaction or block needs
+    Warm(WarmArgs),
+    /// Export cache entries, selected by block range or address, to a bundle file
+    Export(ExportArgs),
+    /// Import the cache entries of a bundle file exported on another machine
+    Import(ImportArgs),
+    /// Replay a request log against a proxy, comparing the responses to the logged ones
+    ReplayLog(ReplayLogArgs),
 }
 
 /// Server mode arguments
@@ -67,10 +83,22 @@ struct ServerArgs {
     port: u16,
 
     /// Upstream RPC URLs (comma-separated, overrides defaults if provided)
-    /// Example: --rpc-urls "https://eth.llamarpc.com,https://rpc.ankr.com/eth"
+    /// HTTP(S) and WebSocket (ws:// or wss://) endpoints can be mixed
+    /// Example: --rpc-urls "https://eth.llamarpc.com,wss://ethereum-rpc.publicnode.com"
     #[arg(long)]
     rpc_urls: Option<String>,
 
+    /// Upstream RPC URLs of another chain to serve (repeatable, CHAIN_ID=URLS)
+    /// Requests are routed by path prefix (/10) or by the X-EDB-Chain header
+    /// Example: --chain-rpc-urls "10=https://mainnet.optimism.io,https://optimism-rpc.publicnode.com"
+    #[arg(long, value_name = "CHAIN_ID=URLS")]
+    chain_rpc_urls: Vec<String>,
+
+    /// Do not probe for a local archive node (IPC sockets, localhost:8545) to prefer
+    /// over the upstream RPC URLs
+    #[arg(long)]
+    no_local_discovery: bool,
+
     // ========== Cache Configuration ==========
     /// Maximum number of cached items
     #[arg(long, default_value = "1024000")]
@@ -84,6 +112,28 @@ struct ServerArgs {
     #[arg(long, default_value = "15")]
     cache_save_interval: u64,
 
+    /// Maximum total size of the cached responses in bytes (0 = unlimited)
+    #[arg(long, default_value = "0")]
+    max_cache_bytes: u64,
+
+    /// Cache eviction policy: lru, lfu, or ttl:<seconds>
+    #[arg(long, default_value = "lru")]
+    cache_policy: EvictionPolicy,
+
+    /// Per-method eviction policies overriding --cache-policy (comma-separated method=policy)
+    /// Example: --cache-method-policies "eth_getCode=lfu,eth_blockNumber=ttl:12"
+    #[arg(long)]
+    cache_method_policies: Option<String>,
+
+    /// Cache store shared with other proxy instances: redis://[:password@]host[:port][/db],
+    /// or the http(s):// URL of an object store (e.g. an S3 bucket) answering GET and PUT
+    #[arg(long)]
+    shared_cache_url: Option<String>,
+
+    /// Bearer token sent to an HTTP shared cache store
+    #[arg(long)]
+    shared_cache_token: Option<String>,
+
     // ========== Provider Health Check Configuration ==========
     /// Maximum consecutive failures before marking provider unhealthy
     #[arg(long, default_value = "3")]
@@ -93,6 +143,20 @@ struct ServerArgs {
     #[arg(long, default_value = "60")]
     health_check_interval: u64,
 
+    // ========== Rate Limit Configuration ==========
+    /// Maximum requests per second sent to each provider (0 = unlimited)
+    #[arg(long, default_value = "0")]
+    rate_limit: f64,
+
+    /// Requests sent to a provider at once before rate limiting applies (default: 1s of budget)
+    #[arg(long)]
+    rate_limit_burst: Option<u32>,
+
+    /// Per-provider rate limits overriding --rate-limit (comma-separated URL=RPS pairs)
+    /// Example: --provider-rate-limits "https://mainnet.infura.io/v3/KEY=10"
+    #[arg(long)]
+    provider_rate_limits: Option<String>,
+
     // ========== EDB Registry Configuration ==========
     /// Grace period in seconds before shutdown when no EDB instances (0 = no auto-shutdown)
     #[arg(long, default_value = "0")]
@@ -102,6 +166,34 @@ struct ServerArgs {
     #[arg(long, default_value = "10")]
     heartbeat_interval: u64,
 
+    // ========== Session Configuration ==========
+    /// Record every response served into a session bundle, written on shutdown
+    /// Example: --record ./session.json
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Serve exclusively from a recorded session bundle, without contacting any provider
+    /// Example: --replay ./session.json
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+
+    /// Log every proxied request, with its response and timing, to a JSON Lines file
+    /// Example: --request-log ./requests.jsonl
+    #[arg(long, value_name = "FILE")]
+    request_log: Option<PathBuf>,
+
+    // ========== Authentication Configuration ==========
+    /// Require requests to carry one of the tokens of a file, one NAME=TOKEN [ROLE] entry
+    /// per line with ROLE user (default) or admin, as an "Authorization: Bearer" header or
+    /// a "token" query parameter
+    /// Example: --auth-tokens ./tokens.txt
+    #[arg(long, value_name = "FILE", conflicts_with = "tui")]
+    auth_tokens: Option<PathBuf>,
+
+    /// Upstream requests allowed to each auth token per hour (0 = unlimited)
+    #[arg(long, default_value = "0", requires = "auth_tokens")]
+    token_quota: u64,
+
     // ========== UI Configuration ==========
     /// Enable TUI monitoring interface
     #[arg(long)]
@@ -123,6 +215,100 @@ struct MonitorArgs {
     timeout: u64,
 }
 
+/// Warm mode arguments
+#[derive(Parser, Debug)]
+#[command(group(ArgGroup::new("target").required(true).args(["tx", "block"])))]
+struct WarmArgs {
+    /// Transaction a later session is on
+    /// Example: --tx 0x5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060
+    #[arg(long)]
+    tx: Option<TxHash>,
+
+    /// Block a later session is on, warming every transaction of the block
+    #[arg(long)]
+    block: Option<u64>,
+
+    /// Skip the transactions preceding the target in its block, as `edb --quick` does
+    #[arg(long)]
+    quick: bool,
+
+    /// URL of a running proxy to warm (default: warm the cache files with a temporary proxy)
+    /// Example: --proxy-url http://localhost:8546
+    #[arg(long, conflicts_with_all = ["rpc_urls", "cache_dir"])]
+    proxy_url: Option<String>,
+
+    /// Upstream RPC URLs of the temporary proxy (comma-separated, overrides defaults if provided)
+    #[arg(long)]
+    rpc_urls: Option<String>,
+
+    /// Cache directory of the temporary proxy (default: ~/.edb/cache/rpc/<chain_id>)
+    #[arg(long)]
+    cache_dir: Option<String>,
+}
+
+/// Export mode arguments
+#[derive(Parser, Debug)]
+struct ExportArgs {
+    /// Bundle file to write
+    #[arg(long, short)]
+    output: PathBuf,
+
+    /// Chain whose cache is exported
+    #[arg(long, default_value = "1")]
+    chain_id: u64,
+
+    /// Cache directory (default: ~/.edb/cache/rpc/<chain_id>)
+    #[arg(long)]
+    cache_dir: Option<String>,
+
+    /// First block of the exported entries
+    #[arg(long)]
+    from_block: Option<u64>,
+
+    /// Last block of the exported entries
+    #[arg(long)]
+    to_block: Option<u64>,
+
+    /// Only export entries touching these addresses (comma-separated)
+    /// Example: --addresses "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+    #[arg(long)]
+    addresses: Option<String>,
+}
+
+/// Import mode arguments
+#[derive(Parser, Debug)]
+struct ImportArgs {
+    /// Bundle file to import
+    input: PathBuf,
+
+    /// Cache directory (default: ~/.edb/cache/rpc/<chain_id>)
+    #[arg(long)]
+    cache_dir: Option<String>,
+
+    /// Maximum number of cached items
+    #[arg(long, default_value = "1024000")]
+    max_cache_items: u32,
+}
+
+/// Replay-log mode arguments
+#[derive(Parser, Debug)]
+struct ReplayLogArgs {
+    /// Request log written with --request-log
+    input: PathBuf,
+
+    /// URL of the proxy to replay against, with its auth token if it requires one
+    #[arg(long, default_value = "http://localhost:8546")]
+    proxy_url: String,
+
+    /// Requests in flight at once
+    #[arg(long, default_value = "1")]
+    concurrency: usize,
+
+    /// Send the requests at the pace they were logged instead of as fast as possible
+    #[arg(long)]
+    preserve_timing: bool,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
@@ -130,6 +316,10 @@ async fn main() -> Result<()> {
     match args.command {
         Commands::Server(server_args) => run_server(server_args).await,
         Commands::Monitor(monitor_args) => run_monitor(monitor_args).await,
+        Commands::Warm(warm_args) => run_warm(warm_args).await,
+        Commands::Export(export_args) => run_export(export_args).await,
+        Commands::Import(import_args) => run_import(import_args).await,
+        Commands::ReplayLog(replay_args) => run_replay_log(replay_args).await,
     }
 }
 
@@ -154,13 +344,66 @@ async fn run_server(args: ServerArgs) -> Result<()> {
         .heartbeat_interval(args.heartbeat_interval)
         .max_failures(args.max_failures)
         .health_check_interval(args.health_check_interval)
-        .cache_save_interval(args.cache_save_interval);
+        .cache_save_interval(args.cache_save_interval)
+        .max_cache_bytes(args.max_cache_bytes)
+        .cache_policy(args.cache_policy)
+        .rate_limit(args.rate_limit)
+        .discover_local_node(!args.no_local_discovery);
+
+    // Set cache method policies if provided
+    if let Some(policies) = args.cache_method_policies {
+        for (method, policy) in CachePolicyConfig::parse_method_policies(&policies)? {
+            builder = builder.cache_method_policy(method, policy);
+        }
+    }
+
+    // Set shared cache if provided
+    if let Some(url) = args.shared_cache_url {
+        builder = builder.shared_cache(url, args.shared_cache_token);
+    }
+
+    // Set rate limits if provided
+    if let Some(burst) = args.rate_limit_burst {
+        builder = builder.rate_limit_burst(burst);
+    }
+    if let Some(limits) = args.provider_rate_limits {
+        for (url, rps) in RateLimitConfig::parse_per_provider(&limits)? {
+            builder = builder.provider_rate_limit(url, rps);
+        }
+    }
 
     // Set RPC URLs if provided
     if let Some(urls) = args.rpc_urls {
         builder = builder.rpc_urls_str(&urls);
     }
 
+    // Set the RPC URLs of other chains if provided
+    for group in args.chain_rpc_urls {
+        let (chain_id, urls) =
+            group.split_once('=').ok_or_else(|| eyre!("Expected CHAIN_ID=URLS, got {group:?}"))?;
+        let chain_id =
+            chain_id.trim().parse().map_err(|e| eyre!("Invalid chain ID in {group:?}: {e}"))?;
+        builder = builder.chain_rpc_urls_str(chain_id, urls);
+    }
+
+    // Record or replay a session if requested
+    if let Some(path) = args.record {
+        builder = builder.record_session(path);
+    }
+    if let Some(path) = args.replay {
+        builder = builder.replay_session(path);
+    }
+
+    // Authenticate requests if tokens are given
+    if let Some(path) = args.auth_tokens {
+        builder = builder.auth_tokens_file(path).token_quota(args.token_quota);
+    }
+
+    // Log the proxied requests if requested
+    if let Some(path) = args.request_log {
+        builder = builder.request_log(path);
+    }
+
     // Set cache directory if provided
     if let Some(cache_dir) = args.cache_dir {
         builder = builder.cache_dir(cache_dir);
@@ -169,7 +412,8 @@ async fn run_server(args: ServerArgs) -> Result<()> {
     let proxy = builder.build().await?;
 
     // Set up graceful shutdown
-    let cache_manager = proxy.cache_manager().clone();
+    let cache_managers = proxy.cache_managers();
+    let session = proxy.session().cloned();
 
     // Start the server
     let ip = IpAddr::from_str(&args.host)?;
@@ -209,9 +453,18 @@ async fn run_server(args: ServerArgs) -> Result<()> {
         }
     }
 
-    // Save cache to disk before exiting
-    if let Err(e) = cache_manager.save_to_disk().await {
-        warn!("Failed to save cache to disk: {}", e);
+    // Save the cache of every chain to disk before exiting
+    for cache_manager in cache_managers {
+        if let Err(e) = cache_manager.save_to_disk().await {
+            warn!("Failed to save cache to disk: {}", e);
+        }
+    }
+
+    // Write the recorded session, if any
+    if let Some(session) = session {
+        if let Err(e) = session.save() {
+            warn!("Failed to save recorded session: {}", e);
+        }
     }
 
     Ok(())
@@ -231,3 +484,170 @@ async fn run_monitor(args: MonitorArgs) -> Result<()> {
     // Create a remote TUI client and run it
     tui::run_tui(args.proxy_url, args.refresh_interval, args.timeout).await
 }
+
+/// Warm the cache of a running proxy, or of the cache files through a temporary proxy
+async fn run_warm(args: WarmArgs) -> Result<()> {
+    init_logging("edb-rpc-proxy", true)?;
+
+    let target = match (args.tx, args.block) {
+        (Some(tx_hash), _) => WarmTarget::Transaction(tx_hash),
+        (None, Some(block)) => WarmTarget::Block(block),
+        (None, None) => unreachable!("clap requires a target"),
+    };
+
+    if let Some(proxy_url) = args.proxy_url {
+        let summary = warm::warm(&proxy_url, target, args.quick).await?;
+        print_warm_summary(&summary);
+        return Ok(());
+    }
+
+    // Serve a temporary proxy on an ephemeral port, sharing the cache files of the server
+    let mut builder = ProxyServerBuilder::new();
+    if let Some(urls) = args.rpc_urls {
+        builder = builder.rpc_urls_str(&urls);
+    }
+    if let Some(cache_dir) = args.cache_dir {
+        builder = builder.cache_dir(cache_dir);
+    }
+    let proxy = builder.build().await?;
+    let cache_manager = proxy.cache_manager().clone();
+    let entries_before = cache_manager.entry_count().await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_url = format!("http://{}", listener.local_addr()?);
+    let server_handle = tokio::spawn(proxy.serve_listener(listener));
+
+    let result = warm::warm(&proxy_url, target, args.quick).await;
+    server_handle.abort();
+
+    // Keep whatever was fetched, even if warming failed midway
+    cache_manager.save_to_disk().await?;
+    let summary = result?;
+    print_warm_summary(&summary);
+    println!(
+        "   {} new cache entries saved to {}",
+        cache_manager.entry_count().await.saturating_sub(entries_before),
+        cache_manager.cache_file_path().display()
+    );
+
+    Ok(())
+}
+
+/// Print what was warmed
+fn print_warm_summary(summary: &WarmSummary) {
+    println!(
+        "✅ Cache warmed for transaction {} in block {} ({} receipts) in {:.1}s",
+        summary.tx_hash,
+        summary.block_number,
+        summary.receipts,
+        summary.elapsed.as_secs_f64()
+    );
+}
+
+/// Export selected entries of the cache of a chain to a bundle file
+async fn run_export(args: ExportArgs) -> Result<()> {
+    init_logging("edb-rpc-proxy", true)?;
+
+    let filter = ExportFilter {
+        from_block: args.from_block,
+        to_block: args.to_block,
+        addresses: match args.addresses {
+            Some(addresses) => ExportFilter::parse_addresses(&addresses)?,
+            None => Default::default(),
+        },
+    };
+
+    // Load the whole cache, it is only read
+    let cache_path =
+        CacheManager::chain_cache_path(args.chain_id, args.cache_dir.map(PathBuf::from))?;
+    let cache_manager = CacheManager::new(u32::MAX, cache_path)?;
+    let mut entries = cache_manager.get_all_entries().await;
+    let total = entries.len();
+    entries.retain(|_, entry| filter.matches(entry));
+
+    let exported = entries.len();
+    CacheBundle::new(args.chain_id, entries).write(&args.output)?;
+    println!(
+        "✅ Exported {} of {} cache entries of chain {} to {}",
+        exported,
+        total,
+        args.chain_id,
+        args.output.display()
+    );
+
+    Ok(())
+}
+
+/// Import the entries of a bundle file into the cache of its chain
+async fn run_import(args: ImportArgs) -> Result<()> {
+    init_logging("edb-rpc-proxy", true)?;
+
+    let bundle = CacheBundle::read(&args.input)?;
+    let cache_path =
+        CacheManager::chain_cache_path(bundle.chain_id, args.cache_dir.map(PathBuf::from))?;
+    let cache_manager = CacheManager::new(args.max_cache_items, cache_path)?;
+
+    let total = bundle.entries.len();
+    let imported = cache_manager.import_entries(bundle.entries).await;
+    cache_manager.save_to_disk().await?;
+    println!(
+        "✅ Imported {} of {} cache entries of chain {} into {}",
+        imported,
+        total,
+        bundle.chain_id,
+        cache_manager.cache_file_path().display()
+    );
+    println!("   Restart a running proxy for it to serve the imported entries");
+
+    Ok(())
+}
+
+/// Replay a request log against a proxy and report the responses differing from the log
+async fn run_replay_log(args: ReplayLogArgs) -> Result<()> {
+    init_logging("edb-rpc-proxy", true)?;
+
+    let options =
+        ReplayOptions { concurrency: args.concurrency, preserve_timing: args.preserve_timing };
+    let report = journal::replay(&args.input, &args.proxy_url, &options).await?;
+
+    println!(
+        "Replayed {} requests in {:.1}s ({:.1} req/s), {} failed",
+        report.requests,
+        report.elapsed.as_secs_f64(),
+        report.throughput(),
+        report.failures
+    );
+    if let (Some(p50), Some(p95), Some(max)) = (
+        report.latency_percentile(0.5),
+        report.latency_percentile(0.95),
+        report.latency_percentile(1.0),
+    ) {
+        println!("   Response times: p50 {p50}ms, p95 {p95}ms, max {max}ms");
+    }
+
+    for mismatch in &report.mismatches {
+        println!(
+            "❌ Request {} ({}): logged {}, replayed {}",
+            mismatch.index,
+            mismatch.method,
+            abbreviate(&mismatch.logged),
+            abbreviate(&mismatch.replayed)
+        );
+    }
+    if !report.mismatches.is_empty() {
+        bail!("{} responses differ from the request log", report.mismatches.len());
+    }
+    println!("✅ All logged responses reproduced");
+
+    Ok(())
+}
+
+/// Shorten a value to print, as results can be whole blocks
+fn abbreviate(value: &impl std::fmt::Display) -> String {
+    const MAX_CHARS: usize = 80;
+    let text = value.to_string();
+    match text.char_indices().nth(MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
@@ -15,7 +15,19 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! In-memory cache manager for RPC responses with disk persistence
-
+//!
+//! The cache is bounded by a number of entries and, optionally, by the total size of the
+//! cached responses. When a bound is reached, entries are evicted following the eviction
+//! policy of their method (LRU by default, LFU, or TTL), see [`EvictionPolicy`].
+//!
+//! A [`SharedCache`] store can back the in-memory cache: local misses are looked up in the
+//! store, and new entries are written through to it, so that several proxy instances
+//! benefit from each other's cached responses.
+//!
+//! Entries can be pinned, e.g. responses a debugging session depends on: pinned entries
+//! never expire and are never evicted, until unpinned or deleted.
+
+use crate::{export::ExportFilter, shared::SharedCache};
 use alloy_chains::NamedChain;
 use edb_common::{
     cache::{CachePath, EdbCachePath},
@@ -26,9 +38,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
     collections::{HashMap, HashSet},
-    fs,
+    fmt, fs,
     path::PathBuf,
-    sync::Arc,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
@@ -36,45 +52,215 @@ use tracing::{debug, error, info, warn};
 /// Maximum size for a single cache file in bytes (20MB)
 const MAX_CACHE_FILE_SIZE: usize = 20 * 1024 * 1024;
 
+/// Current Unix timestamp in seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Policy deciding which cache entries of a method are evicted first
+///
+/// When the cache is full, expired entries are removed first. Then entries are evicted by
+/// policy: TTL entries soonest to expire first, then LRU entries least recently used first,
+/// then LFU entries least frequently used first. Giving a method the LFU policy thus keeps
+/// its entries the longest, while a TTL makes them the first to go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EvictionPolicy {
+    /// Evict the least recently used entries first
+    #[default]
+    Lru,
+    /// Evict the least frequently used entries first
+    Lfu,
+    /// Expire entries the given number of seconds after they were cached
+    Ttl(u64),
+}
+
+impl FromStr for EvictionPolicy {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "lru" => Ok(Self::Lru),
+            "lfu" => Ok(Self::Lfu),
+            other => match other.strip_prefix("ttl:") {
+                Some(seconds) => Ok(Self::Ttl(seconds.parse().map_err(|e| {
+                    eyre::eyre!("Invalid TTL in {s:?}, expected ttl:<seconds>: {e}")
+                })?)),
+                None => {
+                    eyre::bail!("Unknown eviction policy {s:?}, expected lru, lfu or ttl:<seconds>")
+                }
+            },
+        }
+    }
+}
+
+impl fmt::Display for EvictionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lru => write!(f, "lru"),
+            Self::Lfu => write!(f, "lfu"),
+            Self::Ttl(seconds) => write!(f, "ttl:{seconds}"),
+        }
+    }
+}
+
+/// Eviction policies and size limit of the cache
+#[derive(Debug, Clone, Default)]
+pub struct CachePolicyConfig {
+    /// Policy of the methods without an override
+    pub default_policy: EvictionPolicy,
+    /// Policy overrides, by method
+    pub method_policies: HashMap<String, EvictionPolicy>,
+    /// Maximum total size of the cached responses in bytes, unlimited if `None`
+    pub max_bytes: Option<u64>,
+}
+
+impl CachePolicyConfig {
+    /// Parse policy overrides from comma-separated `method=policy` pairs
+    pub fn parse_method_policies(pairs: &str) -> Result<HashMap<String, EvictionPolicy>> {
+        pairs
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (method, policy) = pair
+                    .split_once('=')
+                    .ok_or_else(|| eyre::eyre!("Expected method=policy, got {pair:?}"))?;
+                Ok((method.trim().to_string(), policy.parse()?))
+            })
+            .collect()
+    }
+
+    /// Get the policy of a cache entry from its key, prefixed by the method
+    fn policy_for(&self, key: &str) -> EvictionPolicy {
+        let method = key.split_once(':').map_or(key, |(method, _)| method);
+        self.method_policies.get(method).copied().unwrap_or(self.default_policy)
+    }
+}
+
 /// A cached RPC response entry with metadata
 ///
-/// This struct holds a cached RPC response along with its last access timestamp
-/// for LRU eviction purposes.
+/// This struct holds a cached RPC response along with the timestamps and hit count
+/// used by the eviction policies.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
     /// The cached RPC response data
     pub data: Value,
     /// Unix timestamp when this entry was accessed
     pub accessed_at: u64,
+    /// Unix timestamp when this entry was cached
+    #[serde(default)]
+    pub created_at: u64,
+    /// Number of cache hits served by this entry
+    #[serde(default)]
+    pub hits: u64,
+    /// Size of the serialized response in bytes
+    #[serde(skip)]
+    pub size: u64,
+    /// Method and params of the request, used to select entries for export
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<Value>,
+    /// Whether the entry is pinned against expiry and eviction
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl CacheEntry {
     fn new(data: Value) -> Self {
+        let now = unix_now();
+        let size = data.to_string().len() as u64;
         Self {
             data,
-            accessed_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            accessed_at: now,
+            created_at: now,
+            hits: 0,
+            size,
+            request: None,
+            pinned: false,
         }
     }
 
     fn update_access_time(&mut self) {
-        self.accessed_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        self.accessed_at = unix_now();
+    }
+
+    /// Fill in the metadata not persisted, or missing from older cache files
+    fn restore_metadata(&mut self) {
+        if self.created_at == 0 {
+            self.created_at = self.accessed_at;
+        }
+        self.size = self.data.to_string().len() as u64;
+    }
+
+    /// Whether the entry has expired under the given policy, pinned entries never expire
+    fn is_expired(&self, policy: EvictionPolicy, now: u64) -> bool {
+        !self.pinned
+            && matches!(policy, EvictionPolicy::Ttl(ttl) if self.created_at.saturating_add(ttl) <= now)
+    }
+
+    /// Eviction order of the entry under the given policy, the smallest first
+    fn eviction_rank(&self, policy: EvictionPolicy, now: u64) -> (u8, u64, u64) {
+        match policy {
+            EvictionPolicy::Ttl(ttl) => {
+                let expires_at = self.created_at.saturating_add(ttl);
+                (if expires_at <= now { 0 } else { 1 }, expires_at, 0)
+            }
+            EvictionPolicy::Lru => (2, self.accessed_at, 0),
+            EvictionPolicy::Lfu => (3, self.hits, self.accessed_at),
+        }
+    }
+}
+
+/// Description of a cache entry, as listed for inspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntrySummary {
+    /// Cache key of the entry
+    pub key: String,
+    /// Method of the cached request
+    pub method: String,
+    /// Params of the cached request, if kept with the entry
+    pub params: Option<Value>,
+    /// Size of the cached response in bytes
+    pub size: u64,
+    /// Number of cache hits served by the entry
+    pub hits: u64,
+    /// Unix timestamp when the entry was cached
+    pub created_at: u64,
+    /// Unix timestamp when the entry was accessed
+    pub accessed_at: u64,
+    /// Whether the entry is pinned against expiry and eviction
+    pub pinned: bool,
+}
+
+impl CacheEntrySummary {
+    fn new(key: &str, entry: &CacheEntry) -> Self {
+        Self {
+            key: key.to_string(),
+            method: key.split_once(':').map_or(key, |(method, _)| method).to_string(),
+            params: entry.request.as_ref().and_then(|request| request.get("params")).cloned(),
+            size: entry.size,
+            hits: entry.hits,
+            created_at: entry.created_at,
+            accessed_at: entry.accessed_at,
+            pinned: entry.pinned,
+        }
     }
 }
 
 /// In-memory cache manager for RPC responses with disk persistence
 ///
-/// Manages a thread-safe in-memory cache with LRU eviction and provides
+/// Manages a thread-safe in-memory cache with policy-based eviction and provides
 /// functionality to persist the cache to disk as JSON.
 pub struct CacheManager {
     cache: Arc<RwLock<HashMap<String, CacheEntry>>>,
     max_items: u32,
+    policies: CachePolicyConfig,
+    /// Total size of the cached responses in bytes
+    total_bytes: AtomicU64,
     cache_file_path: PathBuf,
+    /// Store shared with other proxy instances, if any
+    shared: Option<SharedCache>,
+    /// Number of local misses served by the shared store
+    shared_hits: AtomicU64,
 }
 
 impl CacheManager {
@@ -87,13 +273,34 @@ impl CacheManager {
     /// # Returns
     /// A new CacheManager instance, loading any existing cache from disk
     pub fn new(max_items: u32, cache_path: PathBuf) -> Result<Self> {
+        Self::with_policies(max_items, cache_path, CachePolicyConfig::default())
+    }
+
+    /// Creates a new cache manager with the specified capacity, disk path, and eviction policies
+    ///
+    /// # Arguments
+    /// * `max_items` - Maximum number of items to store in the cache
+    /// * `cache_path` - Path where the cache will be persisted to disk
+    /// * `policies` - Eviction policies and size limit of the cache
+    ///
+    /// # Returns
+    /// A new CacheManager instance, loading any existing cache from disk
+    pub fn with_policies(
+        max_items: u32,
+        cache_path: PathBuf,
+        policies: CachePolicyConfig,
+    ) -> Result<Self> {
         info!("Using cache file: {}", cache_path.display());
 
         // Create an instance to use load_existing_cache method
         let mut manager = Self {
             cache: Arc::new(RwLock::new(HashMap::new())),
             max_items,
+            policies,
+            total_bytes: AtomicU64::new(0),
             cache_file_path: cache_path,
+            shared: None,
+            shared_hits: AtomicU64::new(0),
         };
 
         // Load existing cache from disk (handles both legacy and split files)
@@ -110,60 +317,188 @@ impl CacheManager {
             }
         };
 
-        if cache.len() >= max_items as usize {
-            // This will be a hard cap - evict oldest entries
-            Self::evict_to_size(&mut cache, max_items as usize);
-        }
+        // This will be a hard cap - evict entries by policy, and any expired ones
+        Self::evict_to_fit(
+            &manager.policies,
+            &mut cache,
+            max_items as usize,
+            manager.policies.max_bytes,
+        );
 
+        manager.total_bytes.store(Self::total_size(&cache), Ordering::Relaxed);
         manager.cache = Arc::new(RwLock::new(cache));
         Ok(manager)
     }
 
+    /// Back the cache with a store shared with other proxy instances
+    ///
+    /// Local misses are looked up in the shared store, and new entries are written to it.
+    pub fn with_shared_cache(mut self, shared: SharedCache) -> Self {
+        info!("Using {} shared cache", shared.kind());
+        self.shared = Some(shared);
+        self
+    }
+
     /// Retrieves a cached value by key
     ///
+    /// Local misses are looked up in the shared store, if any, and the entries found
+    /// there are cached locally.
+    ///
     /// # Arguments
     /// * `key` - The cache key to look up
     ///
     /// # Returns
     /// The cached value if found, None otherwise
     pub async fn get(&self, key: &str) -> Option<Value> {
-        let mut cache = self.cache.write().await;
-        if let Some(entry) = cache.get_mut(key) {
-            debug!("Cache hit: {}", key);
-            entry.update_access_time(); // Update access time for LRU
-            Some(entry.data.clone())
-        } else {
+        let policy = self.policies.policy_for(key);
+        {
+            let mut cache = self.cache.write().await;
+
+            if cache.get(key).is_some_and(|entry| entry.is_expired(policy, unix_now())) {
+                debug!("Cache entry expired: {}", key);
+                if let Some(entry) = cache.remove(key) {
+                    self.total_bytes.fetch_sub(entry.size, Ordering::Relaxed);
+                }
+            } else if let Some(entry) = cache.get_mut(key) {
+                debug!("Cache hit: {}", key);
+                entry.update_access_time(); // Update access time for LRU
+                entry.hits += 1; // Update hit count for LFU
+                return Some(entry.data.clone());
+            }
+        }
+
+        // The lock is released while waiting for the shared store
+        let Some(shared) = &self.shared else {
             debug!("Cache miss: {}", key);
-            None
+            return None;
+        };
+        let Some(mut entry) = shared.get(key).await else {
+            debug!("Cache miss: {}", key);
+            return None;
+        };
+        entry.restore_metadata();
+        if entry.is_expired(policy, unix_now()) {
+            debug!("Shared cache entry expired: {}", key);
+            return None;
         }
+
+        entry.update_access_time();
+        entry.hits = 1;
+        let data = entry.data.clone();
+        self.insert_entry(&mut *self.cache.write().await, key.to_string(), entry);
+        self.shared_hits.fetch_add(1, Ordering::Relaxed);
+        Some(data)
     }
 
     /// Stores a value in the cache with the given key
     ///
-    /// If the cache is at capacity, this will trigger LRU eviction of the oldest entries.
+    /// If the cache is at capacity (in entries or bytes), this will trigger eviction of
+    /// about 10% of the cache, following the eviction policies.
     ///
     /// # Arguments
     /// * `key` - The cache key to store under
     /// * `value` - The value to cache
     pub async fn set(&self, key: String, value: Value) {
+        self.store(key, CacheEntry::new(value)).await;
+    }
+
+    /// Stores the response to a request in the cache with the given key
+    ///
+    /// Unlike [`CacheManager::set`], the method and params of the request are kept with
+    /// the entry, so that it can be selected by block or address for export.
+    ///
+    /// # Arguments
+    /// * `key` - The cache key to store under
+    /// * `request` - The JSON-RPC request answered by the value
+    /// * `value` - The value to cache
+    pub async fn set_for_request(&self, key: String, request: &Value, value: Value) {
+        let mut entry = CacheEntry::new(value);
+        entry.request = Some(serde_json::json!({
+            "method": request.get("method").cloned().unwrap_or(Value::Null),
+            "params": request.get("params").cloned().unwrap_or(Value::Null),
+        }));
+        self.store(key, entry).await;
+    }
+
+    /// Imports entries, e.g. from an exported bundle
+    ///
+    /// Entries already cached are only replaced by more recently accessed ones.
+    ///
+    /// # Returns
+    /// The number of entries imported
+    pub async fn import_entries(&self, entries: HashMap<String, CacheEntry>) -> usize {
         let mut cache = self.cache.write().await;
+        let mut imported = 0;
+        for (key, mut entry) in entries {
+            entry.restore_metadata();
+            if cache.get(&key).is_some_and(|cached| cached.accessed_at >= entry.accessed_at) {
+                continue;
+            }
+            self.insert_entry(&mut cache, key, entry);
+            imported += 1;
+        }
+        imported
+    }
 
-        // Check if we need to evict entries to make space
-        if cache.len() >= self.max_items as usize {
-            Self::evict_oldest(&mut cache);
+    /// Stores an entry in the cache and the shared store
+    async fn store(&self, key: String, entry: CacheEntry) {
+        // Write through to the shared store, without holding up the response
+        if let Some(shared) = &self.shared {
+            let ttl = match self.policies.policy_for(&key) {
+                EvictionPolicy::Ttl(ttl) => Some(ttl),
+                _ => None,
+            };
+            let (shared, key, entry) = (shared.clone(), key.clone(), entry.clone());
+            tokio::spawn(async move { shared.put(&key, &entry, ttl).await });
         }
 
-        let entry = CacheEntry::new(value);
-        cache.insert(key.clone(), entry);
-        debug!("Cached entry: {}", key);
+        self.insert_entry(&mut *self.cache.write().await, key, entry);
     }
 
-    fn evict_oldest(cache: &mut HashMap<String, CacheEntry>) {
-        // Sort entries by creation time (oldest first) and remove oldest 10%
-        let to_remove = (cache.len() / 10).max(1);
-        debug!("Evicting {} oldest cache entries", to_remove);
+    /// Inserts an entry in the in-memory cache, evicting entries to make space if needed
+    fn insert_entry(
+        &self,
+        cache: &mut HashMap<String, CacheEntry>,
+        key: String,
+        mut entry: CacheEntry,
+    ) {
+        // An entry larger than the whole cache would evict everything else
+        if self.policies.max_bytes.is_some_and(|max| entry.size > max) {
+            debug!("Not caching entry larger than the cache size limit: {}", key);
+            return;
+        }
+
+        // A refreshed entry stays pinned
+        if let Some(previous) = cache.remove(&key) {
+            self.total_bytes.fetch_sub(previous.size, Ordering::Relaxed);
+            entry.pinned |= previous.pinned;
+        }
+
+        // Check if we need to evict entries to make space
+        let total_bytes = self.total_bytes.load(Ordering::Relaxed);
+        let over_items = cache.len() >= self.max_items as usize;
+        let over_bytes =
+            self.policies.max_bytes.is_some_and(|max| total_bytes.saturating_add(entry.size) > max);
+
+        if over_items || over_bytes {
+            let target_items = if over_items {
+                cache.len().saturating_sub((cache.len() / 10).max(1))
+            } else {
+                cache.len()
+            };
+            let target_bytes = self
+                .policies
+                .max_bytes
+                .map(|max| if over_bytes { max - max / 10 } else { max })
+                .map(|max| max.saturating_sub(entry.size));
+
+            Self::evict_to_fit(&self.policies, cache, target_items, target_bytes);
+            self.total_bytes.store(Self::total_size(cache), Ordering::Relaxed);
+        }
 
-        Self::evict_to_size(cache, cache.len().saturating_sub(to_remove));
+        self.total_bytes.fetch_add(entry.size, Ordering::Relaxed);
+        cache.insert(key.clone(), entry);
+        debug!("Cached entry: {}", key);
     }
 
     /// Saves the current cache contents to disk as JSON with atomic write and merge
@@ -243,7 +578,9 @@ impl CacheManager {
 
         let deleted_count = keys_to_delete.len();
         for key in keys_to_delete {
-            cache.remove(&key);
+            if let Some(entry) = cache.remove(&key) {
+                self.total_bytes.fetch_sub(entry.size, Ordering::Relaxed);
+            }
         }
 
         if deleted_count > 0 {
@@ -259,7 +596,11 @@ impl CacheManager {
     /// Delete a single cache entry by key
     pub async fn delete_by_key(&self, key: &str) -> Result<bool> {
         let mut cache = self.cache.write().await;
-        let found = cache.remove(key).is_some();
+        let removed = cache.remove(key);
+        if let Some(entry) = &removed {
+            self.total_bytes.fetch_sub(entry.size, Ordering::Relaxed);
+        }
+        let found = removed.is_some();
 
         if found {
             let current_cache = cache.clone();
@@ -270,6 +611,60 @@ impl CacheManager {
         Ok(found)
     }
 
+    /// Lists the cache entries of a method, or selected by block or address
+    ///
+    /// # Arguments
+    /// * `method` - The method of the entries, all methods if None
+    /// * `filter` - Block range and addresses the entries are selected by
+    /// * `limit` - Maximum number of entries to return
+    ///
+    /// # Returns
+    /// The number of matching entries, and up to `limit` of them, most recently accessed first
+    pub async fn list_entries(
+        &self,
+        method: Option<&str>,
+        filter: &ExportFilter,
+        limit: usize,
+    ) -> (usize, Vec<CacheEntrySummary>) {
+        let prefix = method.map(|method| format!("{method}:"));
+        let cache = self.cache.read().await;
+        let mut entries: Vec<_> = cache
+            .iter()
+            .filter(|(key, _)| prefix.as_ref().is_none_or(|prefix| key.starts_with(prefix)))
+            .filter(|(_, entry)| filter.matches(entry))
+            .collect();
+
+        let total = entries.len();
+        entries.sort_unstable_by(|a, b| b.1.accessed_at.cmp(&a.1.accessed_at).then(a.0.cmp(b.0)));
+        let entries =
+            entries.into_iter().take(limit).map(|(key, entry)| CacheEntrySummary::new(key, entry));
+        (total, entries.collect())
+    }
+
+    /// Pins or unpins a cache entry by key
+    ///
+    /// Pinned entries never expire and are never evicted, so that the responses a session
+    /// depends on stay cached however busy the proxy is.
+    ///
+    /// # Returns
+    /// Whether the entry was found
+    pub async fn set_pinned(&self, key: &str, pinned: bool) -> Result<bool> {
+        {
+            let mut cache = self.cache.write().await;
+            let Some(entry) = cache.get_mut(key) else {
+                return Ok(false);
+            };
+            if entry.pinned == pinned {
+                return Ok(true);
+            }
+            entry.pinned = pinned;
+        }
+
+        info!("{} cache entry {}", if pinned { "Pinned" } else { "Unpinned" }, key);
+        self.save_to_disk().await?;
+        Ok(true)
+    }
+
     /// Force save current cache state to disk without merging
     ///
     /// This method bypasses the normal merge logic and directly overwrites
@@ -306,16 +701,44 @@ impl CacheManager {
             }
         }
 
+        let method_policies: serde_json::Map<String, Value> = self
+            .policies
+            .method_policies
+            .iter()
+            .map(|(method, policy)| (method.clone(), policy.to_string().into()))
+            .collect();
+
         serde_json::json!({
             "total_entries": cache.len(),
             "max_entries": self.max_items,
             "utilization": format!("{:.1}%", (cache.len() as f64 / self.max_items as f64) * 100.0),
+            "total_bytes": self.total_bytes.load(Ordering::Relaxed),
+            "max_bytes": self.policies.max_bytes,
+            "eviction_policy": self.policies.default_policy.to_string(),
+            "method_policies": method_policies,
+            "shared_cache": self.shared.as_ref().map(|shared| shared.kind()),
+            "shared_cache_hits": self.shared_hits.load(Ordering::Relaxed),
             "oldest_entry_age_seconds": oldest_entry.map(|t| current_time.saturating_sub(t)),
             "newest_entry_age_seconds": newest_entry.map(|t| current_time.saturating_sub(t)),
             "cache_file_path": self.cache_file_path.display().to_string(),
         })
     }
 
+    /// Returns the number of entries in the cache
+    pub async fn entry_count(&self) -> usize {
+        self.cache.read().await.len()
+    }
+
+    /// Returns the total size of the cached responses in bytes
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the path the cache is persisted to
+    pub fn cache_file_path(&self) -> &std::path::Path {
+        &self.cache_file_path
+    }
+
     /// Returns all cache entries for testing purposes
     ///
     /// This method is primarily intended for testing and debugging.
@@ -343,6 +766,18 @@ impl CacheManager {
         rpc_urls: &[String],
         cache_dir: Option<PathBuf>,
     ) -> Result<PathBuf> {
+        let chain_id = Self::resolve_chain_id(rpc_urls).await?;
+        Self::chain_cache_path(chain_id, cache_dir)
+    }
+
+    /// Determines the chain served by a group of RPC URLs
+    ///
+    /// # Arguments
+    /// * `rpc_urls` - The RPC endpoint URLs, which must all serve the same chain
+    ///
+    /// # Returns
+    /// The chain ID, or mainnet if no RPC URL is provided (cache-only mode)
+    pub async fn resolve_chain_id(rpc_urls: &[String]) -> Result<u64> {
         let chain_ids: HashSet<_> =
             futures::future::join_all(rpc_urls.iter().map(|url| forking::get_chain_id(url)))
                 .await
@@ -350,15 +785,25 @@ impl CacheManager {
                 .filter_map(Result::ok)
                 .collect();
 
-        let chain_id = if rpc_urls.is_empty() {
+        if rpc_urls.is_empty() {
             warn!("No RPC URL is provided, assume we are on mainnet and enter cache-only mode");
-            NamedChain::Mainnet.into()
+            Ok(NamedChain::Mainnet.into())
         } else if chain_ids.len() == 1 {
-            *chain_ids.iter().next().unwrap()
+            Ok(*chain_ids.iter().next().unwrap())
         } else {
             eyre::bail!("All RPC URLs must belong to the same chain. Found: {:?}", chain_ids);
-        };
+        }
+    }
 
+    /// Generates the cache file path of a chain, ensuring parent directories exist
+    ///
+    /// # Arguments
+    /// * `chain_id` - The chain whose responses are cached
+    /// * `cache_dir` - Optional base cache directory (defaults to ~/.edb/cache)
+    ///
+    /// # Returns
+    /// The full path to the cache file for this chain
+    pub fn chain_cache_path(chain_id: u64, cache_dir: Option<PathBuf>) -> Result<PathBuf> {
         let cache_path = EdbCachePath::new(cache_dir)
             .rpc_chain_cache_dir(chain_id)
             .unwrap_or_else(|| PathBuf::from("."))
@@ -420,7 +865,8 @@ impl CacheManager {
             }
         };
 
-        let combined_cache = Self::merge_caches(legacy_cache, split_cache);
+        let mut combined_cache = Self::merge_caches(legacy_cache, split_cache);
+        combined_cache.values_mut().for_each(CacheEntry::restore_metadata);
 
         Ok(combined_cache)
     }
@@ -486,50 +932,66 @@ impl CacheManager {
             std::cmp::min(self.max_items as usize, merged_cache.len())
         };
 
-        // If merged cache fits within target, return as-is
-        if merged_cache.len() <= target_size {
-            return merged_cache;
-        }
-
-        // Apply LRU eviction to fit target size
-        Self::evict_to_size(&mut merged_cache, target_size);
+        // Apply policy-based eviction to fit target size, dropping expired entries
+        Self::evict_to_fit(&self.policies, &mut merged_cache, target_size, self.policies.max_bytes);
         merged_cache
     }
 
-    /// Evicts oldest entries to fit target size using LRU policy
+    /// Evicts entries following the eviction policies to fit the target size
+    ///
+    /// Expired entries are always removed, then entries are evicted in policy order (see
+    /// [`EvictionPolicy`]) until the cache fits both the number of entries and the bytes.
     ///
     /// # Arguments
+    /// * `policies` - Eviction policies of the cache
     /// * `cache` - Cache to evict from
-    /// * `target_size` - Desired final size
-    ///
-    /// # Returns
-    /// Cache with oldest entries removed to fit target_size
-    fn evict_to_size(cache: &mut HashMap<String, CacheEntry>, target_size: usize) {
-        if cache.len() <= target_size {
-            return;
-        }
-
-        let to_remove = cache.len().saturating_sub(target_size);
-
-        // Sort entries by creation time (oldest first)
-        let mut entries: Vec<(String, u64)> =
-            cache.iter().map(|(key, entry)| (key.clone(), entry.accessed_at)).collect();
-
-        entries.sort_by_key(|(_, accessed_at)| *accessed_at);
+    /// * `target_items` - Desired final number of entries
+    /// * `target_bytes` - Desired final size of the responses in bytes, if limited
+    fn evict_to_fit(
+        policies: &CachePolicyConfig,
+        cache: &mut HashMap<String, CacheEntry>,
+        target_items: usize,
+        target_bytes: Option<u64>,
+    ) {
+        let now = unix_now();
+        let mut items = cache.len();
+        let mut bytes = Self::total_size(cache);
+
+        // Sort entries in eviction order (expired first), pinned entries are never evicted
+        let mut entries: Vec<((u8, u64, u64), String, u64)> = cache
+            .iter()
+            .filter(|(_, entry)| !entry.pinned)
+            .map(|(key, entry)| {
+                (entry.eviction_rank(policies.policy_for(key), now), key.clone(), entry.size)
+            })
+            .collect();
+        entries.sort_unstable();
+
+        let mut evicted = 0;
+        for ((tier, _, _), key, size) in entries {
+            let expired = tier == 0;
+            let fits = items <= target_items && target_bytes.is_none_or(|max| bytes <= max);
+            if fits && !expired {
+                break;
+            }
 
-        // Remove oldest entries
-        let keys_to_remove: Vec<String> =
-            entries.into_iter().take(to_remove).map(|(key, _)| key).collect();
+            cache.remove(&key);
+            items -= 1;
+            bytes = bytes.saturating_sub(size);
+            evicted += 1;
+        }
 
-        for key in &keys_to_remove {
-            cache.remove(key);
+        if evicted > 0 {
+            debug!(
+                "Evicted {} entries to fit target size {} ({} bytes)",
+                evicted, target_items, bytes
+            );
         }
+    }
 
-        debug!(
-            "Evicted {} entries during merge to fit target size {}",
-            keys_to_remove.len(),
-            target_size
-        );
+    /// Total size of the responses of a cache in bytes
+    fn total_size(cache: &HashMap<String, CacheEntry>) -> u64 {
+        cache.values().map(|entry| entry.size).sum()
     }
 
     /// Splits cache content string into segments based on MAX_CACHE_FILE_SIZE
@@ -753,6 +1215,7 @@ impl CacheManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::ErrorKind;
     use tempfile::TempDir;
     use tokio::time::{sleep, Duration};
     use tracing::{debug, info};
@@ -764,6 +1227,23 @@ mod tests {
         (manager, temp_dir)
     }
 
+    async fn skip_if_loopback_binds_restricted(test_name: &str) -> bool {
+        match tokio::net::TcpListener::bind("127.0.0.1:0").await {
+            Ok(listener) => {
+                drop(listener);
+                false
+            }
+            Err(error)
+                if matches!(error.kind(), ErrorKind::PermissionDenied)
+                    || error.raw_os_error() == Some(1) =>
+            {
+                info!("Skipping {test_name} because loopback binds are restricted: {error}");
+                true
+            }
+            Err(error) => panic!("Failed to probe loopback bind availability: {error}"),
+        }
+    }
+
     #[tokio::test]
     async fn test_cache_get_set() {
         edb_common::logging::ensure_test_logging(None);
@@ -896,6 +1376,164 @@ mod tests {
         assert!(entry2.accessed_at > entry1.accessed_at);
     }
 
+    #[tokio::test]
+    async fn test_method_policy_override() {
+        edb_common::logging::ensure_test_logging(None);
+        info!("Testing per-method eviction policies");
+
+        let temp_dir = TempDir::new().unwrap();
+        let policies = CachePolicyConfig {
+            method_policies: CachePolicyConfig::parse_method_policies(
+                "eth_getCode=lfu, eth_blockNumber=ttl:0",
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        let manager =
+            CacheManager::with_policies(3, temp_dir.path().join("test_rpc.json"), policies)
+                .unwrap();
+
+        // LFU entries outlive LRU ones, even when older
+        manager.set("eth_getCode:1".to_string(), serde_json::json!("0x6080")).await;
+        manager.set("eth_getStorageAt:1".to_string(), serde_json::json!("0x01")).await;
+        manager.set("eth_getStorageAt:2".to_string(), serde_json::json!("0x02")).await;
+        manager.set("eth_getStorageAt:3".to_string(), serde_json::json!("0x03")).await;
+
+        assert!(manager.get("eth_getCode:1").await.is_some());
+        assert!(manager.get("eth_getStorageAt:1").await.is_none());
+        assert!(manager.get("eth_getStorageAt:3").await.is_some());
+
+        // Expired TTL entries are misses
+        manager.set("eth_blockNumber:1".to_string(), serde_json::json!("0x10")).await;
+        assert!(manager.get("eth_blockNumber:1").await.is_none());
+
+        assert!("ttl".parse::<EvictionPolicy>().is_err());
+        assert_eq!("TTL:60".parse::<EvictionPolicy>().unwrap(), EvictionPolicy::Ttl(60));
+    }
+
+    #[tokio::test]
+    async fn test_max_bytes_eviction() {
+        edb_common::logging::ensure_test_logging(None);
+        info!("Testing size-based cache eviction");
+
+        let temp_dir = TempDir::new().unwrap();
+        let policies = CachePolicyConfig { max_bytes: Some(100), ..Default::default() };
+        let manager =
+            CacheManager::with_policies(100, temp_dir.path().join("test_rpc.json"), policies)
+                .unwrap();
+
+        // Each entry takes 41 bytes, so the third one goes over the limit
+        let value = serde_json::json!({"data": "a".repeat(30)});
+        manager.set("key_1".to_string(), value.clone()).await;
+        manager.set("key_2".to_string(), value.clone()).await;
+        manager.set("key_3".to_string(), value.clone()).await;
+
+        let stats = manager.detailed_stats().await;
+        assert_eq!(stats["total_entries"], 2);
+        assert_eq!(stats["total_bytes"], 82);
+        assert!(manager.get("key_1").await.is_none());
+        assert!(manager.get("key_3").await.is_some());
+
+        // Entries larger than the whole cache are not cached
+        manager.set("huge".to_string(), serde_json::json!("a".repeat(200))).await;
+        assert!(manager.get("huge").await.is_none());
+        assert!(manager.get("key_2").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_import_entries() {
+        let (manager, _temp_dir) = create_test_cache_manager(10);
+        let request = serde_json::json!({"method": "eth_getCode", "params": ["0x01", "0x1"]});
+        manager.set_for_request("local".to_string(), &request, serde_json::json!("0x60")).await;
+        assert_eq!(manager.get_all_entries().await["local"].request, Some(request));
+
+        let mut stale = CacheEntry::new(serde_json::json!("0x00"));
+        stale.accessed_at = 0;
+        let entries = HashMap::from([
+            ("local".to_string(), stale),
+            ("imported".to_string(), CacheEntry::new(serde_json::json!("0x61"))),
+        ]);
+
+        // Entries already cached are kept over older imported ones
+        assert_eq!(manager.import_entries(entries).await, 1);
+        assert_eq!(manager.get("local").await, Some(serde_json::json!("0x60")));
+        assert_eq!(manager.get("imported").await, Some(serde_json::json!("0x61")));
+        assert_eq!(manager.total_bytes(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_entries() {
+        let (manager, _temp_dir) = create_test_cache_manager(10);
+        let request = serde_json::json!({"method": "eth_getCode", "params": ["0x01", "0x1"]});
+        manager
+            .set_for_request("eth_getCode:1".to_string(), &request, serde_json::json!("0x60"))
+            .await;
+        assert!(manager.set_pinned("eth_getCode:1", true).await.unwrap());
+        assert!(!manager.set_pinned("missing", true).await.unwrap());
+
+        // Pinned entries survive eviction, even the least recently used
+        for i in 0..20 {
+            manager.set(format!("eth_getBalance:{i}"), serde_json::json!(i)).await;
+        }
+        assert_eq!(manager.get("eth_getCode:1").await, Some(serde_json::json!("0x60")));
+
+        let (total, entries) =
+            manager.list_entries(Some("eth_getCode"), &ExportFilter::default(), 10).await;
+        assert_eq!(total, 1);
+        assert_eq!(entries[0].key, "eth_getCode:1");
+        assert_eq!(entries[0].method, "eth_getCode");
+        assert_eq!(entries[0].params, Some(serde_json::json!(["0x01", "0x1"])));
+        assert!(entries[0].pinned);
+
+        let (total, entries) = manager.list_entries(None, &ExportFilter::default(), 3).await;
+        assert_eq!(total, manager.entry_count().await);
+        assert_eq!(entries.len(), 3);
+
+        // Pins are persisted
+        let reloaded = CacheManager::new(10, manager.cache_file_path().to_path_buf()).unwrap();
+        assert!(reloaded.get_all_entries().await["eth_getCode:1"].pinned);
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_backing() {
+        use crate::shared::SharedCacheConfig;
+        use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
+
+        if skip_if_loopback_binds_restricted("test_shared_cache_backing").await {
+            return;
+        }
+        let server = MockServer::start().await;
+        let config = SharedCacheConfig { url: server.uri(), token: None };
+        let value = serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "0x6080"});
+
+        // New entries are written through to the shared store
+        Mock::given(method("PUT"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let (writer, _writer_dir) = create_test_cache_manager(10);
+        let writer = writer.with_shared_cache(SharedCache::new(&config, 1).unwrap());
+        writer.set("eth_getCode:1".to_string(), value.clone()).await;
+        sleep(Duration::from_millis(100)).await;
+
+        // Local misses are served by the shared store once, then cached locally
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(CacheEntry::new(value.clone())))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let (reader, _reader_dir) = create_test_cache_manager(10);
+        let reader = reader.with_shared_cache(SharedCache::new(&config, 1).unwrap());
+        assert_eq!(reader.get("eth_getCode:1").await, Some(value.clone()));
+        assert_eq!(reader.get("eth_getCode:1").await, Some(value));
+
+        let stats = reader.detailed_stats().await;
+        assert_eq!(stats["shared_cache"], "http");
+        assert_eq!(stats["shared_cache_hits"], 1);
+        assert_eq!(stats["total_entries"], 1);
+    }
+
     #[tokio::test]
     async fn test_cache_merge_and_size_management() {
         edb_common::logging::ensure_test_logging(None);
@@ -0,0 +1,210 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local node discovery
+//!
+//! On startup, the proxy can probe the endpoints a node running on the same machine
+//! usually listens on: the IPC sockets of geth and reth in their default locations, then
+//! `http://127.0.0.1:8545`. The first node found on the chain served, and able to serve the
+//! state of an early block (i.e. an archive node), is added as a provider.
+//!
+//! Local providers are preferred over remote ones whatever their response time. A local
+//! node answering that the state of a requested block is missing is demoted to an ordinary
+//! provider, see [`ProviderManager::demote_local_provider`].
+//!
+//! [`ProviderManager::demote_local_provider`]: crate::providers::ProviderManager::demote_local_provider
+
+use crate::ipc::{self, is_ipc_path};
+use eyre::{bail, eyre, Result};
+use serde_json::Value;
+use std::{net::IpAddr, path::PathBuf, time::Duration};
+use tracing::{debug, info};
+
+/// HTTP endpoints a local node usually listens on
+const LOCAL_HTTP_ENDPOINTS: &[&str] = &["http://127.0.0.1:8545"];
+
+/// Timeout for each request probing a local endpoint
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Block whose state only an archive node still has
+const ARCHIVE_PROBE_BLOCK: &str = "0x1";
+
+/// Whether a provider runs on this machine: an IPC socket, or an HTTP or WebSocket
+/// endpoint on a loopback address
+pub fn is_local_provider(url: &str) -> bool {
+    if is_ipc_path(url) {
+        return true;
+    }
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    match parsed.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+        None => false,
+    }
+}
+
+/// Default locations of the IPC sockets of local nodes
+fn local_ipc_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/tmp/reth.ipc")];
+    if let Some(home) = dirs_next::home_dir() {
+        paths.push(home.join(".ethereum").join("geth.ipc"));
+        paths.push(home.join("Library").join("Ethereum").join("geth.ipc"));
+    }
+    paths
+}
+
+/// Look for an archive node of a chain running on this machine
+///
+/// # Arguments
+/// * `chain_id` - Chain the node must serve
+///
+/// # Returns
+/// The URL or IPC path of the first local archive node found, if any
+pub async fn discover_local_node(chain_id: u64) -> Option<String> {
+    let client = reqwest::Client::builder().timeout(PROBE_TIMEOUT).build().ok()?;
+
+    let candidates: Vec<String> = local_ipc_paths()
+        .into_iter()
+        .filter(|path| path.exists())
+        .filter_map(|path| path.to_str().map(str::to_string))
+        .chain(LOCAL_HTTP_ENDPOINTS.iter().map(|url| url.to_string()))
+        .collect();
+
+    let probes = candidates.iter().map(|url| probe(&client, url, chain_id));
+    for (url, result) in candidates.iter().zip(futures::future::join_all(probes).await) {
+        match result {
+            Ok(()) => {
+                info!("Discovered local archive node at {}", url);
+                return Some(url.clone());
+            }
+            Err(e) => debug!("No usable local node at {}: {}", url, e),
+        }
+    }
+    None
+}
+
+/// Check that a local endpoint is an archive node of the chain
+async fn probe(client: &reqwest::Client, url: &str, chain_id: u64) -> Result<()> {
+    let chain = call(client, url, "eth_chainId", serde_json::json!([])).await?;
+    let served_chain_id = chain
+        .as_str()
+        .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+        .ok_or_else(|| eyre!("Invalid chain ID {chain}"))?;
+    if served_chain_id != chain_id {
+        bail!("serves chain {served_chain_id} instead of {chain_id}");
+    }
+
+    // Pruned nodes have dropped the state of early blocks
+    let params = serde_json::json!([alloy_primitives::Address::ZERO, ARCHIVE_PROBE_BLOCK]);
+    call(client, url, "eth_getBalance", params)
+        .await
+        .map_err(|e| eyre!("not an archive node: {e}"))?;
+    Ok(())
+}
+
+/// Call a method of a local node, over IPC or HTTP
+async fn call(client: &reqwest::Client, url: &str, method: &str, params: Value) -> Result<Value> {
+    let request =
+        serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+    let response: Value = if is_ipc_path(url) {
+        tokio::time::timeout(PROBE_TIMEOUT, ipc::request(url, &request))
+            .await
+            .map_err(|_| eyre!("Timed out"))??
+    } else {
+        client.post(url).json(&request).send().await?.json().await?
+    };
+
+    if let Some(error) = response.get("error") {
+        bail!("{error}");
+    }
+    response.get("result").cloned().ok_or_else(|| eyre!("Invalid response {response}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+    use wiremock::{matchers::body_partial_json, Mock, MockServer, ResponseTemplate};
+
+    async fn skip_if_loopback_binds_restricted(test_name: &str) -> bool {
+        match tokio::net::TcpListener::bind("127.0.0.1:0").await {
+            Ok(listener) => {
+                drop(listener);
+                false
+            }
+            Err(error)
+                if matches!(error.kind(), ErrorKind::PermissionDenied)
+                    || error.raw_os_error() == Some(1) =>
+            {
+                info!("Skipping {test_name} because loopback binds are restricted: {error}");
+                true
+            }
+            Err(error) => panic!("Failed to probe loopback bind availability: {error}"),
+        }
+    }
+
+    #[test]
+    fn test_is_local_provider() {
+        assert!(is_local_provider("http://localhost:8545"));
+        assert!(is_local_provider("ws://127.0.0.1:8546"));
+        assert!(is_local_provider("http://[::1]:8545"));
+        assert!(is_local_provider("/home/user/.ethereum/geth.ipc"));
+        assert!(!is_local_provider("https://eth.llamarpc.com"));
+        assert!(!is_local_provider("http://10.0.0.2:8545"));
+    }
+
+    #[tokio::test]
+    async fn test_probe_requires_archive_node_of_the_chain() {
+        if skip_if_loopback_binds_restricted("test_probe_requires_archive_node_of_the_chain").await
+        {
+            return;
+        }
+        let client = reqwest::Client::new();
+        let node =
+            |balance: Value| async move {
+                let server = MockServer::start().await;
+                Mock::given(body_partial_json(serde_json::json!({"method": "eth_chainId"})))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(
+                        serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "0x1"}),
+                    ))
+                    .mount(&server)
+                    .await;
+                Mock::given(body_partial_json(serde_json::json!({"method": "eth_getBalance"})))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(balance))
+                    .mount(&server)
+                    .await;
+                server
+            };
+
+        let archive = node(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "0x0"})).await;
+        assert!(probe(&client, &archive.uri(), 1).await.is_ok());
+        assert!(probe(&client, &archive.uri(), 10).await.is_err());
+
+        let pruned = node(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": {"code": -32000, "message": "missing trie node"}
+        }))
+        .await;
+        assert!(probe(&client, &pruned.uri(), 1).await.is_err());
+    }
+}
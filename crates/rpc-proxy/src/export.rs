@@ -0,0 +1,387 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Cache export and import
+//!
+//! Cache entries can be exported to a portable bundle, optionally selected by block range
+//! and by address, and imported into the cache of another machine, so that a session can
+//! be reproduced there without access to an archive node.
+//!
+//! Bundles store a checksum of their entries, verified on import, so that a bundle truncated
+//! or altered in transfer is rejected with the reason instead of being partially imported.
+//!
+//! Entries are selected from the method and params of the request they answer, which the
+//! cache keeps alongside the response, and from the block and addresses of transactions
+//! and receipts. Entries cached before requests were kept with them can only be selected
+//! from their response.
+
+use crate::cache::CacheEntry;
+use edb_common::{checksum, describe_parse_error, verify_checksum};
+use eyre::{bail, eyre, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+/// Version of the cache bundle format
+const BUNDLE_VERSION: u32 = 1;
+
+/// Methods whose first param is a block number
+const BLOCK_PARAM_METHODS: &[&str] = &[
+    "eth_getBlockByNumber",
+    "eth_getBlockReceipts",
+    "eth_getBlockTransactionCountByNumber",
+    "eth_getUncleCountByBlockNumber",
+    "debug_traceBlockByNumber",
+    "trace_block",
+];
+
+/// Methods whose last param is the block the state is read at
+const STATE_METHODS: &[&str] = &[
+    "eth_getBalance",
+    "eth_getCode",
+    "eth_getStorageAt",
+    "eth_getProof",
+    "eth_getTransactionCount",
+    "eth_call",
+];
+
+/// Fields of transactions, receipts and logs holding an address
+const ADDRESS_FIELDS: &[&str] = &["from", "to", "contractAddress", "address"];
+
+/// Cache entries exported from a chain
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheBundle {
+    /// Version of the bundle format
+    pub version: u32,
+    /// Chain the entries were cached on
+    pub chain_id: u64,
+    /// Unix timestamp of the export
+    pub exported_at: u64,
+    /// Exported entries, by cache key
+    pub entries: HashMap<String, CacheEntry>,
+    /// Checksum of the entries, set when the bundle is written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl CacheBundle {
+    /// Create a bundle of entries of a chain
+    pub fn new(chain_id: u64, entries: HashMap<String, CacheEntry>) -> Self {
+        Self {
+            version: BUNDLE_VERSION,
+            chain_id,
+            exported_at: chrono::Utc::now().timestamp() as u64,
+            entries,
+            checksum: None,
+        }
+    }
+
+    /// Write the bundle to a file, with the checksum of its entries
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let mut bundle = serde_json::to_value(self)?;
+        bundle["checksum"] = checksum(&bundle["entries"]).into();
+        fs::write(path, bundle.to_string())?;
+        Ok(())
+    }
+
+    /// Read a bundle from a file, verifying the checksum of its entries if it has one
+    pub fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let bundle: Value = serde_json::from_str(&content).map_err(|e| {
+            eyre!(
+                "Cache bundle {} is corrupted: {}",
+                path.display(),
+                describe_parse_error(&e, content.len())
+            )
+        })?;
+
+        let version = bundle.get("version").and_then(Value::as_u64).unwrap_or_default();
+        if version != BUNDLE_VERSION as u64 {
+            bail!(
+                "Unsupported cache bundle version {} in {}, expected {}",
+                version,
+                path.display(),
+                BUNDLE_VERSION
+            );
+        }
+        if let Some(expected) = bundle.get("checksum").and_then(Value::as_str) {
+            let entries = bundle.get("entries").unwrap_or(&Value::Null);
+            verify_checksum(entries, expected)
+                .map_err(|e| eyre!("Cache bundle {} is corrupted: {e}", path.display()))?;
+        }
+        Ok(serde_json::from_value(bundle)?)
+    }
+}
+
+/// Selection of the cache entries to export
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// First block of the selected range
+    pub from_block: Option<u64>,
+    /// Last block of the selected range
+    pub to_block: Option<u64>,
+    /// Selected addresses, lowercase
+    pub addresses: HashSet<String>,
+}
+
+impl ExportFilter {
+    /// Parse a comma-separated list of addresses
+    pub fn parse_addresses(addresses: &str) -> Result<HashSet<String>> {
+        addresses
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                if !is_address(address) {
+                    bail!("Invalid address {address:?}");
+                }
+                Ok(address.to_lowercase())
+            })
+            .collect()
+    }
+
+    /// Whether an entry is selected: within the block range, and touching one of the addresses
+    ///
+    /// Entries whose block is unknown are not selected when a block range is given.
+    pub fn matches(&self, entry: &CacheEntry) -> bool {
+        if self.from_block.is_some() || self.to_block.is_some() {
+            let Some((first, last)) = entry_blocks(entry) else {
+                return false;
+            };
+            let from = self.from_block.unwrap_or(0);
+            let to = self.to_block.unwrap_or(u64::MAX);
+            if last < from || first > to {
+                return false;
+            }
+        }
+
+        if !self.addresses.is_empty() {
+            let mut addresses = HashSet::new();
+            collect_entry_addresses(entry, &mut addresses);
+            if addresses.is_disjoint(&self.addresses) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Blocks an entry is about, as an inclusive range
+fn entry_blocks(entry: &CacheEntry) -> Option<(u64, u64)> {
+    if let Some(request) = &entry.request {
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").and_then(Value::as_array);
+
+        let block = if BLOCK_PARAM_METHODS.contains(&method) {
+            params.and_then(|p| p.first()).and_then(block_number)
+        } else if STATE_METHODS.contains(&method) {
+            state_block(request)
+        } else if method == "eth_getLogs" {
+            let filter = params.and_then(|p| p.first());
+            let from = filter.and_then(|f| f.get("fromBlock")).and_then(block_number);
+            let to = filter.and_then(|f| f.get("toBlock")).and_then(block_number);
+            if let (Some(from), Some(to)) = (from, to) {
+                return Some((from, to));
+            }
+            None
+        } else {
+            None
+        };
+        if let Some(block) = block {
+            return Some((block, block));
+        }
+    }
+
+    // Transactions, receipts and traces carry their block
+    let result = entry.data.get("result")?;
+    let block = result.get("blockNumber").or_else(|| result.get(0)?.get("blockNumber"))?;
+    block_number(block).map(|block| (block, block))
+}
+
+/// Block whose state a request reads, if given by number
+///
+/// Only state queries (balances, code, storage, calls, ...) at a numbered block have one;
+/// tags such as `latest` and block hashes are not resolved.
+pub(crate) fn state_block(request: &Value) -> Option<u64> {
+    let method = request.get("method").and_then(Value::as_str)?;
+    if !STATE_METHODS.contains(&method) {
+        return None;
+    }
+    request.get("params").and_then(Value::as_array)?.last().and_then(block_number)
+}
+
+/// Parse a block number, or a block object (`{"blockNumber": ...}`) as in EIP-1898
+fn block_number(block: &Value) -> Option<u64> {
+    let block = block.get("blockNumber").unwrap_or(block);
+    match block {
+        Value::String(hex) => u64::from_str_radix(hex.strip_prefix("0x")?, 16).ok(),
+        Value::Number(number) => number.as_u64(),
+        _ => None,
+    }
+}
+
+/// Collect the addresses an entry touches
+fn collect_entry_addresses(entry: &CacheEntry, addresses: &mut HashSet<String>) {
+    if let Some(params) = entry.request.as_ref().and_then(|request| request.get("params")) {
+        collect_addresses(params, addresses);
+    }
+
+    // Transactions and receipts, and the logs of receipts and log queries
+    let Some(result) = entry.data.get("result") else {
+        return;
+    };
+    let objects = match result {
+        Value::Array(items) => items.iter().collect(),
+        _ => vec![result],
+    };
+    for object in objects {
+        for field in ADDRESS_FIELDS {
+            if let Some(Value::String(address)) = object.get(field) {
+                if is_address(address) {
+                    addresses.insert(address.to_lowercase());
+                }
+            }
+        }
+        for log in object.get("logs").and_then(Value::as_array).into_iter().flatten() {
+            if let Some(Value::String(address)) = log.get("address") {
+                addresses.insert(address.to_lowercase());
+            }
+        }
+    }
+}
+
+/// Collect the addresses in request params, e.g. accounts, call targets, and log filters
+fn collect_addresses(value: &Value, addresses: &mut HashSet<String>) {
+    match value {
+        Value::String(s) if is_address(s) => {
+            addresses.insert(s.to_lowercase());
+        }
+        Value::Array(values) => values.iter().for_each(|v| collect_addresses(v, addresses)),
+        Value::Object(object) => object.values().for_each(|v| collect_addresses(v, addresses)),
+        _ => {}
+    }
+}
+
+/// Whether a string is a hex address
+fn is_address(s: &str) -> bool {
+    s.len() == 42 && s.starts_with("0x") && s[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48";
+
+    fn entry(request: Value, result: Value) -> CacheEntry {
+        serde_json::from_value(serde_json::json!({
+            "data": {"jsonrpc": "2.0", "id": 1, "result": result},
+            "accessed_at": 1_700_000_000,
+            "request": request,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_filter_by_block_range() {
+        let filter =
+            ExportFilter { from_block: Some(100), to_block: Some(200), ..Default::default() };
+
+        let code = |block: &str| {
+            entry(
+                serde_json::json!({"method": "eth_getCode", "params": [TOKEN, block]}),
+                serde_json::json!("0x6080"),
+            )
+        };
+        assert!(filter.matches(&code("0x64")));
+        assert!(!filter.matches(&code("0xc9")));
+
+        let logs = entry(
+            serde_json::json!({"method": "eth_getLogs", "params": [{"fromBlock": "0x0", "toBlock": "0x64"}]}),
+            serde_json::json!([]),
+        );
+        assert!(filter.matches(&logs));
+
+        // Receipts carry their block, entries of unknown block are left out
+        let receipt = entry(
+            serde_json::json!({"method": "eth_getTransactionReceipt", "params": ["0xabc"]}),
+            serde_json::json!({"blockNumber": "0x96", "from": TOKEN}),
+        );
+        assert!(filter.matches(&receipt));
+        let chain_id =
+            entry(serde_json::json!({"method": "eth_chainId"}), serde_json::json!("0x1"));
+        assert!(!filter.matches(&chain_id));
+        assert!(ExportFilter::default().matches(&chain_id));
+    }
+
+    #[test]
+    fn test_filter_by_address() {
+        let filter = ExportFilter {
+            addresses: ExportFilter::parse_addresses(&format!("{TOKEN}, ")).unwrap(),
+            ..Default::default()
+        };
+
+        let call = entry(
+            serde_json::json!({"method": "eth_call", "params": [{"to": TOKEN.to_lowercase(), "data": "0x"}, "0x1"]}),
+            serde_json::json!("0x"),
+        );
+        assert!(filter.matches(&call));
+
+        let other = entry(
+            serde_json::json!({"method": "eth_getBalance", "params": ["0x0000000000000000000000000000000000000001", "0x1"]}),
+            serde_json::json!("0x0"),
+        );
+        assert!(!filter.matches(&other));
+
+        let receipt = entry(
+            serde_json::json!({"method": "eth_getTransactionReceipt", "params": ["0xabc"]}),
+            serde_json::json!({"blockNumber": "0x1", "logs": [{"address": TOKEN}]}),
+        );
+        assert!(filter.matches(&receipt));
+
+        assert!(ExportFilter::parse_addresses("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_bundle_checksum() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("bundle.json");
+        let code = entry(
+            serde_json::json!({"method": "eth_getCode", "params": [TOKEN, "0x1"]}),
+            serde_json::json!("0x6080"),
+        );
+        CacheBundle::new(1, HashMap::from([("code".to_string(), code)])).write(&path).unwrap();
+        let bundle = CacheBundle::read(&path).unwrap();
+        assert_eq!(bundle.entries.len(), 1);
+        assert!(bundle.checksum.is_some());
+
+        // Entries altered in transfer
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace("0x6080", "0x6081")).unwrap();
+        let error = CacheBundle::read(&path).unwrap_err().to_string();
+        assert!(error.contains("checksum mismatch"), "{error}");
+
+        // Truncated bundle
+        fs::write(&path, &content[..content.len() - 10]).unwrap();
+        let error = CacheBundle::read(&path).unwrap_err().to_string();
+        assert!(error.contains("truncated"), "{error}");
+    }
+}
@@ -0,0 +1,225 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! In-flight request deduplication
+//!
+//! Identical requests (same method and params) arriving while one of them is already
+//! being forwarded upstream are coalesced: the first request goes upstream, and the
+//! others wait for its response, which is fanned out to each of them with their own
+//! request id. If the first request is cancelled, or refused because its token is over
+//! its upstream quota, the waiting requests are forwarded on their own.
+
+use crate::auth::QuotaExceeded;
+use eyre::{eyre, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Outcome of an upstream request, shared with the requests coalesced into it
+type SharedOutcome = Result<Value, String>;
+
+/// Whether a request went upstream or waited for an identical one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flight {
+    /// The request was forwarded upstream
+    Leader,
+    /// The request was served by an identical request already in flight
+    Follower,
+}
+
+/// Requests currently forwarded upstream, by cache key
+#[derive(Debug, Default)]
+pub struct InFlightRequests {
+    /// Channel announcing the outcome of each request in flight
+    pending: Mutex<HashMap<String, broadcast::Sender<SharedOutcome>>>,
+}
+
+impl InFlightRequests {
+    /// Run an upstream request, unless an identical one is in flight
+    ///
+    /// # Arguments
+    /// * `key` - Key identifying identical requests (method and params)
+    /// * `request` - The JSON-RPC request, whose id is set on the response
+    /// * `forward` - Future forwarding the request upstream
+    ///
+    /// # Returns
+    /// The response, and whether it came from a request already in flight
+    pub async fn coalesce<F>(
+        &self,
+        key: &str,
+        request: &Value,
+        forward: F,
+    ) -> (Result<Value>, Flight)
+    where
+        F: Future<Output = Result<Value>>,
+    {
+        let (sender, mut receiver) = {
+            let mut pending = self.pending.lock().unwrap();
+            match pending.get(key) {
+                Some(sender) => (None, Some(sender.subscribe())),
+                None => {
+                    let (sender, _) = broadcast::channel(1);
+                    pending.insert(key.to_string(), sender.clone());
+                    (Some(sender), None)
+                }
+            }
+        };
+
+        if let Some(receiver) = receiver.as_mut() {
+            debug!("Waiting for identical request in flight: {}", key);
+            match receiver.recv().await {
+                Ok(outcome) => {
+                    let response = outcome.map_err(|e| eyre!(e)).map(|mut response| {
+                        if let Some(object) = response.as_object_mut() {
+                            object.insert(
+                                "id".to_string(),
+                                request.get("id").cloned().unwrap_or(Value::Null),
+                            );
+                        }
+                        response
+                    });
+                    return (response, Flight::Follower);
+                }
+                // The request in flight was cancelled or refused, forward this one on its own
+                Err(_) => return (forward.await, Flight::Leader),
+            }
+        }
+
+        // Stop coalescing into this request once it completes, or if it is cancelled
+        let guard = FlightGuard { flights: self, key };
+        let response = forward.await;
+        drop(guard);
+
+        // The quota of a token is its own: the requests coalesced into a refused request
+        // are forwarded for their own tokens when the sender is dropped
+        if let Some(sender) = sender.filter(|_| !is_quota_exceeded(&response)) {
+            let outcome = match &response {
+                Ok(response) => Ok(response.clone()),
+                Err(e) => Err(e.to_string()),
+            };
+            // No receivers just means no request was coalesced
+            let _ = sender.send(outcome);
+        }
+
+        (response, Flight::Leader)
+    }
+}
+
+fn is_quota_exceeded(response: &Result<Value>) -> bool {
+    matches!(response, Err(e) if e.is::<QuotaExceeded>())
+}
+
+/// Removes a request from the in-flight requests when dropped
+struct FlightGuard<'a> {
+    flights: &'a InFlightRequests,
+    key: &'a str,
+}
+
+impl Drop for FlightGuard<'_> {
+    fn drop(&mut self) {
+        self.flights.pending.lock().unwrap().remove(self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_identical_requests_are_coalesced() {
+        let flights = InFlightRequests::default();
+        let upstream_calls = &AtomicUsize::new(0);
+        let forward = move || async move {
+            upstream_calls.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok::<_, eyre::Report>(
+                serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "0x6080"}),
+            )
+        };
+
+        let first = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "eth_getCode"});
+        let second = serde_json::json!({"jsonrpc": "2.0", "id": "b", "method": "eth_getCode"});
+        let ((first, first_flight), (second, second_flight)) = futures::future::join(
+            flights.coalesce("eth_getCode:1", &first, forward()),
+            flights.coalesce("eth_getCode:1", &second, forward()),
+        )
+        .await;
+
+        assert_eq!(upstream_calls.load(Ordering::Relaxed), 1);
+        assert_eq!((first_flight, second_flight), (Flight::Leader, Flight::Follower));
+        assert_eq!(first.unwrap()["id"], 1);
+        let second = second.unwrap();
+        assert_eq!(second["id"], "b");
+        assert_eq!(second["result"], "0x6080");
+        assert!(flights.pending.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_fanned_out() {
+        let flights = InFlightRequests::default();
+        let request = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "eth_getCode"});
+        let forward = || async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Err::<Value, _>(eyre!("All providers failed"))
+        };
+
+        let ((first, _), (second, _)) = futures::future::join(
+            flights.coalesce("eth_getCode:1", &request, forward()),
+            flights.coalesce("eth_getCode:1", &request, forward()),
+        )
+        .await;
+
+        assert_eq!(first.unwrap_err().to_string(), "All providers failed");
+        assert_eq!(second.unwrap_err().to_string(), "All providers failed");
+    }
+
+    #[tokio::test]
+    async fn test_quota_errors_are_not_fanned_out() {
+        let auth = crate::auth::TokenAuth::parse("alice=secret-a", Some(0)).unwrap();
+        let quota_exceeded = crate::auth::scope(auth.authenticate("secret-a"), async {
+            crate::auth::charge_upstream_request().unwrap_err()
+        })
+        .await;
+
+        let flights = InFlightRequests::default();
+        let request = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "eth_getCode"});
+        let refused = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Err::<Value, _>(eyre::Report::new(quota_exceeded))
+        };
+        let forwarded = async {
+            Ok::<_, eyre::Report>(
+                serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": "0x6080"}),
+            )
+        };
+
+        // The request coalesced into a refused one is forwarded for its own token
+        let ((first, _), (second, second_flight)) = futures::future::join(
+            flights.coalesce("eth_getCode:1", &request, refused),
+            flights.coalesce("eth_getCode:1", &request, forwarded),
+        )
+        .await;
+
+        assert!(first.unwrap_err().is::<QuotaExceeded>());
+        assert_eq!(second_flight, Flight::Leader);
+        assert_eq!(second.unwrap()["result"], "0x6080");
+    }
+}
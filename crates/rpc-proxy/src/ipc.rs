@@ -0,0 +1,127 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! IPC upstream providers
+//!
+//! Upstreams given as the path of a Unix socket ending in `.ipc`, as geth and reth name
+//! theirs, are served over IPC. Each request opens its own connection, which is cheap for a
+//! node on the same machine, and its response is the first complete JSON value read back.
+
+use eyre::Result;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Timeout for a response to a request sent over IPC
+const IPC_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether an upstream URL is the path of an IPC socket
+pub fn is_ipc_path(url: &str) -> bool {
+    !url.contains("://") && url.ends_with(".ipc")
+}
+
+/// Send a request to the node listening on an IPC socket
+#[cfg(unix)]
+pub async fn request(path: &str, request: &Value) -> Result<Value> {
+    use eyre::{bail, eyre};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixStream,
+    };
+
+    let exchange = async {
+        let mut stream = UnixStream::connect(path).await?;
+        stream.write_all(&serde_json::to_vec(request)?).await?;
+
+        // The response may span several reads, it is complete once it parses
+        let mut buffer = Vec::new();
+        let mut chunk = [0u8; 8192];
+        loop {
+            let read = stream.read(&mut chunk).await?;
+            if read == 0 {
+                bail!("IPC connection to {path} closed before responding");
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+            match serde_json::from_slice::<Value>(&buffer) {
+                Ok(response) => return Ok(response),
+                Err(e) if e.is_eof() => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    };
+
+    tokio::time::timeout(IPC_REQUEST_TIMEOUT, exchange)
+        .await
+        .map_err(|_| eyre!("Timed out waiting for a response from {path}"))?
+}
+
+/// Send a request to the node listening on an IPC socket
+#[cfg(not(unix))]
+pub async fn request(path: &str, _request: &Value) -> Result<Value> {
+    eyre::bail!("IPC providers are only supported on Unix, cannot use {path}")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+    use tempfile::TempDir;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixListener,
+    };
+    use tracing::info;
+
+    #[test]
+    fn test_is_ipc_path() {
+        assert!(is_ipc_path("/tmp/reth.ipc"));
+        assert!(!is_ipc_path("http://localhost:8545"));
+        assert!(!is_ipc_path("/tmp/reth.sock"));
+    }
+
+    #[tokio::test]
+    async fn test_request_over_ipc() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("node.ipc");
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(error)
+                if matches!(error.kind(), ErrorKind::PermissionDenied)
+                    || error.raw_os_error() == Some(1) =>
+            {
+                info!(
+                    "Skipping test_request_over_ipc because Unix sockets are restricted: {error}"
+                );
+                return;
+            }
+            Err(error) => panic!("Failed to bind the IPC socket: {error}"),
+        };
+
+        // The node answers in two writes, as large responses are
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 1024];
+            let _ = stream.read(&mut buffer).await.unwrap();
+            stream.write_all(br#"{"jsonrpc":"2.0","id":1,"#).await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            stream.write_all(b"\"result\":\"0x1\"}\n").await.unwrap();
+        });
+
+        let request = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "eth_chainId"});
+        let response = super::request(path.to_str().unwrap(), &request).await.unwrap();
+        assert_eq!(response["result"], "0x1");
+    }
+}
@@ -0,0 +1,356 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Request journal
+//!
+//! The proxy can log every request it proxies, with its response and timing, to a journal:
+//! a JSON Lines file with one HAR-style entry per request. Management methods (`edb_*`) are
+//! not logged.
+//!
+//! A journal can be replayed against a running proxy, sequentially or concurrently and
+//! optionally at the pace it was recorded, for load testing. Each response is compared to
+//! the logged one, so that a replay against a proxy serving from its cache reveals the
+//! cached responses that differ from what the upstreams answered.
+
+use eyre::{bail, eyre, Result};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{LineWriter, Write},
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// A request proxied, as logged in a journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix timestamp in milliseconds when the request was received
+    pub started_at_ms: u64,
+    /// Time taken to serve the request, in milliseconds
+    pub time_ms: u64,
+    /// Chain the request was sent to, if not the default one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    /// Name of the auth token of the request, if authenticated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    /// The JSON-RPC request
+    pub request: Value,
+    /// The JSON-RPC response, if the request did not fail with an HTTP error
+    pub response: Option<Value>,
+}
+
+impl JournalEntry {
+    /// Method of the request
+    pub fn method(&self) -> &str {
+        self.request.get("method").and_then(Value::as_str).unwrap_or("")
+    }
+}
+
+/// Journal the proxied requests are logged to
+#[derive(Debug)]
+pub struct RequestJournal {
+    writer: Mutex<LineWriter<File>>,
+}
+
+impl RequestJournal {
+    /// Open a journal, appending to the file if it exists
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| eyre!("Failed to open request log {}: {e}", path.display()))?;
+        Ok(Self { writer: Mutex::new(LineWriter::new(file)) })
+    }
+
+    /// Log a proxied request
+    ///
+    /// A journal that cannot be written never fails the request, the entry is dropped.
+    pub fn record(&self, entry: &JournalEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize request log entry: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = writeln!(self.writer.lock().unwrap(), "{line}") {
+            warn!("Failed to write request log entry: {}", e);
+        }
+    }
+}
+
+/// Read the entries of a journal
+pub fn read(path: &Path) -> Result<Vec<JournalEntry>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| eyre!("Failed to read request log {}: {e}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                eyre!("Invalid entry on line {} of {}: {e}", index + 1, path.display())
+            })
+        })
+        .collect()
+}
+
+/// How a journal is replayed
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Requests in flight at once
+    pub concurrency: usize,
+    /// Send each request at the same offset from the first one as when it was logged
+    pub preserve_timing: bool,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self { concurrency: 1, preserve_timing: false }
+    }
+}
+
+/// A replayed response differing from the logged one
+#[derive(Debug, Clone)]
+pub struct Mismatch {
+    /// Index of the entry in the journal
+    pub index: usize,
+    /// Method of the request
+    pub method: String,
+    /// Result logged
+    pub logged: Value,
+    /// Result or error of the replayed request
+    pub replayed: Value,
+}
+
+/// Outcome of replaying a journal
+#[derive(Debug, Clone)]
+pub struct ReplayReport {
+    /// Requests replayed
+    pub requests: usize,
+    /// Requests that failed with an HTTP or connection error
+    pub failures: usize,
+    /// Successful logged responses the replay did not reproduce
+    pub mismatches: Vec<Mismatch>,
+    /// Time taken by the whole replay
+    pub elapsed: Duration,
+    /// Response times of the replayed requests in milliseconds, sorted
+    latencies_ms: Vec<u64>,
+}
+
+impl ReplayReport {
+    /// Response time under which a share of the requests were served, e.g. 0.95
+    pub fn latency_percentile(&self, share: f64) -> Option<u64> {
+        let last = self.latencies_ms.len().checked_sub(1)?;
+        Some(self.latencies_ms[((last as f64) * share).round() as usize])
+    }
+
+    /// Requests replayed per second
+    pub fn throughput(&self) -> f64 {
+        self.requests as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Replay a journal against a proxy
+///
+/// # Arguments
+/// * `path` - Journal to replay
+/// * `proxy_url` - URL of the proxy, including the auth token if it requires one
+/// * `options` - Concurrency and pacing of the replay
+///
+/// # Returns
+/// The failures, mismatches and response times of the replay
+pub async fn replay(path: &Path, proxy_url: &str, options: &ReplayOptions) -> Result<ReplayReport> {
+    let entries = read(path)?;
+    if entries.is_empty() {
+        bail!("No requests logged in {}", path.display());
+    }
+    let base_url = reqwest::Url::parse(proxy_url)?;
+    let client = reqwest::Client::new();
+    let first_started_at = entries.iter().map(|e| e.started_at_ms).min().unwrap_or_default();
+
+    let started = Instant::now();
+    let outcomes: Vec<_> = futures::stream::iter(entries.iter().enumerate())
+        .map(|(index, entry)| {
+            let (client, mut url) = (client.clone(), base_url.clone());
+            async move {
+                if options.preserve_timing {
+                    let offset = entry.started_at_ms.saturating_sub(first_started_at);
+                    tokio::time::sleep_until((started + Duration::from_millis(offset)).into())
+                        .await;
+                }
+                if let Some(chain_id) = entry.chain_id {
+                    url.set_path(&format!("/{chain_id}"));
+                }
+
+                let sent = Instant::now();
+                let response = async {
+                    client
+                        .post(url)
+                        .json(&entry.request)
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .json::<Value>()
+                        .await
+                }
+                .await;
+                (index, entry, response, sent.elapsed().as_millis() as u64)
+            }
+        })
+        .buffer_unordered(options.concurrency.max(1))
+        .collect()
+        .await;
+
+    let mut report = ReplayReport {
+        requests: outcomes.len(),
+        failures: 0,
+        mismatches: Vec::new(),
+        elapsed: started.elapsed(),
+        latencies_ms: Vec::with_capacity(outcomes.len()),
+    };
+    for (index, entry, response, latency_ms) in outcomes {
+        report.latencies_ms.push(latency_ms);
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Replayed request {} failed: {}", index, e);
+                report.failures += 1;
+                continue;
+            }
+        };
+
+        // Only successful responses are expected to be reproduced, errors may be transient
+        let Some(logged) = entry.response.as_ref().and_then(|r| r.get("result")) else {
+            continue;
+        };
+        if response.get("result") != Some(logged) {
+            let replayed = response.get("result").or(response.get("error")).cloned();
+            report.mismatches.push(Mismatch {
+                index,
+                method: entry.method().to_string(),
+                logged: logged.clone(),
+                replayed: replayed.unwrap_or(Value::Null),
+            });
+        }
+    }
+    report.latencies_ms.sort_unstable();
+    report.mismatches.sort_by_key(|m| m.index);
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+    use tempfile::TempDir;
+    use tracing::info;
+    use wiremock::{matchers::body_partial_json, Mock, MockServer, ResponseTemplate};
+
+    async fn skip_if_loopback_binds_restricted(test_name: &str) -> bool {
+        match tokio::net::TcpListener::bind("127.0.0.1:0").await {
+            Ok(listener) => {
+                drop(listener);
+                false
+            }
+            Err(error)
+                if matches!(error.kind(), ErrorKind::PermissionDenied)
+                    || error.raw_os_error() == Some(1) =>
+            {
+                info!("Skipping {test_name} because loopback binds are restricted: {error}");
+                true
+            }
+            Err(error) => panic!("Failed to probe loopback bind availability: {error}"),
+        }
+    }
+
+    fn entry(method: &str, result: &str) -> JournalEntry {
+        JournalEntry {
+            started_at_ms: 1_700_000_000_000,
+            time_ms: 12,
+            chain_id: None,
+            token: None,
+            request: serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": []}),
+            response: Some(serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result})),
+        }
+    }
+
+    #[test]
+    fn test_record_and_read() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("logs").join("requests.jsonl");
+
+        let journal = RequestJournal::open(&path).unwrap();
+        journal.record(&entry("eth_chainId", "0x1"));
+        journal.record(&JournalEntry { response: None, ..entry("eth_gasPrice", "") });
+        drop(journal);
+
+        // Reopening appends
+        RequestJournal::open(&path).unwrap().record(&entry("eth_blockNumber", "0x10"));
+
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].method(), "eth_chainId");
+        assert!(entries[1].response.is_none());
+        assert_eq!(entries[2].method(), "eth_blockNumber");
+
+        fs::write(&path, "{not json}\n").unwrap();
+        assert!(read(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_mismatches() {
+        if skip_if_loopback_binds_restricted("test_replay_reports_mismatches").await {
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("requests.jsonl");
+        let journal = RequestJournal::open(&path).unwrap();
+        journal.record(&entry("eth_chainId", "0x1"));
+        journal.record(&entry("eth_getCode", "0x6080"));
+        drop(journal);
+
+        // The proxy now serves different code than was logged
+        let proxy = MockServer::start().await;
+        for (method, result) in [("eth_chainId", "0x1"), ("eth_getCode", "0x")] {
+            Mock::given(body_partial_json(serde_json::json!({"method": method})))
+                .respond_with(ResponseTemplate::new(200).set_body_json(
+                    serde_json::json!({"jsonrpc": "2.0", "id": 1, "result": result}),
+                ))
+                .mount(&proxy)
+                .await;
+        }
+
+        let options = ReplayOptions { concurrency: 2, ..Default::default() };
+        let report = replay(&path, &proxy.uri(), &options).await.unwrap();
+        assert_eq!(report.requests, 2);
+        assert_eq!(report.failures, 0);
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].method, "eth_getCode");
+        assert_eq!(report.mismatches[0].replayed, "0x");
+        assert!(report.latency_percentile(0.5).is_some());
+    }
+}
@@ -22,14 +22,27 @@
 //! Provides intelligent caching of immutable RPC responses to improve performance and reduce
 //! network overhead for multiple debugging sessions.
 
+pub mod auth;
 pub mod cache;
+pub mod discovery;
+pub mod export;
 pub mod health;
+pub mod inflight;
+pub mod ipc;
+pub mod journal;
+pub mod logs;
 pub mod metrics;
+pub mod prometheus;
 pub mod providers;
 pub mod proxy;
+pub mod ratelimit;
 pub mod registry;
 pub mod rpc;
+pub mod session;
+pub mod shared;
 pub mod tui;
+pub mod warm;
+pub mod ws;
 
 pub use cache::CacheEntry;
 pub use proxy::{ProxyServer, ProxyServerBuilder};
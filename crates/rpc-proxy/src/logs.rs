@@ -0,0 +1,178 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! `eth_getLogs` range splitting
+//!
+//! Log queries over a numeric block range are split into sub-ranges aligned on
+//! [`LOG_RANGE_CHUNK`] blocks, each cached on its own. Overlapping queries then share the
+//! cached chunks they have in common, and no upstream call spans more blocks than
+//! providers usually allow. The filter is normalized (canonical hex block numbers,
+//! lowercase addresses and topics) so that equivalent queries hit the same cache entries.
+
+use serde_json::{Map, Value};
+
+/// Number of blocks in each cached `eth_getLogs` sub-range
+pub const LOG_RANGE_CHUNK: u64 = 2_000;
+
+/// Filter of an `eth_getLogs` request over a numeric block range
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    /// Normalized filter, without its block range
+    filter: Map<String, Value>,
+    /// First block of the range
+    from_block: u64,
+    /// Last block of the range
+    to_block: u64,
+}
+
+impl LogFilter {
+    /// Parse the filter of an `eth_getLogs` request, if it queries a numeric block range
+    pub fn from_request(request: &Value) -> Option<Self> {
+        let mut filter = request.get("params")?.get(0)?.as_object()?.clone();
+        let from_block = parse_block_number(filter.remove("fromBlock")?.as_str()?)?;
+        let to_block = parse_block_number(filter.remove("toBlock")?.as_str()?)?;
+        if from_block > to_block || filter.contains_key("blockHash") {
+            return None;
+        }
+
+        for field in ["address", "topics"] {
+            if let Some(value) = filter.get_mut(field) {
+                lowercase_strings(value);
+            }
+        }
+
+        Some(Self { filter, from_block, to_block })
+    }
+
+    /// Split the block range into sub-ranges aligned on [`LOG_RANGE_CHUNK`] blocks
+    pub fn sub_ranges(&self) -> Vec<(u64, u64)> {
+        let mut ranges = Vec::new();
+        let mut start = self.from_block;
+        loop {
+            let chunk_end = (start / LOG_RANGE_CHUNK + 1) * LOG_RANGE_CHUNK - 1;
+            let end = chunk_end.min(self.to_block);
+            ranges.push((start, end));
+            if end == self.to_block {
+                return ranges;
+            }
+            start = end + 1;
+        }
+    }
+
+    /// Build the request querying a sub-range of the filter, with the id of the original request
+    pub fn sub_request(&self, request: &Value, (from_block, to_block): (u64, u64)) -> Value {
+        let mut filter = self.filter.clone();
+        filter.insert("fromBlock".to_string(), format!("{from_block:#x}").into());
+        filter.insert("toBlock".to_string(), format!("{to_block:#x}").into());
+
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request.get("id").cloned().unwrap_or(Value::Null),
+            "method": "eth_getLogs",
+            "params": [filter],
+        })
+    }
+}
+
+/// Whether an `eth_getLogs` request has an open block range, which defaults to `latest`
+pub fn has_open_block_range(request: &Value) -> bool {
+    let Some(filter) = request.get("params").and_then(|p| p.get(0)) else {
+        return true;
+    };
+    filter.get("blockHash").is_none()
+        && (filter.get("fromBlock").is_none() || filter.get("toBlock").is_none())
+}
+
+/// Parse a hex block number
+fn parse_block_number(block: &str) -> Option<u64> {
+    u64::from_str_radix(block.strip_prefix("0x")?, 16).ok()
+}
+
+/// Lowercase the strings of a value, e.g. hex addresses and topics
+fn lowercase_strings(value: &mut Value) {
+    match value {
+        Value::String(s) => *s = s.to_lowercase(),
+        Value::Array(values) => values.iter_mut().for_each(lowercase_strings),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_logs(filter: Value) -> Value {
+        serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "eth_getLogs", "params": [filter]})
+    }
+
+    #[test]
+    fn test_sub_ranges_are_aligned() {
+        let filter = LogFilter::from_request(&get_logs(serde_json::json!({
+            "fromBlock": "0x5dc", // 1500
+            "toBlock": "0x1194",  // 4500
+        })))
+        .unwrap();
+        assert_eq!(filter.sub_ranges(), [(1500, 1999), (2000, 3999), (4000, 4500)]);
+
+        let single = LogFilter::from_request(&get_logs(serde_json::json!({
+            "fromBlock": "0x7d0",
+            "toBlock": "0x7d0",
+        })))
+        .unwrap();
+        assert_eq!(single.sub_ranges(), [(2000, 2000)]);
+    }
+
+    #[test]
+    fn test_filter_normalization() {
+        let request = get_logs(serde_json::json!({
+            "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            "topics": [["0xDDF252AD1BE2C89B69C2B068FC378DAA952BA7F163C4A11628F55A4DF523B3EF"], null],
+            "fromBlock": "0x00ff",
+            "toBlock": "0x100",
+        }));
+        let filter = LogFilter::from_request(&request).unwrap();
+        let sub_request = filter.sub_request(&request, (255, 256));
+
+        assert_eq!(sub_request["id"], 7);
+        assert_eq!(sub_request["params"][0]["fromBlock"], "0xff");
+        assert_eq!(
+            sub_request["params"][0]["address"],
+            "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"
+        );
+        assert_eq!(
+            sub_request["params"][0]["topics"],
+            serde_json::json!([
+                ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+                null
+            ])
+        );
+    }
+
+    #[test]
+    fn test_non_numeric_ranges() {
+        assert!(LogFilter::from_request(&get_logs(serde_json::json!({
+            "fromBlock": "0x1",
+            "toBlock": "latest",
+        })))
+        .is_none());
+        assert!(
+            LogFilter::from_request(&get_logs(serde_json::json!({"blockHash": "0xabc"}))).is_none()
+        );
+
+        assert!(has_open_block_range(&get_logs(serde_json::json!({"fromBlock": "0x1"}))));
+        assert!(!has_open_block_range(&get_logs(serde_json::json!({"blockHash": "0xabc"}))));
+    }
+}
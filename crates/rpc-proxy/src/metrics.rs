@@ -26,6 +26,9 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
+/// Upper bounds of the provider latency histogram buckets, in milliseconds
+pub const LATENCY_BUCKETS_MS: &[u64] = &[10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000];
+
 /// Method-level performance statistics
 ///
 /// Tracks comprehensive performance metrics for individual RPC methods,
@@ -36,7 +39,7 @@ pub struct MethodStats {
     pub hits: u64,
     /// Number of requests forwarded to upstream providers (cache misses + non-cacheable)
     pub misses: u64,
-    /// Total number of requests for this method (hits + misses)
+    /// Total number of requests for this method (hits + misses + deduplicated requests)
     pub total_requests: u64,
     /// Average response time in milliseconds
     pub avg_response_time_ms: f64,
@@ -107,6 +110,9 @@ pub struct ProviderUsage {
     pub last_used_timestamp: u64,
     /// Recent response times (limited to last 100) for histogram analysis
     pub response_time_history: VecDeque<u64>,
+    /// Number of requests in each bucket of [`LATENCY_BUCKETS_MS`], slower ones excluded
+    #[serde(default)]
+    pub latency_buckets: Vec<u64>,
 }
 
 impl Default for ProviderUsage {
@@ -118,6 +124,7 @@ impl Default for ProviderUsage {
             error_count: 0,
             last_used_timestamp: 0,
             response_time_history: VecDeque::with_capacity(100),
+            latency_buckets: vec![0; LATENCY_BUCKETS_MS.len()],
         }
     }
 }
@@ -163,6 +170,13 @@ impl ProviderUsage {
             self.error_count += 1;
         }
 
+        // Count the request in the first bucket it fits in
+        if let Some(bucket) = LATENCY_BUCKETS_MS.iter().position(|bound| response_time_ms <= *bound)
+        {
+            self.latency_buckets.resize(LATENCY_BUCKETS_MS.len(), 0);
+            self.latency_buckets[bucket] += 1;
+        }
+
         // Keep only last 100 response times for histogram analysis
         self.response_time_history.push_back(response_time_ms);
         if self.response_time_history.len() > 100 {
@@ -211,6 +225,8 @@ pub struct MetricsCollector {
     pub cache_misses: AtomicU64,
     /// Total number of requests processed (all requests)
     pub total_requests: AtomicU64,
+    /// Number of requests served by an identical request already in flight upstream
+    pub deduplicated_requests: AtomicU64,
 
     // Provider metrics - protected by RwLock for complex operations
     /// Per-provider usage statistics and performance metrics
@@ -250,6 +266,7 @@ impl MetricsCollector {
             cache_hits: AtomicU64::new(0),
             cache_misses: AtomicU64::new(0),
             total_requests: AtomicU64::new(0),
+            deduplicated_requests: AtomicU64::new(0),
             provider_usage: Arc::new(RwLock::new(HashMap::new())),
             method_stats: Arc::new(RwLock::new(HashMap::new())),
             metrics_history: Arc::new(RwLock::new(VecDeque::with_capacity(1000))),
@@ -279,6 +296,22 @@ impl MetricsCollector {
         }
     }
 
+    /// Record a request served by an identical request already in flight upstream
+    pub fn record_deduplicated(&self, method: &str, response_time_ms: u64) {
+        self.deduplicated_requests.fetch_add(1, Ordering::Relaxed);
+
+        // Like cache hits, deduplicated requests do not go through forward_request
+        self.total_requests.fetch_add(1, Ordering::Relaxed);
+        self.record_request_timestamp();
+
+        if let Ok(mut stats) = self.method_stats.write() {
+            let method_stat = stats.entry(method.to_string()).or_default();
+            method_stat.total_requests += 1;
+            method_stat.total_response_time_ms += response_time_ms;
+            method_stat.update_avg_response_time();
+        }
+    }
+
     /// Record a cache miss for a specific method
     pub fn record_cache_miss(&self) {
         self.cache_misses.fetch_add(1, Ordering::Relaxed);
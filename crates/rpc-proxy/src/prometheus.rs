@@ -0,0 +1,235 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Prometheus metrics exposition
+//!
+//! Renders the proxy metrics in the Prometheus text format, served at `/metrics`.
+//! Providers are labelled by their origin (`scheme://host[:port]`) only, since the path
+//! and query of provider URLs often carry API keys; providers sharing an origin are
+//! reported together.
+
+use crate::ipc::is_ipc_path;
+use crate::metrics::{MetricsCollector, ProviderUsage, LATENCY_BUCKETS_MS};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Write as _};
+use std::sync::atomic::Ordering;
+
+/// Prefix of all the metric names
+const PREFIX: &str = "edb_rpc_proxy";
+
+/// Point-in-time values of the proxy, reported as gauges
+#[derive(Debug, Clone, Default)]
+pub struct ProxyGauges {
+    /// Number of entries in the cache
+    pub cache_entries: usize,
+    /// Total size of the cached responses in bytes
+    pub cache_bytes: u64,
+    /// Number of healthy providers
+    pub healthy_providers: usize,
+    /// Number of configured providers
+    pub total_providers: usize,
+    /// Number of EDB instances in the registry
+    pub registered_instances: usize,
+}
+
+/// Render the metrics in the Prometheus text exposition format
+pub fn render(metrics: &MetricsCollector, gauges: &ProxyGauges) -> String {
+    let mut out = Exposition::default();
+
+    out.header("requests_total", "counter", "Requests handled by the proxy");
+    out.sample("requests_total", &[], metrics.total_requests.load(Ordering::Relaxed));
+    out.header("cache_hits_total", "counter", "Requests served from the cache");
+    out.sample("cache_hits_total", &[], metrics.cache_hits.load(Ordering::Relaxed));
+    out.header("cache_misses_total", "counter", "Cacheable requests forwarded upstream");
+    out.sample("cache_misses_total", &[], metrics.cache_misses.load(Ordering::Relaxed));
+    out.header(
+        "deduplicated_requests_total",
+        "counter",
+        "Requests served by an identical request in flight",
+    );
+    out.sample(
+        "deduplicated_requests_total",
+        &[],
+        metrics.deduplicated_requests.load(Ordering::Relaxed),
+    );
+
+    out.header("errors_total", "counter", "Errors across all requests");
+    out.sample("errors_total", &[], metrics.total_errors.load(Ordering::Relaxed));
+    out.header("rate_limit_errors_total", "counter", "Rate limit errors from the providers");
+    out.sample("rate_limit_errors_total", &[], metrics.rate_limit_errors.load(Ordering::Relaxed));
+    out.header("user_errors_total", "counter", "Errors caused by invalid requests");
+    out.sample("user_errors_total", &[], metrics.user_errors.load(Ordering::Relaxed));
+
+    let method_stats: BTreeMap<_, _> = metrics.get_method_stats().into_iter().collect();
+    out.header("method_requests_total", "counter", "Requests by method");
+    for (method, stats) in &method_stats {
+        out.sample("method_requests_total", &[("method", method)], stats.total_requests);
+    }
+    out.header("method_cache_hits_total", "counter", "Requests served from the cache by method");
+    for (method, stats) in &method_stats {
+        out.sample("method_cache_hits_total", &[("method", method)], stats.hits);
+    }
+
+    let mut providers: BTreeMap<String, ProviderUsage> = BTreeMap::new();
+    for (url, usage) in metrics.get_provider_usage() {
+        merge_usage(providers.entry(provider_label(&url)).or_default(), &usage);
+    }
+
+    out.header("provider_requests_total", "counter", "Requests forwarded to each provider");
+    for (provider, usage) in &providers {
+        let labels = [("provider", provider.as_str()), ("outcome", "success")];
+        out.sample("provider_requests_total", &labels, usage.success_count);
+        let labels = [("provider", provider.as_str()), ("outcome", "error")];
+        out.sample("provider_requests_total", &labels, usage.error_count);
+    }
+
+    out.header("provider_latency_seconds", "histogram", "Response time of each provider");
+    for (provider, usage) in &providers {
+        let mut cumulative = 0;
+        for (i, bound) in LATENCY_BUCKETS_MS.iter().enumerate() {
+            cumulative += usage.latency_buckets.get(i).copied().unwrap_or_default();
+            let le = (*bound as f64 / 1000.0).to_string();
+            let labels = [("provider", provider.as_str()), ("le", le.as_str())];
+            out.sample("provider_latency_seconds_bucket", &labels, cumulative);
+        }
+        let labels = [("provider", provider.as_str()), ("le", "+Inf")];
+        out.sample("provider_latency_seconds_bucket", &labels, usage.request_count);
+        let labels = [("provider", provider.as_str())];
+        out.sample(
+            "provider_latency_seconds_sum",
+            &labels,
+            usage.total_response_time_ms as f64 / 1000.0,
+        );
+        out.sample("provider_latency_seconds_count", &labels, usage.request_count);
+    }
+
+    out.header("cache_entries", "gauge", "Entries in the cache");
+    out.sample("cache_entries", &[], gauges.cache_entries);
+    out.header("cache_bytes", "gauge", "Total size of the cached responses in bytes");
+    out.sample("cache_bytes", &[], gauges.cache_bytes);
+    out.header("providers", "gauge", "Configured providers by health");
+    out.sample("providers", &[("state", "healthy")], gauges.healthy_providers);
+    out.sample(
+        "providers",
+        &[("state", "unhealthy")],
+        gauges.total_providers.saturating_sub(gauges.healthy_providers),
+    );
+    out.header("registered_instances", "gauge", "EDB instances in the registry");
+    out.sample("registered_instances", &[], gauges.registered_instances);
+
+    out.text
+}
+
+/// Label of a provider: the origin of its URL, without the path and query, or `ipc`
+fn provider_label(url: &str) -> String {
+    if is_ipc_path(url) {
+        return "ipc".to_string();
+    }
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => parsed.origin().ascii_serialization(),
+        Err(_) => "invalid".to_string(),
+    }
+}
+
+/// Add the usage of a provider to the usage of its origin
+fn merge_usage(total: &mut ProviderUsage, usage: &ProviderUsage) {
+    total.request_count += usage.request_count;
+    total.success_count += usage.success_count;
+    total.error_count += usage.error_count;
+    total.total_response_time_ms += usage.total_response_time_ms;
+    total.latency_buckets.resize(LATENCY_BUCKETS_MS.len(), 0);
+    for (total, count) in total.latency_buckets.iter_mut().zip(&usage.latency_buckets) {
+        *total += count;
+    }
+}
+
+/// Builder of a Prometheus text exposition
+#[derive(Default)]
+struct Exposition {
+    text: String,
+}
+
+impl Exposition {
+    /// Write the help and type lines of a metric
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.text, "# HELP {PREFIX}_{name} {help}");
+        let _ = writeln!(self.text, "# TYPE {PREFIX}_{name} {kind}");
+    }
+
+    /// Write a sample of a metric
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        let _ = write!(self.text, "{PREFIX}_{name}");
+        if !labels.is_empty() {
+            let labels: Vec<_> = labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{}\"", escape_label(value)))
+                .collect();
+            let _ = write!(self.text, "{{{}}}", labels.join(","));
+        }
+        let _ = writeln!(self.text, " {value}");
+    }
+}
+
+/// Escape a label value
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_metrics() {
+        let metrics = MetricsCollector::new();
+        metrics.record_cache_hit("eth_getCode", 1);
+        metrics.record_cache_miss();
+        metrics.record_request("eth_getCode", "https://mainnet.infura.io/v3/SECRET", 40, true);
+        metrics.record_request("eth_getCode", "https://mainnet.infura.io/v3/OTHER", 300, false);
+
+        let gauges = ProxyGauges {
+            cache_entries: 1,
+            healthy_providers: 1,
+            total_providers: 2,
+            registered_instances: 3,
+            ..Default::default()
+        };
+        let text = render(&metrics, &gauges);
+
+        assert!(text.contains("# TYPE edb_rpc_proxy_cache_hits_total counter\n"));
+        assert!(text.contains("edb_rpc_proxy_cache_hits_total 1\n"));
+        assert!(text.contains("edb_rpc_proxy_method_requests_total{method=\"eth_getCode\"} 3\n"));
+        assert!(text.contains("edb_rpc_proxy_registered_instances 3\n"));
+        assert!(text.contains("edb_rpc_proxy_providers{state=\"unhealthy\"} 1\n"));
+
+        // API keys in the URLs are not exposed, providers are merged by origin
+        assert!(!text.contains("SECRET"));
+        let provider = "provider=\"https://mainnet.infura.io\"";
+        assert!(text.contains(&format!(
+            "edb_rpc_proxy_provider_latency_seconds_bucket{{{provider},le=\"0.05\"}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "edb_rpc_proxy_provider_latency_seconds_bucket{{{provider},le=\"+Inf\"}} 2\n"
+        )));
+        assert!(text
+            .contains(&format!("edb_rpc_proxy_provider_latency_seconds_sum{{{provider}}} 0.34\n")));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
@@ -15,11 +15,27 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 //! Multi-provider RPC management with health checking and load balancing
-
+//!
+//! Requests are routed to the provider with the lowest routing cost: its smoothed response
+//! time, inflated by its recent error rate. Providers answering with a rate-limit error are
+//! backed off for a while, with the backoff doubling on each consecutive rate limit.
+//!
+//! Providers running on this machine (see [`is_local_provider`]) are preferred over remote
+//! ones whatever their cost, until one answers that the state of a requested block is
+//! missing, i.e. it is not the archive node it was taken for.
+//!
+//! The depth of the state each provider serves is learned from the state queries at a
+//! numbered block it answers: the oldest block it served, and the newest block it answered
+//! was missing. A request failing on missing state is retried on the providers known to
+//! serve that block, and never again on those known to lack it.
+
+use crate::discovery::is_local_provider;
+use crate::ipc::{self, is_ipc_path};
+use crate::ratelimit::{RateLimitConfig, RateLimiter};
+use crate::ws::{is_ws_url, WsPool};
 use eyre::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
@@ -43,6 +59,46 @@ pub const DEFAULT_MAINNET_RPCS: &[&str] = &[
     "https://ethereum.rpc.subquery.network/public",
 ];
 
+/// Weight of the latest sample in the response time and error rate moving averages
+const EWMA_ALPHA: f64 = 0.3;
+
+/// Response time assumed for providers that have not answered yet
+const DEFAULT_RESPONSE_TIME_MS: u64 = 300;
+
+/// How much the routing cost of a provider failing every request exceeds its response time
+const ERROR_RATE_PENALTY: f64 = 4.0;
+
+/// Backoff after a first rate-limit response, doubled on each consecutive one
+const RATE_LIMIT_BASE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Longest backoff of a rate-limited provider
+const RATE_LIMIT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Error messages of nodes missing the state of the requested block, e.g. pruned nodes
+const MISSING_STATE_ERRORS: &[&str] = &[
+    "missing trie node",
+    "historical state",
+    "state is not available",
+    "state not available",
+    "pruned",
+];
+
+/// Whether a JSON-RPC error means the provider lacks the state of the requested block
+pub fn is_missing_state_error(error: &serde_json::Value) -> bool {
+    let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default().to_lowercase();
+    MISSING_STATE_ERRORS.iter().any(|pattern| message.contains(pattern))
+}
+
+/// State a request reads, to route it to a provider serving it
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateRequirement {
+    /// Block whose state is read, if known
+    pub block: Option<u64>,
+    /// Whether a provider already answered that the state is missing, in which case the
+    /// providers known to serve it are tried first
+    pub archive_needed: bool,
+}
+
 /// Information about an RPC provider
 #[derive(Debug, Clone)]
 pub struct ProviderInfo {
@@ -52,10 +108,91 @@ pub struct ProviderInfo {
     pub is_healthy: bool,
     /// When the provider was last health checked
     pub last_health_check: Option<Instant>,
-    /// Response time in milliseconds for the last successful request
+    /// Moving average of the response time in milliseconds of successful requests
     pub response_time_ms: Option<u64>,
     /// Number of consecutive failures (reset on success)
     pub consecutive_failures: u32,
+    /// Moving average of the share of failed requests, from 0 to 1
+    pub error_rate: f64,
+    /// Number of consecutive rate-limit responses (reset on success)
+    pub rate_limit_count: u32,
+    /// Until when the provider is backed off after a rate-limit response
+    pub rate_limited_until: Option<Instant>,
+    /// Whether the provider runs on this machine and is preferred over remote ones
+    pub local: bool,
+    /// Oldest block whose state the provider served
+    pub state_served_from: Option<u64>,
+    /// Newest block whose state the provider answered was missing
+    pub state_missing_until: Option<u64>,
+}
+
+impl ProviderInfo {
+    fn new(url: String) -> Self {
+        Self {
+            local: is_local_provider(&url),
+            url,
+            is_healthy: false,
+            last_health_check: None,
+            response_time_ms: None,
+            consecutive_failures: 0,
+            error_rate: 0.0,
+            rate_limit_count: 0,
+            rate_limited_until: None,
+            state_served_from: None,
+            state_missing_until: None,
+        }
+    }
+
+    /// Whether the provider is known to serve the state of a block
+    ///
+    /// # Returns
+    /// `Some(true)` if it served the state of that block or an older one, `Some(false)` if
+    /// it lacked the state of that block or a newer one, `None` if unknown
+    pub fn serves_state_at(&self, block: u64) -> Option<bool> {
+        if self.state_served_from.is_some_and(|from| from <= block) {
+            Some(true)
+        } else if self.state_missing_until.is_some_and(|until| until >= block) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the provider is known to be an archive node: it served historical state and
+    /// never answered that some was missing
+    pub fn is_known_archive(&self) -> bool {
+        self.state_served_from.is_some() && self.state_missing_until.is_none()
+    }
+
+    /// Rank of the provider for the state a request reads, the lower the better
+    fn state_rank(&self, state: StateRequirement) -> u8 {
+        let serves = match state.block {
+            Some(block) => self.serves_state_at(block),
+            None => self.is_known_archive().then_some(true),
+        };
+        match serves {
+            Some(false) => 2,
+            Some(true) if state.archive_needed => 0,
+            _ => 1,
+        }
+    }
+
+    /// Routing cost of the provider, the lower the better
+    pub fn routing_cost(&self) -> f64 {
+        let response_time = self.response_time_ms.unwrap_or(DEFAULT_RESPONSE_TIME_MS) as f64;
+        response_time * (1.0 + ERROR_RATE_PENALTY * self.error_rate)
+    }
+
+    /// Whether the provider is backed off after a rate-limit response
+    pub fn is_rate_limited(&self, now: Instant) -> bool {
+        self.rate_limited_until.is_some_and(|until| until > now)
+    }
+
+    /// Fold the outcome of a request into the error rate
+    fn record_outcome(&mut self, failed: bool) {
+        let sample = if failed { 1.0 } else { 0.0 };
+        self.error_rate = EWMA_ALPHA * sample + (1.0 - EWMA_ALPHA) * self.error_rate;
+    }
 }
 
 /// Serializable version of ProviderInfo for API responses
@@ -67,10 +204,25 @@ pub struct ProviderInfoResponse {
     pub is_healthy: bool,
     /// Seconds since the last health check
     pub last_health_check_seconds_ago: Option<u64>,
-    /// Response time in milliseconds for the last successful request
+    /// Moving average of the response time in milliseconds of successful requests
     pub response_time_ms: Option<u64>,
     /// Number of consecutive failures (reset on success)
     pub consecutive_failures: u32,
+    /// Moving average of the share of failed requests, from 0 to 1
+    #[serde(default)]
+    pub error_rate: f64,
+    /// Seconds left before a rate-limited provider is used again
+    #[serde(default)]
+    pub rate_limited_seconds_left: Option<u64>,
+    /// Whether the provider runs on this machine and is preferred over remote ones
+    #[serde(default)]
+    pub local: bool,
+    /// Oldest block whose state the provider served
+    #[serde(default)]
+    pub state_served_from: Option<u64>,
+    /// Newest block whose state the provider answered was missing
+    #[serde(default)]
+    pub state_missing_until: Option<u64>,
 }
 
 impl From<&ProviderInfo> for ProviderInfoResponse {
@@ -81,66 +233,51 @@ impl From<&ProviderInfo> for ProviderInfoResponse {
             last_health_check_seconds_ago: info.last_health_check.map(|t| t.elapsed().as_secs()),
             response_time_ms: info.response_time_ms,
             consecutive_failures: info.consecutive_failures,
+            error_rate: info.error_rate,
+            rate_limited_seconds_left: info
+                .rate_limited_until
+                .and_then(|until| until.checked_duration_since(Instant::now()))
+                .map(|left| left.as_secs() + 1),
+            local: info.local,
+            state_served_from: info.state_served_from,
+            state_missing_until: info.state_missing_until,
         }
     }
 }
 
-/// Multi-provider manager with health checking and round-robin load balancing
+/// Multi-provider manager with health checking and cost-based routing
 pub struct ProviderManager {
     /// List of all providers (healthy and unhealthy)
     providers: Arc<RwLock<Vec<ProviderInfo>>>,
-    /// Round-robin counter for load balancing
-    round_robin_counter: AtomicUsize,
     /// HTTP client for health checks
     client: reqwest::Client,
+    /// Connections to the WebSocket providers
+    ws_pool: Arc<WsPool>,
+    /// Requests-per-second budgets of the providers
+    rate_limiter: RateLimiter,
     /// Maximum consecutive failures before marking unhealthy
     max_failures: u32,
 }
 
-/// Calculate performance tier based on response time (100ms buckets)
-/// Lower tier numbers indicate better performance
-fn get_performance_tier(response_time_ms: u64) -> u8 {
-    match response_time_ms / 100 {
-        0..=1 => 1, // 0-199ms: Tier 1 (fastest)
-        2..=3 => 2, // 200-399ms: Tier 2
-        4..=5 => 3, // 400-599ms: Tier 3
-        _ => 4,     // 600ms+: Tier 4 (slowest)
-    }
-}
-
-/// Calculate weight for each performance tier
-/// Higher weight means more likely to be selected
-fn get_tier_weight(tier: u8) -> u32 {
-    match tier {
-        1 => 100, // Fast providers get 100% weight
-        2 => 60,  // Medium providers get 60% weight
-        3 => 30,  // Slow providers get 30% weight
-        4 => 10,  // Very slow providers get 10% weight
-        _ => 1,   // Fallback for unknown tiers
-    }
-}
-
 impl ProviderManager {
     /// Create a new provider manager with the given RPC URLs
+    ///
+    /// URLs may be HTTP(S) or WebSocket (`ws://`, `wss://`) endpoints.
     pub async fn new(rpc_urls: Vec<String>, max_failures: u32) -> Result<Self> {
         let client = reqwest::Client::builder().timeout(Duration::from_secs(5)).build()?;
+        let ws_pool = Arc::new(WsPool::default());
 
         // Initialize providers with parallel health checks
         let health_futures: Vec<_> = rpc_urls
             .iter()
             .map(|url| {
                 let client = client.clone();
+                let ws_pool = ws_pool.clone();
                 let url = url.clone();
                 async move {
-                    let mut provider = ProviderInfo {
-                        url: url.clone(),
-                        is_healthy: false,
-                        last_health_check: None,
-                        response_time_ms: None,
-                        consecutive_failures: 0,
-                    };
-
-                    match Self::check_provider_health(&client, &url).await {
+                    let mut provider = ProviderInfo::new(url.clone());
+
+                    match Self::check_provider_health(&client, &ws_pool, &url).await {
                         Ok(response_time) => {
                             provider.is_healthy = true;
                             provider.response_time_ms = Some(response_time);
@@ -170,14 +307,35 @@ impl ProviderManager {
 
         Ok(Self {
             providers: Arc::new(RwLock::new(providers)),
-            round_robin_counter: AtomicUsize::new(0),
             client,
+            ws_pool,
+            rate_limiter: RateLimiter::default(),
             max_failures,
         })
     }
 
+    /// Set the requests-per-second budgets of the providers
+    pub fn with_rate_limits(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limiter = RateLimiter::new(config);
+        self
+    }
+
+    /// Wait until a request can be sent to a provider within its budget
+    pub async fn acquire_rate_limit(&self, url: &str) {
+        self.rate_limiter.acquire(url).await;
+    }
+
+    /// Returns the connections to the WebSocket providers
+    pub fn ws_pool(&self) -> &Arc<WsPool> {
+        &self.ws_pool
+    }
+
     /// Check the health of a specific provider
-    async fn check_provider_health(client: &reqwest::Client, url: &str) -> Result<u64> {
+    async fn check_provider_health(
+        client: &reqwest::Client,
+        ws_pool: &WsPool,
+        url: &str,
+    ) -> Result<u64> {
         let start = Instant::now();
 
         // Simple eth_blockNumber request to check if provider is responsive
@@ -188,17 +346,24 @@ impl ProviderManager {
             "id": 1
         });
 
-        let response = client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await?;
+        let json: serde_json::Value = if is_ws_url(url) {
+            ws_pool.request(url, &request).await?
+        } else if is_ipc_path(url) {
+            ipc::request(url, &request).await?
+        } else {
+            client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .json(&request)
+                .send()
+                .await?
+                .json()
+                .await?
+        };
 
         let response_time = start.elapsed().as_millis() as u64;
 
         // Check if we got a valid response
-        let json: serde_json::Value = response.json().await?;
         if json.get("result").is_some() {
             Ok(response_time)
         } else {
@@ -206,133 +371,130 @@ impl ProviderManager {
         }
     }
 
-    /// Get a weighted random provider that hasn't been tried yet
-    /// Only considers healthy providers not in the exclusion set
-    pub async fn get_weighted_provider_excluding(
+    /// Get the provider with the lowest routing cost that hasn't been tried yet
+    ///
+    /// Only considers healthy providers not in the exclusion set, local providers first.
+    /// Rate-limited providers are skipped while backed off, unless all candidates are, in
+    /// which case the one whose backoff ends first is returned.
+    pub async fn select_provider_excluding(
+        &self,
+        tried_providers: &HashSet<String>,
+    ) -> Option<String> {
+        self.select_provider_for(tried_providers, StateRequirement::default()).await
+    }
+
+    /// Get the best provider that hasn't been tried yet for a request reading state
+    ///
+    /// Like [`Self::select_provider_excluding`], except that providers known to lack the
+    /// state are tried last, and providers known to serve it first once archive state is
+    /// needed.
+    pub async fn select_provider_for(
         &self,
         tried_providers: &HashSet<String>,
+        state: StateRequirement,
     ) -> Option<String> {
         let providers = self.providers.read().await;
+        let now = Instant::now();
         let available_providers: Vec<_> = providers
             .iter()
             .filter(|p| p.is_healthy && !tried_providers.contains(&p.url))
             .collect();
 
-        if available_providers.is_empty() {
-            return None;
-        }
-
-        // If only one available provider, return it
-        if available_providers.len() == 1 {
-            return Some(available_providers[0].url.clone());
+        if let Some(best) =
+            available_providers.iter().filter(|p| !p.is_rate_limited(now)).min_by(|a, b| {
+                a.state_rank(state)
+                    .cmp(&b.state_rank(state))
+                    .then(b.local.cmp(&a.local))
+                    .then(a.routing_cost().total_cmp(&b.routing_cost()))
+            })
+        {
+            return Some(best.url.clone());
         }
 
-        // Calculate weights for each available provider
-        let mut weighted_providers = Vec::new();
-        let mut total_weight = 0u32;
-
-        for provider in &available_providers {
-            // Use response time if available, otherwise assume medium performance
-            let response_time = provider.response_time_ms.unwrap_or(300); // Default to 300ms
-            let tier = get_performance_tier(response_time);
-            let weight = get_tier_weight(tier);
+        available_providers.iter().min_by_key(|p| p.rate_limited_until).map(|p| p.url.clone())
+    }
 
-            total_weight += weight;
-            weighted_providers.push((provider, weight));
-        }
+    /// Mark a provider as failed and update its health status
+    pub async fn mark_provider_failed(&self, url: &str) {
+        let mut providers = self.providers.write().await;
 
-        // Generate random number for weighted selection
-        let random_weight = rand::random_range(0..total_weight);
+        if let Some(provider) = providers.iter_mut().find(|p| p.url == url) {
+            provider.consecutive_failures += 1;
+            provider.record_outcome(true);
 
-        // Find the provider corresponding to the random weight
-        let mut current_weight = 0u32;
-        for (provider, weight) in weighted_providers {
-            current_weight += weight;
-            if random_weight < current_weight {
-                return Some(provider.url.clone());
+            if provider.consecutive_failures >= self.max_failures {
+                provider.is_healthy = false;
+                debug!("Provider {} marked as unhealthy after {} failures", url, self.max_failures);
             }
         }
-
-        // Fallback to first available provider (should not reach here)
-        Some(available_providers[0].url.clone())
     }
 
-    /// Get a weighted random provider based on response time
-    /// Only considers healthy providers
-    /// DEPRECATED: Use get_weighted_provider_excluding instead
-    #[allow(dead_code)]
-    async fn get_weighted_provider(&self) -> Option<String> {
-        let providers = self.providers.read().await;
-        let healthy_providers: Vec<_> = providers.iter().filter(|p| p.is_healthy).collect();
-
-        if healthy_providers.is_empty() {
-            return None;
-        }
-
-        // If only one healthy provider, return it
-        if healthy_providers.len() == 1 {
-            return Some(healthy_providers[0].url.clone());
-        }
-
-        // Calculate weights for each healthy provider
-        let mut weighted_providers = Vec::new();
-        let mut total_weight = 0u32;
-
-        for provider in &healthy_providers {
-            // Use response time if available, otherwise assume medium performance
-            let response_time = provider.response_time_ms.unwrap_or(300); // Default to 300ms
-            let tier = get_performance_tier(response_time);
-            let weight = get_tier_weight(tier);
+    /// Mark a provider as rate limited and back it off
+    ///
+    /// Rate limits do not count as failures, so the provider stays healthy and is used
+    /// again once its backoff ends.
+    pub async fn mark_provider_rate_limited(&self, url: &str) {
+        let mut providers = self.providers.write().await;
 
-            total_weight += weight;
-            weighted_providers.push((provider, weight));
-        }
+        if let Some(provider) = providers.iter_mut().find(|p| p.url == url) {
+            provider.rate_limit_count += 1;
+            provider.record_outcome(true);
 
-        // Generate random number for weighted selection
-        let random_weight = rand::random_range(0..total_weight);
+            let backoff = RATE_LIMIT_BASE_BACKOFF
+                .saturating_mul(1 << (provider.rate_limit_count - 1).min(16))
+                .min(RATE_LIMIT_MAX_BACKOFF);
+            provider.rate_limited_until = Some(Instant::now() + backoff);
 
-        // Find the provider corresponding to the random weight
-        let mut current_weight = 0u32;
-        for (provider, weight) in weighted_providers {
-            current_weight += weight;
-            if random_weight < current_weight {
-                return Some(provider.url.clone());
-            }
+            debug!("Provider {} rate limited, backing off for {:?}", url, backoff);
         }
-
-        // Fallback to first healthy provider (should not reach here)
-        Some(healthy_providers[0].url.clone())
     }
 
-    /// Get the next healthy provider using round-robin
-    /// DEPRECATED: Use get_weighted_provider_excluding instead
-    #[allow(dead_code)]
-    pub async fn get_next_provider(&self) -> Option<String> {
-        let providers = self.providers.read().await;
-        let healthy_providers: Vec<_> = providers.iter().filter(|p| p.is_healthy).collect();
+    /// Stop preferring a local provider, e.g. after it answered that the state of a
+    /// requested block is missing
+    ///
+    /// The provider is then routed to like a remote one, by cost.
+    pub async fn demote_local_provider(&self, url: &str) {
+        let mut providers = self.providers.write().await;
 
-        if healthy_providers.is_empty() {
-            return None;
+        if let Some(provider) = providers.iter_mut().find(|p| p.url == url && p.local) {
+            provider.local = false;
+            warn!("Local provider {} is missing historical state, no longer preferred", url);
         }
-
-        // Round-robin selection
-        let index =
-            self.round_robin_counter.fetch_add(1, Ordering::Relaxed) % healthy_providers.len();
-        Some(healthy_providers[index].url.clone())
     }
 
-    /// Mark a provider as failed and update its health status
-    pub async fn mark_provider_failed(&self, url: &str) {
+    /// Record that a provider served the state of a block
+    pub async fn mark_state_served(&self, url: &str, block: u64) {
         let mut providers = self.providers.write().await;
 
         if let Some(provider) = providers.iter_mut().find(|p| p.url == url) {
-            provider.consecutive_failures += 1;
+            provider.state_served_from =
+                Some(provider.state_served_from.map_or(block, |from| from.min(block)));
+            // The node has the state after all, e.g. it finished syncing
+            if provider.state_missing_until.is_some_and(|until| until >= block) {
+                provider.state_missing_until = None;
+            }
+        }
+    }
 
-            if provider.consecutive_failures >= self.max_failures {
-                provider.is_healthy = false;
-                debug!("Provider {} marked as unhealthy after {} failures", url, self.max_failures);
+    /// Record that a provider answered that the state of a block is missing
+    ///
+    /// A local provider is demoted as well, see [`Self::demote_local_provider`].
+    pub async fn mark_state_missing(&self, url: &str, block: Option<u64>) {
+        if let Some(block) = block {
+            let mut providers = self.providers.write().await;
+
+            if let Some(provider) = providers.iter_mut().find(|p| p.url == url) {
+                provider.state_missing_until =
+                    Some(provider.state_missing_until.map_or(block, |until| until.max(block)));
+                // The node pruned the state it served before
+                if provider.state_served_from.is_some_and(|from| from <= block) {
+                    provider.state_served_from = None;
+                }
+                debug!("Provider {} is missing the state of block {}", url, block);
             }
         }
+
+        self.demote_local_provider(url).await;
     }
 
     /// Mark a provider as successful and reset failure count
@@ -341,8 +503,16 @@ impl ProviderManager {
 
         if let Some(provider) = providers.iter_mut().find(|p| p.url == url) {
             provider.consecutive_failures = 0;
+            provider.rate_limit_count = 0;
+            provider.rate_limited_until = None;
             provider.is_healthy = true;
-            provider.response_time_ms = Some(response_time_ms);
+            provider.record_outcome(false);
+            provider.response_time_ms = Some(match provider.response_time_ms {
+                Some(average) => (EWMA_ALPHA * response_time_ms as f64
+                    + (1.0 - EWMA_ALPHA) * average as f64)
+                    .round() as u64,
+                None => response_time_ms,
+            });
             provider.last_health_check = Some(Instant::now());
 
             debug!("Provider {} successful ({}ms)", url, response_time_ms);
@@ -367,11 +537,12 @@ impl ProviderManager {
 
                 if needs_check {
                     let client = self.client.clone();
+                    let ws_pool = self.ws_pool.clone();
                     let url = provider.url.clone();
                     let was_healthy = provider.is_healthy;
 
                     Some(async move {
-                        match Self::check_provider_health(&client, &url).await {
+                        match Self::check_provider_health(&client, &ws_pool, &url).await {
                             Ok(response_time) => {
                                 if !was_healthy {
                                     debug!("Provider {} is now healthy", url);
@@ -485,16 +656,15 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_round_robin_selection() {
-        if skip_if_loopback_binds_restricted("test_round_robin_selection").await {
+    async fn test_rate_limited_provider_backoff() {
+        if skip_if_loopback_binds_restricted("test_rate_limited_provider_backoff").await {
             return;
         }
         edb_common::logging::ensure_test_logging(None);
-        info!("Testing round-robin provider selection");
+        info!("Testing backoff of rate-limited providers");
 
-        // Start 3 healthy mock servers
-        let mocks =
-            vec![MockServer::start().await, MockServer::start().await, MockServer::start().await];
+        // Start 2 healthy mock servers
+        let mocks = vec![MockServer::start().await, MockServer::start().await];
 
         for mock in &mocks {
             Mock::given(method("POST"))
@@ -510,17 +680,27 @@ mod tests {
 
         let urls: Vec<String> = mocks.iter().map(|m| m.uri()).collect();
         let manager = ProviderManager::new(urls.clone(), 3).await.unwrap();
+        manager.mark_provider_success(&urls[0], 50).await;
+        manager.mark_provider_success(&urls[1], 400).await;
+        let no_exclusions = HashSet::new();
+        assert_eq!(manager.select_provider_excluding(&no_exclusions).await, Some(urls[0].clone()));
 
-        // Get providers multiple times and verify round-robin
-        let mut selections = Vec::new();
-        for _ in 0..9 {
-            selections.push(manager.get_next_provider().await.unwrap());
-        }
+        // The fast provider is skipped while backed off, but stays healthy
+        manager.mark_provider_rate_limited(&urls[0]).await;
+        manager.mark_provider_rate_limited(&urls[0]).await;
+        assert_eq!(manager.select_provider_excluding(&no_exclusions).await, Some(urls[1].clone()));
+        assert_eq!(manager.healthy_provider_count().await, 2);
 
-        // Each provider should be selected 3 times
-        for url in &urls {
-            assert_eq!(selections.iter().filter(|s| *s == url).count(), 3);
-        }
+        let providers = manager.get_providers_info().await;
+        assert_eq!(providers[0].rate_limited_seconds_left, Some(2));
+
+        // When every candidate is backed off, the one available soonest is used
+        let slow_only = HashSet::from([urls[1].clone()]);
+        assert_eq!(manager.select_provider_excluding(&slow_only).await, Some(urls[0].clone()));
+
+        // A successful request lifts the backoff
+        manager.mark_provider_success(&urls[0], 50).await;
+        assert_eq!(manager.select_provider_excluding(&no_exclusions).await, Some(urls[0].clone()));
     }
 
     #[tokio::test]
@@ -557,12 +737,12 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_weighted_provider_selection() {
-        if skip_if_loopback_binds_restricted("test_weighted_provider_selection").await {
+    async fn test_cost_based_selection() {
+        if skip_if_loopback_binds_restricted("test_cost_based_selection").await {
             return;
         }
         edb_common::logging::ensure_test_logging(None);
-        debug!("Testing weighted provider selection based on response time");
+        debug!("Testing provider selection based on response time and error rate");
 
         // Start 3 healthy mock servers with different response times
         let mocks =
@@ -581,38 +761,116 @@ mod tests {
         }
 
         let urls: Vec<String> = mocks.iter().map(|m| m.uri()).collect();
-        let manager = ProviderManager::new(urls.clone(), 3).await.unwrap();
+        let manager = ProviderManager::new(urls.clone(), 10).await.unwrap();
 
-        // Simulate different response times for providers
-        manager.mark_provider_success(&urls[0], 50).await; // Fast: Tier 1 (100 weight)
-        manager.mark_provider_success(&urls[1], 250).await; // Medium: Tier 2 (60 weight)
-        manager.mark_provider_success(&urls[2], 500).await; // Slow: Tier 3 (30 weight)
+        // Simulate different response times for providers, smoothed over several requests
+        for _ in 0..20 {
+            manager.mark_provider_success(&urls[0], 100).await;
+            manager.mark_provider_success(&urls[1], 250).await;
+            manager.mark_provider_success(&urls[2], 500).await;
+        }
 
-        // Test weighted selection multiple times
-        let mut selections = std::collections::HashMap::new();
-        for _ in 0..100 {
-            if let Some(provider) = manager.get_weighted_provider().await {
-                *selections.entry(provider).or_insert(0) += 1;
-            }
+        // The fastest provider is preferred, then the next fastest untried one
+        let mut tried = HashSet::new();
+        assert_eq!(manager.select_provider_excluding(&tried).await, Some(urls[0].clone()));
+        tried.insert(urls[0].clone());
+        assert_eq!(manager.select_provider_excluding(&tried).await, Some(urls[1].clone()));
+
+        // Failing requests make the fast provider cost more than the medium one
+        for _ in 0..5 {
+            manager.mark_provider_failed(&urls[0]).await;
+        }
+        let providers = manager.get_providers_info().await;
+        debug!("Error rate of the fast provider: {}", providers[0].error_rate);
+        assert!(providers[0].error_rate > 0.5);
+        assert_eq!(manager.select_provider_excluding(&HashSet::new()).await, Some(urls[1].clone()));
+    }
+
+    #[tokio::test]
+    async fn test_local_provider_preference() {
+        if skip_if_loopback_binds_restricted("test_local_provider_preference").await {
+            return;
+        }
+
+        let mocks = vec![MockServer::start().await, MockServer::start().await];
+        for mock in &mocks {
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": "0x1"
+                })))
+                .mount(mock)
+                .await;
         }
 
-        // Verify all providers were selected
-        assert_eq!(selections.len(), 3);
+        // Mock servers listen on loopback, so both start as local providers
+        let urls: Vec<String> = mocks.iter().map(|m| m.uri()).collect();
+        let manager = ProviderManager::new(urls.clone(), 10).await.unwrap();
+        assert!(manager.get_providers_info().await.iter().all(|p| p.local));
+        for _ in 0..20 {
+            manager.mark_provider_success(&urls[0], 50).await;
+            manager.mark_provider_success(&urls[1], 500).await;
+        }
+        assert_eq!(manager.select_provider_excluding(&HashSet::new()).await, Some(urls[0].clone()));
+
+        // A demoted provider loses to the remaining local one, however fast it is
+        let error = serde_json::json!({"code": -32000, "message": "missing trie node abc"});
+        assert!(is_missing_state_error(&error));
+        assert!(!is_missing_state_error(&serde_json::json!({"message": "execution reverted"})));
+        manager.demote_local_provider(&urls[0]).await;
+        assert!(!manager.get_providers_info().await[0].local);
+        assert_eq!(manager.select_provider_excluding(&HashSet::new()).await, Some(urls[1].clone()));
+    }
 
-        // Fast provider should be selected most often due to higher weight
-        let fast_count = selections.get(&urls[0]).unwrap_or(&0);
-        let medium_count = selections.get(&urls[1]).unwrap_or(&0);
-        let slow_count = selections.get(&urls[2]).unwrap_or(&0);
+    #[tokio::test]
+    async fn test_archive_depth_routing() {
+        if skip_if_loopback_binds_restricted("test_archive_depth_routing").await {
+            return;
+        }
 
-        debug!(
-            "Selection counts - Fast: {}, Medium: {}, Slow: {}",
-            fast_count, medium_count, slow_count
-        );
+        let mocks = vec![MockServer::start().await, MockServer::start().await];
+        for mock in &mocks {
+            Mock::given(method("POST"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": "0x1"
+                })))
+                .mount(mock)
+                .await;
+        }
 
-        // Fast provider should have more selections than slow provider
-        assert!(
-            fast_count > slow_count,
-            "Fast provider should be selected more often than slow provider"
-        );
+        // The first provider is faster but pruned, the second one is an archive node
+        let urls: Vec<String> = mocks.iter().map(|m| m.uri()).collect();
+        let manager = ProviderManager::new(urls.clone(), 10).await.unwrap();
+        for _ in 0..20 {
+            manager.mark_provider_success(&urls[0], 50).await;
+            manager.mark_provider_success(&urls[1], 500).await;
+        }
+        manager.mark_state_served(&urls[0], 20_000_000).await;
+        manager.mark_state_served(&urls[1], 1_000).await;
+        manager.mark_state_missing(&urls[0], Some(1_000)).await;
+
+        let info = manager.get_providers_info().await;
+        assert_eq!(info[0].state_served_from, Some(20_000_000));
+        assert_eq!(info[0].state_missing_until, Some(1_000));
+        assert_eq!(info[1].state_served_from, Some(1_000));
+
+        // Recent state goes to the fastest provider, old state to the one serving it
+        let tried = HashSet::new();
+        let recent = StateRequirement { block: Some(20_000_001), archive_needed: false };
+        assert_eq!(manager.select_provider_for(&tried, recent).await, Some(urls[0].clone()));
+        let old = StateRequirement { block: Some(500), archive_needed: false };
+        assert_eq!(manager.select_provider_for(&tried, old).await, Some(urls[1].clone()));
+
+        // Once state was missing, known archive nodes go first even for unknown blocks
+        let unknown = StateRequirement { block: None, archive_needed: true };
+        assert_eq!(manager.select_provider_for(&tried, unknown).await, Some(urls[1].clone()));
+        assert_eq!(manager.select_provider_excluding(&tried).await, Some(urls[0].clone()));
+
+        // Providers known to lack the state are still used when nothing else is left
+        let tried = HashSet::from([urls[1].clone()]);
+        assert_eq!(manager.select_provider_for(&tried, old).await, Some(urls[0].clone()));
     }
 }
@@ -17,54 +17,100 @@
 //! Core proxy server implementation
 
 use crate::{
-    cache::CacheManager,
+    auth::{self, TokenAuth},
+    cache::{CacheManager, CachePolicyConfig, EvictionPolicy},
+    discovery::{discover_local_node, is_local_provider},
+    export::ExportFilter,
     health::HealthService,
+    journal::{JournalEntry, RequestJournal},
     metrics::MetricsCollector,
+    prometheus::{self, ProxyGauges},
     providers::{ProviderManager, DEFAULT_MAINNET_RPCS},
+    ratelimit::RateLimitConfig,
     registry::EdbRegistry,
     rpc::RpcHandler,
+    session::{Session, SessionMode},
+    shared::{SharedCache, SharedCacheConfig},
 };
 use axum::{
-    extract::State,
-    http::{Method, StatusCode},
-    response::Json,
-    routing::post,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, Method, StatusCode},
+    response::{IntoResponse, Json},
+    routing::{get, post},
     Router,
 };
-use eyre::Result;
+use eyre::{bail, Result};
 use serde_json::Value;
-use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+};
 use tokio::{net::TcpListener, sync::broadcast};
 use tower_http::cors::{Any, CorsLayer};
 use tracing::{debug, info, warn};
 
+/// Header selecting the chain a request is proxied to
+pub const CHAIN_HEADER: &str = "x-edb-chain";
+
+/// Number of cache entries listed by `edb_cache_entries` when no limit is given
+const DEFAULT_CACHE_ENTRIES_LIMIT: usize = 100;
+
+/// Maximum number of cache entries listed by `edb_cache_entries`
+const MAX_CACHE_ENTRIES_LIMIT: usize = 1000;
+
 /// Builder for configuring ProxyServer with fluent API and sensible defaults
 #[derive(Debug, Clone)]
 pub struct ProxyServerBuilder {
     rpc_urls: Option<Vec<String>>,
+    chain_rpc_urls: BTreeMap<u64, Vec<String>>,
     max_cache_items: u32,
     cache_dir: Option<PathBuf>,
+    cache_policies: CachePolicyConfig,
+    shared_cache: Option<SharedCacheConfig>,
     grace_period: u64,
     heartbeat_interval: u64,
     max_failures: u32,
     health_check_interval: u64,
     cache_save_interval: u64,
+    rate_limits: RateLimitConfig,
+    session: Option<(SessionMode, PathBuf)>,
+    local_discovery: bool,
+    auth_tokens: Option<PathBuf>,
+    token_quota: Option<u64>,
+    request_log: Option<PathBuf>,
 }
 
 impl Default for ProxyServerBuilder {
     fn default() -> Self {
         Self {
             // General Configuration
-            rpc_urls: None, // Will use DEFAULT_MAINNET_RPCS
+            rpc_urls: None,                  // Will use DEFAULT_MAINNET_RPCS
+            chain_rpc_urls: BTreeMap::new(), // Only the chain of rpc_urls is served
 
             // Cache Configuration
             max_cache_items: 1024000,
             cache_dir: None,        // Will use ~/.edb/cache/rpc/<chain_id>
             cache_save_interval: 5, // 5 minutes
+            cache_policies: CachePolicyConfig::default(), // LRU without size limit
+            shared_cache: None,     // Not shared with other instances
 
             // Provider Health Check Configuration
             max_failures: 3,
             health_check_interval: 60,
+            local_discovery: false, // Only the configured upstreams are used
+
+            // Rate Limit Configuration
+            rate_limits: RateLimitConfig::default(), // Unlimited by default
+
+            // Session Configuration
+            session: None,     // Neither recorded nor replayed
+            request_log: None, // Requests are not logged
+
+            // Authentication Configuration
+            auth_tokens: None, // Requests are not authenticated
+            token_quota: None, // Unlimited upstream requests per token
 
             // EDB Register Configuration
             grace_period: 0, // No auto-shutdown by default
@@ -93,6 +139,27 @@ impl ProxyServerBuilder {
         self
     }
 
+    /// Add the upstream RPC URLs of another chain, from a comma-separated string
+    ///
+    /// Requests are routed to a chain by path prefix (`/<chain_id>`) or by the
+    /// `X-EDB-Chain` header, and each chain has its own cache.
+    pub fn chain_rpc_urls_str(mut self, chain_id: u64, urls: &str) -> Self {
+        self.chain_rpc_urls.insert(
+            chain_id,
+            urls.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect(),
+        );
+        self
+    }
+
+    /// Probe for an archive node running on this machine and prefer it over the upstreams
+    ///
+    /// The IPC sockets of geth and reth and `http://127.0.0.1:8545` are probed on build,
+    /// unless the RPC URLs already include a local provider.
+    pub fn discover_local_node(mut self, enabled: bool) -> Self {
+        self.local_discovery = enabled;
+        self
+    }
+
     /// Set maximum number of cached items
     pub fn max_cache_items(mut self, max_items: u32) -> Self {
         self.max_cache_items = max_items;
@@ -105,6 +172,38 @@ impl ProxyServerBuilder {
         self
     }
 
+    /// Set the default cache eviction policy
+    pub fn cache_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.cache_policies.default_policy = policy;
+        self
+    }
+
+    /// Set the cache eviction policy of a specific method, overriding the default policy
+    pub fn cache_method_policy(
+        mut self,
+        method: impl Into<String>,
+        policy: EvictionPolicy,
+    ) -> Self {
+        self.cache_policies.method_policies.insert(method.into(), policy);
+        self
+    }
+
+    /// Set the maximum total size of the cached responses in bytes (0 = unlimited)
+    pub fn max_cache_bytes(mut self, max_bytes: u64) -> Self {
+        self.cache_policies.max_bytes = (max_bytes > 0).then_some(max_bytes);
+        self
+    }
+
+    /// Share the cache with other proxy instances through a Redis or HTTP object store
+    ///
+    /// # Arguments
+    /// * `url` - URL of the store, `redis://` or `http(s)://`
+    /// * `token` - Bearer token sent to HTTP stores
+    pub fn shared_cache(mut self, url: impl Into<String>, token: Option<String>) -> Self {
+        self.shared_cache = Some(SharedCacheConfig { url: url.into(), token });
+        self
+    }
+
     /// Set grace period in seconds before shutdown when no EDB instances (0 = no auto-shutdown)
     pub fn grace_period(mut self, seconds: u64) -> Self {
         self.grace_period = seconds;
@@ -135,26 +234,141 @@ impl ProxyServerBuilder {
         self
     }
 
+    /// Set the requests per second sent to each provider (0 = unlimited)
+    pub fn rate_limit(mut self, rps: f64) -> Self {
+        self.rate_limits.default_rps = Some(rps);
+        self
+    }
+
+    /// Set the requests a provider can receive at once before rate limiting shapes them
+    pub fn rate_limit_burst(mut self, burst: u32) -> Self {
+        self.rate_limits.burst = Some(burst);
+        self
+    }
+
+    /// Set the requests per second sent to a specific provider, overriding the rate limit
+    pub fn provider_rate_limit(mut self, url: impl Into<String>, rps: f64) -> Self {
+        self.rate_limits.per_provider.insert(url.into(), rps);
+        self
+    }
+
+    /// Record every response served into a session bundle, written on shutdown
+    pub fn record_session<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.session = Some((SessionMode::Record, path.into()));
+        self
+    }
+
+    /// Serve exclusively from a recorded session bundle, without contacting any provider
+    pub fn replay_session<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.session = Some((SessionMode::Replay, path.into()));
+        self
+    }
+
+    /// Log every proxied request, with its response and timing, to a journal file
+    pub fn request_log<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.request_log = Some(path.into());
+        self
+    }
+
+    /// Require requests to carry one of the tokens of a file of `NAME=TOKEN [ROLE]` lines
+    ///
+    /// Tokens are presented as an `Authorization: Bearer` header or a `token` query
+    /// parameter, and the usage of each token is reported by `edb_token_usage`. Only
+    /// `admin` tokens can call the methods managing the proxy ([`auth::ADMIN_METHODS`]).
+    pub fn auth_tokens_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.auth_tokens = Some(path.into());
+        self
+    }
+
+    /// Set the upstream requests allowed to each auth token per hour (0 = unlimited)
+    pub fn token_quota(mut self, requests_per_hour: u64) -> Self {
+        self.token_quota = (requests_per_hour > 0).then_some(requests_per_hour);
+        self
+    }
+
     /// Build the ProxyServer with the configured settings
     pub async fn build(self) -> Result<ProxyServer> {
+        if self.session.is_some() && !self.chain_rpc_urls.is_empty() {
+            bail!("Sessions can only be recorded or replayed on a single chain");
+        }
+
+        // A replayed session is served without any upstream, on the chain it was recorded on
+        let replay = match &self.session {
+            Some((SessionMode::Replay, path)) => Some(Session::replay(path.clone())?),
+            _ => None,
+        };
+
         // Resolve RPC URLs
-        let rpc_urls = self
-            .rpc_urls
-            .unwrap_or_else(|| DEFAULT_MAINNET_RPCS.iter().map(|s| s.to_string()).collect());
+        let mut rpc_urls = match replay {
+            Some(_) => Vec::new(),
+            None => self
+                .rpc_urls
+                .unwrap_or_else(|| DEFAULT_MAINNET_RPCS.iter().map(|s| s.to_string()).collect()),
+        };
 
-        // Resolve cache path
-        let cache_path = CacheManager::get_cache_path(&rpc_urls, self.cache_dir).await?;
+        // Resolve chain and cache path
+        let chain_id = match &replay {
+            Some(session) => session.chain_id(),
+            None => CacheManager::resolve_chain_id(&rpc_urls).await?,
+        };
+
+        // Prefer an archive node running on this machine, unless one is configured already
+        if self.local_discovery
+            && replay.is_none()
+            && !rpc_urls.iter().any(|url| is_local_provider(url))
+        {
+            if let Some(url) = discover_local_node(chain_id).await {
+                rpc_urls.insert(0, url);
+            }
+        }
+        let session = match self.session {
+            Some((SessionMode::Record, path)) => Some(Session::record(path, chain_id)),
+            _ => replay,
+        };
+        let cache_path = CacheManager::chain_cache_path(chain_id, self.cache_dir.clone())?;
+        let auth = self
+            .auth_tokens
+            .map(|path| TokenAuth::load(&path, self.token_quota))
+            .transpose()?
+            .map(Arc::new);
+        let journal =
+            self.request_log.map(|path| RequestJournal::open(&path)).transpose()?.map(Arc::new);
+
+        // Resolve the upstreams of the other chains, checking they serve the configured chain
+        let mut chains = Vec::new();
+        for (other_chain_id, urls) in self.chain_rpc_urls {
+            if other_chain_id == chain_id {
+                bail!("Chain {other_chain_id} is already served by the default RPC URLs");
+            }
+            let served_chain_id = CacheManager::resolve_chain_id(&urls).await?;
+            if served_chain_id != other_chain_id {
+                bail!(
+                    "RPC URLs configured for chain {other_chain_id} serve chain {served_chain_id}"
+                );
+            }
+            let cache_path =
+                CacheManager::chain_cache_path(other_chain_id, self.cache_dir.clone())?;
+            chains.push((other_chain_id, urls, cache_path));
+        }
 
         // Now call the simplified ProxyServer::new with deterministic values
         ProxyServer::new(
+            chain_id,
             rpc_urls,
+            chains,
             self.max_cache_items,
             cache_path,
+            self.cache_policies,
+            self.shared_cache,
             self.grace_period,
             self.heartbeat_interval,
             self.max_failures,
             self.health_check_interval,
             self.cache_save_interval,
+            self.rate_limits,
+            session.map(Arc::new),
+            auth,
+            journal,
         )
         .await
     }
@@ -181,14 +395,22 @@ impl ProxyServerBuilder {
 /// ```
 #[derive(Clone)]
 pub struct ProxyServer {
-    /// RPC request handler with caching capabilities
+    /// RPC request handler with caching capabilities, for the default chain
     pub rpc_handler: Arc<RpcHandler>,
+    /// Chain served by the default RPC URLs
+    pub chain_id: u64,
+    /// RPC request handlers of all the chains served, by chain ID
+    pub chain_handlers: Arc<HashMap<u64, Arc<RpcHandler>>>,
     /// Registry for tracking connected EDB instances
     pub registry: Arc<EdbRegistry>,
     /// Health check service for monitoring
     pub health_service: Arc<HealthService>,
     /// Metrics collector for performance tracking
     pub metrics_collector: Arc<MetricsCollector>,
+    /// Tokens requests must carry, if authentication is required
+    auth: Option<Arc<TokenAuth>>,
+    /// Journal the proxied requests are logged to, if any
+    journal: Option<Arc<RequestJournal>>,
     /// Shutdown signal sender
     shutdown_tx: broadcast::Sender<()>,
 }
@@ -205,45 +427,82 @@ impl ProxyServer {
     /// Use ProxyServerBuilder for a more convenient fluent API.
     ///
     /// # Arguments
+    /// * `chain_id` - Chain served by the default RPC URLs
     /// * `rpc_urls` - List of upstream RPC endpoint URLs
+    /// * `chains` - Chain ID, upstream URLs, and cache path of each other chain served
     /// * `max_cache_items` - Maximum number of items to cache
     /// * `cache_path` - Resolved path for cache persistence
+    /// * `cache_policies` - Eviction policies and size limit of the cache
+    /// * `shared_cache` - Store shared with other proxy instances, if any
     /// * `grace_period` - Seconds to wait before shutdown when no EDB instances
     /// * `heartbeat_interval` - Seconds between heartbeat checks
     /// * `max_failures` - Maximum consecutive failures before marking provider unhealthy
     /// * `health_check_interval` - Seconds between provider health checks
     /// * `cache_save_interval` - Minutes between periodic cache saves
+    /// * `rate_limits` - Requests-per-second budgets of the providers
+    /// * `session` - Session recorded or replayed on the default chain, if any
+    /// * `auth` - Tokens requests must carry, if authentication is required
+    /// * `journal` - Journal the proxied requests are logged to, if any
     ///
     /// # Returns
     /// A new ProxyServer instance with background tasks started
     #[allow(clippy::too_many_arguments)]
     async fn new(
+        chain_id: u64,
         rpc_urls: Vec<String>,
+        chains: Vec<(u64, Vec<String>, PathBuf)>,
         max_cache_items: u32,
         cache_path: PathBuf,
+        cache_policies: CachePolicyConfig,
+        shared_cache: Option<SharedCacheConfig>,
         grace_period: u64,
         heartbeat_interval: u64,
         max_failures: u32,
         health_check_interval: u64,
         cache_save_interval: u64,
+        rate_limits: RateLimitConfig,
+        session: Option<Arc<Session>>,
+        auth: Option<Arc<TokenAuth>>,
+        journal: Option<Arc<RequestJournal>>,
     ) -> Result<Self> {
-        info!("Starting EDB RPC Proxy with {} providers", rpc_urls.len());
-        for url in &rpc_urls {
-            info!("  - {}", url);
-        }
-
-        let cache_manager = Arc::new(CacheManager::new(max_cache_items, cache_path)?);
         let metrics_collector = Arc::new(MetricsCollector::new());
+        let mut chain_handlers = HashMap::new();
 
-        // Create provider manager with all URLs
-        let provider_manager = Arc::new(ProviderManager::new(rpc_urls, max_failures).await?);
+        // Start the default chain, then the others with the same settings and their own cache
+        for (served_chain_id, rpc_urls, cache_path) in
+            std::iter::once((chain_id, rpc_urls, cache_path)).chain(chains)
+        {
+            info!(
+                "Starting EDB RPC Proxy for chain {} with {} providers",
+                served_chain_id,
+                rpc_urls.len()
+            );
+            for url in &rpc_urls {
+                info!("  - {}", url);
+            }
+
+            let mut cache_manager =
+                CacheManager::with_policies(max_cache_items, cache_path, cache_policies.clone())?;
+            if let Some(shared_cache) = &shared_cache {
+                cache_manager = cache_manager
+                    .with_shared_cache(SharedCache::new(shared_cache, served_chain_id)?);
+            }
+            let cache_manager = Arc::new(cache_manager);
+            let handler = Self::start_chain_handler(
+                rpc_urls,
+                cache_manager,
+                metrics_collector.clone(),
+                max_failures,
+                rate_limits.clone(),
+                health_check_interval,
+                cache_save_interval,
+                session.clone().filter(|_| served_chain_id == chain_id),
+            )
+            .await?;
+            chain_handlers.insert(served_chain_id, handler);
+        }
+        let rpc_handler = chain_handlers[&chain_id].clone();
 
-        // Create RPC handler with provider manager
-        let rpc_handler = Arc::new(RpcHandler::new(
-            provider_manager.clone(),
-            cache_manager.clone(),
-            metrics_collector.clone(),
-        )?);
         let health_service = Arc::new(HealthService::new());
         let (shutdown_tx, _) = broadcast::channel(1);
 
@@ -259,6 +518,77 @@ impl ProxyServer {
             });
         }
 
+        // Start background metrics collection task
+        let metrics_collector_clone = metrics_collector.clone();
+        let cache_manager_clone = rpc_handler.cache_manager().clone();
+        let provider_manager_clone = rpc_handler.provider_manager().clone();
+        let registry_clone = registry.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+            loop {
+                interval.tick().await;
+
+                // Collect current metrics for historical tracking
+                let cache_stats = cache_manager_clone.detailed_stats().await;
+                let providers_info = provider_manager_clone.get_providers_info().await;
+                let healthy_providers =
+                    providers_info.iter().filter(|p| p.is_healthy).count() as u64;
+                let total_providers = providers_info.len() as u64;
+                let active_instances = registry_clone.get_active_instances().await.len();
+
+                let total_entries =
+                    cache_stats.get("total_entries").and_then(|v| v.as_u64()).unwrap_or(0);
+                metrics_collector_clone.add_historical_point(
+                    total_entries,
+                    healthy_providers,
+                    total_providers,
+                    active_instances,
+                );
+            }
+        });
+
+        Ok(Self {
+            rpc_handler,
+            chain_id,
+            chain_handlers: Arc::new(chain_handlers),
+            registry,
+            health_service,
+            metrics_collector,
+            auth,
+            journal,
+            shutdown_tx,
+        })
+    }
+
+    /// Creates the RPC handler of a chain, starting its provider health checks and cache saves
+    ///
+    /// # Arguments
+    /// * `rpc_urls` - Upstream RPC endpoint URLs of the chain
+    /// * `cache_manager` - Cache of the chain
+    /// * `metrics_collector` - Metrics collector shared by all chains
+    /// * `max_failures` - Maximum consecutive failures before marking provider unhealthy
+    /// * `rate_limits` - Requests-per-second budgets of the providers
+    /// * `health_check_interval` - Seconds between provider health checks
+    /// * `cache_save_interval` - Minutes between periodic cache saves
+    /// * `session` - Session recorded or replayed on the chain, if any
+    ///
+    /// # Returns
+    /// The RPC handler of the chain
+    async fn start_chain_handler(
+        rpc_urls: Vec<String>,
+        cache_manager: Arc<CacheManager>,
+        metrics_collector: Arc<MetricsCollector>,
+        max_failures: u32,
+        rate_limits: RateLimitConfig,
+        health_check_interval: u64,
+        cache_save_interval: u64,
+        session: Option<Arc<Session>>,
+    ) -> Result<Arc<RpcHandler>> {
+        // Create provider manager with all URLs
+        let provider_manager = Arc::new(
+            ProviderManager::new(rpc_urls, max_failures).await?.with_rate_limits(rate_limits),
+        );
+
         // Start periodic health checks for providers
         let provider_manager_clone = provider_manager.clone();
         tokio::spawn(async move {
@@ -287,36 +617,38 @@ impl ProxyServer {
             });
         }
 
-        // Start background metrics collection task
-        let metrics_collector_clone = metrics_collector.clone();
-        let cache_manager_clone = cache_manager.clone();
-        let provider_manager_clone = provider_manager;
-        let registry_clone = registry.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
-            loop {
-                interval.tick().await;
+        // Create RPC handler with provider manager
+        let mut handler = RpcHandler::new(provider_manager, cache_manager, metrics_collector)?;
+        if let Some(session) = session {
+            handler = handler.with_session(session);
+        }
+        Ok(Arc::new(handler))
+    }
 
-                // Collect current metrics for historical tracking
-                let cache_stats = cache_manager_clone.detailed_stats().await;
-                let providers_info = provider_manager_clone.get_providers_info().await;
-                let healthy_providers =
-                    providers_info.iter().filter(|p| p.is_healthy).count() as u64;
-                let total_providers = providers_info.len() as u64;
-                let active_instances = registry_clone.get_active_instances().await.len();
+    /// Returns the RPC handler of a chain, or of the default chain if none is given
+    ///
+    /// # Returns
+    /// The RPC handler, or None if the chain is not served by this proxy
+    pub fn chain_handler(&self, chain_id: Option<u64>) -> Option<&Arc<RpcHandler>> {
+        match chain_id {
+            Some(chain_id) => self.chain_handlers.get(&chain_id),
+            None => Some(&self.rpc_handler),
+        }
+    }
 
-                let total_entries =
-                    cache_stats.get("total_entries").and_then(|v| v.as_u64()).unwrap_or(0);
-                metrics_collector_clone.add_historical_point(
-                    total_entries,
-                    healthy_providers,
-                    total_providers,
-                    active_instances,
-                );
-            }
-        });
+    /// Returns the tokens requests must carry, if authentication is required
+    pub fn auth(&self) -> Option<&Arc<TokenAuth>> {
+        self.auth.as_ref()
+    }
 
-        Ok(Self { rpc_handler, registry, health_service, metrics_collector, shutdown_tx })
+    /// Returns the session recorded or replayed, if any
+    pub fn session(&self) -> Option<&Arc<Session>> {
+        self.rpc_handler.session()
+    }
+
+    /// Returns the cache managers of all the chains served
+    pub fn cache_managers(&self) -> Vec<Arc<CacheManager>> {
+        self.chain_handlers.values().map(|handler| handler.cache_manager().clone()).collect()
     }
 
     /// Returns a reference to the cache manager
@@ -330,7 +662,8 @@ impl ProxyServer {
     /// Starts the proxy server listening on the specified address
     ///
     /// Creates an Axum web server with routes for:
-    /// - Standard JSON-RPC requests (POST /)
+    /// - Standard JSON-RPC requests (POST /), or to a given chain (POST /<chain_id>)
+    /// - Prometheus metrics (GET /metrics)
     /// - EDB-specific management endpoints (edb_ping, edb_register, etc.)
     ///
     /// # Arguments
@@ -339,11 +672,25 @@ impl ProxyServer {
     /// # Returns
     /// Result indicating server startup success or failure
     pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        self.serve_listener(TcpListener::bind(addr).await?).await
+    }
+
+    /// Starts the proxy server on an already bound listener, e.g. on an ephemeral port
+    ///
+    /// # Arguments
+    /// * `listener` - TCP listener to accept connections from
+    ///
+    /// # Returns
+    /// Result indicating server startup success or failure
+    pub async fn serve_listener(self, listener: TcpListener) -> Result<()> {
         let mut shutdown_rx = self.shutdown_tx.subscribe();
-        let cache_manager_for_shutdown = self.cache_manager().clone();
+        let cache_managers_for_shutdown = self.cache_managers();
+        let session_for_shutdown = self.session().cloned();
 
         let app = Router::new()
             .route("/", post(handle_rpc))
+            .route("/metrics", get(handle_metrics))
+            .route("/{chain_id}", post(handle_rpc))
             .layer(
                 CorsLayer::new()
                     .allow_methods([Method::POST, Method::GET])
@@ -352,19 +699,27 @@ impl ProxyServer {
             )
             .with_state(AppState { proxy: self });
 
-        let listener = TcpListener::bind(addr).await?;
-        info!("EDB RPC Proxy listening on {}", addr);
+        info!("EDB RPC Proxy listening on {}", listener.local_addr()?);
 
         // Create the server with graceful shutdown
         let server = axum::serve(listener, app).with_graceful_shutdown(async move {
             let _ = shutdown_rx.recv().await;
             info!("Shutdown signal received, saving cache and stopping server gracefully");
 
-            // Save cache before shutdown
-            if let Err(e) = cache_manager_for_shutdown.save_to_disk().await {
-                warn!("Failed to save cache during shutdown: {}", e);
-            } else {
-                info!("Cache saved successfully during shutdown");
+            // Save the cache of every chain before shutdown
+            for cache_manager in cache_managers_for_shutdown {
+                if let Err(e) = cache_manager.save_to_disk().await {
+                    warn!("Failed to save cache during shutdown: {}", e);
+                } else {
+                    info!("Cache saved successfully during shutdown");
+                }
+            }
+
+            // Write the recorded session
+            if let Some(session) = session_for_shutdown {
+                if let Err(e) = session.save() {
+                    warn!("Failed to save recorded session during shutdown: {}", e);
+                }
             }
         });
 
@@ -374,10 +729,150 @@ impl ProxyServer {
     }
 }
 
+/// Serve the metrics in the Prometheus text format
+async fn handle_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let proxy = &state.proxy;
+    let mut gauges = ProxyGauges {
+        registered_instances: proxy.registry.get_active_instances().await.len(),
+        ..Default::default()
+    };
+
+    // Caches and providers are summed over the chains served
+    for handler in proxy.chain_handlers.values() {
+        gauges.cache_entries += handler.cache_manager().entry_count().await;
+        gauges.cache_bytes += handler.cache_manager().total_bytes();
+        gauges.healthy_providers += handler.provider_manager().healthy_provider_count().await;
+        gauges.total_providers += handler.provider_manager().get_providers_info().await.len();
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        prometheus::render(&proxy.metrics_collector, &gauges),
+    )
+}
+
+/// Get the chain a request is sent to, from the path prefix or the `X-EDB-Chain` header
+fn requested_chain(
+    path: Option<Path<u64>>,
+    headers: &HeaderMap,
+) -> Result<Option<u64>, StatusCode> {
+    if let Some(Path(chain_id)) = path {
+        return Ok(Some(chain_id));
+    }
+
+    headers
+        .get(CHAIN_HEADER)
+        .map(|value| {
+            value.to_str().ok().and_then(|v| v.trim().parse().ok()).ok_or(StatusCode::BAD_REQUEST)
+        })
+        .transpose()
+}
+
+/// Get the auth token a request carries, from the `Authorization` header or the `token`
+/// query parameter
+fn request_token<'a>(
+    headers: &'a HeaderMap,
+    query: &'a HashMap<String, String>,
+) -> Option<&'a str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .or_else(|| query.get("token").map(String::as_str))
+}
+
+/// Inspect and manage the cache entries of a chain
+///
+/// - `edb_cache_entries [{method?, address?, limit?}]` lists entries, most recently accessed first
+/// - `edb_cache_pin [key]` and `edb_cache_unpin [key]` pin entries against eviction
+/// - `edb_cache_delete [key]` deletes an entry, e.g. a poisoned response
+async fn handle_cache_admin(cache: &CacheManager, method: &str, params: &Value) -> Value {
+    if method == "edb_cache_entries" {
+        let query = params.get(0).unwrap_or(&Value::Null);
+        let filter = match query.get("address").and_then(|a| a.as_str()) {
+            Some(address) => match ExportFilter::parse_addresses(address) {
+                Ok(addresses) => ExportFilter { addresses, ..Default::default() },
+                Err(e) => {
+                    return serde_json::json!({"success": false, "error": e.to_string()});
+                }
+            },
+            None => ExportFilter::default(),
+        };
+        let limit = query
+            .get("limit")
+            .and_then(|l| l.as_u64())
+            .map_or(DEFAULT_CACHE_ENTRIES_LIMIT, |l| l as usize)
+            .min(MAX_CACHE_ENTRIES_LIMIT);
+
+        let method = query.get("method").and_then(|m| m.as_str());
+        let (total, entries) = cache.list_entries(method, &filter, limit).await;
+        return serde_json::json!({"success": true, "total": total, "entries": entries});
+    }
+
+    let Some(key) = params.get(0).and_then(|k| k.as_str()) else {
+        return serde_json::json!({
+            "success": false,
+            "error": "Invalid parameter format. Expected [key]"
+        });
+    };
+
+    let outcome = match method {
+        "edb_cache_pin" => cache.set_pinned(key, true).await,
+        "edb_cache_unpin" => cache.set_pinned(key, false).await,
+        _ => cache.delete_by_key(key).await,
+    };
+    match outcome {
+        Ok(found) => serde_json::json!({
+            "success": true,
+            "found": found,
+            "message": if found {
+                format!("Updated cache entry {key}")
+            } else {
+                format!("Cache entry {key} not found")
+            }
+        }),
+        Err(e) => {
+            warn!("Failed to update cache entry {}: {}", key, e);
+            serde_json::json!({
+                "success": false,
+                "error": format!("Failed to update cache entry: {}", e)
+            })
+        }
+    }
+}
+
 async fn handle_rpc(
     State(state): State<AppState>,
+    path: Option<Path<u64>>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
     Json(request): Json<Value>,
 ) -> Result<Json<Value>, StatusCode> {
+    let chain_id = requested_chain(path, &headers)?;
+
+    // Requests to a shared proxy are authenticated, and accounted to their token
+    let token = match &state.proxy.auth {
+        Some(auth) => {
+            let Some(usage) = request_token(&headers, &query).and_then(|t| auth.authenticate(t))
+            else {
+                debug!("Rejected request without a valid auth token");
+                return Err(StatusCode::UNAUTHORIZED);
+            };
+            usage.record_request();
+            Some(usage)
+        }
+        None => None,
+    };
+
+    // Managing the proxy is reserved to admin tokens
+    if let (Some(usage), Some(method)) = (&token, request.get("method").and_then(|m| m.as_str())) {
+        if auth::ADMIN_METHODS.contains(&method) && !usage.is_admin() {
+            debug!("Rejected {} for non-admin token {}", method, usage.name());
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
     // Handle special EDB health check methods
     debug!("Received RPC request: {}", request);
     let response = if let Some(method) = request.get("method").and_then(|m| m.as_str()) {
@@ -465,6 +960,7 @@ async fn handle_rpc(
                         "total_requests": metrics.total_requests.load(std::sync::atomic::Ordering::Relaxed),
                         "cache_hits": metrics.cache_hits.load(std::sync::atomic::Ordering::Relaxed),
                         "cache_misses": metrics.cache_misses.load(std::sync::atomic::Ordering::Relaxed),
+                        "deduplicated_requests": metrics.deduplicated_requests.load(std::sync::atomic::Ordering::Relaxed),
                         "hit_rate": format!("{:.1}%", metrics.cache_hit_rate()),
                         "error_rate": format!("{:.1}%", metrics.error_rate()),
                         "method_stats": method_stats,
@@ -635,6 +1131,44 @@ async fn handle_rpc(
 
                 Ok(Json(response))
             }
+            "edb_cache_entries" | "edb_cache_pin" | "edb_cache_unpin" | "edb_cache_delete" => {
+                let params = request.get("params").unwrap_or(&serde_json::Value::Null);
+                let result = match state.proxy.chain_handler(chain_id) {
+                    Some(handler) => {
+                        handle_cache_admin(handler.cache_manager(), method, params).await
+                    }
+                    None => serde_json::json!({
+                        "success": false,
+                        "error": format!(
+                            "Chain {} is not served by this proxy",
+                            chain_id.unwrap_or_default()
+                        )
+                    }),
+                };
+
+                let response = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.get("id").unwrap_or(&serde_json::Value::from(1)),
+                    "result": result
+                });
+                Ok(Json(response))
+            }
+            "edb_token_usage" => {
+                // User tokens only read their own usage
+                let tokens = state.proxy.auth.as_ref().map(|auth| match &token {
+                    Some(usage) if !usage.is_admin() => vec![usage.report()],
+                    _ => auth.usage(),
+                });
+                let response = serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.get("id").unwrap_or(&serde_json::Value::from(1)),
+                    "result": {
+                        "enabled": tokens.is_some(),
+                        "tokens": tokens.unwrap_or_default()
+                    }
+                });
+                Ok(Json(response))
+            }
             "edb_shutdown" => {
                 info!("Shutdown request received");
                 let response = serde_json::json!({
@@ -652,8 +1186,40 @@ async fn handle_rpc(
                 Ok(Json(response))
             }
             _ => {
-                // Forward to RPC handler
-                match state.proxy.rpc_handler.handle_request(request).await {
+                // Forward to the RPC handler of the requested chain
+                let Some(handler) = state.proxy.chain_handler(chain_id) else {
+                    return Ok(Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": request.get("id").unwrap_or(&serde_json::Value::Null),
+                        "error": {
+                            "code": -32602,
+                            "message": format!(
+                                "Chain {} is not served by this proxy",
+                                chain_id.unwrap_or_default()
+                            )
+                        }
+                    })));
+                };
+
+                let token_name = token.as_ref().map(|usage| usage.name().to_string());
+                let logged_request = state.proxy.journal.as_ref().map(|_| request.clone());
+                let started_at_ms = chrono::Utc::now().timestamp_millis() as u64;
+                let started = std::time::Instant::now();
+
+                let result = auth::scope(token, handler.handle_request(request)).await;
+
+                if let (Some(journal), Some(request)) = (&state.proxy.journal, logged_request) {
+                    journal.record(&JournalEntry {
+                        started_at_ms,
+                        time_ms: started.elapsed().as_millis() as u64,
+                        chain_id,
+                        token: token_name,
+                        request,
+                        response: result.as_ref().ok().cloned(),
+                    });
+                }
+
+                match result {
                     Ok(response) => Ok(Json(response)),
                     Err(e) => {
                         warn!("RPC request failed: {}", e);
@@ -0,0 +1,183 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-provider rate limiting and burst shaping
+//!
+//! Each upstream provider can be given a requests-per-second budget, enforced with a token
+//! bucket: a burst of requests up to the bucket size goes out at once, and later requests
+//! wait for the bucket to refill. Waiting requests sleep until the next token plus a random
+//! jitter, so that requests queued behind the same provider do not wake up all at once.
+
+use eyre::{eyre, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Largest random jitter added to a wait for a token, as a fraction of the wait
+const JITTER_FRACTION: f64 = 0.25;
+
+/// Rate limits of the upstream providers
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// Requests per second allowed to each provider, unlimited if `None`
+    pub default_rps: Option<f64>,
+    /// Requests allowed at once before shaping kicks in (defaults to one second of budget)
+    pub burst: Option<u32>,
+    /// Requests per second allowed to specific providers, by URL, overriding `default_rps`
+    pub per_provider: HashMap<String, f64>,
+}
+
+impl RateLimitConfig {
+    /// Parse per-provider limits from comma-separated `URL=RPS` pairs
+    pub fn parse_per_provider(pairs: &str) -> Result<HashMap<String, f64>> {
+        pairs
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                // URLs may contain `=` in their query, the rate comes after the last one
+                let (url, rps) =
+                    pair.rsplit_once('=').ok_or_else(|| eyre!("Expected URL=RPS, got {pair:?}"))?;
+                let rps: f64 =
+                    rps.trim().parse().map_err(|e| eyre!("Invalid rate in {pair:?}: {e}"))?;
+                Ok((url.trim().to_string(), rps))
+            })
+            .collect()
+    }
+
+    /// Get the requests per second allowed to a provider, if limited
+    fn rps_for(&self, url: &str) -> Option<f64> {
+        self.per_provider.get(url).copied().or(self.default_rps).filter(|rps| *rps > 0.0)
+    }
+}
+
+/// Token bucket of a provider
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    /// Tokens added per second
+    rate: f64,
+    /// Maximum number of tokens
+    capacity: f64,
+    /// Tokens currently available
+    tokens: f64,
+    /// When the tokens were last refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: Option<u32>, now: Instant) -> Self {
+        let capacity = burst.map_or(rate, f64::from).max(1.0);
+        Self { rate, capacity, tokens: capacity, last_refill: now }
+    }
+
+    /// Take a token, or get how long to wait for the next one
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+/// Rate limiter shaping the requests sent to each provider
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    /// Configured limits
+    config: RateLimitConfig,
+    /// Token bucket of each rate-limited provider, created on first use
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    /// Create a rate limiter with the given limits
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self { config, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    /// Wait until a request can be sent to the provider within its budget
+    pub async fn acquire(&self, url: &str) {
+        let Some(rps) = self.config.rps_for(url) else {
+            return;
+        };
+
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                let now = Instant::now();
+                let bucket = buckets
+                    .entry(url.to_string())
+                    .or_insert_with(|| TokenBucket::new(rps, self.config.burst, now));
+                match bucket.try_take(now) {
+                    Ok(()) => return,
+                    Err(wait) => wait,
+                }
+            };
+
+            let jitter = wait.mul_f64(rand::random_range(0.0..JITTER_FRACTION));
+            debug!("Provider {} is over its budget, waiting {:?}", url, wait + jitter);
+            tokio::time::sleep(wait + jitter).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_burst_and_refill() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, Some(3), start);
+
+        // The burst goes out at once, then requests wait for a token
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+        assert_eq!(bucket.try_take(start), Err(Duration::from_millis(100)));
+
+        // Tokens refill at the configured rate, up to the bucket size
+        assert!(bucket.try_take(start + Duration::from_millis(100)).is_ok());
+        assert!(bucket.try_take(start + Duration::from_millis(150)).is_err());
+        let later = start + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert!(bucket.try_take(later).is_ok());
+        }
+        assert!(bucket.try_take(later).is_err());
+    }
+
+    #[test]
+    fn test_parse_per_provider_limits() {
+        let limits = RateLimitConfig::parse_per_provider(
+            "https://mainnet.infura.io/v3/KEY=10, https://rpc.example.com/?apikey=abc=2.5",
+        )
+        .unwrap();
+        assert_eq!(limits["https://mainnet.infura.io/v3/KEY"], 10.0);
+        assert_eq!(limits["https://rpc.example.com/?apikey=abc"], 2.5);
+
+        assert!(RateLimitConfig::parse_per_provider("https://eth.llamarpc.com").is_err());
+
+        let config = RateLimitConfig { default_rps: Some(0.0), per_provider: limits, burst: None };
+        assert_eq!(config.rps_for("https://mainnet.infura.io/v3/KEY"), Some(10.0));
+        assert_eq!(config.rps_for("https://eth.llamarpc.com"), None);
+    }
+}
@@ -16,10 +16,18 @@
 
 //! RPC request handling and caching logic
 
+use crate::auth::{self, QuotaExceeded, QUOTA_EXCEEDED_ERROR_CODE};
 use crate::cache::CacheManager;
+use crate::export;
+use crate::inflight::{Flight, InFlightRequests};
+use crate::ipc::{self, is_ipc_path};
+use crate::logs::{self, LogFilter};
 use crate::metrics::{ErrorType, MetricsCollector};
-use crate::providers::ProviderManager;
+use crate::providers::{is_missing_state_error, ProviderManager, StateRequirement};
+use crate::session::{Session, SessionMode};
+use crate::ws::is_ws_url;
 use eyre::Result;
+use futures::StreamExt;
 use reqwest::StatusCode;
 use serde_json::Value;
 use std::collections::hash_map::DefaultHasher;
@@ -71,6 +79,9 @@ const CACHEABLE_METHODS: &[&str] = &[
     "trace_call",
 ];
 
+/// Number of `eth_getLogs` sub-ranges fetched concurrently
+const LOG_SUB_RANGE_CONCURRENCY: usize = 4;
+
 /// Common rate limit error patterns found in various RPC providers
 const RATE_LIMIT_PATTERNS: &[&str] = &[
     "rate limit",
@@ -116,6 +127,9 @@ pub struct RpcHandler {
     provider_manager: Arc<ProviderManager>,
     cache_manager: Arc<CacheManager>,
     metrics_collector: Arc<MetricsCollector>,
+    in_flight: InFlightRequests,
+    /// Session being recorded or replayed, if any
+    session: Option<Arc<Session>>,
 }
 
 impl RpcHandler {
@@ -136,7 +150,25 @@ impl RpcHandler {
         let upstream_client =
             reqwest::Client::builder().timeout(std::time::Duration::from_secs(10)).build()?;
 
-        Ok(Self { upstream_client, provider_manager, cache_manager, metrics_collector })
+        Ok(Self {
+            upstream_client,
+            provider_manager,
+            cache_manager,
+            metrics_collector,
+            in_flight: InFlightRequests::default(),
+            session: None,
+        })
+    }
+
+    /// Record the responses served into a session, or serve them from a recorded session
+    pub fn with_session(mut self, session: Arc<Session>) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Returns the session being recorded or replayed, if any
+    pub fn session(&self) -> Option<&Arc<Session>> {
+        self.session.as_ref()
     }
 
     /// Returns a reference to the cache manager
@@ -166,12 +198,50 @@ impl RpcHandler {
     /// - Whether a cached response already exists
     /// - Whether the response is valid (for debug/trace methods)
     ///
+    /// Cache misses for a request identical to one already forwarded upstream wait for
+    /// its response instead of being forwarded again.
+    ///
+    /// When a session is recorded, the responses are recorded into it; when a session is
+    /// replayed, the responses come from it only.
+    ///
     /// # Arguments
     /// * `request` - The JSON-RPC request to handle
     ///
     /// # Returns
     /// The JSON-RPC response, either from cache or upstream
     pub async fn handle_request(&self, request: Value) -> Result<Value> {
+        let Some(session) = &self.session else {
+            return self.serve_request(&request).await;
+        };
+
+        match session.mode() {
+            SessionMode::Replay => Ok(session.replay_response(&request)),
+            SessionMode::Record => {
+                let response = self.serve_request(&request).await?;
+                session.record_response(&request, &response);
+                Ok(response)
+            }
+        }
+    }
+
+    /// Serves a request from the cache or upstream
+    async fn serve_request(&self, request: &Value) -> Result<Value> {
+        // Requests of a token over its upstream quota are only served from the cache
+        match self.route_request(request).await {
+            Err(e) if e.is::<QuotaExceeded>() => {
+                debug!("{}", e);
+                Ok(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request.get("id").cloned().unwrap_or(Value::Null),
+                    "error": {"code": QUOTA_EXCEEDED_ERROR_CODE, "message": e.to_string()}
+                }))
+            }
+            response => response,
+        }
+    }
+
+    /// Routes a request to the cache, the `eth_getLogs` splitter or upstream
+    async fn route_request(&self, request: &Value) -> Result<Value> {
         let start_time = Instant::now();
         let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
 
@@ -181,73 +251,150 @@ impl RpcHandler {
         if CACHEABLE_METHODS.contains(&method) {
             // ALL cacheable methods must pass the deterministic block check
             // This applies uniformly to eth_call, eth_getBalance, etc.
-            if self.has_non_deterministic_block_params(&request) {
+            if self.has_non_deterministic_block_params(request) {
                 debug!("Non-deterministic block params for {}, bypassing cache", method);
-                return self.forward_request(&request).await;
+                return self.forward_request(request).await;
             }
 
-            // Generate cache key from request
-            let cache_key = self.generate_cache_key(&request);
-
-            // Try to get from cache first
-            if let Some(cached_response) = self.cache_manager.get(&cache_key).await {
-                let response_time = start_time.elapsed().as_millis() as u64;
-                self.metrics_collector.record_cache_hit(method, response_time);
-                debug!("Cache hit for {}: {}", method, cache_key);
-                return Ok(cached_response);
+            // Log queries over a block range are split into cached sub-ranges
+            if method == "eth_getLogs" {
+                if let Some(filter) = LogFilter::from_request(request) {
+                    return self.handle_get_logs(request, &filter).await;
+                }
             }
 
-            debug!("Cache miss for {}: {}", method, cache_key);
+            self.handle_cacheable_request(request, method, start_time).await
+        } else {
+            // Non-cacheable request - forward directly
+            debug!("Non-cacheable request: {}", method);
+            self.forward_request(request).await
+        }
+    }
+
+    /// Serves a cacheable request with deterministic parameters from the cache or upstream
+    ///
+    /// # Arguments
+    /// * `request` - The JSON-RPC request to handle
+    /// * `method` - The method of the request
+    /// * `start_time` - When the request was received, for metrics
+    ///
+    /// # Returns
+    /// The JSON-RPC response, either from cache or upstream
+    async fn handle_cacheable_request(
+        &self,
+        request: &Value,
+        method: &str,
+        start_time: Instant,
+    ) -> Result<Value> {
+        // Generate cache key from request
+        let cache_key = self.generate_cache_key(request);
+
+        // Try to get from cache first
+        if let Some(cached_response) = self.cache_manager.get(&cache_key).await {
+            let response_time = start_time.elapsed().as_millis() as u64;
+            self.metrics_collector.record_cache_hit(method, response_time);
+            debug!("Cache hit for {}: {}", method, cache_key);
+            return Ok(cached_response);
+        }
+
+        debug!("Cache miss for {}: {}", method, cache_key);
 
-            // Forward to upstream
-            let response = self.forward_request(&request).await;
-            self.metrics_collector.record_cache_miss();
+        // Forward to upstream, unless an identical request is already in flight
+        let (response, flight) =
+            self.in_flight.coalesce(&cache_key, request, self.forward_request(request)).await;
+        if flight == Flight::Follower {
+            let response_time = start_time.elapsed().as_millis() as u64;
+            self.metrics_collector.record_deduplicated(method, response_time);
+            debug!("Served {} by an identical request in flight", method);
+            return response;
+        }
+        self.metrics_collector.record_cache_miss();
 
-            if let Ok(resp) = &response {
-                let success = resp.get("error").is_none();
+        if let Ok(resp) = &response {
+            let success = resp.get("error").is_none();
 
-                // Only cache successful responses
-                if success {
-                    // Additional validation for debug/trace methods
-                    if (method.starts_with("debug_") || method.starts_with("trace_"))
-                        && !self.is_valid_debug_trace_response(resp)
-                    {
-                        debug!("Invalid debug/trace response for {}, not caching", method);
-                        return Ok(resp.clone());
-                    }
+            // Only cache successful responses
+            if success {
+                // Additional validation for debug/trace methods
+                if (method.starts_with("debug_") || method.starts_with("trace_"))
+                    && !self.is_valid_debug_trace_response(resp)
+                {
+                    debug!("Invalid debug/trace response for {}, not caching", method);
+                    return Ok(resp.clone());
+                }
 
-                    self.cache_manager.set(cache_key, resp.clone()).await;
-                    debug!("Cached response for {}", method);
-                } else {
-                    debug!("Error response for {}, not caching", method);
-                    // Classify error type for metrics
-                    if let Some(error_obj) = resp.get("error") {
-                        if let Some(error_msg) = error_obj.get("message").and_then(|m| m.as_str()) {
-                            let error_msg_lower = error_msg.to_lowercase();
-                            if RATE_LIMIT_PATTERNS
-                                .iter()
-                                .any(|pattern| error_msg_lower.contains(pattern))
-                            {
-                                self.metrics_collector.record_error(ErrorType::RateLimit);
-                            } else if USER_ERROR_PATTERNS
-                                .iter()
-                                .any(|pattern| error_msg_lower.contains(pattern))
-                            {
-                                self.metrics_collector.record_error(ErrorType::UserError);
-                            } else {
-                                self.metrics_collector.record_error(ErrorType::Other);
-                            }
+                self.cache_manager.set_for_request(cache_key, request, resp.clone()).await;
+                debug!("Cached response for {}", method);
+            } else {
+                debug!("Error response for {}, not caching", method);
+                // Classify error type for metrics
+                if let Some(error_obj) = resp.get("error") {
+                    if let Some(error_msg) = error_obj.get("message").and_then(|m| m.as_str()) {
+                        let error_msg_lower = error_msg.to_lowercase();
+                        if RATE_LIMIT_PATTERNS
+                            .iter()
+                            .any(|pattern| error_msg_lower.contains(pattern))
+                        {
+                            self.metrics_collector.record_error(ErrorType::RateLimit);
+                        } else if USER_ERROR_PATTERNS
+                            .iter()
+                            .any(|pattern| error_msg_lower.contains(pattern))
+                        {
+                            self.metrics_collector.record_error(ErrorType::UserError);
+                        } else {
+                            self.metrics_collector.record_error(ErrorType::Other);
                         }
                     }
                 }
             }
+        }
 
-            response
-        } else {
-            // Non-cacheable request - forward directly
-            debug!("Non-cacheable request: {}", method);
-            self.forward_request(&request).await
+        response
+    }
+
+    /// Handles an `eth_getLogs` request by splitting its block range into cached sub-ranges
+    ///
+    /// Sub-ranges are served from the cache or fetched concurrently, then their logs are
+    /// stitched in block order. If a sub-range fails, its error response is returned for
+    /// the whole query.
+    ///
+    /// # Arguments
+    /// * `request` - The `eth_getLogs` request to handle
+    /// * `filter` - The log filter of the request
+    ///
+    /// # Returns
+    /// The JSON-RPC response with the logs of the whole block range
+    async fn handle_get_logs(&self, request: &Value, filter: &LogFilter) -> Result<Value> {
+        let sub_ranges = filter.sub_ranges();
+        debug!("Splitting eth_getLogs into {} sub-ranges", sub_ranges.len());
+
+        let responses: Vec<Result<Value>> = futures::stream::iter(sub_ranges)
+            .map(|range| {
+                let sub_request = filter.sub_request(request, range);
+                async move {
+                    self.handle_cacheable_request(&sub_request, "eth_getLogs", Instant::now()).await
+                }
+            })
+            .buffered(LOG_SUB_RANGE_CONCURRENCY)
+            .collect()
+            .await;
+
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let mut logs = Vec::new();
+        for response in responses {
+            let mut response = response?;
+            match response.get_mut("result").map(Value::take) {
+                Some(Value::Array(sub_logs)) => logs.extend(sub_logs),
+                _ => {
+                    if let Some(object) = response.as_object_mut() {
+                        object.insert("id".to_string(), id);
+                    }
+                    return Ok(response);
+                }
+            }
         }
+
+        Ok(serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": logs }))
     }
 
     /// Detects if a response indicates rate limiting
@@ -408,22 +555,57 @@ impl RpcHandler {
         false
     }
 
+    /// Sends a request to an upstream provider, returning the HTTP status and response body
+    ///
+    /// WebSocket and IPC providers have no per-request HTTP status, so their responses report
+    /// `200 OK` and rate limits are detected from the JSON-RPC error alone.
+    async fn send_upstream(
+        &self,
+        provider_url: &str,
+        request: &Value,
+    ) -> Result<(StatusCode, String)> {
+        if is_ws_url(provider_url) {
+            let response = self.provider_manager.ws_pool().request(provider_url, request).await?;
+            return Ok((StatusCode::OK, response.to_string()));
+        }
+        if is_ipc_path(provider_url) {
+            let response = ipc::request(provider_url, request).await?;
+            return Ok((StatusCode::OK, response.to_string()));
+        }
+
+        let response = self
+            .upstream_client
+            .post(provider_url)
+            .header("Content-Type", "application/json")
+            .json(request)
+            .send()
+            .await?;
+        let status = response.status();
+        Ok((status, response.text().await?))
+    }
+
     async fn forward_request(&self, request: &Value) -> Result<Value> {
         const MAX_RETRIES: usize = 5;
         const MAX_MULTIPLE_SAME_ERROR: usize = 3;
 
+        // Fails with `QuotaExceeded` for a token over its upstream quota, which is not
+        // shared with the identical requests of other tokens (see `InFlightRequests`)
+        auth::charge_upstream_request()?;
+
         // Track providers we've already tried for this request
         let mut tried_providers = HashSet::new();
         // Track errors from different providers using hash as key
         let mut error_responses: HashMap<u64, (Value, usize)> = HashMap::new();
         let mut last_network_error: Option<eyre::Error> = None;
         let mut providers_tried = 0;
+        // State read by the request, to route it to providers serving it
+        let mut state =
+            StateRequirement { block: export::state_block(request), ..Default::default() };
 
         for retry in 0..MAX_RETRIES {
             // Get a provider we haven't tried yet
             let provider_url =
-                match self.provider_manager.get_weighted_provider_excluding(&tried_providers).await
-                {
+                match self.provider_manager.select_provider_for(&tried_providers, state).await {
                     Some(url) => url,
                     None => {
                         debug!(
@@ -467,147 +649,130 @@ impl RpcHandler {
 
             let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("unknown_method");
 
+            // Stay within the provider's budget before timing the request
+            self.provider_manager.acquire_rate_limit(&provider_url).await;
             let start = Instant::now();
 
-            match self
-                .upstream_client
-                .post(&provider_url)
-                .header("Content-Type", "application/json")
-                .json(request)
-                .send()
-                .await
-            {
-                Ok(response) => {
-                    let status = response.status();
+            match self.send_upstream(&provider_url, request).await {
+                Ok((status, response_text)) => {
                     let response_time = start.elapsed().as_millis() as u64;
 
-                    match response.text().await {
-                        Ok(response_text) => {
-                            // Try to parse as JSON
-                            let json_result = serde_json::from_str::<Value>(&response_text);
-
-                            // Check if it's a rate limit error
-                            if self.is_rate_limit_response(
-                                status,
-                                &response_text,
-                                json_result.as_ref().ok(),
-                            ) {
-                                debug!(
-                                    "Provider {} is rate limited (response: {}...)",
-                                    provider_url,
-                                    &response_text.chars().take(200).collect::<String>()
-                                );
+                    // Try to parse as JSON
+                    let json_result = serde_json::from_str::<Value>(&response_text);
+
+                    // Check if it's a rate limit error
+                    if self.is_rate_limit_response(
+                        status,
+                        &response_text,
+                        json_result.as_ref().ok(),
+                    ) {
+                        debug!(
+                            "Provider {} is rate limited (response: {}...)",
+                            provider_url,
+                            &response_text.chars().take(200).collect::<String>()
+                        );
 
+                        self.metrics_collector.record_request(
+                            method,
+                            &provider_url,
+                            response_time,
+                            false,
+                        );
+                        self.provider_manager.mark_provider_rate_limited(&provider_url).await;
+
+                        // Continue to next provider without counting as error
+                        continue;
+                    }
+
+                    // Handle valid JSON response
+                    match json_result {
+                        Ok(response_json) => {
+                            // Check if response contains an error
+                            if let Some(error) = response_json.get("error") {
                                 self.metrics_collector.record_request(
                                     method,
                                     &provider_url,
                                     response_time,
                                     false,
                                 );
-                                self.provider_manager.mark_provider_failed(&provider_url).await;
 
-                                // Continue to next provider without counting as error
-                                continue;
-                            }
+                                // Check if it's a user error (invalid request)
+                                if self.is_user_error(&response_json) {
+                                    debug!("Detected user error, returning immediately");
 
-                            // Handle valid JSON response
-                            match json_result {
-                                Ok(response_json) => {
-                                    // Check if response contains an error
-                                    if let Some(error) = response_json.get("error") {
-                                        self.metrics_collector.record_request(
-                                            method,
-                                            &provider_url,
-                                            response_time,
-                                            false,
-                                        );
+                                    // Don't mark provider as failed - it's user's fault
+                                    self.provider_manager
+                                        .mark_provider_success(&provider_url, response_time)
+                                        .await;
+                                    return Ok(response_json);
+                                }
 
-                                        // Check if it's a user error (invalid request)
-                                        if self.is_user_error(&response_json) {
-                                            debug!("Detected user error, returning immediately");
+                                // It's a provider/blockchain error
+                                let error_hash = self.create_error_signature(error);
 
-                                            // Don't mark provider as failed - it's user's fault
-                                            self.provider_manager
-                                                .mark_provider_success(&provider_url, response_time)
-                                                .await;
-                                            return Ok(response_json);
-                                        }
+                                // Track this error
+                                error_responses
+                                    .entry(error_hash)
+                                    .and_modify(|(_, count)| *count += 1)
+                                    .or_insert((response_json.clone(), 1));
 
-                                        // It's a provider/blockchain error
-                                        let error_hash = self.create_error_signature(error);
+                                debug!(
+                                    "Provider {} returned error (hash: {})",
+                                    provider_url, error_hash
+                                );
 
-                                        // Track this error
-                                        error_responses
-                                            .entry(error_hash)
-                                            .and_modify(|(_, count)| *count += 1)
-                                            .or_insert((response_json.clone(), 1));
+                                // Pruned providers all agree the state is missing, which says
+                                // nothing of the request: retry it on an archive node
+                                if is_missing_state_error(error) {
+                                    self.provider_manager
+                                        .mark_state_missing(&provider_url, state.block)
+                                        .await;
+                                    state.archive_needed = true;
+                                    continue;
+                                }
 
+                                // If multiple providers return the same error, it's likely legitimate
+                                if let Some((_, count)) = error_responses.get(&error_hash) {
+                                    if *count >= MAX_MULTIPLE_SAME_ERROR {
                                         debug!(
-                                            "Provider {} returned error (hash: {})",
-                                            provider_url, error_hash
+                                            "Multiple providers ({}) returned same error, likely legitimate",
+                                            count
                                         );
-
-                                        // If multiple providers return the same error, it's likely legitimate
-                                        if let Some((_, count)) = error_responses.get(&error_hash) {
-                                            if *count >= MAX_MULTIPLE_SAME_ERROR {
-                                                debug!(
-                                                    "Multiple providers ({}) returned same error, likely legitimate",
-                                                    count
-                                                );
-                                                return Ok(response_json);
-                                            }
-                                        }
-
-                                        // Mark provider as failed and continue
-                                        self.provider_manager
-                                            .mark_provider_failed(&provider_url)
-                                            .await;
-                                        continue;
+                                        return Ok(response_json);
                                     }
+                                }
 
-                                    // Success! No error in response
-                                    self.metrics_collector.record_request(
-                                        method,
-                                        &provider_url,
-                                        response_time,
-                                        true,
-                                    );
+                                // Mark provider as failed and continue
+                                self.provider_manager.mark_provider_failed(&provider_url).await;
+                                continue;
+                            }
 
-                                    self.provider_manager
-                                        .mark_provider_success(&provider_url, response_time)
-                                        .await;
+                            // Success! No error in response
+                            self.metrics_collector.record_request(
+                                method,
+                                &provider_url,
+                                response_time,
+                                true,
+                            );
 
-                                    debug!(
-                                        "Request successful via {} ({}ms)",
-                                        provider_url, response_time
-                                    );
-                                    return Ok(response_json);
-                                }
-                                Err(parse_error) => {
-                                    // Response is not valid JSON
-                                    warn!(
-                                        "Invalid JSON response from {} (first 200 chars): {}...",
-                                        provider_url,
-                                        &response_text.chars().take(200).collect::<String>()
-                                    );
-
-                                    self.metrics_collector.record_request(
-                                        method,
-                                        &provider_url,
-                                        response_time,
-                                        false,
-                                    );
-                                    self.provider_manager.mark_provider_failed(&provider_url).await;
-                                    last_network_error = Some(eyre::eyre!(
-                                        "Invalid JSON from provider: {}",
-                                        parse_error
-                                    ));
-                                    continue;
-                                }
+                            self.provider_manager
+                                .mark_provider_success(&provider_url, response_time)
+                                .await;
+                            if let Some(block) = state.block {
+                                self.provider_manager.mark_state_served(&provider_url, block).await;
                             }
+
+                            debug!("Request successful via {} ({}ms)", provider_url, response_time);
+                            return Ok(response_json);
                         }
-                        Err(e) => {
-                            warn!("Failed to read response body from {}: {}", provider_url, e);
+                        Err(parse_error) => {
+                            // Response is not valid JSON
+                            warn!(
+                                "Invalid JSON response from {} (first 200 chars): {}...",
+                                provider_url,
+                                &response_text.chars().take(200).collect::<String>()
+                            );
+
                             self.metrics_collector.record_request(
                                 method,
                                 &provider_url,
@@ -615,7 +780,8 @@ impl RpcHandler {
                                 false,
                             );
                             self.provider_manager.mark_provider_failed(&provider_url).await;
-                            last_network_error = Some(e.into());
+                            last_network_error =
+                                Some(eyre::eyre!("Invalid JSON from provider: {}", parse_error));
                             continue;
                         }
                     }
@@ -658,6 +824,13 @@ impl RpcHandler {
     }
 
     fn has_non_deterministic_block_params(&self, request: &Value) -> bool {
+        // Log queries without a block range or hash default to the latest block
+        if request.get("method").and_then(|m| m.as_str()) == Some("eth_getLogs")
+            && logs::has_open_block_range(request)
+        {
+            return true;
+        }
+
         let params = request.get("params").and_then(|p| p.as_array());
 
         if let Some(params) = params {
@@ -741,7 +914,7 @@ mod tests {
     use tempfile::TempDir;
     use tracing::{debug, info};
     use wiremock::{
-        matchers::{method, path},
+        matchers::{body_partial_json, method, path},
         Mock, MockServer, ResponseTemplate,
     };
 
@@ -840,6 +1013,57 @@ mod tests {
         // Mock server expectations should be met (only 1 call)
     }
 
+    #[tokio::test]
+    async fn test_concurrent_identical_requests_deduplicated() {
+        let Some((handler, mock_server, _temp_dir)) =
+            create_test_rpc_handler("test_concurrent_identical_requests_deduplicated").await
+        else {
+            return;
+        };
+
+        Mock::given(method("POST"))
+            .and(path("/"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "result": "0x6080604052"
+                    }))
+                    .set_delay(std::time::Duration::from_millis(200)),
+            )
+            .expect(1) // Identical requests in flight share one upstream call
+            .mount(&mock_server)
+            .await;
+
+        let request = |id: u64| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "eth_getCode",
+                "params": ["0x1234567890123456789012345678901234567890", "0x1000000"],
+                "id": id
+            })
+        };
+
+        let (first, second) = futures::future::join(
+            handler.handle_request(request(1)),
+            handler.handle_request(request(2)),
+        )
+        .await;
+
+        assert_eq!(first.unwrap()["id"], 1);
+        let second = second.unwrap();
+        assert_eq!(second["id"], 2);
+        assert_eq!(second["result"], "0x6080604052");
+        assert_eq!(
+            handler
+                .metrics_collector()
+                .deduplicated_requests
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+    }
+
     #[tokio::test]
     async fn test_non_cacheable_method_passthrough() {
         let Some((handler, mock_server, _temp_dir)) =
@@ -1016,6 +1240,15 @@ mod tests {
                 }),
                 false,
             ),
+            (
+                serde_json::json!({
+                    "method": "eth_getLogs",
+                    "params": [{
+                        "address": "0x1234567890123456789012345678901234567890"
+                    }]
+                }),
+                true,
+            ),
         ];
 
         for (request, expected) in test_cases {
@@ -1024,6 +1257,64 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_get_logs_range_splitting() {
+        let Some((handler, mock_server, _temp_dir)) =
+            create_test_rpc_handler("test_get_logs_range_splitting").await
+        else {
+            return;
+        };
+
+        // Blocks 1999 and 2000 fall in two sub-ranges, each fetched once
+        for block in ["0x7cf", "0x7d0"] {
+            Mock::given(method("POST"))
+                .and(path("/"))
+                .and(body_partial_json(serde_json::json!({
+                    "method": "eth_getLogs",
+                    "params": [{"fromBlock": block, "toBlock": block}]
+                })))
+                .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": 1,
+                    "result": [{"blockNumber": block, "logIndex": "0x0"}]
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+        }
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getLogs",
+            "params": [{"fromBlock": "0x07cf", "toBlock": "0x7d0"}],
+            "id": 3
+        });
+
+        // The logs of the sub-ranges are stitched in block order
+        let response = handler.handle_request(request.clone()).await.unwrap();
+        assert_eq!(response["id"], 3);
+        let blocks: Vec<_> = response["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|log| log["blockNumber"].as_str().unwrap())
+            .collect();
+        assert_eq!(blocks, ["0x7cf", "0x7d0"]);
+
+        // Overlapping queries are served from the cached sub-ranges
+        assert_eq!(handler.handle_request(request).await.unwrap(), response);
+        let overlapping = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getLogs",
+            "params": [{"fromBlock": "0x7d0", "toBlock": "0x7d0"}],
+            "id": 3
+        });
+        assert_eq!(
+            handler.handle_request(overlapping).await.unwrap()["result"][0]["blockNumber"],
+            "0x7d0"
+        );
+    }
+
     #[tokio::test]
     async fn test_error_response_not_cached() {
         let Some((handler, mock_server, _temp_dir)) =
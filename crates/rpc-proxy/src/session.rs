@@ -0,0 +1,254 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Session recording and replay
+//!
+//! In record mode, every response served by the proxy (from the cache or upstream) is kept
+//! in a session bundle, a portable JSON file written on shutdown. In replay mode, the proxy
+//! serves exclusively from a bundle and never contacts a provider, so that a debugging
+//! session can be reproduced offline and deterministically.
+//!
+//! Responses are recorded by method and params. When a request got different responses
+//! over the session (e.g. `eth_blockNumber`), they are replayed in the order they were
+//! served, the last one being repeated once the others are exhausted.
+
+use eyre::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tracing::{debug, info};
+
+/// Version of the session bundle format
+const BUNDLE_VERSION: u32 = 1;
+
+/// Error code of the requests missing from a replayed session
+pub const NOT_RECORDED_ERROR_CODE: i64 = -32001;
+
+/// Whether a session is being recorded or replayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionMode {
+    /// Responses served are recorded into the bundle
+    Record,
+    /// Responses are served from the bundle only
+    Replay,
+}
+
+/// Responses recorded over a session, as written to disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SessionBundle {
+    /// Version of the bundle format
+    pub version: u32,
+    /// Chain the session was recorded on
+    pub chain_id: u64,
+    /// Unix timestamp when the recording started
+    pub recorded_at: u64,
+    /// Responses served, without their id, by request key and in the order served
+    pub responses: BTreeMap<String, Vec<Value>>,
+}
+
+/// A session being recorded or replayed
+#[derive(Debug)]
+pub struct Session {
+    mode: SessionMode,
+    path: PathBuf,
+    bundle: Mutex<SessionBundle>,
+    /// Index of the next response to replay, by request key
+    cursors: Mutex<HashMap<String, usize>>,
+}
+
+impl Session {
+    /// Start recording a session, to be written to the given path
+    pub fn record(path: impl Into<PathBuf>, chain_id: u64) -> Self {
+        let bundle = SessionBundle {
+            version: BUNDLE_VERSION,
+            chain_id,
+            recorded_at: chrono::Utc::now().timestamp() as u64,
+            responses: BTreeMap::new(),
+        };
+        Self::new(SessionMode::Record, path.into(), bundle)
+    }
+
+    /// Load a recorded session to replay it
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let bundle: SessionBundle = serde_json::from_str(&fs::read_to_string(&path)?)?;
+        if bundle.version != BUNDLE_VERSION {
+            bail!(
+                "Unsupported session bundle version {} in {}, expected {}",
+                bundle.version,
+                path.display(),
+                BUNDLE_VERSION
+            );
+        }
+
+        info!(
+            "Replaying {} recorded requests on chain {} from {}",
+            bundle.responses.len(),
+            bundle.chain_id,
+            path.display()
+        );
+        Ok(Self::new(SessionMode::Replay, path, bundle))
+    }
+
+    fn new(mode: SessionMode, path: PathBuf, bundle: SessionBundle) -> Self {
+        Self { mode, path, bundle: Mutex::new(bundle), cursors: Mutex::new(HashMap::new()) }
+    }
+
+    /// Whether the session is being recorded or replayed
+    pub fn mode(&self) -> SessionMode {
+        self.mode
+    }
+
+    /// Path of the session bundle
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Chain the session is recorded on
+    pub fn chain_id(&self) -> u64 {
+        self.bundle.lock().unwrap().chain_id
+    }
+
+    /// Record a response served for a request
+    pub fn record_response(&self, request: &Value, response: &Value) {
+        let mut response = response.clone();
+        if let Some(object) = response.as_object_mut() {
+            object.remove("id");
+        }
+
+        let mut bundle = self.bundle.lock().unwrap();
+        let responses = bundle.responses.entry(request_key(request)).or_default();
+        // Repeated identical responses, e.g. cache hits, replay the same without the copies
+        if responses.last() != Some(&response) {
+            responses.push(response);
+        }
+    }
+
+    /// Get the recorded response to a request, with the id of the request
+    ///
+    /// # Returns
+    /// The next recorded response, or a JSON-RPC error if the request was not recorded
+    pub fn replay_response(&self, request: &Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let key = request_key(request);
+
+        let bundle = self.bundle.lock().unwrap();
+        let Some(responses) = bundle.responses.get(&key).filter(|r| !r.is_empty()) else {
+            debug!("Request not recorded in the session: {}", key);
+            return serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {
+                    "code": NOT_RECORDED_ERROR_CODE,
+                    "message": format!("Request not recorded in the replayed session: {key}"),
+                },
+            });
+        };
+
+        let mut cursors = self.cursors.lock().unwrap();
+        let cursor = cursors.entry(key).or_default();
+        let mut response = responses[(*cursor).min(responses.len() - 1)].clone();
+        *cursor += 1;
+
+        if let Some(object) = response.as_object_mut() {
+            object.insert("id".to_string(), id);
+        }
+        response
+    }
+
+    /// Write a recorded session to its bundle, doing nothing when replaying
+    pub fn save(&self) -> Result<()> {
+        if self.mode != SessionMode::Record {
+            return Ok(());
+        }
+
+        let content = serde_json::to_string_pretty(&*self.bundle.lock().unwrap())?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write to a temporary file first, so that an interrupted save keeps the previous bundle
+        let temp_path = self.path.with_extension("tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.path)?;
+
+        info!("Session recorded to {}", self.path.display());
+        Ok(())
+    }
+}
+
+/// Key of a request in a session bundle: its method and params
+fn request_key(request: &Value) -> String {
+    let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
+    let params = request.get("params").unwrap_or(&Value::Null);
+    format!("{method}:{params}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn request(id: u64, method: &str) -> Value {
+        serde_json::json!({"jsonrpc": "2.0", "id": id, "method": method, "params": []})
+    }
+
+    fn response(id: u64, result: &str) -> Value {
+        serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result})
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.json");
+
+        let recording = Session::record(&path, 10);
+        recording.record_response(&request(1, "eth_blockNumber"), &response(1, "0x10"));
+        recording.record_response(&request(2, "eth_blockNumber"), &response(2, "0x11"));
+        recording.record_response(&request(3, "eth_chainId"), &response(3, "0xa"));
+        recording.record_response(&request(4, "eth_chainId"), &response(4, "0xa"));
+        recording.save().unwrap();
+
+        let replay = Session::replay(&path).unwrap();
+        assert_eq!(replay.mode(), SessionMode::Replay);
+        assert_eq!(replay.chain_id(), 10);
+
+        // Responses are replayed in order with the caller's id, the last one repeated
+        assert_eq!(replay.replay_response(&request(7, "eth_blockNumber")), response(7, "0x10"));
+        assert_eq!(replay.replay_response(&request(8, "eth_blockNumber")), response(8, "0x11"));
+        assert_eq!(replay.replay_response(&request(9, "eth_blockNumber")), response(9, "0x11"));
+        assert_eq!(replay.bundle.lock().unwrap().responses["eth_chainId:[]"].len(), 1);
+
+        // Requests not recorded are errors, never forwarded
+        let missing = replay.replay_response(&request(5, "eth_gasPrice"));
+        assert_eq!(missing["id"], 5);
+        assert_eq!(missing["error"]["code"], NOT_RECORDED_ERROR_CODE);
+    }
+
+    #[test]
+    fn test_unsupported_bundle_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("session.json");
+        fs::write(&path, r#"{"version": 99, "chain_id": 1, "recorded_at": 0, "responses": {}}"#)
+            .unwrap();
+        assert!(Session::replay(&path).is_err());
+    }
+}
@@ -17,7 +17,8 @@
 //! TUI application state and logic
 
 use super::remote::{
-    RemoteCacheStats, RemoteDataFetcher, RemoteMetricData, RemoteProviderStatus, RemoteProxyClient,
+    CacheSearch, RemoteCacheEntry, RemoteCacheStats, RemoteDataFetcher, RemoteMetricData,
+    RemoteProviderStatus, RemoteProxyClient,
 };
 use ratatui::{prelude::*, widgets::*};
 use std::{
@@ -28,15 +29,20 @@ use std::{
 /// Maximum number of data points to keep in history
 const MAX_HISTORY: usize = 100;
 
+/// Rows moved by PgUp/PgDn in tables
+const PAGE_ROWS: usize = 10;
+
 // Use remote types directly
 pub type MetricData = RemoteMetricData;
 pub type ProviderStatus = RemoteProviderStatus;
 pub type CacheStats = RemoteCacheStats;
+pub type CacheEntry = RemoteCacheEntry;
 
 pub enum Tab {
     Overview,
     Providers,
     Cache,
+    Browser,
     Methods,
     Instances,
 }
@@ -47,12 +53,50 @@ impl Tab {
             Self::Overview => "Overview",
             Self::Providers => "Providers",
             Self::Cache => "Cache",
+            Self::Browser => "Cache Browser",
             Self::Methods => "Methods",
             Self::Instances => "EDB Instances",
         }
     }
 }
 
+/// Order of the per-method breakdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodSort {
+    Requests,
+    Latency,
+    Errors,
+}
+
+impl MethodSort {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Requests => "requests",
+            Self::Latency => "latency",
+            Self::Errors => "errors",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Requests => Self::Latency,
+            Self::Latency => Self::Errors,
+            Self::Errors => Self::Requests,
+        }
+    }
+}
+
+/// Requests and latency of a method, as shown in the per-method breakdown
+#[derive(Debug, Clone)]
+pub struct MethodRow {
+    pub method: String,
+    pub requests: u64,
+    pub hits: u64,
+    pub upstream: u64,
+    pub avg_response_time_ms: f64,
+    pub errors: u64,
+}
+
 pub struct App {
     // Remote data fetcher
     pub fetcher: RemoteDataFetcher,
@@ -67,6 +111,8 @@ pub struct App {
     pub metrics_history: VecDeque<MetricData>,
     pub providers: Vec<ProviderStatus>,
     pub cache_stats: Option<CacheStats>,
+    pub cache_entries: Vec<CacheEntry>,
+    pub cache_entries_total: usize,
     pub active_instances: Vec<u32>,
 
     // Enhanced metrics data (JSON values from API)
@@ -77,6 +123,10 @@ pub struct App {
 
     // UI state
     pub selected_provider: usize,
+    pub selected_cache_entry: usize,
+    pub method_sort: MethodSort,
+    /// Query being typed in the cache browser search bar, if any
+    pub search_input: Option<String>,
 
     // Separate scroll offsets for different tables/sections
     pub provider_analytics_scroll: usize,
@@ -103,12 +153,17 @@ impl App {
             metrics_history: VecDeque::with_capacity(MAX_HISTORY),
             providers: Vec::new(),
             cache_stats: None,
+            cache_entries: Vec::new(),
+            cache_entries_total: 0,
             active_instances: Vec::new(),
             cache_metrics: None,
             provider_metrics: None,
             request_metrics: None,
             system_info: None,
             selected_provider: 0,
+            selected_cache_entry: 0,
+            method_sort: MethodSort::Requests,
+            search_input: None,
             provider_analytics_scroll: 0,
             _provider_list_scroll: 0,
             cache_details_scroll: 0,
@@ -133,6 +188,8 @@ impl App {
 
                 // Update cache stats
                 self.cache_stats = data.cache_stats;
+                self.cache_entries = data.cache_entries;
+                self.cache_entries_total = data.cache_entries_total;
 
                 // Update active instances
                 self.active_instances = data.active_instances;
@@ -206,10 +263,106 @@ impl App {
             }
         }
 
-        // Clamp selected provider
+        // Clamp selected provider and cache entry
         if !self.providers.is_empty() {
             self.selected_provider = self.selected_provider.min(self.providers.len() - 1);
         }
+        self.selected_cache_entry =
+            self.selected_cache_entry.min(self.cache_entries.len().saturating_sub(1));
+        self.methods_scroll = self.methods_scroll.min(self.method_count().saturating_sub(1));
+    }
+
+    /// Requests and latency of each method, in the selected order
+    pub fn method_breakdown(&self) -> Vec<MethodRow> {
+        let Some(method_stats) = self
+            .cache_metrics
+            .as_ref()
+            .and_then(|m| m.get("method_stats"))
+            .and_then(|v| v.as_object())
+        else {
+            return Vec::new();
+        };
+
+        let field = |stats: &serde_json::Value, name: &str| {
+            stats.get(name).and_then(|v| v.as_u64()).unwrap_or(0)
+        };
+        let mut rows: Vec<MethodRow> = method_stats
+            .iter()
+            .map(|(method, stats)| MethodRow {
+                method: method.clone(),
+                requests: field(stats, "total_requests"),
+                hits: field(stats, "hits"),
+                upstream: field(stats, "misses"),
+                avg_response_time_ms: stats
+                    .get("avg_response_time_ms")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(0.0),
+                errors: field(stats, "errors"),
+            })
+            .collect();
+
+        match self.method_sort {
+            MethodSort::Requests => rows.sort_by(|a, b| b.requests.cmp(&a.requests)),
+            MethodSort::Latency => {
+                rows.sort_by(|a, b| b.avg_response_time_ms.total_cmp(&a.avg_response_time_ms))
+            }
+            MethodSort::Errors => rows.sort_by(|a, b| b.errors.cmp(&a.errors)),
+        }
+        rows
+    }
+
+    fn method_count(&self) -> usize {
+        self.cache_metrics
+            .as_ref()
+            .and_then(|m| m.get("method_stats"))
+            .and_then(|v| v.as_object())
+            .map_or(0, |stats| stats.len())
+    }
+
+    /// Sort the per-method breakdown by the next criterion
+    pub fn cycle_method_sort(&mut self) {
+        if matches!(self.current_tab, Tab::Methods) {
+            self.method_sort = self.method_sort.next();
+            self.methods_scroll = 0;
+        }
+    }
+
+    /// Whether a search query is being typed
+    pub fn is_searching(&self) -> bool {
+        self.search_input.is_some()
+    }
+
+    /// Start typing a search query in the cache browser
+    pub fn start_search(&mut self) {
+        self.current_tab = Tab::Browser;
+        self.search_input = Some(self.fetcher.cache_search().describe());
+    }
+
+    pub fn push_search_char(&mut self, c: char) {
+        if let Some(input) = &mut self.search_input {
+            input.push(c);
+        }
+    }
+
+    pub fn pop_search_char(&mut self) {
+        if let Some(input) = &mut self.search_input {
+            input.pop();
+        }
+    }
+
+    /// Stop typing, keeping the current search
+    pub fn cancel_search(&mut self) {
+        self.search_input = None;
+    }
+
+    /// Search the cache entries by the query typed, an empty query listing all entries
+    pub async fn apply_search(&mut self) {
+        let Some(query) = self.search_input.take() else {
+            return;
+        };
+        self.fetcher.set_cache_search(CacheSearch::parse(&query));
+        self.selected_cache_entry = 0;
+        self.update().await;
     }
 
     pub async fn refresh(&mut self) {
@@ -222,6 +375,37 @@ impl App {
         self.update().await;
     }
 
+    /// Pin or unpin the cache entry selected in the cache browser
+    pub async fn toggle_pin_selected(&mut self) {
+        if !matches!(self.current_tab, Tab::Browser) {
+            return;
+        }
+        let Some(entry) = self.cache_entries.get(self.selected_cache_entry) else {
+            return;
+        };
+
+        let client = self.fetcher.client();
+        if let Err(e) = client.set_cache_entry_pinned(&entry.key, !entry.pinned).await {
+            tracing::warn!("Failed to pin cache entry {}: {}", entry.key, e);
+        }
+        self.update().await;
+    }
+
+    /// Delete the cache entry selected in the cache browser, e.g. a poisoned response
+    pub async fn delete_selected_entry(&mut self) {
+        if !matches!(self.current_tab, Tab::Browser) {
+            return;
+        }
+        let Some(entry) = self.cache_entries.get(self.selected_cache_entry) else {
+            return;
+        };
+
+        if let Err(e) = self.fetcher.client().delete_cache_entry(&entry.key).await {
+            tracing::warn!("Failed to delete cache entry {}: {}", entry.key, e);
+        }
+        self.update().await;
+    }
+
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
@@ -230,7 +414,8 @@ impl App {
         self.current_tab = match self.current_tab {
             Tab::Overview => Tab::Providers,
             Tab::Providers => Tab::Cache,
-            Tab::Cache => Tab::Methods,
+            Tab::Cache => Tab::Browser,
+            Tab::Browser => Tab::Methods,
             Tab::Methods => Tab::Instances,
             Tab::Instances => Tab::Overview,
         };
@@ -241,7 +426,8 @@ impl App {
             Tab::Overview => Tab::Instances,
             Tab::Providers => Tab::Overview,
             Tab::Cache => Tab::Providers,
-            Tab::Methods => Tab::Cache,
+            Tab::Browser => Tab::Cache,
+            Tab::Methods => Tab::Browser,
             Tab::Instances => Tab::Methods,
         };
     }
@@ -259,6 +445,9 @@ impl App {
             Tab::Cache => {
                 self.cache_details_scroll = self.cache_details_scroll.saturating_sub(1);
             }
+            Tab::Browser => {
+                self.selected_cache_entry = self.selected_cache_entry.saturating_sub(1);
+            }
             Tab::Methods => {
                 self.methods_scroll = self.methods_scroll.saturating_sub(1);
             }
@@ -280,8 +469,13 @@ impl App {
             Tab::Cache => {
                 self.cache_details_scroll = self.cache_details_scroll.saturating_add(1);
             }
+            Tab::Browser => {
+                self.selected_cache_entry =
+                    (self.selected_cache_entry + 1).min(self.cache_entries.len().saturating_sub(1));
+            }
             Tab::Methods => {
-                self.methods_scroll = self.methods_scroll.saturating_add(1);
+                self.methods_scroll =
+                    (self.methods_scroll + 1).min(self.method_count().saturating_sub(1));
             }
             Tab::Instances => {
                 self.instances_scroll = self.instances_scroll.saturating_add(1);
@@ -289,6 +483,38 @@ impl App {
         }
     }
 
+    pub fn page_up(&mut self) {
+        match self.current_tab {
+            Tab::Cache => {
+                self.cache_details_scroll = self.cache_details_scroll.saturating_sub(5);
+            }
+            Tab::Browser => {
+                self.selected_cache_entry = self.selected_cache_entry.saturating_sub(PAGE_ROWS);
+            }
+            Tab::Methods => {
+                self.methods_scroll = self.methods_scroll.saturating_sub(PAGE_ROWS);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn page_down(&mut self) {
+        match self.current_tab {
+            Tab::Cache => {
+                self.cache_details_scroll = self.cache_details_scroll.saturating_add(5);
+            }
+            Tab::Browser => {
+                self.selected_cache_entry = (self.selected_cache_entry + PAGE_ROWS)
+                    .min(self.cache_entries.len().saturating_sub(1));
+            }
+            Tab::Methods => {
+                self.methods_scroll =
+                    (self.methods_scroll + PAGE_ROWS).min(self.method_count().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+
     pub fn next_provider(&mut self) {
         if !self.providers.is_empty() {
             self.selected_provider = (self.selected_provider + 1) % self.providers.len();
@@ -324,6 +550,7 @@ impl App {
             Tab::Overview => self.render_overview(f, chunks[1]),
             Tab::Providers => self.render_providers(f, chunks[1]),
             Tab::Cache => self.render_cache(f, chunks[1]),
+            Tab::Browser => self.render_browser(f, chunks[1]),
             Tab::Methods => self.render_methods(f, chunks[1]),
             Tab::Instances => self.render_instances(f, chunks[1]),
         }
@@ -342,6 +569,7 @@ impl App {
             Tab::Overview.title(),
             Tab::Providers.title(),
             Tab::Cache.title(),
+            Tab::Browser.title(),
             Tab::Methods.title(),
             Tab::Instances.title(),
         ];
@@ -350,8 +578,9 @@ impl App {
             Tab::Overview => 0,
             Tab::Providers => 1,
             Tab::Cache => 2,
-            Tab::Methods => 3,
-            Tab::Instances => 4,
+            Tab::Browser => 3,
+            Tab::Methods => 4,
+            Tab::Instances => 5,
         };
 
         let title = format!("EDB RPC Proxy Monitor - {}", self.proxy_url);
@@ -367,6 +596,8 @@ impl App {
     fn render_footer(&self, f: &mut Frame<'_>, area: Rect) {
         let help_text = if self.show_help {
             "Press 'h' to hide help"
+        } else if self.is_searching() {
+            "Enter:Search | Esc:Cancel | method and/or addresses, separated by spaces"
         } else {
             "q:Quit | h:Help | r:Refresh | c:Clear Cache | Tab:Switch | ←→:Navigate | ↑↓:Scroll"
         };
@@ -480,6 +711,26 @@ impl App {
                 Span::styled("←→", Style::default().fg(Color::Yellow)),
                 Span::raw("        Navigate providers"),
             ]),
+            Line::from(vec![
+                Span::styled("/", Style::default().fg(Color::Yellow)),
+                Span::raw("        Search cache entries by method or address"),
+            ]),
+            Line::from(vec![
+                Span::styled("p", Style::default().fg(Color::Yellow)),
+                Span::raw("        Pin/unpin the selected cache entry"),
+            ]),
+            Line::from(vec![
+                Span::styled("d", Style::default().fg(Color::Yellow)),
+                Span::raw("        Delete the selected cache entry"),
+            ]),
+            Line::from(vec![
+                Span::styled("s", Style::default().fg(Color::Yellow)),
+                Span::raw("        Sort methods by requests, latency, or errors"),
+            ]),
+            Line::from(vec![
+                Span::styled("PgUp/PgDn", Style::default().fg(Color::Yellow)),
+                Span::raw("  Scroll cache analytics, or a page of entries or methods"),
+            ]),
         ];
 
         let help_block = Paragraph::new(help_text)
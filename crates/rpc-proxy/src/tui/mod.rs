@@ -19,6 +19,8 @@
 //! Provides a real-time monitoring interface showing:
 //! - Provider health and response times
 //! - Cache statistics and hit rates
+//! - A cache browser to search, pin and delete cache entries by method or address
+//! - Request counts, latency and errors per RPC method
 //! - EDB instance registry
 //! - Request metrics and performance charts
 //! - Enhanced metrics (cache hit rates, provider usage analytics)
@@ -55,7 +57,16 @@ where
         // Handle events
         if event::poll(Duration::from_millis(0))? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+                if key.kind == KeyEventKind::Press && app.is_searching() {
+                    // Keys are typed into the search bar until it is applied or cancelled
+                    match key.code {
+                        KeyCode::Esc => app.cancel_search(),
+                        KeyCode::Enter => app.apply_search().await,
+                        KeyCode::Backspace => app.pop_search_char(),
+                        KeyCode::Char(c) => app.push_search_char(c),
+                        _ => {}
+                    }
+                } else if key.kind == KeyEventKind::Press {
                     match key.code {
                         KeyCode::Char('q') | KeyCode::Esc => break,
                         KeyCode::Char('r') => app.refresh().await,
@@ -67,6 +78,12 @@ where
                         KeyCode::Down => app.scroll_down(),
                         KeyCode::Left => app.previous_provider(),
                         KeyCode::Right => app.next_provider(),
+                        KeyCode::PageUp => app.page_up(),
+                        KeyCode::PageDown => app.page_down(),
+                        KeyCode::Char('p') => app.toggle_pin_selected().await,
+                        KeyCode::Char('d') => app.delete_selected_entry().await,
+                        KeyCode::Char('/') => app.start_search(),
+                        KeyCode::Char('s') => app.cycle_method_sort(),
                         _ => {}
                     }
                 }
@@ -21,6 +21,9 @@ use serde_json::Value;
 use std::time::Duration;
 use tracing::{debug, warn};
 
+/// Number of cache entries fetched for the cache browser
+const CACHE_BROWSER_ENTRIES: usize = 200;
+
 /// Remote proxy client for communicating with proxy servers via RPC
 #[derive(Clone)]
 pub struct RemoteProxyClient {
@@ -41,11 +44,19 @@ impl RemoteProxyClient {
 
     /// Make an RPC request to the proxy
     async fn rpc_request(&self, method: &str) -> Result<Value> {
-        let request = serde_json::json!({
+        self.rpc_request_with_params(method, Value::Null).await
+    }
+
+    /// Make an RPC request with params to the proxy
+    async fn rpc_request_with_params(&self, method: &str, params: Value) -> Result<Value> {
+        let mut request = serde_json::json!({
             "jsonrpc": "2.0",
             "method": method,
             "id": 1
         });
+        if !params.is_null() {
+            request["params"] = params;
+        }
 
         debug!("Making RPC request to {}: {}", self.proxy_url, method);
 
@@ -99,6 +110,29 @@ impl RemoteProxyClient {
         self.rpc_request("edb_request_metrics").await
     }
 
+    /// Get the most recently accessed cache entries matching a search
+    pub async fn get_cache_entries(&self, limit: usize, search: &CacheSearch) -> Result<Value> {
+        let mut query = serde_json::json!({"limit": limit});
+        if let Some(method) = &search.method {
+            query["method"] = Value::from(method.as_str());
+        }
+        if !search.addresses.is_empty() {
+            query["address"] = Value::from(search.addresses.join(","));
+        }
+        self.rpc_request_with_params("edb_cache_entries", serde_json::json!([query])).await
+    }
+
+    /// Pin or unpin a cache entry against eviction
+    pub async fn set_cache_entry_pinned(&self, key: &str, pinned: bool) -> Result<Value> {
+        let method = if pinned { "edb_cache_pin" } else { "edb_cache_unpin" };
+        self.rpc_request_with_params(method, serde_json::json!([key])).await
+    }
+
+    /// Delete a cache entry
+    pub async fn delete_cache_entry(&self, key: &str) -> Result<Value> {
+        self.rpc_request_with_params("edb_cache_delete", serde_json::json!([key])).await
+    }
+
     /// Check if proxy is reachable
     pub async fn ping(&self) -> Result<Value> {
         self.rpc_request("edb_ping").await
@@ -153,6 +187,77 @@ impl RemoteCacheStats {
     }
 }
 
+/// Search of the cache browser, by method and addresses
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheSearch {
+    /// Method of the entries
+    pub method: Option<String>,
+    /// Addresses the entries touch, any of them
+    pub addresses: Vec<String>,
+}
+
+impl CacheSearch {
+    /// Parse a search query: a method name and addresses, separated by spaces
+    pub fn parse(query: &str) -> Self {
+        let mut search = Self::default();
+        for term in query.split_whitespace() {
+            let is_address = term.len() == 42
+                && term.starts_with("0x")
+                && term[2..].chars().all(|c| c.is_ascii_hexdigit());
+            if is_address {
+                search.addresses.push(term.to_lowercase());
+            } else {
+                search.method = Some(term.to_string());
+            }
+        }
+        search
+    }
+
+    /// Whether the search selects every entry
+    pub fn is_empty(&self) -> bool {
+        self.method.is_none() && self.addresses.is_empty()
+    }
+
+    /// Describe the search, as typed
+    pub fn describe(&self) -> String {
+        self.method.iter().chain(&self.addresses).cloned().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Cache entry of a remote proxy server, as listed in the cache browser
+#[derive(Debug, Clone)]
+pub struct RemoteCacheEntry {
+    /// Cache key of the entry
+    pub key: String,
+    /// Method of the cached request
+    pub method: String,
+    /// Params of the cached request, if known
+    pub params: Option<Value>,
+    /// Size of the cached response in bytes
+    pub size: u64,
+    /// Number of cache hits served by the entry
+    pub hits: u64,
+    /// Unix timestamp when the entry was accessed
+    pub accessed_at: u64,
+    /// Whether the entry is pinned against eviction
+    pub pinned: bool,
+}
+
+impl RemoteCacheEntry {
+    /// Convert JSON response from proxy server to a structured cache entry
+    pub fn from_json(value: &Value) -> Result<Self> {
+        Ok(Self {
+            key: value.get("key").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            method: value.get("method").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            params: value.get("params").filter(|v| !v.is_null()).cloned(),
+            size: value.get("size").and_then(|v| v.as_u64()).unwrap_or(0),
+            hits: value.get("hits").and_then(|v| v.as_u64()).unwrap_or(0),
+            accessed_at: value.get("accessed_at").and_then(|v| v.as_u64()).unwrap_or(0),
+            pinned: value.get("pinned").and_then(|v| v.as_bool()).unwrap_or(false),
+        })
+    }
+}
+
 /// Health status and metrics for a remote RPC provider endpoint
 #[derive(Debug, Clone)]
 pub struct RemoteProviderStatus {
@@ -262,12 +367,29 @@ impl RemoteMetricData {
 pub struct RemoteDataFetcher {
     /// Client for communicating with the remote proxy server
     client: RemoteProxyClient,
+    /// Search of the cache entries fetched for the cache browser
+    cache_search: CacheSearch,
 }
 
 impl RemoteDataFetcher {
     /// Create a new data fetcher with the given proxy client
     pub fn new(client: RemoteProxyClient) -> Self {
-        Self { client }
+        Self { client, cache_search: CacheSearch::default() }
+    }
+
+    /// Search of the cache entries fetched for the cache browser
+    pub fn cache_search(&self) -> &CacheSearch {
+        &self.cache_search
+    }
+
+    /// Fetch the cache entries matching a search from now on
+    pub fn set_cache_search(&mut self, search: CacheSearch) {
+        self.cache_search = search;
+    }
+
+    /// Client of the remote proxy, e.g. to manage its cache
+    pub fn client(&self) -> &RemoteProxyClient {
+        &self.client
     }
 
     /// Fetch all data needed for TUI display
@@ -282,6 +404,7 @@ impl RemoteDataFetcher {
             history_result,
             request_metrics_result,
             info_result,
+            cache_entries_result,
         ) = tokio::join!(
             self.client.get_cache_stats(),
             self.client.get_cache_metrics(),
@@ -291,6 +414,7 @@ impl RemoteDataFetcher {
             self.client.get_metrics_history(),
             self.client.get_request_metrics(),
             self.client.get_info(),
+            self.client.get_cache_entries(CACHE_BROWSER_ENTRIES, &self.cache_search),
         );
 
         // Convert results and handle errors gracefully
@@ -334,8 +458,26 @@ impl RemoteDataFetcher {
             })
             .unwrap_or_default();
 
+        let cache_entries_data =
+            cache_entries_result.map_err(|e| warn!("Failed to fetch cache entries: {}", e)).ok();
+        let cache_entries = cache_entries_data
+            .as_ref()
+            .and_then(|v| {
+                v.get("entries").and_then(|e| e.as_array()).map(|arr| {
+                    arr.iter().filter_map(|e| RemoteCacheEntry::from_json(e).ok()).collect()
+                })
+            })
+            .unwrap_or_default();
+        let cache_entries_total = cache_entries_data
+            .as_ref()
+            .and_then(|v| v.get("total"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+
         Ok(RemoteProxyData {
             cache_stats,
+            cache_entries,
+            cache_entries_total,
             providers,
             active_instances,
             metrics_history,
@@ -352,6 +494,10 @@ impl RemoteDataFetcher {
 pub struct RemoteProxyData {
     /// Current cache statistics and configuration, if available
     pub cache_stats: Option<RemoteCacheStats>,
+    /// Most recently accessed cache entries matching the search of the cache browser
+    pub cache_entries: Vec<RemoteCacheEntry>,
+    /// Number of cache entries matching the search, listed or not
+    pub cache_entries_total: usize,
     /// Status and health information for all configured RPC providers
     pub providers: Vec<RemoteProviderStatus>,
     /// List of process IDs for active EDB instances connected to the proxy
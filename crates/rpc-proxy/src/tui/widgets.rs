@@ -836,6 +836,104 @@ impl App {
         self.render_enhanced_cache_details(f, chunks[1]);
     }
 
+    pub fn render_browser(&mut self, f: &mut Frame<'_>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Search bar
+                Constraint::Min(0),    // Cache entries
+            ])
+            .split(area);
+
+        let search = if let Some(input) = &self.search_input {
+            Paragraph::new(Line::from(vec![
+                Span::raw(input.clone()),
+                Span::styled("█", Style::default().fg(Color::Yellow)),
+            ]))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Yellow))
+                    .title("Search (method and/or addresses)"),
+            )
+        } else {
+            let search = self.fetcher.cache_search();
+            let query =
+                if search.is_empty() { "all entries".to_string() } else { search.describe() };
+            Paragraph::new(format!(
+                "{} — {} of {} entries",
+                query,
+                self.cache_entries.len(),
+                self.cache_entries_total
+            ))
+            .block(Block::default().borders(Borders::ALL).title("Search (/)"))
+        };
+        f.render_widget(search, chunks[0]);
+
+        // Browser of the cache entries, to pin and delete them
+        self.render_cache_entries(f, chunks[1]);
+    }
+
+    fn render_cache_entries(&self, f: &mut Frame<'_>, area: Rect) {
+        let title = "Cache Entries (/: search, p: pin/unpin, d: delete)";
+        if self.cache_entries.is_empty() {
+            let empty = Paragraph::new("No cache entries")
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .alignment(Alignment::Center);
+            f.render_widget(empty, area);
+            return;
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let rows: Vec<Row<'_>> = self
+            .cache_entries
+            .iter()
+            .map(|entry| {
+                let params = entry
+                    .params
+                    .as_ref()
+                    .map(|params| params.to_string())
+                    .unwrap_or_else(|| entry.key.clone());
+                Row::new(vec![
+                    Cell::from(if entry.pinned { "📌" } else { "" }),
+                    Cell::from(entry.method.clone()),
+                    Cell::from(params),
+                    Cell::from(entry.hits.to_string()),
+                    Cell::from(format!("{}B", entry.size)),
+                    Cell::from(format!("{}s ago", now.saturating_sub(entry.accessed_at))),
+                ])
+                .style(if entry.pinned {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default()
+                })
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            vec![
+                Constraint::Length(2),  // Pinned
+                Constraint::Length(24), // Method
+                Constraint::Min(20),    // Params
+                Constraint::Length(6),  // Hits
+                Constraint::Length(9),  // Size
+                Constraint::Length(10), // Accessed
+            ],
+        )
+        .header(
+            Row::new(vec!["", "Method", "Params", "Hits", "Size", "Accessed"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+        let mut table_state = ratatui::widgets::TableState::default();
+        table_state.select(Some(self.selected_cache_entry));
+
+        f.render_stateful_widget(table, area, &mut table_state);
+    }
+
     fn render_cache_stats(&self, f: &mut Frame<'_>, area: Rect) {
         if let Some(stats) = &self.cache_stats {
             let chunks = Layout::default()
@@ -1218,107 +1316,91 @@ impl App {
     }
 
     fn render_method_details(&self, f: &mut Frame<'_>, area: Rect) {
-        if let Some(metrics) = &self.cache_metrics {
-            if let Some(method_stats) = metrics.get("method_stats").and_then(|v| v.as_object()) {
-                // Sort methods by total requests (hits + misses) descending
-                let mut method_list: Vec<_> = method_stats.iter().collect();
-                method_list.sort_by(|a, b| {
-                    let requests_a = a.1.get("hits").and_then(|v| v.as_u64()).unwrap_or(0)
-                        + a.1.get("misses").and_then(|v| v.as_u64()).unwrap_or(0);
-                    let requests_b = b.1.get("hits").and_then(|v| v.as_u64()).unwrap_or(0)
-                        + b.1.get("misses").and_then(|v| v.as_u64()).unwrap_or(0);
-                    requests_b.cmp(&requests_a)
-                });
-
-                let mut details = vec![
-                    Line::from(vec![Span::styled(
-                        "Method-Level Cache Statistics",
-                        Style::default().add_modifier(Modifier::BOLD),
-                    )]),
-                    Line::from(""),
-                    Line::from(vec![
-                        Span::styled("Method", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw("                 "),
-                        Span::styled("Requests", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw("  "),
-                        Span::styled("Hits", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw("     "),
-                        Span::styled("Misses", Style::default().add_modifier(Modifier::BOLD)),
-                        Span::raw("   "),
-                        Span::styled("Hit Rate", Style::default().add_modifier(Modifier::BOLD)),
-                    ]),
-                    Line::from("─".repeat(80)),
-                ];
-
-                for (method, stats) in method_list.iter().take(15) {
-                    let hits = stats.get("hits").and_then(|v| v.as_u64()).unwrap_or(0);
-                    let misses = stats.get("misses").and_then(|v| v.as_u64()).unwrap_or(0);
-                    let total = hits + misses;
-                    let hit_rate = if total > 0 { hits as f64 / total as f64 * 100.0 } else { 0.0 };
-
-                    let method_display = if method.len() > 25 {
-                        format!("{}...", &method[..22])
-                    } else {
-                        method.to_string()
-                    };
-
-                    let hit_rate_color = if hit_rate > 80.0 {
-                        Color::Green
-                    } else if hit_rate > 50.0 {
-                        Color::Yellow
-                    } else {
-                        Color::Red
-                    };
-
-                    details.push(Line::from(vec![
-                        Span::raw(format!("{method_display:<25}")),
-                        Span::raw(format!("{total:>8}")),
-                        Span::raw(format!("{hits:>8}")),
-                        Span::raw(format!("{misses:>8}")),
-                        Span::raw("  "),
-                        Span::styled(
-                            format!("{hit_rate:>6.1}%"),
-                            Style::default().fg(hit_rate_color),
-                        ),
-                    ]));
-                }
-
-                if method_list.len() > 15 {
-                    details.push(Line::from(""));
-                    details.push(Line::from(vec![Span::styled(
-                        format!(
-                            "... and {} more methods (scroll to see all)",
-                            method_list.len() - 15
-                        ),
-                        Style::default().fg(Color::Gray),
-                    )]));
-                }
-
-                let details_widget = Paragraph::new(details)
-                    .block(
-                        Block::default().borders(Borders::ALL).title("Method Performance Details"),
-                    )
-                    .wrap(Wrap { trim: false })
-                    .scroll((self.methods_scroll as u16, 0));
-
-                f.render_widget(details_widget, area);
-            } else {
-                let empty = Paragraph::new(vec![
+        let title = format!("Method Breakdown (s: sort by {})", self.method_sort.label());
+        let methods = self.method_breakdown();
+        if methods.is_empty() {
+            let message = if self.cache_metrics.is_some() {
+                vec![
                     Line::from("No detailed method statistics available."),
                     Line::from(""),
-                    Line::from("Method-level cache statistics will appear here"),
+                    Line::from("Request counts and latency per method will appear here"),
                     Line::from("once RPC requests have been processed."),
-                ])
-                .block(Block::default().borders(Borders::ALL).title("Method Performance Details"))
-                .alignment(Alignment::Center);
-                f.render_widget(empty, area);
-            }
-        } else {
-            let empty = Paragraph::new("Loading method details...")
-                .block(Block::default().borders(Borders::ALL).title("Method Performance Details"))
+                ]
+            } else {
+                vec![Line::from("Loading method details...")]
+            };
+            let empty = Paragraph::new(message)
+                .block(Block::default().borders(Borders::ALL).title(title))
                 .alignment(Alignment::Center);
             f.render_widget(empty, area);
+            return;
         }
+
+        let rows: Vec<Row<'_>> = methods
+            .iter()
+            .map(|method| {
+                let hit_rate = if method.requests > 0 {
+                    method.hits as f64 / method.requests as f64 * 100.0
+                } else {
+                    0.0
+                };
+                let hit_rate_color = if hit_rate > 80.0 {
+                    Color::Green
+                } else if hit_rate > 50.0 {
+                    Color::Yellow
+                } else {
+                    Color::Red
+                };
+                let errors_style = if method.errors > 0 {
+                    Style::default().fg(Color::Red)
+                } else {
+                    Style::default()
+                };
+
+                Row::new(vec![
+                    Cell::from(method.method.clone()),
+                    Cell::from(method.requests.to_string()),
+                    Cell::from(method.hits.to_string()),
+                    Cell::from(method.upstream.to_string()),
+                    Cell::from(format!("{hit_rate:.1}%"))
+                        .style(Style::default().fg(hit_rate_color)),
+                    Cell::from(format!("{:.1}ms", method.avg_response_time_ms)),
+                    Cell::from(method.errors.to_string()).style(errors_style),
+                ])
+            })
+            .collect();
+
+        let table = Table::new(
+            rows,
+            vec![
+                Constraint::Min(24),    // Method
+                Constraint::Length(10), // Requests
+                Constraint::Length(10), // Hits
+                Constraint::Length(10), // Upstream
+                Constraint::Length(9),  // Hit rate
+                Constraint::Length(12), // Avg latency
+                Constraint::Length(8),  // Errors
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "Method",
+                "Requests",
+                "Hits",
+                "Upstream",
+                "Hit Rate",
+                "Avg Latency",
+                "Errors",
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .row_highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+        let mut table_state = ratatui::widgets::TableState::default();
+        table_state.select(Some(self.methods_scroll));
+
+        f.render_stateful_widget(table, area, &mut table_state);
     }
 
     pub fn render_instances(&mut self, f: &mut Frame<'_>, area: Rect) {
@@ -0,0 +1,113 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Cache warming
+//!
+//! Prefetches through the proxy everything an EDB session on a transaction requests, so
+//! that a session started later is served from the cache. The chain is forked and the
+//! transactions of the block are replayed exactly as EDB does, which fetches the block,
+//! the transactions, and the state (balances, nonces, code, storage) they read; the
+//! target transaction is then executed to fetch the state it reads, and the receipts of
+//! the block are fetched as well. Contract sources, fetched from Etherscan, are not warmed.
+
+use alloy_primitives::TxHash;
+use alloy_provider::{Provider, ProviderBuilder};
+use alloy_rpc_types::{BlockId, BlockNumberOrTag};
+use edb_common::{fork_and_prepare, ForkResult};
+use eyre::{eyre, Result};
+use revm::{ExecuteEvm, MainBuilder};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// What to warm the cache for
+#[derive(Debug, Clone, Copy)]
+pub enum WarmTarget {
+    /// A session on a transaction
+    Transaction(TxHash),
+    /// Sessions on any transaction of a block
+    Block(u64),
+}
+
+/// Outcome of warming the cache
+#[derive(Debug, Clone)]
+pub struct WarmSummary {
+    /// Transaction replayed to warm the cache
+    pub tx_hash: TxHash,
+    /// Block of the transaction
+    pub block_number: u64,
+    /// Number of receipts fetched
+    pub receipts: usize,
+    /// Time spent warming the cache
+    pub elapsed: Duration,
+}
+
+/// Prefetch through a proxy what an EDB session on the target requests
+///
+/// Warming a block replays up to its last transaction, which covers the state read by
+/// every transaction of the block.
+///
+/// # Arguments
+/// * `proxy_url` - URL of the proxy whose cache is warmed
+/// * `target` - Transaction or block a later session is on
+/// * `quick` - Skip the preceding transactions of the block, as `edb --quick` does
+///
+/// # Returns
+/// A summary of what was warmed
+pub async fn warm(proxy_url: &str, target: WarmTarget, quick: bool) -> Result<WarmSummary> {
+    let started = Instant::now();
+    let provider = ProviderBuilder::new().connect(proxy_url).await?;
+
+    let tx_hash = match target {
+        WarmTarget::Transaction(tx_hash) => tx_hash,
+        WarmTarget::Block(number) => {
+            let block = provider
+                .get_block_by_number(BlockNumberOrTag::Number(number))
+                .await?
+                .ok_or_else(|| eyre!("Block {} not found", number))?;
+            block
+                .transactions
+                .hashes()
+                .last()
+                .ok_or_else(|| eyre!("Block {} has no transactions to warm", number))?
+        }
+    };
+
+    // Fetches the block, the transactions, and the state read by the preceding transactions
+    info!("Warming the cache for transaction {:?}", tx_hash);
+    let ForkResult { fork_info, context, target_tx_env, .. } =
+        fork_and_prepare(proxy_url, tx_hash, quick).await?;
+
+    // Fetches the state read by the target transaction itself
+    let mut evm = context.build_mainnet();
+    if let Err(e) = evm.transact(target_tx_env) {
+        warn!(
+            "Failed to execute transaction {:?}, its state is partially warmed: {:?}",
+            tx_hash, e
+        );
+    }
+
+    let receipts = provider
+        .get_block_receipts(BlockId::number(fork_info.block_number))
+        .await?
+        .map_or(0, |receipts| receipts.len());
+
+    Ok(WarmSummary {
+        tx_hash,
+        block_number: fork_info.block_number,
+        receipts,
+        elapsed: started.elapsed(),
+    })
+}
@@ -0,0 +1,305 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! WebSocket upstream providers
+//!
+//! Upstream URLs starting with `ws://` or `wss://` are served over a small pool of
+//! WebSocket connections per provider. Requests are multiplexed on each connection: every
+//! forwarded request gets an id unique to its connection, and the response carrying that
+//! id is routed back to the caller with the caller's original id restored. Connections
+//! that close are reopened by the next request.
+
+use eyre::{bail, eyre, Result};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{debug, warn};
+
+/// Number of connections opened to each WebSocket provider
+const WS_POOL_SIZE: usize = 4;
+
+/// Timeout for opening a WebSocket connection
+const WS_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for a response to a request sent over WebSocket
+const WS_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Requests waiting for their response, by connection-unique id
+type PendingRequests = Arc<Mutex<HashMap<u64, oneshot::Sender<Value>>>>;
+
+/// Whether an upstream URL is served over WebSocket
+pub fn is_ws_url(url: &str) -> bool {
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
+
+/// A WebSocket connection multiplexing concurrent requests
+struct WsConnection {
+    /// Messages to write to the socket
+    outgoing: mpsc::UnboundedSender<Message>,
+    /// Requests waiting for their response
+    pending: PendingRequests,
+    /// Id of the next request sent over the connection
+    next_id: AtomicU64,
+    /// Whether the socket was closed by the provider or failed
+    closed: Arc<AtomicBool>,
+}
+
+impl WsConnection {
+    /// Open a connection and spawn the tasks writing and reading its socket
+    async fn connect(url: &str) -> Result<Self> {
+        let (stream, _) = tokio::time::timeout(WS_CONNECT_TIMEOUT, connect_async(url))
+            .await
+            .map_err(|_| eyre!("Timed out connecting to {url}"))??;
+        let (mut sink, mut source) = stream.split();
+
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<Message>();
+        let pending = PendingRequests::default();
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let writer_url = url.to_string();
+        tokio::spawn(async move {
+            while let Some(message) = outgoing_rx.recv().await {
+                if let Err(e) = sink.send(message).await {
+                    debug!("Failed to write to {}: {}", writer_url, e);
+                    break;
+                }
+            }
+            let _ = sink.close().await;
+        });
+
+        let reader_url = url.to_string();
+        let reader_pending = pending.clone();
+        let reader_closed = closed.clone();
+        tokio::spawn(async move {
+            while let Some(message) = source.next().await {
+                let text = match message {
+                    Ok(Message::Text(text)) => text.to_string(),
+                    Ok(Message::Binary(bytes)) => String::from_utf8_lossy(&bytes).into_owned(),
+                    Ok(Message::Close(_)) => break,
+                    Ok(_) => continue,
+                    Err(e) => {
+                        debug!("WebSocket connection to {} failed: {}", reader_url, e);
+                        break;
+                    }
+                };
+
+                match serde_json::from_str::<Value>(&text) {
+                    Ok(response) => route_response(&reader_pending, response),
+                    Err(e) => warn!("Invalid JSON message from {}: {}", reader_url, e),
+                }
+            }
+
+            debug!("WebSocket connection to {} closed", reader_url);
+            reader_closed.store(true, Ordering::Release);
+            // Dropping the senders fails the requests still waiting on this connection
+            reader_pending.lock().unwrap().clear();
+        });
+
+        Ok(Self { outgoing, pending, next_id: AtomicU64::new(1), closed })
+    }
+
+    /// Whether the connection can no longer be used
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire) || self.outgoing.is_closed()
+    }
+
+    /// Send a request and wait for its response
+    async fn request(&self, request: &Value) -> Result<Value> {
+        let mut request = request.clone();
+        let Some(object) = request.as_object_mut() else {
+            bail!("Only single JSON-RPC requests can be sent over WebSocket");
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let original_id = object.insert("id".to_string(), id.into()).unwrap_or(Value::Null);
+
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        if self.is_closed()
+            || self.outgoing.send(Message::Text(request.to_string().into())).is_err()
+        {
+            self.pending.lock().unwrap().remove(&id);
+            bail!("WebSocket connection closed");
+        }
+
+        let mut response = match tokio::time::timeout(WS_REQUEST_TIMEOUT, receiver).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => bail!("WebSocket connection closed before the response"),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                bail!("Timed out waiting for the response");
+            }
+        };
+
+        if let Some(object) = response.as_object_mut() {
+            object.insert("id".to_string(), original_id);
+        }
+        Ok(response)
+    }
+}
+
+/// Hand a response over to the request waiting for it
+///
+/// Messages without a known id, such as subscription notifications, are dropped.
+fn route_response(pending: &PendingRequests, response: Value) {
+    let Some(id) = response.get("id").and_then(Value::as_u64) else {
+        debug!("Ignoring WebSocket message without a request id");
+        return;
+    };
+
+    match pending.lock().unwrap().remove(&id) {
+        Some(sender) => {
+            let _ = sender.send(response);
+        }
+        None => debug!("Ignoring response to unknown or expired request {}", id),
+    }
+}
+
+/// The pool of connections to a WebSocket provider
+struct WsUpstream {
+    /// The provider URL
+    url: String,
+    /// Connection slots, opened on first use
+    connections: Vec<tokio::sync::Mutex<Option<Arc<WsConnection>>>>,
+    /// Round-robin counter over the slots
+    next_slot: AtomicUsize,
+}
+
+impl WsUpstream {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            connections: (0..WS_POOL_SIZE).map(|_| tokio::sync::Mutex::new(None)).collect(),
+            next_slot: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get an open connection, (re)connecting the next slot if needed
+    async fn connection(&self) -> Result<Arc<WsConnection>> {
+        let index = self.next_slot.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        let mut slot = self.connections[index].lock().await;
+
+        if let Some(connection) = slot.as_ref().filter(|connection| !connection.is_closed()) {
+            return Ok(connection.clone());
+        }
+
+        debug!("Opening WebSocket connection {} to {}", index, self.url);
+        let connection = Arc::new(WsConnection::connect(&self.url).await?);
+        *slot = Some(connection.clone());
+        Ok(connection)
+    }
+}
+
+/// Pools of WebSocket connections to the upstream providers
+#[derive(Default)]
+pub struct WsPool {
+    /// Connection pool of each provider, by URL
+    upstreams: Mutex<HashMap<String, Arc<WsUpstream>>>,
+}
+
+impl WsPool {
+    /// Send a JSON-RPC request to a WebSocket provider and wait for its response
+    ///
+    /// The response carries the id of the given request, as if it was sent on its own.
+    pub async fn request(&self, url: &str, request: &Value) -> Result<Value> {
+        let upstream = self
+            .upstreams
+            .lock()
+            .unwrap()
+            .entry(url.to_string())
+            .or_insert_with(|| Arc::new(WsUpstream::new(url)))
+            .clone();
+
+        upstream.connection().await?.request(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::ErrorKind;
+    use tokio::net::TcpListener;
+    use tracing::info;
+
+    #[test]
+    fn test_is_ws_url() {
+        assert!(is_ws_url("ws://127.0.0.1:8546"));
+        assert!(is_ws_url("wss://eth-mainnet.example.com/ws"));
+        assert!(!is_ws_url("https://eth.llamarpc.com"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_multiplexed() {
+        edb_common::logging::ensure_test_logging(None);
+
+        let listener = match TcpListener::bind("127.0.0.1:0").await {
+            Ok(listener) => listener,
+            Err(error)
+                if matches!(error.kind(), ErrorKind::PermissionDenied)
+                    || error.raw_os_error() == Some(1) =>
+            {
+                info!("Skipping test because loopback binds are restricted: {error}");
+                return;
+            }
+            Err(error) => panic!("Failed to bind test server: {error}"),
+        };
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        // Answer each batch of two requests in reverse order, echoing the method as result
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+                    let mut received = Vec::new();
+                    while let Some(Ok(Message::Text(text))) = ws.next().await {
+                        received.push(serde_json::from_str::<Value>(&text).unwrap());
+                        if received.len() == 2 {
+                            for request in received.drain(..).rev() {
+                                let response = serde_json::json!({
+                                    "jsonrpc": "2.0",
+                                    "id": request["id"],
+                                    "result": request["method"],
+                                });
+                                ws.send(Message::Text(response.to_string().into())).await.unwrap();
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let upstream = WsUpstream::new(&url);
+        let connection = upstream.connection().await.unwrap();
+
+        let first = serde_json::json!({"jsonrpc": "2.0", "id": 7, "method": "eth_chainId"});
+        let second = serde_json::json!({"jsonrpc": "2.0", "id": "a", "method": "eth_blockNumber"});
+        let (first, second) =
+            futures::future::join(connection.request(&first), connection.request(&second)).await;
+
+        let first = first.unwrap();
+        assert_eq!(first["id"], 7);
+        assert_eq!(first["result"], "eth_chainId");
+        let second = second.unwrap();
+        assert_eq!(second["id"], "a");
+        assert_eq!(second["result"], "eth_blockNumber");
+    }
+}
@@ -26,7 +26,7 @@ use std::{collections::HashMap, io::ErrorKind, net::SocketAddr, time::Duration};
 use tempfile::TempDir;
 use tokio::time::sleep;
 use wiremock::{
-    matchers::{method, path},
+    matchers::{body_partial_json, method, path},
     Mock, MockServer, ResponseTemplate,
 };
 
@@ -648,3 +648,329 @@ async fn test_proxy_invalid_request_handling() {
 
     assert_eq!(response.status(), 400); // Bad Request
 }
+
+#[tokio::test]
+async fn test_multi_chain_routing() {
+    if skip_if_loopback_binds_restricted("test_multi_chain_routing").await {
+        return;
+    }
+
+    let mainnet = MockServer::start().await;
+    let optimism = MockServer::start().await;
+    let temp_dir = TempDir::new().unwrap();
+
+    for (server, chain_id, block) in [(&mainnet, "0x1", "0x100"), (&optimism, "0xa", "0x200")] {
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_chainId"})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": chain_id})),
+            )
+            .mount(server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"method": "eth_blockNumber"})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": block})),
+            )
+            .mount(server)
+            .await;
+    }
+
+    let proxy = ProxyServerBuilder::new()
+        .rpc_urls(vec![mainnet.uri()])
+        .chain_rpc_urls_str(10, &optimism.uri())
+        .cache_dir(temp_dir.path())
+        .build()
+        .await
+        .unwrap();
+    let Some(proxy_addr) = start_proxy_server("test_multi_chain_routing", proxy).await else {
+        return;
+    };
+    let client = Client::new();
+    let request = json!({"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 1});
+
+    // Requests without a chain go to the default chain
+    let body: Value = client
+        .post(format!("http://{proxy_addr}"))
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["result"], "0x100");
+
+    // The chain can be selected by path prefix or header
+    let body: Value = client
+        .post(format!("http://{proxy_addr}/10"))
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["result"], "0x200");
+
+    let body: Value = client
+        .post(format!("http://{proxy_addr}"))
+        .header("X-EDB-Chain", "10")
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["result"], "0x200");
+
+    // Chains without upstreams are rejected
+    let body: Value = client
+        .post(format!("http://{proxy_addr}/42161"))
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["error"]["code"], -32602);
+
+    let response = client
+        .post(format!("http://{proxy_addr}"))
+        .header("X-EDB-Chain", "optimism")
+        .json(&request)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+}
+
+#[tokio::test]
+async fn test_record_and_replay_session() {
+    let Some((_proxy, mock_server, temp_dir)) =
+        create_test_proxy("test_record_and_replay_session", 10).await
+    else {
+        return;
+    };
+    let session_path = temp_dir.path().join("session.json");
+
+    Mock::given(method("POST"))
+        .and(path("/"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": "0x1234"})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    // Record a session against the upstream
+    let recording = ProxyServerBuilder::new()
+        .rpc_urls(vec![mock_server.uri()])
+        .cache_dir(temp_dir.path())
+        .record_session(&session_path)
+        .build()
+        .await
+        .unwrap();
+    let request = json!({"jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": 1});
+    let recorded = recording.rpc_handler.handle_request(request.clone()).await.unwrap();
+    assert_eq!(recorded["result"], "0x1234");
+    recording.session().unwrap().save().unwrap();
+
+    // Replay it without any upstream
+    drop(mock_server);
+    let replay = ProxyServerBuilder::new()
+        .cache_dir(temp_dir.path())
+        .replay_session(&session_path)
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(replay.chain_id, recording.chain_id);
+    let replayed = replay
+        .rpc_handler
+        .handle_request(json!({
+            "jsonrpc": "2.0", "method": "eth_gasPrice", "params": [], "id": "replayed"
+        }))
+        .await
+        .unwrap();
+    assert_eq!(replayed, json!({"jsonrpc": "2.0", "id": "replayed", "result": "0x1234"}));
+
+    let missing = replay
+        .rpc_handler
+        .handle_request(
+            json!({"jsonrpc": "2.0", "method": "eth_blockNumber", "params": [], "id": 2}),
+        )
+        .await
+        .unwrap();
+    assert!(missing.get("error").is_some());
+}
+
+#[tokio::test]
+async fn test_cache_admin_endpoints() {
+    let Some((proxy, mock_server, _temp_dir)) =
+        create_test_proxy("test_cache_admin_endpoints", 10).await
+    else {
+        return;
+    };
+    let Some(proxy_addr) = start_proxy_server("test_cache_admin_endpoints", proxy).await else {
+        return;
+    };
+    let client = Client::new();
+    let proxy_url = format!("http://{proxy_addr}");
+    let address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48";
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({"method": "eth_getCode"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": "0x6080"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let rpc = |method: &str, params: Value| {
+        let request = json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1});
+        let (client, proxy_url) = (client.clone(), proxy_url.clone());
+        async move {
+            let response = client.post(&proxy_url).json(&request).send().await.unwrap();
+            response.json::<Value>().await.unwrap()
+        }
+    };
+
+    rpc("eth_getCode", json!([address, "0x1"])).await;
+
+    // Entries are listed by method and address
+    let listed = rpc("edb_cache_entries", json!([{"method": "eth_getCode"}])).await;
+    assert_eq!(listed["result"]["total"], 1);
+    let key = listed["result"]["entries"][0]["key"].as_str().unwrap().to_string();
+    let other = "0x0000000000000000000000000000000000000001";
+    let listed = rpc("edb_cache_entries", json!([{"address": other}])).await;
+    assert_eq!(listed["result"]["total"], 0);
+
+    let pinned = rpc("edb_cache_pin", json!([key])).await;
+    assert_eq!(pinned["result"]["found"], true);
+    let listed = rpc("edb_cache_entries", json!([{"address": address}])).await;
+    assert_eq!(listed["result"]["entries"][0]["pinned"], true);
+
+    // Poisoned entries are deleted by key
+    let deleted = rpc("edb_cache_delete", json!([key])).await;
+    assert_eq!(deleted["result"]["found"], true);
+    let listed = rpc("edb_cache_entries", json!([])).await;
+    assert_eq!(listed["result"]["total"], 0);
+    assert_eq!(rpc("edb_cache_pin", json!([key])).await["result"]["found"], false);
+}
+
+#[tokio::test]
+async fn test_auth_tokens_and_usage() {
+    let Some((_proxy, mock_server, temp_dir)) =
+        create_test_proxy("test_auth_tokens_and_usage", 10).await
+    else {
+        return;
+    };
+    let tokens_path = temp_dir.path().join("tokens.txt");
+    std::fs::write(&tokens_path, "alice=secret-a\nbob=secret-b\nops=secret-o admin\n").unwrap();
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({"method": "eth_getCode"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": "0x6080"
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({"jsonrpc": "2.0", "id": 1, "result": "0x1"})),
+        )
+        .mount(&mock_server)
+        .await;
+
+    let proxy = ProxyServerBuilder::new()
+        .rpc_urls(vec![mock_server.uri()])
+        .cache_dir(temp_dir.path())
+        .auth_tokens_file(&tokens_path)
+        .token_quota(1)
+        .build()
+        .await
+        .unwrap();
+    let Some(proxy_addr) = start_proxy_server("test_auth_tokens_and_usage", proxy).await else {
+        return;
+    };
+    let client = Client::new();
+    let proxy_url = format!("http://{proxy_addr}");
+    let code = |block: &str| {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "eth_getCode",
+            "params": ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48", block],
+            "id": 1
+        })
+    };
+
+    // Requests without a valid token are rejected
+    let response = client.post(&proxy_url).json(&code("0x1")).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+    let response =
+        client.post(&proxy_url).bearer_auth("secret-c").json(&code("0x1")).send().await.unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+    // Alice's first request goes upstream, the cached response is served over her quota
+    let rpc = |token: &str, request: Value| {
+        let (client, url) = (client.clone(), format!("{proxy_url}/?token={token}"));
+        async move {
+            client.post(&url).json(&request).send().await.unwrap().json::<Value>().await.unwrap()
+        }
+    };
+    assert_eq!(rpc("secret-a", code("0x1")).await["result"], "0x6080");
+    assert_eq!(rpc("secret-a", code("0x1")).await["result"], "0x6080");
+    let over_quota = rpc("secret-a", code("0x2")).await;
+    assert_eq!(over_quota["error"]["code"], -32005);
+
+    // Bob has a quota of his own
+    let response =
+        client.post(&proxy_url).bearer_auth("secret-b").json(&code("0x2")).send().await.unwrap();
+    assert_eq!(response.json::<Value>().await.unwrap()["result"], "0x6080");
+
+    // User tokens only read their own usage
+    let token_usage = json!({"jsonrpc": "2.0", "method": "edb_token_usage", "params": [], "id": 1});
+    let usage = rpc("secret-b", token_usage.clone()).await;
+    let tokens = usage["result"]["tokens"].as_array().unwrap();
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0]["name"], "bob");
+    assert_eq!(tokens[0]["role"], "user");
+    assert_eq!(tokens[0]["upstream_requests"], 1);
+
+    // Admin tokens read the usage of every token
+    let usage = rpc("secret-o", token_usage).await;
+    let tokens = &usage["result"]["tokens"];
+    assert_eq!(tokens[0]["name"], "alice");
+    assert_eq!(tokens[0]["requests"], 3);
+    assert_eq!(tokens[0]["upstream_requests"], 1);
+    assert_eq!(tokens[0]["rejected_requests"], 1);
+    assert_eq!(tokens[1]["name"], "bob");
+    assert_eq!(tokens[1]["upstream_requests"], 1);
+    assert_eq!(tokens[2]["role"], "admin");
+
+    // Only admin tokens manage the proxy
+    for (method, params) in [
+        ("edb_shutdown", json!([])),
+        ("edb_deleteCache", json!(["eth_getCode"])),
+        ("edb_cache_pin", json!(["eth_getCode:1"])),
+        ("edb_cache_unpin", json!(["eth_getCode:1"])),
+        ("edb_cache_delete", json!(["eth_getCode:1"])),
+    ] {
+        let request = json!({"jsonrpc": "2.0", "method": method, "params": params, "id": 1});
+        let response =
+            client.post(&proxy_url).bearer_auth("secret-b").json(&request).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN, "{method}");
+    }
+    let pin =
+        json!({"jsonrpc": "2.0", "method": "edb_cache_pin", "params": ["eth_getCode:1"], "id": 1});
+    assert_eq!(rpc("secret-o", pin).await["result"]["found"], false);
+}
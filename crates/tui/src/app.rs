@@ -19,12 +19,16 @@
 //! This module contains the core application state management and event handling.
 
 use crate::data::DataManager;
-use crate::layout::{LayoutConfig, LayoutManager, LayoutType};
+use crate::layout::{
+    LayoutConfig, LayoutManager, LayoutSettings, LayoutType, HORIZONTAL_SPLIT_RANGE,
+    VERTICAL_SPLIT_RANGE,
+};
 use crate::panels::{
-    CodePanel, DisplayPanel, EventResponse, HelpOverlay, Panel, PanelTr, PanelType, TerminalPanel,
-    TracePanel,
+    CodePanel, CommandPalette, DisplayPanel, EventResponse, EventsPanel, HelpOverlay,
+    PaletteAction, PaletteResponse, Panel, PanelTr, PanelType, TerminalPanel, TracePanel,
 };
 use crate::rpc::RpcClient;
+use crate::ui::clipboard;
 use crossterm::event::{
     DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
     MouseButton, MouseEvent, MouseEventKind,
@@ -56,6 +60,24 @@ pub enum ResizeDirection {
     Down,
 }
 
+/// Panel boundary dragged with the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SplitBoundary {
+    /// Boundary between the left and right panels (full layout)
+    Vertical,
+    /// Boundary between the top and bottom panels
+    Horizontal,
+}
+
+/// First key of a two-key mark command, waiting for the mark letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkCommand {
+    /// `m<letter>`: mark the current snapshot
+    Set,
+    /// `'<letter>`: go to a marked snapshot
+    Jump,
+}
+
 /// Type of popup message to display
 #[derive(Debug, Clone)]
 pub enum PopupType {
@@ -159,9 +181,9 @@ pub struct App {
     panels: HashMap<PanelType, Panel>,
     /// Whether the application should exit
     should_exit: bool,
-    /// Main panel type for compact layout (Trace/Code/Display cycle)
+    /// Main panel type for compact layout (Trace/Code/Display/Events cycle)
     compact_main_panel: PanelType,
-    /// Left panel type for full layout (Code/Trace toggle)
+    /// Left panel type for full layout (Trace/Code/Events cycle)
     full_left_panel: PanelType,
     /// RPC connection status and health monitoring
     connection_status: ConnectionStatus,
@@ -170,25 +192,38 @@ pub struct App {
     /// Panel resize ratios
     vertical_split: u16, // Left panel width % (default: 50)
     horizontal_split: u16, // Top panels height % (default: 60)
+    /// Panel boundary being dragged with the mouse
+    dragged_boundary: Option<SplitBoundary>,
     /// Help overlay
     help_overlay: HelpOverlay,
     /// Whether to show help overlay
     show_help: bool,
     /// Popup message (error or notification)
     popup: Option<PopupType>,
+    /// Command palette, if open
+    command_palette: Option<CommandPalette>,
+    /// Mark command waiting for its letter
+    pending_mark: Option<MarkCommand>,
+    /// Whether we already looked for the revert of the transaction on startup
+    revert_focused: bool,
+    /// Summary of the joined `edb server` session, if any
+    session_status: Option<String>,
 }
 
 impl App {
     /// Create a new application instance
     pub async fn new(rpc_client: Arc<RpcClient>, config: LayoutConfig) -> Result<Self> {
-        let layout_manager = LayoutManager::new();
-        let current_panel = PanelType::Trace;
+        // Restore the panel sizes and visible panels of the last session
+        let layout_manager = LayoutManager::load();
+        let layout = layout_manager.settings();
+        let current_panel = layout.left_panel;
 
         // Initialize panels without managers (they will receive DataManager as parameter)
         let mut panels: HashMap<PanelType, Panel> = HashMap::new();
         panels.insert(PanelType::Trace, Panel::Trace(TracePanel::new()));
         panels.insert(PanelType::Code, Panel::Code(CodePanel::new()));
         panels.insert(PanelType::Display, Panel::Display(DisplayPanel::new()));
+        panels.insert(PanelType::Events, Panel::Events(EventsPanel::new()));
         panels.insert(PanelType::Terminal, Panel::Terminal(TerminalPanel::new()));
 
         let popup = if config.enable_mouse {
@@ -210,15 +245,20 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             current_panel,
             panels,
             should_exit: false,
-            compact_main_panel: PanelType::Trace, // Default to Trace in compact mode
-            full_left_panel: PanelType::Trace,    // Default to Trace in full layout left side
+            compact_main_panel: layout.main_panel,
+            full_left_panel: layout.left_panel,
             connection_status: ConnectionStatus::new(),
             last_health_check: None,
-            vertical_split: 50,   // 50% left panel width
-            horizontal_split: 50, // 50% top panels height
+            vertical_split: layout.vertical_split,
+            horizontal_split: layout.horizontal_split,
+            dragged_boundary: None,
             help_overlay: HelpOverlay::new(),
             show_help: false,
             popup,
+            command_palette: None,
+            pending_mark: None,
+            revert_focused: false,
+            session_status: None,
             mouse_enabled: config.enable_mouse,
         })
     }
@@ -229,15 +269,49 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
         let area = frame.area();
         self.layout_manager.update_size(area.width, area.height);
 
+        // Record navigation done from any panel in the session being recorded
+        if let Some(Panel::Terminal(terminal)) = self.panels.get_mut(&PanelType::Terminal) {
+            terminal.observe_navigation(data_manager);
+        }
+
+        // On the first frame, focus the restored main panel if the terminal fits the compact layout
+        if !self.revert_focused && self.layout_manager.layout_type() == LayoutType::Compact {
+            self.current_panel = self.compact_main_panel;
+        }
+
         match self.layout_manager.layout_type() {
             LayoutType::Full => self.render_full_layout(frame, area, data_manager),
             LayoutType::Compact => self.render_compact_layout(frame, area, data_manager),
             LayoutType::Mobile => self.render_mobile_layout(frame, area, data_manager),
         }
 
+        // Start where a reverted transaction reverted, once the panels know their size
+        if !self.revert_focused {
+            self.revert_focused = true;
+            if !data_manager.execution.get_revert_path().is_empty() {
+                self.focus_revert(data_manager);
+            }
+        }
+
         // Render help overlay if active
         if self.show_help {
-            self.help_overlay.render(frame, self.layout_manager.layout_type(), data_manager);
+            let entries = self
+                .panels
+                .get(&self.current_panel)
+                .map(|panel| panel.help_entries())
+                .unwrap_or_default();
+            self.help_overlay.render(
+                frame,
+                self.layout_manager.layout_type(),
+                self.current_panel,
+                &entries,
+                data_manager,
+            );
+        }
+
+        // Render command palette if open
+        if let Some(palette) = &self.command_palette {
+            palette.render(frame, self.current_panel, data_manager);
         }
 
         // Render error popup if active
@@ -251,9 +325,29 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
         // Perform periodic health checks
         self.check_connection_health().await;
 
+        // Persist the layout once the user is done arranging it
+        if self.dragged_boundary.is_none() {
+            self.layout_manager.update_settings(self.layout_settings());
+        }
+
         Ok(())
     }
 
+    /// Show the summary of the joined `edb server` session in the status bar
+    pub fn set_session_status(&mut self, status: Option<String>) {
+        self.session_status = status;
+    }
+
+    /// Get the current panel sizes and visible panels
+    fn layout_settings(&self) -> LayoutSettings {
+        LayoutSettings {
+            vertical_split: self.vertical_split,
+            horizontal_split: self.horizontal_split,
+            left_panel: self.full_left_panel,
+            main_panel: self.compact_main_panel,
+        }
+    }
+
     /// Perform periodic health check on RPC connection
     async fn check_connection_health(&mut self) {
         let now = Instant::now();
@@ -286,7 +380,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
         }
     }
 
-    /// Render the full 3-panel layout (Code/Trace toggle on left, Display and Terminal on right)
+    /// Render the full 3-panel layout (Trace/Code/Events cycle on left, Display and Terminal on right)
     fn render_full_layout(
         &mut self,
         frame: &mut Frame<'_>,
@@ -303,7 +397,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             .split(area);
 
         // Render status bar
-        self.render_status_bar(frame, layout_chunks[0]);
+        self.render_status_bar(frame, layout_chunks[0], data_manager);
 
         // Split main content area horizontally: left (Code/Trace) | right (Display/Terminal)
         let main_chunks = Layout::default()
@@ -357,7 +451,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             .split(area);
 
         // Render status bar
-        self.render_status_bar(frame, layout_chunks[0]);
+        self.render_status_bar(frame, layout_chunks[0], data_manager);
 
         // Split main content for 2-panel layout: Main (cycles Trace/Code/Display) + Terminal (fixed)
         let chunks = Layout::default()
@@ -399,7 +493,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             .split(area);
 
         // Render status bar
-        self.render_status_bar(frame, layout_chunks[0]);
+        self.render_status_bar(frame, layout_chunks[0], data_manager);
 
         // Set focus for panels
         self.update_panel_focus();
@@ -411,7 +505,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
     }
 
     /// Render the status bar at the top of the screen
-    fn render_status_bar(&mut self, frame: &mut Frame<'_>, area: Rect) {
+    fn render_status_bar(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
         use ratatui::{
             style::{Color, Style},
             text::{Line, Span},
@@ -452,6 +546,21 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             status_spans.push(Span::styled(spinner_text, Style::default().fg(Color::Cyan)));
         }
 
+        // Revert reason of a reverted transaction
+        if let Some(reason) = TracePanel::revert_reason(dm) {
+            status_spans.push(Span::raw(" | "));
+            status_spans.push(Span::styled(
+                format!("↩ Reverted: {reason}"),
+                Style::default().fg(Color::LightRed),
+            ));
+        }
+
+        // Shared cursor of the joined session
+        if let Some(session) = &self.session_status {
+            status_spans.push(Span::raw(" | "));
+            status_spans.push(Span::styled(session.clone(), Style::default().fg(Color::Magenta)));
+        }
+
         // Mouse mode indicator
         let mouse_indicator = if self.mouse_enabled { "ON" } else { "OFF" };
         let mouse_color = if self.mouse_enabled { Color::Green } else { Color::Gray };
@@ -632,32 +741,62 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             }
         }
 
-        // If help is showing, handle help-specific keys
+        // If help is showing, it receives every key
         if self.show_help {
-            match key.code {
-                KeyCode::Char('?') | KeyCode::Esc => {
-                    self.show_help = false;
-                    self.help_overlay.reset_scroll();
-                    return Ok(EventResponse::Handled);
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    self.help_overlay.scroll_down(1);
-                    return Ok(EventResponse::Handled);
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    self.help_overlay.scroll_up(1);
-                    return Ok(EventResponse::Handled);
-                }
-                KeyCode::PageDown => {
-                    self.help_overlay.scroll_down(10);
-                    return Ok(EventResponse::Handled);
+            if self.help_overlay.handle_key_event(key) {
+                self.show_help = false;
+            }
+            return Ok(EventResponse::Handled);
+        }
+
+        // If the command palette is open, it receives every key
+        if let Some(palette) = self.command_palette.as_mut() {
+            return match palette.handle_key_event(key) {
+                PaletteResponse::Pending => Ok(EventResponse::Handled),
+                PaletteResponse::Close => {
+                    self.command_palette = None;
+                    Ok(EventResponse::Handled)
                 }
-                KeyCode::PageUp => {
-                    self.help_overlay.scroll_up(10);
-                    return Ok(EventResponse::Handled);
+                PaletteResponse::Run(action) => {
+                    self.command_palette = None;
+                    self.run_palette_action(action, data_manager)
                 }
-                _ => return Ok(EventResponse::Handled), // Consume all other keys when help is open
+            };
+        }
+
+        // Panels editing text (e.g., the inline watch editor) receive every key
+        if let Some(panel) = self.panels.get_mut(&self.current_panel) {
+            if panel.is_capturing_input() {
+                return match panel.handle_key_event(key, data_manager) {
+                    Ok(response) => Ok(response),
+                    Err(e) => {
+                        self.popup = Some(PopupType::Error(format!("{e}")));
+                        Ok(EventResponse::Handled)
+                    }
+                };
+            }
+        }
+
+        // Remap the key to the built-in key it is bound to in the config file
+        let Some(key) = data_manager.keymap.translate(self.current_panel, key) else {
+            return Ok(EventResponse::Handled);
+        };
+
+        self.handle_builtin_key(key, data_manager)
+    }
+
+    /// Handle a built-in key, after the user's keybindings have been applied
+    fn handle_builtin_key(
+        &mut self,
+        key: KeyEvent,
+        data_manager: &mut DataManager,
+    ) -> Result<EventResponse> {
+        // Second key of `m<letter>` or `'<letter>`; any other key cancels the mark command
+        if let Some(command) = self.pending_mark.take() {
+            if let KeyCode::Char(mark) = key.code {
+                self.run_mark_command(command, mark, data_manager);
             }
+            return Ok(EventResponse::Handled);
         }
 
         // First, handle global keys
@@ -671,7 +810,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             KeyCode::Char('?') => {
                 // Open help overlay with '?'
                 self.show_help = true;
-                self.help_overlay.reset_scroll();
+                self.help_overlay.reset();
                 Ok(EventResponse::Handled)
             }
             KeyCode::Char('\\') => {
@@ -705,19 +844,21 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
             KeyCode::Char(' ') => {
                 match self.layout_manager.layout_type() {
                     LayoutType::Full => {
-                        // In full layout, Space toggles between Code and Trace only when focused on the left panel
-                        if self.current_panel == PanelType::Code
-                            || self.current_panel == PanelType::Trace
-                        {
+                        // In full layout, Space cycles through Trace → Code → Events only when focused on the left panel
+                        if matches!(
+                            self.current_panel,
+                            PanelType::Code | PanelType::Trace | PanelType::Events
+                        ) {
                             self.full_left_panel = match self.full_left_panel {
-                                PanelType::Code => PanelType::Trace,
                                 PanelType::Trace => PanelType::Code,
+                                PanelType::Code => PanelType::Events,
+                                PanelType::Events => PanelType::Trace,
                                 _ => PanelType::Code, // Fallback (shouldn't happen)
                             };
                             self.current_panel = self.full_left_panel;
                             Ok(EventResponse::Handled)
                         } else {
-                            // Forward to current panel if not on Code/Trace
+                            // Forward to current panel if not on the left panel
                             if let Some(panel) = self.panels.get_mut(&self.current_panel) {
                                 return match panel.handle_key_event(key, data_manager) {
                                     Ok(response) => Ok(response),
@@ -731,12 +872,13 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                         }
                     }
                     LayoutType::Compact => {
-                        // In compact mode, Space cycles through Trace → Code → Display only when focused on main panel
+                        // In compact mode, Space cycles through Trace → Code → Display → Events only when focused on main panel
                         if self.current_panel != PanelType::Terminal {
                             self.compact_main_panel = match self.compact_main_panel {
                                 PanelType::Trace => PanelType::Code,
                                 PanelType::Code => PanelType::Display,
-                                PanelType::Display => PanelType::Trace,
+                                PanelType::Display => PanelType::Events,
+                                PanelType::Events => PanelType::Trace,
                                 _ => PanelType::Code, // Fallback
                             };
                             self.current_panel = self.compact_main_panel;
@@ -771,6 +913,12 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                 }
             }
             KeyCode::Tab => {
+                // Tab completes the command being typed in the terminal
+                if let Some(Panel::Terminal(terminal)) = self.panels.get_mut(&PanelType::Terminal) {
+                    if self.current_panel == PanelType::Terminal && terminal.wants_tab() {
+                        return terminal.handle_key_event(key, data_manager);
+                    }
+                }
                 self.cycle_panels(false);
                 Ok(EventResponse::Handled)
             }
@@ -805,6 +953,13 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                 self.current_panel = PanelType::Terminal;
                 Ok(EventResponse::Handled)
             }
+            KeyCode::F(5) => {
+                if self.layout_manager.layout_type() != LayoutType::Compact {
+                    self.compact_main_panel = PanelType::Events;
+                }
+                self.current_panel = PanelType::Events;
+                Ok(EventResponse::Handled)
+            }
 
             // Global exit shortcuts
             KeyCode::Char('c')
@@ -840,6 +995,53 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                 // Alt+Q for quick exit
                 Ok(EventResponse::Exit)
             }
+            KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Ctrl+K opens the command palette
+                self.command_palette = Some(CommandPalette::new());
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Ctrl+O goes back in the jump list
+                match data_manager.execution.jump_back() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => self.terminal_message("Already at the oldest jump", false),
+                    Err(e) => self.popup = Some(PopupType::Error(format!("{e}"))),
+                }
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('i') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Ctrl+I goes forward in the jump list (many terminals send Tab instead)
+                match data_manager.execution.jump_forward() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => self.terminal_message("Already at the newest jump", false),
+                    Err(e) => self.popup = Some(PopupType::Error(format!("{e}"))),
+                }
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('m')
+                if self.current_panel != PanelType::Terminal && key.modifiers.is_empty() =>
+            {
+                // m<letter> marks the current snapshot
+                self.pending_mark = Some(MarkCommand::Set);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('\'' | '`')
+                if self.current_panel != PanelType::Terminal && key.modifiers.is_empty() =>
+            {
+                // '<letter> goes to a marked snapshot
+                self.pending_mark = Some(MarkCommand::Jump);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('y') if key.modifiers.is_empty() && !self.is_typing_in_terminal() => {
+                // y copies the selection of the current panel to the clipboard
+                self.yank(data_manager);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                // Ctrl+R toggles between labeled and raw addresses in all panels
+                data_manager.resolver.toggle_raw_addresses();
+                Ok(EventResponse::Handled)
+            }
 
             // Panel boundary resize with Ctrl+Shift+arrow keys
             KeyCode::Left
@@ -901,21 +1103,143 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
         }
     }
 
+    /// Set or go to a mark
+    fn run_mark_command(&mut self, command: MarkCommand, mark: char, dm: &mut DataManager) {
+        let result = match command {
+            MarkCommand::Set => {
+                dm.execution.set_mark(mark).map(|id| format!("Mark '{mark}' set at snapshot {id}"))
+            }
+            MarkCommand::Jump => dm
+                .execution
+                .goto_mark(mark)
+                .map(|id| format!("Went to mark '{mark}' (snapshot {id})")),
+        };
+        match result {
+            Ok(message) => self.terminal_message(&message, false),
+            Err(e) => self.terminal_message(&e.to_string(), true),
+        }
+    }
+
+    /// Whether keys are typed into the terminal input line
+    fn is_typing_in_terminal(&self) -> bool {
+        self.current_panel == PanelType::Terminal
+            && matches!(
+                self.panels.get(&PanelType::Terminal),
+                Some(Panel::Terminal(terminal)) if terminal.is_insert_mode()
+            )
+    }
+
+    /// Copy the selection of the current panel to the clipboard
+    fn yank(&mut self, dm: &mut DataManager) {
+        let Some(yank) = self.panels.get_mut(&self.current_panel).and_then(|panel| panel.yank(dm))
+        else {
+            self.terminal_message("Nothing to copy at the selection", true);
+            return;
+        };
+
+        match clipboard::copy(&yank.text) {
+            Ok(method) => self.terminal_message(
+                &format!("Copied {} to the clipboard ({method})", yank.description),
+                false,
+            ),
+            Err(e) => {
+                self.terminal_message(&format!("Failed to copy {}: {e}", yank.description), true)
+            }
+        }
+    }
+
+    /// Go to where the transaction reverted, selecting the reverting frame in the Trace panel
+    fn focus_revert(&mut self, dm: &mut DataManager) {
+        match dm.execution.goto_revert() {
+            Ok(entry_id) => {
+                if let Some(Panel::Trace(trace)) = self.panels.get_mut(&PanelType::Trace) {
+                    trace.select_entry(entry_id, dm);
+                }
+                self.change_focus(PanelType::Trace);
+                if let Some(reason) = TracePanel::revert_reason(dm) {
+                    self.terminal_message(&format!("Transaction reverted: {reason}"), true);
+                }
+            }
+            Err(e) => self.terminal_message(&format!("Failed to go to the revert: {e}"), true),
+        }
+    }
+
+    /// Print a message in the terminal panel
+    fn terminal_message(&mut self, message: &str, is_error: bool) {
+        if let Some(Panel::Terminal(terminal)) = self.panels.get_mut(&PanelType::Terminal) {
+            if is_error {
+                terminal.add_error(message);
+            } else {
+                terminal.add_system(message);
+            }
+        }
+    }
+
+    /// Run an action picked from the command palette
+    fn run_palette_action(
+        &mut self,
+        action: PaletteAction,
+        data_manager: &mut DataManager,
+    ) -> Result<EventResponse> {
+        match action {
+            PaletteAction::Key(panel, code, modifiers) => {
+                if let Some(panel) = panel {
+                    let function_key = match panel {
+                        PanelType::Trace => 1,
+                        PanelType::Code => 2,
+                        PanelType::Display => 3,
+                        PanelType::Terminal => 4,
+                        PanelType::Events => 5,
+                    };
+                    self.handle_builtin_key(
+                        KeyEvent::new(KeyCode::F(function_key), KeyModifiers::NONE),
+                        data_manager,
+                    )?;
+                }
+                self.handle_builtin_key(KeyEvent::new(code, modifiers), data_manager)
+            }
+            PaletteAction::Command(command) => {
+                let Some(Panel::Terminal(terminal)) = self.panels.get_mut(&PanelType::Terminal)
+                else {
+                    return Ok(EventResponse::NotHandled);
+                };
+                match terminal.run_command(command, data_manager) {
+                    Ok(response) => Ok(response),
+                    Err(e) => {
+                        self.popup = Some(PopupType::Error(format!("{e}")));
+                        Ok(EventResponse::Handled)
+                    }
+                }
+            }
+            PaletteAction::Prefill(command) => {
+                if let Some(Panel::Terminal(terminal)) = self.panels.get_mut(&PanelType::Terminal) {
+                    terminal.prefill_input(command);
+                }
+                self.current_panel = PanelType::Terminal;
+                Ok(EventResponse::Handled)
+            }
+        }
+    }
+
     /// Cycle through panels (Tab key)
     fn cycle_panels(&mut self, reversed: bool) {
         match self.layout_manager.layout_type() {
             LayoutType::Full => {
                 // In Full layout, cycle through the 3 visible panels
-                // The left panel shows either Code or Trace (based on full_left_panel)
+                // The left panel shows Code, Trace, or Events (based on full_left_panel)
                 if !reversed {
                     self.current_panel = match self.current_panel {
-                        PanelType::Code | PanelType::Trace => PanelType::Display,
+                        PanelType::Code | PanelType::Trace | PanelType::Events => {
+                            PanelType::Display
+                        }
                         PanelType::Display => PanelType::Terminal,
                         PanelType::Terminal => self.full_left_panel,
                     };
                 } else {
                     self.current_panel = match self.current_panel {
-                        PanelType::Code | PanelType::Trace => PanelType::Terminal,
+                        PanelType::Code | PanelType::Trace | PanelType::Events => {
+                            PanelType::Terminal
+                        }
                         PanelType::Display => self.full_left_panel,
                         PanelType::Terminal => PanelType::Display,
                     };
@@ -928,12 +1252,13 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                 };
             }
             LayoutType::Mobile => {
-                // Mobile and other layouts cycle through all 4 panels
+                // Mobile and other layouts cycle through all 5 panels
                 if !reversed {
                     self.current_panel = match self.current_panel {
                         PanelType::Trace => PanelType::Code,
                         PanelType::Code => PanelType::Display,
-                        PanelType::Display => PanelType::Terminal,
+                        PanelType::Display => PanelType::Events,
+                        PanelType::Events => PanelType::Terminal,
                         PanelType::Terminal => PanelType::Trace,
                     };
                 } else {
@@ -941,7 +1266,8 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                         PanelType::Trace => PanelType::Terminal,
                         PanelType::Code => PanelType::Trace,
                         PanelType::Display => PanelType::Code,
-                        PanelType::Terminal => PanelType::Display,
+                        PanelType::Events => PanelType::Display,
+                        PanelType::Terminal => PanelType::Events,
                     };
                 }
             }
@@ -955,16 +1281,19 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
 
         match direction {
             ResizeDirection::Left => {
-                self.vertical_split = self.vertical_split.saturating_sub(STEP).max(20);
+                self.vertical_split =
+                    self.vertical_split.saturating_sub(STEP).max(VERTICAL_SPLIT_RANGE.0);
             }
             ResizeDirection::Right => {
-                self.vertical_split = (self.vertical_split + STEP).min(80);
+                self.vertical_split = (self.vertical_split + STEP).min(VERTICAL_SPLIT_RANGE.1);
             }
             ResizeDirection::Up => {
-                self.horizontal_split = self.horizontal_split.saturating_sub(STEP).max(30);
+                self.horizontal_split =
+                    self.horizontal_split.saturating_sub(STEP).max(HORIZONTAL_SPLIT_RANGE.0);
             }
             ResizeDirection::Down => {
-                self.horizontal_split = (self.horizontal_split + STEP).min(80);
+                self.horizontal_split =
+                    (self.horizontal_split + STEP).min(HORIZONTAL_SPLIT_RANGE.1);
             }
         }
 
@@ -1043,11 +1372,22 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
 
         match event.kind {
             MouseEventKind::Down(MouseButton::Left) => {
-                // Handle panel focusing on left click
-                if let Some(panel_type) = self.get_panel_at_position(event.column, event.row) {
+                // Clicking a panel border starts resizing, clicking inside a panel focuses it
+                if let Some(boundary) = self.get_boundary_at_position(event.column, event.row) {
+                    self.dragged_boundary = Some(boundary);
+                } else if let Some(panel_type) = self.get_panel_at_position(event.column, event.row)
+                {
                     self.change_focus(panel_type);
                 }
             }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(boundary) = self.dragged_boundary {
+                    self.drag_boundary(boundary, event.column, event.row);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.dragged_boundary = None;
+            }
             MouseEventKind::ScrollUp => {
                 // Handle scroll up - move selection up
                 if let Some(panel) = self.panels.get_mut(&self.current_panel) {
@@ -1091,6 +1431,55 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
         Ok(())
     }
 
+    /// Get the panel boundary at the given screen position (for mouse resizing)
+    ///
+    /// Both borders along a boundary grab it.
+    fn get_boundary_at_position(&self, column: u16, row: u16) -> Option<SplitBoundary> {
+        let width = self.layout_manager.width();
+        let height = self.layout_manager.height();
+
+        // Skip the status bar (first row)
+        if row == 0 {
+            return None;
+        }
+
+        let content_row = row - 1;
+        let split_row = (height - 1) * self.horizontal_split / 100;
+        let on_split_row = content_row + 1 == split_row || content_row == split_row;
+
+        match self.layout_manager.layout_type() {
+            LayoutType::Full => {
+                let split_col = width * self.vertical_split / 100;
+                if column + 1 == split_col || column == split_col {
+                    Some(SplitBoundary::Vertical)
+                } else if column > split_col && on_split_row {
+                    Some(SplitBoundary::Horizontal)
+                } else {
+                    None
+                }
+            }
+            LayoutType::Compact => on_split_row.then_some(SplitBoundary::Horizontal),
+            LayoutType::Mobile => None,
+        }
+    }
+
+    /// Move a dragged panel boundary to the mouse position
+    fn drag_boundary(&mut self, boundary: SplitBoundary, column: u16, row: u16) {
+        match boundary {
+            SplitBoundary::Vertical => {
+                let width = u32::from(self.layout_manager.width().max(1));
+                let split = (u32::from(column) * 100 / width) as u16;
+                self.vertical_split = split.clamp(VERTICAL_SPLIT_RANGE.0, VERTICAL_SPLIT_RANGE.1);
+            }
+            SplitBoundary::Horizontal => {
+                let height = u32::from(self.layout_manager.height().saturating_sub(1).max(1));
+                let split = (u32::from(row.saturating_sub(1)) * 100 / height) as u16;
+                self.horizontal_split =
+                    split.clamp(HORIZONTAL_SPLIT_RANGE.0, HORIZONTAL_SPLIT_RANGE.1);
+            }
+        }
+    }
+
     /// Get the panel at the given screen position (for mouse click detection)
     fn get_panel_at_position(&self, column: u16, row: u16) -> Option<PanelType> {
         let width = self.layout_manager.width();
@@ -1159,9 +1548,9 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
     pub fn change_focus(&mut self, target_panel: PanelType) {
         match self.layout_manager.layout_type() {
             LayoutType::Full => {
-                // In Full layout, handle Code/Trace visibility
+                // In Full layout, handle Code/Trace/Events visibility
                 match target_panel {
-                    PanelType::Code | PanelType::Trace => {
+                    PanelType::Code | PanelType::Trace | PanelType::Events => {
                         // Make sure the target panel is visible on the left
                         self.full_left_panel = target_panel;
                         self.current_panel = target_panel;
@@ -1179,7 +1568,7 @@ Press '\' to turn off Mouse Mode and re-enable text selection."#;
                         // Terminal is always visible
                         self.current_panel = PanelType::Terminal;
                     }
-                    PanelType::Trace | PanelType::Code | PanelType::Display => {
+                    PanelType::Trace | PanelType::Code | PanelType::Display | PanelType::Events => {
                         // Switch the main panel to show the target
                         self.compact_main_panel = target_panel;
                         self.current_panel = target_panel;
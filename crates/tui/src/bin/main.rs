@@ -20,20 +20,48 @@
 
 use clap::Parser;
 use edb_common::logging;
-use edb_tui::{Config, TuiConfig};
+use edb_common::{RpcClientOptions, RPC_AUTH_TOKEN_ENV};
+use edb_tui::{Config, SessionServerConfig, TuiConfig};
 use eyre::Result;
 use std::path::PathBuf;
 
+/// RPC server URL used when neither `--url` nor the config gives one
+const DEFAULT_RPC_URL: &str = "http://localhost:3030";
+
 /// EDB Terminal User Interface
 #[derive(Debug, Parser)]
 #[command(name = "edb-tui")]
 #[command(about = "Terminal User Interface for EDB debugger", version)]
 struct Args {
-    /// RPC server URL
-    #[arg(long, default_value = "http://localhost:3030")]
-    url: String,
+    /// RPC server URL, e.g., `http://localhost:3030` or `unix:///tmp/edb.sock`
+    ///
+    /// Defaults to `rpc_url` of the config, or `http://localhost:3030`.
+    #[arg(long)]
+    url: Option<String>,
+
+    /// Bearer token of the RPC server, or of `--session-server`
+    #[arg(long, env = RPC_AUTH_TOKEN_ENV, hide_env_values = true)]
+    auth_token: Option<String>,
+
+    /// PEM certificate to trust when the RPC server is served over HTTPS with a
+    /// self-signed certificate
+    #[arg(long)]
+    ca_cert: Option<PathBuf>,
+
+    /// WebSocket URL of an `edb server` to join the session of `--tx` through, e.g.,
+    /// `ws://localhost:9001`
+    #[arg(long, requires = "tx")]
+    session_server: Option<String>,
+
+    /// Transaction whose `edb server` session to join
+    #[arg(long, requires = "session_server")]
+    tx: Option<String>,
+
+    /// Join the `edb server` session as a read-only observer, following its drivers
+    #[arg(long, requires = "session_server")]
+    observe: bool,
 
-    /// Config file path (uses ~/.edb.toml if not specified)
+    /// Config file path (uses ~/.edb.toml, with ./.edb.toml merged over it, if not specified)
     #[arg(long)]
     config: Option<PathBuf>,
 
@@ -61,26 +89,37 @@ async fn main() -> Result<()> {
     // Use stderr so it doesn't interfere with TUI if there are issues
     eprintln!("EDB TUI logs: {}", log_file_path.display());
 
-    // TODO: use the config in TUI
+    // TODO: use the rest of the config in TUI
     // Load configuration
-    let _config = if let Some(config_path) = args.config {
+    let config = if let Some(config_path) = args.config {
         // Load from specified path
         Config::load_from_path(config_path)?
     } else {
-        // Load from default path or create default
+        // Load from default path (with the project's settings) or create default
         Config::load().unwrap_or_default()
     };
+    let url = args.url.or(config.rpc_url).unwrap_or_else(|| DEFAULT_RPC_URL.to_string());
 
     // Create TUI configuration
+    let session_server = args
+        .session_server
+        .zip(args.tx)
+        .map(|(url, tx_hash)| SessionServerConfig { url, tx_hash, observe: args.observe });
     let tui_config = TuiConfig {
-        rpc_url: args.url.clone(),
+        rpc_url: url.clone(),
+        rpc_options: RpcClientOptions {
+            auth_token: args.auth_token,
+            ca_cert: args.ca_cert,
+            timeout: None,
+        },
+        session_server,
         refresh_interval: std::time::Duration::from_millis(args.refresh_interval),
         data_fetch_interval: std::time::Duration::from_millis(args.data_fetch_interval),
         enable_mouse: args.mouse,
     };
 
     tracing::info!("Starting EDB TUI");
-    tracing::info!("Connecting to RPC server at: {}", args.url);
+    tracing::info!("Connecting to RPC server at: {}", url);
 
     // Start the TUI
     match edb_tui::api::start_tui(tui_config).await {
@@ -0,0 +1,264 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Collaborative sessions hosted by `edb server`
+//!
+//! Instead of connecting to an RPC server directly, the TUI can join the session of a
+//! transaction through the WebSocket server of `edb server`. Drivers share a navigation
+//! cursor: every snapshot a driver navigates to is sent to the other clients, which
+//! follow it. Observers follow the cursor too, but their own navigation stays local, and
+//! the RPC server rejects the methods changing the state of the session for them.
+//!
+//! If `edb server` requires a bearer token, the configured token is sent with the
+//! WebSocket handshake. The RPC server of the session is then called with the token the
+//! server hands out when the client attaches.
+
+use crate::data::DataManager;
+use edb_common::{
+    ws_protocol::{ClientRequest, ServerResponse},
+    RpcClientOptions,
+};
+use eyre::{bail, eyre, Result};
+use futures::{SinkExt, StreamExt};
+use tokio::sync::mpsc;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        client::IntoClientRequest,
+        http::{header::AUTHORIZATION, HeaderValue, Uri},
+        Message,
+    },
+};
+use tracing::{debug, info, warn};
+
+/// Session of `edb server` to join
+#[derive(Debug, Clone)]
+pub struct SessionServerConfig {
+    /// WebSocket URL of `edb server`, e.g., `ws://localhost:9001`
+    pub url: String,
+    /// Transaction whose session to join
+    pub tx_hash: String,
+    /// Whether to join as a read-only observer
+    pub observe: bool,
+}
+
+/// A joined session, with the RPC server to debug it through
+pub(crate) struct JoinedSession {
+    /// URL of the RPC server of the session
+    pub(crate) rpc_url: String,
+    /// Bearer token to call the RPC server with
+    pub(crate) rpc_token: Option<String>,
+    /// Shared cursor of the session
+    pub(crate) collaboration: Collaboration,
+}
+
+/// Shared navigation cursor of a joined session
+pub(crate) struct Collaboration {
+    /// Whether this client moves the shared cursor
+    driver: bool,
+    /// Label of this client in the session
+    client: String,
+    /// Requests to send to the server
+    requests: mpsc::UnboundedSender<ClientRequest>,
+    /// Updates received from the server
+    updates: mpsc::UnboundedReceiver<ServerResponse>,
+    /// Snapshot the shared cursor points to, as far as this client knows
+    cursor: Option<usize>,
+    /// Snapshot this client was at when last synchronized
+    local: Option<usize>,
+    /// Label of the client that last moved the cursor
+    moved_by: Option<String>,
+    /// Number of drivers and observers of the session
+    participants: (usize, usize),
+    /// Whether the connection was closed, or taken over by another connection
+    detached: bool,
+}
+
+/// Join the session of a transaction, preparing it if needed
+///
+/// Preparation progress is printed to stderr, since the terminal is not yet taken over
+/// by the TUI.
+pub(crate) async fn join(
+    config: &SessionServerConfig,
+    options: &RpcClientOptions,
+) -> Result<JoinedSession> {
+    let mut handshake = config.url.as_str().into_client_request()?;
+    if let Some(token) = &options.auth_token {
+        handshake
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {token}"))?);
+    }
+    let (socket, _) = connect_async(handshake)
+        .await
+        .map_err(|e| eyre!("Failed to connect to {}: {}", config.url, e))?;
+    let (mut sink, mut stream) = socket.split();
+
+    let tx_hash = config.tx_hash.clone();
+    let request = if config.observe {
+        ClientRequest::Observe { tx_hash }
+    } else {
+        ClientRequest::Replay { tx_hash }
+    };
+    sink.send(Message::text(serde_json::to_string(&request)?)).await?;
+
+    // The server answers with the RPC server of the session, then attaches the client
+    let mut rpc_port = None;
+    let (client, rpc_token) = loop {
+        let message = stream.next().await.ok_or_else(|| eyre!("Session server disconnected"))??;
+        let Message::Text(text) = message else { continue };
+        match serde_json::from_str::<ServerResponse>(&text)? {
+            ServerResponse::Progress { message, .. } => eprintln!("{message}"),
+            ServerResponse::Success { rpc_port: port, .. } => rpc_port = Some(port),
+            ServerResponse::Attached { client, rpc_token, .. } => break (client, rpc_token),
+            ServerResponse::Error { message } => bail!("Failed to join session: {message}"),
+            response => debug!("Ignoring session update before attaching: {:?}", response),
+        }
+    };
+    let rpc_port = rpc_port.ok_or_else(|| eyre!("Attached without an RPC server"))?;
+    let rpc_url = rpc_server_url(&config.url, rpc_port)?;
+    info!("Joined session of {} as {} through {}", config.tx_hash, client, rpc_url);
+
+    // Relay requests and updates between the TUI and the server in the background
+    let (requests, mut requests_rx) = mpsc::unbounded_channel::<ClientRequest>();
+    let (updates_tx, updates) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                Some(request) = requests_rx.recv() => {
+                    let Ok(json) = serde_json::to_string(&request) else { continue };
+                    if sink.send(Message::text(json)).await.is_err() {
+                        break;
+                    }
+                }
+                message = stream.next() => {
+                    let text = match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        Some(Ok(_)) => continue,
+                    };
+                    match serde_json::from_str::<ServerResponse>(&text) {
+                        Ok(update) => {
+                            if updates_tx.send(update).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => warn!("Invalid session update: {}", e),
+                    }
+                }
+            }
+        }
+        debug!("Session connection closed");
+    });
+
+    let collaboration = Collaboration {
+        driver: !config.observe,
+        client,
+        requests,
+        updates,
+        cursor: None,
+        local: None,
+        moved_by: None,
+        participants: (0, 0),
+        detached: false,
+    };
+    Ok(JoinedSession { rpc_url, rpc_token, collaboration })
+}
+
+/// Get the URL of an RPC server on the host of the session server
+fn rpc_server_url(session_url: &str, rpc_port: u16) -> Result<String> {
+    let url: Uri = session_url.parse()?;
+    let scheme = if url.scheme_str() == Some("wss") { "https" } else { "http" };
+    let host = url.host().ok_or_else(|| eyre!("Session server URL has no host"))?;
+    Ok(format!("{scheme}://{host}:{rpc_port}"))
+}
+
+impl Collaboration {
+    /// Apply the updates received from the server, and share the navigation of a driver
+    ///
+    /// Called periodically from the event loop.
+    pub(crate) fn sync(&mut self, dm: &mut DataManager) {
+        while let Ok(update) = self.updates.try_recv() {
+            self.apply(update, dm);
+        }
+        if dm.execution.get_execution_status().is_waiting() {
+            return;
+        }
+
+        let current = dm.execution.get_current_snapshot();
+        let moved = self.local.replace(current).is_some_and(|previous| previous != current);
+        if moved && self.driver && !self.detached && self.cursor != Some(current) {
+            self.cursor = Some(current);
+            self.moved_by = Some(self.client.clone());
+            if self.requests.send(ClientRequest::Navigate { snapshot_id: current }).is_err() {
+                self.detached = true;
+            }
+        }
+    }
+
+    /// Apply an update of the session
+    fn apply(&mut self, update: ServerResponse, dm: &mut DataManager) {
+        match update {
+            ServerResponse::Cursor { snapshot_id, by } => {
+                self.cursor = Some(snapshot_id);
+                self.moved_by = by;
+                if dm.execution.get_current_snapshot() != snapshot_id {
+                    if let Err(e) = dm.execution.goto(snapshot_id, false) {
+                        warn!("Failed to follow the shared cursor: {}", e);
+                    }
+                }
+            }
+            ServerResponse::Participants { drivers, observers } => {
+                self.participants = (drivers, observers);
+            }
+            ServerResponse::TakenOver => {
+                info!("Session taken over by another connection");
+                self.detached = true;
+            }
+            ServerResponse::Error { message } => warn!("Session server error: {}", message),
+            update => debug!("Ignoring session update: {:?}", update),
+        }
+    }
+
+    /// Summary of the session for the status bar
+    pub(crate) fn status(&self) -> String {
+        if self.detached {
+            return "Session: detached".to_string();
+        }
+
+        let role = if self.driver { "driver" } else { "observer" };
+        let (drivers, observers) = self.participants;
+        let mut status = format!("Session: {role}, {drivers} driver(s), {observers} observer(s)");
+        if let (Some(snapshot_id), Some(by)) = (self.cursor, &self.moved_by) {
+            status.push_str(&format!(", cursor #{snapshot_id} by {by}"));
+        }
+        status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_server_url() {
+        assert_eq!(
+            rpc_server_url("ws://example.com:9001", 3030).unwrap(),
+            "http://example.com:3030"
+        );
+        assert_eq!(rpc_server_url("wss://example.com/", 3030).unwrap(), "https://example.com:3030");
+        assert!(rpc_server_url("not a url", 3030).is_err());
+    }
+}
@@ -17,15 +17,26 @@
 //! Configuration system for EDB TUI
 //!
 //! Manages user preferences including color schemes and other settings.
+//!
+//! Settings are read from `~/.edb.toml`, with a `.edb.toml` in the current working
+//! directory merged over it, so that project-specific settings (labels, the RPC
+//! endpoint, ...) can be committed with the project. Tables are merged key by key,
+//! while any other value of the project file replaces the user's one. Settings changed
+//! from the TUI are only written back to `~/.edb.toml`.
 
 use eyre::{Context, Result};
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
-use crate::Theme;
+use crate::{
+    layout::LayoutSettings,
+    ui::colors::{detect_truecolor, parse_color},
+    ColorScheme, Theme,
+};
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -34,6 +45,91 @@ pub struct Config {
     pub theme: ThemeConfig,
     /// Panel-specific settings
     pub panels: PanelConfig,
+    /// Watch expressions kept across sessions
+    #[serde(default)]
+    pub watches: Vec<WatchConfig>,
+    /// Keybinding overrides
+    #[serde(default)]
+    pub keybindings: KeyBindingsConfig,
+    /// Panel sizes and visible panels, as last arranged by the user
+    #[serde(default)]
+    pub layout: LayoutSettings,
+    /// RPC server URL, used when `--url` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_url: Option<String>,
+    /// Address labels, keyed by address
+    ///
+    /// Unlike labels added from the terminal, these are not written to
+    /// `~/.edb/labels.json`, so that a project can share the labels of its contracts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Block explorer of each chain (`[explorers.<chain id>]`), read by the `edb` CLI
+    ///
+    /// Kept as raw TOML, so that saving the config preserves it.
+    #[serde(default, skip_serializing_if = "toml::Table::is_empty")]
+    pub explorers: toml::Table,
+}
+
+/// Built-in set of keybindings applied before the user's own bindings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyPreset {
+    /// The built-in bindings, unchanged
+    #[default]
+    Default,
+    /// Vim-style scrolling (`Ctrl+D`/`Ctrl+U`, `Ctrl+F`/`Ctrl+B`, `Ctrl+E`/`Ctrl+Y`)
+    Vim,
+}
+
+/// Keybinding configuration
+///
+/// Each table maps a key (e.g. `"Ctrl+b"`, `"Alt+1"`, `"F5"`, `"x"`) to the built-in key
+/// it should act as, or to `"none"` to disable it. Panel tables take precedence over the
+/// global table while the panel is focused.
+///
+/// ```toml
+/// [keybindings]
+/// preset = "vim"
+///
+/// [keybindings.global]
+/// "Alt+1" = "F1"
+///
+/// [keybindings.code]
+/// "Ctrl+b" = "b"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBindingsConfig {
+    /// Preset applied before the bindings below
+    #[serde(default)]
+    pub preset: KeyPreset,
+    /// Bindings active in every panel
+    #[serde(default)]
+    pub global: BTreeMap<String, String>,
+    /// Bindings active in the trace panel
+    #[serde(default)]
+    pub trace: BTreeMap<String, String>,
+    /// Bindings active in the code panel
+    #[serde(default)]
+    pub code: BTreeMap<String, String>,
+    /// Bindings active in the display panel
+    #[serde(default)]
+    pub display: BTreeMap<String, String>,
+    /// Bindings active in the events panel
+    #[serde(default)]
+    pub events: BTreeMap<String, String>,
+    /// Bindings active in the terminal panel
+    #[serde(default)]
+    pub terminal: BTreeMap<String, String>,
+}
+
+/// A persisted watch expression
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// Expression source text (without the leading `$`)
+    pub expression: String,
+    /// Whether the watch is pinned to the top of the display
+    #[serde(default)]
+    pub pinned: bool,
 }
 
 /// Theme configuration
@@ -43,6 +139,45 @@ pub struct ThemeConfig {
     pub active: String,
     /// Available themes
     pub themes: std::collections::HashMap<String, Theme>,
+    /// User-defined themes, keyed by name
+    #[serde(default)]
+    pub custom: BTreeMap<String, CustomThemeConfig>,
+    /// Whether the terminal renders 24-bit colors, detected from `COLORTERM` when unset
+    ///
+    /// Without truecolor, RGB colors are approximated with the 256-color palette.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truecolor: Option<bool>,
+}
+
+/// User-defined theme
+///
+/// A custom theme starts from a built-in theme and overrides any of its colors. Colors are
+/// names (`light_blue`), `#rrggbb` or `rgb(r, g, b)` 24-bit colors, or 256-color palette
+/// indices.
+///
+/// ```toml
+/// [theme.custom.midnight]
+/// base = "nord_dark"
+/// description = "Nord with pink keywords"
+///
+/// [theme.custom.midnight.colors]
+/// syntax_keyword_color = "#ff79c6"
+/// panel_bg = "rgb(10, 10, 20)"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomThemeConfig {
+    /// Built-in theme providing the colors that are not overridden
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Name shown in the theme list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Description shown in the theme list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Color overrides, keyed by [`ColorScheme`] field name
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
 }
 
 /// Panel-specific configuration
@@ -98,7 +233,12 @@ impl Default for ThemeConfig {
     fn default() -> Self {
         let themes = Theme::all().iter().map(|theme| (theme.name().to_string(), *theme)).collect();
 
-        Self { active: Theme::default().name().to_string(), themes }
+        Self {
+            active: Theme::default().name().to_string(),
+            themes,
+            custom: BTreeMap::new(),
+            truecolor: None,
+        }
     }
 }
 
@@ -121,8 +261,50 @@ impl Config {
         Ok(home.join(".edb.toml"))
     }
 
-    /// Load configuration from file, creating default if it doesn't exist
+    /// Get the project config file path (.edb.toml in the current working directory)
+    pub fn project_config_path() -> Result<PathBuf> {
+        let dir =
+            std::env::current_dir().with_context(|| "Unable to determine current directory")?;
+        Ok(dir.join(".edb.toml"))
+    }
+
+    /// Load the effective configuration: `~/.edb.toml` with the project's `.edb.toml`
+    /// merged over it
+    ///
+    /// Use [`Self::load_global`] to change settings and [`Self::save`] them, so that
+    /// project settings are not copied into the user's config file.
     pub fn load() -> Result<Self> {
+        let config = Self::load_global()?;
+
+        let Ok(project_path) = Self::project_config_path() else {
+            return Ok(config);
+        };
+        if !project_path.exists() || Self::config_path().is_ok_and(|path| path == project_path) {
+            return Ok(config);
+        }
+        config.merged_with(&project_path)
+    }
+
+    /// Merge the settings of a config file over this configuration
+    fn merged_with(&self, path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {path:?}"))?;
+        let overlay: toml::Table = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file as TOML: {path:?}"))?;
+
+        let mut table =
+            toml::Table::try_from(self).with_context(|| "Failed to serialize config to TOML")?;
+        merge_tables(&mut table, overlay);
+
+        let config: Self = table
+            .try_into()
+            .with_context(|| format!("Invalid settings in config file: {path:?}"))?;
+        debug!("Merged project configuration from {:?}", path);
+        Ok(config)
+    }
+
+    /// Load the user's configuration from `~/.edb.toml`, creating default if it doesn't exist
+    pub fn load_global() -> Result<Self> {
         let config_path = Self::config_path()?;
 
         if !config_path.exists() {
@@ -172,51 +354,170 @@ impl Config {
         Ok(())
     }
 
-    /// Get the currently active theme
+    /// Get the currently active theme, if it is a built-in theme
     pub fn get_active_theme(&self) -> Option<&Theme> {
         self.theme.themes.get(&self.theme.active)
     }
 
+    /// Resolve a theme name, custom or built-in, to its canonical name
+    ///
+    /// Names are matched case-insensitively, and the `dark`, `light`, `solarized`, and
+    /// `high-contrast` presets are accepted as aliases of built-in themes.
+    pub fn resolve_theme_name(&self, theme_name: &str) -> Option<String> {
+        let find = |name: &str| {
+            self.theme
+                .custom
+                .keys()
+                .chain(self.theme.themes.keys())
+                .find(|candidate| candidate.eq_ignore_ascii_case(name))
+                .cloned()
+        };
+
+        find(theme_name).or_else(|| {
+            let builtin = match theme_name.to_lowercase().replace('_', "-").as_str() {
+                "dark" => Theme::ModernIDE,
+                "light" => Theme::VSCodeLight,
+                "solarized" => Theme::SolarizedDark,
+                "high-contrast" => Theme::HighContrast,
+                _ => return None,
+            };
+            find(builtin.name())
+        })
+    }
+
+    /// Build the color scheme of a theme, custom or built-in
+    pub fn color_scheme(&self, theme_name: &str) -> Result<ColorScheme> {
+        let name = self
+            .resolve_theme_name(theme_name)
+            .ok_or_else(|| eyre::eyre!("Theme '{}' not found", theme_name))?;
+
+        if let Some(custom) = self.theme.custom.get(&name) {
+            let base = match &custom.base {
+                Some(base) => self
+                    .theme
+                    .themes
+                    .get(base)
+                    .or_else(|| Theme::all().iter().find(|theme| theme.name() == base))
+                    .copied()
+                    .ok_or_else(|| {
+                        eyre::eyre!("Base of theme '{}' is not a built-in theme: {}", name, base)
+                    })?,
+                None => Theme::default(),
+            };
+            return ColorScheme::from(base)
+                .with_overrides(&custom.colors)
+                .with_context(|| format!("Invalid colors in theme '{name}'"));
+        }
+
+        let theme = self.theme.themes.get(&name).copied().unwrap_or_default();
+        Ok(theme.into())
+    }
+
+    /// Build the color scheme of the active theme, adapted to the terminal's color support
+    pub fn active_color_scheme(&self) -> Result<ColorScheme> {
+        let scheme = self.color_scheme(&self.theme.active)?;
+        if self.theme.truecolor.unwrap_or_else(detect_truecolor) {
+            Ok(scheme)
+        } else {
+            debug!("Terminal lacks truecolor support, using the 256-color palette");
+            Ok(scheme.to_ansi256())
+        }
+    }
+
     /// Switch to a different theme
     pub fn set_theme(&mut self, theme_name: &str) -> Result<()> {
-        if !self.theme.themes.contains_key(theme_name) {
-            return Err(eyre::eyre!("Theme '{}' not found", theme_name));
-        }
+        let name = self
+            .resolve_theme_name(theme_name)
+            .ok_or_else(|| eyre::eyre!("Theme '{}' not found", theme_name))?;
+
+        // Reject custom themes with invalid colors before switching to them
+        self.color_scheme(&name)?;
 
-        self.theme.active = theme_name.to_string();
-        info!("Switched to theme: {}", theme_name);
+        self.theme.active = name;
+        info!("Switched to theme: {}", self.theme.active);
         Ok(())
     }
 
-    /// List available themes
-    pub fn list_themes(&self) -> Vec<(&String, &Theme)> {
-        self.theme.themes.iter().collect()
+    /// List available themes as (name, display name, description), built-in themes first
+    pub fn list_themes(&self) -> Vec<(String, String, String)> {
+        let mut builtin: Vec<_> = self
+            .theme
+            .themes
+            .iter()
+            .map(|(name, theme)| {
+                (name.clone(), theme.display_name().to_string(), theme.description().to_string())
+            })
+            .collect();
+        builtin.sort();
+
+        let custom = self.theme.custom.iter().map(|(name, theme)| {
+            (
+                name.clone(),
+                theme.display_name.clone().unwrap_or_else(|| name.clone()),
+                theme.description.clone().unwrap_or_else(|| "Custom theme".to_string()),
+            )
+        });
+        builtin.into_iter().chain(custom).collect()
     }
 
     /// Convert color string to ratatui Color
     pub fn parse_color(color_str: &str) -> Color {
-        match color_str.to_lowercase().as_str() {
-            "black" => Color::Black,
-            "red" => Color::Red,
-            "green" => Color::Green,
-            "yellow" => Color::Yellow,
-            "blue" => Color::Blue,
-            "magenta" => Color::Magenta,
-            "cyan" => Color::Cyan,
-            "gray" => Color::Gray,
-            "dark_gray" => Color::DarkGray,
-            "light_red" => Color::LightRed,
-            "light_green" => Color::LightGreen,
-            "light_yellow" => Color::LightYellow,
-            "light_blue" => Color::LightBlue,
-            "light_magenta" => Color::LightMagenta,
-            "light_cyan" => Color::LightCyan,
-            "white" => Color::White,
-            "light_gray" => Color::Gray,
-            _ => {
-                warn!("Unknown color '{}', using default gray", color_str);
-                Color::Gray
+        parse_color(color_str).unwrap_or_else(|e| {
+            warn!("{}, using default gray", e);
+            Color::Gray
+        })
+    }
+}
+
+/// Merge a TOML table over another: nested tables are merged key by key, and any other
+/// value replaces the one it overlays
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => {
+                merge_tables(base, overlay)
+            }
+            (_, value) => {
+                base.insert(key, value);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_config_merged_over_user_config() {
+        let mut user = Config::default();
+        user.theme.active = "nord_dark".to_string();
+        user.layout.vertical_split = 60;
+        user.labels.insert("0x01".to_string(), "Old".to_string());
+        user.watches.push(WatchConfig { expression: "owner".to_string(), pinned: false });
+
+        let project = r#"
+rpc_url = "http://localhost:4040"
+watches = [{ expression = "totalSupply" }]
+
+[layout]
+left_panel = "code"
+
+[labels]
+"0x02" = "Vault"
+"#;
+        let path = std::env::temp_dir().join(format!("edb-project-{}.toml", std::process::id()));
+        fs::write(&path, project).unwrap();
+        let config = user.merged_with(&path);
+        fs::remove_file(&path).unwrap();
+        let config = config.unwrap();
+
+        assert_eq!(config.rpc_url.as_deref(), Some("http://localhost:4040"));
+        assert_eq!(config.theme.active, "nord_dark");
+        assert_eq!(config.layout.vertical_split, 60);
+        assert_eq!(config.layout.left_panel, crate::panels::PanelType::Code);
+        assert_eq!(config.labels.len(), 2);
+        let watches: Vec<_> = config.watches.iter().map(|w| w.expression.as_str()).collect();
+        assert_eq!(watches, ["totalSupply"]);
+    }
+}
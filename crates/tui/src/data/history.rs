@@ -0,0 +1,157 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Terminal command history
+//!
+//! Commands typed in the Terminal panel are persisted in `~/.edb/history.json`, so that
+//! ↑/↓ recalls the commands of previous debugging sessions. Multi-line expressions are
+//! kept as a single entry.
+
+use std::{collections::VecDeque, fs, path::PathBuf};
+
+use eyre::{eyre, Context, Result};
+use tracing::{debug, warn};
+
+/// Maximum number of commands kept in the history
+const MAX_COMMAND_HISTORY: usize = 100;
+
+/// Command history of the Terminal panel
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    /// Commands, oldest first
+    commands: VecDeque<String>,
+    /// Whether changes are written back to the history file
+    persistent: bool,
+}
+
+impl CommandHistory {
+    /// Get the history file path (~/.edb/history.json)
+    pub fn history_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().ok_or_else(|| eyre!("Unable to determine home directory"))?;
+        Ok(home.join(".edb").join("history.json"))
+    }
+
+    /// Load the commands persisted in the history file
+    ///
+    /// Any later command pushed to the returned history is written back to the history file.
+    pub fn load() -> Self {
+        let mut commands: VecDeque<String> = Self::history_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(VecDeque::new());
+                }
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read history file: {path:?}"))?;
+                serde_json::from_str(&content).with_context(|| "Failed to parse history file")
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to load command history: {e}");
+                VecDeque::new()
+            });
+
+        while commands.len() > MAX_COMMAND_HISTORY {
+            commands.pop_front();
+        }
+
+        debug!("Loaded {} commands from history", commands.len());
+        Self { commands, persistent: true }
+    }
+
+    /// Add a command, unless it repeats the last one
+    pub fn push(&mut self, command: &str) {
+        if command.trim().is_empty() || self.last() == Some(command) {
+            return;
+        }
+
+        if self.commands.len() >= MAX_COMMAND_HISTORY {
+            self.commands.pop_front();
+        }
+        self.commands.push_back(command.to_string());
+        self.persist();
+    }
+
+    /// Get the command at the given position, oldest first
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.commands.get(index).map(String::as_str)
+    }
+
+    /// Get the last command
+    pub fn last(&self) -> Option<&str> {
+        self.commands.back().map(String::as_str)
+    }
+
+    /// Iterate over the commands, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().map(String::as_str)
+    }
+
+    /// Get the count of commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether no command has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Write the history back to the history file
+    fn persist(&self) {
+        if !self.persistent {
+            return;
+        }
+
+        let result = Self::history_path().and_then(|path| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, serde_json::to_string_pretty(&self.commands)?)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            warn!("Failed to persist command history: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_skips_empty_and_repeated_commands() {
+        let mut history = CommandHistory::default();
+        history.push("next");
+        history.push("next");
+        history.push("  ");
+        history.push("$ a +\n  b");
+
+        let commands: Vec<&str> = history.iter().collect();
+        assert_eq!(commands, ["next", "$ a +\n  b"]);
+    }
+
+    #[test]
+    fn test_push_drops_oldest_command() {
+        let mut history = CommandHistory::default();
+        for i in 0..=MAX_COMMAND_HISTORY {
+            history.push(&format!("step {i}"));
+        }
+
+        assert_eq!(history.len(), MAX_COMMAND_HISTORY);
+        assert_eq!(history.get(0), Some("step 1"));
+        assert_eq!(history.last(), Some(&*format!("step {MAX_COMMAND_HISTORY}")));
+    }
+}
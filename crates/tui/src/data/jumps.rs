@@ -0,0 +1,129 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Jump list of visited snapshots
+//!
+//! Like Vim's jump list, every jump (going to a snapshot, running to a breakpoint, jumping
+//! to a call or a mark) remembers the snapshot it left, while single steps do not.
+//! `Ctrl-O` walks back through the list and `Ctrl-I` walks forward again.
+
+/// Maximum number of snapshots kept in the jump list
+const MAX_JUMPS: usize = 100;
+
+/// History of snapshots left by jumps
+#[derive(Debug, Clone, Default)]
+pub struct JumpList {
+    /// Snapshots, oldest first
+    entries: Vec<usize>,
+    /// Index of the entry being visited, or `entries.len()` when not walking the list
+    position: usize,
+}
+
+impl JumpList {
+    /// Remember the snapshot left by a jump
+    ///
+    /// Jumping while walking the list discards the entries after the visited one.
+    pub fn push(&mut self, from: usize) {
+        self.entries.truncate(self.position);
+        if self.entries.last() != Some(&from) {
+            self.entries.push(from);
+        }
+        if self.entries.len() > MAX_JUMPS {
+            self.entries.remove(0);
+        }
+        self.position = self.entries.len();
+    }
+
+    /// Get the snapshot to go back to from `current`
+    pub fn back(&mut self, current: usize) -> Option<usize> {
+        if self.position == self.entries.len() {
+            // Remember where we leave the head of the list, so that we can come back
+            if self.entries.last() != Some(&current) {
+                self.entries.push(current);
+            }
+            self.position = self.entries.len() - 1;
+        }
+
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.entries[self.position])
+    }
+
+    /// Get the snapshot to go forward to, after going back
+    pub fn forward(&mut self) -> Option<usize> {
+        if self.position + 1 >= self.entries.len() {
+            return None;
+        }
+        self.position += 1;
+        Some(self.entries[self.position])
+    }
+
+    /// Iterate over the snapshots, oldest first, flagging the one being visited
+    pub fn iter(&self) -> impl Iterator<Item = (usize, bool)> + '_ {
+        self.entries.iter().enumerate().map(|(i, id)| (*id, i == self.position))
+    }
+
+    /// Whether no jump has been made yet
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jump_back_and_forth() {
+        let mut jumps = JumpList::default();
+        assert_eq!(jumps.back(0), None);
+
+        // 0 -> 10 -> 20 -> 30
+        jumps.push(0);
+        jumps.push(10);
+        jumps.push(20);
+
+        assert_eq!(jumps.back(30), Some(20));
+        assert_eq!(jumps.back(20), Some(10));
+        assert_eq!(jumps.forward(), Some(20));
+        assert_eq!(jumps.forward(), Some(30));
+        assert_eq!(jumps.forward(), None);
+
+        assert_eq!(jumps.back(30), Some(20));
+        assert_eq!(jumps.back(20), Some(10));
+        assert_eq!(jumps.back(10), Some(0));
+        assert_eq!(jumps.back(0), None);
+    }
+
+    #[test]
+    fn test_jump_discards_forward_entries() {
+        let mut jumps = JumpList::default();
+        jumps.push(0);
+        jumps.push(10);
+        assert_eq!(jumps.back(20), Some(10));
+
+        // Jumping from 10 to 50 drops 20
+        jumps.push(10);
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back(50), Some(10));
+        assert_eq!(jumps.back(10), Some(0));
+
+        let entries: Vec<usize> = jumps.iter().map(|(id, _)| id).collect();
+        assert_eq!(entries, [0, 10, 50]);
+    }
+}
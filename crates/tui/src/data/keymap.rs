@@ -0,0 +1,405 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! User-configurable keybindings
+//!
+//! Panels keep matching on their built-in keys. The [`KeyMap`] sits in front of them and
+//! translates each key pressed by the user into the built-in key it is bound to, so that
+//! every panel action can be remapped from the `[keybindings]` section of the config file
+//! without the panels knowing about it.
+
+use std::{collections::HashMap, fmt};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use eyre::{bail, eyre, Result};
+use tracing::warn;
+
+use crate::{
+    config::{Config, KeyBindingsConfig, KeyPreset},
+    panels::PanelType,
+};
+
+/// Target that disables a key
+const UNBOUND: &str = "none";
+
+/// Bindings of the vim preset, applied in every panel
+const VIM_PRESET: &[(&str, &str)] = &[
+    ("Ctrl+d", "PageDown"),
+    ("Ctrl+u", "PageUp"),
+    ("Ctrl+f", "PageDown"),
+    ("Ctrl+b", "PageUp"),
+    ("Ctrl+e", "Down"),
+    ("Ctrl+y", "Up"),
+];
+
+/// A key together with its modifiers, e.g. `Ctrl+Shift+Left`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeySpec {
+    /// Key code
+    pub code: KeyCode,
+    /// Modifiers held with the key
+    pub modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    /// Create a key spec, normalizing the modifiers
+    ///
+    /// Shift is dropped for characters and `BackTab`, since it is already part of the key
+    /// (`A` rather than `Shift+a`).
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self {
+            code,
+            modifiers: modifiers
+                & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT),
+        }
+    }
+
+    /// Parse a key spec such as `x`, `Ctrl+p`, `Alt+Enter`, or `F5`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            bail!("Empty key");
+        }
+
+        // A trailing `+` is the key itself (e.g. `Ctrl++`)
+        let (prefix, key) = match spec.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None => match spec.rsplit_once('+') {
+                Some((prefix, key)) if !key.is_empty() => (prefix, key),
+                _ => ("", spec),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in prefix.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("Unknown modifier `{modifier}` in `{spec}`"),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdown" | "pgdn" => KeyCode::PageDown,
+                lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=24).contains(&n) => KeyCode::F(n),
+                    _ => bail!("Unknown key `{key}` in `{spec}`"),
+                },
+            },
+        };
+
+        // `Shift+a` is the same key as `A`
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            code => code,
+        };
+
+        Ok(Self::new(code, modifiers))
+    }
+
+    /// Whether the key types a character rather than triggering an action
+    fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_))
+            && !self.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyEvent> for KeySpec {
+    fn from(key: KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Built-in key a user key is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    /// Act as the given built-in key
+    Key(KeySpec),
+    /// Ignore the key
+    Unbound,
+}
+
+impl Binding {
+    /// Parse a binding target: a key spec, or `none`
+    pub fn parse(target: &str) -> Result<Self> {
+        if target.trim().eq_ignore_ascii_case(UNBOUND) {
+            Ok(Self::Unbound)
+        } else {
+            KeySpec::parse(target).map(Self::Key)
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{key}"),
+            Self::Unbound => write!(f, "{UNBOUND}"),
+        }
+    }
+}
+
+/// Keybindings of every panel, built from the config file
+#[derive(Debug, Clone, Default)]
+pub struct KeyMap {
+    /// Bindings active in every panel
+    global: HashMap<KeySpec, Binding>,
+    /// Bindings active in a single panel, taking precedence over the global ones
+    panels: HashMap<PanelType, HashMap<KeySpec, Binding>>,
+}
+
+impl KeyMap {
+    /// Load the keybindings from the config file
+    pub fn load() -> Self {
+        match Config::load() {
+            Ok(config) => Self::from_config(&config.keybindings),
+            Err(e) => {
+                warn!("Failed to load keybindings: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Build the keybindings from their configuration
+    ///
+    /// Invalid entries are skipped with a warning rather than failing the whole TUI.
+    pub fn from_config(config: &KeyBindingsConfig) -> Self {
+        let mut keymap = Self::default();
+
+        if config.preset == KeyPreset::Vim {
+            for (key, target) in VIM_PRESET {
+                keymap.bind(None, key, target).expect("vim preset is valid");
+            }
+        }
+
+        let scopes = [
+            (None, &config.global),
+            (Some(PanelType::Trace), &config.trace),
+            (Some(PanelType::Code), &config.code),
+            (Some(PanelType::Display), &config.display),
+            (Some(PanelType::Events), &config.events),
+            (Some(PanelType::Terminal), &config.terminal),
+        ];
+        for (panel, bindings) in scopes {
+            for (key, target) in bindings {
+                if let Err(e) = keymap.bind(panel, key, target) {
+                    warn!("Ignoring keybinding `{key}` = `{target}`: {e}");
+                }
+            }
+        }
+
+        keymap
+    }
+
+    /// Bind a key, globally or in a single panel
+    pub fn bind(&mut self, panel: Option<PanelType>, key: &str, target: &str) -> Result<()> {
+        let key = KeySpec::parse(key)?;
+        let binding =
+            Binding::parse(target).map_err(|e| eyre!("Invalid target `{target}`: {e}"))?;
+        let scope = match panel {
+            Some(panel) => self.panels.entry(panel).or_default(),
+            None => &mut self.global,
+        };
+        scope.insert(key, binding);
+        Ok(())
+    }
+
+    /// Translate a key pressed in a panel into the built-in key it is bound to
+    ///
+    /// Returns `None` if the key is unbound. Unmapped keys are returned unchanged. In the
+    /// terminal panel, global bindings of plain characters are skipped so that they can
+    /// still be typed.
+    pub fn translate(&self, panel: PanelType, key: KeyEvent) -> Option<KeyEvent> {
+        let spec = KeySpec::from(key);
+        let binding =
+            self.panels.get(&panel).and_then(|bindings| bindings.get(&spec)).or_else(|| {
+                if panel == PanelType::Terminal && spec.is_text() {
+                    None
+                } else {
+                    self.global.get(&spec)
+                }
+            });
+
+        match binding {
+            None => Some(key),
+            Some(Binding::Unbound) => None,
+            Some(Binding::Key(target)) => {
+                Some(KeyEvent { code: target.code, modifiers: target.modifiers, ..key })
+            }
+        }
+    }
+
+    /// Get the keys that act as the given built-in key in a panel, sorted by name
+    ///
+    /// The built-in key itself is included unless it has been rebound.
+    pub fn keys_for(&self, panel: PanelType, target: KeySpec) -> Vec<KeySpec> {
+        let mut keys: Vec<KeySpec> = self
+            .iter()
+            .filter(|(_, _, binding)| **binding == Binding::Key(target))
+            .map(|(_, key, _)| *key)
+            .chain(std::iter::once(target))
+            .filter(|key| {
+                self.translate(panel, KeyEvent::new(key.code, key.modifiers)).map(KeySpec::from)
+                    == Some(target)
+            })
+            .collect();
+        keys.sort_by_key(ToString::to_string);
+        keys.dedup();
+        keys
+    }
+
+    /// Iterate over the custom bindings, with the panel they are scoped to
+    pub fn iter(&self) -> impl Iterator<Item = (Option<PanelType>, &KeySpec, &Binding)> {
+        self.global.iter().map(|(key, binding)| (None, key, binding)).chain(
+            self.panels.iter().flat_map(|(panel, bindings)| {
+                bindings.iter().map(move |(key, binding)| (Some(*panel), key, binding))
+            }),
+        )
+    }
+
+    /// Whether no custom binding is configured
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.panels.values().all(HashMap::is_empty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_spec() {
+        let parse = |spec| KeySpec::parse(spec).unwrap();
+        assert_eq!(parse("x"), KeySpec::new(KeyCode::Char('x'), KeyModifiers::NONE));
+        assert_eq!(parse("Ctrl+p"), KeySpec::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        assert_eq!(parse("Shift+a"), KeySpec::new(KeyCode::Char('A'), KeyModifiers::NONE));
+        assert_eq!(parse("Shift+Tab"), KeySpec::new(KeyCode::BackTab, KeyModifiers::NONE));
+        assert_eq!(parse("Ctrl++"), KeySpec::new(KeyCode::Char('+'), KeyModifiers::CONTROL));
+        assert_eq!(parse("alt+pgdn"), KeySpec::new(KeyCode::PageDown, KeyModifiers::ALT));
+        assert_eq!(parse("F12"), KeySpec::new(KeyCode::F(12), KeyModifiers::NONE));
+        assert_eq!(parse("Space"), KeySpec::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert_eq!(
+            parse("Ctrl+Shift+Left"),
+            KeySpec::new(KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::SHIFT)
+        );
+
+        assert!(KeySpec::parse("").is_err());
+        assert!(KeySpec::parse("Hyper+x").is_err());
+        assert!(KeySpec::parse("F25").is_err());
+        assert!(KeySpec::parse("Foo").is_err());
+    }
+
+    #[test]
+    fn test_key_spec_display_round_trip() {
+        for spec in ["x", "Ctrl+p", "Alt+Enter", "F5", "Space", "PageDown", "Ctrl+Shift+Left"] {
+            let parsed = KeySpec::parse(spec).unwrap();
+            assert_eq!(KeySpec::parse(&parsed.to_string()).unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        let mut config = KeyBindingsConfig { preset: KeyPreset::Vim, ..Default::default() };
+        config.global.insert("Alt+1".into(), "F1".into());
+        config.global.insert("x".into(), "none".into());
+        config.code.insert("Ctrl+d".into(), "d".into());
+        config.trace.insert("bogus".into(), "j".into());
+        let keymap = KeyMap::from_config(&config);
+
+        let ctrl_d = key(KeyCode::Char('d'), KeyModifiers::CONTROL);
+        assert_eq!(
+            keymap.translate(PanelType::Trace, ctrl_d),
+            Some(key(KeyCode::PageDown, KeyModifiers::NONE))
+        );
+        // Panel bindings take precedence over the preset
+        assert_eq!(
+            keymap.translate(PanelType::Code, ctrl_d),
+            Some(key(KeyCode::Char('d'), KeyModifiers::NONE))
+        );
+
+        let alt_1 = key(KeyCode::Char('1'), KeyModifiers::ALT);
+        assert_eq!(
+            keymap.translate(PanelType::Terminal, alt_1),
+            Some(key(KeyCode::F(1), KeyModifiers::NONE))
+        );
+
+        // Unbound keys are swallowed, except for typing in the terminal
+        let x = key(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(keymap.translate(PanelType::Display, x), None);
+        assert_eq!(keymap.translate(PanelType::Terminal, x), Some(x));
+
+        let j = key(KeyCode::Char('j'), KeyModifiers::NONE);
+        assert_eq!(keymap.translate(PanelType::Trace, j), Some(j));
+
+        let page_down = KeySpec::parse("PageDown").unwrap();
+        let keys: Vec<String> =
+            keymap.keys_for(PanelType::Trace, page_down).iter().map(ToString::to_string).collect();
+        assert_eq!(keys, ["Ctrl+d", "Ctrl+f", "PageDown"]);
+        assert!(keymap.keys_for(PanelType::Display, KeySpec::parse("x").unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! User-defined address labels
+//!
+//! Labels are imported from CSV (`address,label` per line) or JSON (an object mapping
+//! addresses to labels, or an array of `{ "address", "label" }` objects) and persisted
+//! in `~/.edb/labels.json`, so that they are used across debugging sessions. They take
+//! precedence over the well-known and ENS labels resolved by the engine.
+//!
+//! Labels can also be listed in the `[labels]` table of `.edb.toml`, e.g. shared with a
+//! project. Those are never written back, and labels set from the terminal override them.
+
+use std::{collections::BTreeMap, fs, path::Path, path::PathBuf};
+
+use alloy_primitives::Address;
+use eyre::{bail, eyre, Context, Result};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::config::Config;
+
+/// Store of user-defined address labels
+#[derive(Debug, Clone, Default)]
+pub struct LabelStore {
+    /// Label of each address
+    labels: BTreeMap<Address, String>,
+    /// Labels from the `[labels]` table of the config files
+    config_labels: BTreeMap<Address, String>,
+    /// Whether changes are written back to the labels file
+    persistent: bool,
+}
+
+impl LabelStore {
+    /// Get the labels file path (~/.edb/labels.json)
+    pub fn labels_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().ok_or_else(|| eyre!("Unable to determine home directory"))?;
+        Ok(home.join(".edb").join("labels.json"))
+    }
+
+    /// Load the labels persisted in the labels file
+    ///
+    /// Any later change to the returned store is written back to the labels file.
+    pub fn load() -> Self {
+        let labels = Self::labels_path()
+            .and_then(|path| {
+                if !path.exists() {
+                    return Ok(BTreeMap::new());
+                }
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read labels file: {path:?}"))?;
+                serde_json::from_str(&content).with_context(|| "Failed to parse labels file")
+            })
+            .unwrap_or_else(|e| {
+                warn!("Failed to load address labels: {e}");
+                BTreeMap::new()
+            });
+
+        let config_labels = Config::load()
+            .map(|config| config.labels)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|(address, label)| {
+                parse_entry(address, label)
+                    .inspect_err(|e| warn!("Skipping label in config file: {e}"))
+                    .ok()
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        debug!("Loaded {} address labels ({} from config)", labels.len(), config_labels.len());
+        Self { labels, config_labels, persistent: true }
+    }
+
+    /// Import labels from a CSV or JSON file, returning the number of imported labels
+    ///
+    /// Imported labels replace existing labels of the same addresses.
+    pub fn import(&mut self, path: &Path) -> Result<usize> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+        let is_json = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => ext.eq_ignore_ascii_case("json"),
+            None => content.trim_start().starts_with(['{', '[']),
+        };
+        let labels = if is_json { parse_json(&content)? } else { parse_csv(&content)? };
+
+        let count = labels.len();
+        self.labels.extend(labels);
+        self.persist();
+        Ok(count)
+    }
+
+    /// Set the label of an address
+    pub fn insert(&mut self, address: Address, label: String) -> Option<String> {
+        let old = self.labels.insert(address, label);
+        self.persist();
+        old
+    }
+
+    /// Remove the label of an address
+    pub fn remove(&mut self, address: Address) -> Option<String> {
+        let label = self.labels.remove(&address)?;
+        self.persist();
+        Some(label)
+    }
+
+    /// Get the label of an address
+    pub fn get(&self, address: Address) -> Option<&str> {
+        self.labels.get(&address).or_else(|| self.config_labels.get(&address)).map(String::as_str)
+    }
+
+    /// Iterate over the labels of the config files, ordered by address
+    pub fn config_labels(&self) -> impl Iterator<Item = (&Address, &String)> {
+        self.config_labels.iter()
+    }
+
+    /// Iterate over all labels, ordered by address
+    pub fn iter(&self) -> impl Iterator<Item = (&Address, &String)> {
+        self.labels.iter()
+    }
+
+    /// Get the count of labels
+    pub fn count(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Remove all labels
+    pub fn clear(&mut self) {
+        self.labels.clear();
+        self.persist();
+    }
+
+    /// Write the labels back to the labels file
+    fn persist(&self) {
+        if !self.persistent {
+            return;
+        }
+
+        let result = Self::labels_path().and_then(|path| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&path, serde_json::to_string_pretty(&self.labels)?)?;
+            Ok(())
+        });
+        if let Err(e) = result {
+            warn!("Failed to persist address labels: {e}");
+        }
+    }
+}
+
+/// Parse an address and a non-empty label
+fn parse_entry(address: &str, label: &str) -> Result<(Address, String)> {
+    let address =
+        address.trim().parse::<Address>().map_err(|e| eyre!("Invalid address {address}: {e}"))?;
+    let label = label.trim();
+    if label.is_empty() {
+        bail!("Empty label for {address}");
+    }
+    Ok((address, label.to_string()))
+}
+
+/// Parse `address,label` lines, skipping blank lines, `#` comments, and a header line
+fn parse_csv(content: &str) -> Result<Vec<(Address, String)>> {
+    let mut labels = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let unquote = |field: &str| field.trim().trim_matches('"').to_string();
+        let (address, label) = line
+            .split_once(',')
+            .map(|(address, label)| (unquote(address), unquote(label)))
+            .ok_or_else(|| eyre!("Line {}: expected `address,label`", i + 1))?;
+
+        match parse_entry(&address, &label) {
+            Ok(entry) => labels.push(entry),
+            // The first row may be a header such as `address,label`
+            Err(_) if labels.is_empty() && address.parse::<Address>().is_err() => {}
+            Err(e) => bail!("Line {}: {e}", i + 1),
+        }
+    }
+    Ok(labels)
+}
+
+/// Parse an object mapping addresses to labels, or an array of `{ address, label }` objects
+fn parse_json(content: &str) -> Result<Vec<(Address, String)>> {
+    match serde_json::from_str::<Value>(content).with_context(|| "Invalid JSON")? {
+        Value::Object(map) => map
+            .iter()
+            .map(|(address, label)| {
+                let label =
+                    label.as_str().ok_or_else(|| eyre!("Label of {address} is not a string"))?;
+                parse_entry(address, label)
+            })
+            .collect(),
+        Value::Array(entries) => entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let field = |key: &str| entry.get(key).and_then(Value::as_str);
+                let address =
+                    field("address").ok_or_else(|| eyre!("Entry {i}: missing `address`"))?;
+                let label = field("label")
+                    .or_else(|| field("name"))
+                    .ok_or_else(|| eyre!("Entry {i}: missing `label`"))?;
+                parse_entry(address, label)
+            })
+            .collect(),
+        _ => bail!("Expected an object or an array of labels"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+
+    const ALICE: Address = address!("0x1111111111111111111111111111111111111111");
+    const BOB: Address = address!("0x2222222222222222222222222222222222222222");
+
+    #[test]
+    fn test_parse_csv() {
+        let content = "address,label\n# deployments\n0x1111111111111111111111111111111111111111, Vault\n\n\"0x2222222222222222222222222222222222222222\",\"Hot wallet, Binance\"\n";
+        let labels = parse_csv(content).unwrap();
+        assert_eq!(
+            labels,
+            vec![(ALICE, "Vault".to_string()), (BOB, "Hot wallet, Binance".to_string())]
+        );
+
+        assert!(parse_csv("0x1111111111111111111111111111111111111111,Vault\n0x12,Bad").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let object = r#"{ "0x1111111111111111111111111111111111111111": "Vault" }"#;
+        assert_eq!(parse_json(object).unwrap(), vec![(ALICE, "Vault".to_string())]);
+
+        let array = r#"[
+            { "address": "0x1111111111111111111111111111111111111111", "label": "Vault" },
+            { "address": "0x2222222222222222222222222222222222222222", "name": "Router" }
+        ]"#;
+        assert_eq!(
+            parse_json(array).unwrap(),
+            vec![(ALICE, "Vault".to_string()), (BOB, "Router".to_string())]
+        );
+
+        assert!(parse_json(r#"[{ "address": "0x11" , "label": "Bad" }]"#).is_err());
+    }
+}
@@ -27,18 +27,24 @@
 use alloy_primitives::{Address, U256};
 use eyre::{bail, Result};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     ops::Deref,
     sync::Arc,
 };
 use tokio::sync::RwLock;
 use tracing::{debug, error};
 
-use edb_common::types::{Breakpoint, BreakpointLocation, Code, SnapshotInfo, Trace};
+use edb_common::types::{
+    Breakpoint, BreakpointLocation, BridgeMessage, CapturedLog, Code, Finding, GasHeatmap,
+    SnapshotInfo, SourceMapInfo, Trace,
+};
 
 use crate::{
-    data::manager::core::{
-        FetchCache, ManagerCore, ManagerInner, ManagerRequestTr, ManagerStateTr, ManagerTr,
+    data::{
+        jumps::JumpList,
+        manager::core::{
+            FetchCache, ManagerCore, ManagerInner, ManagerRequestTr, ManagerStateTr, ManagerTr,
+        },
     },
     RpcClient,
 };
@@ -48,28 +54,53 @@ pub struct ExecutionState {
     snapshot_count: usize,
     snapshot_info: FetchCache<usize, SnapshotInfo>,
     code: FetchCache<Address, Code>,
+    source_map: FetchCache<Address, SourceMapInfo>,
+    gas_heatmap: FetchCache<Address, GasHeatmap>,
     next_call: FetchCache<usize, usize>,
     prev_call: FetchCache<usize, usize>,
     storage: FetchCache<(usize, U256), U256>,
     storage_diff: FetchCache<usize, HashMap<U256, (U256, U256)>>,
     breakpoint_hits: FetchCache<Breakpoint, Vec<usize>>,
+    /// Trace entries loaded so far, always a prefix of the trace
     trace_data: Trace,
+    /// Total number of entries in the trace
+    trace_total: usize,
+    revert_path: Vec<usize>,
+    findings: Vec<Finding>,
+    bridge_messages: Vec<BridgeMessage>,
+    logs: Vec<CapturedLog>,
 }
 
 impl ManagerStateTr for ExecutionState {
     async fn with_rpc_client(rpc_client: Arc<RpcClient>) -> Result<Self> {
         let snapshot_count = rpc_client.get_snapshot_count().await?;
-        let trace_data = rpc_client.get_trace().await?;
+        // Only the first page of the trace is fetched up front, the others are fetched
+        // as the trace panel scrolls to them
+        let page = rpc_client.get_trace_page(0).await?;
+        let trace_total = page.total;
+        let mut trace_data = Trace::new();
+        trace_data.extend(page.entries);
+        let revert_path = trace_data.revert_path();
+        let findings = rpc_client.get_findings().await?;
+        let bridge_messages = rpc_client.get_bridge_messages().await?;
+        let logs = rpc_client.get_logs().await?;
         Ok(Self {
             snapshot_count,
             snapshot_info: FetchCache::new(),
             code: FetchCache::new(),
+            source_map: FetchCache::new(),
+            gas_heatmap: FetchCache::new(),
             next_call: FetchCache::new(),
             prev_call: FetchCache::new(),
             storage: FetchCache::new(),
             storage_diff: FetchCache::new(),
             breakpoint_hits: FetchCache::new(),
             trace_data,
+            trace_total,
+            revert_path,
+            findings,
+            bridge_messages,
+            logs,
         })
     }
 
@@ -82,6 +113,14 @@ impl ManagerStateTr for ExecutionState {
             self.code.update(&other.code);
         }
 
+        if self.source_map.need_update(&other.source_map) {
+            self.source_map.update(&other.source_map);
+        }
+
+        if self.gas_heatmap.need_update(&other.gas_heatmap) {
+            self.gas_heatmap.update(&other.gas_heatmap);
+        }
+
         if self.next_call.need_update(&other.next_call) {
             self.next_call.update(&other.next_call);
         }
@@ -101,6 +140,11 @@ impl ManagerStateTr for ExecutionState {
         if self.breakpoint_hits.need_update(&other.breakpoint_hits) {
             self.breakpoint_hits.update(&other.breakpoint_hits);
         }
+
+        if self.trace_data.len() < other.trace_data.len() {
+            self.trace_data.extend(other.trace_data[self.trace_data.len()..].iter().cloned());
+            self.revert_path = other.revert_path.clone();
+        }
     }
 }
 
@@ -109,11 +153,15 @@ pub enum ExecutionRequest {
     SnapshotInfo(usize),
     Code(usize),
     CodeByAddress(Address),
+    SourceMap(Address),
+    GasHeatmap(Address),
     NextCall(usize),
     PrevCall(usize),
     Storage(usize, U256),
     StorageDiff(usize),
     BreakpointHits(Breakpoint),
+    /// Page of trace entries starting at an entry id
+    TracePage(usize),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -122,6 +170,21 @@ pub enum ExecutionStatus {
     WaitNextCall(usize),
     WaitPrevCall(usize),
     WaitBreakpointHits(Breakpoint),
+    WaitRevert(RevertSearch),
+}
+
+/// Search for the last snapshot of the frame where the transaction reverted
+///
+/// Snapshots are inspected backward from the end of the frame's subtree: the snapshots
+/// in between belong to its ancestors unwinding the revert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RevertSearch {
+    /// Trace entry of the reverting frame
+    entry_id: usize,
+    /// First snapshot of the frame
+    first: usize,
+    /// Snapshot to inspect next
+    candidate: usize,
 }
 
 impl ExecutionStatus {
@@ -178,6 +241,22 @@ impl ManagerRequestTr<ExecutionState> for ExecutionRequest {
                 let code = rpc_client.get_code_by_address(*address).await?;
                 state.code.insert(*address, Some(code));
             }
+            Self::SourceMap(ref address) => {
+                if state.source_map.contains_key(address) {
+                    return Ok(());
+                }
+
+                let source_map = rpc_client.get_source_map(*address).await?;
+                state.source_map.insert(*address, source_map);
+            }
+            Self::GasHeatmap(ref address) => {
+                if state.gas_heatmap.contains_key(address) {
+                    return Ok(());
+                }
+
+                let heatmap = rpc_client.get_gas_heatmap(*address).await?;
+                state.gas_heatmap.insert(*address, Some(heatmap));
+            }
             Self::NextCall(ref id) => {
                 if state.next_call.contains_key(id) {
                     return Ok(());
@@ -232,6 +311,16 @@ impl ManagerRequestTr<ExecutionState> for ExecutionRequest {
                 let hits = rpc_client.get_breakpoint_hits(breakpoint).await?;
                 state.breakpoint_hits.insert(breakpoint.clone(), Some(hits));
             }
+            Self::TracePage(cursor) => {
+                // Pages are appended in order, so that the loaded entries stay a prefix
+                if cursor != state.trace_data.len() || cursor >= state.trace_total {
+                    return Ok(());
+                }
+
+                let page = rpc_client.get_trace_page(cursor).await?;
+                state.trace_data.extend(page.entries);
+                state.revert_path = state.trace_data.revert_path();
+            }
         }
 
         Ok(())
@@ -255,7 +344,7 @@ impl ManagerRequestTr<ExecutionState> for ExecutionRequest {
 /// - `current_line`: Active source line being executed
 /// - `current_file`: Active source file
 /// - `is_paused`: Execution pause state
-/// - `trace_data`: Full trace data for rendering
+/// - `trace_data`: Trace entries loaded so far for rendering
 ///
 /// # Usage Pattern
 ///
@@ -276,6 +365,10 @@ pub struct ExecutionManager {
     current_snapshot: usize,
     display_snapshot: usize,
 
+    // Snapshot bookmarks and jump history
+    marks: BTreeMap<char, usize>,
+    jumps: JumpList,
+
     // Breakpoints
     breakpoint_set: HashSet<Breakpoint>,
     breakpoints: Vec<(Breakpoint, bool)>, // bool indicates enabled/disabled
@@ -313,6 +406,8 @@ impl ExecutionManager {
             core,
             current_snapshot: 0,
             display_snapshot: 0,
+            marks: BTreeMap::new(),
+            jumps: JumpList::default(),
             breakpoints: Vec::new(),
             breakpoint_set: HashSet::new(),
         };
@@ -451,10 +546,56 @@ impl ExecutionManager {
         self.state.snapshot_count
     }
 
+    /// Get the trace entries loaded so far, see [`Self::load_trace_until`]
     pub fn get_trace(&self) -> &Trace {
         &self.state.trace_data
     }
 
+    /// Get the total number of entries in the trace, including those not loaded yet
+    pub fn get_trace_total(&self) -> usize {
+        self.state.trace_total
+    }
+
+    /// Whether every entry of the trace has been loaded
+    pub fn is_trace_loaded(&self) -> bool {
+        self.state.trace_data.len() >= self.state.trace_total
+    }
+
+    /// Fetch the next page of trace entries if the entry with the given id is not loaded
+    /// yet
+    ///
+    /// Returns whether the entry is already loaded.
+    pub fn load_trace_until(&mut self, entry_id: usize) -> bool {
+        let _ = self.pull_from_core();
+
+        let loaded = self.state.trace_data.len();
+        if entry_id < loaded {
+            return true;
+        }
+        if loaded < self.state.trace_total {
+            debug!("Trace entry {entry_id} not loaded yet, fetching from {loaded}...");
+            self.new_fetching_request(ExecutionRequest::TracePage(loaded));
+        }
+        false
+    }
+
+    /// Get the failed trace entries from the top-level call to the origin of its revert
+    pub fn get_revert_path(&self) -> &[usize] {
+        &self.state.revert_path
+    }
+
+    pub fn get_findings(&self) -> &[Finding] {
+        &self.state.findings
+    }
+
+    pub fn get_bridge_messages(&self) -> &[BridgeMessage] {
+        &self.state.bridge_messages
+    }
+
+    pub fn get_logs(&self) -> &[CapturedLog] {
+        &self.state.logs
+    }
+
     pub fn get_code(&mut self, id: usize) -> Option<&Code> {
         let _ = self.pull_from_core();
 
@@ -499,6 +640,36 @@ impl ExecutionManager {
         }
     }
 
+    pub fn get_source_map(&mut self, address: Address) -> Option<&SourceMapInfo> {
+        let _ = self.pull_from_core();
+
+        if !self.state.source_map.contains_key(&address) {
+            debug!("Source map not found in cache, fetching...");
+            self.new_fetching_request(ExecutionRequest::SourceMap(address));
+            return None;
+        }
+
+        match self.state.source_map.get(&address) {
+            Some(source_map) => source_map.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_gas_heatmap(&mut self, address: Address) -> Option<&GasHeatmap> {
+        let _ = self.pull_from_core();
+
+        if !self.state.gas_heatmap.contains_key(&address) {
+            debug!("Gas heatmap not found in cache, fetching...");
+            self.new_fetching_request(ExecutionRequest::GasHeatmap(address));
+            return None;
+        }
+
+        match self.state.gas_heatmap.get(&address) {
+            Some(heatmap) => heatmap.as_ref(),
+            _ => None,
+        }
+    }
+
     pub fn get_current_snapshot(&mut self) -> usize {
         let _ = self.check_pending_request();
         self.current_snapshot
@@ -564,9 +735,11 @@ impl ExecutionManager {
                     self.execution_status = ExecutionStatus::Normal;
 
                     // We will override the current snapshot
+                    let from = self.current_snapshot;
                     let _ = self
                         .goto_snapshot(next_id, true)
                         .and_then(|to_id| self.display_snapshot(to_id));
+                    self.record_jump(from);
                 }
 
                 // Any other execution request will be rejected
@@ -580,9 +753,11 @@ impl ExecutionManager {
                     self.execution_status = ExecutionStatus::Normal;
 
                     // We will override the current snapshot
+                    let from = self.current_snapshot;
                     let _ = self
                         .goto_snapshot(prev_id, false)
                         .and_then(|to_id| self.display_snapshot(to_id));
+                    self.record_jump(from);
                 }
 
                 // Any other execution request will be rejected
@@ -599,6 +774,33 @@ impl ExecutionManager {
                 // Any other execution request will be rejected
                 false
             }
+            ExecutionStatus::WaitRevert(search) => {
+                // There is a pending execution request, for which we should wait
+                // and should not update current_snapshot
+                let entry_id = self
+                    .get_snapshot_info(search.candidate)
+                    .map(|info| info.frame_id().trace_entry_id());
+                if let Some(entry_id) = entry_id {
+                    if entry_id == search.entry_id || search.candidate <= search.first {
+                        // The pending request is ready, we can proceed
+                        self.execution_status = ExecutionStatus::Normal;
+
+                        let from = self.current_snapshot;
+                        let _ = self
+                            .goto_snapshot(search.candidate, false)
+                            .and_then(|to_id| self.display_snapshot(to_id));
+                        self.record_jump(from);
+                    } else {
+                        self.execution_status = ExecutionStatus::WaitRevert(RevertSearch {
+                            candidate: search.candidate - 1,
+                            ..search
+                        });
+                    }
+                }
+
+                // Any other execution request will be rejected
+                false
+            }
             ExecutionStatus::Normal => true,
         }
     }
@@ -608,18 +810,74 @@ impl ExecutionManager {
     }
 
     /// The actual function that deals with execution
+    ///
+    /// The snapshot we leave is recorded in the jump list.
     pub fn goto(&mut self, id: usize, stop_on_breakpoint: bool) -> Result<()> {
         if !self.check_pending_request() {
             // There is a pending request, we should not update current_snapshot
             return Ok(());
         }
 
+        let from = self.current_snapshot;
+        self.move_to(id, stop_on_breakpoint);
+        self.record_jump(from);
+
+        Ok(())
+    }
+
+    /// Go to where the transaction reverted: the last snapshot of the deepest failed frame
+    ///
+    /// The snapshot is searched for in the background, and the trace entry of the frame
+    /// is returned.
+    pub fn goto_revert(&mut self) -> Result<usize> {
+        if !self.check_pending_request() {
+            bail!("The backend is waiting for an execution request");
+        }
+
+        if self.state.revert_path.is_empty() {
+            bail!("The transaction did not revert");
+        }
+
+        // Precompiles and calls to accounts without code have no snapshot
+        let trace = &self.state.trace_data;
+        let Some((entry_id, depth, first)) = self
+            .state
+            .revert_path
+            .iter()
+            .rev()
+            .filter_map(|id| trace.get(*id))
+            .find_map(|entry| Some((entry.id, entry.depth, entry.first_snapshot_id?)))
+        else {
+            bail!("No snapshot found in the reverted frames");
+        };
+
+        // The frame ends before the first snapshot of a frame entered after its subtree
+        let end = trace
+            .iter()
+            .skip(entry_id + 1)
+            .skip_while(|entry| entry.depth > depth)
+            .find_map(|entry| entry.first_snapshot_id)
+            .unwrap_or(self.state.snapshot_count);
+        let candidate = end.saturating_sub(1).max(first);
+
+        self.execution_status =
+            ExecutionStatus::WaitRevert(RevertSearch { entry_id, first, candidate });
+        Ok(entry_id)
+    }
+
+    /// Move to a snapshot without recording a jump (e.g., single steps)
+    fn move_to(&mut self, id: usize, stop_on_breakpoint: bool) {
         let goto_id = self.get_sanitized_id(id);
         let _ = self
             .goto_snapshot(goto_id, stop_on_breakpoint)
             .and_then(|to_id| self.display_snapshot(to_id));
+    }
 
-        Ok(())
+    /// Record a jump from the given snapshot, if we moved away from it
+    fn record_jump(&mut self, from: usize) {
+        if self.current_snapshot != from {
+            self.jumps.push(from);
+        }
     }
 
     pub fn step(&mut self, count: usize) -> Result<()> {
@@ -629,7 +887,8 @@ impl ExecutionManager {
         }
 
         let next_id = self.get_sanitized_id(self.current_snapshot.saturating_add(count));
-        self.goto(next_id, true)
+        self.move_to(next_id, true);
+        Ok(())
     }
 
     pub fn next(&mut self) -> Result<()> {
@@ -641,7 +900,8 @@ impl ExecutionManager {
         let current_id = self.get_current_snapshot();
         if let Some(snapshot_info) = self.get_snapshot_info(current_id) {
             let next_id = snapshot_info.next_id();
-            self.goto(next_id, true)
+            self.move_to(next_id, true);
+            Ok(())
         } else {
             // We do nothing if we do not have the snapshot info
             Ok(())
@@ -657,7 +917,8 @@ impl ExecutionManager {
         let current_id = self.get_current_snapshot();
         if let Some(snapshot_info) = self.get_snapshot_info(current_id) {
             let prev_id = snapshot_info.prev_id();
-            self.goto(prev_id, false)
+            self.move_to(prev_id, false);
+            Ok(())
         } else {
             // We do nothing if we do not have the snapshot info
             Ok(())
@@ -671,7 +932,8 @@ impl ExecutionManager {
         }
 
         let prev_id = self.current_snapshot.saturating_sub(count).max(0);
-        self.goto(prev_id, false)
+        self.move_to(prev_id, false);
+        Ok(())
     }
 
     pub fn next_call(&mut self) -> Result<()> {
@@ -704,6 +966,76 @@ impl ExecutionManager {
         Ok(())
     }
 
+    /////////////////////////////////////////////
+    // Bookmarks and jump list
+    /////////////////////////////////////////////
+
+    /// Mark the current snapshot with a letter, returning the marked snapshot
+    pub fn set_mark(&mut self, mark: char) -> Result<usize> {
+        if !mark.is_ascii_alphabetic() {
+            bail!("Invalid mark '{mark}': marks are letters (a-z, A-Z)");
+        }
+
+        let id = self.get_current_snapshot();
+        self.marks.insert(mark, id);
+        Ok(id)
+    }
+
+    /// Go to the snapshot marked with a letter, returning the marked snapshot
+    pub fn goto_mark(&mut self, mark: char) -> Result<usize> {
+        let id = *self.marks.get(&mark).ok_or_else(|| eyre::eyre!("Mark '{mark}' is not set"))?;
+        self.goto(id, false)?;
+        Ok(id)
+    }
+
+    /// Remove a mark, returning the snapshot it marked
+    pub fn remove_mark(&mut self, mark: char) -> Option<usize> {
+        self.marks.remove(&mark)
+    }
+
+    /// Remove all marks
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+    }
+
+    /// Iterate over the marks and their snapshots, ordered by mark
+    pub fn list_marks(&self) -> impl Iterator<Item = (char, usize)> + '_ {
+        self.marks.iter().map(|(mark, id)| (*mark, *id))
+    }
+
+    /// Get the jump list
+    pub fn jumps(&self) -> &JumpList {
+        &self.jumps
+    }
+
+    /// Go back to the snapshot left by the previous jump (`Ctrl-O`)
+    pub fn jump_back(&mut self) -> Result<Option<usize>> {
+        if !self.check_pending_request() {
+            // There is a pending request, we should not update current_snapshot
+            return Ok(None);
+        }
+
+        let target = self.jumps.back(self.current_snapshot);
+        if let Some(id) = target {
+            self.move_to(id, false);
+        }
+        Ok(target)
+    }
+
+    /// Go forward again after going back in the jump list (`Ctrl-I`)
+    pub fn jump_forward(&mut self) -> Result<Option<usize>> {
+        if !self.check_pending_request() {
+            // There is a pending request, we should not update current_snapshot
+            return Ok(None);
+        }
+
+        let target = self.jumps.forward();
+        if let Some(id) = target {
+            self.move_to(id, false);
+        }
+        Ok(target)
+    }
+
     /////////////////////////////////////////////
     // Breakpoint management
     /////////////////////////////////////////////
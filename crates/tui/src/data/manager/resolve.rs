@@ -25,8 +25,11 @@
 //! maintaining consistency across the application.
 
 use crate::{
-    data::manager::core::{
-        FetchCache, ManagerCore, ManagerInner, ManagerRequestTr, ManagerStateTr, ManagerTr,
+    data::{
+        labels::LabelStore,
+        manager::core::{
+            FetchCache, ManagerCore, ManagerInner, ManagerRequestTr, ManagerStateTr, ManagerTr,
+        },
     },
     rpc::RpcClient,
 };
@@ -34,13 +37,29 @@ use alloy_dyn_abi::{DynSolValue, EventExt, FunctionExt, JsonAbiExt};
 use alloy_json_abi::JsonAbi;
 use alloy_primitives::{hex, Address, Bytes, LogData, Selector, U256};
 use edb_common::types::{
-    CallableAbiInfo, EdbSolValue, SolValueFormatter, SolValueFormatterContext as FormatCtx,
+    AddressLabel, CallableAbiInfo, EdbSolValue, SolTypeHint, SolValueFormatter,
+    SolValueFormatterContext as FormatCtx, TokenInfo,
 };
 use eyre::Result;
 use std::{collections::HashSet, ops::Deref, sync::Arc};
 use tokio::sync::RwLock;
 use tracing::debug;
 
+/// ERC-20 functions whose `uint256` arguments are token amounts
+const ERC20_AMOUNT_FUNCTIONS: &[&str] = &[
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+];
+
+/// ERC-20 functions returning a token amount
+const ERC20_AMOUNT_RETURNS: &[&str] =
+    &["balanceOf(address)", "totalSupply()", "allowance(address,address)"];
+
+/// ERC-20 events whose `uint256` fields are token amounts
+const ERC20_AMOUNT_EVENTS: &[&str] =
+    &["Transfer(address,address,uint256)", "Approval(address,address,uint256)"];
+
 // Generate a unique expression identifier by removing whitespace
 fn remove_whitespace(expr: &str) -> String {
     expr.replace(|c: char| c.is_whitespace(), "")
@@ -52,6 +71,8 @@ pub struct ResolverState {
     callable_abi: FetchCache<Address, Vec<CallableAbiInfo>>,
     constructor_args: FetchCache<Address, Bytes>,
     expr_value: FetchCache<(usize, String), core::result::Result<EdbSolValue, String>>,
+    address_label: FetchCache<Address, AddressLabel>,
+    token_info: FetchCache<Address, TokenInfo>,
 }
 
 impl ManagerStateTr for ResolverState {
@@ -75,6 +96,14 @@ impl ManagerStateTr for ResolverState {
         if self.expr_value.need_update(&other.expr_value) {
             self.expr_value.update(&other.expr_value);
         }
+
+        if self.address_label.need_update(&other.address_label) {
+            self.address_label.update(&other.address_label);
+        }
+
+        if self.token_info.need_update(&other.token_info) {
+            self.token_info.update(&other.token_info);
+        }
     }
 }
 
@@ -91,6 +120,12 @@ pub enum ResolverRequest {
 
     /// Evaluate expression on snapshot
     ExprOnSnapshot(usize, String),
+
+    /// Request for the well-known or ENS name of an address
+    AddressLabel(Address),
+
+    /// Request for ERC-20 token metadata
+    TokenInfo(Address),
 }
 
 impl ManagerRequestTr<ResolverState> for ResolverRequest {
@@ -124,6 +159,20 @@ impl ManagerRequestTr<ResolverState> for ResolverRequest {
                 let value = rpc_client.eval_on_snapshot(snapshot_id, &expr).await?;
                 state.expr_value.insert((snapshot_id, expr), Some(value));
             }
+            Self::AddressLabel(address) => {
+                if state.address_label.has_cached(&address) {
+                    return Ok(());
+                }
+                let label = rpc_client.get_address_label(address).await?;
+                state.address_label.insert(address, label);
+            }
+            Self::TokenInfo(address) => {
+                if state.token_info.has_cached(&address) {
+                    return Ok(());
+                }
+                let token_info = rpc_client.get_token_info(address).await?;
+                state.token_info.insert(address, token_info);
+            }
         }
         Ok(())
     }
@@ -152,6 +201,10 @@ pub struct Resolver {
     /// Pending requests
     pending_requests: HashSet<ResolverRequest>,
     state: ResolverState,
+    /// Whether addresses are shown raw instead of with their labels
+    show_raw_addresses: bool,
+    /// User-defined labels, taking precedence over the labels resolved by the engine
+    custom_labels: LabelStore,
 
     core: Arc<RwLock<ManagerCore<ResolverState, ResolverRequest>>>,
 }
@@ -186,6 +239,8 @@ impl Resolver {
         Self {
             pending_requests: HashSet::new(),
             state: core.clone().read().await.state.clone(),
+            show_raw_addresses: false,
+            custom_labels: LabelStore::load(),
             core,
         }
     }
@@ -275,6 +330,8 @@ impl Resolver {
             .and_then(|addr| self.get_contract_abi(addr, false))
             .and_then(|abi| abi.function_by_selector(selector).cloned())
         {
+            let token = address
+                .filter(|_| ERC20_AMOUNT_RETURNS.contains(&function_abi.signature().as_str()));
             match function_abi.abi_decode_output(output) {
                 Ok(decoded_values) => {
                     if decoded_values.is_empty() {
@@ -295,12 +352,14 @@ impl Resolver {
                                 "{} {}: {}",
                                 value.format_type(),
                                 name,
-                                self.resolve_sol_value(value, None),
+                                self.resolve_token_value(value, token, None),
                             ));
                         } else {
-                            return_parts.push(
-                                self.resolve_sol_value(value, Some(FormatCtx::new().with_ty(true))),
-                            );
+                            return_parts.push(self.resolve_token_value(
+                                value,
+                                token,
+                                Some(FormatCtx::new().with_ty(true)),
+                            ));
                         }
                     }
 
@@ -333,12 +392,18 @@ impl Resolver {
             .and_then(|addr| self.get_contract_abi(addr, false))
             .and_then(|abi| abi.function_by_selector(selector).cloned())
         {
+            let token = address
+                .filter(|_| ERC20_AMOUNT_FUNCTIONS.contains(&function_abi.signature().as_str()));
             match function_abi.abi_decode_input(&calldata[4..]) {
                 Ok(decoded) => {
                     let params: Vec<String> = decoded
                         .iter()
                         .map(|param| {
-                            self.resolve_sol_value(param, Some(FormatCtx::new().with_ty(true)))
+                            self.resolve_token_value(
+                                param,
+                                token,
+                                Some(FormatCtx::new().with_ty(true)),
+                            )
                         })
                         .collect();
 
@@ -357,6 +422,67 @@ impl Resolver {
         None
     }
 
+    /// Resolve a custom error from revert data, e.g. `InsufficientBalance(uint256(1), ...)`
+    pub fn resolve_custom_error(
+        &mut self,
+        output: &Bytes,
+        address: Option<Address>,
+    ) -> Option<String> {
+        if output.len() < 4 {
+            return None;
+        }
+
+        let selector = Selector::from_slice(&output[..4]);
+        let error_abi = address
+            .and_then(|addr| self.get_contract_abi(addr, false))
+            .and_then(|abi| abi.errors().find(|error| error.selector() == selector).cloned())?;
+        let decoded = error_abi.abi_decode_input(&output[4..]).ok()?;
+        let params: Vec<String> = decoded
+            .iter()
+            .map(|param| self.resolve_sol_value(param, Some(FormatCtx::new().with_ty(true))))
+            .collect();
+
+        Some(format!("{}({})", error_abi.name, params.join(", ")))
+    }
+
+    /// Resolve a function call into its name and its named, formatted arguments
+    pub fn resolve_function_arguments(
+        &mut self,
+        calldata: &Bytes,
+        address: Option<Address>,
+    ) -> Option<(String, Vec<(String, String)>)> {
+        if calldata.len() < 4 {
+            return None;
+        }
+
+        let selector = Selector::from_slice(&calldata[..4]);
+        let function_abi = address
+            .and_then(|addr| self.get_contract_abi(addr, false))
+            .and_then(|abi| abi.function_by_selector(selector).cloned())?;
+        let decoded = function_abi.abi_decode_input(&calldata[4..]).ok()?;
+        let token =
+            address.filter(|_| ERC20_AMOUNT_FUNCTIONS.contains(&function_abi.signature().as_str()));
+
+        let arguments = decoded
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let name = function_abi
+                    .inputs
+                    .get(i)
+                    .map(|param| param.name.clone())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| format!("arg{i}"));
+                (
+                    format!("{} {}", value.format_type(), name),
+                    self.resolve_token_value(value, token, None),
+                )
+            })
+            .collect();
+
+        Some((function_abi.name, arguments))
+    }
+
     /// Resolve constructor call
     pub fn resolve_constructor_call(&mut self, address: Address) -> Option<String> {
         let args = self.get_constructor_args(address).cloned()?;
@@ -395,6 +521,9 @@ impl Resolver {
             .and_then(|addr| self.get_contract_abi(addr, false))
             .and_then(|abi| abi.events().find(|e| e.selector() == event_signature).cloned())
         {
+            let token =
+                address.filter(|_| ERC20_AMOUNT_EVENTS.contains(&event_abi.signature().as_str()));
+
             // Try to decode the event
             match event_abi.decode_log(event) {
                 Ok(decoded) => {
@@ -405,7 +534,7 @@ impl Resolver {
                             "{} {}: {}",
                             value.format_type(),
                             param.name,
-                            self.resolve_sol_value(value, None),
+                            self.resolve_token_value(value, token, None),
                         ));
                     }
 
@@ -431,6 +560,16 @@ impl Resolver {
 
     /// Resolve solidity value
     pub fn resolve_sol_value(&mut self, value: &DynSolValue, ctx: Option<FormatCtx>) -> String {
+        self.resolve_hinted_sol_value(value, None, ctx)
+    }
+
+    /// Resolve solidity value, rendered according to its source-level type hint
+    pub fn resolve_hinted_sol_value(
+        &mut self,
+        value: &DynSolValue,
+        hint: Option<&SolTypeHint>,
+        ctx: Option<FormatCtx>,
+    ) -> String {
         let mut ctx = ctx.unwrap_or_default();
 
         // Use unsafe to extend lifetime of self reference in closure
@@ -439,12 +578,97 @@ impl Resolver {
         ctx.resolve_address =
             Some(Box::new(move |addr| unsafe { (*self_ptr).resolve_address_label(addr) }));
 
-        value.format_value(&ctx)
+        value.format_value_with_hint(hint, &ctx)
+    }
+
+    /// Fetch the well-known or ENS name of a specific address
+    pub fn get_address_label(&mut self, address: Address) -> Option<&AddressLabel> {
+        let _ = self.pull_from_core(); // Try to update cache
+
+        if !self.state.address_label.contains_key(&address) {
+            debug!("Address label not found in cache, fetching...");
+            self.new_fetching_request(ResolverRequest::AddressLabel(address));
+            return None;
+        }
+
+        match self.state.address_label.get(&address) {
+            Some(label) => label.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Fetch the ERC-20 token metadata of a specific address
+    pub fn get_token_info(&mut self, address: Address) -> Option<&TokenInfo> {
+        let _ = self.pull_from_core(); // Try to update cache
+
+        if !self.state.token_info.contains_key(&address) {
+            debug!("Token info not found in cache, fetching...");
+            self.new_fetching_request(ResolverRequest::TokenInfo(address));
+            return None;
+        }
+
+        match self.state.token_info.get(&address) {
+            Some(token_info) => token_info.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Get the user-defined labels
+    pub fn custom_labels(&self) -> &LabelStore {
+        &self.custom_labels
+    }
+
+    /// Get the user-defined labels for modification
+    pub fn custom_labels_mut(&mut self) -> &mut LabelStore {
+        &mut self.custom_labels
+    }
+
+    /// Get the name of an address, preferring user-defined labels
+    pub fn label_name(&mut self, address: Address) -> Option<String> {
+        if let Some(label) = self.custom_labels.get(address) {
+            return Some(label.to_string());
+        }
+        self.get_address_label(address).map(|label| label.name.clone())
+    }
+
+    /// Whether addresses are shown raw instead of with their labels
+    pub fn show_raw_addresses(&self) -> bool {
+        self.show_raw_addresses
+    }
+
+    /// Toggle between labeled and raw addresses
+    pub fn toggle_raw_addresses(&mut self) {
+        self.show_raw_addresses = !self.show_raw_addresses;
+        debug!("Raw addresses toggled: {}", self.show_raw_addresses);
+    }
+
+    /// Resolve a value flowing to or from `token`, formatted as a token amount if possible
+    ///
+    /// Values are formatted as plain solidity values if `token` is `None`, if the value is
+    /// not a `uint256`, or while the token metadata is not (or cannot be) resolved.
+    pub fn resolve_token_value(
+        &mut self,
+        value: &DynSolValue,
+        token: Option<Address>,
+        ctx: Option<FormatCtx>,
+    ) -> String {
+        if let (Some(address), DynSolValue::Uint(amount, 256)) = (token, value) {
+            if let Some(token_info) = self.get_token_info(address) {
+                return token_info.format_amount(*amount);
+            }
+        }
+        self.resolve_sol_value(value, ctx)
     }
 
     /// Resolve and format address with label if available
-    pub fn resolve_address_label(&mut self, _address: Address) -> Option<String> {
-        None
+    pub fn resolve_address_label(&mut self, address: Address) -> Option<String> {
+        if self.show_raw_addresses {
+            return None;
+        }
+
+        let name = self.label_name(address)?;
+        let addr_str = address.to_checksum(None);
+        Some(format!("{name} ({}...{})", &addr_str[..8], &addr_str[addr_str.len() - 6..]))
     }
 
     /// Resolve and format address
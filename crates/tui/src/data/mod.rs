@@ -22,6 +22,7 @@
 //! - `ExecutionManager`: Manages trace and snapshot data with cached state
 //! - `Resolver`: Handles ABI resolution and address labeling with cached lookups
 //! - `Theme`: Direct theme configuration without async wrapping
+//! - `KeyMap`: User-configured keybindings translated before panels see a key
 //!
 //! # Architecture
 //!
@@ -57,12 +58,18 @@ use eyre::Result;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+pub mod history;
+pub mod jumps;
+pub mod keymap;
+pub mod labels;
 pub mod manager;
+pub mod session;
 pub mod theme;
 pub mod watcher;
 
 use crate::{
     data::{
+        keymap::KeyMap,
         manager::{
             core::{ManagerCore, ManagerTr},
             execution::{ExecutionManager, ExecutionRequest, ExecutionState},
@@ -85,8 +92,10 @@ pub struct DataManager {
     pub resolver: Resolver,
     /// Theme configuration (no Arc/RwLock needed)
     pub theme: Theme,
-    /// Expression watcher (no Arc/RwLock needed)
+    /// Expression watcher, persisted in the config file (no Arc/RwLock needed)
     pub watcher: Watcher,
+    /// User-configured keybindings, read from the config file
+    pub keymap: KeyMap,
 }
 
 impl DataManager {
@@ -100,7 +109,8 @@ impl DataManager {
             execution: ExecutionManager::new(exec_core).await,
             resolver: Resolver::new(resolver_core).await,
             theme: Theme::default(),
-            watcher: Watcher::default(),
+            watcher: Watcher::load(),
+            keymap: KeyMap::load(),
         })
     }
 
@@ -0,0 +1,328 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Session recording and replay
+//!
+//! A recording lists the steps taken while debugging: terminal commands (navigation,
+//! evaluations, marks, ...) and navigation done from the other panels, each with the
+//! snapshot it led to, its output, and an optional note. Recordings are saved as JSON,
+//! so that a teammate can replay them step by step or read them as an annotated
+//! markdown walkthrough.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// A step of a recorded session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStep {
+    /// Terminal command of the step, or `goto <id>` for navigation from the panels
+    pub command: String,
+    /// Snapshot reached by the step
+    pub snapshot: usize,
+    /// Output of the command, e.g. an evaluation result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<String>,
+    /// Note attached by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl SessionStep {
+    /// Whether the step evaluates an expression, which is evaluated again on replay
+    pub fn is_evaluation(&self) -> bool {
+        self.command.starts_with('$')
+    }
+}
+
+/// A recorded session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecording {
+    /// Version of EDB that recorded the session
+    pub version: String,
+    /// Snapshot the recording started from
+    pub start_snapshot: usize,
+    /// Recorded steps, in order
+    pub steps: Vec<SessionStep>,
+}
+
+impl SessionRecording {
+    /// Create an empty recording starting from the given snapshot
+    pub fn new(start_snapshot: usize) -> Self {
+        Self { version: env!("CARGO_PKG_VERSION").to_string(), start_snapshot, steps: Vec::new() }
+    }
+
+    /// Load a recording from a file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {path:?}"))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {path:?}"))
+    }
+
+    /// Save the recording to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {path:?}"))
+    }
+
+    /// Render the recording as an annotated markdown walkthrough
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# {title}");
+        let _ = writeln!(md);
+        let _ = writeln!(
+            md,
+            "Recorded with EDB v{}, starting at snapshot {}. Replay it with `replay <file>` \
+             in the EDB terminal.",
+            self.version, self.start_snapshot
+        );
+
+        for (i, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(md);
+            let _ = writeln!(md, "## Step {}: `{}`", i + 1, step.command.replace('\n', " "));
+            let _ = writeln!(md);
+            let _ = writeln!(md, "Snapshot {}", step.snapshot);
+
+            if let Some(note) = &step.note {
+                let _ = writeln!(md);
+                for line in note.lines() {
+                    let _ = writeln!(md, "> {line}");
+                }
+            }
+
+            if !step.output.is_empty() {
+                let _ = writeln!(md);
+                let _ = writeln!(md, "```text");
+                for line in &step.output {
+                    let _ = writeln!(md, "{line}");
+                }
+                let _ = writeln!(md, "```");
+            }
+        }
+
+        md
+    }
+}
+
+/// Recorder of the current session, saving every step to its file
+#[derive(Debug, Clone)]
+pub struct SessionRecorder {
+    /// File the recording is saved to
+    path: PathBuf,
+    /// Steps recorded so far
+    recording: SessionRecording,
+    /// Snapshot reached by the last step
+    last_snapshot: usize,
+    /// Whether the last step is waiting for its command to finish
+    awaiting: bool,
+}
+
+impl SessionRecorder {
+    /// Start recording to a file from the given snapshot
+    pub fn start(path: PathBuf, current_snapshot: usize) -> Result<Self> {
+        let recorder = Self {
+            path,
+            recording: SessionRecording::new(current_snapshot),
+            last_snapshot: current_snapshot,
+            awaiting: false,
+        };
+        recorder.recording.save(&recorder.path)?;
+        Ok(recorder)
+    }
+
+    /// Record a command, which is finished by [`Self::finish_step`] if still running
+    pub fn record(&mut self, command: &str, snapshot: usize, running: bool) {
+        self.recording.steps.push(SessionStep {
+            command: command.to_string(),
+            snapshot,
+            output: Vec::new(),
+            note: None,
+        });
+        self.last_snapshot = snapshot;
+        self.awaiting = running;
+        self.persist();
+    }
+
+    /// Finish the running command of the last step with the snapshot and output it led to
+    pub fn finish_step(&mut self, snapshot: usize, output: &str) {
+        if !self.awaiting {
+            return;
+        }
+        self.awaiting = false;
+
+        if let Some(step) = self.recording.steps.last_mut() {
+            step.snapshot = snapshot;
+            step.output.extend(output.lines().map(str::to_string));
+        }
+        self.last_snapshot = snapshot;
+        self.persist();
+    }
+
+    /// Record the navigation to another snapshot done outside of the terminal
+    pub fn observe(&mut self, snapshot: usize) {
+        if self.awaiting || snapshot == self.last_snapshot {
+            return;
+        }
+        self.record(&format!("goto {snapshot}"), snapshot, false);
+    }
+
+    /// Attach a note to the last step, returning false if nothing was recorded yet
+    pub fn note(&mut self, note: &str) -> bool {
+        let Some(step) = self.recording.steps.last_mut() else {
+            return false;
+        };
+        step.note = Some(match step.note.take() {
+            Some(previous) => format!("{previous}\n{note}"),
+            None => note.to_string(),
+        });
+        self.persist();
+        true
+    }
+
+    /// Get the file the recording is saved to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the recording so far
+    pub fn recording(&self) -> &SessionRecording {
+        &self.recording
+    }
+
+    /// Write the recording to its file
+    fn persist(&self) {
+        if let Err(e) = self.recording.save(&self.path) {
+            warn!("Failed to save session recording: {e}");
+        }
+    }
+}
+
+/// Replay of a recorded session, one step at a time
+#[derive(Debug, Clone)]
+pub struct SessionReplay {
+    /// Recording being replayed
+    recording: SessionRecording,
+    /// Number of steps replayed so far
+    position: usize,
+}
+
+impl SessionReplay {
+    /// Replay a recording from its first step
+    pub fn new(recording: SessionRecording) -> Self {
+        Self { recording, position: 0 }
+    }
+
+    /// Get the next step to replay
+    pub fn next_step(&mut self) -> Option<&SessionStep> {
+        let step = self.recording.steps.get(self.position)?;
+        self.position += 1;
+        Some(step)
+    }
+
+    /// Get the step before the last replayed one, going back one step
+    pub fn prev_step(&mut self) -> Option<&SessionStep> {
+        if self.position < 2 {
+            return None;
+        }
+        self.position -= 1;
+        self.recording.steps.get(self.position - 1)
+    }
+
+    /// Get the number of steps replayed so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the recording being replayed
+    pub fn recording(&self) -> &SessionRecording {
+        &self.recording
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorder() -> SessionRecorder {
+        SessionRecorder {
+            path: PathBuf::new(),
+            recording: SessionRecording::new(0),
+            last_snapshot: 0,
+            awaiting: false,
+        }
+    }
+
+    #[test]
+    fn test_recorder_steps() {
+        let mut recorder = recorder();
+
+        // Navigation from the panels is only recorded when the snapshot changes
+        recorder.observe(0);
+        recorder.observe(5);
+
+        // Running commands are finished by their output
+        recorder.record("$ balances[owner]", 5, true);
+        recorder.observe(7);
+        recorder.finish_step(5, "uint256(42)");
+        assert!(recorder.note("Owner's balance"));
+
+        let commands: Vec<_> =
+            recorder.recording().steps.iter().map(|step| step.command.as_str()).collect();
+        assert_eq!(commands, ["goto 5", "$ balances[owner]"]);
+        assert_eq!(recorder.recording().steps[1].output, ["uint256(42)"]);
+        assert_eq!(recorder.recording().steps[1].note.as_deref(), Some("Owner's balance"));
+    }
+
+    #[test]
+    fn test_replay_back_and_forth() {
+        let mut recorder = recorder();
+        recorder.record("next", 1, false);
+        recorder.record("next", 2, false);
+        let mut replay = SessionReplay::new(recorder.recording().clone());
+
+        assert_eq!(replay.prev_step(), None);
+        assert_eq!(replay.next_step().map(|step| step.snapshot), Some(1));
+        assert_eq!(replay.next_step().map(|step| step.snapshot), Some(2));
+        assert_eq!(replay.next_step(), None);
+        assert_eq!(replay.prev_step().map(|step| step.snapshot), Some(1));
+        assert_eq!(replay.position(), 1);
+    }
+
+    #[test]
+    fn test_markdown_walkthrough() {
+        let mut recording = SessionRecording::new(3);
+        recording.steps.push(SessionStep {
+            command: "$ owner".to_string(),
+            snapshot: 3,
+            output: vec!["0x0000000000000000000000000000000000000001".to_string()],
+            note: Some("The owner was never set".to_string()),
+        });
+
+        let md = recording.to_markdown("Exploit walkthrough");
+        assert!(md.starts_with("# Exploit walkthrough\n"));
+        assert!(md.contains("## Step 1: `$ owner`\n\nSnapshot 3\n\n> The owner was never set\n"));
+        assert!(md.contains("```text\n0x0000000000000000000000000000000000000001\n```\n"));
+    }
+}
@@ -26,7 +26,7 @@ use crate::{
     ColorScheme,
 };
 use eyre::Result;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Direct theme configuration without unnecessary wrapping
 #[derive(Debug, Clone)]
@@ -54,21 +54,16 @@ impl Deref for Theme {
 impl Default for Theme {
     fn default() -> Self {
         let config = Config::load().unwrap_or_default();
-        let color_scheme = if let Some(theme) = config.get_active_theme() {
-            (*theme).into()
-        } else {
+        let color_scheme = config.active_color_scheme().unwrap_or_else(|e| {
+            warn!("Failed to load theme '{}': {}", config.theme.active, e);
             ColorScheme::default()
-        };
+        });
 
         Self {
             color_scheme,
             panel_configs: config.panels.clone(),
             active_theme: config.theme.active.clone(),
-            _available_themes: config
-                .list_themes()
-                .into_iter()
-                .map(|(name, _)| name.clone())
-                .collect(),
+            _available_themes: config.list_themes().into_iter().map(|(name, ..)| name).collect(),
             config,
         }
     }
@@ -87,29 +82,31 @@ impl Theme {
     }
 
     /// Switch to a different theme by name
+    ///
+    /// The config files are re-read first, so that custom themes added since startup can be
+    /// selected and settings persisted elsewhere (e.g., watches) are kept. Only the theme
+    /// choice is written back to `~/.edb.toml`.
     pub fn switch_theme(&mut self, theme_name: &str) -> Result<()> {
-        self.config.set_theme(theme_name)?;
-        self.config.save()?;
+        let mut config = Config::load().unwrap_or_else(|_| self.config.clone());
+        config.set_theme(theme_name)?;
+        let color_scheme = config.active_color_scheme()?;
+
+        let mut user_config = Config::load_global()?;
+        user_config.theme.active = config.theme.active.clone();
+        user_config.save()?;
 
         // Update current state
-        if let Some(theme) = self.config.get_active_theme() {
-            self.color_scheme = (*theme).into();
-            self.active_theme = theme_name.to_string();
-            info!("Theme switched to: {}", theme_name);
-        }
+        self.color_scheme = color_scheme;
+        self.active_theme = config.theme.active.clone();
+        self.config = config;
+        info!("Theme switched to: {}", self.active_theme);
 
         Ok(())
     }
 
-    /// Get list of available themes
+    /// Get list of available themes as (name, display name, description)
     pub fn list_themes(&self) -> Vec<(String, String, String)> {
-        self.config
-            .list_themes()
-            .into_iter()
-            .map(|(name, theme)| {
-                (name.clone(), theme.display_name().to_string(), theme.description().to_string())
-            })
-            .collect()
+        self.config.list_themes()
     }
 
     /// Get current theme name
@@ -128,10 +125,8 @@ impl Theme {
         self.config = new_config.clone();
         self.panel_configs = new_config.panels.clone();
 
-        if let Some(theme) = new_config.get_active_theme() {
-            self.color_scheme = (*theme).into();
-            self.active_theme = new_config.theme.active.clone();
-        }
+        self.color_scheme = new_config.active_color_scheme()?;
+        self.active_theme = new_config.theme.active.clone();
 
         debug!("Theme configuration reloaded");
         Ok(())
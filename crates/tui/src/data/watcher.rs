@@ -14,9 +14,17 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+//! Watch expressions evaluated on every snapshot
+//!
+//! Watches are persisted in the config file (`~/.edb.toml`) so that they survive
+//! across debugging sessions.
+
 use std::collections::HashSet;
 
 use edb_common::normalize_expression;
+use tracing::warn;
+
+use crate::config::{Config, WatchConfig};
 
 /// Watcher for monitoring user-defined expressions
 #[derive(Debug, Clone, Default)]
@@ -25,33 +33,115 @@ pub struct Watcher {
     expressions: Vec<String>,
     /// Keys of the expressions in the current state
     expression_keys: HashSet<String>,
+    /// Keys of the pinned expressions
+    pinned_keys: HashSet<String>,
+    /// Whether changes are written back to the config file
+    persistent: bool,
 }
 
 impl Watcher {
+    /// Load the watches persisted in the config file
+    ///
+    /// Any later change to the returned watcher is written back to the config file.
+    pub fn load() -> Self {
+        let config = Config::load().unwrap_or_default();
+        let mut watcher = Self::from_config(&config.watches);
+        watcher.persistent = true;
+        watcher
+    }
+
+    /// Create a (non-persistent) watcher from persisted watch entries
+    pub fn from_config(watches: &[WatchConfig]) -> Self {
+        let mut watcher = Self::default();
+        for watch in watches {
+            let expr_key = normalize_expression(&watch.expression);
+            if watcher.expression_keys.insert(expr_key.clone()) {
+                watcher.expressions.push(watch.expression.clone());
+                if watch.pinned {
+                    watcher.pinned_keys.insert(expr_key);
+                }
+            }
+        }
+        watcher
+    }
+
+    /// Convert the watches into entries for the config file
+    pub fn to_config(&self) -> Vec<WatchConfig> {
+        self.expressions
+            .iter()
+            .map(|expr| WatchConfig { expression: expr.clone(), pinned: self.is_pinned(expr) })
+            .collect()
+    }
+
     /// Add a new expression to the watcher
     pub fn add_expression(&mut self, expr: String) -> Option<usize> {
         let expr_key = normalize_expression(&expr);
         if !self.expression_keys.contains(&expr_key) {
             self.expression_keys.insert(expr_key);
             self.expressions.push(expr);
+            self.persist();
             Some(self.expressions.len()) // Return 1-based index
         } else {
             None
         }
     }
 
+    /// Replace the expression with the given ID, keeping its position and pin state
+    ///
+    /// Returns the previous expression, or `None` if the ID is unknown or the new
+    /// expression is already watched under another ID.
+    pub fn edit_expression(&mut self, expr_id: usize, new_expr: String) -> Option<String> {
+        let index = expr_id.checked_sub(1).filter(|i| *i < self.expressions.len())?;
+        let old_key = normalize_expression(&self.expressions[index]);
+        let new_key = normalize_expression(&new_expr);
+        if new_key != old_key && self.expression_keys.contains(&new_key) {
+            return None;
+        }
+
+        self.expression_keys.remove(&old_key);
+        self.expression_keys.insert(new_key.clone());
+        if self.pinned_keys.remove(&old_key) {
+            self.pinned_keys.insert(new_key);
+        }
+
+        let old_expr = std::mem::replace(&mut self.expressions[index], new_expr);
+        self.persist();
+        Some(old_expr)
+    }
+
     /// Remove an expression from the watcher
     pub fn remove_expression(&mut self, expr_id: usize) -> Option<String> {
         if expr_id > 0 && expr_id <= self.expressions.len() {
             let expr = self.expressions.remove(expr_id.saturating_sub(1));
             let expr_key = normalize_expression(&expr);
             self.expression_keys.remove(&expr_key);
+            self.pinned_keys.remove(&expr_key);
+            self.persist();
             Some(expr)
         } else {
             None
         }
     }
 
+    /// Toggle the pin state of an expression, returning the new state
+    pub fn toggle_pin(&mut self, expr_id: usize) -> Option<bool> {
+        let expr = self.expressions.get(expr_id.checked_sub(1)?)?;
+        let expr_key = normalize_expression(expr);
+        let pinned = if self.pinned_keys.remove(&expr_key) {
+            false
+        } else {
+            self.pinned_keys.insert(expr_key);
+            true
+        };
+        self.persist();
+        Some(pinned)
+    }
+
+    /// Check whether an expression is pinned
+    pub fn is_pinned(&self, expr: &str) -> bool {
+        self.pinned_keys.contains(&normalize_expression(expr))
+    }
+
     /// Return a list of all watched expressions with their IDs
     pub fn list_expressions(&self) -> impl Iterator<Item = (usize, &String)> {
         // Return 1-based index for user-friendly display
@@ -66,5 +156,53 @@ impl Watcher {
     /// Clear all expressions from the watcher
     pub fn clear(&mut self) {
         self.expressions.clear();
+        self.expression_keys.clear();
+        self.pinned_keys.clear();
+        self.persist();
+    }
+
+    /// Write the watches back to the config file
+    fn persist(&self) {
+        if !self.persistent {
+            return;
+        }
+
+        let result = Config::load_global().and_then(|mut config| {
+            config.watches = self.to_config();
+            config.save()
+        });
+        if let Err(e) = result {
+            warn!("Failed to persist watch expressions: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher_edit_pin_and_config_roundtrip() {
+        let mut watcher = Watcher::default();
+        assert_eq!(watcher.add_expression("a + b".to_string()), Some(1));
+        assert_eq!(watcher.add_expression("balance".to_string()), Some(2));
+        assert_eq!(watcher.add_expression("balance".to_string()), None);
+
+        // Editing into an existing expression is rejected
+        assert_eq!(watcher.edit_expression(1, "balance".to_string()), None);
+
+        assert_eq!(watcher.toggle_pin(1), Some(true));
+        assert_eq!(watcher.edit_expression(1, "a * b".to_string()), Some("a + b".to_string()));
+        assert!(watcher.is_pinned("a * b"));
+        assert!(!watcher.is_pinned("balance"));
+
+        let restored = Watcher::from_config(&watcher.to_config());
+        let exprs: Vec<_> = restored.list_expressions().map(|(_, e)| e.clone()).collect();
+        assert_eq!(exprs, vec!["a * b".to_string(), "balance".to_string()]);
+        assert!(restored.is_pinned("a * b"));
+
+        watcher.clear();
+        assert_eq!(watcher.count(), 0);
+        assert_eq!(watcher.add_expression("balance".to_string()), Some(1));
     }
 }
@@ -16,7 +16,20 @@
 
 //! Adaptive layout management
 //!
-//! This module handles responsive layout switching based on terminal size.
+//! This module handles responsive layout switching based on terminal size. Panel
+//! sizes and the panels shown in each layout are persisted in the `[layout]` section
+//! of `~/.edb.toml`, and restored on the next start.
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{config::Config, panels::PanelType};
+
+/// Bounds of the width of the left panels in the full layout, in percent
+pub const VERTICAL_SPLIT_RANGE: (u16, u16) = (20, 80);
+
+/// Bounds of the height of the top panels, in percent
+pub const HORIZONTAL_SPLIT_RANGE: (u16, u16) = (30, 80);
 
 /// Configuration for layout manager
 #[derive(Debug, Clone, Default)]
@@ -36,18 +49,122 @@ pub enum LayoutType {
     Mobile,
 }
 
+/// Panel layout arranged by the user
+///
+/// ```toml
+/// [layout]
+/// vertical_split = 60
+/// horizontal_split = 45
+/// left_panel = "code"
+/// main_panel = "trace"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutSettings {
+    /// Width of the left panels in the full layout, in percent
+    pub vertical_split: u16,
+    /// Height of the top panels, in percent
+    pub horizontal_split: u16,
+    /// Panel shown on the left of the full layout (trace, code or events)
+    pub left_panel: PanelType,
+    /// Panel shown above the terminal in the compact layout
+    pub main_panel: PanelType,
+}
+
+impl Default for LayoutSettings {
+    fn default() -> Self {
+        Self {
+            vertical_split: 50,
+            horizontal_split: 50,
+            left_panel: PanelType::Trace,
+            main_panel: PanelType::Trace,
+        }
+    }
+}
+
+impl LayoutSettings {
+    /// Bring the splits within their bounds, and the panels to ones their layout can show
+    pub fn sanitized(self) -> Self {
+        let left_panel = match self.left_panel {
+            PanelType::Trace | PanelType::Code | PanelType::Events => self.left_panel,
+            PanelType::Display | PanelType::Terminal => PanelType::Trace,
+        };
+        let main_panel = match self.main_panel {
+            PanelType::Terminal => PanelType::Trace,
+            panel => panel,
+        };
+
+        Self {
+            vertical_split: self
+                .vertical_split
+                .clamp(VERTICAL_SPLIT_RANGE.0, VERTICAL_SPLIT_RANGE.1),
+            horizontal_split: self
+                .horizontal_split
+                .clamp(HORIZONTAL_SPLIT_RANGE.0, HORIZONTAL_SPLIT_RANGE.1),
+            left_panel,
+            main_panel,
+        }
+    }
+}
+
 /// Layout manager for responsive design
 #[derive(Debug)]
 pub struct LayoutManager {
     current_layout: LayoutType,
     terminal_width: u16,
     terminal_height: u16,
+    /// Layout arranged by the user, as last persisted
+    settings: LayoutSettings,
+    /// Whether layout changes are written back to the config file
+    persistent: bool,
 }
 
 impl LayoutManager {
     /// Create a new layout manager with default values
     pub fn new() -> Self {
-        Self { current_layout: LayoutType::Full, terminal_width: 80, terminal_height: 24 }
+        Self {
+            current_layout: LayoutType::Full,
+            terminal_width: 80,
+            terminal_height: 24,
+            settings: LayoutSettings::default(),
+            persistent: false,
+        }
+    }
+
+    /// Create a layout manager restoring the layout persisted in the config file
+    ///
+    /// Any later layout change is written back to the config file.
+    pub fn load() -> Self {
+        let settings = Config::load().map(|config| config.layout.sanitized()).unwrap_or_else(|e| {
+            warn!("Failed to load the panel layout: {e}");
+            LayoutSettings::default()
+        });
+        Self { settings, persistent: true, ..Self::new() }
+    }
+
+    /// Get the layout arranged by the user
+    pub fn settings(&self) -> LayoutSettings {
+        self.settings
+    }
+
+    /// Remember the layout arranged by the user, persisting it if it changed
+    pub fn update_settings(&mut self, settings: LayoutSettings) {
+        let settings = settings.sanitized();
+        if settings == self.settings {
+            return;
+        }
+        self.settings = settings;
+
+        if !self.persistent {
+            return;
+        }
+        let result = Config::load_global().and_then(|mut config| {
+            config.layout = settings;
+            config.save()
+        });
+        if let Err(e) = result {
+            warn!("Failed to persist the panel layout: {e}");
+        }
     }
 
     /// Update terminal dimensions and recalculate layout
@@ -139,6 +256,23 @@ mod tests {
         assert!(!manager.supports_multiple_panels());
     }
 
+    #[test]
+    fn test_layout_settings() {
+        let settings: LayoutSettings =
+            toml::from_str("vertical_split = 95\nleft_panel = \"terminal\"\nmain_panel = \"code\"")
+                .unwrap();
+        assert_eq!(settings.horizontal_split, 50);
+
+        let settings = settings.sanitized();
+        assert_eq!(settings.vertical_split, VERTICAL_SPLIT_RANGE.1);
+        assert_eq!(settings.left_panel, PanelType::Trace);
+        assert_eq!(settings.main_panel, PanelType::Code);
+
+        let mut manager = LayoutManager::new();
+        manager.update_settings(LayoutSettings { horizontal_split: 10, ..settings });
+        assert_eq!(manager.settings().horizontal_split, HORIZONTAL_SPLIT_RANGE.0);
+    }
+
     #[test]
     fn test_min_widths() {
         assert_eq!(LayoutManager::min_width_for_layout(LayoutType::Full), 120);
@@ -21,16 +21,20 @@
 //! This crate provides a terminal-based interface for interacting with the EDB engine.
 
 mod app;
+mod collab;
 mod config;
 mod data;
 mod layout;
 mod panels;
 mod rpc;
+#[cfg(test)]
+mod test_utils;
 mod ui;
 
 pub use app::App;
+pub use collab::SessionServerConfig;
 pub use config::Config;
-pub use layout::{LayoutConfig, LayoutManager, LayoutType};
+pub use layout::{LayoutConfig, LayoutManager, LayoutSettings, LayoutType};
 pub use panels::EventResponse;
 pub use rpc::RpcClient;
 pub use ui::{
@@ -40,24 +44,40 @@ pub use ui::{
 };
 
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyEvent},
+    event::{
+        DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent,
+        KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use edb_common::{PreparationState, PreparationStatus, RpcClientOptions};
 use eyre::{bail, Result};
 use futures::{FutureExt, StreamExt};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout},
+    widgets::{Block, Gauge, Paragraph},
+    Frame, Terminal,
+};
 use std::{io, sync::Arc, time::Duration};
 use tokio::{select, time::interval};
 use tracing::{debug, error, info, warn};
 
-use crate::data::DataManager;
+use crate::{collab::Collaboration, data::DataManager};
+
+/// Interval at which the preparation status is polled before the TUI starts
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 /// Configuration for the TUI
 #[derive(Debug, Clone)]
 pub struct TuiConfig {
     /// RPC endpoint URL
     pub rpc_url: String,
+    /// Bearer token and CA certificate of the RPC server, if it requires them
+    pub rpc_options: RpcClientOptions,
+    /// Session of `edb server` to join instead of connecting to `rpc_url` directly
+    pub session_server: Option<SessionServerConfig>,
     /// Terminal refresh interval
     pub refresh_interval: Duration,
     /// Data fetch interval
@@ -70,6 +90,8 @@ impl Default for TuiConfig {
     fn default() -> Self {
         Self {
             rpc_url: "http://localhost:3030".to_string(),
+            rpc_options: RpcClientOptions::default(),
+            session_server: None,
             refresh_interval: Duration::from_millis(50),
             data_fetch_interval: Duration::from_millis(200),
             enable_mouse: false,
@@ -85,12 +107,25 @@ pub struct Tui {
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
     /// Configuration settings for the TUI behavior
     config: TuiConfig,
+    /// Shared cursor of the `edb server` session, if one was joined
+    collaboration: Option<Collaboration>,
 }
 
 impl Tui {
     /// Create a new TUI instance
-    pub async fn new(config: TuiConfig) -> Result<Self> {
-        info!("Initializing TUI with config: {:?}", config);
+    pub async fn new(mut config: TuiConfig) -> Result<Self> {
+        info!("Initializing TUI for {}", config.rpc_url);
+
+        // Join the session before taking over the terminal, so its preparation is visible
+        let collaboration = match &config.session_server {
+            Some(session_server) => {
+                let joined = collab::join(session_server, &config.rpc_options).await?;
+                config.rpc_url = joined.rpc_url;
+                config.rpc_options.auth_token = joined.rpc_token;
+                Some(joined.collaboration)
+            }
+            None => None,
+        };
 
         // Setup terminal
         enable_raw_mode()?;
@@ -105,17 +140,23 @@ impl Tui {
         let terminal = Terminal::new(backend)?;
 
         // Create RPC client
-        let rpc_client = Arc::new(RpcClient::new(&config.rpc_url).await?);
+        let rpc_client =
+            Arc::new(RpcClient::with_options(&config.rpc_url, config.rpc_options.clone()).await?);
 
         // Create app with layout manager
         let layout_config = LayoutConfig { enable_mouse: config.enable_mouse };
         let app = App::new(rpc_client, layout_config).await?;
 
-        Ok(Self { app, terminal, config })
+        Ok(Self { app, terminal, config, collaboration })
     }
 
     /// Run the main TUI event loop
     pub async fn run(mut self) -> Result<()> {
+        if !self.wait_for_preparation().await? {
+            info!("Exit requested while waiting for preparation");
+            return Ok(());
+        }
+
         info!("Starting TUI event loop");
 
         // Create DataManager
@@ -233,6 +274,12 @@ impl Tui {
 
                     // Pull updates from cores
                     data_manager.process_core_updates()?;
+
+                    // Follow and share the cursor of the joined session
+                    if let Some(collaboration) = &mut self.collaboration {
+                        collaboration.sync(&mut data_manager);
+                        self.app.set_session_status(Some(collaboration.status()));
+                    }
                 }
             }
 
@@ -251,6 +298,50 @@ impl Tui {
         result
     }
 
+    /// Wait until the transaction behind the RPC URL is prepared, showing its progress
+    ///
+    /// Returns false if the user quit while waiting.
+    async fn wait_for_preparation(&mut self) -> Result<bool> {
+        let mut event_stream = EventStream::new();
+        let mut ticker = interval(STATUS_POLL_INTERVAL);
+
+        loop {
+            let status = match self.app.rpc_client.preparation_status().await {
+                Ok(status) => status,
+                Err(e) => {
+                    // Servers without a status endpoint only start once the transaction is ready
+                    debug!("No preparation status available: {}", e);
+                    return Ok(true);
+                }
+            };
+
+            match status.state {
+                PreparationState::Ready => return Ok(true),
+                PreparationState::Failed => {
+                    bail!("Preparation failed: {}", status.error.unwrap_or_default())
+                }
+                PreparationState::Cancelled => bail!("Preparation cancelled"),
+                PreparationState::Preparing => {}
+            }
+
+            self.terminal.draw(|frame| render_preparation_status(frame, &status))?;
+
+            select! {
+                event_result = event_stream.next() => {
+                    if let Some(Ok(Event::Key(key_event))) = event_result {
+                        let quit = key_event.code == KeyCode::Char('q')
+                            || (key_event.code == KeyCode::Char('c')
+                                && key_event.modifiers.contains(KeyModifiers::CONTROL));
+                        if quit {
+                            return Ok(false);
+                        }
+                    }
+                }
+                _ = ticker.tick() => {}
+            }
+        }
+    }
+
     // Handle a single resize event
     fn handle_resize(&mut self, width: u16, height: u16) {
         debug!("Terminal resized: {}x{}", width, height);
@@ -282,6 +373,24 @@ impl Tui {
     }
 }
 
+/// Render the progress of a preparation that is still running
+fn render_preparation_status(frame: &mut Frame<'_>, status: &PreparationStatus) {
+    let [_, gauge_area, hint_area, _] = Layout::vertical([
+        Constraint::Fill(1),
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Fill(1),
+    ])
+    .areas(frame.area());
+
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(format!(" Preparing {} ", status.tx_hash)))
+        .ratio((status.percent / 100.0).clamp(0.0, 1.0))
+        .label(status.to_string());
+    frame.render_widget(gauge, gauge_area);
+    frame.render_widget(Paragraph::new("Press q to quit").centered(), hint_area);
+}
+
 impl Drop for Tui {
     fn drop(&mut self) {
         // Restore terminal state
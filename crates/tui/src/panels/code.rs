@@ -17,26 +17,32 @@
 //! Code panel for displaying source code or opcodes
 //!
 //! This panel shows source code with syntax highlighting and current line indication.
+//! Press `/` to search incrementally within the displayed code, and `Ctrl-P` to open
+//! a fuzzy picker across the source files of every contract in the trace. Press `d`
+//! to show the disassembly next to the source, linked to it through the source map.
+//! Press `H` to color the line-number gutter by the gas spent on each line.
 
-use super::{EventResponse, PanelTr, PanelType};
+use super::{utils, EventResponse, HelpEntry, PanelTr, PanelType, Yank};
 use crate::data::DataManager;
 use crate::ui::borders::BorderPresets;
 use crate::ui::status::{FileStatus, StatusBar};
 use crate::ui::syntax::{SyntaxHighlighter, SyntaxType};
 use alloy_primitives::Address;
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use edb_common::types::{Breakpoint, BreakpointLocation, Code, SnapshotInfoDetail};
+use edb_common::types::{
+    Breakpoint, BreakpointLocation, Code, GasHeatmap, ModifierFrame, SnapshotInfoDetail,
+};
 use eyre::Result;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
     Frame,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 /// Code display mode
@@ -74,6 +80,80 @@ struct CodeDisplayInfo {
     file_info: Vec<FileInfo>,
 }
 
+/// Incremental search state (`/`)
+#[derive(Debug, Default)]
+struct SourceSearch {
+    /// Whether the search prompt is open
+    active: bool,
+    /// Query typed so far
+    query: String,
+    /// Last confirmed query, repeated by `/` followed by Enter
+    last_query: String,
+    /// Cursor line when the search started (restored on cancel)
+    origin: usize,
+}
+
+/// A source file offered by the fuzzy file picker
+#[derive(Debug, Clone)]
+struct FilePickerEntry {
+    /// Source file path
+    path: String,
+    /// Bytecode address of the contract the file belongs to
+    address: Address,
+    /// Snapshot to display when the file belongs to another contract
+    snapshot: Option<usize>,
+    /// Contract label shown for files of other contracts
+    contract: Option<String>,
+}
+
+/// Fuzzy file picker state (`Ctrl-P`)
+#[derive(Debug, Default)]
+struct FilePicker {
+    /// Query typed so far
+    query: String,
+    /// All candidate files
+    entries: Vec<FilePickerEntry>,
+    /// Indices into `entries` matching the query, best match first
+    matches: Vec<usize>,
+    /// Selected index into `matches`
+    selected: usize,
+}
+
+impl FilePicker {
+    /// Recompute the matches for the current query
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                utils::fuzzy_score(&self.query, &entry.path).map(|score| (score, idx))
+            })
+            .collect();
+        if !self.query.is_empty() {
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        }
+        self.matches = scored.into_iter().map(|(_, idx)| idx).collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    /// Get the selected entry
+    fn selected_entry(&self) -> Option<&FilePickerEntry> {
+        self.matches.get(self.selected).and_then(|idx| self.entries.get(*idx))
+    }
+}
+
+/// Disassembly shown next to the source in split view (`d`)
+#[derive(Debug)]
+struct DisassemblyPane {
+    /// Contract and source file the rows were mapped against
+    key: (Address, String),
+    /// Program counter, instruction and the 1-based source line it maps to in that file
+    rows: Vec<(usize, String, Option<usize>)>,
+    /// Scroll offset
+    scroll_offset: usize,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum DisplayLocation {
     Unknown,
@@ -87,8 +167,12 @@ pub struct CodePanel {
     // ========== Display ==========
     /// Current execution line (server-controlled, 1-based)
     current_execution_line: Option<usize>,
+    /// Virtual frame of the modifier being evaluated at the execution line
+    modifier_frame: Option<ModifierFrame>,
     /// User cursor line (user-controlled for breakpoints, 1-based)
     user_cursor_line: Option<usize>,
+    /// Line where the line selection started, if selecting (1-based)
+    selection_anchor: Option<usize>,
     /// Scroll offset
     scroll_offset: usize,
     /// Whether this panel is focused
@@ -119,6 +203,18 @@ pub struct CodePanel {
     vim_command_buffer: String,
     /// Whether we're in VIM command mode (after pressing :)
     vim_command_mode: bool,
+    /// Incremental search state
+    search: SourceSearch,
+    /// Fuzzy file picker, if open
+    file_picker: Option<FilePicker>,
+    /// File picked from another contract, selected once that contract is displayed
+    pending_file: Option<(Address, String)>,
+    /// Whether the disassembly is shown next to the source
+    split_view: bool,
+    /// Disassembly pane of the split view
+    disassembly: Option<DisassemblyPane>,
+    /// Whether line numbers are colored by gas consumption
+    gas_heatmap: bool,
 
     // ========== Data (Flag) ==========
     /// Current display snapshot id
@@ -162,7 +258,9 @@ impl CodePanel {
             opcodes: Vec::new(),
             selected_path_index: 0,
             current_execution_line: Some(1),
+            modifier_frame: None,
             user_cursor_line: Some(1),
+            selection_anchor: None,
             scroll_offset: 0,
             focused: false,
             show_file_selector: false,
@@ -178,6 +276,12 @@ impl CodePanel {
             vim_number_prefix: String::new(),
             vim_command_buffer: String::new(),
             vim_command_mode: false,
+            search: SourceSearch::default(),
+            file_picker: None,
+            pending_file: None,
+            split_view: false,
+            disassembly: None,
+            gas_heatmap: false,
         }
     }
 
@@ -189,6 +293,13 @@ impl CodePanel {
         }
     }
 
+    /// Get the lines of the current line selection (1-based, inclusive)
+    fn selected_lines(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        let anchor = self.selection_anchor?;
+        let cursor = self.user_cursor_line?;
+        Some(anchor.min(cursor)..=anchor.max(cursor))
+    }
+
     /// Calculate the maximum line width including line numbers and indicators
     fn calculate_max_line_width(&mut self) {
         let lines = self.get_display_lines();
@@ -318,6 +429,319 @@ impl CodePanel {
         }
     }
 
+    /// Whether a line matches the search query (case-insensitive unless the query
+    /// contains uppercase characters)
+    fn line_matches_query(line: &str, query: &str) -> bool {
+        if query.chars().any(char::is_uppercase) {
+            line.contains(query)
+        } else {
+            line.to_lowercase().contains(query)
+        }
+    }
+
+    /// Find the next (or previous) line matching `query`, starting at `from` (1-based,
+    /// inclusive) and wrapping around
+    fn find_match(&self, query: &str, from: usize, forward: bool) -> Option<usize> {
+        let lines = self.get_display_lines();
+        if query.is_empty() || lines.is_empty() {
+            return None;
+        }
+
+        let count = lines.len();
+        let start = from.clamp(1, count) - 1;
+        (0..count)
+            .map(
+                |step| {
+                    if forward {
+                        (start + step) % count
+                    } else {
+                        (start + count - step) % count
+                    }
+                },
+            )
+            .find(|idx| Self::line_matches_query(&lines[*idx], query))
+            .map(|idx| idx + 1)
+    }
+
+    /// Count the lines matching the search query
+    fn count_matches(&self, query: &str) -> usize {
+        if query.is_empty() {
+            return 0;
+        }
+        self.get_display_lines().iter().filter(|line| Self::line_matches_query(line, query)).count()
+    }
+
+    /// Open the search prompt
+    fn start_search(&mut self) {
+        if !self.search.query.is_empty() {
+            self.search.last_query = mem::take(&mut self.search.query);
+        }
+        self.search.active = true;
+        self.search.origin = self.user_cursor_line.unwrap_or(1);
+    }
+
+    /// Handle a key while the search prompt is open
+    fn handle_search_key(&mut self, event: KeyEvent) -> EventResponse {
+        let current = self.user_cursor_line.unwrap_or(1);
+        match event.code {
+            KeyCode::Esc => {
+                self.search.active = false;
+                self.search.query.clear();
+                self.move_to(self.search.origin);
+            }
+            KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search.active = false;
+                self.search.query.clear();
+                self.move_to(self.search.origin);
+            }
+            KeyCode::Enter => {
+                self.search.active = false;
+                if self.search.query.is_empty() {
+                    // Repeat the last search from the line after the cursor
+                    self.search.query = self.search.last_query.clone();
+                    if let Some(line) = self.find_match(&self.search.query, current + 1, true) {
+                        self.move_to(line);
+                    }
+                }
+                self.search.last_query = self.search.query.clone();
+            }
+            KeyCode::Down => {
+                if let Some(line) = self.find_match(&self.search.query, current + 1, true) {
+                    self.move_to(line);
+                }
+            }
+            KeyCode::Up => {
+                let from = if current > 1 { current - 1 } else { self.get_display_lines().len() };
+                if let Some(line) = self.find_match(&self.search.query, from, false) {
+                    self.move_to(line);
+                }
+            }
+            KeyCode::Backspace | KeyCode::Char(_) => {
+                match event.code {
+                    KeyCode::Char(c) => self.search.query.push(c),
+                    _ => {
+                        self.search.query.pop();
+                    }
+                }
+
+                // Incremental: jump to the first match from where the search started
+                match self.find_match(&self.search.query, self.search.origin, true) {
+                    Some(line) => self.move_to(line),
+                    None => self.move_to(self.search.origin),
+                }
+            }
+            _ => {}
+        }
+        EventResponse::Handled
+    }
+
+    /// Collect the source files of the displayed contract and of every contract in the trace
+    ///
+    /// Code of other contracts is fetched on demand, so their files appear once available.
+    fn collect_picker_entries(&self, dm: &mut DataManager) -> Vec<FilePickerEntry> {
+        let current_address = match &self.display_info.location {
+            DisplayLocation::Hook(addr, _) | DisplayLocation::Opcode(addr) => Some(*addr),
+            DisplayLocation::Unknown => None,
+        };
+
+        let mut entries: Vec<FilePickerEntry> = match current_address {
+            Some(address) if self.display_info.has_source_code => self
+                .display_info
+                .file_info
+                .iter()
+                .map(|info| FilePickerEntry {
+                    path: info.path.clone(),
+                    address,
+                    snapshot: None,
+                    contract: None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // One snapshot per contract, in trace order
+        let mut seen: HashSet<Address> = current_address.into_iter().collect();
+        let contracts: Vec<(Address, usize)> = dm
+            .execution
+            .get_trace()
+            .iter()
+            .filter_map(|entry| entry.first_snapshot_id.map(|id| (entry.code_address, id)))
+            .filter(|(address, _)| seen.insert(*address))
+            .collect();
+
+        for (address, snapshot) in contracts {
+            let Some(Code::Source(info)) = dm.execution.get_code_by_bytecode_address(address)
+            else {
+                continue;
+            };
+            let mut paths: Vec<String> =
+                info.sources.keys().map(|p| p.as_os_str().to_string_lossy().to_string()).collect();
+            paths.sort();
+
+            let contract = dm.resolver.resolve_address(address);
+            entries.extend(paths.into_iter().map(|path| FilePickerEntry {
+                path,
+                address,
+                snapshot: Some(snapshot),
+                contract: Some(contract.clone()),
+            }));
+        }
+
+        entries
+    }
+
+    /// Open the fuzzy file picker
+    fn open_file_picker(&mut self, dm: &mut DataManager) {
+        let mut picker =
+            FilePicker { entries: self.collect_picker_entries(dm), ..Default::default() };
+        picker.update_matches();
+        self.file_picker = Some(picker);
+    }
+
+    /// Handle a key while the fuzzy file picker is open
+    fn handle_file_picker_key(
+        &mut self,
+        event: KeyEvent,
+        dm: &mut DataManager,
+    ) -> Result<EventResponse> {
+        let Some(picker) = self.file_picker.as_mut() else {
+            return Ok(EventResponse::NotHandled);
+        };
+
+        match event.code {
+            KeyCode::Esc => self.file_picker = None,
+            KeyCode::Char('c' | 'p') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.file_picker = None;
+            }
+            KeyCode::Char('n') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                picker.selected = (picker.selected + 1).min(picker.matches.len().saturating_sub(1));
+            }
+            KeyCode::Down => {
+                picker.selected = (picker.selected + 1).min(picker.matches.len().saturating_sub(1));
+            }
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                picker.query.pop();
+                picker.selected = 0;
+                picker.update_matches();
+            }
+            KeyCode::Char(c) => {
+                picker.query.push(c);
+                picker.selected = 0;
+                picker.update_matches();
+            }
+            KeyCode::Enter => {
+                let entry = picker.selected_entry().cloned();
+                self.file_picker = None;
+                if let Some(entry) = entry {
+                    if let Some(snapshot) = entry.snapshot {
+                        dm.execution.display(snapshot)?;
+                    }
+                    self.pending_file = Some((entry.address, entry.path));
+                    self.apply_pending_file();
+                }
+            }
+            _ => {}
+        }
+        Ok(EventResponse::Handled)
+    }
+
+    /// Select the file picked from the fuzzy picker once its contract is displayed
+    fn apply_pending_file(&mut self) {
+        let location_address = match &self.display_info.location {
+            DisplayLocation::Hook(addr, _) => *addr,
+            _ => return,
+        };
+
+        match self.pending_file.take() {
+            Some((address, path)) if address == location_address => {
+                if let Some(index) =
+                    self.display_info.file_info.iter().position(|info| info.path == path)
+                {
+                    if index != self.selected_path_index {
+                        // Pretend the previous file is shown, so that switching resets the view
+                        self.current_selected_path_id = Some(self.selected_path_index);
+                        self.selected_path_index = index;
+                    }
+                }
+            }
+            other => self.pending_file = other,
+        }
+    }
+
+    /// Render the fuzzy file picker as a popup over the code area
+    fn render_file_picker(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
+        // Pick up files of contracts whose code has been fetched in the meantime
+        let entries = self.collect_picker_entries(dm);
+        let Some(picker) = self.file_picker.as_mut() else {
+            return;
+        };
+        if entries.len() != picker.entries.len() {
+            picker.entries = entries;
+            picker.update_matches();
+        }
+
+        let width = (area.width * 4 / 5).max(20).min(area.width);
+        let height = (area.height * 3 / 5).max(6).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .title(format!("🔍 Go to file ({}/{})", picker.matches.len(), picker.entries.len()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(dm.theme.accent_color));
+        let inner = block.inner(popup);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+        if inner.height < 2 {
+            return;
+        }
+
+        let input = Line::from(vec![
+            Span::styled("> ", Style::default().fg(dm.theme.accent_color)),
+            Span::raw(picker.query.clone()),
+            Span::styled("█", Style::default().fg(dm.theme.accent_color)),
+        ]);
+        frame.render_widget(Paragraph::new(input), Rect { height: 1, ..inner });
+
+        let list_height = (inner.height - 1) as usize;
+        let scroll = (picker.selected + 1).saturating_sub(list_height);
+        let items: Vec<ListItem<'_>> = picker
+            .matches
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(list_height)
+            .map(|(idx, entry_idx)| {
+                let entry = &picker.entries[*entry_idx];
+                let mut spans = vec![Span::raw(entry.path.clone())];
+                if let Some(contract) = &entry.contract {
+                    spans.push(Span::styled(
+                        format!("  {contract}"),
+                        Style::default().fg(dm.theme.comment_color),
+                    ));
+                }
+                let style = if idx == picker.selected {
+                    Style::default()
+                        .bg(dm.theme.selection_bg)
+                        .fg(dm.theme.selection_fg)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Line::from(spans)).style(style)
+            })
+            .collect();
+        frame.render_widget(
+            List::new(items),
+            Rect { y: inner.y + 1, height: inner.height - 1, ..inner },
+        );
+    }
+
     /// Render the file selector panel
     fn render_file_selector(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
         // Calculate file selector context height for viewport calculations
@@ -445,6 +869,16 @@ impl CodePanel {
 
         let max_line_num = lines.len();
 
+        // Gas spent per line of the displayed file, if the heatmap is enabled and loaded
+        let line_gas = if self.gas_heatmap {
+            self.display_address()
+                .and_then(|address| dm.execution.get_gas_heatmap(address))
+                .map(|heatmap| self.gas_per_line(heatmap))
+        } else {
+            None
+        };
+        let max_gas = line_gas.as_ref().and_then(|gas| gas.values().max().copied()).unwrap_or(0);
+
         // Create list items with syntax highlighting, line numbers, and indicators
         let list_items: Vec<ListItem<'_>> = display_lines
             .iter()
@@ -452,7 +886,10 @@ impl CodePanel {
                 let line_num = line_idx + 1;
                 let is_execution = self.current_execution_line == Some(line_num);
                 let is_user_cursor = self.user_cursor_line == Some(line_num);
-                let has_breakpoint = self.line_has_breakpoint(line_num, dm);
+                let is_selected =
+                    self.selected_lines().is_some_and(|lines| lines.contains(&line_num));
+                let breakpoint_state = self.line_breakpoint_state(line_num, dm);
+                let has_breakpoint = breakpoint_state == Some(true);
 
                 // Start with syntax-highlighted line
                 let highlighted_line = self.highlight_line(line, line_num, max_line_num, dm);
@@ -468,9 +905,29 @@ impl CodePanel {
                     (false, false, true) => "●",  // Breakpoint only
                     (false, false, false) => " ", // Nothing
                 };
+                // Disabled breakpoints only show up when nothing else is on the line
+                let status_indicator = if status_indicator == " " && breakpoint_state == Some(false)
+                {
+                    "○"
+                } else {
+                    status_indicator
+                };
 
                 // Insert status indicator after line number
-                let mut new_spans = vec![highlighted_line.spans[0].clone()]; // Line number
+                let mut line_num_span = highlighted_line.spans[0].clone();
+                if let Some(gas) =
+                    line_gas.as_ref().and_then(|gas| gas.get(&line_num)).filter(|gas| **gas > 0)
+                {
+                    let heat = if *gas * 2 >= max_gas {
+                        dm.theme.error_color
+                    } else if *gas * 10 >= max_gas {
+                        dm.theme.warning_color
+                    } else {
+                        dm.theme.success_color
+                    };
+                    line_num_span.style = Style::default().fg(dm.theme.line_number_bg).bg(heat);
+                }
+                let mut new_spans = vec![line_num_span]; // Line number
                 new_spans.push(Span::raw(format!(" {status_indicator} │ ")));
 
                 // Add the syntax highlighted content (skip the line number span)
@@ -488,6 +945,8 @@ impl CodePanel {
                     Style::default().bg(dm.theme.current_line_bg)
                 } else if is_user_cursor {
                     Style::default().bg(dm.theme.highlight_bg)
+                } else if is_selected {
+                    Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
                 } else {
                     Style::default()
                 };
@@ -540,6 +999,15 @@ impl CodePanel {
                 ));
             }
 
+            if self.gas_heatmap {
+                status_bar = match (&line_gas, self.user_cursor_line) {
+                    (Some(gas), Some(line)) => status_bar
+                        .message(format!("Gas: {}", gas.get(&line).copied().unwrap_or_default())),
+                    (None, _) => status_bar.message("Gas: loading...".to_string()),
+                    _ => status_bar,
+                };
+            }
+
             let status_text = status_bar.build();
 
             // Add horizontal scroll indicator if content is scrollable
@@ -585,19 +1053,27 @@ impl CodePanel {
             let help_text = if self.vim_command_mode {
                 // Show VIM command mode prompt
                 format!(":{}", self.vim_command_buffer)
+            } else if self.search.active {
+                let matches = self.count_matches(&self.search.query);
+                format!(
+                    "/{}█ [{matches} matches] • ↑/↓: Prev/Next • Enter: Done • Esc: Cancel",
+                    self.search.query
+                )
             } else if self.show_file_selector {
                 "Vim-like Navigation • Enter: Select • F: Close".to_string()
             } else {
                 let mut help = String::from("Vim-like Navigation");
                 help.push_str(" • s/S/n/N/c/C/r/R: Execution");
                 if self.display_info.mode == CodeMode::Source {
-                    help.push_str(" • F: Files");
+                    help.push_str(" • F: Files • d: Split view");
                 }
-                help.push_str(" • B: Breakpoint • Space: Trace Panel • ?: Help");
+                help.push_str(" • /: Search • Ctrl-P: Go to file • H: Gas heatmap");
+                help.push_str(" • V/y: Select/Copy lines");
+                help.push_str(" • B: Breakpoint • Space: Events Panel • ?: Help");
                 help
             };
 
-            let help_style = if self.vim_command_mode {
+            let help_style = if self.vim_command_mode || self.search.active {
                 // Use a different style for VIM command mode to make it more prominent
                 Style::default().fg(dm.theme.help_text_color).bg(dm.theme.highlight_bg)
             } else {
@@ -609,6 +1085,103 @@ impl CodePanel {
         }
     }
 
+    /// Map the source map of the displayed contract onto the displayed file
+    fn update_disassembly_pane(&mut self, dm: &mut DataManager) -> Option<()> {
+        let DisplayLocation::Hook(address, _) = self.display_info.location else {
+            self.disassembly = None;
+            return None;
+        };
+        let path = self.display_info.available_files.get(self.selected_path_index)?.clone();
+        let key = (address, path);
+        if self.disassembly.as_ref().is_some_and(|pane| pane.key == key) {
+            return Some(());
+        }
+
+        self.disassembly = None;
+        let source_map = dm.execution.get_source_map(address)?;
+        let source = self.sources.get(&key.1)?;
+        let newlines: Vec<usize> =
+            source.bytes().enumerate().filter(|(_, b)| *b == b'\n').map(|(i, _)| i).collect();
+
+        let rows = source_map
+            .entries
+            .iter()
+            .map(|entry| {
+                let line = entry
+                    .path
+                    .as_ref()
+                    .filter(|p| p.to_string_lossy() == key.1)
+                    .map(|_| newlines.partition_point(|nl| *nl < entry.offset) + 1);
+                (entry.pc, entry.instruction.clone(), line)
+            })
+            .collect();
+
+        self.disassembly = Some(DisassemblyPane { key, rows, scroll_offset: 0 });
+        Some(())
+    }
+
+    /// Render the disassembly pane of the split view
+    fn render_disassembly_pane(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
+        let height = area.height.saturating_sub(2) as usize;
+        let Some(pane) = self.disassembly.as_mut() else {
+            let paragraph = Paragraph::new("No source map available").block(BorderPresets::code(
+                self.focused,
+                "Disassembly".to_string(),
+                dm.theme.focused_border,
+                dm.theme.unfocused_border,
+            ));
+            frame.render_widget(paragraph, area);
+            return;
+        };
+
+        // Keep the first instruction of the cursor line in view
+        let target = self
+            .user_cursor_line
+            .and_then(|line| pane.rows.iter().position(|(_, _, l)| *l == Some(line)));
+        if let Some(target) = target {
+            if target < pane.scroll_offset || target >= pane.scroll_offset + height {
+                pane.scroll_offset = target.saturating_sub(height / 3);
+            }
+        }
+
+        let items: Vec<ListItem<'_>> = pane
+            .rows
+            .iter()
+            .skip(pane.scroll_offset)
+            .take(height)
+            .map(|(pc, instruction, line)| {
+                let instruction_style = if line.is_some() {
+                    Style::default().fg(dm.theme.syntax_opcode_color)
+                } else {
+                    Style::default().fg(dm.theme.comment_color)
+                };
+                let item_style = if line.is_some() && *line == self.current_execution_line {
+                    Style::default().bg(dm.theme.current_line_bg)
+                } else if line.is_some() && *line == self.user_cursor_line {
+                    Style::default().bg(dm.theme.highlight_bg)
+                } else {
+                    Style::default()
+                };
+
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{pc:05}"), Style::default().fg(dm.theme.line_number)),
+                    Span::raw(" │ "),
+                    Span::styled(instruction.clone(), instruction_style),
+                ]))
+                .style(item_style)
+            })
+            .collect();
+
+        let title = format!("Disassembly ({} instructions)", pane.rows.len());
+        let list = List::new(items).block(BorderPresets::code(
+            self.focused,
+            title,
+            dm.theme.focused_border,
+            dm.theme.unfocused_border,
+        ));
+        frame.render_widget(list, area);
+    }
+
     /// Get repetition count from vim_number_prefix, defaulting to 1
     fn get_vim_repetition(&self) -> usize {
         if self.vim_number_prefix.is_empty() {
@@ -906,6 +1479,7 @@ impl CodePanel {
         let execution_address = dm.execution.get_trace().get(execution_entry_id)?.code_address;
         let display_address = dm.execution.get_trace().get(display_entry_id)?.code_address;
 
+        self.modifier_frame = None;
         if execution_address != display_address {
             // We do not need to show the execution line
             self.current_execution_line = None;
@@ -914,6 +1488,7 @@ impl CodePanel {
 
         match dm.execution.get_snapshot_info(execution_snapshot_id)?.detail() {
             SnapshotInfoDetail::Hook(hook_info) => {
+                self.modifier_frame = hook_info.modifier.clone();
                 let execution_path = hook_info.path.as_os_str().to_string_lossy();
                 let display_path = &self.display_info.available_files[self.selected_path_index];
                 if &*execution_path != display_path {
@@ -944,12 +1519,38 @@ impl CodePanel {
     }
 
     /// Get breakpoint location for a given line number
+    /// Bytecode address of the displayed code
+    fn display_address(&self) -> Option<Address> {
+        match &self.display_info.location {
+            DisplayLocation::Opcode(addr) | DisplayLocation::Hook(addr, _) => Some(*addr),
+            DisplayLocation::Unknown => None,
+        }
+    }
+
+    /// Gas spent on each displayed line (1-based), looked up in the contract's heatmap
+    fn gas_per_line(&self, heatmap: &GasHeatmap) -> BTreeMap<usize, u64> {
+        match self.display_info.mode {
+            CodeMode::Source => self
+                .display_info
+                .available_files
+                .get(self.selected_path_index)
+                .and_then(|path| heatmap.lines.get(Path::new(path)))
+                .cloned()
+                .unwrap_or_default(),
+            CodeMode::Opcodes => (1..=self.opcode_lines.len())
+                .filter_map(|line| match self.get_breakpoint_location_for_line(line)? {
+                    BreakpointLocation::Opcode { pc, .. } => {
+                        heatmap.instructions.get(&pc).map(|gas| (line, *gas))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
     fn get_breakpoint_location_for_line(&self, line: usize) -> Option<BreakpointLocation> {
         // Get the bytecode address from current location
-        let bytecode_address = match &self.display_info.location {
-            DisplayLocation::Opcode(addr) | DisplayLocation::Hook(addr, _) => *addr,
-            DisplayLocation::Unknown => return None,
-        };
+        let bytecode_address = self.display_address()?;
 
         // Create breakpoint location based on display mode
         match self.display_info.mode {
@@ -1017,13 +1618,18 @@ impl CodePanel {
         Ok(())
     }
 
-    /// Check if a line has a breakpoint
-    fn line_has_breakpoint(&self, line: usize, dm: &DataManager) -> bool {
-        if let Some(location) = self.get_breakpoint_location_for_line(line) {
-            let test_bp = Breakpoint::new(Some(location), None);
-            dm.execution.find_breakpoints(&test_bp, true).iter().any(|(_, enabled)| *enabled)
+    /// Get the breakpoint state of a line
+    ///
+    /// Returns `Some(true)` if an enabled breakpoint is set on the line, `Some(false)` if
+    /// only disabled breakpoints are set, and `None` if there is no breakpoint.
+    fn line_breakpoint_state(&self, line: usize, dm: &DataManager) -> Option<bool> {
+        let location = self.get_breakpoint_location_for_line(line)?;
+        let test_bp = Breakpoint::new(Some(location), None);
+        let found = dm.execution.find_breakpoints(&test_bp, true);
+        if found.is_empty() {
+            None
         } else {
-            false
+            Some(found.iter().any(|(_, enabled)| *enabled))
         }
     }
 }
@@ -1060,7 +1666,18 @@ impl PanelTr for CodePanel {
             String::new()
         };
 
-        format!("{mode_str} {availability} - {path_str}{file_count}")
+        // Modifiers have no frame of their own, show the one being evaluated
+        let modifier = match &self.modifier_frame {
+            Some(ModifierFrame { modifier, function: Some(function), .. }) => {
+                format!(" - in modifier {modifier} of {function}")
+            }
+            Some(ModifierFrame { modifier, function: None, .. }) => {
+                format!(" - in modifier {modifier}")
+            }
+            None => String::new(),
+        };
+
+        format!("{mode_str} {availability} - {path_str}{file_count}{modifier}")
     }
 
     fn render(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
@@ -1076,6 +1693,18 @@ impl PanelTr for CodePanel {
             (None, area)
         };
 
+        // Split the code area again if the disassembly is shown next to the source
+        let (code_area, disassembly_area) =
+            if self.split_view && self.display_info.mode == CodeMode::Source {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .split(code_area);
+                (chunks[0], Some(chunks[1]))
+            } else {
+                (code_area, None)
+            };
+
         // Calculate viewport height and width (for code area)
         self.context_height = if self.focused && code_area.height > 10 {
             code_area.height.saturating_sub(4) // Account for borders and status lines
@@ -1087,6 +1716,7 @@ impl PanelTr for CodePanel {
         // We update display information after we have the self.context_height/_width
         let _ = self
             .update_display_info(dm)
+            .map(|_| self.apply_pending_file())
             .and_then(|_| self.fresh_source_code(dm))
             .and_then(|_| self.update_execution_info(dm));
 
@@ -1097,6 +1727,16 @@ impl PanelTr for CodePanel {
 
         // Render main code content
         self.render_code_content(frame, code_area, dm);
+
+        if let Some(disassembly_area) = disassembly_area {
+            let _ = self.update_disassembly_pane(dm);
+            self.render_disassembly_pane(frame, disassembly_area, dm);
+        }
+
+        // Render the fuzzy file picker on top
+        if self.file_picker.is_some() {
+            self.render_file_picker(frame, code_area, dm);
+        }
     }
 
     fn handle_key_event(&mut self, event: KeyEvent, dm: &mut DataManager) -> Result<EventResponse> {
@@ -1104,7 +1744,15 @@ impl PanelTr for CodePanel {
             return Ok(EventResponse::NotHandled);
         }
 
-        // Handle VIM command mode first
+        // Handle the file picker and search prompt first
+        if self.file_picker.is_some() {
+            return self.handle_file_picker_key(event, dm);
+        }
+        if self.search.active {
+            return Ok(self.handle_search_key(event));
+        }
+
+        // Handle VIM command mode
         if self.vim_command_mode {
             match event.code {
                 KeyCode::Backspace => {
@@ -1139,6 +1787,36 @@ impl PanelTr for CodePanel {
                     self.vim_number_prefix.push(c);
                     Ok(EventResponse::Handled)
                 }
+                // Ctrl-P opens the fuzzy file picker
+                KeyCode::Char('p') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.open_file_picker(dm);
+                    Ok(EventResponse::Handled)
+                }
+                // '/' starts an incremental search
+                KeyCode::Char('/') if !self.show_file_selector => {
+                    self.start_search();
+                    Ok(EventResponse::Handled)
+                }
+                // 'd' toggles the disassembly split view
+                KeyCode::Char('d') if !self.show_file_selector => {
+                    self.split_view = !self.split_view;
+                    debug!("Split view toggled: {}", self.split_view);
+                    Ok(EventResponse::Handled)
+                }
+                // 'V' starts or cancels a line selection to copy with 'y'
+                KeyCode::Char('v') | KeyCode::Char('V') if !self.show_file_selector => {
+                    self.selection_anchor = match self.selection_anchor {
+                        Some(_) => None,
+                        None => self.user_cursor_line,
+                    };
+                    Ok(EventResponse::Handled)
+                }
+                // 'H' toggles the gas heatmap
+                KeyCode::Char('H') if !self.show_file_selector => {
+                    self.gas_heatmap = !self.gas_heatmap;
+                    debug!("Gas heatmap toggled: {}", self.gas_heatmap);
+                    Ok(EventResponse::Handled)
+                }
                 // 'F' key toggles file selector
                 KeyCode::Char('f') | KeyCode::Char('F') => {
                     self.toggle_file_selector();
@@ -1323,6 +2001,31 @@ impl PanelTr for CodePanel {
         }
     }
 
+    fn is_capturing_input(&self) -> bool {
+        self.focused && (self.file_picker.is_some() || self.search.active || self.vim_command_mode)
+    }
+
+    fn yank(&mut self, _dm: &mut DataManager) -> Option<Yank> {
+        // Copy the selected lines, or the line under the cursor
+        let lines = self.selected_lines().or_else(|| {
+            let line = self.user_cursor_line.or(self.current_execution_line)?;
+            Some(line..=line)
+        })?;
+        let (start, end) = (*lines.start(), *lines.end());
+
+        let display_lines = self.get_display_lines();
+        if start == 0 || start > display_lines.len() {
+            return None;
+        }
+        let end = end.min(display_lines.len());
+        let text = display_lines[start - 1..end].join("\n");
+
+        self.selection_anchor = None;
+        let description =
+            if start == end { format!("line {start}") } else { format!("lines {start}-{end}") };
+        Some(Yank::new(description, text))
+    }
+
     fn on_focus(&mut self) {
         self.focused = true;
         debug!("Code panel gained focus");
@@ -1330,9 +2033,35 @@ impl PanelTr for CodePanel {
 
     fn on_blur(&mut self) {
         self.focused = false;
+        self.file_picker = None;
+        self.search.active = false;
         debug!("Code panel lost focus");
     }
 
+    fn help_entries(&self) -> Vec<HelpEntry> {
+        let mut entries = Vec::new();
+        if self.selection_anchor.is_some() {
+            entries.push(HelpEntry::new("y", "Copy the selected lines"));
+            entries.push(HelpEntry::new("V", "Cancel the line selection"));
+        }
+        if self.show_file_selector {
+            entries.push(HelpEntry::new("f", "Close the file selector"));
+        }
+        if self.split_view {
+            entries.push(HelpEntry::new("d", "Hide the disassembly shown next to the source"));
+        }
+        if self.gas_heatmap {
+            entries.push(HelpEntry::new("H", "Turn off the gas heatmap"));
+        }
+        if !self.search.last_query.is_empty() {
+            entries.push(HelpEntry::new(
+                "/ Enter",
+                format!("Search '{}' again", self.search.last_query),
+            ));
+        }
+        entries
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
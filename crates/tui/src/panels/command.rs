@@ -0,0 +1,164 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Parser of the terminal command language
+//!
+//! Besides the canonical commands, the terminal accepts a few shorthands, which are
+//! parsed into the command they stand for:
+//!
+//! - `watch <expr>` for `watch add $<expr>`
+//! - `break @<loc> [if $<expr>]` and `break if $<expr>` for `break add ...`
+//! - `break <file>:<line> [if $<expr>]` for a breakpoint at a line of a source file, whose
+//!   contract is resolved from the trace
+//! - a bare expression, not starting with a command name, for `$<expr>`
+
+/// Names of all terminal commands, including aliases
+const COMMAND_WORDS: &[&str] = &[
+    "abi", "address", "break", "bridge", "c", "call", "calldata", "clear", "cls", "exit", "export",
+    "g", "goto", "h", "help", "history", "info", "jumps", "label", "mark", "marks", "memory", "n",
+    "next", "p", "prev", "q", "quit", "r", "rb", "rc", "rcall", "record", "replay", "reverse",
+    "revert", "rs", "run", "runback", "s", "sload", "stack", "step", "theme", "tsload", "watch",
+];
+
+/// Subcommands of `break`
+const BREAK_SUBCOMMANDS: &[&str] =
+    &["add", "add_expr", "clear", "disable", "enable", "list", "remove"];
+
+/// Subcommands of `watch`
+const WATCH_SUBCOMMANDS: &[&str] = &["add", "clear", "edit", "list", "pin", "remove"];
+
+/// A terminal input, parsed into the command it stands for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerminalCommand {
+    /// A canonical command, handled as is
+    Command(String),
+    /// A Solidity expression to evaluate at the current snapshot, without the `$` prefix
+    Expression(String),
+    /// A breakpoint at a line of a source file
+    SourceBreakpoint {
+        /// Path, or trailing components of the path, of the source file
+        file: String,
+        /// Line number in the source file (1-based)
+        line: usize,
+        /// Condition of the breakpoint, including the `$` prefix
+        condition: Option<String>,
+    },
+}
+
+/// Parse a terminal input
+pub fn parse_command(input: &str) -> TerminalCommand {
+    let input = input.trim();
+    if let Some(expr) = input.strip_prefix('$') {
+        return TerminalCommand::Expression(expr.trim().to_string());
+    }
+
+    let (name, args) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let args = args.trim();
+    if !input.is_empty() && !COMMAND_WORDS.contains(&name) {
+        return TerminalCommand::Expression(input.to_string());
+    }
+
+    let subcommand = args.split_whitespace().next().unwrap_or_default();
+    match name {
+        "watch" if !args.is_empty() && !WATCH_SUBCOMMANDS.contains(&subcommand) => {
+            let expr = args.strip_prefix('$').unwrap_or(args).trim();
+            TerminalCommand::Command(format!("watch add ${expr}"))
+        }
+        "break" if !args.is_empty() && !BREAK_SUBCOMMANDS.contains(&subcommand) => {
+            if args.starts_with('@') || subcommand == "if" {
+                return TerminalCommand::Command(format!("break add {args}"));
+            }
+            parse_source_breakpoint(args)
+                .unwrap_or_else(|| TerminalCommand::Command(input.to_string()))
+        }
+        _ => TerminalCommand::Command(input.to_string()),
+    }
+}
+
+/// Parse `<file>:<line> [if $<expr>]`
+fn parse_source_breakpoint(args: &str) -> Option<TerminalCommand> {
+    let (loc, condition) = match args.split_once(" if ") {
+        Some((loc, condition)) => (loc.trim(), Some(condition.trim().to_string())),
+        None => (args, None),
+    };
+    let (file, line) = loc.rsplit_once(':')?;
+    let line = line.parse::<usize>().ok().filter(|line| *line > 0)?;
+    if file.is_empty() || file.contains(char::is_whitespace) {
+        return None;
+    }
+
+    Some(TerminalCommand::SourceBreakpoint { file: file.to_string(), line, condition })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(cmd: &str) -> TerminalCommand {
+        TerminalCommand::Command(cmd.to_string())
+    }
+
+    #[test]
+    fn test_canonical_commands() {
+        assert_eq!(parse_command("step 10"), command("step 10"));
+        assert_eq!(parse_command("  goto 1234 "), command("goto 1234"));
+        assert_eq!(parse_command("watch remove 2"), command("watch remove 2"));
+        assert_eq!(parse_command("break list"), command("break list"));
+        assert_eq!(parse_command("export trace.json"), command("export trace.json"));
+        assert_eq!(parse_command("export trace out.json"), command("export trace out.json"));
+        assert_eq!(parse_command(""), command(""));
+    }
+
+    #[test]
+    fn test_expressions() {
+        assert_eq!(parse_command("$x + 1"), TerminalCommand::Expression("x + 1".into()));
+        assert_eq!(parse_command("balance"), TerminalCommand::Expression("balance".into()));
+        assert_eq!(
+            parse_command("balanceOf(msg.sender) > 0"),
+            TerminalCommand::Expression("balanceOf(msg.sender) > 0".into())
+        );
+    }
+
+    #[test]
+    fn test_watch_shorthand() {
+        assert_eq!(parse_command("watch x"), command("watch add $x"));
+        assert_eq!(parse_command("watch $a[1] + b"), command("watch add $a[1] + b"));
+        assert_eq!(parse_command("watch"), command("watch"));
+    }
+
+    #[test]
+    fn test_break_shorthand() {
+        assert_eq!(parse_command("break @0x01:42"), command("break add @0x01:42"));
+        assert_eq!(parse_command("break if $x > 1"), command("break add if $x > 1"));
+        assert_eq!(
+            parse_command("break Contract.sol:88"),
+            TerminalCommand::SourceBreakpoint {
+                file: "Contract.sol".into(),
+                line: 88,
+                condition: None
+            }
+        );
+        assert_eq!(
+            parse_command("break src/Vault.sol:12 if $amount == 0"),
+            TerminalCommand::SourceBreakpoint {
+                file: "src/Vault.sol".into(),
+                line: 12,
+                condition: Some("$amount == 0".into())
+            }
+        );
+        assert_eq!(parse_command("break Contract.sol"), command("break Contract.sol"));
+    }
+}
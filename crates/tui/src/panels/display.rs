@@ -16,27 +16,32 @@
 
 //! Display panel for variables, stack, memory, and other debugging information
 //!
-//! This panel can switch between different display modes based on context.
-
-use super::{EventResponse, PanelTr, PanelType};
+//! This panel is a tabbed container whose available tabs depend on the snapshot
+//! type. Tabs can be cycled with `s`/`S` or selected directly with number keys.
+//! Stack items are shown as a table, memory and calldata in a hexdump, and
+//! locals and storage as trees. The Storage Diff tab lists the slots written by
+//! the step that led to the current snapshot. Variables whose value changed while
+//! stepping are highlighted, fading out over the next few steps.
+
+use super::{EventResponse, PanelTr, PanelType, Yank};
 use crate::data::DataManager;
 use crate::panels::utils;
 use crate::ui::borders::BorderPresets;
-use crate::ui::colors::ColorScheme;
+use crate::ui::hexdump::{self, HexDump, HexRegion, HEXDUMP_ROW_WIDTH};
 use crate::ui::status::StatusBar;
 use crate::ui::syntax::{SyntaxHighlighter, SyntaxType};
-use alloy_primitives::{Address, Bytes, U256};
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use alloy_primitives::{hex, Address, Bytes, Selector, U256};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use edb_common::types::{
-    Breakpoint, EdbSolValue, HookSnapshotInfoDetail, OpcodeSnapshotInfoDetail, SnapshotInfoDetail,
-    SolValueFormatterContext,
+    Breakpoint, EdbSolValue, HookSnapshotInfoDetail, OpcodeSnapshotInfoDetail, SnapshotInfo,
+    SnapshotInfoDetail, SolTypeHint, SolValueFormatter, SolValueFormatterContext,
 };
 use eyre::Result;
 use ratatui::{
-    layout::Rect,
+    layout::{Constraint, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{List, ListItem, Paragraph},
+    widgets::{Cell, Clear, List, ListItem, Paragraph, Row, Table, Tabs},
     Frame,
 };
 use revm::state::TransientStorage;
@@ -48,8 +53,11 @@ use std::{
 };
 use tracing::debug;
 
+/// Number of steps a changed variable stays highlighted
+const VARIABLE_FADE_STEPS: usize = 2;
+
 /// Variable display category for hooked snapshots
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum VariableCategory {
     /// Expression watches
     Expression,
@@ -79,6 +87,8 @@ pub enum DisplayMode {
     Storage,
     /// Show transient storage state (for opcode snapshots)
     TransientStorage,
+    /// Show storage slots changed since the previous snapshot (for both snapshot types)
+    StorageDiff,
 }
 
 /// Represents a variable entry for display
@@ -90,6 +100,8 @@ struct VariableEntry {
     is_multi_line: bool,
     /// For expressions, stores the original expression text
     expression: Option<String>,
+    /// How to render the value beyond its ABI type (e.g., enum member names)
+    hint: Option<SolTypeHint>,
 }
 
 /// Represents a breakpoint entry for display
@@ -99,6 +111,8 @@ struct BreakpointEntry {
     breakpoint: Breakpoint,
     enabled: bool,
     is_hit: bool,
+    /// Number of snapshots hitting this breakpoint (`None` while being fetched)
+    hit_count: Option<usize>,
 }
 
 impl cmp::PartialOrd for VariableEntry {
@@ -138,6 +152,7 @@ impl DisplayMode {
             Self::CallData => "Call Data",
             Self::Storage => "Storage",
             Self::TransientStorage => "Transient Storage",
+            Self::StorageDiff => "Storage Diff",
         }
     }
 }
@@ -150,12 +165,13 @@ struct StackItem {
     diff_status: DiffStatus,
 }
 
-/// Represents a memory chunk with potential diff
-#[derive(Debug, Clone)]
-struct MemoryChunk {
-    offset: usize,
-    data: Vec<u8>,
-    changed_bytes: Vec<usize>, // Indices of changed bytes within chunk
+/// Inline editor state for adding or editing a watch expression
+#[derive(Debug, Clone, Default)]
+struct WatchInput {
+    /// ID of the watch being edited, or `None` when adding a new watch
+    target: Option<usize>,
+    /// Expression text typed so far
+    buffer: String,
 }
 
 /// Status of an item compared to previous snapshot
@@ -214,14 +230,22 @@ pub struct DisplayPanel {
     // ========== Data (Cached) ==========
     /// Stack items with diff status
     stack_items: Vec<StackItem>,
-    /// Memory chunks with diff status
-    memory_chunks: Vec<MemoryChunk>,
+    /// Raw memory
+    memory: Vec<u8>,
+    /// Bytes of the memory changed by the last step
+    memory_changes: Vec<bool>,
     /// Raw calldata
     calldata: Bytes,
+    /// Bytes of the calldata that differ from the previous step, e.g. after entering a call
+    calldata_changes: Vec<bool>,
+    /// Address whose ABI the calldata is annotated with
+    calldata_address: Option<Address>,
     /// Storage changes (slot -> (old_value, new_value))
     storage_changes: HashMap<U256, (U256, U256)>,
     /// Transient storage data
     transient_storage: HashMap<U256, U256>,
+    /// Storage changed by the last step (slot, value before, value after), sorted by slot
+    step_storage_changes: Vec<(U256, U256, U256)>,
     /// Previous opcode info for SSTORE/TSTORE detection
     prev_opcode: Option<u8>,
     /// Previous stack for SSTORE/TSTORE slot extraction
@@ -234,16 +258,28 @@ pub struct DisplayPanel {
     variables: Vec<VariableEntry>,
     /// Variables that are toggled to multi-line (persisted by name)
     multi_line_variables: HashSet<String>,
+    /// Steps since each local/state variable last changed (only recent changes are kept)
+    changed_variables: HashMap<(VariableCategory, String), usize>,
+    /// Trace entry the current variables belong to
+    variables_entry: Option<usize>,
     /// Expression watch entries
     expressions: Vec<VariableEntry>,
     /// Expressions that are toggled to multi-line (persisted by expression)
     multi_line_expressions: HashSet<String>,
+    /// Watch IDs of the expression entries (same order as `expressions`)
+    expression_ids: Vec<usize>,
+    /// Inline watch editor, if active
+    watch_input: Option<WatchInput>,
     /// Cached display line count for storage mode
     storage_display_lines: usize,
     /// Cached display line count for transient storage mode
     tstorage_display_lines: usize,
     /// Current display line count (cached)
     displayed_line_count: usize,
+    /// Selected line of the storage modes, as rendered (for yanking)
+    selected_storage_line: Option<String>,
+    /// Storage and code addresses, when the current frame runs borrowed code
+    proxy_context: Option<(Address, Address)>,
 }
 
 impl DisplayPanel {
@@ -263,22 +299,32 @@ impl DisplayPanel {
             current_execution_snapshot: None,
             is_opcode_snapshot: true,
             stack_items: Vec::new(),
-            memory_chunks: Vec::new(),
+            memory: Vec::new(),
+            memory_changes: Vec::new(),
             calldata: Bytes::new(),
+            calldata_changes: Vec::new(),
+            calldata_address: None,
             storage_changes: HashMap::new(),
             transient_storage: HashMap::new(),
+            step_storage_changes: Vec::new(),
             prev_opcode: None,
             prev_stack: None,
             variables: Vec::new(),
             multi_line_variables: HashSet::new(),
+            changed_variables: HashMap::new(),
+            variables_entry: None,
             expressions: Vec::new(),
             multi_line_expressions: HashSet::new(),
+            expression_ids: Vec::new(),
+            watch_input: None,
             breakpoints: Vec::new(),
             hit_breakpoints: Vec::new(),
             storage_display_lines: 0,
             tstorage_display_lines: 0,
             syntax_highlighter: SyntaxHighlighter::new(),
             displayed_line_count: 0,
+            selected_storage_line: None,
+            proxy_context: None,
         }
     }
 
@@ -303,6 +349,14 @@ impl DisplayPanel {
         // Get snapshot info
         let snapshot_info = dm.execution.get_snapshot_info(current_snapshot)?.clone();
 
+        // Storage written by the step leading to this snapshot
+        self.update_step_storage_diff(current_snapshot, &snapshot_info, dm)?;
+
+        // In a delegatecall frame, variables and storage belong to the proxy
+        self.proxy_context = snapshot_info
+            .is_proxy_context()
+            .then_some((snapshot_info.storage_address(), snapshot_info.bytecode_address));
+
         // Determine snapshot type and update available modes
         match snapshot_info.detail() {
             SnapshotInfoDetail::Opcode(opcode_detail) => {
@@ -315,6 +369,7 @@ impl DisplayPanel {
                     DisplayMode::TransientStorage,
                     DisplayMode::Expressions,
                     DisplayMode::Breakpoints,
+                    DisplayMode::StorageDiff,
                 ];
 
                 // Update opcode-specific data
@@ -329,10 +384,15 @@ impl DisplayPanel {
             }
             SnapshotInfoDetail::Hook(hook_detail) => {
                 self.is_opcode_snapshot = false;
-                self.available_modes = vec![DisplayMode::Variables, DisplayMode::Breakpoints];
+                self.available_modes = vec![
+                    DisplayMode::Variables,
+                    DisplayMode::Expressions,
+                    DisplayMode::Breakpoints,
+                    DisplayMode::StorageDiff,
+                ];
 
                 // Update hook-specific data
-                self.update_hook_data(hook_detail);
+                self.update_hook_data(hook_detail, snapshot_info.frame_id().trace_entry_id());
 
                 // Switch to Variables mode if not already in a hook mode
                 if !self.available_modes.contains(&self.mode) {
@@ -349,8 +409,50 @@ impl DisplayPanel {
         Some(())
     }
 
+    /// Update the storage changed between the previous snapshot and the current one
+    ///
+    /// `edb_getStorageDiff` reports changes relative to the start of the transaction,
+    /// so the step diff is the difference between the reports of both snapshots.
+    fn update_step_storage_diff(
+        &mut self,
+        current_id: usize,
+        info: &SnapshotInfo,
+        dm: &mut DataManager,
+    ) -> Option<()> {
+        let prev_id = info.prev_id();
+        let prev_changes = if prev_id != current_id
+            && dm.execution.get_snapshot_info(prev_id)?.target_address == info.target_address
+        {
+            dm.execution.get_storage_diff(prev_id)?.clone()
+        } else {
+            // Entering a new frame (or the very first snapshot) does not write storage
+            HashMap::new()
+        };
+
+        let changes = dm.execution.get_storage_diff(current_id)?;
+        let mut step_changes: Vec<_> = changes
+            .iter()
+            .filter_map(|(slot, (original, new))| {
+                let before = prev_changes.get(slot).map_or(*original, |(_, value)| *value);
+                (before != *new).then_some((*slot, before, *new))
+            })
+            .collect();
+        step_changes.sort_by_key(|(slot, ..)| *slot);
+
+        self.step_storage_changes = step_changes;
+        Some(())
+    }
+
     /// Update data for hook snapshots
-    fn update_hook_data(&mut self, hook_detail: &HookSnapshotInfoDetail) {
+    fn update_hook_data(&mut self, hook_detail: &HookSnapshotInfoDetail, entry_id: usize) {
+        // Remember the previous values to highlight what this step changed
+        let prev_values: HashMap<_, _> = self
+            .variables
+            .iter()
+            .filter(|var| var.category != VariableCategory::Expression)
+            .map(|var| ((var.category, var.name.clone()), var.value.clone()))
+            .collect();
+
         // Let's include expression first
         self.variables.retain(|var| var.category == VariableCategory::Expression);
 
@@ -363,6 +465,7 @@ impl DisplayPanel {
                 category: VariableCategory::Local,
                 is_multi_line,
                 expression: None,
+                hint: hook_detail.hints.get(name).cloned(),
             });
         }
 
@@ -375,10 +478,42 @@ impl DisplayPanel {
                 category: VariableCategory::State,
                 is_multi_line,
                 expression: None,
+                hint: hook_detail.hints.get(name).cloned(),
             });
         }
 
         self.variables.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        self.update_changed_variables(&prev_values, entry_id);
+    }
+
+    /// Age the change highlights and mark the variables changed by the last step
+    fn update_changed_variables(
+        &mut self,
+        prev_values: &HashMap<(VariableCategory, String), Option<Arc<EdbSolValue>>>,
+        entry_id: usize,
+    ) {
+        // Values of another call frame are not comparable
+        if self.variables_entry.replace(entry_id) != Some(entry_id) {
+            self.changed_variables.clear();
+            return;
+        }
+
+        let mut changed = HashMap::new();
+        for var in self.variables.iter().filter(|var| var.category != VariableCategory::Expression)
+        {
+            let key = (var.category, var.name.clone());
+            let is_changed = var.value.is_some()
+                && prev_values.get(&key).is_some_and(|prev| prev.is_some() && *prev != var.value);
+            if is_changed {
+                changed.insert(key, 0);
+            } else if let Some(age) = self.changed_variables.get(&key) {
+                if *age < VARIABLE_FADE_STEPS {
+                    changed.insert(key, age + 1);
+                }
+            }
+        }
+        self.changed_variables = changed;
     }
 
     /// Update data for opcode snapshots
@@ -421,14 +556,19 @@ impl DisplayPanel {
             prev_snapshot_detail.as_ref().map(|d| &d.stack),
         );
 
-        // Update memory with diff
-        self.update_memory_diff(
+        // Update memory and calldata with diff
+        self.memory = opcode_detail.memory.clone();
+        self.memory_changes = hexdump::changed_bytes(
             &opcode_detail.memory,
-            prev_snapshot_detail.as_ref().map(|d| &d.memory),
+            prev_snapshot_detail.as_ref().map(|d| d.memory.as_slice()),
         );
-
-        // Update calldata
         self.calldata = opcode_detail.calldata.clone();
+        self.calldata_changes = hexdump::changed_bytes(
+            &opcode_detail.calldata,
+            prev_snapshot_detail.as_ref().map(|d| &d.calldata[..]),
+        );
+        self.calldata_address =
+            dm.execution.get_snapshot_info(current_id).map(|info| info.bytecode_address);
 
         // Update transient storage (only for current address)
         self.update_transient_storage(&opcode_detail.transient_storage, current_addr);
@@ -465,38 +605,6 @@ impl DisplayPanel {
         }
     }
 
-    /// Update memory chunks with diff status
-    fn update_memory_diff(&mut self, current_memory: &[u8], prev_memory: Option<&Vec<u8>>) {
-        self.memory_chunks.clear();
-
-        // Process memory in 32-byte chunks
-        const CHUNK_SIZE: usize = 32;
-
-        for offset in (0..current_memory.len()).step_by(CHUNK_SIZE) {
-            let end = (offset + CHUNK_SIZE).min(current_memory.len());
-            let chunk_data = current_memory[offset..end].to_vec();
-
-            // Find changed bytes if we have previous memory
-            let changed_bytes = if let Some(prev) = prev_memory {
-                let mut changes = Vec::new();
-                for (i, item) in chunk_data.iter().enumerate() {
-                    let mem_idx = offset + i;
-                    if mem_idx >= prev.len() || prev[mem_idx] != *item {
-                        changes.push(i);
-                    }
-                }
-                changes
-            } else {
-                // All bytes are new
-                (0..chunk_data.len()).collect()
-            };
-
-            if !chunk_data.is_empty() {
-                self.memory_chunks.push(MemoryChunk { offset, data: chunk_data, changed_bytes });
-            }
-        }
-    }
-
     /// Update transient storage
     fn update_transient_storage(&mut self, tstorage: &TransientStorage, current_addr: Address) {
         self.transient_storage.clear();
@@ -529,16 +637,16 @@ impl DisplayPanel {
                     .max()
                     .unwrap_or(0)
             }
-            DisplayMode::Memory => {
-                // Memory lines: "0x1234: " + hex bytes + "  " + ascii
-                // 32 bytes = 64 hex chars + 31 spaces + 2 spaces + 32 ascii = ~129 chars
-                // Plus offset (8 chars) = ~137 chars
-                145
-            }
+            DisplayMode::Memory => HEXDUMP_ROW_WIDTH,
             DisplayMode::CallData => {
-                // Calldata: hex only format
-                // 32 bytes = 64 hex chars + 31 spaces + offset (8 chars) = ~103 chars
-                110
+                // Rows starting an argument are followed by its name
+                let regions = self.calldata_regions(dm);
+                let dump = HexDump::new(&self.calldata).with_regions(&regions);
+                (0..dump.row_count())
+                    .map(|row| dump.row(row, &dm.theme).width())
+                    .max()
+                    .unwrap_or(0)
+                    .max(HEXDUMP_ROW_WIDTH)
             }
             DisplayMode::Storage => {
                 // Calculate actual max width by checking all storage items
@@ -551,11 +659,11 @@ impl DisplayPanel {
 
                 // Check storage changes
                 for (slot, (old_value, new_value)) in &self.storage_changes {
-                    let slot_line = format!("• Slot: {slot:#066x}");
+                    let slot_line = format!("▾ Slot: {slot:#066x}");
                     let old_line =
-                        format!("  Old:  {}", utils::format_value_with_decode(old_value));
+                        format!("  ├─ Old: {}", utils::format_value_with_decode(old_value));
                     let new_line =
-                        format!("  New:  {}", utils::format_value_with_decode(new_value));
+                        format!("  └─ New: {}", utils::format_value_with_decode(new_value));
 
                     max_width = max_width.max(slot_line.len());
                     max_width = max_width.max(old_line.len());
@@ -577,8 +685,8 @@ impl DisplayPanel {
 
                 // Check transient storage items
                 for (slot, value) in &self.transient_storage {
-                    let slot_line = format!("• Slot: {slot:#066x}");
-                    let val_line = format!("  Val:  {}", utils::format_value_with_decode(value));
+                    let slot_line = format!("▾ Slot: {slot:#066x}");
+                    let val_line = format!("  └─ Val: {}", utils::format_value_with_decode(value));
 
                     max_width = max_width.max(slot_line.len());
                     max_width = max_width.max(val_line.len());
@@ -588,6 +696,19 @@ impl DisplayPanel {
                 let header_width = "─── Transient Storage ───".len();
                 max_width.max(header_width).max(80) // At least 80 chars
             }
+            DisplayMode::StorageDiff => self
+                .step_storage_changes
+                .iter()
+                .flat_map(|(slot, before, after)| {
+                    [
+                        format!("▾ Slot: {slot:#066x}").len(),
+                        format!("  ├─ Old: {}", utils::format_value_with_decode(before)).len(),
+                        format!("  └─ New: {}", utils::format_value_with_decode(after)).len(),
+                    ]
+                })
+                .max()
+                .unwrap_or(0)
+                .max(80),
             DisplayMode::Variables => {
                 // Calculate actual max width for variables by formatting each entry
                 self.calculate_variables_max_width(dm)
@@ -625,6 +746,21 @@ impl DisplayPanel {
         }
     }
 
+    /// Switch directly to the display mode at `index` in the tab bar
+    fn select_mode(&mut self, index: usize) -> bool {
+        let Some(mode) = self.available_modes.get(index).copied() else {
+            return false;
+        };
+        if mode != self.mode {
+            self.mode = mode;
+            self.selected_index = 0;
+            self.scroll_offset = 0;
+            self.horizontal_offset = 0;
+            debug!("Selected display mode: {:?}", self.mode);
+        }
+        true
+    }
+
     /// Calculate the number of display lines for storage
     fn calculate_storage_display_lines(&self, dm: &mut DataManager) -> usize {
         let mut count = 0;
@@ -697,7 +833,15 @@ impl DisplayPanel {
         };
 
         let mut spans = vec![Span::raw(prefix.to_string())];
-        spans.push(Span::raw(format!("{} = ", entry.name)));
+
+        // Highlight recently changed variables, fading out with every step
+        let name_style = match self.changed_variables.get(&(entry.category, entry.name.clone())) {
+            Some(0) => Style::default().fg(dm.theme.warning_color).add_modifier(Modifier::BOLD),
+            Some(1) => Style::default().fg(dm.theme.warning_color),
+            Some(_) => Style::default().fg(dm.theme.warning_color).add_modifier(Modifier::DIM),
+            None => Style::default(),
+        };
+        spans.push(Span::styled(format!("{} = ", entry.name), name_style));
 
         // Format and highlight the value
         match &entry.value {
@@ -707,7 +851,8 @@ impl DisplayPanel {
                 } else {
                     SolValueFormatterContext::new().with_ty(true)
                 };
-                let value_str = dm.resolver.resolve_sol_value(value, Some(ctx));
+                let value_str =
+                    dm.resolver.resolve_hinted_sol_value(value, entry.hint.as_ref(), Some(ctx));
 
                 // Apply syntax highlighting to the value string
                 spans.extend(self.highlight_solidity_value(value_str, dm));
@@ -768,24 +913,48 @@ impl DisplayPanel {
     fn update_expression_watches(&mut self, dm: &mut DataManager, current_snapshot: usize) -> bool {
         // Store old values for comparison
         let old_values = mem::take(&mut self.expressions);
+        self.expression_ids.clear();
+
+        // Pinned watches are listed first, otherwise watches keep their order
+        let mut watches: Vec<(usize, String, bool)> = dm
+            .watcher
+            .list_expressions()
+            .map(|(expr_id, expression)| {
+                (expr_id, expression.clone(), dm.watcher.is_pinned(expression))
+            })
+            .collect();
+        watches.sort_by_key(|(_, _, pinned)| !pinned);
 
-        for (expr_id, expression) in dm.watcher.list_expressions() {
-            let is_multi_line = self.multi_line_expressions.contains(expression);
-            let name = format!("${expr_id}: {expression}");
+        for (expr_id, expression, pinned) in watches {
+            let is_multi_line = self.multi_line_expressions.contains(&expression);
+            let name = if pinned {
+                format!("📌 ${expr_id}: {expression}")
+            } else {
+                format!("${expr_id}: {expression}")
+            };
 
             // Evaluate expression on current snapshot
-            let value = match dm.resolver.eval_on_snapshot(current_snapshot, expression) {
+            let value = match dm.resolver.eval_on_snapshot(current_snapshot, &expression) {
                 Some(Ok(sol_value)) => Some(Arc::new(sol_value.clone())),
                 _ => None, // N/A for None or Some(Err)
             };
 
+            // A watched variable renders like the variable itself
+            let hint = dm
+                .execution
+                .get_snapshot_info(current_snapshot)
+                .and_then(|info| info.variable_hint(expression.trim()))
+                .cloned();
+
             self.expressions.push(VariableEntry {
                 name,
                 value,
                 category: VariableCategory::Expression,
                 is_multi_line,
-                expression: Some(expression.clone()),
+                expression: Some(expression),
+                hint,
             });
+            self.expression_ids.push(expr_id);
         }
 
         old_values.len() != self.expressions.len()
@@ -799,14 +968,15 @@ impl DisplayPanel {
 
         // Update breakpoint entries
         self.breakpoints.clear();
-        for (id, breakpoint, enabled) in dm.execution.list_breakpoints() {
+        let breakpoints: Vec<_> = dm
+            .execution
+            .list_breakpoints()
+            .map(|(id, breakpoint, enabled)| (id, breakpoint.clone(), enabled))
+            .collect();
+        for (id, breakpoint, enabled) in breakpoints {
             let is_hit = self.hit_breakpoints.contains(&id);
-            self.breakpoints.push(BreakpointEntry {
-                id,
-                breakpoint: breakpoint.clone(),
-                enabled,
-                is_hit,
-            });
+            let hit_count = dm.execution.get_breakpoint_hits(&breakpoint).map(|hits| hits.len());
+            self.breakpoints.push(BreakpointEntry { id, breakpoint, enabled, is_hit, hit_count });
         }
     }
 
@@ -816,7 +986,8 @@ impl DisplayPanel {
 
         for entry in &self.variables {
             // Calculate prefix width (Unicode emojis take more space)
-            let prefix_width = 4; // all 🔵, 🟠, and 🟢 take roughly 4 display chars
+            // Tree branch ("├─ ") plus 🔵, 🟠, or 🟢 which take roughly 4 display chars
+            let prefix_width = 3 + 4;
             let name_width = entry.name.len() + 4; // " = "
 
             // Calculate value width by actually formatting it
@@ -827,7 +998,8 @@ impl DisplayPanel {
                     } else {
                         SolValueFormatterContext::new().with_ty(true)
                     };
-                    let formatted = dm.resolver.resolve_sol_value(value, Some(ctx));
+                    let formatted =
+                        dm.resolver.resolve_hinted_sol_value(value, entry.hint.as_ref(), Some(ctx));
 
                     // For multi-line values, find the longest line
                     if entry.is_multi_line {
@@ -857,7 +1029,8 @@ impl DisplayPanel {
                 // Multi-line variables take multiple lines - actually count them
                 if let Some(value) = &entry.value {
                     let ctx = SolValueFormatterContext::new().with_ty(true).multi_line(true);
-                    let formatted = dm.resolver.resolve_sol_value(value, Some(ctx));
+                    let formatted =
+                        dm.resolver.resolve_hinted_sol_value(value, entry.hint.as_ref(), Some(ctx));
                     total_lines += formatted.lines().count().max(1);
                 } else {
                     total_lines += 1;
@@ -887,7 +1060,8 @@ impl DisplayPanel {
                     } else {
                         SolValueFormatterContext::new().with_ty(true)
                     };
-                    let formatted = dm.resolver.resolve_sol_value(value, Some(ctx));
+                    let formatted =
+                        dm.resolver.resolve_hinted_sol_value(value, entry.hint.as_ref(), Some(ctx));
 
                     // For multi-line values, find the longest line
                     if entry.is_multi_line {
@@ -917,7 +1091,8 @@ impl DisplayPanel {
                 // Multi-line expressions take multiple lines - actually count them
                 if let Some(value) = &entry.value {
                     let ctx = SolValueFormatterContext::new().with_ty(true).multi_line(true);
-                    let formatted = dm.resolver.resolve_sol_value(value, Some(ctx));
+                    let formatted =
+                        dm.resolver.resolve_hinted_sol_value(value, entry.hint.as_ref(), Some(ctx));
                     total_lines += formatted.lines().count().max(1);
                 } else {
                     total_lines += 1;
@@ -1030,6 +1205,14 @@ impl DisplayPanel {
             spans.push(Span::styled(condition.to_string(), condition_style));
         }
 
+        // Hit count
+        let hit_count = match entry.hit_count {
+            Some(1) => " (1 hit)".to_string(),
+            Some(count) => format!(" ({count} hits)"),
+            None => " (… hits)".to_string(),
+        };
+        spans.push(Span::styled(hit_count, Style::default().fg(dm.theme.comment_color)));
+
         spans
     }
 
@@ -1098,6 +1281,112 @@ impl DisplayPanel {
         }
     }
 
+    /// Open the inline watch editor, prefilled with the selected watch when editing
+    fn start_watch_input(&mut self, edit_selected: bool) {
+        let input = if edit_selected {
+            let Some(expr_id) = self.expression_ids.get(self.selected_index).copied() else {
+                return;
+            };
+            let buffer =
+                self.expressions[self.selected_index].expression.clone().unwrap_or_default();
+            WatchInput { target: Some(expr_id), buffer }
+        } else {
+            WatchInput::default()
+        };
+        self.watch_input = Some(input);
+    }
+
+    /// Handle a key while the inline watch editor is open
+    fn handle_watch_input(&mut self, event: KeyEvent, dm: &mut DataManager) -> EventResponse {
+        let Some(input) = self.watch_input.as_mut() else {
+            return EventResponse::NotHandled;
+        };
+
+        match event.code {
+            KeyCode::Esc => self.watch_input = None,
+            KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.watch_input = None;
+            }
+            KeyCode::Enter => {
+                if let Some(input) = self.watch_input.take() {
+                    self.commit_watch_input(input, dm);
+                }
+            }
+            KeyCode::Backspace => {
+                input.buffer.pop();
+            }
+            KeyCode::Char(c) => input.buffer.push(c),
+            _ => {}
+        }
+
+        EventResponse::Handled
+    }
+
+    /// Apply the text of the inline watch editor to the watcher
+    fn commit_watch_input(&mut self, input: WatchInput, dm: &mut DataManager) {
+        let expr = input.buffer.trim().trim_start_matches('$').trim();
+        if expr.is_empty() {
+            return;
+        }
+
+        match input.target {
+            Some(expr_id) => {
+                if dm.watcher.edit_expression(expr_id, expr.to_string()).is_none() {
+                    debug!("Failed to update watch #{expr_id} to '{expr}'");
+                }
+            }
+            None => {
+                if dm.watcher.add_expression(expr.to_string()).is_none() {
+                    debug!("Expression already being watched: {expr}");
+                }
+            }
+        }
+    }
+
+    /// Remove the currently selected watch expression
+    fn remove_selected_watch(&mut self, dm: &mut DataManager) {
+        if let Some(expr_id) = self.expression_ids.get(self.selected_index).copied() {
+            dm.watcher.remove_expression(expr_id);
+            self.selected_index = self.selected_index.min(dm.watcher.count().saturating_sub(1));
+        }
+    }
+
+    /// Pin or unpin the currently selected watch expression
+    fn toggle_selected_watch_pin(&mut self, dm: &mut DataManager) {
+        if let Some(expr_id) = self.expression_ids.get(self.selected_index).copied() {
+            dm.watcher.toggle_pin(expr_id);
+        }
+    }
+
+    /// Render the inline watch editor on the last line inside the border
+    fn render_watch_input(&self, frame: &mut Frame<'_>, area: Rect, dm: &DataManager) {
+        let Some(input) = &self.watch_input else {
+            return;
+        };
+        if area.height < 3 || area.width < 3 {
+            return;
+        }
+
+        let input_area =
+            Rect { x: area.x + 1, y: area.y + area.height - 2, width: area.width - 2, height: 1 };
+        let label = match input.target {
+            Some(expr_id) => format!("Edit #{expr_id}: $"),
+            None => "Watch: $".to_string(),
+        };
+        let line = Line::from(vec![
+            Span::styled(label, Style::default().fg(dm.theme.accent_color)),
+            Span::raw(input.buffer.clone()),
+            Span::styled("█", Style::default().fg(dm.theme.accent_color)),
+            Span::styled(
+                "  (Enter: Save • Esc: Cancel)",
+                Style::default().fg(dm.theme.help_text_color),
+            ),
+        ]);
+
+        frame.render_widget(Clear, input_area);
+        frame.render_widget(Paragraph::new(line), input_area);
+    }
+
     /// Toggle enable/disable state for the currently selected breakpoint
     fn toggle_breakpoint(&mut self, dm: &mut DataManager) {
         if let Some(entry) = self.breakpoints.get(self.selected_index) {
@@ -1127,6 +1416,45 @@ impl DisplayPanel {
         }
     }
 
+    /// Remove the currently selected breakpoint
+    fn remove_selected_breakpoint(&mut self, dm: &mut DataManager) {
+        if let Some(entry) = self.breakpoints.get(self.selected_index) {
+            let breakpoint_id = entry.id;
+            if let Err(e) = dm.execution.remove_breakpoint(breakpoint_id) {
+                debug!("Failed to remove breakpoint {}: {}", breakpoint_id, e);
+            } else {
+                debug!("Removed breakpoint #{}", breakpoint_id);
+                self.breakpoints.remove(self.selected_index);
+                self.selected_index =
+                    self.selected_index.min(self.breakpoints.len().saturating_sub(1));
+            }
+        }
+    }
+
+    /// Jump to the next snapshot hitting the selected breakpoint (wrapping around)
+    fn goto_next_breakpoint_hit(&mut self, dm: &mut DataManager) {
+        let Some(entry) = self.breakpoints.get(self.selected_index) else {
+            return;
+        };
+        let breakpoint = entry.breakpoint.clone();
+        let current = dm.execution.get_current_snapshot();
+
+        let Some(hits) = dm.execution.get_breakpoint_hits(&breakpoint) else {
+            debug!("Breakpoint hits are still being fetched");
+            return;
+        };
+        let Some(target) =
+            hits.iter().copied().find(|id| *id > current).or_else(|| hits.first().copied())
+        else {
+            debug!("Breakpoint #{} has no hits", entry.id);
+            return;
+        };
+
+        if let Err(e) = dm.execution.goto(target, false) {
+            debug!("Failed to jump to snapshot {}: {}", target, e);
+        }
+    }
+
     /// Move selection up with auto-scrolling
     fn move_up(&mut self, n: usize) {
         self.selected_index = self.selected_index.saturating_sub(n);
@@ -1154,16 +1482,11 @@ impl DisplayPanel {
     fn calculate_context_lines(&self, dm: &mut DataManager) -> usize {
         match self.mode {
             DisplayMode::Stack => self.stack_items.len(),
-            DisplayMode::Memory => self.memory_chunks.len(),
-            DisplayMode::CallData => {
-                if self.calldata.is_empty() {
-                    0
-                } else {
-                    self.calldata.len().div_ceil(32)
-                }
-            }
+            DisplayMode::Memory => HexDump::new(&self.memory).row_count(),
+            DisplayMode::CallData => HexDump::new(&self.calldata).row_count(),
             DisplayMode::Storage => self.storage_display_lines.max(1),
             DisplayMode::TransientStorage => self.tstorage_display_lines.max(1),
+            DisplayMode::StorageDiff => (self.step_storage_changes.len() * 4).max(1),
             DisplayMode::Variables => self.calculate_variables_display_lines(dm),
             DisplayMode::Expressions => self.calculate_expressions_display_lines(dm),
             DisplayMode::Breakpoints => self.breakpoints.len(),
@@ -1183,8 +1506,8 @@ impl DisplayPanel {
             return;
         }
 
-        // Create list items for stack display (reversed - stack top first)
-        let items: Vec<ListItem<'_>> = self
+        // Create table rows for stack display (reversed - stack top first)
+        let rows: Vec<Row<'_>> = self
             .stack_items
             .iter()
             .rev() // Reverse to show stack top first
@@ -1194,28 +1517,17 @@ impl DisplayPanel {
             .map(|(display_idx, item)| {
                 let is_selected = display_idx == self.selected_index;
 
-                // Format the stack item
-                let index_str = format!("[{:2}]", item.index);
-                let value_str = utils::format_value_with_decode(&item.value);
+                let value_line = self.apply_horizontal_offset(Line::from(
+                    utils::format_value_with_decode(&item.value),
+                ));
 
-                // Add diff indicator
+                // Diff indicator column
                 let diff_indicator = match item.diff_status {
-                    DiffStatus::New => " [NEW]",
-                    DiffStatus::Modified => " [CHG]",
+                    DiffStatus::New => "NEW",
+                    DiffStatus::Modified => "CHG",
                     _ => "",
                 };
 
-                // Create line with spans for better styling
-                let mut line_spans =
-                    vec![Span::raw(index_str), Span::raw(" "), Span::raw(value_str)];
-
-                if !diff_indicator.is_empty() {
-                    line_spans.push(Span::raw(diff_indicator));
-                }
-
-                let line = Line::from(line_spans);
-                let formatted_line = self.apply_horizontal_offset(line);
-
                 // Apply styling based on diff status and selection
                 let mut style = if is_selected && self.focused {
                     Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
@@ -1230,128 +1542,97 @@ impl DisplayPanel {
                     style = style.fg(dm.theme.warning_color);
                 }
 
-                ListItem::new(formatted_line).style(style)
+                Row::new(vec![
+                    Cell::from(format!("{:>3}", item.index)),
+                    Cell::from(value_line),
+                    Cell::from(diff_indicator),
+                ])
+                .style(style)
             })
             .collect();
 
-        let list = List::new(items).block(BorderPresets::display(
-            self.focused,
-            self.title(dm),
-            dm.theme.focused_border,
-            dm.theme.unfocused_border,
-        ));
+        let header = Row::new(vec!["  #", "Value", "Diff"])
+            .style(Style::default().fg(dm.theme.accent_color).add_modifier(Modifier::BOLD));
 
-        frame.render_widget(list, area);
+        let table =
+            Table::new(rows, [Constraint::Length(3), Constraint::Fill(1), Constraint::Length(4)])
+                .header(header)
+                .column_spacing(1)
+                .block(BorderPresets::display(
+                    self.focused,
+                    self.title(dm),
+                    dm.theme.focused_border,
+                    dm.theme.unfocused_border,
+                ));
+
+        frame.render_widget(table, area);
         self.render_status_and_help(frame, area, dm);
     }
 
     /// Render memory display
     fn render_memory(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
-        if self.memory_chunks.is_empty() {
-            let paragraph = Paragraph::new("Memory is empty").block(BorderPresets::display(
-                self.focused,
-                self.title(dm),
-                dm.theme.focused_border,
-                dm.theme.unfocused_border,
-            ));
-            frame.render_widget(paragraph, area);
-            return;
-        }
-
-        // Create list items for memory display
-        let items: Vec<ListItem<'_>> = self
-            .memory_chunks
-            .iter()
-            .enumerate()
-            .skip(self.scroll_offset)
-            .take(self.context_height)
-            .map(|(display_idx, chunk)| {
-                let is_selected = display_idx == self.selected_index;
-
-                // Format offset
-                let offset_str = format!("0x{:04x}:", chunk.offset);
-
-                // Format bytes with highlighting
-                let byte_spans =
-                    format_bytes_with_decode(&chunk.data, &chunk.changed_bytes, &dm.theme);
-
-                let mut line = vec![Span::raw(offset_str), Span::raw(" ")];
-                line.extend(byte_spans);
-
-                let line_obj = Line::from(line);
-                let formatted_line = self.apply_horizontal_offset(line_obj);
-
-                let style = if is_selected && self.focused {
-                    Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
-                } else {
-                    Style::default()
-                };
-
-                ListItem::new(formatted_line).style(style)
-            })
-            .collect();
+        let dump = HexDump::new(&self.memory).with_changes(&self.memory_changes);
+        self.render_hexdump(frame, area, dm, dump, "Memory is empty");
+    }
 
-        let list = List::new(items).block(BorderPresets::display(
-            self.focused,
-            self.title(dm),
-            dm.theme.focused_border,
-            dm.theme.unfocused_border,
-        ));
+    /// Render calldata display, annotated with the arguments if the ABI is known
+    fn render_calldata(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
+        let regions = self.calldata_regions(dm);
+        let dump = HexDump::new(&self.calldata)
+            .with_changes(&self.calldata_changes)
+            .with_regions(&regions);
+        self.render_hexdump(frame, area, dm, dump, "No calldata");
+    }
 
-        frame.render_widget(list, area);
-        self.render_status_and_help(frame, area, dm);
+    /// Regions of the calldata, decoded with the ABI of the called function
+    fn calldata_regions(&self, dm: &mut DataManager) -> Vec<HexRegion> {
+        if self.calldata.len() < 4 {
+            return vec![];
+        }
+        let selector = Selector::from_slice(&self.calldata[..4]);
+        self.calldata_address
+            .and_then(|address| dm.resolver.get_contract_abi(address, false))
+            .and_then(|abi| abi.function_by_selector(selector))
+            .map(|function| hexdump::calldata_regions(function, &self.calldata))
+            .unwrap_or_default()
     }
 
-    /// Render calldata display
-    fn render_calldata(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
-        if self.calldata.is_empty() {
-            let paragraph = Paragraph::new("No calldata").block(BorderPresets::display(
-                self.focused,
-                self.title(dm),
-                dm.theme.focused_border,
-                dm.theme.unfocused_border,
-            ));
+    /// Render a hexdump with scrolling and selection
+    fn render_hexdump(
+        &self,
+        frame: &mut Frame<'_>,
+        area: Rect,
+        dm: &mut DataManager,
+        dump: HexDump<'_>,
+        empty_message: &str,
+    ) {
+        if dump.row_count() == 0 {
+            let paragraph =
+                Paragraph::new(empty_message.to_string()).block(BorderPresets::display(
+                    self.focused,
+                    self.title(dm),
+                    dm.theme.focused_border,
+                    dm.theme.unfocused_border,
+                ));
             frame.render_widget(paragraph, area);
             return;
         }
 
-        // Display calldata in 32-byte chunks
-        let mut items = Vec::new();
-        let calldata_bytes = &self.calldata[..];
-
-        for (chunk_idx, chunk) in calldata_bytes.chunks(32).enumerate() {
-            let offset = chunk_idx * 32;
-            let offset_str = format!("0x{offset:04x}:");
-
-            // Format bytes (hex only for calldata, no ASCII decoding)
-            let byte_spans = format_bytes_hex_only(chunk, &[], &dm.theme);
-
-            let mut line = vec![Span::raw(offset_str), Span::raw(" ")];
-            line.extend(byte_spans);
-
-            let line_obj = Line::from(line);
-            let formatted_line = self.apply_horizontal_offset(line_obj);
-            items.push(ListItem::new(formatted_line));
-        }
-
-        // Apply scrolling and selection
-        let visible_items: Vec<ListItem<'_>> = items
-            .into_iter()
-            .enumerate()
+        let items: Vec<ListItem<'_>> = (0..dump.row_count())
             .skip(self.scroll_offset)
             .take(self.context_height)
-            .map(|(display_idx, item)| {
-                let is_selected = display_idx == self.selected_index;
-                let style = if is_selected && self.focused {
+            .map(|row| {
+                let line = self.apply_horizontal_offset(dump.row(row, &dm.theme));
+                let style = if row == self.selected_index && self.focused {
                     Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
                 } else {
                     Style::default()
                 };
-                item.style(style)
+                ListItem::new(line).style(style)
             })
             .collect();
 
-        let list = List::new(visible_items).block(BorderPresets::display(
+        let list = List::new(items).block(BorderPresets::display(
             self.focused,
             self.title(dm),
             dm.theme.focused_border,
@@ -1470,17 +1751,17 @@ impl DisplayPanel {
 
             for (slot, (old_value, new_value)) in sorted_changes {
                 // Slot line
-                display_items.push(format!("• Slot: {slot:#066x}"));
+                display_items.push(format!("▾ Slot: {slot:#066x}"));
                 item_styles.push(StorageItemStyle::SlotLine);
 
                 // Old value
                 display_items
-                    .push(format!("  Old:  {}", utils::format_value_with_decode(old_value)));
+                    .push(format!("  ├─ Old: {}", utils::format_value_with_decode(old_value)));
                 item_styles.push(StorageItemStyle::Normal);
 
                 // New value with arrow
                 display_items
-                    .push(format!("  New:  {}", utils::format_value_with_decode(new_value)));
+                    .push(format!("  └─ New: {}", utils::format_value_with_decode(new_value)));
                 item_styles.push(StorageItemStyle::ChangeLine);
 
                 // Add small separator between items
@@ -1489,6 +1770,8 @@ impl DisplayPanel {
             }
         }
 
+        self.selected_storage_line = display_items.get(self.selected_index).cloned();
+
         // Create list items with proper scrolling and selection
         let items: Vec<ListItem<'_>> = display_items
             .iter()
@@ -1552,6 +1835,66 @@ impl DisplayPanel {
         self.render_status_and_help(frame, area, dm);
     }
 
+    /// Render the storage changed by the last step
+    fn render_storage_diff(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
+        if self.step_storage_changes.is_empty() {
+            let paragraph =
+                Paragraph::new("No storage changed at this step").block(BorderPresets::display(
+                    self.focused,
+                    self.title(dm),
+                    dm.theme.focused_border,
+                    dm.theme.unfocused_border,
+                ));
+            frame.render_widget(paragraph, area);
+            return;
+        }
+
+        let mut display_items = Vec::new();
+        for (slot, before, after) in &self.step_storage_changes {
+            display_items.push((
+                format!("▾ Slot: {slot:#066x}"),
+                Style::default().fg(dm.theme.accent_color).add_modifier(Modifier::BOLD),
+            ));
+            display_items.push((
+                format!("  ├─ Old: {}", utils::format_value_with_decode(before)),
+                Style::default().fg(dm.theme.error_color),
+            ));
+            display_items.push((
+                format!("  └─ New: {}", utils::format_value_with_decode(after)),
+                Style::default().fg(dm.theme.success_color).add_modifier(Modifier::BOLD),
+            ));
+            display_items.push((String::new(), Style::default()));
+        }
+
+        self.selected_storage_line =
+            display_items.get(self.selected_index).map(|(line, _)| line.clone());
+
+        let items: Vec<ListItem<'_>> = display_items
+            .into_iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.context_height)
+            .map(|(display_idx, (line, style))| {
+                let style = if display_idx == self.selected_index && self.focused {
+                    Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
+                } else {
+                    style
+                };
+                ListItem::new(self.apply_horizontal_offset(Line::from(line))).style(style)
+            })
+            .collect();
+
+        let list = List::new(items).block(BorderPresets::display(
+            self.focused,
+            self.title(dm),
+            dm.theme.focused_border,
+            dm.theme.unfocused_border,
+        ));
+
+        frame.render_widget(list, area);
+        self.render_status_and_help(frame, area, dm);
+    }
+
     /// Render transient storage display
     fn render_transient_storage(
         &mut self,
@@ -1657,10 +2000,10 @@ impl DisplayPanel {
 
             for (slot, value) in tstorage_items {
                 // Slot and value with clean formatting
-                display_items.push(format!("• Slot: {slot:#066x}"));
+                display_items.push(format!("▾ Slot: {slot:#066x}"));
                 item_styles.push(StorageItemStyle::SlotLine);
 
-                display_items.push(format!("  Val:  {}", utils::format_value_with_decode(value)));
+                display_items.push(format!("  └─ Val: {}", utils::format_value_with_decode(value)));
                 item_styles.push(StorageItemStyle::ChangeLine);
 
                 // Add separator
@@ -1669,6 +2012,8 @@ impl DisplayPanel {
             }
         }
 
+        self.selected_storage_line = display_items.get(self.selected_index).cloned();
+
         let items: Vec<ListItem<'_>> = display_items
             .iter()
             .zip(item_styles.iter())
@@ -1734,7 +2079,7 @@ impl DisplayPanel {
     /// Render expressions display (for both opcode and hooked snapshots)
     fn render_expressions(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
         if self.expressions.is_empty() {
-            let empty_msg = "No expressions watched";
+            let empty_msg = "No expressions watched (press 'a' to add one)";
             let paragraph = Paragraph::new(empty_msg).block(BorderPresets::display(
                 self.focused,
                 self.title(dm),
@@ -1808,8 +2153,16 @@ impl DisplayPanel {
             .map(|(display_idx, entry)| {
                 let is_selected = display_idx == self.selected_index;
 
+                // Variables are grouped by category; draw them as branches of a tree
+                let is_last_in_category = current_variables
+                    .get(display_idx + 1)
+                    .is_none_or(|next| next.category != entry.category);
+                let branch = if is_last_in_category { "└─ " } else { "├─ " };
+
                 // Format the variable entry with syntax highlighting
-                let formatted_spans = self.format_variable_entry(entry, dm);
+                let mut formatted_spans =
+                    vec![Span::styled(branch, Style::default().fg(dm.theme.comment_color))];
+                formatted_spans.extend(self.format_variable_entry(entry, dm));
 
                 // Create a line from the spans
                 let line = Line::from(formatted_spans);
@@ -1891,6 +2244,33 @@ impl DisplayPanel {
         self.render_status_and_help(frame, area, dm);
     }
 
+    /// Render the tab bar above the active view and return the area left for it
+    fn render_tab_bar(&self, frame: &mut Frame<'_>, area: Rect, dm: &DataManager) -> Rect {
+        if self.available_modes.len() < 2 || area.height <= 6 {
+            return area;
+        }
+
+        let tab_area = Rect { height: 1, ..area };
+        let titles: Vec<Line<'_>> = self
+            .available_modes
+            .iter()
+            .enumerate()
+            .map(|(i, mode)| Line::from(format!("{} {}", i + 1, mode.name())))
+            .collect();
+        let selected = self.available_modes.iter().position(|m| *m == self.mode);
+
+        let tabs = Tabs::new(titles)
+            .select(selected)
+            .style(Style::default().fg(dm.theme.comment_color))
+            .highlight_style(
+                Style::default().fg(dm.theme.accent_color).add_modifier(Modifier::BOLD),
+            )
+            .divider("│");
+        frame.render_widget(tabs, tab_area);
+
+        Rect { y: area.y + 1, height: area.height - 1, ..area }
+    }
+
     /// Render status and help text
     fn render_status_and_help(&self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
         if !self.focused || area.height <= 10 {
@@ -1946,15 +2326,19 @@ impl DisplayPanel {
             Rect { x: area.x + 1, y: area.y + area.height - 2, width: area.width - 2, height: 1 };
 
         let help_text = match self.mode {
-            DisplayMode::Variables | DisplayMode::Expressions => {
-                "Vim-like Navigation • s/S: Switch mode • Enter: Toggle multi-line • ?: Help"
+            DisplayMode::Variables => {
+                "Vim-like Navigation • 1-9/s/S: Switch tab • Enter: Toggle multi-line • y: Copy • ?: Help"
+                    .to_string()
+            }
+            DisplayMode::Expressions => {
+                "1-9/s/S: Switch tab • a/e/d: Add/Edit/Delete • p: Pin • Enter: Multi-line • y: Copy • ?: Help"
                     .to_string()
             }
             DisplayMode::Breakpoints => {
-                "Vim-like Navigation • s/S: Switch mode • Enter: Toggle enable/disable • ?: Help"
+                "1-9/s/S: Switch tab • Enter: Enable/disable • d: Delete • g: Next hit • ?: Help"
                     .to_string()
             }
-            _ => "Vim-like Navigation • s/S: Switch mode • ?: Help".to_string(),
+            _ => "Vim-like Navigation • 1-9/s/S: Switch tab • y: Copy • ?: Help".to_string(),
         };
 
         let help_paragraph =
@@ -1968,40 +2352,53 @@ impl PanelTr for DisplayPanel {
         PanelType::Display
     }
 
-    fn title(&self, _dm: &mut DataManager) -> String {
+    fn title(&self, dm: &mut DataManager) -> String {
         let item_count = match self.mode {
             DisplayMode::Stack => self.stack_items.len(),
-            DisplayMode::Memory => self.memory_chunks.len(),
-            DisplayMode::CallData => {
-                if self.calldata.is_empty() {
-                    0
-                } else {
-                    self.calldata.len().div_ceil(32)
-                }
-            }
+            DisplayMode::Memory => HexDump::new(&self.memory).row_count(),
+            DisplayMode::CallData => HexDump::new(&self.calldata).row_count(),
             DisplayMode::Storage => self.storage_display_lines,
             DisplayMode::TransientStorage => self.tstorage_display_lines,
+            DisplayMode::StorageDiff => self.step_storage_changes.len(),
             DisplayMode::Variables => self.variables.len(),
             DisplayMode::Expressions => self.expressions.len(),
             DisplayMode::Breakpoints => self.breakpoints.len(),
         };
 
         let snapshot_type = if self.is_opcode_snapshot { "Opcode" } else { "Hook" };
-        format!("{} - {} ({} items)", snapshot_type, self.mode.name(), item_count)
+
+        // Make clear whose storage is shown when running code of another account
+        let proxy_context = match self.proxy_context {
+            Some((storage_address, code_address)) => format!(
+                " - ⚠ Proxy context: storage of {}, code of {}",
+                dm.resolver.resolve_address(storage_address),
+                dm.resolver.resolve_address(code_address)
+            ),
+            None => String::new(),
+        };
+
+        format!("{} - {} ({} items){}", snapshot_type, self.mode.name(), item_count, proxy_context)
     }
 
     fn render(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
+        // Update snapshot data if needed (this also refreshes the available tabs)
+        let _ = self.update_snapshot_data(dm);
+
+        // Render the tab bar and keep the remaining area for the active view
+        let area = self.render_tab_bar(frame, area, dm);
+
         // Calculate context height and width for viewport calculations
         self.context_height = if self.focused && area.height > 10 {
             area.height.saturating_sub(4) // Account for borders and status lines
         } else {
             area.height.saturating_sub(2) // Just borders
         } as usize;
+        if self.mode == DisplayMode::Stack {
+            // The stack table has a header row
+            self.context_height = self.context_height.saturating_sub(1);
+        }
         self.content_width = area.width.saturating_sub(2) as usize; // Account for borders
 
-        // Update snapshot data if needed
-        let _ = self.update_snapshot_data(dm);
-
         // Calculate max line width for horizontal scrolling
         self.calculate_max_line_width(dm);
 
@@ -2015,10 +2412,13 @@ impl PanelTr for DisplayPanel {
             DisplayMode::CallData => self.render_calldata(frame, area, dm),
             DisplayMode::Storage => self.render_storage(frame, area, dm),
             DisplayMode::TransientStorage => self.render_transient_storage(frame, area, dm),
+            DisplayMode::StorageDiff => self.render_storage_diff(frame, area, dm),
             DisplayMode::Variables => self.render_variables(frame, area, dm),
             DisplayMode::Expressions => self.render_expressions(frame, area, dm),
             DisplayMode::Breakpoints => self.render_breakpoints(frame, area, dm),
         }
+
+        self.render_watch_input(frame, area, dm);
     }
 
     fn handle_key_event(&mut self, event: KeyEvent, dm: &mut DataManager) -> Result<EventResponse> {
@@ -2026,7 +2426,35 @@ impl PanelTr for DisplayPanel {
             return Ok(EventResponse::NotHandled);
         }
 
+        if self.watch_input.is_some() {
+            return Ok(self.handle_watch_input(event, dm));
+        }
+
         match event.code {
+            KeyCode::Char('a') if self.mode == DisplayMode::Expressions => {
+                self.start_watch_input(false);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('e') if self.mode == DisplayMode::Expressions => {
+                self.start_watch_input(true);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.mode == DisplayMode::Expressions => {
+                self.remove_selected_watch(dm);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('p') if self.mode == DisplayMode::Expressions => {
+                self.toggle_selected_watch_pin(dm);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.mode == DisplayMode::Breakpoints => {
+                self.remove_selected_breakpoint(dm);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('g') if self.mode == DisplayMode::Breakpoints => {
+                self.goto_next_breakpoint_hit(dm);
+                Ok(EventResponse::Handled)
+            }
             KeyCode::Char('s') => {
                 self.next_mode();
                 Ok(EventResponse::Handled)
@@ -2035,6 +2463,14 @@ impl PanelTr for DisplayPanel {
                 self.prev_mode();
                 Ok(EventResponse::Handled)
             }
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if self.select_mode(index) {
+                    Ok(EventResponse::Handled)
+                } else {
+                    Ok(EventResponse::NotHandled)
+                }
+            }
             KeyCode::Up => {
                 self.move_up(1);
                 Ok(EventResponse::Handled)
@@ -2117,6 +2553,49 @@ impl PanelTr for DisplayPanel {
         }
     }
 
+    fn yank(&mut self, _dm: &mut DataManager) -> Option<Yank> {
+        let index = self.selected_index;
+        match self.mode {
+            DisplayMode::Variables | DisplayMode::Expressions => {
+                let entries = if self.mode == DisplayMode::Variables {
+                    &self.variables
+                } else {
+                    &self.expressions
+                };
+                let entry = entries.get(index)?;
+                let name = entry.expression.as_deref().unwrap_or(&entry.name);
+                let value = entry.value.as_ref()?.format_value(&SolValueFormatterContext::new());
+                Some(Yank::new(format!("value of {name}"), value))
+            }
+            DisplayMode::Breakpoints => {
+                let entry = self.breakpoints.get(index)?;
+                Some(Yank::new(format!("breakpoint #{}", entry.id), entry.breakpoint.to_string()))
+            }
+            DisplayMode::Stack => {
+                let item = self.stack_items.iter().rev().nth(index)?;
+                Some(Yank::new(format!("stack item {}", item.index), format!("{:#x}", item.value)))
+            }
+            DisplayMode::Memory => {
+                let bytes = HexDump::new(&self.memory).row_bytes(index);
+                (!bytes.is_empty()).then(|| {
+                    Yank::new(
+                        format!("memory at {:#x}", HexDump::row_offset(index)),
+                        hex::encode_prefixed(bytes),
+                    )
+                })
+            }
+            DisplayMode::CallData => (!self.calldata.is_empty())
+                .then(|| Yank::new("call data", self.calldata.to_string())),
+            DisplayMode::Storage | DisplayMode::TransientStorage | DisplayMode::StorageDiff => {
+                parse_storage_line(self.selected_storage_line.as_deref()?)
+            }
+        }
+    }
+
+    fn is_capturing_input(&self) -> bool {
+        self.focused && self.watch_input.is_some()
+    }
+
     fn on_focus(&mut self) {
         self.focused = true;
         debug!("Display panel gained focus");
@@ -2124,6 +2603,7 @@ impl PanelTr for DisplayPanel {
 
     fn on_blur(&mut self) {
         self.focused = false;
+        self.watch_input = None;
         debug!("Display panel lost focus");
     }
 
@@ -2133,72 +2613,142 @@ impl PanelTr for DisplayPanel {
 }
 
 // Helper functions
-/// Format bytes as hex with ASCII decode (like xxd)
-fn format_bytes_with_decode<'a>(
-    bytes: &'a [u8],
-    highlight_indices: &'a [usize],
-    theme: &ColorScheme,
-) -> Vec<Span<'a>> {
-    let mut spans = Vec::new();
-
-    // Hex part
-    for (i, byte) in bytes.iter().enumerate() {
-        let hex = format!("{byte:02x}");
-        if highlight_indices.contains(&i) {
-            spans.push(Span::styled(
-                hex,
-                Style::default().fg(theme.warning_color).add_modifier(Modifier::BOLD),
-            ));
-        } else {
-            spans.push(Span::raw(hex));
-        }
-        if i < bytes.len() - 1 {
-            spans.push(Span::raw(" "));
-        }
-    }
+/// Get the slot or value on a rendered storage line, e.g. `  ├─ Old: 0x… (     42) …`
+fn parse_storage_line(line: &str) -> Option<Yank> {
+    const LABELS: [(&str, &str); 4] = [
+        ("Slot:", "storage slot"),
+        ("Old:", "old storage value"),
+        ("New:", "new storage value"),
+        ("Val:", "transient storage value"),
+    ];
+
+    LABELS.iter().find_map(|(label, description)| {
+        let (_, rest) = line.split_once(label)?;
+        let value = rest.split_whitespace().next()?;
+        Some(Yank::new(*description, value))
+    })
+}
 
-    // ASCII part
-    spans.push(Span::raw("  "));
-    for (i, byte) in bytes.iter().enumerate() {
-        let ch = if byte.is_ascii_graphic() || *byte == b' ' {
-            *byte as char
-        } else {
-            '.' // Show all non-printable bytes (including 0) as dots
-        };
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
 
-        if highlight_indices.contains(&i) {
-            spans.push(Span::styled(ch.to_string(), Style::default().fg(theme.warning_color)));
-        } else {
-            spans.push(Span::raw(ch.to_string()));
+    use alloy_dyn_abi::DynSolValue;
+    use alloy_primitives::U256;
+    use ratatui::style::Color;
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::{
+        draw, draw_buffer, find_cell, hook_snapshot, render, settle,
+        skip_if_loopback_binds_restricted, MockEngine,
+    };
+
+    #[tokio::test]
+    async fn test_breakpoint_hit_counts() {
+        if skip_if_loopback_binds_restricted("test_breakpoint_hit_counts").await {
+            return;
         }
+        let mut dm = MockEngine::new(10)
+            .with_method("edb_getBreakpointHits", |params| match params[0]["condition"].as_str() {
+                Some("x > 1") => json!([1, 4, 7]),
+                _ => json!([2]),
+            })
+            .data_manager()
+            .await;
+        dm.execution.add_breakpoint(Breakpoint::new(None, Some("x > 1".to_string()))).unwrap();
+        settle(&mut dm).await;
+        dm.execution.add_breakpoint(Breakpoint::new(None, Some("y == 0".to_string()))).unwrap();
+
+        let mut panel = DisplayPanel::new();
+        panel.mode = DisplayMode::Breakpoints;
+
+        // The hit count of a breakpoint is pending until the engine answered
+        let lines = draw(&mut panel, &mut dm, 80, 10);
+        assert!(lines.iter().any(|line| line.contains("x > 1") && line.contains("(3 hits)")));
+        assert!(lines.iter().any(|line| line.contains("y == 0") && line.contains("(… hits)")));
+
+        settle(&mut dm).await;
+        let lines = draw(&mut panel, &mut dm, 80, 10);
+        assert!(lines.iter().any(|line| line.contains("x > 1") && line.contains("(3 hits)")));
+        assert!(lines.iter().any(|line| line.contains("y == 0") && line.contains("(1 hit)")));
     }
 
-    spans
-}
+    #[tokio::test]
+    async fn test_storage_diff_shows_step_changes() {
+        if skip_if_loopback_binds_restricted("test_storage_diff_shows_step_changes").await {
+            return;
+        }
+        let snapshots = (0..3).map(|id| hook_snapshot(id, 3, vec![])).collect();
+        let slot = |value: u64| U256::from(value);
+        let mut dm = MockEngine::new(1)
+            .with_snapshots(snapshots)
+            .with_method("edb_getStorageDiff", move |params| {
+                // Storage diffs are cumulative since the start of the transaction
+                let diff: HashMap<U256, (U256, U256)> = match params[0].as_u64().unwrap() {
+                    0 => HashMap::new(),
+                    1 => HashMap::from([(slot(1), (slot(0), slot(5)))]),
+                    _ => HashMap::from([
+                        (slot(1), (slot(0), slot(5))),
+                        (slot(2), (slot(3), slot(7))),
+                    ]),
+                };
+                serde_json::to_value(diff).unwrap()
+            })
+            .data_manager()
+            .await;
+
+        let mut panel = DisplayPanel::new();
+        panel.mode = DisplayMode::StorageDiff;
+
+        let lines = render(&mut panel, &mut dm, 160, 20).await;
+        assert!(lines.iter().any(|line| line.contains("No storage changed at this step")));
+
+        // Only the slot written by the last step is shown, with its value before the step
+        dm.execution.step(2).unwrap();
+        let lines = render(&mut panel, &mut dm, 160, 20).await;
+        let slot_line = |value: u64| format!("Slot: {:#066x}", U256::from(value));
+        assert!(lines.iter().any(|line| line.contains(&slot_line(2))));
+        assert!(!lines.iter().any(|line| line.contains(&slot_line(1))));
+        assert!(lines.iter().any(|line| line.contains("Old:") && line.contains("(      3)")));
+        assert!(lines.iter().any(|line| line.contains("New:") && line.contains("(      7)")));
+    }
 
-/// Format bytes as hex only (no ASCII decode) - for calldata
-fn format_bytes_hex_only<'a>(
-    bytes: &'a [u8],
-    highlight_indices: &'a [usize],
-    theme: &ColorScheme,
-) -> Vec<Span<'a>> {
-    let mut spans = Vec::new();
-
-    // Hex part only
-    for (i, byte) in bytes.iter().enumerate() {
-        let hex = format!("{byte:02x}");
-        if highlight_indices.contains(&i) {
-            spans.push(Span::styled(
-                hex,
-                Style::default().fg(theme.warning_color).add_modifier(Modifier::BOLD),
-            ));
-        } else {
-            spans.push(Span::raw(hex));
+    #[tokio::test]
+    async fn test_changed_variables_are_highlighted() {
+        if skip_if_loopback_binds_restricted("test_changed_variables_are_highlighted").await {
+            return;
         }
-        if i < bytes.len() - 1 {
-            spans.push(Span::raw(" "));
+        let uint = |value: u64| EdbSolValue(DynSolValue::Uint(U256::from(value), 256));
+        let snapshots = [1, 2, 2, 2, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(id, x)| hook_snapshot(id, 5, vec![("x", uint(x)), ("y", uint(0))]))
+            .collect();
+        let mut dm = MockEngine::new(1).with_snapshots(snapshots).data_manager().await;
+        let theme = dm.theme.clone();
+
+        let mut panel = DisplayPanel::new();
+        panel.mode = DisplayMode::Variables;
+        render(&mut panel, &mut dm, 80, 10).await;
+
+        // `x` is highlighted when it changes and fades out over the following steps,
+        // while `y` never changes
+        let expected = [
+            (theme.warning_color, Modifier::BOLD),
+            (theme.warning_color, Modifier::empty()),
+            (theme.warning_color, Modifier::DIM),
+            (Color::Reset, Modifier::empty()),
+        ];
+        for (fg, modifier) in expected {
+            dm.execution.step(1).unwrap();
+            render(&mut panel, &mut dm, 80, 10).await;
+
+            let buffer = draw_buffer(&mut panel, &mut dm, 80, 10);
+            let x = find_cell(&buffer, "x = ").unwrap();
+            assert_eq!((x.fg, x.modifier), (fg, modifier));
+            let y = find_cell(&buffer, "y = ").unwrap();
+            assert_eq!((y.fg, y.modifier), (Color::Reset, Modifier::empty()));
         }
     }
-
-    spans
 }
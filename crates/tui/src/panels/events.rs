@@ -0,0 +1,392 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Events panel for browsing emitted events
+//!
+//! This panel lists the decoded events emitted up to the current snapshot, in
+//! emission order. Enter jumps to the snapshot that emitted the selected event.
+
+use super::{utils, EventResponse, HelpEntry, PanelTr, PanelType, Yank};
+use crate::data::DataManager;
+use crate::ui::borders::BorderPresets;
+use crate::ui::status::StatusBar;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use edb_common::types::{CapturedLog, Trace};
+use eyre::{bail, Result};
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{List, ListItem, Paragraph},
+    Frame,
+};
+use tracing::debug;
+
+/// Get the snapshot at which a log was emitted
+///
+/// Logs whose emitting snapshot is unknown fall back to the first snapshot of their
+/// trace entry.
+fn emitting_snapshot(log: &CapturedLog, trace: &Trace) -> Option<usize> {
+    log.snapshot_id.or_else(|| trace.get(log.trace_entry_id).and_then(|e| e.first_snapshot_id))
+}
+
+/// Get the indices of the logs to list, in emission order
+///
+/// Only logs emitted up to the current snapshot are listed, unless `show_all` is set.
+fn visible_logs(logs: &[CapturedLog], trace: &Trace, current: usize, show_all: bool) -> Vec<usize> {
+    logs.iter()
+        .enumerate()
+        .filter(|(_, log)| {
+            show_all || emitting_snapshot(log, trace).is_some_and(|id| id <= current)
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Events panel implementation
+#[derive(Debug)]
+pub struct EventsPanel {
+    /// Currently selected row
+    selected_index: usize,
+    /// Scroll offset
+    scroll_offset: usize,
+    /// Current content height
+    context_height: usize,
+    /// Whether this panel is focused
+    focused: bool,
+    /// Whether events emitted after the current snapshot are listed as well
+    show_all: bool,
+    /// Indices of the listed logs
+    rows: Vec<usize>,
+    /// Snapshot the rows were computed for
+    current_snapshot: Option<usize>,
+}
+
+impl EventsPanel {
+    /// Create a new events panel
+    pub fn new() -> Self {
+        Self {
+            selected_index: 0,
+            scroll_offset: 0,
+            context_height: 0,
+            focused: false,
+            show_all: false,
+            rows: Vec::new(),
+            current_snapshot: None,
+        }
+    }
+
+    /// Move selection up
+    fn move_up(&mut self, count: usize) {
+        self.selected_index = self.selected_index.saturating_sub(count);
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        }
+    }
+
+    /// Move selection down
+    fn move_down(&mut self, count: usize) {
+        self.selected_index =
+            self.selected_index.saturating_add(count).min(self.rows.len().saturating_sub(1));
+        if self.selected_index >= self.scroll_offset + self.context_height {
+            self.scroll_offset = (self.selected_index + 1).saturating_sub(self.context_height);
+        }
+    }
+
+    /// Recompute the listed rows, following execution when the snapshot changed
+    fn update_rows(&mut self, dm: &mut DataManager) {
+        let current = dm.execution.get_current_snapshot();
+        let trace = dm.execution.get_trace();
+        self.rows = visible_logs(dm.execution.get_logs(), trace, current, self.show_all);
+
+        if self.current_snapshot != Some(current) {
+            // Select the latest event emitted so far
+            let logs = dm.execution.get_logs();
+            let latest = self.rows.iter().rposition(|&index| {
+                emitting_snapshot(&logs[index], trace).is_some_and(|id| id <= current)
+            });
+            self.selected_index = 0;
+            self.scroll_offset = 0;
+            self.move_down(latest.unwrap_or_default());
+            self.current_snapshot = Some(current);
+        } else if self.selected_index >= self.rows.len() {
+            self.selected_index = self.rows.len().saturating_sub(1);
+            self.scroll_offset = self.scroll_offset.min(self.selected_index);
+        }
+    }
+
+    /// Format a single event row
+    fn format_row(&self, row: usize, log: &CapturedLog, dm: &mut DataManager) -> Line<'static> {
+        let decode_address = dm
+            .execution
+            .get_trace()
+            .get(log.trace_entry_id)
+            .map(|entry| entry.code_address)
+            .unwrap_or(log.address);
+        let event_text = dm
+            .resolver
+            .resolve_event(&log.log, Some(decode_address))
+            .unwrap_or_else(|| utils::format_undecoded_event(&log.log));
+        let emitter = dm.resolver.resolve_address(log.address);
+        let snapshot = match emitting_snapshot(log, dm.execution.get_trace()) {
+            Some(id) => format!(" #{id}"),
+            None => " #?".to_string(),
+        };
+
+        Line::from(vec![
+            Span::styled(format!("{:>4} ", row + 1), Style::default().fg(dm.theme.line_number)),
+            Span::styled(event_text, Style::default().fg(dm.theme.accent_color)),
+            Span::styled(format!("  @ {emitter}"), Style::default().fg(dm.theme.comment_color)),
+            Span::styled(snapshot, Style::default().fg(dm.theme.comment_color)),
+        ])
+    }
+}
+
+impl PanelTr for EventsPanel {
+    fn panel_type(&self) -> PanelType {
+        PanelType::Events
+    }
+
+    fn title(&self, dm: &mut DataManager) -> String {
+        let total = dm.execution.get_logs().len();
+        if self.show_all {
+            format!("Events ({total} total) [all]")
+        } else {
+            format!("Events ({}/{} emitted)", self.rows.len(), total)
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
+        self.context_height = if self.focused && area.height > 10 {
+            area.height.saturating_sub(4) // Account for borders and status lines
+        } else {
+            area.height.saturating_sub(2) // Just borders
+        } as usize;
+
+        self.update_rows(dm);
+
+        let block = BorderPresets::events(
+            self.focused,
+            self.title(dm),
+            dm.theme.focused_border,
+            dm.theme.unfocused_border,
+        );
+
+        if self.rows.is_empty() {
+            let message = if dm.execution.get_logs().is_empty() {
+                "No events were emitted in this transaction"
+            } else {
+                "No events emitted up to the current snapshot (a: Show all)"
+            };
+            frame.render_widget(Paragraph::new(message).block(block), area);
+            return;
+        }
+
+        let current = dm.execution.get_current_snapshot();
+        let shown: Vec<(usize, CapturedLog)> = self
+            .rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.context_height)
+            .map(|(row, &index)| (row, dm.execution.get_logs()[index].clone()))
+            .collect();
+        let items: Vec<ListItem<'_>> = shown
+            .iter()
+            .map(|(row, log)| {
+                let row = *row;
+                let emitted_at = emitting_snapshot(log, dm.execution.get_trace());
+                let is_selected = row == self.selected_index;
+
+                let style = if is_selected && self.focused {
+                    Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
+                } else if is_selected {
+                    Style::default().bg(dm.theme.highlight_bg)
+                } else if emitted_at == Some(current) {
+                    // Event emitted by the current snapshot
+                    Style::default().bg(dm.theme.current_line_bg)
+                } else if emitted_at.is_none_or(|id| id > current) {
+                    // Not emitted yet (only listed when showing all events)
+                    Style::default().fg(dm.theme.comment_color)
+                } else {
+                    Style::default()
+                };
+
+                let line = self.format_row(row, log, dm);
+                ListItem::new(line).style(style)
+            })
+            .collect();
+
+        frame.render_widget(List::new(items).block(block), area);
+
+        // Add status and help text at the bottom if focused
+        if self.focused && area.height > 10 {
+            let status_area = Rect {
+                x: area.x + 1,
+                y: area.y + area.height - 3,
+                width: area.width - 2,
+                height: 1,
+            };
+
+            let status_bar = StatusBar::new()
+                .current_panel("Events".to_string())
+                .message(format!("Event: {}/{}", self.selected_index + 1, self.rows.len()))
+                .message(format!("Snapshot: {current}"));
+            let status_paragraph = Paragraph::new(status_bar.build())
+                .style(Style::default().fg(dm.theme.accent_color));
+            frame.render_widget(status_paragraph, status_area);
+
+            let help_area = Rect {
+                x: area.x + 1,
+                y: area.y + area.height - 2,
+                width: area.width - 2,
+                height: 1,
+            };
+            let help_text = "↑/↓/j/k: Navigate • Enter: Go to emitting snapshot • a: Show all • y: Copy address • Space: Trace Panel • ?: Help";
+            let help_paragraph =
+                Paragraph::new(help_text).style(Style::default().fg(dm.theme.help_text_color));
+            frame.render_widget(help_paragraph, help_area);
+        }
+    }
+
+    fn handle_key_event(&mut self, event: KeyEvent, dm: &mut DataManager) -> Result<EventResponse> {
+        if !self.focused || event.kind != KeyEventKind::Press {
+            return Ok(EventResponse::NotHandled);
+        }
+
+        match event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.move_up(1);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.move_down(1);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::PageUp => {
+                self.move_up(self.context_height / 2);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::PageDown => {
+                self.move_down(self.context_height / 2);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.move_up(self.selected_index);
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.move_down(self.rows.len());
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Char('a') => {
+                self.show_all = !self.show_all;
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Enter => {
+                let Some(log) = self
+                    .rows
+                    .get(self.selected_index)
+                    .and_then(|&i| dm.execution.get_logs().get(i))
+                else {
+                    return Ok(EventResponse::Handled);
+                };
+
+                let Some(snapshot_id) = emitting_snapshot(log, dm.execution.get_trace()) else {
+                    bail!("The selected event has no associated snapshot. We cannot change execution to it.");
+                };
+                debug!("Jumping to emitting snapshot: {}", snapshot_id);
+                dm.execution.goto(snapshot_id, false)?;
+                Ok(EventResponse::Handled)
+            }
+            _ => Ok(EventResponse::NotHandled),
+        }
+    }
+
+    fn handle_mouse_event(
+        &mut self,
+        event: crossterm::event::MouseEvent,
+        _dm: &mut DataManager,
+    ) -> Result<EventResponse> {
+        use crossterm::event::MouseEventKind;
+
+        match event.kind {
+            MouseEventKind::ScrollUp => {
+                self.move_up(1);
+                Ok(EventResponse::Handled)
+            }
+            MouseEventKind::ScrollDown => {
+                self.move_down(1);
+                Ok(EventResponse::Handled)
+            }
+            _ => Ok(EventResponse::NotHandled),
+        }
+    }
+
+    fn on_focus(&mut self) {
+        self.focused = true;
+        debug!("Events panel gained focus");
+    }
+
+    fn on_blur(&mut self) {
+        self.focused = false;
+        debug!("Events panel lost focus");
+    }
+
+    fn help_entries(&self) -> Vec<HelpEntry> {
+        if self.show_all {
+            vec![HelpEntry::new("a", "Only list events emitted up to the current snapshot")]
+        } else {
+            vec![HelpEntry::new("a", "Also list events emitted after the current snapshot")]
+        }
+    }
+
+    fn yank(&mut self, dm: &mut DataManager) -> Option<Yank> {
+        let log =
+            self.rows.get(self.selected_index).and_then(|&i| dm.execution.get_logs().get(i))?;
+        Some(Yank::new("emitting address", log.address.to_string()))
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, LogData};
+
+    fn log(snapshot_id: Option<usize>) -> CapturedLog {
+        CapturedLog {
+            trace_entry_id: 0,
+            index: 0,
+            address: Address::ZERO,
+            log: LogData::default(),
+            snapshot_id,
+        }
+    }
+
+    #[test]
+    fn test_visible_logs() {
+        let logs = vec![log(Some(3)), log(Some(7)), log(None), log(Some(12))];
+        let trace = Trace::default();
+
+        assert_eq!(visible_logs(&logs, &trace, 7, false), vec![0, 1]);
+        assert_eq!(visible_logs(&logs, &trace, 2, false), Vec::<usize>::new());
+        assert_eq!(visible_logs(&logs, &trace, 2, true), vec![0, 1, 2, 3]);
+    }
+}
@@ -17,9 +17,14 @@
 //! Help overlay for displaying keyboard shortcuts and navigation
 //!
 //! This module provides a help overlay that displays context-aware keyboard shortcuts.
+//! By default it lists the bindings of the focused panel, followed by entries the panel
+//! contributes for its current state (e.g., active filters) and the global bindings.
+//! `Tab` switches to the full keymap, and `/` searches it.
 
+use super::{HelpEntry, PanelType};
 use crate::data::DataManager;
 use crate::layout::LayoutType;
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
@@ -40,24 +45,49 @@ struct HelpItem {
 #[derive(Debug, Clone)]
 struct HelpSection {
     title: &'static str,
+    /// Panel the shortcuts apply to, or `None` for global shortcuts
+    panel: Option<PanelType>,
     items: Vec<HelpItem>,
 }
 
+/// Section as displayed, once filtered: title and (keys, description) rows
+type VisibleSection = (String, Vec<(String, String)>);
+
 /// Help overlay renderer
 pub struct HelpOverlay {
     scroll_offset: usize,
     content_height: usize,
     viewport_height: usize,
+    /// Whether every binding is listed, rather than those relevant to the focused panel
+    show_full_keymap: bool,
+    /// Search query over the full keymap
+    query: String,
+    /// Whether the search prompt is open
+    searching: bool,
 }
 
 impl HelpOverlay {
     /// Create a new help overlay
     pub fn new() -> Self {
-        Self { scroll_offset: 0, content_height: 0, viewport_height: 0 }
+        Self {
+            scroll_offset: 0,
+            content_height: 0,
+            viewport_height: 0,
+            show_full_keymap: false,
+            query: String::new(),
+            searching: false,
+        }
     }
 
-    /// Render the help overlay
-    pub fn render(&mut self, frame: &mut Frame<'_>, layout_type: LayoutType, dm: &DataManager) {
+    /// Render the help overlay for the focused panel, with the entries it contributes
+    pub fn render(
+        &mut self,
+        frame: &mut Frame<'_>,
+        layout_type: LayoutType,
+        panel: PanelType,
+        entries: &[HelpEntry],
+        dm: &DataManager,
+    ) {
         let area = frame.area();
 
         // Create a centered popup area (80% width, 90% height)
@@ -67,22 +97,26 @@ impl HelpOverlay {
         frame.render_widget(Clear, popup_area);
 
         // Create the help content based on layout
-        let help_content = self.generate_help_content(layout_type, dm);
+        let sections = self.visible_sections(layout_type, panel, entries);
+        let help_content = self.generate_help_content(&sections, dm);
 
         // Calculate content dimensions
         self.content_height = help_content.lines.len();
         self.viewport_height = popup_area.height.saturating_sub(2) as usize; // -2 for borders
 
         // Create the help block with borders
+        let layout_name = match layout_type {
+            LayoutType::Full => "Full",
+            LayoutType::Compact => "Compact",
+            LayoutType::Mobile => "Mobile",
+        };
+        let view_name = if self.is_full_view() {
+            "Full Keymap".to_string()
+        } else {
+            format!("{panel:?} Panel")
+        };
         let help_block = Block::default()
-            .title(format!(
-                " EDB Debugger Help - {} Layout ",
-                match layout_type {
-                    LayoutType::Full => "Full",
-                    LayoutType::Compact => "Compact",
-                    LayoutType::Mobile => "Mobile",
-                }
-            ))
+            .title(format!(" EDB Debugger Help - {view_name} ({layout_name} Layout) "))
             .borders(Borders::ALL)
             .border_style(Style::default().fg(dm.theme.focused_border))
             .title_alignment(Alignment::Center);
@@ -102,52 +136,146 @@ impl HelpOverlay {
         }
     }
 
-    /// Generate help content based on the current layout
-    fn generate_help_content(&self, layout_type: LayoutType, dm: &DataManager) -> Text<'static> {
+    /// Whether the full keymap is listed, rather than the bindings of the focused panel
+    fn is_full_view(&self) -> bool {
+        self.show_full_keymap || !self.query.is_empty()
+    }
+
+    /// Get the sections to display, filtered by the view, the layout, and the search query
+    fn visible_sections(
+        &self,
+        layout_type: LayoutType,
+        panel: PanelType,
+        entries: &[HelpEntry],
+    ) -> Vec<VisibleSection> {
+        let mut sections = self.get_help_sections(layout_type);
+        if !self.is_full_view() {
+            // The focused panel first, then the global bindings
+            sections.retain(|section| section.panel.is_none_or(|p| p == panel));
+            sections.sort_by_key(|section| section.panel.is_none());
+        }
+
+        let mut visible: Vec<VisibleSection> = sections
+            .iter()
+            .map(|section| {
+                let items = section
+                    .items
+                    .iter()
+                    // Skip items that are layout-specific and don't match current layout
+                    .filter(|item| item.layout_specific.is_none_or(|layout| layout == layout_type))
+                    .map(|item| (item.keys.to_string(), item.description.to_string()))
+                    .collect();
+                (section.title.to_string(), items)
+            })
+            .collect();
+
+        // Entries contributed by the panel follow its own bindings
+        if !entries.is_empty() {
+            let position = sections
+                .iter()
+                .rposition(|section| section.panel == Some(panel))
+                .map_or(0, |i| i + 1);
+            let items = entries
+                .iter()
+                .map(|entry| (entry.keys.clone(), entry.description.clone()))
+                .collect();
+            visible.insert(position, (format!("{panel:?} Panel (current state)"), items));
+        }
+
+        let query = self.query.to_lowercase();
+        if !query.is_empty() {
+            for (_, items) in &mut visible {
+                items.retain(|(keys, description)| {
+                    keys.to_lowercase().contains(&query)
+                        || description.to_lowercase().contains(&query)
+                });
+            }
+        }
+        visible.retain(|(_, items)| !items.is_empty());
+        visible
+    }
+
+    /// Generate help content from the visible sections
+    fn generate_help_content(
+        &self,
+        sections: &[VisibleSection],
+        dm: &DataManager,
+    ) -> Text<'static> {
         let mut lines = Vec::new();
 
-        // Add sections based on layout
-        let sections = self.get_help_sections(layout_type);
+        // Search prompt
+        if self.searching || !self.query.is_empty() {
+            let cursor = if self.searching { "█" } else { "" };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Search: ",
+                    Style::default().fg(dm.theme.warning_color).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("{}{cursor}", self.query),
+                    Style::default().fg(dm.theme.accent_color),
+                ),
+            ]));
+            lines.push(Line::from(""));
+
+            if sections.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    format!("No bindings match '{}'", self.query),
+                    Style::default().fg(dm.theme.help_text_color),
+                )));
+            }
+        }
 
-        for (i, section) in sections.iter().enumerate() {
+        for (i, (title, items)) in sections.iter().enumerate() {
             if i > 0 {
                 lines.push(Line::from("")); // Empty line between sections
             }
 
             // Section title
             lines.push(Line::from(vec![Span::styled(
-                section.title,
+                title.clone(),
                 Style::default().fg(dm.theme.warning_color).add_modifier(Modifier::BOLD),
             )]));
 
             // Section separator
             lines.push(Line::from(vec![Span::styled(
-                "─".repeat(section.title.len()),
+                "─".repeat(title.chars().count()),
                 Style::default().fg(dm.theme.unfocused_border),
             )]));
 
             // Section items
-            for item in &section.items {
-                // Skip items that are layout-specific and don't match current layout
-                if let Some(specific_layout) = item.layout_specific {
-                    if specific_layout != layout_type {
-                        continue;
-                    }
-                }
-
+            for (keys, description) in items {
                 lines.push(Line::from(vec![
                     Span::styled(
-                        format!("  {:20} ", item.keys),
+                        format!("  {keys:20} "),
                         Style::default().fg(dm.theme.accent_color).add_modifier(Modifier::BOLD),
                     ),
-                    Span::styled(item.description, Style::default().fg(dm.theme.help_text_color)),
+                    Span::styled(
+                        description.clone(),
+                        Style::default().fg(dm.theme.help_text_color),
+                    ),
                 ]));
             }
         }
 
         // Add footer with instructions
+        let key_style = Style::default().fg(dm.theme.success_color).add_modifier(Modifier::BOLD);
+        let text_style = Style::default().fg(dm.theme.help_text_color);
         lines.push(Line::from(""));
         lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled("Tab", key_style),
+            Span::styled(
+                if self.is_full_view() {
+                    " for the focused panel's bindings, "
+                } else {
+                    " for the full keymap, "
+                },
+                text_style,
+            ),
+            Span::styled("/", key_style),
+            Span::styled(" to search", text_style),
+        ]));
         lines.push(Line::from(vec![
             Span::styled("Press ", Style::default().fg(dm.theme.help_text_color)),
             Span::styled(
@@ -172,6 +300,7 @@ impl HelpOverlay {
         // Navigation & Focus section
         sections.push(HelpSection {
             title: "Navigation & Focus",
+            panel: None,
             items: vec![
                 HelpItem {
                     keys: "?",
@@ -184,8 +313,8 @@ impl HelpOverlay {
                     layout_specific: None,
                 },
                 HelpItem {
-                    keys: "F1-F4",
-                    description: "Jump to panel (Trace/Code/Display/Terminal)",
+                    keys: "F1-F5",
+                    description: "Jump to panel (Trace/Code/Display/Terminal/Events)",
                     layout_specific: None,
                 },
                 HelpItem {
@@ -193,11 +322,47 @@ impl HelpOverlay {
                     description: "Toggle mouse mode (click to focus, scroll to navigate)",
                     layout_specific: None,
                 },
+                HelpItem {
+                    keys: "m<letter> / '<letter>",
+                    description: "Mark the current snapshot / Go to a marked snapshot",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "y",
+                    description: "Copy the selected value, address, slot, or lines (V in Code)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Ctrl+O / Ctrl+I",
+                    description: "Jump back / forward through visited snapshots",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Ctrl+K",
+                    description: "Command palette (fuzzy search over all actions)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Ctrl+R",
+                    description: "Toggle raw addresses (hide well-known and ENS labels)",
+                    layout_specific: None,
+                },
                 HelpItem {
                     keys: "ESC",
                     description: "Return to Terminal panel",
                     layout_specific: None,
                 },
+                HelpItem {
+                    keys: "[keybindings]",
+                    description:
+                        "Remap keys in ~/.edb.toml (preset = \"vim\" for Ctrl+D/U scrolling)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "./.edb.toml",
+                    description: "Project settings (labels, rpc_url, ...) merged over ~/.edb.toml",
+                    layout_specific: None,
+                },
             ],
         });
 
@@ -205,6 +370,7 @@ impl HelpOverlay {
         if layout_type != LayoutType::Mobile {
             sections.push(HelpSection {
                 title: "Panel Resizing",
+                panel: None,
                 items: vec![
                     HelpItem {
                         keys: "Ctrl+Shift+←/→",
@@ -216,6 +382,16 @@ impl HelpOverlay {
                         description: "Adjust horizontal split",
                         layout_specific: None,
                     },
+                    HelpItem {
+                        keys: "Mouse drag",
+                        description: "Drag panel borders (in mouse mode)",
+                        layout_specific: None,
+                    },
+                    HelpItem {
+                        keys: "[layout]",
+                        description: "Sizes and visible panels are kept in ~/.edb.toml",
+                        layout_specific: None,
+                    },
                 ],
             });
         }
@@ -225,6 +401,7 @@ impl HelpOverlay {
             // Always show for reference
             sections.push(HelpSection {
                 title: "Code Panel",
+                panel: Some(PanelType::Code),
                 items: vec![
                     HelpItem {
                         keys: "Vim Navigation",
@@ -241,6 +418,26 @@ impl HelpOverlay {
                         description: "Toggle breakpoint at cursor",
                         layout_specific: None,
                     },
+                    HelpItem {
+                        keys: "/",
+                        description: "Incremental search (↑/↓: prev/next, Enter on empty: repeat)",
+                        layout_specific: None,
+                    },
+                    HelpItem {
+                        keys: "Ctrl-P",
+                        description: "Fuzzy file picker across all contracts in the trace",
+                        layout_specific: None,
+                    },
+                    HelpItem {
+                        keys: "H",
+                        description: "Toggle gas heatmap (line numbers colored by gas spent)",
+                        layout_specific: None,
+                    },
+                    HelpItem {
+                        keys: "d",
+                        description: "Show source and disassembly side by side",
+                        layout_specific: None,
+                    },
                     HelpItem {
                         keys: "n/N",
                         description: "Next/Previous step",
@@ -263,12 +460,12 @@ impl HelpOverlay {
                     },
                     HelpItem {
                         keys: "Space",
-                        description: "Switch to Trace panel",
+                        description: "Switch to Events panel",
                         layout_specific: Some(LayoutType::Full),
                     },
                     HelpItem {
                         keys: "Space",
-                        description: "Cycle: Trace → Code → Display",
+                        description: "Cycle: Trace → Code → Display → Events",
                         layout_specific: Some(LayoutType::Compact),
                     },
                     HelpItem {
@@ -283,6 +480,7 @@ impl HelpOverlay {
         // Trace Panel section
         sections.push(HelpSection {
             title: "Trace Panel",
+            panel: Some(PanelType::Trace),
             items: vec![
                 HelpItem {
                     keys: "Vim Navigation",
@@ -293,9 +491,31 @@ impl HelpOverlay {
                 HelpItem { keys: "v/V", description: "View code", layout_specific: None },
                 HelpItem {
                     keys: "Enter",
+                    description: "Show decoded call details",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "z",
                     description: "Toggle expand/collapse",
                     layout_specific: None,
                 },
+                HelpItem { keys: "-/+", description: "Collapse/Expand all", layout_specific: None },
+                HelpItem {
+                    keys: "S/P/R",
+                    description: "Hide static calls / Hide precompiles / Only reverted subtrees",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "a",
+                    description: "Only frames touching the selected address (:addr <address>)",
+                    layout_specific: None,
+                },
+                HelpItem { keys: "F", description: "Clear trace filters", layout_specific: None },
+                HelpItem {
+                    keys: "A",
+                    description: "Toggle call graph summary (calls and gas per caller → callee)",
+                    layout_specific: None,
+                },
                 HelpItem {
                     keys: "Space",
                     description: "Switch to Code panel",
@@ -303,7 +523,7 @@ impl HelpOverlay {
                 },
                 HelpItem {
                     keys: "Space",
-                    description: "Cycle: Trace → Code → Display",
+                    description: "Cycle: Trace → Code → Display → Events",
                     layout_specific: Some(LayoutType::Compact),
                 },
             ],
@@ -312,14 +532,20 @@ impl HelpOverlay {
         // Display Panel section
         sections.push(HelpSection {
             title: "Display Panel",
+            panel: Some(PanelType::Display),
             items: vec![
                 HelpItem { keys: "↑/↓", description: "Navigate items", layout_specific: None },
                 HelpItem {
                     keys: "←/→", description: "Horizontal scroll", layout_specific: None
                 },
+                HelpItem {
+                    keys: "1-9",
+                    description: "Jump to display tab by number",
+                    layout_specific: None,
+                },
                 HelpItem {
                     keys: "s/S",
-                    description: "Cycle display modes forward/backward",
+                    description: "Cycle display tabs forward/backward",
                     layout_specific: None,
                 },
                 HelpItem {
@@ -332,9 +558,63 @@ impl HelpOverlay {
                     description: "Toggle multi-line view (Variables/Expressions)",
                     layout_specific: None,
                 },
+                HelpItem {
+                    keys: "a/e/d",
+                    description: "Add/Edit/Delete watch expression (Expressions)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "p",
+                    description: "Pin/unpin watch expression (Expressions)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Enter/d",
+                    description: "Enable-disable/Delete breakpoint (Breakpoints)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "g",
+                    description: "Jump to next hit of breakpoint (Breakpoints)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Space",
+                    description: "Cycle: Trace → Code → Display → Events",
+                    layout_specific: Some(LayoutType::Compact),
+                },
+            ],
+        });
+
+        // Events Panel section
+        sections.push(HelpSection {
+            title: "Events Panel",
+            panel: Some(PanelType::Events),
+            items: vec![
+                HelpItem {
+                    keys: "↑/↓, j/k",
+                    description: "Navigate events",
+                    layout_specific: None,
+                },
+                HelpItem { keys: "g/G", description: "First/Last event", layout_specific: None },
+                HelpItem {
+                    keys: "Enter",
+                    description: "Go to the snapshot that emitted the event",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "a",
+                    description: "Also list events emitted after the current snapshot",
+                    layout_specific: None,
+                },
                 HelpItem {
                     keys: "Space",
-                    description: "Cycle: Trace → Code → Display",
+                    description: "Switch to Trace panel",
+                    layout_specific: Some(LayoutType::Full),
+                },
+                HelpItem {
+                    keys: "Space",
+                    description: "Cycle: Trace → Code → Display → Events",
                     layout_specific: Some(LayoutType::Compact),
                 },
             ],
@@ -343,6 +623,7 @@ impl HelpOverlay {
         // Terminal Panel section
         sections.push(HelpSection {
             title: "Terminal Panel",
+            panel: Some(PanelType::Terminal),
             items: vec![
                 HelpItem {
                     keys: "ESC",
@@ -351,7 +632,17 @@ impl HelpOverlay {
                 },
                 HelpItem {
                     keys: "↑/↓",
-                    description: "Command history (Insert mode)",
+                    description: "Command history, kept across sessions (Insert mode)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Tab",
+                    description: "Complete commands, variables and functions (Insert mode)",
+                    layout_specific: None,
+                },
+                HelpItem {
+                    keys: "Alt+Enter",
+                    description: "New line in a multi-line expression (Insert mode)",
                     layout_specific: None,
                 },
                 HelpItem {
@@ -364,6 +655,11 @@ impl HelpOverlay {
                     description: "j/k/h/l, gg/G, {/}, ↑/↓, ←/→, numeric prefixes (Vim mode)",
                     layout_specific: None,
                 },
+                HelpItem {
+                    keys: "record / replay",
+                    description: "Record a guided tour of the session, then replay or export it",
+                    layout_specific: None,
+                },
                 HelpItem { keys: "Ctrl+L", description: "Clear terminal", layout_specific: None },
                 HelpItem {
                     keys: "Ctrl+C (2x)",
@@ -376,6 +672,7 @@ impl HelpOverlay {
         // Exit Commands section
         sections.push(HelpSection {
             title: "Exit Commands",
+            panel: None,
             items: vec![
                 HelpItem { keys: "Ctrl+Q", description: "Quit application", layout_specific: None },
                 HelpItem {
@@ -455,9 +752,60 @@ impl HelpOverlay {
         self.scroll_offset = (self.scroll_offset + amount).min(max_scroll);
     }
 
-    /// Reset scroll position
-    pub fn reset_scroll(&mut self) {
+    /// Reset scroll position, view, and search
+    pub fn reset(&mut self) {
         self.scroll_offset = 0;
+        self.show_full_keymap = false;
+        self.query.clear();
+        self.searching = false;
+    }
+
+    /// Handle a key while the help is shown, returning whether the help should close
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> bool {
+        if self.searching {
+            match key.code {
+                KeyCode::Esc => {
+                    self.query.clear();
+                    self.searching = false;
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Backspace => {
+                    self.query.pop();
+                }
+                KeyCode::Char(c) => self.query.push(c),
+                _ => {}
+            }
+            self.scroll_offset = 0;
+            return false;
+        }
+
+        match key.code {
+            // ESC first clears the search
+            KeyCode::Esc if !self.query.is_empty() => {
+                self.query.clear();
+                self.scroll_offset = 0;
+            }
+            KeyCode::Char('?') | KeyCode::Esc => {
+                self.reset();
+                return true;
+            }
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.query.clear();
+                self.scroll_offset = 0;
+            }
+            KeyCode::Tab => {
+                self.show_full_keymap = !self.is_full_view();
+                self.query.clear();
+                self.scroll_offset = 0;
+            }
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_down(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_up(1),
+            KeyCode::PageDown => self.scroll_down(10),
+            KeyCode::PageUp => self.scroll_up(10),
+            _ => {}
+        }
+        false
     }
 }
 
@@ -481,3 +829,37 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(sections: &[VisibleSection]) -> Vec<&str> {
+        sections.iter().map(|(title, _)| title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_context_and_search_views() {
+        let mut help = HelpOverlay::new();
+        let entries = [HelpEntry::new("F", "Clear the active filters (reverted)")];
+
+        // The focused panel's bindings and state come first, then the global bindings
+        let sections = help.visible_sections(LayoutType::Full, PanelType::Trace, &entries);
+        assert_eq!(
+            titles(&sections),
+            [
+                "Trace Panel",
+                "Trace Panel (current state)",
+                "Navigation & Focus",
+                "Panel Resizing",
+                "Exit Commands"
+            ]
+        );
+
+        // Searching goes through the bindings of every panel
+        help.query = "heatmap".to_string();
+        let sections = help.visible_sections(LayoutType::Full, PanelType::Trace, &entries);
+        assert_eq!(titles(&sections), ["Code Panel"]);
+        assert!(sections[0].1.iter().all(|(_, description)| description.contains("heatmap")));
+    }
+}
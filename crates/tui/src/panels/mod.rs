@@ -22,10 +22,12 @@ use crate::data::DataManager;
 use crossterm::event::{KeyEvent, MouseEvent};
 use eyre::Result;
 use ratatui::{layout::Rect, Frame};
+use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
 
 /// Panel types for identification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PanelType {
     /// Trace panel showing execution trace
     Trace,
@@ -33,6 +35,8 @@ pub enum PanelType {
     Code,
     /// Display panel showing variables, stack, memory, etc.
     Display,
+    /// Events panel listing the events emitted so far
+    Events,
     /// Terminal panel for command input/output
     Terminal,
 }
@@ -50,6 +54,38 @@ pub enum EventResponse {
     Exit,
 }
 
+/// Text copied from a panel with the yank key (`y`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Yank {
+    /// What was copied, e.g. "value of balance"
+    pub description: String,
+    /// Copied text
+    pub text: String,
+}
+
+impl Yank {
+    /// Create a new yank
+    pub fn new(description: impl Into<String>, text: impl Into<String>) -> Self {
+        Self { description: description.into(), text: text.into() }
+    }
+}
+
+/// Help entry contributed by a panel for its current state, e.g. an active filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    /// Keys or command
+    pub keys: String,
+    /// What they do in the current state
+    pub description: String,
+}
+
+impl HelpEntry {
+    /// Create a new help entry
+    pub fn new(keys: impl Into<String>, description: impl Into<String>) -> Self {
+        Self { keys: keys.into(), description: description.into() }
+    }
+}
+
 /// Trait for UI panels
 pub trait PanelTr: Debug + Send {
     /// Render the panel content
@@ -77,6 +113,25 @@ pub trait PanelTr: Debug + Send {
         Ok(EventResponse::NotHandled)
     }
 
+    /// Whether the panel is capturing text input
+    ///
+    /// While this returns true, global shortcuts are disabled and every key is
+    /// forwarded to the panel.
+    fn is_capturing_input(&self) -> bool {
+        false
+    }
+
+    /// Get the text to copy to the clipboard for the current selection, if any
+    fn yank(&mut self, data_manager: &mut DataManager) -> Option<Yank> {
+        let _ = data_manager;
+        None
+    }
+
+    /// Get help entries for the current state of the panel, shown in the help overlay
+    fn help_entries(&self) -> Vec<HelpEntry> {
+        Vec::new()
+    }
+
     /// Called when this panel gains focus
     fn on_focus(&mut self) {}
 
@@ -97,15 +152,20 @@ pub trait PanelTr: Debug + Send {
 
 // Re-export all panel implementations
 pub mod code;
+mod command;
 pub mod display;
+pub mod events;
 pub mod help;
+pub mod palette;
 pub mod terminal;
 pub mod trace;
 mod utils;
 
 pub use code::CodePanel;
 pub use display::DisplayPanel;
+pub use events::EventsPanel;
 pub use help::HelpOverlay;
+pub use palette::{CommandPalette, PaletteAction, PaletteResponse};
 pub use terminal::TerminalPanel;
 pub use trace::TracePanel;
 
@@ -113,6 +173,7 @@ pub use trace::TracePanel;
 pub enum Panel {
     Code(CodePanel),
     Display(DisplayPanel),
+    Events(EventsPanel),
     Terminal(TerminalPanel),
     Trace(TracePanel),
 }
@@ -122,6 +183,7 @@ impl PanelTr for Panel {
         match self {
             Self::Code(panel) => panel.render(frame, area, data_manager),
             Self::Display(panel) => panel.render(frame, area, data_manager),
+            Self::Events(panel) => panel.render(frame, area, data_manager),
             Self::Terminal(panel) => panel.render(frame, area, data_manager),
             Self::Trace(panel) => panel.render(frame, area, data_manager),
         }
@@ -135,6 +197,7 @@ impl PanelTr for Panel {
         match self {
             Self::Code(panel) => panel.handle_key_event(event, data_manager),
             Self::Display(panel) => panel.handle_key_event(event, data_manager),
+            Self::Events(panel) => panel.handle_key_event(event, data_manager),
             Self::Terminal(panel) => panel.handle_key_event(event, data_manager),
             Self::Trace(panel) => panel.handle_key_event(event, data_manager),
         }
@@ -148,15 +211,47 @@ impl PanelTr for Panel {
         match self {
             Self::Code(panel) => panel.handle_mouse_event(event, data_manager),
             Self::Display(panel) => panel.handle_mouse_event(event, data_manager),
+            Self::Events(panel) => panel.handle_mouse_event(event, data_manager),
             Self::Terminal(panel) => panel.handle_mouse_event(event, data_manager),
             Self::Trace(panel) => panel.handle_mouse_event(event, data_manager),
         }
     }
 
+    fn is_capturing_input(&self) -> bool {
+        match self {
+            Self::Code(panel) => panel.is_capturing_input(),
+            Self::Display(panel) => panel.is_capturing_input(),
+            Self::Events(panel) => panel.is_capturing_input(),
+            Self::Terminal(panel) => panel.is_capturing_input(),
+            Self::Trace(panel) => panel.is_capturing_input(),
+        }
+    }
+
+    fn yank(&mut self, data_manager: &mut DataManager) -> Option<Yank> {
+        match self {
+            Self::Code(panel) => panel.yank(data_manager),
+            Self::Display(panel) => panel.yank(data_manager),
+            Self::Events(panel) => panel.yank(data_manager),
+            Self::Terminal(panel) => panel.yank(data_manager),
+            Self::Trace(panel) => panel.yank(data_manager),
+        }
+    }
+
+    fn help_entries(&self) -> Vec<HelpEntry> {
+        match self {
+            Self::Code(panel) => panel.help_entries(),
+            Self::Display(panel) => panel.help_entries(),
+            Self::Events(panel) => panel.help_entries(),
+            Self::Terminal(panel) => panel.help_entries(),
+            Self::Trace(panel) => panel.help_entries(),
+        }
+    }
+
     fn on_focus(&mut self) {
         match self {
             Self::Code(panel) => panel.on_focus(),
             Self::Display(panel) => panel.on_focus(),
+            Self::Events(panel) => panel.on_focus(),
             Self::Terminal(panel) => panel.on_focus(),
             Self::Trace(panel) => panel.on_focus(),
         }
@@ -166,6 +261,7 @@ impl PanelTr for Panel {
         match self {
             Self::Code(panel) => panel.on_blur(),
             Self::Display(panel) => panel.on_blur(),
+            Self::Events(panel) => panel.on_blur(),
             Self::Terminal(panel) => panel.on_blur(),
             Self::Trace(panel) => panel.on_blur(),
         }
@@ -175,6 +271,7 @@ impl PanelTr for Panel {
         match self {
             Self::Code(_) => PanelType::Code,
             Self::Display(_) => PanelType::Display,
+            Self::Events(_) => PanelType::Events,
             Self::Terminal(_) => PanelType::Terminal,
             Self::Trace(_) => PanelType::Trace,
         }
@@ -184,6 +281,7 @@ impl PanelTr for Panel {
         match self {
             Self::Code(_) => "Code".to_string(),
             Self::Display(_) => "Display".to_string(),
+            Self::Events(_) => "Events".to_string(),
             Self::Terminal(_) => "Terminal".to_string(),
             Self::Trace(_) => "Trace".to_string(),
         }
@@ -193,6 +291,7 @@ impl PanelTr for Panel {
         match self {
             Self::Code(panel) => panel.as_any_mut(),
             Self::Display(panel) => panel.as_any_mut(),
+            Self::Events(panel) => panel.as_any_mut(),
             Self::Terminal(panel) => panel.as_any_mut(),
             Self::Trace(panel) => panel.as_any_mut(),
         }
@@ -0,0 +1,370 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Command palette overlay (`Ctrl-K`)
+//!
+//! The palette lists the actions of every panel with a fuzzy search over their names,
+//! showing the key that triggers each action so that shortcuts can be discovered. Actions
+//! either press the built-in key of a panel, run a terminal command, or put a command in
+//! the terminal input for the user to complete.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::{utils, PanelType};
+use crate::data::{keymap::KeySpec, DataManager};
+
+/// What running a palette entry does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteAction {
+    /// Press a built-in key, after focusing the given panel
+    Key(Option<PanelType>, KeyCode, KeyModifiers),
+    /// Run a terminal command
+    Command(&'static str),
+    /// Put a command in the terminal input for the user to complete
+    Prefill(&'static str),
+}
+
+/// Outcome of a key pressed while the palette is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteResponse {
+    /// Keep the palette open
+    Pending,
+    /// Close the palette without running anything
+    Close,
+    /// Close the palette and run the action
+    Run(PaletteAction),
+}
+
+/// An action listed in the palette
+#[derive(Debug, Clone, Copy)]
+struct PaletteEntry {
+    /// Group of the action, e.g. `Code`
+    category: &'static str,
+    /// Name of the action
+    title: &'static str,
+    /// What the action does
+    action: PaletteAction,
+}
+
+const fn key(
+    category: &'static str,
+    title: &'static str,
+    panel: Option<PanelType>,
+    code: KeyCode,
+) -> PaletteEntry {
+    PaletteEntry { category, title, action: PaletteAction::Key(panel, code, KeyModifiers::NONE) }
+}
+
+const fn ctrl(
+    category: &'static str,
+    title: &'static str,
+    panel: Option<PanelType>,
+    c: char,
+) -> PaletteEntry {
+    PaletteEntry {
+        category,
+        title,
+        action: PaletteAction::Key(panel, KeyCode::Char(c), KeyModifiers::CONTROL),
+    }
+}
+
+const fn command(category: &'static str, title: &'static str, cmd: &'static str) -> PaletteEntry {
+    PaletteEntry { category, title, action: PaletteAction::Command(cmd) }
+}
+
+const fn prefill(category: &'static str, title: &'static str, cmd: &'static str) -> PaletteEntry {
+    PaletteEntry { category, title, action: PaletteAction::Prefill(cmd) }
+}
+
+const CODE: Option<PanelType> = Some(PanelType::Code);
+const TRACE: Option<PanelType> = Some(PanelType::Trace);
+const EVENTS: Option<PanelType> = Some(PanelType::Events);
+
+/// Every action offered by the palette, in display order
+const ENTRIES: &[PaletteEntry] = &[
+    // Panels
+    key("Panels", "Focus Trace panel", None, KeyCode::F(1)),
+    key("Panels", "Focus Code panel", None, KeyCode::F(2)),
+    key("Panels", "Focus Display panel", None, KeyCode::F(3)),
+    key("Panels", "Focus Terminal panel", None, KeyCode::F(4)),
+    key("Panels", "Focus Events panel", None, KeyCode::F(5)),
+    key("Panels", "Cycle through panels", None, KeyCode::Tab),
+    key("Panels", "Show keyboard shortcuts", None, KeyCode::Char('?')),
+    // Navigation
+    command("Navigation", "Step to next snapshot", "next"),
+    command("Navigation", "Step to previous snapshot", "prev"),
+    command("Navigation", "Step forward into callees", "step"),
+    command("Navigation", "Step backward into callees", "reverse"),
+    command("Navigation", "Step to next call", "call"),
+    command("Navigation", "Step back from call", "rcall"),
+    command("Navigation", "Run to next breakpoint", "run"),
+    command("Navigation", "Run backward to previous breakpoint", "runback"),
+    command("Navigation", "Go to where the transaction reverted", "revert"),
+    ctrl("Navigation", "Jump back", None, 'o'),
+    ctrl("Navigation", "Jump forward", None, 'i'),
+    command("Navigation", "Show jump list", "jumps"),
+    prefill("Marks", "Mark current snapshot", "mark "),
+    prefill("Marks", "Go to mark", "mark goto "),
+    command("Marks", "List marks", "marks"),
+    // Clipboard
+    key("Clipboard", "Copy selection to clipboard", None, KeyCode::Char('y')),
+    key("Clipboard", "Select source lines to copy", CODE, KeyCode::Char('V')),
+    // Code panel
+    ctrl("Code", "Go to file", CODE, 'p'),
+    key("Code", "Search in file", CODE, KeyCode::Char('/')),
+    key("Code", "Toggle file selector", CODE, KeyCode::Char('f')),
+    key("Code", "Toggle gas heatmap", CODE, KeyCode::Char('H')),
+    key("Code", "Toggle source and disassembly side by side", CODE, KeyCode::Char('d')),
+    // Trace panel
+    key("Trace", "Collapse all calls", TRACE, KeyCode::Char('-')),
+    key("Trace", "Expand all calls", TRACE, KeyCode::Char('+')),
+    key("Trace", "Hide static calls", TRACE, KeyCode::Char('S')),
+    key("Trace", "Hide precompiles", TRACE, KeyCode::Char('P')),
+    key("Trace", "Show only reverted subtrees", TRACE, KeyCode::Char('R')),
+    key("Trace", "Clear trace filters", TRACE, KeyCode::Char('F')),
+    key("Trace", "Toggle call graph summary", TRACE, KeyCode::Char('A')),
+    // Events panel
+    key("Events", "Toggle events emitted after the current snapshot", EVENTS, KeyCode::Char('a')),
+    // Breakpoints
+    key("Breakpoints", "Toggle breakpoint at cursor", CODE, KeyCode::Char('b')),
+    prefill("Breakpoints", "Add breakpoint", "break add "),
+    command("Breakpoints", "List breakpoints", "break list"),
+    command("Breakpoints", "Enable all breakpoints", "break enable"),
+    command("Breakpoints", "Disable all breakpoints", "break disable"),
+    command("Breakpoints", "Clear all breakpoints", "break clear"),
+    // Watches
+    prefill("Watches", "Add watch expression", "watch add $"),
+    command("Watches", "List watch expressions", "watch list"),
+    command("Watches", "Clear watch expressions", "watch clear"),
+    prefill("Watches", "Evaluate expression", "$"),
+    // Labels
+    ctrl("Labels", "Toggle raw addresses", None, 'r'),
+    prefill("Labels", "Import address labels", "label import "),
+    prefill("Labels", "Label an address", "label add "),
+    command("Labels", "List custom labels", "label list"),
+    // Sessions
+    prefill("Session", "Record session", "record start "),
+    prefill("Session", "Note last recorded step", "record note "),
+    command("Session", "Stop recording", "record stop"),
+    prefill("Session", "Replay session", "replay "),
+    command("Session", "Replay next step", "replay next"),
+    command("Session", "Replay previous step", "replay prev"),
+    prefill("Session", "Export session as markdown", "export "),
+    // Settings
+    prefill("Settings", "Switch theme", "theme "),
+    command("Settings", "List themes", "theme"),
+    key("Settings", "Toggle mouse mode", None, KeyCode::Char('\\')),
+    // Terminal
+    command("Terminal", "Clear terminal", "clear"),
+    command("Terminal", "Show command history", "history"),
+    ctrl("Application", "Quit", None, 'q'),
+];
+
+/// Command palette state
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    /// Query typed so far
+    query: String,
+    /// Indices into [`ENTRIES`] matching the query, best match first
+    matches: Vec<usize>,
+    /// Selected index into `matches`
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// Create a palette listing every action
+    pub fn new() -> Self {
+        let mut palette = Self::default();
+        palette.update_matches();
+        palette
+    }
+
+    /// Recompute the matches for the current query
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, usize)> = ENTRIES
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                let text = format!("{}: {}", entry.category, entry.title);
+                utils::fuzzy_score(&self.query, &text).map(|score| (score, idx))
+            })
+            .collect();
+        if !self.query.is_empty() {
+            scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        }
+        self.matches = scored.into_iter().map(|(_, idx)| idx).collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    /// Handle a key while the palette is open
+    pub fn handle_key_event(&mut self, event: KeyEvent) -> PaletteResponse {
+        let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+        match event.code {
+            KeyCode::Esc => return PaletteResponse::Close,
+            KeyCode::Char('c' | 'k') if ctrl => return PaletteResponse::Close,
+            KeyCode::Char('n') if ctrl => self.select_next(),
+            KeyCode::Char('p') if ctrl => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.select_next(),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+                self.update_matches();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.selected = 0;
+                self.update_matches();
+            }
+            KeyCode::Enter => {
+                return match self.matches.get(self.selected) {
+                    Some(idx) => PaletteResponse::Run(ENTRIES[*idx].action),
+                    None => PaletteResponse::Close,
+                };
+            }
+            _ => {}
+        }
+        PaletteResponse::Pending
+    }
+
+    /// Move the selection down
+    fn select_next(&mut self) {
+        self.selected = (self.selected + 1).min(self.matches.len().saturating_sub(1));
+    }
+
+    /// Keybinding hint of an action, taking the user's keybindings into account
+    fn hint(action: PaletteAction, current_panel: PanelType, dm: &DataManager) -> String {
+        match action {
+            PaletteAction::Key(panel, code, modifiers) => {
+                let keys = dm
+                    .keymap
+                    .keys_for(panel.unwrap_or(current_panel), KeySpec::new(code, modifiers));
+                keys.iter().map(ToString::to_string).collect::<Vec<_>>().join(" / ")
+            }
+            PaletteAction::Command(cmd) => format!(":{cmd}"),
+            PaletteAction::Prefill(cmd) => format!(":{}…", cmd.trim_end()),
+        }
+    }
+
+    /// Render the palette as a popup near the top of the screen
+    pub fn render(&self, frame: &mut Frame<'_>, current_panel: PanelType, dm: &DataManager) {
+        let area = frame.area();
+        let width = (area.width * 3 / 5).max(40).min(area.width);
+        let height = (area.height * 3 / 5).max(8).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 4,
+            width,
+            height,
+        };
+
+        let block = Block::default()
+            .title(format!("⌘ Command Palette ({}/{})", self.matches.len(), ENTRIES.len()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(dm.theme.accent_color));
+        let inner = block.inner(popup);
+        frame.render_widget(Clear, popup);
+        frame.render_widget(block, popup);
+        if inner.height < 2 {
+            return;
+        }
+
+        let input = Line::from(vec![
+            Span::styled("> ", Style::default().fg(dm.theme.accent_color)),
+            Span::raw(self.query.clone()),
+            Span::styled("█", Style::default().fg(dm.theme.accent_color)),
+        ]);
+        frame.render_widget(Paragraph::new(input), Rect { height: 1, ..inner });
+
+        let list_height = (inner.height - 1) as usize;
+        let scroll = (self.selected + 1).saturating_sub(list_height);
+        let items: Vec<ListItem<'_>> = self
+            .matches
+            .iter()
+            .enumerate()
+            .skip(scroll)
+            .take(list_height)
+            .map(|(idx, entry_idx)| {
+                let entry = &ENTRIES[*entry_idx];
+                let category = format!("{}: ", entry.category);
+                let hint = Self::hint(entry.action, current_panel, dm);
+                let used = category.chars().count() + entry.title.chars().count();
+                let padding = (inner.width as usize).saturating_sub(used + hint.chars().count());
+
+                let style = if idx == self.selected {
+                    Style::default()
+                        .bg(dm.theme.selection_bg)
+                        .fg(dm.theme.selection_fg)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(category, Style::default().fg(dm.theme.comment_color)),
+                    Span::raw(entry.title),
+                    Span::raw(" ".repeat(padding.max(1))),
+                    Span::styled(hint, Style::default().fg(dm.theme.info_color)),
+                ]))
+                .style(style)
+            })
+            .collect();
+        frame.render_widget(
+            List::new(items),
+            Rect { y: inner.y + 1, height: inner.height - 1, ..inner },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(palette: &mut CommandPalette, code: KeyCode) -> PaletteResponse {
+        palette.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_palette_search() {
+        let mut palette = CommandPalette::new();
+        assert_eq!(palette.matches.len(), ENTRIES.len());
+
+        for c in "heatmap".chars() {
+            press(&mut palette, KeyCode::Char(c));
+        }
+        assert_eq!(
+            press(&mut palette, KeyCode::Enter),
+            PaletteResponse::Run(PaletteAction::Key(
+                Some(PanelType::Code),
+                KeyCode::Char('H'),
+                KeyModifiers::NONE
+            ))
+        );
+
+        for c in "zzzz".chars() {
+            press(&mut palette, KeyCode::Char(c));
+        }
+        assert!(palette.matches.is_empty());
+        assert_eq!(press(&mut palette, KeyCode::Enter), PaletteResponse::Close);
+        assert_eq!(press(&mut palette, KeyCode::Esc), PaletteResponse::Close);
+    }
+}
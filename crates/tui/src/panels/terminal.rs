@@ -18,7 +18,10 @@
 //!
 //! This panel provides a command-line interface for debugging commands.
 
-use super::{EventResponse, PanelTr, PanelType};
+use super::command::{parse_command, TerminalCommand};
+use super::{EventResponse, HelpEntry, PanelTr, PanelType, Yank};
+use crate::data::history::CommandHistory;
+use crate::data::session::{SessionRecorder, SessionRecording, SessionReplay, SessionStep};
 use crate::data::DataManager;
 use crate::panels::utils;
 use crate::ui::borders::BorderPresets;
@@ -41,7 +44,8 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Paragraph},
     Frame,
 };
-use std::collections::VecDeque;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Instant;
 use tracing::debug;
@@ -49,8 +53,40 @@ use tracing::debug;
 /// Maximum number of terminal lines to keep in history
 const MAX_TERMINAL_LINES: usize = 1000;
 
-/// Maximum number of command history entries
-const MAX_COMMAND_HISTORY: usize = 100;
+/// Maximum number of completion candidates listed when a completion is ambiguous
+const MAX_COMPLETION_CANDIDATES: usize = 20;
+
+/// Terminal commands offered by Tab completion
+const COMMAND_NAMES: &[&str] = &[
+    "abi", "break", "call", "clear", "exit", "export", "goto", "help", "history", "info", "jumps",
+    "label", "mark", "marks", "next", "prev", "quit", "rcall", "record", "replay", "reverse",
+    "revert", "run", "runback", "step", "theme", "watch",
+];
+
+/// Commands that are not recorded in sessions, as they do not take part in the debugging
+const UNRECORDED_COMMANDS: &[&str] = &[
+    "clear", "cls", "exit", "export", "h", "help", "history", "q", "quit", "record", "replay",
+    "theme",
+];
+
+/// Default file of session recordings
+const DEFAULT_SESSION_FILE: &str = "edb-session.json";
+
+/// Expression builtins offered by Tab completion after `$`
+const EXPRESSION_BUILTINS: &[&str] = &[
+    "block.number",
+    "block.timestamp",
+    "edb_calldata",
+    "edb_help",
+    "edb_memory",
+    "edb_sload",
+    "edb_stack",
+    "edb_tsload",
+    "keccak256",
+    "msg.sender",
+    "msg.value",
+    "tx.origin",
+];
 
 /// Type of terminal line
 #[derive(Debug, Clone, PartialEq)]
@@ -388,19 +424,34 @@ impl PendingCommand {
             Self::ShowAddress(id) => {
                 let info =
                     dm.execution.get_snapshot_info(*id).ok_or(eyre!("No snapshot info found"))?;
-                Ok(format!(
-                    "Address:          {}\nBytecode Address: {}",
-                    info.target_address, info.bytecode_address
-                ))
+                let (target_address, bytecode_address) =
+                    (info.target_address, info.bytecode_address);
+                let mut with_label = |address: Address| match dm.resolver.label_name(address) {
+                    Some(name) => format!("{address} ({name})"),
+                    None => address.to_string(),
+                };
+                let target = with_label(target_address);
+                let bytecode = with_label(bytecode_address);
+                Ok(format!("Address:          {target}\nBytecode Address: {bytecode}"))
             }
             Self::EvalExpr(_, expr) => {
                 let value =
                     dm.resolver.eval_on_snapshot(id, expr).ok_or(eyre!("No value found"))?.clone();
 
+                // An expression naming a variable renders like the variable itself
+                let hint = dm
+                    .execution
+                    .get_snapshot_info(id)
+                    .and_then(|info| info.variable_hint(expr.trim()))
+                    .cloned();
                 let ctx = SolValueFormatterContext::new().with_ty(true).multi_line(true);
 
                 value
-                    .map(|v| format!("{} = {}", expr, dm.resolver.resolve_sol_value(&v, Some(ctx))))
+                    .map(|v| {
+                        let value =
+                            dm.resolver.resolve_hinted_sol_value(&v, hint.as_ref(), Some(ctx));
+                        format!("{expr} = {value}")
+                    })
                     .map_err(|e| eyre!(e))
             }
             Self::BreakpointHits(bp) => Ok(format!("Breakpoint added: {bp}")),
@@ -420,8 +471,8 @@ pub struct TerminalPanel {
     input_buffer: String,
     /// Cursor position in input buffer
     cursor_position: usize,
-    /// Command history for ↑/↓ navigation in INSERT mode
-    command_history: VecDeque<String>,
+    /// Command history for ↑/↓ navigation in INSERT mode, persisted across sessions
+    command_history: CommandHistory,
     /// Current position in command history (None = no history browsing)
     history_position: Option<usize>,
     /// Scroll position in terminal history (0 = bottom/latest)
@@ -452,6 +503,10 @@ pub struct TerminalPanel {
     spinner: Spinner,
     /// Syntax highlighter for commands and output
     syntax_highlighter: SyntaxHighlighter,
+    /// Recorder of the session, while recording
+    recorder: Option<SessionRecorder>,
+    /// Replay of a recorded session, while replaying
+    replay: Option<SessionReplay>,
 }
 
 impl TerminalPanel {
@@ -462,7 +517,7 @@ impl TerminalPanel {
             mode: TerminalMode::Insert,
             input_buffer: String::new(),
             cursor_position: 0,
-            command_history: VecDeque::new(),
+            command_history: CommandHistory::load(),
             history_position: None,
             scroll_offset: 0,
             content_height: 0,
@@ -478,6 +533,8 @@ impl TerminalPanel {
             pending_command: None,
             spinner: Spinner::new(Some(SpinnerStyles::SQUARE), None),
             syntax_highlighter: SyntaxHighlighter::new(),
+            recorder: None,
+            replay: None,
         };
 
         // Add welcome message with fancy styling
@@ -585,29 +642,57 @@ impl TerminalPanel {
         self.add_line(&format!("> {command}"), LineType::Command);
     }
 
+    /// Run a command as if it was typed in the terminal (e.g., from the command palette)
+    pub fn run_command(&mut self, command: &str, dm: &mut DataManager) -> Result<EventResponse> {
+        self.execute_command(command, dm)
+    }
+
+    /// Put a command in the input line for the user to complete, switching to Insert mode
+    pub fn prefill_input(&mut self, command: &str) {
+        self.mode = TerminalMode::Insert;
+        self.input_buffer = command.to_string();
+        self.cursor_position = self.input_buffer.len();
+    }
+
     /// Execute a command
     fn execute_command(&mut self, command: &str, dm: &mut DataManager) -> Result<EventResponse> {
         debug!("Executing command: {}", command);
 
         // Add command to history
-        if !command.trim().is_empty()
-            && self.command_history.back().is_none_or(|last| last != command)
-        {
-            if self.command_history.len() >= MAX_COMMAND_HISTORY {
-                self.command_history.pop_front();
-            }
-            self.command_history.push_back(command.to_string());
-        }
+        self.command_history.push(command);
 
         // Empty command will be treated as the previous command
         if command.trim().is_empty() {
-            if let Some(cmd) = self.command_history.back().cloned() {
+            if let Some(cmd) = self.command_history.last().map(str::to_string) {
                 return self.execute_command(cmd.as_str(), dm);
             }
         }
 
-        // Add command to terminal history
-        self.add_command(command);
+        // Add command to terminal history, one line per line of a multi-line expression
+        let mut lines = command.lines();
+        self.add_command(lines.next().unwrap_or_default());
+        for line in lines {
+            self.add_line(&format!("  {line}"), LineType::Command);
+        }
+
+        // Lines of a multi-line expression are only separated by whitespace
+        let command = command.lines().map(str::trim).collect::<Vec<_>>().join(" ");
+
+        // Shorthands are handled as the canonical command they stand for
+        let command = match parse_command(&command) {
+            TerminalCommand::Command(cmd) => cmd,
+            TerminalCommand::Expression(expr) => format!("${expr}"),
+            TerminalCommand::SourceBreakpoint { file, line, condition } => {
+                match source_breakpoint_command(&file, line, condition.as_deref(), dm) {
+                    Ok(cmd) => cmd,
+                    Err(e) => {
+                        self.add_error(&e.to_string());
+                        return Ok(EventResponse::Handled);
+                    }
+                }
+            }
+        };
+        let command = command.as_str();
 
         // Handle built-in commands
         match command.trim() {
@@ -639,6 +724,18 @@ impl TerminalPanel {
             cmd if cmd.starts_with("watch") => {
                 self.handle_watch_command(cmd[5..].trim(), dm);
             }
+            cmd if cmd.starts_with("label") => {
+                self.handle_label_command(cmd[5..].trim(), dm);
+            }
+            cmd if cmd.starts_with("record") => {
+                self.handle_record_command(cmd[6..].trim(), dm);
+            }
+            cmd if cmd.starts_with("replay") => {
+                self.handle_replay_command(cmd[6..].trim(), dm);
+            }
+            cmd if cmd.starts_with("export") => {
+                self.handle_export_command(cmd[6..].trim(), dm);
+            }
             cmd if cmd.starts_with('$') => {
                 // Solidity expression evaluation
                 let id = dm.execution.get_current_snapshot();
@@ -656,6 +753,19 @@ impl TerminalPanel {
             }
         }
 
+        // Record the command, whose output is added once it finishes if still pending
+        let recorded = command
+            .split_whitespace()
+            .next()
+            .is_some_and(|name| !UNRECORDED_COMMANDS.contains(&name));
+        if let Some(recorder) = self.recorder.as_mut().filter(|_| recorded) {
+            recorder.record(
+                command.trim(),
+                dm.execution.get_current_snapshot(),
+                self.pending_command.is_some(),
+            );
+        }
+
         Ok(EventResponse::Handled)
     }
 
@@ -770,6 +880,9 @@ impl TerminalPanel {
                     self.add_output("  break add [@<loc>] [if $<expr>] - Add breakpoint");
                     self.add_output("        <loc> := <addr>:<path>:<line> (source)");
                     self.add_output("               | <addr>:<pc>          (opcode)");
+                    self.add_output(
+                        "  break <file>:<line> [if $<expr>] - Break at a line of a source file",
+                    );
                     self.add_output("  break remove <id>               - Remove breakpoint");
                     self.add_output("  break enable [id]               - Enable breakpoint(s)");
                     self.add_output("  break disable [id]              - Disable breakpoint(s)");
@@ -1082,6 +1195,17 @@ impl TerminalPanel {
                 self.spinner
                     .start_loading(&format!("Fetching transient storage at slot {slot}..."));
             }
+            "mark" => self.handle_mark_command(&parts[1..], dm),
+            "marks" => self.show_marks(dm),
+            "jumps" => self.show_jumps(dm),
+            "bridge" => self.show_bridge_messages(dm),
+            "revert" => match dm.execution.goto_revert() {
+                Ok(entry_id) => self.add_output(&format!(
+                    "Going to where the transaction reverted (trace entry {})",
+                    entry_id + 1
+                )),
+                Err(e) => self.add_error(&e.to_string()),
+            },
             _ => {
                 self.add_output(&format!("Unknown command: {}", parts[0]));
                 self.add_output("Type 'help' for available commands");
@@ -1104,6 +1228,12 @@ impl TerminalPanel {
         self.add_output("  (VIM mode) gg/G    - Go to top/bottom");
         self.add_output("  (VIM mode) i       - Return to INSERT mode");
         self.add_output("");
+        self.add_output("⌨️ Input Editing:");
+        self.add_output("  ↑/↓                - Browse command history (kept across sessions)");
+        self.add_output("  Tab                - Complete commands, variables and functions");
+        self.add_output("  Alt+Enter          - Continue the input on a new line");
+        self.add_output("  (unclosed bracket) - Enter continues a $ expression on a new line");
+        self.add_output("");
         self.add_output("🚀 Debug Commands:");
         self.add_output("  next, n             - Step to next snapshot");
         self.add_output("  prev, p             - Step to previous snapshot");
@@ -1113,6 +1243,7 @@ impl TerminalPanel {
         self.add_output("  rcall, rc           - Step back from function call");
         self.add_output("  run, r              - Run until next breakpoint or end");
         self.add_output("  runback, rb         - Run backward until previous breakpoint or start");
+        self.add_output("  revert              - Go to where the transaction reverted");
         self.add_output("");
         self.add_output("🔍 Inspection:");
         self.add_output("  address                 - Show current address");
@@ -1122,14 +1253,30 @@ impl TerminalPanel {
         self.add_output("  calldata                - Show calldata");
         self.add_output("  sload <slot>            - Show storage at slot");
         self.add_output("  tsload <slot>           - Show transient storage at slot");
+        self.add_output(
+            "  bridge                  - Show bridge messages and correlated transactions",
+        );
         self.add_output("");
         self.add_output("👁️ Watcher:");
         self.add_output("  watch add $<expr>   - Add watch expression");
+        self.add_output("  watch <expr>        - Same as watch add $<expr>");
         self.add_output("  watch remove <id>   - Remove watch expression");
+        self.add_output("🔖 Marks & Jumps:");
+        self.add_output("  mark <letter>       - Mark the current snapshot (m<letter> in panels)");
+        self.add_output("  mark goto <letter>  - Go to a marked snapshot ('<letter> in panels)");
+        self.add_output("  marks               - List all marks");
+        self.add_output("  jumps               - Show the jump list (Ctrl-O back, Ctrl-I forward)");
+        self.add_output("🏷️ Labels:");
+        self.add_output("  label import <file>      - Import address labels (CSV or JSON)");
+        self.add_output("  label add <addr> <label> - Label an address");
+        self.add_output("  label remove <addr>      - Remove an address label");
+        self.add_output("  label list               - List all custom labels");
+        self.add_output("  label clear              - Clear all custom labels");
         self.add_output("🔴 Breakpoints:");
         self.add_output("  break add [@<loc>] [if $<expr>] - Add breakpoint");
         self.add_output("        <loc> := <addr>:<path>:<line> (source)");
         self.add_output("               | <addr>:<pc>          (opcode)");
+        self.add_output("  break <file>:<line> [if $<expr>] - Break at a line of a source file");
         self.add_output("  break remove <id>               - Remove breakpoint");
         self.add_output("  break enable [id]               - Enable breakpoint (all if no id)");
         self.add_output("  break disable [id]              - Disable breakpoint (all if no id)");
@@ -1142,8 +1289,19 @@ impl TerminalPanel {
         self.add_output("         - Both (location AND condition)");
         self.add_output("         - Neither (invalid)");
         self.add_output("");
-        self.add_output("💻 Solidity expressions (prefix with $):");
+        self.add_output("🎬 Sessions:");
+        self.add_output("  record start [file]  - Record navigation and evaluations to a file");
+        self.add_output("  record note <text>   - Attach a note to the last recorded step");
+        self.add_output("  record stop          - Stop recording");
+        self.add_output("  replay <file>        - Replay a recorded session");
+        self.add_output("  replay next/prev     - Go to the next/previous recorded step");
+        self.add_output("  replay stop          - Stop replaying");
+        self.add_output("  export <file> [out]  - Export a session as a markdown walkthrough");
+        self.add_output("  export trace <file>  - Export the trace as JSON to a new file");
+        self.add_output("");
+        self.add_output("💻 Solidity expressions:");
         self.add_output("  $<expr>          - Evaluate expression");
+        self.add_output("  <expr>           - Same, unless it starts with a command name");
         self.add_output("  $edb_help()      - Show more help on expressions");
         self.add_output("");
         self.add_output("⚙️  Other:");
@@ -1165,7 +1323,7 @@ impl TerminalPanel {
                 .command_history
                 .iter()
                 .enumerate()
-                .map(|(i, cmd)| format!("  {}: {}", i + 1, cmd))
+                .map(|(i, cmd)| format!("  {}: {}", i + 1, cmd.replace('\n', " ")))
                 .collect();
             for line in history_lines {
                 self.add_output(&line);
@@ -1185,8 +1343,9 @@ impl TerminalPanel {
         }
         self.add_output("");
         self.add_output("Usage:");
-        self.add_output("  theme <name>    Switch to theme");
+        self.add_output("  theme <name>    Switch to theme (or a preset: dark, light, solarized, high-contrast)");
         self.add_output("  theme           List available themes");
+        self.add_output("Custom themes are defined under [theme.custom.<name>] in ~/.edb.toml");
     }
 
     /// Handle watch command
@@ -1196,6 +1355,8 @@ impl TerminalPanel {
             self.add_output("Usage:");
             self.add_output("  watch add $<expr>   - Add watch expression");
             self.add_output("  watch remove <id>  - Remove watch expression");
+            self.add_output("  watch edit <id> $<expr> - Replace watch expression");
+            self.add_output("  watch pin <id>     - Pin/unpin watch expression");
             self.add_output("  watch list          - List all watch expressions");
             self.add_output("  watch clear         - Clear all watch expressions");
             return;
@@ -1240,6 +1401,47 @@ impl TerminalPanel {
                     }
                 }
             }
+            "edit" => {
+                if parts.len() < 3 {
+                    self.add_error("Usage: watch edit <id> $<expr>");
+                    return;
+                }
+                let Ok(id) = parts[1].parse::<usize>() else {
+                    self.add_error("Invalid watch id");
+                    return;
+                };
+                let expr = args[4..].trim()[parts[1].len()..].trim();
+                if !expr.starts_with('$') {
+                    self.add_error("Watch expression must start with '$'");
+                    return;
+                }
+                match dm.watcher.edit_expression(id, expr[1..].trim().to_string()) {
+                    Some(old) => {
+                        self.add_output(&format!("Updated watch #{id}: ${old} -> {expr}"));
+                    }
+                    None => {
+                        self.add_error(&format!(
+                            "No watch found with id {id}, or {expr} is already being watched"
+                        ));
+                    }
+                }
+            }
+            "pin" => {
+                if parts.len() != 2 {
+                    self.add_error("Usage: watch pin <id>");
+                    return;
+                }
+                match parts[1].parse::<usize>() {
+                    Ok(id) => match dm.watcher.toggle_pin(id) {
+                        Some(true) => self.add_output(&format!("Pinned watch #{id}")),
+                        Some(false) => self.add_output(&format!("Unpinned watch #{id}")),
+                        None => self.add_error(&format!("No watch found with id {id}")),
+                    },
+                    Err(_) => {
+                        self.add_error("Invalid watch id");
+                    }
+                }
+            }
             "list" => {
                 if parts.len() != 1 {
                     self.add_error("Usage: watch list");
@@ -1250,7 +1452,8 @@ impl TerminalPanel {
                 } else {
                     self.add_output("Current watch expressions:");
                     for (id, expr) in dm.watcher.list_expressions() {
-                        self.add_output(&format!("  #{id}: {expr}"));
+                        let pin = if dm.watcher.is_pinned(expr) { " (pinned)" } else { "" };
+                        self.add_output(&format!("  #{id}: {expr}{pin}"));
                     }
                 }
             }
@@ -1267,12 +1470,479 @@ impl TerminalPanel {
                 self.add_output("Usage:");
                 self.add_output("  watch add $<expr>    - Add watch expression");
                 self.add_output("  watch remove <id>   - Remove watch expression");
+                self.add_output("  watch edit <id> $<expr> - Replace watch expression");
+                self.add_output("  watch pin <id>      - Pin/unpin watch expression");
                 self.add_output("  watch list          - List all watch expressions");
                 self.add_output("  watch clear         - Clear all watch expressions");
             }
         }
     }
 
+    /// Show usage of the mark command
+    fn show_mark_usage(&mut self) {
+        self.add_output("Usage:");
+        self.add_output("  mark <letter>        - Mark the current snapshot (like m<letter>)");
+        self.add_output("  mark goto <letter>   - Go to a marked snapshot (like '<letter>)");
+        self.add_output("  mark remove <letter> - Remove a mark");
+        self.add_output("  mark clear           - Remove all marks");
+        self.add_output("  marks                - List all marks");
+    }
+
+    /// Handle mark command
+    fn handle_mark_command(&mut self, args: &[&str], dm: &mut DataManager) {
+        let letter = |arg: Option<&&str>| {
+            let mut chars = arg?.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(c),
+                _ => None,
+            }
+        };
+
+        match args {
+            ["clear"] => {
+                dm.execution.clear_marks();
+                self.add_system("All marks cleared");
+            }
+            ["goto", rest @ ..] => match letter(rest.first()) {
+                Some(mark) => match dm.execution.goto_mark(mark) {
+                    Ok(id) => {
+                        self.pending_command = Some(PendingCommand::Goto(id, None));
+                        self.spinner.start_loading(&format!("Going to mark '{mark}'..."));
+                    }
+                    Err(e) => self.add_error(&e.to_string()),
+                },
+                None => self.show_mark_usage(),
+            },
+            ["remove", rest @ ..] => match letter(rest.first()) {
+                Some(mark) => match dm.execution.remove_mark(mark) {
+                    Some(id) => self.add_system(&format!("Removed mark '{mark}' (snapshot {id})")),
+                    None => self.add_error(&format!("Mark '{mark}' is not set")),
+                },
+                None => self.show_mark_usage(),
+            },
+            [arg] => match letter(Some(arg)) {
+                Some(mark) => match dm.execution.set_mark(mark) {
+                    Ok(id) => self.add_system(&format!("Mark '{mark}' set at snapshot {id}")),
+                    Err(e) => self.add_error(&e.to_string()),
+                },
+                None => self.show_mark_usage(),
+            },
+            _ => self.show_mark_usage(),
+        }
+    }
+
+    /// Show all marks
+    fn show_marks(&mut self, dm: &mut DataManager) {
+        let current = dm.execution.get_current_snapshot();
+        let marks: Vec<(char, usize)> = dm.execution.list_marks().collect();
+        if marks.is_empty() {
+            self.add_output("No marks set (press m<letter> in any panel but the terminal)");
+            return;
+        }
+
+        self.add_output("Marks:");
+        for (mark, id) in marks {
+            let marker = if id == current { "→" } else { " " };
+            self.add_output(&format!("{marker} '{mark}  snapshot {id}"));
+        }
+    }
+
+    /// Show the jump list
+    fn show_jumps(&mut self, dm: &mut DataManager) {
+        let jumps: Vec<(usize, bool)> = dm.execution.jumps().iter().collect();
+        if jumps.is_empty() {
+            self.add_output("No jumps yet");
+            return;
+        }
+
+        self.add_output("Jump list (oldest first, Ctrl-O/Ctrl-I to move):");
+        for (id, visited) in jumps {
+            let marker = if visited { "→" } else { " " };
+            self.add_output(&format!("{marker} snapshot {id}"));
+        }
+    }
+
+    /// Show the messages sent through canonical bridges and their correlated transactions
+    fn show_bridge_messages(&mut self, dm: &mut DataManager) {
+        let messages = dm.execution.get_bridge_messages().to_vec();
+        if messages.is_empty() {
+            self.add_output("No messages sent through a known canonical bridge");
+            return;
+        }
+
+        self.add_output("Bridge messages:");
+        for message in messages {
+            let snapshot =
+                message.snapshot_id.map_or_else(String::new, |id| format!(" (snapshot {id})"));
+            self.add_output(&format!(
+                "  ⇄ {} from {:#x}{}",
+                message.summary(),
+                message.sender,
+                snapshot
+            ));
+            match message.correlated_tx_hash {
+                Some(tx_hash) => self.add_output(&format!(
+                    "    correlated transaction {tx_hash:#x} on chain {}",
+                    message.correlated_chain_id()
+                )),
+                None => self.add_output(&format!(
+                    "    message hash {:#x}, correlated transaction not found",
+                    message.message_hash
+                )),
+            }
+        }
+        self.add_output("Exit to open a correlated transaction as a second session");
+    }
+
+    /// Show usage of the label command
+    fn show_label_usage(&mut self) {
+        self.add_output("Usage:");
+        self.add_output("  label import <file>      - Import `address,label` CSV or JSON labels");
+        self.add_output("  label add <addr> <label> - Label an address");
+        self.add_output("  label remove <addr>      - Remove an address label");
+        self.add_output("  label list               - List all custom labels");
+        self.add_output("  label clear              - Clear all custom labels");
+    }
+
+    /// Handle label command
+    fn handle_label_command(&mut self, args: &str, dm: &mut DataManager) {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        if parts.is_empty() {
+            self.show_label_usage();
+            return;
+        }
+
+        match parts[0] {
+            "import" => {
+                if parts.len() < 2 {
+                    self.add_error("Usage: label import <file>");
+                    return;
+                }
+                let path = expand_home(args["import".len()..].trim());
+                match dm.resolver.custom_labels_mut().import(&path) {
+                    Ok(count) => self.add_output(&format!(
+                        "Imported {count} labels from {} ({} labels in total)",
+                        path.display(),
+                        dm.resolver.custom_labels().count()
+                    )),
+                    Err(e) => self.add_error(&format!("Failed to import labels: {e}")),
+                }
+            }
+            "add" => {
+                if parts.len() < 3 {
+                    self.add_error("Usage: label add <addr> <label>");
+                    return;
+                }
+                let Ok(address) = parts[1].parse::<Address>() else {
+                    self.add_error(&format!("Invalid address: {}", parts[1]));
+                    return;
+                };
+                let label = args[3..].trim()[parts[1].len()..].trim().to_string();
+                match dm.resolver.custom_labels_mut().insert(address, label.clone()) {
+                    Some(old) => self.add_output(&format!("Relabeled {address}: {old} -> {label}")),
+                    None => self.add_output(&format!("Labeled {address}: {label}")),
+                }
+            }
+            "remove" => {
+                if parts.len() != 2 {
+                    self.add_error("Usage: label remove <addr>");
+                    return;
+                }
+                let Ok(address) = parts[1].parse::<Address>() else {
+                    self.add_error(&format!("Invalid address: {}", parts[1]));
+                    return;
+                };
+                match dm.resolver.custom_labels_mut().remove(address) {
+                    Some(label) => self.add_output(&format!("Removed label of {address}: {label}")),
+                    None => self.add_error(&format!("No custom label for {address}")),
+                }
+            }
+            "list" => {
+                let config_lines: Vec<String> = dm
+                    .resolver
+                    .custom_labels()
+                    .config_labels()
+                    .map(|(address, label)| format!("  {address}: {label}"))
+                    .collect();
+                if !config_lines.is_empty() {
+                    self.add_output("Labels from .edb.toml:");
+                    for line in config_lines {
+                        self.add_output(&line);
+                    }
+                }
+
+                if dm.resolver.custom_labels().count() == 0 {
+                    self.add_output("No custom labels set");
+                } else {
+                    let lines: Vec<String> = dm
+                        .resolver
+                        .custom_labels()
+                        .iter()
+                        .map(|(address, label)| format!("  {address}: {label}"))
+                        .collect();
+                    self.add_output("Custom labels:");
+                    for line in lines {
+                        self.add_output(&line);
+                    }
+                }
+            }
+            "clear" => {
+                dm.resolver.custom_labels_mut().clear();
+                self.add_output("Cleared all custom labels");
+            }
+            _ => {
+                self.add_error("Unknown label command");
+                self.show_label_usage();
+            }
+        }
+    }
+
+    /// Show record command usage
+    fn show_record_usage(&mut self) {
+        self.add_output("Usage:");
+        self.add_output("  record start [file] - Record the session (default: edb-session.json)");
+        self.add_output("  record note <text>  - Attach a note to the last recorded step");
+        self.add_output("  record stop         - Stop recording");
+    }
+
+    /// Handle record command
+    fn handle_record_command(&mut self, args: &str, dm: &mut DataManager) {
+        let (action, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let rest = rest.trim();
+
+        match action {
+            "start" => {
+                if let Some(recorder) = &self.recorder {
+                    let path = recorder.path().display().to_string();
+                    self.add_error(&format!("Already recording to {path}, 'record stop' first"));
+                    return;
+                }
+                let path = expand_home(if rest.is_empty() { DEFAULT_SESSION_FILE } else { rest });
+                match SessionRecorder::start(path, dm.execution.get_current_snapshot()) {
+                    Ok(recorder) => {
+                        self.add_system(&format!(
+                            "Recording the session to {}",
+                            recorder.path().display()
+                        ));
+                        self.add_output("Attach notes to steps with 'record note <text>'");
+                        self.recorder = Some(recorder);
+                    }
+                    Err(e) => self.add_error(&format!("Failed to start recording: {e}")),
+                }
+            }
+            "note" => {
+                if rest.is_empty() {
+                    self.add_error("Usage: record note <text>");
+                    return;
+                }
+                match self.recorder.as_mut().map(|recorder| recorder.note(rest)) {
+                    Some(true) => self.add_output(&format!("Noted: {rest}")),
+                    Some(false) => self.add_error("No step recorded yet"),
+                    None => self.add_error("Not recording, 'record start' first"),
+                }
+            }
+            "stop" => match self.recorder.take() {
+                Some(recorder) => {
+                    let path = recorder.path().display().to_string();
+                    self.add_system(&format!(
+                        "Recorded {} steps to {path}",
+                        recorder.recording().steps.len()
+                    ));
+                    self.add_output(&format!(
+                        "Replay it with 'replay {path}' or export it with 'export {path}'"
+                    ));
+                }
+                None => self.add_error("Not recording"),
+            },
+            "" => {
+                if let Some(recorder) = &self.recorder {
+                    let status = format!(
+                        "Recording to {} ({} steps)",
+                        recorder.path().display(),
+                        recorder.recording().steps.len()
+                    );
+                    self.add_output(&status);
+                }
+                self.show_record_usage();
+            }
+            _ => self.show_record_usage(),
+        }
+    }
+
+    /// Show replay command usage
+    fn show_replay_usage(&mut self) {
+        self.add_output("Usage:");
+        self.add_output("  replay <file> - Replay a recorded session");
+        self.add_output("  replay next   - Replay the next step");
+        self.add_output("  replay prev   - Go back to the previous step");
+        self.add_output("  replay stop   - Stop replaying");
+    }
+
+    /// Handle replay command
+    fn handle_replay_command(&mut self, args: &str, dm: &mut DataManager) {
+        let navigates = !matches!(args, "" | "stop");
+        if navigates && dm.execution.get_execution_status().is_waiting() {
+            self.add_error("The backend is waiting for an execution request");
+            return;
+        }
+
+        match args {
+            "" => {
+                if let Some(replay) = &self.replay {
+                    let status = format!(
+                        "Replaying step {}/{}",
+                        replay.position(),
+                        replay.recording().steps.len()
+                    );
+                    self.add_output(&status);
+                }
+                self.show_replay_usage();
+            }
+            "next" | "n" | "prev" | "p" => {
+                let Some(replay) = &mut self.replay else {
+                    self.add_error("Not replaying, 'replay <file>' first");
+                    return;
+                };
+                let forward = matches!(args, "next" | "n");
+                let step =
+                    if forward { replay.next_step().cloned() } else { replay.prev_step().cloned() };
+                let (position, total) = (replay.position(), replay.recording().steps.len());
+                match step {
+                    Some(step) => self.replay_step(&step, position, total, dm),
+                    None if forward => {
+                        self.add_system("End of the session, 'replay stop' to leave it")
+                    }
+                    None => self.add_error("Already at the first step"),
+                }
+            }
+            "stop" => match self.replay.take() {
+                Some(_) => self.add_system("Stopped replaying"),
+                None => self.add_error("Not replaying"),
+            },
+            file => {
+                let path = expand_home(file);
+                let recording = match SessionRecording::load(&path) {
+                    Ok(recording) => recording,
+                    Err(e) => {
+                        self.add_error(&format!("Failed to load session: {e}"));
+                        return;
+                    }
+                };
+
+                self.add_system(&format!(
+                    "Replaying {} steps from {} (recorded with EDB v{})",
+                    recording.steps.len(),
+                    path.display(),
+                    recording.version
+                ));
+                self.add_output("Type 'replay next' to go through the steps");
+
+                let start = recording.start_snapshot;
+                self.replay = Some(SessionReplay::new(recording));
+                self.goto_snapshot(start, dm);
+            }
+        }
+    }
+
+    /// Replay a step: go to its snapshot, show its note and output, and evaluate it again
+    /// if it is an expression
+    fn replay_step(
+        &mut self,
+        step: &SessionStep,
+        position: usize,
+        total: usize,
+        dm: &mut DataManager,
+    ) {
+        self.add_system(&format!("Step {position}/{total}: {}", step.command));
+        if let Some(note) = &step.note {
+            for line in note.lines() {
+                self.add_output(&format!("📝 {line}"));
+            }
+        }
+
+        if step.is_evaluation() {
+            if let Err(e) = dm.execution.goto(step.snapshot, false) {
+                self.add_error(&format!("Failed to go to snapshot {}: {e}", step.snapshot));
+                return;
+            }
+            let expr = step.command[1..].trim().to_string();
+            self.pending_command = Some(PendingCommand::EvalExpr(step.snapshot, expr));
+            self.spinner.start_loading("Fetching evaluation result...");
+        } else {
+            for line in &step.output {
+                self.add_output(line);
+            }
+            self.goto_snapshot(step.snapshot, dm);
+        }
+    }
+
+    /// Go to a snapshot, without stopping at breakpoints
+    fn goto_snapshot(&mut self, id: usize, dm: &mut DataManager) {
+        let id = dm.execution.get_sanitized_id(id);
+        match dm.execution.goto(id, false) {
+            Ok(()) => {
+                self.pending_command = Some(PendingCommand::Goto(id, None));
+                self.spinner.start_loading(&format!("Going to snapshot {id}..."));
+            }
+            Err(e) => self.add_error(&format!("Failed to go to snapshot {id}: {e}")),
+        }
+    }
+
+    /// Handle export command, writing a recorded session as a markdown walkthrough, or
+    /// the trace as JSON when the file is not a recorded session
+    fn handle_export_command(&mut self, args: &str, dm: &mut DataManager) {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        match parts.as_slice() {
+            ["trace", file] => return self.export_trace(&expand_home(file), dm),
+            [_] | [_, _] => {}
+            _ => {
+                self.add_error("Usage: export <session.json> [walkthrough.md]");
+                self.add_error("       export trace <trace.json>");
+                return;
+            }
+        }
+
+        let path = expand_home(parts[0]);
+        let out = parts.get(1).map_or_else(|| path.with_extension("md"), |out| expand_home(out));
+        let title = path.file_stem().map_or("session".into(), |stem| stem.to_string_lossy());
+
+        let result = SessionRecording::load(&path).and_then(|recording| {
+            let markdown = recording.to_markdown(&format!("EDB walkthrough: {title}"));
+            std::fs::write(&out, markdown)?;
+            Ok(recording.steps.len())
+        });
+        match result {
+            Ok(count) => self.add_output(&format!("Exported {count} steps to {}", out.display())),
+            Err(e) => self.add_error(&format!("Failed to export session: {e}")),
+        }
+    }
+
+    /// Write the trace as JSON to a new file, never replacing an existing one
+    fn export_trace(&mut self, path: &Path, dm: &mut DataManager) {
+        let trace = dm.execution.get_trace();
+        let result =
+            serde_json::to_string_pretty(trace).map_err(eyre::Report::from).and_then(|json| {
+                let mut file =
+                    std::fs::OpenOptions::new().write(true).create_new(true).open(path).map_err(
+                        |e| match e.kind() {
+                            std::io::ErrorKind::AlreadyExists => {
+                                eyre!("{} already exists", path.display())
+                            }
+                            _ => e.into(),
+                        },
+                    )?;
+                Ok(file.write_all(json.as_bytes())?)
+            });
+        match result {
+            Ok(()) => self.add_output(&format!(
+                "Exported {} trace entries to {}",
+                trace.len(),
+                path.display()
+            )),
+            Err(e) => self.add_error(&format!("Failed to export trace: {e}")),
+        }
+    }
+
     /// Handle theme switching command
     fn handle_theme_command(&mut self, theme_name: &str, dm: &mut DataManager) {
         let theme_name = theme_name.to_lowercase();
@@ -1315,6 +1985,113 @@ impl TerminalPanel {
         }
     }
 
+    /// Get the line and column of the cursor in a multi-line input
+    fn cursor_line_col(&self) -> (usize, usize) {
+        let before = &self.input_buffer[..self.cursor_position];
+        let line = before.matches('\n').count();
+        let col = before.len() - before.rfind('\n').map_or(0, |i| i + 1);
+        (line, col)
+    }
+
+    /// Move the cursor to the previous or next line of a multi-line input
+    ///
+    /// Returns false if there is no such line.
+    fn move_cursor_line(&mut self, up: bool) -> bool {
+        let (line, col) = self.cursor_line_col();
+        let Some(target) = (if up { line.checked_sub(1) } else { Some(line + 1) }) else {
+            return false;
+        };
+
+        let mut start = 0;
+        for (i, text) in self.input_buffer.split('\n').enumerate() {
+            if i == target {
+                self.cursor_position = start + col.min(text.len());
+                return true;
+            }
+            start += text.len() + 1;
+        }
+        false
+    }
+
+    /// Record the navigation done from the other panels, while recording a session
+    pub fn observe_navigation(&mut self, dm: &mut DataManager) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+        // Snapshots reached by terminal commands are recorded with the commands
+        if self.pending_command.is_some() || dm.execution.get_execution_status().is_waiting() {
+            return;
+        }
+        recorder.observe(dm.execution.get_current_snapshot());
+    }
+
+    /// Whether keys are typed into the input line (INSERT mode)
+    pub fn is_insert_mode(&self) -> bool {
+        self.mode == TerminalMode::Insert
+    }
+
+    /// Whether Tab should complete the input instead of switching panels
+    pub fn wants_tab(&self) -> bool {
+        self.mode == TerminalMode::Insert
+            && self.pending_command.is_none()
+            && !self.input_buffer.trim().is_empty()
+    }
+
+    /// Complete the word before the cursor
+    ///
+    /// The first word completes to a command name, while words of a `$` expression complete
+    /// to the variables and functions in the current snapshot's scope. An ambiguous word is
+    /// completed to the longest common prefix, and the candidates are listed.
+    fn complete(&mut self, dm: &mut DataManager) {
+        let before = &self.input_buffer[..self.cursor_position];
+        let word_start = before
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .map_or(0, |i| i + 1);
+        let word = before[word_start..].to_string();
+
+        let is_expression = self.input_buffer.trim_start().starts_with('$');
+        let candidates = if is_expression {
+            scope_names(dm)
+        } else if before[..word_start].trim().is_empty() {
+            COMMAND_NAMES.iter().map(ToString::to_string).collect()
+        } else {
+            return;
+        };
+
+        let mut matches: Vec<String> =
+            candidates.into_iter().filter(|name| name.starts_with(&word)).collect();
+        matches.sort();
+        matches.dedup();
+
+        let completion = match matches.as_slice() {
+            [] => return,
+            [name] if is_expression => name[word.len()..].to_string(),
+            [name] => format!("{} ", &name[word.len()..]),
+            _ => utils::common_prefix(&matches)[word.len()..].to_string(),
+        };
+
+        if completion.is_empty() {
+            // Nothing more to complete, list the candidates instead
+            let mut listing = matches
+                .iter()
+                .take(MAX_COMPLETION_CANDIDATES)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join("  ");
+            if matches.len() > MAX_COMPLETION_CANDIDATES {
+                listing.push_str(&format!(
+                    "  ... ({} more)",
+                    matches.len() - MAX_COMPLETION_CANDIDATES
+                ));
+            }
+            self.add_output(&listing);
+        } else {
+            self.input_buffer.insert_str(self.cursor_position, &completion);
+            self.cursor_position += completion.len();
+            self.history_position = None;
+        }
+    }
+
     /// Navigate command history
     fn history_up(&mut self) {
         if self.command_history.is_empty() {
@@ -1334,7 +2111,7 @@ impl TerminalPanel {
 
         if let Some(pos) = self.history_position {
             if let Some(cmd) = self.command_history.get(pos) {
-                self.input_buffer = cmd.clone();
+                self.input_buffer = cmd.to_string();
                 self.cursor_position = self.input_buffer.len();
             }
         }
@@ -1345,7 +2122,7 @@ impl TerminalPanel {
             Some(pos) if pos < self.command_history.len() - 1 => {
                 self.history_position = Some(pos + 1);
                 if let Some(cmd) = self.command_history.get(pos + 1) {
-                    self.input_buffer = cmd.clone();
+                    self.input_buffer = cmd.to_string();
                     self.cursor_position = self.input_buffer.len();
                 }
             }
@@ -1415,6 +2192,17 @@ impl TerminalPanel {
         dm: &mut DataManager,
     ) -> Result<EventResponse> {
         match event.code {
+            // Alt+Enter / Shift+Enter, or Enter within an unclosed bracket, continues the input
+            KeyCode::Enter
+                if event.modifiers.intersects(KeyModifiers::ALT | KeyModifiers::SHIFT)
+                    || (self.input_buffer.trim_start().starts_with('$')
+                        && utils::has_unclosed_bracket(&self.input_buffer)) =>
+            {
+                self.input_buffer.insert(self.cursor_position, '\n');
+                self.cursor_position += 1;
+                self.history_position = None;
+                Ok(EventResponse::Handled)
+            }
             KeyCode::Enter => {
                 let command = self.input_buffer.clone();
                 self.input_buffer.clear();
@@ -1447,19 +2235,30 @@ impl TerminalPanel {
                 Ok(EventResponse::Handled)
             }
             KeyCode::Up => {
-                self.history_up();
+                // Move within a multi-line input before browsing history
+                if !self.move_cursor_line(true) {
+                    self.history_up();
+                }
                 Ok(EventResponse::Handled)
             }
             KeyCode::Down => {
-                self.history_down();
+                if !self.move_cursor_line(false) {
+                    self.history_down();
+                }
                 Ok(EventResponse::Handled)
             }
             KeyCode::Home => {
-                self.cursor_position = 0;
+                let before = &self.input_buffer[..self.cursor_position];
+                self.cursor_position = before.rfind('\n').map_or(0, |i| i + 1);
                 Ok(EventResponse::Handled)
             }
             KeyCode::End => {
-                self.cursor_position = self.input_buffer.len();
+                let after = &self.input_buffer[self.cursor_position..];
+                self.cursor_position += after.find('\n').unwrap_or(after.len());
+                Ok(EventResponse::Handled)
+            }
+            KeyCode::Tab => {
+                self.complete(dm);
                 Ok(EventResponse::Handled)
             }
             KeyCode::Char(c) => {
@@ -1860,7 +2659,8 @@ impl TerminalPanel {
         if self.pending_command.as_ref().is_some_and(|cmd| !cmd.is_pending(dm)) {
             self.spinner.finish_loading();
             let pending_command = self.pending_command.take().unwrap();
-            match pending_command.output_finished(dm) {
+            let output = pending_command.output_finished(dm);
+            match &output {
                 Ok(output) => {
                     for line in output.lines() {
                         self.add_output(line);
@@ -1873,6 +2673,10 @@ impl TerminalPanel {
                     }
                 }
             }
+            if let Some(recorder) = &mut self.recorder {
+                let output = output.unwrap_or_else(|e| e.to_string());
+                recorder.finish_step(dm.execution.get_current_snapshot(), &output);
+            }
         }
 
         // Start with all terminal history
@@ -1887,15 +2691,24 @@ impl TerminalPanel {
                 line_type: LineType::System,
             });
         } else {
-            // Add current input line
+            // Add current input lines, continuation lines of a multi-line input aligned
+            // under the first one
             let prompt = format!(
-                "{} edb{} {}",
+                "{} edb{} ",
                 if self.connected { Icons::CONNECTED } else { Icons::DISCONNECTED },
                 Icons::ARROW_RIGHT,
-                self.input_buffer
             );
-            all_content.push(TerminalLine { content: prompt, line_type: LineType::Command });
+            let continuation = format!("{:>width$}", "... ", width = prompt.chars().count());
+            for (i, line) in self.input_buffer.split('\n').enumerate() {
+                let prefix = if i == 0 { &prompt } else { &continuation };
+                all_content.push(TerminalLine {
+                    content: format!("{prefix}{line}"),
+                    line_type: LineType::Command,
+                });
+            }
         }
+        let input_lines = self.input_buffer.split('\n').count();
+        let (cursor_line, cursor_col) = self.cursor_line_col();
 
         // Calculate visible area (leave space for status and help text if needed)
         let status_help_height = if self.focused && area.height > 10 { 2 } else { 0 };
@@ -1940,18 +2753,22 @@ impl TerminalPanel {
                 };
 
                 // Create the line content with syntax highlighting
-                let styled_line = if terminal_line.line_type == LineType::Command
-                    && display_row == all_content.len().saturating_sub(1) - start_idx  // Last line (input line)
-                    && self.focused
-                    && self.mode == TerminalMode::Insert
-                {
-                    // This is the input line - apply block cursor overlay
+                // Index of the input line being rendered, if any
+                let input_line =
+                    (start_idx + display_row).checked_sub(all_content.len() - input_lines);
+
+                let styled_line = if let Some(input_line) = input_line.filter(|_| {
+                    terminal_line.line_type == LineType::Command
+                        && self.focused
+                        && self.mode == TerminalMode::Insert
+                }) {
+                    // This is an input line - apply block cursor overlay
                     let content = &terminal_line.content;
                     let mut spans = Vec::new();
 
                     // Find where the cursor should be in the displayed prompt
-                    // The prompt format is: "{icon} edb{arrow} {input}"
-                    // We need to find where the input starts
+                    // The prompt format is: "{icon} edb{arrow} {input}", and continuation
+                    // lines are prefixed with "... " of the same width
                     let prompt_prefix = if self.connected {
                         format!("{} edb{} ", Icons::CONNECTED, Icons::ARROW_RIGHT)
                     } else {
@@ -1961,17 +2778,20 @@ impl TerminalPanel {
 
                     // Convert content to chars for proper indexing
                     let chars: Vec<char> = content.chars().collect();
-                    let cursor_pos_in_line = prefix_len + self.cursor_position;
+                    let cursor_pos_in_line =
+                        (input_line == cursor_line).then_some(prefix_len + cursor_col);
 
                     // Build the line with cursor overlay
                     for (i, ch) in chars.iter().enumerate() {
-                        if i == cursor_pos_in_line {
+                        if Some(i) == cursor_pos_in_line {
                             // This is where the cursor should be - show as block
                             spans.push(Span::styled(
                                 ch.to_string(),
                                 Style::default().bg(dm.theme.cursor_color).fg(dm.theme.panel_bg), // Invert colors for block cursor
                             ));
-                        } else if i == chars.len() - 1 && cursor_pos_in_line >= chars.len() {
+                        } else if i == chars.len() - 1
+                            && cursor_pos_in_line.is_some_and(|pos| pos >= chars.len())
+                        {
                             // Cursor at end of line - add the character normally then add block
                             spans.push(Span::styled(ch.to_string(), base_style));
                             spans.push(Span::styled(
@@ -2140,6 +2960,77 @@ impl TerminalPanel {
     }
 }
 
+/// Expand a leading `~/` of a path to the home directory
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map_or(path.into(), |home| home.join(rest)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Resolve a breakpoint at a line of a source file into a `break add` command.
+///
+/// The contract is the first one in the trace, starting with the current one, whose
+/// source code includes the file.
+fn source_breakpoint_command(
+    file: &str,
+    line: usize,
+    condition: Option<&str>,
+    dm: &mut DataManager,
+) -> Result<String> {
+    let id = dm.execution.get_current_snapshot();
+    let mut addresses: Vec<Address> =
+        dm.execution.get_snapshot_info(id).map(|info| info.bytecode_address).into_iter().collect();
+    for entry in dm.execution.get_trace().iter() {
+        if !addresses.contains(&entry.code_address) {
+            addresses.push(entry.code_address);
+        }
+    }
+
+    let mut loading = false;
+    for address in addresses {
+        match dm.execution.get_code_by_bytecode_address(address) {
+            Some(Code::Source(info)) => {
+                if let Some(path) = info.sources.keys().find(|path| path.ends_with(file)) {
+                    let condition = condition.map(|c| format!(" if {c}")).unwrap_or_default();
+                    return Ok(format!(
+                        "break add @{address}:{}:{line}{condition}",
+                        path.display()
+                    ));
+                }
+            }
+            Some(Code::Opcode(_)) => {}
+            None => loading = true,
+        }
+    }
+
+    if loading {
+        bail!("Source code is still being fetched, try again in a moment");
+    }
+    bail!("No contract in the trace has a source file matching {file}")
+}
+
+/// Get the names in the current snapshot's scope: variables, callable functions and builtins
+fn scope_names(dm: &mut DataManager) -> Vec<String> {
+    let mut names: Vec<String> = EXPRESSION_BUILTINS.iter().map(ToString::to_string).collect();
+
+    let id = dm.execution.get_current_snapshot();
+    let Some(info) = dm.execution.get_snapshot_info(id) else {
+        return names;
+    };
+    let bytecode_address = info.bytecode_address;
+    if let SnapshotInfoDetail::Hook(detail) = info.detail() {
+        names.extend(detail.locals.keys().cloned());
+        names.extend(detail.state_variables.keys().cloned());
+    }
+
+    if let Some(abi_list) = dm.resolver.get_callable_abi_list(bytecode_address) {
+        names.extend(abi_list.iter().flat_map(|abi| abi.entries.iter().map(|e| e.name.clone())));
+    }
+
+    names
+}
+
 impl PanelTr for TerminalPanel {
     fn panel_type(&self) -> PanelType {
         PanelType::Terminal
@@ -2254,6 +3145,44 @@ impl PanelTr for TerminalPanel {
         }
     }
 
+    fn yank(&mut self, _data_manager: &mut DataManager) -> Option<Yank> {
+        // Copy the line under the VIM cursor
+        if self.mode != TerminalMode::Vim {
+            return None;
+        }
+
+        let line = self.lines.get(self.vim_cursor_line.checked_sub(1)?)?;
+        let text = match line.line_type {
+            LineType::Command => line.content.strip_prefix("> ").unwrap_or(&line.content),
+            LineType::System => line.content.strip_prefix("⚡ ").unwrap_or(&line.content),
+            _ => &line.content,
+        };
+        Some(Yank::new(format!("line {}", self.vim_cursor_line), text.trim_end()))
+    }
+
+    fn help_entries(&self) -> Vec<HelpEntry> {
+        let mut entries = Vec::new();
+        if let Some(recorder) = &self.recorder {
+            entries.push(HelpEntry::new(
+                "record note <text>",
+                format!("Annotate the last step recorded to {}", recorder.path().display()),
+            ));
+            entries.push(HelpEntry::new("record stop", "Stop recording the session"));
+        }
+        if let Some(replay) = &self.replay {
+            entries.push(HelpEntry::new(
+                "replay next/prev",
+                format!(
+                    "Replay the next/previous step ({}/{} replayed)",
+                    replay.position(),
+                    replay.recording().steps.len()
+                ),
+            ));
+            entries.push(HelpEntry::new("replay stop", "Stop replaying"));
+        }
+        entries
+    }
+
     fn handle_mouse_event(
         &mut self,
         event: crossterm::event::MouseEvent,
@@ -17,29 +17,34 @@
 //! Trace panel for displaying execution trace
 //!
 //! This panel shows the call trace and allows navigation through trace entries.
+//! Large traces can be narrowed with filters (static calls, precompiles, frames
+//! touching an address, reverted subtrees) and folded with collapse/expand-all.
+//! Rows show decoded calls with argument previews; Enter opens the full details.
+//! The call graph mode (`A`) aggregates the trace into caller → callee edges with
+//! call counts and gas, for a structural overview of complex transactions.
 
-use super::{EventResponse, PanelTr, PanelType};
+use super::{utils, EventResponse, HelpEntry, PanelTr, PanelType, Yank};
 use crate::data::DataManager;
 use crate::ui::borders::BorderPresets;
 use crate::ui::status::StatusBar;
 use crate::ui::syntax::{SyntaxHighlighter, SyntaxType};
 use alloy_dyn_abi::DynSolValue;
-use alloy_primitives::{hex, Bytes};
+use alloy_primitives::{hex, Address};
 use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use edb_common::types::{CallResult, CallType, Trace, TraceEntry};
+use edb_common::types::{CallResult, CallType, FindingSeverity, Trace, TraceEntry};
 use eyre::{bail, Result};
 use ratatui::{
     layout::Rect,
-    style::Style,
+    style::{Color, Style},
     text::{Line, Span},
-    widgets::{List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
     Frame,
 };
 use revm::{
     context::CreateScheme,
     interpreter::{CallScheme, InstructionResult},
 };
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 use tracing::debug;
 
@@ -54,10 +59,185 @@ enum TraceLineType {
     Return(usize), // trace entry id
 }
 
+/// Maximum number of characters of a decoded call shown on a trace row
+const CALL_PREVIEW_CHARS: usize = 96;
+
+/// Shorten a decoded call to a preview, keeping the closing parenthesis
+fn preview_call(call: &str, max_chars: usize) -> String {
+    if call.chars().count() <= max_chars {
+        return call.to_string();
+    }
+
+    let truncated: String = call.chars().take(max_chars.saturating_sub(2)).collect();
+    if call.ends_with(')') {
+        format!("{truncated}…)")
+    } else {
+        format!("{truncated}…")
+    }
+}
+
+/// Filters narrowing down which trace entries are displayed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct TraceFilter {
+    /// Hide STATICCALL frames and their subtrees
+    hide_static_calls: bool,
+    /// Hide calls into precompiles
+    hide_precompiles: bool,
+    /// Only show frames whose caller, target or code address is this address
+    address: Option<Address>,
+    /// Only show subtrees rooted at a reverted frame
+    only_reverted: bool,
+}
+
+impl TraceFilter {
+    /// Whether any filter is enabled
+    fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Short description of the enabled filters, for the panel title
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.hide_static_calls {
+            parts.push("no static".to_string());
+        }
+        if self.hide_precompiles {
+            parts.push("no precompiles".to_string());
+        }
+        if let Some(address) = self.address {
+            parts.push(format!("addr {address}"));
+        }
+        if self.only_reverted {
+            parts.push("reverted".to_string());
+        }
+        parts.join(", ")
+    }
+
+    /// Compute the ids of the entries hidden by this filter
+    ///
+    /// Parents always precede their children in the trace, so a single pass
+    /// is enough to propagate decisions down each subtree.
+    fn hidden_entries(&self, trace: &Trace) -> HashSet<usize> {
+        let mut hidden = HashSet::new();
+
+        // Structural filters hide whole subtrees
+        for entry in trace.iter() {
+            let parent_hidden = entry.parent_id.is_some_and(|pid| hidden.contains(&pid));
+            if parent_hidden
+                || (self.hide_static_calls
+                    && matches!(entry.call_type, CallType::Call(CallScheme::StaticCall)))
+                || (self.hide_precompiles && is_precompile(entry.code_address))
+            {
+                hidden.insert(entry.id);
+            }
+        }
+
+        if self.address.is_none() && !self.only_reverted {
+            return hidden;
+        }
+
+        // Selection filters keep matching frames together with their ancestors
+        let mut reverted = HashSet::new();
+        let mut kept = HashSet::new();
+        for entry in trace.iter() {
+            let in_reverted_subtree = entry.parent_id.is_some_and(|pid| reverted.contains(&pid))
+                || matches!(entry.result, Some(ref r) if !matches!(r, CallResult::Success { .. }));
+            if in_reverted_subtree {
+                reverted.insert(entry.id);
+            }
+
+            let touches_address = self.address.is_none_or(|address| {
+                entry.caller == address || entry.target == address || entry.code_address == address
+            });
+            if touches_address && (!self.only_reverted || in_reverted_subtree) {
+                let mut current = Some(entry);
+                while let Some(e) = current {
+                    if !kept.insert(e.id) {
+                        break;
+                    }
+                    current = e.parent_id.and_then(|pid| trace.get(pid));
+                }
+            }
+        }
+
+        hidden.extend(trace.iter().map(|e| e.id).filter(|id| !kept.contains(id)));
+        hidden
+    }
+}
+
+/// Width of the gas bar of the heaviest call graph edge
+const CALL_GRAPH_BAR_WIDTH: u64 = 24;
+
+/// Aggregated calls from one contract to another
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CallGraphEdge {
+    /// Code address of the calling frame (the sender for top-level calls)
+    from: Address,
+    /// Code address of the called frames
+    to: Address,
+    /// Number of calls
+    calls: usize,
+    /// Number of calls that reverted or failed
+    failed: usize,
+    /// Gas used by the calls, including their subcalls
+    gas_used: u64,
+    /// First snapshot of the earliest call that has one
+    first_snapshot_id: Option<usize>,
+}
+
+/// Aggregate the displayed trace entries into a call graph
+///
+/// Edges are grouped by caller, in order of first appearance, and sorted by
+/// gas within each group.
+fn build_call_graph(trace: &Trace, hidden: &HashSet<usize>) -> Vec<CallGraphEdge> {
+    let mut edges: Vec<CallGraphEdge> = Vec::new();
+    let mut index = HashMap::new();
+    for entry in trace.iter().filter(|entry| !hidden.contains(&entry.id)) {
+        let from = entry
+            .parent_id
+            .and_then(|pid| trace.get(pid))
+            .map_or(entry.caller, |parent| parent.code_address);
+        let i = *index.entry((from, entry.code_address)).or_insert_with(|| {
+            edges.push(CallGraphEdge { from, to: entry.code_address, ..Default::default() });
+            edges.len() - 1
+        });
+
+        let edge = &mut edges[i];
+        edge.calls += 1;
+        if matches!(entry.result, Some(ref r) if !matches!(r, CallResult::Success { .. })) {
+            edge.failed += 1;
+        }
+        edge.gas_used = edge.gas_used.saturating_add(entry.gas_used);
+        edge.first_snapshot_id = edge.first_snapshot_id.or(entry.first_snapshot_id);
+    }
+
+    let mut callers = HashMap::new();
+    for edge in &edges {
+        let order = callers.len();
+        callers.entry(edge.from).or_insert(order);
+    }
+    edges.sort_by(|a, b| callers[&a.from].cmp(&callers[&b.from]).then(b.gas_used.cmp(&a.gas_used)));
+    edges
+}
+
+/// Check whether an address is one of the standard precompiles (including the
+/// BLS12-381 and P256 ones)
+fn is_precompile(address: Address) -> bool {
+    let bytes = address.as_slice();
+    if bytes[..18].iter().any(|b| *b != 0) {
+        return false;
+    }
+    let index = u16::from_be_bytes([bytes[18], bytes[19]]);
+    (0x01..=0x11).contains(&index) || index == 0x100
+}
+
 #[derive(Debug)]
 pub struct TracePanel {
     inner: TracePanelInner,
+    /// Local copy of the trace entries loaded so far
     trace: Option<Trace>,
+    /// Entry to select once the page containing it has been loaded
+    pending_selection: Option<usize>,
 }
 
 impl Deref for TracePanel {
@@ -76,7 +256,63 @@ impl DerefMut for TracePanel {
 
 impl TracePanel {
     pub fn new() -> Self {
-        Self { inner: TracePanelInner::new(), trace: None }
+        Self { inner: TracePanelInner::new(), trace: None, pending_selection: None }
+    }
+
+    /// Get the decoded reason of the revert, from the frame where it originated
+    pub fn revert_reason(dm: &mut DataManager) -> Option<String> {
+        let entry_id = *dm.execution.get_revert_path().last()?;
+        let entry = dm.execution.get_trace().get(entry_id)?;
+        let (address, result) = (entry.code_address, entry.result.clone()?);
+
+        match result {
+            CallResult::Revert { output, .. } => Some(
+                dm.resolver
+                    .resolve_custom_error(&output, Some(address))
+                    .unwrap_or_else(|| utils::decode_revert_reason(&output)),
+            ),
+            CallResult::Error { output, result } => {
+                Some(utils::format_instruction_result(result, &output))
+            }
+            CallResult::Success { .. } => None,
+        }
+    }
+
+    /// Select the call line of a trace entry, e.g., the frame where the transaction reverted
+    pub fn select_entry(&mut self, entry_id: usize, dm: &mut DataManager) {
+        self.sync_trace(dm);
+        if !dm.execution.load_trace_until(entry_id) {
+            self.pending_selection = Some(entry_id);
+            return;
+        }
+
+        let trace = self.trace.as_ref().unwrap(); // Synced above
+        self.inner.reselect_entry(Some(entry_id), trace);
+    }
+
+    /// Refresh the local copy of the trace once more entries have been loaded
+    fn sync_trace(&mut self, dm: &mut DataManager) {
+        let loaded = dm.execution.get_trace();
+        if self.trace.as_ref().is_some_and(|trace| trace.len() == loaded.len()) {
+            return;
+        }
+
+        let trace = self.trace.insert(loaded.clone());
+        if self.inner.filter.is_active() {
+            self.inner.filtered_entries = self.inner.filter.hidden_entries(trace);
+        }
+        if let Some(entry_id) = self.pending_selection.take_if(|id| *id < trace.len()) {
+            self.inner.reselect_entry(Some(entry_id), trace);
+        }
+    }
+
+    /// Fetch the next page of the trace once the viewport gets close to the last loaded
+    /// entry
+    fn load_visible_entries(&self, display_lines: &[TraceLineType], dm: &mut DataManager) {
+        let last_visible = self.scroll_offset + self.context_height * 2;
+        if last_visible >= display_lines.len() && !dm.execution.is_trace_loaded() {
+            dm.execution.load_trace_until(dm.execution.get_trace().len());
+        }
     }
 }
 
@@ -103,6 +339,14 @@ pub struct TracePanelInner {
     syntax_highlighter: SyntaxHighlighter,
     /// Set of collapsed trace entry IDs (when collapsed, we hide children)
     collapsed_entries: HashSet<usize>,
+    /// Active trace filters
+    filter: TraceFilter,
+    /// Trace entry IDs hidden by the active filters
+    filtered_entries: HashSet<usize>,
+    /// Trace entry whose details are shown in a popup
+    detail_entry: Option<usize>,
+    /// Scroll offset of the detail popup
+    detail_scroll: usize,
     /// VIM mode number prefix for repetition (e.g., "10" in "10j")
     vim_number_prefix: String,
     /// VIM command buffer for commands like ":n"
@@ -111,6 +355,10 @@ pub struct TracePanelInner {
     vim_command_mode: bool,
     /// Number of lines that will be displayed (for scrolling calculations)
     displayed_line_count: usize,
+    /// Whether the aggregated call graph is shown instead of the call tree
+    call_graph: bool,
+    /// Selection and scroll offset of the view that is not shown (tree or call graph)
+    other_view_position: (usize, usize),
 
     // ========== Execution Tracking ==========
     /// Currently executing trace entry ID (from execution snapshot)
@@ -131,8 +379,14 @@ impl TracePanelInner {
             horizontal_offset: 0,
             max_line_width: 0,
             displayed_line_count: 0,
+            call_graph: false,
+            other_view_position: (0, 0),
             syntax_highlighter: SyntaxHighlighter::new(),
             collapsed_entries: HashSet::new(),
+            filter: TraceFilter::default(),
+            filtered_entries: HashSet::new(),
+            detail_entry: None,
+            detail_scroll: 0,
             current_execution_entry: None,
             current_execution_snapshot: None,
             vim_number_prefix: String::new(),
@@ -251,16 +505,96 @@ impl TracePanelInner {
     }
 
     /// Execute VIM command from command buffer
-    fn execute_vim_command(&mut self) {
-        let command = self.vim_command_buffer.trim();
+    ///
+    /// Besides `:n`, `:addr <address>` only shows frames touching an address
+    /// and `:addr` alone clears that filter.
+    fn execute_vim_command(&mut self, trace: &Trace) {
+        let command = self.vim_command_buffer.trim().to_string();
         if let Ok(line_number) = command.parse::<usize>() {
             self.move_to(line_number);
+        } else if let Some(arg) = command.strip_prefix("addr") {
+            let arg = arg.trim();
+            if arg.is_empty() {
+                self.set_filter(TraceFilter { address: None, ..self.filter.clone() }, trace);
+            } else if let Ok(address) = arg.parse::<Address>() {
+                self.set_filter(
+                    TraceFilter { address: Some(address), ..self.filter.clone() },
+                    trace,
+                );
+            } else {
+                debug!("Invalid address in trace filter: {arg}");
+            }
         }
         // Clear command mode
         self.vim_command_buffer.clear();
         self.vim_command_mode = false;
     }
 
+    /// Switch between the call tree and the call graph, each keeping its own selection
+    fn toggle_call_graph(&mut self, trace: &Trace) {
+        self.call_graph = !self.call_graph;
+        let position = (self.selected_index, self.scroll_offset);
+        (self.selected_index, self.scroll_offset) = self.other_view_position;
+        self.other_view_position = position;
+        self.horizontal_offset = 0;
+
+        // Filters may have changed while the tree was hidden
+        if !self.call_graph {
+            self.reselect_entry(None, trace);
+        }
+    }
+
+    /// Get the call graph edge under the selection
+    fn selected_edge(&self, trace: &Trace) -> Option<CallGraphEdge> {
+        build_call_graph(trace, &self.filtered_entries).into_iter().nth(self.selected_index)
+    }
+
+    /// Replace the active filters, keeping the selection on the same entry when possible
+    fn set_filter(&mut self, filter: TraceFilter, trace: &Trace) {
+        if self.call_graph {
+            // Edges are not entries, the selection is clamped on the next render
+            self.filtered_entries = filter.hidden_entries(trace);
+            self.filter = filter;
+            return;
+        }
+
+        let selected = self.selected_entry(trace).map(|e| e.id);
+        self.filtered_entries = filter.hidden_entries(trace);
+        self.filter = filter;
+        self.reselect_entry(selected, trace);
+    }
+
+    /// Collapse every entry that has children
+    fn collapse_all(&mut self, trace: &Trace) {
+        let selected = self.selected_entry(trace).map(|e| e.id);
+        self.collapsed_entries = trace.iter().filter_map(|e| e.parent_id).collect();
+        self.reselect_entry(selected, trace);
+    }
+
+    /// Expand every collapsed entry
+    fn expand_all(&mut self, trace: &Trace) {
+        let selected = self.selected_entry(trace).map(|e| e.id);
+        self.collapsed_entries.clear();
+        self.reselect_entry(selected, trace);
+    }
+
+    /// Select the call line of an entry, or of its closest visible ancestor
+    fn reselect_entry(&mut self, entry_id: Option<usize>, trace: &Trace) {
+        let mut current = entry_id.and_then(|id| trace.get(id));
+        while let Some(entry) = current {
+            if self.is_entry_visible(entry, trace) {
+                self.adjust_selection_after_expansion(entry.id, trace);
+                return;
+            }
+            current = entry.parent_id.and_then(|pid| trace.get(pid));
+        }
+
+        // Nothing to anchor on, stay within the displayed lines
+        let line_count = self.generate_display_lines(trace).len();
+        self.selected_index = self.selected_index.min(line_count.saturating_sub(1));
+        self.scroll_offset = self.scroll_offset.min(self.selected_index);
+    }
+
     /// Get currently selected trace entry
     pub fn selected_entry<'a>(&mut self, trace: &'a Trace) -> Option<&'a TraceEntry> {
         let display_lines = self.generate_display_lines(trace);
@@ -329,6 +663,10 @@ impl TracePanelInner {
 
     /// Check if an entry should be visible (not hidden by collapsed parent)
     fn is_entry_visible(&self, entry: &TraceEntry, trace: &Trace) -> bool {
+        if self.filtered_entries.contains(&entry.id) {
+            return false;
+        }
+
         if let Some(parent_id) = entry.parent_id {
             // Check if any ancestor is collapsed
             let mut current_parent_id = Some(parent_id);
@@ -524,21 +862,18 @@ impl TracePanelInner {
             }
         };
 
-        let (call_type_str, call_color) = match &entry.call_type {
-            CallType::Call(CallScheme::Call) => ("CALL", dm.theme.call_color),
-            CallType::Call(CallScheme::CallCode) => ("CALLCODE", dm.theme.call_color),
-            CallType::Call(CallScheme::DelegateCall) => ("DELEGATECALL", dm.theme.call_color),
-            CallType::Call(CallScheme::StaticCall) => ("STATICCALL", dm.theme.call_color),
-            CallType::Create(CreateScheme::Create) => ("CREATE", dm.theme.create_color),
-            CallType::Create(CreateScheme::Create2 { .. }) => ("CREATE2", dm.theme.create_color),
-            CallType::Create(CreateScheme::Custom { .. }) => {
-                ("CUSTOM_CREATE", dm.theme.create_color)
-            }
-        };
+        let (call_type_str, call_color) = self.call_type_style(&entry.call_type, dm);
+
+        // The tree branches leading to the revert are drawn in the revert color
+        let revert_path = dm.execution.get_revert_path();
+        let on_revert_path = revert_path.contains(&entry.id);
+        let reverts_here = revert_path.last() == Some(&entry.id);
+        let prefix_color =
+            if on_revert_path { dm.theme.revert_color } else { dm.theme.comment_color };
 
         // Build spans with the new format
         let mut spans = vec![
-            Span::styled(line_prefix, Style::default().fg(dm.theme.comment_color)),
+            Span::styled(line_prefix, Style::default().fg(prefix_color)),
             Span::styled(call_type_str, Style::default().fg(call_color)),
             Span::raw(" "),
             Span::styled(
@@ -567,7 +902,9 @@ impl TracePanelInner {
             dm.resolver.resolve_function_call(&entry.input, Some(entry.code_address))
         {
             spans.push(Span::raw(" "));
-            spans.extend(self.highlight_solidity_code(call_str, dm));
+            spans.extend(
+                self.highlight_solidity_code(preview_call(&call_str, CALL_PREVIEW_CHARS), dm),
+            );
         } else if !entry.input.is_empty() {
             spans.push(Span::raw(" "));
             if entry.input.len() >= 4 {
@@ -589,7 +926,8 @@ impl TracePanelInner {
 
         // Add self-destruct indicator if present
         if let Some((beneficiary, value)) = &entry.self_destruct {
-            spans.push(Span::styled(" [SELFDESTRUCT]", Style::default().fg(dm.theme.error_color)));
+            let label = if entry.destroyed { " [SELFDESTRUCT]" } else { " [SENDALL]" };
+            spans.push(Span::styled(label, Style::default().fg(dm.theme.error_color)));
             spans.push(Span::styled(
                 format!(
                     " → {} ({} ETH)",
@@ -624,6 +962,35 @@ impl TracePanelInner {
 
         spans.push(Span::raw(" "));
         spans.push(Span::styled(result_char, Style::default().fg(result_color)));
+        if reverts_here {
+            spans
+                .push(Span::styled(" ◀ reverted here", Style::default().fg(dm.theme.revert_color)));
+        }
+
+        // Add badges for findings attached to this entry (e.g., reentrancy)
+        for finding in dm.execution.get_findings().iter().filter(|f| f.is_attached_to(entry.id)) {
+            let badge_color = match finding.severity {
+                FindingSeverity::High | FindingSeverity::Medium => dm.theme.error_color,
+                FindingSeverity::Low => dm.theme.warning_color,
+                FindingSeverity::Info => dm.theme.comment_color,
+            };
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!("[{}]", finding.category().to_uppercase()),
+                Style::default().fg(badge_color),
+            ));
+        }
+
+        // Add badges for messages sent through a canonical bridge by this entry
+        for message in
+            dm.execution.get_bridge_messages().iter().filter(|m| m.trace_entry_id == entry.id)
+        {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!("[⇄ {}]", message.direction.to_string().to_uppercase()),
+                Style::default().fg(dm.theme.accent_color),
+            ));
+        }
 
         // Add ether value if present
         if entry.value > 0 {
@@ -634,9 +1001,227 @@ impl TracePanelInner {
             ));
         }
 
+        // Add the ether moved by the subtree when descendants move some of it
+        if entry.subtree_value > entry.transferred_value() {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!("[Σ {} ETH]", dm.resolver.resolve_ether(entry.subtree_value)),
+                Style::default().fg(dm.theme.warning_color),
+            ));
+        }
+
         Line::from(spans)
     }
 
+    /// Label and color of a call type
+    fn call_type_style(&self, call_type: &CallType, dm: &DataManager) -> (&'static str, Color) {
+        match call_type {
+            CallType::Call(CallScheme::Call) => ("CALL", dm.theme.call_color),
+            CallType::Call(CallScheme::CallCode) => ("CALLCODE", dm.theme.call_color),
+            CallType::Call(CallScheme::DelegateCall) => ("DELEGATECALL", dm.theme.call_color),
+            CallType::Call(CallScheme::StaticCall) => ("STATICCALL", dm.theme.call_color),
+            CallType::Create(CreateScheme::Create) => ("CREATE", dm.theme.create_color),
+            CallType::Create(CreateScheme::Create2 { .. }) => ("CREATE2", dm.theme.create_color),
+            CallType::Create(CreateScheme::Custom { .. }) => {
+                ("CUSTOM_CREATE", dm.theme.create_color)
+            }
+        }
+    }
+
+    /// Build a `label  value` line of the detail popup
+    fn detail_row(
+        label: &str,
+        value: String,
+        label_color: Color,
+        value_color: Color,
+    ) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(format!("{label:<12}"), Style::default().fg(label_color)),
+            Span::styled(value, Style::default().fg(value_color)),
+        ])
+    }
+
+    /// Build the content of the detail popup for a trace entry
+    fn format_entry_details(
+        &mut self,
+        entry: &TraceEntry,
+        dm: &mut DataManager,
+    ) -> Vec<Line<'static>> {
+        let label_color = dm.theme.comment_color;
+        let value_color = dm.theme.accent_color;
+        let mut lines = Vec::new();
+
+        let (call_type_str, call_color) = self.call_type_style(&entry.call_type, dm);
+        lines.push(Self::detail_row("Type", call_type_str.to_string(), label_color, call_color));
+
+        let (status, status_color) = match &entry.result {
+            Some(CallResult::Success { .. }) => ("success".to_string(), dm.theme.success_color),
+            Some(CallResult::Revert { output, .. }) => {
+                (format!("reverted: {}", utils::decode_revert_reason(output)), dm.theme.error_color)
+            }
+            Some(CallResult::Error { output, result }) => {
+                (utils::format_instruction_result(*result, output), dm.theme.error_color)
+            }
+            None => ("unknown".to_string(), dm.theme.comment_color),
+        };
+        lines.push(Self::detail_row("Status", status, label_color, status_color));
+
+        for (label, address) in [("From", entry.caller), ("To", entry.target)] {
+            let resolved = dm.resolver.resolve_sol_value(&DynSolValue::Address(address), None);
+            lines.push(Self::detail_row(label, resolved, label_color, value_color));
+        }
+        if entry.code_address != entry.target {
+            let resolved =
+                dm.resolver.resolve_sol_value(&DynSolValue::Address(entry.code_address), None);
+            lines.push(Self::detail_row("Code", resolved, label_color, value_color));
+        }
+        if entry.value > 0 {
+            lines.push(Self::detail_row(
+                "Value",
+                format!("{} ETH", dm.resolver.resolve_ether(entry.value)),
+                label_color,
+                dm.theme.warning_color,
+            ));
+        }
+        if entry.moves_value() {
+            lines.push(Self::detail_row(
+                "Value flow",
+                format!("{} ETH (with subcalls)", dm.resolver.resolve_ether(entry.subtree_value)),
+                label_color,
+                dm.theme.warning_color,
+            ));
+        }
+        if entry.readonly {
+            lines.push(Self::detail_row(
+                "Readonly",
+                "static context, state changes revert".to_string(),
+                label_color,
+                value_color,
+            ));
+        }
+        lines.push(Self::detail_row(
+            "Gas",
+            format!("{} used / {} limit", entry.gas_used, entry.gas_limit),
+            label_color,
+            value_color,
+        ));
+        lines.push(Line::from(""));
+
+        // Decoded call with one argument per line
+        if matches!(entry.call_type, CallType::Create(_)) {
+            let constructor = dm
+                .resolver
+                .resolve_constructor_call(entry.code_address)
+                .unwrap_or_else(|| "constructor(...)".to_string());
+            lines.push(Self::detail_row("Constructor", constructor, label_color, value_color));
+        } else if let Some((name, arguments)) =
+            dm.resolver.resolve_function_arguments(&entry.input, Some(entry.code_address))
+        {
+            lines.push(Self::detail_row("Function", name, label_color, dm.theme.keyword_color));
+            for (param, value) in arguments {
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    Span::styled(param, Style::default().fg(dm.theme.syntax_type_color)),
+                    Span::styled(" = ", Style::default().fg(label_color)),
+                    Span::styled(value, Style::default().fg(dm.theme.syntax_string_color)),
+                ]));
+            }
+        } else if entry.input.len() >= 4 {
+            lines.push(Self::detail_row(
+                "Selector",
+                format!("0x{}", hex::encode(&entry.input[..4])),
+                label_color,
+                value_color,
+            ));
+            lines.push(Self::detail_row(
+                "Calldata",
+                format!("{} bytes", entry.input.len()),
+                label_color,
+                value_color,
+            ));
+        } else {
+            lines.push(Self::detail_row(
+                "Calldata",
+                format!("0x{}", hex::encode(&entry.input)),
+                label_color,
+                value_color,
+            ));
+        }
+
+        if let Some(CallResult::Success { output, .. }) = &entry.result {
+            if !output.is_empty() {
+                let returned = dm
+                    .resolver
+                    .resolve_function_return(&entry.input, output, Some(entry.code_address))
+                    .unwrap_or_else(|| format!("0x{}", hex::encode(output)));
+                lines.push(Self::detail_row(
+                    "Returns",
+                    returned,
+                    label_color,
+                    dm.theme.syntax_string_color,
+                ));
+            }
+        }
+
+        if !entry.events.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Self::detail_row(
+                "Events",
+                entry.events.len().to_string(),
+                label_color,
+                value_color,
+            ));
+            for event in &entry.events {
+                let event_text = dm
+                    .resolver
+                    .resolve_event(event, Some(entry.code_address))
+                    .unwrap_or_else(|| {
+                        format!("{} topics, {} bytes data", event.topics().len(), event.data.len())
+                    });
+                lines.push(Line::from(vec![
+                    Span::styled("  · ", Style::default().fg(label_color)),
+                    Span::styled(event_text, Style::default().fg(value_color)),
+                ]));
+            }
+        }
+
+        lines
+    }
+
+    /// Render the detail popup of the selected trace entry
+    fn render_entry_details(
+        &mut self,
+        frame: &mut Frame<'_>,
+        area: Rect,
+        entry: &TraceEntry,
+        dm: &mut DataManager,
+    ) {
+        let width = (area.width * 4 / 5).max(20).min(area.width);
+        let height = (area.height * 4 / 5).max(6).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+
+        let lines = self.format_entry_details(entry, dm);
+        let max_scroll = lines.len().saturating_sub(height.saturating_sub(2) as usize);
+        self.detail_scroll = self.detail_scroll.min(max_scroll);
+
+        let block = Block::default()
+            .title(format!("Trace entry {} (j/k: Scroll • Esc: Close)", entry.id + 1))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(dm.theme.accent_color));
+        let paragraph = Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .scroll((self.detail_scroll as u16, 0));
+
+        frame.render_widget(Clear, popup);
+        frame.render_widget(paragraph, popup);
+    }
+
     /// Format an event line
     fn format_event_line(
         &mut self,
@@ -665,18 +1250,10 @@ impl TracePanelInner {
                 }
             };
 
-            let event_text = match dm.resolver.resolve_event(event, Some(entry.code_address)) {
-                Some(text) => text,
-                None if event.topics().is_empty() => {
-                    format!("Anonymous event ({} bytes data)", event.data.len())
-                }
-                None => format!(
-                    "Event 0x{}... ({} indexed, {} bytes data)",
-                    hex::encode(&event.topics()[0].as_slice()[..4]),
-                    event.topics().len() - 1,
-                    event.data.len()
-                ),
-            };
+            let event_text = dm
+                .resolver
+                .resolve_event(event, Some(entry.code_address))
+                .unwrap_or_else(|| utils::format_undecoded_event(event));
 
             Line::from(vec![
                 Span::styled(full_indent, Style::default().fg(dm.theme.comment_color)),
@@ -745,7 +1322,7 @@ impl TracePanelInner {
                 ])
             }
             Some(CallResult::Revert { output, .. }) => {
-                let revert_text = self.decode_revert_reason(output);
+                let revert_text = utils::decode_revert_reason(output);
                 Line::from(vec![
                     Span::styled(full_indent, Style::default().fg(dm.theme.comment_color)),
                     Span::styled("· ", Style::default().fg(dm.theme.comment_color)),
@@ -754,7 +1331,7 @@ impl TracePanelInner {
                 ])
             }
             Some(CallResult::Error { output, result }) => {
-                let error_text = self.format_instruction_result(*result, output);
+                let error_text = utils::format_instruction_result(*result, output);
                 Line::from(vec![
                     Span::styled(full_indent, Style::default().fg(dm.theme.comment_color)),
                     Span::styled("· ", Style::default().fg(dm.theme.comment_color)),
@@ -766,88 +1343,6 @@ impl TracePanelInner {
         }
     }
 
-    /// Decode revert reason from output data
-    fn decode_revert_reason(&self, output: &Bytes) -> String {
-        if output.is_empty() {
-            return "(empty revert)".to_string();
-        }
-
-        // Check if it's a standard Error(string) revert (0x08c379a0)
-        if output.len() >= 4 && output.starts_with(&[0x08, 0xc3, 0x79, 0xa0]) {
-            // Try to decode the string from Error(string) signature
-            if let Ok(DynSolValue::String(reason)) =
-                alloy_dyn_abi::DynSolType::String.abi_decode(&output[4..])
-            {
-                return format!("\"{reason}\"");
-            }
-        }
-
-        // Check if it's a Panic(uint256) revert (0x4e487b71)
-        if output.len() >= 4 && output.starts_with(&[0x4e, 0x48, 0x7b, 0x71]) {
-            if let Ok(DynSolValue::Uint(panic_code, _)) =
-                alloy_dyn_abi::DynSolType::Uint(256).abi_decode(&output[4..])
-            {
-                let panic_reason = match panic_code.to_string().as_str() {
-                    "1" => "assertion failed",
-                    "17" => "arithmetic overflow/underflow",
-                    "18" => "division by zero",
-                    "33" => "enum conversion error",
-                    "34" => "invalid storage byte array access",
-                    "49" => "pop() on empty array",
-                    "50" => "array index out of bounds",
-                    "65" => "memory allocation overflow",
-                    "81" => "zero initialization of invalid type",
-                    _ => "unknown panic",
-                };
-                return format!("Panic({panic_code}: {panic_reason})");
-            }
-        }
-
-        // Fallback: show hex data
-        if output.len() <= 32 {
-            format!("0x{}", hex::encode(output))
-        } else {
-            format!("0x{}...({} bytes)", hex::encode(&output[..8]), output.len())
-        }
-    }
-
-    /// Format InstructionResult with context
-    fn format_instruction_result(&self, result: InstructionResult, output: &Bytes) -> String {
-        match result {
-            InstructionResult::Stop => "stop".to_string(),
-            InstructionResult::Return => "return".to_string(),
-            InstructionResult::SelfDestruct => "selfdestruct".to_string(),
-            InstructionResult::Revert => {
-                // This shouldn't happen in Error variant, but handle it
-                self.decode_revert_reason(output)
-            }
-            InstructionResult::CallTooDeep => "call stack too deep".to_string(),
-            InstructionResult::OutOfFunds => "insufficient funds".to_string(),
-            InstructionResult::InvalidJump => "invalid jump destination".to_string(),
-            InstructionResult::StackOverflow => "stack overflow".to_string(),
-            InstructionResult::StackUnderflow => "stack underflow".to_string(),
-            InstructionResult::OutOfGas => "out of gas".to_string(),
-            InstructionResult::MemoryOOG => "out of gas (memory)".to_string(),
-            InstructionResult::MemoryLimitOOG => "memory limit exceeded".to_string(),
-            InstructionResult::PrecompileOOG => "precompile out of gas".to_string(),
-            InstructionResult::InvalidOperandOOG => "invalid operand (OOG)".to_string(),
-            InstructionResult::OpcodeNotFound => "opcode not found".to_string(),
-            InstructionResult::CreateInitCodeSizeLimit => "init code size limit".to_string(),
-            InstructionResult::CreateContractSizeLimit => "contract size limit".to_string(),
-            InstructionResult::OverflowPayment => "payment overflow".to_string(),
-            InstructionResult::StateChangeDuringStaticCall => {
-                "state change in static call".to_string()
-            }
-            InstructionResult::CallNotAllowedInsideStatic => {
-                "call not allowed in static".to_string()
-            }
-            InstructionResult::OutOfOffset => "out of offset".to_string(),
-            InstructionResult::CreateCollision => "create collision".to_string(),
-            InstructionResult::FatalExternalError => "fatal external error".to_string(),
-            _ => format!("unknown error ({result:?})"),
-        }
-    }
-
     /// Apply syntax highlighting to Solidity code using the existing syntax highlighter (for owned strings)
     fn highlight_solidity_code(&self, code: String, dm: &DataManager) -> Vec<Span<'static>> {
         let tokens = self.syntax_highlighter.tokenize(&code, SyntaxType::Solidity);
@@ -884,6 +1379,141 @@ impl TracePanelInner {
         spans
     }
 
+    /// Render the call graph: one row per caller → callee edge, with a bar sized by gas
+    fn render_call_graph(
+        &mut self,
+        frame: &mut Frame<'_>,
+        area: Rect,
+        trace: &Trace,
+        title: String,
+        dm: &mut DataManager,
+    ) {
+        let edges = build_call_graph(trace, &self.filtered_entries);
+        self.displayed_line_count = edges.len();
+        let block = BorderPresets::trace(
+            self.focused,
+            title,
+            dm.theme.focused_border,
+            dm.theme.unfocused_border,
+        );
+
+        if edges.is_empty() {
+            let paragraph = Paragraph::new("No calls match the current filters (F: Clear)");
+            frame.render_widget(paragraph.block(block), area);
+            return;
+        }
+        self.selected_index = self.selected_index.min(edges.len() - 1);
+        self.scroll_offset = self.scroll_offset.min(self.selected_index);
+
+        let mut label =
+            |address: Address| dm.resolver.resolve_sol_value(&DynSolValue::Address(address), None);
+        let labels: Vec<(String, String)> =
+            edges.iter().map(|edge| (label(edge.from), label(edge.to))).collect();
+
+        // Align the columns across all edges
+        let from_width = labels.iter().map(|(from, _)| from.chars().count()).max().unwrap_or(0);
+        let to_width = labels.iter().map(|(_, to)| to.chars().count()).max().unwrap_or(0);
+        let calls_width = edges.iter().map(|e| e.calls.to_string().len()).max().unwrap_or(0);
+        let gas_width = edges.iter().map(|e| e.gas_used.to_string().len()).max().unwrap_or(0);
+        let max_gas = edges.iter().map(|e| e.gas_used).max().unwrap_or(0).max(1);
+
+        let lines: Vec<Line<'static>> = edges
+            .iter()
+            .zip(&labels)
+            .enumerate()
+            .map(|(i, (edge, (from, to)))| {
+                // Only the first edge of a caller names it
+                let from = if i > 0 && edges[i - 1].from == edge.from { "" } else { from.as_str() };
+                let bar_len =
+                    (edge.gas_used.saturating_mul(CALL_GRAPH_BAR_WIDTH)).div_ceil(max_gas);
+                let flow_color =
+                    if edge.failed > 0 { dm.theme.revert_color } else { dm.theme.call_color };
+
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{from:<from_width$}"),
+                        Style::default().fg(dm.theme.accent_color),
+                    ),
+                    Span::styled(" ─▶ ", Style::default().fg(flow_color)),
+                    Span::styled(
+                        format!("{to:<to_width$}"),
+                        Style::default().fg(dm.theme.accent_color),
+                    ),
+                    Span::styled(
+                        format!("  ×{:<calls_width$}", edge.calls),
+                        Style::default().fg(dm.theme.keyword_color),
+                    ),
+                    Span::styled(
+                        format!("  {:>gas_width$} gas  ", edge.gas_used),
+                        Style::default().fg(dm.theme.comment_color),
+                    ),
+                    Span::styled("█".repeat(bar_len as usize), Style::default().fg(flow_color)),
+                ];
+                if edge.failed > 0 {
+                    spans.push(Span::styled(
+                        format!(" {} failed", edge.failed),
+                        Style::default().fg(dm.theme.error_color),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        self.max_line_width = lines.iter().map(Line::width).max().unwrap_or(0);
+
+        let items: Vec<ListItem<'_>> = lines
+            .into_iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(self.context_height)
+            .map(|(i, line)| {
+                let style = if i == self.selected_index && self.focused {
+                    Style::default().bg(dm.theme.selection_bg).fg(dm.theme.selection_fg)
+                } else if i == self.selected_index {
+                    Style::default().bg(dm.theme.highlight_bg)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(self.apply_horizontal_offset(line)).style(style)
+            })
+            .collect();
+        frame.render_widget(List::new(items).block(block), area);
+
+        if self.focused && area.height > 10 {
+            let status_area = Rect {
+                x: area.x + 1,
+                y: area.y + area.height - 3,
+                width: area.width - 2,
+                height: 1,
+            };
+            let edge = &edges[self.selected_index];
+            let status_text = StatusBar::new()
+                .current_panel("Call Graph".to_string())
+                .message(format!("Edge: {}/{}", self.selected_index + 1, edges.len()))
+                .message(format!("Calls: {} | Gas: {}", edge.calls, edge.gas_used))
+                .build();
+            let status_paragraph =
+                Paragraph::new(status_text).style(Style::default().fg(dm.theme.accent_color));
+            frame.render_widget(status_paragraph, status_area);
+
+            let help_area = Rect {
+                x: area.x + 1,
+                y: area.y + area.height - 2,
+                width: area.width - 2,
+                height: 1,
+            };
+            let help_text = if self.vim_command_mode {
+                format!(":{}", self.vim_command_buffer)
+            } else {
+                "Vim-like Navigation • Enter/C: Goto first call • S/P/R/F: Filter • y: Copy address \
+                 • A: Call tree • ?: Help"
+                    .to_string()
+            };
+            let help_paragraph =
+                Paragraph::new(help_text).style(Style::default().fg(dm.theme.help_text_color));
+            frame.render_widget(help_paragraph, help_area);
+        }
+    }
+
     /// Update execution info from DataManager
     fn update_execution_info(&mut self, dm: &mut DataManager) -> Option<()> {
         let snapshot_id = dm.execution.get_current_snapshot();
@@ -1006,9 +1636,36 @@ impl PanelTr for TracePanel {
 
     fn title(&self, dm: &mut DataManager) -> String {
         let trace = dm.execution.get_trace();
+        if self.call_graph {
+            let edges = build_call_graph(trace, &self.filtered_entries);
+            let contracts: HashSet<Address> = edges.iter().flat_map(|e| [e.from, e.to]).collect();
+            let title =
+                format!("Call Graph ({} edges, {} contracts)", edges.len(), contracts.len());
+            return if self.filter.is_active() {
+                format!("{title} [{}]", self.filter.describe())
+            } else {
+                title
+            };
+        }
+
         let visible_entries =
             trace.iter().filter(|entry| self.is_entry_visible(entry, trace)).count();
-        format!("Trace ({} lines, {} entries)", self.displayed_line_count, visible_entries)
+        let title = if self.filter.is_active() {
+            format!(
+                "Trace ({} lines, {}/{} entries) [{}]",
+                self.displayed_line_count,
+                visible_entries,
+                trace.len(),
+                self.filter.describe()
+            )
+        } else {
+            format!("Trace ({} lines, {} entries)", self.displayed_line_count, visible_entries)
+        };
+        if dm.execution.is_trace_loaded() {
+            title
+        } else {
+            format!("{title} [loaded {}/{}]", trace.len(), dm.execution.get_trace_total())
+        }
     }
 
     fn render(&mut self, frame: &mut Frame<'_>, area: Rect, dm: &mut DataManager) {
@@ -1023,9 +1680,7 @@ impl PanelTr for TracePanel {
         // Update execution info from DataManager
         self.inner.update_execution_info(dm);
 
-        if self.trace.is_none() {
-            self.trace = Some(dm.execution.get_trace().clone());
-        }
+        self.sync_trace(dm);
 
         let trace = self.trace.as_ref().unwrap(); // This must be safe
         if trace.is_empty() {
@@ -1039,9 +1694,28 @@ impl PanelTr for TracePanel {
             return;
         }
 
+        if self.call_graph {
+            let title = self.title(dm);
+            self.inner.render_call_graph(frame, area, trace, title, dm);
+            return;
+        }
+
         // Generate display lines with expansion/collapse support
         let display_lines = self.generate_display_lines(trace);
         self.inner.displayed_line_count = display_lines.len();
+        self.load_visible_entries(&display_lines, dm);
+
+        if display_lines.is_empty() {
+            let paragraph = Paragraph::new("No trace entries match the current filters (F: Clear)")
+                .block(BorderPresets::trace(
+                    self.focused,
+                    self.title(dm),
+                    dm.theme.focused_border,
+                    dm.theme.unfocused_border,
+                ));
+            frame.render_widget(paragraph, area);
+            return;
+        }
 
         // Calculate max line width for horizontal scrolling
         self.inner.calculate_max_line_width(trace, dm);
@@ -1172,8 +1846,9 @@ impl PanelTr for TracePanel {
             } else {
                 let mut help = String::from("Vim-like Navigation");
                 help.push_str(" • V/C: View/Goto code");
-                help.push_str(" • Enter: Expand");
-                help.push_str(" • Space: Code Panel");
+                help.push_str(" • Enter: Details • z: Expand • -/+: Collapse/Expand all");
+                help.push_str(" • S/P/R/a/F: Filter • y: Copy address");
+                help.push_str(" • A: Call graph • Space: Code Panel");
                 help.push_str(" • ?: Help");
                 help
             };
@@ -1188,6 +1863,11 @@ impl PanelTr for TracePanel {
             let help_paragraph = Paragraph::new(help_text).style(help_style);
             frame.render_widget(help_paragraph, help_area);
         }
+
+        // Render the detail popup on top
+        if let Some(entry) = self.inner.detail_entry.and_then(|id| trace.get(id)) {
+            self.inner.render_entry_details(frame, area, entry, dm);
+        }
     }
 
     fn handle_key_event(&mut self, event: KeyEvent, dm: &mut DataManager) -> Result<EventResponse> {
@@ -1195,13 +1875,28 @@ impl PanelTr for TracePanel {
             return Ok(EventResponse::NotHandled);
         }
 
-        if self.trace.is_none() {
-            self.trace = Some(dm.execution.get_trace().clone());
-        }
+        self.sync_trace(dm);
 
         let trace = self.trace.as_ref().unwrap(); // This must be safe
 
-        // Handle VIM command mode first
+        // Handle the detail popup first
+        if self.inner.detail_entry.is_some() {
+            match event.code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                    self.inner.detail_entry = None;
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    self.inner.detail_scroll = self.inner.detail_scroll.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.inner.detail_scroll += 1;
+                }
+                _ => {}
+            }
+            return Ok(EventResponse::Handled);
+        }
+
+        // Handle VIM command mode
         if self.inner.vim_command_mode {
             match event.code {
                 KeyCode::Backspace => {
@@ -1209,7 +1904,7 @@ impl PanelTr for TracePanel {
                     Ok(EventResponse::Handled)
                 }
                 KeyCode::Enter => {
-                    self.inner.execute_vim_command();
+                    self.inner.execute_vim_command(trace);
                     Ok(EventResponse::Handled)
                 }
                 KeyCode::Esc => {
@@ -1316,11 +2011,86 @@ impl PanelTr for TracePanel {
                     self.inner.move_down(self.inner.context_height / 2);
                     Ok(EventResponse::Handled)
                 }
+                KeyCode::Char('A') => {
+                    self.inner.toggle_call_graph(trace);
+                    Ok(EventResponse::Handled)
+                }
+                // Edges of the call graph jump to their first call
+                KeyCode::Enter | KeyCode::Char('c') | KeyCode::Char('C')
+                    if self.inner.call_graph =>
+                {
+                    let Some(edge) = self.inner.selected_edge(trace) else {
+                        return Ok(EventResponse::Handled);
+                    };
+                    let Some(snapshot_id) = edge.first_snapshot_id else {
+                        bail!("None of the calls from {} to {} has an associated snapshot (likely precompiles or state variable view functions).", edge.from, edge.to);
+                    };
+                    debug!("Jumping to snapshot: {}", snapshot_id);
+                    dm.execution.goto(snapshot_id, false)?;
+                    Ok(EventResponse::ChangeFocus(PanelType::Code))
+                }
+                // The call tree operations below have no meaning on the call graph
+                KeyCode::Char('z' | '-' | '+' | '=' | 'a' | 'v' | 'V') if self.inner.call_graph => {
+                    Ok(EventResponse::Handled)
+                }
                 // Other operations
                 KeyCode::Enter => {
+                    self.inner.detail_entry = self.inner.selected_entry(trace).map(|e| e.id);
+                    self.inner.detail_scroll = 0;
+                    Ok(EventResponse::Handled)
+                }
+                KeyCode::Char('z') => {
                     self.inner.toggle_expansion(trace);
                     Ok(EventResponse::Handled)
                 }
+                KeyCode::Char('-') => {
+                    self.inner.collapse_all(trace);
+                    Ok(EventResponse::Handled)
+                }
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    self.inner.expand_all(trace);
+                    Ok(EventResponse::Handled)
+                }
+                // Filters
+                KeyCode::Char('S') => {
+                    let filter = TraceFilter {
+                        hide_static_calls: !self.inner.filter.hide_static_calls,
+                        ..self.inner.filter.clone()
+                    };
+                    self.inner.set_filter(filter, trace);
+                    Ok(EventResponse::Handled)
+                }
+                KeyCode::Char('P') => {
+                    let filter = TraceFilter {
+                        hide_precompiles: !self.inner.filter.hide_precompiles,
+                        ..self.inner.filter.clone()
+                    };
+                    self.inner.set_filter(filter, trace);
+                    Ok(EventResponse::Handled)
+                }
+                KeyCode::Char('R') => {
+                    let filter = TraceFilter {
+                        only_reverted: !self.inner.filter.only_reverted,
+                        ..self.inner.filter.clone()
+                    };
+                    self.inner.set_filter(filter, trace);
+                    Ok(EventResponse::Handled)
+                }
+                KeyCode::Char('a') => {
+                    // Toggle the address filter on the selected frame's target
+                    let address = if self.inner.filter.address.is_some() {
+                        None
+                    } else {
+                        self.inner.selected_entry(trace).map(|e| e.target)
+                    };
+                    let filter = TraceFilter { address, ..self.inner.filter.clone() };
+                    self.inner.set_filter(filter, trace);
+                    Ok(EventResponse::Handled)
+                }
+                KeyCode::Char('F') => {
+                    self.inner.set_filter(TraceFilter::default(), trace);
+                    Ok(EventResponse::Handled)
+                }
                 KeyCode::Char('c') | KeyCode::Char('C') => {
                     if let Some(entry) = self.inner.selected_entry(trace) {
                         debug!("Selected trace entry ID: {} at depth: {}", entry.id, entry.depth);
@@ -1383,12 +2153,137 @@ impl PanelTr for TracePanel {
         debug!("Trace panel gained focus");
     }
 
+    fn is_capturing_input(&self) -> bool {
+        self.focused && self.detail_entry.is_some()
+    }
+
+    fn yank(&mut self, dm: &mut DataManager) -> Option<Yank> {
+        self.sync_trace(dm);
+
+        let trace = self.trace.as_ref()?;
+        if self.inner.call_graph {
+            let edge = self.inner.selected_edge(trace)?;
+            return Some(Yank::new("callee address", edge.to.to_string()));
+        }
+        let entry = self.inner.selected_entry(trace)?;
+        Some(Yank::new("target address", entry.target.to_string()))
+    }
+
     fn on_blur(&mut self) {
         self.focused = false;
+        self.detail_entry = None;
         debug!("Trace panel lost focus");
     }
 
+    fn help_entries(&self) -> Vec<HelpEntry> {
+        let mut entries = Vec::new();
+        if self.call_graph {
+            entries.push(HelpEntry::new("A", "Back to the call tree (showing the call graph)"));
+            entries.push(HelpEntry::new("Enter/C", "Go to the first call of the selected edge"));
+        } else if !self.collapsed_entries.is_empty() {
+            entries.push(HelpEntry::new(
+                "+",
+                format!("Expand the {} collapsed calls", self.collapsed_entries.len()),
+            ));
+        }
+        if self.filter.is_active() {
+            entries.push(HelpEntry::new(
+                "F",
+                format!("Clear the active filters ({})", self.filter.describe()),
+            ));
+        }
+        entries
+    }
+
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, Bytes, U256};
+
+    #[test]
+    fn test_preview_call() {
+        assert_eq!(preview_call("transfer(0x01, 1)", 96), "transfer(0x01, 1)");
+        assert_eq!(preview_call("transfer(0x0123456789, 1)", 12), "transfer(0…)");
+        assert_eq!(preview_call("abcdefgh", 5), "abc…");
+    }
+
+    #[test]
+    fn test_is_precompile() {
+        assert!(is_precompile(address!("0x0000000000000000000000000000000000000001")));
+        assert!(is_precompile(address!("0x0000000000000000000000000000000000000011")));
+        assert!(is_precompile(address!("0x0000000000000000000000000000000000000100")));
+        assert!(!is_precompile(Address::ZERO));
+        assert!(!is_precompile(address!("0x0000000000000000000000000000000000000012")));
+        assert!(!is_precompile(address!("0x1000000000000000000000000000000000000001")));
+    }
+
+    #[test]
+    fn test_build_call_graph() {
+        let eoa = address!("0x00000000000000000000000000000000000000e0");
+        let router = address!("0x00000000000000000000000000000000000000a1");
+        let token = address!("0x00000000000000000000000000000000000000b2");
+        let pool = address!("0x00000000000000000000000000000000000000c3");
+
+        let entry =
+            |id: usize, parent_id: Option<usize>, code_address, gas_used, reverted| TraceEntry {
+                id,
+                parent_id,
+                depth: parent_id.map_or(0, |_| 1),
+                call_type: CallType::Call(CallScheme::Call),
+                caller: eoa,
+                target: code_address,
+                code_address,
+                input: Bytes::new(),
+                value: U256::ZERO,
+                gas_limit: 0,
+                gas_used,
+                result: Some(if reverted {
+                    CallResult::Revert { output: Bytes::new(), result: InstructionResult::Revert }
+                } else {
+                    CallResult::Success { output: Bytes::new(), result: InstructionResult::Stop }
+                }),
+                created_contract: false,
+                create_scheme: None,
+                bytecode: None,
+                target_label: None,
+                self_destruct: None,
+                destroyed: false,
+                readonly: false,
+                subtree_value: U256::ZERO,
+                events: vec![],
+                first_snapshot_id: (id > 0).then_some(id * 10),
+            };
+
+        // eoa -> router -> {token, pool -> token (reverted), token}
+        let mut trace = Trace::new();
+        trace.push(entry(0, None, router, 1000, false));
+        trace.push(entry(1, Some(0), token, 100, false));
+        trace.push(entry(2, Some(0), pool, 500, false));
+        trace.push(entry(3, Some(2), token, 50, true));
+        trace.push(entry(4, Some(0), token, 200, false));
+
+        let edges = build_call_graph(&trace, &HashSet::new());
+        let summary: Vec<_> =
+            edges.iter().map(|e| (e.from, e.to, e.calls, e.failed, e.gas_used)).collect();
+        assert_eq!(
+            summary,
+            [
+                (eoa, router, 1, 0, 1000),
+                (router, pool, 1, 0, 500),
+                (router, token, 2, 0, 300),
+                (pool, token, 1, 1, 50),
+            ]
+        );
+        assert_eq!(edges[2].first_snapshot_id, Some(10));
+
+        // Hidden entries are left out of the graph
+        let edges = build_call_graph(&trace, &HashSet::from([2, 3]));
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[1].calls, 2);
+    }
+}
@@ -14,7 +14,9 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use alloy_primitives::U256;
+use alloy_dyn_abi::DynSolValue;
+use alloy_primitives::{hex, Bytes, LogData, U256};
+use revm::interpreter::InstructionResult;
 
 /// Format a U256 value with hex, optional decimal, and ASCII decode
 pub fn format_value_with_decode(value: &U256) -> String {
@@ -72,3 +74,231 @@ pub fn format_bytes_with_decode(bytes: &[u8]) -> String {
     }
     lines.join("\n")
 }
+
+/// Summarize an event that could not be decoded with a known ABI
+pub fn format_undecoded_event(event: &LogData) -> String {
+    match event.topics().first() {
+        None => format!("Anonymous event ({} bytes data)", event.data.len()),
+        Some(signature) => format!(
+            "Event 0x{}... ({} indexed, {} bytes data)",
+            hex::encode(&signature.as_slice()[..4]),
+            event.topics().len() - 1,
+            event.data.len()
+        ),
+    }
+}
+
+/// Decode revert reason from output data
+pub fn decode_revert_reason(output: &Bytes) -> String {
+    if output.is_empty() {
+        return "(empty revert)".to_string();
+    }
+
+    // Check if it's a standard Error(string) revert (0x08c379a0)
+    if output.len() >= 4 && output.starts_with(&[0x08, 0xc3, 0x79, 0xa0]) {
+        // Try to decode the string from Error(string) signature
+        if let Ok(DynSolValue::String(reason)) =
+            alloy_dyn_abi::DynSolType::String.abi_decode(&output[4..])
+        {
+            return format!("\"{reason}\"");
+        }
+    }
+
+    // Check if it's a Panic(uint256) revert (0x4e487b71)
+    if output.len() >= 4 && output.starts_with(&[0x4e, 0x48, 0x7b, 0x71]) {
+        if let Ok(DynSolValue::Uint(panic_code, _)) =
+            alloy_dyn_abi::DynSolType::Uint(256).abi_decode(&output[4..])
+        {
+            let panic_reason = match panic_code.to_string().as_str() {
+                "1" => "assertion failed",
+                "17" => "arithmetic overflow/underflow",
+                "18" => "division by zero",
+                "33" => "enum conversion error",
+                "34" => "invalid storage byte array access",
+                "49" => "pop() on empty array",
+                "50" => "array index out of bounds",
+                "65" => "memory allocation overflow",
+                "81" => "zero initialization of invalid type",
+                _ => "unknown panic",
+            };
+            return format!("Panic({panic_code}: {panic_reason})");
+        }
+    }
+
+    // Fallback: show hex data
+    if output.len() <= 32 {
+        format!("0x{}", hex::encode(output))
+    } else {
+        format!("0x{}...({} bytes)", hex::encode(&output[..8]), output.len())
+    }
+}
+
+/// Format InstructionResult with context
+pub fn format_instruction_result(result: InstructionResult, output: &Bytes) -> String {
+    match result {
+        InstructionResult::Stop => "stop".to_string(),
+        InstructionResult::Return => "return".to_string(),
+        InstructionResult::SelfDestruct => "selfdestruct".to_string(),
+        InstructionResult::Revert => {
+            // This shouldn't happen in Error variant, but handle it
+            decode_revert_reason(output)
+        }
+        InstructionResult::CallTooDeep => "call stack too deep".to_string(),
+        InstructionResult::OutOfFunds => "insufficient funds".to_string(),
+        InstructionResult::InvalidJump => "invalid jump destination".to_string(),
+        InstructionResult::StackOverflow => "stack overflow".to_string(),
+        InstructionResult::StackUnderflow => "stack underflow".to_string(),
+        InstructionResult::OutOfGas => "out of gas".to_string(),
+        InstructionResult::MemoryOOG => "out of gas (memory)".to_string(),
+        InstructionResult::MemoryLimitOOG => "memory limit exceeded".to_string(),
+        InstructionResult::PrecompileOOG => "precompile out of gas".to_string(),
+        InstructionResult::InvalidOperandOOG => "invalid operand (OOG)".to_string(),
+        InstructionResult::OpcodeNotFound => "opcode not found".to_string(),
+        InstructionResult::CreateInitCodeSizeLimit => "init code size limit".to_string(),
+        InstructionResult::CreateContractSizeLimit => "contract size limit".to_string(),
+        InstructionResult::OverflowPayment => "payment overflow".to_string(),
+        InstructionResult::StateChangeDuringStaticCall => "state change in static call".to_string(),
+        InstructionResult::CallNotAllowedInsideStatic => "call not allowed in static".to_string(),
+        InstructionResult::OutOfOffset => "out of offset".to_string(),
+        InstructionResult::CreateCollision => "create collision".to_string(),
+        InstructionResult::FatalExternalError => "fatal external error".to_string(),
+        _ => format!("unknown error ({result:?})"),
+    }
+}
+
+/// Score how well `candidate` matches `query` as a fuzzy (subsequence) match
+///
+/// Matching is case-insensitive. Consecutive characters and characters at the start
+/// of a path segment or word score higher. Returns `None` if `query` is not a
+/// subsequence of `candidate`.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let mut score = 0i64;
+    let mut query_idx = 0;
+    let mut prev_match: Option<usize> = None;
+    let mut prev_char: Option<char> = None;
+
+    for (idx, ch) in candidate.chars().enumerate() {
+        if query_idx == query.len() {
+            break;
+        }
+
+        if ch.to_lowercase().eq(query[query_idx].to_lowercase()) {
+            score += 1;
+            if prev_match.is_some_and(|prev| prev + 1 == idx) {
+                // Consecutive match
+                score += 8;
+            }
+            if prev_char.is_none_or(|c| matches!(c, '/' | '_' | '-' | '.' | ' ')) {
+                // Start of a path segment or word
+                score += 6;
+            }
+            if let Some(prev) = prev_match {
+                // Penalize gaps between matches
+                score -= (idx - prev - 1).min(10) as i64;
+            }
+            prev_match = Some(idx);
+            query_idx += 1;
+        }
+        prev_char = Some(ch);
+    }
+
+    if query_idx < query.len() {
+        return None;
+    }
+
+    // Prefer shorter candidates on ties
+    Some(score * 100 - candidate.chars().count() as i64)
+}
+
+/// Get the longest common prefix of the given strings
+pub fn common_prefix<S: AsRef<str>>(strings: &[S]) -> &str {
+    let Some(first) = strings.first().map(AsRef::as_ref) else {
+        return "";
+    };
+
+    let mut len = first.len();
+    for other in &strings[1..] {
+        let common = first.char_indices().zip(other.as_ref().chars()).find(|((_, a), b)| a != b);
+        if let Some(((idx, _), _)) = common {
+            len = len.min(idx);
+        } else {
+            len = len.min(other.as_ref().len());
+        }
+    }
+    &first[..len]
+}
+
+/// Whether an expression has an opening bracket that is not closed yet
+///
+/// Brackets within string literals are ignored.
+pub fn has_unclosed_bracket(expr: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for ch in expr.chars() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == q {
+                quote = None;
+            }
+            continue;
+        }
+
+        match ch {
+            '"' | '\'' => quote = Some(ch),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "src/Vault.sol"), Some(0));
+        assert!(fuzzy_score("xyz", "src/Vault.sol").is_none());
+        assert!(fuzzy_score("VAULT", "src/Vault.sol").is_some());
+
+        // Segment starts and consecutive matches rank higher
+        let vault = fuzzy_score("vault", "src/Vault.sol").unwrap();
+        let scattered = fuzzy_score("vault", "src/v_a_u_l_t_old.sol").unwrap();
+        assert!(vault > scattered);
+
+        // Shorter candidates win ties
+        let short = fuzzy_score("vault", "Vault.sol").unwrap();
+        let long = fuzzy_score("vault", "Vault.t.sol").unwrap();
+        assert!(short > long);
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        assert_eq!(common_prefix::<&str>(&[]), "");
+        assert_eq!(common_prefix(&["balanceOf"]), "balanceOf");
+        assert_eq!(common_prefix(&["balance", "balanceOf", "balances"]), "balance");
+        assert_eq!(common_prefix(&["owner", "totalSupply"]), "");
+    }
+
+    #[test]
+    fn test_has_unclosed_bracket() {
+        assert!(!has_unclosed_bracket("$ balances[owner]"));
+        assert!(has_unclosed_bracket("$ keccak256(abi.encode(owner,"));
+        assert!(has_unclosed_bracket("$ balances[\n  owner"));
+        assert!(!has_unclosed_bracket("$ name == \"(\""));
+        assert!(!has_unclosed_bracket("$ a)"));
+    }
+}
@@ -17,16 +17,24 @@
 //! RPC client for communicating with the debug server
 //!
 //! This module provides a client for making JSON-RPC calls to the debug server.
+//! Servers are reached over HTTP(S), or over a unix domain socket when the server URL
+//! has the form `unix:///path/to/socket`. Servers requiring authentication are called
+//! with a bearer token.
+
+#[cfg(unix)]
+mod unix;
 
 use crate::ui::spinner::Spinner;
 use alloy_json_abi::JsonAbi;
 use alloy_primitives::{Address, Bytes, U256};
-use edb_common::types::{Breakpoint, CallableAbiInfo, Code, EdbSolValue, SnapshotInfo, Trace};
-use eyre::Result;
-use jsonrpsee::{
-    core::client::ClientT,
-    http_client::{HttpClient, HttpClientBuilder},
+use edb_common::{
+    types::{
+        AddressLabel, Breakpoint, BridgeMessage, CallableAbiInfo, CapturedLog, Code, EdbSolValue,
+        Finding, GasHeatmap, SnapshotInfo, SourceMapInfo, TokenInfo, TracePage, TraceQuery,
+    },
+    EngineRpcClient, PreparationStatus, RpcClientOptions,
 };
+use eyre::Result;
 use serde_json::Value;
 use std::{
     collections::HashMap,
@@ -54,10 +62,60 @@ macro_rules! rpc_params {
         ]
     };
 }
+/// Timeout of RPC requests, unless configured otherwise
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of trace entries fetched per `edb_getTrace` request
+pub const TRACE_PAGE_SIZE: usize = 1000;
+
+/// Transport carrying the JSON-RPC requests of a client
+#[derive(Debug)]
+enum Transport {
+    /// JSON-RPC over HTTP(S)
+    Http(EngineRpcClient),
+    /// JSON-RPC over HTTP on a unix domain socket
+    #[cfg(unix)]
+    Unix(unix::UnixSocketClient),
+}
+
+impl Transport {
+    /// Create the transport for a server URL
+    fn connect(server_url: &str, mut options: RpcClientOptions) -> Result<Self> {
+        let request_timeout = *options.timeout.get_or_insert(DEFAULT_REQUEST_TIMEOUT);
+
+        #[cfg(unix)]
+        if let Some(path) = server_url.strip_prefix("unix://") {
+            let client = unix::UnixSocketClient::new(path, request_timeout)
+                .with_auth_token(options.auth_token);
+            return Ok(Self::Unix(client));
+        }
+
+        Ok(Self::Http(EngineRpcClient::new(server_url, &options)?))
+    }
+
+    /// Make a JSON-RPC call and return its result
+    async fn request(&self, method: &str, params: Vec<Value>) -> Result<Value> {
+        match self {
+            Self::Http(client) => client.request(method, Value::Array(params)).await,
+            #[cfg(unix)]
+            Self::Unix(client) => client.request(method, params).await,
+        }
+    }
+
+    /// Fetch a JSON document of the server, e.g., `/status`
+    async fn get(&self, path: &str) -> Result<Value> {
+        match self {
+            Self::Http(client) => client.get(path).await,
+            #[cfg(unix)]
+            Self::Unix(client) => client.get(path).await,
+        }
+    }
+}
+
 /// RPC client for debug server communication
 #[derive(Debug)]
 pub struct RpcClient {
-    client: HttpClient,
+    client: Transport,
     server_url: String,
     /// Shared spinner state for loading indication
     spinner: Arc<RwLock<Spinner>>,
@@ -66,9 +124,13 @@ pub struct RpcClient {
 impl RpcClient {
     /// Create a new RPC client
     pub async fn new(server_url: &str) -> Result<Self> {
-        let client = HttpClientBuilder::default()
-            .request_timeout(Duration::from_secs(30))
-            .build(server_url)?;
+        Self::with_options(server_url, RpcClientOptions::default()).await
+    }
+
+    /// Create a new RPC client for a server requiring a bearer token or a custom CA
+    /// certificate
+    pub async fn with_options(server_url: &str, options: RpcClientOptions) -> Result<Self> {
+        let client = Transport::connect(server_url, options)?;
 
         debug!("Created RPC client for: {}", server_url);
         Ok(Self {
@@ -82,19 +144,19 @@ impl RpcClient {
     pub async fn test_connection(server_url: &str) -> Result<()> {
         debug!("Testing connection to: {}", server_url);
 
-        let client = HttpClientBuilder::default()
-            .request_timeout(Duration::from_secs(5))
-            .build(server_url)?;
+        let options =
+            RpcClientOptions { timeout: Some(Duration::from_secs(5)), ..Default::default() };
+        let client = Transport::connect(server_url, options)?;
 
         // Try a simple health check or method call
-        match client.request::<Value, _>("debug.getSnapshotCount", rpc_params!()).await {
+        match client.request("debug.getSnapshotCount", rpc_params!()).await {
             Ok(_) => {
                 debug!("Connection test successful for: {}", server_url);
                 Ok(())
             }
             Err(e) => {
                 debug!("Connection test failed for {}: {}", server_url, e);
-                Err(e.into())
+                Err(e)
             }
         }
     }
@@ -116,7 +178,7 @@ impl RpcClient {
             }
             Err(e) => {
                 error!("{} failed: {}", operation_name, e);
-                Err(e.into())
+                Err(e)
             }
         };
 
@@ -165,24 +227,37 @@ impl RpcClient {
     pub async fn health_check(&self) -> Result<Value> {
         debug!("Checking server health");
 
-        // Make a simple HTTP GET request to the health endpoint
-        let health_url = format!("{}/health", self.server_url.trim_end_matches('/'));
-
-        let response = reqwest::get(&health_url).await?;
-        let health_data: Value = response.json().await?;
+        let health_data = self.client.get("/health").await?;
 
         debug!("Server health: {:?}", health_data);
         Ok(health_data)
     }
+
+    /// Get the preparation status of the transaction served at the server URL
+    pub async fn preparation_status(&self) -> Result<PreparationStatus> {
+        let status = self.client.get("/status").await?;
+
+        serde_json::from_value(status)
+            .map_err(|e| eyre::eyre!("Failed to parse preparation status: {}", e))
+    }
 }
 
 // Rpc methods
 impl RpcClient {
-    /// Get execution trace
-    pub async fn get_trace(&self) -> Result<Trace> {
+    /// Get a page of at most [`TRACE_PAGE_SIZE`] trace entries, starting at an entry id
+    ///
+    /// Large traces are fetched page by page as they are displayed, so that they do not
+    /// stall the server or delay the start of the TUI.
+    pub async fn get_trace_page(&self, cursor: usize) -> Result<TracePage> {
+        let query = TraceQuery { cursor, limit: Some(TRACE_PAGE_SIZE), ..Default::default() };
         let value = self
-            .request_with_spinner("edb_getTrace", rpc_params!(), "Fetching execution trace")
+            .request_with_spinner(
+                "edb_getTrace",
+                rpc_params!(&query),
+                &format!("Fetching execution trace (from entry {cursor})"),
+            )
             .await?;
+
         serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse trace: {}", e))
     }
 
@@ -218,6 +293,33 @@ impl RpcClient {
             .map_err(|e| eyre::eyre!("Failed to parse callable ABI: {}", e))
     }
 
+    /// Get the well-known or ENS name of an address
+    pub async fn get_address_label(&self, address: Address) -> Result<Option<AddressLabel>> {
+        let value = self
+            .request_with_spinner(
+                "edb_getAddressLabel",
+                rpc_params!(address),
+                &format!("Resolving label for {address}"),
+            )
+            .await?;
+
+        serde_json::from_value(value)
+            .map_err(|e| eyre::eyre!("Failed to parse address label: {}", e))
+    }
+
+    /// Get the symbol and decimals of an ERC-20 token
+    pub async fn get_token_info(&self, address: Address) -> Result<Option<TokenInfo>> {
+        let value = self
+            .request_with_spinner(
+                "edb_getTokenInfo",
+                rpc_params!(address),
+                &format!("Fetching token info for {address}"),
+            )
+            .await?;
+
+        serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse token info: {}", e))
+    }
+
     /// Get contract constructor arguments
     pub async fn get_constructor_args(&self, address: Address) -> Result<Option<Bytes>> {
         let value = self
@@ -286,6 +388,32 @@ impl RpcClient {
         serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse code: {}", e))
     }
 
+    /// Get the instruction-level source map of a verified contract
+    pub async fn get_source_map(&self, address: Address) -> Result<Option<SourceMapInfo>> {
+        let value = self
+            .request_with_spinner(
+                "edb_getSourceMap",
+                rpc_params!(address),
+                &format!("Getting source map for address {address}"),
+            )
+            .await?;
+
+        serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse source map: {}", e))
+    }
+
+    /// Get the gas spent per source line and per instruction of a contract
+    pub async fn get_gas_heatmap(&self, address: Address) -> Result<GasHeatmap> {
+        let value = self
+            .request_with_spinner(
+                "edb_getGasHeatmap",
+                rpc_params!(address),
+                &format!("Getting gas heatmap for address {address}"),
+            )
+            .await?;
+
+        serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse gas heatmap: {}", e))
+    }
+
     /// Get next call
     pub async fn get_next_call(&self, snapshot_id: usize) -> Result<usize> {
         let value = self
@@ -374,6 +502,37 @@ impl RpcClient {
         serde_json::from_value(value)
             .map_err(|e| eyre::eyre!("Failed to parse breakpoint hits: {}", e))
     }
+
+    /// Get findings detected by the engine
+    pub async fn get_findings(&self) -> Result<Vec<Finding>> {
+        let value = self
+            .request_with_spinner("edb_getFindings", rpc_params!(), "Fetching findings")
+            .await?;
+
+        serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse findings: {}", e))
+    }
+
+    /// Get the messages sent through canonical bridges, with their correlated transactions
+    pub async fn get_bridge_messages(&self) -> Result<Vec<BridgeMessage>> {
+        let value = self
+            .request_with_spinner(
+                "edb_getBridgeMessages",
+                rpc_params!(),
+                "Fetching bridge messages",
+            )
+            .await?;
+
+        serde_json::from_value(value)
+            .map_err(|e| eyre::eyre!("Failed to parse bridge messages: {}", e))
+    }
+
+    /// Get the events emitted during the transaction, in emission order
+    pub async fn get_logs(&self) -> Result<Vec<CapturedLog>> {
+        let value =
+            self.request_with_spinner("edb_getLogs", rpc_params!(), "Fetching event logs").await?;
+
+        serde_json::from_value(value).map_err(|e| eyre::eyre!("Failed to parse event logs: {}", e))
+    }
 }
 
 #[cfg(test)]
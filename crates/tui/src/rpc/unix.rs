@@ -0,0 +1,155 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Unix domain socket transport for the RPC client
+//!
+//! The engine can serve its JSON-RPC API on a unix domain socket instead of a TCP port.
+//! This module implements the minimal HTTP/1.1 exchange needed to talk to such a server:
+//! each request opens a fresh connection, sends a single request, and reads the response
+//! until the server closes the connection.
+
+use edb_common::into_rpc_result;
+use eyre::{bail, eyre, Result};
+use serde_json::{json, Value};
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::UnixStream,
+    time::timeout,
+};
+
+/// JSON-RPC client connected to a server over a unix domain socket
+#[derive(Debug)]
+pub(crate) struct UnixSocketClient {
+    /// Path of the server socket
+    path: PathBuf,
+    /// Timeout of a single request
+    request_timeout: Duration,
+    /// Id of the next JSON-RPC request
+    next_id: AtomicU64,
+    /// Bearer token sent with every request, if the server requires one
+    auth_token: Option<String>,
+}
+
+impl UnixSocketClient {
+    /// Create a client for the socket at `path`
+    pub(crate) fn new(path: impl Into<PathBuf>, request_timeout: Duration) -> Self {
+        Self { path: path.into(), request_timeout, next_id: AtomicU64::new(1), auth_token: None }
+    }
+
+    /// Send the given bearer token with every request
+    pub(crate) fn with_auth_token(mut self, auth_token: Option<String>) -> Self {
+        self.auth_token = auth_token;
+        self
+    }
+
+    /// Make a JSON-RPC call and return its result
+    pub(crate) async fn request(&self, method: &str, params: Vec<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+
+        let body = self.send("POST", "/", Some(serde_json::to_vec(&request)?)).await?;
+        into_rpc_result(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetch a JSON document, e.g., `/health`
+    pub(crate) async fn get(&self, target: &str) -> Result<Value> {
+        let body = self.send("GET", target, None).await?;
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Send a single HTTP request and return the response body
+    async fn send(&self, method: &str, target: &str, body: Option<Vec<u8>>) -> Result<Vec<u8>> {
+        let exchange = async {
+            let mut stream = UnixStream::connect(&self.path).await?;
+
+            let body = body.unwrap_or_default();
+            let authorization = self
+                .auth_token
+                .as_ref()
+                .map(|token| format!("Authorization: Bearer {token}\r\n"))
+                .unwrap_or_default();
+            let head = format!(
+                "{method} {target} HTTP/1.1\r\nHost: localhost\r\n{authorization}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(&body).await?;
+
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).await?;
+            Ok::<_, eyre::Report>(response)
+        };
+
+        let response = timeout(self.request_timeout, exchange).await.map_err(|_| {
+            eyre!("Request to {} timed out after {:?}", self.path.display(), self.request_timeout)
+        })??;
+        parse_response(&response).map(<[u8]>::to_vec)
+    }
+}
+
+/// Extract the body of an HTTP/1.1 response, failing on non-2xx statuses
+fn parse_response(response: &[u8]) -> Result<&[u8]> {
+    let header_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| eyre!("Malformed HTTP response"))?;
+    let head = std::str::from_utf8(&response[..header_end])?;
+    let body = &response[header_end + 4..];
+
+    let status_line = head.lines().next().unwrap_or_default();
+    let status: u16 = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| eyre!("Malformed HTTP status line: {status_line}"))?;
+    if !(200..300).contains(&status) {
+        bail!("HTTP request failed: {status_line}");
+    }
+
+    // Responses of the RPC server always carry their length, so the body is complete
+    let chunked = head.lines().any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.eq_ignore_ascii_case("transfer-encoding") && value.trim() == "chunked"
+        })
+    });
+    if chunked {
+        bail!("Chunked HTTP responses are not supported");
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response() {
+        let response =
+            b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\n\r\n{}";
+        assert_eq!(parse_response(response).unwrap(), b"{}");
+
+        let unauthorized = b"HTTP/1.1 401 Unauthorized\r\ncontent-length: 0\r\n\r\n";
+        assert!(parse_response(unauthorized).is_err());
+
+        assert!(parse_response(b"garbage").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Mock engine RPC server and helpers shared by the panel tests
+//!
+//! Panels are tested against a [`DataManager`] connected to a [`MockEngine`], which
+//! answers the JSON-RPC methods the managers call with canned results, and rendered on
+//! a [`TestBackend`] to assert on the displayed text.
+
+use std::{collections::HashMap, io::ErrorKind, path::PathBuf, sync::Arc};
+
+use alloy_primitives::Address;
+use edb_common::types::{
+    EdbSolValue, ExecutionFrameId, HookSnapshotInfoDetail, SnapshotInfo, Trace, TraceQuery,
+};
+use ratatui::{
+    backend::TestBackend,
+    buffer::{Buffer, Cell},
+    Terminal,
+};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::RwLock,
+};
+
+use crate::{
+    data::{
+        keymap::KeyMap,
+        manager::{core::ManagerCore, execution::ExecutionManager, resolve::Resolver},
+        theme::Theme,
+        watcher::Watcher,
+        DataManager,
+    },
+    panels::PanelTr,
+    RpcClient,
+};
+
+/// Whether a test serving a [`MockEngine`] should be skipped, because the environment
+/// does not allow binding loopback ports
+pub(crate) async fn skip_if_loopback_binds_restricted(test_name: &str) -> bool {
+    match TcpListener::bind("127.0.0.1:0").await {
+        Ok(listener) => {
+            drop(listener);
+            false
+        }
+        Err(error)
+            if matches!(error.kind(), ErrorKind::PermissionDenied)
+                || error.raw_os_error() == Some(1) =>
+        {
+            tracing::info!("Skipping {test_name} because loopback binds are restricted: {error}");
+            true
+        }
+        Err(error) => panic!("Failed to probe loopback bind availability: {error}"),
+    }
+}
+
+/// Result of a JSON-RPC method, computed from its parameters
+type MethodResult = Arc<dyn Fn(&[Value]) -> Value + Send + Sync>;
+
+/// Engine RPC server answering JSON-RPC methods with canned results
+pub(crate) struct MockEngine {
+    methods: HashMap<&'static str, MethodResult>,
+}
+
+impl MockEngine {
+    /// Create an engine serving an empty transaction with the given number of snapshots
+    pub(crate) fn new(snapshot_count: usize) -> Self {
+        Self { methods: HashMap::new() }
+            .with_result("edb_getSnapshotCount", json!(snapshot_count))
+            .with_trace(Trace::default())
+            .with_result("edb_getFindings", json!([]))
+            .with_result("edb_getBridgeMessages", json!([]))
+            .with_result("edb_getLogs", json!([]))
+    }
+
+    /// Serve the given trace through `edb_getTrace`
+    pub(crate) fn with_trace(self, trace: Trace) -> Self {
+        self.with_method("edb_getTrace", move |params| {
+            let query: TraceQuery = params
+                .first()
+                .map(|query| serde_json::from_value(query.clone()).unwrap())
+                .unwrap_or_default();
+            serde_json::to_value(trace.page(&query)).unwrap()
+        })
+    }
+
+    /// Serve the given snapshots through `edb_getSnapshotCount` and `edb_getSnapshotInfo`
+    pub(crate) fn with_snapshots(self, snapshots: Vec<SnapshotInfo>) -> Self {
+        self.with_result("edb_getSnapshotCount", json!(snapshots.len())).with_method(
+            "edb_getSnapshotInfo",
+            move |params| {
+                let id = params[0].as_u64().unwrap() as usize;
+                serde_json::to_value(&snapshots[id]).unwrap()
+            },
+        )
+    }
+
+    /// Answer a method with a fixed result
+    pub(crate) fn with_result(self, method: &'static str, result: Value) -> Self {
+        self.with_method(method, move |_| result.clone())
+    }
+
+    /// Answer a method with a result computed from its parameters
+    pub(crate) fn with_method(
+        mut self,
+        method: &'static str,
+        result: impl Fn(&[Value]) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.methods.insert(method, Arc::new(result));
+        self
+    }
+
+    /// Serve the engine on an ephemeral port and connect a data manager to it
+    pub(crate) async fn data_manager(self) -> DataManager {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let methods = Arc::new(self.methods);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(stream, methods.clone()));
+            }
+        });
+
+        let rpc_client = Arc::new(RpcClient::new(&url).await.unwrap());
+        let exec_core = Arc::new(RwLock::new(ManagerCore::new(rpc_client.clone()).await.unwrap()));
+        let resolver_core = Arc::new(RwLock::new(ManagerCore::new(rpc_client).await.unwrap()));
+
+        // The watcher and keymap are not loaded from the config file of the user
+        DataManager {
+            execution: ExecutionManager::new(exec_core).await,
+            resolver: Resolver::new(resolver_core).await,
+            theme: Theme::default(),
+            watcher: Watcher::default(),
+            keymap: KeyMap::default(),
+        }
+    }
+}
+
+/// Source-level snapshot `id` out of `count` snapshots of a single frame, with the given
+/// local variables
+pub(crate) fn hook_snapshot(
+    id: usize,
+    count: usize,
+    locals: Vec<(&str, EdbSolValue)>,
+) -> SnapshotInfo {
+    let frame_id = ExecutionFrameId(0, 0);
+    let locals =
+        locals.into_iter().map(|(name, value)| (name.to_string(), Some(Arc::new(value)))).collect();
+
+    SnapshotInfo {
+        id,
+        frame_id,
+        next_id: (id + 1).min(count - 1),
+        prev_id: id.saturating_sub(1),
+        detail: HookSnapshotInfoDetail {
+            id,
+            frame_id,
+            path: PathBuf::from("Counter.sol"),
+            offset: 0,
+            length: 0,
+            locals,
+            state_variables: HashMap::new(),
+            modifier: None,
+            hints: HashMap::new(),
+        }
+        .into(),
+        target_address: Address::ZERO,
+        bytecode_address: Address::ZERO,
+        delegated: false,
+        watches: Default::default(),
+    }
+}
+
+/// Answer a single HTTP request carrying a JSON-RPC request, then close the connection
+async fn serve_connection(
+    mut stream: TcpStream,
+    methods: Arc<HashMap<&'static str, MethodResult>>,
+) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let (header_len, content_length) = loop {
+        let n = stream.read(&mut buf).await.unwrap_or(0);
+        if n == 0 {
+            return;
+        }
+        request.extend_from_slice(&buf[..n]);
+
+        let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") else {
+            continue;
+        };
+        let headers = String::from_utf8_lossy(&request[..end]).to_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0);
+        break (end + 4, content_length);
+    };
+    while request.len() < header_len + content_length {
+        let n = stream.read(&mut buf).await.unwrap_or(0);
+        if n == 0 {
+            return;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request: Value =
+        serde_json::from_slice(&request[header_len..header_len + content_length]).unwrap();
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = request["params"].as_array().cloned().unwrap_or_default();
+    let response = match methods.get(method) {
+        Some(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result(&params) }),
+        None => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": -32601, "message": format!("Method not found: {method}") },
+        }),
+    };
+
+    let body = response.to_string();
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\
+         connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+/// Process the requests queued by the managers until no new data arrives
+pub(crate) async fn settle(dm: &mut DataManager) {
+    for _ in 0..5 {
+        dm.update_pending_requests().await.unwrap();
+        dm.get_execution_core().write().await.process_pending_requests().await.unwrap();
+        dm.get_resolver_core().write().await.process_pending_requests().await.unwrap();
+        dm.process_core_updates().unwrap();
+    }
+}
+
+/// Draw a panel on a terminal of the given size and return the displayed lines
+pub(crate) fn draw(
+    panel: &mut dyn PanelTr,
+    dm: &mut DataManager,
+    width: u16,
+    height: u16,
+) -> Vec<String> {
+    let buffer = draw_buffer(panel, dm, width, height);
+    buffer
+        .content
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+        .collect()
+}
+
+/// Draw a panel on a terminal of the given size and return the drawn cells
+pub(crate) fn draw_buffer(
+    panel: &mut dyn PanelTr,
+    dm: &mut DataManager,
+    width: u16,
+    height: u16,
+) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|frame| panel.render(frame, frame.area(), dm)).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// First cell of the first occurrence of `text` in a drawn buffer
+pub(crate) fn find_cell<'a>(buffer: &'a Buffer, text: &str) -> Option<&'a Cell> {
+    let width = buffer.area.width as usize;
+    buffer.content.chunks(width).find_map(|row| {
+        (0..width).find_map(|start| {
+            let drawn: String = row[start..].iter().map(|cell| cell.symbol()).collect();
+            drawn.starts_with(text).then(|| &row[start])
+        })
+    })
+}
+
+/// Draw a panel until the data it requests has been fetched and return the displayed lines
+pub(crate) async fn render(
+    panel: &mut dyn PanelTr,
+    dm: &mut DataManager,
+    width: u16,
+    height: u16,
+) -> Vec<String> {
+    // Panels fetch their data step by step, each frame requesting what the previous one
+    // found missing
+    for _ in 0..5 {
+        draw(panel, dm, width, height);
+        settle(dm).await;
+    }
+    draw(panel, dm, width, height)
+}
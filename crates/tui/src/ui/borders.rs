@@ -196,6 +196,21 @@ impl BorderPresets {
             .build()
     }
 
+    /// Events panel border - thick like the trace it shares the screen with
+    pub fn events(
+        focused: bool,
+        title: String,
+        focused_color: Color,
+        unfocused_color: Color,
+    ) -> Block<'static> {
+        EnhancedBorder::thick()
+            .focused(focused)
+            .title(title)
+            .focused_color(focused_color)
+            .unfocused_color(unfocused_color)
+            .build()
+    }
+
     /// Display panel border - rounded standard
     pub fn display(
         focused: bool,
@@ -0,0 +1,186 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! System clipboard integration
+//!
+//! Text is copied with the platform clipboard tool (`pbcopy`, `wl-copy`, `xclip`, `xsel`
+//! or `clip.exe`) when one is available. In SSH sessions, or when no tool works, the text
+//! is sent to the terminal emulator as an OSC 52 escape sequence instead, which most
+//! terminals (and tmux with `set-clipboard on`) forward to the local clipboard.
+
+use std::{
+    env, fmt,
+    io::{self, Write},
+    process::{Command, Stdio},
+};
+
+use eyre::{bail, eyre, Result};
+use tracing::debug;
+
+/// Maximum size of the text sent through OSC 52, as many terminals drop larger payloads
+const MAX_OSC52_BYTES: usize = 74_994;
+
+/// How text reached the clipboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipboardMethod {
+    /// Through a platform clipboard tool
+    Native(&'static str),
+    /// Through the OSC 52 escape sequence of the terminal
+    Osc52,
+}
+
+impl fmt::Display for ClipboardMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Native(program) => write!(f, "{program}"),
+            Self::Osc52 => write!(f, "OSC 52"),
+        }
+    }
+}
+
+/// Copy text to the system clipboard
+pub fn copy(text: &str) -> Result<ClipboardMethod> {
+    if !is_ssh_session() {
+        for (program, args) in native_commands() {
+            match copy_with(program, args, text) {
+                Ok(()) => return Ok(ClipboardMethod::Native(program)),
+                Err(e) => debug!("Failed to copy with {program}: {e}"),
+            }
+        }
+    }
+
+    copy_osc52(text)?;
+    Ok(ClipboardMethod::Osc52)
+}
+
+/// Whether we run in an SSH session, where platform tools would copy on the remote host
+fn is_ssh_session() -> bool {
+    ["SSH_TTY", "SSH_CONNECTION", "SSH_CLIENT"].iter().any(|var| env::var_os(var).is_some())
+}
+
+/// Get the platform clipboard tools to try, in order
+fn native_commands() -> Vec<(&'static str, &'static [&'static str])> {
+    if cfg!(target_os = "macos") {
+        return vec![("pbcopy", &[])];
+    }
+    if cfg!(windows) {
+        return vec![("clip.exe", &[])];
+    }
+
+    let mut commands: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if env::var_os("WAYLAND_DISPLAY").is_some() {
+        commands.push(("wl-copy", &[]));
+    }
+    if env::var_os("DISPLAY").is_some() {
+        commands.push(("xclip", &["-selection", "clipboard"]));
+        commands.push(("xsel", &["--clipboard", "--input"]));
+    }
+    if env::var_os("WSL_DISTRO_NAME").is_some() {
+        commands.push(("clip.exe", &[]));
+    }
+    commands
+}
+
+/// Copy text by piping it into a clipboard tool
+fn copy_with(program: &str, args: &[&str], text: &str) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    // Close stdin once written, so that the tool knows the text is complete
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| eyre!("No stdin for {program}"))?
+        .write_all(text.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        bail!("{program} exited with {status}");
+    }
+    Ok(())
+}
+
+/// Copy text through the OSC 52 escape sequence
+fn copy_osc52(text: &str) -> Result<()> {
+    if text.len() > MAX_OSC52_BYTES {
+        bail!(
+            "Text is too large to copy through the terminal ({} bytes, at most {MAX_OSC52_BYTES})",
+            text.len()
+        );
+    }
+
+    let sequence = osc52_sequence(text, env::var_os("TMUX").is_some());
+    let mut stdout = io::stdout();
+    stdout.write_all(sequence.as_bytes())?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Build the OSC 52 sequence setting the clipboard, wrapped for tmux if needed
+fn osc52_sequence(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()));
+    if tmux {
+        // tmux passes through sequences wrapped in a DCS, with ESC doubled
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Encode bytes as standard base64 with padding
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], chunk.get(1).copied().unwrap_or(0), chunk.get(2).copied().unwrap_or(0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xff, 0xfe, 0xfd]), "//79");
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("0x1234", false), "\x1b]52;c;MHgxMjM0\x07");
+        assert_eq!(osc52_sequence("0x1234", true), "\x1bPtmux;\x1b\x1b]52;c;MHgxMjM0\x07\x1b\\");
+    }
+}
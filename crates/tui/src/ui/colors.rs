@@ -19,6 +19,7 @@
 //! This module provides comprehensive color schemes for the TUI,
 //! including multiple professional themes and semantic color mapping.
 
+use eyre::{bail, eyre, Result};
 use ratatui::style::Color;
 use serde::{Deserialize, Serialize};
 
@@ -257,6 +258,230 @@ impl Default for ColorScheme {
     }
 }
 
+impl ColorScheme {
+    /// Names of all colors, as used for custom themes in the config file
+    pub const KEYS: &'static [&'static str] = &[
+        "focused_border",
+        "unfocused_border",
+        "panel_bg",
+        "keyword_color",
+        "string_color",
+        "comment_color",
+        "number_color",
+        "identifier_color",
+        "operator_color",
+        "line_number",
+        "line_number_bg",
+        "syntax_keyword_color",
+        "syntax_type_color",
+        "syntax_string_color",
+        "syntax_number_color",
+        "syntax_comment_color",
+        "syntax_identifier_color",
+        "syntax_operator_color",
+        "syntax_punctuation_color",
+        "syntax_address_color",
+        "syntax_pragma_color",
+        "syntax_opcode_color",
+        "highlight_bg",
+        "highlight_fg",
+        "help_text_color",
+        "current_line_bg",
+        "current_line_fg",
+        "breakpoint_color",
+        "error_color",
+        "success_color",
+        "warning_color",
+        "info_color",
+        "call_color",
+        "return_color",
+        "revert_color",
+        "create_color",
+        "prompt_color",
+        "command_color",
+        "output_color",
+        "cursor_color",
+        "selection_bg",
+        "selection_fg",
+        "hover_color",
+        "accent_color",
+    ];
+
+    /// Get a mutable reference to a color by its config name
+    pub fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        let color = match key {
+            "focused_border" => &mut self.focused_border,
+            "unfocused_border" => &mut self.unfocused_border,
+            "panel_bg" => &mut self.panel_bg,
+            "keyword_color" => &mut self.keyword_color,
+            "string_color" => &mut self.string_color,
+            "comment_color" => &mut self.comment_color,
+            "number_color" => &mut self.number_color,
+            "identifier_color" => &mut self.identifier_color,
+            "operator_color" => &mut self.operator_color,
+            "line_number" => &mut self.line_number,
+            "line_number_bg" => &mut self.line_number_bg,
+            "syntax_keyword_color" => &mut self.syntax_keyword_color,
+            "syntax_type_color" => &mut self.syntax_type_color,
+            "syntax_string_color" => &mut self.syntax_string_color,
+            "syntax_number_color" => &mut self.syntax_number_color,
+            "syntax_comment_color" => &mut self.syntax_comment_color,
+            "syntax_identifier_color" => &mut self.syntax_identifier_color,
+            "syntax_operator_color" => &mut self.syntax_operator_color,
+            "syntax_punctuation_color" => &mut self.syntax_punctuation_color,
+            "syntax_address_color" => &mut self.syntax_address_color,
+            "syntax_pragma_color" => &mut self.syntax_pragma_color,
+            "syntax_opcode_color" => &mut self.syntax_opcode_color,
+            "highlight_bg" => &mut self.highlight_bg,
+            "highlight_fg" => &mut self.highlight_fg,
+            "help_text_color" => &mut self.help_text_color,
+            "current_line_bg" => &mut self.current_line_bg,
+            "current_line_fg" => &mut self.current_line_fg,
+            "breakpoint_color" => &mut self.breakpoint_color,
+            "error_color" => &mut self.error_color,
+            "success_color" => &mut self.success_color,
+            "warning_color" => &mut self.warning_color,
+            "info_color" => &mut self.info_color,
+            "call_color" => &mut self.call_color,
+            "return_color" => &mut self.return_color,
+            "revert_color" => &mut self.revert_color,
+            "create_color" => &mut self.create_color,
+            "prompt_color" => &mut self.prompt_color,
+            "command_color" => &mut self.command_color,
+            "output_color" => &mut self.output_color,
+            "cursor_color" => &mut self.cursor_color,
+            "selection_bg" => &mut self.selection_bg,
+            "selection_fg" => &mut self.selection_fg,
+            "hover_color" => &mut self.hover_color,
+            "accent_color" => &mut self.accent_color,
+            _ => return None,
+        };
+        Some(color)
+    }
+
+    /// Override colors by their config name, e.g. `syntax_keyword_color = "#ff79c6"`
+    pub fn with_overrides<'a>(
+        mut self,
+        colors: impl IntoIterator<Item = (&'a String, &'a String)>,
+    ) -> Result<Self> {
+        for (key, value) in colors {
+            let color = parse_color(value).map_err(|e| eyre!("Invalid color for `{key}`: {e}"))?;
+            *self.color_mut(key).ok_or_else(|| eyre!("Unknown color `{key}`"))? = color;
+        }
+        Ok(self)
+    }
+
+    /// Approximate every 24-bit color with the 256-color palette
+    ///
+    /// Used on terminals without truecolor support, which would otherwise render RGB
+    /// colors with arbitrary palette entries.
+    pub fn to_ansi256(mut self) -> Self {
+        for key in Self::KEYS {
+            if let Some(color) = self.color_mut(key) {
+                if let Color::Rgb(r, g, b) = *color {
+                    *color = Color::Indexed(rgb_to_ansi256(r, g, b));
+                }
+            }
+        }
+        self
+    }
+}
+
+/// Whether the terminal supports 24-bit colors, according to `COLORTERM`
+pub fn detect_truecolor() -> bool {
+    std::env::var("COLORTERM")
+        .map(|value| matches!(value.to_ascii_lowercase().as_str(), "truecolor" | "24bit"))
+        .unwrap_or(false)
+}
+
+/// Parse a color: a name (`light_blue`), `#rrggbb`, `#rgb`, `rgb(r, g, b)`, or a 256-color
+/// palette index
+pub fn parse_color(value: &str) -> Result<Color> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+
+    if let Some(hex) = lower.strip_prefix('#') {
+        let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+        let rgb = match hex.len() {
+            6 => channel(&hex[0..2]).zip(channel(&hex[2..4])).zip(channel(&hex[4..6])),
+            3 => {
+                let short = |i: usize| channel(&hex[i..i + 1]).map(|c| c * 17);
+                short(0).zip(short(1)).zip(short(2))
+            }
+            _ => None,
+        };
+        let ((r, g), b) = rgb.ok_or_else(|| eyre!("invalid hex color `{value}`"))?;
+        return Ok(Color::Rgb(r, g, b));
+    }
+
+    if let Some(args) = lower.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+        let channels = args
+            .split(',')
+            .map(|channel| channel.trim().parse::<u8>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| eyre!("invalid channel in `{value}`: {e}"))?;
+        let [r, g, b] = channels[..] else {
+            bail!("expected three channels in `{value}`");
+        };
+        return Ok(Color::Rgb(r, g, b));
+    }
+
+    if let Ok(index) = lower.parse::<u8>() {
+        return Ok(Color::Indexed(index));
+    }
+
+    let color = match lower.replace(['-', ' '], "_").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" | "light_gray" | "light_grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => bail!("unknown color `{value}`"),
+    };
+    Ok(color)
+}
+
+/// Map a 24-bit color to the closest entry of the 6x6x6 cube or the grayscale ramp of the
+/// 256-color palette
+fn rgb_to_ansi256(r: u8, g: u8, b: u8) -> u8 {
+    // Levels of the color cube
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let nearest_level =
+        |c: u8| (0..LEVELS.len()).min_by_key(|&i| (LEVELS[i] as i32 - c as i32).abs()).unwrap_or(0);
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // The grayscale ramp goes from 8 to 238 in steps of 10
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_step = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray = 8 + 10 * gray_step;
+    let gray_index = 232 + gray_step as usize;
+
+    if distance((gray, gray, gray)) < distance(cube) {
+        gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
 /// Cyberpunk dark theme with deep purples, electric blues, and neon greens
 fn cyberpunk_dark() -> ColorScheme {
     ColorScheme {
@@ -881,6 +1106,7 @@ fn vscode_light() -> ColorScheme {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::BTreeMap;
 
     #[test]
     fn test_theme_cycle() {
@@ -921,6 +1147,57 @@ mod tests {
         assert_eq!(Theme::VSCodeLight.display_name(), "VS Code Light");
     }
 
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff79c6").unwrap(), Color::Rgb(255, 121, 198));
+        assert_eq!(parse_color("#FFF").unwrap(), Color::Rgb(255, 255, 255));
+        assert_eq!(parse_color("rgb(10, 20, 30)").unwrap(), Color::Rgb(10, 20, 30));
+        assert_eq!(parse_color("208").unwrap(), Color::Indexed(208));
+        assert_eq!(parse_color("Light-Blue").unwrap(), Color::LightBlue);
+        assert_eq!(parse_color("dark_grey").unwrap(), Color::DarkGray);
+
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("rgb(1, 2)").is_err());
+        assert!(parse_color("rgb(1, 2, 300)").is_err());
+        assert!(parse_color("chartreuse").is_err());
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let colors: BTreeMap<String, String> = [
+            ("syntax_keyword_color".to_string(), "#ff0000".to_string()),
+            ("panel_bg".to_string(), "black".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let scheme = ColorScheme::default().with_overrides(&colors).unwrap();
+        assert_eq!(scheme.syntax_keyword_color, Color::Rgb(255, 0, 0));
+        assert_eq!(scheme.panel_bg, Color::Black);
+
+        let unknown: BTreeMap<String, String> =
+            [("keyword".to_string(), "red".to_string())].into_iter().collect();
+        assert!(ColorScheme::default().with_overrides(&unknown).is_err());
+
+        // Every key is addressable
+        let mut scheme = ColorScheme::default();
+        for key in ColorScheme::KEYS {
+            assert!(scheme.color_mut(key).is_some(), "missing color {key}");
+        }
+    }
+
+    #[test]
+    fn test_rgb_to_ansi256() {
+        assert_eq!(rgb_to_ansi256(0, 0, 0), 16);
+        assert_eq!(rgb_to_ansi256(255, 255, 255), 231);
+        assert_eq!(rgb_to_ansi256(255, 0, 0), 196);
+        assert_eq!(rgb_to_ansi256(128, 128, 128), 244);
+
+        let mut scheme = ColorScheme::default().to_ansi256();
+        for key in ColorScheme::KEYS {
+            assert!(!matches!(scheme.color_mut(key).copied(), Some(Color::Rgb(..))));
+        }
+    }
+
     #[test]
     fn test_color_scheme_conversion() {
         let scheme: ColorScheme = Theme::CyberpunkDark.into();
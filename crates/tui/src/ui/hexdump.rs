@@ -0,0 +1,314 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Hexdump widget for memory and calldata
+//!
+//! Each row shows the offset, 32 bytes in hex grouped by eight, and an ASCII gutter.
+//! Bytes that changed since the previous step are highlighted, and labelled regions,
+//! e.g. the ABI-encoded arguments of a call, are tinted with their label at the end of
+//! the row they start in.
+
+use alloy_dyn_abi::{DynSolType, Specifier};
+use alloy_json_abi::{Function, Param};
+use alloy_primitives::U256;
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::ui::colors::ColorScheme;
+
+/// Number of bytes shown per row
+pub const HEXDUMP_ROW_BYTES: usize = 32;
+
+/// Width of a row without region labels, for offsets of up to six hex digits
+pub const HEXDUMP_ROW_WIDTH: usize =
+    8 + 2 + (HEXDUMP_ROW_BYTES * 3 + 2) + 3 + HEXDUMP_ROW_BYTES + 1;
+
+/// A labelled range of bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexRegion {
+    /// First byte of the region
+    pub start: usize,
+    /// End of the region (exclusive)
+    pub end: usize,
+    /// Label shown next to the row the region starts in
+    pub label: String,
+}
+
+impl HexRegion {
+    /// Create a region covering `start..end`
+    pub fn new(start: usize, end: usize, label: impl Into<String>) -> Self {
+        Self { start, end, label: label.into() }
+    }
+}
+
+/// Hexdump of a byte buffer
+#[derive(Debug, Clone, Copy)]
+pub struct HexDump<'a> {
+    data: &'a [u8],
+    changed: &'a [bool],
+    regions: &'a [HexRegion],
+}
+
+impl<'a> HexDump<'a> {
+    /// Create a hexdump of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, changed: &[], regions: &[] }
+    }
+
+    /// Highlight bytes flagged in `changed`, indexed like the data
+    pub fn with_changes(mut self, changed: &'a [bool]) -> Self {
+        self.changed = changed;
+        self
+    }
+
+    /// Tint and label regions, ordered by start
+    pub fn with_regions(mut self, regions: &'a [HexRegion]) -> Self {
+        self.regions = regions;
+        self
+    }
+
+    /// Number of rows of the hexdump
+    pub fn row_count(&self) -> usize {
+        self.data.len().div_ceil(HEXDUMP_ROW_BYTES)
+    }
+
+    /// Offset of the first byte of a row
+    pub fn row_offset(row: usize) -> usize {
+        row * HEXDUMP_ROW_BYTES
+    }
+
+    /// Bytes of a row
+    pub fn row_bytes(&self, row: usize) -> &'a [u8] {
+        let start = Self::row_offset(row).min(self.data.len());
+        let end = (start + HEXDUMP_ROW_BYTES).min(self.data.len());
+        &self.data[start..end]
+    }
+
+    /// Render a row
+    pub fn row(&self, row: usize, theme: &ColorScheme) -> Line<'static> {
+        let offset = Self::row_offset(row);
+        let bytes = self.row_bytes(row);
+
+        let mut spans = vec![
+            Span::styled(format!("{offset:#08x}"), Style::default().fg(theme.line_number)),
+            Span::raw("  "),
+        ];
+
+        // Hex bytes, padded on the last row to keep the ASCII gutter aligned
+        for i in 0..HEXDUMP_ROW_BYTES {
+            if i > 0 {
+                spans.push(Span::raw(if i % 8 == 0 { "  " } else { " " }));
+            }
+            match bytes.get(i) {
+                Some(byte) => {
+                    spans.push(Span::styled(format!("{byte:02x}"), self.style(offset + i, theme)))
+                }
+                None => spans.push(Span::raw("  ")),
+            }
+        }
+
+        // ASCII gutter
+        spans.push(Span::styled("  │", Style::default().fg(theme.unfocused_border)));
+        for (i, byte) in bytes.iter().enumerate() {
+            let ch = if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' };
+            spans.push(Span::styled(ch.to_string(), self.style(offset + i, theme)));
+        }
+        spans.push(Span::styled(
+            format!("{}│", " ".repeat(HEXDUMP_ROW_BYTES - bytes.len())),
+            Style::default().fg(theme.unfocused_border),
+        ));
+
+        // Labels of the regions starting in this row, or of the region it continues
+        let end = offset + HEXDUMP_ROW_BYTES;
+        let starting: Vec<_> = self
+            .regions
+            .iter()
+            .filter(|region| (offset..end).contains(&region.start))
+            .map(|region| region.label.as_str())
+            .collect();
+        if !starting.is_empty() {
+            spans.push(Span::styled(
+                format!("  ◂ {}", starting.join(", ")),
+                Style::default().fg(theme.comment_color),
+            ));
+        } else if let Some(region) = self.region_at(offset) {
+            spans.push(Span::styled(
+                format!("  ┆ {}", region.1.label),
+                Style::default().fg(theme.comment_color),
+            ));
+        }
+
+        Line::from(spans)
+    }
+
+    /// The region covering a byte, with its index
+    fn region_at(&self, index: usize) -> Option<(usize, &'a HexRegion)> {
+        self.regions
+            .iter()
+            .enumerate()
+            .rfind(|(_, region)| region.start <= index && index < region.end)
+    }
+
+    /// Style of a byte: changed bytes stand out, regions alternate between two tints,
+    /// and zero bytes outside of regions are dimmed
+    fn style(&self, index: usize, theme: &ColorScheme) -> Style {
+        if self.changed.get(index).copied().unwrap_or_default() {
+            return Style::default().fg(theme.warning_color).add_modifier(Modifier::BOLD);
+        }
+        match self.region_at(index) {
+            Some((i, _)) => Style::default().fg(region_color(i, theme)),
+            None if self.data[index] == 0 => Style::default().fg(theme.comment_color),
+            None => Style::default(),
+        }
+    }
+}
+
+fn region_color(index: usize, theme: &ColorScheme) -> Color {
+    if index % 2 == 0 {
+        theme.syntax_type_color
+    } else {
+        theme.syntax_string_color
+    }
+}
+
+/// Flag the bytes of `current` that differ from `previous`, treating all bytes as new
+/// if there is nothing to compare with
+pub fn changed_bytes(current: &[u8], previous: Option<&[u8]>) -> Vec<bool> {
+    match previous {
+        Some(previous) => {
+            current.iter().enumerate().map(|(i, byte)| previous.get(i) != Some(byte)).collect()
+        }
+        None => vec![true; current.len()],
+    }
+}
+
+/// Regions of the calldata of a call to `function`: the selector, the head of every
+/// argument, and the tail of every dynamic argument.
+pub fn calldata_regions(function: &Function, calldata: &[u8]) -> Vec<HexRegion> {
+    if calldata.len() < 4 {
+        return vec![];
+    }
+
+    let mut regions = vec![HexRegion::new(0, 4, function.signature())];
+    regions.extend(param_regions(&function.inputs, calldata, 4));
+    regions
+}
+
+/// Regions of parameters ABI-encoded in `data` starting at `base`, ordered by start.
+fn param_regions(params: &[Param], data: &[u8], base: usize) -> Vec<HexRegion> {
+    let mut regions = Vec::new();
+    let mut tails = Vec::new();
+
+    let mut head = base;
+    for (i, param) in params.iter().enumerate() {
+        let name = if param.name.is_empty() { format!("arg{i}") } else { param.name.clone() };
+        let Ok(ty) = param.resolve() else { break };
+
+        if ty.is_dynamic() {
+            // The head holds the offset of the tail, relative to the start of the encoding
+            regions.push(HexRegion::new(head, head + 32, format!("{name} (offset)")));
+            if let Some(offset) = read_word(data, head) {
+                tails.push((base.saturating_add(offset), name));
+            }
+            head += 32;
+        } else {
+            let size = 32 * static_words(&ty);
+            regions.push(HexRegion::new(head, head + size, name));
+            head += size;
+        }
+    }
+
+    // Tails are encoded one after the other, so each one ends where the next begins
+    tails.sort_by_key(|(start, _)| *start);
+    for (i, (start, name)) in tails.iter().enumerate() {
+        let end = tails.get(i + 1).map_or(data.len(), |(next, _)| *next);
+        regions.push(HexRegion::new(*start, end, name.clone()));
+    }
+
+    regions.retain_mut(|region| {
+        region.end = region.end.min(data.len());
+        region.start < region.end
+    });
+    regions.sort_by_key(|region| region.start);
+    regions
+}
+
+/// Number of words a static type occupies in the head of an encoding
+fn static_words(ty: &DynSolType) -> usize {
+    match ty {
+        DynSolType::FixedArray(inner, len) => static_words(inner) * len,
+        DynSolType::Tuple(types) => types.iter().map(static_words).sum(),
+        _ => 1,
+    }
+}
+
+/// Read a word at `offset` as an offset into the data
+fn read_word(data: &[u8], offset: usize) -> Option<usize> {
+    let word = data.get(offset..offset.checked_add(32)?)?;
+    usize::try_from(U256::from_be_slice(word)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_row_layout() {
+        let data = b"hello";
+        let dump = HexDump::new(data);
+        assert_eq!(dump.row_count(), 1);
+
+        let row = text(&dump.row(0, &ColorScheme::default()));
+        assert!(row.starts_with("0x000000  68 65 6c 6c 6f "));
+        assert!(row.ends_with(&format!("│hello{}│", " ".repeat(27))));
+        assert_eq!(row.chars().count(), HEXDUMP_ROW_WIDTH);
+    }
+
+    #[test]
+    fn test_changed_bytes() {
+        assert_eq!(changed_bytes(&[1, 2, 3], Some(&[1, 5])), vec![false, true, true]);
+        assert_eq!(changed_bytes(&[1, 2], None), vec![true, true]);
+    }
+
+    #[test]
+    fn test_calldata_regions() {
+        let function = Function::parse("function f(uint256 amount, bytes data, address)").unwrap();
+
+        let mut calldata = vec![0xaa; 4];
+        calldata.extend(U256::from(7).to_be_bytes::<32>());
+        calldata.extend(U256::from(0x60).to_be_bytes::<32>());
+        calldata.extend([0u8; 32]);
+        calldata.extend(U256::from(2).to_be_bytes::<32>());
+        calldata.extend([0xbb; 32]);
+
+        assert_eq!(
+            calldata_regions(&function, &calldata),
+            vec![
+                HexRegion::new(0, 4, "f(uint256,bytes,address)"),
+                HexRegion::new(4, 36, "amount"),
+                HexRegion::new(36, 68, "data (offset)"),
+                HexRegion::new(68, 100, "arg2"),
+                HexRegion::new(100, 164, "data"),
+            ]
+        );
+    }
+}
@@ -20,7 +20,9 @@
 //! and other visual enhancements for the TUI.
 
 pub mod borders;
+pub mod clipboard;
 pub mod colors;
+pub mod hexdump;
 pub mod icons;
 pub mod spinner;
 pub mod status;
@@ -28,6 +30,7 @@ pub mod syntax;
 
 pub use borders::{BorderPresets, EnhancedBorder};
 pub use colors::{ColorScheme, Theme};
+pub use hexdump::{HexDump, HexRegion};
 pub use icons::Icons;
 pub use spinner::{Spinner, SpinnerAnimation, SpinnerStyles};
 pub use status::{
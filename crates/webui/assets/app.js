@@ -0,0 +1,539 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+// SPDX-License-Identifier: AGPL-3.0
+
+// Single-page frontend of the EDB web UI.
+//
+// All engine calls go through the JSON-RPC reverse proxy of the web UI server (`/rpc`),
+// which holds the credentials of the engine RPC server.
+
+"use strict";
+
+const TRACE_PAGE_SIZE = 200;
+const WATCHES_KEY = "edb.watches";
+const CLIENT_LABEL = "webui";
+
+const state = {
+  rpcPath: "/rpc",
+  session: null,
+  view: "debugger",
+  link: null,
+  snapshotCount: 0,
+  current: 0,
+  info: null,
+  trace: [],
+  traceTotal: 0,
+  codes: new Map(),
+  previousValues: new Map(),
+  watches: JSON.parse(localStorage.getItem(WATCHES_KEY) || "[]"),
+};
+
+// ========== Engine RPC ==========
+
+let nextRequestId = 1;
+
+async function rpc(method, params = []) {
+  const response = await fetch(state.rpcPath, {
+    method: "POST",
+    headers: { "content-type": "application/json" },
+    body: JSON.stringify({ jsonrpc: "2.0", id: nextRequestId++, method, params }),
+  });
+  if (!response.ok) {
+    throw new Error(`${response.status} ${await response.text()}`);
+  }
+  const body = await response.json();
+  if (body.error) {
+    throw new Error(body.error.message);
+  }
+  return body.result;
+}
+
+// ========== Helpers ==========
+
+const $ = (id) => document.getElementById(id);
+
+function element(tag, className, text) {
+  const node = document.createElement(tag);
+  if (className) node.className = className;
+  if (text !== undefined) node.textContent = text;
+  return node;
+}
+
+function setStatus(text, isError = false) {
+  const status = $("status");
+  status.textContent = text;
+  status.style.color = isError ? "var(--error)" : "";
+}
+
+function variant(value) {
+  const [name] = Object.keys(value);
+  return [name, value[name]];
+}
+
+function shortAddress(address) {
+  return `${address.slice(0, 8)}…${address.slice(-6)}`;
+}
+
+function toDecimal(value) {
+  try {
+    return BigInt(value).toString();
+  } catch {
+    return String(value);
+  }
+}
+
+// Format a serialized Solidity value (`{ type, value }`) for display
+function formatValue(value) {
+  if (value === null || value === undefined) return "<unavailable>";
+  const inner = value.value;
+  switch (value.type) {
+    case "Bool":
+      return String(inner);
+    case "Int":
+    case "Uint":
+      return toDecimal(inner.value);
+    case "FixedBytes":
+      return inner.value.slice(0, 2 + 2 * inner.size);
+    case "Address":
+    case "Function":
+      return inner;
+    case "Bytes":
+      return "0x" + inner.map((byte) => byte.toString(16).padStart(2, "0")).join("");
+    case "String":
+      return JSON.stringify(inner);
+    case "Array":
+    case "FixedArray":
+      return `[${inner.map(formatValue).join(", ")}]`;
+    case "Tuple":
+      return `(${inner.map(formatValue).join(", ")})`;
+    case "CustomStruct":
+      return `${inner.name} { ${inner.prop_names
+        .map((name, i) => `${name}: ${formatValue(inner.tuple[i])}`)
+        .join(", ")} }`;
+    default:
+      return JSON.stringify(value);
+  }
+}
+
+// ========== Deep links ==========
+
+// Parse a deep link to an execution point: `/session/<id>/step/<snapshot>` on a session
+// server, `/step/<snapshot>` otherwise, optionally with `?file=<path>&line=<line>`.
+// `/flamegraph` and `/storage` (after the same session prefix) open the other pages.
+function parseLocation() {
+  const match = location.pathname.match(
+    /^(?:\/session\/(\d+))?(?:\/step\/(\d+)|\/(flamegraph|storage))?\/?$/,
+  );
+  const params = new URLSearchParams(location.search);
+  const line = Number(params.get("line"));
+  return {
+    session: match && match[1] !== undefined ? Number(match[1]) : null,
+    step: match && match[2] !== undefined ? Number(match[2]) : 0,
+    view: match && match[3] !== undefined ? match[3] : "debugger",
+    link: params.has("file") && line > 0 ? { file: params.get("file"), line } : null,
+  };
+}
+
+function sessionPrefix() {
+  return state.session === null ? "" : `/session/${state.session}`;
+}
+
+function stepPath(id) {
+  return `${sessionPrefix()}/step/${id}`;
+}
+
+function viewPath(view) {
+  return view === "debugger" ? stepPath(state.current) : `${sessionPrefix()}/${view}`;
+}
+
+// Link to the current execution point, with the current source line if known
+function currentLink() {
+  const url = new URL(stepPath(state.current), location.origin);
+  const [mode, detail] = variant(state.info.detail);
+  const line = $("code").querySelector(".line.current .number");
+  if (mode === "Hook" && line) {
+    url.searchParams.set("file", detail.path);
+    url.searchParams.set("line", line.textContent);
+  }
+  return url.toString();
+}
+
+async function copyLink() {
+  const link = currentLink();
+  try {
+    await navigator.clipboard.writeText(link);
+    setStatus("Link copied");
+  } catch {
+    print(link);
+  }
+}
+
+// ========== Navigation ==========
+
+// Go to a snapshot, publishing the new position to the other clients of the session
+// unless following one of them
+async function goto(id, publish = true) {
+  if (id < 0 || id >= state.snapshotCount) return;
+
+  try {
+    const info = await rpc("edb_getSnapshotInfo", [id]);
+    const sameFrame = state.info && info.frame_id.join() === state.info.frame_id.join();
+    if (!sameFrame) state.previousValues.clear();
+
+    state.current = id;
+    state.info = info;
+    if (publish) rpc("edb_setPosition", [id, CLIENT_LABEL]).catch(() => {});
+    if (state.view === "debugger") {
+      history.replaceState(null, "", stepPath(id) + (state.link ? location.search : ""));
+    }
+
+    renderPosition();
+    renderTrace();
+    await renderCode();
+    renderVariables();
+    await renderWatches();
+    setStatus("");
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+async function stepBy(count) {
+  state.link = null;
+  await goto(Math.min(Math.max(state.current + count, 0), state.snapshotCount - 1));
+}
+
+async function gotoCall(method) {
+  state.link = null;
+  try {
+    await goto(await rpc(method, [state.current]));
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+function renderPosition() {
+  $("position").textContent = `Snapshot ${state.current} / ${state.snapshotCount - 1}`;
+}
+
+// ========== Trace ==========
+
+async function loadTracePage() {
+  const page = await rpc("edb_getTrace", [
+    { cursor: state.trace.length, limit: TRACE_PAGE_SIZE, summary: true },
+  ]);
+  state.trace.push(...page.entries);
+  state.traceTotal = page.total;
+  renderTrace();
+}
+
+function traceLabel(entry) {
+  const [kind, scheme] = variant(entry.call_type);
+  const callKind = typeof scheme === "string" ? scheme : kind;
+  const target = entry.target_label || shortAddress(entry.target);
+  const selector = kind === "Call" && entry.input.length >= 10 ? entry.input.slice(0, 10) : "";
+  return [callKind.toUpperCase(), target, selector];
+}
+
+function currentEntryId() {
+  return state.info ? state.info.frame_id[0] : null;
+}
+
+function renderTrace() {
+  const list = $("trace");
+  list.replaceChildren();
+  const current = currentEntryId();
+
+  for (const entry of state.trace) {
+    const item = element("li");
+    item.style.paddingLeft = `${entry.depth}rem`;
+    if (entry.id === current) item.classList.add("current");
+    if (entry.result && variant(entry.result)[0] !== "Success") item.classList.add("reverted");
+
+    const [kind, target, selector] = traceLabel(entry);
+    item.append(`${kind} `, element("span", "label", target), ` ${selector}`);
+    item.title = `#${entry.id} ${entry.target} (gas used ${entry.gas_used})`;
+    if (entry.first_snapshot_id !== null && entry.first_snapshot_id !== undefined) {
+      item.addEventListener("click", () => goto(entry.first_snapshot_id));
+    }
+    list.append(item);
+  }
+
+  $("trace-more").hidden = state.trace.length >= state.traceTotal;
+}
+
+// ========== Code ==========
+
+async function fetchCode(info) {
+  const key = info.bytecode_address;
+  if (!state.codes.has(key)) {
+    state.codes.set(key, await rpc("edb_getCode", [info.id]));
+  }
+  return state.codes.get(key);
+}
+
+// Line (1-based) of a byte offset within a source file
+function lineOfOffset(source, offset) {
+  const bytes = new TextEncoder().encode(source);
+  const prefix = new TextDecoder().decode(bytes.slice(0, offset));
+  return prefix.split("\n").length;
+}
+
+function codeLine(number, text, isCurrent) {
+  const line = element("div", isCurrent ? "line current" : "line");
+  line.append(element("span", "number", String(number)));
+  line.append(element("span", isCurrent ? "executing" : "", text));
+  return line;
+}
+
+async function renderCode() {
+  const view = $("code");
+  view.replaceChildren();
+  const info = state.info;
+  const [mode, code] = variant(await fetchCode(info));
+  const [, detail] = variant(info.detail);
+
+  if (mode === "Source") {
+    const source = code.sources[detail.path] || "";
+    const current = lineOfOffset(source, detail.offset);
+    $("code-title").textContent = `${detail.path}:${current}`;
+    source.split("\n").forEach((text, i) => view.append(codeLine(i + 1, text, i + 1 === current)));
+
+    // The line a deep link points to is shown until the next step
+    if (state.link && state.link.file === detail.path) {
+      const linked = view.children[state.link.line - 1];
+      if (linked) linked.classList.add("linked");
+    }
+  } else {
+    $("code-title").textContent = `${info.bytecode_address} (opcodes)`;
+    const pcs = Object.keys(code.codes)
+      .map(Number)
+      .sort((a, b) => a - b);
+    for (const pc of pcs) view.append(codeLine(pc, code.codes[pc], pc === detail.pc));
+  }
+
+  const current = view.querySelector(".line.linked") || view.querySelector(".line.current");
+  if (current) current.scrollIntoView({ block: "center" });
+}
+
+// ========== Variables and watches ==========
+
+function valueRow(name, text, className = "") {
+  const row = element("tr", className);
+  row.append(element("td", "name", name), element("td", "", text));
+  return row;
+}
+
+function renderVariables() {
+  const table = $("variables");
+  table.replaceChildren();
+  const [mode, detail] = variant(state.info.detail);
+
+  if (mode === "Opcode") {
+    // Top of the stack first
+    [...detail.stack].reverse().forEach((word, i) => table.append(valueRow(`[${i}]`, word)));
+    return;
+  }
+
+  const values = new Map();
+  for (const [scope, variables] of [
+    ["local", detail.locals],
+    ["state", detail.state_variables],
+  ]) {
+    for (const name of Object.keys(variables).sort()) {
+      const text = formatValue(variables[name]);
+      const key = `${scope}:${name}`;
+      const previous = state.previousValues.get(key);
+      const changed = previous !== undefined && previous !== text;
+      values.set(key, text);
+      table.append(valueRow(scope === "state" ? `this.${name}` : name, text, changed ? "changed" : ""));
+    }
+  }
+  state.previousValues = values;
+}
+
+async function evaluate(expression) {
+  const [outcome, value] = variant(await rpc("edb_evalOnSnapshot", [state.current, expression]));
+  if (outcome === "Err") throw new Error(value);
+  return formatValue(value);
+}
+
+async function renderWatches() {
+  const table = $("watches");
+  const rows = await Promise.all(
+    state.watches.map(async (expression, index) => {
+      let row;
+      try {
+        row = valueRow(expression, await evaluate(expression));
+      } catch (e) {
+        row = valueRow(expression, e.message);
+        row.lastChild.classList.add("error");
+      }
+      const remove = element("button", "", "✕");
+      remove.title = "Remove watch";
+      remove.addEventListener("click", () => {
+        state.watches.splice(index, 1);
+        saveWatches();
+        renderWatches();
+      });
+      const cell = element("td");
+      cell.append(remove);
+      row.append(cell);
+      return row;
+    }),
+  );
+  table.replaceChildren(...rows);
+}
+
+function saveWatches() {
+  localStorage.setItem(WATCHES_KEY, JSON.stringify(state.watches));
+}
+
+// ========== Terminal ==========
+
+function print(text, className = "") {
+  const output = $("terminal-output");
+  output.append(element("div", className, text));
+  output.scrollTop = output.scrollHeight;
+}
+
+async function runCommand(input) {
+  print(`edb> ${input}`, "input");
+  const [command, ...args] = input.split(/\s+/);
+
+  try {
+    switch (command) {
+      case ":help":
+        print(":goto N      go to snapshot N");
+        print(":watch EXPR  watch an expression at every step");
+        print("EXPR         evaluate a Solidity expression at the current snapshot");
+        break;
+      case ":goto":
+        await goto(Number(args[0]));
+        break;
+      case ":watch":
+        state.watches.push(args.join(" "));
+        saveWatches();
+        await renderWatches();
+        break;
+      default:
+        print(await evaluate(input));
+    }
+  } catch (e) {
+    print(e.message, "error");
+  }
+}
+
+// ========== Engine events ==========
+
+// Follow the other clients of the session (e.g., the TUI) and report the preparation
+// progress and findings, through the Server-Sent Events of the web UI server
+function followEvents() {
+  const session = state.session === null ? "" : `?session=${state.session}`;
+  const events = new EventSource(`/api/events${session}`);
+  events.addEventListener("navigation", (message) => {
+    const event = JSON.parse(message.data);
+    if (event.client !== CLIENT_LABEL && event.snapshot_id !== state.current) {
+      goto(event.snapshot_id, false);
+    }
+  });
+  events.addEventListener("progress", (message) => setStatus(JSON.parse(message.data).message));
+  events.addEventListener("finding", (message) => {
+    const { finding } = JSON.parse(message.data);
+    print(`[${finding.severity}] ${finding.message}`);
+  });
+}
+
+// ========== Pages ==========
+
+// Show one of the pages (debugger, flamegraph, storage), loading its data on entry
+function showView(view) {
+  state.view = view;
+  for (const name of ["debugger", "flamegraph", "storage"]) {
+    $(`${name}-view`).hidden = name !== view;
+    $(`nav-${name}`).classList.toggle("active", name === view);
+    $(`nav-${name}`).href = viewPath(name);
+  }
+  if (view === "flamegraph" && flamegraph.root === null) {
+    loadFlamegraph();
+  } else if (view === "storage") {
+    loadStorage(state.current);
+  }
+}
+
+function bindViews() {
+  for (const link of document.querySelectorAll("nav.views a")) {
+    link.addEventListener("click", (event) => {
+      event.preventDefault();
+      const view = link.dataset.view;
+      history.pushState(null, "", viewPath(view));
+      showView(view);
+    });
+  }
+  window.addEventListener("popstate", () => {
+    const { step, view } = parseLocation();
+    showView(view);
+    if (view === "debugger" && step !== state.current) goto(step, false);
+  });
+}
+
+// ========== Startup ==========
+
+function bindControls() {
+  $("step").addEventListener("click", () => stepBy(1));
+  $("step-back").addEventListener("click", () => stepBy(-1));
+  $("next-call").addEventListener("click", () => gotoCall("edb_getNextCall"));
+  $("prev-call").addEventListener("click", () => gotoCall("edb_getPrevCall"));
+  $("trace-more").addEventListener("click", () => loadTracePage());
+  $("copy-link").addEventListener("click", () => copyLink());
+  bindViews();
+  bindFlamegraphControls();
+  bindStorageControls();
+
+  $("watch-form").addEventListener("submit", (event) => {
+    event.preventDefault();
+    const input = $("watch-input");
+    if (input.value.trim()) {
+      state.watches.push(input.value.trim());
+      saveWatches();
+      renderWatches();
+    }
+    input.value = "";
+  });
+
+  $("terminal-form").addEventListener("submit", (event) => {
+    event.preventDefault();
+    const input = $("terminal-input");
+    if (input.value.trim()) runCommand(input.value.trim());
+    input.value = "";
+  });
+
+  document.addEventListener("keydown", (event) => {
+    if (event.target.tagName === "INPUT" || state.view !== "debugger") return;
+    if (event.key === "ArrowRight") {
+      event.shiftKey ? gotoCall("edb_getNextCall") : stepBy(1);
+    } else if (event.key === "ArrowLeft") {
+      event.shiftKey ? gotoCall("edb_getPrevCall") : stepBy(-1);
+    }
+  });
+}
+
+async function start() {
+  const { session, step, view, link } = parseLocation();
+  state.session = session;
+  state.rpcPath = session === null ? "/rpc" : `/rpc/session/${session}`;
+  state.link = link;
+
+  bindControls();
+  try {
+    state.snapshotCount = await rpc("edb_getSnapshotCount");
+    await loadTracePage();
+    await goto(Math.min(step, state.snapshotCount - 1), false);
+    showView(view);
+    followEvents();
+  } catch (e) {
+    setStatus(`Failed to reach the engine: ${e.message}`, true);
+  }
+}
+
+start();
@@ -0,0 +1,151 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+// SPDX-License-Identifier: AGPL-3.0
+
+// Interactive flamegraph of the `edb_getFlamegraph` folded stacks, with zoom and search.
+//
+// Frames are laid out top-down (icicle): the root call spans the full width and each
+// frame is as wide as its share of the weight of the zoomed frame.
+
+"use strict";
+
+const FRAME_HEIGHT = 18;
+const MIN_FRAME_WIDTH = 0.001;
+
+const flamegraph = {
+  weight: null,
+  root: null,
+  zoomed: null,
+};
+
+// Build the call tree of folded stacks (`frame;frame;... weight` lines)
+function parseFoldedStacks(folded) {
+  const root = { name: "all", value: 0, children: new Map(), parent: null };
+  for (const line of folded.split("\n")) {
+    const separator = line.lastIndexOf(" ");
+    if (separator <= 0) continue;
+    const value = Number(line.slice(separator + 1));
+    if (!Number.isFinite(value)) continue;
+
+    let node = root;
+    node.value += value;
+    for (const name of line.slice(0, separator).split(";")) {
+      if (!node.children.has(name)) {
+        node.children.set(name, { name, value: 0, children: new Map(), parent: node });
+      }
+      node = node.children.get(name);
+      node.value += value;
+    }
+  }
+  return root;
+}
+
+// Warm color derived from the frame name, so that a frame keeps its color when zooming
+function frameColor(name) {
+  let hash = 0;
+  for (const char of name) hash = (hash * 31 + char.charCodeAt(0)) | 0;
+  const hue = 10 + (Math.abs(hash) % 40);
+  return `hsl(${hue}, 80%, ${55 + (Math.abs(hash >> 8) % 15)}%)`;
+}
+
+function searchPattern() {
+  const text = $("flamegraph-search").value.trim();
+  if (!text) return null;
+  try {
+    return new RegExp(text, "i");
+  } catch {
+    return null;
+  }
+}
+
+function renderFlamegraph() {
+  const view = $("flamegraph");
+  view.replaceChildren();
+  const zoomed = flamegraph.zoomed;
+  if (!zoomed || zoomed.value === 0) {
+    $("flamegraph-summary").textContent = "No frames";
+    return;
+  }
+
+  const pattern = searchPattern();
+  let matched = 0;
+  let depth = 0;
+
+  // Lay out the zoomed frame and its descendants, relative to its weight
+  const layout = (node, x, level, insideMatch) => {
+    const width = node.value / zoomed.value;
+    if (width < MIN_FRAME_WIDTH) return;
+    depth = Math.max(depth, level + 1);
+
+    const isMatch = pattern !== null && pattern.test(node.name);
+    if (isMatch && !insideMatch) matched += node.value;
+
+    const frame = element("div", "frame", node.name);
+    frame.style.left = `${x * 100}%`;
+    frame.style.width = `${width * 100}%`;
+    frame.style.top = `${level * FRAME_HEIGHT}px`;
+    frame.style.background = frameColor(node.name);
+    if (isMatch) frame.classList.add("match");
+    else if (pattern) frame.classList.add("dimmed");
+    frame.title = `${node.name}\n${node.value} ${flamegraph.weight} (${(
+      (100 * node.value) /
+      flamegraph.root.value
+    ).toFixed(2)}% of the transaction)`;
+    frame.addEventListener("click", () => zoomFlamegraph(node));
+    view.append(frame);
+
+    let childX = x;
+    for (const child of node.children.values()) {
+      layout(child, childX, level + 1, insideMatch || isMatch);
+      childX += child.value / zoomed.value;
+    }
+  };
+  layout(zoomed, 0, 0, false);
+  view.style.height = `${depth * FRAME_HEIGHT}px`;
+
+  const total = `${flamegraph.root.value} ${flamegraph.weight}`;
+  $("flamegraph-summary").textContent = pattern
+    ? `Matched ${((100 * matched) / flamegraph.root.value).toFixed(2)}% of ${total}`
+    : `Total ${total}`;
+  renderFlamegraphPath();
+}
+
+// Breadcrumb of the ancestors of the zoomed frame, each zooming back out to it
+function renderFlamegraphPath() {
+  const path = $("flamegraph-path");
+  path.replaceChildren();
+  const ancestors = [];
+  for (let node = flamegraph.zoomed; node; node = node.parent) ancestors.unshift(node);
+  ancestors.forEach((node, i) => {
+    if (i > 0) path.append(" › ");
+    const crumb = element("span", "", node.name);
+    crumb.addEventListener("click", () => zoomFlamegraph(node));
+    path.append(crumb);
+  });
+}
+
+function zoomFlamegraph(node) {
+  flamegraph.zoomed = node;
+  renderFlamegraph();
+}
+
+async function loadFlamegraph() {
+  const weight = $("flamegraph-weight").value;
+  try {
+    const folded = await rpc("edb_getFlamegraph", [weight]);
+    flamegraph.weight = weight === "time" ? "ns" : "gas";
+    flamegraph.root = parseFoldedStacks(folded);
+    flamegraph.zoomed = flamegraph.root;
+    renderFlamegraph();
+    setStatus("");
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+function bindFlamegraphControls() {
+  $("flamegraph-weight").addEventListener("change", () => loadFlamegraph());
+  $("flamegraph-search").addEventListener("input", () => renderFlamegraph());
+  $("flamegraph-reset").addEventListener("click", () => zoomFlamegraph(flamegraph.root));
+  $("flamegraph-form").addEventListener("submit", (event) => event.preventDefault());
+}
@@ -0,0 +1,78 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+// SPDX-License-Identifier: AGPL-3.0
+
+// Storage explorer: the decoded state variables of the contract executing at a snapshot,
+// the slots the transaction wrote up to it, and raw reads of any other slot.
+
+"use strict";
+
+// Hexadecimal word with its decimal value when small enough to be a number
+function formatWord(word) {
+  const value = BigInt(word);
+  const hex = `0x${value.toString(16)}`;
+  return value < 1000000000000n ? `${hex} (${value})` : hex;
+}
+
+function toSlot(text) {
+  const slot = BigInt(text.trim());
+  return `0x${slot.toString(16)}`;
+}
+
+async function loadStorage(id) {
+  $("storage-snapshot").value = id;
+  $("storage-value").textContent = "";
+  try {
+    const info = await rpc("edb_getSnapshotInfo", [id]);
+    const [mode, detail] = variant(info.detail);
+    $("storage-contract").textContent = info.delegated
+      ? `Storage of ${info.target_address} (code of ${info.bytecode_address})`
+      : `Storage of ${info.target_address}`;
+
+    const variables = $("storage-variables");
+    variables.replaceChildren();
+    if (mode === "Hook") {
+      for (const name of Object.keys(detail.state_variables).sort()) {
+        variables.append(valueRow(name, formatValue(detail.state_variables[name])));
+      }
+    }
+    if (!variables.children.length) {
+      variables.append(valueRow("", "No decoded state variables at this snapshot"));
+    }
+
+    const slots = $("storage-slots");
+    slots.replaceChildren();
+    const diff = await rpc("edb_getStorageDiff", [id]);
+    const written = Object.entries(diff).sort(([a], [b]) => (BigInt(a) < BigInt(b) ? -1 : 1));
+    for (const [slot, [original, current]] of written) {
+      slots.append(valueRow(formatWord(slot), `${formatWord(original)} → ${formatWord(current)}`));
+    }
+    if (!written.length) {
+      slots.append(valueRow("", "No slot written yet"));
+    }
+    setStatus("");
+  } catch (e) {
+    setStatus(e.message, true);
+  }
+}
+
+async function readSlot() {
+  const id = Number($("storage-snapshot").value);
+  try {
+    const value = await rpc("edb_getStorage", [id, toSlot($("storage-slot").value)]);
+    $("storage-value").textContent = formatWord(value);
+  } catch (e) {
+    $("storage-value").textContent = e.message;
+  }
+}
+
+function bindStorageControls() {
+  $("storage-form").addEventListener("submit", (event) => {
+    event.preventDefault();
+    loadStorage(Number($("storage-snapshot").value));
+  });
+  $("storage-read").addEventListener("submit", (event) => {
+    event.preventDefault();
+    readSlot();
+  });
+}
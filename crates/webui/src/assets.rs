@@ -0,0 +1,44 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Frontend assets embedded in the binary.
+//!
+//! The files of `assets/` are compiled into the crate, so that `edb-webui` and
+//! `edb --ui web` serve the full UI without the source tree at hand.
+
+use axum::{
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Response},
+};
+use rust_embed::RustEmbed;
+
+/// Files of the `assets/` directory of this crate
+#[derive(RustEmbed)]
+#[folder = "assets/"]
+struct Assets;
+
+/// Serve an embedded asset, or `index.html` for any other path so that the frontend can
+/// route deep links
+pub(crate) async fn serve(uri: Uri) -> Response {
+    let path = uri.path().trim_start_matches('/');
+    let Some(file) =
+        Assets::get(path).filter(|_| !path.is_empty()).or_else(|| Assets::get("index.html"))
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    ([(header::CONTENT_TYPE, file.metadata.mimetype().to_string())], file.data).into_response()
+}
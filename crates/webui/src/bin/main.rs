@@ -0,0 +1,74 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! EDB Web UI
+//!
+//! Serves the browser frontend of an engine RPC server that is already running, e.g.,
+//! one started by `edb replay`.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use edb_common::{init_logging, EngineRpcClient, RpcClientOptions};
+use edb_webui::WebUiServer;
+use eyre::Result;
+
+/// EDB Web UI Server
+#[derive(Parser, Debug)]
+#[command(name = "edb-webui")]
+#[command(about = "EDB Web UI Server")]
+#[command(version)]
+struct Args {
+    /// URL of the engine RPC server
+    #[arg(long)]
+    url: String,
+
+    /// Port to serve the web UI on
+    #[arg(long, default_value = "8080")]
+    port: u16,
+
+    /// Bearer token of the engine RPC server
+    #[arg(long, env = edb_common::RPC_AUTH_TOKEN_ENV, hide_env_values = true)]
+    auth_token: Option<String>,
+
+    /// PEM certificate to trust when the engine RPC server is served over HTTPS with a
+    /// self-signed certificate
+    #[arg(long)]
+    ca_cert: Option<PathBuf>,
+
+    /// Directory to serve the frontend assets from instead of the embedded ones
+    #[arg(long)]
+    assets_dir: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    init_logging("edb-webui", true)?;
+
+    let options =
+        RpcClientOptions { auth_token: args.auth_token, ca_cert: args.ca_cert, timeout: None };
+    let mut server = WebUiServer::new(EngineRpcClient::new(args.url, &options)?);
+    if let Some(assets_dir) = args.assets_dir {
+        server = server.with_assets_dir(assets_dir);
+    }
+
+    let handle = server.start_on_port(args.port).await?;
+    println!("EDB Web UI available at {}", handle.url());
+
+    tokio::signal::ctrl_c().await?;
+    handle.shutdown()
+}
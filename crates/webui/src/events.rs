@@ -0,0 +1,263 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Server-Sent Events of the engine events.
+//!
+//! Browsers cannot set the bearer token of the engine on a WebSocket handshake without
+//! exposing it to the page, and dashboards may not speak JSON-RPC at all. `GET
+//! /api/events` therefore opens a WebSocket to the engine (`/ws`) on behalf of the
+//! client, subscribes to the requested topics with `edb_subscribe`, and streams every
+//! event as a Server-Sent Event named after its type, whose data is the JSON event:
+//!
+//! ```text
+//! event: navigation
+//! data: {"type":"navigation","tx_hash":"0x…","snapshot_id":42,"client":"tui"}
+//! ```
+//!
+//! Query parameters:
+//!
+//! - `topics` - Comma-separated topics among `progress`, `navigation` and `findings`
+//!   (all of them by default)
+//! - `session` - Only stream the events of a session of an engine session server
+//!
+//! The engine connection is closed when the client disconnects.
+
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use edb_common::into_rpc_result;
+use eyre::{bail, eyre, Result};
+use futures::{SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{
+        client::IntoClientRequest,
+        http::{header::AUTHORIZATION, HeaderValue},
+        Message,
+    },
+};
+use tracing::{debug, warn};
+
+use crate::server::WebUiState;
+
+/// Topics streamed when the client does not choose any
+const DEFAULT_TOPICS: &str = "progress,navigation,findings";
+
+/// Method name of the event notifications of the engine
+const SUBSCRIPTION_NOTIFICATION: &str = "edb_subscription";
+
+/// Query parameters of `GET /api/events`
+#[derive(Debug, Deserialize)]
+pub(crate) struct EventsQuery {
+    /// Comma-separated topics to subscribe to
+    #[serde(default = "default_topics")]
+    topics: String,
+    /// Session whose events are streamed, if the engine is a session server
+    session: Option<u64>,
+}
+
+fn default_topics() -> String {
+    DEFAULT_TOPICS.to_string()
+}
+
+/// Stream the events of the engine as Server-Sent Events
+pub(crate) async fn events(
+    State(state): State<Arc<WebUiState>>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    match subscribe(&state, &query).await {
+        Ok(events) => Sse::new(events).keep_alive(KeepAlive::default()).into_response(),
+        Err(e) => {
+            warn!("Failed to subscribe to the engine events: {e}");
+            (StatusCode::BAD_GATEWAY, format!("Failed to subscribe to the engine events: {e}"))
+                .into_response()
+        }
+    }
+}
+
+/// Subscribe to the engine events and turn the notifications into Server-Sent Events
+async fn subscribe(
+    state: &WebUiState,
+    query: &EventsQuery,
+) -> Result<impl Stream<Item = Result<Event, Infallible>>> {
+    // Events of a session are selected by the transaction it debugs
+    let tx_hash = match query.session {
+        Some(id) => Some(session_tx_hash(state, id).await?),
+        None => None,
+    };
+
+    let mut handshake = events_url(state.engine.url())?.into_client_request()?;
+    if let Some(token) = state.engine.auth_token() {
+        handshake
+            .headers_mut()
+            .insert(AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {token}"))?);
+    }
+    let (mut socket, _) = connect_async(handshake)
+        .await
+        .map_err(|e| eyre!("Failed to connect to {}: {}", state.engine.url(), e))?;
+
+    let topics = query.topics.split(',').map(str::trim).filter(|topic| !topic.is_empty());
+    for (id, topic) in topics.enumerate() {
+        let params = match &tx_hash {
+            Some(tx_hash) => json!([topic, tx_hash]),
+            None => json!([topic]),
+        };
+        let request =
+            json!({ "jsonrpc": "2.0", "id": id, "method": "edb_subscribe", "params": params });
+        socket.send(Message::text(request.to_string())).await?;
+    }
+
+    Ok(futures::stream::unfold(socket, |mut socket| async move {
+        loop {
+            let message = match socket.next().await? {
+                Ok(message) => message,
+                Err(e) => {
+                    debug!("Engine event stream closed: {e}");
+                    return None;
+                }
+            };
+            let Message::Text(text) = message else { continue };
+            if let Some(event) = into_sse_event(text.as_str()) {
+                return Some((Ok(event), socket));
+            }
+        }
+    }))
+}
+
+/// Convert an event notification of the engine into a Server-Sent Event
+fn into_sse_event(text: &str) -> Option<Event> {
+    let message: Value = serde_json::from_str(text).ok()?;
+    if message["method"] != SUBSCRIPTION_NOTIFICATION {
+        // Responses to the subscriptions
+        if let Some(error) = message.get("error") {
+            warn!("Engine rejected an event subscription: {error}");
+        }
+        return None;
+    }
+
+    let event = &message["params"]["result"];
+    let name = event["type"].as_str().unwrap_or("message");
+    Event::default().event(name).json_data(event).ok()
+}
+
+/// Transaction debugged in a session of the engine session server
+async fn session_tx_hash(state: &WebUiState, id: u64) -> Result<Value> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "edb_listSessions", "params": [] });
+    let response = state
+        .engine
+        .forward("/", &request)
+        .await?
+        .ok_or_else(|| eyre!("Engine RPC server sent no response"))?;
+
+    let sessions = into_rpc_result(response)?;
+    sessions
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|session| session["id"] == id)
+        .map(|session| session["tx_hash"].clone())
+        .ok_or_else(|| eyre!("Session {id} not found"))
+}
+
+/// URL of the WebSocket endpoint of the engine RPC server at `engine_url`
+fn events_url(engine_url: &str) -> Result<String> {
+    let base = engine_url.trim_end_matches('/');
+    if let Some(address) = base.strip_prefix("https://") {
+        Ok(format!("wss://{address}/ws"))
+    } else if let Some(address) = base.strip_prefix("http://") {
+        Ok(format!("ws://{address}/ws"))
+    } else {
+        bail!("Engine events are not available over {engine_url}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        extract::ws::{Message as WsMessage, WebSocketUpgrade},
+        routing::get,
+        Router,
+    };
+    use edb_common::{EngineRpcClient, RpcClientOptions};
+
+    use super::*;
+    use crate::WebUiServer;
+
+    #[test]
+    fn test_events_url() {
+        assert_eq!(events_url("http://127.0.0.1:3030").unwrap(), "ws://127.0.0.1:3030/ws");
+        assert_eq!(events_url("https://debug-box:3030/").unwrap(), "wss://debug-box:3030/ws");
+        assert!(events_url("unix:///tmp/edb.sock").is_err());
+    }
+
+    /// Serve a fake engine publishing a navigation event to every subscription
+    async fn fake_engine() -> String {
+        let app = Router::new().route(
+            "/ws",
+            get(|upgrade: WebSocketUpgrade| async move {
+                upgrade.on_upgrade(|mut socket| async move {
+                    while let Some(Ok(WsMessage::Text(text))) = socket.recv().await {
+                        let request: Value = serde_json::from_str(text.as_str()).unwrap();
+                        let topic = request["params"][0].clone();
+                        let response =
+                            json!({ "jsonrpc": "2.0", "id": request["id"], "result": "0x1" });
+                        let notification = json!({
+                            "jsonrpc": "2.0",
+                            "method": SUBSCRIPTION_NOTIFICATION,
+                            "params": {
+                                "subscription": "0x1",
+                                "result": { "type": topic, "snapshot_id": 42, "client": "tui" },
+                            },
+                        });
+                        socket.send(WsMessage::text(response.to_string())).await.unwrap();
+                        socket.send(WsMessage::text(notification.to_string())).await.unwrap();
+                    }
+                })
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_stream_engine_events() {
+        let engine =
+            EngineRpcClient::new(fake_engine().await, &RpcClientOptions::default()).unwrap();
+        let handle = WebUiServer::new(engine).start_on_port(0).await.unwrap();
+
+        let mut response =
+            reqwest::get(format!("{}/api/events?topics=navigation", handle.url())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let chunk = response.chunk().await.unwrap().unwrap();
+        let chunk = String::from_utf8_lossy(&chunk);
+        assert!(chunk.contains("event: navigation"));
+        assert!(chunk.contains(r#""snapshot_id":42"#));
+
+        handle.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! EDB Web UI
+//!
+//! Browser frontend of the EDB engine. [`WebUiServer`] serves a single-page application
+//! with the same views as the Terminal UI (trace tree, code viewer with stepping,
+//! variable and watch panes, and an expression terminal), and reverse-proxies the engine
+//! JSON-RPC under its own origin (see [`proxy`]), so the browser never needs the
+//! credentials of the engine RPC server. Clients that do not speak JSON-RPC, such as
+//! dashboards, can use the REST routes and Server-Sent Events of [`rest`] and [`events`].
+
+mod assets;
+pub mod events;
+pub mod proxy;
+pub mod rest;
+pub mod server;
+
+pub use server::{WebUiHandle, WebUiServer};
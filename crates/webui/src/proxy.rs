@@ -0,0 +1,154 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Reverse proxy of the engine JSON-RPC.
+//!
+//! The engine RPC server may require a bearer token and may live on another machine, so
+//! the frontend does not talk to it directly: it sends its JSON-RPC requests (and batches)
+//! to the web UI under the same origin, which forwards them to the engine with its own
+//! credentials and returns the responses untouched.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Json, Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use tracing::warn;
+
+use crate::server::WebUiState;
+
+/// Forward a JSON-RPC payload to the engine RPC server
+pub(crate) async fn forward_rpc(
+    State(state): State<Arc<WebUiState>>,
+    Json(payload): Json<Value>,
+) -> Response {
+    forward(&state, &engine_path(None), &payload).await
+}
+
+/// Forward a JSON-RPC payload to a session of an engine session server
+pub(crate) async fn forward_session_rpc(
+    State(state): State<Arc<WebUiState>>,
+    Path(id): Path<u64>,
+    Json(payload): Json<Value>,
+) -> Response {
+    forward(&state, &engine_path(Some(id)), &payload).await
+}
+
+/// Path of the engine RPC server serving the JSON-RPC methods of a session, or of the
+/// only transaction of a debug RPC server
+pub(crate) fn engine_path(session: Option<u64>) -> String {
+    session.map_or_else(|| "/".to_string(), |id| format!("/session/{id}"))
+}
+
+/// Forward a JSON-RPC payload to a path of the engine RPC server
+async fn forward(state: &WebUiState, path: &str, payload: &Value) -> Response {
+    match state.engine.forward(path, payload).await {
+        Ok(Some(response)) => Json(response).into_response(),
+        Ok(None) => StatusCode::NO_CONTENT.into_response(),
+        Err(e) => {
+            warn!("Failed to forward request to the engine RPC server: {e}");
+            (StatusCode::BAD_GATEWAY, format!("Engine RPC server request failed: {e}"))
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Router};
+    use edb_common::{EngineRpcClient, RpcClientOptions};
+    use serde_json::json;
+
+    use super::*;
+    use crate::WebUiServer;
+
+    /// Serve a fake engine answering every request with its method and path
+    async fn fake_engine() -> String {
+        async fn answer(path: &'static str, request: Value) -> Json<Value> {
+            Json(json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "method": request["method"], "path": path },
+            }))
+        }
+
+        let app = Router::new()
+            .route("/", post(|Json(request): Json<Value>| answer("/", request)))
+            .route("/session/7", post(|Json(request): Json<Value>| answer("/session/7", request)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_forward_rpc_requests() {
+        let engine =
+            EngineRpcClient::new(fake_engine().await, &RpcClientOptions::default()).unwrap();
+        let handle = WebUiServer::new(engine).start_on_port(0).await.unwrap();
+        let client = reqwest::Client::new();
+
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "edb_getSnapshotCount" });
+        let response: Value = client
+            .post(format!("{}/rpc", handle.url()))
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["result"], json!({ "method": "edb_getSnapshotCount", "path": "/" }));
+
+        let response: Value = client
+            .post(format!("{}/rpc/session/7", handle.url()))
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["path"], "/session/7");
+
+        // The frontend is served from the same origin
+        let index = client.get(handle.url()).send().await.unwrap().text().await.unwrap();
+        assert!(index.contains("<title>EDB</title>"));
+
+        handle.shutdown().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_engine() {
+        // Nothing listens on the discard port
+        let engine =
+            EngineRpcClient::new("http://127.0.0.1:9", &RpcClientOptions::default()).unwrap();
+        let handle = WebUiServer::new(engine).start_on_port(0).await.unwrap();
+
+        let response = reqwest::Client::new()
+            .post(format!("{}/rpc", handle.url()))
+            .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "edb_getTrace" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        handle.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,280 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! REST routes over the engine JSON-RPC.
+//!
+//! Browsers and dashboards can read a debugging session with plain `GET` requests
+//! instead of JSON-RPC. Every route answers with the JSON result of the engine method it
+//! maps to, or with `400 Bad Request` and the error message of the engine, and accepts
+//! `?session=<id>` to address a session of an engine session server:
+//!
+//! - `GET /api/snapshots` - Number of snapshots (`edb_getSnapshotCount`)
+//! - `GET /api/snapshots/{id}` - Details of a snapshot (`edb_getSnapshotInfo`)
+//! - `GET /api/snapshots/{id}/code` - Code executed at a snapshot (`edb_getCode`)
+//! - `GET /api/snapshots/{id}/storage-diff` - Storage changed by the transaction up to a
+//!   snapshot (`edb_getStorageDiff`)
+//! - `GET /api/snapshots/{id}/storage?slot=<slot>` - Value of a storage slot of the
+//!   contract executing at a snapshot (`edb_getStorage`)
+//! - `GET /api/snapshots/{id}/eval?expr=<expr>` - Value of an expression at a snapshot
+//!   (`edb_evalOnSnapshot`)
+//! - `GET /api/trace?cursor=&limit=&maxDepth=&parentId=&summary=` - Page of the call
+//!   trace (`edb_getTrace`)
+//! - `GET /api/flamegraph?weight=<gas|time>` - Folded stacks of the transaction
+//!   (`edb_getFlamegraph`)
+//! - `GET /api/findings` - Findings of the detectors (`edb_getFindings`)
+//! - `GET /api/logs` - Events emitted by the transaction (`edb_getLogs`)
+//! - `GET /api/events?topics=<topics>` - Server-Sent Events (see [`crate::events`])
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use edb_common::{into_rpc_result, types::TraceQuery};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{proxy::engine_path, server::WebUiState};
+
+/// Session addressed by a request, if the engine is a session server
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SessionQuery {
+    /// Identifier of the session
+    pub(crate) session: Option<u64>,
+}
+
+/// Expression evaluated by `GET /api/snapshots/{id}/eval`
+#[derive(Debug, Deserialize)]
+pub(crate) struct EvalQuery {
+    /// Solidity expression to evaluate
+    expr: String,
+}
+
+/// Get the number of snapshots
+pub(crate) async fn snapshot_count(
+    State(state): State<Arc<WebUiState>>,
+    Query(session): Query<SessionQuery>,
+) -> Response {
+    call(&state, &session, "edb_getSnapshotCount", json!([])).await
+}
+
+/// Get the details of a snapshot
+pub(crate) async fn snapshot_info(
+    State(state): State<Arc<WebUiState>>,
+    Path(id): Path<usize>,
+    Query(session): Query<SessionQuery>,
+) -> Response {
+    call(&state, &session, "edb_getSnapshotInfo", json!([id])).await
+}
+
+/// Get the code executed at a snapshot
+pub(crate) async fn code(
+    State(state): State<Arc<WebUiState>>,
+    Path(id): Path<usize>,
+    Query(session): Query<SessionQuery>,
+) -> Response {
+    call(&state, &session, "edb_getCode", json!([id])).await
+}
+
+/// Get the storage changed by the transaction up to a snapshot
+pub(crate) async fn storage_diff(
+    State(state): State<Arc<WebUiState>>,
+    Path(id): Path<usize>,
+    Query(session): Query<SessionQuery>,
+) -> Response {
+    call(&state, &session, "edb_getStorageDiff", json!([id])).await
+}
+
+/// Evaluate an expression at a snapshot
+pub(crate) async fn eval(
+    State(state): State<Arc<WebUiState>>,
+    Path(id): Path<usize>,
+    Query(session): Query<SessionQuery>,
+    Query(query): Query<EvalQuery>,
+) -> Response {
+    call(&state, &session, "edb_evalOnSnapshot", json!([id, query.expr])).await
+}
+
+/// Slot read by `GET /api/snapshots/{id}/storage`
+#[derive(Debug, Deserialize)]
+pub(crate) struct StorageQuery {
+    /// Storage slot, as a hexadecimal or decimal number
+    slot: String,
+}
+
+/// Weight of the frames of `GET /api/flamegraph`
+#[derive(Debug, Deserialize)]
+pub(crate) struct FlamegraphQuery {
+    /// `gas` (the default) or `time`
+    #[serde(default = "default_weight")]
+    weight: String,
+}
+
+fn default_weight() -> String {
+    "gas".to_string()
+}
+
+/// Read a storage slot of the contract executing at a snapshot
+pub(crate) async fn storage(
+    State(state): State<Arc<WebUiState>>,
+    Path(id): Path<usize>,
+    Query(session): Query<SessionQuery>,
+    Query(query): Query<StorageQuery>,
+) -> Response {
+    call(&state, &session, "edb_getStorage", json!([id, query.slot])).await
+}
+
+/// Get the folded stacks of the transaction
+pub(crate) async fn flamegraph(
+    State(state): State<Arc<WebUiState>>,
+    Query(session): Query<SessionQuery>,
+    Query(query): Query<FlamegraphQuery>,
+) -> Response {
+    call(&state, &session, "edb_getFlamegraph", json!([query.weight])).await
+}
+
+/// Get a page of the call trace
+pub(crate) async fn trace(
+    State(state): State<Arc<WebUiState>>,
+    Query(session): Query<SessionQuery>,
+    Query(query): Query<TraceQuery>,
+) -> Response {
+    call(&state, &session, "edb_getTrace", json!([query])).await
+}
+
+/// Get the findings of the detectors
+pub(crate) async fn findings(
+    State(state): State<Arc<WebUiState>>,
+    Query(session): Query<SessionQuery>,
+) -> Response {
+    call(&state, &session, "edb_getFindings", json!([])).await
+}
+
+/// Get the events emitted by the transaction
+pub(crate) async fn logs(
+    State(state): State<Arc<WebUiState>>,
+    Query(session): Query<SessionQuery>,
+) -> Response {
+    call(&state, &session, "edb_getLogs", json!([])).await
+}
+
+/// Call a JSON-RPC method of the engine and answer with its result
+async fn call(state: &WebUiState, session: &SessionQuery, method: &str, params: Value) -> Response {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let response = match state.engine.forward(&engine_path(session.session), &request).await {
+        Ok(Some(response)) => response,
+        Ok(None) => {
+            return (StatusCode::BAD_GATEWAY, "Engine RPC server sent no response").into_response()
+        }
+        Err(e) => {
+            warn!("Failed to call {method} on the engine RPC server: {e}");
+            return (StatusCode::BAD_GATEWAY, format!("Engine RPC server request failed: {e}"))
+                .into_response();
+        }
+    };
+
+    match into_rpc_result(response) {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => {
+            (StatusCode::BAD_REQUEST, Json(json!({ "error": e.to_string() }))).into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{routing::post, Router};
+    use edb_common::{EngineRpcClient, RpcClientOptions};
+
+    use super::*;
+    use crate::WebUiServer;
+
+    /// Serve a fake engine echoing the method and parameters of every request, failing
+    /// `edb_getCode`
+    async fn fake_engine() -> String {
+        async fn answer(path: &'static str, request: Value) -> Json<Value> {
+            let id = request["id"].clone();
+            if request["method"] == "edb_getCode" {
+                return Json(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -33002, "message": "Snapshot with id 9 not found" },
+                }));
+            }
+            Json(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": { "method": request["method"], "params": request["params"], "path": path },
+            }))
+        }
+
+        let app = Router::new()
+            .route("/", post(|Json(request): Json<Value>| answer("/", request)))
+            .route("/session/7", post(|Json(request): Json<Value>| answer("/session/7", request)));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_rest_routes() {
+        let engine =
+            EngineRpcClient::new(fake_engine().await, &RpcClientOptions::default()).unwrap();
+        let handle = WebUiServer::new(engine).start_on_port(0).await.unwrap();
+        let get = |path: &str| {
+            let url = format!("{}{path}", handle.url());
+            async move { reqwest::get(url).await.unwrap() }
+        };
+
+        let result: Value = get("/api/snapshots/3").await.json().await.unwrap();
+        assert_eq!(result, json!({ "method": "edb_getSnapshotInfo", "params": [3], "path": "/" }));
+
+        let result: Value =
+            get("/api/snapshots/3/eval?expr=a%20%2B%201&session=7").await.json().await.unwrap();
+        assert_eq!(
+            result,
+            json!({ "method": "edb_evalOnSnapshot", "params": [3, "a + 1"], "path": "/session/7" })
+        );
+
+        let result: Value = get("/api/trace?limit=10&maxDepth=1").await.json().await.unwrap();
+        assert_eq!(result["method"], "edb_getTrace");
+        assert_eq!(result["params"][0]["limit"], 10);
+        assert_eq!(result["params"][0]["maxDepth"], 1);
+
+        let result: Value = get("/api/snapshots/3/storage?slot=0x2").await.json().await.unwrap();
+        assert_eq!(
+            result,
+            json!({ "method": "edb_getStorage", "params": [3, "0x2"], "path": "/" })
+        );
+
+        let result: Value = get("/api/flamegraph").await.json().await.unwrap();
+        assert_eq!(result["params"], json!(["gas"]));
+        let result: Value = get("/api/flamegraph?weight=time").await.json().await.unwrap();
+        assert_eq!(result["params"], json!(["time"]));
+
+        // Errors of the engine are reported with their message
+        let response = get("/api/snapshots/9/code").await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error: Value = response.json().await.unwrap();
+        assert!(error["error"].as_str().unwrap().contains("Snapshot with id 9 not found"));
+
+        handle.shutdown().unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+// EDB - Ethereum Debugger
+// Copyright (C) 2024 Zhuo Zhang and Wuqi Zhang
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Web UI HTTP server.
+//!
+//! Routes:
+//!
+//! - `POST /rpc` - JSON-RPC requests forwarded to the engine RPC server
+//! - `POST /rpc/session/{id}` - JSON-RPC requests forwarded to a session of an engine
+//!   session server
+//! - `GET /api/*` - REST routes over the engine JSON-RPC and Server-Sent Events of the
+//!   engine events (see [`crate::rest`])
+//! - `GET /*` - Frontend assets (`index.html`, scripts and stylesheets), embedded in the
+//!   binary unless [`WebUiServer::with_assets_dir`] serves them from a directory
+//!
+//! Any other path serves `index.html`, so that the frontend can route deep links to an
+//! execution point, e.g., `/session/<id>/step/<snapshot>?file=<path>&line=<line>` or
+//! `/step/<snapshot>` on a debug RPC server with a single transaction.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use edb_common::EngineRpcClient;
+use eyre::Result;
+use tokio::sync::oneshot;
+use tower_http::services::{ServeDir, ServeFile};
+use tracing::{info, warn};
+
+use crate::{assets, events, proxy, rest};
+
+/// State shared by the handlers of the web UI server
+#[derive(Debug)]
+pub struct WebUiState {
+    /// Client of the engine RPC server the UI debugs
+    pub engine: EngineRpcClient,
+}
+
+/// HTTP server of the web UI for a single engine RPC server
+#[derive(Debug)]
+pub struct WebUiServer {
+    engine: EngineRpcClient,
+    assets_dir: Option<PathBuf>,
+}
+
+impl WebUiServer {
+    /// Create a server for the engine RPC server reached by `engine`, serving the frontend
+    /// assets embedded in the binary
+    pub fn new(engine: EngineRpcClient) -> Self {
+        Self { engine, assets_dir: None }
+    }
+
+    /// Serve the frontend assets from a directory instead, e.g., while developing them
+    pub fn with_assets_dir(mut self, assets_dir: impl Into<PathBuf>) -> Self {
+        self.assets_dir = Some(assets_dir.into());
+        self
+    }
+
+    /// Build the router of the server
+    pub fn router(self) -> Router {
+        let state = Arc::new(WebUiState { engine: self.engine });
+
+        let router = Router::new()
+            .route("/rpc", post(proxy::forward_rpc))
+            .route("/rpc/session/{id}", post(proxy::forward_session_rpc))
+            .route("/api/snapshots", get(rest::snapshot_count))
+            .route("/api/snapshots/{id}", get(rest::snapshot_info))
+            .route("/api/snapshots/{id}/code", get(rest::code))
+            .route("/api/snapshots/{id}/storage", get(rest::storage))
+            .route("/api/snapshots/{id}/storage-diff", get(rest::storage_diff))
+            .route("/api/snapshots/{id}/eval", get(rest::eval))
+            .route("/api/trace", get(rest::trace))
+            .route("/api/flamegraph", get(rest::flamegraph))
+            .route("/api/findings", get(rest::findings))
+            .route("/api/logs", get(rest::logs))
+            .route("/api/events", get(events::events));
+
+        let router = match self.assets_dir {
+            Some(dir) => {
+                let index = ServeFile::new(dir.join("index.html"));
+                router.fallback_service(ServeDir::new(dir).fallback(index))
+            }
+            None => router.fallback(assets::serve),
+        };
+        router.with_state(state)
+    }
+
+    /// Start the server on a specific port of the loopback interface (0 for any free port)
+    pub async fn start_on_port(self, port: u16) -> Result<WebUiHandle> {
+        let app = self.router();
+
+        let listener = tokio::net::TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await?;
+        let addr = listener.local_addr()?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        tokio::spawn(async move {
+            axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    shutdown_rx.await.ok();
+                })
+                .await
+                .expect("Web UI server failed");
+        });
+        info!("Web UI server started on {addr}");
+
+        Ok(WebUiHandle { addr, shutdown_tx })
+    }
+}
+
+/// Handle to control a running web UI server
+#[derive(Debug)]
+pub struct WebUiHandle {
+    /// Address the server is listening on
+    pub addr: SocketAddr,
+    /// Shutdown signal sender (consumed when shutting down)
+    shutdown_tx: oneshot::Sender<()>,
+}
+
+impl WebUiHandle {
+    /// Get the server address
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the URL to open in a browser
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Gracefully shutdown the web UI server
+    pub fn shutdown(self) -> Result<()> {
+        if self.shutdown_tx.send(()).is_err() {
+            warn!("Web UI server already shut down");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use edb_common::RpcClientOptions;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_deep_links_serve_the_frontend() {
+        let engine =
+            EngineRpcClient::new("http://127.0.0.1:9", &RpcClientOptions::default()).unwrap();
+        let handle = WebUiServer::new(engine).start_on_port(0).await.unwrap();
+
+        for path in ["/", "/step/42", "/session/3/step/42?file=src/Vault.sol&line=17"] {
+            let response = reqwest::get(format!("{}{path}", handle.url())).await.unwrap();
+            assert_eq!(response.status(), 200, "{path}");
+            assert!(response.text().await.unwrap().contains("<title>EDB</title>"), "{path}");
+        }
+
+        // Assets are still served as such
+        let script = reqwest::get(format!("{}/app.js", handle.url())).await.unwrap();
+        assert!(script.headers()["content-type"].to_str().unwrap().contains("javascript"));
+        assert!(script.text().await.unwrap().contains("function goto"));
+
+        handle.shutdown().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_assets_dir_override() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("index.html"), "<title>Dev</title>").unwrap();
+        std::fs::write(dir.path().join("app.js"), "// dev build").unwrap();
+
+        let engine =
+            EngineRpcClient::new("http://127.0.0.1:9", &RpcClientOptions::default()).unwrap();
+        let handle =
+            WebUiServer::new(engine).with_assets_dir(dir.path()).start_on_port(0).await.unwrap();
+
+        let page = reqwest::get(format!("{}/step/1", handle.url())).await.unwrap();
+        assert_eq!(page.text().await.unwrap(), "<title>Dev</title>");
+        let script = reqwest::get(format!("{}/app.js", handle.url())).await.unwrap();
+        assert_eq!(script.text().await.unwrap(), "// dev build");
+
+        handle.shutdown().unwrap();
+    }
+}